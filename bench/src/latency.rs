@@ -0,0 +1,510 @@
+//! End-to-end latency across the Rust and TypeScript boundary
+//!
+//! Throughput says how fast a runtime decodes, not how long a message takes
+//! to get from a device to the application. `postcard-ts-bench latency`
+//! plays the device: it sends each fixture of the corpus in a timestamped
+//! [`Probe`] over a WebSocket or a serial line, and the runtime's
+//! `echoLatencyProbes` decodes it, encodes it again and sends it back in an
+//! [`Echo`] saying how long that took. The round trip then splits into
+//! decoding, encoding and the transport, the rest, both ways:
+//!
+//! ```
+//! use postcard_ts_bench::{Echo, LatencyRun, LatencySample};
+//!
+//! let mut run = LatencyRun::new("websocket");
+//! for (round_trip, decode) in [(250.0, 12.0), (310.0, 14.0), (290.0, 11.0)] {
+//!     run.samples.push(LatencySample {
+//!         type_name: "Reading".to_string(),
+//!         bytes: 5,
+//!         round_trip,
+//!         handled: decode + 20.0,
+//!         decode,
+//!         encode: 8.0,
+//!     });
+//! }
+//! let reading = &run.summaries()[0];
+//! assert_eq!(reading.round_trip.p50, 290.0);
+//! assert_eq!(reading.transport.max, 276.0);
+//! ```
+//!
+//! Times are in microseconds. The TypeScript side reads them from
+//! `performance.now()`, which browsers coarsen, so run the echo under Node
+//! or on a cross-origin isolated page for fine decode and encode times.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use crate::write_table;
+
+/// How long to keep sending the first probe before giving up on an echo
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the first probe is sent until the echo answers it
+const CONNECT_RETRY: Duration = Duration::from_millis(500);
+/// How long to wait for the echo of any later probe
+const ECHO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message for the echo to decode and send back, the runtime's
+/// `LatencyProbeSchema`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Probe {
+    pub seq: u32,
+    /// Microseconds since the run started, returned as is
+    pub sent_at: u64,
+    pub type_name: String,
+    /// The message, encoded
+    pub payload: Vec<u8>,
+}
+
+/// The answer to a [`Probe`], the runtime's `LatencyEchoSchema`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Echo {
+    pub seq: u32,
+    pub sent_at: u64,
+    pub decode_micros: f64,
+    pub encode_micros: f64,
+    /// From the probe's arrival to the echo being encoded
+    pub handled_micros: f64,
+    /// The message decoded and encoded again; empty on failure
+    pub payload: Vec<u8>,
+    pub error: Option<String>,
+}
+
+/// A message from the corpus to send
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fixture {
+    pub file: String,
+    pub type_name: String,
+    pub frame: Vec<u8>,
+}
+
+/// A connection to an echo
+pub trait Link {
+    fn send(&mut self, probe: &Probe) -> io::Result<()>;
+    /// The next echo, or `None` if none arrives within `timeout`
+    fn receive(&mut self, timeout: Duration) -> io::Result<Option<Echo>>;
+}
+
+/// An echo on the other end of a WebSocket, one probe or echo per binary
+/// message
+pub struct WebSocketLink {
+    socket: WebSocket<TcpStream>,
+}
+
+impl WebSocketLink {
+    /// Complete the WebSocket handshake of a connection from the echo
+    pub fn accept(stream: TcpStream) -> io::Result<Self> {
+        let socket = tungstenite::accept(stream)
+            .map_err(|error| io::Error::new(ErrorKind::ConnectionAborted, error.to_string()))?;
+        Ok(WebSocketLink { socket })
+    }
+}
+
+impl Link for WebSocketLink {
+    fn send(&mut self, probe: &Probe) -> io::Result<()> {
+        let frame = postcard::to_allocvec(probe).map_err(invalid)?;
+        self.socket
+            .send(Message::binary(frame))
+            .map_err(websocket_error)
+    }
+
+    fn receive(&mut self, timeout: Duration) -> io::Result<Option<Echo>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(None);
+            }
+            self.socket.get_ref().set_read_timeout(Some(left))?;
+            match self.socket.read() {
+                Ok(Message::Binary(frame)) => {
+                    return postcard::from_bytes(&frame).map(Some).map_err(invalid)
+                }
+                Ok(Message::Close(_)) => {
+                    return Err(io::Error::new(
+                        ErrorKind::ConnectionAborted,
+                        "the echo closed the WebSocket",
+                    ))
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(error))
+                    if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    return Ok(None)
+                }
+                Err(error) => return Err(websocket_error(error)),
+            }
+        }
+    }
+}
+
+fn websocket_error(error: tungstenite::Error) -> io::Error {
+    match error {
+        tungstenite::Error::Io(error) => error,
+        error => io::Error::new(ErrorKind::ConnectionAborted, error.to_string()),
+    }
+}
+
+fn invalid(error: postcard::Error) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error.to_string())
+}
+
+/// An echo on the other end of a serial line, probes and echoes delimited
+/// with COBS as postcard's `to_allocvec_cobs` does
+///
+/// The device is opened as a file, so the line must be set up beforehand:
+/// `stty -F /dev/ttyUSB0 115200 raw -echo` for a port, or
+/// `socat -d -d pty,raw,echo=0 pty,raw,echo=0` for a pair of pseudo
+/// terminals standing in for one.
+pub struct SerialLink {
+    port: File,
+    frames: Receiver<io::Result<Vec<u8>>>,
+}
+
+impl SerialLink {
+    pub fn open(device: &Path) -> io::Result<Self> {
+        let port = OpenOptions::new().read(true).write(true).open(device)?;
+        let mut reader = port.try_clone()?;
+        let (sender, frames) = mpsc::channel();
+        // Reads block without a timeout, so they get a thread of their own
+        thread::spawn(move || {
+            let mut frame = Vec::new();
+            let mut chunk = [0; 1024];
+            loop {
+                let read = match reader.read(&mut chunk) {
+                    Ok(0) => Err(io::Error::from(ErrorKind::UnexpectedEof)),
+                    result => result,
+                };
+                let read = match read {
+                    Ok(read) => read,
+                    Err(error) => {
+                        let _ = sender.send(Err(error));
+                        return;
+                    }
+                };
+                for &byte in &chunk[..read] {
+                    if byte != 0 {
+                        frame.push(byte);
+                    } else if !frame.is_empty()
+                        && sender.send(Ok(std::mem::take(&mut frame))).is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(SerialLink { port, frames })
+    }
+}
+
+impl Link for SerialLink {
+    fn send(&mut self, probe: &Probe) -> io::Result<()> {
+        let frame = postcard::to_allocvec_cobs(probe).map_err(invalid)?;
+        self.port.write_all(&frame)?;
+        self.port.flush()
+    }
+
+    fn receive(&mut self, timeout: Duration) -> io::Result<Option<Echo>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let mut frame = match self.frames.recv_timeout(left) {
+                Ok(frame) => frame?,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::from(ErrorKind::UnexpectedEof))
+                }
+            };
+            // A frame damaged on the line is dropped, as the runtime does
+            if let Ok(echo) = postcard::from_bytes_cobs(&mut frame) {
+                return Ok(Some(echo));
+            }
+        }
+    }
+}
+
+/// One probe's timings, in microseconds
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatencySample {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Size of the message
+    pub bytes: usize,
+    /// From sending the probe to receiving its echo
+    pub round_trip: f64,
+    /// Spent by the echo from the probe's arrival to sending the echo
+    pub handled: f64,
+    pub decode: f64,
+    pub encode: f64,
+}
+
+impl LatencySample {
+    /// The round trip but for the time the echo spent handling the probe:
+    /// both ways over the link, with framing and scheduling
+    pub fn transport(&self) -> f64 {
+        (self.round_trip - self.handled).max(0.0)
+    }
+}
+
+/// A probe the echo could not decode or encode again
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatencyFailure {
+    pub file: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub error: String,
+}
+
+/// Everything measured over one transport
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatencyRun {
+    pub transport: String,
+    pub samples: Vec<LatencySample>,
+    pub failures: Vec<LatencyFailure>,
+}
+
+/// How a time is spread over the samples, by nearest rank
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Self {
+        let mut values: Vec<f64> = values.into_iter().collect();
+        if values.is_empty() {
+            return Distribution::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let rank = |p: f64| values[((p * values.len() as f64).ceil() as usize).max(1) - 1];
+        Distribution {
+            p50: rank(0.5),
+            p90: rank(0.9),
+            p99: rank(0.99),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// The timings of one message type, or of all of them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// The type, or `all`
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub probes: usize,
+    /// The largest message of the type
+    pub bytes: usize,
+    pub round_trip: Distribution,
+    pub transport: Distribution,
+    pub decode: Distribution,
+    pub encode: Distribution,
+}
+
+impl LatencySummary {
+    fn of<'a>(type_name: &str, samples: impl Iterator<Item = &'a LatencySample> + Clone) -> Self {
+        LatencySummary {
+            type_name: type_name.to_string(),
+            probes: samples.clone().count(),
+            bytes: samples.clone().map(|s| s.bytes).max().unwrap_or(0),
+            round_trip: Distribution::of(samples.clone().map(|s| s.round_trip)),
+            transport: Distribution::of(samples.clone().map(LatencySample::transport)),
+            decode: Distribution::of(samples.clone().map(|s| s.decode)),
+            encode: Distribution::of(samples.map(|s| s.encode)),
+        }
+    }
+}
+
+impl LatencyRun {
+    pub fn new(transport: &str) -> Self {
+        LatencyRun {
+            transport: transport.to_string(),
+            samples: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// The timings of each type, in the order first probed, followed by
+    /// those of every probe when there are several types
+    pub fn summaries(&self) -> Vec<LatencySummary> {
+        let mut types: Vec<&str> = Vec::new();
+        for sample in &self.samples {
+            if !types.contains(&sample.type_name.as_str()) {
+                types.push(&sample.type_name);
+            }
+        }
+        let mut summaries: Vec<LatencySummary> = types
+            .iter()
+            .map(|&type_name| {
+                LatencySummary::of(
+                    type_name,
+                    self.samples
+                        .iter()
+                        .filter(move |s| s.type_name == type_name),
+                )
+            })
+            .collect();
+        if types.len() > 1 {
+            summaries.push(LatencySummary::of("all", self.samples.iter()));
+        }
+        summaries
+    }
+}
+
+/// Send every fixture `rounds` times over `link`, one probe at a time, and
+/// time the echoes
+///
+/// The first probe is sent again until the echo answers, for an echo that
+/// starts listening after the link is up; a first round, not counted, warms
+/// the echo up. Fails if a later probe gets no echo within five seconds.
+pub fn measure_latency(
+    link: &mut impl Link,
+    transport: &str,
+    fixtures: &[Fixture],
+    rounds: usize,
+) -> io::Result<LatencyRun> {
+    let mut run = LatencyRun::new(transport);
+    let Some(first) = fixtures.first() else {
+        return Ok(run);
+    };
+    let started = Instant::now();
+    let probe = |seq: u32, fixture: &Fixture| Probe {
+        seq,
+        sent_at: started.elapsed().as_micros() as u64,
+        type_name: fixture.type_name.clone(),
+        payload: fixture.frame.clone(),
+    };
+
+    let connecting = Instant::now();
+    loop {
+        link.send(&probe(0, first))?;
+        if link.receive(CONNECT_RETRY)?.is_some() {
+            break;
+        }
+        if connecting.elapsed() > CONNECT_TIMEOUT {
+            return Err(io::Error::new(
+                ErrorKind::TimedOut,
+                format!("no echo within {} s", CONNECT_TIMEOUT.as_secs()),
+            ));
+        }
+    }
+
+    let mut seq = 0;
+    for round in 0..=rounds {
+        for fixture in fixtures {
+            seq += 1;
+            let sent = Instant::now();
+            link.send(&probe(seq, fixture))?;
+            // Echoes of the first probe sent again may still be on their way
+            let echo = loop {
+                match link.receive(ECHO_TIMEOUT)? {
+                    Some(echo) if echo.seq == seq => break echo,
+                    Some(_) => {}
+                    None => {
+                        return Err(io::Error::new(
+                            ErrorKind::TimedOut,
+                            format!(
+                                "no echo of {} within {} s",
+                                fixture.file,
+                                ECHO_TIMEOUT.as_secs()
+                            ),
+                        ))
+                    }
+                }
+            };
+            let round_trip = sent.elapsed().as_secs_f64() * 1e6;
+            if round == 0 {
+                continue;
+            }
+            let error = match echo.error {
+                Some(error) => Some(error),
+                None if echo.payload != fixture.frame => {
+                    Some("encoded again differently".to_string())
+                }
+                None => None,
+            };
+            if let Some(error) = error {
+                if !run.failures.iter().any(|f| f.file == fixture.file) {
+                    run.failures.push(LatencyFailure {
+                        file: fixture.file.clone(),
+                        type_name: fixture.type_name.clone(),
+                        error,
+                    });
+                }
+                continue;
+            }
+            run.samples.push(LatencySample {
+                type_name: fixture.type_name.clone(),
+                bytes: fixture.frame.len(),
+                round_trip,
+                handled: echo.handled_micros,
+                decode: echo.decode_micros,
+                encode: echo.encode_micros,
+            });
+        }
+    }
+    Ok(run)
+}
+
+/// A table per run of the median and 99th percentile of each time, in
+/// microseconds, per message type, followed by the fixtures the echo could
+/// not handle
+pub fn latency_report(runs: &[LatencyRun]) -> String {
+    let mut out = String::new();
+    for run in runs {
+        let mut rows = vec![vec![
+            format!("{} (us)", run.transport),
+            "probes".to_string(),
+            "bytes".to_string(),
+            "round trip p50".to_string(),
+            "p99".to_string(),
+            "transport p50".to_string(),
+            "p99".to_string(),
+            "decode p50".to_string(),
+            "p99".to_string(),
+            "encode p50".to_string(),
+            "p99".to_string(),
+        ]];
+        for summary in run.summaries() {
+            let mut row = vec![
+                summary.type_name,
+                summary.probes.to_string(),
+                summary.bytes.to_string(),
+            ];
+            for distribution in [
+                summary.round_trip,
+                summary.transport,
+                summary.decode,
+                summary.encode,
+            ] {
+                row.push(format!("{:.1}", distribution.p50));
+                row.push(format!("{:.1}", distribution.p99));
+            }
+            rows.push(row);
+        }
+        write_table(&mut out, &rows);
+        let mut failed: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for failure in &run.failures {
+            failed
+                .entry(&failure.error)
+                .or_default()
+                .push(&failure.file);
+        }
+        for (error, files) in failed {
+            out.push_str(&format!("failed ({}): {}\n", error, files.join(", ")));
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,255 @@
+//! Encode and decode throughput of TypeScript runtimes over the fixture corpus
+//!
+//! `tests/bench/throughput-runner.ts` decodes and re-encodes every fixture the
+//! generator wrote, printing one JSON line per fixture; the `postcard-ts-bench`
+//! binary runs it once per runtime (the sources, or builds such as one of an
+//! earlier release) and [`report`] lines the runs up side by side:
+//!
+//! ```
+//! use postcard_ts_bench::{report, Run};
+//!
+//! let before = Run::parse(
+//!     "dist-0.4",
+//!     r#"{"file":"point.bin","type":"Point","bytes":2,"decode":1000000,"encode":500000,"encodeInto":null}"#,
+//! )?;
+//! let after = Run::parse(
+//!     "src",
+//!     r#"{"file":"point.bin","type":"Point","bytes":2,"decode":1000000,"encode":1000000,"encodeInto":2000000}"#,
+//! )?;
+//! assert_eq!(after.totals().encode, 2.0);
+//! assert!(report(&[before, after]).contains("x2.00"));
+//! # Ok::<(), postcard_ts_bench::ParseError>(())
+//! ```
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use serde::{Deserialize, Serialize};
+
+mod latency;
+
+pub use latency::{
+    latency_report, measure_latency, Distribution, Echo, Fixture, LatencyFailure, LatencyRun,
+    LatencySample, LatencySummary, Link, Probe, SerialLink, WebSocketLink,
+};
+
+/// Throughput of one runtime on one fixture, in operations per second
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub file: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Size of the encoded fixture
+    pub bytes: usize,
+    pub decode: f64,
+    pub encode: f64,
+    /// Encoding into a reused `Writer`; `None` for runtimes without one
+    #[serde(rename = "encodeInto")]
+    pub encode_into: Option<f64>,
+}
+
+/// A fixture the runner could not measure
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Skipped {
+    pub file: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(rename = "skipped")]
+    pub reason: String,
+}
+
+/// Everything measured for one runtime
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// The runtime directory, or `src` for the sources
+    pub runtime: String,
+    pub measurements: Vec<Measurement>,
+    pub skipped: Vec<Skipped>,
+}
+
+/// Throughput over the whole corpus, in MB/s
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Totals {
+    pub decode: f64,
+    pub encode: f64,
+    pub encode_into: Option<f64>,
+}
+
+/// A runner line that is not a measurement
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub source: serde_json::Error,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Runner output line {}: {}", self.line, self.source)
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Skipped(Skipped),
+    Measured(Measurement),
+}
+
+impl Run {
+    /// Read the runner's output for `runtime`; blank lines are ignored
+    pub fn parse(runtime: &str, output: &str) -> Result<Self, ParseError> {
+        let mut run = Run {
+            runtime: runtime.to_string(),
+            measurements: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for (index, line) in output.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed = serde_json::from_str(line).map_err(|source| ParseError {
+                line: index + 1,
+                source,
+            })?;
+            match parsed {
+                Line::Measured(measurement) => run.measurements.push(measurement),
+                Line::Skipped(skipped) => run.skipped.push(skipped),
+            }
+        }
+        Ok(run)
+    }
+
+    /// Throughput of handling every fixture once: the bytes of the corpus
+    /// over the time all of them take, so large fixtures weigh more
+    pub fn totals(&self) -> Totals {
+        Totals {
+            decode: self.throughput(|m| Some(m.decode)).unwrap_or(0.0),
+            encode: self.throughput(|m| Some(m.encode)).unwrap_or(0.0),
+            encode_into: self.throughput(|m| m.encode_into),
+        }
+    }
+
+    /// Corpus throughput at the rates `rate` picks, if every fixture has one
+    fn throughput(&self, rate: Rate) -> Option<f64> {
+        let mut bytes = 0.0;
+        let mut seconds = 0.0;
+        for measurement in &self.measurements {
+            bytes += measurement.bytes as f64;
+            seconds += 1.0 / rate(measurement)?;
+        }
+        Some(if seconds > 0.0 {
+            bytes / seconds / 1e6
+        } else {
+            0.0
+        })
+    }
+
+    fn find(&self, file: &str) -> Option<&Measurement> {
+        self.measurements.iter().find(|m| m.file == file)
+    }
+}
+
+/// Picks one of the rates of a measurement
+type Rate = fn(&Measurement) -> Option<f64>;
+
+/// A table of decode and encode throughput (MB/s) per fixture, one column per
+/// run, followed by the corpus totals
+///
+/// With more than one run, a last column gives the last run's throughput as a
+/// multiple of the first's. Fixtures are listed in the first run's order;
+/// ones another run did not measure show `-` there.
+pub fn report(runs: &[Run]) -> String {
+    let mut out = String::new();
+    let Some(first) = runs.first() else {
+        return out;
+    };
+    let sections: [(&str, Rate); 3] = [
+        ("decode", |m| Some(m.decode)),
+        ("encode", |m| Some(m.encode)),
+        ("encode into a reused writer", |m| m.encode_into),
+    ];
+    for (title, rate) in sections {
+        if runs
+            .iter()
+            .all(|run| run.measurements.iter().all(|m| rate(m).is_none()))
+        {
+            continue;
+        }
+        let mut rows = vec![header(title, runs)];
+        for measurement in &first.measurements {
+            let cells = runs.iter().map(|run| {
+                run.find(&measurement.file)
+                    .and_then(|m| Some(m.bytes as f64 * rate(m)? / 1e6))
+            });
+            rows.push(row(&measurement.file, measurement.bytes, cells));
+        }
+        let totals = runs.iter().map(|run| run.throughput(rate));
+        let corpus: usize = first.measurements.iter().map(|m| m.bytes).sum();
+        rows.push(row("total", corpus, totals));
+        write_table(&mut out, &rows);
+        out.push('\n');
+    }
+    let skipped: Vec<&str> = first.skipped.iter().map(|s| s.file.as_str()).collect();
+    if !skipped.is_empty() {
+        let _ = writeln!(out, "skipped (no schema): {}", skipped.join(", "));
+    }
+    out
+}
+
+fn header(title: &str, runs: &[Run]) -> Vec<String> {
+    let mut cells = vec![format!("{} (MB/s)", title), "bytes".to_string()];
+    cells.extend(runs.iter().map(|run| run.runtime.clone()));
+    if runs.len() > 1 {
+        cells.push("change".to_string());
+    }
+    cells
+}
+
+fn row(label: &str, bytes: usize, rates: impl Iterator<Item = Option<f64>>) -> Vec<String> {
+    let rates: Vec<Option<f64>> = rates.collect();
+    let mut cells = vec![label.to_string(), bytes.to_string()];
+    cells.extend(rates.iter().map(|rate| match rate {
+        Some(rate) => format!("{:.1}", rate),
+        None => "-".to_string(),
+    }));
+    if rates.len() > 1 {
+        cells.push(match (rates.first(), rates.last()) {
+            (Some(Some(first)), Some(Some(last))) if *first > 0.0 => {
+                format!("x{:.2}", last / first)
+            }
+            _ => "-".to_string(),
+        });
+    }
+    cells
+}
+
+/// Write `rows` with the first column left-aligned and the rest right-aligned
+fn write_table(out: &mut String, rows: &[Vec<String>]) {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(String::len)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows {
+        let mut line = String::new();
+        for (column, cell) in row.iter().enumerate() {
+            if column == 0 {
+                let _ = write!(line, "{:<width$}", cell, width = widths[column]);
+            } else {
+                let _ = write!(line, "  {:>width$}", cell, width = widths[column]);
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
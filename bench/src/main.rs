@@ -0,0 +1,387 @@
+//! `postcard-ts-bench`: compares the encode and decode throughput of
+//! TypeScript runtimes over the fixture corpus, or measures the latency of
+//! messages crossing from Rust to TypeScript and back
+//!
+//! Runs `tests/bench/throughput-runner.ts` once per `--runtime`, under Node
+//! through vite-node (from the npm dev dependencies) or under deno with
+//! `POSTCARD_TS_RUNNER=deno`, and prints the runs side by side. The
+//! `latency` mode runs `tests/bench/latency-echo.ts` the same way, or waits
+//! for an application's own echo to connect.
+
+use std::env;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use postcard_ts_bench::{
+    latency_report, measure_latency, report, Fixture, LatencyRun, Link, Run, SerialLink,
+    WebSocketLink,
+};
+
+const RUNNER: &str = "tests/bench/throughput-runner.ts";
+const ECHO: &str = "tests/bench/latency-echo.ts";
+
+const USAGE: &str = "\
+Usage: postcard-ts-bench [options]
+       postcard-ts-bench latency [options]
+
+Decodes and re-encodes every fixture in the corpus with each runtime, as
+often as fits in the time given, and prints the throughput in MB/s; with
+several runtimes, the last is compared with the first.
+
+Options:
+  --runtime <dir>     A built runtime, the directory holding its index.js;
+                      `src` for the sources. Repeat to compare (default: src)
+  --fixtures <dir>    Fixture corpus (default: test-fixtures/fixtures, from
+                      `npm run generate-fixtures`)
+  --millis <n>        Time spent on each fixture and operation (default: 200)
+  --json              Print the measurements as JSON instead of a table
+  -h, --help          Print this help
+
+latency: sends every fixture to an echo, which decodes it and encodes it
+again, and prints percentiles of the round trip, of decoding, of encoding
+and of the transport, the rest, in microseconds.
+
+Options:
+  --websocket <addr>  Listen for the echo on <addr> (default: 127.0.0.1:7420)
+  --serial <device>   Reach the echo over this end of a serial line instead
+  --serial-peer <dev> The end the echo opens (default: none, see --external)
+  --external          Start no echo; wait for one, such as an application
+                      calling echoLatencyProbes, to connect
+  --fixtures <dir>    Fixture corpus (default: test-fixtures/fixtures)
+  --rounds <n>        Probes per fixture (default: 100)
+  --json              Print the samples as JSON instead of a table";
+
+enum Task {
+    Throughput(Options),
+    Latency(LatencyOptions),
+}
+
+struct Options {
+    runtimes: Vec<String>,
+    fixtures: PathBuf,
+    millis: u64,
+    json: bool,
+}
+
+struct LatencyOptions {
+    websocket: String,
+    serial: Option<PathBuf>,
+    serial_peer: Option<PathBuf>,
+    external: bool,
+    fixtures: PathBuf,
+    rounds: usize,
+    json: bool,
+}
+
+fn parse_args(root: &Path) -> Result<Option<Task>, String> {
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("latency") {
+        args.next();
+        return Ok(parse_latency(root, args)?.map(Task::Latency));
+    }
+    let mut options = Options {
+        runtimes: Vec::new(),
+        fixtures: root.join("test-fixtures/fixtures"),
+        millis: 200,
+        json: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--runtime" => options.runtimes.push(value()?),
+            "--fixtures" => options.fixtures = PathBuf::from(value()?),
+            "--millis" => {
+                let millis = value()?;
+                options.millis = millis
+                    .parse()
+                    .map_err(|_| format!("--millis must be a number, got {}", millis))?;
+            }
+            "--json" => options.json = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unexpected argument {}", other)),
+        }
+    }
+    if options.runtimes.is_empty() {
+        options.runtimes.push("src".to_string());
+    }
+    Ok(Some(Task::Throughput(options)))
+}
+
+fn parse_latency(
+    root: &Path,
+    mut args: impl Iterator<Item = String>,
+) -> Result<Option<LatencyOptions>, String> {
+    let mut options = LatencyOptions {
+        websocket: "127.0.0.1:7420".to_string(),
+        serial: None,
+        serial_peer: None,
+        external: false,
+        fixtures: root.join("test-fixtures/fixtures"),
+        rounds: 100,
+        json: false,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--websocket" => options.websocket = value()?,
+            "--serial" => options.serial = Some(PathBuf::from(value()?)),
+            "--serial-peer" => options.serial_peer = Some(PathBuf::from(value()?)),
+            "--external" => options.external = true,
+            "--fixtures" => options.fixtures = PathBuf::from(value()?),
+            "--rounds" => {
+                let rounds = value()?;
+                options.rounds = rounds
+                    .parse()
+                    .map_err(|_| format!("--rounds must be a number, got {}", rounds))?;
+            }
+            "--json" => options.json = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unexpected argument {}", other)),
+        }
+    }
+    if options.serial.is_some() && options.serial_peer.is_none() && !options.external {
+        return Err("--serial needs --serial-peer for the echo, or --external".to_string());
+    }
+    Ok(Some(options))
+}
+
+fn runner(root: &Path) -> Result<Command, String> {
+    match env::var("POSTCARD_TS_RUNNER").as_deref() {
+        Ok("deno") => {
+            let mut command = Command::new("deno");
+            command.args([
+                "run",
+                "--allow-read",
+                "--allow-env",
+                "--unstable-sloppy-imports",
+            ]);
+            Ok(command)
+        }
+        Ok("node") | Err(_) => Ok(Command::new(root.join("node_modules/.bin/vite-node"))),
+        Ok(other) => Err(format!(
+            "unknown POSTCARD_TS_RUNNER {}; use node or deno",
+            other
+        )),
+    }
+}
+
+fn measure(root: &Path, runtime: &str, options: &Options) -> Result<Run, String> {
+    let mut command = runner(root)?;
+    command
+        .arg(RUNNER)
+        .arg(&options.fixtures)
+        .arg(options.millis.to_string())
+        .current_dir(root);
+    if runtime != "src" {
+        let dir = env::current_dir()
+            .map_err(|error| error.to_string())?
+            .join(runtime);
+        command.env("POSTCARD_RUNTIME", dir);
+    } else {
+        command.env_remove("POSTCARD_RUNTIME");
+    }
+    let output = command
+        .output()
+        .map_err(|error| format!("could not run {}: {}", RUNNER, error))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed for runtime {}:\n{}",
+            RUNNER,
+            runtime,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Run::parse(runtime, &String::from_utf8_lossy(&output.stdout)).map_err(|error| error.to_string())
+}
+
+/// `fixtures` made absolute, as the runners start in the repository root
+fn fixtures_dir(fixtures: &Path) -> Result<PathBuf, String> {
+    let fixtures = env::current_dir()
+        .map_err(|error| error.to_string())?
+        .join(fixtures);
+    if !fixtures.join("manifest.json").is_file() {
+        return Err(format!(
+            "no manifest.json in {} (run `npm run generate-fixtures`)",
+            fixtures.display()
+        ));
+    }
+    Ok(fixtures)
+}
+
+fn run(root: &Path, mut options: Options) -> Result<(), String> {
+    options.fixtures = fixtures_dir(&options.fixtures)?;
+    let mut runs = Vec::new();
+    for runtime in &options.runtimes {
+        eprintln!("measuring {}", runtime);
+        runs.push(measure(root, runtime, &options)?);
+    }
+    if options.json {
+        let json = serde_json::to_string_pretty(&runs).map_err(|error| error.to_string())?;
+        println!("{}", json);
+    } else {
+        print!("{}", report(&runs));
+    }
+    Ok(())
+}
+
+/// The fixtures the manifest lists, with their frames
+fn load_fixtures(dir: &Path) -> Result<Vec<Fixture>, String> {
+    #[derive(serde::Deserialize)]
+    struct Manifest {
+        fixtures: Vec<Entry>,
+    }
+    #[derive(serde::Deserialize)]
+    struct Entry {
+        file: String,
+        #[serde(rename = "type")]
+        type_name: String,
+    }
+
+    let manifest = std::fs::read_to_string(dir.join("manifest.json"))
+        .map_err(|error| format!("could not read the manifest: {}", error))?;
+    let manifest: Manifest = serde_json::from_str(&manifest)
+        .map_err(|error| format!("could not parse the manifest: {}", error))?;
+    manifest
+        .fixtures
+        .into_iter()
+        .map(|entry| {
+            let frame = std::fs::read(dir.join(&entry.file))
+                .map_err(|error| format!("could not read {}: {}", entry.file, error))?;
+            Ok(Fixture {
+                file: entry.file,
+                type_name: entry.type_name,
+                frame,
+            })
+        })
+        .collect()
+}
+
+/// Start the echo runner, its output passed on to stderr
+fn spawn_echo(root: &Path, mode: &str, target: &str) -> Result<Child, String> {
+    let mut command = runner(root)?;
+    let mut child = command
+        .arg(ECHO)
+        .args([mode, target])
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("could not run {}: {}", ECHO, error))?;
+    if let Some(stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                eprintln!("{}", line);
+            }
+        });
+    }
+    Ok(child)
+}
+
+/// Accept the echo's connection, unless the runner exits first
+fn accept_echo(
+    listener: &TcpListener,
+    mut echo: Option<&mut Child>,
+) -> Result<WebSocketLink, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|error| error.to_string())?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream
+                    .set_nonblocking(false)
+                    .map_err(|error| error.to_string())?;
+                return WebSocketLink::accept(stream).map_err(|error| error.to_string());
+            }
+            Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+            Err(error) => return Err(error.to_string()),
+        }
+        if let Some(child) = echo.as_deref_mut() {
+            if let Some(status) = child.try_wait().map_err(|error| error.to_string())? {
+                return Err(format!("{} exited before connecting ({})", ECHO, status));
+            }
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn run_latency(root: &Path, mut options: LatencyOptions) -> Result<(), String> {
+    options.fixtures = fixtures_dir(&options.fixtures)?;
+    let fixtures = load_fixtures(&options.fixtures)?;
+    let mut echo = None;
+    let measured = match &options.serial {
+        Some(device) => {
+            let mut link = SerialLink::open(device)
+                .map_err(|error| format!("could not open {}: {}", device.display(), error))?;
+            if let (Some(peer), false) = (&options.serial_peer, options.external) {
+                echo = Some(spawn_echo(root, "serial", &peer.to_string_lossy())?);
+            }
+            eprintln!("measuring serial on {}", device.display());
+            measure_link(&mut link, "serial", &fixtures, options.rounds)
+        }
+        None => {
+            let listener = TcpListener::bind(&options.websocket)
+                .map_err(|error| format!("could not listen on {}: {}", options.websocket, error))?;
+            if options.external {
+                eprintln!("waiting for an echo on ws://{}", options.websocket);
+            } else {
+                let url = format!("ws://{}", options.websocket);
+                echo = Some(spawn_echo(root, "websocket", &url)?);
+            }
+            let mut link = accept_echo(&listener, echo.as_mut())?;
+            eprintln!("measuring websocket on {}", options.websocket);
+            measure_link(&mut link, "websocket", &fixtures, options.rounds)
+        }
+    };
+    if let Some(mut child) = echo {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let run = measured?;
+    if options.json {
+        let json = serde_json::to_string_pretty(&run).map_err(|error| error.to_string())?;
+        println!("{}", json);
+    } else {
+        print!("{}", latency_report(&[run]));
+    }
+    Ok(())
+}
+
+fn measure_link(
+    link: &mut impl Link,
+    transport: &str,
+    fixtures: &[Fixture],
+    rounds: usize,
+) -> Result<LatencyRun, String> {
+    measure_latency(link, transport, fixtures, rounds)
+        .map_err(|error| format!("measuring {} failed: {}", transport, error))
+}
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("the bench crate is inside the repository")
+        .to_path_buf();
+    let task = match parse_args(&root) {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    let result = match task {
+        Task::Throughput(options) => run(&root, options),
+        Task::Latency(options) => run_latency(&root, options),
+    };
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use postcard_ts_bench::{
+    latency_report, measure_latency, Distribution, Echo, Fixture, Link, Probe, WebSocketLink,
+};
+use tungstenite::Message;
+
+/// An echo answering in-process, as `echoLatencyProbes` would
+struct FakeEcho {
+    pending: VecDeque<Echo>,
+    /// Probes to leave unanswered, as an echo not yet listening does
+    deaf: usize,
+}
+
+fn echo(probe: &Probe) -> Echo {
+    let (payload, error) = match probe.type_name.as_str() {
+        "Point" => (probe.payload.clone(), None),
+        // Drops a trailing byte, as a schema missing a field would
+        "Short" => (probe.payload[..probe.payload.len() - 1].to_vec(), None),
+        _ => (
+            Vec::new(),
+            Some(format!("No schema for {}", probe.type_name)),
+        ),
+    };
+    Echo {
+        seq: probe.seq,
+        sent_at: probe.sent_at,
+        decode_micros: 2.0,
+        encode_micros: 3.0,
+        handled_micros: 6.0,
+        payload,
+        error,
+    }
+}
+
+impl Link for FakeEcho {
+    fn send(&mut self, probe: &Probe) -> io::Result<()> {
+        if self.deaf > 0 {
+            self.deaf -= 1;
+        } else {
+            self.pending.push_back(echo(probe));
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, _timeout: Duration) -> io::Result<Option<Echo>> {
+        Ok(self.pending.pop_front())
+    }
+}
+
+fn fixture(file: &str, type_name: &str, frame: &[u8]) -> Fixture {
+    Fixture {
+        file: file.to_string(),
+        type_name: type_name.to_string(),
+        frame: frame.to_vec(),
+    }
+}
+
+#[test]
+fn percentiles_are_nearest_rank() {
+    let distribution = Distribution::of((1..=200).rev().map(f64::from));
+    assert_eq!(
+        distribution,
+        Distribution {
+            p50: 100.0,
+            p90: 180.0,
+            p99: 198.0,
+            max: 200.0,
+        }
+    );
+    assert_eq!(Distribution::of([]), Distribution::default());
+}
+
+#[test]
+fn times_each_fixture_and_records_failures() {
+    let mut link = FakeEcho {
+        pending: VecDeque::new(),
+        deaf: 2,
+    };
+    let fixtures = [
+        fixture("point.bin", "Point", &[1, 2]),
+        fixture("short.bin", "Short", &[1, 2, 3]),
+        fixture("command.bin", "Command", &[0]),
+    ];
+    let run = measure_latency(&mut link, "fake", &fixtures, 3).unwrap();
+    assert_eq!(run.samples.len(), 3);
+    assert!(run.samples.iter().all(|sample| sample.type_name == "Point"
+        && sample.decode == 2.0
+        && sample.handled == 6.0));
+    let failures: Vec<_> = run
+        .failures
+        .iter()
+        .map(|failure| (failure.file.as_str(), failure.error.as_str()))
+        .collect();
+    assert_eq!(
+        failures,
+        [
+            ("short.bin", "encoded again differently"),
+            ("command.bin", "No schema for Command"),
+        ]
+    );
+
+    let report = latency_report(&[run]);
+    assert!(report.starts_with("fake (us)"));
+    assert!(report.contains("Point"));
+    assert!(report.contains("    2.0"));
+    assert!(report.ends_with(
+        "failed (No schema for Command): command.bin\n\
+         failed (encoded again differently): short.bin\n\n"
+    ));
+}
+
+#[test]
+fn echoes_over_a_websocket() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let echoing = thread::spawn(move || {
+        let (mut socket, _) = tungstenite::connect(url).unwrap();
+        while let Ok(message) = socket.read() {
+            if let Message::Binary(frame) = message {
+                let probe: Probe = postcard::from_bytes(&frame).unwrap();
+                let echo = postcard::to_allocvec(&echo(&probe)).unwrap();
+                socket.send(Message::binary(echo)).unwrap();
+            }
+        }
+    });
+    let (stream, _) = listener.accept().unwrap();
+    let mut link = WebSocketLink::accept(stream).unwrap();
+    let fixtures = [fixture("point.bin", "Point", &[1, 2])];
+    let run = measure_latency(&mut link, "websocket", &fixtures, 5).unwrap();
+    drop(link);
+    echoing.join().unwrap();
+    assert_eq!(run.samples.len(), 5);
+    assert!(run.samples.iter().all(|sample| sample.round_trip > 0.0));
+    assert!(run.failures.is_empty());
+}
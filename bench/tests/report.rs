@@ -0,0 +1,93 @@
+use postcard_ts_bench::{report, Run};
+
+const BEFORE: &str = r#"
+{"file":"point.bin","type":"Point","bytes":4,"decode":2000000,"encode":1000000,"encodeInto":null}
+{"file":"telemetry.bin","type":"Telemetry","bytes":100,"decode":100000,"encode":50000,"encodeInto":null}
+{"file":"generic.bin","type":"Wrapper<u8>","skipped":"no schema"}
+"#;
+
+const AFTER: &str = r#"
+{"file":"point.bin","type":"Point","bytes":4,"decode":2000000,"encode":2000000,"encodeInto":4000000}
+{"file":"telemetry.bin","type":"Telemetry","bytes":100,"decode":100000,"encode":100000,"encodeInto":200000}
+{"file":"generic.bin","type":"Wrapper<u8>","skipped":"no schema"}
+"#;
+
+#[test]
+fn parses_measurements_and_skipped_fixtures() {
+    let run = Run::parse("src", AFTER).unwrap();
+    assert_eq!(run.runtime, "src");
+    assert_eq!(run.measurements.len(), 2);
+    assert_eq!(run.measurements[1].type_name, "Telemetry");
+    assert_eq!(run.measurements[1].encode_into, Some(200000.0));
+    assert_eq!(run.skipped.len(), 1);
+    assert_eq!(run.skipped[0].reason, "no schema");
+}
+
+#[test]
+fn names_the_line_that_is_not_a_measurement() {
+    let error = Run::parse("src", "\n{\"file\":\"point.bin\"}\n").unwrap_err();
+    assert_eq!(error.line, 2);
+    assert!(error.to_string().starts_with("Runner output line 2:"));
+}
+
+#[test]
+fn totals_weigh_fixtures_by_time_taken() {
+    let run = Run::parse("before", BEFORE).unwrap();
+    let totals = run.totals();
+    // 104 bytes in 1/2000000 + 1/100000 seconds
+    let decode = 104.0 / (0.5e-6 + 10e-6) / 1e6;
+    assert!((totals.decode - decode).abs() < 1e-9);
+    assert!((totals.encode - decode / 2.0).abs() < 1e-9);
+    assert_eq!(totals.encode_into, None);
+}
+
+#[test]
+fn report_compares_the_last_run_with_the_first() {
+    let runs = [
+        Run::parse("dist-0.4", BEFORE).unwrap(),
+        Run::parse("src", AFTER).unwrap(),
+    ];
+    let report = report(&runs);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(
+        lines[0].split_whitespace().collect::<Vec<_>>(),
+        ["decode", "(MB/s)", "bytes", "dist-0.4", "src", "change"]
+    );
+    let row = |section: &str, label: &str| -> Vec<String> {
+        let start = lines
+            .iter()
+            .position(|line| line.starts_with(section))
+            .unwrap();
+        lines[start..]
+            .iter()
+            .find(|line| line.starts_with(label))
+            .unwrap()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(
+        row("decode", "telemetry.bin"),
+        ["telemetry.bin", "100", "10.0", "10.0", "x1.00"]
+    );
+    assert_eq!(
+        row("encode", "point.bin"),
+        ["point.bin", "4", "4.0", "8.0", "x2.00"]
+    );
+    assert_eq!(
+        row("encode", "total"),
+        ["total", "104", "5.0", "9.9", "x2.00"]
+    );
+    assert_eq!(
+        row("encode into", "point.bin"),
+        ["point.bin", "4", "-", "16.0", "-"]
+    );
+    assert_eq!(lines.last(), Some(&"skipped (no schema): generic.bin"));
+}
+
+#[test]
+fn report_leaves_out_what_no_runtime_measured() {
+    let report = report(&[Run::parse("dist-0.4", BEFORE).unwrap()]);
+    assert!(!report.contains("reused writer"));
+    assert!(!report.contains("change"));
+}
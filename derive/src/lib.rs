@@ -0,0 +1,957 @@
+//! `#[derive(PostcardTs)]` for serde-postcard-ts
+//!
+//! Implements `PostcardTs` for a struct or enum and submits its definition to
+//! the registry read by `serde_postcard_ts::generate_all()`. Use it through the
+//! `serde-postcard-ts` crate, which re-exports it.
+//!
+//! Field and variant names follow `#[serde(rename)]`, `#[serde(rename_all)]`
+//! and `#[serde(rename_all_fields)]`, so the TypeScript objects use the same
+//! names as serde. Postcard encodes fields by position, so the bytes are the
+//! same either way.
+//!
+//! Fields with `#[serde(skip)]` are never on the wire and are left out, while
+//! `#[serde(default)]` changes nothing: postcard always reads every field.
+//! Attributes that would make a field's presence differ between writing and
+//! reading (`skip_serializing`, `skip_deserializing`, `skip_serializing_if`)
+//! are rejected, since postcard has no field tags to tell it was left out.
+//!
+//! Generic types register their definition in terms of their type parameters
+//! (`Shape::Param`); their `PostcardTs` impl requires every parameter to be
+//! `PostcardTs` and describes itself as `Shape::Generic` applied to the
+//! arguments' shapes. Lifetime and const parameters are rejected.
+//!
+//! Non-generic types declare `SCHEMA_HASH`, their schema hash computed at
+//! compile time from `PostcardTs::CONST_SHAPE`, which the derive writes out
+//! next to the registered definition. `#[postcard_ts(schema_hash = 0x...)]`
+//! pins it: the type fails to compile once its wire shape no longer hashes to
+//! the pinned value, which is also registered for `postcard-ts check`.
+//!
+//! `#[postcard_ts(encode_only)]` or `#[postcard_ts(decode_only)]` limits the
+//! generated schema to that direction, for types only ever sent to or only
+//! ever received from the device.
+//!
+//! `#[postcard_ts(max_len = N)]` on a string field limits it to `N` bytes of
+//! UTF-8, as a `heapless::String<N>` on the device would; the generated schema
+//! rejects or truncates longer strings.
+//!
+//! Enum representations other than serde's default, externally tagged one
+//! (`#[serde(untagged)]`, `#[serde(tag = "...")]` with or without
+//! `content`) are rejected: postcard cannot read them back. So is any other
+//! serde attribute not known to leave the wire shape alone, such as
+//! `flatten` and `transparent`.
+//!
+//! Doc comments on the type and on its named fields and variants are
+//! registered too, and become JSDoc comments on the generated TypeScript;
+//! `#[deprecated]` becomes `@deprecated`, with its note.
+//!
+//! `#[postcard_ts(display = "...")]` on an enum variant registers the name
+//! shown to people for it, in the tables `Generator::display_names` emits.
+//!
+//! `#[serde(with = "serde_bytes")]` on a `Vec<u8>` or `&[u8]` field (or an
+//! `Option` of one) makes it a byte string (`Shape::Bytes`), decoded to a
+//! `Uint8Array`. Other `with` modules write what they like, so such fields
+//! must be described as another type with `#[postcard_ts(with = "Type")]`,
+//! as must fields with `serialize_with` or `deserialize_with`; a stand-in
+//! type can be given its wire shape with `Generator::override_type`. The same
+//! attribute on a struct or enum describes it as `Type`, which serde's
+//! `from`, `into` and `try_from` on the type require.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Expr, ExprLit, Field,
+    Fields, GenericParam, Ident, Lit, LitInt, LitStr, Meta, MetaNameValue, Token,
+};
+
+#[proc_macro_derive(PostcardTs, attributes(serde, postcard_ts))]
+pub fn derive_postcard_ts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if let Some(param) = input
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Type(_)))
+    {
+        return Err(Error::new_spanned(
+            param,
+            "PostcardTs cannot be derived for types with lifetime or const parameters yet",
+        ));
+    }
+
+    let krate = quote!(::serde_postcard_ts);
+    let ident = &input.ident;
+    let name = ident.unraw().to_string();
+    let container = SerdeAttrs::parse(&input.attrs)?;
+    let ContainerAttrs {
+        schema_hash,
+        direction,
+        with,
+    } = ContainerAttrs::parse(&input.attrs)?;
+    if let (Some(converted), None) = (&container.converted, &with) {
+        return Err(Error::new_spanned(
+            converted,
+            format!(
+                "PostcardTs cannot tell what is on the wire with #[serde({})]; describe the \
+                 type as that with #[postcard_ts(with = \"Type\")]",
+                attr_name(converted),
+            ),
+        ));
+    }
+    if let (Some(hash), false) = (&schema_hash, input.generics.params.is_empty()) {
+        return Err(Error::new_spanned(
+            hash,
+            "generic types have no schema hash of their own; pin it on the types using them",
+        ));
+    }
+
+    let doc = DocAttrs::parse(&input.attrs)?.tokens();
+    let mut field_docs = Vec::new();
+    let mut variant_docs = Vec::new();
+    let mut const_definition = quote!(#krate::ConstShape::Unit);
+    let mut definition = match &input.data {
+        Data::Struct(data) => {
+            let shapes = const_shapes(&data.fields)?;
+            const_definition = quote!(#krate::ConstShape::Tuple(&[#(#shapes),*]));
+            match &data.fields {
+                Fields::Named(_) => {
+                    let (fields, docs) = fields(&data.fields, container.rename_all)?;
+                    field_docs = docs;
+                    quote!(#krate::Definition::Struct(vec![#(#fields),*]))
+                }
+                Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                    let shape = field_shape(newtype_field(&unnamed.unnamed)?)?;
+                    quote!(#krate::Definition::NewtypeStruct(#shape))
+                }
+                Fields::Unnamed(unnamed) => {
+                    let shapes = field_shapes(&unnamed.unnamed)?;
+                    quote!(#krate::Definition::TupleStruct(vec![#(#shapes),*]))
+                }
+                Fields::Unit => quote!(#krate::Definition::UnitStruct),
+            }
+        }
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            let mut const_variants = Vec::new();
+            for variant in &data.variants {
+                let attrs = SerdeAttrs::parse(&variant.attrs)?;
+                if let Some(converted) = &attrs.converted {
+                    return Err(Error::new_spanned(
+                        converted,
+                        format!(
+                            "PostcardTs cannot tell what is on the wire with #[serde({})] on a \
+                             variant; use it on the variant's fields, with \
+                             #[postcard_ts(with = \"Type\")]",
+                            attr_name(converted),
+                        ),
+                    ));
+                }
+                let name = attrs.rename.unwrap_or_else(|| {
+                    let name = variant.ident.unraw().to_string();
+                    match container.rename_all {
+                        Some(rule) => rule.apply_to_variant(&name),
+                        None => name,
+                    }
+                });
+                let doc = DocAttrs::parse(&variant.attrs)?;
+                let VariantAttrs { display } = VariantAttrs::parse(&variant.attrs)?;
+                let mut docs = Vec::new();
+                let kind = match &variant.fields {
+                    Fields::Named(_) => {
+                        let rule = attrs.rename_all.or(container.rename_all_fields);
+                        let (fields, documented) = fields(&variant.fields, rule)?;
+                        docs = documented;
+                        quote!(#krate::VariantKind::Struct(vec![#(#fields),*]))
+                    }
+                    Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                        let shape = field_shape(newtype_field(&unnamed.unnamed)?)?;
+                        quote!(#krate::VariantKind::Newtype(#shape))
+                    }
+                    Fields::Unnamed(unnamed) => {
+                        let shapes = field_shapes(&unnamed.unnamed)?;
+                        quote!(#krate::VariantKind::Tuple(vec![#(#shapes),*]))
+                    }
+                    Fields::Unit => quote!(#krate::VariantKind::Unit),
+                };
+                if !doc.is_empty() || !docs.is_empty() || display.is_some() {
+                    let doc = doc.tokens();
+                    let display = match display {
+                        Some(display) => quote!(::core::option::Option::Some(#display)),
+                        None => quote!(::core::option::Option::None),
+                    };
+                    variant_docs.push(quote!(#krate::VariantDocs {
+                        name: #name,
+                        doc: #doc,
+                        fields: &[#(#docs),*],
+                        display: #display,
+                    }));
+                }
+                variants.push(quote!(#krate::Variant { name: #name, kind: #kind }));
+                let shapes = const_shapes(&variant.fields)?;
+                const_variants.push(quote!(&[#(#shapes),*]));
+            }
+            const_definition = quote!(#krate::ConstShape::Enum(&[#(#const_variants),*]));
+            quote!(#krate::Definition::Enum(vec![#(#variants),*]))
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "PostcardTs cannot be derived for unions",
+            ))
+        }
+    };
+
+    // Serde writes the type as another one, which describes it
+    if let Some(with) = &with {
+        definition = quote!(#krate::Definition::NewtypeStruct(
+            <#with as #krate::PostcardTs>::shape()
+        ));
+        const_definition = quote!(<#with as #krate::PostcardTs>::CONST_SHAPE);
+        field_docs.clear();
+        variant_docs.clear();
+    }
+
+    let params: Vec<&Ident> = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let param_names: Vec<String> = params
+        .iter()
+        .map(|param| param.unraw().to_string())
+        .collect();
+    let type_name = quote!(#krate::TypeName {
+        name: #name,
+        module: ::core::module_path!(),
+        params: &[#(#param_names),*],
+    });
+
+    // Every type parameter must have a shape for the type to have one
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#krate::PostcardTs));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let shape = if params.is_empty() {
+        quote!(#krate::Shape::Named(#type_name))
+    } else {
+        quote!(#krate::Shape::Generic(#type_name, vec![
+            #(<#params as #krate::PostcardTs>::shape()),*
+        ]))
+    };
+
+    // The definition is written in terms of the parameters, which the
+    // registration and the `static` holding the constant one have no values
+    // for: stand-in types of the same names make the field types resolve to
+    // `Shape::Param` and `ConstShape::Param`
+    let indices = 0..params.len();
+    let stand_ins = quote! {
+        #(
+            #[allow(dead_code, non_camel_case_types)]
+            struct #params;
+            impl #krate::PostcardTs for #params {
+                const CONST_SHAPE: #krate::ConstShape = #krate::ConstShape::Param(#indices);
+
+                fn shape() -> #krate::Shape {
+                    #krate::Shape::Param(#param_names)
+                }
+            }
+        )*
+    };
+    let definition = if params.is_empty() {
+        definition
+    } else {
+        quote!({
+            #stand_ins
+            #definition
+        })
+    };
+    // The definition is a `static`, which the constant of a recursive type
+    // can refer back to
+    let const_shape = quote!(#krate::ConstShape::Named(
+        #type_name,
+        &[#(<#params as #krate::PostcardTs>::CONST_SHAPE),*],
+        {
+            #stand_ins
+            static DEFINITION: #krate::ConstShape = #const_definition;
+            #krate::ConstDefinition::new(&DEFINITION)
+        },
+    ));
+
+    let hash = quote!(#krate::const_schema_hash(
+        &<#ident as #krate::PostcardTs>::CONST_SHAPE
+    ));
+    let (pinned, constant) = match &schema_hash {
+        // Generic types are hashed where they are used, with their arguments
+        _ if !params.is_empty() => (quote!(::core::option::Option::None), quote!()),
+        Some(pin) => {
+            // Evaluated even if nothing uses the constant, so that a stale pin
+            // fails to compile
+            let check = quote_spanned! {pin.span()=>
+                #[allow(deprecated)]
+                const _: u64 = #ident::SCHEMA_HASH;
+            };
+            (
+                quote!(::core::option::Option::Some(#pin)),
+                quote! {
+                    #[allow(deprecated)]
+                    impl #ident {
+                        /// The schema hash of the type's wire shape, checked
+                        /// against the one pinned with
+                        /// `#[postcard_ts(schema_hash = ...)]`
+                        pub const SCHEMA_HASH: u64 =
+                            #krate::check_pinned_schema_hash(#hash, #pin);
+                    }
+
+                    #check
+                },
+            )
+        }
+        None => (
+            quote!(::core::option::Option::None),
+            quote! {
+                #[allow(deprecated)]
+                impl #ident {
+                    /// The schema hash of the type's wire shape, as
+                    /// `serde_postcard_ts::schema_hash` gives it
+                    pub const SCHEMA_HASH: u64 = #hash;
+                }
+            },
+        ),
+    };
+    let direction = match direction {
+        Some(direction) => quote!(::core::option::Option::Some(#krate::Direction::#direction)),
+        None => quote!(::core::option::Option::None),
+    };
+
+    // Spanned by the type's name, so they give where it is declared
+    let line = quote_spanned!(ident.span()=> ::core::line!());
+    let column = quote_spanned!(ident.span()=> ::core::column!());
+
+    // A deprecated type is still described, without warning about it
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #krate::PostcardTs for #ident #ty_generics #where_clause {
+            const CONST_SHAPE: #krate::ConstShape = #const_shape;
+
+            fn shape() -> #krate::Shape {
+                #shape
+            }
+        }
+
+        #constant
+
+        #krate::inventory::submit! {
+            #krate::Registration {
+                type_name: #type_name,
+                definition: || #definition,
+                schema_hash: #pinned,
+                direction: #direction,
+                file: ::core::file!(),
+                line: #line,
+                column: #column,
+                manifest_dir: ::core::env!("CARGO_MANIFEST_DIR"),
+                docs: &#krate::Docs {
+                    doc: #doc,
+                    fields: &[#(#field_docs),*],
+                    variants: &[#(#variant_docs),*],
+                },
+            }
+        }
+    })
+}
+
+/// The written named fields, and the docs of those with doc comments
+fn fields(
+    fields: &Fields,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<(Vec<TokenStream2>, Vec<TokenStream2>)> {
+    let mut out = Vec::new();
+    let mut docs = Vec::new();
+    for (field, attrs) in written_with_attrs(fields)? {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+        let name = attrs.rename.unwrap_or_else(|| {
+            let name = ident.unraw().to_string();
+            match rename_all {
+                Some(rule) => rule.apply_to_field(&name),
+                None => name,
+            }
+        });
+        let doc = DocAttrs::parse(&field.attrs)?;
+        if !doc.is_empty() {
+            let doc = doc.tokens();
+            docs.push(quote!((#name, #doc)));
+        }
+        let shape = field_shape(field)?;
+        out.push(quote!(::serde_postcard_ts::Field {
+            name: #name,
+            shape: #shape,
+        }));
+    }
+    Ok((out, docs))
+}
+
+/// The shape of a field's type, limited by `#[postcard_ts(max_len = N)]`,
+/// written as a byte string by `#[serde(with = "serde_bytes")]` or described
+/// by the type in `#[postcard_ts(with = "Type")]`
+fn field_shape(field: &Field) -> syn::Result<TokenStream2> {
+    let ty = &field.ty;
+    let attrs = FieldAttrs::parse(&field.attrs)?;
+    match (attrs.max_len, attrs.with) {
+        (Some(max), None) => {
+            return Ok(quote!(<#ty as ::serde_postcard_ts::MaxLen>::max_len(#max)));
+        }
+        (None, Some(with)) => {
+            return Ok(quote!(<#with as ::serde_postcard_ts::PostcardTs>::shape()));
+        }
+        (Some(max), Some(_)) => {
+            return Err(Error::new_spanned(
+                max,
+                "max_len cannot limit a field described by another type; limit that type instead",
+            ));
+        }
+        (None, None) => {}
+    }
+    let serde = SerdeAttrs::parse(&field.attrs)?;
+    if let Some(converted) = &serde.converted {
+        return Err(Error::new_spanned(
+            converted,
+            format!(
+                "PostcardTs cannot tell what is on the wire with #[serde({})]; describe the \
+                 field as that with #[postcard_ts(with = \"Type\")]",
+                attr_name(converted),
+            ),
+        ));
+    }
+    Ok(if serde.serde_bytes {
+        quote!(<#ty as ::serde_postcard_ts::SerdeBytes>::bytes_shape())
+    } else {
+        quote!(<#ty as ::serde_postcard_ts::PostcardTs>::shape())
+    })
+}
+
+fn field_shapes(fields: &Punctuated<Field, Token![,]>) -> syn::Result<Vec<TokenStream2>> {
+    written(fields)?.into_iter().map(field_shape).collect()
+}
+
+/// The constant shapes of the written fields, for the schema hash: limits
+/// and byte strings leave the hash alone, so only `with` changes the type
+fn const_shapes(fields: &Fields) -> syn::Result<Vec<TokenStream2>> {
+    written(fields)?
+        .into_iter()
+        .map(|field| {
+            let ty = match FieldAttrs::parse(&field.attrs)?.with {
+                Some(with) => with,
+                None => field.ty.clone(),
+            };
+            Ok(quote!(<#ty as ::serde_postcard_ts::PostcardTs>::CONST_SHAPE))
+        })
+        .collect()
+}
+
+/// The fields postcard writes and reads, i.e. all but `#[serde(skip)]` ones
+fn written<'a>(fields: impl IntoIterator<Item = &'a Field>) -> syn::Result<Vec<&'a Field>> {
+    Ok(written_with_attrs(fields)?
+        .into_iter()
+        .map(|(field, _)| field)
+        .collect())
+}
+
+fn written_with_attrs<'a>(
+    fields: impl IntoIterator<Item = &'a Field>,
+) -> syn::Result<Vec<(&'a Field, SerdeAttrs)>> {
+    let mut out = Vec::new();
+    for field in fields {
+        let attrs = SerdeAttrs::parse(&field.attrs)?;
+        match (attrs.skip_serializing, attrs.skip_deserializing) {
+            (false, false) => out.push((field, attrs)),
+            (true, true) => {}
+            _ => {
+                return Err(Error::new_spanned(
+                    field,
+                    "PostcardTs needs a field to be both serialized and deserialized, or \
+                     neither (#[serde(skip)]): postcard has no field names to tell it is missing",
+                ))
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// The field of a newtype struct or variant, which serde always writes
+fn newtype_field(fields: &Punctuated<Field, Token![,]>) -> syn::Result<&Field> {
+    let field = &fields[0];
+    if written([field])?.is_empty() {
+        return Err(Error::new_spanned(
+            field,
+            "PostcardTs cannot skip the only field of a newtype",
+        ));
+    }
+    Ok(field)
+}
+
+/// `#[postcard_ts(...)]` on a struct or enum
+#[derive(Default)]
+struct ContainerAttrs {
+    /// `schema_hash = 0x...`, the pinned schema hash
+    schema_hash: Option<LitInt>,
+    /// The `Direction` variant of `encode_only` or `decode_only`
+    direction: Option<Ident>,
+    /// `with = "Type"`, describing the type as `Type`
+    with: Option<syn::Type>,
+}
+
+impl ContainerAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = ContainerAttrs::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("postcard_ts"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("schema_hash") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    lit.base10_parse::<u64>()?;
+                    out.schema_hash = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("with") {
+                    out.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                    return Ok(());
+                }
+                let direction = if meta.path.is_ident("encode_only") {
+                    "EncodeOnly"
+                } else if meta.path.is_ident("decode_only") {
+                    "DecodeOnly"
+                } else {
+                    return Err(
+                        meta.error("expected schema_hash, encode_only, decode_only or with")
+                    );
+                };
+                if out
+                    .direction
+                    .as_ref()
+                    .is_some_and(|other| other != direction)
+                {
+                    return Err(meta.error("a type cannot be both encode_only and decode_only"));
+                }
+                out.direction = Some(Ident::new(direction, Span::call_site()));
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// `#[postcard_ts(...)]` on a field
+#[derive(Default)]
+struct FieldAttrs {
+    /// `max_len = N`, limiting a string to `N` bytes
+    max_len: Option<LitInt>,
+    /// `with = "Type"`, describing the field as `Type`
+    with: Option<syn::Type>,
+}
+
+impl FieldAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = FieldAttrs::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("postcard_ts"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("max_len") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    lit.base10_parse::<usize>()?;
+                    out.max_len = Some(lit);
+                } else if meta.path.is_ident("with") {
+                    out.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else {
+                    return Err(meta.error("expected max_len or with"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// `#[postcard_ts(...)]` on an enum variant
+#[derive(Default)]
+struct VariantAttrs {
+    /// `display = "..."`, the name shown to people
+    display: Option<LitStr>,
+}
+
+impl VariantAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = VariantAttrs::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("postcard_ts"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("display") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    if lit.value().trim().is_empty() {
+                        return Err(Error::new_spanned(lit, "a display name cannot be empty"));
+                    }
+                    out.display = Some(lit);
+                } else {
+                    return Err(meta.error("expected display"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// The doc comment and `#[deprecated]` of a type, field or variant
+#[derive(Default)]
+struct DocAttrs {
+    /// The lines of `///` comments (or `#[doc = "..."]`), without the
+    /// indentation common to them or blank lines around them
+    lines: Vec<String>,
+    /// The note and `since` of `#[deprecated]`, empty without either
+    deprecated: Option<String>,
+}
+
+impl DocAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = DocAttrs::default();
+        let mut lines = Vec::new();
+        for attr in attrs {
+            if attr.path().is_ident("doc") {
+                // `#[doc = include_str!(...)]` and the like are left out
+                if let Meta::NameValue(MetaNameValue {
+                    value:
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(lit), ..
+                        }),
+                    ..
+                }) = &attr.meta
+                {
+                    let value = lit.value();
+                    lines.extend(value.split('\n').map(|line| line.trim_end().to_string()));
+                }
+            } else if attr.path().is_ident("deprecated") {
+                out.deprecated = Some(deprecation(&attr.meta)?);
+            }
+        }
+        let indent = lines
+            .iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|line| line.get(indent..).unwrap_or("").to_string())
+            .skip_while(String::is_empty)
+            .collect();
+        let end = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        out.lines = lines[..end].to_vec();
+        Ok(out)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.deprecated.is_none()
+    }
+
+    /// The registered `Doc`
+    fn tokens(&self) -> TokenStream2 {
+        let lines = &self.lines;
+        let deprecated = match &self.deprecated {
+            Some(note) => quote!(::core::option::Option::Some(#note)),
+            None => quote!(::core::option::Option::None),
+        };
+        quote!(::serde_postcard_ts::Doc {
+            lines: &[#(#lines),*],
+            deprecated: #deprecated,
+        })
+    }
+}
+
+/// The note of `#[deprecated]`, `#[deprecated = "note"]` or
+/// `#[deprecated(since = "...", note = "...")]`, e.g. "use X (since 1.2)"
+fn deprecation(meta: &Meta) -> syn::Result<String> {
+    let mut note = String::new();
+    let mut since = None;
+    match meta {
+        Meta::Path(_) => {}
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }),
+            ..
+        }) => note = lit.value(),
+        Meta::NameValue(meta) => {
+            return Err(Error::new_spanned(&meta.value, "expected a string"));
+        }
+        Meta::List(list) => list.parse_nested_meta(|meta| {
+            let value = meta.value()?.parse::<LitStr>()?.value();
+            if meta.path.is_ident("note") {
+                note = value;
+            } else if meta.path.is_ident("since") {
+                since = Some(value);
+            } else {
+                return Err(meta.error("expected note or since"));
+            }
+            Ok(())
+        })?,
+    }
+    Ok(match since {
+        Some(since) if note.is_empty() => format!("since {}", since),
+        Some(since) => format!("{} (since {})", note, since),
+        None => note,
+    })
+}
+
+/// The `#[serde(...)]` attributes that change names or which fields are
+/// written; the others are left to serde
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    rename_all_fields: Option<RenameRule>,
+    skip_serializing: bool,
+    skip_deserializing: bool,
+    /// `with = "serde_bytes"`, writing the field as one byte string
+    serde_bytes: bool,
+    /// Another `with`, `serialize_with`, `deserialize_with`, `from`, `into` or
+    /// `try_from`, making serde write or read something other than what is
+    /// declared
+    converted: Option<syn::Path>,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = SerdeAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    out.rename = Some(single_name(&meta)?.value());
+                } else if meta.path.is_ident("rename_all") {
+                    out.rename_all = Some(RenameRule::parse(&single_name(&meta)?)?);
+                } else if meta.path.is_ident("rename_all_fields") {
+                    out.rename_all_fields = Some(RenameRule::parse(&single_name(&meta)?)?);
+                } else if meta.path.is_ident("skip") {
+                    out.skip_serializing = true;
+                    out.skip_deserializing = true;
+                } else if meta.path.is_ident("skip_serializing") {
+                    out.skip_serializing = true;
+                } else if meta.path.is_ident("skip_deserializing") {
+                    out.skip_deserializing = true;
+                } else if meta.path.is_ident("skip_serializing_if") {
+                    return Err(meta.error(
+                        "PostcardTs cannot represent skip_serializing_if: postcard has no field \
+                         names, so a field left out would be read from the next field's bytes; \
+                         remove it to always write the field, or use #[serde(skip)]",
+                    ));
+                } else if ["untagged", "tag", "content"]
+                    .iter()
+                    .any(|name| meta.path.is_ident(name))
+                {
+                    return Err(Error::new_spanned(
+                        &meta.path,
+                        format!(
+                            "PostcardTs cannot represent #[serde({})]: postcard writes an enum as \
+                             its variant index, and cannot read untagged, internally or \
+                             adjacently tagged enums, which need a self-describing format",
+                            attr_name(&meta.path),
+                        ),
+                    ));
+                } else if meta.path.is_ident("with") {
+                    out.serde_bytes = meta.value()?.parse::<LitStr>()?.value() == "serde_bytes";
+                    if !out.serde_bytes {
+                        out.converted = Some(meta.path.clone());
+                    }
+                } else if ["serialize_with", "deserialize_with", "from", "into", "try_from"]
+                    .iter()
+                    .any(|name| meta.path.is_ident(name))
+                {
+                    meta.value()?.parse::<LitStr>()?;
+                    out.converted = Some(meta.path.clone());
+                } else if UNCHANGED_SHAPE.iter().any(|name| meta.path.is_ident(name)) {
+                    if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<syn::Expr>()?;
+                    } else if meta.input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        content.parse::<TokenStream2>()?;
+                    }
+                } else {
+                    return Err(Error::new_spanned(
+                        &meta.path,
+                        format!(
+                            "PostcardTs does not support #[serde({})]: it may make postcard write or \
+                             read other than the declared fields and variants",
+                            attr_name(&meta.path),
+                        ),
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// serde attributes that leave what postcard writes as the declared fields
+/// and variants say; the derive rejects those it does not know
+const UNCHANGED_SHAPE: &[&str] = &[
+    "alias",
+    "borrow",
+    "bound",
+    "crate",
+    "default",
+    "deny_unknown_fields",
+    "expecting",
+    "getter",
+    "remote",
+];
+
+/// `flatten` for `#[serde(flatten)]`, in errors
+fn attr_name(path: &syn::Path) -> String {
+    quote!(#path).to_string()
+}
+
+/// The name in `rename = "..."`, or in `rename(serialize = "...", deserialize = "...")`
+/// if both are the same: TypeScript encodes and decodes with one set of names
+fn single_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<LitStr> {
+    if meta.input.peek(Token![=]) {
+        return meta.value()?.parse();
+    }
+    let mut serialize: Option<LitStr> = None;
+    let mut deserialize: Option<LitStr> = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("serialize") {
+            serialize = Some(inner.value()?.parse()?);
+        } else if inner.path.is_ident("deserialize") {
+            deserialize = Some(inner.value()?.parse()?);
+        } else {
+            return Err(inner.error("expected serialize or deserialize"));
+        }
+        Ok(())
+    })?;
+    match (serialize, deserialize) {
+        (Some(serialize), Some(deserialize)) if serialize.value() == deserialize.value() => {
+            Ok(serialize)
+        }
+        _ => Err(meta.error(
+            "PostcardTs needs the same name for serialize and deserialize, as TypeScript uses one",
+        )),
+    }
+}
+
+/// serde's `rename_all` rules
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> syn::Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            other => {
+                return Err(Error::new_spanned(
+                    rule,
+                    format!("unknown rename rule rename_all = {:?}", other),
+                ))
+            }
+        })
+    }
+
+    /// Variant names are written in PascalCase
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_string(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => lowercase_first(variant),
+            RenameRule::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.extend(ch.to_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnake => RenameRule::Snake
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::Kebab => RenameRule::Snake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Field names are written in snake_case
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.extend(ch.to_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => lowercase_first(&RenameRule::Pascal.apply_to_field(field)),
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+/// `name` with its first character in lowercase, which need not be ASCII
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,131 @@
+//! Error codes shared between postcard and serde-postcard-ts
+//!
+//! Every error from the TypeScript codec carries a stable string code
+//! (`error.code`). This crate maps [`postcard::Error`] onto the same codes, so
+//! services decoding with postcard and dashboards decoding with
+//! serde-postcard-ts report identical identifiers for the same bad bytes.
+//!
+//! ```
+//! use postcard_ts_error_codes::ErrorCode;
+//!
+//! let error = postcard::from_bytes::<bool>(&[0x02]).unwrap_err();
+//! assert_eq!(ErrorCode::from(&error).as_str(), "BAD_BOOL");
+//! ```
+//!
+//! The TypeScript catalogue lives in `src/types/error-codes.ts`. The fixture
+//! generator in `test-fixtures` records postcard's codes for a set of malformed
+//! inputs, and the TypeScript suite checks that it reports the same ones.
+
+#![no_std]
+
+use core::fmt::{self, Display, Formatter};
+
+/// A stable error identifier, the same string on both sides
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// Input ended before the value did
+    UnexpectedEnd,
+    /// Varint too long or too large for its type
+    BadVarint,
+    /// Bool byte other than 0 or 1
+    BadBool,
+    /// Char that is not a single Unicode scalar value
+    BadChar,
+    /// String bytes that are not valid UTF-8
+    BadUtf8,
+    /// Option tag other than 0 or 1
+    BadOption,
+    /// Enum discriminant above `u32::MAX`
+    BadEnum,
+    /// Malformed framing around the data
+    BadEncoding,
+    /// CRC mismatch
+    BadCrc,
+    /// Well-formed data the target type rejects, such as an unknown enum variant index
+    InvalidValue,
+    /// A value that cannot be encoded
+    SerializeFailed,
+    /// Fixed-size output buffer too small
+    BufferFull,
+    /// Sequence serialized without a known length
+    SeqLengthUnknown,
+    /// Feature postcard does not support
+    Unsupported,
+}
+
+impl ErrorCode {
+    /// Every code, in catalogue order
+    pub const ALL: [ErrorCode; 14] = [
+        ErrorCode::UnexpectedEnd,
+        ErrorCode::BadVarint,
+        ErrorCode::BadBool,
+        ErrorCode::BadChar,
+        ErrorCode::BadUtf8,
+        ErrorCode::BadOption,
+        ErrorCode::BadEnum,
+        ErrorCode::BadEncoding,
+        ErrorCode::BadCrc,
+        ErrorCode::InvalidValue,
+        ErrorCode::SerializeFailed,
+        ErrorCode::BufferFull,
+        ErrorCode::SeqLengthUnknown,
+        ErrorCode::Unsupported,
+    ];
+
+    /// The code as reported by serde-postcard-ts
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedEnd => "UNEXPECTED_END",
+            ErrorCode::BadVarint => "BAD_VARINT",
+            ErrorCode::BadBool => "BAD_BOOL",
+            ErrorCode::BadChar => "BAD_CHAR",
+            ErrorCode::BadUtf8 => "BAD_UTF8",
+            ErrorCode::BadOption => "BAD_OPTION",
+            ErrorCode::BadEnum => "BAD_ENUM",
+            ErrorCode::BadEncoding => "BAD_ENCODING",
+            ErrorCode::BadCrc => "BAD_CRC",
+            ErrorCode::InvalidValue => "INVALID_VALUE",
+            ErrorCode::SerializeFailed => "SERIALIZE_FAILED",
+            ErrorCode::BufferFull => "BUFFER_FULL",
+            ErrorCode::SeqLengthUnknown => "SEQ_LENGTH_UNKNOWN",
+            ErrorCode::Unsupported => "UNSUPPORTED",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&postcard::Error> for ErrorCode {
+    fn from(error: &postcard::Error) -> Self {
+        use postcard::Error::*;
+        match error {
+            DeserializeUnexpectedEnd => ErrorCode::UnexpectedEnd,
+            DeserializeBadVarint => ErrorCode::BadVarint,
+            DeserializeBadBool => ErrorCode::BadBool,
+            DeserializeBadChar => ErrorCode::BadChar,
+            DeserializeBadUtf8 => ErrorCode::BadUtf8,
+            DeserializeBadOption => ErrorCode::BadOption,
+            DeserializeBadEnum => ErrorCode::BadEnum,
+            DeserializeBadEncoding => ErrorCode::BadEncoding,
+            DeserializeBadCrc => ErrorCode::BadCrc,
+            SerdeDeCustom => ErrorCode::InvalidValue,
+            SerdeSerCustom | CollectStrError => ErrorCode::SerializeFailed,
+            SerializeBufferFull => ErrorCode::BufferFull,
+            SerializeSeqLengthUnknown => ErrorCode::SeqLengthUnknown,
+            WontImplement | NotYetImplemented => ErrorCode::Unsupported,
+            // postcard::Error is non-exhaustive; variants added later are
+            // reported as rejected data until they are catalogued
+            _ => ErrorCode::InvalidValue,
+        }
+    }
+}
+
+impl From<postcard::Error> for ErrorCode {
+    fn from(error: postcard::Error) -> Self {
+        ErrorCode::from(&error)
+    }
+}
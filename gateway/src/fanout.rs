@@ -0,0 +1,86 @@
+//! Messages for browsers, and handing them to every connected one
+
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde_json::{json, Value};
+
+/// A decoded value and the name of its type
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub type_name: String,
+    pub value: Value,
+}
+
+impl Message {
+    /// The message as a Server-Sent Event, with the type name as the event
+    /// name so browsers can `addEventListener` per type
+    pub fn sse(&self, id: Option<u64>) -> String {
+        let mut event = String::new();
+        if let Some(id) = id {
+            event.push_str(&format!("id: {}\n", id));
+        }
+        // Compact JSON has no newlines, so one data line holds it all
+        event.push_str(&format!(
+            "event: {}\ndata: {}\n\n",
+            self.type_name, self.value
+        ));
+        event
+    }
+
+    /// The message as a WebSocket text frame, `{"type": ..., "value": ...}`
+    pub fn ws_text(&self) -> String {
+        json!({ "type": self.type_name, "value": self.value }).to_string()
+    }
+}
+
+/// Hands each published frame to every subscriber
+///
+/// Each subscriber has a queue of `capacity` frames. One that falls that far
+/// behind, or whose receiver is gone, is dropped instead of waited for, so a
+/// slow browser never holds up reading from the device; its connection
+/// handler sees the channel close and can reconnect it.
+#[derive(Debug)]
+pub struct Fanout {
+    capacity: usize,
+    subscribers: Mutex<Vec<SyncSender<Arc<str>>>>,
+}
+
+impl Fanout {
+    pub fn new(capacity: usize) -> Self {
+        Fanout {
+            capacity,
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Receive the frames published from now on
+    pub fn subscribe(&self) -> Receiver<Arc<str>> {
+        let (sender, receiver) = mpsc::sync_channel(self.capacity);
+        self.lock().push(sender);
+        receiver
+    }
+
+    /// Queue `frame` for every subscriber, returning how many got it
+    pub fn publish(&self, frame: impl Into<Arc<str>>) -> usize {
+        let frame = frame.into();
+        let mut subscribers = self.lock();
+        subscribers.retain(|subscriber| match subscriber.try_send(Arc::clone(&frame)) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+        });
+        subscribers.len()
+    }
+
+    /// The number of subscribers still connected as of the last publish
+    pub fn subscribers(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<SyncSender<Arc<str>>>> {
+        // A panic elsewhere while holding the lock leaves the list intact
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
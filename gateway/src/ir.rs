@@ -0,0 +1,272 @@
+//! The generator's JSON IR, as read by the gateway
+//!
+//! The format is the one `Generator::generate_ir()` writes and the TypeScript
+//! runtime reads (`schemasFromIr`); see the generator's `ir` module. It is
+//! checked on load the same way: the version, unknown kinds, references to
+//! types that do not exist or with the wrong number of type arguments, and
+//! float map keys.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use serde::Deserialize;
+
+/// Version of the IR format understood here
+pub const IR_VERSION: u32 = 1;
+
+/// An IR that cannot be loaded, or a type it does not define
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IrError {
+    pub message: String,
+}
+
+impl IrError {
+    fn new(message: impl Into<String>) -> Self {
+        IrError {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for IrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for IrError {}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Type {
+    pub name: String,
+    /// Type parameters of a generic type, used in it as `param` shapes
+    #[serde(default)]
+    pub params: Vec<String>,
+    #[serde(flatten)]
+    pub definition: Definition,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Definition {
+    Struct { fields: Vec<Field> },
+    TupleStruct { items: Vec<Shape> },
+    NewtypeStruct { inner: Shape },
+    UnitStruct,
+    Enum { variants: Vec<Variant> },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub shape: Shape,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Variant {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: VariantKind,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum VariantKind {
+    Unit,
+    Newtype { inner: Shape },
+    Tuple { items: Vec<Shape> },
+    Struct { fields: Vec<Field> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Shape {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String {
+        #[serde(rename = "maxBytes")]
+        max_bytes: Option<usize>,
+    },
+    Bytes,
+    Unit,
+    Option {
+        inner: Box<Shape>,
+    },
+    Seq {
+        item: Box<Shape>,
+        #[serde(rename = "maxLength")]
+        max_length: Option<usize>,
+    },
+    Tuple {
+        items: Vec<Shape>,
+    },
+    Map {
+        key: Box<Shape>,
+        value: Box<Shape>,
+        #[serde(rename = "maxLength")]
+        max_length: Option<usize>,
+    },
+    Ref {
+        name: String,
+        #[serde(default)]
+        args: Vec<Shape>,
+    },
+    Param {
+        name: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Ir {
+    types: Vec<Type>,
+}
+
+/// Parse and check an IR document, returning its types by name
+pub(crate) fn parse(ir: &str) -> Result<HashMap<String, Type>, IrError> {
+    let header: Header =
+        serde_json::from_str(ir).map_err(|error| IrError::new(format!("Invalid IR: {}", error)))?;
+    if header.version != IR_VERSION {
+        return Err(IrError::new(format!(
+            "Unsupported IR version {}",
+            header.version
+        )));
+    }
+    let Ir { types } =
+        serde_json::from_str(ir).map_err(|error| IrError::new(format!("Invalid IR: {}", error)))?;
+
+    let mut by_name = HashMap::new();
+    for type_def in types {
+        if by_name.contains_key(&type_def.name) {
+            return Err(IrError::new(format!(
+                "Type {} is defined more than once",
+                type_def.name
+            )));
+        }
+        by_name.insert(type_def.name.clone(), type_def);
+    }
+    for type_def in by_name.values() {
+        Checker {
+            types: &by_name,
+            params: &type_def.params,
+        }
+        .definition(&type_def.name, &type_def.definition)?;
+    }
+    Ok(by_name)
+}
+
+/// Checks the shapes of one type against the others
+struct Checker<'a> {
+    types: &'a HashMap<String, Type>,
+    params: &'a [String],
+}
+
+impl Checker<'_> {
+    fn definition(&self, path: &str, definition: &Definition) -> Result<(), IrError> {
+        match definition {
+            Definition::Struct { fields } => self.fields(path, fields),
+            Definition::TupleStruct { items } => self.items(path, items),
+            Definition::NewtypeStruct { inner } => self.shape(&format!("{}.0", path), inner),
+            Definition::UnitStruct => Ok(()),
+            Definition::Enum { variants } => variants.iter().try_for_each(|variant| {
+                let path = format!("{}.{}", path, variant.name);
+                match &variant.kind {
+                    VariantKind::Unit => Ok(()),
+                    VariantKind::Newtype { inner } => self.shape(&format!("{}.0", path), inner),
+                    VariantKind::Tuple { items } => self.items(&path, items),
+                    VariantKind::Struct { fields } => self.fields(&path, fields),
+                }
+            }),
+        }
+    }
+
+    fn fields(&self, path: &str, fields: &[Field]) -> Result<(), IrError> {
+        fields
+            .iter()
+            .try_for_each(|field| self.shape(&format!("{}.{}", path, field.name), &field.shape))
+    }
+
+    fn items(&self, path: &str, items: &[Shape]) -> Result<(), IrError> {
+        items
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, item)| self.shape(&format!("{}.{}", path, i), item))
+    }
+
+    fn shape(&self, path: &str, shape: &Shape) -> Result<(), IrError> {
+        match shape {
+            Shape::Option { inner } => self.shape(path, inner),
+            Shape::Seq { item, .. } => self.shape(path, item),
+            Shape::Tuple { items } => self.items(path, items),
+            Shape::Map { key, value, .. } => {
+                if self.is_float_key(key, 0) {
+                    return Err(IrError::new(format!(
+                        "{}: map keys cannot be f32 or f64",
+                        path
+                    )));
+                }
+                self.shape(path, key)?;
+                self.shape(path, value)
+            }
+            Shape::Ref { name, args } => {
+                let referenced = self
+                    .types
+                    .get(name)
+                    .ok_or_else(|| IrError::new(format!("{}: unknown type {}", path, name)))?;
+                if referenced.params.len() != args.len() {
+                    return Err(IrError::new(format!(
+                        "{}: {} takes {} type arguments, got {}",
+                        path,
+                        name,
+                        referenced.params.len(),
+                        args.len()
+                    )));
+                }
+                args.iter()
+                    .enumerate()
+                    .try_for_each(|(i, arg)| self.shape(&format!("{}<{}>", path, i), arg))
+            }
+            Shape::Param { name } if !self.params.contains(name) => Err(IrError::new(format!(
+                "{}: unknown type parameter {}",
+                path, name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a key is a float, also inside an option or a newtype struct,
+    /// which a JavaScript `Map` on the other end could not tell apart
+    fn is_float_key(&self, key: &Shape, depth: usize) -> bool {
+        match key {
+            Shape::F32 | Shape::F64 => true,
+            Shape::Option { inner } => self.is_float_key(inner, depth),
+            // Newtypes around themselves are not valid Rust; the depth only
+            // guards against such an IR
+            Shape::Ref { name, .. } if depth < 16 => match self.types.get(name) {
+                Some(Type {
+                    definition: Definition::NewtypeStruct { inner },
+                    ..
+                }) => self.is_float_key(inner, depth + 1),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
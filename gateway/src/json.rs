@@ -0,0 +1,600 @@
+//! Postcard bytes to JSON, driven by the IR
+//!
+//! The conventions are listed in the crate documentation.
+//!
+//! Errors carry the byte offset and path of the value that failed, like
+//! `DeserializeError` in TypeScript.
+
+use std::fmt::{self, Display, Formatter};
+
+use postcard_ts_error_codes::ErrorCode;
+use serde_json::{Map, Number, Value};
+
+use crate::ir::{Definition, Field, Shape, Type, VariantKind};
+use crate::Protocol;
+
+/// Bytes that are not a value of the type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub code: ErrorCode,
+    /// Absolute index in the input where the value that failed starts
+    pub offset: usize,
+    /// The fields, items and variants from the type to that value, e.g.
+    /// `GameState.player.inventory.items[2].Weapon.element`
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at byte {}: {} ({})",
+            self.path, self.offset, self.message, self.code
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// How 64- and 128-bit integers, bigints in TypeScript, are written
+///
+/// JSON consumers disagree on them: a dashboard wants numbers where it can
+/// get them, a warehouse import wants one type per column. Set for the
+/// whole protocol with [`Protocol::bigints`] and per field with
+/// [`Protocol::field_bigints`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BigIntJson {
+    /// Numbers up to 2^53 - 1 either way, where JavaScript represents them
+    /// exactly, decimal strings beyond
+    #[default]
+    NumberIfSafe,
+    /// Always decimal strings, `"42"`
+    String,
+    /// Always objects holding the decimal string, `{"$bigint": "42"}`, telling
+    /// them apart from other strings
+    Tagged,
+}
+
+impl BigIntJson {
+    fn unsigned(self, value: u128) -> Value {
+        match self {
+            BigIntJson::NumberIfSafe if value <= MAX_SAFE_INTEGER => Value::from(value as u64),
+            _ => self.text(value.to_string()),
+        }
+    }
+
+    fn signed(self, value: i128) -> Value {
+        match self {
+            BigIntJson::NumberIfSafe if value.unsigned_abs() <= MAX_SAFE_INTEGER => {
+                Value::from(value as i64)
+            }
+            _ => self.text(value.to_string()),
+        }
+    }
+
+    fn text(self, decimal: String) -> Value {
+        match self {
+            BigIntJson::Tagged => {
+                let mut object = Map::new();
+                object.insert("$bigint".to_string(), Value::String(decimal));
+                Value::Object(object)
+            }
+            _ => Value::String(decimal),
+        }
+    }
+}
+
+/// The largest integer JavaScript numbers represent exactly
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+/// Type parameters bound to the shapes given for them, which are read in the
+/// scope of the reference that gave them
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    params: &'a [String],
+    args: &'a [Shape],
+    outer: Option<&'a Scope<'a>>,
+}
+
+pub(crate) struct Decoder<'p, 'b> {
+    protocol: &'p Protocol,
+    data: &'b [u8],
+    pos: usize,
+    path: Vec<String>,
+    /// For the field being decoded
+    bigints: BigIntJson,
+    /// Types, options, sequences and maps the value being decoded is in
+    depth: usize,
+}
+
+impl<'p, 'b> Decoder<'p, 'b> {
+    pub fn new(protocol: &'p Protocol, data: &'b [u8], root: &str) -> Self {
+        Decoder {
+            protocol,
+            data,
+            pos: 0,
+            path: vec![root.to_string()],
+            bigints: protocol.bigints,
+            depth: 0,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn value(&mut self, shape: &Shape) -> Result<Value, DecodeError> {
+        self.shape(shape, None)
+    }
+
+    fn fail<T>(&self, offset: usize, code: ErrorCode, message: String) -> Result<T, DecodeError> {
+        Err(DecodeError {
+            code,
+            offset,
+            path: self.path.concat(),
+            message,
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], DecodeError> {
+        let Some(end) = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+        else {
+            return self.fail(
+                self.pos,
+                ErrorCode::UnexpectedEnd,
+                format!(
+                    "{} more bytes needed, {} left",
+                    len,
+                    self.data.len() - self.pos
+                ),
+            );
+        };
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// An unsigned LEB128 varint of at most `bits` bits
+    fn varint(&mut self, bits: u32) -> Result<u128, DecodeError> {
+        let start = self.pos;
+        let mut value: u128 = 0;
+        for i in 0..bits.div_ceil(7) {
+            let byte = self.byte()?;
+            let shift = i * 7;
+            let part = u128::from(byte & 0x7f);
+            if shift + 7 > bits && part >> (bits - shift) != 0 {
+                break;
+            }
+            value |= part << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        self.fail(
+            start,
+            ErrorCode::BadVarint,
+            format!("Varint does not fit in {} bits", bits),
+        )
+    }
+
+    /// A zigzag-encoded signed varint
+    fn signed(&mut self, bits: u32) -> Result<i128, DecodeError> {
+        let value = self.varint(bits)?;
+        let magnitude = (value >> 1) as i128;
+        Ok(if value & 1 == 0 {
+            magnitude
+        } else {
+            -magnitude - 1
+        })
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let start = self.pos;
+        let len = self.varint(64)?;
+        usize::try_from(len).or_else(|_| {
+            self.fail(
+                start,
+                ErrorCode::UnexpectedEnd,
+                format!("Length {} does not fit in memory", len),
+            )
+        })
+    }
+
+    /// The length of a sequence or map, which may not exceed its capacity
+    fn capped_len(
+        &mut self,
+        what: &str,
+        unit: &str,
+        max_length: Option<usize>,
+    ) -> Result<usize, DecodeError> {
+        let start = self.pos;
+        let len = self.len()?;
+        match max_length {
+            Some(max) if len > max => self.fail(
+                start,
+                ErrorCode::InvalidValue,
+                format!("{} of {} {} exceeds maxLength ({})", what, len, unit, max),
+            ),
+            _ => Ok(len),
+        }
+    }
+
+    fn text(&mut self) -> Result<&'b str, DecodeError> {
+        let start = self.pos;
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes).or_else(|error| {
+            self.fail(
+                start,
+                ErrorCode::BadUtf8,
+                format!("Invalid UTF-8: {}", error),
+            )
+        })
+    }
+
+    /// A value of `shape`, failing beyond the protocol's nesting limit before
+    /// recursion could overflow the stack
+    fn shape(&mut self, shape: &Shape, scope: Option<&Scope<'_>>) -> Result<Value, DecodeError> {
+        let nests = matches!(
+            shape,
+            Shape::Option { .. } | Shape::Seq { .. } | Shape::Map { .. } | Shape::Ref { .. }
+        );
+        if !nests {
+            return self.unnested(shape, scope);
+        }
+        self.depth += 1;
+        if self.depth > self.protocol.max_depth {
+            return self.fail(
+                self.pos,
+                ErrorCode::InvalidValue,
+                format!(
+                    "Values nested deeper than the limit ({})",
+                    self.protocol.max_depth
+                ),
+            );
+        }
+        let value = self.unnested(shape, scope)?;
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn unnested(&mut self, shape: &Shape, scope: Option<&Scope<'_>>) -> Result<Value, DecodeError> {
+        let start = self.pos;
+        Ok(match shape {
+            Shape::Bool => match self.byte()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                byte => {
+                    return self.fail(
+                        start,
+                        ErrorCode::BadBool,
+                        format!("Invalid bool 0x{:02x}", byte),
+                    )
+                }
+            },
+            Shape::U8 => Value::from(self.byte()?),
+            Shape::I8 => Value::from(self.byte()? as i8),
+            Shape::U16 | Shape::U32 => {
+                let bits = if *shape == Shape::U16 { 16 } else { 32 };
+                Value::from(self.varint(bits)? as u64)
+            }
+            Shape::I16 | Shape::I32 => {
+                let bits = if *shape == Shape::I16 { 16 } else { 32 };
+                Value::from(self.signed(bits)? as i64)
+            }
+            Shape::U64 => self.bigints.unsigned(self.varint(64)?),
+            Shape::U128 => self.bigints.unsigned(self.varint(128)?),
+            Shape::I64 => self.bigints.signed(self.signed(64)?),
+            Shape::I128 => self.bigints.signed(self.signed(128)?),
+            Shape::F32 => {
+                let bytes = self.take(4)?;
+                let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                // The shortest text that reads back as the same f32, widened
+                value
+                    .to_string()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map_or(Value::Null, Value::Number)
+            }
+            Shape::F64 => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Number::from_f64(f64::from_le_bytes(bytes)).map_or(Value::Null, Value::Number)
+            }
+            Shape::Char => {
+                let text = self.text()?;
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Value::String(c.to_string()),
+                    _ => {
+                        return self.fail(
+                            start,
+                            ErrorCode::BadChar,
+                            format!("Expected one char, got {:?}", text),
+                        )
+                    }
+                }
+            }
+            Shape::String { max_bytes } => {
+                let text = self.text()?;
+                if let Some(max) = max_bytes.filter(|max| text.len() > *max) {
+                    return self.fail(
+                        start,
+                        ErrorCode::InvalidValue,
+                        format!("String of {} bytes exceeds maxBytes ({})", text.len(), max),
+                    );
+                }
+                Value::String(text.to_string())
+            }
+            Shape::Bytes => {
+                let len = self.len()?;
+                Value::Array(
+                    self.take(len)?
+                        .iter()
+                        .map(|&byte| Value::from(byte))
+                        .collect(),
+                )
+            }
+            Shape::Unit => Value::Null,
+            Shape::Option { inner } => match self.byte()? {
+                0 => Value::Null,
+                1 => self.shape(inner, scope)?,
+                byte => {
+                    return self.fail(
+                        start,
+                        ErrorCode::BadOption,
+                        format!("Invalid option discriminant 0x{:02x}", byte),
+                    )
+                }
+            },
+            Shape::Seq { item, max_length } => {
+                let len = self.capped_len("Seq", "items", *max_length)?;
+                // Not more than the bytes left, so a corrupt length fails
+                // when the data runs out rather than up front
+                let mut items = Vec::with_capacity(len.min(self.data.len() - self.pos));
+                for i in 0..len {
+                    items.push(self.nested(format!("[{}]", i), item, scope)?);
+                }
+                Value::Array(items)
+            }
+            Shape::Tuple { items } => self.items(None, "", items, scope)?,
+            Shape::Map {
+                key,
+                value,
+                max_length,
+            } => self.map(key, value, *max_length, scope)?,
+            Shape::Ref { name, args } => {
+                let type_def = self.protocol.type_def(name);
+                let inner = Scope {
+                    params: &type_def.params,
+                    args,
+                    outer: scope,
+                };
+                self.definition(&type_def.name, &type_def.definition, Some(&inner))?
+            }
+            Shape::Param { name } => {
+                let scope = scope.expect("the IR was checked for unbound type parameters");
+                let index = scope
+                    .params
+                    .iter()
+                    .position(|param| param == name)
+                    .expect("the IR was checked for unbound type parameters");
+                self.shape(&scope.args[index], scope.outer)?
+            }
+        })
+    }
+
+    fn nested(
+        &mut self,
+        segment: String,
+        shape: &Shape,
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        self.path.push(segment);
+        let value = self.shape(shape, scope)?;
+        self.path.pop();
+        Ok(value)
+    }
+
+    /// Decode the field `segment` of the type `owner`, with the bigints
+    /// chosen for it; `owner` is `None` for anonymous tuples, whose items
+    /// keep the bigints of the field holding them
+    fn field(
+        &mut self,
+        owner: Option<&str>,
+        segment: String,
+        shape: &Shape,
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        let Some(owner) = owner else {
+            return self.nested(segment, shape, scope);
+        };
+        let outer = self.bigints;
+        let key = format!("{}{}", owner, segment);
+        self.bigints = self
+            .protocol
+            .field_bigints
+            .get(&key)
+            .copied()
+            .unwrap_or(self.protocol.bigints);
+        let value = self.nested(segment, shape, scope);
+        self.bigints = outer;
+        value
+    }
+
+    fn items(
+        &mut self,
+        owner: Option<&str>,
+        prefix: &str,
+        items: &[Shape],
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        let mut values = Vec::with_capacity(items.len());
+        for (i, item) in items.iter().enumerate() {
+            values.push(self.field(owner, format!("{}.{}", prefix, i), item, scope)?);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn fields(
+        &mut self,
+        owner: &str,
+        prefix: &str,
+        fields: &[Field],
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        let mut object = Map::new();
+        for field in fields {
+            let segment = format!("{}.{}", prefix, field.name);
+            let value = self.field(Some(owner), segment, &field.shape, scope)?;
+            object.insert(field.name.clone(), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn map(
+        &mut self,
+        key: &Shape,
+        value: &Shape,
+        max_length: Option<usize>,
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        let len = self.capped_len("Map", "entries", max_length)?;
+        let as_object = self.is_scalar_key(key, scope, 0);
+        let mut object = Map::new();
+        let mut pairs = Vec::new();
+        for i in 0..len {
+            // Object keys are strings, whatever the bigints
+            let outer = self.bigints;
+            if as_object {
+                self.bigints = BigIntJson::String;
+            }
+            let key = self.nested(format!("[{}].key", i), key, scope)?;
+            self.bigints = outer;
+            let value = self.nested(format!("[{}].value", i), value, scope)?;
+            if as_object {
+                let key = match key {
+                    Value::String(text) => text,
+                    scalar => scalar.to_string(),
+                };
+                // A repeated key replaces the earlier value, as in Rust
+                object.insert(key, value);
+            } else {
+                pairs.push(Value::Array(vec![key, value]));
+            }
+        }
+        Ok(if as_object {
+            Value::Object(object)
+        } else {
+            Value::Array(pairs)
+        })
+    }
+
+    /// Whether keys of this shape are JSON strings, numbers or bools
+    fn is_scalar_key(&self, key: &Shape, scope: Option<&Scope<'_>>, depth: usize) -> bool {
+        match key {
+            Shape::Bool
+            | Shape::I8
+            | Shape::I16
+            | Shape::I32
+            | Shape::I64
+            | Shape::I128
+            | Shape::U8
+            | Shape::U16
+            | Shape::U32
+            | Shape::U64
+            | Shape::U128
+            | Shape::Char
+            | Shape::String { .. } => true,
+            // Newtypes around themselves are not valid Rust; the depth only
+            // guards against such an IR
+            Shape::Ref { name, args } if depth < 16 => {
+                let type_def = self.protocol.type_def(name);
+                let inner = Scope {
+                    params: &type_def.params,
+                    args,
+                    outer: scope,
+                };
+                match &type_def.definition {
+                    Definition::NewtypeStruct { inner: key } => {
+                        self.is_scalar_key(key, Some(&inner), depth + 1)
+                    }
+                    Definition::Enum { variants } => variants
+                        .iter()
+                        .all(|variant| matches!(variant.kind, VariantKind::Unit)),
+                    _ => false,
+                }
+            }
+            Shape::Param { name } => {
+                let scope = scope.expect("the IR was checked for unbound type parameters");
+                let index = scope.params.iter().position(|param| param == name);
+                index.is_some_and(|index| {
+                    self.is_scalar_key(&scope.args[index], scope.outer, depth + 1)
+                })
+            }
+            _ => false,
+        }
+    }
+
+    fn definition(
+        &mut self,
+        name: &str,
+        definition: &Definition,
+        scope: Option<&Scope<'_>>,
+    ) -> Result<Value, DecodeError> {
+        Ok(match definition {
+            Definition::Struct { fields } => self.fields(name, "", fields, scope)?,
+            Definition::TupleStruct { items } => self.items(Some(name), "", items, scope)?,
+            Definition::NewtypeStruct { inner } => self.shape(inner, scope)?,
+            Definition::UnitStruct => Value::Null,
+            Definition::Enum { variants } => {
+                let start = self.pos;
+                let index = self.varint(32)?;
+                let Some(variant) = usize::try_from(index).ok().and_then(|i| variants.get(i))
+                else {
+                    return self.fail(
+                        start,
+                        ErrorCode::InvalidValue,
+                        format!(
+                            "Invalid enum discriminant for {}: {} ({} variants)",
+                            name,
+                            index,
+                            variants.len()
+                        ),
+                    );
+                };
+                let prefix = format!(".{}", variant.name);
+                let value = match &variant.kind {
+                    VariantKind::Unit => return Ok(Value::String(variant.name.clone())),
+                    VariantKind::Newtype { inner } => {
+                        self.field(Some(name), prefix, inner, scope)?
+                    }
+                    VariantKind::Tuple { items } => {
+                        self.items(Some(name), &prefix, items, scope)?
+                    }
+                    VariantKind::Struct { fields } => self.fields(name, &prefix, fields, scope)?,
+                };
+                let mut object = Map::new();
+                object.insert(variant.name.clone(), value);
+                Value::Object(object)
+            }
+        })
+    }
+}
+
+impl Protocol {
+    fn type_def(&self, name: &str) -> &Type {
+        self.types
+            .get(name)
+            .expect("the IR was checked for unknown references")
+    }
+}
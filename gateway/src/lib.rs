@@ -0,0 +1,273 @@
+//! Forwards postcard messages from devices to browsers as JSON
+//!
+//! A gateway sits between devices speaking postcard and browsers that would
+//! rather not decode it. [`Protocol`] loads the JSON IR written by
+//! `serde_postcard_ts::Generator::generate_ir()` and, with no Rust types of
+//! its own, decodes any type in it to the JSON the TypeScript side works with,
+//! checks a device's schema hash handshake against it, and formats messages
+//! for Server-Sent Events or WebSocket text frames; [`Fanout`] hands each
+//! frame to every connected browser.
+//!
+//! ```
+//! use postcard_ts_gateway::Protocol;
+//!
+//! let protocol = Protocol::from_ir(r#"{
+//!     "version": 1,
+//!     "types": [{
+//!         "name": "Reading",
+//!         "kind": "struct",
+//!         "fields": [
+//!             { "name": "sensor", "type": { "kind": "u8" } },
+//!             { "name": "celsius", "type": { "kind": "f32" } }
+//!         ]
+//!     }]
+//! }"#)?;
+//!
+//! let bytes = [3, 0x66, 0x66, 0xa2, 0x41];
+//! let message = protocol.message("Reading", &bytes)?;
+//! assert_eq!(message.value, serde_json::json!({ "sensor": 3, "celsius": 20.3 }));
+//! assert_eq!(
+//!     message.sse(Some(7)),
+//!     "id: 7\nevent: Reading\ndata: {\"sensor\":3,\"celsius\":20.3}\n\n"
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ## JSON
+//!
+//! The JSON is what the TypeScript side compares decoded values with (the
+//! shape serde_json writes; see `toSerdeJson` in its test fixtures), so a
+//! browser receives the same values whether it decodes the bytes itself or
+//! gets them from the gateway:
+//!
+//! - Structs are objects and tuples, tuple structs and sequences arrays;
+//!   newtype structs are their inner value
+//! - Enums are externally tagged: `"Unit"` or `{"Variant": value}`
+//! - Unit, unit structs and `None` are `null`
+//! - 64- and 128-bit integers are numbers when JavaScript represents them
+//!   exactly (up to 2^53 - 1 either way), decimal strings otherwise; JSON
+//!   consumers needing one type per field choose strings or tagged objects
+//!   instead, for the protocol or per field ([`BigIntJson`])
+//! - `f32` values use their shortest representation (`20.3`, not
+//!   `20.299999237060547`); non-finite floats are `null`, as in serde_json
+//! - Byte strings are arrays of numbers
+//! - Maps with string, char, integer, bool or unit-variant keys (also in
+//!   newtype structs) are objects with the keys as strings; other maps, which
+//!   serde_json cannot write, are arrays of `[key, value]` pairs in wire order
+
+mod fanout;
+mod ir;
+mod json;
+mod schema_hash;
+
+use std::collections::{BTreeMap, HashMap};
+
+use postcard_ts_error_codes::ErrorCode;
+use serde_json::Value;
+
+pub use fanout::{Fanout, Message};
+pub use ir::{IrError, IR_VERSION};
+pub use json::{BigIntJson, DecodeError};
+pub use schema_hash::{decode_schema_hashes, SchemaHashError, SchemaHashMismatch};
+
+use ir::Shape;
+use json::Decoder;
+
+/// The types of one IR, ready to decode
+#[derive(Clone, Debug)]
+pub struct Protocol {
+    types: HashMap<String, ir::Type>,
+    bigints: BigIntJson,
+    /// By `Type.field`
+    field_bigints: HashMap<String, BigIntJson>,
+    max_depth: usize,
+}
+
+/// Deepest nesting [`Protocol::max_depth`] allows unless set otherwise
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl Protocol {
+    /// Load an IR, checking it as the TypeScript runtime does
+    pub fn from_ir(ir: &str) -> Result<Self, IrError> {
+        Ok(Protocol {
+            types: ir::parse(ir)?,
+            bigints: BigIntJson::default(),
+            field_bigints: HashMap::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        })
+    }
+
+    /// Write 64- and 128-bit integers as `bigints` says, except in fields
+    /// given their own with [`field_bigints`](Self::field_bigints)
+    pub fn bigints(mut self, bigints: BigIntJson) -> Self {
+        self.bigints = bigints;
+        self
+    }
+
+    /// Write the 64- and 128-bit integers of one field as `bigints` says,
+    /// including those in options, sequences and newtypes it holds
+    ///
+    /// Fields are named after their type: `Reading.timestamp`,
+    /// `Telemetry.Log.at` in a struct variant, `Telemetry.Uptime` for a
+    /// newtype variant and `Span.0` for a tuple struct's item.
+    ///
+    /// ```
+    /// use postcard_ts_gateway::{BigIntJson, Protocol};
+    ///
+    /// let protocol = Protocol::from_ir(r#"{
+    ///     "version": 1,
+    ///     "types": [{
+    ///         "name": "Reading",
+    ///         "kind": "struct",
+    ///         "fields": [
+    ///             { "name": "timestamp", "type": { "kind": "u64" } },
+    ///             { "name": "count", "type": { "kind": "u64" } }
+    ///         ]
+    ///     }]
+    /// }"#)?
+    /// .field_bigints("Reading.timestamp", BigIntJson::String);
+    ///
+    /// let value = protocol.to_json("Reading", &[0xe8, 0x07, 3])?;
+    /// assert_eq!(value, serde_json::json!({ "timestamp": "1000", "count": 3 }));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn field_bigints(mut self, field: &str, bigints: BigIntJson) -> Self {
+        self.field_bigints.insert(field.to_string(), bigints);
+        self
+    }
+
+    /// Fail with `INVALID_VALUE` on values nested deeper than `max_depth`
+    /// (default [`DEFAULT_MAX_DEPTH`]), counting each type, option, sequence
+    /// and map a value is in
+    ///
+    /// Decoding recurses once per level, so without a limit a device sending
+    /// a recursive type nested deep enough would overflow the gateway's
+    /// stack, which aborts the process rather than panicking. Raise it only
+    /// as far as the stack of the decoding threads allows.
+    ///
+    /// ```
+    /// use postcard_ts_gateway::Protocol;
+    ///
+    /// let protocol = Protocol::from_ir(r#"{
+    ///     "version": 1,
+    ///     "types": [{
+    ///         "name": "Node",
+    ///         "kind": "struct",
+    ///         "fields": [{
+    ///             "name": "children",
+    ///             "type": { "kind": "seq", "item": { "kind": "ref", "name": "Node" } }
+    ///         }]
+    ///     }]
+    /// }"#)?
+    /// .max_depth(8);
+    ///
+    /// assert!(protocol.to_json("Node", &[1, 1, 0]).is_ok());
+    /// let error = protocol.to_json("Node", &[1; 16]).unwrap_err();
+    /// assert_eq!(error.message, "Values nested deeper than the limit (8)");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The names of the types in the IR, sorted
+    pub fn type_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.types.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Decode one value of `type_name` as JSON
+    ///
+    /// Bytes after the value are ignored, as `postcard::from_bytes` does.
+    pub fn to_json(&self, type_name: &str, bytes: &[u8]) -> Result<Value, DecodeError> {
+        self.take_json(type_name, bytes).map(|(value, _)| value)
+    }
+
+    /// Decode one value of `type_name` as JSON, returning the bytes after it
+    pub fn take_json<'b>(
+        &self,
+        type_name: &str,
+        bytes: &'b [u8],
+    ) -> Result<(Value, &'b [u8]), DecodeError> {
+        let shape = self.root(type_name)?;
+        let mut decoder = Decoder::new(self, bytes, type_name);
+        let value = decoder.value(&shape)?;
+        Ok((value, &bytes[decoder.position()..]))
+    }
+
+    /// Decode one value of `type_name` as a message for the browsers
+    pub fn message(&self, type_name: &str, bytes: &[u8]) -> Result<Message, DecodeError> {
+        Ok(Message {
+            type_name: type_name.to_string(),
+            value: self.to_json(type_name, bytes)?,
+        })
+    }
+
+    /// The schema hash of `type_name`, the same as `schema_hash` gives for the
+    /// Rust type; `None` for types not in the IR and generic types
+    pub fn schema_hash(&self, type_name: &str) -> Option<u64> {
+        let type_def = self.types.get(type_name)?;
+        type_def
+            .params
+            .is_empty()
+            .then(|| schema_hash::hash(&self.types, type_name))
+    }
+
+    /// Check the hashes a peer sent (see [`decode_schema_hashes`]) for each of
+    /// `type_names`
+    ///
+    /// Every one must be sent with the hash it has in the IR; names without a
+    /// hash (not in the IR, or generic) are skipped. Other types the peer
+    /// knows are ignored, so a device may be newer than the gateway in the
+    /// messages the gateway does not forward.
+    pub fn check_schema_hashes(
+        &self,
+        type_names: &[&str],
+        peer: &BTreeMap<String, u64>,
+    ) -> Result<(), SchemaHashError> {
+        let mismatches: Vec<SchemaHashMismatch> = type_names
+            .iter()
+            .filter_map(|&type_name| {
+                let expected = self.schema_hash(type_name)?;
+                let received = peer.get(type_name).copied();
+                (received != Some(expected)).then(|| SchemaHashMismatch {
+                    type_name: type_name.to_string(),
+                    expected,
+                    received,
+                })
+            })
+            .collect();
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaHashError { mismatches })
+        }
+    }
+
+    /// The shape to decode a value of `type_name` with
+    fn root(&self, type_name: &str) -> Result<Shape, DecodeError> {
+        let message = match self.types.get(type_name) {
+            None => format!("Type {} is not in the IR", type_name),
+            Some(type_def) if !type_def.params.is_empty() => {
+                format!(
+                    "Type {} is generic and has no wire shape of its own",
+                    type_name
+                )
+            }
+            Some(_) => {
+                return Ok(Shape::Ref {
+                    name: type_name.to_string(),
+                    args: Vec::new(),
+                })
+            }
+        };
+        Err(DecodeError {
+            code: ErrorCode::InvalidValue,
+            offset: 0,
+            path: type_name.to_string(),
+            message,
+        })
+    }
+}
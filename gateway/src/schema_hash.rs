@@ -0,0 +1,243 @@
+//! Schema hashes computed from the IR
+//!
+//! The same canonical form and 64-bit FNV-1a hash as the generator's
+//! `schema_hash`, so the gateway can check a device's handshake against the
+//! IR it was given without the Rust types the device was built from.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+use postcard_ts_error_codes::ErrorCode;
+
+use crate::ir::{Definition, Shape, Type, VariantKind};
+use crate::json::DecodeError;
+
+/// A type whose hash the peer sent differently, or not at all
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaHashMismatch {
+    pub type_name: String,
+    /// The hash of the type in the IR
+    pub expected: u64,
+    /// The hash the peer sent, if any
+    pub received: Option<u64>,
+}
+
+/// The peer was built from other versions of some types
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaHashError {
+    pub mismatches: Vec<SchemaHashMismatch>,
+}
+
+impl Display for SchemaHashError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let listed: Vec<String> = self
+            .mismatches
+            .iter()
+            .map(|mismatch| match mismatch.received {
+                Some(received) => format!(
+                    "{} (expected {:#018x}, received {:#018x})",
+                    mismatch.type_name, mismatch.expected, received
+                ),
+                None => format!("{} (not sent)", mismatch.type_name),
+            })
+            .collect();
+        write!(f, "Schema hashes differ: {}", listed.join(", "))
+    }
+}
+
+impl std::error::Error for SchemaHashError {}
+
+/// Decode a handshake frame: the peer's hashes by type name, as postcard
+/// encodes a `BTreeMap<&str, u64>`
+pub fn decode_schema_hashes(frame: &[u8]) -> Result<BTreeMap<String, u64>, DecodeError> {
+    postcard::from_bytes(frame).map_err(|error| DecodeError {
+        code: ErrorCode::from(&error),
+        offset: 0,
+        path: String::new(),
+        message: format!("Invalid schema hash handshake: {}", error),
+    })
+}
+
+/// 64-bit FNV-1a
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The hash of a non-generic type
+pub(crate) fn hash(types: &HashMap<String, Type>, name: &str) -> u64 {
+    let mut writer = Writer {
+        types,
+        stack: Vec::new(),
+        out: String::new(),
+    };
+    writer.shape(
+        &Shape::Ref {
+            name: name.to_string(),
+            args: Vec::new(),
+        },
+        &HashMap::new(),
+    );
+    fnv1a(writer.out.as_bytes())
+}
+
+/// Writes the canonical form of a shape
+struct Writer<'a> {
+    types: &'a HashMap<String, Type>,
+    /// Types being written, with their arguments filled in
+    stack: Vec<Shape>,
+    out: String,
+}
+
+impl Writer<'_> {
+    /// Write `shape`, whose type parameters are bound to shapes without any
+    fn shape(&mut self, shape: &Shape, bindings: &HashMap<&str, Shape>) {
+        match shape {
+            Shape::Unit => {}
+            Shape::Tuple { items } => self.shapes(items, bindings),
+            Shape::Option { inner } => self.wrapped("option", inner, bindings),
+            Shape::Seq { item, .. } => self.wrapped("seq", item, bindings),
+            // The same bytes as a `Vec<u8>`
+            Shape::Bytes => self.out.push_str("seq(u8;);"),
+            Shape::Map { key, value, .. } => {
+                self.out.push_str("map(");
+                self.shape(key, bindings);
+                self.out.push_str(")(");
+                self.shape(value, bindings);
+                self.out.push_str(");");
+            }
+            Shape::Ref { name, args } => {
+                let args: Vec<Shape> = args.iter().map(|arg| bound(arg, bindings)).collect();
+                let filled = Shape::Ref {
+                    name: name.clone(),
+                    args: args.clone(),
+                };
+                if let Some(depth) = self.stack.iter().position(|open| *open == filled) {
+                    self.out.push_str(&format!("rec({});", depth));
+                    return;
+                }
+                let types = self.types;
+                let type_def = &types[name];
+                let inner: HashMap<&str, Shape> = type_def
+                    .params
+                    .iter()
+                    .map(String::as_str)
+                    .zip(args)
+                    .collect();
+                self.stack.push(filled);
+                self.definition(&type_def.definition, &inner);
+                self.stack.pop();
+            }
+            Shape::Param { name } => match bindings.get(name.as_str()) {
+                Some(bound) => self.shape(bound, &HashMap::new()),
+                None => self.out.push_str(&format!("param({});", name)),
+            },
+            primitive => {
+                self.out.push_str(kind(primitive));
+                self.out.push(';');
+            }
+        }
+    }
+
+    fn shapes<'s>(
+        &mut self,
+        shapes: impl IntoIterator<Item = &'s Shape>,
+        bindings: &HashMap<&str, Shape>,
+    ) {
+        for shape in shapes {
+            self.shape(shape, bindings);
+        }
+    }
+
+    fn wrapped(&mut self, name: &str, inner: &Shape, bindings: &HashMap<&str, Shape>) {
+        self.out.push_str(name);
+        self.out.push('(');
+        self.shape(inner, bindings);
+        self.out.push_str(");");
+    }
+
+    fn definition(&mut self, definition: &Definition, bindings: &HashMap<&str, Shape>) {
+        match definition {
+            Definition::Struct { fields } => {
+                self.shapes(fields.iter().map(|field| &field.shape), bindings)
+            }
+            Definition::TupleStruct { items } => self.shapes(items, bindings),
+            Definition::NewtypeStruct { inner } => self.shape(inner, bindings),
+            Definition::UnitStruct => {}
+            Definition::Enum { variants } => {
+                self.out.push_str("enum(");
+                for variant in variants {
+                    self.out.push('(');
+                    match &variant.kind {
+                        VariantKind::Unit => {}
+                        VariantKind::Newtype { inner } => self.shape(inner, bindings),
+                        VariantKind::Tuple { items } => self.shapes(items, bindings),
+                        VariantKind::Struct { fields } => {
+                            self.shapes(fields.iter().map(|field| &field.shape), bindings)
+                        }
+                    }
+                    self.out.push(')');
+                }
+                self.out.push_str(");");
+            }
+        }
+    }
+}
+
+/// `shape` with its type parameters replaced by what they are bound to
+fn bound(shape: &Shape, bindings: &HashMap<&str, Shape>) -> Shape {
+    let boxed = |shape: &Shape| Box::new(bound(shape, bindings));
+    match shape {
+        Shape::Param { name } => bindings
+            .get(name.as_str())
+            .cloned()
+            .unwrap_or_else(|| shape.clone()),
+        Shape::Option { inner } => Shape::Option {
+            inner: boxed(inner),
+        },
+        Shape::Seq { item, max_length } => Shape::Seq {
+            item: boxed(item),
+            max_length: *max_length,
+        },
+        Shape::Tuple { items } => Shape::Tuple {
+            items: items.iter().map(|item| bound(item, bindings)).collect(),
+        },
+        Shape::Map {
+            key,
+            value,
+            max_length,
+        } => Shape::Map {
+            key: boxed(key),
+            value: boxed(value),
+            max_length: *max_length,
+        },
+        Shape::Ref { name, args } => Shape::Ref {
+            name: name.clone(),
+            args: args.iter().map(|arg| bound(arg, bindings)).collect(),
+        },
+        other => other.clone(),
+    }
+}
+
+/// The IR `kind` of a primitive shape
+fn kind(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::Bool => "bool",
+        Shape::I8 => "i8",
+        Shape::I16 => "i16",
+        Shape::I32 => "i32",
+        Shape::I64 => "i64",
+        Shape::I128 => "i128",
+        Shape::U8 => "u8",
+        Shape::U16 => "u16",
+        Shape::U32 => "u32",
+        Shape::U64 => "u64",
+        Shape::U128 => "u128",
+        Shape::F32 => "f32",
+        Shape::F64 => "f64",
+        Shape::Char => "char",
+        Shape::String { .. } => "string",
+        _ => unreachable!("not a primitive: {:?}", shape),
+    }
+}
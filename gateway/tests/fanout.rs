@@ -0,0 +1,62 @@
+use postcard_ts_gateway::{Fanout, Message};
+use serde_json::json;
+
+fn message() -> Message {
+    Message {
+        type_name: "Reading".to_string(),
+        value: json!({ "sensor": 3, "label": "a\nb" }),
+    }
+}
+
+#[test]
+fn sse_events_are_named_after_the_type() {
+    assert_eq!(
+        message().sse(Some(12)),
+        "id: 12\nevent: Reading\ndata: {\"sensor\":3,\"label\":\"a\\nb\"}\n\n"
+    );
+    assert_eq!(
+        message().sse(None),
+        "event: Reading\ndata: {\"sensor\":3,\"label\":\"a\\nb\"}\n\n"
+    );
+}
+
+#[test]
+fn ws_text_wraps_the_value_with_its_type() {
+    let text = message().ws_text();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+        json!({ "type": "Reading", "value": { "sensor": 3, "label": "a\nb" } })
+    );
+}
+
+#[test]
+fn every_subscriber_gets_each_frame() {
+    let fanout = Fanout::new(4);
+    let first = fanout.subscribe();
+    let second = fanout.subscribe();
+
+    assert_eq!(fanout.publish("one"), 2);
+    assert_eq!(fanout.publish(message().ws_text()), 2);
+    assert_eq!(&*first.recv().unwrap(), "one");
+    assert_eq!(&*second.recv().unwrap(), "one");
+    assert_eq!(*first.recv().unwrap(), *message().ws_text());
+}
+
+#[test]
+fn slow_and_closed_subscribers_are_dropped() {
+    let fanout = Fanout::new(1);
+    let slow = fanout.subscribe();
+    let closed = fanout.subscribe();
+    drop(closed);
+    let reading = fanout.subscribe();
+
+    assert_eq!(fanout.publish("one"), 2);
+    assert_eq!(&*reading.recv().unwrap(), "one");
+    assert_eq!(fanout.publish("two"), 1);
+    assert_eq!(fanout.subscribers(), 1);
+
+    // The slow one keeps what it had queued, then sees the channel close
+    assert_eq!(&*slow.recv().unwrap(), "one");
+    assert!(slow.recv().is_err());
+    assert_eq!(&*reading.recv().unwrap(), "two");
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+
+use postcard_ts_error_codes::ErrorCode;
+use postcard_ts_gateway::{BigIntJson, Protocol};
+use serde::Serialize;
+use serde_json::json;
+use serde_postcard_ts::{Generator, PostcardTs};
+
+#[derive(PostcardTs, Serialize)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs, Serialize)]
+enum Command {
+    Stop,
+    SetTarget(f32),
+    Move { x: i16, y: i16 },
+    Pair(u8, bool),
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Envelope<T> {
+    seq: u32,
+    body: T,
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Tree {
+    value: u8,
+    children: Vec<Tree>,
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Frame {
+    reading: Envelope<Reading>,
+    commands: Vec<Command>,
+    tag: Option<char>,
+    label: String,
+    raw: Vec<u8>,
+    nothing: (),
+    point: (i32, u16),
+    tree: Tree,
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Counters {
+    total: u64,
+    offset: i64,
+    wide: u128,
+    by_name: BTreeMap<String, u32>,
+    by_id: BTreeMap<u16, bool>,
+    by_cell: BTreeMap<(u8, u8), u8>,
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Timestamp(u64);
+
+#[derive(PostcardTs, Serialize)]
+enum Event {
+    Boot { at: Timestamp, uptime: u64 },
+    Tick(u64),
+}
+
+#[derive(PostcardTs, Serialize)]
+struct Log {
+    events: Vec<Event>,
+    totals: Option<Vec<u64>>,
+    by_id: BTreeMap<u64, i64>,
+}
+
+fn protocol() -> Protocol {
+    Protocol::from_ir(&Generator::new().generate_ir().unwrap()).unwrap()
+}
+
+fn frame() -> Frame {
+    Frame {
+        reading: Envelope {
+            seq: 300,
+            body: Reading {
+                sensor: 3,
+                celsius: -1.5,
+            },
+        },
+        commands: vec![
+            Command::Stop,
+            Command::SetTarget(0.25),
+            Command::Move { x: -4, y: 900 },
+            Command::Pair(7, true),
+        ],
+        tag: Some('é'),
+        label: "boiler".to_string(),
+        raw: vec![0, 255],
+        nothing: (),
+        point: (-70000, 65535),
+        tree: Tree {
+            value: 1,
+            children: vec![Tree {
+                value: 2,
+                children: Vec::new(),
+            }],
+        },
+    }
+}
+
+#[test]
+fn json_matches_serde_json() {
+    let value = frame();
+    let bytes = postcard::to_allocvec(&value).unwrap();
+
+    // Compared as text, so fields are in declaration order too
+    assert_eq!(
+        protocol().to_json("Frame", &bytes).unwrap().to_string(),
+        serde_json::to_string(&value).unwrap()
+    );
+}
+
+#[test]
+fn f32_uses_shortest_representation() {
+    let bytes = postcard::to_allocvec(&Reading {
+        sensor: 1,
+        celsius: 20.3,
+    })
+    .unwrap();
+
+    assert_eq!(
+        protocol().to_json("Reading", &bytes).unwrap(),
+        json!({ "sensor": 1, "celsius": 20.3 })
+    );
+}
+
+#[test]
+fn wide_integers_are_strings_beyond_the_safe_range() {
+    let counters = |total: u64, offset: i64, wide: u128| Counters {
+        total,
+        offset,
+        wide,
+        by_name: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+        by_id: BTreeMap::from([(10, true)]),
+        by_cell: BTreeMap::from([((1, 2), 3)]),
+    };
+    let protocol = protocol();
+    let decode = |value: &Counters| {
+        protocol
+            .to_json("Counters", &postcard::to_allocvec(value).unwrap())
+            .unwrap()
+    };
+
+    let safe = decode(&counters((1 << 53) - 1, -((1 << 53) - 1), 5));
+    assert_eq!(safe["total"], json!(9007199254740991u64));
+    assert_eq!(safe["offset"], json!(-9007199254740991i64));
+    assert_eq!(safe["wide"], json!(5));
+
+    let unsafe_ = decode(&counters(1 << 53, i64::MIN, u128::MAX));
+    assert_eq!(unsafe_["total"], json!("9007199254740992"));
+    assert_eq!(unsafe_["offset"], json!("-9223372036854775808"));
+    assert_eq!(unsafe_["wide"], json!(u128::MAX.to_string()));
+}
+
+#[test]
+fn wide_integers_are_written_as_chosen_globally_or_per_field() {
+    let bytes = postcard::to_allocvec(&Log {
+        events: vec![
+            Event::Boot {
+                at: Timestamp(1000),
+                uptime: 5,
+            },
+            Event::Tick(u64::MAX),
+        ],
+        totals: Some(vec![7]),
+        by_id: BTreeMap::from([(9, -1)]),
+    })
+    .unwrap();
+
+    let value = protocol()
+        .bigints(BigIntJson::String)
+        .to_json("Log", &bytes)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "events": [
+                { "Boot": { "at": "1000", "uptime": "5" } },
+                { "Tick": "18446744073709551615" },
+            ],
+            "totals": ["7"],
+            "by_id": { "9": "-1" },
+        })
+    );
+
+    // An override covers the options and sequences of its field, no other fields
+    let value = protocol()
+        .bigints(BigIntJson::Tagged)
+        .field_bigints("Event.Boot.uptime", BigIntJson::NumberIfSafe)
+        .field_bigints("Log.totals", BigIntJson::String)
+        .to_json("Log", &bytes)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "events": [
+                { "Boot": { "at": { "$bigint": "1000" }, "uptime": 5 } },
+                { "Tick": { "$bigint": "18446744073709551615" } },
+            ],
+            "totals": ["7"],
+            "by_id": { "9": { "$bigint": "-1" } },
+        })
+    );
+
+    let value = protocol()
+        .field_bigints("Event.Tick", BigIntJson::Tagged)
+        .to_json("Log", &bytes)
+        .unwrap();
+    assert_eq!(
+        value["events"][0],
+        json!({ "Boot": { "at": 1000, "uptime": 5 } })
+    );
+    assert_eq!(
+        value["events"][1],
+        json!({ "Tick": { "$bigint": "18446744073709551615" } })
+    );
+}
+
+#[test]
+fn maps_are_objects_or_pairs_by_key() {
+    let bytes = postcard::to_allocvec(&Counters {
+        total: 0,
+        offset: 0,
+        wide: 0,
+        by_name: BTreeMap::from([("a".to_string(), 1)]),
+        by_id: BTreeMap::from([(10, true), (2, false)]),
+        by_cell: BTreeMap::from([((1, 2), 3), ((0, 9), 4)]),
+    })
+    .unwrap();
+    let value = protocol().to_json("Counters", &bytes).unwrap();
+
+    assert_eq!(value["by_name"], json!({ "a": 1 }));
+    assert_eq!(value["by_id"], json!({ "2": false, "10": true }));
+    assert_eq!(value["by_cell"], json!([[[0, 9], 4], [[1, 2], 3]]));
+}
+
+#[test]
+fn take_json_returns_the_bytes_after_the_value() {
+    let mut bytes = postcard::to_allocvec(&Command::Pair(1, false)).unwrap();
+    bytes.extend([0xaa, 0xbb]);
+
+    let (value, rest) = protocol().take_json("Command", &bytes).unwrap();
+    assert_eq!(value, json!({ "Pair": [1, false] }));
+    assert_eq!(rest, [0xaa, 0xbb]);
+}
+
+#[test]
+fn errors_carry_path_and_offset() {
+    let protocol = protocol();
+    let bytes = postcard::to_allocvec(&frame()).unwrap();
+    // seq 300 is two bytes, then sensor, celsius, the command count and Stop
+    let set_target = 2 + 1 + 4 + 1 + 1;
+
+    let mut bad = bytes.clone();
+    bad[set_target] = 9;
+    let error = protocol.to_json("Frame", &bad).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+    assert_eq!(error.path, "Frame.commands[1]");
+    assert_eq!(error.offset, set_target);
+
+    let error = protocol
+        .to_json("Frame", &bytes[..set_target + 3])
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::UnexpectedEnd);
+    assert_eq!(error.path, "Frame.commands[1].SetTarget");
+    assert_eq!(error.offset, set_target + 1);
+    assert_eq!(
+        error.to_string(),
+        "Frame.commands[1].SetTarget at byte 10: 4 more bytes needed, 2 left (UNEXPECTED_END)"
+    );
+
+    let error = protocol.to_json("Reading", &[2, 0, 0]).unwrap_err();
+    assert_eq!(error.path, "Reading.celsius");
+    assert_eq!(error.offset, 1);
+}
+
+#[test]
+fn malformed_primitives_have_postcards_codes() {
+    let protocol = Protocol::from_ir(
+        r#"{
+            "version": 1,
+            "types": [{
+                "name": "Flags",
+                "kind": "tuple_struct",
+                "items": [
+                    { "kind": "bool" },
+                    { "kind": "option", "inner": { "kind": "u8" } },
+                    { "kind": "string" },
+                    { "kind": "u16" }
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let code = |bytes: &[u8]| protocol.to_json("Flags", bytes).unwrap_err().code;
+
+    assert_eq!(code(&[2]), ErrorCode::BadBool);
+    assert_eq!(code(&[1, 2]), ErrorCode::BadOption);
+    assert_eq!(code(&[1, 0, 1, 0xff]), ErrorCode::BadUtf8);
+    assert_eq!(code(&[1, 0, 0, 0xff, 0xff, 0x04]), ErrorCode::BadVarint);
+    assert_eq!(
+        protocol
+            .to_json("Flags", &[1, 0, 0, 0xff, 0xff, 0x03])
+            .unwrap(),
+        json!([true, null, "", 65535])
+    );
+}
+
+#[test]
+fn containers_over_their_capacity_are_rejected() {
+    let protocol = Protocol::from_ir(
+        r#"{
+            "version": 1,
+            "types": [{
+                "name": "Queue",
+                "kind": "tuple_struct",
+                "items": [
+                    { "kind": "seq", "item": { "kind": "u8" }, "maxLength": 2 },
+                    {
+                        "kind": "map",
+                        "key": { "kind": "u8" },
+                        "value": { "kind": "bool" },
+                        "maxLength": 1
+                    }
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        protocol.to_json("Queue", &[2, 7, 8, 1, 3, 1]).unwrap(),
+        json!([[7, 8], { "3": true }])
+    );
+    let error = protocol.to_json("Queue", &[3, 7, 8, 9, 0]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+    assert_eq!(error.message, "Seq of 3 items exceeds maxLength (2)");
+    let error = protocol.to_json("Queue", &[0, 2, 3, 1, 4, 0]).unwrap_err();
+    assert_eq!(error.message, "Map of 2 entries exceeds maxLength (1)");
+    assert_eq!(error.offset, 1);
+}
+
+#[test]
+fn values_nested_past_the_limit_are_rejected() {
+    // Each tree but the last holds one child: value 1, one child
+    let trees = |count: usize| {
+        let mut bytes = [1, 1].repeat(count - 1);
+        bytes.extend([1, 0]);
+        bytes
+    };
+    let protocol = protocol();
+
+    // A tree and its children list are a level each
+    assert!(protocol.to_json("Tree", &trees(64)).is_ok());
+    let error = protocol.to_json("Tree", &trees(65)).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+    assert_eq!(error.message, "Values nested deeper than the limit (128)");
+    assert!(protocol
+        .clone()
+        .max_depth(130)
+        .to_json("Tree", &trees(65))
+        .is_ok());
+
+    // Fails instead of overflowing the stack
+    let error = protocol.to_json("Tree", &[1; 200_000]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+}
+
+#[test]
+fn unknown_and_generic_types_are_rejected() {
+    let protocol = protocol();
+
+    let error = protocol.to_json("Missing", &[]).unwrap_err();
+    assert_eq!(error.message, "Type Missing is not in the IR");
+    let error = protocol.to_json("Envelope", &[]).unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidValue);
+}
+
+#[test]
+fn invalid_ir_is_rejected() {
+    let error = Protocol::from_ir(r#"{ "version": 2, "types": [] }"#).unwrap_err();
+    assert_eq!(error.message, "Unsupported IR version 2");
+
+    let error = Protocol::from_ir(
+        r#"{
+            "version": 1,
+            "types": [{
+                "name": "Holder",
+                "kind": "newtype_struct",
+                "inner": { "kind": "ref", "name": "Missing" }
+            }]
+        }"#,
+    )
+    .unwrap_err();
+    assert_eq!(error.message, "Holder.0: unknown type Missing");
+}
@@ -0,0 +1,113 @@
+// The derived types only exist to be hashed
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use postcard_ts_error_codes::ErrorCode;
+use postcard_ts_gateway::{decode_schema_hashes, Protocol, SchemaHashMismatch};
+use serde_postcard_ts::{registered, schema_hash, schema_hashes, Generator, PostcardTs};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+enum Command {
+    Stop,
+    SetTarget(f32),
+    Batch(Vec<Command>),
+}
+
+#[derive(PostcardTs)]
+struct Envelope<T> {
+    seq: u32,
+    body: T,
+}
+
+#[derive(PostcardTs)]
+struct Node<T> {
+    value: T,
+    next: Option<Box<Node<T>>>,
+}
+
+#[derive(PostcardTs)]
+struct Frame {
+    reading: Envelope<Reading>,
+    command: Envelope<Command>,
+    chain: Node<(u8, String)>,
+    labels: BTreeMap<String, Option<i64>>,
+    pair: (char, ()),
+}
+
+fn protocol() -> Protocol {
+    Protocol::from_ir(&Generator::new().generate_ir().unwrap()).unwrap()
+}
+
+#[test]
+fn hashes_match_the_generator() {
+    let protocol = protocol();
+    let hashes = schema_hashes(&registered()).unwrap();
+
+    assert_eq!(hashes.len(), 3);
+    for (type_name, hash) in hashes {
+        assert_eq!(
+            protocol.schema_hash(type_name.name),
+            Some(hash),
+            "{}",
+            type_name.name
+        );
+    }
+    assert_eq!(
+        protocol.schema_hash("Frame"),
+        Some(schema_hash::<Frame>().unwrap())
+    );
+    assert_eq!(protocol.schema_hash("Envelope"), None);
+    assert_eq!(protocol.schema_hash("Missing"), None);
+}
+
+#[test]
+fn handshake_is_checked_against_the_ir() {
+    let protocol = protocol();
+    let reading = protocol.schema_hash("Reading").unwrap();
+    let command = protocol.schema_hash("Command").unwrap();
+    let handshake = |hashes: &[(&str, u64)]| {
+        let hashes: BTreeMap<&str, u64> = hashes.iter().copied().collect();
+        decode_schema_hashes(&postcard::to_allocvec(&hashes).unwrap()).unwrap()
+    };
+
+    let peer = handshake(&[("Reading", reading), ("Command", command), ("Extra", 1)]);
+    assert_eq!(peer["Reading"], reading);
+    assert_eq!(
+        protocol.check_schema_hashes(&["Reading", "Command"], &peer),
+        Ok(())
+    );
+
+    let peer = handshake(&[("Reading", reading ^ 1)]);
+    let error = protocol
+        .check_schema_hashes(&["Reading", "Command"], &peer)
+        .unwrap_err();
+    assert_eq!(
+        error.mismatches,
+        [
+            SchemaHashMismatch {
+                type_name: "Reading".to_string(),
+                expected: reading,
+                received: Some(reading ^ 1),
+            },
+            SchemaHashMismatch {
+                type_name: "Command".to_string(),
+                expected: command,
+                received: None,
+            },
+        ]
+    );
+    assert!(error.to_string().ends_with(", Command (not sent)"));
+}
+
+#[test]
+fn malformed_handshakes_are_rejected() {
+    let error = decode_schema_hashes(&[1, 1, b'A']).unwrap_err();
+    assert_eq!(error.code, ErrorCode::UnexpectedEnd);
+}
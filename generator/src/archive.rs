@@ -0,0 +1,235 @@
+//! Self-describing archives of postcard records
+//!
+//! Postcard writes no names or tags, so stored records are only readable with
+//! the types they were written with. An archive carries its own description:
+//! a header with the JSON IR of the protocol (see
+//! [`Generator::generate_ir`](crate::Generator::generate_ir)), the name of the
+//! records' type and its schema hash, then the records, each prefixed with its
+//! length. The TypeScript runtime's `readArchive` decodes one with nothing but
+//! the file, years after the firmware that wrote it is gone.
+//!
+//! The header is itself a postcard value, so it reads with the matching
+//! struct:
+//!
+//! ```text
+//! struct ArchiveHeader { magic: [u8; 4], version: u32, type_name: String, schema_hash: u64, ir: String }
+//! ```
+//!
+//! Records follow until the end of the file, so an archive can be appended to
+//! as records come in.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+
+use crate::capture_stats::Reader;
+use crate::generate::{GenerateError, Generator};
+use crate::schema_hash::schema_hash;
+use crate::shape::{PostcardTs, Shape};
+
+/// The first bytes of every archive
+pub const ARCHIVE_MAGIC: [u8; 4] = *b"PCTA";
+
+/// Archive format version written and read here
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// The file does not start with [`ARCHIVE_MAGIC`]
+    NotAnArchive,
+    /// The header or a record is malformed or cut off
+    Malformed,
+    UnsupportedVersion(u32),
+    /// Only registered types without type parameters are named in the IR
+    UnnamedType,
+    Generate(GenerateError),
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::NotAnArchive => write!(f, "not an archive"),
+            ArchiveError::Malformed => write!(f, "malformed or cut off archive"),
+            ArchiveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported archive version {}", version)
+            }
+            ArchiveError::UnnamedType => write!(
+                f,
+                "archived records must be of a type deriving PostcardTs without type parameters"
+            ),
+            ArchiveError::Generate(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ArchiveError {}
+
+impl From<GenerateError> for ArchiveError {
+    fn from(error: GenerateError) -> Self {
+        ArchiveError::Generate(error)
+    }
+}
+
+/// What an archive says about its records
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveHeader {
+    /// The records' type, as named in the IR
+    pub type_name: String,
+    /// [`schema_hash`](crate::schema_hash) of the records' type
+    pub schema_hash: u64,
+    /// The JSON IR of the protocol
+    pub ir: String,
+}
+
+impl ArchiveHeader {
+    /// The header of an archive of `T`s, with the IR of every registered type
+    ///
+    /// ```
+    /// use serde_postcard_ts::{ArchiveHeader, PostcardTs};
+    ///
+    /// #[derive(PostcardTs)]
+    /// struct Reading {
+    ///     sensor: u8,
+    ///     celsius: f32,
+    /// }
+    ///
+    /// let header = ArchiveHeader::of::<Reading>()?;
+    /// assert_eq!(header.type_name, "Reading");
+    /// assert!(header.ir.contains("\"name\": \"Reading\""));
+    /// # Ok::<(), serde_postcard_ts::ArchiveError>(())
+    /// ```
+    pub fn of<T: PostcardTs>() -> Result<Self, ArchiveError> {
+        Self::with_generator::<T>(&Generator::new())
+    }
+
+    /// The header of an archive of `T`s, with the IR `generator` describes
+    /// them with, e.g. with overrides
+    pub fn with_generator<T: PostcardTs>(generator: &Generator) -> Result<Self, ArchiveError> {
+        let type_name = match T::shape() {
+            Shape::Named(type_name) if type_name.params.is_empty() => type_name.name,
+            _ => return Err(ArchiveError::UnnamedType),
+        };
+        Ok(ArchiveHeader {
+            type_name: type_name.to_string(),
+            schema_hash: schema_hash::<T>()?,
+            ir: generator.generate_ir()?,
+        })
+    }
+
+    /// The header as written at the start of an archive
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = ARCHIVE_MAGIC.to_vec();
+        push_varint(&mut bytes, u64::from(ARCHIVE_VERSION));
+        push_varint(&mut bytes, self.type_name.len() as u64);
+        bytes.extend_from_slice(self.type_name.as_bytes());
+        push_varint(&mut bytes, self.schema_hash);
+        push_varint(&mut bytes, self.ir.len() as u64);
+        bytes.extend_from_slice(self.ir.as_bytes());
+        bytes
+    }
+}
+
+/// Writes an archive record by record
+///
+/// ```
+/// use serde_postcard_ts::{read_archive, ArchiveHeader, ArchiveWriter, PostcardTs};
+///
+/// #[derive(PostcardTs)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// let mut writer = ArchiveWriter::new(Vec::new(), &ArchiveHeader::of::<Reading>()?)?;
+/// // Each record is a Reading as postcard::to_allocvec writes it
+/// writer.write_record(&[0x02, 0x00, 0x00, 0xb4, 0x41])?;
+/// let archive = read_archive(&writer.into_inner())?;
+/// assert_eq!(archive.records, [vec![0x02, 0x00, 0x00, 0xb4, 0x41]]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct ArchiveWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Start an archive by writing `header` to `out`
+    pub fn new(mut out: W, header: &ArchiveHeader) -> io::Result<Self> {
+        out.write_all(&header.to_bytes())?;
+        Ok(ArchiveWriter { out })
+    }
+
+    /// Continue an archive that `out` appends to, its header already written
+    pub fn append(out: W) -> Self {
+        ArchiveWriter { out }
+    }
+
+    /// Write one record, a value of the header's type as postcard encodes it
+    pub fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        let mut prefix = Vec::with_capacity(5);
+        push_varint(&mut prefix, record.len() as u64);
+        self.out.write_all(&prefix)?;
+        self.out.write_all(record)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// An archive read back
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Archive {
+    pub header: ArchiveHeader,
+    /// The records, each as postcard encoded it
+    pub records: Vec<Vec<u8>>,
+}
+
+/// Read the header and records of an archive
+pub fn read_archive(data: &[u8]) -> Result<Archive, ArchiveError> {
+    if !data.starts_with(&ARCHIVE_MAGIC) {
+        return Err(ArchiveError::NotAnArchive);
+    }
+    let mut reader = Reader {
+        data,
+        pos: ARCHIVE_MAGIC.len(),
+    };
+    let malformed = |_| ArchiveError::Malformed;
+    let version = reader.varint(32).map_err(malformed)? as u32;
+    if version != ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(version));
+    }
+    let string = |reader: &mut Reader| {
+        let len = reader.varint(64).map_err(malformed)? as usize;
+        let bytes = reader.take(len).map_err(malformed)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ArchiveError::Malformed)
+    };
+    let type_name = string(&mut reader)?;
+    let schema_hash = reader.varint(64).map_err(malformed)? as u64;
+    let ir = string(&mut reader)?;
+    let mut records = Vec::new();
+    while reader.pos < data.len() {
+        let len = reader.varint(32).map_err(malformed)? as usize;
+        records.push(reader.take(len).map_err(malformed)?.to_vec());
+    }
+    Ok(Archive {
+        header: ArchiveHeader {
+            type_name,
+            schema_hash,
+            ir,
+        },
+        records,
+    })
+}
+
+/// An unsigned LEB128 varint, as postcard writes integers
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
@@ -0,0 +1,2638 @@
+//! `postcard-ts generate`: writes the schemas of a crate's `PostcardTs` types
+//!
+//! Registered types are only visible from inside a binary linking the crate,
+//! so this builds a small helper binary depending on the target crate (and on
+//! the same `serde-postcard-ts` it uses) under the crate's target directory,
+//! and runs it to generate and write the files. With `--lsp-json`, compiler
+//! errors, generator errors and the wire layout are printed as JSON lines
+//! instead (see `serde_postcard_ts::lsp`); with `--message-format json`,
+//! every warning and error is printed as a versioned JSON record (see
+//! `serde_postcard_ts::diagnostics`).
+//!
+//! `postcard-ts check`: compares the schema hashes pinned with
+//! `#[postcard_ts(schema_hash = ...)]`, and the protocol group hashes pinned
+//! in `--config`, with the crate's wire shapes, through the same kind of
+//! helper binary, and fails if any changed.
+//!
+//! `postcard-ts stats`: summarises capture files decoded as one of the crate's
+//! types, through the same kind of helper binary.
+//!
+//! `postcard-ts docs`: generates as `generate --typedoc` does, then runs
+//! TypeDoc on the output.
+//!
+//! `postcard-ts schema snapshot`: writes the crate's types as a JSON IR
+//! snapshot through the same kind of helper binary. `postcard-ts schema diff`
+//! compares a snapshot with another, or with the crate's types through the
+//! helper, and fails if a change breaks the wire format.
+//!
+//! `postcard-ts migrate`: compares a file of hand-written TypeScript types
+//! with the crate's generated ones through the same kind of helper binary,
+//! and writes shims converting between them (see
+//! `serde_postcard_ts::TsMigration`).
+//!
+//! `postcard-ts verify`: generates the crate's schemas through the same kind
+//! of helper binary, then decodes a payload with them under Node (through
+//! `npx tsx`), encodes it again and prints where the bytes differ (see
+//! `serde_postcard_ts::RoundTrip`).
+//!
+//! `postcard-ts registry serve`: serves a directory of snapshots over HTTP by
+//! the schema hashes of their types (see `serde_postcard_ts::SchemaRegistry`).
+//!
+//! `postcard-ts scaffold`: writes a runnable example project.
+
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use serde_json::{json, Value};
+use serde_postcard_ts::{
+    diff_snapshots, write_files, ConstantValue, CrcAlgorithm, Diagnostic, Example, Generator,
+    Ident, Layout, ProtocolConstant, ProtocolFeature, RoundTrip, SchemaRegistry, Severity, Span,
+    TracedValue, WriteOptions,
+};
+
+const USAGE: &str = "\
+Usage: postcard-ts generate --crate <path> --out <dir> [options]
+       postcard-ts docs --crate <path> --out <dir> [options]
+       postcard-ts check --crate <path> [options]
+       postcard-ts stats --crate <path> --type <name> [options] <capture>...
+       postcard-ts schema snapshot --crate <path> --out <file> [options]
+       postcard-ts schema diff [--crate <path>] [options] <old> [<new>]
+       postcard-ts migrate --crate <path> --types <file> [options]
+       postcard-ts verify --crate <path> --type <name> [options] <payload>
+       postcard-ts registry serve --dir <dir> [--listen <address>]
+       postcard-ts scaffold --example <name> [--out <dir>] [--overwrite]
+
+generate: builds the crate at <path> and writes a TypeScript schema module
+for every type in it (and its dependencies) deriving PostcardTs.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --out <dir>            Output directory (required)
+  --layout <layout>      single: everything in index.ts (default)
+                         modules: one file per Rust module, plus index.ts
+                         groups: one file per protocol group in --config,
+                         common.ts for the other types, plus index.ts
+                         types: one file per type in types/, plus index.ts
+  --config <file>        JSON file declaring protocol groups, simulated
+                         devices, encode- or decode-only types and protocol
+                         constants (see below)
+  --import-from <module> Module to import the schema builders from
+                         (default: @variegated-coffee/serde-postcard-ts)
+  --enum-tag <name>      Emit enums as flat unions tagged by <name>,
+                         e.g. { kind: \"Move\", x: 1 }
+  --crc <algorithm>      Also emit wrappers appending and checking a CRC,
+                         as postcard's CRC flavor; a crc crate catalog name
+                         such as CRC_32_ISCSI
+  --features <features>  Features to enable on the crate, comma separated
+  --clean                Remove generated files that are no longer produced
+  --overwrite            Replace files in the way that were not generated
+  --lsp-json             Print diagnostics and the wire layout of every type
+                         as JSON lines, for editor extensions
+  --message-format <fmt> human: warnings and errors as text (default)
+                         json: every warning and error as a JSON record
+                         with its code, severity, span and suggested fix,
+                         one per line, for build systems and editor plugins
+  --schema-hashes        Also emit the schema hash of every type
+  --max-sizes            Also emit the maximum encoded size of every type
+                         that has one, as <TYPE>_MAX_SIZE
+  --variant-peeks        Also emit is<Enum><Variant>(frame) per enum variant,
+                         reading only the variant index of a frame
+  --salvage              Also emit salvage<Struct>(data) per struct holding a
+                         list of records, keeping the records decoded before
+                         a corrupt one
+  --decoders             Also emit decode<Type>(data), tryDecode<Type>(data)
+                         returning a Result instead of throwing, and
+                         create<Type>StreamDecoder() per decoded type
+  --truncate-strings     Truncate strings over their max_len or heapless
+                         capacity when encoding, instead of failing
+  --u8-vecs-as-bytes     Emit every Vec<u8> as bytes(), a Uint8Array
+  --validators <library> Also emit a runtime validator per type, <Name>Zod
+                         for zod (the only library so far)
+  --time <repr>          date: Duration as milliseconds, chrono DateTime<Utc>
+                         and time OffsetDateTime as Date (default)
+                         nanos: all of them as bigint nanoseconds
+  --uuid <repr>          string: uuid Uuid as its hyphenated text (default)
+                         bytes: as its 16 bytes
+  --branded-newtypes     Emit newtype structs as branded types, so that ids
+                         of different types cannot be mixed up
+  --dispatchers          Also write dispatchers.ts, routing the messages of
+                         a length-prefixed or COBS stream to handlers by
+                         variant, for every top-level enum
+  --introspection        Also export __schema from every module, describing
+                         its types for tools inspecting them at runtime
+  --display-names        Also emit <Enum>DisplayNames per enum, the name to
+                         show for each variant: the one given with
+                         #[postcard_ts(display = \"...\")], or else the
+                         variant's name as words
+  --schema-loaders       Also write loaders.ts, importing each type's module
+                         on first use, so that bundlers split the protocol
+                         into a chunk per module or group
+  --typedoc              Also document every export for TypeDoc, under a
+                         category per protocol group or Rust module, and
+                         write typedoc.json
+  --examples <dir>       With --typedoc, show each <dir>/<type>.bin, named
+                         in snake_case (device_settings.bin), decoded in
+                         the comment of its type
+  --rust-constants <file>
+                         Also write the protocol constants and features in
+                         --config as a Rust module, for the firmware to
+                         include
+  -h, --help             Print this help
+
+docs: generates as generate --typedoc does, then runs TypeDoc (npx typedoc)
+in <dir>, writing a browsable reference of the protocol to <dir>/docs. Takes
+the options of generate.
+
+check: builds the crate at <path> and fails if the wire shape of a type no
+longer matches the hash pinned with #[postcard_ts(schema_hash = ...)], which
+fails to compile, or a protocol group no longer matches the hash pinned for
+its version, printing the new hash to pin once the change is intended.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --config <file>        JSON file declaring protocol groups (see below)
+  --features <features>  Features to enable on the crate, comma separated
+
+stats: builds the crate at <path> and summarises captures recorded by the
+flight recorder, decoding every frame as <name>: frames and bytes per type
+and enum variant, the bytes of each field and nested variant within them,
+frame sizes, arrival intervals and the busiest values of a field.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --type <name>          Type of the captured messages, Name or module::Name
+                         (required)
+  --talker <field>       Rank the values of this field by bytes sent, as a
+                         dotted path such as sensor or header.device
+  --top <n>              Talkers to list (default: 10)
+  --json                 Print JSON instead of a table
+  --features <features>  Features to enable on the crate, comma separated
+
+schema snapshot: builds the crate at <path> and writes the wire shapes of
+its types to <file>, as the versioned JSON IR, to commit with each release.
+
+schema diff: compares the snapshot <old> with <new>, or with the crate at
+<path> when no <new> is given, printing every change as compatible (old and
+new programs still read each other's messages) or breaking, and fails if
+any change is breaking.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --out <file>           Snapshot to write (snapshot only, required)
+  --features <features>  Features to enable on the crate, comma separated
+
+migrate: builds the crate at <path> and compares the interfaces and object
+types exported by the hand-written TypeScript <file> with the generated
+types of the same names, printing each difference: fields named otherwise,
+optional properties for | null, numbers for bigints and the other way
+round, and differences that take more than converting the value.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --types <file>         Hand-written TypeScript types (required)
+  --out <file>           Also write fromX() and toX() shims converting
+                         between the generated and the hand-written type X,
+                         for every type whose differences are all adapted
+  --generated <module>   Module the shims import the generated types from,
+                         e.g. ./generated/index.js (required with --out)
+  --config <file>        JSON file naming generated identifiers (see below)
+  --u8-vecs-as-bytes     Compare every Vec<u8> as a Uint8Array, as generate
+                         --u8-vecs-as-bytes emits it
+  --features <features>  Features to enable on the crate, comma separated
+
+verify: builds the crate at <path>, generates its schemas and, under Node,
+decodes the postcard <payload> as <name>, encodes the value again and
+compares the bytes, printing each value that re-encodes differently with
+its bytes on both sides, and any bytes after the value. Fails if the bytes
+differ. Runs npx tsx in the current directory, so the runtime resolves from
+its node_modules.
+
+Options:
+  --crate <path>         Crate directory or Cargo.toml (default: .)
+  --type <name>          Type of the payload, as named in TypeScript
+                         (required)
+  --import-from <module> Module to import the schema builders from
+                         (default: @variegated-coffee/serde-postcard-ts)
+  --features <features>  Features to enable on the crate, comma separated
+
+registry serve: serves the schema snapshots in <dir> over HTTP, so a
+dashboard can fetch the one a device was built from by the schema hash it
+reports. POST /ir uploads a snapshot, kept in <dir>; GET /ir lists the
+snapshots and the hashes of their types; GET /ir/<type>/<hash> returns the
+snapshot in which <type> has <hash>, 16 hex digits.
+
+Options:
+  --dir <dir>            Directory of the snapshots (required)
+  --listen <address>     Address to listen on (default: 127.0.0.1:7410)
+
+scaffold: writes a runnable example project using this checkout of
+serde-postcard-ts.
+
+Options:
+  --example <name>       websocket-dashboard: Rust WebSocket server and
+                         Vite frontend decoding its messages
+  --out <dir>            Project directory (default: the example's name)
+  --overwrite            Replace existing files
+
+Protocol groups gather types by what they are for, whatever their Rust
+module, each with a version and, once released, the hash of that version:
+
+  {
+    \"groups\": [
+      { \"name\": \"telemetry\", \"version\": 3,
+        \"types\": [\"app::telemetry::*\", \"Heartbeat\"],
+        \"hash\": \"0x6f2a93c10d4e5b78\" }
+    ]
+  }
+
+Types are given by name, path (app::telemetry::Reading) or module
+(app::telemetry::*, with its submodules).
+
+Simulators declare fake devices for UI tests, each generated as a
+<name>-simulator.ts with a handler per variant of the request enum:
+
+  {
+    \"simulators\": [
+      { \"name\": \"Thermostat\", \"request\": \"Command\",
+        \"response\": \"Reply\", \"telemetry\": \"Reading\" }
+    ]
+  }
+
+The telemetry type defaults to the response type.
+
+Types only ever sent or only ever received can be limited to that direction,
+as #[postcard_ts(encode_only)] and #[postcard_ts(decode_only)] do:
+
+  {
+    \"encode_only\": [\"app::control::*\"],
+    \"decode_only\": [\"app::telemetry::*\"]
+  }
+
+Lints (optional_seq, bigint_id, string_map, deep_nesting) warn by default;
+each can be allowed or denied, denied ones failing generation, and
+deep_nesting given the depth it allows (default 8):
+
+  {
+    \"lints\": { \"bigint_id\": \"allow\", \"string_map\": \"deny\", \"max_depth\": 12 }
+  }
+
+Protocol constants are emitted as TypeScript constants, and with
+--rust-constants as Rust ones, so the firmware and the frontend share them.
+Integers need their Rust type; strings are &str and arrays of bytes [u8; N]:
+
+  {
+    \"constants\": [
+      { \"name\": \"MAX_FRAME_LENGTH\", \"type\": \"usize\", \"value\": 512,
+        \"doc\": \"Longest frame the device accepts\" },
+      { \"name\": \"FRAME_MAGIC\", \"value\": [202, 254] },
+      { \"name\": \"TELEMETRY_TOPIC\", \"value\": \"devices/telemetry\" }
+    ]
+  }
+
+Features a device may have are emitted with them, as PROTOCOL_FEATURES and
+as Rust constants of their bits in the capability mask the device sends
+when it connects. Each lists the types or enum variants needing it, which
+the runtime's FeatureSet refuses to encode for a device without it:
+
+  {
+    \"features\": [
+      { \"name\": \"firmware_update\", \"bit\": 0,
+        \"messages\": [\"Command::Update\", \"FirmwareChunk\"],
+        \"doc\": \"Accepts firmware over the air\" }
+    ]
+  }
+
+Generated names follow templates, with {name} the Rust type, {NAME} the
+same in SCREAMING_SNAKE_CASE and {variant} an enum variant. Each kind of
+name (type, schema, schema_hash, max_size, key, variant_peek, serialize_crc,
+deserialize_crc, salvage, decode, try_decode, stream_decoder, zod,
+dispatcher, display_names) can be given one:
+
+  {
+    \"naming\": { \"type\": \"I{name}\", \"schema\": \"{name}Codec\" }
+  }
+";
+
+enum Task {
+    Generate(Options),
+    Docs(Options),
+    Check(CheckOptions),
+    Stats(StatsOptions),
+    Snapshot(SnapshotOptions),
+    Diff(DiffOptions),
+    Migrate(MigrateOptions),
+    Verify(VerifyOptions),
+    Registry(RegistryOptions),
+    Scaffold(ScaffoldOptions),
+}
+
+struct Options {
+    manifest: PathBuf,
+    out: PathBuf,
+    layout: Layout,
+    config: Option<PathBuf>,
+    import_from: Option<String>,
+    enum_tag: Option<String>,
+    crc: Option<&'static str>,
+    features: Option<String>,
+    clean: bool,
+    overwrite: bool,
+    message_format: MessageFormat,
+    schema_hashes: bool,
+    max_sizes: bool,
+    variant_peeks: bool,
+    salvage: bool,
+    decoders: bool,
+    truncate_strings: bool,
+    u8_vecs_as_bytes: bool,
+    validators: Option<&'static str>,
+    time_representation: Option<&'static str>,
+    uuid_representation: Option<&'static str>,
+    branded_newtypes: bool,
+    dispatchers: bool,
+    introspection: bool,
+    display_names: bool,
+    schema_loaders: bool,
+    typedoc: bool,
+    /// Directory of example frames, `<type>.bin`
+    examples: Option<PathBuf>,
+    /// File to write the protocol constants to as a Rust module
+    rust_constants: Option<PathBuf>,
+}
+
+/// How `generate` prints warnings and errors
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MessageFormat {
+    /// As text on stderr, and progress on stdout
+    Human,
+    /// `--message-format json`: as `serde_postcard_ts::diagnostics` records
+    Json,
+    /// `--lsp-json`: as `serde_postcard_ts::lsp` messages, with the wire layout
+    Lsp,
+}
+
+struct CheckOptions {
+    manifest: PathBuf,
+    config: Option<PathBuf>,
+    features: Option<String>,
+}
+
+/// What `--config` declares
+#[derive(Default)]
+struct Config {
+    groups: Vec<GroupConfig>,
+    simulators: Vec<SimulatorConfig>,
+    /// Patterns of the types only encoded, and of those only decoded
+    encode_only: Vec<String>,
+    decode_only: Vec<String>,
+    lints: LintsConfig,
+    constants: Vec<ConstantConfig>,
+    features: Vec<FeatureConfig>,
+    /// The naming template of each kind of identifier given one
+    naming: Vec<(Ident, String)>,
+}
+
+/// The lint levels declared in `--config`
+#[derive(Default)]
+struct LintsConfig {
+    /// The `LintLevel` variant of each lint given one
+    levels: Vec<(String, &'static str)>,
+    max_depth: Option<u64>,
+}
+
+/// A simulated device declared in `--config`
+struct SimulatorConfig {
+    name: String,
+    request: String,
+    response: String,
+    telemetry: Option<String>,
+}
+
+/// A protocol constant declared in `--config`
+struct ConstantConfig {
+    name: String,
+    value: ConstantValue,
+    doc: Option<String>,
+}
+
+impl ConstantConfig {
+    fn constant(&self) -> ProtocolConstant {
+        let constant = ProtocolConstant::new(self.name.clone(), self.value.clone());
+        match &self.doc {
+            Some(doc) => constant.doc(doc.clone()),
+            None => constant,
+        }
+    }
+}
+
+/// A protocol feature declared in `--config`
+struct FeatureConfig {
+    name: String,
+    bit: u32,
+    messages: Vec<String>,
+    doc: Option<String>,
+}
+
+impl FeatureConfig {
+    fn feature(&self) -> ProtocolFeature {
+        let feature =
+            ProtocolFeature::new(self.name.clone(), self.bit).messages(self.messages.clone());
+        match &self.doc {
+            Some(doc) => feature.doc(doc.clone()),
+            None => feature,
+        }
+    }
+}
+
+/// A protocol group declared in `--config`
+struct GroupConfig {
+    name: String,
+    version: u32,
+    types: Vec<String>,
+    hash: Option<u64>,
+}
+
+struct StatsOptions {
+    manifest: PathBuf,
+    type_name: String,
+    talker: Option<String>,
+    top: usize,
+    json: bool,
+    features: Option<String>,
+    captures: Vec<PathBuf>,
+}
+
+struct SnapshotOptions {
+    manifest: PathBuf,
+    out: PathBuf,
+    features: Option<String>,
+}
+
+struct DiffOptions {
+    manifest: PathBuf,
+    features: Option<String>,
+    old: PathBuf,
+    /// The crate's types when `None`
+    new: Option<PathBuf>,
+}
+
+struct MigrateOptions {
+    manifest: PathBuf,
+    types: PathBuf,
+    /// Where to write the shims, and the module they import the generated
+    /// types from
+    shims: Option<(PathBuf, String)>,
+    config: Option<PathBuf>,
+    u8_vecs_as_bytes: bool,
+    features: Option<String>,
+}
+
+struct VerifyOptions {
+    manifest: PathBuf,
+    type_name: String,
+    import_from: Option<String>,
+    features: Option<String>,
+    payload: PathBuf,
+}
+
+struct RegistryOptions {
+    dir: PathBuf,
+    listen: String,
+}
+
+struct ScaffoldOptions {
+    example: Example,
+    out: PathBuf,
+    overwrite: bool,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let task = match parse_args(&args) {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    let result = match &task {
+        Task::Generate(options) => generate(options),
+        Task::Docs(options) => docs(options),
+        Task::Check(options) => check(options),
+        Task::Stats(options) => stats(options),
+        Task::Snapshot(options) => snapshot(options),
+        Task::Diff(options) => diff(options),
+        Task::Migrate(options) => migrate(options),
+        Task::Verify(options) => verify(options),
+        Task::Registry(options) => registry(options),
+        Task::Scaffold(options) => scaffold(options),
+    };
+    if let Err(message) = result {
+        let format = match &task {
+            Task::Generate(options) | Task::Docs(options) => options.message_format,
+            _ => MessageFormat::Human,
+        };
+        match format {
+            MessageFormat::Human => eprintln!("error: {}", message),
+            MessageFormat::Json => println!("{}", cli_record(&message).to_json()),
+            MessageFormat::Lsp => println!("{}", cli_diagnostic(&message)),
+        }
+        process::exit(1);
+    }
+}
+
+/// `Ok(None)` for `--help`
+fn parse_args(args: &[String]) -> Result<Option<Task>, String> {
+    match args.first().map(String::as_str) {
+        Some("generate") => Ok(parse_generate(&args[1..])?.map(Task::Generate)),
+        Some("docs") => Ok(parse_generate(&args[1..])?.map(|options| {
+            Task::Docs(Options {
+                typedoc: true,
+                ..options
+            })
+        })),
+        Some("check") => Ok(parse_check(&args[1..])?.map(Task::Check)),
+        Some("stats") => Ok(parse_stats(&args[1..])?.map(Task::Stats)),
+        Some("schema") => parse_schema(&args[1..]),
+        Some("migrate") => Ok(parse_migrate(&args[1..])?.map(Task::Migrate)),
+        Some("verify") => Ok(parse_verify(&args[1..])?.map(Task::Verify)),
+        Some("registry") => Ok(parse_registry(&args[1..])?.map(Task::Registry)),
+        Some("scaffold") => Ok(parse_scaffold(&args[1..])?.map(Task::Scaffold)),
+        Some("-h" | "--help") | None => Ok(None),
+        Some(other) => Err(format!("unknown command {}", other)),
+    }
+}
+
+fn parse_generate(args: &[String]) -> Result<Option<Options>, String> {
+    let mut args = args.iter();
+
+    let mut crate_path = PathBuf::from(".");
+    let mut out = None;
+    let mut layout = Layout::Single;
+    let mut config = None;
+    let mut import_from = None;
+    let mut enum_tag = None;
+    let mut crc = None;
+    let mut features = None;
+    let mut clean = false;
+    let mut overwrite = false;
+    let mut lsp_json = false;
+    let mut json = false;
+    let mut schema_hashes = false;
+    let mut max_sizes = false;
+    let mut variant_peeks = false;
+    let mut salvage = false;
+    let mut decoders = false;
+    let mut truncate_strings = false;
+    let mut u8_vecs_as_bytes = false;
+    let mut validators = None;
+    let mut time_representation = None;
+    let mut uuid_representation = None;
+    let mut branded_newtypes = false;
+    let mut dispatchers = false;
+    let mut introspection = false;
+    let mut display_names = false;
+    let mut schema_loaders = false;
+    let mut typedoc = false;
+    let mut examples = None;
+    let mut rust_constants = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--layout" => {
+                layout = match value()?.as_str() {
+                    "single" => Layout::Single,
+                    "modules" => Layout::Modules,
+                    "groups" => Layout::Groups,
+                    "types" => Layout::Types,
+                    other => return Err(format!("unknown layout {}", other)),
+                }
+            }
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--import-from" => import_from = Some(value()?),
+            "--enum-tag" => enum_tag = Some(value()?),
+            "--crc" => {
+                let name = value()?;
+                crc = Some(
+                    CrcAlgorithm::named(&name)
+                        .and_then(|algorithm| algorithm.name())
+                        .ok_or_else(|| format!("unknown CRC algorithm {}", name))?,
+                );
+            }
+            "--features" => features = Some(value()?),
+            "--clean" => clean = true,
+            "--overwrite" => overwrite = true,
+            "--lsp-json" => lsp_json = true,
+            "--message-format" => {
+                json = match value()?.as_str() {
+                    "human" => false,
+                    "json" => true,
+                    other => return Err(format!("unknown message format {}", other)),
+                }
+            }
+            "--schema-hashes" => schema_hashes = true,
+            "--max-sizes" => max_sizes = true,
+            "--variant-peeks" => variant_peeks = true,
+            "--salvage" => salvage = true,
+            "--decoders" => decoders = true,
+            "--truncate-strings" => truncate_strings = true,
+            "--u8-vecs-as-bytes" => u8_vecs_as_bytes = true,
+            "--validators" => {
+                validators = match value()?.as_str() {
+                    "zod" => Some("Zod"),
+                    other => return Err(format!("unknown validator library {}", other)),
+                }
+            }
+            "--time" => {
+                time_representation = match value()?.as_str() {
+                    "date" => Some("Date"),
+                    "nanos" => Some("Nanos"),
+                    other => return Err(format!("unknown time representation {}", other)),
+                }
+            }
+            "--uuid" => {
+                uuid_representation = match value()?.as_str() {
+                    "string" => Some("String"),
+                    "bytes" => Some("Bytes"),
+                    other => return Err(format!("unknown uuid representation {}", other)),
+                }
+            }
+            "--branded-newtypes" => branded_newtypes = true,
+            "--dispatchers" => dispatchers = true,
+            "--introspection" => introspection = true,
+            "--display-names" => display_names = true,
+            "--schema-loaders" => schema_loaders = true,
+            "--typedoc" => typedoc = true,
+            "--examples" => examples = Some(PathBuf::from(value()?)),
+            "--rust-constants" => rust_constants = Some(PathBuf::from(value()?)),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    let message_format = match (lsp_json, json) {
+        (true, true) => return Err("--lsp-json and --message-format json conflict".to_string()),
+        (true, false) => MessageFormat::Lsp,
+        (false, true) => MessageFormat::Json,
+        (false, false) => MessageFormat::Human,
+    };
+
+    Ok(Some(Options {
+        manifest: manifest(crate_path),
+        out: out.ok_or("--out is required")?,
+        layout,
+        config,
+        import_from,
+        enum_tag,
+        crc,
+        features,
+        clean,
+        overwrite,
+        message_format,
+        schema_hashes,
+        max_sizes,
+        variant_peeks,
+        salvage,
+        decoders,
+        truncate_strings,
+        u8_vecs_as_bytes,
+        validators,
+        time_representation,
+        uuid_representation,
+        branded_newtypes,
+        dispatchers,
+        introspection,
+        display_names,
+        schema_loaders,
+        typedoc,
+        examples,
+        rust_constants,
+    }))
+}
+
+fn parse_check(args: &[String]) -> Result<Option<CheckOptions>, String> {
+    let mut args = args.iter();
+    let mut crate_path = PathBuf::from(".");
+    let mut config = None;
+    let mut features = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--features" => features = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+    Ok(Some(CheckOptions {
+        manifest: manifest(crate_path),
+        config,
+        features,
+    }))
+}
+
+fn parse_stats(args: &[String]) -> Result<Option<StatsOptions>, String> {
+    let mut args = args.iter();
+    let mut crate_path = PathBuf::from(".");
+    let mut type_name = None;
+    let mut talker = None;
+    let mut top = 10;
+    let mut json = false;
+    let mut features = None;
+    let mut captures = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--type" => type_name = Some(value()?),
+            "--talker" => talker = Some(value()?),
+            "--top" => {
+                let count = value()?;
+                top = count
+                    .parse()
+                    .map_err(|_| format!("--top needs a count, not {}", count))?;
+            }
+            "--json" => json = true,
+            "--features" => features = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other if other.starts_with("--") => return Err(format!("unknown option {}", other)),
+            capture => captures.push(PathBuf::from(capture)),
+        }
+    }
+
+    if captures.is_empty() {
+        return Err("no capture files given".to_string());
+    }
+    Ok(Some(StatsOptions {
+        manifest: manifest(crate_path),
+        type_name: type_name.ok_or("--type is required")?,
+        talker,
+        top,
+        json,
+        features,
+        captures,
+    }))
+}
+
+fn parse_schema(args: &[String]) -> Result<Option<Task>, String> {
+    let task = args.first().map(String::as_str);
+    if matches!(task, None | Some("-h" | "--help")) {
+        return Ok(None);
+    }
+    let mut rest = args[1..].iter();
+    let mut crate_path = PathBuf::from(".");
+    let mut out = None;
+    let mut features = None;
+    let mut snapshots = Vec::new();
+    while let Some(arg) = rest.next() {
+        let mut value = || {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--out" if task == Some("snapshot") => out = Some(PathBuf::from(value()?)),
+            "--features" => features = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other if other.starts_with("--") => return Err(format!("unknown option {}", other)),
+            snapshot => snapshots.push(PathBuf::from(snapshot)),
+        }
+    }
+
+    let manifest = manifest(crate_path);
+    match task {
+        Some("snapshot") => {
+            if !snapshots.is_empty() {
+                return Err(format!("unexpected argument {}", snapshots[0].display()));
+            }
+            Ok(Some(Task::Snapshot(SnapshotOptions {
+                manifest,
+                out: out.ok_or("--out is required")?,
+                features,
+            })))
+        }
+        Some("diff") => {
+            let mut snapshots = snapshots.into_iter();
+            let old = snapshots.next().ok_or("no snapshot given to compare")?;
+            let new = snapshots.next();
+            if let Some(extra) = snapshots.next() {
+                return Err(format!("unexpected argument {}", extra.display()));
+            }
+            Ok(Some(Task::Diff(DiffOptions {
+                manifest,
+                features,
+                old,
+                new,
+            })))
+        }
+        Some(other) => Err(format!("unknown schema task {}", other)),
+        None => unreachable!(),
+    }
+}
+
+fn parse_migrate(args: &[String]) -> Result<Option<MigrateOptions>, String> {
+    let mut args = args.iter();
+    let mut crate_path = PathBuf::from(".");
+    let mut types = None;
+    let mut out = None;
+    let mut generated = None;
+    let mut config = None;
+    let mut u8_vecs_as_bytes = false;
+    let mut features = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--types" => types = Some(PathBuf::from(value()?)),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--generated" => generated = Some(value()?),
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--u8-vecs-as-bytes" => u8_vecs_as_bytes = true,
+            "--features" => features = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    let shims = match (out, generated) {
+        (Some(out), Some(generated)) => Some((out, generated)),
+        (Some(_), None) => return Err("--out needs --generated".to_string()),
+        (None, Some(_)) => return Err("--generated needs --out".to_string()),
+        (None, None) => None,
+    };
+    Ok(Some(MigrateOptions {
+        manifest: manifest(crate_path),
+        types: types.ok_or("--types is required")?,
+        shims,
+        config,
+        u8_vecs_as_bytes,
+        features,
+    }))
+}
+
+fn parse_verify(args: &[String]) -> Result<Option<VerifyOptions>, String> {
+    let mut args = args.iter();
+    let mut crate_path = PathBuf::from(".");
+    let mut type_name = None;
+    let mut import_from = None;
+    let mut features = None;
+    let mut payloads = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--crate" => crate_path = PathBuf::from(value()?),
+            "--type" => type_name = Some(value()?),
+            "--import-from" => import_from = Some(value()?),
+            "--features" => features = Some(value()?),
+            "-h" | "--help" => return Ok(None),
+            other if other.starts_with("--") => return Err(format!("unknown option {}", other)),
+            payload => payloads.push(PathBuf::from(payload)),
+        }
+    }
+
+    let payload = match payloads.len() {
+        0 => return Err("no payload given".to_string()),
+        1 => payloads.remove(0),
+        _ => return Err("verify takes one payload".to_string()),
+    };
+    Ok(Some(VerifyOptions {
+        manifest: manifest(crate_path),
+        type_name: type_name.ok_or("--type is required")?,
+        import_from,
+        features,
+        payload,
+    }))
+}
+
+fn parse_registry(args: &[String]) -> Result<Option<RegistryOptions>, String> {
+    match args.first().map(String::as_str) {
+        None | Some("-h" | "--help") => return Ok(None),
+        Some("serve") => {}
+        Some(other) => return Err(format!("unknown registry task {}", other)),
+    }
+    let mut args = args[1..].iter();
+    let mut dir = None;
+    let mut listen = "127.0.0.1:7410".to_string();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--dir" => dir = Some(PathBuf::from(value()?)),
+            "--listen" => listen = value()?,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+    Ok(Some(RegistryOptions {
+        dir: dir.ok_or("--dir is required")?,
+        listen,
+    }))
+}
+
+/// The manifest of the crate at `--crate`
+fn manifest(crate_path: PathBuf) -> PathBuf {
+    if crate_path.ends_with("Cargo.toml") {
+        crate_path
+    } else {
+        crate_path.join("Cargo.toml")
+    }
+}
+
+fn parse_scaffold(args: &[String]) -> Result<Option<ScaffoldOptions>, String> {
+    let mut args = args.iter();
+    let mut example = None;
+    let mut out = None;
+    let mut overwrite = false;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--example" => {
+                let name = value()?;
+                example = Some(
+                    Example::from_name(&name).ok_or_else(|| format!("unknown example {}", name))?,
+                )
+            }
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--overwrite" => overwrite = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown option {}", other)),
+        }
+    }
+
+    let example = example.ok_or("--example is required")?;
+    Ok(Some(ScaffoldOptions {
+        example,
+        out: out.unwrap_or_else(|| PathBuf::from(example.name())),
+        overwrite,
+    }))
+}
+
+fn migrate(options: &MigrateOptions) -> Result<(), String> {
+    let config = read_config(options.config.as_deref())?;
+    let helper = Helper::new(&options.manifest)?;
+    let cwd = env::current_dir().map_err(|e| e.to_string())?;
+    let types = cwd.join(&options.types);
+    let shims = options.shims.as_ref().map(|(out, generated)| {
+        let out = cwd.join(out);
+        let hand_written = import_specifier(out.parent().unwrap_or(&cwd), &types);
+        (out, generated.clone(), hand_written)
+    });
+    if let Some(dir) = shims.as_ref().and_then(|(out, _, _)| out.parent()) {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    let mut generator = naming_code(&config.naming);
+    if options.u8_vecs_as_bytes {
+        generator.push_str(".u8_vecs_as_bytes()");
+    }
+    helper.write(
+        options.features.as_deref(),
+        &migrate_main(&types, shims.as_ref(), &generator),
+    )?;
+    if !helper.run()? {
+        return Err(format!("comparing {} failed", options.types.display()));
+    }
+    Ok(())
+}
+
+/// How a module in `from` imports the TypeScript file `to`: relative, and
+/// with the `.js` extension TypeScript resolves to `.ts`
+fn import_specifier(from: &Path, to: &Path) -> String {
+    let normalize = |path: &Path| {
+        let mut components = Vec::new();
+        for component in path.components() {
+            match component {
+                std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => {
+                    components.pop();
+                }
+                component => components.push(component.as_os_str().to_os_string()),
+            }
+        }
+        components
+    };
+    let (from, to) = (normalize(from), normalize(&to.with_extension("js")));
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    if parts.is_empty() {
+        parts.push(".".to_string());
+    }
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|part| part.to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+fn verify(options: &VerifyOptions) -> Result<(), String> {
+    let cwd = env::current_dir().map_err(|e| e.to_string())?;
+    let payload = cwd.join(&options.payload);
+    let original = fs::read(&payload).map_err(|e| format!("{}: {}", payload.display(), e))?;
+    // Under node_modules, so that the script resolves the runtime from it
+    let dir = cwd
+        .join("node_modules")
+        .join(".cache")
+        .join("postcard-ts-verify");
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let helper = Helper::new(&options.manifest)?;
+    let mut generator = String::new();
+    if let Some(module) = &options.import_from {
+        generator.push_str(&format!(".import_from({:?})", module));
+    }
+    helper.write(
+        options.features.as_deref(),
+        &verify_main(&dir.join("schemas.ts"), &generator),
+    )?;
+    if !helper.run()? {
+        return Err(format!("generating from {} failed", helper.target.name));
+    }
+    let script = dir.join("verify.ts");
+    let runtime = options
+        .import_from
+        .as_deref()
+        .unwrap_or("@variegated-coffee/serde-postcard-ts");
+    fs::write(&script, verify_script(runtime, &options.type_name))
+        .map_err(|e| format!("{}: {}", script.display(), e))?;
+
+    let output = Command::new(if cfg!(windows) { "npx.cmd" } else { "npx" })
+        .arg("tsx")
+        .arg(&script)
+        .arg(&payload)
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| format!("running npx tsx: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("running {} failed", script.display()));
+    }
+    let result: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("reading the output of {}: {}", script.display(), e))?;
+    if let Some(error) = result["error"].as_str() {
+        return Err(error.to_string());
+    }
+
+    let round_trip = RoundTrip {
+        type_name: options.type_name.clone(),
+        original,
+        bytes_read: result["bytesRead"].as_u64().unwrap_or_default() as usize,
+        values: traced_values(&result["values"]).unwrap_or_default(),
+        reencoded: result["reencoded"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|byte| byte.as_u64().map(|byte| byte as u8))
+            .collect(),
+        reencoded_values: traced_values(&result["reencodedValues"]),
+    };
+    print!("{}", round_trip.report());
+    if !round_trip.is_identical() {
+        eprintln!(
+            "error: {} does not re-encode to the same bytes",
+            options.payload.display()
+        );
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// The runtime's trace events in `events`, `None` unless it is an array
+fn traced_values(events: &Value) -> Option<Vec<TracedValue>> {
+    let events = events.as_array()?;
+    Some(
+        events
+            .iter()
+            .map(|event| TracedValue {
+                path: event["path"].as_str().unwrap_or_default().to_string(),
+                kind: event["kind"].as_str().unwrap_or_default().to_string(),
+                offset: event["offset"].as_u64().unwrap_or_default() as usize,
+                len: event["bytesRead"].as_u64().unwrap_or_default() as usize,
+            })
+            .collect(),
+    )
+}
+
+/// The script `postcard-ts verify` runs under Node: decodes the payload file
+/// given as its argument as `type_name`, encodes it again and decodes that,
+/// tracing both, and prints the result as JSON
+fn verify_script(runtime: &str, type_name: &str) -> String {
+    format!(
+        "// Written by postcard-ts verify\n\
+         import {{ readFileSync }} from \"node:fs\";\n\
+         import {{\n\
+         \x20 tryDeserialize,\n\
+         \x20 trySerialize,\n\
+         \x20 type DecodeTraceEvent,\n\
+         \x20 type ModuleSchema,\n\
+         \x20 type Schema,\n\
+         }} from {runtime:?};\n\
+         import {{ __schema }} from \"./schemas.js\";\n\
+         \n\
+         const TYPE = {type_name:?};\n\
+         \n\
+         function done(result: object): never {{\n\
+         \x20 console.log(JSON.stringify(result));\n\
+         \x20 process.exit(0);\n\
+         }}\n\
+         \n\
+         function decode(data: Uint8Array) {{\n\
+         \x20 const values: DecodeTraceEvent[] = [];\n\
+         \x20 const result = tryDeserialize(schema, data, 0, {{\n\
+         \x20   root: TYPE,\n\
+         \x20   trace: (event) => values.push(event),\n\
+         \x20 }});\n\
+         \x20 return result.ok ? {{ ...result.value, values }} : result;\n\
+         }}\n\
+         \n\
+         const types: ModuleSchema = __schema;\n\
+         const type = types[TYPE];\n\
+         if (type === undefined) {{\n\
+         \x20 done({{ error: `no generated type ${{TYPE}}` }});\n\
+         }}\n\
+         if (typeof type.schema === \"function\") {{\n\
+         \x20 done({{ error: `${{TYPE}} is generic; verify a type using it instead` }});\n\
+         }}\n\
+         const schema: Schema = type.schema;\n\
+         \n\
+         const original = decode(new Uint8Array(readFileSync(process.argv[2]!)));\n\
+         if (!(\"values\" in original)) {{\n\
+         \x20 done({{ error: original.error.message }});\n\
+         }}\n\
+         const reencoded = trySerialize(schema, original.value);\n\
+         if (!reencoded.ok) {{\n\
+         \x20 done({{ error: `${{TYPE}} decodes but cannot be encoded again: ${{reencoded.error.message}}` }});\n\
+         }}\n\
+         const again = decode(reencoded.value);\n\
+         done({{\n\
+         \x20 bytesRead: original.bytesRead,\n\
+         \x20 values: original.values,\n\
+         \x20 reencoded: Array.from(reencoded.value),\n\
+         \x20 reencodedValues: \"values\" in again ? again.values : null,\n\
+         }});\n"
+    )
+}
+
+fn registry(options: &RegistryOptions) -> Result<(), String> {
+    let mut registry = SchemaRegistry::open(&options.dir).map_err(|e| e.to_string())?;
+    let listener = TcpListener::bind(&options.listen)
+        .map_err(|e| format!("listening on {}: {}", options.listen, e))?;
+    let count = registry.entries().len();
+    println!(
+        "serving {} snapshot{} from {} on http://{}",
+        count,
+        if count == 1 { "" } else { "s" },
+        options.dir.display(),
+        options.listen
+    );
+    registry.serve(&listener).map_err(|e| e.to_string())
+}
+
+fn scaffold(options: &ScaffoldOptions) -> Result<(), String> {
+    let files = options.example.files();
+    let write_options = WriteOptions {
+        clean: false,
+        overwrite: options.overwrite,
+    };
+    let report = write_files(&options.out, &files, write_options).map_err(|e| e.to_string())?;
+    for path in &report.written {
+        println!("  wrote {}", path.display());
+    }
+    println!(
+        "\n{} is ready; see {} to run it",
+        options.example.name(),
+        options.out.join("README.md").display()
+    );
+    Ok(())
+}
+
+/// The protocol groups, simulators and directions in the `--config` file, if
+/// one was given
+fn read_config(config: Option<&Path>) -> Result<Config, String> {
+    let Some(path) = config else {
+        return Ok(Config::default());
+    };
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let config: Value =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let invalid = |message: String| format!("{}: {}", path.display(), message);
+    Ok(Config {
+        groups: read_groups(&config, &invalid)?,
+        simulators: read_simulators(&config, &invalid)?,
+        encode_only: read_patterns(&config, "encode_only", &invalid)?,
+        decode_only: read_patterns(&config, "decode_only", &invalid)?,
+        lints: read_lints(&config, &invalid)?,
+        constants: read_constants(&config, &invalid)?,
+        features: read_features(&config, &invalid)?,
+        naming: read_naming(&config, &invalid)?,
+    })
+}
+
+fn read_naming(
+    config: &Value,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<(Ident, String)>, String> {
+    let Some(naming) = config.get("naming") else {
+        return Ok(Vec::new());
+    };
+    let naming = naming
+        .as_object()
+        .ok_or_else(|| invalid("naming must be an object".to_string()))?;
+    naming
+        .iter()
+        .map(|(key, template)| {
+            let ident =
+                Ident::named(key).ok_or_else(|| invalid(format!("unknown name {:?}", key)))?;
+            let template = template
+                .as_str()
+                .ok_or_else(|| invalid(format!("the {} name template must be a string", key)))?;
+            Ok((ident, template.to_string()))
+        })
+        .collect()
+}
+
+fn read_constants(
+    config: &Value,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<ConstantConfig>, String> {
+    let Some(constants) = config.get("constants") else {
+        return Ok(Vec::new());
+    };
+    let constants = constants
+        .as_array()
+        .ok_or_else(|| invalid("constants must be an array".to_string()))?;
+    constants
+        .iter()
+        .map(|constant| {
+            let object = constant
+                .as_object()
+                .ok_or_else(|| invalid("each constant must be an object".to_string()))?;
+            if let Some(key) = object
+                .keys()
+                .find(|key| !["name", "type", "value", "doc"].contains(&key.as_str()))
+            {
+                return Err(invalid(format!("unknown constant key {:?}", key)));
+            }
+            let name = constant["name"]
+                .as_str()
+                .ok_or_else(|| invalid("a constant has no name".to_string()))?
+                .to_string();
+            let value = constant_value(&constant["type"], &constant["value"])
+                .map_err(|reason| invalid(format!("constant {} {}", name, reason)))?;
+            let doc = match &constant["doc"] {
+                Value::Null => None,
+                doc => Some(
+                    doc.as_str()
+                        .ok_or_else(|| {
+                            invalid(format!("the doc of constant {} must be a string", name))
+                        })?
+                        .to_string(),
+                ),
+            };
+            Ok(ConstantConfig { name, value, doc })
+        })
+        .collect()
+}
+
+fn read_features(
+    config: &Value,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<FeatureConfig>, String> {
+    let Some(features) = config.get("features") else {
+        return Ok(Vec::new());
+    };
+    let features = features
+        .as_array()
+        .ok_or_else(|| invalid("features must be an array".to_string()))?;
+    features
+        .iter()
+        .map(|feature| {
+            let object = feature
+                .as_object()
+                .ok_or_else(|| invalid("each feature must be an object".to_string()))?;
+            if let Some(key) = object
+                .keys()
+                .find(|key| !["name", "bit", "messages", "doc"].contains(&key.as_str()))
+            {
+                return Err(invalid(format!("unknown feature key {:?}", key)));
+            }
+            let name = feature["name"]
+                .as_str()
+                .ok_or_else(|| invalid("a feature has no name".to_string()))?
+                .to_string();
+            let bit = feature["bit"]
+                .as_u64()
+                .and_then(|bit| u32::try_from(bit).ok())
+                .ok_or_else(|| invalid(format!("feature {} needs a bit", name)))?;
+            let messages = match &feature["messages"] {
+                Value::Null => Vec::new(),
+                Value::Array(messages) => messages
+                    .iter()
+                    .map(|message| message.as_str().map(str::to_string))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| {
+                        invalid(format!("the messages of feature {} must be strings", name))
+                    })?,
+                _ => {
+                    return Err(invalid(format!(
+                        "the messages of feature {} must be an array",
+                        name
+                    )))
+                }
+            };
+            let doc = match &feature["doc"] {
+                Value::Null => None,
+                doc => Some(
+                    doc.as_str()
+                        .ok_or_else(|| {
+                            invalid(format!("the doc of feature {} must be a string", name))
+                        })?
+                        .to_string(),
+                ),
+            };
+            Ok(FeatureConfig {
+                name,
+                bit,
+                messages,
+                doc,
+            })
+        })
+        .collect()
+}
+
+/// The value of a constant from its `type` and `value` keys, or why it has
+/// none
+fn constant_value(rust_type: &Value, value: &Value) -> Result<ConstantValue, String> {
+    fn integer<T: TryFrom<u64> + TryFrom<i64>>(value: &Value) -> Option<T> {
+        match value.as_u64() {
+            Some(unsigned) => T::try_from(unsigned).ok(),
+            None => T::try_from(value.as_i64()?).ok(),
+        }
+    }
+    let value = match (rust_type.as_str(), value) {
+        (None, Value::String(text)) => ConstantValue::Str(text.clone()),
+        (None, Value::Array(items)) => ConstantValue::Bytes(
+            items
+                .iter()
+                .map(|item| item.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<_>>()
+                .ok_or("must list bytes, numbers from 0 to 255")?,
+        ),
+        (None, Value::Number(_)) => return Err("needs the Rust type of its value".to_string()),
+        (None, _) => return Err("needs a number, string or array of bytes".to_string()),
+        (Some(rust_type), value) => {
+            let parsed = match rust_type {
+                "u8" => integer(value).map(ConstantValue::U8),
+                "u16" => integer(value).map(ConstantValue::U16),
+                "u32" => integer(value).map(ConstantValue::U32),
+                "u64" => integer(value).map(ConstantValue::U64),
+                "usize" => integer(value).map(ConstantValue::Usize),
+                "i8" => integer(value).map(ConstantValue::I8),
+                "i16" => integer(value).map(ConstantValue::I16),
+                "i32" => integer(value).map(ConstantValue::I32),
+                "i64" => integer(value).map(ConstantValue::I64),
+                other => return Err(format!("has unknown type {:?}", other)),
+            };
+            parsed.ok_or_else(|| format!("needs an integer value that fits in {}", rust_type))?
+        }
+    };
+    Ok(value)
+}
+
+fn read_lints(config: &Value, invalid: &dyn Fn(String) -> String) -> Result<LintsConfig, String> {
+    let Some(lints) = config.get("lints") else {
+        return Ok(LintsConfig::default());
+    };
+    let lints = lints
+        .as_object()
+        .ok_or_else(|| invalid("lints must be an object".to_string()))?;
+    let mut config = LintsConfig::default();
+    for (key, value) in lints {
+        if key == "max_depth" {
+            config.max_depth = Some(
+                value
+                    .as_u64()
+                    .ok_or_else(|| invalid("max_depth must be a number".to_string()))?,
+            );
+            continue;
+        }
+        if !serde_postcard_ts::LINTS.contains(&key.as_str()) {
+            return Err(invalid(format!("unknown lint {:?}", key)));
+        }
+        let level = match value.as_str() {
+            Some("allow") => "Allow",
+            Some("warn") => "Warn",
+            Some("deny") => "Deny",
+            _ => {
+                return Err(invalid(format!(
+                    "lint {} must be \"allow\", \"warn\" or \"deny\"",
+                    key
+                )))
+            }
+        };
+        config.levels.push((key.clone(), level));
+    }
+    Ok(config)
+}
+
+/// The type patterns listed under `key`
+fn read_patterns(
+    config: &Value,
+    key: &str,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<String>, String> {
+    match config.get(key) {
+        None => Ok(Vec::new()),
+        Some(patterns) => patterns
+            .as_array()
+            .and_then(|types| types.iter().map(|t| t.as_str().map(String::from)).collect())
+            .ok_or_else(|| invalid(format!("{} must be a list of types", key))),
+    }
+}
+
+fn read_groups(
+    config: &Value,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<GroupConfig>, String> {
+    let Some(groups) = config.get("groups") else {
+        return Ok(Vec::new());
+    };
+    let groups = groups
+        .as_array()
+        .ok_or_else(|| invalid("groups must be an array".to_string()))?;
+    groups
+        .iter()
+        .map(|group| {
+            let object = group
+                .as_object()
+                .ok_or_else(|| invalid("each group must be an object".to_string()))?;
+            if let Some(key) = object
+                .keys()
+                .find(|key| !["name", "version", "types", "hash"].contains(&key.as_str()))
+            {
+                return Err(invalid(format!("unknown group key {:?}", key)));
+            }
+            let name = group["name"]
+                .as_str()
+                .ok_or_else(|| invalid("a group has no name".to_string()))?
+                .to_string();
+            let version = group["version"]
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| invalid(format!("group {} needs a version number", name)))?;
+            let types = group["types"]
+                .as_array()
+                .and_then(|types| types.iter().map(|t| t.as_str().map(String::from)).collect())
+                .ok_or_else(|| invalid(format!("group {} needs a list of types", name)))?;
+            let hash = match &group["hash"] {
+                Value::Null => None,
+                hash => Some(
+                    hash.as_str()
+                        .and_then(|hash| hash.strip_prefix("0x"))
+                        .and_then(|hex| u64::from_str_radix(&hex.replace('_', ""), 16).ok())
+                        .ok_or_else(|| {
+                            invalid(format!(
+                                "the hash of group {} must be 0x and 16 hex digits",
+                                name
+                            ))
+                        })?,
+                ),
+            };
+            Ok(GroupConfig {
+                name,
+                version,
+                types,
+                hash,
+            })
+        })
+        .collect()
+}
+
+fn read_simulators(
+    config: &Value,
+    invalid: &dyn Fn(String) -> String,
+) -> Result<Vec<SimulatorConfig>, String> {
+    let Some(simulators) = config.get("simulators") else {
+        return Ok(Vec::new());
+    };
+    let simulators = simulators
+        .as_array()
+        .ok_or_else(|| invalid("simulators must be an array".to_string()))?;
+    simulators
+        .iter()
+        .map(|simulator| {
+            let object = simulator
+                .as_object()
+                .ok_or_else(|| invalid("each simulator must be an object".to_string()))?;
+            if let Some(key) = object
+                .keys()
+                .find(|key| !["name", "request", "response", "telemetry"].contains(&key.as_str()))
+            {
+                return Err(invalid(format!("unknown simulator key {:?}", key)));
+            }
+            let name = simulator["name"]
+                .as_str()
+                .ok_or_else(|| invalid("a simulator has no name".to_string()))?
+                .to_string();
+            let type_of = |key: &str| {
+                simulator[key]
+                    .as_str()
+                    .map(String::from)
+                    .ok_or_else(|| invalid(format!("simulator {} needs a {} type", name, key)))
+            };
+            Ok(SimulatorConfig {
+                request: type_of("request")?,
+                response: type_of("response")?,
+                telemetry: match &simulator["telemetry"] {
+                    Value::Null => None,
+                    _ => Some(type_of("telemetry")?),
+                },
+                name,
+            })
+        })
+        .collect()
+}
+
+/// The builder calls adding `simulators` to a `Generator`
+fn simulators_code(simulators: &[SimulatorConfig]) -> String {
+    simulators
+        .iter()
+        .map(|simulator| {
+            let mut code = format!(
+                ".simulator(serde_postcard_ts::Simulator::new({:?}, {:?}, {:?})",
+                simulator.name, simulator.request, simulator.response
+            );
+            if let Some(telemetry) = &simulator.telemetry {
+                code.push_str(&format!(".telemetry({:?})", telemetry));
+            }
+            code.push(')');
+            code
+        })
+        .collect()
+}
+
+/// The builder calls adding `constants` to a `Generator`
+fn constants_code(constants: &[ConstantConfig]) -> String {
+    constants
+        .iter()
+        .map(|constant| {
+            let value = match &constant.value {
+                ConstantValue::U8(v) => format!("{}u8", v),
+                ConstantValue::U16(v) => format!("{}u16", v),
+                ConstantValue::U32(v) => format!("{}u32", v),
+                ConstantValue::U64(v) => format!("{}u64", v),
+                ConstantValue::Usize(v) => format!("{}usize", v),
+                ConstantValue::I8(v) => format!("{}i8", v),
+                ConstantValue::I16(v) => format!("{}i16", v),
+                ConstantValue::I32(v) => format!("{}i32", v),
+                ConstantValue::I64(v) => format!("{}i64", v),
+                ConstantValue::Str(text) => format!("{:?}", text),
+                ConstantValue::Bytes(bytes) => format!("vec!{:?}", bytes),
+            };
+            let mut code = format!(
+                ".constant(serde_postcard_ts::ProtocolConstant::new({:?}, {})",
+                constant.name, value
+            );
+            if let Some(doc) = &constant.doc {
+                code.push_str(&format!(".doc({:?})", doc));
+            }
+            code.push(')');
+            code
+        })
+        .collect()
+}
+
+/// The builder calls adding `features` to a `Generator`
+fn features_code(features: &[FeatureConfig]) -> String {
+    features
+        .iter()
+        .map(|feature| {
+            let mut code = format!(
+                ".feature(serde_postcard_ts::ProtocolFeature::new({:?}, {})",
+                feature.name, feature.bit
+            );
+            if !feature.messages.is_empty() {
+                code.push_str(&format!(".messages({:?})", feature.messages));
+            }
+            if let Some(doc) = &feature.doc {
+                code.push_str(&format!(".doc({:?})", doc));
+            }
+            code.push(')');
+            code
+        })
+        .collect()
+}
+
+/// The builder call naming identifiers with the templates of `naming`, if
+/// any
+fn naming_code(naming: &[(Ident, String)]) -> String {
+    if naming.is_empty() {
+        return String::new();
+    }
+    let templates: String = naming
+        .iter()
+        .map(|(ident, template)| {
+            format!(
+                ".template(serde_postcard_ts::Ident::{:?}, {:?})",
+                ident, template
+            )
+        })
+        .collect();
+    format!(".naming(serde_postcard_ts::Naming::new(){})", templates)
+}
+
+/// The builder calls adding `groups` to a `Generator`
+fn groups_code(groups: &[GroupConfig]) -> String {
+    groups
+        .iter()
+        .map(|group| {
+            let mut code = format!(
+                ".group(serde_postcard_ts::ProtocolGroup::new({:?}, {}).types({:?})",
+                group.name, group.version, group.types
+            );
+            if let Some(hash) = group.hash {
+                code.push_str(&format!(".pin_hash({:#018x})", hash));
+            }
+            code.push(')');
+            code
+        })
+        .collect()
+}
+
+fn generate(options: &Options) -> Result<(), String> {
+    let config = read_config(options.config.as_deref())?;
+    if options.layout == Layout::Groups && config.groups.is_empty() {
+        return Err("--layout groups needs protocol groups declared with --config".to_string());
+    }
+    if options.rust_constants.is_some() && config.constants.is_empty() && config.features.is_empty()
+    {
+        return Err(
+            "--rust-constants needs protocol constants or features declared with --config"
+                .to_string(),
+        );
+    }
+    let helper = Helper::new(&options.manifest)?;
+    let cwd = env::current_dir().map_err(|e| e.to_string())?;
+    let out = cwd.join(&options.out);
+    let examples = match &options.examples {
+        Some(dir) => read_examples(&cwd.join(dir))?,
+        None => Vec::new(),
+    };
+    helper.write(
+        options.features.as_deref(),
+        &generate_main(options, &config, &out, &examples),
+    )?;
+
+    if options.message_format != MessageFormat::Human {
+        build_helper(&helper, options.message_format)?;
+    }
+    if !helper.run()? {
+        if options.message_format != MessageFormat::Human {
+            // The helper printed the diagnostic
+            process::exit(1);
+        }
+        return Err(format!("generating from {} failed", helper.target.name));
+    }
+    if let Some(path) = &options.rust_constants {
+        write_rust_constants(&cwd.join(path), &config, options.message_format)?;
+    }
+    Ok(())
+}
+
+/// Write the protocol constants and features of `config` as a Rust module to `path`,
+/// unless it already holds them
+fn write_rust_constants(
+    path: &Path,
+    config: &Config,
+    message_format: MessageFormat,
+) -> Result<(), String> {
+    let generator = config
+        .constants
+        .iter()
+        .fold(Generator::new(), |generator, constant| {
+            generator.constant(constant.constant())
+        });
+    let generator = config
+        .features
+        .iter()
+        .fold(generator, |generator, feature| {
+            generator.feature(feature.feature())
+        });
+    let module = generator
+        .generate_rust_constants()
+        .map_err(|e| e.to_string())?;
+    if fs::read_to_string(path).is_ok_and(|existing| existing == module) {
+        return Ok(());
+    }
+    fs::write(path, module).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if message_format == MessageFormat::Human {
+        println!("  wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn docs(options: &Options) -> Result<(), String> {
+    generate(options)?;
+    let mut typedoc = Command::new(if cfg!(windows) { "npx.cmd" } else { "npx" });
+    typedoc
+        .args(["typedoc", "--options", "typedoc.json"])
+        .current_dir(&options.out);
+    if options.message_format != MessageFormat::Human {
+        // Only records go to stdout
+        typedoc.stdout(std::io::stderr());
+    }
+    let status = typedoc
+        .status()
+        .map_err(|e| format!("running npx typedoc: {}", e))?;
+    if !status.success() {
+        return Err("TypeDoc failed".to_string());
+    }
+    if options.message_format == MessageFormat::Human {
+        println!("  wrote {}", options.out.join("docs").display());
+    }
+    Ok(())
+}
+
+/// The type name and path of each example frame in `dir`, `device_settings.bin`
+/// standing for `DeviceSettings`
+fn read_examples(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut examples = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("{}: {}", dir.display(), e))?
+            .path();
+        if path.extension().is_none_or(|extension| extension != "bin") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let name: String = stem
+            .split('_')
+            .flat_map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase())
+                    .into_iter()
+                    .chain(chars)
+            })
+            .collect();
+        examples.push((name, path));
+    }
+    examples.sort();
+    Ok(examples)
+}
+
+fn check(options: &CheckOptions) -> Result<(), String> {
+    let config = read_config(options.config.as_deref())?;
+    let helper = Helper::new(&options.manifest)?;
+    helper.write(options.features.as_deref(), &check_main(&config.groups))?;
+    if !helper.run()? {
+        // The helper printed the types and groups whose hashes are stale
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn stats(options: &StatsOptions) -> Result<(), String> {
+    let helper = Helper::new(&options.manifest)?;
+    let cwd = env::current_dir().map_err(|e| e.to_string())?;
+    let captures: Vec<PathBuf> = options
+        .captures
+        .iter()
+        .map(|capture| cwd.join(capture))
+        .collect();
+    helper.write(options.features.as_deref(), &stats_main(options, &captures))?;
+    if !helper.run()? {
+        return Err(format!("summarising {} failed", options.type_name));
+    }
+    Ok(())
+}
+
+fn snapshot(options: &SnapshotOptions) -> Result<(), String> {
+    let helper = Helper::new(&options.manifest)?;
+    let out = env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join(&options.out);
+    helper.write(options.features.as_deref(), &snapshot_main(&out))?;
+    if !helper.run()? {
+        return Err(format!("snapshotting {} failed", helper.target.name));
+    }
+    Ok(())
+}
+
+fn diff(options: &DiffOptions) -> Result<(), String> {
+    let read =
+        |path: &Path| fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e));
+    let old = read(&options.old)?;
+    let Some(new) = &options.new else {
+        let helper = Helper::new(&options.manifest)?;
+        let old = env::current_dir()
+            .map_err(|e| e.to_string())?
+            .join(&options.old);
+        helper.write(options.features.as_deref(), &diff_main(&old))?;
+        if !helper.run()? {
+            // The helper printed the changes, or why it could not compare them
+            process::exit(1);
+        }
+        return Ok(());
+    };
+    let diff = diff_snapshots(&old, &read(new)?).map_err(|e| e.to_string())?;
+    print!("{}", diff.report());
+    if diff.is_breaking() {
+        eprintln!("error: the wire format changed incompatibly");
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Build the helper ahead of running it, printing compiler errors as
+/// diagnostics in `format`
+fn build_helper(helper: &Helper, format: MessageFormat) -> Result<(), String> {
+    let target = &helper.target;
+    let output = Command::new(cargo())
+        .arg("build")
+        .arg("--quiet")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(helper.dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&helper.target_dir)
+        .output()
+        .map_err(|e| format!("running cargo: {}", e))?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-message" || message["message"]["level"] != "error" {
+            continue;
+        }
+        match format {
+            MessageFormat::Json => {
+                println!(
+                    "{}",
+                    compiler_record(&message["message"], &target.dir).to_json()
+                )
+            }
+            _ => println!("{}", compiler_diagnostic(&message["message"], &target.dir)),
+        }
+    }
+    if !output.status.success() {
+        return Err(format!("building {} failed", target.name));
+    }
+    Ok(())
+}
+
+/// A rustc error as an `--lsp-json` diagnostic, located by its primary span
+///
+/// Ranges are zero-based like LSP positions; rustc counts from one.
+fn compiler_diagnostic(message: &Value, dir: &Path) -> Value {
+    let mut diagnostic = json!({
+        "kind": "diagnostic",
+        "severity": "error",
+        "code": "rustc",
+        "message": message["message"],
+    });
+    let span = message["spans"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|span| span["is_primary"] == true);
+    if let Some(span) = span {
+        let position = |line: &str, column: &str| {
+            json!({
+                "line": span[line].as_u64().unwrap_or(1).saturating_sub(1),
+                "character": span[column].as_u64().unwrap_or(1).saturating_sub(1),
+            })
+        };
+        // Relative to the workspace the crate was compiled in, which for the
+        // target crate as a path dependency is the crate itself
+        let file = dir.join(span["file_name"].as_str().unwrap_or_default());
+        diagnostic["file"] = json!(file.display().to_string());
+        diagnostic["range"] = json!({
+            "start": position("line_start", "column_start"),
+            "end": position("line_end", "column_end"),
+        });
+    }
+    diagnostic
+}
+
+/// A rustc error as a `--message-format json` record, located by its
+/// primary span, with the first help rustc gives as the fix
+fn compiler_record(message: &Value, dir: &Path) -> Diagnostic {
+    let span = message["spans"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|span| span["is_primary"] == true)
+        .map(|span| Span {
+            file: dir.join(span["file_name"].as_str().unwrap_or_default()),
+            line: span["line_start"].as_u64().unwrap_or(1) as u32,
+            column: span["column_start"].as_u64().unwrap_or(1) as u32,
+        });
+    let fix = message["children"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|child| child["level"] == "help")
+        .and_then(|child| child["message"].as_str())
+        .map(str::to_string);
+    Diagnostic {
+        code: "rustc",
+        severity: Severity::Error,
+        message: message["message"].as_str().unwrap_or_default().to_string(),
+        span,
+        fix,
+    }
+}
+
+/// An error of the CLI itself as a `--message-format json` record
+fn cli_record(message: &str) -> Diagnostic {
+    Diagnostic {
+        code: "cli",
+        severity: Severity::Error,
+        message: message.to_string(),
+        span: None,
+        fix: None,
+    }
+}
+
+/// An error of the CLI itself as an `--lsp-json` diagnostic
+fn cli_diagnostic(message: &str) -> Value {
+    json!({
+        "kind": "diagnostic",
+        "severity": "error",
+        "code": "cli",
+        "message": message,
+    })
+}
+
+fn cargo() -> String {
+    env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+fn cargo_metadata(manifest: &Path) -> Result<Value, String> {
+    let output = Command::new(cargo())
+        .args(["metadata", "--format-version", "1", "--manifest-path"])
+        .arg(manifest)
+        .output()
+        .map_err(|e| format!("running cargo metadata: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "cargo metadata failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("reading cargo metadata: {}", e))
+}
+
+/// The crate to generate from, and how the helper depends on `serde-postcard-ts`
+struct TargetCrate {
+    name: String,
+    dir: PathBuf,
+    generator: String,
+}
+
+impl TargetCrate {
+    fn find(metadata: &Value, manifest: &Path) -> Result<Self, String> {
+        let packages = metadata["packages"]
+            .as_array()
+            .ok_or("cargo metadata has no packages")?;
+        let package = packages
+            .iter()
+            .find(|package| {
+                package["manifest_path"]
+                    .as_str()
+                    .is_some_and(|path| Path::new(path) == manifest)
+            })
+            .ok_or_else(|| format!("{} is not a package manifest", manifest.display()))?;
+        let name = package["name"].as_str().unwrap_or_default().to_string();
+        let has_lib = package["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|target| {
+                target["kind"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|kind| kind == "lib" || kind == "rlib")
+            });
+        if !has_lib {
+            return Err(format!(
+                "{} has no library target; move the PostcardTs types into a library",
+                name
+            ));
+        }
+
+        // The helper must link the very same serde-postcard-ts as the crate, or
+        // it would look at a registry the crate's types were never added to
+        let id = package["id"].as_str().unwrap_or_default();
+        let dependencies: Vec<&str> = metadata["resolve"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|node| node["id"] == id)
+            .and_then(|node| node["dependencies"].as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let generator = packages
+            .iter()
+            .find(|package| {
+                package["name"] == "serde-postcard-ts"
+                    && package["id"]
+                        .as_str()
+                        .is_some_and(|id| dependencies.contains(&id))
+            })
+            .ok_or_else(|| format!("{} does not depend on serde-postcard-ts", name))?;
+
+        Ok(TargetCrate {
+            name,
+            dir: manifest.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            generator: dependency_source(generator)?,
+        })
+    }
+}
+
+/// A Cargo.toml dependency specification for a package in `cargo metadata`
+fn dependency_source(package: &Value) -> Result<String, String> {
+    let version = package["version"].as_str().unwrap_or_default();
+    match package["source"].as_str() {
+        None => {
+            let manifest = Path::new(package["manifest_path"].as_str().unwrap_or_default());
+            let dir = manifest.parent().unwrap_or(Path::new("."));
+            Ok(format!("{{ path = {:?} }}", dir.display().to_string()))
+        }
+        Some(source) if source.starts_with("registry+") => Ok(format!("\"={}\"", version)),
+        Some(source) if source.starts_with("git+") => {
+            let (url, rev) = source["git+".len()..]
+                .split_once('#')
+                .ok_or_else(|| format!("unsupported git source {}", source))?;
+            let url = url.split('?').next().unwrap_or(url);
+            Ok(format!("{{ git = {:?}, rev = {:?} }}", url, rev))
+        }
+        Some(source) => Err(format!("unsupported serde-postcard-ts source {}", source)),
+    }
+}
+
+/// The helper binary, a crate under the target crate's target directory
+struct Helper {
+    dir: PathBuf,
+    target_dir: String,
+    target: TargetCrate,
+    metadata: Value,
+}
+
+impl Helper {
+    fn new(manifest: &Path) -> Result<Self, String> {
+        let manifest = manifest
+            .canonicalize()
+            .map_err(|e| format!("{}: {}", manifest.display(), e))?;
+        let metadata = cargo_metadata(&manifest)?;
+        let target = TargetCrate::find(&metadata, &manifest)?;
+        let target_dir = metadata["target_directory"]
+            .as_str()
+            .ok_or("cargo metadata has no target directory")?
+            .to_string();
+        Ok(Helper {
+            dir: Path::new(&target_dir).join("postcard-ts"),
+            target_dir,
+            target,
+            metadata,
+        })
+    }
+
+    /// Write the helper crate, with `main` as its `main.rs`
+    fn write(&self, features: Option<&str>, main: &str) -> Result<(), String> {
+        self.write_files(features, main)
+            .map_err(|e| format!("writing {}: {}", self.dir.display(), e))
+    }
+
+    fn write_files(&self, features: Option<&str>, main: &str) -> std::io::Result<()> {
+        fs::create_dir_all(self.dir.join("src"))?;
+
+        let features = match features {
+            Some(features) => {
+                let features: Vec<String> = features
+                    .split(',')
+                    .map(|feature| format!("{:?}", feature.trim()))
+                    .collect();
+                format!(", features = [{}]", features.join(", "))
+            }
+            None => String::new(),
+        };
+        fs::write(
+            self.dir.join("Cargo.toml"),
+            format!(
+                "[package]\n\
+                 name = \"postcard-ts-generate\"\n\
+                 version = \"0.0.0\"\n\
+                 edition = \"2021\"\n\
+                 publish = false\n\
+                 \n\
+                 [dependencies]\n\
+                 target-crate = {{ package = {:?}, path = {:?}{} }}\n\
+                 serde-postcard-ts = {}\n\
+                 \n\
+                 # Not part of the target crate's workspace\n\
+                 [workspace]\n",
+                self.target.name,
+                self.target.dir.display().to_string(),
+                features,
+                self.target.generator
+            ),
+        )?;
+
+        // Resolve dependencies exactly as the target crate does
+        if let Some(root) = self.metadata["workspace_root"].as_str() {
+            let lock = Path::new(root).join("Cargo.lock");
+            if lock.exists() {
+                fs::copy(lock, self.dir.join("Cargo.lock"))?;
+            }
+        }
+
+        fs::write(self.dir.join("src").join("main.rs"), main)
+    }
+
+    /// Run the helper, `false` if it failed
+    fn run(&self) -> Result<bool, String> {
+        let status = Command::new(cargo())
+            .arg("run")
+            .arg("--quiet")
+            .arg("--manifest-path")
+            .arg(self.dir.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&self.target_dir)
+            .status()
+            .map_err(|e| format!("running cargo: {}", e))?;
+        Ok(status.success())
+    }
+}
+
+/// The helper's `main.rs` for `postcard-ts generate`
+fn generate_main(
+    options: &Options,
+    config: &Config,
+    out: &Path,
+    examples: &[(String, PathBuf)],
+) -> String {
+    let mut generator = "Generator::new()".to_string();
+    if let Some(module) = &options.import_from {
+        generator.push_str(&format!(".import_from({:?})", module));
+    }
+    if let Some(tag) = &options.enum_tag {
+        generator.push_str(&format!(".enum_tag({:?})", tag));
+    }
+    if let Some(crc) = options.crc {
+        generator.push_str(&format!(".crc(serde_postcard_ts::crc::{})", crc));
+    }
+    if options.schema_hashes {
+        generator.push_str(".schema_hashes()");
+    }
+    if options.max_sizes {
+        generator.push_str(".max_sizes()");
+    }
+    if options.variant_peeks {
+        generator.push_str(".variant_peeks()");
+    }
+    if options.salvage {
+        generator.push_str(".salvage()");
+    }
+    if options.decoders {
+        generator.push_str(".decoders()");
+    }
+    if options.truncate_strings {
+        generator.push_str(".truncate_strings()");
+    }
+    if options.u8_vecs_as_bytes {
+        generator.push_str(".u8_vecs_as_bytes()");
+    }
+    if let Some(validator) = options.validators {
+        generator.push_str(&format!(
+            ".with_validators(serde_postcard_ts::Validator::{})",
+            validator
+        ));
+    }
+    if let Some(representation) = options.time_representation {
+        generator.push_str(&format!(
+            ".time_representation(serde_postcard_ts::TimeRepresentation::{})",
+            representation
+        ));
+    }
+    if let Some(representation) = options.uuid_representation {
+        generator.push_str(&format!(
+            ".uuid_representation(serde_postcard_ts::UuidRepresentation::{})",
+            representation
+        ));
+    }
+    if options.branded_newtypes {
+        generator.push_str(".branded_newtypes()");
+    }
+    if options.dispatchers {
+        generator.push_str(".dispatchers()");
+    }
+    if options.introspection {
+        generator.push_str(".introspection()");
+    }
+    if options.display_names {
+        generator.push_str(".display_names()");
+    }
+    if options.schema_loaders {
+        generator.push_str(".schema_loaders()");
+    }
+    generator.push_str(&groups_code(&config.groups));
+    generator.push_str(&simulators_code(&config.simulators));
+    generator.push_str(&constants_code(&config.constants));
+    generator.push_str(&features_code(&config.features));
+    generator.push_str(&naming_code(&config.naming));
+    if !config.encode_only.is_empty() {
+        generator.push_str(&format!(".encode_only({:?})", config.encode_only));
+    }
+    if !config.decode_only.is_empty() {
+        generator.push_str(&format!(".decode_only({:?})", config.decode_only));
+    }
+    if options.typedoc {
+        generator.push_str(".typedoc()");
+    }
+    if options.typedoc && !examples.is_empty() {
+        // Example directories are usually fixture directories, with frames
+        // of other types too; those are skipped
+        let examples: Vec<String> = examples
+            .iter()
+            .map(|(name, path)| format!("({:?}, {:?})", name, path.display().to_string()))
+            .collect();
+        generator = format!(
+            "{{ let mut generator = {}; \
+             for (name, path) in [{}] {{ \
+             if serde_postcard_ts::registered().iter().any(|(t, _)| t.name == name && t.params.is_empty()) {{ \
+             let frame = std::fs::read(path).unwrap_or_else(|e| {{ eprintln!(\"error: {{}}: {{}}\", path, e); std::process::exit(1) }}); \
+             generator = generator.typedoc_example(name, frame); }} }} \
+             generator }}",
+            generator,
+            examples.join(", ")
+        );
+    }
+    let mut lints = "LintConfig::new()".to_string();
+    for (code, level) in &config.lints.levels {
+        lints.push_str(&format!(".level({:?}, LintLevel::{})", code, level));
+    }
+    if let Some(depth) = config.lints.max_depth {
+        lints.push_str(&format!(".max_depth({})", depth));
+    }
+    let layout = format!("Layout::{:?}", options.layout);
+    let records = match options.message_format {
+        MessageFormat::Human => None,
+        MessageFormat::Json => Some(("diagnostics", "generate")),
+        MessageFormat::Lsp => Some(("lsp", "generate_with_lints")),
+    };
+    if let Some((module, function)) = records {
+        format!(
+            "// Written by postcard-ts generate; linking the target crate registers its types\n\
+             use target_crate as _;\n\
+             \n\
+             use serde_postcard_ts::{{{module}, registered, Generator, Layout, LintConfig, LintLevel, WriteOptions}};\n\
+             use std::path::Path;\n\
+             \n\
+             fn main() {{\n\
+             \x20   let options = WriteOptions {{ clean: {clean}, overwrite: {overwrite} }};\n\
+             \x20   let out = Path::new({out:?});\n\
+             \x20   let lints = {lints};\n\
+             \x20   let result = {module}::{function}(&{generator}, registered(), {layout}, out, options, &lints);\n\
+             \x20   let failed = result.is_err();\n\
+             \x20   for message in result.unwrap_or_else(|messages| messages) {{\n\
+             \x20       println!(\"{{}}\", message);\n\
+             \x20   }}\n\
+             \x20   if failed {{\n\
+             \x20       std::process::exit(1);\n\
+             \x20   }}\n\
+             }}\n",
+            module = module,
+            function = function,
+            generator = generator,
+            lints = lints,
+            layout = layout,
+            clean = options.clean,
+            overwrite = options.overwrite,
+            out = out.display().to_string(),
+        )
+    } else {
+        format!(
+            "// Written by postcard-ts generate; linking the target crate registers its types\n\
+             use target_crate as _;\n\
+             \n\
+             use serde_postcard_ts::{{lint_with, write_files, Generator, Layout, LintConfig, LintLevel, WriteOptions}};\n\
+             use std::path::Path;\n\
+             \n\
+             fn main() {{\n\
+             \x20   let mut denied = false;\n\
+             \x20   for lint in lint_with(&{lints}) {{\n\
+             \x20       denied |= lint.level == LintLevel::Deny;\n\
+             \x20       let level = if lint.level == LintLevel::Deny {{ \"error\" }} else {{ \"warning\" }};\n\
+             \x20       eprintln!(\"{{}}: {{}}\", level, lint);\n\
+             \x20       if let Some(span) = &lint.span {{\n\
+             \x20           eprintln!(\"  --> {{}}\", span);\n\
+             \x20       }}\n\
+             \x20   }}\n\
+             \x20   if denied {{\n\
+             \x20       std::process::exit(1);\n\
+             \x20   }}\n\
+             \x20   let files = {generator}.generate_files({layout}).unwrap_or_else(|e| {{\n\
+             \x20       eprintln!(\"error: {{}}\", e);\n\
+             \x20       std::process::exit(1);\n\
+             \x20   }});\n\
+             \x20   let options = WriteOptions {{ clean: {clean}, overwrite: {overwrite} }};\n\
+             \x20   let report = write_files(Path::new({out:?}), &files, options).unwrap_or_else(|e| {{\n\
+             \x20       eprintln!(\"error: {{}}\", e);\n\
+             \x20       std::process::exit(1);\n\
+             \x20   }});\n\
+             \x20   for path in &report.written {{\n\
+             \x20       println!(\"  wrote {{}}\", path.display());\n\
+             \x20   }}\n\
+             \x20   for path in &report.removed {{\n\
+             \x20       println!(\"  removed {{}}\", path.display());\n\
+             \x20   }}\n\
+             \x20   println!(\n\
+             \x20       \"{{}} written, {{}} unchanged, {{}} removed\",\n\
+             \x20       report.written.len(),\n\
+             \x20       report.unchanged.len(),\n\
+             \x20       report.removed.len()\n\
+             \x20   );\n\
+             }}\n",
+            generator = generator,
+            lints = lints,
+            layout = layout,
+            clean = options.clean,
+            overwrite = options.overwrite,
+            out = out.display().to_string(),
+        )
+    }
+}
+
+/// The helper's `main.rs` for `postcard-ts stats`
+fn stats_main(options: &StatsOptions, captures: &[PathBuf]) -> String {
+    let captures: Vec<String> = captures
+        .iter()
+        .map(|capture| format!("{:?}", capture.display().to_string()))
+        .collect();
+    format!(
+        "// Written by postcard-ts stats; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         use serde_postcard_ts::{{read_capture, registered, CaptureStats, StatsOptions}};\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let mut frames = Vec::new();\n\
+         \x20   for path in [{captures}] {{\n\
+         \x20       let data = std::fs::read(path).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         \x20       frames.extend(read_capture(&data).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e))));\n\
+         \x20   }}\n\
+         \x20   frames.sort_by_key(|frame| frame.time_ms);\n\
+         \x20   let options = StatsOptions {{ talker: {talker}, top: {top} }};\n\
+         \x20   let stats = CaptureStats::collect(&registered(), {type_name:?}, &frames, &options)\n\
+         \x20       .unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   print!(\"{{}}\", stats.{output}());\n\
+         }}\n",
+        captures = captures.join(", "),
+        talker = match &options.talker {
+            Some(field) => format!("Some({:?}.to_string())", field),
+            None => "None".to_string(),
+        },
+        top = options.top,
+        type_name = options.type_name,
+        output = if options.json { "to_json" } else { "table" },
+    )
+}
+
+/// The helper's `main.rs` for `postcard-ts schema snapshot`
+fn snapshot_main(out: &Path) -> String {
+    format!(
+        "// Written by postcard-ts schema snapshot; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         use serde_postcard_ts::Generator;\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let path = {out:?};\n\
+         \x20   let snapshot = Generator::new().generate_ir().unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   std::fs::write(path, snapshot).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         \x20   println!(\"  wrote {{}}\", path);\n\
+         }}\n",
+        out = out.display().to_string(),
+    )
+}
+
+/// The helper's `main.rs` for `postcard-ts schema diff` against the crate
+fn diff_main(old: &Path) -> String {
+    format!(
+        "// Written by postcard-ts schema diff; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         use serde_postcard_ts::{{diff_snapshots, Generator}};\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let path = {old:?};\n\
+         \x20   let old = std::fs::read_to_string(path).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         \x20   let new = Generator::new().generate_ir().unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   let diff = diff_snapshots(&old, &new).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         \x20   print!(\"{{}}\", diff.report());\n\
+         \x20   if diff.is_breaking() {{\n\
+         \x20       fail(\"the wire format changed incompatibly\".to_string());\n\
+         \x20   }}\n\
+         }}\n",
+        old = old.display().to_string(),
+    )
+}
+
+/// The helper's `main.rs` for `postcard-ts migrate`, with `generator` the
+/// builder calls configuring the generator
+fn migrate_main(
+    types: &Path,
+    shims: Option<&(PathBuf, String, String)>,
+    generator: &str,
+) -> String {
+    let write = match shims {
+        Some((out, generated, hand_written)) => format!(
+            "\x20   let out = {out:?};\n\
+             \x20   let shims = migration.shims({generated:?}, {hand_written:?});\n\
+             \x20   std::fs::write(out, shims).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", out, e)));\n\
+             \x20   println!(\"  wrote {{}}\", out);\n",
+            out = out.display().to_string(),
+        ),
+        None => String::new(),
+    };
+    format!(
+        "// Written by postcard-ts migrate; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         use serde_postcard_ts::Generator;\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let path = {types:?};\n\
+         \x20   let source = std::fs::read_to_string(path).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         \x20   let migration = Generator::new(){generator}\n\
+         \x20       .migrate_ts(&source)\n\
+         \x20       .unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   print!(\"{{}}\", migration.report());\n\
+         {write}\
+         }}\n",
+        types = types.display().to_string(),
+    )
+}
+
+/// The helper's `main.rs` for `postcard-ts verify`, writing the schemas with
+/// `__schema` to `out`, with `generator` the builder calls configuring the
+/// generator
+fn verify_main(out: &Path, generator: &str) -> String {
+    format!(
+        "// Written by postcard-ts verify; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         use serde_postcard_ts::Generator;\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let path = {out:?};\n\
+         \x20   let schemas = Generator::new(){generator}\n\
+         \x20       .introspection()\n\
+         \x20       .generate()\n\
+         \x20       .unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   std::fs::write(path, schemas).unwrap_or_else(|e| fail(format!(\"{{}}: {{}}\", path, e)));\n\
+         }}\n",
+        out = out.display().to_string(),
+    )
+}
+
+/// The helper's `main.rs` for `postcard-ts check`
+fn check_main(groups: &[GroupConfig]) -> String {
+    format!(
+        "// Written by postcard-ts check; linking the target crate registers its types\n\
+         use target_crate as _;\n\
+         \n\
+         #[allow(unused_imports)]\n\
+         use serde_postcard_ts::{{check_schema_hashes, Generator}};\n\
+         \n\
+         fn fail(message: String) -> ! {{\n\
+         \x20   eprintln!(\"error: {{}}\", message);\n\
+         \x20   std::process::exit(1);\n\
+         }}\n\
+         \n\
+         fn main() {{\n\
+         \x20   let checks = check_schema_hashes().unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   let stale: Vec<_> = checks.iter().filter(|check| check.is_stale()).collect();\n\
+         \x20   for check in &stale {{\n\
+         \x20       println!(\n\
+         \x20           \"  {{}}::{{}}: pinned {{:#018x}}, wire shape now hashes to {{:#018x}}\",\n\
+         \x20           check.type_name.module,\n\
+         \x20           check.type_name.name,\n\
+         \x20           check.pinned.unwrap_or_default(),\n\
+         \x20           check.hash\n\
+         \x20       );\n\
+         \x20   }}\n\
+         \x20   let pinned = checks.iter().filter(|check| check.pinned.is_some()).count();\n\
+         \x20   println!(\n\
+         \x20       \"{{}} pinned, {{}} stale, {{}} not pinned\",\n\
+         \x20       pinned,\n\
+         \x20       stale.len(),\n\
+         \x20       checks.len() - pinned\n\
+         \x20   );\n\
+         \n\
+         \x20   let groups = Generator::new(){groups}.check_groups().unwrap_or_else(|e| fail(e.to_string()));\n\
+         \x20   let mut stale_groups = 0;\n\
+         \x20   for group in &groups {{\n\
+         \x20       match group.pinned {{\n\
+         \x20           Some(pinned) if group.is_stale() => {{\n\
+         \x20               stale_groups += 1;\n\
+         \x20               println!(\n\
+         \x20                   \"  group {{}} v{{}}: pinned {{:#018x}}, its types now hash to {{:#018x}}\",\n\
+         \x20                   group.group, group.version, pinned, group.hash\n\
+         \x20               );\n\
+         \x20           }}\n\
+         \x20           Some(_) => {{}}\n\
+         \x20           None => println!(\"  group {{}} v{{}}: {{:#018x}}, not pinned\", group.group, group.version, group.hash),\n\
+         \x20       }}\n\
+         \x20   }}\n\
+         \x20   if !groups.is_empty() {{\n\
+         \x20       println!(\"{{}} groups, {{}} stale\", groups.len(), stale_groups);\n\
+         \x20   }}\n\
+         \n\
+         \x20   if !stale.is_empty() {{\n\
+         \x20       eprintln!(\n\
+         \x20           \"error: wire shapes changed since their hashes were pinned; pin the new hashes once the change is intended\"\n\
+         \x20       );\n\
+         \x20   }}\n\
+         \x20   if stale_groups > 0 {{\n\
+         \x20       eprintln!(\n\
+         \x20           \"error: protocol groups changed since their versions were pinned; give them new versions with the new hashes once the change is intended\"\n\
+         \x20       );\n\
+         \x20   }}\n\
+         \x20   if !stale.is_empty() || stale_groups > 0 {{\n\
+         \x20       std::process::exit(1);\n\
+         \x20   }}\n\
+         }}\n",
+        groups = groups_code(groups),
+    )
+}
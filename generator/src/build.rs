@@ -0,0 +1,93 @@
+//! Generating TypeScript from a build script
+//!
+//! A build script sees the types of its build-dependencies, so with the
+//! protocol crate among them, [`emit`] regenerates the TypeScript on every
+//! `cargo build` that changed it:
+//!
+//! ```no_run
+//! // build.rs, with `protocol` in [build-dependencies]
+//! # mod protocol {}
+//! use protocol as _; // links the crate, so its types are registered
+//!
+//! fn main() {
+//!     serde_postcard_ts::build::emit("../web/src/generated").unwrap();
+//! }
+//! ```
+//!
+//! Files whose content is unchanged are not rewritten, so a frontend dev
+//! server or bundler watching them does not rebuild for nothing. Cargo is
+//! told to rerun the script when a file declaring one of the types changes
+//! rather than whenever anything in the package does.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::generate::{GenerateError, Generator, Layout};
+use crate::output::{write_files, WriteOptions, WriteReport};
+use crate::registry;
+
+#[derive(Debug)]
+pub enum BuildError {
+    Generate(GenerateError),
+    Write(io::Error),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Generate(error) => write!(f, "generating TypeScript failed: {}", error),
+            BuildError::Write(error) => write!(f, "writing TypeScript failed: {}", error),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BuildError::Generate(error) => Some(error),
+            BuildError::Write(error) => Some(error),
+        }
+    }
+}
+
+/// Generate every registered type into `dir/index.ts`, as [`emit_with`]
+/// does with the default generator and options
+pub fn emit(dir: impl AsRef<Path>) -> Result<WriteReport, BuildError> {
+    emit_with(
+        &Generator::new(),
+        Layout::Single,
+        dir,
+        WriteOptions::default(),
+    )
+}
+
+/// Generate every registered type with `generator`, write the files that
+/// changed below `dir` and print `cargo:rerun-if-changed` for
+/// [`source_files`]
+///
+/// The directives are printed even when generating fails, so that fixing
+/// the types reruns the script.
+pub fn emit_with(
+    generator: &Generator,
+    layout: Layout,
+    dir: impl AsRef<Path>,
+    options: WriteOptions,
+) -> Result<WriteReport, BuildError> {
+    for path in source_files() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    let files = generator
+        .generate_files(layout)
+        .map_err(BuildError::Generate)?;
+    write_files(dir.as_ref(), &files, options).map_err(BuildError::Write)
+}
+
+/// The files declaring the registered types, sorted
+///
+/// Types from crates whose sources cannot be found, and built-in types, have
+/// none.
+pub fn source_files() -> Vec<PathBuf> {
+    registry::source_files()
+}
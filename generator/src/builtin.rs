@@ -0,0 +1,371 @@
+//! Built-in types
+//!
+//! `std::time::Duration` and the `NonZero` integers, and with the `chrono`,
+//! `time` and `uuid` features `chrono::DateTime<Utc>`, `time::OffsetDateTime`
+//! and `uuid::Uuid`, describe themselves as named types the generator
+//! declares on its own when they are used, with the runtime's builder for the
+//! wire format their serde impls write for postcard:
+//!
+//! ```typescript
+//! export const DateTimeSchema = chronoDateTime("date");
+//! export type DateTime = InferType<typeof DateTimeSchema>;
+//! ```
+//!
+//! Instants decode to a `Date` and durations to a number of milliseconds,
+//! or to exact bigint nanoseconds with [`TimeRepresentation::Nanos`]. UUIDs
+//! decode to their hyphenated text, or to their bytes with
+//! [`UuidRepresentation::Bytes`].
+
+use std::collections::BTreeSet;
+
+use crate::generate::references;
+use crate::shape::{ConstDefinition, ConstShape, Definition, Field, PostcardTs, Shape, TypeName};
+
+/// What the built-in date and time types decode to in TypeScript
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeRepresentation {
+    /// Instants as `Date` and durations as a `number` of milliseconds, both
+    /// to the millisecond
+    #[default]
+    Date,
+    /// Instants as `bigint` nanoseconds since the Unix epoch and durations
+    /// as `bigint` nanoseconds, exactly
+    Nanos,
+}
+
+impl TimeRepresentation {
+    /// The argument of the runtime's builders
+    fn argument(self) -> &'static str {
+        match self {
+            TimeRepresentation::Date => "\"date\"",
+            TimeRepresentation::Nanos => "\"nanos\"",
+        }
+    }
+}
+
+/// What `uuid::Uuid` decodes to in TypeScript
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UuidRepresentation {
+    /// The lowercase hyphenated text, as a branded `Uuid` string
+    #[default]
+    String,
+    /// The 16 bytes, as a `Uint8Array`
+    Bytes,
+}
+
+impl UuidRepresentation {
+    /// The argument of the runtime's builder
+    fn argument(self) -> &'static str {
+        match self {
+            UuidRepresentation::String => "\"string\"",
+            UuidRepresentation::Bytes => "\"bytes\"",
+        }
+    }
+}
+
+/// How the generator was asked to represent the built-in types
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Representations {
+    pub(crate) time: TimeRepresentation,
+    pub(crate) uuid: UuidRepresentation,
+}
+
+const DURATION: TypeName = TypeName {
+    name: "Duration",
+    module: "std::time",
+    params: &[],
+};
+
+const CHRONO_DATE_TIME: TypeName = TypeName {
+    name: "DateTime",
+    module: "chrono",
+    params: &[],
+};
+
+const OFFSET_DATE_TIME: TypeName = TypeName {
+    name: "OffsetDateTime",
+    module: "time",
+    params: &[],
+};
+
+const UUID: TypeName = TypeName {
+    name: "Uuid",
+    module: "uuid",
+    params: &[],
+};
+
+macro_rules! non_zero {
+    ($($ty:ident => $shape:ident),* $(,)?) => {
+        /// The `NonZero` integers and the shapes of their values
+        const NON_ZERO: &[(TypeName, Shape)] = &[$(
+            (
+                TypeName {
+                    name: stringify!($ty),
+                    module: "core::num",
+                    params: &[],
+                },
+                Shape::$shape,
+            ),
+        )*];
+
+        $(
+            impl PostcardTs for std::num::$ty {
+                const CONST_SHAPE: ConstShape = ConstShape::Named(
+                    TypeName {
+                        name: stringify!($ty),
+                        module: "core::num",
+                        params: &[],
+                    },
+                    &[],
+                    ConstDefinition::new(&ConstShape::$shape),
+                );
+
+                fn shape() -> Shape {
+                    Shape::Named(TypeName {
+                        name: stringify!($ty),
+                        module: "core::num",
+                        params: &[],
+                    })
+                }
+            }
+        )*
+    };
+}
+
+non_zero! {
+    NonZeroU8 => U8,
+    NonZeroU16 => U16,
+    NonZeroU32 => U32,
+    NonZeroU64 => U64,
+    NonZeroU128 => U128,
+    NonZeroUsize => U64,
+    NonZeroI8 => I8,
+    NonZeroI16 => I16,
+    NonZeroI32 => I32,
+    NonZeroI64 => I64,
+    NonZeroI128 => I128,
+    NonZeroIsize => I64,
+}
+
+/// What a built-in type decodes to
+pub(crate) enum Kind {
+    Instant,
+    Duration,
+    Uuid,
+    /// An integer other than zero, of this shape
+    NonZero(Shape),
+}
+
+/// A built-in type, declared with a runtime builder rather than from its
+/// definition
+pub(crate) struct Builtin {
+    /// The runtime's builder of its schema
+    pub(crate) builder: &'static str,
+    /// The wire format, which is what the checks, hashes and sizes go by
+    pub(crate) definition: Definition,
+    pub(crate) kind: Kind,
+}
+
+impl Builtin {
+    /// The schema expression declaring the type, with `shape` rendering the
+    /// schema of a `NonZero` integer's values
+    pub(crate) fn schema(
+        &self,
+        representations: Representations,
+        shape: impl FnOnce(&Shape) -> String,
+    ) -> String {
+        let argument = match &self.kind {
+            Kind::Instant | Kind::Duration => representations.time.argument().to_string(),
+            Kind::Uuid => representations.uuid.argument().to_string(),
+            Kind::NonZero(inner) => shape(inner),
+        };
+        format!("{}({})", self.builder, argument)
+    }
+
+    /// The TypeScript type values decode to
+    pub(crate) fn ts_type(&self, representations: Representations) -> &'static str {
+        match (&self.kind, representations.time, representations.uuid) {
+            (Kind::Instant, TimeRepresentation::Date, _) => "Date",
+            (Kind::Duration, TimeRepresentation::Date, _) => "number",
+            (Kind::Instant | Kind::Duration, TimeRepresentation::Nanos, _) => "bigint",
+            // The runtime's `Uuid`, spelled out
+            (Kind::Uuid, _, UuidRepresentation::String) => {
+                "string & { readonly __brand: \"Uuid\" }"
+            }
+            (Kind::Uuid, _, UuidRepresentation::Bytes) => "Uint8Array",
+            (Kind::NonZero(Shape::I64 | Shape::I128 | Shape::U64 | Shape::U128), _, _) => "bigint",
+            (Kind::NonZero(_), _, _) => "number",
+        }
+    }
+
+    /// The Zod schema of the values, with `shape` rendering the Zod schema of
+    /// a `NonZero` integer's values
+    pub(crate) fn zod(
+        &self,
+        representations: Representations,
+        shape: impl FnOnce(&Shape) -> String,
+    ) -> String {
+        match (&self.kind, representations.time, representations.uuid) {
+            (Kind::Instant, TimeRepresentation::Date, _) => "z.date()".to_string(),
+            (Kind::Duration, TimeRepresentation::Date, _) => "z.number().nonnegative()".to_string(),
+            (Kind::Instant | Kind::Duration, TimeRepresentation::Nanos, _) => {
+                "z.bigint()".to_string()
+            }
+            (Kind::Uuid, _, UuidRepresentation::String) => "z.string().uuid()".to_string(),
+            (Kind::Uuid, _, UuidRepresentation::Bytes) => {
+                "z.instanceof(Uint8Array).refine((value) => value.length === 16, \
+                 \"expected 16 bytes\")"
+                    .to_string()
+            }
+            (Kind::NonZero(inner), _, _) => {
+                let zero = match inner {
+                    Shape::I64 | Shape::I128 | Shape::U64 | Shape::U128 => "0n",
+                    _ => "0",
+                };
+                format!(
+                    "{}.refine((value) => value !== {zero}, \"must not be zero\")",
+                    shape(inner)
+                )
+            }
+        }
+    }
+}
+
+/// The built-in type named `type_name`, if it is one
+pub(crate) fn builtin(type_name: &TypeName) -> Option<Builtin> {
+    let builtin = match *type_name {
+        DURATION => Builtin {
+            builder: "duration",
+            // Serialized by serde as `struct Duration { secs, nanos }`
+            definition: Definition::Struct(vec![
+                Field {
+                    name: "secs",
+                    shape: Shape::U64,
+                },
+                Field {
+                    name: "nanos",
+                    shape: Shape::U32,
+                },
+            ]),
+            kind: Kind::Duration,
+        },
+        // Always its RFC 3339 text, as `to_rfc3339_opts(SecondsFormat::AutoSi, true)`
+        CHRONO_DATE_TIME => Builtin {
+            builder: "chronoDateTime",
+            definition: Definition::NewtypeStruct(Shape::String),
+            kind: Kind::Instant,
+        },
+        // Not human-readable: year, day of the year, hour, minute, second,
+        // nanosecond, then the offset's hours, minutes and seconds
+        OFFSET_DATE_TIME => Builtin {
+            builder: "offsetDateTime",
+            definition: Definition::NewtypeStruct(Shape::Tuple(vec![
+                Shape::I32,
+                Shape::U16,
+                Shape::U8,
+                Shape::U8,
+                Shape::U8,
+                Shape::U32,
+                Shape::I8,
+                Shape::I8,
+                Shape::I8,
+            ])),
+            kind: Kind::Instant,
+        },
+        // Not human-readable: its 16 bytes, as a byte string
+        UUID => Builtin {
+            builder: "uuid",
+            definition: Definition::NewtypeStruct(Shape::Bytes),
+            kind: Kind::Uuid,
+        },
+        _ => {
+            let (_, shape) = NON_ZERO.iter().find(|(name, _)| name == type_name)?;
+            Builtin {
+                builder: "nonZero",
+                definition: Definition::NewtypeStruct(shape.clone()),
+                kind: Kind::NonZero(shape.clone()),
+            }
+        }
+    };
+    Some(builtin)
+}
+
+/// Add the built-in types `types` refer to without declaring them
+pub(crate) fn add_builtins(types: &mut Vec<(TypeName, Definition)>) {
+    let referenced: BTreeSet<TypeName> = types
+        .iter()
+        .flat_map(|(_, definition)| references(definition))
+        .collect();
+    let names = [DURATION, CHRONO_DATE_TIME, OFFSET_DATE_TIME, UUID]
+        .into_iter()
+        .chain(NON_ZERO.iter().map(|(type_name, _)| *type_name));
+    for type_name in names {
+        let declared = types.iter().any(|(other, _)| *other == type_name);
+        if !referenced.contains(&type_name) || declared {
+            continue;
+        }
+        if let Some(builtin) = builtin(&type_name) {
+            types.push((type_name, builtin.definition));
+        }
+    }
+}
+
+// The definitions of the built-in types are those `builtin` gives them
+
+impl PostcardTs for std::time::Duration {
+    const CONST_SHAPE: ConstShape = ConstShape::Named(
+        DURATION,
+        &[],
+        ConstDefinition::new(&ConstShape::Tuple(&[ConstShape::U64, ConstShape::U32])),
+    );
+
+    fn shape() -> Shape {
+        Shape::Named(DURATION)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl PostcardTs for chrono::DateTime<chrono::Utc> {
+    const CONST_SHAPE: ConstShape = ConstShape::Named(
+        CHRONO_DATE_TIME,
+        &[],
+        ConstDefinition::new(&ConstShape::String),
+    );
+
+    fn shape() -> Shape {
+        Shape::Named(CHRONO_DATE_TIME)
+    }
+}
+
+#[cfg(feature = "time")]
+impl PostcardTs for time::OffsetDateTime {
+    const CONST_SHAPE: ConstShape = ConstShape::Named(
+        OFFSET_DATE_TIME,
+        &[],
+        ConstDefinition::new(&ConstShape::Tuple(&[
+            ConstShape::I32,
+            ConstShape::U16,
+            ConstShape::U8,
+            ConstShape::U8,
+            ConstShape::U8,
+            ConstShape::U32,
+            ConstShape::I8,
+            ConstShape::I8,
+            ConstShape::I8,
+        ])),
+    );
+
+    fn shape() -> Shape {
+        Shape::Named(OFFSET_DATE_TIME)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl PostcardTs for uuid::Uuid {
+    const CONST_SHAPE: ConstShape =
+        ConstShape::Named(UUID, &[], ConstDefinition::new(&ConstShape::Bytes));
+
+    fn shape() -> Shape {
+        Shape::Named(UUID)
+    }
+}
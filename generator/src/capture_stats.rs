@@ -0,0 +1,927 @@
+//! Statistics over capture files, for deciding what to compress, delta-encode
+//! or downsample
+//!
+//! A capture (written by the TypeScript runtime's flight recorder) holds raw
+//! frames with the time each one arrived. Decoding every frame as one
+//! registered message type tells which kinds of message make up the traffic:
+//! frames and bytes per type and enum variant, how the frame sizes spread,
+//! how often each kind arrives and, given a field such as `sensor`, which
+//! values of it send the most. Within each kind, the bytes are broken down
+//! by field and by the variants of nested enums (`world` 41%, of which
+//! `world.locations` 35%), showing which fields are worth optimizing.
+//! `postcard-ts stats` prints it as a table or as JSON.
+//!
+//! Frames are decoded from the wire shape alone, so a capture can be
+//! summarised with the types of any crate; frames that do not decode are
+//! counted by error instead.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::ir::string;
+use crate::shape::{Definition, Shape, TypeName, VariantKind};
+use crate::wire_layout::{join, substitute_definition};
+
+/// Capture format version read here, as written by `encodeCapture`
+pub const CAPTURE_VERSION: u32 = 1;
+
+/// One frame of a capture file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Milliseconds since the Unix epoch
+    pub time_ms: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptureError {
+    /// The file is not a capture, or is cut off
+    Malformed,
+    UnsupportedVersion(u32),
+    /// No registered type has the name asked for
+    UnknownType(String),
+    /// Generic types need arguments to be decoded
+    GenericType(String),
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::Malformed => write!(f, "not a capture file, or cut off"),
+            CaptureError::UnsupportedVersion(version) => {
+                write!(f, "unsupported capture version {}", version)
+            }
+            CaptureError::UnknownType(name) => {
+                write!(f, "no type named {} derives PostcardTs", name)
+            }
+            CaptureError::GenericType(name) => write!(
+                f,
+                "{} is generic; summarise a type using it with arguments",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for CaptureError {}
+
+/// Read the frames of a capture file
+pub fn read_capture(data: &[u8]) -> Result<Vec<CapturedFrame>, CaptureError> {
+    let mut reader = Reader { data, pos: 0 };
+    let malformed = |_| CaptureError::Malformed;
+    let version = reader.varint(32).map_err(malformed)? as u32;
+    if version != CAPTURE_VERSION {
+        return Err(CaptureError::UnsupportedVersion(version));
+    }
+    let count = reader.varint(64).map_err(malformed)?;
+    let mut frames = Vec::new();
+    for _ in 0..count {
+        let time_ms = reader.varint(64).map_err(malformed)? as u64;
+        let len = reader.varint(64).map_err(malformed)? as usize;
+        let data = reader.take(len).map_err(malformed)?.to_vec();
+        frames.push(CapturedFrame { time_ms, data });
+    }
+    if reader.pos != data.len() {
+        return Err(CaptureError::Malformed);
+    }
+    Ok(frames)
+}
+
+/// What to summarise besides the message kinds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// Dotted path of a field to rank the senders by, e.g. `sensor` or
+    /// `header.device`; in enums, the path starts inside the variant
+    pub talker: Option<String>,
+    /// Talkers to list
+    pub top: usize,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        StatsOptions {
+            talker: None,
+            top: 10,
+        }
+    }
+}
+
+/// Frame sizes by power-of-two bucket
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// Inclusive upper bound of each bucket and the frames in it, from the
+    /// bucket of the smallest frame to that of the largest
+    pub buckets: Vec<(usize, usize)>,
+}
+
+/// Time between consecutive frames
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    pub min_ms: u64,
+    pub mean_ms: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// The frames of one kind of message: a type, or a type and its enum variants
+#[derive(Clone, Debug, PartialEq)]
+pub struct KindStats {
+    /// `Message::Reading`, or `Message::Sensor::Reading` for a variant
+    /// wrapping another enum
+    pub kind: String,
+    pub frames: usize,
+    pub bytes: usize,
+    pub min_size: usize,
+    pub max_size: usize,
+    pub sizes: Histogram,
+    /// Between consecutive frames of this kind; `None` with fewer than two
+    pub interval: Option<Timing>,
+    /// The bytes of each field, parents before the fields they hold
+    pub fields: Vec<FieldStats>,
+}
+
+/// The bytes one field or nested variant takes up in the frames of a kind
+///
+/// `path` names struct fields after a `.` and enum variants after `::`:
+/// `world.locations`, `events::Move.x`. The items of sequences and maps
+/// count towards the collection, and their fields, such as `events.x`,
+/// total all items. The variants of the kind itself are not repeated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldStats {
+    pub path: String,
+    /// The frames holding the field, fewer than the kind's for a variant
+    pub frames: usize,
+    /// Including its length prefixes and variant indices
+    pub bytes: usize,
+}
+
+/// The frames carrying one value of the talker field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TalkerStats {
+    pub value: String,
+    pub frames: usize,
+    pub bytes: usize,
+}
+
+/// Statistics of a capture decoded as one message type
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaptureStats {
+    pub type_name: String,
+    pub frames: usize,
+    pub bytes: usize,
+    /// From the first frame to the last
+    pub duration_ms: u64,
+    /// Decoded frames by kind, most bytes first
+    pub kinds: Vec<KindStats>,
+    pub sizes: Histogram,
+    pub interval: Option<Timing>,
+    /// The talker field and its values sending the most bytes, most first
+    pub talkers: Option<(String, Vec<TalkerStats>)>,
+    /// Frames that did not decode, by error code
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl CaptureStats {
+    /// Decode `frames` as the registered type named `name` (or
+    /// `module::Name`) and summarise them
+    pub fn collect(
+        types: &[(TypeName, Definition)],
+        name: &str,
+        frames: &[CapturedFrame],
+        options: &StatsOptions,
+    ) -> Result<Self, CaptureError> {
+        let (type_name, _) = types
+            .iter()
+            .find(|(type_name, _)| {
+                type_name.name == name
+                    || format!("{}::{}", type_name.module, type_name.name) == name
+            })
+            .ok_or_else(|| CaptureError::UnknownType(name.to_string()))?;
+        if !type_name.params.is_empty() {
+            return Err(CaptureError::GenericType(name.to_string()));
+        }
+        let definitions: HashMap<TypeName, &Definition> = types
+            .iter()
+            .map(|(type_name, definition)| (*type_name, definition))
+            .collect();
+
+        let mut kinds: BTreeMap<String, Vec<&CapturedFrame>> = BTreeMap::new();
+        let mut fields: HashMap<String, FieldTally> = HashMap::new();
+        let mut talkers: HashMap<String, TalkerStats> = HashMap::new();
+        let mut errors: BTreeMap<String, usize> = BTreeMap::new();
+        for frame in frames {
+            let mut decoder = Decoder {
+                definitions: &definitions,
+                reader: Reader {
+                    data: &frame.data,
+                    pos: 0,
+                },
+                talker: options.talker.as_deref(),
+                kind: type_name.name.to_string(),
+                talker_value: None,
+                part: String::new(),
+                parts: Vec::new(),
+            };
+            let decoded = match decoder.value(&Shape::Named(*type_name), "") {
+                Ok(_) if decoder.reader.pos != frame.data.len() => Err("TRAILING_BYTES"),
+                result => result,
+            };
+            if let Err(code) = decoded {
+                *errors.entry(code.to_string()).or_default() += 1;
+                continue;
+            }
+            if let Some(value) = decoder.talker_value {
+                let talker = talkers.entry(value.clone()).or_insert(TalkerStats {
+                    value,
+                    frames: 0,
+                    bytes: 0,
+                });
+                talker.frames += 1;
+                talker.bytes += frame.data.len();
+            }
+            fields
+                .entry(decoder.kind.clone())
+                .or_default()
+                .add(decoder.parts);
+            kinds.entry(decoder.kind).or_default().push(frame);
+        }
+
+        let mut kinds: Vec<KindStats> = kinds
+            .into_iter()
+            .map(|(kind, frames)| KindStats {
+                fields: fields.remove(&kind).unwrap_or_default().stats(),
+                kind,
+                frames: frames.len(),
+                bytes: frames.iter().map(|frame| frame.data.len()).sum(),
+                min_size: frames
+                    .iter()
+                    .map(|frame| frame.data.len())
+                    .min()
+                    .unwrap_or(0),
+                max_size: frames
+                    .iter()
+                    .map(|frame| frame.data.len())
+                    .max()
+                    .unwrap_or(0),
+                sizes: histogram(frames.iter().map(|frame| frame.data.len())),
+                interval: timing(frames.iter().map(|frame| frame.time_ms)),
+            })
+            .collect();
+        kinds.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.kind.cmp(&b.kind)));
+
+        let talkers = options.talker.as_ref().map(|field| {
+            let mut talkers: Vec<TalkerStats> = talkers.into_values().collect();
+            talkers.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.value.cmp(&b.value)));
+            talkers.truncate(options.top);
+            (field.clone(), talkers)
+        });
+
+        let first = frames.iter().map(|frame| frame.time_ms).min().unwrap_or(0);
+        let last = frames.iter().map(|frame| frame.time_ms).max().unwrap_or(0);
+        Ok(CaptureStats {
+            type_name: type_name.name.to_string(),
+            frames: frames.len(),
+            bytes: frames.iter().map(|frame| frame.data.len()).sum(),
+            duration_ms: last - first,
+            kinds,
+            sizes: histogram(frames.iter().map(|frame| frame.data.len())),
+            interval: timing(frames.iter().map(|frame| frame.time_ms)),
+            talkers,
+            errors,
+        })
+    }
+
+    /// The statistics as a plain-text report
+    pub fn table(&self) -> String {
+        let mut out = format!(
+            "{}: {} frames, {} bytes over {:.1} s",
+            self.type_name,
+            self.frames,
+            self.bytes,
+            self.duration_ms as f64 / 1000.0
+        );
+        if self.duration_ms > 0 {
+            out.push_str(&format!(
+                " ({:.1} frames/s, {:.0} B/s)",
+                self.frames as f64 * 1000.0 / self.duration_ms as f64,
+                self.bytes as f64 * 1000.0 / self.duration_ms as f64
+            ));
+        }
+        out.push('\n');
+        if let Some(interval) = &self.interval {
+            out.push_str(&format!("interval: {}\n", interval.summary()));
+        }
+
+        let width = self
+            .kinds
+            .iter()
+            .map(|kind| kind.kind.len())
+            .max()
+            .unwrap_or(0)
+            .max("kind".len());
+        out.push_str(&format!(
+            "\n{:<width$}  {:>7}  {:>9}  {:>6}  {:>16}  {:>13}\n",
+            "kind", "frames", "bytes", "share", "size min/avg/max", "ms p50/p95",
+        ));
+        for kind in &self.kinds {
+            let sizes = format!(
+                "{}/{:.1}/{}",
+                kind.min_size,
+                kind.bytes as f64 / kind.frames as f64,
+                kind.max_size
+            );
+            let interval = match &kind.interval {
+                Some(interval) => format!("{}/{}", interval.p50_ms, interval.p95_ms),
+                None => "-".to_string(),
+            };
+            out.push_str(&format!(
+                "{:<width$}  {:>7}  {:>9}  {:>5.1}%  {:>16}  {:>13}\n",
+                kind.kind,
+                kind.frames,
+                kind.bytes,
+                share(kind.bytes, self.bytes),
+                sizes,
+                interval,
+            ));
+        }
+
+        out.push_str("\nframe sizes\n");
+        let most = self
+            .sizes
+            .buckets
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+        for (upper, count) in &self.sizes.buckets {
+            let bar = (count * 40).checked_div(most).unwrap_or(0);
+            let line = format!(
+                "  {:>13}  {:>7}  {}",
+                bucket_label(*upper),
+                count,
+                "#".repeat(bar.max(usize::from(*count > 0)))
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+
+        for kind in self.kinds.iter().filter(|kind| !kind.fields.is_empty()) {
+            out.push_str(&format!("\n{} by field\n", kind.kind));
+            let rows: Vec<(String, &FieldStats)> = kind
+                .fields
+                .iter()
+                .map(|field| {
+                    let (depth, label) = field_label(&field.path);
+                    (format!("{}{}", "  ".repeat(depth), label), field)
+                })
+                .collect();
+            let width = rows
+                .iter()
+                .map(|(label, _)| label.len())
+                .max()
+                .unwrap_or(0)
+                .max("field".len());
+            out.push_str(&format!(
+                "  {:<width$}  {:>7}  {:>9}  {:>6}\n",
+                "field", "frames", "bytes", "share"
+            ));
+            for (label, field) in rows {
+                out.push_str(&format!(
+                    "  {:<width$}  {:>7}  {:>9}  {:>5.1}%\n",
+                    label,
+                    field.frames,
+                    field.bytes,
+                    share(field.bytes, kind.bytes)
+                ));
+            }
+        }
+
+        if let Some((field, talkers)) = &self.talkers {
+            out.push_str(&format!("\ntop talkers by {}\n", field));
+            let width = talkers
+                .iter()
+                .map(|talker| talker.value.len())
+                .max()
+                .unwrap_or(0)
+                .max("value".len());
+            out.push_str(&format!(
+                "  {:<width$}  {:>7}  {:>9}  {:>6}\n",
+                "value", "frames", "bytes", "share"
+            ));
+            for talker in talkers {
+                out.push_str(&format!(
+                    "  {:<width$}  {:>7}  {:>9}  {:>5.1}%\n",
+                    talker.value,
+                    talker.frames,
+                    talker.bytes,
+                    share(talker.bytes, self.bytes)
+                ));
+            }
+        }
+
+        if !self.errors.is_empty() {
+            out.push_str("\nframes that did not decode\n");
+            for (code, count) in &self.errors {
+                out.push_str(&format!("  {:<16}  {:>7}\n", code, count));
+            }
+        }
+        out
+    }
+
+    /// The statistics as a JSON document
+    pub fn to_json(&self) -> String {
+        let kinds: Vec<String> = self
+            .kinds
+            .iter()
+            .map(|kind| {
+                let fields: Vec<String> = kind
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        format!(
+                            "{{\"path\": {}, \"frames\": {}, \"bytes\": {}}}",
+                            string(&field.path),
+                            field.frames,
+                            field.bytes
+                        )
+                    })
+                    .collect();
+                format!(
+                    "    {{\"kind\": {}, \"frames\": {}, \"bytes\": {}, \"minSize\": {}, \"maxSize\": {}, \"sizes\": {}, \"interval\": {}, \"fields\": [{}]}}",
+                    string(&kind.kind),
+                    kind.frames,
+                    kind.bytes,
+                    kind.min_size,
+                    kind.max_size,
+                    kind.sizes.json(),
+                    timing_json(&kind.interval),
+                    fields.join(", ")
+                )
+            })
+            .collect();
+        let talkers = match &self.talkers {
+            Some((field, talkers)) => {
+                let talkers: Vec<String> = talkers
+                    .iter()
+                    .map(|talker| {
+                        format!(
+                            "{{\"value\": {}, \"frames\": {}, \"bytes\": {}}}",
+                            string(&talker.value),
+                            talker.frames,
+                            talker.bytes
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"field\": {}, \"top\": [{}]}}",
+                    string(field),
+                    talkers.join(", ")
+                )
+            }
+            None => "null".to_string(),
+        };
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|(code, count)| format!("{}: {}", string(code), count))
+            .collect();
+        format!(
+            "{{\n  \"type\": {},\n  \"frames\": {},\n  \"bytes\": {},\n  \"durationMs\": {},\n  \"sizes\": {},\n  \"interval\": {},\n  \"kinds\": [\n{}\n  ],\n  \"talkers\": {},\n  \"errors\": {{{}}}\n}}\n",
+            string(&self.type_name),
+            self.frames,
+            self.bytes,
+            self.duration_ms,
+            self.sizes.json(),
+            timing_json(&self.interval),
+            kinds.join(",\n"),
+            talkers,
+            errors.join(", ")
+        )
+    }
+}
+
+impl Histogram {
+    fn json(&self) -> String {
+        let buckets: Vec<String> = self
+            .buckets
+            .iter()
+            .map(|(upper, count)| format!("{{\"max\": {}, \"frames\": {}}}", upper, count))
+            .collect();
+        format!("[{}]", buckets.join(", "))
+    }
+}
+
+impl Timing {
+    fn summary(&self) -> String {
+        format!(
+            "min {} ms, mean {:.1} ms, p50 {} ms, p95 {} ms, max {} ms",
+            self.min_ms, self.mean_ms, self.p50_ms, self.p95_ms, self.max_ms
+        )
+    }
+}
+
+fn timing_json(timing: &Option<Timing>) -> String {
+    match timing {
+        Some(timing) => format!(
+            "{{\"minMs\": {}, \"meanMs\": {:.3}, \"p50Ms\": {}, \"p95Ms\": {}, \"maxMs\": {}}}",
+            timing.min_ms, timing.mean_ms, timing.p50_ms, timing.p95_ms, timing.max_ms
+        ),
+        None => "null".to_string(),
+    }
+}
+
+/// The bytes of each field of one kind, over its frames
+#[derive(Default)]
+struct FieldTally {
+    /// The index of each path in `fields`, in the order they were first read
+    index: HashMap<String, usize>,
+    fields: Vec<FieldStats>,
+}
+
+impl FieldTally {
+    /// Count the `parts` one frame was read as
+    fn add(&mut self, parts: Vec<(String, usize)>) {
+        let mut seen = HashSet::new();
+        for (path, bytes) in parts {
+            let i = *self.index.entry(path.clone()).or_insert_with(|| {
+                self.fields.push(FieldStats {
+                    path: path.clone(),
+                    frames: 0,
+                    bytes: 0,
+                });
+                self.fields.len() - 1
+            });
+            self.fields[i].bytes += bytes;
+            if seen.insert(path) {
+                self.fields[i].frames += 1;
+            }
+        }
+    }
+
+    /// The fields as a tree: each after its parent, siblings in the order
+    /// they were first read
+    fn stats(self) -> Vec<FieldStats> {
+        let key = |path: &str| -> Vec<usize> {
+            prefixes(path)
+                .map(|prefix| self.index.get(prefix).copied().unwrap_or(usize::MAX))
+                .collect()
+        };
+        let mut keyed: Vec<(Vec<usize>, FieldStats)> = self
+            .fields
+            .iter()
+            .map(|field| (key(&field.path), field.clone()))
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        keyed.into_iter().map(|(_, field)| field).collect()
+    }
+}
+
+/// `world`, `world.locations` and `world.locations::Town` for
+/// `world.locations::Town`
+fn prefixes(path: &str) -> impl Iterator<Item = &str> {
+    path.char_indices()
+        .filter(|&(i, ch)| ch == '.' || (path[i..].starts_with("::") && !path[..i].ends_with(':')))
+        .map(|(i, _)| &path[..i])
+        .filter(|prefix| !prefix.is_empty())
+        .chain([path])
+}
+
+/// How deep `path` is and its last part: `(1, "locations")` for
+/// `world.locations`, `(1, "::Move")` for `events::Move`
+fn field_label(path: &str) -> (usize, &str) {
+    let depth = prefixes(path).count() - 1;
+    let start = prefixes(path)
+        .nth(depth.saturating_sub(1))
+        .filter(|_| depth > 0)
+        .map_or(0, str::len);
+    (depth, path[start..].trim_start_matches('.'))
+}
+
+fn share(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// `0`, `1`, `2`, `3-4`, `5-8`, ...
+fn bucket_label(upper: usize) -> String {
+    if upper <= 2 {
+        return upper.to_string();
+    }
+    format!("{}-{}", upper / 2 + 1, upper)
+}
+
+/// Sizes by bucket: 0, 1, 2, 3-4, 5-8, ...
+fn histogram(sizes: impl Iterator<Item = usize>) -> Histogram {
+    let mut counts: BTreeMap<usize, usize> = BTreeMap::new();
+    for size in sizes {
+        *counts
+            .entry(size.next_power_of_two().max(size))
+            .or_default() += 1;
+    }
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Histogram::default();
+    };
+    let mut buckets = Vec::new();
+    let mut upper = first;
+    loop {
+        buckets.push((upper, counts.get(&upper).copied().unwrap_or(0)));
+        if upper >= last {
+            break;
+        }
+        upper = if upper == 0 { 1 } else { upper * 2 };
+    }
+    Histogram { buckets }
+}
+
+/// Intervals between consecutive times, ignoring clock steps backwards
+fn timing(times: impl Iterator<Item = u64>) -> Option<Timing> {
+    let times: Vec<u64> = times.collect();
+    let mut deltas: Vec<u64> = times
+        .windows(2)
+        .filter_map(|pair| pair[1].checked_sub(pair[0]))
+        .collect();
+    if deltas.is_empty() {
+        return None;
+    }
+    deltas.sort_unstable();
+    // Nearest rank
+    let percentile = |p: usize| deltas[(deltas.len() * p).div_ceil(100).max(1) - 1];
+    Some(Timing {
+        min_ms: deltas[0],
+        mean_ms: deltas.iter().sum::<u64>() as f64 / deltas.len() as f64,
+        p50_ms: percentile(50),
+        p95_ms: percentile(95),
+        max_ms: deltas[deltas.len() - 1],
+    })
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) data: &'a [u8],
+    pub(crate) pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("UNEXPECTED_END")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// An unsigned LEB128 varint of at most `bits` bits
+    pub(crate) fn varint(&mut self, bits: u32) -> Result<u128, &'static str> {
+        let mut value: u128 = 0;
+        for i in 0..bits.div_ceil(7) {
+            let byte = self.byte()?;
+            let shift = i * 7;
+            let part = u128::from(byte & 0x7f);
+            if shift + 7 > bits && part >> (bits - shift) != 0 {
+                return Err("BAD_VARINT");
+            }
+            value |= part << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("BAD_VARINT")
+    }
+
+    /// A zigzag-encoded signed varint, as text
+    fn signed(&mut self, bits: u32) -> Result<String, &'static str> {
+        let value = self.varint(bits)?;
+        let magnitude = (value >> 1) as i128;
+        Ok(if value & 1 == 0 {
+            magnitude.to_string()
+        } else {
+            (-magnitude - 1).to_string()
+        })
+    }
+
+    fn len(&mut self) -> Result<usize, &'static str> {
+        usize::try_from(self.varint(64)?).map_err(|_| "UNEXPECTED_END")
+    }
+
+    fn text(&mut self) -> Result<&'a str, &'static str> {
+        let len = self.len()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| "BAD_UTF8")
+    }
+}
+
+/// Walks a frame along its wire shape, noting its kind, talker value and the
+/// bytes of each field
+struct Decoder<'d, 'a> {
+    definitions: &'d HashMap<TypeName, &'d Definition>,
+    reader: Reader<'a>,
+    talker: Option<&'d str>,
+    kind: String,
+    talker_value: Option<String>,
+    /// The path of the field being read, as in [`FieldStats`]
+    part: String,
+    /// The path and bytes of each field read, fields before their parents
+    parts: Vec<(String, usize)>,
+}
+
+impl Decoder<'_, '_> {
+    /// Read the field or variant `name` of the part being read with `read`,
+    /// noting the bytes it takes up
+    fn part<T>(
+        &mut self,
+        name: &str,
+        read: impl FnOnce(&mut Self) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        let outer = self.part.len();
+        if outer > 0 && !name.starts_with("::") {
+            self.part.push('.');
+        }
+        self.part.push_str(name);
+        let start = self.reader.pos;
+        let result = read(self);
+        if result.is_ok() {
+            self.parts
+                .push((self.part.clone(), self.reader.pos - start));
+        }
+        self.part.truncate(outer);
+        result
+    }
+
+    /// Read a value of `shape` at `path`, returning primitives as text
+    fn value(&mut self, shape: &Shape, path: &str) -> Result<Option<String>, &'static str> {
+        let text = match shape {
+            Shape::Bool => match self.reader.byte()? {
+                0 => Some("false".to_string()),
+                1 => Some("true".to_string()),
+                _ => return Err("BAD_BOOL"),
+            },
+            Shape::U8 => Some(self.reader.byte()?.to_string()),
+            Shape::I8 => Some((self.reader.byte()? as i8).to_string()),
+            Shape::U16 => Some(self.reader.varint(16)?.to_string()),
+            Shape::U32 => Some(self.reader.varint(32)?.to_string()),
+            Shape::U64 => Some(self.reader.varint(64)?.to_string()),
+            Shape::U128 => Some(self.reader.varint(128)?.to_string()),
+            Shape::I16 => Some(self.reader.signed(16)?),
+            Shape::I32 => Some(self.reader.signed(32)?),
+            Shape::I64 => Some(self.reader.signed(64)?),
+            Shape::I128 => Some(self.reader.signed(128)?),
+            Shape::F32 => {
+                let bytes = self.reader.take(4)?;
+                Some(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).to_string())
+            }
+            Shape::F64 => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.reader.take(8)?);
+                Some(f64::from_le_bytes(bytes).to_string())
+            }
+            Shape::Char => {
+                let text = self.reader.text()?;
+                if text.chars().count() != 1 {
+                    return Err("BAD_CHAR");
+                }
+                Some(text.to_string())
+            }
+            Shape::String | Shape::BoundedString(_) => Some(self.reader.text()?.to_string()),
+            Shape::Bytes => {
+                let len = self.reader.len()?;
+                self.reader.take(len)?;
+                None
+            }
+            Shape::Unit | Shape::Param(_) => None,
+            Shape::Option(inner) => match self.reader.byte()? {
+                0 => Some("None".to_string()),
+                1 => self.value(inner, path)?,
+                _ => return Err("BAD_OPTION"),
+            },
+            Shape::Seq(item) | Shape::BoundedSeq(item, _) => {
+                let len = self.reader.len()?;
+                for i in 0..len {
+                    self.value(item, &join(path, &i.to_string()))?;
+                }
+                None
+            }
+            Shape::Tuple(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let index = i.to_string();
+                    self.part(&index, |d| d.value(item, &join(path, &index)))?;
+                }
+                None
+            }
+            Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+                let len = self.reader.len()?;
+                for i in 0..len {
+                    let entry = join(path, &i.to_string());
+                    self.value(key, &entry)?;
+                    self.value(value, &entry)?;
+                }
+                None
+            }
+            Shape::Named(type_name) | Shape::Generic(type_name, _) => {
+                let definition = self.definitions.get(type_name).ok_or("UNSUPPORTED")?;
+                let definition = match shape {
+                    Shape::Generic(_, args) => {
+                        let bindings: HashMap<&str, &Shape> =
+                            type_name.params.iter().copied().zip(args).collect();
+                        substitute_definition(definition, &bindings)
+                    }
+                    _ => (*definition).clone(),
+                };
+                self.definition(&definition, path)?
+            }
+        };
+        if let (Some(talker), Some(text)) = (self.talker, &text) {
+            if talker == path && self.talker_value.is_none() {
+                self.talker_value = Some(text.clone());
+            }
+        }
+        Ok(text)
+    }
+
+    fn definition(
+        &mut self,
+        definition: &Definition,
+        path: &str,
+    ) -> Result<Option<String>, &'static str> {
+        match definition {
+            Definition::Struct(fields) => {
+                for field in fields {
+                    self.part(field.name, |d| {
+                        d.value(&field.shape, &join(path, field.name))
+                    })?;
+                }
+                Ok(None)
+            }
+            Definition::TupleStruct(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let index = i.to_string();
+                    self.part(&index, |d| d.value(item, &join(path, &index)))?;
+                }
+                Ok(None)
+            }
+            Definition::NewtypeStruct(inner) => self.value(inner, path),
+            Definition::UnitStruct => Ok(None),
+            Definition::Enum(variants) => {
+                let start = self.reader.pos;
+                let index = self.reader.varint(32)?;
+                let variant = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| variants.get(index))
+                    .ok_or("INVALID_VALUE")?;
+                // Variants of the message itself, or of enums it wraps directly
+                if path.is_empty() {
+                    self.kind.push_str("::");
+                    self.kind.push_str(variant.name);
+                    return self.variant(&variant.kind, variant.name, path);
+                }
+                // The variant's bytes include its index
+                let index_len = self.reader.pos - start;
+                self.reader.pos = start;
+                self.part(&format!("::{}", variant.name), |d| {
+                    d.reader.pos += index_len;
+                    d.variant(&variant.kind, variant.name, path)
+                })
+            }
+        }
+    }
+
+    /// Read the content of the variant `name`, of `kind`
+    fn variant(
+        &mut self,
+        kind: &VariantKind,
+        name: &str,
+        path: &str,
+    ) -> Result<Option<String>, &'static str> {
+        match kind {
+            VariantKind::Unit => Ok(Some(name.to_string())),
+            VariantKind::Newtype(inner) => {
+                self.value(inner, path)?;
+                Ok(None)
+            }
+            VariantKind::Tuple(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    let index = i.to_string();
+                    self.part(&index, |d| d.value(item, &join(path, &index)))?;
+                }
+                Ok(None)
+            }
+            VariantKind::Struct(fields) => {
+                for field in fields {
+                    self.part(field.name, |d| {
+                        d.value(&field.shape, &join(path, field.name))
+                    })?;
+                }
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! CRC parameters for [`Generator::crc`](crate::Generator::crc)
+//!
+//! The constants mirror the `crc` crate's catalog, so the generated wrappers
+//! check the same checksum as `Crc::<u32>::new(&CRC_32_ISCSI)` on the Rust
+//! side; any other algorithm can be described by its parameters.
+
+/// Parameters of a CRC, named as in the `crc` crate's `Algorithm`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CrcAlgorithm {
+    /// Bits in the checksum, 1 to 64
+    pub width: u8,
+    pub poly: u64,
+    pub init: u64,
+    pub refin: bool,
+    pub refout: bool,
+    pub xorout: u64,
+    /// The checksum of `b"123456789"`
+    pub check: u64,
+}
+
+pub const CRC_8_SMBUS: CrcAlgorithm = CrcAlgorithm {
+    width: 8,
+    poly: 0x07,
+    init: 0x00,
+    refin: false,
+    refout: false,
+    xorout: 0x00,
+    check: 0xf4,
+};
+
+pub const CRC_8_MAXIM_DOW: CrcAlgorithm = CrcAlgorithm {
+    width: 8,
+    poly: 0x31,
+    init: 0x00,
+    refin: true,
+    refout: true,
+    xorout: 0x00,
+    check: 0xa1,
+};
+
+pub const CRC_16_ARC: CrcAlgorithm = CrcAlgorithm {
+    width: 16,
+    poly: 0x8005,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0xbb3d,
+};
+
+pub const CRC_16_IBM_SDLC: CrcAlgorithm = CrcAlgorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0xffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffff,
+    check: 0x906e,
+};
+
+pub const CRC_16_KERMIT: CrcAlgorithm = CrcAlgorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x2189,
+};
+
+pub const CRC_16_MODBUS: CrcAlgorithm = CrcAlgorithm {
+    width: 16,
+    poly: 0x8005,
+    init: 0xffff,
+    refin: true,
+    refout: true,
+    xorout: 0x0000,
+    check: 0x4b37,
+};
+
+pub const CRC_16_XMODEM: CrcAlgorithm = CrcAlgorithm {
+    width: 16,
+    poly: 0x1021,
+    init: 0x0000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000,
+    check: 0x31c3,
+};
+
+pub const CRC_32_BZIP2: CrcAlgorithm = CrcAlgorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: false,
+    refout: false,
+    xorout: 0xffffffff,
+    check: 0xfc891918,
+};
+
+pub const CRC_32_ISCSI: CrcAlgorithm = CrcAlgorithm {
+    width: 32,
+    poly: 0x1edc6f41,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+    check: 0xe3069283,
+};
+
+pub const CRC_32_ISO_HDLC: CrcAlgorithm = CrcAlgorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffff,
+    check: 0xcbf43926,
+};
+
+pub const CRC_32_MPEG_2: CrcAlgorithm = CrcAlgorithm {
+    width: 32,
+    poly: 0x04c11db7,
+    init: 0xffffffff,
+    refin: false,
+    refout: false,
+    xorout: 0x00000000,
+    check: 0x0376e6e7,
+};
+
+pub const CRC_64_ECMA_182: CrcAlgorithm = CrcAlgorithm {
+    width: 64,
+    poly: 0x42f0e1eba9ea3693,
+    init: 0x0000000000000000,
+    refin: false,
+    refout: false,
+    xorout: 0x0000000000000000,
+    check: 0x6c40df5f0b497347,
+};
+
+pub const CRC_64_XZ: CrcAlgorithm = CrcAlgorithm {
+    width: 64,
+    poly: 0x42f0e1eba9ea3693,
+    init: 0xffffffffffffffff,
+    refin: true,
+    refout: true,
+    xorout: 0xffffffffffffffff,
+    check: 0x995dc9bbdf1939fa,
+};
+
+/// The catalog constants, by name
+pub const CATALOG: &[(&str, CrcAlgorithm)] = &[
+    ("CRC_8_SMBUS", CRC_8_SMBUS),
+    ("CRC_8_MAXIM_DOW", CRC_8_MAXIM_DOW),
+    ("CRC_16_ARC", CRC_16_ARC),
+    ("CRC_16_IBM_SDLC", CRC_16_IBM_SDLC),
+    ("CRC_16_KERMIT", CRC_16_KERMIT),
+    ("CRC_16_MODBUS", CRC_16_MODBUS),
+    ("CRC_16_XMODEM", CRC_16_XMODEM),
+    ("CRC_32_BZIP2", CRC_32_BZIP2),
+    ("CRC_32_ISCSI", CRC_32_ISCSI),
+    ("CRC_32_ISO_HDLC", CRC_32_ISO_HDLC),
+    ("CRC_32_MPEG_2", CRC_32_MPEG_2),
+    ("CRC_64_ECMA_182", CRC_64_ECMA_182),
+    ("CRC_64_XZ", CRC_64_XZ),
+];
+
+impl CrcAlgorithm {
+    /// The catalog constant named `name`, e.g. `"CRC_32_ISCSI"`
+    pub fn named(name: &str) -> Option<Self> {
+        CATALOG
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, algorithm)| *algorithm)
+    }
+
+    /// The name of this algorithm in the catalog, if it is one of the constants
+    pub fn name(&self) -> Option<&'static str> {
+        CATALOG
+            .iter()
+            .find(|(_, algorithm)| algorithm == self)
+            .map(|(name, _)| *name)
+    }
+
+    /// The algorithm as a TypeScript `CrcAlgorithm`: the runtime's constant
+    /// when it is in the catalog, an object literal otherwise
+    ///
+    /// Parameters of CRCs wider than 32 bits are bigints, which hold them exactly.
+    pub(crate) fn typescript(&self) -> String {
+        if let Some(name) = self.name() {
+            return name.to_string();
+        }
+        let number = |value: u64| {
+            if self.width > 32 {
+                format!("0x{:x}n", value)
+            } else {
+                format!("0x{:x}", value)
+            }
+        };
+        format!(
+            "{{ width: {}, poly: {}, init: {}, refin: {}, refout: {}, xorout: {}, check: {} }}",
+            self.width,
+            number(self.poly),
+            number(self.init),
+            self.refin,
+            self.refout,
+            number(self.xorout),
+            number(self.check)
+        )
+    }
+}
@@ -0,0 +1,248 @@
+//! Output of `postcard-ts generate --message-format json`
+//!
+//! One JSON object per line, for build systems and editor plugins to present
+//! as their own diagnostics rather than parse the human-readable text:
+//!
+//! ```json
+//! {"kind": "diagnostic", "version": 1, "code": "bigint_id", "severity": "warning", "message": "Player.id is a u64, ...", "span": {"file": "src/lib.rs", "line": 12, "column": 8}, "fix": "make the id a u32, or allow bigint_id if it needs 64 bits"}
+//! {"kind": "written", "version": 1, "written": ["web/src/generated/index.ts"], "unchanged": [], "removed": []}
+//! ```
+//!
+//! Every record has every field: `span` (1-based, pointing at the type's
+//! name) and `fix` (what would make the diagnostic go away) are `null` when
+//! unknown. `code` is a lint (see [`LINTS`](crate::LINTS)), a
+//! [`GenerateError::code`], or from the CLI `rustc`, `io` or `cli`. Records
+//! of a [`MESSAGE_FORMAT_VERSION`] only ever gain fields; a changed meaning
+//! gets a new version.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::generate::{GenerateError, Generator, Layout};
+use crate::ir::string;
+use crate::lint::{self, lint_types_with, Lint, LintConfig, LintLevel, Span};
+use crate::output::{write_files, WriteOptions, WriteReport};
+use crate::shape::{Definition, TypeName};
+
+/// Version of the records [`Diagnostic::to_json`] and [`generate`] write
+pub const MESSAGE_FORMAT_VERSION: u32 = 1;
+
+/// How bad a [`Diagnostic`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// Nothing was written
+    Error,
+    /// The files were written, but something in them is likely a mistake
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// One warning or error of generation, as a record of `--message-format json`
+///
+/// ```
+/// use serde_postcard_ts::{Diagnostic, GenerateError};
+///
+/// let error = GenerateError::FloatKey { name: "Calibration" };
+/// let diagnostic = Diagnostic::error(&error);
+/// assert_eq!(diagnostic.code, "float_key");
+/// assert!(diagnostic.to_json().starts_with(
+///     r#"{"kind": "diagnostic", "version": 1, "code": "float_key", "severity": "error""#
+/// ));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Stable identifier of the check or error, e.g. `bigint_id`
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Where the cause is, if known
+    pub span: Option<Span>,
+    /// What would make the diagnostic go away, if there is an obvious way
+    pub fix: Option<String>,
+}
+
+impl Diagnostic {
+    /// The diagnostic of a type the generator cannot handle
+    pub fn error(error: &GenerateError) -> Self {
+        Diagnostic {
+            code: error.code(),
+            severity: Severity::Error,
+            message: error.to_string(),
+            span: None,
+            fix: fix(error),
+        }
+    }
+
+    /// The diagnostic of a lint, an error if it is denied
+    pub fn lint(lint: &Lint) -> Self {
+        let fix = match lint.code {
+            "optional_seq" => "make all of the lists optional, or none of them",
+            "bigint_id" => "make the id a u32, or allow bigint_id if it needs 64 bits",
+            "string_map" => "replace the map with a struct if its keys are a fixed set",
+            "deep_nesting" => "flatten the type, or raise max_depth in the lints config",
+            _ => "",
+        };
+        Diagnostic {
+            code: lint.code,
+            severity: match lint.level {
+                LintLevel::Deny => Severity::Error,
+                LintLevel::Allow | LintLevel::Warn => Severity::Warning,
+            },
+            message: lint.message.clone(),
+            span: lint.span.clone(),
+            fix: Some(fix.to_string()).filter(|fix| !fix.is_empty()),
+        }
+    }
+
+    /// The diagnostic as one line of JSON
+    pub fn to_json(&self) -> String {
+        let span = match &self.span {
+            Some(span) => format!(
+                "{{\"file\": {}, \"line\": {}, \"column\": {}}}",
+                string(&span.file.display().to_string()),
+                span.line,
+                span.column
+            ),
+            None => "null".to_string(),
+        };
+        let fix = match &self.fix {
+            Some(fix) => string(fix),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"kind\": \"diagnostic\", \"version\": {}, \"code\": {}, \"severity\": \"{}\", \"message\": {}, \"span\": {}, \"fix\": {}}}",
+            MESSAGE_FORMAT_VERSION,
+            string(self.code),
+            self.severity.as_str(),
+            string(&self.message),
+            span,
+            fix
+        )
+    }
+}
+
+/// Generate and write `types` as [`write_files`] would, linting as `lints`
+/// says, returning the records to print
+///
+/// `Err` when generating or writing failed, or a lint is denied (before
+/// writing anything), the records ending with the diagnostics saying why.
+pub fn generate(
+    generator: &Generator,
+    types: Vec<(TypeName, Definition)>,
+    layout: Layout,
+    out: &Path,
+    options: WriteOptions,
+    lints: &LintConfig,
+) -> Result<Vec<String>, Vec<String>> {
+    let files = generator
+        .generate_types_files(types.clone(), layout)
+        .map_err(|error| vec![Diagnostic::error(&error).to_json()])?;
+    let mut found = lint_types_with(&types, lints);
+    lint::locate(&mut found);
+    let mut records: Vec<String> = found
+        .iter()
+        .map(|lint| Diagnostic::lint(lint).to_json())
+        .collect();
+    if found.iter().any(|lint| lint.level == LintLevel::Deny) {
+        return Err(records);
+    }
+    match write_files(out, &files, options) {
+        Ok(report) => {
+            records.push(written_record(&report));
+            Ok(records)
+        }
+        Err(error) => {
+            records.push(io_diagnostic(&error).to_json());
+            Err(records)
+        }
+    }
+}
+
+/// How to fix `error`, when the message does not already say
+fn fix(error: &GenerateError) -> Option<String> {
+    let fix = match error {
+        GenerateError::DuplicateName { .. } => "rename one of the types".to_string(),
+        GenerateError::Missing { name, .. } => format!(
+            "derive PostcardTs for {}, or declare it with Generator::override_type",
+            name
+        ),
+        GenerateError::Recursive(_) => {
+            "make a type on the path non-generic to break the cycle".to_string()
+        }
+        GenerateError::ModuleCycle(_) => {
+            "move the shared types to a module of their own, or use another layout".to_string()
+        }
+        GenerateError::TagClash { .. } => {
+            "rename the property, or tag enums with another name".to_string()
+        }
+        GenerateError::FloatKey { .. } => {
+            "key the map by the floats' bits, or make it a list of pairs".to_string()
+        }
+        GenerateError::GroupOverlap { name, .. } => {
+            format!("narrow the patterns so that one group matches {}", name)
+        }
+        GenerateError::UnmatchedGroupPattern { .. }
+        | GenerateError::UnmatchedDirectionPattern(_) => {
+            "correct the pattern, or remove it".to_string()
+        }
+        GenerateError::GroupCycle(_) => {
+            "move the shared types to one of the groups, or leave them out of both".to_string()
+        }
+        GenerateError::Unbounded { .. } => {
+            "bound it with heapless collections or #[postcard_ts(max_len = N)]".to_string()
+        }
+        GenerateError::DirectionConflict { .. } => {
+            "mark it encode-only or decode-only, not both".to_string()
+        }
+        GenerateError::WasmWireShape { .. } => {
+            "declare its wire shape with Generator::override_type instead".to_string()
+        }
+        GenerateError::NameCollision { .. } => {
+            "change the naming templates so that each export gets a name of its own".to_string()
+        }
+        GenerateError::InvalidGroupName(_)
+        | GenerateError::InvalidSimulatorName(_)
+        | GenerateError::InvalidSimulator { .. }
+        | GenerateError::InvalidOverride { .. }
+        | GenerateError::UnmatchedExample(_)
+        | GenerateError::InvalidConstant { .. }
+        | GenerateError::InvalidFeature { .. }
+        | GenerateError::InvalidNaming { .. } => return None,
+    };
+    Some(fix)
+}
+
+fn io_diagnostic(error: &io::Error) -> Diagnostic {
+    Diagnostic {
+        code: "io",
+        severity: Severity::Error,
+        message: error.to_string(),
+        span: None,
+        fix: None,
+    }
+}
+
+fn written_record(report: &WriteReport) -> String {
+    let paths = |paths: &[PathBuf]| {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| string(&path.display().to_string()))
+            .collect();
+        format!("[{}]", paths.join(", "))
+    };
+    format!(
+        "{{\"kind\": \"written\", \"version\": {}, \"written\": {}, \"unchanged\": {}, \"removed\": {}}}",
+        MESSAGE_FORMAT_VERSION,
+        paths(&report.written),
+        paths(&report.unchanged),
+        paths(&report.removed)
+    )
+}
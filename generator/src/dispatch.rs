@@ -0,0 +1,88 @@
+//! Typed message dispatchers for the enums a link carries
+//!
+//! With [`Generator::dispatchers`](crate::Generator::dispatchers), layouts
+//! that write files also write `dispatchers.ts`, declaring a subclass of the
+//! runtime's `MessageDispatcher` for each top-level enum: a non-generic enum
+//! that no other type contains and that is decoded, such as the `GameEvent`
+//! a game server sends. It splits a stream into length-prefixed (or COBS)
+//! frames and calls the handlers of each message's variant:
+//!
+//! ```typescript
+//! const events = new GameEventDispatcher();
+//! events.on("EnemySpawn", (event) => spawn(event.value.enemy_type, event.value.count));
+//! socket.onmessage = (event) => events.push(new Uint8Array(event.data));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::generate::{references, GeneratedFile};
+use crate::naming::{Ident, Naming};
+use crate::registry::Direction;
+use crate::shape::{Definition, TypeName};
+
+/// `dispatchers.ts`, starting with `header` and importing the schemas from
+/// `index` and the runtime from `import_from`; `None` without top-level enums
+pub(crate) fn render(
+    types: &[(TypeName, Definition)],
+    directions: &HashMap<TypeName, Direction>,
+    enum_tag: Option<&str>,
+    naming: &Naming,
+    header: &str,
+    import_from: &str,
+    index: &str,
+) -> Option<GeneratedFile> {
+    let contained: HashSet<TypeName> = types
+        .iter()
+        .flat_map(|(_, definition)| references(definition))
+        .collect();
+    let enums: Vec<(&TypeName, Vec<&str>)> = types
+        .iter()
+        .filter_map(|(type_name, definition)| match definition {
+            Definition::Enum(variants)
+                if type_name.params.is_empty()
+                    && !contained.contains(type_name)
+                    && directions.get(type_name) != Some(&Direction::EncodeOnly) =>
+            {
+                Some((type_name, variants.iter().map(|v| v.name).collect()))
+            }
+            _ => None,
+        })
+        .collect();
+    if enums.is_empty() {
+        return None;
+    }
+
+    let tag = enum_tag.unwrap_or("type");
+    let mut source = format!(
+        "{header}\n\
+         import {{ MessageDispatcher, type MessageDispatcherOptions }} from \"{import_from}\";\n\
+         import {{\n"
+    );
+    for (type_name, _) in &enums {
+        source.push_str(&format!("  {},\n", naming.schema(type_name.name)));
+    }
+    source.push_str(&format!("}} from \"{index}\";\n"));
+
+    for (type_name, variants) in &enums {
+        let name = type_name.name;
+        let schema = naming.schema(name);
+        let dispatcher = naming.ident(Ident::Dispatcher, name);
+        source.push_str(&format!(
+            "\n\
+             /**\n\
+             \x20* Routes {name} messages from a stream to handlers by variant: {}\n\
+             \x20*/\n\
+             export class {dispatcher} extends MessageDispatcher<typeof {schema}, {tag:?}> {{\n\
+             \x20 constructor(options: Omit<MessageDispatcherOptions<{tag:?}>, \"tag\"> = {{}}) {{\n\
+             \x20   super({schema}, {{ ...options, tag: {tag:?} }});\n\
+             \x20 }}\n\
+             }}\n",
+            variants.join(", "),
+        ));
+    }
+
+    Some(GeneratedFile {
+        path: "dispatchers.ts".to_string(),
+        source,
+    })
+}
@@ -0,0 +1,127 @@
+//! Doc comments of registered types, for the JSDoc on the generated code, and
+//! the display names of their variants
+
+/// The doc comments of a type registered with `#[derive(PostcardTs)]`, and of
+/// its fields and variants
+///
+/// Only the named fields of structs and struct variants have docs of their
+/// own: TypeScript has nowhere to put the docs of tuple items.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Docs {
+    pub doc: Doc,
+    /// The documented fields of a struct, by serde name
+    pub fields: &'static [(&'static str, Doc)],
+    /// The documented variants of an enum, or variants with documented
+    /// fields or a display name, by serde name
+    pub variants: &'static [VariantDocs],
+}
+
+/// The doc comments of an enum variant and its fields
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VariantDocs {
+    pub name: &'static str,
+    pub doc: Doc,
+    /// The documented fields of a struct variant, by serde name
+    pub fields: &'static [(&'static str, Doc)],
+    /// The name shown to people, `#[postcard_ts(display = "...")]`
+    pub display: Option<&'static str>,
+}
+
+/// One doc comment, and the note of `#[deprecated]`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Doc {
+    /// The lines of the comment, without the `///` and the indentation
+    /// common to them
+    pub lines: &'static [&'static str],
+    /// `Some` for `#[deprecated]`, with its note and `since` (empty without
+    /// either)
+    pub deprecated: Option<&'static str>,
+}
+
+impl Docs {
+    /// The docs of the field `name` of a struct
+    pub(crate) fn field(&self, name: &str) -> Doc {
+        find(self.fields, name)
+    }
+
+    /// The docs of the variant `name` of an enum
+    pub(crate) fn variant(&self, name: &str) -> VariantDocs {
+        self.variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl VariantDocs {
+    /// The docs of the field `name` of the variant
+    pub(crate) fn field(&self, name: &str) -> Doc {
+        find(self.fields, name)
+    }
+}
+
+impl Doc {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.deprecated.is_none()
+    }
+
+    /// The lines of the JSDoc comment: the doc comment, then `@deprecated`
+    pub(crate) fn comment_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().map(|line| escape(line)).collect();
+        if let Some(note) = self.deprecated {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(deprecated(note));
+        }
+        lines
+    }
+
+    /// The JSDoc comment before a member indented by `indent`, or nothing
+    pub(crate) fn jsdoc(&self, indent: &str) -> String {
+        let lines = self.comment_lines();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        match lines.as_slice() {
+            [] => String::new(),
+            lines => jsdoc(lines, indent),
+        }
+    }
+}
+
+/// The `@deprecated` tag of `#[deprecated]` with `note`
+pub(crate) fn deprecated(note: &str) -> String {
+    match note {
+        "" => "@deprecated".to_string(),
+        note => format!("@deprecated {}", escape(note)),
+    }
+}
+
+/// A JSDoc comment of `lines` indented by `indent`, on one line if there is
+/// only one
+pub(crate) fn jsdoc(lines: &[&str], indent: &str) -> String {
+    if let [line] = lines {
+        return format!("{indent}/** {line} */\n");
+    }
+    let mut comment = format!("{indent}/**\n");
+    for line in lines {
+        match *line {
+            "" => comment.push_str(&format!("{indent} *\n")),
+            line => comment.push_str(&format!("{indent} * {line}\n")),
+        }
+    }
+    comment.push_str(&format!("{indent} */\n"));
+    comment
+}
+
+fn find(docs: &[(&str, Doc)], name: &str) -> Doc {
+    docs.iter()
+        .find(|(documented, _)| *documented == name)
+        .map(|(_, doc)| *doc)
+        .unwrap_or_default()
+}
+
+/// `line` without a `*/` closing the comment early
+fn escape(line: &str) -> String {
+    line.replace("*/", "*\\/")
+}
@@ -0,0 +1,212 @@
+//! Feature negotiation for mixed fleets
+//!
+//! Devices in the field run whatever firmware they were last updated to, so
+//! a dashboard talking to all of them must not send a message one of them
+//! cannot parse. A [`ProtocolFeature`] names a capability, gives it a bit in
+//! a `u64` capability mask, and lists the messages that need it: types
+//! (`FirmwareChunk`) or enum variants (`Command::Update`). A device sends its
+//! mask when it connects, built from the constants of
+//! [`Generator::generate_rust_constants`](crate::Generator::generate_rust_constants):
+//!
+//! ```text
+//! pub const FEATURE_FIRMWARE_UPDATE: u64 = 1 << 0;
+//! ```
+//!
+//! The generated TypeScript declares the same features as
+//! `PROTOCOL_FEATURES`, which the runtime's `FeatureSet` reads the mask
+//! with: `hasFeature("firmware_update")` is checked against the names, and
+//! its `trySerialize` refuses messages the device lacks a feature for.
+
+use std::collections::HashSet;
+
+use crate::docs;
+use crate::generate::GenerateError;
+use crate::ir;
+use crate::shape::{Definition, TypeName};
+
+/// The name of the TypeScript constant declaring the features
+const FEATURES_CONSTANT: &str = "PROTOCOL_FEATURES";
+
+/// A capability a peer may have, and the messages needing it
+///
+/// ```
+/// use serde_postcard_ts::ProtocolFeature;
+///
+/// let update = ProtocolFeature::new("firmware_update", 0)
+///     .messages(["Command::Update", "FirmwareChunk"])
+///     .doc("Accepts firmware over the air");
+/// assert_eq!(update.name(), "firmware_update");
+/// assert_eq!(update.mask(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolFeature {
+    name: String,
+    bit: u32,
+    messages: Vec<String>,
+    doc: Option<String>,
+}
+
+impl ProtocolFeature {
+    /// A feature named `name`, lowercase letters, digits and `_`, starting
+    /// with a letter, at `bit` (0 to 63) of the capability mask
+    pub fn new(name: impl Into<String>, bit: u32) -> Self {
+        ProtocolFeature {
+            name: name.into(),
+            bit,
+            messages: Vec::new(),
+            doc: None,
+        }
+    }
+
+    /// Add messages only a peer with the feature can parse: types by name
+    /// (`FirmwareChunk`) or variants of enums (`Command::Update`)
+    pub fn messages<I>(mut self, messages: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.messages.extend(messages.into_iter().map(Into::into));
+        self
+    }
+
+    /// A doc comment for both declarations
+    pub fn doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn bit(&self) -> u32 {
+        self.bit
+    }
+
+    /// The feature's bit in the capability mask; 0 for a bit above 63
+    pub fn mask(&self) -> u64 {
+        1u64.checked_shl(self.bit).unwrap_or(0)
+    }
+
+    fn doc_lines(&self) -> Vec<&str> {
+        self.doc
+            .as_deref()
+            .map_or_else(Vec::new, |doc| doc.lines().collect())
+    }
+
+    /// The feature's entry in the TypeScript declaration
+    fn typescript(&self) -> String {
+        let doc = match self.doc_lines().as_slice() {
+            [] => String::new(),
+            lines => docs::jsdoc(lines, "  "),
+        };
+        let messages: Vec<String> = self.messages.iter().map(|m| ir::string(m)).collect();
+        format!(
+            "{doc}  {}: {{ bit: {}, messages: [{}] }},\n",
+            self.name,
+            self.bit,
+            messages.join(", ")
+        )
+    }
+
+    /// The Rust constant of the feature's mask
+    fn rust(&self) -> String {
+        let doc: String = self
+            .doc_lines()
+            .iter()
+            .map(|line| match *line {
+                "" => "///\n".to_string(),
+                line => format!("/// {}\n", line),
+            })
+            .collect();
+        format!(
+            "{doc}pub const FEATURE_{}: u64 = 1 << {};\n",
+            self.name.to_ascii_uppercase(),
+            self.bit
+        )
+    }
+
+    fn invalid(&self, reason: String) -> GenerateError {
+        GenerateError::InvalidFeature {
+            name: self.name.clone(),
+            reason,
+        }
+    }
+}
+
+/// Fail on a feature named other than a lowercase identifier, at a bit
+/// outside the mask, or sharing its name or bit with an earlier one
+pub(crate) fn check(features: &[ProtocolFeature]) -> Result<(), GenerateError> {
+    let mut names = HashSet::new();
+    let mut bits = HashSet::new();
+    for feature in features {
+        let name = &feature.name;
+        let valid = name.starts_with(|ch: char| ch.is_ascii_lowercase())
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_');
+        let reason = if !valid {
+            "use lowercase letters, digits and _, starting with a letter".to_string()
+        } else if feature.bit > 63 {
+            format!("bit {} is outside the 64-bit mask", feature.bit)
+        } else if !names.insert(name.as_str()) {
+            "it is declared twice".to_string()
+        } else if !bits.insert(feature.bit) {
+            format!("bit {} is taken by another feature", feature.bit)
+        } else {
+            continue;
+        };
+        return Err(feature.invalid(reason));
+    }
+    Ok(())
+}
+
+/// Fail on a message naming neither one of `types` nor a variant of one of
+/// its enums
+pub(crate) fn check_messages(
+    features: &[ProtocolFeature],
+    types: &[(TypeName, Definition)],
+) -> Result<(), GenerateError> {
+    for feature in features {
+        for message in &feature.messages {
+            let known = match message.split_once("::") {
+                None => types.iter().any(|(type_name, _)| type_name.name == message),
+                Some((enum_name, variant)) => types.iter().any(|(type_name, definition)| {
+                    type_name.name == enum_name
+                        && matches!(definition, Definition::Enum(variants)
+                            if variants.iter().any(|v| v.name == variant))
+                }),
+            };
+            if !known {
+                return Err(
+                    feature.invalid(format!("message {} names no type or enum variant", message))
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The TypeScript declaration of `features`, or nothing without any
+pub(crate) fn typescript(features: &[ProtocolFeature]) -> String {
+    if features.is_empty() {
+        return String::new();
+    }
+    let entries: String = features.iter().map(ProtocolFeature::typescript).collect();
+    format!(
+        "/**\n \
+         * The features a peer may have: the bit of each in its capability mask\n \
+         * and the messages needing it, for `FeatureSet`\n \
+         */\n\
+         export const {} = {{\n{}}} as const;\n",
+        FEATURES_CONSTANT, entries
+    )
+}
+
+/// The Rust constants of the features' masks, each after a blank line
+pub(crate) fn rust(features: &[ProtocolFeature]) -> String {
+    features
+        .iter()
+        .map(|feature| format!("\n{}", feature.rust()))
+        .collect()
+}
@@ -0,0 +1,3418 @@
+//! TypeScript emission
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::builtin::{self, Representations, TimeRepresentation, UuidRepresentation};
+use crate::crc::CrcAlgorithm;
+use crate::docs::{Docs, VariantDocs};
+use crate::features::{self, ProtocolFeature};
+use crate::groups::{self, GroupCheck, ProtocolGroup};
+use crate::naming::{Ident, Naming};
+use crate::protocol_constants::{self, ProtocolConstant};
+use crate::registry::Direction;
+use crate::shape::{Definition, Field, PostcardTs, Shape, TypeName, Variant, VariantKind};
+use crate::simulator::{self, Simulator};
+use crate::ts_migration::{self, TsMigration};
+use crate::ts_override::TsOverride;
+use crate::typedoc::{self, Facts, TypeDoc};
+use crate::validator::{Validator, Zod};
+use crate::wasm::WasmBackend;
+use crate::{dispatch, ir, loaders, max_size, registry, schema_hash, wire_layout};
+
+/// Module the generated code imports the schema builders from by default
+pub const DEFAULT_IMPORT: &str = "@variegated-coffee/serde-postcard-ts";
+
+/// First line of every generated file; also how generated files are recognised
+pub(crate) const HEADER: &str =
+    "// Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.\n";
+
+const RUST_CONSTANTS_HEADER: &str =
+    "// Generated by serde-postcard-ts from the protocol constants; do not edit.\n";
+
+/// How the generated schemas are split into files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Every type in `index.ts`
+    #[default]
+    Single,
+    /// One file per Rust module (`my_crate::proto` in `my_crate/proto.ts`),
+    /// importing from each other, plus an `index.ts` re-exporting them all
+    Modules,
+    /// One file per protocol group ([`Generator::group`], `telemetry` in
+    /// `telemetry.ts`) with its version and hash constants, the types in no
+    /// group in `common.ts`, plus an `index.ts` re-exporting them all
+    Groups,
+    /// One file per type (`Reading` in `types/Reading.ts`), importing from
+    /// each other, plus an `index.ts` re-exporting them all; types referring
+    /// to each other in a circle share the file of the first
+    Types,
+}
+
+/// What kind of module the generated files are
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ModuleFormat {
+    /// TypeScript ES modules (`.ts`)
+    #[default]
+    Esm,
+    /// TypeScript CommonJS modules (`.cts`), which TypeScript compiles to
+    /// `require()` calls whatever the package's `"type"`
+    CommonJs,
+    /// Type declarations only (`.d.ts`): the types, without the schemas,
+    /// helpers, constants and simulators, for code that only type-checks
+    /// against the protocol
+    Declarations,
+}
+
+impl ModuleFormat {
+    /// Extension of the generated files
+    fn extension(self) -> &'static str {
+        match self {
+            ModuleFormat::Esm => ".ts",
+            ModuleFormat::CommonJs => ".cts",
+            ModuleFormat::Declarations => ".d.ts",
+        }
+    }
+}
+
+/// How imports between generated files name the imported file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImportExtension {
+    /// The compiled file, `./ids.js` (`./ids.cjs` for CommonJS), as Node's
+    /// and TypeScript's `nodenext` resolution need
+    #[default]
+    Js,
+    /// The generated file, `./ids.ts` (`./ids.cts` for CommonJS), for
+    /// `allowImportingTsExtensions`; declarations still import `./ids.js`,
+    /// as a `.d.ts` file cannot be imported by its own name
+    Ts,
+    /// No extension, `./ids`, for bundler resolution
+    None,
+}
+
+/// A generated TypeScript file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// Path relative to the output directory, with `/` separators
+    pub path: String,
+    pub source: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// Two types share a name; the generated module has a single namespace
+    DuplicateName {
+        name: &'static str,
+        modules: [&'static str; 2],
+    },
+    /// A type refers to a type that is not among those being generated
+    Missing {
+        name: &'static str,
+        referenced_by: &'static str,
+    },
+    /// A generic type contains itself, so its schema factory would call
+    /// itself forever; the path runs from the type back to itself
+    Recursive(Vec<&'static str>),
+    /// With [`Layout::Modules`], modules whose types use each other's types;
+    /// their files would import each other, which ES modules cannot evaluate
+    ModuleCycle(Vec<&'static str>),
+    /// With [`Generator::enum_tag`], a variant that already has a property
+    /// named like the tag
+    TagClash {
+        name: &'static str,
+        variant: &'static str,
+        tag: String,
+    },
+    /// A map keyed by `f32` or `f64` (possibly in an `Option` or a newtype
+    /// struct); a JS `Map` holds `0.0` and `-0.0`, or two NaNs, as one key, so
+    /// decoding could drop entries
+    FloatKey { name: &'static str },
+    /// A protocol group named other than lowercase letters, digits and `_`,
+    /// named `common` or `index`, or named like another group
+    InvalidGroupName(String),
+    /// A type matched by the patterns of two protocol groups
+    GroupOverlap {
+        name: &'static str,
+        groups: [String; 2],
+    },
+    /// A protocol group pattern matching none of the types
+    UnmatchedGroupPattern { group: String, pattern: String },
+    /// With [`Layout::Groups`], groups whose types use each other's types
+    GroupCycle(Vec<String>),
+    /// A type with no maximum encoded size, asked for one with
+    /// [`max_size`](crate::max_size); `reason` is what it contains
+    Unbounded {
+        name: &'static str,
+        reason: &'static str,
+    },
+    /// A simulator named other than an identifier starting with an uppercase
+    /// letter, or named `Device`
+    InvalidSimulatorName(String),
+    /// A type of a simulator that is missing, generic, or for requests, not
+    /// an enum; `reason` says which
+    InvalidSimulator {
+        simulator: String,
+        type_name: String,
+        reason: &'static str,
+    },
+    /// A [`Generator::override_type`] that cannot be generated; `name` is the
+    /// Rust type's
+    InvalidOverride { name: String, reason: &'static str },
+    /// A type marked both encode-only and decode-only, by
+    /// `#[postcard_ts(...)]` or the generator's patterns
+    DirectionConflict { name: &'static str },
+    /// A type given to [`Generator::wasm_type`], asked for output that needs
+    /// its wire shape: the IR, the wire layout or protocol group hashes
+    WasmWireShape { name: &'static str },
+    /// A [`Generator::encode_only`] or [`Generator::decode_only`] pattern
+    /// matching none of the types
+    UnmatchedDirectionPattern(String),
+    /// A [`Generator::typedoc_example`] naming no non-generic type
+    UnmatchedExample(String),
+    /// A [`ProtocolConstant`] named other than an uppercase identifier, or
+    /// like another one
+    InvalidConstant { name: String, reason: &'static str },
+    /// A [`ProtocolFeature`] named other than a lowercase identifier, at a
+    /// bit outside the mask or another feature's, or naming an unknown message
+    InvalidFeature { name: String, reason: String },
+    /// A [`Naming`] template that does not make an identifier per type
+    InvalidNaming {
+        ident: Ident,
+        template: String,
+        reason: &'static str,
+    },
+    /// Two exports of the generated code share a name, with the kinds of
+    /// identifier and the types it stands for
+    NameCollision {
+        name: String,
+        uses: Vec<(Ident, &'static str)>,
+    },
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::DuplicateName { name, modules } => write!(
+                f,
+                "type name {} is used by both {} and {}",
+                name, modules[0], modules[1]
+            ),
+            GenerateError::Missing {
+                name,
+                referenced_by,
+            } => write!(
+                f,
+                "{} refers to {}, which does not derive PostcardTs",
+                referenced_by, name
+            ),
+            GenerateError::Recursive(path) => {
+                write!(
+                    f,
+                    "recursive generic types are not supported: {}",
+                    path.join(" -> ")
+                )
+            }
+            GenerateError::ModuleCycle(path) => write!(
+                f,
+                "modules use each other's types, so their files would import each other: {}",
+                path.join(" -> ")
+            ),
+            GenerateError::TagClash { name, variant, tag } => write!(
+                f,
+                "variant {} of {} already has a property named like the enum tag {:?}",
+                variant, name, tag
+            ),
+            GenerateError::FloatKey { name } => write!(
+                f,
+                "{} has a map keyed by floats, which a JS Map cannot tell apart",
+                name
+            ),
+            GenerateError::InvalidGroupName(name) => write!(
+                f,
+                "invalid protocol group name {:?}; use lowercase letters, digits and _, \
+                 other than common and index, once each",
+                name
+            ),
+            GenerateError::GroupOverlap { name, groups } => write!(
+                f,
+                "type {} is in both protocol groups {} and {}",
+                name, groups[0], groups[1]
+            ),
+            GenerateError::UnmatchedGroupPattern { group, pattern } => write!(
+                f,
+                "{} in protocol group {} matches no type",
+                pattern, group
+            ),
+            GenerateError::GroupCycle(path) => write!(
+                f,
+                "protocol groups use each other's types, so their files would import each other: {}",
+                path.join(" -> ")
+            ),
+            GenerateError::Unbounded { name, reason } => write!(
+                f,
+                "{} has no maximum encoded size: it contains {}",
+                name, reason
+            ),
+            GenerateError::InvalidSimulatorName(name) => write!(
+                f,
+                "invalid simulator name {:?}; use letters, digits and _, starting with \
+                 an uppercase letter, other than Device",
+                name
+            ),
+            GenerateError::InvalidSimulator {
+                simulator,
+                type_name,
+                reason,
+            } => write!(f, "{} in simulator {} {}", type_name, simulator, reason),
+            GenerateError::InvalidOverride { name, reason } => {
+                write!(f, "cannot override {}: {}", name, reason)
+            }
+            GenerateError::DirectionConflict { name } => {
+                write!(f, "{} is marked both encode-only and decode-only", name)
+            }
+            GenerateError::WasmWireShape { name } => write!(
+                f,
+                "{} is encoded by the wasm backend, so its wire shape is unknown",
+                name
+            ),
+            GenerateError::UnmatchedDirectionPattern(pattern) => {
+                write!(f, "encode-only or decode-only pattern {} matches no type", pattern)
+            }
+            GenerateError::UnmatchedExample(pattern) => {
+                write!(f, "example for {} matches no non-generic type", pattern)
+            }
+            GenerateError::InvalidConstant { name, reason } => {
+                write!(f, "invalid protocol constant {:?}: {}", name, reason)
+            }
+            GenerateError::InvalidFeature { name, reason } => {
+                write!(f, "invalid protocol feature {:?}: {}", name, reason)
+            }
+            GenerateError::InvalidNaming {
+                ident,
+                template,
+                reason,
+            } => write!(f, "invalid {} name template {:?}: {}", ident, template, reason),
+            GenerateError::NameCollision { name, uses } => {
+                write!(f, "generated name {} is exported more than once", name)?;
+                let uses: Vec<String> = uses
+                    .iter()
+                    .map(|(ident, type_name)| format!("the {} of {}", ident, type_name))
+                    .collect();
+                if !uses.is_empty() {
+                    write!(f, ", as {}", uses.join(" and "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+impl GenerateError {
+    /// Stable identifier of the error, e.g. `missing`, as diagnostics give it
+    pub fn code(&self) -> &'static str {
+        match self {
+            GenerateError::DuplicateName { .. } => "duplicate_name",
+            GenerateError::Missing { .. } => "missing",
+            GenerateError::Recursive(_) => "recursive",
+            GenerateError::ModuleCycle(_) => "module_cycle",
+            GenerateError::TagClash { .. } => "tag_clash",
+            GenerateError::FloatKey { .. } => "float_key",
+            GenerateError::InvalidGroupName(_) => "invalid_group_name",
+            GenerateError::GroupOverlap { .. } => "group_overlap",
+            GenerateError::UnmatchedGroupPattern { .. } => "unmatched_group_pattern",
+            GenerateError::GroupCycle(_) => "group_cycle",
+            GenerateError::Unbounded { .. } => "unbounded",
+            GenerateError::InvalidSimulatorName(_) => "invalid_simulator_name",
+            GenerateError::InvalidSimulator { .. } => "invalid_simulator",
+            GenerateError::InvalidOverride { .. } => "invalid_override",
+            GenerateError::DirectionConflict { .. } => "direction_conflict",
+            GenerateError::WasmWireShape { .. } => "wasm_wire_shape",
+            GenerateError::UnmatchedDirectionPattern(_) => "unmatched_direction_pattern",
+            GenerateError::UnmatchedExample(_) => "unmatched_example",
+            GenerateError::InvalidConstant { .. } => "invalid_constant",
+            GenerateError::InvalidFeature { .. } => "invalid_feature",
+            GenerateError::InvalidNaming { .. } => "invalid_naming",
+            GenerateError::NameCollision { .. } => "name_collision",
+        }
+    }
+}
+
+/// Emits a TypeScript module declaring a schema and a type for each Rust type
+///
+/// ```
+/// use serde_postcard_ts::Generator;
+///
+/// let source = Generator::new().import_from("../runtime/index.js").generate()?;
+/// assert!(source.contains("from \"../runtime/index.js\""));
+/// # Ok::<(), serde_postcard_ts::GenerateError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Generator {
+    import_from: String,
+    enum_tag: Option<String>,
+    crc: Option<CrcAlgorithm>,
+    schema_hashes: bool,
+    max_sizes: bool,
+    variant_peeks: bool,
+    salvage: bool,
+    decoders: bool,
+    truncate_strings: bool,
+    u8_vecs_as_bytes: bool,
+    groups: Vec<ProtocolGroup>,
+    simulators: Vec<Simulator>,
+    dispatchers: bool,
+    introspection: bool,
+    display_names: bool,
+    schema_loaders: bool,
+    protocol_constants: Vec<ProtocolConstant>,
+    protocol_features: Vec<ProtocolFeature>,
+    /// The shape and Rust name of each overridden type, and its override
+    overrides: Vec<(Shape, &'static str, TsOverride)>,
+    wasm: Option<WasmBackend>,
+    /// The shape and Rust path of each type encoded by the wasm backend
+    wasm_types: Vec<(Shape, &'static str)>,
+    module_format: ModuleFormat,
+    import_extension: ImportExtension,
+    /// Comment lines following the header of every file
+    banner: String,
+    /// Patterns of the types only encoded, and of those only decoded
+    encode_only: Vec<String>,
+    decode_only: Vec<String>,
+    validator: Option<Validator>,
+    representations: Representations,
+    branded_newtypes: bool,
+    naming: Naming,
+    typedoc: bool,
+    /// The type each example frame is of, by name or `module::Name`
+    examples: Vec<(String, Vec<u8>)>,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            import_from: DEFAULT_IMPORT.to_string(),
+            enum_tag: None,
+            crc: None,
+            schema_hashes: false,
+            max_sizes: false,
+            variant_peeks: false,
+            salvage: false,
+            decoders: false,
+            truncate_strings: false,
+            u8_vecs_as_bytes: false,
+            groups: Vec::new(),
+            simulators: Vec::new(),
+            dispatchers: false,
+            introspection: false,
+            display_names: false,
+            schema_loaders: false,
+            protocol_constants: Vec::new(),
+            protocol_features: Vec::new(),
+            overrides: Vec::new(),
+            wasm: None,
+            wasm_types: Vec::new(),
+            module_format: ModuleFormat::default(),
+            import_extension: ImportExtension::default(),
+            banner: String::new(),
+            encode_only: Vec::new(),
+            decode_only: Vec::new(),
+            validator: None,
+            representations: Representations::default(),
+            branded_newtypes: false,
+            naming: Naming::default(),
+            typedoc: false,
+            examples: Vec::new(),
+        }
+    }
+}
+
+impl Generator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Import the schema builders from `module` instead of the published package
+    pub fn import_from(mut self, module: impl Into<String>) -> Self {
+        self.import_from = module.into();
+        self
+    }
+
+    /// Emit enums as flat unions tagged by `tag` (`{ kind: "Move", x: 1 }`)
+    /// through `discriminated()`, instead of `{ type: "Move", value: { x: 1 } }`
+    ///
+    /// The wire format is the same either way.
+    pub fn enum_tag(mut self, tag: impl Into<String>) -> Self {
+        self.enum_tag = Some(tag.into());
+        self
+    }
+
+    /// Also emit `serializeXCrc` / `deserializeXCrc` for each non-generic type,
+    /// which append and check a CRC as postcard's CRC flavor does
+    ///
+    /// ```
+    /// use serde_postcard_ts::{crc, Definition, Generator, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "Id", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .crc(crc::CRC_32_ISCSI)
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains("const crc = new Crc(CRC_32_ISCSI);"));
+    /// assert!(source.contains("export const deserializeIdCrc = "));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn crc(mut self, algorithm: CrcAlgorithm) -> Self {
+        self.crc = Some(algorithm);
+        self
+    }
+
+    /// Also emit `XSchemaHash`, the [`schema_hash`](crate::schema_hash) of each
+    /// non-generic type as a bigint, for handshakes (`checkSchemaHashes`)
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "Id", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .schema_hashes()
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains("export const IdSchemaHash = 0x"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn schema_hashes(mut self) -> Self {
+        self.schema_hashes = true;
+        self
+    }
+
+    /// Also emit `X_MAX_SIZE`, the [`max_size`](crate::max_size) of each
+    /// non-generic type that has one, for sizing buffers
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "DeviceId", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .max_sizes()
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains("export const DEVICE_ID_MAX_SIZE = 10;"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn max_sizes(mut self) -> Self {
+        self.max_sizes = true;
+        self
+    }
+
+    /// Also emit `isXVariant` for each variant of each non-generic enum,
+    /// telling whether a frame holds that variant from its variant index
+    /// alone, for dropping frames before decoding them (`variantFilter`)
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, TypeName, Variant, VariantKind};
+    ///
+    /// let telemetry = TypeName { name: "Telemetry", module: "app", params: &[] };
+    /// let variants = vec![
+    ///     Variant { name: "Heartbeat", kind: VariantKind::Unit },
+    ///     Variant { name: "Alarm", kind: VariantKind::Unit },
+    /// ];
+    /// let source = Generator::new()
+    ///     .variant_peeks()
+    ///     .generate_types(vec![(telemetry, Definition::Enum(variants))])?;
+    /// assert!(source.contains(
+    ///     "export const isTelemetryAlarm = variantFilter(TelemetrySchema, [\"Alarm\"]);"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn variant_peeks(mut self) -> Self {
+        self.variant_peeks = true;
+        self
+    }
+
+    /// Also emit `salvageX` for each non-generic struct holding a list of
+    /// records, its first field that is a `Vec` of a named type: it decodes
+    /// as many of the records as it can and returns them with the error and
+    /// offset decoding stopped at, for logs pulled off flash that may be
+    /// partly corrupt (`salvage`)
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Field, Generator, Shape, TypeName};
+    ///
+    /// let entry = TypeName { name: "LogEntry", module: "app", params: &[] };
+    /// let batch = TypeName { name: "LogBatch", module: "app", params: &[] };
+    /// let fields = vec![
+    ///     Field { name: "device", shape: Shape::U8 },
+    ///     Field { name: "entries", shape: Shape::Seq(Box::new(Shape::Named(entry))) },
+    /// ];
+    /// let source = Generator::new()
+    ///     .salvage()
+    ///     .generate_types(vec![
+    ///         (entry, Definition::Struct(vec![Field { name: "at", shape: Shape::U32 }])),
+    ///         (batch, Definition::Struct(fields)),
+    ///     ])?;
+    /// assert!(source.contains(") => salvage(LogBatchSchema, \"entries\", data, offset, options);"));
+    /// assert!(!source.contains("salvageLogEntry"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn salvage(mut self) -> Self {
+        self.salvage = true;
+        self
+    }
+
+    /// Also emit, for each non-generic type that is decoded, `decodeX`
+    /// throwing on failure, `tryDecodeX` returning a `Result` (`{ ok: true,
+    /// value } | { ok: false, error }`) instead, and
+    /// `createXStreamDecoder`, whose `StreamDecoder` has `tryNext` and
+    /// `tryDrain` alongside the throwing `next` and iterator; for codebases
+    /// that keep exceptions out of control flow
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "Id", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .decoders()
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains(") => tryDeserializeValue(IdSchema, data, offset, options);"));
+    /// assert!(source.contains("export const createIdStreamDecoder = "));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn decoders(mut self) -> Self {
+        self.decoders = true;
+        self
+    }
+
+    /// Truncate strings longer than their limit (`heapless::String<N>`,
+    /// `#[postcard_ts(max_len = N)]`) at a character boundary when encoding,
+    /// instead of failing
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let name = TypeName { name: "Name", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .truncate_strings()
+    ///     .generate_types(vec![(name, Definition::NewtypeStruct(Shape::BoundedString(16)))])?;
+    /// assert!(source.contains("string({ maxBytes: 16, overflow: \"truncate\" })"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn truncate_strings(mut self) -> Self {
+        self.truncate_strings = true;
+        self
+    }
+
+    /// Emit every `Vec<u8>` (and other sequence of `u8`) as `bytes()`, decoded
+    /// to a `Uint8Array` in one copy instead of a `number[]` item by item
+    ///
+    /// Postcard writes both the same way, so only the TypeScript type changes;
+    /// `#[serde(with = "serde_bytes")]` does the same for a single field.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let blob = TypeName { name: "Blob", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .u8_vecs_as_bytes()
+    ///     .generate_types(vec![(blob, Definition::NewtypeStruct(Shape::Seq(Box::new(Shape::U8))))])?;
+    /// assert!(source.contains("newtypeStruct(\"Blob\", bytes())"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn u8_vecs_as_bytes(mut self) -> Self {
+        self.u8_vecs_as_bytes = true;
+        self
+    }
+
+    /// Brand the type of every newtype with its name, so that a `PlayerId`
+    /// and an `ItemId` wrapping the same `u64` cannot be mixed up in
+    /// TypeScript, although both decode to a `bigint`
+    ///
+    /// Values of the type are then made with a cast, `42n as PlayerId`. Only
+    /// the TypeScript type changes; generic and recursive newtypes are left
+    /// unbranded.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "PlayerId", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .branded_newtypes()
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains(
+    ///     "export const PlayerIdSchema = branded(newtypeStruct(\"PlayerId\", u64()), \"PlayerId\");"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn branded_newtypes(mut self) -> Self {
+        self.branded_newtypes = true;
+        self
+    }
+
+    /// Name the generated identifiers with the templates of `naming`
+    /// instead of after the Rust types alone, e.g. `IReading` for the type
+    /// of `Reading`
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Ident, Naming, Shape, TypeName};
+    ///
+    /// let id = TypeName { name: "PlayerId", module: "app", params: &[] };
+    /// let naming = Naming::new()
+    ///     .template(Ident::Type, "I{name}")
+    ///     .template(Ident::Schema, "{name}Codec");
+    /// let source = Generator::new()
+    ///     .naming(naming)
+    ///     .generate_types(vec![(id, Definition::NewtypeStruct(Shape::U64))])?;
+    /// assert!(source.contains("export type IPlayerId = InferType<typeof PlayerIdCodec>;"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn naming(mut self, naming: Naming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Add a protocol group, generated as its own file with
+    /// [`Layout::Groups`] and checked by [`Generator::check_groups`]
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Layout, ProtocolGroup, Shape, TypeName};
+    ///
+    /// let reading = TypeName { name: "Reading", module: "app::sensors", params: &[] };
+    /// let files = Generator::new()
+    ///     .group(ProtocolGroup::new("telemetry", 3).types(["app::sensors::*"]))
+    ///     .generate_types_files(vec![(reading, Definition::NewtypeStruct(Shape::F32))], Layout::Groups)?;
+    /// assert_eq!(files[0].path, "telemetry.ts");
+    /// assert!(files[0].source.contains("export const TELEMETRY_PROTOCOL_VERSION = 3;"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn group(mut self, group: ProtocolGroup) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    /// Add a simulated device, generated as `<name>-simulator.ts` next to the
+    /// `index.ts` of the files layouts
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Layout, Shape, Simulator, TypeName, Variant, VariantKind};
+    ///
+    /// let command = TypeName { name: "Command", module: "app", params: &[] };
+    /// let ping = Variant { name: "Ping", kind: VariantKind::Unit };
+    /// let files = Generator::new()
+    ///     .simulator(Simulator::new("Thermostat", "Command", "Command"))
+    ///     .generate_types_files(vec![(command, Definition::Enum(vec![ping]))], Layout::Single)?;
+    /// assert_eq!(files[1].path, "thermostat-simulator.ts");
+    /// assert!(files[1].source.contains("export class ThermostatSimulator extends DeviceSimulator<"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn simulator(mut self, simulator: Simulator) -> Self {
+        self.simulators.push(simulator);
+        self
+    }
+
+    /// Declare a protocol constant, emitted after the types in a single file
+    /// and in `constants.ts` with the layouts writing several, and into the
+    /// Rust module of [`Generator::generate_rust_constants`]
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, ProtocolConstant};
+    ///
+    /// let source = Generator::new()
+    ///     .constant(ProtocolConstant::new("MAX_FRAME_LENGTH", 512usize))
+    ///     .constant(ProtocolConstant::new("TELEMETRY_TOPIC", "devices/telemetry"))
+    ///     .generate_types(vec![])?;
+    /// assert!(source.ends_with(
+    ///     "export const MAX_FRAME_LENGTH = 512;\n\
+    ///      export const TELEMETRY_TOPIC = \"devices/telemetry\";\n"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn constant(mut self, constant: ProtocolConstant) -> Self {
+        self.protocol_constants.push(constant);
+        self
+    }
+
+    /// Declare a protocol feature, emitted with the protocol constants as an
+    /// entry of `PROTOCOL_FEATURES` and as the Rust constant of its mask
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, ProtocolFeature};
+    ///
+    /// let generator = Generator::new()
+    ///     .feature(ProtocolFeature::new("logs", 0))
+    ///     .feature(ProtocolFeature::new("firmware_update", 1));
+    /// assert!(generator.generate_types(vec![])?.ends_with(
+    ///     "export const PROTOCOL_FEATURES = {\n  \
+    ///        logs: { bit: 0, messages: [] },\n  \
+    ///        firmware_update: { bit: 1, messages: [] },\n\
+    ///      } as const;\n"
+    /// ));
+    /// assert!(generator
+    ///     .generate_rust_constants()?
+    ///     .ends_with("pub const FEATURE_FIRMWARE_UPDATE: u64 = 1 << 1;\n"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn feature(mut self, feature: ProtocolFeature) -> Self {
+        self.protocol_features.push(feature);
+        self
+    }
+
+    /// Declare the wire shape and TypeScript representation of `T`, for types
+    /// with a hand-written `Serialize` impl or stand-ins for fields written
+    /// by a `#[serde(with = "...")]` module; its registered definition, if
+    /// any, is replaced
+    ///
+    /// `T` must describe itself as a [`Shape::Named`], as derived non-generic
+    /// types do.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, PostcardTs, Shape, TsOverride};
+    ///
+    /// // Written by `#[serde(with = "ts_millis")]` as milliseconds
+    /// #[derive(PostcardTs)]
+    /// struct Timestamp;
+    ///
+    /// let source = Generator::new()
+    ///     .override_type::<Timestamp>(
+    ///         TsOverride::new(Shape::U64)
+    ///             .ts_type("Date")
+    ///             .codec("(ms: bigint) => new Date(Number(ms))", "(date: Date) => BigInt(date.getTime())"),
+    ///     )
+    ///     .generate()?;
+    /// assert!(source.contains("export type Timestamp = Date;\nexport const TimestampSchema = transform(u64(), {"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn override_type<T: PostcardTs>(mut self, ts_override: TsOverride) -> Self {
+        self.overrides
+            .push((T::shape(), std::any::type_name::<T>(), ts_override));
+        self
+    }
+
+    /// Write the wasm-bindgen crate encoding the types given to
+    /// [`Generator::wasm_type`] as `backend` says; see [`WasmBackend`]
+    pub fn wasm_backend(mut self, backend: WasmBackend) -> Self {
+        self.wasm = Some(backend);
+        self
+    }
+
+    /// Leave encoding and decoding `T` to postcard itself, through the crate
+    /// of the [`wasm_backend`](Self::wasm_backend), for types whose wire
+    /// shape is not worth declaring with [`override_type`](Self::override_type)
+    ///
+    /// `T` must describe itself as a [`Shape::Named`], as derived non-generic
+    /// types do, and implement `Serialize` and `DeserializeOwned`. Its values
+    /// are typed `unknown`, and it and the types containing it get no schema
+    /// hash or maximum size. The crate is among the files of
+    /// [`generate_files`](Self::generate_files), but not of declarations.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, Layout, PostcardTs, WasmBackend};
+    ///
+    /// #[derive(PostcardTs)]
+    /// struct Calibration;
+    ///
+    /// let files = Generator::new()
+    ///     .wasm_backend(WasmBackend::new("protocol-wasm"))
+    ///     .wasm_type::<Calibration>()
+    ///     .generate_files(Layout::Single)?;
+    /// assert!(files[0].source.contains("export const CalibrationSchema = external<Calibration>("));
+    /// assert_eq!(files[1].path, "protocol-wasm/Cargo.toml");
+    /// assert!(files[2].source.contains("pub fn decode_calibration(bytes: &[u8])"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn wasm_type<T: PostcardTs>(mut self) -> Self {
+        self.wasm_types
+            .push((T::shape(), std::any::type_name::<T>()));
+        self
+    }
+
+    /// Generate modules of `format` instead of ES modules
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, ModuleFormat};
+    ///
+    /// let files = Generator::new()
+    ///     .module_format(ModuleFormat::Declarations)
+    ///     .generate_files(Default::default())?;
+    /// assert_eq!(files[0].path, "index.d.ts");
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn module_format(mut self, format: ModuleFormat) -> Self {
+        self.module_format = format;
+        self
+    }
+
+    /// Name imported files in imports between generated files as `extension`
+    /// says (default: [`ImportExtension::Js`])
+    pub fn import_extension(mut self, extension: ImportExtension) -> Self {
+        self.import_extension = extension;
+        self
+    }
+
+    /// Put `text`, e.g. a license notice, in a comment at the top of every
+    /// generated file, after the line marking it as generated
+    pub fn banner(mut self, text: &str) -> Self {
+        self.banner = text
+            .lines()
+            .map(|line| match line {
+                "" => "//\n".to_string(),
+                line => format!("// {}\n", line),
+            })
+            .collect();
+        self
+    }
+
+    /// Generate the types `patterns` give, by name, path or `module::*` as
+    /// in [`ProtocolGroup::types`], for encoding only, as
+    /// `#[postcard_ts(encode_only)]` does
+    ///
+    /// Their schemas are wrapped in `encodeOnly()`, which `deserialize()`
+    /// rejects, and get no CRC decoding wrapper.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let command = TypeName { name: "Command", module: "app::control", params: &[] };
+    /// let source = Generator::new()
+    ///     .encode_only(["app::control::*"])
+    ///     .generate_types(vec![(command, Definition::NewtypeStruct(Shape::U8))])?;
+    /// assert!(source.contains("export const CommandSchema = encodeOnly(newtypeStruct(\"Command\", u8()));"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn encode_only<I>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.encode_only
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Generate the types `patterns` give for decoding only, as
+    /// `#[postcard_ts(decode_only)]` does; see [`Generator::encode_only`]
+    pub fn decode_only<I>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.decode_only
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also emit a runtime validator per type, `<Name>Zod` with
+    /// [`Validator::Zod`], accepting the values its schema decodes to and
+    /// encodes; not for [`ModuleFormat::Declarations`]
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName, Validator};
+    ///
+    /// let level = TypeName { name: "Level", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .with_validators(Validator::Zod)
+    ///     .generate_types(vec![(level, Definition::NewtypeStruct(Shape::U8))])?;
+    /// assert!(source.contains("import { z } from \"zod\";"));
+    /// assert!(source.contains("export const LevelZod = z.number().int().min(0).max(255);"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn with_validators(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Decode the built-in date and time types (`std::time::Duration`, and
+    /// with the `chrono` and `time` features `chrono::DateTime<Utc>` and
+    /// `time::OffsetDateTime`) as `representation` (default: `Date`)
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, PostcardTs, TimeRepresentation};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(PostcardTs)]
+    /// struct Lease {
+    ///     length: Duration,
+    /// }
+    ///
+    /// let source = Generator::new()
+    ///     .time_representation(TimeRepresentation::Nanos)
+    ///     .generate()?;
+    /// assert!(source.contains("export const DurationSchema = duration(\"nanos\");"));
+    /// assert!(source.contains("  length: DurationSchema,"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn time_representation(mut self, representation: TimeRepresentation) -> Self {
+        self.representations.time = representation;
+        self
+    }
+
+    /// Decode `uuid::Uuid` (with the `uuid` feature) as `representation`
+    /// (default: the hyphenated text, as a branded `Uuid` string)
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName, UuidRepresentation};
+    ///
+    /// let session = TypeName { name: "Session", module: "app", params: &[] };
+    /// let uuid = TypeName { name: "Uuid", module: "uuid", params: &[] };
+    /// let source = Generator::new()
+    ///     .uuid_representation(UuidRepresentation::Bytes)
+    ///     .generate_types(vec![(session, Definition::NewtypeStruct(Shape::Named(uuid)))])?;
+    /// assert!(source.contains("export const UuidSchema = uuid(\"bytes\");"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn uuid_representation(mut self, representation: UuidRepresentation) -> Self {
+        self.representations.uuid = representation;
+        self
+    }
+
+    /// Document every export for TypeDoc: the type with its Rust path, kind,
+    /// maximum size, schema hash and example, if any, and everything under a
+    /// `@category` of its protocol group or Rust module
+    ///
+    /// The files layouts also get `dispatchers.ts`, with a typed
+    /// `MessageDispatcher` per top-level enum (one that is decoded, not
+    /// generic and in no other type) routing the messages of a stream to
+    /// handlers by variant
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Layout, Shape, TypeName, Variant, VariantKind};
+    ///
+    /// let event = TypeName { name: "Event", module: "app", params: &[] };
+    /// let variants = vec![
+    ///     Variant { name: "Started", kind: VariantKind::Unit },
+    ///     Variant { name: "Progress", kind: VariantKind::Newtype(Shape::U8) },
+    /// ];
+    /// let files = Generator::new()
+    ///     .dispatchers()
+    ///     .generate_types_files(vec![(event, Definition::Enum(variants))], Layout::Single)?;
+    /// assert_eq!(files[1].path, "dispatchers.ts");
+    /// assert!(files[1].source.contains("export class EventDispatcher extends MessageDispatcher<"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn dispatchers(mut self) -> Self {
+        self.dispatchers = true;
+        self
+    }
+
+    /// Also emit `__schema` from each module, describing the types it
+    /// declares for tools in the same app that look at them at runtime, such
+    /// as a protocol explorer or a log formatter (`ModuleSchema`): the kind
+    /// of each type, its schema, its fields or tuple length, the index of
+    /// each variant, its type parameters and its schema hash. The index of
+    /// layouts writing several files merges them.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, TypeName, Variant, VariantKind};
+    ///
+    /// let mode = TypeName { name: "Mode", module: "app", params: &[] };
+    /// let variants = vec![
+    ///     Variant { name: "Idle", kind: VariantKind::Unit },
+    ///     Variant { name: "Run", kind: VariantKind::Unit },
+    /// ];
+    /// let source = Generator::new()
+    ///     .introspection()
+    ///     .generate_types(vec![(mode, Definition::Enum(variants))])?;
+    /// assert!(source.contains(
+    ///     "  Mode: { kind: \"enum\", schema: ModeSchema, variants: { Idle: 0, Run: 1 }, hash: 0x"
+    /// ));
+    /// assert!(source.ends_with("} as const satisfies ModuleSchema;\n"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn introspection(mut self) -> Self {
+        self.introspection = true;
+        self
+    }
+
+    /// Also emit `XDisplayNames` for each enum, the name to show people for
+    /// each variant, by variant: the one given with
+    /// `#[postcard_ts(display = "...")]`, or else the variant's name as words
+    /// (`DarkLord` as `Dark lord`). Nested by enum, `{ Phase:
+    /// PhaseDisplayNames }`, the tables are the default messages of an i18n
+    /// catalog keyed `Phase.DarkLord`, so that a UI labels the variants the
+    /// protocol has rather than strings of its own.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, TypeName, Variant, VariantKind};
+    ///
+    /// let phase = TypeName { name: "Phase", module: "app", params: &[] };
+    /// let variants = vec![
+    ///     Variant { name: "Idle", kind: VariantKind::Unit },
+    ///     Variant { name: "DarkLord", kind: VariantKind::Unit },
+    /// ];
+    /// let source = Generator::new()
+    ///     .display_names()
+    ///     .generate_types(vec![(phase, Definition::Enum(variants))])?;
+    /// assert!(source.contains(
+    ///     "export const PhaseDisplayNames = {\n  Idle: \"Idle\",\n  DarkLord: \"Dark lord\",\n} as const;\n"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn display_names(mut self) -> Self {
+        self.display_names = true;
+        self
+    }
+
+    /// The files layouts also get `loaders.ts`, mapping each non-generic type
+    /// to a dynamic import of the file declaring it. Bundlers then split each
+    /// module, or each protocol group, into a chunk that the runtime's
+    /// `LazySchemas` loads the first time one of its types is decoded, so
+    /// that a protocol of thousands of types costs an app only the chunks it
+    /// uses. The index still exports everything; import `loaders.ts` instead
+    /// of it for the split to happen.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Layout, Shape, TypeName};
+    ///
+    /// let level = TypeName { name: "Level", module: "app::sensors", params: &[] };
+    /// let files = Generator::new()
+    ///     .schema_loaders()
+    ///     .generate_types_files(vec![(level, Definition::NewtypeStruct(Shape::U16))], Layout::Modules)?;
+    /// let loaders = files.iter().find(|file| file.path == "loaders.ts").unwrap();
+    /// assert!(loaders.source.contains(
+    ///     "  Level: () => import(\"./app/sensors.js\").then((module) => module.LevelSchema),\n"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn schema_loaders(mut self) -> Self {
+        self.schema_loaders = true;
+        self
+    }
+
+    /// The files layouts also get a `typedoc.json` documenting `index.ts`
+    /// into `docs/`, so that `npx typedoc --options typedoc.json` in the
+    /// output directory builds a browsable reference of the protocol.
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let level = TypeName { name: "Level", module: "app::sensors", params: &[] };
+    /// let source = Generator::new()
+    ///     .typedoc()
+    ///     .generate_types(vec![(level, Definition::NewtypeStruct(Shape::U16))])?;
+    /// assert!(source.contains(" * Rust `app::sensors::Level`, a newtype struct, at most 3 bytes on the wire.\n"));
+    /// assert!(source.contains(" * @category app::sensors\n */\nexport type Level = "));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn typedoc(mut self) -> Self {
+        self.typedoc = true;
+        self
+    }
+
+    /// With [`typedoc`](Self::typedoc), show `frame`, e.g. a test fixture,
+    /// decoded as the type `type_name` (`Name` or `module::Name`) in its
+    /// comment
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Definition, Generator, Shape, TypeName};
+    ///
+    /// let level = TypeName { name: "Level", module: "app", params: &[] };
+    /// let source = Generator::new()
+    ///     .typedoc()
+    ///     .typedoc_example("Level", [7])
+    ///     .generate_types(vec![(level, Definition::NewtypeStruct(Shape::U8))])?;
+    /// assert!(source.contains(" * const frame = new Uint8Array([0x07]);\n"));
+    /// assert!(source.contains(" * const level = deserialize(LevelSchema, frame);\n"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn typedoc_example(
+        mut self,
+        type_name: impl Into<String>,
+        frame: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.examples.push((type_name.into(), frame.into()));
+        self
+    }
+
+    /// The header and banner starting every generated file
+    fn header(&self) -> String {
+        format!("{}{}", HEADER, self.banner)
+    }
+
+    /// Import specifier of the generated file `to` from `from`, both relative
+    /// to the output directory and named `.ts`
+    fn import_path(&self, from: &str, to: &str) -> String {
+        let extension = match (self.import_extension, self.module_format) {
+            (ImportExtension::None, _) => "",
+            (ImportExtension::Ts, ModuleFormat::Esm) => ".ts",
+            (ImportExtension::Ts, ModuleFormat::CommonJs) => ".cts",
+            (_, ModuleFormat::CommonJs) => ".cjs",
+            (_, ModuleFormat::Esm | ModuleFormat::Declarations) => ".js",
+        };
+        relative_import(from, to, extension)
+    }
+
+    /// The overridden types, by name, including those the wasm backend encodes
+    fn overrides(&self) -> Result<BTreeMap<TypeName, Cow<'_, TsOverride>>, GenerateError> {
+        let mut overrides = BTreeMap::new();
+        for (shape, name, ts_override) in &self.overrides {
+            let invalid = |reason| GenerateError::InvalidOverride {
+                name: name.to_string(),
+                reason,
+            };
+            let Shape::Named(type_name) = shape else {
+                return Err(invalid(
+                    "only types describing themselves as a Shape::Named, as derived \
+                     non-generic types do, can be overridden",
+                ));
+            };
+            if let Some(reason) = ts_override.invalid() {
+                return Err(invalid(reason));
+            }
+            if self.module_format == ModuleFormat::Declarations
+                && ts_override.transforms()
+                && ts_override.infers()
+            {
+                return Err(invalid(
+                    "declarations need the TypeScript type of a codec, which is otherwise \
+                     inferred from the schema",
+                ));
+            }
+            overrides.insert(*type_name, Cow::Borrowed(ts_override));
+        }
+        if let Some(backend) = &self.wasm {
+            let module = backend.import();
+            for (type_name, name) in self.wasm_types()? {
+                if overrides.contains_key(&type_name) {
+                    return Err(GenerateError::InvalidOverride {
+                        name: name.to_string(),
+                        reason: "a type cannot be both overridden and encoded by the wasm backend",
+                    });
+                }
+                let ts_override = TsOverride::external(type_name.name, &module);
+                overrides.insert(type_name, Cow::Owned(ts_override));
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// The types the wasm backend encodes, with their Rust paths
+    fn wasm_types(&self) -> Result<Vec<(TypeName, &'static str)>, GenerateError> {
+        let mut types = Vec::with_capacity(self.wasm_types.len());
+        for (shape, name) in &self.wasm_types {
+            let invalid = |reason| GenerateError::InvalidOverride {
+                name: name.to_string(),
+                reason,
+            };
+            let Shape::Named(type_name) = shape else {
+                return Err(invalid(
+                    "only types describing themselves as a Shape::Named, as derived \
+                     non-generic types do, can be encoded by the wasm backend",
+                ));
+            };
+            match &self.wasm {
+                None => return Err(invalid("wasm types need a Generator::wasm_backend")),
+                Some(backend) if !backend.valid() => {
+                    return Err(invalid(
+                        "the wasm backend's package name is not letters, digits, - and _",
+                    ))
+                }
+                Some(_) => {}
+            }
+            types.push((*type_name, *name));
+        }
+        Ok(types)
+    }
+
+    /// Reject output needing the wire shapes of the wasm backend's types
+    fn check_wire_shapes(&self) -> Result<(), GenerateError> {
+        match self.wasm_types()?.first() {
+            Some((type_name, _)) => Err(GenerateError::WasmWireShape {
+                name: type_name.name,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The files of the wasm backend's crate, if it has types to encode and
+    /// the module format runs code
+    fn wasm_files(&self) -> Result<Vec<GeneratedFile>, GenerateError> {
+        let types = self.wasm_types()?;
+        match &self.wasm {
+            Some(backend)
+                if !types.is_empty() && self.module_format != ModuleFormat::Declarations =>
+            {
+                Ok(backend.files(&types))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// The types limited to one direction, by `#[postcard_ts(...)]` on
+    /// registered types or by the generator's patterns
+    fn directions(
+        &self,
+        types: &[(TypeName, Definition)],
+    ) -> Result<HashMap<TypeName, Direction>, GenerateError> {
+        let mut directions = HashMap::new();
+        for (type_name, direction) in registry::directions() {
+            set_direction(&mut directions, type_name, direction)?;
+        }
+        let patterns = (self.encode_only.iter().map(|p| (p, Direction::EncodeOnly)))
+            .chain(self.decode_only.iter().map(|p| (p, Direction::DecodeOnly)));
+        for (pattern, direction) in patterns {
+            let mut matched = false;
+            for (type_name, _) in types {
+                if groups::matches(pattern, type_name) {
+                    matched = true;
+                    set_direction(&mut directions, *type_name, direction)?;
+                }
+            }
+            if !matched {
+                return Err(GenerateError::UnmatchedDirectionPattern(pattern.clone()));
+            }
+        }
+        Ok(directions)
+    }
+
+    /// `types` with the definitions of overridden types replaced by their
+    /// wire shapes, which is all the checks, hashes and sizes go by, and the
+    /// built-in types they refer to added
+    fn with_overrides(
+        &self,
+        mut types: Vec<(TypeName, Definition)>,
+    ) -> Result<Vec<(TypeName, Definition)>, GenerateError> {
+        for (type_name, ts_override) in self.overrides()? {
+            let definition = Definition::NewtypeStruct(ts_override.wire().clone());
+            match types.iter_mut().find(|(other, _)| *other == type_name) {
+                Some(found) => found.1 = definition,
+                None => types.push((type_name, definition)),
+            }
+        }
+        builtin::add_builtins(&mut types);
+        Ok(types)
+    }
+
+    /// Compare the hash of each protocol group over the types registered with
+    /// `#[derive(PostcardTs)]` with the one pinned for its version; see
+    /// `postcard-ts check`
+    pub fn check_groups(&self) -> Result<Vec<GroupCheck>, GenerateError> {
+        self.check_types_groups(&registry::registered())
+    }
+
+    /// Compare the hash of each protocol group over `types` with the one
+    /// pinned for its version
+    ///
+    /// A stale group changed since that version was released: give it a new
+    /// version and pin the new hash.
+    pub fn check_types_groups(
+        &self,
+        types: &[(TypeName, Definition)],
+    ) -> Result<Vec<GroupCheck>, GenerateError> {
+        self.check_wire_shapes()?;
+        groups::check(&self.groups, &self.with_overrides(types.to_vec())?)
+    }
+
+    /// Generate every type registered with `#[derive(PostcardTs)]`
+    pub fn generate(&self) -> Result<String, GenerateError> {
+        self.generate_types(registry::registered())
+    }
+
+    /// Generate the given types, each declared after the types it refers to
+    pub fn generate_types(
+        &self,
+        types: Vec<(TypeName, Definition)>,
+    ) -> Result<String, GenerateError> {
+        let types = self.with_overrides(types)?;
+        check_names(&types)?;
+        check_keys(&types)?;
+        self.check_enum_tag(&types)?;
+        protocol_constants::check(&self.protocol_constants)?;
+        features::check(&self.protocol_features)?;
+        features::check_messages(&self.protocol_features, &types)?;
+        self.naming.check()?;
+        let order = dependency_order(&types)?;
+        let constants = self.constants(&types)?;
+        let mut source = self.render(&types, &order.indices, &order.cycles, &constants, 0, &[])?;
+        source.push_str(&self.protocol_constants_ending());
+        self.check_collisions(&types, [source.as_str()])?;
+        Ok(source)
+    }
+
+    /// A Rust module declaring the protocol constants given to
+    /// [`Generator::constant`], and the masks of the features given to
+    /// [`Generator::feature`], for the firmware to include
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, ProtocolConstant};
+    ///
+    /// let module = Generator::new()
+    ///     .constant(ProtocolConstant::new("FRAME_MAGIC", [0xca, 0xfe]).doc("Starts every frame"))
+    ///     .constant(ProtocolConstant::new("PROTOCOL_VERSION", 3u16))
+    ///     .generate_rust_constants()?;
+    /// assert!(module.ends_with(
+    ///     "\n/// Starts every frame\n\
+    ///      pub const FRAME_MAGIC: [u8; 2] = [0xca, 0xfe];\n\
+    ///      \n\
+    ///      pub const PROTOCOL_VERSION: u16 = 3;\n"
+    /// ));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn generate_rust_constants(&self) -> Result<String, GenerateError> {
+        protocol_constants::check(&self.protocol_constants)?;
+        features::check(&self.protocol_features)?;
+        let header = format!("{}{}", RUST_CONSTANTS_HEADER, self.banner);
+        let mut module = protocol_constants::rust(&self.protocol_constants, &header);
+        module.push_str(&features::rust(&self.protocol_features));
+        Ok(module)
+    }
+
+    /// Whether there are protocol constants or features to emit
+    fn has_protocol_constants(&self) -> bool {
+        !(self.protocol_constants.is_empty() && self.protocol_features.is_empty())
+            && self.module_format != ModuleFormat::Declarations
+    }
+
+    /// The protocol constants and features as the end of a file, or nothing;
+    /// type declarations leave constants out
+    fn protocol_constants_ending(&self) -> String {
+        if !self.has_protocol_constants() {
+            return String::new();
+        }
+        let mut ending = String::new();
+        if !self.protocol_constants.is_empty() {
+            ending.push('\n');
+            ending.push_str(&protocol_constants::typescript(&self.protocol_constants));
+        }
+        if !self.protocol_features.is_empty() {
+            ending.push('\n');
+            ending.push_str(&features::typescript(&self.protocol_features));
+        }
+        ending
+    }
+
+    /// Generate every type registered with `#[derive(PostcardTs)]` as files
+    pub fn generate_files(&self, layout: Layout) -> Result<Vec<GeneratedFile>, GenerateError> {
+        self.generate_types_files(registry::registered(), layout)
+    }
+
+    /// Generate the given types as files laid out by `layout`
+    ///
+    /// With [`Layout::Modules`], a relative `import_from` is taken relative to
+    /// the output directory and adjusted for files in subdirectories.
+    pub fn generate_types_files(
+        &self,
+        types: Vec<(TypeName, Definition)>,
+        layout: Layout,
+    ) -> Result<Vec<GeneratedFile>, GenerateError> {
+        let types = self.with_overrides(types)?;
+        check_names(&types)?;
+        check_keys(&types)?;
+        self.check_enum_tag(&types)?;
+        protocol_constants::check(&self.protocol_constants)?;
+        features::check(&self.protocol_features)?;
+        features::check_messages(&self.protocol_features, &types)?;
+        self.naming.check()?;
+        let order = dependency_order(&types)?;
+        let constants = self.constants(&types)?;
+        // The file each type goes in, and with groups, the constants ending it
+        let (files_of, mut endings): (Vec<String>, BTreeMap<String, String>) = match layout {
+            Layout::Single => {
+                let mut source =
+                    self.render(&types, &order.indices, &order.cycles, &constants, 0, &[])?;
+                source.push_str(&self.protocol_constants_ending());
+                let mut files = vec![GeneratedFile {
+                    path: "index.ts".to_string(),
+                    source,
+                }];
+                files.extend(self.simulator_files(&types)?);
+                files.extend(self.dispatcher_file(&types)?);
+                files.extend(self.loaders_file(&types, |_| "index.ts"));
+                self.check_collisions(&types, files.iter().map(|file| file.source.as_str()))?;
+                let mut files = self.named_by_format(files);
+                files.extend(self.typedoc_options());
+                files.extend(self.wasm_files()?);
+                return Ok(files);
+            }
+            Layout::Modules => {
+                check_module_cycles(&types)?;
+                let files = types
+                    .iter()
+                    .map(|(type_name, _)| module_file(type_name.module))
+                    .collect();
+                (files, BTreeMap::new())
+            }
+            Layout::Groups => {
+                self.check_wire_shapes()?;
+                let assigned = groups::assign(&self.groups, &types)?;
+                let group_hashes = groups::hashes(&self.groups, &types, &assigned)?;
+                let units: Vec<String> = assigned
+                    .iter()
+                    .map(|group| match group {
+                        Some(g) => self.groups[*g].name().to_string(),
+                        None => "common".to_string(),
+                    })
+                    .collect();
+                if let Some(cycle) = unit_cycle(&types, &units) {
+                    return Err(GenerateError::GroupCycle(cycle));
+                }
+                let endings = self
+                    .groups
+                    .iter()
+                    .zip(group_hashes)
+                    .filter(|_| self.module_format != ModuleFormat::Declarations)
+                    .map(|(group, hash)| {
+                        let prefix = group.constant_prefix();
+                        let constants = format!(
+                            "\nexport const {prefix}_PROTOCOL_VERSION = {};\n\
+                             export const {prefix}_PROTOCOL_HASH = 0x{:016x}n;\n",
+                            group.version(),
+                            hash,
+                        );
+                        (format!("{}.ts", group.name()), constants)
+                    })
+                    .collect();
+                let files = units.iter().map(|unit| format!("{}.ts", unit)).collect();
+                (files, endings)
+            }
+            Layout::Types => {
+                // The first type of each circle, whose file the circle shares
+                let mut first: HashMap<usize, &str> = HashMap::new();
+                for &i in &order.indices {
+                    if let Some(&cycle) = order.cycles.get(&types[i].0) {
+                        first.entry(cycle).or_insert(types[i].0.name);
+                    }
+                }
+                let files = types
+                    .iter()
+                    .map(|(type_name, _)| {
+                        let name = order
+                            .cycles
+                            .get(type_name)
+                            .map_or(type_name.name, |cycle| first[cycle]);
+                        format!("types/{}.ts", name)
+                    })
+                    .collect();
+                (files, BTreeMap::new())
+            }
+        };
+
+        let mut by_file: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for &i in &order.indices {
+            by_file.entry(&files_of[i]).or_default().push(i);
+        }
+        // Groups without types of their own still get their constants
+        let empty: Vec<String> = endings
+            .keys()
+            .filter(|path| !by_file.contains_key(path.as_str()))
+            .cloned()
+            .collect();
+        for path in &empty {
+            by_file.insert(path, Vec::new());
+        }
+        // Protocol constants get a file of their own, unless a module or
+        // group already has its name
+        let constants_file = self.has_protocol_constants();
+        if constants_file && by_file.contains_key("constants.ts") {
+            endings
+                .entry("constants.ts".to_string())
+                .or_default()
+                .push_str(&self.protocol_constants_ending());
+        }
+
+        let index_of: HashMap<TypeName, usize> = types
+            .iter()
+            .enumerate()
+            .map(|(i, (type_name, _))| (*type_name, i))
+            .collect();
+        let declarations = self.module_format == ModuleFormat::Declarations;
+        let mut files = Vec::with_capacity(by_file.len() + 1);
+        let mut index = self.header();
+        index.push('\n');
+        // The modules whose `__schema` the index merges
+        let mut introspected = Vec::new();
+        for (&path, indices) in &by_file {
+            // Names used from other files, and whether by their type as well
+            // as their schema
+            let mut used: BTreeMap<&str, BTreeMap<&str, bool>> = BTreeMap::new();
+            for &i in indices {
+                // Spelled-out types name the types they refer to
+                let spelled = declarations || order.cycles.contains_key(&types[i].0);
+                for reference in references(&types[i].1) {
+                    let other = &files_of[index_of[&reference]];
+                    if other != path {
+                        let typed = used
+                            .entry(other)
+                            .or_default()
+                            .entry(reference.name)
+                            .or_default();
+                        *typed |= spelled;
+                    }
+                }
+            }
+            let imports: Vec<(String, Vec<String>)> = used
+                .into_iter()
+                .map(|(other, names)| {
+                    let names = names
+                        .into_iter()
+                        .flat_map(|(name, typed)| {
+                            let mut names = match (declarations, typed) {
+                                (true, _) => vec![self.naming.type_name(name)],
+                                (false, false) => vec![self.naming.schema(name)],
+                                (false, true) => vec![
+                                    self.naming.schema(name),
+                                    format!("type {}", self.naming.type_name(name)),
+                                ],
+                            };
+                            if let (Some(validator), false) = (self.validator, declarations) {
+                                names.push(validator.name(&self.naming, name));
+                            }
+                            names
+                        })
+                        .collect();
+                    (self.import_path(path, other), names)
+                })
+                .collect();
+            let depth = path.matches('/').count();
+            let mut source =
+                self.render(&types, indices, &order.cycles, &constants, depth, &imports)?;
+            if let Some(ending) = endings.remove(path) {
+                source.push_str(&ending);
+            }
+
+            index.push_str(&format!(
+                "export * from \"{}\";\n",
+                self.import_path("index.ts", path)
+            ));
+            if self.introspection && !declarations && !indices.is_empty() {
+                introspected.push(self.import_path("index.ts", path));
+            }
+            files.push(GeneratedFile {
+                path: path.to_string(),
+                source,
+            });
+        }
+        if constants_file && !by_file.contains_key("constants.ts") {
+            index.push_str(&format!(
+                "export * from \"{}\";\n",
+                self.import_path("index.ts", "constants.ts")
+            ));
+            files.push(GeneratedFile {
+                path: "constants.ts".to_string(),
+                source: format!("{}{}", self.header(), self.protocol_constants_ending()),
+            });
+        }
+        if !introspected.is_empty() {
+            index.push_str(&merged_module_schema(&introspected));
+        }
+        files.push(GeneratedFile {
+            path: "index.ts".to_string(),
+            source: index,
+        });
+        files.extend(self.simulator_files(&types)?);
+        files.extend(self.dispatcher_file(&types)?);
+        files.extend(self.loaders_file(&types, |i| &files_of[i]));
+        self.check_collisions(&types, files.iter().map(|file| file.source.as_str()))?;
+        let mut files = self.named_by_format(files);
+        files.extend(self.typedoc_options());
+        files.extend(self.wasm_files()?);
+        Ok(files)
+    }
+
+    /// Reject generated `sources` exporting a name twice, as a
+    /// [`Generator::naming`] can make them
+    fn check_collisions<'s>(
+        &self,
+        types: &[(TypeName, Definition)],
+        sources: impl IntoIterator<Item = &'s str>,
+    ) -> Result<(), GenerateError> {
+        let types: Vec<(&'static str, Vec<&'static str>)> = types
+            .iter()
+            .map(|(type_name, definition)| {
+                let variants = match definition {
+                    Definition::Enum(variants) => variants.iter().map(|v| v.name).collect(),
+                    _ => Vec::new(),
+                };
+                (type_name.name, variants)
+            })
+            .collect();
+        self.naming.check_collisions(sources, &types)
+    }
+
+    /// `files`, named `.ts`, renamed with the extension of the module format
+    fn named_by_format(&self, mut files: Vec<GeneratedFile>) -> Vec<GeneratedFile> {
+        for file in &mut files {
+            if let Some(stem) = file.path.strip_suffix(".ts") {
+                file.path = format!("{}{}", stem, self.module_format.extension());
+            }
+        }
+        files
+    }
+
+    /// `typedoc.json`, with [`Generator::typedoc`]
+    fn typedoc_options(&self) -> Option<GeneratedFile> {
+        let index = format!("index{}", self.module_format.extension());
+        self.typedoc
+            .then(|| typedoc::options(&self.header(), &index))
+    }
+
+    /// A file per simulator, importing the types from `index.ts`; none for
+    /// declarations, as a simulator is a class
+    fn simulator_files(
+        &self,
+        types: &[(TypeName, Definition)],
+    ) -> Result<Vec<GeneratedFile>, GenerateError> {
+        if self.module_format == ModuleFormat::Declarations {
+            return Ok(Vec::new());
+        }
+        let mut files: Vec<GeneratedFile> = Vec::with_capacity(self.simulators.len());
+        for simulator in &self.simulators {
+            let file = simulator::render(
+                simulator,
+                types,
+                self.enum_tag.as_deref(),
+                &self.naming,
+                &self.header(),
+                &self.import_from,
+                &self.import_path(&simulator.path(), "index.ts"),
+            )?;
+            if files.iter().any(|other| other.path == file.path) {
+                return Err(GenerateError::InvalidSimulatorName(
+                    simulator.name().to_string(),
+                ));
+            }
+            files.push(file);
+        }
+        Ok(files)
+    }
+
+    /// `dispatchers.ts`, with [`Generator::dispatchers`]; none for
+    /// declarations, as a dispatcher is a class
+    fn dispatcher_file(
+        &self,
+        types: &[(TypeName, Definition)],
+    ) -> Result<Option<GeneratedFile>, GenerateError> {
+        if !self.dispatchers || self.module_format == ModuleFormat::Declarations {
+            return Ok(None);
+        }
+        Ok(dispatch::render(
+            types,
+            &self.directions(types)?,
+            self.enum_tag.as_deref(),
+            &self.naming,
+            &self.header(),
+            &self.import_from,
+            &self.import_path("dispatchers.ts", "index.ts"),
+        ))
+    }
+
+    /// `loaders.ts`, with [`Generator::schema_loaders`], importing each type
+    /// from the file `file_of` its index gives; none for declarations, which
+    /// hold no schemas
+    fn loaders_file<'f>(
+        &self,
+        types: &[(TypeName, Definition)],
+        file_of: impl Fn(usize) -> &'f str,
+    ) -> Option<GeneratedFile> {
+        if !self.schema_loaders || self.module_format == ModuleFormat::Declarations {
+            return None;
+        }
+        loaders::render(
+            types,
+            &self.naming,
+            &self.header(),
+            &self.import_from,
+            |i| self.import_path("loaders.ts", file_of(i)),
+        )
+    }
+
+    /// Reject enums that `discriminated()` could not tag without a clash
+    fn check_enum_tag(&self, types: &[(TypeName, Definition)]) -> Result<(), GenerateError> {
+        let Some(tag) = &self.enum_tag else {
+            return Ok(());
+        };
+        for (type_name, definition) in types {
+            let Definition::Enum(variants) = definition else {
+                continue;
+            };
+            for variant in variants {
+                let clash = match &variant.kind {
+                    VariantKind::Unit => false,
+                    VariantKind::Newtype(_) | VariantKind::Tuple(_) => tag == "value",
+                    VariantKind::Struct(fields) => fields.iter().any(|field| field.name == tag),
+                };
+                if clash {
+                    return Err(GenerateError::TagClash {
+                        name: type_name.name,
+                        variant: variant.name,
+                        tag: tag.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The schema hashes and maximum sizes to emit, if any, and the comments
+    /// of [`Generator::typedoc`]
+    fn constants(&self, types: &[(TypeName, Definition)]) -> Result<Constants, GenerateError> {
+        let mut constants = Constants::default();
+        // The comments state them whether or not they are emitted
+        if self.schema_hashes || self.typedoc || self.introspection {
+            constants.hashes = schema_hash::schema_hashes(types)?.into_iter().collect();
+        }
+        if self.max_sizes || self.typedoc {
+            constants.max_sizes = max_size::max_sizes(types)?.into_iter().collect();
+        }
+        // The wire shapes of the wasm backend's types, and so of the types
+        // containing them, are unknown
+        let mut unknown: BTreeSet<TypeName> = self
+            .wasm_types()?
+            .into_iter()
+            .map(|(type_name, _)| type_name)
+            .collect();
+        let mut grown = !unknown.is_empty();
+        while grown {
+            grown = false;
+            for (type_name, definition) in types {
+                if !unknown.contains(type_name)
+                    && references(definition).iter().any(|r| unknown.contains(r))
+                {
+                    unknown.insert(*type_name);
+                    grown = true;
+                }
+            }
+        }
+        constants
+            .hashes
+            .retain(|type_name, _| !unknown.contains(type_name));
+        constants
+            .max_sizes
+            .retain(|type_name, _| !unknown.contains(type_name));
+        if self.introspection {
+            constants.introspected = constants.hashes.clone();
+        }
+        if self.typedoc {
+            constants.docs = self.docs(types, &constants)?;
+        } else {
+            constants.docs = registry::docs()
+                .into_iter()
+                .filter_map(|(type_name, docs)| Some((type_name, TypeDoc::doc_comment(docs.doc)?)))
+                .collect();
+        }
+        if !self.schema_hashes {
+            constants.hashes.clear();
+        }
+        if !self.max_sizes {
+            constants.max_sizes.clear();
+        }
+        Ok(constants)
+    }
+
+    /// The comment of each type, stating what `constants` know of it after
+    /// its doc comment
+    fn docs(
+        &self,
+        types: &[(TypeName, Definition)],
+        constants: &Constants,
+    ) -> Result<HashMap<TypeName, TypeDoc>, GenerateError> {
+        let examples = typedoc::examples(&self.examples, types)?;
+        let docs = registry::docs();
+        let directions = self.directions(types)?;
+        let assigned = if self.groups.is_empty() {
+            vec![None; types.len()]
+        } else {
+            groups::assign(&self.groups, types)?
+        };
+        Ok(types
+            .iter()
+            .zip(assigned)
+            .map(|((type_name, definition), group)| {
+                let facts = Facts {
+                    category: match group {
+                        Some(g) => self.groups[g].name().to_string(),
+                        None => type_name.module.to_string(),
+                    },
+                    hash: constants.hashes.get(type_name).copied(),
+                    max_size: constants.max_sizes.get(type_name).copied(),
+                    direction: directions.get(type_name).copied(),
+                    example: examples.get(type_name).copied(),
+                    doc: docs.get(type_name).map(|docs| docs.doc).unwrap_or_default(),
+                };
+                let doc = TypeDoc::new(type_name, definition, facts, &self.naming);
+                (*type_name, doc)
+            })
+            .collect())
+    }
+
+    /// One TypeScript module declaring `types[i]` for each of `indices`, in order
+    fn render(
+        &self,
+        types: &[(TypeName, Definition)],
+        indices: &[usize],
+        cycles: &HashMap<TypeName, usize>,
+        constants: &Constants,
+        depth: usize,
+        imports: &[(String, Vec<String>)],
+    ) -> Result<String, GenerateError> {
+        let overrides = self.overrides()?;
+        let directions = self.directions(types)?;
+        let docs = registry::docs();
+        let mut emitter = Emitter {
+            enum_tag: self.enum_tag.as_deref(),
+            crc: self.crc.as_ref(),
+            truncate_strings: self.truncate_strings,
+            u8_vecs_as_bytes: self.u8_vecs_as_bytes,
+            builders: BTreeSet::new(),
+            generic: false,
+            cycles,
+            cycle: None,
+            recursive: false,
+            overrides: &overrides,
+            directions: &directions,
+            docs: &docs,
+            representations: self.representations,
+            branded_newtypes: self.branded_newtypes,
+            naming: &self.naming,
+        };
+        if self.module_format == ModuleFormat::Declarations {
+            return Ok(
+                self.render_declarations(types, indices, &emitter, constants, depth, imports)
+            );
+        }
+        let keys = key_types(types);
+        let zod = Zod {
+            enum_tag: self.enum_tag.as_deref(),
+            truncate_strings: self.truncate_strings,
+            u8_vecs_as_bytes: self.u8_vecs_as_bytes,
+            cycles,
+            overrides: &overrides,
+            representations: self.representations,
+            naming: &self.naming,
+        };
+        let declarations: Vec<String> = indices
+            .iter()
+            .map(|&i| {
+                let (type_name, definition) = &types[i];
+                let mut declaration = emitter.declaration(type_name, definition);
+                match self.validator {
+                    Some(Validator::Zod) => {
+                        declaration.push_str(&zod.declaration(type_name, definition))
+                    }
+                    None => {}
+                }
+                if keys.contains(type_name) {
+                    declaration.push_str(&emitter.key_helper(type_name.name));
+                }
+                if let (true, Definition::Enum(variants)) = (self.variant_peeks, definition) {
+                    if type_name.params.is_empty() {
+                        declaration.push_str(&emitter.variant_peeks(type_name.name, variants));
+                    }
+                }
+                if let (true, Definition::Enum(variants)) = (self.display_names, definition) {
+                    declaration.push_str(&emitter.display_names(type_name, variants));
+                }
+                if self.crc.is_some() && type_name.params.is_empty() {
+                    declaration.push_str(&emitter.crc_wrappers(type_name));
+                }
+                if let (true, Definition::Struct(fields)) = (self.salvage, definition) {
+                    let salvageable = type_name.params.is_empty()
+                        && !cycles.contains_key(type_name)
+                        && !overrides.contains_key(type_name)
+                        && directions.get(type_name) != Some(&Direction::EncodeOnly);
+                    if let (true, Some(field)) = (salvageable, records_field(fields)) {
+                        declaration.push_str(&emitter.salvage_wrapper(type_name.name, field));
+                    }
+                }
+                if self.decoders
+                    && type_name.params.is_empty()
+                    && directions.get(type_name) != Some(&Direction::EncodeOnly)
+                {
+                    declaration.push_str(&emitter.decoders(type_name.name));
+                }
+                if let Some(hash) = constants.hashes.get(type_name) {
+                    declaration.push_str(&format!(
+                        "export const {} = 0x{:016x}n;\n",
+                        self.naming.ident(Ident::SchemaHash, type_name.name),
+                        hash
+                    ));
+                }
+                if let Some(size) = constants.max_sizes.get(type_name) {
+                    declaration.push_str(&format!(
+                        "export const {} = {};\n",
+                        self.naming.ident(Ident::MaxSize, type_name.name),
+                        size
+                    ));
+                }
+                match constants.docs.get(type_name) {
+                    Some(doc) => doc.annotate(&self.naming, type_name.name, &declaration),
+                    None => declaration,
+                }
+            })
+            .collect();
+
+        let mut builders: Vec<String> = emitter.builders.iter().map(|b| b.to_string()).collect();
+        // Recursive types are spelled out rather than inferred
+        let inferred = indices.iter().any(|&i| {
+            let type_name = &types[i].0;
+            !cycles.contains_key(type_name) && overrides.get(type_name).is_none_or(|o| o.infers())
+        });
+        if ["deserializeCrc", "salvage", "deserializeValue"]
+            .iter()
+            .any(|builder| emitter.builders.contains(builder))
+        {
+            builders.push("type DeserializeOptions".to_string());
+        }
+        if emitter.builders.contains("StreamDecoder") {
+            builders.push("type StreamDecoderOptions".to_string());
+        }
+        if inferred || indices.is_empty() {
+            builders.push("type InferType".to_string());
+        }
+        if emitter.generic || emitter.recursive {
+            builders.push("type Schema".to_string());
+        }
+        if emitter.generic {
+            builders.push("type SchemaOf".to_string());
+        }
+        if self.introspection {
+            builders.push("type ModuleSchema".to_string());
+        }
+
+        let mut source = format!("{}\nimport {{\n", self.header());
+        for builder in builders {
+            source.push_str(&format!("  {},\n", builder));
+        }
+        source.push_str(&format!(
+            "}} from \"{}\";\n",
+            runtime_import(&self.import_from, depth)
+        ));
+        if let Some(validator) = self.validator {
+            source.push_str(validator.import());
+        }
+        let mut override_imports = BTreeMap::new();
+        for &i in indices {
+            if let Some(ts_override) = overrides.get(&types[i].0) {
+                ts_override.imports(&mut override_imports);
+            }
+        }
+        for (module, names) in override_imports {
+            let names: Vec<&str> = names.into_iter().collect();
+            source.push_str(&format!(
+                "import {{ {} }} from \"{}\";\n",
+                names.join(", "),
+                runtime_import(module, depth)
+            ));
+        }
+        for (module, names) in imports {
+            source.push_str(&format!(
+                "import {{ {} }} from \"{}\";\n",
+                names.join(", "),
+                module
+            ));
+        }
+        let crc_used = ["serializeCrc", "deserializeCrc"]
+            .iter()
+            .any(|wrapper| emitter.builders.contains(wrapper));
+        if let (Some(crc), true) = (&self.crc, crc_used) {
+            source.push_str(&format!("\nconst crc = new Crc({});\n", crc.typescript()));
+        }
+        for declaration in declarations {
+            source.push('\n');
+            source.push_str(&declaration);
+        }
+        if self.introspection {
+            source.push('\n');
+            source.push_str(&self.module_schema(types, indices, constants));
+        }
+        Ok(source)
+    }
+
+    /// `__schema`, describing `types[i]` for each of `indices`
+    fn module_schema(
+        &self,
+        types: &[(TypeName, Definition)],
+        indices: &[usize],
+        constants: &Constants,
+    ) -> String {
+        let mut table = String::from(
+            "/** The types this module declares, for tools that look at them at runtime */\n\
+             export const __schema = {\n",
+        );
+        for &i in indices {
+            let (type_name, definition) = &types[i];
+            let (kind, details) = match definition {
+                Definition::Struct(fields) => {
+                    let fields: Vec<String> = fields.iter().map(|f| quote(f.name)).collect();
+                    ("struct", format!(", fields: [{}]", fields.join(", ")))
+                }
+                Definition::TupleStruct(items) => {
+                    ("tuple_struct", format!(", length: {}", items.len()))
+                }
+                Definition::NewtypeStruct(_) => ("newtype_struct", String::new()),
+                Definition::UnitStruct => ("unit_struct", String::new()),
+                Definition::Enum(variants) => {
+                    let variants: Vec<String> = variants
+                        .iter()
+                        .enumerate()
+                        .map(|(index, variant)| format!("{}: {}", property(variant.name), index))
+                        .collect();
+                    ("enum", format!(", variants: {{ {} }}", variants.join(", ")))
+                }
+            };
+            let mut entry = format!(
+                "kind: \"{}\", schema: {}{}",
+                kind,
+                self.naming.schema(type_name.name),
+                details
+            );
+            if !type_name.params.is_empty() {
+                let params: Vec<String> = type_name.params.iter().map(|p| quote(p)).collect();
+                entry.push_str(&format!(", params: [{}]", params.join(", ")));
+            }
+            if let Some(hash) = constants.introspected.get(type_name) {
+                entry.push_str(&format!(", hash: 0x{:016x}n", hash));
+            }
+            table.push_str(&format!(
+                "  {}: {{ {} }},\n",
+                property(&self.naming.type_name(type_name.name)),
+                entry
+            ));
+        }
+        table.push_str("} as const satisfies ModuleSchema;\n");
+        table
+    }
+
+    /// One declaration file declaring the type of `types[i]` for each of
+    /// `indices`, in order
+    fn render_declarations(
+        &self,
+        types: &[(TypeName, Definition)],
+        indices: &[usize],
+        emitter: &Emitter,
+        constants: &Constants,
+        depth: usize,
+        imports: &[(String, Vec<String>)],
+    ) -> String {
+        let mut import_lines = String::new();
+        // Only the types overrides import are needed to name their types
+        let mut override_imports = BTreeMap::new();
+        for &i in indices {
+            if let Some(ts_override) = emitter.overrides.get(&types[i].0) {
+                ts_override.imports(&mut override_imports);
+            }
+        }
+        for (module, names) in override_imports {
+            let names: Vec<&str> = names
+                .into_iter()
+                .filter_map(|name| name.strip_prefix("type "))
+                .collect();
+            if !names.is_empty() {
+                import_lines.push_str(&format!(
+                    "import type {{ {} }} from \"{}\";\n",
+                    names.join(", "),
+                    runtime_import(module, depth)
+                ));
+            }
+        }
+        for (module, names) in imports {
+            import_lines.push_str(&format!(
+                "import type {{ {} }} from \"{}\";\n",
+                names.join(", "),
+                module
+            ));
+        }
+
+        let mut source = self.header();
+        if !import_lines.is_empty() {
+            source.push('\n');
+            source.push_str(&import_lines);
+        }
+        for &i in indices {
+            let (type_name, definition) = &types[i];
+            let declared = emitter
+                .overrides
+                .get(type_name)
+                .and_then(|ts_override| ts_override.type_declaration(&self.naming, type_name.name));
+            let declaration =
+                declared.unwrap_or_else(|| emitter.type_declaration(type_name, definition));
+            source.push('\n');
+            match constants.docs.get(type_name) {
+                Some(doc) => {
+                    source.push_str(&doc.annotate(&self.naming, type_name.name, &declaration))
+                }
+                None => source.push_str(&declaration),
+            }
+        }
+        source
+    }
+
+    /// The JSON IR of every type registered with `#[derive(PostcardTs)]`
+    ///
+    /// The TypeScript runtime builds schemas from it at runtime (`schemasFromIr`),
+    /// for tools that decode types they were not compiled against.
+    pub fn generate_ir(&self) -> Result<String, GenerateError> {
+        self.generate_types_ir(registry::registered())
+    }
+
+    /// The JSON IR of the given types
+    ///
+    /// Unlike the TypeScript output, the IR refers to types by name, so it may be
+    /// recursive.
+    pub fn generate_types_ir(
+        &self,
+        types: Vec<(TypeName, Definition)>,
+    ) -> Result<String, GenerateError> {
+        self.check_wire_shapes()?;
+        let types = self.with_overrides(types)?;
+        check_names(&types)?;
+        check_missing(&types)?;
+        check_keys(&types)?;
+        Ok(ir::to_json(&types))
+    }
+
+    /// Compare the types exported by the hand-written TypeScript `source`
+    /// with the ones generated for every type registered with
+    /// `#[derive(PostcardTs)]`
+    ///
+    /// ```
+    /// use serde_postcard_ts::{Generator, PostcardTs};
+    ///
+    /// #[derive(PostcardTs)]
+    /// struct Reading {
+    ///     sensor_id: u8,
+    ///     at: u64,
+    /// }
+    ///
+    /// let migration = Generator::new()
+    ///     .migrate_ts("export interface Reading { sensorId: number; at: number }")?;
+    /// assert_eq!(
+    ///     migration.report(),
+    ///     "adapted   Reading.sensorId: named sensor_id in generated\n\
+    ///      adapted   Reading.at: bigint in generated, number here, exact up to 2^53\n\
+    ///      2 differences, 0 not adapted\n"
+    /// );
+    /// assert!(migration.shims("./generated.js", "./types.js").contains("at: BigInt(value.at),"));
+    /// # Ok::<(), serde_postcard_ts::GenerateError>(())
+    /// ```
+    pub fn migrate_ts(&self, source: &str) -> Result<TsMigration, GenerateError> {
+        self.migrate_types_ts(registry::registered(), source)
+    }
+
+    /// Compare the types exported by the hand-written TypeScript `source`
+    /// with the ones generated for `types`
+    pub fn migrate_types_ts(
+        &self,
+        types: Vec<(TypeName, Definition)>,
+        source: &str,
+    ) -> Result<TsMigration, GenerateError> {
+        let types = self.with_overrides(types)?;
+        check_names(&types)?;
+        self.naming.check()?;
+        Ok(ts_migration::compare(
+            &types,
+            &self.naming,
+            self.u8_vecs_as_bytes,
+            source,
+        ))
+    }
+
+    /// The wire layout of every type registered with `#[derive(PostcardTs)]`
+    ///
+    /// Lists where each field sits on the wire and how it is encoded, for
+    /// editor tooling; see `postcard-ts generate --lsp-json`.
+    pub fn generate_wire_layout(&self) -> Result<String, GenerateError> {
+        self.generate_types_wire_layout(registry::registered())
+    }
+
+    /// The wire layout of the given types
+    ///
+    /// Like the IR, the layout may describe recursive types; a field holding its
+    /// own type is one segment of any size.
+    pub fn generate_types_wire_layout(
+        &self,
+        types: Vec<(TypeName, Definition)>,
+    ) -> Result<String, GenerateError> {
+        self.check_wire_shapes()?;
+        let types = self.with_overrides(types)?;
+        check_names(&types)?;
+        check_missing(&types)?;
+        Ok(wire_layout::to_json(&types))
+    }
+}
+
+/// Reject references to types that are not being generated
+/// Record `direction` for `type_name`, unless it has the other
+fn set_direction(
+    directions: &mut HashMap<TypeName, Direction>,
+    type_name: TypeName,
+    direction: Direction,
+) -> Result<(), GenerateError> {
+    match directions.insert(type_name, direction) {
+        Some(other) if other != direction => Err(GenerateError::DirectionConflict {
+            name: type_name.name,
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn check_missing(types: &[(TypeName, Definition)]) -> Result<(), GenerateError> {
+    for (type_name, definition) in types {
+        if let Some(missing) = references(definition)
+            .into_iter()
+            .find(|reference| !types.iter().any(|(other, _)| other == reference))
+        {
+            return Err(GenerateError::Missing {
+                name: missing.name,
+                referenced_by: type_name.name,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reject maps keyed by floats, which decode to `number` keys that a JS `Map`
+/// compares with SameValueZero
+fn check_keys(types: &[(TypeName, Definition)]) -> Result<(), GenerateError> {
+    fn float_key(
+        shape: &Shape,
+        types: &[(TypeName, Definition)],
+        seen: &mut Vec<TypeName>,
+    ) -> bool {
+        match shape {
+            Shape::F32 | Shape::F64 => true,
+            Shape::Option(inner) => float_key(inner, types, seen),
+            // Newtype structs decode to their inner value
+            Shape::Named(type_name) if !seen.contains(type_name) => {
+                seen.push(*type_name);
+                types.iter().any(|(other, definition)| {
+                    other == type_name
+                        && matches!(definition, Definition::NewtypeStruct(inner) if float_key(inner, types, seen))
+                })
+            }
+            _ => false,
+        }
+    }
+
+    fn visit(shape: &Shape, types: &[(TypeName, Definition)], found: &mut bool) {
+        match shape {
+            Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+                *found |= float_key(key, types, &mut Vec::new());
+                visit(key, types, found);
+                visit(value, types, found);
+            }
+            Shape::Option(inner) | Shape::Seq(inner) | Shape::BoundedSeq(inner, _) => {
+                visit(inner, types, found)
+            }
+            Shape::Tuple(items) | Shape::Generic(_, items) => {
+                items.iter().for_each(|item| visit(item, types, found))
+            }
+            _ => {}
+        }
+    }
+
+    for (type_name, definition) in types {
+        let mut found = false;
+        definition_shapes(definition, &mut |shape| visit(shape, types, &mut found));
+        if found {
+            return Err(GenerateError::FloatKey {
+                name: type_name.name,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reject types sharing a name
+fn check_names(types: &[(TypeName, Definition)]) -> Result<(), GenerateError> {
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    for (type_name, _) in types {
+        if let Some(module) = names.insert(type_name.name, type_name.module) {
+            return Err(GenerateError::DuplicateName {
+                name: type_name.name,
+                modules: [module, type_name.module],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Generate every type registered with `#[derive(PostcardTs)]`, importing the
+/// builders from the published package
+pub fn generate_all() -> Result<String, GenerateError> {
+    Generator::new().generate()
+}
+
+/// The order types are declared in, found with Tarjan's algorithm
+struct Order {
+    /// Indices of `types` such that every type comes after the types it refers
+    /// to, but for types referring to each other in a circle
+    indices: Vec<usize>,
+    /// The recursive types, by the circle of types they are part of; types of
+    /// one circle come together and refer to each other through `lazy()`
+    cycles: HashMap<TypeName, usize>,
+}
+
+fn dependency_order(types: &[(TypeName, Definition)]) -> Result<Order, GenerateError> {
+    let mut search = Search {
+        types,
+        index: types
+            .iter()
+            .enumerate()
+            .map(|(i, (type_name, _))| (*type_name, i))
+            .collect(),
+        visits: vec![None; types.len()],
+        visited: 0,
+        stack: Vec::new(),
+        order: Order {
+            indices: Vec::with_capacity(types.len()),
+            cycles: HashMap::new(),
+        },
+    };
+    for i in 0..types.len() {
+        if search.visits[i].is_none() {
+            search.visit(i)?;
+        }
+    }
+    Ok(search.order)
+}
+
+/// State of the depth-first search of [`dependency_order`]
+struct Search<'t> {
+    types: &'t [(TypeName, Definition)],
+    index: HashMap<TypeName, usize>,
+    /// Per type, when it was visited and the earliest visit still on the
+    /// stack it refers to, directly or not
+    visits: Vec<Option<(usize, usize)>>,
+    visited: usize,
+    /// Types visited whose circle is not complete yet
+    stack: Vec<usize>,
+    order: Order,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, i: usize) -> Result<(), GenerateError> {
+        let visit = self.visited;
+        self.visited += 1;
+        self.visits[i] = Some((visit, visit));
+        self.stack.push(i);
+
+        let mut earliest = visit;
+        for dependency in references(&self.types[i].1) {
+            let &j = self.index.get(&dependency).ok_or(GenerateError::Missing {
+                name: dependency.name,
+                referenced_by: self.types[i].0.name,
+            })?;
+            match self.visits[j] {
+                None => {
+                    self.visit(j)?;
+                    earliest = earliest.min(self.visits[j].map_or(earliest, |(_, e)| e));
+                }
+                Some((other, _)) if self.stack.contains(&j) => earliest = earliest.min(other),
+                Some(_) => {}
+            }
+        }
+        self.visits[i] = Some((visit, earliest));
+        if earliest != visit {
+            return Ok(());
+        }
+
+        // `i` is the first visited of a circle, or a type outside any
+        let start = self.stack.iter().rposition(|&p| p == i).unwrap_or(0);
+        let members: Vec<usize> = self.stack.drain(start..).collect();
+        let recursive =
+            members.len() > 1 || references(&self.types[i].1).contains(&self.types[i].0);
+        if recursive {
+            if let Some(&generic) = members
+                .iter()
+                .find(|&&m| !self.types[m].0.params.is_empty())
+            {
+                return Err(GenerateError::Recursive(self.cycle(generic, &members)));
+            }
+            let cycle = self.order.cycles.len();
+            for &m in &members {
+                self.order.cycles.insert(self.types[m].0, cycle);
+            }
+        }
+        self.order.indices.extend(members);
+        Ok(())
+    }
+
+    /// The names along the shortest circle of references from `types[start]`
+    /// back to itself, through `members`
+    fn cycle(&self, start: usize, members: &[usize]) -> Vec<&'static str> {
+        let mut previous: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            for dependency in references(&self.types[i].1) {
+                let j = self.index[&dependency];
+                if !members.contains(&j) || previous.contains_key(&j) {
+                    continue;
+                }
+                previous.insert(j, i);
+                if j == start {
+                    queue.clear();
+                    break;
+                }
+                queue.push_back(j);
+            }
+        }
+        let mut path = vec![self.types[start].0.name];
+        let mut at = start;
+        while let Some(&p) = previous.get(&at) {
+            path.push(self.types[p].0.name);
+            at = p;
+            if at == start {
+                break;
+            }
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Reject module dependencies that go round in a circle
+fn check_module_cycles(types: &[(TypeName, Definition)]) -> Result<(), GenerateError> {
+    let modules: Vec<&'static str> = types
+        .iter()
+        .map(|(type_name, _)| type_name.module)
+        .collect();
+    match unit_cycle(types, &modules) {
+        Some(cycle) => Err(GenerateError::ModuleCycle(cycle)),
+        None => Ok(()),
+    }
+}
+
+/// Units (modules, groups) whose types use each other's types in a circle,
+/// `units[i]` being the unit of `types[i]`
+fn unit_cycle<U: Ord + Clone>(types: &[(TypeName, Definition)], units: &[U]) -> Option<Vec<U>> {
+    let unit_of: HashMap<TypeName, &U> = types
+        .iter()
+        .zip(units)
+        .map(|((type_name, _), unit)| (*type_name, unit))
+        .collect();
+    let mut uses: BTreeMap<&U, BTreeSet<&U>> = BTreeMap::new();
+    for ((_, definition), unit) in types.iter().zip(units) {
+        let used = uses.entry(unit).or_default();
+        for reference in references(definition) {
+            if let Some(&other) = unit_of.get(&reference) {
+                if other != unit {
+                    used.insert(other);
+                }
+            }
+        }
+    }
+
+    fn visit<'u, U: Ord + Clone>(
+        unit: &'u U,
+        uses: &BTreeMap<&'u U, BTreeSet<&'u U>>,
+        path: &mut Vec<&'u U>,
+        done: &mut BTreeSet<&'u U>,
+    ) -> Option<Vec<U>> {
+        if done.contains(unit) {
+            return None;
+        }
+        if let Some(start) = path.iter().position(|&u| u == unit) {
+            let mut cycle: Vec<U> = path[start..].iter().map(|&u| u.clone()).collect();
+            cycle.push(unit.clone());
+            return Some(cycle);
+        }
+        path.push(unit);
+        for &used in uses.get(unit).into_iter().flatten() {
+            if let Some(cycle) = visit(used, uses, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(unit);
+        None
+    }
+
+    let mut done = BTreeSet::new();
+    uses.keys()
+        .find_map(|&unit| visit(unit, &uses, &mut Vec::new(), &mut done))
+}
+
+/// `my_crate::proto::sensors` is generated as `my_crate/proto/sensors.ts`
+fn module_file(module: &str) -> String {
+    format!("{}.ts", module.replace("::", "/"))
+}
+
+/// Import specifier of the file `to` from the file `from`, both relative to
+/// the output directory, with `.ts` replaced by `extension`
+fn relative_import(from: &str, to: &str, extension: &str) -> String {
+    let from_dirs: Vec<&str> = from.split('/').collect();
+    let from_dirs = &from_dirs[..from_dirs.len() - 1];
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from_dirs
+        .iter()
+        .zip(&to_parts[..to_parts.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+    let up = match from_dirs.len() - common {
+        0 => "./".to_string(),
+        n => "../".repeat(n),
+    };
+    let rest = to_parts[common..].join("/");
+    format!(
+        "{}{}{}",
+        up,
+        rest.strip_suffix(".ts").unwrap_or(&rest),
+        extension
+    )
+}
+
+/// The runtime import from a file `depth` directories below the output
+/// directory; package names are left alone
+fn runtime_import(import_from: &str, depth: usize) -> String {
+    if depth == 0 || !import_from.starts_with('.') {
+        return import_from.to_string();
+    }
+    let path = import_from.strip_prefix("./").unwrap_or(import_from);
+    format!("{}{}", "../".repeat(depth), path)
+}
+
+/// The named types a definition refers to
+pub(crate) fn references(definition: &Definition) -> Vec<TypeName> {
+    let mut references = Vec::new();
+    definition_shapes(definition, &mut |shape| named(shape, &mut references));
+    references
+}
+
+fn definition_shapes(definition: &Definition, f: &mut impl FnMut(&Shape)) {
+    let fields = |fields: &[Field], f: &mut dyn FnMut(&Shape)| {
+        fields.iter().for_each(|field| f(&field.shape))
+    };
+    match definition {
+        Definition::Struct(items) => fields(items, f),
+        Definition::TupleStruct(items) => items.iter().for_each(f),
+        Definition::NewtypeStruct(inner) => f(inner),
+        Definition::UnitStruct => {}
+        Definition::Enum(variants) => {
+            for variant in variants {
+                match &variant.kind {
+                    VariantKind::Unit => {}
+                    VariantKind::Newtype(inner) => f(inner),
+                    VariantKind::Tuple(items) => items.iter().for_each(&mut *f),
+                    VariantKind::Struct(items) => fields(items, f),
+                }
+            }
+        }
+    }
+}
+
+/// Collect the named types a shape refers to
+fn named(shape: &Shape, out: &mut Vec<TypeName>) {
+    match shape {
+        Shape::Named(type_name) => out.push(*type_name),
+        Shape::Generic(type_name, args) => {
+            out.push(*type_name);
+            args.iter().for_each(|arg| named(arg, out));
+        }
+        Shape::Option(inner) | Shape::Seq(inner) | Shape::BoundedSeq(inner, _) => named(inner, out),
+        Shape::Tuple(items) => items.iter().for_each(|item| named(item, out)),
+        Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+            named(key, out);
+            named(value, out);
+        }
+        _ => {}
+    }
+}
+
+/// The named types used as map keys anywhere in `types`
+fn key_types(types: &[(TypeName, Definition)]) -> BTreeSet<TypeName> {
+    fn visit(shape: &Shape, out: &mut BTreeSet<TypeName>) {
+        match shape {
+            Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+                if let Shape::Named(type_name) = **key {
+                    out.insert(type_name);
+                }
+                visit(key, out);
+                visit(value, out);
+            }
+            Shape::Option(inner) | Shape::Seq(inner) | Shape::BoundedSeq(inner, _) => {
+                visit(inner, out)
+            }
+            Shape::Tuple(items) | Shape::Generic(_, items) => {
+                items.iter().for_each(|item| visit(item, out))
+            }
+            _ => {}
+        }
+    }
+
+    let mut keys = BTreeSet::new();
+    for (_, definition) in types {
+        definition_shapes(definition, &mut |shape| visit(shape, &mut keys));
+    }
+    keys
+}
+
+/// Constants emitted after a type's declaration, and the comments on it
+#[derive(Default)]
+struct Constants {
+    hashes: HashMap<TypeName, u64>,
+    max_sizes: HashMap<TypeName, usize>,
+    docs: HashMap<TypeName, TypeDoc>,
+    /// The hashes `__schema` states, with [`Generator::introspection`]
+    introspected: HashMap<TypeName, u64>,
+}
+
+/// Renders schema expressions, recording which builders they use
+struct Emitter<'a> {
+    enum_tag: Option<&'a str>,
+    crc: Option<&'a CrcAlgorithm>,
+    truncate_strings: bool,
+    u8_vecs_as_bytes: bool,
+    builders: BTreeSet<&'static str>,
+    /// Whether a generic type was declared, which needs the `Schema` types
+    generic: bool,
+    /// The circle of recursive types each is part of
+    cycles: &'a HashMap<TypeName, usize>,
+    /// The circle of the type being declared, whose types it refers to lazily
+    cycle: Option<usize>,
+    /// Whether a recursive type was declared, which needs the `Schema` type
+    recursive: bool,
+    overrides: &'a BTreeMap<TypeName, Cow<'a, TsOverride>>,
+    /// The types limited to one direction
+    directions: &'a HashMap<TypeName, Direction>,
+    /// The doc comments of registered types, for their fields and variants
+    docs: &'a HashMap<TypeName, &'static Docs>,
+    representations: Representations,
+    /// Whether newtypes decode to a type branded with their name
+    branded_newtypes: bool,
+    naming: &'a Naming,
+}
+
+impl Emitter<'_> {
+    fn declaration(&mut self, type_name: &TypeName, definition: &Definition) -> String {
+        let name = type_name.name;
+        let ty = self.naming.type_name(name);
+        let schema_name = self.naming.schema(name);
+        self.cycle = self.cycles.get(type_name).copied();
+        if let Some(ts_override) = self.overrides.get(type_name) {
+            let wire = if ts_override.is_external() {
+                self.builders.insert("external");
+                String::new()
+            } else {
+                if ts_override.transforms() {
+                    self.builders.insert("transform");
+                }
+                self.shape(ts_override.wire())
+            };
+            let direction = self.direction(type_name);
+            return ts_override.declaration(self.naming, name, &wire, direction);
+        }
+        if let Some(builtin) = builtin::builtin(type_name) {
+            self.builders.insert(builtin.builder);
+            let representations = self.representations;
+            let schema = builtin.schema(representations, |inner| self.shape(inner));
+            let schema = match self.direction(type_name) {
+                Some(builder) => format!("{}({})", builder, schema),
+                None => schema,
+            };
+            return format!(
+                "export const {schema_name} = {schema};\nexport type {ty} = InferType<typeof {schema_name}>;\n",
+            );
+        }
+        let docs = self.type_docs(type_name);
+        let schema = match definition {
+            Definition::Struct(fields) if !fields.is_empty() => {
+                self.builders.insert("struct");
+                let lines: String = fields
+                    .iter()
+                    .map(|field| {
+                        let shape = self.shape(&field.shape);
+                        let doc = docs.field(field.name).jsdoc("  ");
+                        format!("{}  {}: {},\n", doc, property(field.name), shape)
+                    })
+                    .collect();
+                format!("struct({{\n{}}})", lines)
+            }
+            Definition::Struct(_) => self.call("struct", vec!["{}".to_string()]),
+            Definition::TupleStruct(items) => {
+                let mut args = vec![quote(name)];
+                args.extend(items.iter().map(|item| self.shape(item)));
+                self.call("tupleStruct", args)
+            }
+            Definition::NewtypeStruct(inner) => {
+                let inner = self.shape(inner);
+                let schema = self.call("newtypeStruct", vec![quote(name), inner]);
+                if self.branded(type_name) {
+                    self.call("branded", vec![schema, quote(name)])
+                } else {
+                    schema
+                }
+            }
+            Definition::UnitStruct => self.call("unitStruct", vec![quote(name)]),
+            // Uninhabited: no value can be decoded
+            Definition::Enum(variants) if variants.is_empty() => self.call("never", vec![]),
+            Definition::Enum(variants) => {
+                self.builders.insert("enumType");
+                let lines: String = variants
+                    .iter()
+                    .map(|variant| {
+                        let label = quote(variant.name);
+                        let docs = docs.variant(variant.name);
+                        let schema = match &variant.kind {
+                            VariantKind::Unit => self.call("unitVariant", vec![label]),
+                            VariantKind::Newtype(inner) => {
+                                let inner = self.shape(inner);
+                                self.call("newtypeVariant", vec![label, inner])
+                            }
+                            VariantKind::Tuple(items) => {
+                                let mut args = vec![label];
+                                args.extend(items.iter().map(|item| self.shape(item)));
+                                self.call("tupleVariant", args)
+                            }
+                            VariantKind::Struct(fields) => {
+                                let fields = self.fields(fields, &docs);
+                                self.call("structVariant", vec![label, fields])
+                            }
+                        };
+                        let doc = docs.doc.jsdoc("  ");
+                        format!("{}  {}: {},\n", doc, property(variant.name), schema)
+                    })
+                    .collect();
+                let schema = format!("enumType({}, {{\n{}}})", quote(name), lines);
+                match self.enum_tag {
+                    Some(tag) => self.call("discriminated", vec![schema, quote(tag)]),
+                    None => schema,
+                }
+            }
+        };
+
+        let schema = match self.direction(type_name) {
+            Some(builder) => format!("{}({})", builder, schema),
+            None => schema,
+        };
+
+        // The type of a recursive schema cannot be inferred from its own
+        // initializer, so it is spelled out
+        if self.cycle.is_some() {
+            self.recursive = true;
+            let declared = self.type_declaration(type_name, definition);
+            return format!("{declared}export const {schema_name} = {schema};\n");
+        }
+        if type_name.params.is_empty() {
+            return format!(
+                "export const {schema_name} = {schema};\nexport type {ty} = InferType<typeof {schema_name}>;\n",
+            );
+        }
+
+        // A generic type is a schema factory taking a schema per type parameter;
+        // SchemaOf<T> names the value type through the factory's return type
+        self.generic = true;
+        let params = type_name.params;
+        let bounds: Vec<String> = params
+            .iter()
+            .map(|p| format!("{} extends Schema", p))
+            .collect();
+        let args: Vec<String> = params.iter().map(|p| format!("{}: {}", p, p)).collect();
+        let schema_of: Vec<String> = params.iter().map(|p| format!("SchemaOf<{}>", p)).collect();
+        format!(
+            "export const {schema_name} = <{}>({}) =>\n  {};\nexport type {ty}<{}> = InferType<ReturnType<typeof {schema_name}<{}>>>;\n",
+            bounds.join(", "),
+            args.join(", "),
+            schema.replace('\n', "\n  "),
+            params.join(", "),
+            schema_of.join(", "),
+        )
+    }
+
+    fn shape(&mut self, shape: &Shape) -> String {
+        let primitive = match shape {
+            Shape::Bool => "bool",
+            Shape::I8 => "i8",
+            Shape::I16 => "i16",
+            Shape::I32 => "i32",
+            Shape::I64 => "i64",
+            Shape::I128 => "i128",
+            Shape::U8 => "u8",
+            Shape::U16 => "u16",
+            Shape::U32 => "u32",
+            Shape::U64 => "u64",
+            Shape::U128 => "u128",
+            Shape::F32 => "f32",
+            Shape::F64 => "f64",
+            Shape::Char => "char",
+            Shape::String => "string",
+            Shape::BoundedString(max) => {
+                let overflow = if self.truncate_strings {
+                    ", overflow: \"truncate\""
+                } else {
+                    ""
+                };
+                return self.call(
+                    "string",
+                    vec![format!("{{ maxBytes: {}{} }}", max, overflow)],
+                );
+            }
+            Shape::Bytes => "bytes",
+            Shape::Seq(item) if self.u8_vecs_as_bytes && **item == Shape::U8 => "bytes",
+            Shape::Unit => "unit",
+            Shape::Option(inner) => {
+                let inner = self.shape(inner);
+                return self.call("option", vec![inner]);
+            }
+            Shape::Seq(item) => {
+                let item = self.shape(item);
+                return self.call("seq", vec![item]);
+            }
+            Shape::BoundedSeq(item, max) => {
+                let args = vec![self.shape(item), format!("{{ maxLength: {} }}", max)];
+                return self.call("seq", args);
+            }
+            Shape::Tuple(items) => {
+                if let Some(item) = array_item(items) {
+                    let args = vec![self.shape(item), items.len().to_string()];
+                    return self.call("array", args);
+                }
+                let items = items.iter().map(|item| self.shape(item)).collect();
+                return self.call("tuple", items);
+            }
+            Shape::Map(key, value) => {
+                let args = vec![self.shape(key), self.shape(value)];
+                return self.call("map", args);
+            }
+            Shape::BoundedMap(key, value, max) => {
+                let args = vec![
+                    self.shape(key),
+                    self.shape(value),
+                    format!("{{ maxLength: {} }}", max),
+                ];
+                return self.call("map", args);
+            }
+            // Declared later or being declared: resolved when first used
+            Shape::Named(type_name)
+                if self.cycle.is_some() && self.cycles.get(type_name) == self.cycle.as_ref() =>
+            {
+                self.builders.insert("lazy");
+                let ty = self.naming.type_name(type_name.name);
+                let schema = self.naming.schema(type_name.name);
+                return format!("lazy<{ty}>((): Schema => {schema})");
+            }
+            Shape::Named(type_name) => return self.naming.schema(type_name.name),
+            Shape::Generic(type_name, args) => {
+                let args: Vec<String> = args.iter().map(|arg| self.shape(arg)).collect();
+                return format!(
+                    "{}({})",
+                    self.naming.schema(type_name.name),
+                    args.join(", ")
+                );
+            }
+            // The argument of the schema factory
+            Shape::Param(name) => return name.to_string(),
+        };
+        self.call(primitive, vec![])
+    }
+
+    /// The declaration of `type_name` as the TypeScript type values of
+    /// `definition` decode to, spelled out rather than inferred
+    fn type_declaration(&self, type_name: &TypeName, definition: &Definition) -> String {
+        let name = self.naming.type_name(type_name.name);
+        let builtin =
+            builtin::builtin(type_name).filter(|_| !self.overrides.contains_key(type_name));
+        if let Some(builtin) = builtin {
+            let ts_type = builtin.ts_type(self.representations);
+            return format!("export type {name} = {ts_type};\n");
+        }
+        let params = match type_name.params {
+            [] => String::new(),
+            params => format!("<{}>", params.join(", ")),
+        };
+        match definition {
+            Definition::Struct(fields) if !fields.is_empty() => {
+                let docs = self.type_docs(type_name);
+                format!(
+                    "export interface {name}{params} {}\n",
+                    self.fields_type(fields, Some(&docs))
+                )
+            }
+            Definition::NewtypeStruct(inner) if self.branded(type_name) => {
+                let declared = self.value_type(inner);
+                let declared = if declared.contains(" | ") {
+                    format!("({declared})")
+                } else {
+                    declared
+                };
+                // The runtime's `Brand`, spelled out
+                format!(
+                    "export type {name} = {declared} & {{ readonly __brand: {} }};\n",
+                    quote(type_name.name)
+                )
+            }
+            _ => {
+                // Enums are a union of variants, one per line
+                let declared = self.definition_type(definition);
+                let space = if declared.starts_with('\n') { "" } else { " " };
+                format!("export type {name}{params} ={space}{declared};\n")
+            }
+        }
+    }
+
+    /// The TypeScript type values of `definition` decode to, as `InferType`
+    /// gives it
+    fn definition_type(&self, definition: &Definition) -> String {
+        match definition {
+            Definition::Struct(fields) => self.fields_type(fields, None),
+            Definition::TupleStruct(items) => self.tuple_type(items),
+            Definition::NewtypeStruct(inner) => self.value_type(inner),
+            Definition::UnitStruct => "Record<string, never>".to_string(),
+            Definition::Enum(variants) if variants.is_empty() => "never".to_string(),
+            Definition::Enum(variants) => {
+                let tag = property(self.enum_tag.unwrap_or("type"));
+                variants
+                    .iter()
+                    .map(|variant| {
+                        let label = quote(variant.name);
+                        let members = match &variant.kind {
+                            VariantKind::Unit => format!("{tag}: {label}"),
+                            VariantKind::Newtype(inner) => {
+                                format!("{tag}: {label}; value: {}", self.value_type(inner))
+                            }
+                            VariantKind::Tuple(items) => {
+                                format!("{tag}: {label}; value: {}", self.tuple_type(items))
+                            }
+                            // discriminated() puts the fields next to the tag
+                            VariantKind::Struct(fields) if self.enum_tag.is_some() => fields
+                                .iter()
+                                .fold(format!("{tag}: {label}"), |members, field| {
+                                    let shape = self.value_type(&field.shape);
+                                    format!("{members}; {}: {shape}", property(field.name))
+                                }),
+                            VariantKind::Struct(fields) => {
+                                let fields = self.fields_type(fields, None);
+                                format!("{tag}: {label}; value: {fields}")
+                            }
+                        };
+                        format!("\n  | {{ {members} }}")
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The TypeScript type of a struct's fields, all on one line, or one per
+    /// line after its doc comment from `docs`
+    fn fields_type(&self, fields: &[Field], one_per_line: Option<&Docs>) -> String {
+        if fields.is_empty() {
+            return "{}".to_string();
+        }
+        let types: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}: {}",
+                    property(field.name),
+                    self.value_type(&field.shape)
+                )
+            })
+            .collect();
+        match one_per_line {
+            Some(docs) => {
+                let lines: String = fields
+                    .iter()
+                    .zip(&types)
+                    .map(|(field, declared)| {
+                        format!("{}  {};\n", docs.field(field.name).jsdoc("  "), declared)
+                    })
+                    .collect();
+                format!("{{\n{}}}", lines)
+            }
+            None => format!("{{ {} }}", types.join("; ")),
+        }
+    }
+
+    fn tuple_type(&self, items: &[Shape]) -> String {
+        tuple_type(items, self.naming, self.u8_vecs_as_bytes)
+    }
+
+    fn value_type(&self, shape: &Shape) -> String {
+        value_type(shape, self.naming, self.u8_vecs_as_bytes)
+    }
+
+    /// A stable string per key for a type used as a map key: its values decode
+    /// to objects, which a `Map` compares by identity
+    fn key_helper(&mut self, name: &str) -> String {
+        let helper = self.call("canonicalKey", vec![self.naming.schema(name)]);
+        format!(
+            "export const {} = {};\n",
+            self.naming.ident(Ident::Key, name),
+            helper
+        )
+    }
+
+    /// A check per variant of whether a frame holds it, reading only the
+    /// variant index: `isTelemetryAlarm` for `Telemetry::Alarm`
+    fn variant_peeks(&mut self, name: &str, variants: &[Variant]) -> String {
+        variants
+            .iter()
+            .map(|variant| {
+                let filter = self.call(
+                    "variantFilter",
+                    vec![
+                        self.naming.schema(name),
+                        format!("[{}]", quote(variant.name)),
+                    ],
+                );
+                format!(
+                    "export const {} = {};\n",
+                    self.naming.variant_peek(name, variant.name),
+                    filter
+                )
+            })
+            .collect()
+    }
+
+    /// The name to show people for each variant, `PhaseDisplayNames`: the
+    /// one registered with `#[postcard_ts(display = "...")]`, or the
+    /// variant's name as words
+    fn display_names(&self, type_name: &TypeName, variants: &[Variant]) -> String {
+        let docs = self.type_docs(type_name);
+        let mut table = format!(
+            "export const {} = {{\n",
+            self.naming.ident(Ident::DisplayNames, type_name.name)
+        );
+        for variant in variants {
+            let display = match docs.variant(variant.name).display {
+                Some(display) => display.to_string(),
+                None => humanize(variant.name),
+            };
+            table.push_str(&format!(
+                "  {}: {},\n",
+                property(variant.name),
+                ir::string(&display)
+            ));
+        }
+        table.push_str("} as const;\n");
+        table
+    }
+
+    /// The builder limiting the schema of `type_name` to its direction, if
+    /// it has one
+    /// Whether `type_name`, a newtype, decodes to a type branded with its name
+    fn branded(&self, type_name: &TypeName) -> bool {
+        self.branded_newtypes && type_name.params.is_empty() && !self.cycles.contains_key(type_name)
+    }
+
+    fn direction(&mut self, type_name: &TypeName) -> Option<&'static str> {
+        let builder = match self.directions.get(type_name)? {
+            Direction::EncodeOnly => "encodeOnly",
+            Direction::DecodeOnly => "decodeOnly",
+        };
+        self.builders.insert(builder);
+        Some(builder)
+    }
+
+    /// Wrappers appending and checking the generator's CRC, only the one for
+    /// the direction of a type limited to one
+    fn crc_wrappers(&mut self, type_name: &TypeName) -> String {
+        let name = type_name.name;
+        let ty = self.naming.type_name(name);
+        self.builders.insert("Crc");
+        if let Some(constant) = self.crc.and_then(CrcAlgorithm::name) {
+            self.builders.insert(constant);
+        }
+        let direction = self.directions.get(type_name).copied();
+        let mut wrappers = String::new();
+        if direction != Some(Direction::DecodeOnly) {
+            let serialize = self.call(
+                "serializeCrc",
+                vec![
+                    self.naming.schema(name),
+                    "value".to_string(),
+                    "crc".to_string(),
+                ],
+            );
+            wrappers.push_str(&format!(
+                "export const {} = (value: {ty}): Uint8Array =>\n  {serialize};\n",
+                self.naming.ident(Ident::SerializeCrc, name)
+            ));
+        }
+        if direction != Some(Direction::EncodeOnly) {
+            let deserialize = self.call(
+                "deserializeCrc",
+                vec![
+                    self.naming.schema(name),
+                    "data".to_string(),
+                    "crc".to_string(),
+                    "offset".to_string(),
+                    "options".to_string(),
+                ],
+            );
+            wrappers.push_str(&format!(
+                "export const {} = (\n  \
+                 data: Uint8Array,\n  \
+                 offset?: number,\n  \
+                 options?: DeserializeOptions\n\
+                 ) => {deserialize};\n",
+                self.naming.ident(Ident::DeserializeCrc, name)
+            ));
+        }
+        wrappers
+    }
+
+    /// A decoder of the batch `name`, keeping the records of its list
+    /// `field` decoded before any failure
+    fn salvage_wrapper(&mut self, name: &str, field: &str) -> String {
+        let salvage = self.call(
+            "salvage",
+            vec![
+                self.naming.schema(name),
+                quote(field),
+                "data".to_string(),
+                "offset".to_string(),
+                "options".to_string(),
+            ],
+        );
+        format!(
+            "export const {} = (\n  \
+             data: Uint8Array,\n  \
+             offset?: number,\n  \
+             options?: DeserializeOptions\n\
+             ) => {salvage};\n",
+            self.naming.ident(Ident::Salvage, name)
+        )
+    }
+
+    /// The decoders of `name`: throwing, returning a `Result`, and from a
+    /// stream
+    fn decoders(&mut self, name: &str) -> String {
+        let args = vec![
+            self.naming.schema(name),
+            "data".to_string(),
+            "offset".to_string(),
+            "options".to_string(),
+        ];
+        let decode = self.call("deserializeValue", args.clone());
+        let try_decode = self.call("tryDeserializeValue", args);
+        self.builders.insert("StreamDecoder");
+        let mut decoders = String::new();
+        for (ident, call) in [(Ident::Decode, decode), (Ident::TryDecode, try_decode)] {
+            decoders.push_str(&format!(
+                "export const {} = (\n  \
+                 data: Uint8Array,\n  \
+                 offset?: number,\n  \
+                 options?: DeserializeOptions\n\
+                 ) => {call};\n",
+                self.naming.ident(ident, name)
+            ));
+        }
+        decoders.push_str(&format!(
+            "export const {} = (options?: StreamDecoderOptions) =>\n  \
+             new StreamDecoder({}, options);\n",
+            self.naming.ident(Ident::StreamDecoder, name),
+            self.naming.schema(name)
+        ));
+        decoders
+    }
+
+    /// The fields of a struct variant, on one line unless some have doc
+    /// comments in `docs`, which are then one per line after their comments
+    fn fields(&mut self, fields: &[Field], docs: &VariantDocs) -> String {
+        if fields.is_empty() {
+            return "{}".to_string();
+        }
+        let schemas: Vec<String> = fields
+            .iter()
+            .map(|field| format!("{}: {}", property(field.name), self.shape(&field.shape)))
+            .collect();
+        if fields.iter().all(|field| docs.field(field.name).is_empty()) {
+            return format!("{{ {} }}", schemas.join(", "));
+        }
+        let lines: String = fields
+            .iter()
+            .zip(&schemas)
+            .map(|(field, schema)| {
+                format!("{}    {},\n", docs.field(field.name).jsdoc("    "), schema)
+            })
+            .collect();
+        format!("{{\n{}  }}", lines)
+    }
+
+    /// The doc comments of `type_name` and its fields and variants
+    fn type_docs(&self, type_name: &TypeName) -> Docs {
+        self.docs
+            .get(type_name)
+            .map(|docs| **docs)
+            .unwrap_or_default()
+    }
+
+    fn call(&mut self, builder: &'static str, args: Vec<String>) -> String {
+        self.builders.insert(builder);
+        format!("{}({})", builder, args.join(", "))
+    }
+}
+
+/// The TypeScript type values of `shape` decode to, `Vec<u8>` as a
+/// `Uint8Array` with `u8_vecs_as_bytes`
+pub(crate) fn value_type(shape: &Shape, naming: &Naming, u8_vecs_as_bytes: bool) -> String {
+    let value_type = |shape| value_type(shape, naming, u8_vecs_as_bytes);
+    let array_type = |item| match value_type(item) {
+        item if item.contains(" | ") => format!("({})[]", item),
+        item => format!("{}[]", item),
+    };
+    match shape {
+        Shape::Bool => "boolean".to_string(),
+        Shape::I8
+        | Shape::I16
+        | Shape::I32
+        | Shape::U8
+        | Shape::U16
+        | Shape::U32
+        | Shape::F32
+        | Shape::F64 => "number".to_string(),
+        Shape::I64 | Shape::I128 | Shape::U64 | Shape::U128 => "bigint".to_string(),
+        Shape::Char | Shape::String | Shape::BoundedString(_) => "string".to_string(),
+        Shape::Bytes => "Uint8Array".to_string(),
+        Shape::Seq(item) if u8_vecs_as_bytes && **item == Shape::U8 => "Uint8Array".to_string(),
+        Shape::Unit => "void".to_string(),
+        Shape::Option(inner) => format!("{} | null", value_type(inner)),
+        Shape::Seq(item) | Shape::BoundedSeq(item, _) => array_type(item),
+        Shape::Tuple(items) => match array_item(items) {
+            Some(item) => array_type(item),
+            None => tuple_type(items, naming, u8_vecs_as_bytes),
+        },
+        Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+            format!("Map<{}, {}>", value_type(key), value_type(value))
+        }
+        Shape::Named(type_name) => naming.type_name(type_name.name),
+        Shape::Generic(type_name, args) => {
+            let args: Vec<String> = args.iter().map(value_type).collect();
+            format!("{}<{}>", naming.type_name(type_name.name), args.join(", "))
+        }
+        Shape::Param(name) => name.to_string(),
+    }
+}
+
+fn tuple_type(items: &[Shape], naming: &Naming, u8_vecs_as_bytes: bool) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|item| value_type(item, naming, u8_vecs_as_bytes))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// The item of an array `[T; N]` emitted as `array(item, N)`: serde writes
+/// tuples of at most 16 items, so a longer tuple of one shape is an array,
+/// whose values are better typed as `T[]` than as a tuple spelled out
+pub(crate) fn array_item(items: &[Shape]) -> Option<&Shape> {
+    let (first, rest) = items.split_first()?;
+    (items.len() > 16 && rest.iter().all(|item| item == first)).then_some(first)
+}
+
+/// The field of a struct holding its records, the first that is a list of a
+/// named type: `entries` of `struct LogBatch { device: u8, entries: Vec<LogEntry> }`
+fn records_field(fields: &[Field]) -> Option<&'static str> {
+    fields.iter().find_map(|field| match &field.shape {
+        Shape::Seq(item) | Shape::BoundedSeq(item, _)
+            if matches!(**item, Shape::Named(_) | Shape::Generic(..)) =>
+        {
+            Some(field.name)
+        }
+        _ => None,
+    })
+}
+
+/// The end of an index re-exporting `modules`: its own `__schema`, theirs
+/// merged, which takes precedence over those `export *` would re-export
+fn merged_module_schema(modules: &[String]) -> String {
+    let mut merged = String::from("\n");
+    for (i, module) in modules.iter().enumerate() {
+        merged.push_str(&format!(
+            "import {{ __schema as __schema{} }} from \"{}\";\n",
+            i, module
+        ));
+    }
+    merged.push_str(
+        "\n/** The types of every module, for tools that look at them at runtime */\n\
+         export const __schema = {\n",
+    );
+    for i in 0..modules.len() {
+        merged.push_str(&format!("  ...__schema{},\n", i));
+    }
+    merged.push_str("} as const;\n");
+    merged
+}
+
+pub(crate) fn quote(name: &str) -> String {
+    format!("\"{}\"", name)
+}
+
+/// A type name as a constant prefix: `GameState` as `GAME_STATE`,
+/// `HTTPRequest` as `HTTP_REQUEST`
+pub(crate) fn screaming_snake(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, &ch) in chars.iter().enumerate() {
+        if i > 0 && ch.is_ascii_uppercase() && chars[i - 1] != '_' {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if !previous.is_ascii_uppercase() || next_lower {
+                out.push('_');
+            }
+        }
+        out.push(ch.to_ascii_uppercase());
+    }
+    out
+}
+
+/// A variant name as words for people: `DarkLord` and `dark_lord` as
+/// `Dark lord`, `HTTPError` as `HTTP error`, `SHUTDOWN` as `Shutdown`
+pub(crate) fn humanize(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if !ch.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            continue;
+        }
+        // Word boundaries as in `screaming_snake`
+        if i > 0 && ch.is_uppercase() && !word.is_empty() {
+            let previous = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if !previous.is_uppercase() || next_lower {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(ch);
+    }
+    words.extend((!word.is_empty()).then_some(word));
+
+    // Acronyms keep their capitals, unless the whole name is in capitals
+    let shouting = !name.chars().any(char::is_lowercase);
+    let acronym = |word: &str| !shouting && word.chars().filter(|ch| ch.is_uppercase()).count() > 1;
+    let mut humanized = String::with_capacity(name.len());
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            humanized.push(' ');
+        }
+        if acronym(word) {
+            humanized.push_str(word);
+        } else if i == 0 {
+            let mut chars = word.chars();
+            humanized.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+            humanized.push_str(&chars.as_str().to_lowercase());
+        } else {
+            humanized.push_str(&word.to_lowercase());
+        }
+    }
+    humanized
+}
+
+/// A variant name as part of an identifier: `Alarm` as is, `set-target` or
+/// `set_target` as `SetTarget`
+pub(crate) fn pascal_case(name: &str) -> String {
+    name.split(|ch: char| !ch.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// An object key, quoted unless it is an identifier (e.g. after
+/// `#[serde(rename_all = "kebab-case")]`)
+pub(crate) fn property(name: &str) -> String {
+    let identifier = name.chars().enumerate().all(|(i, ch)| {
+        ch == '_' || ch == '$' || ch.is_ascii_alphabetic() || (i > 0 && ch.is_ascii_digit())
+    });
+    if identifier && !name.is_empty() {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
@@ -0,0 +1,214 @@
+//! Protocol groups: types grouped by what they are for, not where they live
+//!
+//! Teams version their protocol surface in parts (commands, telemetry,
+//! configuration, firmware updates) that rarely line up with Rust modules.
+//! A [`ProtocolGroup`] names such a part, lists its types and gives it a
+//! version. With [`Layout::Groups`](crate::Layout::Groups) each group is
+//! generated as its own file along with constants for its version and hash,
+//! and [`Generator::check_groups`](crate::Generator::check_groups) compares
+//! each group's hash with the one pinned for its version.
+//!
+//! The group hash covers the names and schema hashes of the group's
+//! non-generic types, written as `Name=0123456789abcdef;` in name order and
+//! hashed with 64-bit FNV-1a like the schema hash. It changes when a type
+//! joins or leaves the group, is renamed or changes its wire shape; a
+//! generic type has no hash of its own, so only its uses count.
+
+use std::collections::BTreeMap;
+
+use crate::generate::GenerateError;
+use crate::schema_hash;
+use crate::shape::{Definition, TypeName};
+
+/// A named, versioned set of types
+///
+/// ```
+/// use serde_postcard_ts::ProtocolGroup;
+///
+/// let telemetry = ProtocolGroup::new("telemetry", 3)
+///     .types(["app::telemetry::*", "Heartbeat"])
+///     .pin_hash(0x6f2a_93c1_0d4e_5b78);
+/// assert_eq!(telemetry.name(), "telemetry");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolGroup {
+    name: String,
+    version: u32,
+    patterns: Vec<String>,
+    pinned_hash: Option<u64>,
+}
+
+impl ProtocolGroup {
+    /// A group without types; `name` is its file name and constant prefix,
+    /// lowercase letters, digits and `_`, starting with a letter
+    pub fn new(name: impl Into<String>, version: u32) -> Self {
+        ProtocolGroup {
+            name: name.into(),
+            version,
+            patterns: Vec::new(),
+            pinned_hash: None,
+        }
+    }
+
+    /// Add types by name (`Reading`), by path (`app::telemetry::Reading`) or
+    /// by module (`app::telemetry::*`, including its submodules)
+    pub fn types<I>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.patterns.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// The group hash released as this version; see
+    /// [`Generator::check_groups`](crate::Generator::check_groups)
+    pub fn pin_hash(mut self, hash: u64) -> Self {
+        self.pinned_hash = Some(hash);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The prefix of the group's TypeScript constants, e.g. `TELEMETRY`
+    pub(crate) fn constant_prefix(&self) -> String {
+        self.name.to_ascii_uppercase()
+    }
+}
+
+/// Whether `type_name` is the type, or in the module, that `pattern` gives by
+/// name, path or `module::*`
+pub(crate) fn matches(pattern: &str, type_name: &TypeName) -> bool {
+    if let Some(module) = pattern.strip_suffix("::*") {
+        type_name.module == module
+            || type_name
+                .module
+                .strip_prefix(module)
+                .is_some_and(|rest| rest.starts_with("::"))
+    } else if let Some((module, name)) = pattern.rsplit_once("::") {
+        type_name.module == module && type_name.name == name
+    } else {
+        type_name.name == pattern
+    }
+}
+
+/// A group's hash, next to the one pinned for its version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupCheck {
+    pub group: String,
+    pub version: u32,
+    /// The hash of the group's types as they are now
+    pub hash: u64,
+    /// The hash pinned with [`ProtocolGroup::pin_hash`], if any
+    pub pinned: Option<u64>,
+}
+
+impl GroupCheck {
+    /// Whether the group changed without a new version
+    pub fn is_stale(&self) -> bool {
+        self.pinned.is_some_and(|pinned| pinned != self.hash)
+    }
+}
+
+/// Reserved for the types in no group, and for the file re-exporting them all
+const RESERVED: [&str; 2] = ["common", "index"];
+
+/// The group of each of `types` (an index into `groups`), `None` for types
+/// in no group, after checking the groups against the types
+pub(crate) fn assign(
+    groups: &[ProtocolGroup],
+    types: &[(TypeName, Definition)],
+) -> Result<Vec<Option<usize>>, GenerateError> {
+    for (i, group) in groups.iter().enumerate() {
+        let valid = group.name.starts_with(|c: char| c.is_ascii_lowercase())
+            && group
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            && !RESERVED.contains(&group.name.as_str());
+        let duplicate = groups[..i].iter().any(|other| other.name == group.name);
+        if !valid || duplicate {
+            return Err(GenerateError::InvalidGroupName(group.name.clone()));
+        }
+    }
+
+    let mut assigned: Vec<Option<usize>> = vec![None; types.len()];
+    for (g, group) in groups.iter().enumerate() {
+        for pattern in &group.patterns {
+            let mut matched = false;
+            for (i, (type_name, _)) in types.iter().enumerate() {
+                if !matches(pattern, type_name) {
+                    continue;
+                }
+                matched = true;
+                match assigned[i] {
+                    Some(other) if other != g => {
+                        return Err(GenerateError::GroupOverlap {
+                            name: type_name.name,
+                            groups: [groups[other].name.clone(), group.name.clone()],
+                        })
+                    }
+                    _ => assigned[i] = Some(g),
+                }
+            }
+            if !matched {
+                return Err(GenerateError::UnmatchedGroupPattern {
+                    group: group.name.clone(),
+                    pattern: pattern.clone(),
+                });
+            }
+        }
+    }
+    Ok(assigned)
+}
+
+/// The hash of each group, in the order of `groups`
+pub(crate) fn hashes(
+    groups: &[ProtocolGroup],
+    types: &[(TypeName, Definition)],
+    assigned: &[Option<usize>],
+) -> Result<Vec<u64>, GenerateError> {
+    let type_hashes: BTreeMap<TypeName, u64> =
+        schema_hash::schema_hashes(types)?.into_iter().collect();
+    let mut members: Vec<BTreeMap<&str, u64>> = vec![BTreeMap::new(); groups.len()];
+    for ((type_name, _), group) in types.iter().zip(assigned) {
+        if let (Some(g), Some(hash)) = (group, type_hashes.get(type_name)) {
+            members[*g].insert(type_name.name, *hash);
+        }
+    }
+    Ok(members
+        .iter()
+        .map(|members| {
+            let canonical: String = members
+                .iter()
+                .map(|(name, hash)| format!("{}={:016x};", name, hash))
+                .collect();
+            schema_hash::fnv1a(canonical.as_bytes())
+        })
+        .collect())
+}
+
+/// Compare the hash of each of `groups` over `types` with the one pinned for it
+pub(crate) fn check(
+    groups: &[ProtocolGroup],
+    types: &[(TypeName, Definition)],
+) -> Result<Vec<GroupCheck>, GenerateError> {
+    let assigned = assign(groups, types)?;
+    let hashes = hashes(groups, types, &assigned)?;
+    Ok(groups
+        .iter()
+        .zip(hashes)
+        .map(|(group, hash)| GroupCheck {
+            group: group.name.clone(),
+            version: group.version,
+            hash,
+            pinned: group.pinned_hash,
+        })
+        .collect())
+}
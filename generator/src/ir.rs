@@ -0,0 +1,209 @@
+//! JSON IR shared with the TypeScript runtime
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "types": [
+//!     {"name": "Mode", "kind": "enum", "variants": [{"name": "Idle", "kind": "unit"}, ...]},
+//!     {"name": "Reading", "kind": "struct", "fields": [{"name": "sensor", "type": {"kind": "u8"}}, ...]}
+//!   ]
+//! }
+//! ```
+//!
+//! Types are `struct` (`fields`), `tuple_struct` (`items`), `newtype_struct`
+//! (`inner`), `unit_struct` or `enum` (`variants`, each `unit`, `newtype`,
+//! `tuple` or `struct`). Shapes use the TypeScript builder names (`u8`,
+//! `string`, `option` with `inner`, `seq` with `item`, `tuple` with `items`,
+//! `map` with `key` and `value`), plus `ref` with the `name` of another type.
+//! Strings with a length limit carry it as `maxBytes`.
+//! Fields and variants are arrays, in declaration order, since the order is
+//! the wire format.
+//!
+//! Generic types list their type parameters in `params` and use them as
+//! `param` shapes with a `name`; references to them carry the type arguments
+//! in `args`:
+//!
+//! ```json
+//! {"name": "Envelope", "params": ["T"], "kind": "struct", "fields": [{"name": "payload", "type": {"kind": "param", "name": "T"}}, ...]}
+//! {"kind": "ref", "name": "Envelope", "args": [{"kind": "u8"}]}
+//! ```
+
+use crate::shape::{Definition, Field, Shape, TypeName, VariantKind};
+
+/// Version of the IR format written here
+pub const IR_VERSION: u32 = 1;
+
+pub(crate) fn to_json(types: &[(TypeName, Definition)]) -> String {
+    let types: Vec<String> = types
+        .iter()
+        .map(|(type_name, definition)| format!("    {}", type_json(type_name, definition)))
+        .collect();
+    format!(
+        "{{\n  \"version\": {},\n  \"types\": [\n{}\n  ]\n}}\n",
+        IR_VERSION,
+        types.join(",\n")
+    )
+}
+
+fn type_json(type_name: &TypeName, definition: &Definition) -> String {
+    // The name, followed by the parameters of a generic type
+    let mut name = string(type_name.name);
+    if !type_name.params.is_empty() {
+        let params: Vec<String> = type_name.params.iter().map(|param| string(param)).collect();
+        name.push_str(&format!(", \"params\": [{}]", params.join(", ")));
+    }
+    match definition {
+        Definition::Struct(fields) => format!(
+            "{{\"name\": {}, \"kind\": \"struct\", \"fields\": {}}}",
+            name,
+            fields_json(fields)
+        ),
+        Definition::TupleStruct(items) => format!(
+            "{{\"name\": {}, \"kind\": \"tuple_struct\", \"items\": {}}}",
+            name,
+            shapes_json(items)
+        ),
+        Definition::NewtypeStruct(inner) => format!(
+            "{{\"name\": {}, \"kind\": \"newtype_struct\", \"inner\": {}}}",
+            name,
+            shape_json(inner)
+        ),
+        Definition::UnitStruct => format!("{{\"name\": {}, \"kind\": \"unit_struct\"}}", name),
+        Definition::Enum(variants) => {
+            let variants: Vec<String> = variants
+                .iter()
+                .map(|variant| {
+                    let name = string(variant.name);
+                    match &variant.kind {
+                        VariantKind::Unit => format!("{{\"name\": {}, \"kind\": \"unit\"}}", name),
+                        VariantKind::Newtype(inner) => format!(
+                            "{{\"name\": {}, \"kind\": \"newtype\", \"inner\": {}}}",
+                            name,
+                            shape_json(inner)
+                        ),
+                        VariantKind::Tuple(items) => format!(
+                            "{{\"name\": {}, \"kind\": \"tuple\", \"items\": {}}}",
+                            name,
+                            shapes_json(items)
+                        ),
+                        VariantKind::Struct(fields) => format!(
+                            "{{\"name\": {}, \"kind\": \"struct\", \"fields\": {}}}",
+                            name,
+                            fields_json(fields)
+                        ),
+                    }
+                })
+                .collect();
+            format!(
+                "{{\"name\": {}, \"kind\": \"enum\", \"variants\": [{}]}}",
+                name,
+                variants.join(", ")
+            )
+        }
+    }
+}
+
+fn fields_json(fields: &[Field]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{{\"name\": {}, \"type\": {}}}",
+                string(field.name),
+                shape_json(&field.shape)
+            )
+        })
+        .collect();
+    format!("[{}]", fields.join(", "))
+}
+
+fn shapes_json(shapes: &[Shape]) -> String {
+    let shapes: Vec<String> = shapes.iter().map(shape_json).collect();
+    format!("[{}]", shapes.join(", "))
+}
+
+fn shape_json(shape: &Shape) -> String {
+    match shape {
+        Shape::Option(inner) => {
+            format!("{{\"kind\": \"option\", \"inner\": {}}}", shape_json(inner))
+        }
+        Shape::Seq(item) => format!("{{\"kind\": \"seq\", \"item\": {}}}", shape_json(item)),
+        Shape::BoundedSeq(item, max) => format!(
+            "{{\"kind\": \"seq\", \"item\": {}, \"maxLength\": {}}}",
+            shape_json(item),
+            max
+        ),
+        Shape::Tuple(items) => {
+            format!("{{\"kind\": \"tuple\", \"items\": {}}}", shapes_json(items))
+        }
+        Shape::Map(key, value) => format!(
+            "{{\"kind\": \"map\", \"key\": {}, \"value\": {}}}",
+            shape_json(key),
+            shape_json(value)
+        ),
+        Shape::BoundedMap(key, value, max) => format!(
+            "{{\"kind\": \"map\", \"key\": {}, \"value\": {}, \"maxLength\": {}}}",
+            shape_json(key),
+            shape_json(value),
+            max
+        ),
+        Shape::Named(type_name) => format!(
+            "{{\"kind\": \"ref\", \"name\": {}}}",
+            string(type_name.name)
+        ),
+        Shape::Generic(type_name, args) => format!(
+            "{{\"kind\": \"ref\", \"name\": {}, \"args\": {}}}",
+            string(type_name.name),
+            shapes_json(args)
+        ),
+        Shape::Param(name) => format!("{{\"kind\": \"param\", \"name\": {}}}", string(name)),
+        Shape::BoundedString(max) => format!("{{\"kind\": \"string\", \"maxBytes\": {}}}", max),
+        _ => format!("{{\"kind\": \"{}\"}}", kind(shape)),
+    }
+}
+
+/// The IR `kind` of a shape
+pub(crate) fn kind(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::Bool => "bool",
+        Shape::I8 => "i8",
+        Shape::I16 => "i16",
+        Shape::I32 => "i32",
+        Shape::I64 => "i64",
+        Shape::I128 => "i128",
+        Shape::U8 => "u8",
+        Shape::U16 => "u16",
+        Shape::U32 => "u32",
+        Shape::U64 => "u64",
+        Shape::U128 => "u128",
+        Shape::F32 => "f32",
+        Shape::F64 => "f64",
+        Shape::Char => "char",
+        Shape::String | Shape::BoundedString(_) => "string",
+        Shape::Bytes => "bytes",
+        Shape::Unit => "unit",
+        Shape::Option(_) => "option",
+        Shape::Seq(_) | Shape::BoundedSeq(_, _) => "seq",
+        Shape::Tuple(_) => "tuple",
+        Shape::Map(_, _) | Shape::BoundedMap(_, _, _) => "map",
+        Shape::Named(_) | Shape::Generic(_, _) => "ref",
+        Shape::Param(_) => "param",
+    }
+}
+
+/// JSON string literal
+pub(crate) fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
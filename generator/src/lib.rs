@@ -0,0 +1,258 @@
+//! Generates serde-postcard-ts schemas from Rust types
+//!
+//! Deriving [`PostcardTs`] registers a type's wire shape; [`generate_all`]
+//! then emits a TypeScript module declaring a schema and an inferred type for
+//! every registered type, so the TypeScript side no longer mirrors the Rust
+//! definitions by hand:
+//!
+//! ```
+//! use serde_postcard_ts::PostcardTs;
+//!
+//! #[derive(PostcardTs)]
+//! struct Reading {
+//!     sensor: u8,
+//!     samples: Vec<f32>,
+//! }
+//!
+//! let source = serde_postcard_ts::generate_all()?;
+//! assert!(source.contains("export const ReadingSchema = struct({"));
+//! assert!(source.contains("  samples: seq(f32()),"));
+//! # Ok::<(), serde_postcard_ts::GenerateError>(())
+//! ```
+//!
+//! Registration goes through `inventory`, so types derived in dependencies are
+//! generated too. Call [`generate_all`] from a binary, test or build script and
+//! write the result to a `.ts` file.
+//!
+//! For build pipelines, [`Generator::generate_files`] splits the output into
+//! one file per Rust module ([`Layout::Modules`]) or per type
+//! ([`Layout::Types`]) and [`write_files`] writes it out. The files are
+//! TypeScript ES modules unless [`Generator::module_format`] asks for CommonJS
+//! or type declarations only. [`build::emit`] does both from a build script,
+//! rewriting only files that changed. The `postcard-ts` command line tool
+//! (feature `cli`) generates and writes files for a crate given by path:
+//!
+//! ```text
+//! postcard-ts generate --crate ./firmware --out ./web/src/generated --layout modules --clean
+//! ```
+//!
+//! Doc comments on derived types, fields and variants, and `#[deprecated]`,
+//! are written as JSDoc on the generated code ([`Docs`]).
+//!
+//! [`Generator::typedoc`] documents every export for TypeDoc, filed under a
+//! category per protocol group or Rust module, with examples decoding
+//! fixtures; `postcard-ts docs` builds the reference site from it.
+//!
+//! `postcard-ts scaffold --example websocket-dashboard` writes a runnable
+//! example project, a Rust server and a web frontend sharing generated
+//! schemas ([`Example`]).
+//!
+//! [`Generator::generate_ir`] writes the same types as a JSON IR instead, which
+//! the TypeScript runtime turns into schemas at runtime (`schemasFromIr`) for
+//! tools that cannot be rebuilt for every protocol change.
+//!
+//! [`Generator::generate_wire_layout`] describes where each field sits on the
+//! wire and how it is encoded, for editor extensions showing it on hover;
+//! `postcard-ts generate --lsp-json` streams it along with diagnostics.
+//! `--message-format json` prints just the warnings and errors instead, as
+//! versioned [`Diagnostic`] records with a span and a suggested fix.
+//!
+//! With [`Generator::crc`], each type also gets wrappers appending and checking
+//! a CRC as postcard's CRC flavor does; [`crc`] mirrors the `crc` crate's
+//! catalog of algorithms.
+//!
+//! [`schema_hash`] condenses a type's wire shape into a `u64` for handshakes
+//! between programs built from different versions of the types, and each
+//! derived non-generic type declares it as a `SCHEMA_HASH` constant
+//! ([`const_schema_hash`]). Pinning it with
+//! `#[postcard_ts(schema_hash = 0x...)]` makes the type fail to compile, and
+//! so `postcard-ts check` fail, when the wire shape no longer matches the
+//! pinned hash; [`Generator::schema_hashes`] emits the same hashes as
+//! TypeScript constants.
+//!
+//! [`diff_snapshots`] compares two snapshots of the IR, such as the one
+//! committed with the last release and the current one, and classifies each
+//! change as wire-compatible or breaking ([`SchemaDiff`]); `postcard-ts schema
+//! snapshot` writes a crate's snapshot and `postcard-ts schema diff` fails on
+//! breaking changes. A [`SchemaRegistry`] serves snapshots over HTTP by the
+//! schema hashes of their types ([`snapshot_schema_hashes`]), for dashboards
+//! to fetch the one a connected device was built from.
+//!
+//! [`max_size`] gives the most bytes a type takes on the wire, the bound
+//! postcard's `MaxSize` derive gives, for sizing buffers; types with strings,
+//! sequences or maps have none. [`Generator::max_sizes`] emits the bounds as
+//! TypeScript constants (`PLAYER_MAX_SIZE`).
+//!
+//! [`ProtocolGroup`]s gather types by what they are for (commands,
+//! telemetry, ...) into named, versioned groups, generated one file each
+//! with [`Layout::Groups`] and checked against the hash pinned for their
+//! version with [`Generator::check_groups`].
+//!
+//! A [`Simulator`] maps a device's request enum to its response and
+//! telemetry types; [`Generator::simulator`] generates a typed subclass of
+//! the TypeScript runtime's `DeviceSimulator` for it, a fake device for UI
+//! tests that answers each request variant with a handler and emits
+//! scripted telemetry on a virtual clock. [`Generator::dispatchers`] writes a
+//! `MessageDispatcher` subclass per top-level enum, routing the messages of
+//! a stream to handlers by variant.
+//!
+//! [`ProtocolConstant`]s, such as frame magic bytes, the longest frame or
+//! topic paths, are declared once with [`Generator::constant`] and emitted
+//! both as TypeScript constants and as a Rust module
+//! ([`Generator::generate_rust_constants`]), so the two sides cannot drift.
+//! [`ProtocolFeature`]s give the capabilities a device may have a bit each
+//! in the mask it sends when it connects, and name the messages needing
+//! them, which the runtime's `FeatureSet` refuses to send to a device
+//! without them.
+//!
+//! Types the derive cannot describe, such as ones with a hand-written
+//! `Serialize` impl, get their wire shape and TypeScript codec from a
+//! [`TsOverride`] given to [`Generator::override_type`]. Those not worth
+//! describing can be left to postcard itself with [`Generator::wasm_type`]:
+//! the generator writes a wasm-bindgen crate encoding them ([`WasmBackend`])
+//! and their schemas call it.
+//!
+//! Types only ever sent or only ever received can be limited to that
+//! [`Direction`] with `#[postcard_ts(encode_only)]` or
+//! `#[postcard_ts(decode_only)]`, or with [`Generator::encode_only`] and
+//! [`Generator::decode_only`]; TypeScript then rejects using their schemas
+//! the other way, and only the matching CRC wrapper is generated.
+//!
+//! [`Generator::with_validators`] also emits a runtime validator per type,
+//! Zod schemas with [`Validator::Zod`], for checking values built in app code
+//! before they are encoded.
+//!
+//! `std::time::Duration` has a built-in schema, and so do
+//! `chrono::DateTime<Utc>` and `time::OffsetDateTime` with the `chrono` and
+//! `time` features: instants decode to a `Date` and durations to a number of
+//! milliseconds, or all of them to bigint nanoseconds with
+//! [`Generator::time_representation`]. The `NonZero` integers decode to their
+//! values, checked not to be zero, and `uuid::Uuid` with the `uuid` feature to
+//! a branded string, or to its bytes with [`Generator::uuid_representation`].
+//! [`Generator::branded_newtypes`] brands newtype structs too, so that a
+//! `PlayerId(u64)` cannot be passed where an `ItemId(u64)` is expected.
+//!
+//! [`lint`] warns about types that generate fine but are easy to misread,
+//! such as a struct saying "no items" both as `None` and as an empty `Vec`,
+//! or awkward to use from TypeScript, such as ids decoded to a `bigint`;
+//! `postcard-ts generate` prints the warnings. [`lint_with`] allows or denies
+//! each lint as a [`LintConfig`] says.
+//!
+//! [`CaptureStats`] summarises a capture recorded by the TypeScript flight
+//! recorder, decoded as one message type: frames and bytes per type and
+//! variant, the bytes of each field and nested variant within them, frame
+//! sizes, arrival intervals and the busiest values of a field.
+//! `postcard-ts stats` prints it as a table or JSON.
+//!
+//! An [`ArchiveWriter`] stores records with the IR and schema hash of their
+//! type in the header ([`ArchiveHeader`]), so that the TypeScript runtime's
+//! `readArchive` can decode the archive long after the types are gone;
+//! [`read_archive`] reads one back in Rust.
+//!
+//! [`Generator::migrate_ts`] compares the types of a hand-written
+//! TypeScript file with the generated ones, reporting renamed fields,
+//! optional properties written for `| null` and numbers written for
+//! bigints ([`TsMigration`]), and writes shims converting between the two
+//! where only the values differ; `postcard-ts migrate` runs it on a crate.
+//!
+//! [`RoundTrip`] compares a payload with what the generated TypeScript
+//! decoder and encoder turn it into, naming the values whose bytes differ;
+//! `postcard-ts verify` runs a captured frame through them under Node.
+//!
+//! Every generated identifier is named after the Rust type by a template
+//! that [`Generator::naming`] can change, `I{name}` for the types of a
+//! codebase prefixing its interfaces for instance; see [`Naming`].
+
+mod archive;
+pub mod build;
+mod builtin;
+mod capture_stats;
+pub mod crc;
+pub mod diagnostics;
+mod dispatch;
+mod docs;
+mod features;
+mod generate;
+mod groups;
+mod ir;
+mod lint;
+mod loaders;
+mod max_size;
+mod naming;
+mod output;
+mod protocol_constants;
+mod registry;
+mod round_trip;
+mod scaffold;
+mod schema_diff;
+mod schema_hash;
+mod schema_registry;
+mod shape;
+mod simulator;
+mod ts_migration;
+mod ts_override;
+mod typedoc;
+mod validator;
+mod wasm;
+mod wire_layout;
+
+pub use archive::{
+    read_archive, Archive, ArchiveError, ArchiveHeader, ArchiveWriter, ARCHIVE_MAGIC,
+    ARCHIVE_VERSION,
+};
+pub use builtin::{TimeRepresentation, UuidRepresentation};
+pub use capture_stats::{
+    read_capture, CaptureError, CaptureStats, CapturedFrame, FieldStats, Histogram, KindStats,
+    StatsOptions, TalkerStats, Timing, CAPTURE_VERSION,
+};
+pub use crc::CrcAlgorithm;
+pub use diagnostics::{Diagnostic, Severity, MESSAGE_FORMAT_VERSION};
+pub use docs::{Doc, Docs, VariantDocs};
+pub use features::ProtocolFeature;
+pub use generate::{
+    generate_all, GenerateError, GeneratedFile, Generator, ImportExtension, Layout, ModuleFormat,
+    DEFAULT_IMPORT,
+};
+pub use groups::{GroupCheck, ProtocolGroup};
+pub use ir::IR_VERSION;
+pub use lint::{
+    lint, lint_types, lint_types_with, lint_with, Lint, LintConfig, LintLevel, Span, LINTS,
+};
+pub use max_size::{max_size, max_sizes};
+pub use naming::{Ident, Naming};
+pub use output::{write_files, WriteOptions, WriteReport};
+pub use protocol_constants::{ConstantValue, ProtocolConstant};
+pub use registry::{registered, Direction, Registration};
+pub use round_trip::{RoundTrip, RoundTripDifference, TracedValue};
+pub use scaffold::Example;
+pub use schema_diff::{
+    diff_snapshots, snapshot_schema_hashes, Compatibility, SchemaChange, SchemaDiff, SnapshotError,
+};
+pub use schema_hash::{
+    check_schema_hashes, check_types_schema_hashes, const_schema_hash, schema_hash, schema_hashes,
+    SchemaHashCheck,
+};
+pub use schema_registry::{RegistryEntry, RegistryResponse, SchemaRegistry};
+pub use shape::{
+    ConstDefinition, ConstShape, Definition, Field, MaxLen, PostcardTs, SerdeBytes, Shape,
+    TypeName, Variant, VariantKind,
+};
+pub use simulator::Simulator;
+pub use ts_migration::{TsMigration, TsMismatch};
+pub use ts_override::TsOverride;
+pub use validator::Validator;
+pub use wasm::WasmBackend;
+pub use wire_layout::WIRE_LAYOUT_VERSION;
+
+#[cfg(feature = "derive")]
+pub use serde_postcard_ts_derive::PostcardTs;
+
+// Used by the derive macro's expansion
+#[doc(hidden)]
+pub use inventory;
+#[doc(hidden)]
+pub use schema_hash::check_pinned_schema_hash;
+
+// Used by the helper binary of `postcard-ts generate --lsp-json`
+#[doc(hidden)]
+pub mod lsp;
@@ -0,0 +1,511 @@
+//! Warnings about types that generate fine but are easy to misread, or
+//! awkward to use from TypeScript
+//!
+//! Lints never stop generation unless a [`LintConfig`] denies them;
+//! `postcard-ts generate` prints them as warnings (or `warning` diagnostics
+//! with `--lsp-json`), pointing at the type's declaration.
+//!
+//! `optional_seq`: a struct or variant with both an `Option<Vec<T>>` field
+//! and a plain `Vec<T>` field. Both usually mean "maybe no items", but they
+//! say it differently: the plain list is `[]` (`00` on the wire), the
+//! optional one `null` (`00`) or `[]` (`01 00`), and code on either side
+//! easily treats one convention as the other. `Vec<Option<T>>`, a list with
+//! gaps, is a different thing and never warned about.
+//!
+//! `bigint_id`: an id (a field named `id` or `..._id`, or a newtype named
+//! `...Id`) that is a 64- or 128-bit integer. TypeScript decodes those to a
+//! `bigint`, which cannot be mixed with numbers, used as an array index or
+//! written to JSON; ids rarely need more than a `u32`.
+//!
+//! `string_map`: a map keyed by strings, such as `HashMap<String, T>`. It
+//! decodes to a `Map`, which is clumsier than an object in TypeScript, and
+//! sends every key with every message; when the keys are a fixed set, a
+//! struct is a plain object and sends none.
+//!
+//! `deep_nesting`: a type nested deeper than [`LintConfig::max_depth`]
+//! (default 8) levels of structs, enums, sequences, tuples and maps, counted
+//! from a type no other type contains. Every level is another object or
+//! array to walk, and a long chain of optional chaining, in TypeScript.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+use crate::generate::references;
+use crate::registry;
+use crate::shape::{Definition, Field, Shape, TypeName, VariantKind};
+
+/// Every check [`lint`] runs, by code
+pub const LINTS: [&str; 4] = ["optional_seq", "bigint_id", "string_map", "deep_nesting"];
+
+/// A warning about one type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// Stable identifier of the check, e.g. `optional_seq`
+    pub code: &'static str,
+    pub type_name: TypeName,
+    pub message: String,
+    /// [`LintLevel::Deny`] when the [`LintConfig`] denies the check, which
+    /// should then fail generation; never [`LintLevel::Allow`]
+    pub level: LintLevel,
+    /// Where the type is declared, for types registered with
+    /// `#[derive(PostcardTs)]`
+    pub span: Option<Span>,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+/// The name of a type in the file declaring it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub file: PathBuf,
+    /// 1-based, as `line!()` counts
+    pub line: u32,
+    /// 1-based, as `column!()` counts
+    pub column: u32,
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file.display(), self.line, self.column)
+    }
+}
+
+/// What to do about a lint
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// Do not report it
+    Allow,
+    /// Report it as a warning
+    #[default]
+    Warn,
+    /// Report it as an error, failing generation
+    Deny,
+}
+
+/// Which lints to report and how, for [`lint_with`]
+///
+/// ```
+/// use serde_postcard_ts::{lint_types_with, Definition, LintConfig, LintLevel, Shape, TypeName};
+///
+/// let id = TypeName { name: "PlayerId", module: "app", params: &[] };
+/// let types = vec![(id, Definition::NewtypeStruct(Shape::U64))];
+/// let lints = lint_types_with(&types, &LintConfig::new().deny("bigint_id"));
+/// assert_eq!(lints[0].code, "bigint_id");
+/// assert_eq!(lints[0].level, LintLevel::Deny);
+/// assert!(lint_types_with(&types, &LintConfig::new().allow("bigint_id")).is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct LintConfig {
+    levels: BTreeMap<String, LintLevel>,
+    max_depth: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            levels: BTreeMap::new(),
+            max_depth: 8,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report the check `code` (one of [`LINTS`]) at `level`; checks not
+    /// given a level warn
+    pub fn level(mut self, code: &str, level: LintLevel) -> Self {
+        self.levels.insert(code.to_string(), level);
+        self
+    }
+
+    /// Do not report the check `code`
+    pub fn allow(self, code: &str) -> Self {
+        self.level(code, LintLevel::Allow)
+    }
+
+    /// Report the check `code` as an error
+    pub fn deny(self, code: &str) -> Self {
+        self.level(code, LintLevel::Deny)
+    }
+
+    /// Levels of nesting `deep_nesting` accepts (default: 8)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    fn level_of(&self, code: &str) -> LintLevel {
+        self.levels.get(code).copied().unwrap_or_default()
+    }
+}
+
+/// Lint every type registered with `#[derive(PostcardTs)]`
+pub fn lint() -> Vec<Lint> {
+    lint_with(&LintConfig::default())
+}
+
+/// Lint every registered type as `config` says
+pub fn lint_with(config: &LintConfig) -> Vec<Lint> {
+    let mut lints = lint_types_with(&registry::registered(), config);
+    locate(&mut lints);
+    lints
+}
+
+/// Lint the given types
+pub fn lint_types(types: &[(TypeName, Definition)]) -> Vec<Lint> {
+    lint_types_with(types, &LintConfig::default())
+}
+
+/// Lint the given types as `config` says
+pub fn lint_types_with(types: &[(TypeName, Definition)], config: &LintConfig) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    for (type_name, definition) in types {
+        match definition {
+            Definition::Struct(fields) => {
+                fields_lints(*type_name, type_name.name, &named(fields), &mut lints)
+            }
+            Definition::TupleStruct(items) => {
+                fields_lints(*type_name, type_name.name, &numbered(items), &mut lints)
+            }
+            Definition::NewtypeStruct(inner) => {
+                if is_id(type_name.name) && is_wide(inner) {
+                    lints.push(bigint_id(*type_name, type_name.name, inner));
+                }
+                if let Some(path) = string_map(inner, String::new()) {
+                    lints.push(string_map_lint(*type_name, type_name.name, &path));
+                }
+            }
+            Definition::UnitStruct => {}
+            Definition::Enum(variants) => {
+                for variant in variants {
+                    let fields = match &variant.kind {
+                        VariantKind::Struct(fields) => named(fields),
+                        VariantKind::Tuple(items) => numbered(items),
+                        VariantKind::Newtype(inner) => vec![("0".to_string(), inner)],
+                        VariantKind::Unit => continue,
+                    };
+                    let owner = format!("{}::{}", type_name.name, variant.name);
+                    fields_lints(*type_name, &owner, &fields, &mut lints);
+                }
+            }
+        }
+    }
+    deep_nesting(types, config.max_depth, &mut lints);
+
+    lints.retain_mut(|lint| {
+        lint.level = config.level_of(lint.code);
+        lint.level != LintLevel::Allow
+    });
+    lints
+}
+
+/// Point `lints` at the declarations of their types
+pub(crate) fn locate(lints: &mut [Lint]) {
+    let spans = registry::spans();
+    for lint in lints {
+        lint.span = spans.get(&lint.type_name).cloned();
+    }
+}
+
+fn named(fields: &[Field]) -> Vec<(String, &Shape)> {
+    fields
+        .iter()
+        .map(|field| (field.name.to_string(), &field.shape))
+        .collect()
+}
+
+fn numbered(items: &[Shape]) -> Vec<(String, &Shape)> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (i.to_string(), item))
+        .collect()
+}
+
+fn warning(code: &'static str, type_name: TypeName, message: String) -> Lint {
+    Lint {
+        code,
+        type_name,
+        message,
+        level: LintLevel::Warn,
+        span: None,
+    }
+}
+
+/// The lints about the fields of a struct or variant
+fn fields_lints(
+    type_name: TypeName,
+    owner: &str,
+    fields: &[(String, &Shape)],
+    lints: &mut Vec<Lint>,
+) {
+    optional_seq(type_name, owner, fields, lints);
+    for (name, shape) in fields {
+        let field = format!("{}.{}", owner, name);
+        if is_id(name) && is_wide(shape) {
+            lints.push(bigint_id(type_name, &field, shape));
+        }
+        if let Some(path) = string_map(shape, String::new()) {
+            lints.push(string_map_lint(type_name, &field, &path));
+        }
+    }
+}
+
+fn optional_seq(
+    type_name: TypeName,
+    owner: &str,
+    fields: &[(String, &Shape)],
+    lints: &mut Vec<Lint>,
+) {
+    let with = |wanted: fn(&Shape) -> bool| -> Vec<&str> {
+        fields
+            .iter()
+            .filter(|(_, shape)| wanted(shape))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    };
+    let optional = with(|shape| matches!(shape, Shape::Option(inner) if is_list(inner)));
+    let plain = with(is_list);
+    if optional.is_empty() || plain.is_empty() {
+        return;
+    }
+    lints.push(warning(
+        "optional_seq",
+        type_name,
+        format!(
+            "{} has optional lists ({}) next to plain ones ({}); no items is null or [] in the \
+             former and [] in the latter, so pick one way to say it",
+            owner,
+            optional.join(", "),
+            plain.join(", ")
+        ),
+    ));
+}
+
+fn is_list(shape: &Shape) -> bool {
+    matches!(
+        shape,
+        Shape::Seq(_) | Shape::BoundedSeq(_, _) | Shape::Bytes
+    )
+}
+
+/// Whether `name`, a field or type name, names an id: `id`, `sensor_id`,
+/// `sensorId` or `SensorId`
+fn is_id(name: &str) -> bool {
+    name == "id" || name.ends_with("_id") || name.ends_with("Id")
+}
+
+/// Whether `shape` decodes to a bigint, directly or as an option
+fn is_wide(shape: &Shape) -> bool {
+    match shape {
+        Shape::U64 | Shape::I64 | Shape::U128 | Shape::I128 => true,
+        Shape::Option(inner) => is_wide(inner),
+        _ => false,
+    }
+}
+
+fn bigint_id(type_name: TypeName, owner: &str, shape: &Shape) -> Lint {
+    let integer = match shape {
+        Shape::Option(inner) => inner,
+        shape => shape,
+    };
+    let integer = match integer {
+        Shape::U64 => "a u64",
+        Shape::I64 => "an i64",
+        Shape::U128 => "a u128",
+        _ => "an i128",
+    };
+    warning(
+        "bigint_id",
+        type_name,
+        format!(
+            "{} is {}, which TypeScript decodes to a bigint that mixes with neither numbers \
+             nor JSON; if the ids fit in 32 bits, a u32 decodes to a plain number",
+            owner, integer
+        ),
+    )
+}
+
+/// The path below `path` of the first map keyed by strings in `shape`, not
+/// looking into other types
+fn string_map(shape: &Shape, path: String) -> Option<String> {
+    match shape {
+        Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+            if matches!(**key, Shape::String | Shape::BoundedString(_)) {
+                Some(path)
+            } else {
+                string_map(value, format!("{}{{}}", path))
+            }
+        }
+        Shape::Option(inner) => string_map(inner, path),
+        Shape::Seq(item) | Shape::BoundedSeq(item, _) => string_map(item, format!("{}[]", path)),
+        Shape::Tuple(items) => items
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| string_map(item, format!("{}.{}", path, i))),
+        Shape::Generic(_, args) => args.iter().find_map(|arg| string_map(arg, path.clone())),
+        _ => None,
+    }
+}
+
+fn string_map_lint(type_name: TypeName, owner: &str, path: &str) -> Lint {
+    warning(
+        "string_map",
+        type_name,
+        format!(
+            "{}{} is a map keyed by strings, a Map in TypeScript that sends every key with \
+             every message; if the keys are a fixed set, a struct decodes to a plain object \
+             and sends none",
+            owner, path
+        ),
+    )
+}
+
+/// Warn about the types no other type contains that nest deeper than
+/// `max_depth`
+fn deep_nesting(types: &[(TypeName, Definition)], max_depth: usize, lints: &mut Vec<Lint>) {
+    let contained: HashSet<TypeName> = types
+        .iter()
+        .flat_map(|(type_name, definition)| {
+            references(definition)
+                .into_iter()
+                .filter(move |reference| reference != type_name)
+        })
+        .collect();
+    let mut depths = Depths {
+        types: types
+            .iter()
+            .map(|(type_name, definition)| (*type_name, definition))
+            .collect(),
+        visiting: HashSet::new(),
+    };
+    for (type_name, _) in types {
+        if contained.contains(type_name) || !type_name.params.is_empty() {
+            continue;
+        }
+        let (depth, path) = depths.named(type_name, Vec::new());
+        if depth > max_depth {
+            lints.push(warning(
+                "deep_nesting",
+                *type_name,
+                format!(
+                    "{} nests {} levels deep, down {}{}, more than the {} allowed; every level \
+                     is another object or array to walk in TypeScript",
+                    type_name.name, depth, type_name.name, path, max_depth
+                ),
+            ));
+        }
+    }
+}
+
+/// The levels below a shape and the path down the deepest one
+type Depth = (usize, String);
+
+/// How deep shapes nest, through the definitions of `types`
+struct Depths<'t> {
+    types: HashMap<TypeName, &'t Definition>,
+    /// The types being measured, whose recursive uses count as one level
+    visiting: HashSet<TypeName>,
+}
+
+impl Depths<'_> {
+    /// The depth of `shape`, `args` being the depths of the type parameters
+    /// of the type it is in
+    fn shape(&mut self, shape: &Shape, args: &[(&str, Depth)]) -> Depth {
+        let below = |(depth, path): Depth, step: &str| (depth + 1, format!("{}{}", step, path));
+        match shape {
+            Shape::Option(inner) => self.shape(inner, args),
+            Shape::Seq(item) | Shape::BoundedSeq(item, _) => below(self.shape(item, args), "[]"),
+            Shape::Map(_, value) | Shape::BoundedMap(_, value, _) => {
+                below(self.shape(value, args), "{}")
+            }
+            Shape::Tuple(items) => {
+                let deepest = deepest(items.iter().enumerate().map(|(i, item)| {
+                    let (depth, path) = self.shape(item, args);
+                    (depth, format!(".{}{}", i, path))
+                }));
+                below(deepest, "")
+            }
+            Shape::Named(type_name) => self.named(type_name, Vec::new()),
+            Shape::Generic(type_name, shapes) => {
+                let args = shapes.iter().map(|arg| self.shape(arg, args)).collect();
+                self.named(type_name, args)
+            }
+            Shape::Param(name) => args
+                .iter()
+                .find(|(param, _)| param == name)
+                .map(|(_, depth)| depth.clone())
+                .unwrap_or_default(),
+            _ => (0, String::new()),
+        }
+    }
+
+    /// The depth of the type `type_name` applied to arguments of depths `args`
+    fn named(&mut self, type_name: &TypeName, args: Vec<Depth>) -> Depth {
+        let Some(definition) = self.types.get(type_name).copied() else {
+            return (1, String::new());
+        };
+        if !self.visiting.insert(*type_name) {
+            return (1, String::new());
+        }
+        let args: Vec<(&str, Depth)> = type_name.params.iter().copied().zip(args).collect();
+        let mut fields: Vec<(String, &Shape)> = Vec::new();
+        match definition {
+            Definition::Struct(items) => fields.extend(
+                named(items)
+                    .into_iter()
+                    .map(|(name, shape)| (format!(".{}", name), shape)),
+            ),
+            Definition::TupleStruct(items) => fields.extend(
+                numbered(items)
+                    .into_iter()
+                    .map(|(name, shape)| (format!(".{}", name), shape)),
+            ),
+            Definition::NewtypeStruct(inner) => fields.push((String::new(), inner)),
+            Definition::UnitStruct => {}
+            Definition::Enum(variants) => {
+                for variant in variants {
+                    let items = match &variant.kind {
+                        VariantKind::Struct(items) => named(items),
+                        VariantKind::Tuple(items) => numbered(items),
+                        VariantKind::Newtype(inner) => {
+                            fields.push((format!("::{}", variant.name), inner));
+                            continue;
+                        }
+                        VariantKind::Unit => continue,
+                    };
+                    fields.extend(
+                        items
+                            .into_iter()
+                            .map(|(name, shape)| (format!("::{}.{}", variant.name, name), shape)),
+                    );
+                }
+            }
+        }
+        let depth = deepest(fields.into_iter().map(|(step, shape)| {
+            let (depth, path) = self.shape(shape, &args);
+            (depth, format!("{}{}", step, path))
+        }));
+        self.visiting.remove(type_name);
+        (depth.0 + 1, depth.1)
+    }
+}
+
+/// The first of the deepest of `depths`
+fn deepest(depths: impl Iterator<Item = Depth>) -> Depth {
+    depths.fold((0, String::new()), |deepest, depth| {
+        if depth.0 > deepest.0 {
+            depth
+        } else {
+            deepest
+        }
+    })
+}
@@ -0,0 +1,60 @@
+//! Schemas imported on first use, a chunk per module
+//!
+//! With [`Generator::schema_loaders`](crate::Generator::schema_loaders),
+//! layouts that write files also write `loaders.ts`, mapping each non-generic
+//! type to a dynamic import of the file declaring it. Bundlers split every
+//! module (or protocol group) into a chunk of its own, so that an app decoding
+//! a few of thousands of types only loads the chunks holding them, through
+//! the runtime's `LazySchemas`:
+//!
+//! ```typescript
+//! const schemas = new LazySchemas(SCHEMA_LOADERS);
+//! const { value } = await schemas.decode("Reading", bytes);
+//! ```
+
+use crate::generate::GeneratedFile;
+use crate::naming::Naming;
+use crate::shape::{Definition, TypeName};
+
+/// `loaders.ts`, starting with `header`, importing the runtime's types from
+/// `import_from` and each type from the file `import_of` its index gives;
+/// `None` without non-generic types
+pub(crate) fn render(
+    types: &[(TypeName, Definition)],
+    naming: &Naming,
+    header: &str,
+    import_from: &str,
+    import_of: impl Fn(usize) -> String,
+) -> Option<GeneratedFile> {
+    let loaders: Vec<String> = types
+        .iter()
+        .enumerate()
+        .filter(|(_, (type_name, _))| type_name.params.is_empty())
+        .map(|(i, (type_name, _))| {
+            format!(
+                "  {}: () => import(\"{}\").then((module) => module.{}),\n",
+                naming.type_name(type_name.name),
+                import_of(i),
+                naming.schema(type_name.name),
+            )
+        })
+        .collect();
+    if loaders.is_empty() {
+        return None;
+    }
+
+    let mut source = format!(
+        "{header}\n\
+         import type {{ SchemaLoaders }} from \"{import_from}\";\n\
+         \n\
+         /** Each type's schema, imported with the module declaring it on first use */\n\
+         export const SCHEMA_LOADERS = {{\n"
+    );
+    source.extend(loaders);
+    source.push_str("} satisfies SchemaLoaders;\n");
+
+    Some(GeneratedFile {
+        path: "loaders.ts".to_string(),
+        source,
+    })
+}
@@ -0,0 +1,200 @@
+//! Output of `postcard-ts generate --lsp-json`
+//!
+//! One JSON object per line, for an editor extension to read as they come:
+//!
+//! ```json
+//! {"kind": "diagnostic", "severity": "error", "code": "missing", "message": "Frame refers to Reading, which does not derive PostcardTs", "types": ["Frame", "Reading"]}
+//! {"kind": "layout", "version": 1, "types": [...]}
+//! {"kind": "written", "written": ["web/src/generated/index.ts"], "unchanged": [], "removed": []}
+//! ```
+//!
+//! `layout` carries the wire layout of every type (see
+//! [`Generator::generate_wire_layout`](crate::Generator::generate_wire_layout)),
+//! so hovers in Rust and TypeScript files can both be answered from it.
+//! Diagnostics name the types (or, for `module_cycle`, the modules, for the
+//! protocol group errors other than `group_overlap`, the groups, and for
+//! `unmatched_direction_pattern`, the pattern) involved;
+//! lints (see [`lint`](crate::lint()) come first, as `warning` diagnostics,
+//! or `error` ones for lints the [`LintConfig`] denies, which stop the files
+//! being written. Lints of registered types, and the CLI's diagnostics from
+//! compiling the crate, have a `file` and a zero-based `range`.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::generate::{GenerateError, Generator, Layout};
+use crate::ir::string;
+use crate::lint::{self, lint_types_with, Lint, LintConfig, LintLevel};
+use crate::output::{write_files, WriteOptions, WriteReport};
+use crate::shape::{Definition, TypeName};
+use crate::wire_layout::{self, WIRE_LAYOUT_VERSION};
+
+/// Generate and write `types` as [`write_files`] would, returning the messages
+/// to print
+///
+/// `Err` when generating or writing failed, the messages ending with a
+/// diagnostic saying why.
+pub fn generate(
+    generator: &Generator,
+    types: Vec<(TypeName, Definition)>,
+    layout: Layout,
+    out: &Path,
+    options: WriteOptions,
+) -> Result<Vec<String>, Vec<String>> {
+    generate_with_lints(
+        generator,
+        types,
+        layout,
+        out,
+        options,
+        &LintConfig::default(),
+    )
+}
+
+/// [`generate`], linting as `lints` says
+///
+/// `Err` without writing anything when a lint is denied.
+pub fn generate_with_lints(
+    generator: &Generator,
+    types: Vec<(TypeName, Definition)>,
+    layout: Layout,
+    out: &Path,
+    options: WriteOptions,
+    lints: &LintConfig,
+) -> Result<Vec<String>, Vec<String>> {
+    let files = generator
+        .generate_types_files(types.clone(), layout)
+        .map_err(|error| vec![diagnostic(&error)])?;
+    let mut found = lint_types_with(&types, lints);
+    lint::locate(&mut found);
+    let mut messages: Vec<String> = found.iter().map(lint_diagnostic).collect();
+    if found.iter().any(|lint| lint.level == LintLevel::Deny) {
+        return Err(messages);
+    }
+    messages.push(layout_message(&types));
+    match write_files(out, &files, options) {
+        Ok(report) => {
+            messages.push(written_message(&report));
+            Ok(messages)
+        }
+        Err(error) => {
+            messages.push(io_diagnostic(&error));
+            Err(messages)
+        }
+    }
+}
+
+/// A diagnostic for a type the generator cannot handle
+pub fn diagnostic(error: &GenerateError) -> String {
+    let names: Vec<&str> = match error {
+        GenerateError::DuplicateName { name, .. } => vec![*name],
+        GenerateError::Missing {
+            name,
+            referenced_by,
+        } => vec![*referenced_by, *name],
+        GenerateError::Recursive(path) | GenerateError::ModuleCycle(path) => path.clone(),
+        GenerateError::TagClash { name, .. }
+        | GenerateError::FloatKey { name }
+        | GenerateError::GroupOverlap { name, .. }
+        | GenerateError::Unbounded { name, .. }
+        | GenerateError::DirectionConflict { name }
+        | GenerateError::WasmWireShape { name } => vec![*name],
+        GenerateError::InvalidGroupName(group)
+        | GenerateError::UnmatchedGroupPattern { group, .. } => vec![group],
+        GenerateError::GroupCycle(path) => path.iter().map(String::as_str).collect(),
+        GenerateError::InvalidSimulatorName(simulator) => vec![simulator],
+        GenerateError::InvalidSimulator { type_name, .. } => vec![type_name],
+        GenerateError::InvalidOverride { name, .. } => vec![name],
+        GenerateError::UnmatchedDirectionPattern(pattern)
+        | GenerateError::UnmatchedExample(pattern) => vec![pattern],
+        GenerateError::InvalidConstant { name, .. }
+        | GenerateError::InvalidFeature { name, .. } => vec![name],
+        GenerateError::InvalidNaming { ident, .. } => vec![ident.key()],
+        GenerateError::NameCollision { uses, .. } => {
+            uses.iter().map(|(_, type_name)| *type_name).collect()
+        }
+    };
+    let key = match error {
+        GenerateError::ModuleCycle(_) => "modules",
+        GenerateError::InvalidGroupName(_)
+        | GenerateError::UnmatchedGroupPattern { .. }
+        | GenerateError::GroupCycle(_) => "groups",
+        GenerateError::InvalidSimulatorName(_) => "simulators",
+        GenerateError::InvalidConstant { .. } => "constants",
+        GenerateError::InvalidFeature { .. } => "features",
+        GenerateError::InvalidNaming { .. } => "naming",
+        GenerateError::UnmatchedDirectionPattern(_) | GenerateError::UnmatchedExample(_) => {
+            "patterns"
+        }
+        _ => "types",
+    };
+    let names: Vec<String> = names.iter().map(|name| string(name)).collect();
+    format!(
+        "{{\"kind\": \"diagnostic\", \"severity\": \"error\", \"code\": \"{}\", \"message\": {}, \"{}\": [{}]}}",
+        error.code(),
+        string(&error.to_string()),
+        key,
+        names.join(", ")
+    )
+}
+
+/// A diagnostic for a type that generates but is easy to misread, a warning
+/// unless the lint is denied
+pub fn lint_diagnostic(lint: &Lint) -> String {
+    let severity = match lint.level {
+        LintLevel::Deny => "error",
+        LintLevel::Allow | LintLevel::Warn => "warning",
+    };
+    // The name of the type, a range of one line
+    let span = match &lint.span {
+        Some(span) => {
+            let line = span.line.saturating_sub(1);
+            let start = span.column.saturating_sub(1);
+            let end = start as usize + lint.type_name.name.len();
+            format!(
+                ", \"file\": {}, \"range\": {{\"start\": {{\"line\": {line}, \"character\": {start}}}, \"end\": {{\"line\": {line}, \"character\": {end}}}}}",
+                string(&span.file.display().to_string())
+            )
+        }
+        None => String::new(),
+    };
+    format!(
+        "{{\"kind\": \"diagnostic\", \"severity\": \"{}\", \"code\": \"{}\", \"message\": {}{}, \"types\": [{}]}}",
+        severity,
+        lint.code,
+        string(&lint.message),
+        span,
+        string(lint.type_name.name)
+    )
+}
+
+fn io_diagnostic(error: &io::Error) -> String {
+    format!(
+        "{{\"kind\": \"diagnostic\", \"severity\": \"error\", \"code\": \"io\", \"message\": {}}}",
+        string(&error.to_string())
+    )
+}
+
+fn layout_message(types: &[(TypeName, Definition)]) -> String {
+    format!(
+        "{{\"kind\": \"layout\", \"version\": {}, \"types\": [{}]}}",
+        WIRE_LAYOUT_VERSION,
+        wire_layout::type_layouts(types).join(", ")
+    )
+}
+
+fn written_message(report: &WriteReport) -> String {
+    let paths = |paths: &[PathBuf]| {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| string(&path.display().to_string()))
+            .collect();
+        format!("[{}]", paths.join(", "))
+    };
+    format!(
+        "{{\"kind\": \"written\", \"written\": {}, \"unchanged\": {}, \"removed\": {}}}",
+        paths(&report.written),
+        paths(&report.unchanged),
+        paths(&report.removed)
+    )
+}
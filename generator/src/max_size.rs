@@ -0,0 +1,224 @@
+//! Upper bounds on the encoded size of types, as postcard's `MaxSize` gives
+//!
+//! Firmware sizes its buffers (DMA, ring buffers, static frames) with
+//! `T::POSTCARD_MAX_SIZE`; the bound computed here is the same one, so the
+//! TypeScript side can size its buffers and check frames alike. Varints count
+//! at their longest (3 bytes for a `u16`, 19 for a `u128`), a `char` as 5
+//! bytes, an `Option` as a byte more than its contents and an enum as its
+//! largest variant after the varint of the number of variants, which is how
+//! the derive counts the variant index.
+//!
+//! Strings, byte strings, sequences and maps have no bound, nor do types
+//! containing themselves, unless they have a capacity: `heapless::String<N>`
+//! and `#[postcard_ts(max_len = N)]` strings are bounded, and so are the
+//! `heapless` sequences and maps, by the varint of `N` and `N` items.
+
+use std::collections::HashMap;
+
+use crate::generate::GenerateError;
+use crate::registry;
+use crate::shape::{Definition, PostcardTs, Shape, TypeName, VariantKind};
+use crate::wire_layout::substitute_definition;
+
+/// The most bytes a value of `T` takes on the wire, looking up the types it
+/// refers to among those registered with `#[derive(PostcardTs)]`
+///
+/// ```
+/// use serde_postcard_ts::{max_size, GenerateError, PostcardTs};
+///
+/// #[derive(PostcardTs)]
+/// struct Player {
+///     id: u32,
+///     position: [f32; 3],
+///     team: Option<u8>,
+/// }
+///
+/// #[derive(PostcardTs)]
+/// struct Chat {
+///     text: String,
+/// }
+///
+/// assert_eq!(max_size::<Player>()?, 5 + 12 + 2);
+/// assert_eq!(
+///     max_size::<Chat>(),
+///     Err(GenerateError::Unbounded { name: "Chat", reason: "a String" })
+/// );
+/// # Ok::<(), serde_postcard_ts::GenerateError>(())
+/// ```
+pub fn max_size<T: PostcardTs>() -> Result<usize, GenerateError> {
+    let types = registry::registered();
+    let shape = T::shape();
+    let name = match &shape {
+        Shape::Named(type_name) | Shape::Generic(type_name, _) => type_name.name,
+        _ => std::any::type_name::<T>(),
+    };
+    Sizer::new(&types, name).size(&shape)
+}
+
+/// The maximum encoded size of each bounded non-generic type among `types`
+///
+/// Types without a bound are left out; [`max_size`] says why a type has none.
+pub fn max_sizes(
+    types: &[(TypeName, Definition)],
+) -> Result<Vec<(TypeName, usize)>, GenerateError> {
+    let mut sizes = Vec::new();
+    for (type_name, _) in types.iter().filter(|(name, _)| name.params.is_empty()) {
+        match Sizer::new(types, type_name.name).size(&Shape::Named(*type_name)) {
+            Ok(size) => sizes.push((*type_name, size)),
+            Err(GenerateError::Unbounded { .. }) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(sizes)
+}
+
+/// Bytes of the varint of `value`, 0 for 0, as postcard counts a
+/// discriminant
+fn discriminant_len(value: usize) -> usize {
+    let bits = usize::BITS - value.leading_zeros();
+    (bits as usize).div_ceil(7)
+}
+
+struct Sizer<'a> {
+    definitions: HashMap<TypeName, &'a Definition>,
+    /// The type being sized, for errors
+    name: &'static str,
+    /// Named types being sized, with their arguments filled in
+    stack: Vec<Shape>,
+}
+
+impl<'a> Sizer<'a> {
+    fn new(types: &'a [(TypeName, Definition)], name: &'static str) -> Self {
+        Sizer {
+            definitions: types
+                .iter()
+                .map(|(type_name, definition)| (*type_name, definition))
+                .collect(),
+            name,
+            stack: Vec::new(),
+        }
+    }
+
+    fn unbounded(&self, reason: &'static str) -> GenerateError {
+        GenerateError::Unbounded {
+            name: self.name,
+            reason,
+        }
+    }
+
+    fn size(&mut self, shape: &Shape) -> Result<usize, GenerateError> {
+        Ok(match shape {
+            Shape::Bool | Shape::I8 | Shape::U8 => 1,
+            Shape::I16 | Shape::U16 => 3,
+            Shape::I32 | Shape::U32 => 5,
+            Shape::I64 | Shape::U64 => 10,
+            Shape::I128 | Shape::U128 => 19,
+            Shape::F32 => 4,
+            Shape::F64 => 8,
+            Shape::Char => 5,
+            Shape::Unit => 0,
+            // An empty string still writes its length
+            Shape::BoundedString(max) => self.add(discriminant_len(*max).max(1), *max)?,
+            Shape::String => return Err(self.unbounded("a String")),
+            Shape::Bytes => return Err(self.unbounded("a byte string")),
+            Shape::Seq(_) => return Err(self.unbounded("a sequence")),
+            Shape::Map(_, _) => return Err(self.unbounded("a map")),
+            Shape::BoundedSeq(item, max) => {
+                let item = self.size(item)?;
+                self.repeated(item, *max)?
+            }
+            Shape::BoundedMap(key, value, max) => {
+                let entry = self.sizes([&**key, &**value])?;
+                self.repeated(entry, *max)?
+            }
+            Shape::Param(_) => return Err(self.unbounded("a type parameter")),
+            Shape::Option(inner) => {
+                let inner = self.size(inner)?;
+                self.add(1, inner)?
+            }
+            Shape::Tuple(items) => self.sizes(items)?,
+            Shape::Named(type_name) | Shape::Generic(type_name, _) => {
+                if self.stack.contains(shape) {
+                    return Err(self.unbounded("itself"));
+                }
+                let definition = self.definition(type_name, shape)?;
+                self.stack.push(shape.clone());
+                let size = self.definition_size(&definition)?;
+                self.stack.pop();
+                size
+            }
+        })
+    }
+
+    fn sizes<'s>(
+        &mut self,
+        shapes: impl IntoIterator<Item = &'s Shape>,
+    ) -> Result<usize, GenerateError> {
+        let mut total = 0;
+        for shape in shapes {
+            let size = self.size(shape)?;
+            total = self.add(total, size)?;
+        }
+        Ok(total)
+    }
+
+    fn definition_size(&mut self, definition: &Definition) -> Result<usize, GenerateError> {
+        match definition {
+            Definition::Struct(fields) => self.sizes(fields.iter().map(|field| &field.shape)),
+            Definition::TupleStruct(items) => self.sizes(items),
+            Definition::NewtypeStruct(inner) => self.size(inner),
+            Definition::UnitStruct => Ok(0),
+            Definition::Enum(variants) => {
+                let mut largest = 0;
+                for variant in variants {
+                    let size = match &variant.kind {
+                        VariantKind::Unit => 0,
+                        VariantKind::Newtype(inner) => self.size(inner)?,
+                        VariantKind::Tuple(items) => self.sizes(items)?,
+                        VariantKind::Struct(fields) => {
+                            self.sizes(fields.iter().map(|field| &field.shape))?
+                        }
+                    };
+                    largest = largest.max(size);
+                }
+                self.add(discriminant_len(variants.len()), largest)
+            }
+        }
+    }
+
+    /// A sum too large for a `usize` is no bound either
+    /// A length prefix of up to `max`, then `max` items of `item` bytes
+    fn repeated(&self, item: usize, max: usize) -> Result<usize, GenerateError> {
+        let items = item
+            .checked_mul(max)
+            .ok_or_else(|| self.unbounded("more bytes than a usize counts"))?;
+        self.add(discriminant_len(max).max(1), items)
+    }
+
+    fn add(&self, a: usize, b: usize) -> Result<usize, GenerateError> {
+        a.checked_add(b)
+            .ok_or_else(|| self.unbounded("more bytes than a usize counts"))
+    }
+
+    /// The definition of a named type, with a generic type's arguments filled in
+    fn definition(&self, type_name: &TypeName, shape: &Shape) -> Result<Definition, GenerateError> {
+        let definition = self
+            .definitions
+            .get(type_name)
+            .ok_or_else(|| GenerateError::Missing {
+                name: type_name.name,
+                referenced_by: match self.stack.last() {
+                    Some(Shape::Named(outer) | Shape::Generic(outer, _)) => outer.name,
+                    _ => type_name.name,
+                },
+            })?;
+        Ok(match shape {
+            Shape::Generic(_, args) => {
+                let bindings: HashMap<&str, &Shape> =
+                    type_name.params.iter().copied().zip(args).collect();
+                substitute_definition(definition, &bindings)
+            }
+            _ => (*definition).clone(),
+        })
+    }
+}
@@ -0,0 +1,321 @@
+//! The names of generated identifiers
+//!
+//! Every export the generator writes for a type is named after the Rust type:
+//! `ReadingSchema`, `type Reading`, `serializeReadingCrc`, `isTelemetryAlarm`.
+//! Codebases with naming conventions of their own (`I`-prefixed types,
+//! `Codec` suffixes, verbs first) change the template of each kind of
+//! identifier ([`Ident`]) with a [`Naming`] rather than post-processing the
+//! generated files. Templates name the Rust type as `{name}`, or as `{NAME}`
+//! in SCREAMING_SNAKE_CASE, and variant checks name the variant as
+//! `{variant}`:
+//!
+//! ```text
+//! Ident::Type    "I{name}"          export type IReading = InferType<typeof ReadingCodec>;
+//! Ident::Schema  "{name}Codec"      export const ReadingCodec = struct({ ... });
+//! ```
+//!
+//! Templates giving two exports the same name, such as `{name}Schema` for
+//! [`Ident::Zod`] as well, fail generation
+//! ([`GenerateError::NameCollision`]) rather than emitting code that does not
+//! compile.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use crate::generate::{pascal_case, screaming_snake, GenerateError};
+
+/// A kind of identifier generated per type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ident {
+    /// The TypeScript type, `{name}`
+    Type,
+    /// The schema, `{name}Schema`
+    Schema,
+    /// [`Generator::schema_hashes`](crate::Generator::schema_hashes),
+    /// `{name}SchemaHash`
+    SchemaHash,
+    /// [`Generator::max_sizes`](crate::Generator::max_sizes), `{NAME}_MAX_SIZE`
+    MaxSize,
+    /// The key of a type used as a map key, `{name}Key`
+    Key,
+    /// [`Generator::variant_peeks`](crate::Generator::variant_peeks),
+    /// `is{name}{variant}`
+    VariantPeek,
+    /// [`Generator::crc`](crate::Generator::crc), `serialize{name}Crc`
+    SerializeCrc,
+    /// [`Generator::crc`](crate::Generator::crc), `deserialize{name}Crc`
+    DeserializeCrc,
+    /// [`Generator::salvage`](crate::Generator::salvage), `salvage{name}`
+    Salvage,
+    /// [`Generator::decoders`](crate::Generator::decoders), `decode{name}`
+    Decode,
+    /// [`Generator::decoders`](crate::Generator::decoders), `tryDecode{name}`
+    TryDecode,
+    /// [`Generator::decoders`](crate::Generator::decoders),
+    /// `create{name}StreamDecoder`
+    StreamDecoder,
+    /// [`Validator::Zod`](crate::Validator::Zod), `{name}Zod`
+    Zod,
+    /// [`Generator::dispatchers`](crate::Generator::dispatchers),
+    /// `{name}Dispatcher`
+    Dispatcher,
+    /// [`Generator::display_names`](crate::Generator::display_names),
+    /// `{name}DisplayNames`
+    DisplayNames,
+}
+
+impl Ident {
+    pub const ALL: [Ident; 15] = [
+        Ident::Type,
+        Ident::Schema,
+        Ident::SchemaHash,
+        Ident::MaxSize,
+        Ident::Key,
+        Ident::VariantPeek,
+        Ident::SerializeCrc,
+        Ident::DeserializeCrc,
+        Ident::Salvage,
+        Ident::Decode,
+        Ident::TryDecode,
+        Ident::StreamDecoder,
+        Ident::Zod,
+        Ident::Dispatcher,
+        Ident::DisplayNames,
+    ];
+
+    /// The key naming the kind in the `postcard-ts` config, e.g. `schema_hash`
+    pub fn key(self) -> &'static str {
+        match self {
+            Ident::Type => "type",
+            Ident::Schema => "schema",
+            Ident::SchemaHash => "schema_hash",
+            Ident::MaxSize => "max_size",
+            Ident::Key => "key",
+            Ident::VariantPeek => "variant_peek",
+            Ident::SerializeCrc => "serialize_crc",
+            Ident::DeserializeCrc => "deserialize_crc",
+            Ident::Salvage => "salvage",
+            Ident::Decode => "decode",
+            Ident::TryDecode => "try_decode",
+            Ident::StreamDecoder => "stream_decoder",
+            Ident::Zod => "zod",
+            Ident::Dispatcher => "dispatcher",
+            Ident::DisplayNames => "display_names",
+        }
+    }
+
+    /// The kind with config key `key`
+    pub fn named(key: &str) -> Option<Ident> {
+        Ident::ALL.into_iter().find(|ident| ident.key() == key)
+    }
+
+    /// The template used unless a [`Naming`] changes it
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Ident::Type => "{name}",
+            Ident::Schema => "{name}Schema",
+            Ident::SchemaHash => "{name}SchemaHash",
+            Ident::MaxSize => "{NAME}_MAX_SIZE",
+            Ident::Key => "{name}Key",
+            Ident::VariantPeek => "is{name}{variant}",
+            Ident::SerializeCrc => "serialize{name}Crc",
+            Ident::DeserializeCrc => "deserialize{name}Crc",
+            Ident::Salvage => "salvage{name}",
+            Ident::Decode => "decode{name}",
+            Ident::TryDecode => "tryDecode{name}",
+            Ident::StreamDecoder => "create{name}StreamDecoder",
+            Ident::Zod => "{name}Zod",
+            Ident::Dispatcher => "{name}Dispatcher",
+            Ident::DisplayNames => "{name}DisplayNames",
+        }
+    }
+}
+
+impl Display for Ident {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Ident::Type => "type",
+            Ident::Schema => "schema",
+            Ident::SchemaHash => "schema hash",
+            Ident::MaxSize => "maximum size",
+            Ident::Key => "map key helper",
+            Ident::VariantPeek => "variant check",
+            Ident::SerializeCrc => "CRC serializer",
+            Ident::DeserializeCrc => "CRC deserializer",
+            Ident::Salvage => "salvage decoder",
+            Ident::Decode => "decoder",
+            Ident::TryDecode => "Result decoder",
+            Ident::StreamDecoder => "stream decoder factory",
+            Ident::Zod => "Zod validator",
+            Ident::Dispatcher => "dispatcher",
+            Ident::DisplayNames => "display name table",
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Space {
+    Type,
+    Value,
+}
+
+/// The templates generated identifiers are named with
+///
+/// ```
+/// use serde_postcard_ts::{Ident, Naming};
+///
+/// let naming = Naming::new()
+///     .template(Ident::Type, "I{name}")
+///     .template(Ident::VariantPeek, "{name}Is{variant}");
+/// assert_eq!(naming.ident(Ident::Type, "Reading"), "IReading");
+/// assert_eq!(naming.ident(Ident::Schema, "Reading"), "ReadingSchema");
+/// assert_eq!(naming.variant_peek("Telemetry", "alarm"), "TelemetryIsAlarm");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Naming {
+    templates: BTreeMap<Ident, String>,
+}
+
+impl Naming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the identifiers of kind `ident` with `template`
+    pub fn template(mut self, ident: Ident, template: impl Into<String>) -> Self {
+        self.templates.insert(ident, template.into());
+        self
+    }
+
+    fn template_of(&self, ident: Ident) -> &str {
+        self.templates
+            .get(&ident)
+            .map_or(ident.default_template(), String::as_str)
+    }
+
+    /// The identifier of kind `ident` for the Rust type `name`
+    pub fn ident(&self, ident: Ident, name: &str) -> String {
+        fill(self.template_of(ident), name, "")
+    }
+
+    /// The check of whether a frame holds `variant` of the enum `name`
+    pub fn variant_peek(&self, name: &str, variant: &str) -> String {
+        fill(
+            self.template_of(Ident::VariantPeek),
+            name,
+            &pascal_case(variant),
+        )
+    }
+
+    pub(crate) fn type_name(&self, name: &str) -> String {
+        self.ident(Ident::Type, name)
+    }
+
+    pub(crate) fn schema(&self, name: &str) -> String {
+        self.ident(Ident::Schema, name)
+    }
+
+    /// Fail on a template that names no type, or does not make an identifier
+    pub(crate) fn check(&self) -> Result<(), GenerateError> {
+        for (&ident, template) in &self.templates {
+            let reason = if !template.contains("{name}") && !template.contains("{NAME}") {
+                "it must contain {name} or {NAME}"
+            } else if ident == Ident::VariantPeek && !template.contains("{variant}") {
+                "it must contain {variant}"
+            } else if !is_identifier(&fill(template, "Name", "Variant")) {
+                "it must make an identifier: letters, digits, _ and $, not starting with a digit"
+            } else {
+                continue;
+            };
+            return Err(GenerateError::InvalidNaming {
+                ident,
+                template: template.clone(),
+                reason,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fail on a name exported twice from `sources`, the generated files,
+    /// naming the types and kinds of identifier it stands for among `types`
+    /// and the `variants` of their enums
+    pub(crate) fn check_collisions<'a>(
+        &self,
+        sources: impl IntoIterator<Item = &'a str>,
+        types: &[(&'static str, Vec<&'static str>)],
+    ) -> Result<(), GenerateError> {
+        let mut seen = HashSet::new();
+        for source in sources {
+            for (name, spaces) in source.lines().filter_map(export) {
+                // Every module exports its own, and the index merges them
+                if name == "__schema" {
+                    continue;
+                }
+                for &space in spaces {
+                    if !seen.insert((space, name)) {
+                        return Err(GenerateError::NameCollision {
+                            name: name.to_string(),
+                            uses: self.uses(name, types),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The kinds of identifier `name` stands for, and for which types
+    fn uses(
+        &self,
+        name: &str,
+        types: &[(&'static str, Vec<&'static str>)],
+    ) -> Vec<(Ident, &'static str)> {
+        let mut uses = Vec::new();
+        for (type_name, variants) in types {
+            for ident in Ident::ALL {
+                let named = match ident {
+                    Ident::VariantPeek => variants
+                        .iter()
+                        .any(|variant| self.variant_peek(type_name, variant) == name),
+                    _ => self.ident(ident, type_name) == name,
+                };
+                if named {
+                    uses.push((ident, *type_name));
+                }
+            }
+        }
+        uses
+    }
+}
+
+fn fill(template: &str, name: &str, variant: &str) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{NAME}", &screaming_snake(name))
+        .replace("{variant}", variant)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$')
+}
+
+/// The name a line of generated code exports, and what it names
+fn export(line: &str) -> Option<(&str, &'static [Space])> {
+    let declared = line.strip_prefix("export ")?;
+    let declared = declared.strip_prefix("declare ").unwrap_or(declared);
+    let (keyword, rest) = declared.split_once(' ')?;
+    let spaces: &'static [Space] = match keyword {
+        "const" | "function" => &[Space::Value],
+        "type" | "interface" => &[Space::Type],
+        "class" => &[Space::Type, Space::Value],
+        _ => return None,
+    };
+    let end = rest
+        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '$'))
+        .unwrap_or(rest.len());
+    Some((&rest[..end], spaces))
+}
@@ -0,0 +1,107 @@
+//! Writing generated files to disk
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::generate::{GeneratedFile, HEADER};
+
+/// What [`write_files`] may do to files already in the output directory
+///
+/// Files written by the generator are always replaced; the options only
+/// concern files that are not part of the new output or not generated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Remove generated files that are no longer produced, e.g. for a module
+    /// whose types were moved
+    pub clean: bool,
+    /// Replace files in the way that were not written by the generator
+    pub overwrite: bool,
+}
+
+/// Files touched by [`write_files`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteReport {
+    pub written: Vec<PathBuf>,
+    /// Already up to date, so left untouched for file watchers and build caches
+    pub unchanged: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+/// Write generated files below `dir`, creating directories as needed
+///
+/// Nothing is written if a file in the way was not generated and
+/// `options.overwrite` is not set; that is reported as
+/// [`io::ErrorKind::AlreadyExists`].
+pub fn write_files(
+    dir: &Path,
+    files: &[GeneratedFile],
+    options: WriteOptions,
+) -> io::Result<WriteReport> {
+    let targets: Vec<PathBuf> = files.iter().map(|file| dir.join(&file.path)).collect();
+    if !options.overwrite {
+        if let Some(path) = targets
+            .iter()
+            .find(|path| path.exists() && !is_generated(path))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} exists and was not generated; set overwrite (--overwrite) to replace it",
+                    path.display()
+                ),
+            ));
+        }
+    }
+
+    let mut report = WriteReport::default();
+    for (file, path) in files.iter().zip(&targets) {
+        if fs::read_to_string(path).is_ok_and(|existing| existing == file.source) {
+            report.unchanged.push(path.clone());
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &file.source)?;
+        report.written.push(path.clone());
+    }
+
+    if options.clean && dir.exists() {
+        let mut stale = Vec::new();
+        find_generated(dir, &mut stale)?;
+        for path in stale.into_iter().filter(|path| !targets.contains(path)) {
+            fs::remove_file(&path)?;
+            report.removed.push(path);
+        }
+    }
+    Ok(report)
+}
+
+/// Whether the file starts with the header, as a `//` comment or, for the
+/// wasm backend's `Cargo.toml`, a `#` one
+fn is_generated(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|source| {
+        source.starts_with(HEADER)
+            || source
+                .strip_prefix('#')
+                .is_some_and(|rest| rest.starts_with(&HEADER[2..]))
+    })
+}
+
+/// Generated `.ts`, `.cts` and `.d.ts` files anywhere below `dir`
+fn find_generated(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_generated(&path, out)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "ts" || ext == "cts")
+            && is_generated(&path)
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
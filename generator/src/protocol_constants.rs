@@ -0,0 +1,215 @@
+//! Protocol constants shared by the firmware and the frontend
+//!
+//! Frame magic bytes, the longest frame a device accepts, topic paths and
+//! version numbers are part of the protocol as much as its types, and drift
+//! just as easily when each side spells them out. A [`ProtocolConstant`] is
+//! declared once, on the [`Generator`](crate::Generator) or in the
+//! `postcard-ts` config, and emitted both into the generated TypeScript and
+//! into a Rust module the firmware includes
+//! ([`Generator::generate_rust_constants`](crate::Generator::generate_rust_constants)):
+//!
+//! ```text
+//! pub const MAX_FRAME_LENGTH: usize = 512;      export const MAX_FRAME_LENGTH = 512;
+//! pub const FRAME_MAGIC: [u8; 2] = [0xca, 0xfe]; export const FRAME_MAGIC = new Uint8Array([0xca, 0xfe]);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::docs;
+use crate::generate::GenerateError;
+use crate::ir;
+
+/// The value of a protocol constant, and the Rust type it is declared with
+///
+/// `u64` and `i64` values are `bigint`s in TypeScript, as the schemas decode
+/// them; `usize` is a number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstantValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    Usize(usize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    /// A `&str`
+    Str(String),
+    /// A `[u8; N]`
+    Bytes(Vec<u8>),
+}
+
+macro_rules! from_value {
+    ($($type:ty => $variant:ident),*) => {
+        $(impl From<$type> for ConstantValue {
+            fn from(value: $type) -> Self {
+                ConstantValue::$variant(value)
+            }
+        })*
+    };
+}
+
+from_value!(
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64, usize => Usize,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, String => Str, Vec<u8> => Bytes
+);
+
+impl From<&str> for ConstantValue {
+    fn from(value: &str) -> Self {
+        ConstantValue::Str(value.to_string())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for ConstantValue {
+    fn from(value: [u8; N]) -> Self {
+        ConstantValue::Bytes(value.to_vec())
+    }
+}
+
+/// A named constant of the protocol
+///
+/// ```
+/// use serde_postcard_ts::{ConstantValue, ProtocolConstant};
+///
+/// let magic = ProtocolConstant::new("FRAME_MAGIC", [0xca, 0xfe]).doc("Starts every frame");
+/// assert_eq!(magic.name(), "FRAME_MAGIC");
+/// assert_eq!(magic.value(), &ConstantValue::Bytes(vec![0xca, 0xfe]));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolConstant {
+    name: String,
+    value: ConstantValue,
+    doc: Option<String>,
+}
+
+impl ProtocolConstant {
+    /// A constant named `name`, uppercase letters, digits and `_`, starting
+    /// with a letter, as constants are named in both languages
+    pub fn new(name: impl Into<String>, value: impl Into<ConstantValue>) -> Self {
+        ProtocolConstant {
+            name: name.into(),
+            value: value.into(),
+            doc: None,
+        }
+    }
+
+    /// A doc comment for both declarations
+    pub fn doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &ConstantValue {
+        &self.value
+    }
+
+    fn doc_lines(&self) -> Vec<&str> {
+        self.doc
+            .as_deref()
+            .map_or_else(Vec::new, |doc| doc.lines().collect())
+    }
+
+    /// The TypeScript declaration
+    fn typescript(&self) -> String {
+        let value = match &self.value {
+            ConstantValue::U8(v) => v.to_string(),
+            ConstantValue::U16(v) => v.to_string(),
+            ConstantValue::U32(v) => v.to_string(),
+            ConstantValue::U64(v) => format!("{}n", v),
+            ConstantValue::Usize(v) => v.to_string(),
+            ConstantValue::I8(v) => v.to_string(),
+            ConstantValue::I16(v) => v.to_string(),
+            ConstantValue::I32(v) => v.to_string(),
+            ConstantValue::I64(v) => format!("{}n", v),
+            ConstantValue::Str(v) => ir::string(v),
+            ConstantValue::Bytes(bytes) => format!("new Uint8Array([{}])", hex_bytes(bytes)),
+        };
+        let doc = match self.doc_lines().as_slice() {
+            [] => String::new(),
+            lines => docs::jsdoc(lines, ""),
+        };
+        format!("{doc}export const {} = {};\n", self.name, value)
+    }
+
+    /// The Rust declaration
+    fn rust(&self) -> String {
+        let (rust_type, value) = match &self.value {
+            ConstantValue::U8(v) => ("u8".to_string(), v.to_string()),
+            ConstantValue::U16(v) => ("u16".to_string(), v.to_string()),
+            ConstantValue::U32(v) => ("u32".to_string(), v.to_string()),
+            ConstantValue::U64(v) => ("u64".to_string(), v.to_string()),
+            ConstantValue::Usize(v) => ("usize".to_string(), v.to_string()),
+            ConstantValue::I8(v) => ("i8".to_string(), v.to_string()),
+            ConstantValue::I16(v) => ("i16".to_string(), v.to_string()),
+            ConstantValue::I32(v) => ("i32".to_string(), v.to_string()),
+            ConstantValue::I64(v) => ("i64".to_string(), v.to_string()),
+            ConstantValue::Str(v) => ("&str".to_string(), format!("{:?}", v)),
+            ConstantValue::Bytes(bytes) => (
+                format!("[u8; {}]", bytes.len()),
+                format!("[{}]", hex_bytes(bytes)),
+            ),
+        };
+        let doc: String = self
+            .doc_lines()
+            .iter()
+            .map(|line| match *line {
+                "" => "///\n".to_string(),
+                line => format!("/// {}\n", line),
+            })
+            .collect();
+        format!("{doc}pub const {}: {} = {};\n", self.name, rust_type, value)
+    }
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:#04x}", byte))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Fail on a constant named other than an uppercase identifier, or named
+/// like an earlier one
+pub(crate) fn check(constants: &[ProtocolConstant]) -> Result<(), GenerateError> {
+    let mut seen = HashSet::new();
+    for constant in constants {
+        let name = &constant.name;
+        let valid = name.starts_with(|ch: char| ch.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '_');
+        let reason = if !valid {
+            "use uppercase letters, digits and _, starting with a letter"
+        } else if !seen.insert(name.as_str()) {
+            "it is declared twice"
+        } else {
+            continue;
+        };
+        return Err(GenerateError::InvalidConstant {
+            name: name.clone(),
+            reason,
+        });
+    }
+    Ok(())
+}
+
+/// The TypeScript declarations of `constants`, one after the other
+pub(crate) fn typescript(constants: &[ProtocolConstant]) -> String {
+    constants.iter().map(ProtocolConstant::typescript).collect()
+}
+
+/// A Rust module declaring `constants`, starting with `header`
+pub(crate) fn rust(constants: &[ProtocolConstant], header: &str) -> String {
+    let mut source = header.to_string();
+    for constant in constants {
+        source.push('\n');
+        source.push_str(&constant.rust());
+    }
+    source
+}
@@ -0,0 +1,127 @@
+//! Types registered by `#[derive(PostcardTs)]`
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::builtin;
+use crate::docs::Docs;
+use crate::lint::Span;
+use crate::shape::{Definition, TypeName};
+
+/// One derived type, submitted to the registry by the derive macro
+pub struct Registration {
+    pub type_name: TypeName,
+    pub definition: fn() -> Definition,
+    /// The hash pinned with `#[postcard_ts(schema_hash = ...)]`
+    pub schema_hash: Option<u64>,
+    /// The direction set with `#[postcard_ts(encode_only)]` or
+    /// `#[postcard_ts(decode_only)]`
+    pub direction: Option<Direction>,
+    /// The doc comments and `#[deprecated]` notes of the type, its fields and
+    /// its variants
+    pub docs: &'static Docs,
+    /// The file declaring the type, as `file!()` gives it
+    pub file: &'static str,
+    /// The line of the type's name in `file`, as `line!()` gives it
+    pub line: u32,
+    /// The column of the type's name in `file`, as `column!()` gives it
+    pub column: u32,
+    /// The declaring crate's directory, which a relative `file` is under or
+    /// is in the workspace of
+    pub manifest_dir: &'static str,
+}
+
+/// The one way a type's values travel, for types only ever sent to the device
+/// or only ever received from it
+///
+/// The generated schema is wrapped in `encodeOnly()` or `decodeOnly()`, so
+/// TypeScript rejects using it the other way, and only the matching CRC
+/// wrapper is generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Only serialized by TypeScript, e.g. commands to the device
+    EncodeOnly,
+    /// Only deserialized by TypeScript, e.g. telemetry from the device
+    DecodeOnly,
+}
+
+inventory::collect!(Registration);
+
+/// Every registered type, from this crate and its dependencies, and the
+/// built-in types they use, sorted by name
+pub fn registered() -> Vec<(TypeName, Definition)> {
+    let mut types: Vec<_> = inventory::iter::<Registration>
+        .into_iter()
+        .map(|registration| (registration.type_name, (registration.definition)()))
+        .collect();
+    builtin::add_builtins(&mut types);
+    types.sort_by_key(|(type_name, _)| *type_name);
+    types
+}
+
+/// The schema hashes pinned on registered types
+pub(crate) fn pinned_schema_hashes() -> Vec<(TypeName, u64)> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .filter_map(|registration| Some((registration.type_name, registration.schema_hash?)))
+        .collect()
+}
+
+/// The directions set on registered types
+pub(crate) fn directions() -> Vec<(TypeName, Direction)> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .filter_map(|registration| Some((registration.type_name, registration.direction?)))
+        .collect()
+}
+
+/// The docs of registered types that have any
+pub(crate) fn docs() -> HashMap<TypeName, &'static Docs> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .filter(|registration| *registration.docs != Docs::default())
+        .map(|registration| (registration.type_name, registration.docs))
+        .collect()
+}
+
+/// The files declaring registered types, for build scripts to watch
+///
+/// Files that cannot be found are left out.
+pub(crate) fn source_files() -> Vec<PathBuf> {
+    let files: BTreeSet<PathBuf> = inventory::iter::<Registration>
+        .into_iter()
+        .filter_map(source_file)
+        .collect();
+    files.into_iter().collect()
+}
+
+/// Where registered types are declared, for those whose files can be found
+pub(crate) fn spans() -> HashMap<TypeName, Span> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .filter_map(|registration| {
+            let span = Span {
+                file: source_file(registration)?,
+                line: registration.line,
+                column: registration.column,
+            };
+            Some((registration.type_name, span))
+        })
+        .collect()
+}
+
+/// The file declaring `registration`'s type
+///
+/// `file!()` is relative to the directory rustc was run from, which is the
+/// crate's for a crate of its own and the workspace's for a member, so it is
+/// looked up from the crate's directory upwards.
+fn source_file(registration: &Registration) -> Option<PathBuf> {
+    let file = Path::new(registration.file);
+    if file.is_absolute() {
+        return Some(file.to_path_buf());
+    }
+    Path::new(registration.manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join(file))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,217 @@
+//! Checking that a payload decodes and encodes back to the same bytes
+//!
+//! A frame the TypeScript side garbles usually decodes fine: the schema, or a
+//! transform in it, only writes the value back differently. `postcard-ts
+//! verify` runs a captured payload through the generated decoder and encoder
+//! under Node, decodes the re-encoded bytes again, and collects where each
+//! value sits in both (the runtime's `trace` option) as a [`RoundTrip`]. Its
+//! report names the innermost values whose bytes differ:
+//!
+//! ```text
+//! GameState: 7 bytes, 6 read, re-encoded to 5 bytes
+//!
+//! original             re-encoded           value
+//!    1: 80 01             1: 7f             GameState.health (u16)
+//!    6: 00             -                    not read
+//! 2 differences
+//! ```
+//!
+//! Values are paired in the order they were decoded, so when the re-encoded
+//! bytes decode to a value of another shape, or not at all, the report falls
+//! back to the first byte that differs.
+
+use std::ops::Range;
+
+/// A value decoded from a payload, as the runtime's `trace` option reports it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracedValue {
+    /// Path of the value, as in decode errors (`GameState.player.health`)
+    pub path: String,
+    /// Schema kind (`u16`, `struct`, `enum`, ...)
+    pub kind: String,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl TracedValue {
+    fn span(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// A payload decoded, encoded again and that decoded once more
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RoundTrip {
+    /// The type the payload was decoded as
+    pub type_name: String,
+    pub original: Vec<u8>,
+    /// Bytes of `original` the value was decoded from, at its start
+    pub bytes_read: usize,
+    /// The values decoded from `original`, inner values first
+    pub values: Vec<TracedValue>,
+    pub reencoded: Vec<u8>,
+    /// The values decoded from `reencoded`, or `None` if it does not decode
+    pub reencoded_values: Option<Vec<TracedValue>>,
+}
+
+/// Bytes of a payload that did not come back the same
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundTripDifference {
+    /// The value the bytes encode, or `None` for bytes after the value
+    pub value: Option<TracedValue>,
+    pub original: Range<usize>,
+    /// The bytes written in their place, or `None` for bytes after the value
+    pub reencoded: Option<Range<usize>>,
+}
+
+impl RoundTrip {
+    /// Whether the payload was read to its end and re-encoded to the same bytes
+    pub fn is_identical(&self) -> bool {
+        self.bytes_read == self.original.len() && self.reencoded == self.original
+    }
+
+    /// The innermost values whose bytes differ, in the order of the payload,
+    /// then any bytes after the value
+    pub fn differences(&self) -> Vec<RoundTripDifference> {
+        let read = &self.original[..self.bytes_read];
+        let mut differences = if read == self.reencoded {
+            Vec::new()
+        } else {
+            self.paired_differences()
+                .filter(|differences| !differences.is_empty())
+                .unwrap_or_else(|| vec![self.first_difference()])
+        };
+        differences.sort_by_key(|difference| difference.original.start);
+        if self.bytes_read < self.original.len() {
+            differences.push(RoundTripDifference {
+                value: None,
+                original: self.bytes_read..self.original.len(),
+                reencoded: None,
+            });
+        }
+        differences
+    }
+
+    /// The differing values with no differing value inside them, if the
+    /// re-encoded bytes decoded to values of the same paths
+    fn paired_differences(&self) -> Option<Vec<RoundTripDifference>> {
+        let reencoded_values = self.reencoded_values.as_ref()?;
+        if reencoded_values.len() != self.values.len()
+            || reencoded_values
+                .iter()
+                .zip(&self.values)
+                .any(|(reencoded, original)| reencoded.path != original.path)
+        {
+            return None;
+        }
+        let differs: Vec<bool> = self
+            .values
+            .iter()
+            .zip(reencoded_values)
+            .map(|(original, reencoded)| {
+                self.original.get(original.span()) != self.reencoded.get(reencoded.span())
+            })
+            .collect();
+        // Inner values come first, so a value spanning the same bytes as
+        // another is inside it if it was decoded before it
+        let inside = |inner: usize, outer: usize| {
+            let (inner_span, outer_span) = (self.values[inner].span(), self.values[outer].span());
+            inner != outer
+                && outer_span.start <= inner_span.start
+                && inner_span.end <= outer_span.end
+                && (inner_span != outer_span || inner < outer)
+        };
+        Some(
+            (0..self.values.len())
+                .filter(|&i| {
+                    differs[i] && !(0..self.values.len()).any(|j| differs[j] && inside(j, i))
+                })
+                .map(|i| RoundTripDifference {
+                    value: Some(self.values[i].clone()),
+                    original: self.values[i].span(),
+                    reencoded: Some(reencoded_values[i].span()),
+                })
+                .collect(),
+        )
+    }
+
+    /// From the first byte that differs to the end of both, in the
+    /// innermost value holding it
+    fn first_difference(&self) -> RoundTripDifference {
+        let first = self.original[..self.bytes_read]
+            .iter()
+            .zip(&self.reencoded)
+            .take_while(|(original, reencoded)| original == reencoded)
+            .count();
+        RoundTripDifference {
+            value: self
+                .values
+                .iter()
+                .find(|value| value.span().contains(&first))
+                .cloned(),
+            original: first..self.bytes_read,
+            reencoded: Some(first..self.reencoded.len()),
+        }
+    }
+
+    /// A line per difference, with the bytes on both sides and the value they
+    /// encode, and a count of them
+    pub fn report(&self) -> String {
+        let mut out = format!("{}: {} bytes", self.type_name, self.original.len());
+        if self.bytes_read < self.original.len() {
+            out.push_str(&format!(", {} read", self.bytes_read));
+        }
+        if self.is_identical() {
+            out.push_str(", re-encoded identically\n");
+            return out;
+        }
+        out.push_str(&format!(
+            ", re-encoded to {} bytes\n\n{:<20} {:<20} value\n",
+            self.reencoded.len(),
+            "original",
+            "re-encoded"
+        ));
+        let differences = self.differences();
+        for difference in &differences {
+            let reencoded = match &difference.reencoded {
+                Some(range) => bytes_at(&self.reencoded, range),
+                None => "-".to_string(),
+            };
+            let value = match &difference.value {
+                Some(value) => format!("{} ({})", value.path, value.kind),
+                None => "not read".to_string(),
+            };
+            out.push_str(&format!(
+                "{:<20} {:<20} {}\n",
+                bytes_at(&self.original, &difference.original),
+                reencoded,
+                value
+            ));
+        }
+        out.push_str(&format!(
+            "{} difference{}\n",
+            differences.len(),
+            if differences.len() == 1 { "" } else { "s" }
+        ));
+        out
+    }
+}
+
+/// Bytes to show of a difference; longer ones are cut short
+const SHOWN_BYTES: usize = 4;
+
+/// `   4: 80 01`, the offset and bytes of `range` in `data`
+fn bytes_at(data: &[u8], range: &Range<usize>) -> String {
+    let bytes = &data[range.clone()];
+    let mut hex: Vec<String> = bytes
+        .iter()
+        .take(SHOWN_BYTES)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if bytes.is_empty() {
+        hex.push("(none)".to_string());
+    } else if bytes.len() > SHOWN_BYTES {
+        hex.push(format!("+{}", bytes.len() - SHOWN_BYTES));
+    }
+    format!("{:>4}: {}", range.start, hex.join(" "))
+}
@@ -0,0 +1,124 @@
+//! Example projects written by `postcard-ts scaffold`
+//!
+//! The examples are kept as templates next to this file and filled in with
+//! the location of this checkout, so they build against the very code the
+//! tool was built from. `tests/scaffold.rs` keeps their generated bindings in
+//! step with the generator.
+
+use std::path::Path;
+
+use crate::generate::GeneratedFile;
+
+/// A runnable example project
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Example {
+    /// A Rust server streaming readings over a WebSocket (and echoing
+    /// messages back), with a Vite frontend decoding them through generated
+    /// schemas
+    WebsocketDashboard,
+}
+
+const WEBSOCKET_DASHBOARD: &[(&str, &str)] = &[
+    (
+        "README.md",
+        include_str!("../templates/websocket-dashboard/README.md"),
+    ),
+    (
+        "server/src/lib.rs",
+        include_str!("../templates/websocket-dashboard/server/src/lib.rs"),
+    ),
+    (
+        "server/src/main.rs",
+        include_str!("../templates/websocket-dashboard/server/src/main.rs"),
+    ),
+    (
+        "web/index.html",
+        include_str!("../templates/websocket-dashboard/web/index.html"),
+    ),
+    (
+        "web/package.json",
+        include_str!("../templates/websocket-dashboard/web/package.json"),
+    ),
+    (
+        "web/src/generated/index.ts",
+        include_str!("../templates/websocket-dashboard/web/src/generated/index.ts"),
+    ),
+    (
+        "web/src/main.ts",
+        include_str!("../templates/websocket-dashboard/web/src/main.ts"),
+    ),
+    (
+        "web/tsconfig.json",
+        include_str!("../templates/websocket-dashboard/web/tsconfig.json"),
+    ),
+    (
+        "web/vite.config.ts",
+        include_str!("../templates/websocket-dashboard/web/vite.config.ts"),
+    ),
+];
+
+/// Stands for the quoted path of the TypeScript runtime's entry point
+const RUNTIME: &str = "\"{{runtime}}\"";
+
+impl Example {
+    pub const ALL: &'static [Example] = &[Example::WebsocketDashboard];
+
+    /// The name given to `postcard-ts scaffold --example`
+    pub fn name(self) -> &'static str {
+        match self {
+            Example::WebsocketDashboard => "websocket-dashboard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|example| example.name() == name)
+    }
+
+    /// The example's files, with paths relative to the project directory
+    pub fn files(self) -> Vec<GeneratedFile> {
+        let generator = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let runtime = generator.parent().unwrap_or(generator).join("src/index.ts");
+        let runtime = format!("{:?}", runtime.display().to_string());
+
+        let templates = match self {
+            Example::WebsocketDashboard => WEBSOCKET_DASHBOARD,
+        };
+        let mut files: Vec<GeneratedFile> = templates
+            .iter()
+            .map(|(path, source)| GeneratedFile {
+                path: path.to_string(),
+                source: source.replace(RUNTIME, &runtime),
+            })
+            .collect();
+        files.push(GeneratedFile {
+            path: "server/Cargo.toml".to_string(),
+            source: server_manifest(self, generator),
+        });
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+}
+
+fn server_manifest(example: Example, generator: &Path) -> String {
+    format!(
+        "[package]\n\
+         name = \"{}-server\"\n\
+         version = \"0.1.0\"\n\
+         edition = \"2021\"\n\
+         publish = false\n\
+         \n\
+         [dependencies]\n\
+         postcard = {{ version = \"1\", features = [\"alloc\"] }}\n\
+         serde = {{ version = \"1\", features = [\"derive\"] }}\n\
+         serde-postcard-ts = {{ path = {:?} }}\n\
+         tungstenite = \"0.24\"\n\
+         \n\
+         # Not part of any enclosing workspace\n\
+         [workspace]\n",
+        example.name(),
+        generator.display().to_string()
+    )
+}
@@ -0,0 +1,1132 @@
+//! Schema snapshots, and whether the changes between two break the wire format
+//!
+//! Postcard writes no field names, type tags or struct lengths, so a peer
+//! built from other types than the sender's misreads its messages, often
+//! without failing. A snapshot is the JSON IR of every registered type
+//! ([`Generator::generate_ir`](crate::Generator::generate_ir)), kept with a
+//! release; [`diff_snapshots`] compares it with the IR of the next one and
+//! tells which changes peers on the old types would notice:
+//!
+//! ```text
+//! breaking    Reading.sensor: u8 -> u16
+//! breaking    Reading: fields reordered: sensor, at -> at, sensor
+//! compatible  Mode::Boost: added
+//! 3 changes, 2 breaking
+//! ```
+//!
+//! Changes are judged by the bytes they make: renaming fields, variants and
+//! referenced types, turning a struct into a tuple struct, or a `Vec<u8>`
+//! into bytes, is compatible; adding, removing or reordering fields,
+//! removing or reordering variants, changing a primitive (widths included)
+//! and lowering a length limit is breaking. A variant appended to an enum is
+//! compatible, as the old variants encode as before, though peers on the old
+//! types cannot decode the new one. `postcard-ts schema snapshot` writes a
+//! crate's snapshot and `postcard-ts schema diff` compares with it.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use crate::ir::IR_VERSION;
+use crate::schema_hash::fnv1a;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// Not JSON, or not the IR; the reason says what is wrong
+    Malformed(String),
+    /// Written by a newer generator, in an IR version this one cannot read
+    UnsupportedVersion(u32),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Malformed(reason) => write!(f, "not a schema snapshot: {}", reason),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported snapshot version {} (IR versions up to {} are read)",
+                version, IR_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// Whether peers on the old types still read and write the same bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    Compatible,
+    Breaking,
+}
+
+/// One difference between two snapshots
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// What changed: a type (`Reading`), a field (`Reading.sensor`, or
+    /// `Pair.0` in tuples), a variant (`Mode::Boost`) or a field of one
+    /// (`Mode::Move.x`); `[]` stands for the items of a sequence or map and
+    /// `{}` for the keys of a map
+    pub path: String,
+    pub compatibility: Compatibility,
+    pub description: String,
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let compatibility = match self.compatibility {
+            Compatibility::Compatible => "compatible",
+            Compatibility::Breaking => "breaking",
+        };
+        write!(
+            f,
+            "{:<10}  {}: {}",
+            compatibility, self.path, self.description
+        )
+    }
+}
+
+/// The changes between two snapshots, in the order of the old one's types,
+/// followed by the added types
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Whether any change breaks the wire format
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.compatibility == Compatibility::Breaking)
+    }
+
+    /// A line per change, and a count of them
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        for change in &self.changes {
+            out.push_str(&format!("{}\n", change));
+        }
+        match self.changes.len() {
+            0 => out.push_str("no changes\n"),
+            count => out.push_str(&format!(
+                "{} change{}, {} breaking\n",
+                count,
+                if count == 1 { "" } else { "s" },
+                self.breaking().count()
+            )),
+        }
+        out
+    }
+}
+
+/// Compare the snapshot `new` with `old`, both IR JSON as
+/// [`Generator::generate_ir`](crate::Generator::generate_ir) writes it
+///
+/// ```
+/// use serde_postcard_ts::{diff_snapshots, Definition, Field, Generator, Shape, TypeName};
+///
+/// let reading = TypeName { name: "Reading", module: "app", params: &[] };
+/// let field = |shape| vec![Field { name: "sensor", shape }];
+/// let old = Generator::new()
+///     .generate_types_ir(vec![(reading, Definition::Struct(field(Shape::U8)))])?;
+/// let new = Generator::new()
+///     .generate_types_ir(vec![(reading, Definition::Struct(field(Shape::U16)))])?;
+///
+/// let diff = diff_snapshots(&old, &new)?;
+/// assert!(diff.is_breaking());
+/// assert_eq!(diff.changes[0].to_string(), "breaking    Reading.sensor: u8 -> u16");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn diff_snapshots(old: &str, new: &str) -> Result<SchemaDiff, SnapshotError> {
+    let old = Snapshot::parse(old)?;
+    let new = Snapshot::parse(new)?;
+    let mut differ = Differ {
+        old: &old,
+        new: &new,
+        changes: Vec::new(),
+        comparing: HashSet::new(),
+    };
+    differ.types();
+    Ok(SchemaDiff {
+        changes: differ.changes,
+    })
+}
+
+// ============================================================================
+// Snapshots
+// ============================================================================
+
+struct Snapshot {
+    types: Vec<(String, Type)>,
+}
+
+struct Type {
+    params: Vec<String>,
+    body: Body,
+}
+
+enum Body {
+    /// A struct, tuple struct, newtype struct or unit struct, by its kind
+    Struct(&'static str, Items),
+    Enum(Vec<Variant>),
+}
+
+struct Variant {
+    name: String,
+    /// `unit`, `newtype`, `tuple` or `struct`
+    kind: &'static str,
+    items: Items,
+}
+
+/// The fields of a struct or variant, in wire order; positional ones are
+/// named by their index
+struct Items {
+    named: bool,
+    items: Vec<(String, Shape)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Shape {
+    /// The IR kind of a primitive, `unit` included
+    Primitive(String),
+    String(Option<u64>),
+    Bytes,
+    Option(Box<Shape>),
+    Seq(Box<Shape>, Option<u64>),
+    Tuple(Vec<Shape>),
+    Map(Box<Shape>, Box<Shape>, Option<u64>),
+    Ref(String, Vec<Shape>),
+    /// The position of a type parameter among its type's, and its name
+    Param(usize, String),
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let list = |shapes: &[Shape]| {
+            shapes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Shape::Primitive(kind) if kind == "unit" => write!(f, "()"),
+            Shape::Primitive(kind) => write!(f, "{}", kind),
+            Shape::String(_) => write!(f, "String"),
+            Shape::Bytes => write!(f, "bytes"),
+            Shape::Option(inner) => write!(f, "Option<{}>", inner),
+            Shape::Seq(item, _) => write!(f, "Vec<{}>", item),
+            Shape::Tuple(items) => write!(f, "({})", list(items)),
+            Shape::Map(key, value, _) => write!(f, "Map<{}, {}>", key, value),
+            Shape::Ref(name, args) if args.is_empty() => write!(f, "{}", name),
+            Shape::Ref(name, args) => write!(f, "{}<{}>", name, list(args)),
+            Shape::Param(_, name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Snapshot {
+    fn parse(source: &str) -> Result<Snapshot, SnapshotError> {
+        let malformed = |reason: &str| SnapshotError::Malformed(reason.to_string());
+        let json = Parser::new(source)
+            .document()
+            .map_err(SnapshotError::Malformed)?;
+        let version = json
+            .get("version")
+            .and_then(Json::as_u64)
+            .ok_or_else(|| malformed("no IR version"))?;
+        if version > u64::from(IR_VERSION) {
+            return Err(SnapshotError::UnsupportedVersion(
+                u32::try_from(version).unwrap_or(u32::MAX),
+            ));
+        }
+        let types = json
+            .get("types")
+            .and_then(Json::as_array)
+            .ok_or_else(|| malformed("no types"))?;
+        let mut parsed = Vec::with_capacity(types.len());
+        for json in types {
+            let name = json
+                .get("name")
+                .and_then(Json::as_str)
+                .ok_or_else(|| malformed("a type has no name"))?;
+            let at = |reason: &str| SnapshotError::Malformed(format!("{}: {}", name, reason));
+            let params: Vec<String> = match json.get("params") {
+                Some(params) => params
+                    .as_array()
+                    .ok_or_else(|| at("params is not a list"))?
+                    .iter()
+                    .map(|param| param.as_str().map(str::to_string))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| at("a type parameter is not a string"))?,
+                None => Vec::new(),
+            };
+            let body = body(json, &params).map_err(|reason| at(&reason))?;
+            parsed.push((name.to_string(), Type { params, body }));
+        }
+        Ok(Snapshot { types: parsed })
+    }
+
+    fn get(&self, name: &str) -> Option<&Type> {
+        self.types
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, ty)| ty)
+    }
+}
+
+fn body(json: &Json, params: &[String]) -> Result<Body, String> {
+    let kind = json.get("kind").and_then(Json::as_str).unwrap_or_default();
+    Ok(match kind {
+        "enum" => {
+            let variants = json
+                .get("variants")
+                .and_then(Json::as_array)
+                .ok_or("an enum has no variants")?;
+            let variants = variants
+                .iter()
+                .map(|variant| {
+                    let name = variant
+                        .get("name")
+                        .and_then(Json::as_str)
+                        .ok_or("a variant has no name")?;
+                    let kind = variant
+                        .get("kind")
+                        .and_then(Json::as_str)
+                        .unwrap_or_default();
+                    let (kind, items) = match kind {
+                        "unit" => ("unit", positional(Vec::new())),
+                        "newtype" => (
+                            "newtype",
+                            positional(vec![member(variant, "inner", params)?]),
+                        ),
+                        "tuple" => ("tuple", positional(shapes(variant, "items", params)?)),
+                        "struct" => ("struct", fields(variant, params)?),
+                        other => return Err(format!("unknown variant kind {:?}", other)),
+                    };
+                    Ok(Variant {
+                        name: name.to_string(),
+                        kind,
+                        items,
+                    })
+                })
+                .collect::<Result<_, String>>()?;
+            Body::Enum(variants)
+        }
+        "struct" => Body::Struct("struct", fields(json, params)?),
+        "tuple_struct" => Body::Struct("tuple struct", positional(shapes(json, "items", params)?)),
+        "newtype_struct" => Body::Struct(
+            "newtype struct",
+            positional(vec![member(json, "inner", params)?]),
+        ),
+        "unit_struct" => Body::Struct("unit struct", positional(Vec::new())),
+        other => return Err(format!("unknown type kind {:?}", other)),
+    })
+}
+
+fn positional(shapes: Vec<Shape>) -> Items {
+    Items {
+        named: false,
+        items: shapes
+            .into_iter()
+            .enumerate()
+            .map(|(i, shape)| (i.to_string(), shape))
+            .collect(),
+    }
+}
+
+fn fields(json: &Json, params: &[String]) -> Result<Items, String> {
+    let fields = json
+        .get("fields")
+        .and_then(Json::as_array)
+        .ok_or("a struct has no fields")?;
+    let items = fields
+        .iter()
+        .map(|field| {
+            let name = field
+                .get("name")
+                .and_then(Json::as_str)
+                .ok_or("a field has no name")?;
+            Ok((name.to_string(), member(field, "type", params)?))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Items { named: true, items })
+}
+
+fn member(json: &Json, key: &str, params: &[String]) -> Result<Shape, String> {
+    shape(json.get(key).ok_or_else(|| format!("no {}", key))?, params)
+}
+
+fn shapes(json: &Json, key: &str, params: &[String]) -> Result<Vec<Shape>, String> {
+    json.get(key)
+        .and_then(Json::as_array)
+        .ok_or_else(|| format!("no {}", key))?
+        .iter()
+        .map(|item| shape(item, params))
+        .collect()
+}
+
+fn shape(json: &Json, params: &[String]) -> Result<Shape, String> {
+    let kind = json
+        .get("kind")
+        .and_then(Json::as_str)
+        .ok_or("a shape has no kind")?;
+    let limit = |key| json.get(key).and_then(Json::as_u64);
+    let boxed = |key| member(json, key, params).map(Box::new);
+    Ok(match kind {
+        "string" => Shape::String(limit("maxBytes")),
+        "bytes" => Shape::Bytes,
+        "option" => Shape::Option(boxed("inner")?),
+        "seq" => Shape::Seq(boxed("item")?, limit("maxLength")),
+        "tuple" => Shape::Tuple(shapes(json, "items", params)?),
+        "map" => Shape::Map(boxed("key")?, boxed("value")?, limit("maxLength")),
+        "ref" => {
+            let name = json
+                .get("name")
+                .and_then(Json::as_str)
+                .ok_or("a reference has no name")?;
+            let args = match json.get("args") {
+                Some(_) => shapes(json, "args", params)?,
+                None => Vec::new(),
+            };
+            Shape::Ref(name.to_string(), args)
+        }
+        "param" => {
+            let name = json
+                .get("name")
+                .and_then(Json::as_str)
+                .ok_or("a type parameter has no name")?;
+            let index = params
+                .iter()
+                .position(|param| param == name)
+                .ok_or_else(|| format!("unknown type parameter {}", name))?;
+            Shape::Param(index, name.to_string())
+        }
+        "bool" | "i8" | "i16" | "i32" | "i64" | "i128" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "f32" | "f64" | "char" | "unit" => Shape::Primitive(kind.to_string()),
+        other => return Err(format!("unknown shape kind {:?}", other)),
+    })
+}
+
+// ============================================================================
+// Schema hashes
+// ============================================================================
+
+/// The schema hash of each non-generic type of a snapshot, as
+/// [`schema_hash`](crate::schema_hash) gives it for the Rust type, so a peer's
+/// hashes can be matched with snapshots without the types they came from
+///
+/// ```
+/// use serde_postcard_ts::{schema_hash, snapshot_schema_hashes, Generator, PostcardTs};
+///
+/// #[derive(PostcardTs)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// let snapshot = Generator::new().generate_ir()?;
+/// let hashes = snapshot_schema_hashes(&snapshot)?;
+/// assert!(hashes.contains(&("Reading".to_string(), schema_hash::<Reading>()?)));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn snapshot_schema_hashes(snapshot: &str) -> Result<Vec<(String, u64)>, SnapshotError> {
+    let snapshot = Snapshot::parse(snapshot)?;
+    snapshot
+        .types
+        .iter()
+        .filter(|(_, ty)| ty.params.is_empty())
+        .map(|(name, _)| {
+            let mut writer = HashWriter {
+                snapshot: &snapshot,
+                stack: Vec::new(),
+                out: String::new(),
+            };
+            writer.shape(&Shape::Ref(name.clone(), Vec::new()), &[])?;
+            Ok((name.clone(), fnv1a(writer.out.as_bytes())))
+        })
+        .collect()
+}
+
+/// Writes the canonical form of a shape, as the generator's `schema_hash`
+struct HashWriter<'s> {
+    snapshot: &'s Snapshot,
+    /// Types being written, with their arguments filled in
+    stack: Vec<Shape>,
+    out: String,
+}
+
+impl HashWriter<'_> {
+    /// Write `shape`, its type parameters bound to `args`, shapes without any
+    fn shape(&mut self, shape: &Shape, args: &[Shape]) -> Result<(), SnapshotError> {
+        match shape {
+            Shape::Primitive(kind) if kind == "unit" => {}
+            Shape::Primitive(kind) => {
+                self.out.push_str(kind);
+                self.out.push(';');
+            }
+            Shape::String(_) => self.out.push_str("string;"),
+            // The same bytes as a `Vec<u8>`
+            Shape::Bytes => self.out.push_str("seq(u8;);"),
+            Shape::Option(inner) => self.wrapped("option", inner, args)?,
+            Shape::Seq(item, _) => self.wrapped("seq", item, args)?,
+            Shape::Tuple(items) => self.shapes(items, args)?,
+            Shape::Map(key, value, _) => {
+                self.out.push_str("map(");
+                self.shape(key, args)?;
+                self.out.push_str(")(");
+                self.shape(value, args)?;
+                self.out.push_str(");");
+            }
+            Shape::Ref(name, ref_args) => {
+                let bound: Vec<Shape> = ref_args.iter().map(|arg| bind(arg, args)).collect();
+                let filled = Shape::Ref(name.clone(), bound.clone());
+                if let Some(depth) = self.stack.iter().position(|open| *open == filled) {
+                    self.out.push_str(&format!("rec({});", depth));
+                    return Ok(());
+                }
+                let snapshot = self.snapshot;
+                let ty = snapshot.get(name).ok_or_else(|| {
+                    SnapshotError::Malformed(format!("no type {} is declared", name))
+                })?;
+                self.stack.push(filled);
+                match &ty.body {
+                    Body::Struct(_, items) => self.items(items, &bound)?,
+                    Body::Enum(variants) => {
+                        self.out.push_str("enum(");
+                        for variant in variants {
+                            self.out.push('(');
+                            self.items(&variant.items, &bound)?;
+                            self.out.push(')');
+                        }
+                        self.out.push_str(");");
+                    }
+                }
+                self.stack.pop();
+            }
+            Shape::Param(index, name) => match args.get(*index) {
+                Some(arg) => self.shape(arg, &[])?,
+                None => self.out.push_str(&format!("param({});", name)),
+            },
+        }
+        Ok(())
+    }
+
+    fn shapes(&mut self, shapes: &[Shape], args: &[Shape]) -> Result<(), SnapshotError> {
+        shapes.iter().try_for_each(|shape| self.shape(shape, args))
+    }
+
+    fn items(&mut self, items: &Items, args: &[Shape]) -> Result<(), SnapshotError> {
+        items
+            .items
+            .iter()
+            .try_for_each(|(_, shape)| self.shape(shape, args))
+    }
+
+    fn wrapped(&mut self, name: &str, inner: &Shape, args: &[Shape]) -> Result<(), SnapshotError> {
+        self.out.push_str(name);
+        self.out.push('(');
+        self.shape(inner, args)?;
+        self.out.push_str(");");
+        Ok(())
+    }
+}
+
+/// `shape` with its type parameters replaced by `args`
+fn bind(shape: &Shape, args: &[Shape]) -> Shape {
+    let boxed = |shape: &Shape| Box::new(bind(shape, args));
+    match shape {
+        Shape::Param(index, _) => args.get(*index).cloned().unwrap_or_else(|| shape.clone()),
+        Shape::Option(inner) => Shape::Option(boxed(inner)),
+        Shape::Seq(item, limit) => Shape::Seq(boxed(item), *limit),
+        Shape::Tuple(items) => Shape::Tuple(items.iter().map(|item| bind(item, args)).collect()),
+        Shape::Map(key, value, limit) => Shape::Map(boxed(key), boxed(value), *limit),
+        Shape::Ref(name, ref_args) => Shape::Ref(
+            name.clone(),
+            ref_args.iter().map(|arg| bind(arg, args)).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+struct Differ<'s> {
+    old: &'s Snapshot,
+    new: &'s Snapshot,
+    changes: Vec<SchemaChange>,
+    /// The enums being compared for encoding alike, by old and new name,
+    /// which recursive enums reach again
+    comparing: HashSet<(&'s str, &'s str)>,
+}
+
+impl<'s> Differ<'s> {
+    fn push(&mut self, path: &str, compatibility: Compatibility, description: String) {
+        self.changes.push(SchemaChange {
+            path: path.to_string(),
+            compatibility,
+            description,
+        });
+    }
+
+    fn types(&mut self) {
+        for (name, old) in &self.old.types {
+            match self.new.get(name) {
+                Some(new) => self.ty(name, old, new),
+                None => self.push(name, Compatibility::Breaking, "removed".to_string()),
+            }
+        }
+        for (name, _) in &self.new.types {
+            if self.old.get(name).is_none() {
+                self.push(name, Compatibility::Compatible, "added".to_string());
+            }
+        }
+    }
+
+    fn ty(&mut self, name: &str, old: &'s Type, new: &'s Type) {
+        if old.params.len() != new.params.len() {
+            let description = format!(
+                "type parameters <{}> -> <{}>",
+                old.params.join(", "),
+                new.params.join(", ")
+            );
+            return self.push(name, Compatibility::Breaking, description);
+        }
+        match (&old.body, &new.body) {
+            (Body::Enum(old), Body::Enum(new)) => self.variants(name, old, new),
+            (Body::Struct(old_kind, old), Body::Struct(new_kind, new)) => {
+                if old_kind != new_kind {
+                    let description = format!("{} -> {}", old_kind, new_kind);
+                    self.push(name, Compatibility::Compatible, description);
+                }
+                self.items(name, old, new);
+            }
+            (Body::Enum(_), Body::Struct(kind, _)) => {
+                self.push(name, Compatibility::Breaking, format!("enum -> {}", kind))
+            }
+            (Body::Struct(kind, _), Body::Enum(_)) => {
+                self.push(name, Compatibility::Breaking, format!("{} -> enum", kind))
+            }
+        }
+    }
+
+    /// Variants are written as their index, so they match by position
+    fn variants(&mut self, name: &str, old: &'s [Variant], new: &'s [Variant]) {
+        let index = |variants: &[Variant], wanted: &str| {
+            variants.iter().position(|variant| variant.name == wanted)
+        };
+        // A name at the same index in place of one that is gone is a rename
+        let renamed = |i: usize| match (old.get(i), new.get(i)) {
+            (Some(o), Some(n)) => {
+                o.name != n.name && index(new, &o.name).is_none() && index(old, &n.name).is_none()
+            }
+            _ => false,
+        };
+
+        for (i, variant) in old.iter().enumerate() {
+            let path = format!("{}::{}", name, variant.name);
+            if renamed(i) {
+                continue;
+            }
+            match index(new, &variant.name) {
+                None => self.push(&path, Compatibility::Breaking, "removed".to_string()),
+                Some(j) if j != i => {
+                    let description = format!("moved from index {} to {}", i, j);
+                    self.push(&path, Compatibility::Breaking, description);
+                }
+                Some(_) => {}
+            }
+        }
+        for (j, variant) in new.iter().enumerate() {
+            let path = format!("{}::{}", name, variant.name);
+            if renamed(j) {
+                let description = format!("renamed from {}", old[j].name);
+                self.push(&path, Compatibility::Compatible, description);
+                self.variant(&path, &old[j], variant);
+            } else if let Some(i) = index(old, &variant.name) {
+                self.variant(&path, &old[i], variant);
+            } else if j >= old.len() {
+                self.push(&path, Compatibility::Compatible, "added".to_string());
+            } else {
+                let description = format!("added at index {}, before variants already in use", j);
+                self.push(&path, Compatibility::Breaking, description);
+            }
+        }
+    }
+
+    fn variant(&mut self, path: &str, old: &'s Variant, new: &'s Variant) {
+        if old.kind != new.kind {
+            let description = format!("{} variant -> {} variant", old.kind, new.kind);
+            let compatibility = if self.same_items(&old.items, &new.items) {
+                Compatibility::Compatible
+            } else {
+                Compatibility::Breaking
+            };
+            return self.push(path, compatibility, description);
+        }
+        self.items(path, &old.items, &new.items);
+    }
+
+    /// Fields are written one after the other, so they match by position;
+    /// named ones are told apart by name, to report moves
+    fn items(&mut self, path: &str, old: &'s Items, new: &'s Items) {
+        let position =
+            |items: &Items, wanted: &str| items.items.iter().position(|(name, _)| name == wanted);
+        if !(old.named && new.named) {
+            for i in 0..old.items.len().max(new.items.len()) {
+                match (old.items.get(i), new.items.get(i)) {
+                    (Some((_, o)), Some((name, n))) => {
+                        self.shape(&format!("{}.{}", path, name), o, n)
+                    }
+                    (Some((name, _)), None) => self.push(
+                        &format!("{}.{}", path, name),
+                        Compatibility::Breaking,
+                        "removed".to_string(),
+                    ),
+                    (None, Some((name, _))) => self.push(
+                        &format!("{}.{}", path, name),
+                        Compatibility::Breaking,
+                        "added".to_string(),
+                    ),
+                    (None, None) => {}
+                }
+            }
+            return;
+        }
+
+        let renamed = |i: usize| match (old.items.get(i), new.items.get(i)) {
+            (Some((o, _)), Some((n, _))) => {
+                o != n && position(new, o).is_none() && position(old, n).is_none()
+            }
+            _ => false,
+        };
+        for (i, (name, _)) in old.items.iter().enumerate() {
+            if !renamed(i) && position(new, name).is_none() {
+                self.push(
+                    &format!("{}.{}", path, name),
+                    Compatibility::Breaking,
+                    "removed".to_string(),
+                );
+            }
+        }
+        let common = |from: &'s Items, to: &'s Items| -> Vec<&'s str> {
+            from.items
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .filter(|name| position(to, name).is_some())
+                .collect()
+        };
+        let (before, after) = (common(old, new), common(new, old));
+        if before != after {
+            let description = format!(
+                "fields reordered: {} -> {}",
+                before.join(", "),
+                after.join(", ")
+            );
+            self.push(path, Compatibility::Breaking, description);
+        }
+        for (j, (name, shape)) in new.items.iter().enumerate() {
+            let field = format!("{}.{}", path, name);
+            if renamed(j) {
+                let (old_name, old_shape) = &old.items[j];
+                self.push(
+                    &field,
+                    Compatibility::Compatible,
+                    format!("renamed from {}", old_name),
+                );
+                self.shape(&field, old_shape, shape);
+            } else if let Some(i) = position(old, name) {
+                self.shape(&field, &old.items[i].1, shape);
+            } else {
+                self.push(&field, Compatibility::Breaking, "added".to_string());
+            }
+        }
+    }
+
+    fn shape(&mut self, path: &str, old: &'s Shape, new: &'s Shape) {
+        match (old, new) {
+            (Shape::Primitive(a), Shape::Primitive(b)) if a == b => {}
+            (Shape::Bytes, Shape::Bytes) => {}
+            (Shape::String(a), Shape::String(b)) => self.limit(path, "bytes", *a, *b),
+            (Shape::Option(a), Shape::Option(b)) => self.shape(path, a, b),
+            (Shape::Seq(a, a_limit), Shape::Seq(b, b_limit)) => {
+                self.limit(path, "items", *a_limit, *b_limit);
+                self.shape(&format!("{}[]", path), a, b);
+            }
+            (Shape::Map(a_key, a_value, a_limit), Shape::Map(b_key, b_value, b_limit)) => {
+                self.limit(path, "entries", *a_limit, *b_limit);
+                self.shape(&format!("{}{{}}", path), a_key, b_key);
+                self.shape(&format!("{}[]", path), a_value, b_value);
+            }
+            (Shape::Tuple(a), Shape::Tuple(b)) if a.len() == b.len() => {
+                for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                    self.shape(&format!("{}.{}", path, i), a, b);
+                }
+            }
+            (Shape::Param(a, _), Shape::Param(b, _)) if a == b => {}
+            // The type itself is compared on its own
+            (Shape::Ref(a, a_args), Shape::Ref(b, b_args))
+                if a == b && a_args.is_empty() && b_args.is_empty() => {}
+            _ if old == new => {}
+            _ => {
+                let alike = self.same_wire(old, new);
+                let (compatibility, alike) = if alike {
+                    (Compatibility::Compatible, ", encoded alike")
+                } else {
+                    (Compatibility::Breaking, "")
+                };
+                self.push(path, compatibility, format!("{} -> {}{}", old, new, alike));
+            }
+        }
+    }
+
+    /// A change of the most `unit` a string, sequence or map may hold, which
+    /// decoding checks
+    fn limit(&mut self, path: &str, unit: &str, old: Option<u64>, new: Option<u64>) {
+        let (compatibility, description) = match (old, new) {
+            (Some(old), Some(new)) if new < old => (
+                Compatibility::Breaking,
+                format!("at most {} {} -> {}", old, unit, new),
+            ),
+            (Some(old), Some(new)) if new > old => (
+                Compatibility::Compatible,
+                format!("at most {} {} -> {}", old, unit, new),
+            ),
+            (None, Some(new)) => (
+                Compatibility::Breaking,
+                format!("now at most {} {}", new, unit),
+            ),
+            (Some(old), None) => (
+                Compatibility::Compatible,
+                format!("no longer limited to {} {}", old, unit),
+            ),
+            _ => return,
+        };
+        self.push(path, compatibility, description);
+    }
+
+    /// Whether values of `old` and `new` are written as the same bytes
+    fn same_wire(&mut self, old: &'s Shape, new: &'s Shape) -> bool {
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        flatten(self.old, old, &mut a, 0);
+        flatten(self.new, new, &mut b, 0);
+        a.len() == b.len() && a.into_iter().zip(b).all(|(a, b)| self.same_unit(a, b))
+    }
+
+    fn same_items(&mut self, old: &'s Items, new: &'s Items) -> bool {
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        for (_, shape) in &old.items {
+            flatten(self.old, shape, &mut a, 0);
+        }
+        for (_, shape) in &new.items {
+            flatten(self.new, shape, &mut b, 0);
+        }
+        a.len() == b.len() && a.into_iter().zip(b).all(|(a, b)| self.same_unit(a, b))
+    }
+
+    /// Whether two shapes that [`flatten`] leaves whole are written alike
+    fn same_unit(&mut self, old: &'s Shape, new: &'s Shape) -> bool {
+        let is_byte_seq = |shape: &Shape| match shape {
+            Shape::Bytes => true,
+            Shape::Seq(item, _) => **item == Shape::Primitive("u8".to_string()),
+            _ => false,
+        };
+        if is_byte_seq(old) && is_byte_seq(new) {
+            return true;
+        }
+        match (old, new) {
+            (Shape::Primitive(a), Shape::Primitive(b)) => a == b,
+            (Shape::String(_), Shape::String(_)) => true,
+            (Shape::Option(a), Shape::Option(b)) | (Shape::Seq(a, _), Shape::Seq(b, _)) => {
+                self.same_wire(a, b)
+            }
+            (Shape::Map(a_key, a_value, _), Shape::Map(b_key, b_value, _)) => {
+                self.same_wire(a_key, b_key) && self.same_wire(a_value, b_value)
+            }
+            (Shape::Param(a, _), Shape::Param(b, _)) => a == b,
+            (Shape::Ref(a, a_args), Shape::Ref(b, b_args))
+                if !a_args.is_empty() || !b_args.is_empty() =>
+            {
+                a == b
+                    && a_args.len() == b_args.len()
+                    && a_args.iter().zip(b_args).all(|(a, b)| self.same_wire(a, b))
+            }
+            (Shape::Ref(a, _), Shape::Ref(b, _)) => {
+                let (Some(Body::Enum(a_variants)), Some(Body::Enum(b_variants))) = (
+                    self.old.get(a).map(|ty| &ty.body),
+                    self.new.get(b).map(|ty| &ty.body),
+                ) else {
+                    return false;
+                };
+                // Enums reached again while comparing them are alike if the
+                // rest of them is
+                if !self.comparing.insert((a, b)) {
+                    return true;
+                }
+                let same = a_variants.len() == b_variants.len()
+                    && a_variants
+                        .iter()
+                        .zip(b_variants)
+                        .all(|(a, b)| self.same_items(&a.items, &b.items));
+                self.comparing.remove(&(a.as_str(), b.as_str()));
+                same
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The parts of `shape` written one after the other: tuples and struct types
+/// are written as their fields, and `()` as nothing
+fn flatten<'s>(snapshot: &'s Snapshot, shape: &'s Shape, out: &mut Vec<&'s Shape>, depth: usize) {
+    // Structs cannot contain themselves but by a sequence or an option; a
+    // hand-edited snapshot might
+    if depth > 64 {
+        return out.push(shape);
+    }
+    match shape {
+        Shape::Primitive(kind) if kind == "unit" => {}
+        Shape::Tuple(items) => {
+            for item in items {
+                flatten(snapshot, item, out, depth + 1);
+            }
+        }
+        Shape::Ref(name, args) if args.is_empty() => match snapshot.get(name).map(|ty| &ty.body) {
+            Some(Body::Struct(_, items)) => {
+                for (_, item) in &items.items {
+                    flatten(snapshot, item, out, depth + 1);
+                }
+            }
+            _ => out.push(shape),
+        },
+        _ => out.push(shape),
+    }
+}
+
+// ============================================================================
+// JSON
+// ============================================================================
+
+/// A JSON value, as much of it as the IR uses
+enum Json {
+    /// `true`, `false` or `null`, which the IR does not use
+    Literal,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser { source, pos: 0 }
+    }
+
+    fn document(mut self) -> Result<Json, String> {
+        let value = self.value(0)?;
+        self.whitespace();
+        if self.pos != self.source.len() {
+            return Err(self.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, what: &str) -> String {
+        format!("{} at byte {}", what, self.pos)
+    }
+
+    fn whitespace(&mut self) {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}", byte as char)))
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > 128 {
+            return Err(self.error("nested too deeply"));
+        }
+        self.whitespace();
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    self.whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    members.push((key, self.value(depth + 1)?));
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+                    self.pos += 1;
+                }
+                self.source[start..self.pos]
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| self.error("invalid number"))
+            }
+            _ => {
+                for word in ["true", "false", "null"] {
+                    if self.source[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(Json::Literal);
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.source[self.pos..];
+            let Some(end) = rest.find(['"', '\\']) else {
+                return Err(self.error("unterminated string"));
+            };
+            out.push_str(&rest[..end]);
+            self.pos += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Ok(out);
+            }
+            let escaped = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    self.pos += 1;
+                    let high = self.hex()?;
+                    let code = if (0xd800..0xdc00).contains(&high)
+                        && self.source[self.pos..].starts_with("\\u")
+                    {
+                        self.pos += 2;
+                        let low = self.hex()?;
+                        0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff)
+                    } else {
+                        high
+                    };
+                    out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    continue;
+                }
+                _ => return Err(self.error("invalid escape")),
+            };
+            out.push(escaped);
+            self.pos += 1;
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        let digits = self
+            .source
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("invalid escape"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
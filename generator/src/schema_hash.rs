@@ -0,0 +1,556 @@
+//! Hashes of the wire shape of types, for compatibility checks
+//!
+//! Postcard has no tags or names on the wire, so two programs only understand
+//! each other when their types agree on field order, variant indices and
+//! primitive kinds. The schema hash condenses exactly that: a type is written
+//! out in a canonical form and hashed with 64-bit FNV-1a. Names of types,
+//! fields and variants are left out, as are the boundaries of structs,
+//! tuples and newtypes, which postcard does not write either; renaming a
+//! field or wrapping two fields in a struct keeps the hash, while reordering
+//! fields, inserting a variant or widening a `u16` to a `u32` changes it.
+//!
+//! The canonical form lists the values a type is made of, each followed by
+//! `;`: primitives by kind (`u8;`, `string;`), and `option(...)`, `seq(...)`,
+//! `map(...)(...)` and `enum((...)(...))` around what they contain. A type
+//! containing itself refers back to it as `rec(n)`, `n` counting the named
+//! types entered from the outermost one. Byte strings are written as
+//! `seq(u8;)`, which they are on the wire. The form is stable: hashes only
+//! change when the wire shape does.
+//!
+//! [`const_schema_hash`] computes the same hash at compile time from a type's
+//! [`PostcardTs::CONST_SHAPE`], for the `SCHEMA_HASH` constant
+//! `#[derive(PostcardTs)]` declares on every non-generic type.
+
+use std::collections::HashMap;
+
+use crate::generate::GenerateError;
+use crate::ir::kind;
+use crate::registry;
+use crate::shape::{ConstShape, Definition, PostcardTs, Shape, TypeName, VariantKind};
+use crate::wire_layout::substitute_definition;
+
+/// A type's schema hash, next to the one pinned with
+/// `#[postcard_ts(schema_hash = ...)]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaHashCheck {
+    pub type_name: TypeName,
+    /// The hash of the type's wire shape as it is now
+    pub hash: u64,
+    /// The hash pinned on the type, if any
+    pub pinned: Option<u64>,
+}
+
+impl SchemaHashCheck {
+    /// Whether the wire shape changed since the hash was pinned
+    pub fn is_stale(&self) -> bool {
+        self.pinned.is_some_and(|pinned| pinned != self.hash)
+    }
+}
+
+/// The schema hash of `T`, looking up the types it refers to among those
+/// registered with `#[derive(PostcardTs)]`
+///
+/// ```
+/// use serde_postcard_ts::{schema_hash, PostcardTs};
+///
+/// #[derive(PostcardTs)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// #[derive(PostcardTs)]
+/// struct Sample(u8, f32);
+///
+/// // Only names differ, which postcard does not write
+/// assert_eq!(schema_hash::<Reading>()?, schema_hash::<Sample>()?);
+/// assert_ne!(schema_hash::<Reading>()?, schema_hash::<(f32, u8)>()?);
+/// # Ok::<(), serde_postcard_ts::GenerateError>(())
+/// ```
+pub fn schema_hash<T: PostcardTs>() -> Result<u64, GenerateError> {
+    let types = registry::registered();
+    Hasher::new(&types).hash(&T::shape())
+}
+
+/// The schema hash of each non-generic type among `types`
+///
+/// Generic types have no hash of their own; each use of one is hashed with
+/// its arguments filled in.
+pub fn schema_hashes(
+    types: &[(TypeName, Definition)],
+) -> Result<Vec<(TypeName, u64)>, GenerateError> {
+    let hasher = Hasher::new(types);
+    types
+        .iter()
+        .filter(|(type_name, _)| type_name.params.is_empty())
+        .map(|(type_name, _)| Ok((*type_name, hasher.hash(&Shape::Named(*type_name))?)))
+        .collect()
+}
+
+/// Compare the schema hash of every registered non-generic type with the one
+/// pinned on it; see `postcard-ts check`
+pub fn check_schema_hashes() -> Result<Vec<SchemaHashCheck>, GenerateError> {
+    check_types_schema_hashes(&registry::registered(), &registry::pinned_schema_hashes())
+}
+
+/// Compare the schema hash of each non-generic type among `types` with the
+/// one pinned on it in `pinned`
+pub fn check_types_schema_hashes(
+    types: &[(TypeName, Definition)],
+    pinned: &[(TypeName, u64)],
+) -> Result<Vec<SchemaHashCheck>, GenerateError> {
+    let pinned: HashMap<TypeName, u64> = pinned.iter().copied().collect();
+    Ok(schema_hashes(types)?
+        .into_iter()
+        .map(|(type_name, hash)| SchemaHashCheck {
+            type_name,
+            hash,
+            pinned: pinned.get(&type_name).copied(),
+        })
+        .collect())
+}
+
+/// The schema hash of a type's [`PostcardTs::CONST_SHAPE`], the same as
+/// [`schema_hash`] gives, at compile time
+///
+/// ```
+/// use serde_postcard_ts::{const_schema_hash, schema_hash, PostcardTs};
+///
+/// #[derive(PostcardTs)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// const HASH: u64 = const_schema_hash(&<(u8, f32) as PostcardTs>::CONST_SHAPE);
+/// assert_eq!(Reading::SCHEMA_HASH, HASH);
+/// assert_eq!(schema_hash::<Reading>()?, HASH);
+/// # Ok::<(), serde_postcard_ts::GenerateError>(())
+/// ```
+pub const fn const_schema_hash(shape: &ConstShape) -> u64 {
+    const_writer::shape(FNV_OFFSET_BASIS, shape, None, None)
+}
+
+/// `hash`, failing to compile where it differs from the one `pinned` on a type
+#[doc(hidden)]
+pub const fn check_pinned_schema_hash(hash: u64, pinned: u64) -> u64 {
+    if hash == pinned {
+        return hash;
+    }
+    let mut message = *b"the wire shape hashes to 0x0000000000000000, not to the pinned \
+        schema_hash 0x0000000000000000; pin the new hash once the change is intended";
+    const_writer::hex(&mut message, 27, hash);
+    const_writer::hex(&mut message, 77, pinned);
+    match core::str::from_utf8(&message) {
+        Ok(message) => panic!("{}", message),
+        Err(_) => unreachable!(),
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+/// 64-bit FNV-1a
+pub(crate) const fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(FNV_OFFSET_BASIS, bytes)
+}
+
+/// 64-bit FNV-1a of what `hash` is the hash of, followed by `bytes`
+const fn fnv1a_extend(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+struct Hasher<'a> {
+    definitions: HashMap<TypeName, &'a Definition>,
+}
+
+impl<'a> Hasher<'a> {
+    fn new(types: &'a [(TypeName, Definition)]) -> Self {
+        Hasher {
+            definitions: types
+                .iter()
+                .map(|(type_name, definition)| (*type_name, definition))
+                .collect(),
+        }
+    }
+
+    fn hash(&self, shape: &Shape) -> Result<u64, GenerateError> {
+        let mut writer = Writer {
+            hasher: self,
+            stack: Vec::new(),
+            out: String::new(),
+        };
+        writer.shape(shape)?;
+        Ok(fnv1a(writer.out.as_bytes()))
+    }
+}
+
+/// Writes the canonical form of a shape
+struct Writer<'h, 'a> {
+    hasher: &'h Hasher<'a>,
+    /// Named types being written, with their arguments filled in
+    stack: Vec<Shape>,
+    out: String,
+}
+
+impl Writer<'_, '_> {
+    fn shape(&mut self, shape: &Shape) -> Result<(), GenerateError> {
+        match shape {
+            Shape::Unit => {}
+            Shape::Tuple(items) => self.shapes(items)?,
+            Shape::Option(inner) => self.wrapped("option", inner)?,
+            // A capacity is not on the wire, as `maxBytes` is not
+            Shape::Seq(inner) | Shape::BoundedSeq(inner, _) => self.wrapped("seq", inner)?,
+            // The same bytes as a `Vec<u8>`
+            Shape::Bytes => self.out.push_str("seq(u8;);"),
+            Shape::Map(key, value) | Shape::BoundedMap(key, value, _) => {
+                self.out.push_str("map(");
+                self.shape(key)?;
+                self.out.push_str(")(");
+                self.shape(value)?;
+                self.out.push_str(");");
+            }
+            Shape::Named(type_name) | Shape::Generic(type_name, _) => {
+                if let Some(depth) = self.stack.iter().position(|open| open == shape) {
+                    self.out.push_str(&format!("rec({});", depth));
+                    return Ok(());
+                }
+                let definition = self.definition(type_name, shape)?;
+                self.stack.push(shape.clone());
+                self.definition_shape(&definition)?;
+                self.stack.pop();
+            }
+            Shape::Param(name) => self.out.push_str(&format!("param({});", name)),
+            primitive => {
+                self.out.push_str(kind(primitive));
+                self.out.push(';');
+            }
+        }
+        Ok(())
+    }
+
+    fn shapes<'s>(
+        &mut self,
+        shapes: impl IntoIterator<Item = &'s Shape>,
+    ) -> Result<(), GenerateError> {
+        shapes.into_iter().try_for_each(|shape| self.shape(shape))
+    }
+
+    fn wrapped(&mut self, name: &str, inner: &Shape) -> Result<(), GenerateError> {
+        self.out.push_str(name);
+        self.out.push('(');
+        self.shape(inner)?;
+        self.out.push_str(");");
+        Ok(())
+    }
+
+    fn definition_shape(&mut self, definition: &Definition) -> Result<(), GenerateError> {
+        match definition {
+            Definition::Struct(fields) => self.shapes(fields.iter().map(|field| &field.shape)),
+            Definition::TupleStruct(items) => self.shapes(items),
+            Definition::NewtypeStruct(inner) => self.shape(inner),
+            Definition::UnitStruct => Ok(()),
+            Definition::Enum(variants) => {
+                self.out.push_str("enum(");
+                for variant in variants {
+                    self.out.push('(');
+                    match &variant.kind {
+                        VariantKind::Unit => {}
+                        VariantKind::Newtype(inner) => self.shape(inner)?,
+                        VariantKind::Tuple(items) => self.shapes(items)?,
+                        VariantKind::Struct(fields) => {
+                            self.shapes(fields.iter().map(|field| &field.shape))?
+                        }
+                    }
+                    self.out.push(')');
+                }
+                self.out.push_str(");");
+                Ok(())
+            }
+        }
+    }
+
+    /// The definition of a named type, with a generic type's arguments filled in
+    fn definition(&self, type_name: &TypeName, shape: &Shape) -> Result<Definition, GenerateError> {
+        let definition =
+            self.hasher
+                .definitions
+                .get(type_name)
+                .ok_or_else(|| GenerateError::Missing {
+                    name: type_name.name,
+                    referenced_by: match self.stack.last() {
+                        Some(Shape::Named(outer) | Shape::Generic(outer, _)) => outer.name,
+                        _ => type_name.name,
+                    },
+                })?;
+        Ok(match shape {
+            Shape::Generic(_, args) => {
+                let bindings: HashMap<&str, &Shape> =
+                    type_name.params.iter().copied().zip(args).collect();
+                substitute_definition(definition, &bindings)
+            }
+            _ => (*definition).clone(),
+        })
+    }
+}
+
+/// Writes the canonical form of a [`ConstShape`] into the hash, as [`Writer`]
+/// writes that of a [`Shape`]
+mod const_writer {
+    use super::fnv1a_extend;
+    use crate::shape::{ConstShape, TypeName};
+
+    /// The arguments of the named types being written, innermost first
+    pub(super) struct Params<'a> {
+        args: &'a [ConstShape],
+        /// Where the arguments are written, and their own parameters resolve
+        outer: Option<&'a Params<'a>>,
+    }
+
+    /// The named types being written, innermost first
+    pub(super) struct Open<'a> {
+        shape: &'a ConstShape,
+        params: Option<&'a Params<'a>>,
+        outer: Option<&'a Open<'a>>,
+        /// The number of named types entered before this one
+        depth: usize,
+    }
+
+    pub(super) const fn shape(
+        hash: u64,
+        shape: &ConstShape,
+        params: Option<&Params>,
+        open: Option<&Open>,
+    ) -> u64 {
+        match shape {
+            ConstShape::Unit => hash,
+            ConstShape::Tuple(items) => shapes(hash, items, params, open),
+            ConstShape::Option(inner) => wrapped(hash, b"option", inner, params, open),
+            ConstShape::Seq(inner) | ConstShape::BoundedSeq(inner, _) => {
+                wrapped(hash, b"seq", inner, params, open)
+            }
+            ConstShape::Bytes => fnv1a_extend(hash, b"seq(u8;);"),
+            ConstShape::BoundedString(_) => fnv1a_extend(hash, b"string;"),
+            ConstShape::Map(key, value) | ConstShape::BoundedMap(key, value, _) => {
+                let hash = self::shape(fnv1a_extend(hash, b"map("), key, params, open);
+                let hash = self::shape(fnv1a_extend(hash, b")("), value, params, open);
+                fnv1a_extend(hash, b");")
+            }
+            ConstShape::Named(_, args, definition) => {
+                let mut outer = open;
+                while let Some(entered) = outer {
+                    if same(shape, params, entered.shape, entered.params) {
+                        let hash = number(fnv1a_extend(hash, b"rec("), entered.depth);
+                        return fnv1a_extend(hash, b");");
+                    }
+                    outer = entered.outer;
+                }
+                let depth = match open {
+                    Some(entered) => entered.depth + 1,
+                    None => 0,
+                };
+                let entered = Open {
+                    shape,
+                    params,
+                    outer: open,
+                    depth,
+                };
+                let args = Params {
+                    args,
+                    outer: params,
+                };
+                self::shape(hash, definition.get(), Some(&args), Some(&entered))
+            }
+            ConstShape::Enum(variants) => {
+                let mut hash = fnv1a_extend(hash, b"enum(");
+                let mut i = 0;
+                while i < variants.len() {
+                    hash = shapes(fnv1a_extend(hash, b"("), variants[i], params, open);
+                    hash = fnv1a_extend(hash, b")");
+                    i += 1;
+                }
+                fnv1a_extend(hash, b");")
+            }
+            ConstShape::Param(index) => match params {
+                Some(params) => self::shape(hash, &params.args[*index], params.outer, open),
+                None => panic!("a type parameter outside of the type declaring it"),
+            },
+            primitive => match kind(primitive) {
+                Some(kind) => fnv1a_extend(fnv1a_extend(hash, kind.as_bytes()), b";"),
+                None => hash,
+            },
+        }
+    }
+
+    const fn shapes(
+        mut hash: u64,
+        shapes: &[ConstShape],
+        params: Option<&Params>,
+        open: Option<&Open>,
+    ) -> u64 {
+        let mut i = 0;
+        while i < shapes.len() {
+            hash = shape(hash, &shapes[i], params, open);
+            i += 1;
+        }
+        hash
+    }
+
+    const fn wrapped(
+        hash: u64,
+        name: &[u8],
+        inner: &ConstShape,
+        params: Option<&Params>,
+        open: Option<&Open>,
+    ) -> u64 {
+        let hash = shape(
+            fnv1a_extend(fnv1a_extend(hash, name), b"("),
+            inner,
+            params,
+            open,
+        );
+        fnv1a_extend(hash, b");")
+    }
+
+    const fn number(hash: u64, n: usize) -> u64 {
+        let hash = if n >= 10 { number(hash, n / 10) } else { hash };
+        fnv1a_extend(hash, &[b'0' + (n % 10) as u8])
+    }
+
+    /// The IR kind of the shapes that are a single value, as `ir::kind` gives it
+    const fn kind(shape: &ConstShape) -> Option<&'static str> {
+        Some(match shape {
+            ConstShape::Bool => "bool",
+            ConstShape::I8 => "i8",
+            ConstShape::I16 => "i16",
+            ConstShape::I32 => "i32",
+            ConstShape::I64 => "i64",
+            ConstShape::I128 => "i128",
+            ConstShape::U8 => "u8",
+            ConstShape::U16 => "u16",
+            ConstShape::U32 => "u32",
+            ConstShape::U64 => "u64",
+            ConstShape::U128 => "u128",
+            ConstShape::F32 => "f32",
+            ConstShape::F64 => "f64",
+            ConstShape::Char => "char",
+            ConstShape::String => "string",
+            ConstShape::Bytes => "bytes",
+            ConstShape::Unit => "unit",
+            _ => return None,
+        })
+    }
+
+    /// Whether `a` and `b` are the same shape once their parameters are
+    /// resolved, as the `Shape`s [`Writer`](super::Writer) compares are
+    const fn same(
+        a: &ConstShape,
+        a_params: Option<&Params>,
+        b: &ConstShape,
+        b_params: Option<&Params>,
+    ) -> bool {
+        if let (ConstShape::Param(index), Some(params)) = (a, a_params) {
+            return same(&params.args[*index], params.outer, b, b_params);
+        }
+        if let (ConstShape::Param(index), Some(params)) = (b, b_params) {
+            return same(a, a_params, &params.args[*index], params.outer);
+        }
+        match (a, b) {
+            (ConstShape::BoundedString(a), ConstShape::BoundedString(b)) => *a == *b,
+            (ConstShape::Option(a), ConstShape::Option(b))
+            | (ConstShape::Seq(a), ConstShape::Seq(b)) => same(a, a_params, b, b_params),
+            (ConstShape::BoundedSeq(a, a_max), ConstShape::BoundedSeq(b, b_max)) => {
+                *a_max == *b_max && same(a, a_params, b, b_params)
+            }
+            (ConstShape::Tuple(a), ConstShape::Tuple(b)) => all_same(a, a_params, b, b_params),
+            (ConstShape::Map(a_key, a_value), ConstShape::Map(b_key, b_value)) => {
+                same(a_key, a_params, b_key, b_params) && same(a_value, a_params, b_value, b_params)
+            }
+            (
+                ConstShape::BoundedMap(a_key, a_value, a_max),
+                ConstShape::BoundedMap(b_key, b_value, b_max),
+            ) => {
+                *a_max == *b_max
+                    && same(a_key, a_params, b_key, b_params)
+                    && same(a_value, a_params, b_value, b_params)
+            }
+            (ConstShape::Named(a_name, a_args, _), ConstShape::Named(b_name, b_args, _)) => {
+                same_name(a_name, b_name) && all_same(a_args, a_params, b_args, b_params)
+            }
+            (ConstShape::Param(a), ConstShape::Param(b)) => *a == *b,
+            _ => match (kind(a), kind(b)) {
+                (Some(a), Some(b)) => same_str(a, b),
+                _ => false,
+            },
+        }
+    }
+
+    const fn all_same(
+        a: &[ConstShape],
+        a_params: Option<&Params>,
+        b: &[ConstShape],
+        b_params: Option<&Params>,
+    ) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if !same(&a[i], a_params, &b[i], b_params) {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    const fn same_name(a: &TypeName, b: &TypeName) -> bool {
+        if !same_str(a.name, b.name)
+            || !same_str(a.module, b.module)
+            || a.params.len() != b.params.len()
+        {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.params.len() {
+            if !same_str(a.params[i], b.params[i]) {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    const fn same_str(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Write `value` as 16 hex digits at `at` in `message`
+    pub(super) const fn hex(message: &mut [u8], at: usize, value: u64) {
+        let mut i = 0;
+        while i < 16 {
+            let digit = (value >> (60 - 4 * i)) as u8 & 0xf;
+            message[at + i] = if digit < 10 {
+                b'0' + digit
+            } else {
+                b'a' + digit - 10
+            };
+            i += 1;
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! A registry of schema snapshots, served over HTTP
+//!
+//! Dashboards are built against one version of the protocol, but the devices
+//! they connect to run whatever firmware they shipped with. A registry keeps
+//! the snapshot of every release ([`Generator::generate_ir`](crate::Generator::generate_ir),
+//! `postcard-ts schema snapshot`) and finds the one a device was built from by
+//! the schema hash it reports in its handshake, so the dashboard can decode
+//! its messages with the runtime's `DynamicDecoder` instead of misreading
+//! them with its own bindings. `postcard-ts registry serve` runs it on a
+//! local network with no other service:
+//!
+//! ```text
+//! POST /ir                     upload a snapshot; answers its id and hashes
+//! GET  /ir                     every snapshot's id and the hashes of its types
+//! GET  /ir/Reading/<hash>      the snapshot in which Reading has the hash
+//! ```
+//!
+//! Hashes are written as 16 lowercase hex digits, as JavaScript numbers
+//! cannot hold them. Each snapshot is identified by the FNV-1a hash of its
+//! text, and kept as `<id>.json` in the registry's directory. When several
+//! snapshots give a type the same hash, the one added last is served, as its
+//! names are the most recent.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use crate::ir;
+use crate::schema_diff::{snapshot_schema_hashes, SnapshotError};
+use crate::schema_hash::fnv1a;
+
+/// The largest snapshot accepted by [`SchemaRegistry::serve`]
+const MAX_UPLOAD: usize = 16 << 20;
+
+/// A snapshot in a registry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryEntry {
+    /// The FNV-1a hash of the snapshot, as 16 hex digits
+    pub id: String,
+    /// The snapshot, the IR as uploaded
+    pub ir: String,
+    /// The schema hash of each non-generic type of the snapshot
+    pub hashes: Vec<(String, u64)>,
+}
+
+/// What [`SchemaRegistry::respond`] answers a request with; the body is JSON
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl RegistryResponse {
+    fn json(status: u16, body: String) -> Self {
+        RegistryResponse { status, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        RegistryResponse::json(status, format!("{{\"error\": {}}}\n", ir::string(message)))
+    }
+}
+
+/// Schema snapshots by the schema hashes of their types
+///
+/// ```
+/// use serde_postcard_ts::{schema_hash, Generator, PostcardTs, SchemaRegistry};
+///
+/// #[derive(PostcardTs)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// let mut registry = SchemaRegistry::new();
+/// registry.add(&Generator::new().generate_ir()?)?;
+/// let hash = format!("{:016x}", schema_hash::<Reading>()?);
+/// let response = registry.respond("GET", &format!("/ir/Reading/{}", hash), &[]);
+/// assert_eq!(response.status, 200);
+/// assert_eq!(registry.respond("GET", "/ir/Reading/0000000000000000", &[]).status, 404);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRegistry {
+    entries: Vec<RegistryEntry>,
+    /// Where snapshots are kept, if anywhere
+    dir: Option<PathBuf>,
+}
+
+impl SchemaRegistry {
+    /// An empty registry, kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry keeping its snapshots in `dir`, created if missing, with
+    /// the `.json` snapshots already there, oldest first
+    ///
+    /// A file there that is not a snapshot fails with
+    /// [`io::ErrorKind::InvalidData`].
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                files.push((fs::metadata(&path)?.modified()?, path));
+            }
+        }
+        files.sort();
+        let mut registry = SchemaRegistry::new();
+        for (_, path) in files {
+            let ir = fs::read_to_string(&path)?;
+            registry.insert(ir).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), error),
+                )
+            })?;
+        }
+        registry.dir = Some(dir);
+        Ok(registry)
+    }
+
+    /// Add the snapshot `ir`, unless the registry already has it, and write
+    /// it to the registry's directory
+    ///
+    /// A snapshot that is not IR fails with [`io::ErrorKind::InvalidData`].
+    pub fn add(&mut self, ir: &str) -> io::Result<&RegistryEntry> {
+        let index = self
+            .insert(ir.to_string())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        let entry = &self.entries[index];
+        if let Some(dir) = &self.dir {
+            let path = snapshot_path(dir, &entry.id);
+            if !path.exists() {
+                fs::write(path, &entry.ir)?;
+            }
+        }
+        Ok(entry)
+    }
+
+    /// The index of the entry of `ir`, added unless it is there already
+    fn insert(&mut self, ir: String) -> Result<usize, SnapshotError> {
+        let id = format!("{:016x}", fnv1a(ir.as_bytes()));
+        if let Some(index) = self.entries.iter().position(|entry| entry.id == id) {
+            return Ok(index);
+        }
+        let hashes = snapshot_schema_hashes(&ir)?;
+        self.entries.push(RegistryEntry { id, ir, hashes });
+        Ok(self.entries.len() - 1)
+    }
+
+    /// The snapshots, in the order they were added
+    pub fn entries(&self) -> &[RegistryEntry] {
+        &self.entries
+    }
+
+    /// The snapshot added last in which `type_name` has the schema hash
+    /// `hash`
+    pub fn find(&self, type_name: &str, hash: u64) -> Option<&RegistryEntry> {
+        self.entries.iter().rev().find(|entry| {
+            entry
+                .hashes
+                .iter()
+                .any(|(name, other)| name == type_name && *other == hash)
+        })
+    }
+
+    /// Answer the HTTP request `method` `path` (a query is ignored) with
+    /// `body`
+    pub fn respond(&mut self, method: &str, path: &str, body: &[u8]) -> RegistryResponse {
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            // Preflight of an upload from a browser
+            ("OPTIONS", _) => RegistryResponse::json(204, String::new()),
+            ("GET", ["ir"]) => {
+                let entries: Vec<String> = self.entries.iter().map(entry_json).collect();
+                RegistryResponse::json(
+                    200,
+                    format!("{{\"snapshots\": [{}]}}\n", entries.join(", ")),
+                )
+            }
+            ("POST", ["ir"]) => {
+                let Ok(ir) = std::str::from_utf8(body) else {
+                    return RegistryResponse::error(400, "the snapshot is not UTF-8");
+                };
+                match self.add(ir) {
+                    Ok(entry) => RegistryResponse::json(201, format!("{}\n", entry_json(entry))),
+                    Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                        RegistryResponse::error(400, &error.to_string())
+                    }
+                    Err(error) => RegistryResponse::error(500, &error.to_string()),
+                }
+            }
+            ("GET", ["ir", type_name, hash]) => {
+                let Ok(hash) = u64::from_str_radix(hash, 16) else {
+                    return RegistryResponse::error(400, &format!("{} is not a hex hash", hash));
+                };
+                match self.find(type_name, hash) {
+                    Some(entry) => RegistryResponse::json(200, entry.ir.clone()),
+                    None => RegistryResponse::error(
+                        404,
+                        &format!(
+                            "no snapshot has {} with schema hash {:016x}",
+                            type_name, hash
+                        ),
+                    ),
+                }
+            }
+            (_, ["ir"] | ["ir", _, _]) => RegistryResponse::error(405, "method not allowed"),
+            _ => RegistryResponse::error(404, &format!("no such resource {}", path)),
+        }
+    }
+
+    /// Answer the HTTP requests on `listener`, one connection at a time,
+    /// until accepting one fails
+    ///
+    /// A connection that fails is dropped. Responses allow any origin, so a
+    /// dashboard served from elsewhere can fetch snapshots.
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            let _ = self.handle(&mut stream);
+        }
+        Ok(())
+    }
+
+    /// Read one HTTP request from `stream` and answer it
+    pub fn handle(&mut self, stream: &mut (impl Read + Write)) -> io::Result<()> {
+        let response = match read_request(&mut BufReader::new(&mut *stream)) {
+            Ok((method, path, body)) => self.respond(&method, &path, &body),
+            Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                RegistryResponse::error(400, &error.to_string())
+            }
+            Err(error) => return Err(error),
+        };
+        write_response(stream, &response)
+    }
+}
+
+/// An entry's id and the hashes of its types, as JSON
+fn entry_json(entry: &RegistryEntry) -> String {
+    let types: Vec<String> = entry
+        .hashes
+        .iter()
+        .map(|(name, hash)| format!("{}: \"{:016x}\"", ir::string(name), hash))
+        .collect();
+    format!(
+        "{{\"id\": \"{}\", \"types\": {{{}}}}}",
+        entry.id,
+        types.join(", ")
+    )
+}
+
+/// The method, path and body of an HTTP/1.1 request
+fn read_request(reader: &mut impl BufRead) -> io::Result<(String, String, Vec<u8>)> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(invalid("the headers end early"));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("malformed Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_UPLOAD {
+        return Err(invalid("the body is too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok((method, path, body))
+}
+
+fn write_response(stream: &mut impl Write, response: &RegistryResponse) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Connection: close\r\n\
+         \r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Where a registry in `dir` keeps the snapshot `id`
+fn snapshot_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
@@ -0,0 +1,505 @@
+//! The wire shape of Rust types, as the generator sees them
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// How a type appears on the wire, mirroring the TypeScript schema builders
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Shape {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String,
+    /// A string of at most this many UTF-8 bytes, e.g. `heapless::String<N>`
+    /// or a field with `#[postcard_ts(max_len = N)]`; written like `String`
+    BoundedString(usize),
+    /// A byte string, as `serde_bytes` writes it: a varint length, then the
+    /// bytes, which is also how postcard writes a `Vec<u8>`
+    Bytes,
+    Unit,
+    Option(Box<Shape>),
+    Seq(Box<Shape>),
+    /// A sequence of at most this many items, e.g. `heapless::Vec<T, N>`;
+    /// written like `Seq`
+    BoundedSeq(Box<Shape>, usize),
+    Tuple(Vec<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    /// A map of at most this many entries, e.g. `heapless::IndexMap`; written
+    /// like `Map`
+    BoundedMap(Box<Shape>, Box<Shape>, usize),
+    /// A type registered with `#[derive(PostcardTs)]`, emitted once and referenced by name
+    Named(TypeName),
+    /// A generic registered type applied to type arguments, e.g. `Envelope<u8>`
+    Generic(TypeName, Vec<Shape>),
+    /// A type parameter of the generic type being defined, e.g. `T` in `Envelope<T>`
+    Param(&'static str),
+}
+
+/// Identity of a registered type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypeName {
+    /// Rust type name, also used for the TypeScript names
+    pub name: &'static str,
+    /// `module_path!()` of the definition
+    pub module: &'static str,
+    /// Type parameters of a generic type (`["T"]` for `Envelope<T>`), empty otherwise
+    pub params: &'static [&'static str],
+}
+
+/// The definition of a registered type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Definition {
+    Struct(Vec<Field>),
+    TupleStruct(Vec<Shape>),
+    NewtypeStruct(Shape),
+    UnitStruct,
+    Enum(Vec<Variant>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The serde name, after `#[serde(rename)]` and `rename_all`
+    pub name: &'static str,
+    pub shape: Shape,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variant {
+    /// The serde name, after `#[serde(rename)]` and `rename_all`
+    pub name: &'static str,
+    pub kind: VariantKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VariantKind {
+    Unit,
+    Newtype(Shape),
+    Tuple(Vec<Shape>),
+    Struct(Vec<Field>),
+}
+
+/// A type's wire shape as a constant, which its schema hash is computed from
+/// at compile time (see [`const_schema_hash`](crate::const_schema_hash))
+///
+/// Mirrors [`Shape`], with the definition of each named type referred to
+/// through a [`ConstDefinition`]. Type parameters are numbered, and resolve to
+/// the arguments of the named type whose definition they appear in.
+#[derive(Clone, Copy, Debug)]
+pub enum ConstShape {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String,
+    BoundedString(usize),
+    Bytes,
+    Unit,
+    Option(&'static ConstShape),
+    Seq(&'static ConstShape),
+    BoundedSeq(&'static ConstShape, usize),
+    Tuple(&'static [ConstShape]),
+    Map(&'static ConstShape, &'static ConstShape),
+    BoundedMap(&'static ConstShape, &'static ConstShape, usize),
+    /// A registered or built-in type, applied to its type arguments
+    Named(TypeName, &'static [ConstShape], ConstDefinition),
+    /// The type parameter at this index of the type being defined
+    Param(usize),
+    /// An enum, as the fields of each variant; only ever a definition
+    Enum(&'static [&'static [ConstShape]]),
+}
+
+/// The definition of a named type in a [`ConstShape`]: its fields in order
+/// (a struct's, a tuple struct's or a newtype's alike, as postcard writes
+/// them), or its variants as [`ConstShape::Enum`]
+///
+/// Held as a pointer, which the compiler does not follow when checking a
+/// constant, so that the definition of a recursive type can be a `static`
+/// containing the type's own constant.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstDefinition(*const ConstShape);
+
+// Only ever points to a `&'static ConstShape`, which is immutable
+unsafe impl Send for ConstDefinition {}
+unsafe impl Sync for ConstDefinition {}
+
+impl ConstDefinition {
+    pub const fn new(definition: &'static ConstShape) -> Self {
+        ConstDefinition(definition)
+    }
+
+    pub const fn get(self) -> &'static ConstShape {
+        // SAFETY: made from a `&'static ConstShape` in `new`
+        unsafe { &*self.0 }
+    }
+}
+
+/// Types with a known wire shape
+///
+/// Implemented for the primitives and std containers, and by
+/// `#[derive(PostcardTs)]` for your own types.
+pub trait PostcardTs {
+    /// The wire shape as a constant, for computing schema hashes at compile
+    /// time
+    const CONST_SHAPE: ConstShape;
+
+    fn shape() -> Shape;
+}
+
+macro_rules! primitive {
+    ($($ty:ty => $shape:ident),* $(,)?) => {
+        $(
+            impl PostcardTs for $ty {
+                const CONST_SHAPE: ConstShape = ConstShape::$shape;
+
+                fn shape() -> Shape {
+                    Shape::$shape
+                }
+            }
+        )*
+    };
+}
+
+primitive! {
+    bool => Bool,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    isize => I64,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    usize => U64,
+    f32 => F32,
+    f64 => F64,
+    char => Char,
+    str => String,
+    String => String,
+    () => Unit,
+}
+
+// Wrappers that serialize as the value they hold
+impl<T: PostcardTs + ?Sized> PostcardTs for &T {
+    const CONST_SHAPE: ConstShape = T::CONST_SHAPE;
+
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+impl<T: PostcardTs + ?Sized> PostcardTs for Box<T> {
+    const CONST_SHAPE: ConstShape = T::CONST_SHAPE;
+
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+// With serde's `rc` feature, which writes the shared value itself
+impl<T: PostcardTs + ?Sized> PostcardTs for Rc<T> {
+    const CONST_SHAPE: ConstShape = T::CONST_SHAPE;
+
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+impl<T: PostcardTs + ?Sized> PostcardTs for Arc<T> {
+    const CONST_SHAPE: ConstShape = T::CONST_SHAPE;
+
+    fn shape() -> Shape {
+        T::shape()
+    }
+}
+
+impl<T: PostcardTs> PostcardTs for Option<T> {
+    const CONST_SHAPE: ConstShape = ConstShape::Option(&T::CONST_SHAPE);
+
+    fn shape() -> Shape {
+        Shape::Option(Box::new(T::shape()))
+    }
+}
+
+macro_rules! sequence {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: PostcardTs> PostcardTs for $ty<T> {
+                const CONST_SHAPE: ConstShape = ConstShape::Seq(&T::CONST_SHAPE);
+
+                fn shape() -> Shape {
+                    Shape::Seq(Box::new(T::shape()))
+                }
+            }
+        )*
+    };
+}
+
+sequence!(Vec, VecDeque, BTreeSet);
+
+impl<T: PostcardTs> PostcardTs for [T] {
+    const CONST_SHAPE: ConstShape = ConstShape::Seq(&T::CONST_SHAPE);
+
+    fn shape() -> Shape {
+        Shape::Seq(Box::new(T::shape()))
+    }
+}
+
+impl<T: PostcardTs, S> PostcardTs for HashSet<T, S> {
+    const CONST_SHAPE: ConstShape = ConstShape::Seq(&T::CONST_SHAPE);
+
+    fn shape() -> Shape {
+        Shape::Seq(Box::new(T::shape()))
+    }
+}
+
+// Arrays have a fixed length, so postcard writes them as tuples
+impl<T: PostcardTs, const N: usize> PostcardTs for [T; N] {
+    const CONST_SHAPE: ConstShape = ConstShape::Tuple(&[T::CONST_SHAPE; N]);
+
+    fn shape() -> Shape {
+        Shape::Tuple(vec![T::shape(); N])
+    }
+}
+
+impl<K: PostcardTs, V: PostcardTs> PostcardTs for BTreeMap<K, V> {
+    const CONST_SHAPE: ConstShape = ConstShape::Map(&K::CONST_SHAPE, &V::CONST_SHAPE);
+
+    fn shape() -> Shape {
+        Shape::Map(Box::new(K::shape()), Box::new(V::shape()))
+    }
+}
+
+impl<K: PostcardTs, V: PostcardTs, S> PostcardTs for HashMap<K, V, S> {
+    const CONST_SHAPE: ConstShape = ConstShape::Map(&K::CONST_SHAPE, &V::CONST_SHAPE);
+
+    fn shape() -> Shape {
+        Shape::Map(Box::new(K::shape()), Box::new(V::shape()))
+    }
+}
+
+macro_rules! tuple {
+    ($($name:ident)+) => {
+        impl<$($name: PostcardTs),+> PostcardTs for ($($name,)+) {
+            const CONST_SHAPE: ConstShape = ConstShape::Tuple(&[$($name::CONST_SHAPE),+]);
+
+            fn shape() -> Shape {
+                Shape::Tuple(vec![$($name::shape()),+])
+            }
+        }
+    };
+}
+
+tuple!(A);
+tuple!(A B);
+tuple!(A B C);
+tuple!(A B C D);
+tuple!(A B C D E);
+tuple!(A B C D E F);
+tuple!(A B C D E F G);
+tuple!(A B C D E F G H);
+
+/// Text types a length limit applies to, for `#[postcard_ts(max_len = N)]`
+///
+/// Only implemented for strings (and options of them), so the attribute on
+/// any other field fails to compile.
+pub trait MaxLen: PostcardTs {
+    /// The shape of the type holding at most `max` bytes of UTF-8
+    fn max_len(max: usize) -> Shape;
+}
+
+impl MaxLen for String {
+    fn max_len(max: usize) -> Shape {
+        Shape::BoundedString(max)
+    }
+}
+
+impl MaxLen for str {
+    fn max_len(max: usize) -> Shape {
+        Shape::BoundedString(max)
+    }
+}
+
+impl<T: MaxLen + ?Sized> MaxLen for &T {
+    fn max_len(max: usize) -> Shape {
+        T::max_len(max)
+    }
+}
+
+impl<T: MaxLen + ?Sized> MaxLen for Box<T> {
+    fn max_len(max: usize) -> Shape {
+        T::max_len(max)
+    }
+}
+
+impl<T: MaxLen> MaxLen for Option<T> {
+    fn max_len(max: usize) -> Shape {
+        Shape::Option(Box::new(T::max_len(max)))
+    }
+}
+
+/// Byte containers `#[serde(with = "serde_bytes")]` applies to
+///
+/// The field is written as one byte string, which the generated schema
+/// decodes to a `Uint8Array` rather than a `number[]`; on the wire it is the
+/// same as a `Vec<u8>`. Arrays (`[u8; N]`, `ByteArray<N>`) are left out: as
+/// byte strings they gain a length prefix that must be exactly `N`, which
+/// `bytes()` does not check.
+pub trait SerdeBytes: PostcardTs {
+    /// The shape of the type written with `serde_bytes`
+    fn bytes_shape() -> Shape;
+}
+
+impl SerdeBytes for Vec<u8> {
+    fn bytes_shape() -> Shape {
+        Shape::Bytes
+    }
+}
+
+impl SerdeBytes for [u8] {
+    fn bytes_shape() -> Shape {
+        Shape::Bytes
+    }
+}
+
+impl<T: SerdeBytes + ?Sized> SerdeBytes for &T {
+    fn bytes_shape() -> Shape {
+        T::bytes_shape()
+    }
+}
+
+impl<T: SerdeBytes + ?Sized> SerdeBytes for Box<T> {
+    fn bytes_shape() -> Shape {
+        T::bytes_shape()
+    }
+}
+
+impl<T: SerdeBytes> SerdeBytes for Option<T> {
+    fn bytes_shape() -> Shape {
+        Shape::Option(Box::new(T::bytes_shape()))
+    }
+}
+
+/// `serde_bytes`' own types, which are always written as byte strings
+#[cfg(feature = "serde_bytes")]
+mod serde_bytes_impls {
+    use super::{ConstShape, PostcardTs, SerdeBytes, Shape};
+
+    macro_rules! byte_string {
+        ($($ty:ty),*) => {
+            $(
+                impl PostcardTs for $ty {
+                    const CONST_SHAPE: ConstShape = ConstShape::Bytes;
+
+                    fn shape() -> Shape {
+                        Shape::Bytes
+                    }
+                }
+
+                impl SerdeBytes for $ty {
+                    fn bytes_shape() -> Shape {
+                        Shape::Bytes
+                    }
+                }
+            )*
+        };
+    }
+
+    byte_string!(serde_bytes::ByteBuf, serde_bytes::Bytes);
+}
+
+/// `heapless::String<N>` holds at most `N` bytes and the other containers at
+/// most `N` items or entries; all are written like their std counterparts
+#[cfg(feature = "heapless")]
+mod heapless_impls {
+    use super::{ConstShape, MaxLen, PostcardTs, Shape};
+
+    fn bounded_seq<T: PostcardTs>(max: usize) -> Shape {
+        Shape::BoundedSeq(Box::new(T::shape()), max)
+    }
+
+    fn bounded_map<K: PostcardTs, V: PostcardTs>(max: usize) -> Shape {
+        Shape::BoundedMap(Box::new(K::shape()), Box::new(V::shape()), max)
+    }
+
+    impl<const N: usize> PostcardTs for heapless::String<N> {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedString(N);
+
+        fn shape() -> Shape {
+            Shape::BoundedString(N)
+        }
+    }
+
+    /// A tighter limit than the capacity, as `max_len` on the field gives
+    impl<const N: usize> MaxLen for heapless::String<N> {
+        fn max_len(max: usize) -> Shape {
+            Shape::BoundedString(max.min(N))
+        }
+    }
+
+    impl<T: PostcardTs, const N: usize> PostcardTs for heapless::Vec<T, N> {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedSeq(&T::CONST_SHAPE, N);
+
+        fn shape() -> Shape {
+            bounded_seq::<T>(N)
+        }
+    }
+
+    impl<T: PostcardTs, K, const N: usize> PostcardTs for heapless::BinaryHeap<T, K, N> {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedSeq(&T::CONST_SHAPE, N);
+
+        fn shape() -> Shape {
+            bounded_seq::<T>(N)
+        }
+    }
+
+    impl<T: PostcardTs, S, const N: usize> PostcardTs for heapless::IndexSet<T, S, N> {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedSeq(&T::CONST_SHAPE, N);
+
+        fn shape() -> Shape {
+            bounded_seq::<T>(N)
+        }
+    }
+
+    impl<K: PostcardTs, V: PostcardTs, S, const N: usize> PostcardTs
+        for heapless::IndexMap<K, V, S, N>
+    {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedMap(&K::CONST_SHAPE, &V::CONST_SHAPE, N);
+
+        fn shape() -> Shape {
+            bounded_map::<K, V>(N)
+        }
+    }
+
+    impl<K: PostcardTs, V: PostcardTs, const N: usize> PostcardTs for heapless::LinearMap<K, V, N> {
+        const CONST_SHAPE: ConstShape = ConstShape::BoundedMap(&K::CONST_SHAPE, &V::CONST_SHAPE, N);
+
+        fn shape() -> Shape {
+            bounded_map::<K, V>(N)
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Typed fake devices for UI tests
+//!
+//! A [`Simulator`] maps a device's request type, an enum with a variant per
+//! request, to the type it responds with and the type of the telemetry it
+//! sends unprompted. With [`Layout`](crate::Layout)s that write files, each
+//! simulator becomes a TypeScript file declaring a subclass of the runtime's
+//! `DeviceSimulator` with a typed handler per request variant, so UI tests
+//! can run against a deterministic fake speaking real postcard bytes:
+//!
+//! ```typescript
+//! const device = new ThermostatSimulator({
+//!   send: (bytes) => socket.deliver(bytes),
+//!   handlers: { SetTarget: (request) => ({ type: "Ack" }) },
+//! });
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::generate::{property, screaming_snake, GenerateError, GeneratedFile};
+use crate::naming::Naming;
+use crate::shape::{Definition, TypeName};
+
+/// A simulated device: what it is sent, what it answers and what it reports
+///
+/// ```
+/// use serde_postcard_ts::Simulator;
+///
+/// let thermostat = Simulator::new("Thermostat", "Command", "Reply").telemetry("app::Reading");
+/// assert_eq!(thermostat.name(), "Thermostat");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulator {
+    name: String,
+    request: String,
+    response: String,
+    telemetry: Option<String>,
+}
+
+impl Simulator {
+    /// A device named `name` (the class is `<name>Simulator`) answering the
+    /// variants of the enum `request` with `response`; types are given by
+    /// name (`Command`) or by path (`app::control::Command`)
+    pub fn new(
+        name: impl Into<String>,
+        request: impl Into<String>,
+        response: impl Into<String>,
+    ) -> Self {
+        Simulator {
+            name: name.into(),
+            request: request.into(),
+            response: response.into(),
+            telemetry: None,
+        }
+    }
+
+    /// The type of the telemetry the device sends unprompted (default: the
+    /// response type, for devices sending both as one enum)
+    pub fn telemetry(mut self, telemetry: impl Into<String>) -> Self {
+        self.telemetry = Some(telemetry.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The file the simulator is generated in, e.g. `hvac-unit-simulator.ts`
+    pub(crate) fn path(&self) -> String {
+        format!(
+            "{}-simulator.ts",
+            screaming_snake(&self.name)
+                .to_ascii_lowercase()
+                .replace('_', "-")
+        )
+    }
+
+    fn invalid(&self, type_name: &str, reason: &'static str) -> GenerateError {
+        GenerateError::InvalidSimulator {
+            simulator: self.name.clone(),
+            type_name: type_name.to_string(),
+            reason,
+        }
+    }
+
+    /// The non-generic type `pattern` names
+    fn find<'t>(
+        &self,
+        pattern: &str,
+        types: &'t [(TypeName, Definition)],
+    ) -> Result<&'t (TypeName, Definition), GenerateError> {
+        let found = types
+            .iter()
+            .find(|(type_name, _)| match pattern.rsplit_once("::") {
+                Some((module, name)) => type_name.module == module && type_name.name == name,
+                None => type_name.name == pattern,
+            });
+        match found {
+            None => Err(self.invalid(pattern, "matches no type")),
+            Some((type_name, _)) if !type_name.params.is_empty() => {
+                Err(self.invalid(pattern, "is generic"))
+            }
+            Some(found) => Ok(found),
+        }
+    }
+}
+
+/// The simulator class `simulator` describes, starting with `header` and
+/// importing the schemas from `index` and the runtime from `import_from`
+pub(crate) fn render(
+    simulator: &Simulator,
+    types: &[(TypeName, Definition)],
+    enum_tag: Option<&str>,
+    naming: &Naming,
+    header: &str,
+    import_from: &str,
+    index: &str,
+) -> Result<GeneratedFile, GenerateError> {
+    let name = &simulator.name;
+    let valid = name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "Device";
+    if !valid {
+        return Err(GenerateError::InvalidSimulatorName(name.clone()));
+    }
+    let (request, definition) = simulator.find(&simulator.request, types)?;
+    let Definition::Enum(variants) = definition else {
+        return Err(simulator.invalid(&simulator.request, "is not an enum"));
+    };
+    let response = simulator.find(&simulator.response, types)?.0.name;
+    let telemetry = match &simulator.telemetry {
+        Some(telemetry) => simulator.find(telemetry, types)?.0.name,
+        None => response,
+    };
+    let [request_schema, response_schema, telemetry_schema] =
+        [request.name, response, telemetry].map(|name| naming.schema(name));
+    let request = naming.type_name(request.name);
+    let response = naming.type_name(response);
+    let telemetry = naming.type_name(telemetry);
+
+    let class = format!("{}Simulator", name);
+    let handlers = format!("{}Handlers", name);
+    let tag = enum_tag.unwrap_or("type");
+    let mut imports: BTreeSet<String> = [&request_schema, &response_schema, &telemetry_schema]
+        .into_iter()
+        .cloned()
+        .collect();
+    imports.insert(format!("type {}", request));
+    imports.insert(format!("type {}", response));
+
+    let mut source = format!(
+        "{header}\n\
+         import {{\n\
+         \x20 DeviceSimulator,\n\
+         \x20 type DeviceSimulatorOptions,\n\
+         \x20 type SimulatorReply,\n\
+         }} from \"{import_from}\";\n\
+         import {{\n"
+    );
+    for import in &imports {
+        source.push_str(&format!("  {},\n", import));
+    }
+    source.push_str(&format!("}} from \"{index}\";\n\n"));
+
+    source.push_str(&format!(
+        "/** Handlers of the {request} requests {class} answers, by variant */\n\
+         export type {handlers} = {{\n"
+    ));
+    for variant in variants {
+        source.push_str(&format!(
+            "  {}?: (\n\
+             \x20   request: Extract<{request}, {{ {}: \"{}\" }}>,\n\
+             \x20   simulator: {class}\n\
+             \x20 ) => SimulatorReply<{response}>;\n",
+            property(variant.name),
+            property(tag),
+            variant.name,
+        ));
+    }
+    source.push_str("};\n\n");
+
+    // Handlers are looked up by the tag of discriminated enums
+    let options = match enum_tag {
+        Some(tag) if property(tag) == tag => {
+            format!("{{ kindOf: (request) => (request as {request}).{tag}, ...options }}")
+        }
+        Some(tag) => {
+            format!("{{ kindOf: (request) => (request as {request})[{tag:?}], ...options }}")
+        }
+        None => "options".to_string(),
+    };
+    source.push_str(&format!(
+        "export interface {class}Options extends DeviceSimulatorOptions {{\n\
+         \x20 readonly handlers?: {handlers};\n\
+         }}\n\
+         \n\
+         /**\n\
+         \x20* Simulated {name} for UI tests, answering {request} requests with {response}\n\
+         \x20* and sending {telemetry} telemetry\n\
+         \x20*/\n\
+         export class {class} extends DeviceSimulator<\n\
+         \x20 typeof {request_schema},\n\
+         \x20 typeof {response_schema},\n\
+         \x20 typeof {telemetry_schema}\n\
+         > {{\n\
+         \x20 constructor(options: {class}Options) {{\n\
+         \x20   super(\n\
+         \x20     {{ request: {request_schema}, response: {response_schema}, telemetry: {telemetry_schema} }},\n\
+         \x20     {options}\n\
+         \x20   );\n\
+         \x20   for (const [variant, handler] of Object.entries(options.handlers ?? {{}})) {{\n\
+         \x20     this.setHandler(variant, handler);\n\
+         \x20   }}\n\
+         \x20 }}\n\
+         \n\
+         \x20 /**\n\
+         \x20  * Answer `variant` requests with `handler`; without one (`undefined`),\n\
+         \x20  * they are rejected as unhandled\n\
+         \x20  */\n\
+         \x20 on<K extends keyof {handlers}>(variant: K, handler: {handlers}[K]): this {{\n\
+         \x20   this.setHandler(variant, handler);\n\
+         \x20   return this;\n\
+         \x20 }}\n\
+         }}\n"
+    ));
+
+    Ok(GeneratedFile {
+        path: simulator.path(),
+        source,
+    })
+}
@@ -0,0 +1,977 @@
+//! Moving from hand-written TypeScript types to the generated ones
+//!
+//! A codebase that mirrored its Rust types by hand has interfaces of its own,
+//! which rarely match the generated ones exactly: a field renamed to
+//! camelCase, a `u64` typed as `number`, an `Option` written as an optional
+//! property instead of `| null`. [`Generator::migrate_ts`](crate::Generator::migrate_ts)
+//! compares the types a TypeScript file exports with the ones that would be
+//! generated and reports where they differ:
+//!
+//! ```text
+//! adapted   Reading.sensorId: named sensor_id in generated
+//! adapted   Reading.timestamp: bigint in generated, number here, exact up to 2^53
+//! mismatch  Settings.unit: string in generated, "C" | "F" here
+//! 3 differences, 1 not adapted
+//! ```
+//!
+//! Differences that only take converting the value are adapted by shims,
+//! `fromReading` and `toReading` functions between the two types
+//! ([`TsMigration::shims`]), so code can move to the generated decoders one
+//! call site at a time. A type with a difference that cannot be converted
+//! gets no shims, and neither do the types holding it. `postcard-ts migrate`
+//! prints the report and writes the shims.
+//!
+//! Only `export interface X { ... }` and `export type X = { ... }` declarations
+//! of non-generic types are read, each compared with the generated struct of
+//! the same name; other declarations, and types not generated, are left
+//! alone.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+use crate::generate::{array_item, property, value_type};
+use crate::naming::Naming;
+use crate::shape::{Definition, Field, Shape, TypeName};
+
+/// One difference between a hand-written type and the generated one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TsMismatch {
+    /// The hand-written type (`Reading`) or a field of it (`Reading.sensorId`)
+    pub path: String,
+    pub description: String,
+    /// Whether the shims convert between the two
+    pub adapted: bool,
+}
+
+impl Display for TsMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let adapted = if self.adapted { "adapted" } else { "mismatch" };
+        write!(f, "{:<8}  {}: {}", adapted, self.path, self.description)
+    }
+}
+
+/// The differences between a file of hand-written types and the generated
+/// ones, in the order the file declares its types
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TsMigration {
+    pub mismatches: Vec<TsMismatch>,
+    /// The types that get shims, in the order the file declares them
+    adapters: Vec<Adapter>,
+}
+
+impl TsMigration {
+    /// Whether the shims adapt every difference
+    pub fn is_adapted(&self) -> bool {
+        self.mismatches.iter().all(|mismatch| mismatch.adapted)
+    }
+
+    /// The names of the types the shims convert
+    pub fn adapted_types(&self) -> impl Iterator<Item = &str> {
+        self.adapters.iter().map(|adapter| adapter.name.as_str())
+    }
+
+    /// A line per difference, and a count of them
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        for mismatch in &self.mismatches {
+            out.push_str(&format!("{}\n", mismatch));
+        }
+        match self.mismatches.len() {
+            0 => out.push_str("no differences\n"),
+            count => out.push_str(&format!(
+                "{} difference{}, {} not adapted\n",
+                count,
+                if count == 1 { "" } else { "s" },
+                self.mismatches
+                    .iter()
+                    .filter(|mismatch| !mismatch.adapted)
+                    .count()
+            )),
+        }
+        out
+    }
+
+    /// A TypeScript module of a `fromX` and a `toX` function per adapted type
+    /// `X`, converting the generated type imported from the module
+    /// `generated` into the hand-written one imported from `hand_written`,
+    /// and back
+    ///
+    /// Both are import specifiers as the shims' module would write them,
+    /// `./generated/index.js` for instance.
+    pub fn shims(&self, generated: &str, hand_written: &str) -> String {
+        let mut out = format!(
+            "// Generated by postcard-ts migrate; delete each pair of functions once\n\
+             // nothing uses its hand-written type.\n\
+             \n\
+             import type * as generated from \"{}\";\n\
+             import type * as handWritten from \"{}\";\n",
+            generated, hand_written
+        );
+        for adapter in &self.adapters {
+            out.push('\n');
+            out.push_str(&adapter.from());
+            out.push('\n');
+            out.push_str(&adapter.to());
+        }
+        out
+    }
+}
+
+/// The shims of a type
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Adapter {
+    name: String,
+    fields: Vec<FieldAdapter>,
+}
+
+/// How a field moves between the two types
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FieldAdapter {
+    /// The field's name in the generated type, if it has it
+    generated: Option<String>,
+    /// Its name in the hand-written type, if it has it
+    hand_written: Option<String>,
+    /// Whether the hand-written type declares it optional, `name?: T`
+    optional: bool,
+    conversion: Conversion,
+}
+
+/// How a value of the generated type converts to the hand-written one
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Conversion {
+    Same,
+    /// A bigint written as a number
+    Number,
+    /// A number written as a bigint
+    BigInt,
+    /// `T | null` in both, the `T` converted
+    Nullable(Box<Conversion>),
+    /// `T | null` written as `T | undefined`, or with `null_too` as
+    /// `T | null | undefined`
+    Undefinable {
+        inner: Box<Conversion>,
+        null_too: bool,
+    },
+    /// An array, its items converted
+    Array(Box<Conversion>),
+    /// A type with shims of its own
+    Shim(String),
+}
+
+impl Conversion {
+    /// The shims `self` calls, anywhere within it
+    fn shims(&self, out: &mut Vec<String>) {
+        match self {
+            Conversion::Same | Conversion::Number | Conversion::BigInt => {}
+            Conversion::Nullable(inner)
+            | Conversion::Undefinable { inner, .. }
+            | Conversion::Array(inner) => inner.shims(out),
+            Conversion::Shim(name) => out.push(name.clone()),
+        }
+    }
+
+    /// `self` with the shims of types without any replaced by no conversion
+    fn resolve(self, adapted: &BTreeSet<String>) -> Conversion {
+        match self {
+            Conversion::Nullable(inner) => match inner.resolve(adapted) {
+                Conversion::Same => Conversion::Same,
+                inner => Conversion::Nullable(Box::new(inner)),
+            },
+            Conversion::Undefinable { inner, null_too } => Conversion::Undefinable {
+                inner: Box::new(inner.resolve(adapted)),
+                null_too,
+            },
+            Conversion::Array(inner) => match inner.resolve(adapted) {
+                Conversion::Same => Conversion::Same,
+                inner => Conversion::Array(Box::new(inner)),
+            },
+            Conversion::Shim(name) if !adapted.contains(&name) => Conversion::Same,
+            conversion => conversion,
+        }
+    }
+
+    /// The expression converting the generated `value` to the hand-written
+    /// type
+    fn from(&self, value: &str) -> String {
+        match self {
+            Conversion::Same => value.to_string(),
+            Conversion::Number => format!("Number({})", value),
+            Conversion::BigInt => format!("BigInt({})", value),
+            Conversion::Nullable(inner)
+            | Conversion::Undefinable {
+                inner,
+                null_too: true,
+            } => match **inner {
+                Conversion::Same => value.to_string(),
+                _ => format!("{} === null ? null : {}", value, inner.from(value)),
+            },
+            Conversion::Undefinable { inner, .. } => {
+                format!("{} === null ? undefined : {}", value, inner.from(value))
+            }
+            Conversion::Array(inner) => format!("{}.map((item) => {})", value, inner.from("item")),
+            Conversion::Shim(name) => format!("from{}({})", name, value),
+        }
+    }
+
+    /// The expression converting the hand-written `value` to the generated
+    /// type
+    fn to(&self, value: &str) -> String {
+        match self {
+            Conversion::Same => value.to_string(),
+            Conversion::Number => format!("BigInt({})", value),
+            Conversion::BigInt => format!("Number({})", value),
+            Conversion::Nullable(inner) => {
+                format!("{} === null ? null : {}", value, inner.to(value))
+            }
+            Conversion::Undefinable { inner, null_too } => match (&**inner, null_too) {
+                (Conversion::Same, _) => format!("{} ?? null", value),
+                (_, true) => format!(
+                    "{} === undefined || {} === null ? null : {}",
+                    value,
+                    value,
+                    inner.to(value)
+                ),
+                (_, false) => format!("{} === undefined ? null : {}", value, inner.to(value)),
+            },
+            Conversion::Array(inner) => format!("{}.map((item) => {})", value, inner.to("item")),
+            Conversion::Shim(name) => format!("to{}({})", name, value),
+        }
+    }
+}
+
+impl Adapter {
+    fn from(&self) -> String {
+        let mut out = format!(
+            "export function from{}(value: generated.{}): handWritten.{} {{\n  return {{\n",
+            self.name, self.name, self.name
+        );
+        for field in &self.fields {
+            let (Some(generated), Some(hand_written)) = (&field.generated, &field.hand_written)
+            else {
+                continue;
+            };
+            let value = member("value", generated);
+            match &field.conversion {
+                // Left out rather than set to undefined, which
+                // exactOptionalPropertyTypes forbids
+                Conversion::Undefinable {
+                    inner,
+                    null_too: false,
+                } if field.optional => out.push_str(&format!(
+                    "    ...({} === null ? {{}} : {{ {}: {} }}),\n",
+                    value,
+                    property(hand_written),
+                    inner.from(&value)
+                )),
+                conversion => out.push_str(&format!(
+                    "    {}: {},\n",
+                    property(hand_written),
+                    conversion.from(&value)
+                )),
+            }
+        }
+        out.push_str("  };\n}\n");
+        out
+    }
+
+    fn to(&self) -> String {
+        let mut out = format!(
+            "export function to{}(value: handWritten.{}): generated.{} {{\n  return {{\n",
+            self.name, self.name, self.name
+        );
+        for field in &self.fields {
+            let Some(generated) = &field.generated else {
+                continue;
+            };
+            let value = match &field.hand_written {
+                Some(hand_written) => field.conversion.to(&member("value", hand_written)),
+                None => "null".to_string(),
+            };
+            out.push_str(&format!("    {}: {},\n", property(generated), value));
+        }
+        out.push_str("  };\n}\n");
+        out
+    }
+}
+
+/// `object.name`, or `object["name"]` for a name that is no identifier
+fn member(object: &str, name: &str) -> String {
+    let name = property(name);
+    if name.starts_with('"') {
+        format!("{}[{}]", object, name)
+    } else {
+        format!("{}.{}", object, name)
+    }
+}
+
+/// A type the hand-written file declares
+#[derive(Debug)]
+struct Declared {
+    name: String,
+    members: Vec<Member>,
+}
+
+/// A property of a declared type
+#[derive(Debug)]
+struct Member {
+    name: String,
+    optional: bool,
+    /// The declared type, as written
+    ts_type: String,
+}
+
+/// A hand-written type compared with the generated one
+struct Compared {
+    mismatches: Vec<TsMismatch>,
+    fields: Vec<FieldAdapter>,
+    /// Whether every difference of its own can be converted
+    adaptable: bool,
+}
+
+/// Compare the types exported by the TypeScript `source` with `types` as
+/// the generator writes them
+pub(crate) fn compare(
+    types: &[(TypeName, Definition)],
+    naming: &Naming,
+    u8_vecs_as_bytes: bool,
+    source: &str,
+) -> TsMigration {
+    let declared = declarations(&strip_comments(source));
+    let generated: HashMap<String, &Definition> = types
+        .iter()
+        .filter(|(type_name, _)| type_name.params.is_empty())
+        .map(|(type_name, definition)| (naming.type_name(type_name.name), definition))
+        .collect();
+    let declared: Vec<Declared> = declared
+        .into_iter()
+        .filter(|declared| generated.contains_key(&declared.name))
+        .collect();
+    let comparer = Comparer {
+        naming,
+        u8_vecs_as_bytes,
+        declared: declared
+            .iter()
+            .map(|declared| declared.name.clone())
+            .collect(),
+    };
+    let mut compared: Vec<Compared> = declared
+        .iter()
+        .map(|declared| comparer.compare(declared, generated[&declared.name]))
+        .collect();
+
+    // A type holding one that cannot be converted cannot be either, and one
+    // holding a type that differs needs shims too
+    let mut adaptable: BTreeSet<&str> = declared
+        .iter()
+        .zip(&compared)
+        .filter(|(_, compared)| compared.adaptable)
+        .map(|(declared, _)| declared.name.as_str())
+        .collect();
+    let mut differs: BTreeSet<&str> = declared
+        .iter()
+        .zip(&compared)
+        .filter(|(_, compared)| !compared.mismatches.is_empty())
+        .map(|(declared, _)| declared.name.as_str())
+        .collect();
+    let shims: Vec<Vec<String>> = compared
+        .iter()
+        .map(|compared| {
+            let mut shims = Vec::new();
+            for field in &compared.fields {
+                field.conversion.shims(&mut shims);
+            }
+            shims
+        })
+        .collect();
+    loop {
+        let mut changed = false;
+        for (declared, shims) in declared.iter().zip(&shims) {
+            let name = declared.name.as_str();
+            if adaptable.contains(name)
+                && shims.iter().any(|shim| !adaptable.contains(shim.as_str()))
+            {
+                adaptable.remove(name);
+                changed = true;
+            }
+            if !differs.contains(name) && shims.iter().any(|shim| differs.contains(shim.as_str())) {
+                differs.insert(name);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let adapted: BTreeSet<String> = differs
+        .intersection(&adaptable)
+        .map(|name| name.to_string())
+        .collect();
+    let mut migration = TsMigration::default();
+    for ((declared, compared), shims) in declared.iter().zip(&mut compared).zip(&shims) {
+        let name = declared.name.as_str();
+        if !adaptable.contains(name) {
+            if compared.adaptable {
+                let held: BTreeSet<&str> = shims
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|shim| !adaptable.contains(shim))
+                    .collect();
+                compared.mismatches.push(TsMismatch {
+                    path: name.to_string(),
+                    description: format!(
+                        "holds {}, which cannot be adapted",
+                        held.into_iter().collect::<Vec<_>>().join(", ")
+                    ),
+                    adapted: false,
+                });
+            }
+            for mismatch in &mut compared.mismatches {
+                mismatch.adapted = false;
+            }
+        }
+        migration.mismatches.append(&mut compared.mismatches);
+        if adapted.contains(name) {
+            migration.adapters.push(Adapter {
+                name: name.to_string(),
+                fields: compared
+                    .fields
+                    .drain(..)
+                    .map(|field| FieldAdapter {
+                        conversion: field.conversion.resolve(&adapted),
+                        ..field
+                    })
+                    .collect(),
+            });
+        }
+    }
+    migration
+}
+
+struct Comparer<'a> {
+    naming: &'a Naming,
+    u8_vecs_as_bytes: bool,
+    /// The hand-written types compared, by name
+    declared: BTreeSet<String>,
+}
+
+impl Comparer<'_> {
+    fn compare(&self, declared: &Declared, definition: &Definition) -> Compared {
+        let name = &declared.name;
+        let mut compared = Compared {
+            mismatches: Vec::new(),
+            fields: Vec::new(),
+            adaptable: true,
+        };
+        let fields = match definition {
+            Definition::Struct(fields) => fields,
+            other => {
+                let kind = match other {
+                    Definition::TupleStruct(_) => "a tuple".to_string(),
+                    Definition::NewtypeStruct(shape) => {
+                        value_type(shape, self.naming, self.u8_vecs_as_bytes)
+                    }
+                    Definition::UnitStruct => "null".to_string(),
+                    _ => "a union of its variants".to_string(),
+                };
+                compared.mismatches.push(TsMismatch {
+                    path: name.clone(),
+                    description: format!("generated as {}, not an object", kind),
+                    adapted: false,
+                });
+                compared.adaptable = false;
+                return compared;
+            }
+        };
+
+        let mut matched = vec![false; declared.members.len()];
+        for field in fields {
+            let path = |member: &str| format!("{}.{}", name, member);
+            let found = declared
+                .members
+                .iter()
+                .position(|member| member.name == field.name)
+                .or_else(|| {
+                    declared.members.iter().enumerate().position(|(i, member)| {
+                        !matched[i] && loose_name(&member.name) == loose_name(field.name)
+                    })
+                });
+            let Some(index) = found else {
+                let optional = matches!(field.shape, Shape::Option(_));
+                compared.mismatches.push(TsMismatch {
+                    path: path(field.name),
+                    description: if optional {
+                        "only in generated, where it may be null".to_string()
+                    } else {
+                        "only in generated".to_string()
+                    },
+                    adapted: optional,
+                });
+                compared.adaptable &= optional;
+                compared.fields.push(FieldAdapter {
+                    generated: Some(field.name.to_string()),
+                    hand_written: None,
+                    optional: false,
+                    conversion: Conversion::Same,
+                });
+                continue;
+            };
+            matched[index] = true;
+            let member = &declared.members[index];
+            let mut notes = Vec::new();
+            if member.name != field.name {
+                notes.push(format!("named {} in generated", field.name));
+            }
+            let conversion = match self.field(field, member) {
+                Ok((conversion, adapted)) => {
+                    notes.extend(adapted.into_iter().map(str::to_string));
+                    conversion
+                }
+                Err(description) => {
+                    compared.mismatches.push(TsMismatch {
+                        path: path(&member.name),
+                        description,
+                        adapted: false,
+                    });
+                    compared.adaptable = false;
+                    Conversion::Same
+                }
+            };
+            if !notes.is_empty() {
+                compared.mismatches.push(TsMismatch {
+                    path: path(&member.name),
+                    description: notes.join("; "),
+                    adapted: true,
+                });
+            }
+            compared.fields.push(FieldAdapter {
+                generated: Some(field.name.to_string()),
+                hand_written: Some(member.name.clone()),
+                optional: member.optional,
+                conversion,
+            });
+        }
+        for (member, _) in declared
+            .members
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+        {
+            compared.mismatches.push(TsMismatch {
+                path: format!("{}.{}", name, member.name),
+                description: if member.optional {
+                    "only here, and left out".to_string()
+                } else {
+                    "only here".to_string()
+                },
+                adapted: member.optional,
+            });
+            compared.adaptable &= member.optional;
+        }
+        compared
+    }
+
+    /// How `field` converts to `member`, and the differences converted, or
+    /// why it cannot
+    fn field(
+        &self,
+        field: &Field,
+        member: &Member,
+    ) -> Result<(Conversion, BTreeSet<&'static str>), String> {
+        let expected = value_type(&field.shape, self.naming, self.u8_vecs_as_bytes);
+        if member.optional && !matches!(field.shape, Shape::Option(_)) {
+            return Err(format!(
+                "optional here, always there in generated as {}",
+                expected
+            ));
+        }
+        let mut notes = BTreeSet::new();
+        match self.convert(&field.shape, &member.ts_type, member.optional, &mut notes) {
+            Some(conversion) => Ok((conversion, notes)),
+            None => Err(format!(
+                "{} in generated, {} here",
+                expected,
+                spaced(&member.ts_type)
+            )),
+        }
+    }
+
+    /// How a value of `shape` converts to the TypeScript type `ts`, if it
+    /// does, noting the differences converted
+    fn convert(
+        &self,
+        shape: &Shape,
+        ts: &str,
+        optional: bool,
+        notes: &mut BTreeSet<&'static str>,
+    ) -> Option<Conversion> {
+        let members = union_members(ts);
+        let null = members.contains(&"null");
+        let undefined = optional || members.contains(&"undefined");
+        let rest: Vec<&str> = members
+            .iter()
+            .copied()
+            .filter(|member| *member != "null" && *member != "undefined")
+            .collect();
+        let rest = rest.join(" | ");
+
+        if let Shape::Option(inner) = shape {
+            if !null && !undefined {
+                return None;
+            }
+            let inner = Box::new(self.convert(inner, &rest, false, notes)?);
+            return Some(if undefined {
+                notes.insert(if null {
+                    "null in generated, null or undefined here"
+                } else {
+                    "null in generated, undefined here"
+                });
+                Conversion::Undefinable {
+                    inner,
+                    null_too: null,
+                }
+            } else {
+                Conversion::Nullable(inner)
+            });
+        }
+        if null || undefined {
+            return None;
+        }
+
+        let item = match shape {
+            Shape::Seq(item) if self.u8_vecs_as_bytes && **item == Shape::U8 => None,
+            Shape::Seq(item) | Shape::BoundedSeq(item, _) => Some(&**item),
+            Shape::Tuple(items) => array_item(items),
+            _ => None,
+        };
+        if let (Some(item), Some(ts_item)) = (item, array_item_type(&rest)) {
+            let item = self.convert(item, ts_item, false, notes)?;
+            return Some(Conversion::Array(Box::new(item)));
+        }
+        let rest = compact(&rest);
+        if let Shape::Named(type_name) = shape {
+            let name = self.naming.type_name(type_name.name);
+            if rest == name && self.declared.contains(&name) {
+                return Some(Conversion::Shim(name));
+            }
+        }
+        let expected = compact(&value_type(shape, self.naming, self.u8_vecs_as_bytes));
+        match (expected.as_str(), rest.as_str()) {
+            (expected, rest) if expected == rest => Some(Conversion::Same),
+            ("bigint", "number") => {
+                notes.insert("bigint in generated, number here, exact up to 2^53");
+                Some(Conversion::Number)
+            }
+            ("number", "bigint") => {
+                notes.insert("number in generated, bigint here");
+                Some(Conversion::BigInt)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A field name with case and underscores ignored, `sensor_id` as `sensorid`
+/// and so `sensorId`
+fn loose_name(name: &str) -> String {
+    name.chars()
+        .filter(|ch| *ch != '_')
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect()
+}
+
+/// `source` with its comments replaced by spaces, strings kept
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' | '\'' | '`' => {
+                out.push(ch);
+                while let Some(next) = chars.next() {
+                    out.push(next);
+                    if next == '\\' {
+                        out.extend(chars.next());
+                    } else if next == ch {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|next| *next != '\n').is_some() {}
+                out.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+                out.push(' ');
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// The non-generic object types `source` exports
+fn declarations(source: &str) -> Vec<Declared> {
+    let mut declared = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("export") {
+        let before = &source[..source.len() - rest.len() + start];
+        rest = &rest[start + "export".len()..];
+        if before
+            .chars()
+            .next_back()
+            .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$' || ch == '.')
+        {
+            continue;
+        }
+        let after = rest.trim_start();
+        let after = keyword(after, "declare").unwrap_or(after);
+        let (is_interface, after) = match (keyword(after, "interface"), keyword(after, "type")) {
+            (Some(after), _) => (true, after),
+            (_, Some(after)) => (false, after),
+            _ => continue,
+        };
+        let name_length = after
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '$'))
+            .unwrap_or(after.len());
+        let (name, after) = after.split_at(name_length);
+        let after = after.trim_start();
+        // Generic types, and interfaces extending others, are left alone
+        let body = if is_interface {
+            after.strip_prefix('{')
+        } else {
+            after
+                .strip_prefix('=')
+                .and_then(|after| after.trim_start().strip_prefix('{'))
+        };
+        let (Some(body), false) = (body, name.is_empty()) else {
+            continue;
+        };
+        let Some(end) = closing_brace(body) else {
+            break;
+        };
+        declared.push(Declared {
+            name: name.to_string(),
+            members: split_members(&body[..end])
+                .into_iter()
+                .filter_map(parse_member)
+                .collect(),
+        });
+        rest = &body[end..];
+    }
+    declared
+}
+
+/// `text` after the keyword `word` and the whitespace following it
+fn keyword<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let after = text.strip_prefix(word)?;
+    after
+        .starts_with(char::is_whitespace)
+        .then(|| after.trim_start())
+}
+
+/// The index of the `}` closing the braces opened before `body`
+fn closing_brace(body: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, ch) in body.char_indices() {
+        match (quote, ch) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(ch),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return Some(i),
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The members of an object type's body, split at `;`, `,` and line ends
+/// outside brackets; a line starting or ending a union or intersection
+/// continues the member
+fn split_members(body: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut previous = ' ';
+    for (i, ch) in body.char_indices() {
+        match (quote, ch) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(ch),
+            (None, '(' | '[' | '{' | '<') => depth += 1,
+            // The `>` of an arrow closes nothing
+            (None, '>') if previous == '=' => {}
+            (None, ')' | ']' | '}' | '>') => depth -= 1,
+            (None, ';' | ',') if depth == 0 => {
+                members.push(&body[start..i]);
+                start = i + 1;
+            }
+            (None, '\n') if depth == 0 => {
+                let member = body[start..i].trim();
+                let continued = member.is_empty()
+                    || ["|", "&", ":", "=>"]
+                        .iter()
+                        .any(|end| member.ends_with(end))
+                    || body[i..].trim_start().starts_with(['|', '&']);
+                if !continued {
+                    members.push(&body[start..i]);
+                    start = i + 1;
+                }
+            }
+            _ => {}
+        }
+        previous = ch;
+    }
+    members.push(&body[start..]);
+    members
+        .into_iter()
+        .map(str::trim)
+        .filter(|member| !member.is_empty())
+        .collect()
+}
+
+/// A property signature; index signatures and methods are none
+fn parse_member(text: &str) -> Option<Member> {
+    let text = match keyword(text, "readonly") {
+        Some(after) if !after.starts_with([':', '?']) => after,
+        _ => text,
+    };
+    let (name, rest) = match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = text[1..].find(quote)? + 1;
+            (&text[1..end], &text[end + 1..])
+        }
+        _ => {
+            let length = text
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '$'))
+                .unwrap_or(text.len());
+            text.split_at(length)
+        }
+    };
+    let rest = rest.trim_start();
+    let (optional, rest) = match rest.strip_prefix('?') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, rest),
+    };
+    let ts_type = rest.strip_prefix(':')?.trim();
+    if name.is_empty() || ts_type.is_empty() {
+        return None;
+    }
+    Some(Member {
+        name: name.to_string(),
+        optional,
+        ts_type: ts_type.to_string(),
+    })
+}
+
+/// The members of a union type, split at `|` outside brackets
+fn union_members(ts: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut previous = ' ';
+    for (i, ch) in ts.char_indices() {
+        match ch {
+            '(' | '[' | '{' | '<' => depth += 1,
+            '>' if previous == '=' => {}
+            ')' | ']' | '}' | '>' => depth -= 1,
+            '|' if depth == 0 => {
+                members.push(&ts[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        previous = ch;
+    }
+    members.push(&ts[start..]);
+    members
+        .into_iter()
+        .map(|member| unparenthesize(member.trim()))
+        .filter(|member| !member.is_empty())
+        .collect()
+}
+
+/// `ts` without parentheses around all of it
+fn unparenthesize(ts: &str) -> &str {
+    match ts.strip_prefix('(').and_then(|ts| ts.strip_suffix(')')) {
+        Some(inner) if closing_paren(inner).is_none() => unparenthesize(inner.trim()),
+        _ => ts,
+    }
+}
+
+/// The index of a `)` in `ts` closing a parenthesis opened before it
+fn closing_paren(ts: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, ch) in ts.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The item type of an array type, `T[]`, `readonly T[]`, `Array<T>` or
+/// `ReadonlyArray<T>`
+fn array_item_type(ts: &str) -> Option<&str> {
+    let ts = ts.trim();
+    if union_members(ts).len() != 1 {
+        return None;
+    }
+    let ts = keyword(ts, "readonly").unwrap_or(ts);
+    if let Some(item) = ts.strip_suffix("[]") {
+        return Some(unparenthesize(item.trim()));
+    }
+    ["Array<", "ReadonlyArray<"]
+        .iter()
+        .find_map(|prefix| ts.strip_prefix(prefix)?.strip_suffix('>'))
+        .map(str::trim)
+}
+
+/// `ts` without whitespace, but for single spaces between words, for
+/// comparing types however they are laid out
+fn compact(ts: &str) -> String {
+    let word = |ch: char| ch.is_ascii_alphanumeric() || ch == '_' || ch == '$';
+    let mut out = String::with_capacity(ts.len());
+    let mut space = false;
+    for ch in ts.trim().chars() {
+        if ch.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && out.ends_with(word) && word(ch) {
+            out.push(' ');
+        }
+        space = false;
+        out.push(ch);
+    }
+    out
+}
+
+/// `ts` on one line, without the `|` a union laid out a member per line
+/// starts with, for reports
+fn spaced(ts: &str) -> String {
+    let ts = ts.trim();
+    let ts = ts.strip_prefix('|').unwrap_or(ts);
+    ts.split_whitespace().collect::<Vec<_>>().join(" ")
+}
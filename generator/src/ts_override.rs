@@ -0,0 +1,209 @@
+//! Hand-written TypeScript for types the derive cannot describe
+//!
+//! A type with a hand-written `Serialize` impl, or a field written by a
+//! `#[serde(with = "...")]` module, has a wire shape only its author knows.
+//! [`Generator::override_type`](crate::Generator::override_type) declares it
+//! with a [`TsOverride`]: the shape of the bytes and, optionally, functions
+//! converting the values decoded from them into a TypeScript type of choice,
+//! which the generated schema applies with `transform()`:
+//!
+//! ```typescript
+//! export type Timestamp = Date;
+//! export const TimestampSchema = transform(u64(), {
+//!   decode: (value): Timestamp => fromMillis(value),
+//!   encode: (value: Timestamp) => toMillis(value),
+//! });
+//! ```
+//!
+//! Fields written by a `with` module name a stand-in type to describe them,
+//! `#[postcard_ts(with = "Timestamp")]`, which is then overridden; so do
+//! types serde writes through `into`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::generate::property;
+use crate::naming::Naming;
+use crate::shape::Shape;
+use crate::wasm;
+
+/// The wire shape and TypeScript representation of an overridden type
+///
+/// ```
+/// use serde_postcard_ts::{Shape, TsOverride};
+///
+/// let millis = TsOverride::new(Shape::U64)
+///     .ts_type("Date")
+///     .codec("fromMillis", "toMillis")
+///     .import("fromMillis", "./codecs.js")
+///     .import("toMillis", "./codecs.js");
+/// assert_eq!(millis.wire(), &Shape::U64);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TsOverride {
+    wire: Shape,
+    ts_type: Option<String>,
+    codec: Option<(String, String)>,
+    imports: Vec<(String, String)>,
+    /// Whether the codec encodes and decodes the bytes itself, through
+    /// `external()`, rather than converting values of the wire shape
+    external: bool,
+}
+
+impl TsOverride {
+    /// A type written as `wire`, decoding to what the schema of `wire` decodes
+    /// to unless given a [`codec`](Self::codec)
+    pub fn new(wire: Shape) -> Self {
+        TsOverride {
+            wire,
+            ts_type: None,
+            codec: None,
+            imports: Vec::new(),
+            external: false,
+        }
+    }
+
+    /// The type `name`, encoded by the functions of the wasm crate imported
+    /// from `module`; its wire shape is unknown, so `wire` is only a stand-in
+    pub(crate) fn external(name: &str, module: &str) -> Self {
+        let (encode, decode) = wasm::functions(name);
+        TsOverride {
+            external: true,
+            ..TsOverride::new(Shape::Unit)
+                .ts_type("unknown")
+                .import(&encode, module)
+                .import(&decode, module)
+                .codec(decode, encode)
+        }
+    }
+
+    /// The TypeScript type the codec converts values to (default: inferred
+    /// from the codec's functions)
+    pub fn ts_type(mut self, ts_type: impl Into<String>) -> Self {
+        self.ts_type = Some(ts_type.into());
+        self
+    }
+
+    /// TypeScript expressions of the functions converting decoded values of
+    /// the wire shape to the type's values, and back for encoding
+    pub fn codec(mut self, decode: impl Into<String>, encode: impl Into<String>) -> Self {
+        self.codec = Some((decode.into(), encode.into()));
+        self
+    }
+
+    /// Import `name` (or `type Name`) from `module` into the files declaring
+    /// the type; like [`Generator::import_from`](crate::Generator::import_from),
+    /// a relative `module` is taken relative to the output directory
+    pub fn import(mut self, name: impl Into<String>, module: impl Into<String>) -> Self {
+        self.imports.push((name.into(), module.into()));
+        self
+    }
+
+    pub fn wire(&self) -> &Shape {
+        &self.wire
+    }
+
+    /// Why the override cannot be generated, if it cannot
+    pub(crate) fn invalid(&self) -> Option<&'static str> {
+        match (&self.ts_type, &self.codec) {
+            (Some(_), None) => Some("a TypeScript type needs a codec converting to it"),
+            _ => None,
+        }
+    }
+
+    /// Record the names to import, by module
+    pub(crate) fn imports<'a>(&'a self, out: &mut BTreeMap<&'a str, BTreeSet<&'a str>>) {
+        for (name, module) in &self.imports {
+            out.entry(module).or_default().insert(name);
+        }
+    }
+
+    /// Whether the declared type is inferred from the schema
+    pub(crate) fn infers(&self) -> bool {
+        self.ts_type.is_none()
+    }
+
+    /// Whether the schema is wrapped in `transform()`, or is `external()`
+    pub(crate) fn transforms(&self) -> bool {
+        self.codec.is_some()
+    }
+
+    /// Whether the schema is `external()`, with no wire shape to render
+    pub(crate) fn is_external(&self) -> bool {
+        self.external
+    }
+
+    /// The declaration of the type `name` alone, if it is not inferred
+    pub(crate) fn type_declaration(&self, naming: &Naming, name: &str) -> Option<String> {
+        let ts_type = self.ts_type.as_ref()?;
+        Some(format!(
+            "export type {} = {ts_type};\n",
+            naming.type_name(name)
+        ))
+    }
+
+    /// The declaration of the type `name`, whose wire shape has the schema
+    /// expression `wire`, wrapped in the `direction` builder if it has one
+    pub(crate) fn declaration(
+        &self,
+        naming: &Naming,
+        name: &str,
+        wire: &str,
+        direction: Option<&str>,
+    ) -> String {
+        // The schema limited to its direction, if the type has one
+        let directed = |schema: String| match direction {
+            Some(builder) => format!("{}({})", builder, schema),
+            None => schema,
+        };
+        let ty = naming.type_name(name);
+        let schema_name = naming.schema(name);
+        let Some((decode, encode)) = &self.codec else {
+            return format!(
+                "export const {schema_name} = {};\nexport type {ty} = InferType<typeof {schema_name}>;\n",
+                directed(wire.to_string())
+            );
+        };
+        let Some(ts_type) = &self.ts_type else {
+            let schema = directed(format!(
+                "transform({wire}, {{\n  \
+                 decode: {decode},\n  \
+                 encode: {encode},\n\
+                 }})"
+            ));
+            return format!(
+                "export const {schema_name} = {schema};\n\
+                 export type {ty} = InferType<typeof {schema_name}>;\n"
+            );
+        };
+        if self.external {
+            let schema = directed(format!(
+                "external<{ty}>(\"{name}\", {{\n  \
+                 encode: {encode},\n  \
+                 decode: {decode},\n\
+                 }})"
+            ));
+            return format!(
+                "export type {ty} = {ts_type};\nexport const {schema_name} = {schema};\n"
+            );
+        }
+        let schema = directed(format!(
+            "transform({wire}, {{\n  \
+             decode: (value): {ty} => {}(value),\n  \
+             encode: (value: {ty}) => {}(value),\n\
+             }})",
+            callee(decode),
+            callee(encode),
+        ));
+        format!("export type {ty} = {ts_type};\nexport const {schema_name} = {schema};\n")
+    }
+}
+
+/// A function expression as the callee of a call, parenthesized unless it is
+/// a name such as `fromMillis` or `codecs.fromMillis`
+fn callee(function: &str) -> String {
+    if function.split('.').all(|part| property(part) == part) {
+        function.to_string()
+    } else {
+        format!("({})", function)
+    }
+}
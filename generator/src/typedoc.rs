@@ -0,0 +1,261 @@
+//! Comments and options for documenting the generated modules with TypeDoc
+
+use std::collections::HashMap;
+
+use crate::docs::{self, jsdoc, Doc};
+use crate::generate::{GenerateError, GeneratedFile};
+use crate::naming::Naming;
+use crate::registry::Direction;
+use crate::shape::{Definition, TypeName};
+
+/// Bytes per line of an example frame
+const FRAME_LINE: usize = 12;
+
+/// What the comments on the exports of one type say
+pub(crate) struct TypeDoc {
+    /// The `@category` of every export: the type's protocol group or Rust
+    /// module; `None` without TypeDoc comments, when only the type's doc
+    /// comment is written
+    category: Option<String>,
+    /// Lines of the comment on the type itself, before its category
+    lines: Vec<String>,
+    /// The `@deprecated` tag of a type with `#[deprecated]`, on its schema too
+    deprecated: Option<String>,
+}
+
+/// What is known about a type to document it
+pub(crate) struct Facts<'a> {
+    pub(crate) category: String,
+    pub(crate) hash: Option<u64>,
+    pub(crate) max_size: Option<usize>,
+    pub(crate) direction: Option<Direction>,
+    pub(crate) example: Option<&'a [u8]>,
+    /// The type's doc comment
+    pub(crate) doc: Doc,
+}
+
+impl TypeDoc {
+    pub(crate) fn new(
+        type_name: &TypeName,
+        definition: &Definition,
+        facts: Facts,
+        naming: &Naming,
+    ) -> Self {
+        let mut summary = format!(
+            "Rust `{}::{}`, {}",
+            type_name.module,
+            type_name.name,
+            kind(definition)
+        );
+        match facts.max_size {
+            Some(1) => summary.push_str(", at most 1 byte on the wire"),
+            Some(size) => summary.push_str(&format!(", at most {} bytes on the wire", size)),
+            None => {}
+        }
+        summary.push('.');
+        let mut lines: Vec<String> = Doc {
+            deprecated: None,
+            ..facts.doc
+        }
+        .comment_lines();
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.push(summary);
+        if let Some(hash) = facts.hash {
+            lines.push(String::new());
+            lines.push(format!("Schema hash `0x{:016x}`.", hash));
+        }
+        if let Some(frame) = facts.example {
+            lines.push(String::new());
+            lines.push("@example".to_string());
+            lines.push("```ts".to_string());
+            lines.extend(example(naming, type_name.name, frame, facts.direction));
+            lines.push("```".to_string());
+        }
+        TypeDoc {
+            category: Some(facts.category),
+            lines,
+            deprecated: facts.doc.deprecated.map(docs::deprecated),
+        }
+    }
+
+    /// The type's doc comment alone, if it has one
+    pub(crate) fn doc_comment(doc: Doc) -> Option<Self> {
+        if doc.is_empty() {
+            return None;
+        }
+        Some(TypeDoc {
+            category: None,
+            lines: Doc {
+                deprecated: None,
+                ..doc
+            }
+            .comment_lines(),
+            deprecated: doc.deprecated.map(docs::deprecated),
+        })
+    }
+
+    /// `declaration` of the type `name` with a comment before each export:
+    /// the type's own comment on its type, a link to it on its schema (or,
+    /// without a category, the type's comment again), and the category alone
+    /// on the rest
+    pub(crate) fn annotate(&self, naming: &Naming, name: &str, declaration: &str) -> String {
+        let category = self
+            .category
+            .as_ref()
+            .map(|category| format!("@category {}", category));
+        let schema = format!("export const {} ", naming.schema(name));
+        let name = naming.type_name(name);
+        let link = format!("The schema of {{@link {}}}.", name);
+        let mut out = String::with_capacity(declaration.len());
+        for line in declaration.split_inclusive('\n') {
+            if line.starts_with("export ") {
+                let is_type = ["export type ", "export interface "].iter().any(|keyword| {
+                    line.strip_prefix(keyword)
+                        .and_then(|rest| rest.strip_prefix(name.as_str()))
+                        .is_some_and(|rest| rest.starts_with([' ', '<']))
+                });
+                let mut lines: Vec<&str> = Vec::new();
+                if is_type || (line.starts_with(&schema) && category.is_none()) {
+                    lines.extend(self.lines.iter().map(String::as_str));
+                } else if line.starts_with(&schema) {
+                    lines.push(&link);
+                }
+                if is_type || line.starts_with(&schema) {
+                    if let Some(deprecated) = &self.deprecated {
+                        if !lines.is_empty() {
+                            lines.push("");
+                        }
+                        lines.push(deprecated);
+                    }
+                }
+                if let Some(category) = &category {
+                    if !lines.is_empty() {
+                        lines.push("");
+                    }
+                    lines.push(category);
+                }
+                if !lines.is_empty() {
+                    out.push_str(&jsdoc(&lines, ""));
+                }
+            }
+            out.push_str(line);
+        }
+        out
+    }
+}
+
+/// What kind of type `definition` declares, e.g. "a struct of 2 fields"
+fn kind(definition: &Definition) -> String {
+    let count = |n: usize, one: &str, many: &str| match n {
+        1 => format!("1 {}", one),
+        n => format!("{} {}", n, many),
+    };
+    match definition {
+        Definition::Struct(fields) => {
+            format!("a struct of {}", count(fields.len(), "field", "fields"))
+        }
+        Definition::TupleStruct(items) => {
+            format!("a tuple struct of {}", count(items.len(), "item", "items"))
+        }
+        Definition::NewtypeStruct(_) => "a newtype struct".to_string(),
+        Definition::UnitStruct => "a unit struct".to_string(),
+        Definition::Enum(variants) if variants.is_empty() => "an enum without variants".to_string(),
+        Definition::Enum(variants) => {
+            let names: Vec<&str> = variants.iter().map(|variant| variant.name).collect();
+            format!(
+                "an enum of {} ({})",
+                count(variants.len(), "variant", "variants"),
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Lines of TypeScript decoding `frame` as the type `name`, or saying it is
+/// what encoding one gives for types only encoded
+fn example(naming: &Naming, name: &str, frame: &[u8], direction: Option<Direction>) -> Vec<String> {
+    let value = {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_ascii_lowercase())
+            .into_iter()
+            .chain(chars)
+            .collect::<String>()
+    };
+    let bytes: Vec<String> = frame.iter().map(|byte| format!("0x{:02x}", byte)).collect();
+    let mut lines = Vec::new();
+    if direction == Some(Direction::EncodeOnly) {
+        lines.push(format!(
+            "// What serialize({}, {}) writes for one {}",
+            naming.schema(name),
+            value,
+            naming.type_name(name)
+        ));
+    }
+    if bytes.len() <= FRAME_LINE {
+        lines.push(format!(
+            "const frame = new Uint8Array([{}]);",
+            bytes.join(", ")
+        ));
+    } else {
+        lines.push("const frame = new Uint8Array([".to_string());
+        for chunk in bytes.chunks(FRAME_LINE) {
+            lines.push(format!("  {},", chunk.join(", ")));
+        }
+        lines.push("]);".to_string());
+    }
+    if direction != Some(Direction::EncodeOnly) {
+        lines.push(format!(
+            "const {} = deserialize({}, frame);",
+            value,
+            naming.schema(name)
+        ));
+    }
+    lines
+}
+
+/// The frame of each type given an example, by name or `module::Name`
+pub(crate) fn examples<'a>(
+    examples: &'a [(String, Vec<u8>)],
+    types: &[(TypeName, Definition)],
+) -> Result<HashMap<TypeName, &'a [u8]>, GenerateError> {
+    let mut frames = HashMap::new();
+    for (pattern, frame) in examples {
+        let found = types.iter().find(|(type_name, _)| {
+            type_name.params.is_empty()
+                && match pattern.rsplit_once("::") {
+                    Some((module, name)) => type_name.module == module && type_name.name == name,
+                    None => type_name.name == pattern,
+                }
+        });
+        match found {
+            Some((type_name, _)) => {
+                frames.insert(*type_name, frame.as_slice());
+            }
+            None => return Err(GenerateError::UnmatchedExample(pattern.clone())),
+        }
+    }
+    Ok(frames)
+}
+
+/// `typedoc.json`, documenting the module `index` into `docs/`
+///
+/// TypeDoc reads options files allowing comments, so it starts with
+/// `header` like the other generated files.
+pub(crate) fn options(header: &str, index: &str) -> GeneratedFile {
+    GeneratedFile {
+        path: "typedoc.json".to_string(),
+        source: format!(
+            "{header}{{\n  \
+             \"entryPoints\": [\"{index}\"],\n  \
+             \"out\": \"docs\",\n  \
+             \"categorizeByGroup\": false,\n  \
+             \"defaultCategory\": \"Other\",\n  \
+             \"skipErrorChecking\": true\n\
+             }}\n"
+        ),
+    }
+}
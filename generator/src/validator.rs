@@ -0,0 +1,261 @@
+//! Runtime validators emitted next to the schemas
+//!
+//! With [`Generator::with_validators`](crate::Generator::with_validators),
+//! every type also gets a validator accepting exactly the values its schema
+//! decodes to and encodes, for checking values built in app code before
+//! encoding them, or taken from anywhere else than a decode:
+//!
+//! ```typescript
+//! const reading = ReadingZod.parse(JSON.parse(message));
+//! const bytes = serialize(ReadingSchema, reading);
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::builtin::{self, Representations};
+use crate::generate::{array_item, property, quote};
+use crate::naming::{Ident, Naming};
+use crate::shape::{Definition, Field, Shape, TypeName, VariantKind};
+use crate::ts_override::TsOverride;
+
+/// The library the validators are written for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Validator {
+    /// Zod schemas (`zod` 3), one `<Name>Zod` per type, a factory taking a
+    /// validator per type parameter for generic types
+    Zod,
+}
+
+impl Validator {
+    /// The import the validators need
+    pub(crate) fn import(self) -> &'static str {
+        match self {
+            Validator::Zod => "import { z } from \"zod\";\n",
+        }
+    }
+
+    /// The name of the validator of the type `name`
+    pub(crate) fn name(self, naming: &Naming, name: &str) -> String {
+        match self {
+            Validator::Zod => naming.ident(Ident::Zod, name),
+        }
+    }
+}
+
+/// Renders the Zod schema of each type
+pub(crate) struct Zod<'a> {
+    pub(crate) enum_tag: Option<&'a str>,
+    pub(crate) truncate_strings: bool,
+    pub(crate) u8_vecs_as_bytes: bool,
+    /// The circle of recursive types each is part of
+    pub(crate) cycles: &'a HashMap<TypeName, usize>,
+    pub(crate) overrides: &'a BTreeMap<TypeName, Cow<'a, TsOverride>>,
+    pub(crate) representations: Representations,
+    pub(crate) naming: &'a Naming,
+}
+
+impl Zod<'_> {
+    /// The declaration of `<Name>Zod` for `type_name`
+    pub(crate) fn declaration(&self, type_name: &TypeName, definition: &Definition) -> String {
+        let name = self.naming.ident(Ident::Zod, type_name.name);
+        let ty = self.naming.type_name(type_name.name);
+        let cycle = self.cycles.get(type_name).copied();
+        // A codec's TypeScript type is opaque to the generator
+        if self
+            .overrides
+            .get(type_name)
+            .is_some_and(|o| o.transforms())
+        {
+            return format!("export const {name} = z.custom<{ty}>();\n");
+        }
+        let builtin =
+            builtin::builtin(type_name).filter(|_| !self.overrides.contains_key(type_name));
+        if let Some(builtin) = builtin {
+            let validator = builtin.zod(self.representations, |inner| self.shape(inner, cycle));
+            return format!("export const {name} = {validator};\n");
+        }
+        let validator = self.definition(definition, cycle);
+
+        // As for the schema, the type of a recursive validator cannot be
+        // inferred from its own initializer
+        if cycle.is_some() {
+            return format!("export const {name}: z.ZodType<{ty}> = {validator};\n");
+        }
+        if type_name.params.is_empty() {
+            return format!("export const {name} = {validator};\n");
+        }
+        let params = type_name.params;
+        let bounds: Vec<String> = params
+            .iter()
+            .map(|p| format!("{} extends z.ZodTypeAny", p))
+            .collect();
+        let args: Vec<String> = params.iter().map(|p| format!("{}: {}", p, p)).collect();
+        format!(
+            "export const {name} = <{}>({}) =>\n  {};\n",
+            bounds.join(", "),
+            args.join(", "),
+            validator.replace('\n', "\n  "),
+        )
+    }
+
+    fn definition(&self, definition: &Definition, cycle: Option<usize>) -> String {
+        match definition {
+            Definition::Struct(fields) if !fields.is_empty() => {
+                let lines: String = fields
+                    .iter()
+                    .map(|field| {
+                        let shape = self.shape(&field.shape, cycle);
+                        format!("  {}: {},\n", property(field.name), shape)
+                    })
+                    .collect();
+                format!("z.object({{\n{}}})", lines)
+            }
+            Definition::Struct(_) | Definition::UnitStruct => "z.object({}).strict()".to_string(),
+            Definition::TupleStruct(items) => self.tuple(items, cycle),
+            Definition::NewtypeStruct(inner) => self.shape(inner, cycle),
+            Definition::Enum(variants) if variants.is_empty() => "z.never()".to_string(),
+            Definition::Enum(variants) => {
+                let tag = self.enum_tag.unwrap_or("type");
+                let lines: String = variants
+                    .iter()
+                    .map(|variant| {
+                        let mut members = vec![format!(
+                            "{}: z.literal({})",
+                            property(tag),
+                            quote(variant.name)
+                        )];
+                        match &variant.kind {
+                            VariantKind::Unit => {}
+                            VariantKind::Newtype(inner) => {
+                                members.push(format!("value: {}", self.shape(inner, cycle)))
+                            }
+                            VariantKind::Tuple(items) => {
+                                members.push(format!("value: {}", self.tuple(items, cycle)))
+                            }
+                            // discriminated() puts the fields next to the tag
+                            VariantKind::Struct(fields) if self.enum_tag.is_some() => {
+                                members.extend(self.members(fields, cycle))
+                            }
+                            VariantKind::Struct(fields) => {
+                                members.push(format!("value: {}", self.fields(fields, cycle)))
+                            }
+                        }
+                        format!("  z.object({{ {} }}),\n", members.join(", "))
+                    })
+                    .collect();
+                format!("z.discriminatedUnion({}, [\n{}])", quote(tag), lines)
+            }
+        }
+    }
+
+    fn shape(&self, shape: &Shape, cycle: Option<usize>) -> String {
+        match shape {
+            Shape::Bool => "z.boolean()".to_string(),
+            Shape::I8 => integer(i8::MIN.into(), i8::MAX.into()),
+            Shape::I16 => integer(i16::MIN.into(), i16::MAX.into()),
+            Shape::I32 => integer(i32::MIN.into(), i32::MAX.into()),
+            Shape::U8 => integer(0, u8::MAX.into()),
+            Shape::U16 => integer(0, u16::MAX.into()),
+            Shape::U32 => integer(0, u32::MAX.into()),
+            Shape::I64 => big_integer(i64::MIN.to_string(), i64::MAX.to_string()),
+            Shape::I128 => big_integer(i128::MIN.to_string(), i128::MAX.to_string()),
+            Shape::U64 => big_integer("0".to_string(), u64::MAX.to_string()),
+            Shape::U128 => big_integer("0".to_string(), u128::MAX.to_string()),
+            // Zod's numbers leave out NaN, which floats decode to
+            Shape::F32 | Shape::F64 => "z.number().or(z.nan())".to_string(),
+            Shape::Char => {
+                "z.string().refine((value) => [...value].length === 1, \"expected one character\")"
+                    .to_string()
+            }
+            Shape::String => "z.string()".to_string(),
+            // Encoding truncates strings over the limit instead of failing
+            Shape::BoundedString(_) if self.truncate_strings => "z.string()".to_string(),
+            Shape::BoundedString(max) => format!(
+                "z.string().refine((value) => new TextEncoder().encode(value).length <= {max}, \
+                 \"longer than {max} bytes\")"
+            ),
+            Shape::Bytes => "z.instanceof(Uint8Array)".to_string(),
+            Shape::Seq(item) if self.u8_vecs_as_bytes && **item == Shape::U8 => {
+                "z.instanceof(Uint8Array)".to_string()
+            }
+            Shape::Unit => "z.undefined()".to_string(),
+            Shape::Option(inner) => format!("{}.nullable()", self.shape(inner, cycle)),
+            Shape::Seq(item) => format!("z.array({})", self.shape(item, cycle)),
+            Shape::BoundedSeq(item, max) => {
+                format!("z.array({}).max({})", self.shape(item, cycle), max)
+            }
+            Shape::Tuple(items) => match array_item(items) {
+                Some(item) => format!(
+                    "z.array({}).length({})",
+                    self.shape(item, cycle),
+                    items.len()
+                ),
+                None => self.tuple(items, cycle),
+            },
+            Shape::Map(key, value) => format!(
+                "z.map({}, {})",
+                self.shape(key, cycle),
+                self.shape(value, cycle)
+            ),
+            Shape::BoundedMap(key, value, max) => format!(
+                "z.map({}, {}).refine((value) => value.size <= {max}, \"more than {max} entries\")",
+                self.shape(key, cycle),
+                self.shape(value, cycle)
+            ),
+            // Declared later or being declared: resolved when first used
+            Shape::Named(type_name)
+                if cycle.is_some() && self.cycles.get(type_name) == cycle.as_ref() =>
+            {
+                format!(
+                    "z.lazy(() => {})",
+                    self.naming.ident(Ident::Zod, type_name.name)
+                )
+            }
+            Shape::Named(type_name) => self.naming.ident(Ident::Zod, type_name.name),
+            Shape::Generic(type_name, args) => {
+                let args: Vec<String> = args.iter().map(|arg| self.shape(arg, cycle)).collect();
+                format!(
+                    "{}({})",
+                    self.naming.ident(Ident::Zod, type_name.name),
+                    args.join(", ")
+                )
+            }
+            // The argument of the validator factory
+            Shape::Param(name) => name.to_string(),
+        }
+    }
+
+    fn tuple(&self, items: &[Shape], cycle: Option<usize>) -> String {
+        let items: Vec<String> = items.iter().map(|item| self.shape(item, cycle)).collect();
+        format!("z.tuple([{}])", items.join(", "))
+    }
+
+    fn members(&self, fields: &[Field], cycle: Option<usize>) -> Vec<String> {
+        fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "{}: {}",
+                    property(field.name),
+                    self.shape(&field.shape, cycle)
+                )
+            })
+            .collect()
+    }
+
+    fn fields(&self, fields: &[Field], cycle: Option<usize>) -> String {
+        if fields.is_empty() {
+            return "z.object({})".to_string();
+        }
+        format!("z.object({{ {} }})", self.members(fields, cycle).join(", "))
+    }
+}
+
+fn integer(min: i64, max: i64) -> String {
+    format!("z.number().int().min({}).max({})", min, max)
+}
+
+fn big_integer(min: String, max: String) -> String {
+    format!("z.bigint().min({}n).max({}n)", min, max)
+}
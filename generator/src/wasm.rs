@@ -0,0 +1,194 @@
+//! Types encoded in Rust, through a generated wasm-bindgen crate
+//!
+//! Some types are easier left to postcard itself than described to the
+//! TypeScript schemas: a hand-written `Serialize` impl whose wire shape
+//! nobody wants to spell out in a [`TsOverride`](crate::TsOverride), or serde
+//! attributes the derive does not follow. [`Generator::wasm_type`] hands such
+//! a type to a [`WasmBackend`]: the generator writes a small wasm-bindgen
+//! crate exporting `encode_<type>` and `decode_<type>` for it, and the
+//! type's schema calls them through `external()`, so it is serialized,
+//! deserialized and nested in other schemas like any other:
+//!
+//! ```typescript
+//! import { decode_calibration, encode_calibration } from "./protocol-wasm/pkg/protocol_wasm.js";
+//!
+//! export type Calibration = unknown;
+//! export const CalibrationSchema = external<Calibration>("Calibration", {
+//!   encode: encode_calibration,
+//!   decode: decode_calibration,
+//! });
+//! ```
+//!
+//! Values cross into Rust as `serde-wasm-bindgen` represents them (objects
+//! for structs and maps, `{ Variant: value }` for enums, bigints for 64-bit
+//! integers), so they are typed `unknown`. The crate is written next to the
+//! TypeScript files and built with `wasm-pack build --target bundler`.
+//!
+//! [`Generator::wasm_type`]: crate::Generator::wasm_type
+
+use crate::generate::{screaming_snake, GeneratedFile, HEADER};
+use crate::shape::TypeName;
+
+/// The wasm-bindgen crate encoding the types given to
+/// [`Generator::wasm_type`](crate::Generator::wasm_type)
+///
+/// ```
+/// use serde_postcard_ts::WasmBackend;
+///
+/// let backend = WasmBackend::new("protocol-wasm")
+///     .dependency(r#"firmware = { path = "../../../firmware" }"#);
+/// assert_eq!(backend.package(), "protocol-wasm");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasmBackend {
+    package: String,
+    dependencies: Vec<String>,
+    import_from: Option<String>,
+}
+
+impl WasmBackend {
+    /// A crate named `package`, written to the `package` directory of the
+    /// output
+    pub fn new(package: impl Into<String>) -> Self {
+        WasmBackend {
+            package: package.into(),
+            dependencies: Vec::new(),
+            import_from: None,
+        }
+    }
+
+    /// Add a line to the crate's `[dependencies]`, typically the crate
+    /// declaring the types, with a path relative to the crate's directory
+    pub fn dependency(mut self, line: impl Into<String>) -> Self {
+        self.dependencies.push(line.into());
+        self
+    }
+
+    /// Import the crate's functions from `module` instead of
+    /// `./<package>/pkg/<package>.js`, where `wasm-pack` puts them; like
+    /// [`Generator::import_from`](crate::Generator::import_from), a relative
+    /// `module` is taken relative to the output directory
+    pub fn import_from(mut self, module: impl Into<String>) -> Self {
+        self.import_from = Some(module.into());
+        self
+    }
+
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Whether the package name is one Cargo takes
+    pub(crate) fn valid(&self) -> bool {
+        !self.package.is_empty()
+            && self
+                .package
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    }
+
+    /// The module the generated TypeScript imports the functions from
+    pub(crate) fn import(&self) -> String {
+        match &self.import_from {
+            Some(module) => module.clone(),
+            None => format!(
+                "./{}/pkg/{}.js",
+                self.package,
+                self.package.replace('-', "_")
+            ),
+        }
+    }
+
+    /// The crate's `Cargo.toml` and `src/lib.rs`, for the types and the Rust
+    /// paths they are named by
+    pub(crate) fn files(&self, types: &[(TypeName, &str)]) -> Vec<GeneratedFile> {
+        let mut manifest = format!(
+            "#{}\n\
+             [package]\n\
+             name = \"{}\"\n\
+             version = \"0.1.0\"\n\
+             edition = \"2021\"\n\
+             publish = false\n\
+             \n\
+             [lib]\n\
+             crate-type = [\"cdylib\"]\n\
+             \n\
+             [dependencies]\n\
+             postcard = {{ version = \"1\", features = [\"use-std\"] }}\n\
+             serde = \"1\"\n\
+             serde-wasm-bindgen = \"0.6\"\n\
+             wasm-bindgen = \"0.2\"\n",
+            &HEADER[2..HEADER.len() - 1],
+            self.package
+        );
+        for line in &self.dependencies {
+            manifest.push_str(line);
+            manifest.push('\n');
+        }
+        // A workspace of its own, wherever the output directory is
+        manifest.push_str("\n[workspace]\n");
+
+        let mut source = format!(
+            "{HEADER}\n\
+             use serde::de::DeserializeOwned;\n\
+             use serde::Serialize;\n\
+             use wasm_bindgen::prelude::*;\n\
+             \n\
+             /// A value decoded from the start of a buffer, and how many bytes it took\n\
+             #[derive(Serialize)]\n\
+             #[serde(rename_all = \"camelCase\")]\n\
+             struct Decoded<T> {{\n\
+             \x20   value: T,\n\
+             \x20   bytes_read: usize,\n\
+             }}\n\
+             \n\
+             fn encode<T: DeserializeOwned + Serialize>(value: JsValue) -> Result<Vec<u8>, JsError> {{\n\
+             \x20   let value: T = serde_wasm_bindgen::from_value(value)?;\n\
+             \x20   Ok(postcard::to_allocvec(&value)?)\n\
+             }}\n\
+             \n\
+             fn decode<T: DeserializeOwned + Serialize>(bytes: &[u8]) -> Result<JsValue, JsError> {{\n\
+             \x20   let (value, rest) = postcard::take_from_bytes::<T>(bytes)?;\n\
+             \x20   let decoded = Decoded {{\n\
+             \x20       value,\n\
+             \x20       bytes_read: bytes.len() - rest.len(),\n\
+             \x20   }};\n\
+             \x20   let serializer =\n\
+             \x20       serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);\n\
+             \x20   Ok(decoded.serialize(&serializer)?)\n\
+             }}\n"
+        );
+        for (type_name, path) in types {
+            let (encode, decode) = functions(type_name.name);
+            source.push_str(&format!(
+                "\n\
+                 #[wasm_bindgen]\n\
+                 pub fn {encode}(value: JsValue) -> Result<Vec<u8>, JsError> {{\n\
+                 \x20   encode::<{path}>(value)\n\
+                 }}\n\
+                 \n\
+                 #[wasm_bindgen]\n\
+                 pub fn {decode}(bytes: &[u8]) -> Result<JsValue, JsError> {{\n\
+                 \x20   decode::<{path}>(bytes)\n\
+                 }}\n"
+            ));
+        }
+
+        vec![
+            GeneratedFile {
+                path: format!("{}/Cargo.toml", self.package),
+                source: manifest,
+            },
+            GeneratedFile {
+                path: format!("{}/src/lib.rs", self.package),
+                source,
+            },
+        ]
+    }
+}
+
+/// The names of the functions encoding and decoding the type `name`:
+/// `encode_game_state` and `decode_game_state` for `GameState`
+pub(crate) fn functions(name: &str) -> (String, String) {
+    let snake = screaming_snake(name).to_lowercase();
+    (format!("encode_{}", snake), format!("decode_{}", snake))
+}
@@ -0,0 +1,506 @@
+//! Where each field sits on the wire, for editor tooling
+//!
+//! Postcard writes fields back to back with no tags, so the position of a
+//! field follows from the fields before it. The wire layout spells that out per
+//! type, so that an editor can show "bytes 1..5, f32, little-endian" when
+//! hovering a field:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "types": [
+//!     {"name": "Reading", "module": "app", "size": {"min": 6, "max": 10}, "segments": [
+//!       {"path": "sensor", "kind": "u8", "encoding": "byte", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}},
+//!       {"path": "celsius", "kind": "f32", "encoding": "f32, little-endian", "offset": {"min": 1, "max": 1}, "size": {"min": 4, "max": 4}},
+//!       ...
+//!     ]}
+//!   ]
+//! }
+//! ```
+//!
+//! Segments are in wire order. Structs, tuples and newtypes take no bytes of
+//! their own, so their fields are listed in their place under dotted paths
+//! (`position.x`, `cells.0`); options, sequences, maps, strings and enums are
+//! one segment each, and segments of named types carry their `type`. Offsets
+//! and sizes are ranges of bytes, `max` being null when unbounded, so a field
+//! at a fixed position has `min == max`. Enums list their `variants` instead,
+//! each with its `index` and with segments starting at the variant index
+//! itself (path `""`). Type parameters of generic types are `param` segments
+//! of any size.
+
+use std::collections::HashMap;
+
+use crate::ir::{kind, string};
+use crate::shape::{Definition, Field, Shape, TypeName, Variant, VariantKind};
+
+/// Version of the wire layout format written here
+pub const WIRE_LAYOUT_VERSION: u32 = 1;
+
+pub(crate) fn to_json(types: &[(TypeName, Definition)]) -> String {
+    let types: Vec<String> = type_layouts(types)
+        .iter()
+        .map(|layout| format!("    {}", layout))
+        .collect();
+    format!(
+        "{{\n  \"version\": {},\n  \"types\": [\n{}\n  ]\n}}\n",
+        WIRE_LAYOUT_VERSION,
+        types.join(",\n")
+    )
+}
+
+/// The layout of each type, one JSON object each
+pub(crate) fn type_layouts(types: &[(TypeName, Definition)]) -> Vec<String> {
+    let definitions: HashMap<TypeName, &Definition> = types
+        .iter()
+        .map(|(type_name, definition)| (*type_name, definition))
+        .collect();
+    let walker = Walker {
+        definitions: &definitions,
+        stack: Vec::new(),
+    };
+    types
+        .iter()
+        .map(|(type_name, definition)| walker.clone().type_json(type_name, definition))
+        .collect()
+}
+
+/// A number of bytes; `max` is `None` when unbounded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bytes {
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Bytes {
+    const NONE: Bytes = Bytes::exactly(0);
+    const ANY: Bytes = Bytes { min: 0, max: None };
+
+    const fn exactly(n: usize) -> Self {
+        Bytes {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    const fn between(min: usize, max: usize) -> Self {
+        Bytes {
+            min,
+            max: Some(max),
+        }
+    }
+
+    /// This many bytes followed by `other`
+    fn then(self, other: Bytes) -> Bytes {
+        Bytes {
+            min: self.min.saturating_add(other.min),
+            max: self.max.zip(other.max).and_then(|(a, b)| a.checked_add(b)),
+        }
+    }
+
+    /// A length prefix of up to `max`, then that many times this many bytes
+    fn repeated(self, max: usize) -> Bytes {
+        Bytes {
+            min: 1,
+            max: self
+                .max
+                .and_then(|item| item.checked_mul(max))
+                .and_then(|items| items.checked_add(varint_len(max))),
+        }
+    }
+
+    /// Either this many bytes or `other`
+    fn or(self, other: Bytes) -> Bytes {
+        Bytes {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(a, b)| a.max(b)),
+        }
+    }
+
+    fn json(self) -> String {
+        match self.max {
+            Some(max) => format!("{{\"min\": {}, \"max\": {}}}", self.min, max),
+            None => format!("{{\"min\": {}, \"max\": null}}", self.min),
+        }
+    }
+}
+
+/// Bytes of the varint of a variant index
+fn varint_len(value: usize) -> usize {
+    let bits = usize::BITS - value.leading_zeros();
+    (bits.max(1) as usize).div_ceil(7)
+}
+
+struct Segment {
+    path: String,
+    kind: &'static str,
+    encoding: &'static str,
+    /// The named type of the segment, for enums and recursive references
+    type_name: Option<&'static str>,
+    offset: Bytes,
+    size: Bytes,
+}
+
+fn segments_json(segments: &[Segment]) -> String {
+    let segments: Vec<String> = segments
+        .iter()
+        .map(|segment| {
+            let type_name = match segment.type_name {
+                Some(name) => format!(", \"type\": {}", string(name)),
+                None => String::new(),
+            };
+            format!(
+                "{{\"path\": {}, \"kind\": \"{}\", \"encoding\": \"{}\"{}, \"offset\": {}, \"size\": {}}}",
+                string(&segment.path),
+                segment.kind,
+                segment.encoding,
+                type_name,
+                segment.offset.json(),
+                segment.size.json()
+            )
+        })
+        .collect();
+    format!("[{}]", segments.join(", "))
+}
+
+/// Walks definitions, keeping the named types it is inside of to stop at
+/// recursive ones
+#[derive(Clone)]
+struct Walker<'a> {
+    definitions: &'a HashMap<TypeName, &'a Definition>,
+    stack: Vec<TypeName>,
+}
+
+impl Walker<'_> {
+    fn type_json(&mut self, type_name: &TypeName, definition: &Definition) -> String {
+        self.stack.push(*type_name);
+        let name = string(type_name.name);
+        let module = string(type_name.module);
+        if let Definition::Enum(variants) = definition {
+            let mut size: Option<Bytes> = None;
+            let mut variants_json = Vec::with_capacity(variants.len());
+            for (index, variant) in variants.iter().enumerate() {
+                let mut segments = Vec::new();
+                let variant_size = self.variant(index, variant, &mut segments);
+                size = Some(size.map_or(variant_size, |size| size.or(variant_size)));
+                variants_json.push(format!(
+                    "{{\"name\": {}, \"index\": {}, \"segments\": {}}}",
+                    string(variant.name),
+                    index,
+                    segments_json(&segments)
+                ));
+            }
+            // An enum without variants has no values, so never takes any bytes
+            return format!(
+                "{{\"name\": {}, \"module\": {}, \"size\": {}, \"variants\": [{}]}}",
+                name,
+                module,
+                size.unwrap_or(Bytes::NONE).json(),
+                variants_json.join(", ")
+            );
+        }
+
+        let mut segments = Vec::new();
+        let size = self.definition(definition, "", Bytes::NONE, &mut segments);
+        format!(
+            "{{\"name\": {}, \"module\": {}, \"size\": {}, \"segments\": {}}}",
+            name,
+            module,
+            size.json(),
+            segments_json(&segments)
+        )
+    }
+
+    /// Segments of a variant, starting with its index; returns its size
+    fn variant(&mut self, index: usize, variant: &Variant, out: &mut Vec<Segment>) -> Bytes {
+        let index_size = Bytes::exactly(varint_len(index));
+        out.push(Segment {
+            path: String::new(),
+            kind: "variant_index",
+            encoding: "varint",
+            type_name: None,
+            offset: Bytes::NONE,
+            size: index_size,
+        });
+        let data = match &variant.kind {
+            VariantKind::Unit => Bytes::NONE,
+            VariantKind::Newtype(inner) => self.shape(inner, "0", index_size, out),
+            VariantKind::Tuple(items) => self.items(items, "", index_size, out),
+            VariantKind::Struct(fields) => self.fields(fields, "", index_size, out),
+        };
+        index_size.then(data)
+    }
+
+    /// Segments of a struct-like definition at `offset`; returns its size
+    fn definition(
+        &mut self,
+        definition: &Definition,
+        path: &str,
+        offset: Bytes,
+        out: &mut Vec<Segment>,
+    ) -> Bytes {
+        match definition {
+            Definition::Struct(fields) => self.fields(fields, path, offset, out),
+            Definition::TupleStruct(items) => self.items(items, path, offset, out),
+            Definition::NewtypeStruct(inner) => self.shape(inner, path, offset, out),
+            Definition::UnitStruct => Bytes::NONE,
+            // Enums are single segments; see `shape`
+            Definition::Enum(_) => Bytes::NONE,
+        }
+    }
+
+    fn fields(
+        &mut self,
+        fields: &[Field],
+        path: &str,
+        offset: Bytes,
+        out: &mut Vec<Segment>,
+    ) -> Bytes {
+        let mut size = Bytes::NONE;
+        for field in fields {
+            let field_path = join(path, field.name);
+            size = size.then(self.shape(&field.shape, &field_path, offset.then(size), out));
+        }
+        size
+    }
+
+    fn items(
+        &mut self,
+        items: &[Shape],
+        path: &str,
+        offset: Bytes,
+        out: &mut Vec<Segment>,
+    ) -> Bytes {
+        let mut size = Bytes::NONE;
+        for (i, item) in items.iter().enumerate() {
+            let item_path = join(path, &i.to_string());
+            size = size.then(self.shape(item, &item_path, offset.then(size), out));
+        }
+        size
+    }
+
+    /// Segments of a value of `shape` at `offset`; returns its size
+    fn shape(&mut self, shape: &Shape, path: &str, offset: Bytes, out: &mut Vec<Segment>) -> Bytes {
+        if let Shape::Tuple(items) = shape {
+            return self.items(items, path, offset, out);
+        }
+        if let Some((type_name, definition)) = self.instance(shape) {
+            if !matches!(definition, Definition::Enum(_)) && !self.stack.contains(&type_name) {
+                self.stack.push(type_name);
+                let size = self.definition(&definition, path, offset, out);
+                self.stack.pop();
+                return size;
+            }
+        }
+
+        let size = self.size(shape);
+        let (kind, encoding, type_name) = match self.instance(shape) {
+            Some((type_name, Definition::Enum(_))) => (
+                "enum",
+                "varint variant index, then the variant's fields",
+                Some(type_name.name),
+            ),
+            // Within itself
+            Some((type_name, _)) => ("ref", "as the named type", Some(type_name.name)),
+            None => (kind(shape), encoding(shape), None),
+        };
+        out.push(Segment {
+            path: path.to_string(),
+            kind,
+            encoding,
+            type_name,
+            offset,
+            size,
+        });
+        size
+    }
+
+    /// The definition of a named type, with a generic type's arguments filled in
+    fn instance(&self, shape: &Shape) -> Option<(TypeName, Definition)> {
+        match shape {
+            Shape::Named(type_name) => {
+                let definition = self.definitions.get(type_name)?;
+                Some((*type_name, (*definition).clone()))
+            }
+            Shape::Generic(type_name, args) => {
+                let definition = self.definitions.get(type_name)?;
+                let bindings: HashMap<&str, &Shape> =
+                    type_name.params.iter().copied().zip(args).collect();
+                Some((*type_name, substitute_definition(definition, &bindings)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Bytes taken by a value of `shape`
+    fn size(&mut self, shape: &Shape) -> Bytes {
+        match shape {
+            Shape::Bool | Shape::I8 | Shape::U8 => Bytes::exactly(1),
+            // Varints carry 7 bits per byte
+            Shape::I16 | Shape::U16 => Bytes::between(1, 3),
+            Shape::I32 | Shape::U32 => Bytes::between(1, 5),
+            Shape::I64 | Shape::U64 => Bytes::between(1, 10),
+            Shape::I128 | Shape::U128 => Bytes::between(1, 19),
+            Shape::F32 => Bytes::exactly(4),
+            Shape::F64 => Bytes::exactly(8),
+            // A length byte and 1 to 4 bytes of UTF-8
+            Shape::Char => Bytes::between(2, 5),
+            Shape::String | Shape::Bytes | Shape::Seq(_) | Shape::Map(_, _) => {
+                Bytes { min: 1, max: None }
+            }
+            Shape::BoundedString(max) => Bytes::between(1, varint_len(*max) + max),
+            Shape::BoundedSeq(item, max) => self.size(item).repeated(*max),
+            Shape::BoundedMap(key, value, max) => {
+                let entry = self.size(key).then(self.size(value));
+                entry.repeated(*max)
+            }
+            Shape::Unit => Bytes::NONE,
+            Shape::Param(_) => Bytes::ANY,
+            Shape::Option(inner) => {
+                let some = Bytes::exactly(1).then(self.size(inner));
+                Bytes::exactly(1).or(some)
+            }
+            Shape::Tuple(items) => self.sizes(items.iter()),
+            Shape::Named(_) | Shape::Generic(_, _) => {
+                let Some((type_name, definition)) = self.instance(shape) else {
+                    return Bytes::ANY;
+                };
+                // Within itself, through an option or a sequence
+                if self.stack.contains(&type_name) {
+                    return Bytes::ANY;
+                }
+                self.stack.push(type_name);
+                let size = self.definition_size(&definition);
+                self.stack.pop();
+                size
+            }
+        }
+    }
+
+    fn sizes<'s>(&mut self, shapes: impl Iterator<Item = &'s Shape>) -> Bytes {
+        shapes.fold(Bytes::NONE, |size, shape| size.then(self.size(shape)))
+    }
+
+    fn definition_size(&mut self, definition: &Definition) -> Bytes {
+        match definition {
+            Definition::Struct(fields) => self.sizes(fields.iter().map(|field| &field.shape)),
+            Definition::TupleStruct(items) => self.sizes(items.iter()),
+            Definition::NewtypeStruct(inner) => self.size(inner),
+            Definition::UnitStruct => Bytes::NONE,
+            Definition::Enum(variants) => {
+                let mut size: Option<Bytes> = None;
+                for (index, variant) in variants.iter().enumerate() {
+                    let data = match &variant.kind {
+                        VariantKind::Unit => Bytes::NONE,
+                        VariantKind::Newtype(inner) => self.size(inner),
+                        VariantKind::Tuple(items) => self.sizes(items.iter()),
+                        VariantKind::Struct(fields) => {
+                            self.sizes(fields.iter().map(|field| &field.shape))
+                        }
+                    };
+                    let variant_size = Bytes::exactly(varint_len(index)).then(data);
+                    size = Some(size.map_or(variant_size, |size| size.or(variant_size)));
+                }
+                size.unwrap_or(Bytes::NONE)
+            }
+        }
+    }
+}
+
+pub(crate) fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// How a value of `shape` is written, for display
+fn encoding(shape: &Shape) -> &'static str {
+    match shape {
+        Shape::Bool => "byte, 0 or 1",
+        Shape::U8 => "byte",
+        Shape::I8 => "byte, two's complement",
+        Shape::U16 | Shape::U32 | Shape::U64 | Shape::U128 => "varint",
+        Shape::I16 | Shape::I32 | Shape::I64 | Shape::I128 => "zigzag varint",
+        Shape::F32 => "f32, little-endian",
+        Shape::F64 => "f64, little-endian",
+        Shape::Char | Shape::String | Shape::BoundedString(_) => "varint length, then UTF-8",
+        Shape::Bytes => "varint length, then the bytes",
+        Shape::Unit => "nothing",
+        Shape::Option(_) => "byte, 0 for None or 1 for Some followed by the value",
+        Shape::Seq(_) | Shape::BoundedSeq(_, _) => "varint length, then the items",
+        Shape::Map(_, _) | Shape::BoundedMap(_, _, _) => "varint length, then the keys and values",
+        Shape::Tuple(_) => "the items, back to back",
+        Shape::Param(_) => "as the type argument",
+        Shape::Named(_) | Shape::Generic(_, _) => "as the named type",
+    }
+}
+
+/// `shape` with type parameters replaced by their arguments
+fn substitute(shape: &Shape, bindings: &HashMap<&str, &Shape>) -> Shape {
+    let boxed = |shape: &Shape| Box::new(substitute(shape, bindings));
+    match shape {
+        Shape::Param(name) => bindings
+            .get(name)
+            .map_or_else(|| shape.clone(), |arg| (*arg).clone()),
+        Shape::Option(inner) => Shape::Option(boxed(inner)),
+        Shape::Seq(item) => Shape::Seq(boxed(item)),
+        Shape::BoundedSeq(item, max) => Shape::BoundedSeq(boxed(item), *max),
+        Shape::Tuple(items) => Shape::Tuple(substitute_all(items, bindings)),
+        Shape::Map(key, value) => Shape::Map(boxed(key), boxed(value)),
+        Shape::BoundedMap(key, value, max) => Shape::BoundedMap(boxed(key), boxed(value), *max),
+        Shape::Generic(type_name, args) => {
+            Shape::Generic(*type_name, substitute_all(args, bindings))
+        }
+        _ => shape.clone(),
+    }
+}
+
+fn substitute_all(shapes: &[Shape], bindings: &HashMap<&str, &Shape>) -> Vec<Shape> {
+    shapes
+        .iter()
+        .map(|shape| substitute(shape, bindings))
+        .collect()
+}
+
+fn substitute_fields(fields: &[Field], bindings: &HashMap<&str, &Shape>) -> Vec<Field> {
+    fields
+        .iter()
+        .map(|field| Field {
+            name: field.name,
+            shape: substitute(&field.shape, bindings),
+        })
+        .collect()
+}
+
+pub(crate) fn substitute_definition(
+    definition: &Definition,
+    bindings: &HashMap<&str, &Shape>,
+) -> Definition {
+    match definition {
+        Definition::Struct(fields) => Definition::Struct(substitute_fields(fields, bindings)),
+        Definition::TupleStruct(items) => Definition::TupleStruct(substitute_all(items, bindings)),
+        Definition::NewtypeStruct(inner) => Definition::NewtypeStruct(substitute(inner, bindings)),
+        Definition::UnitStruct => Definition::UnitStruct,
+        Definition::Enum(variants) => Definition::Enum(
+            variants
+                .iter()
+                .map(|variant| Variant {
+                    name: variant.name,
+                    kind: match &variant.kind {
+                        VariantKind::Unit => VariantKind::Unit,
+                        VariantKind::Newtype(inner) => {
+                            VariantKind::Newtype(substitute(inner, bindings))
+                        }
+                        VariantKind::Tuple(items) => {
+                            VariantKind::Tuple(substitute_all(items, bindings))
+                        }
+                        VariantKind::Struct(fields) => {
+                            VariantKind::Struct(substitute_fields(fields, bindings))
+                        }
+                    },
+                })
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,31 @@
+//! Messages between the dashboard and the server
+//!
+//! The frontend's schemas in `web/src/generated` are generated from these
+//! types; run `npm run generate` in `web` after changing them.
+
+use serde::{Deserialize, Serialize};
+use serde_postcard_ts::PostcardTs;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct Reading {
+    pub sensor: u8,
+    pub celsius: f32,
+    pub uptime_ms: u32,
+}
+
+/// Sent by the dashboard
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum ClientMessage {
+    /// Sent straight back as [`ServerMessage::Echo`]
+    Echo(String),
+    /// Change how often readings are sent
+    SetInterval { millis: u16 },
+}
+
+/// Sent by the server
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum ServerMessage {
+    Echo(String),
+    Reading(Reading),
+    IntervalChanged { millis: u16 },
+}
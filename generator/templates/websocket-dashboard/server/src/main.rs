@@ -0,0 +1,83 @@
+//! Echoes dashboard messages back and streams made-up sensor readings
+//!
+//! Every WebSocket message, in both directions, is a single postcard-encoded
+//! `ClientMessage` or `ServerMessage` in a binary frame.
+
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tungstenite::{Message, WebSocket};
+use websocket_dashboard_server::{ClientMessage, Reading, ServerMessage};
+
+const ADDRESS: &str = "127.0.0.1:9001";
+
+fn main() {
+    let listener = TcpListener::bind(ADDRESS).expect("binding the server address");
+    println!("listening on ws://{}", ADDRESS);
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            if let Err(e) = serve(stream) {
+                eprintln!("connection closed: {}", e);
+            }
+        });
+    }
+}
+
+fn serve(stream: TcpStream) -> Result<(), Box<dyn std::error::Error>> {
+    let mut socket = tungstenite::accept(stream)?;
+    // Wake up regularly to send readings even while the dashboard is quiet
+    socket
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(20)))?;
+
+    let started = Instant::now();
+    let mut interval = Duration::from_millis(500);
+    let mut next_reading = started;
+    loop {
+        match socket.read() {
+            Ok(Message::Binary(data)) => {
+                let reply = match postcard::from_bytes::<ClientMessage>(&data) {
+                    Ok(ClientMessage::Echo(text)) => ServerMessage::Echo(text),
+                    Ok(ClientMessage::SetInterval { millis }) => {
+                        let millis = millis.max(50);
+                        interval = Duration::from_millis(millis.into());
+                        next_reading = Instant::now();
+                        ServerMessage::IntervalChanged { millis }
+                    }
+                    Err(e) => {
+                        eprintln!("undecodable message {:02x?}: {}", &data[..], e);
+                        continue;
+                    }
+                };
+                send(&mut socket, &reply)?;
+            }
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if Instant::now() >= next_reading {
+            next_reading += interval;
+            let uptime = started.elapsed();
+            let reading = Reading {
+                sensor: 1,
+                celsius: 21.0 + 3.0 * (uptime.as_secs_f32() / 5.0).sin(),
+                uptime_ms: uptime.as_millis() as u32,
+            };
+            send(&mut socket, &ServerMessage::Reading(reading))?;
+        }
+    }
+}
+
+fn send(
+    socket: &mut WebSocket<TcpStream>,
+    message: &ServerMessage,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = postcard::to_allocvec(message)?;
+    socket.send(Message::binary(bytes))?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.
+
+import {
+  enumType,
+  f32,
+  newtypeVariant,
+  string,
+  struct,
+  structVariant,
+  u16,
+  u32,
+  u8,
+  type InferType,
+} from "@variegated-coffee/serde-postcard-ts";
+
+/** Sent by the dashboard */
+export const ClientMessageSchema = enumType("ClientMessage", {
+  /** Sent straight back as [`ServerMessage::Echo`] */
+  Echo: newtypeVariant("Echo", string()),
+  /** Change how often readings are sent */
+  SetInterval: structVariant("SetInterval", { millis: u16() }),
+});
+/** Sent by the dashboard */
+export type ClientMessage = InferType<typeof ClientMessageSchema>;
+
+export const ReadingSchema = struct({
+  sensor: u8(),
+  celsius: f32(),
+  uptime_ms: u32(),
+});
+export type Reading = InferType<typeof ReadingSchema>;
+
+/** Sent by the server */
+export const ServerMessageSchema = enumType("ServerMessage", {
+  Echo: newtypeVariant("Echo", string()),
+  Reading: newtypeVariant("Reading", ReadingSchema),
+  IntervalChanged: structVariant("IntervalChanged", { millis: u16() }),
+});
+/** Sent by the server */
+export type ServerMessage = InferType<typeof ServerMessageSchema>;
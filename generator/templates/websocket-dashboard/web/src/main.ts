@@ -0,0 +1,66 @@
+/**
+ * Decodes the server's messages with the generated schemas
+ */
+
+import { deserialize, serialize } from "@variegated-coffee/serde-postcard-ts";
+import { ClientMessageSchema, ServerMessageSchema, type ClientMessage } from "./generated/index.js";
+
+const SERVER_URL = "ws://127.0.0.1:9001";
+
+function element<T extends HTMLElement>(id: string): T {
+  const found = document.getElementById(id);
+  if (found === null) {
+    throw new Error(`#${id} is missing from index.html`);
+  }
+  return found as T;
+}
+
+const status = element<HTMLParagraphElement>("status");
+const temperature = element<HTMLParagraphElement>("temperature");
+const echoForm = element<HTMLFormElement>("echo");
+const interval = element<HTMLSelectElement>("interval");
+const log = element<HTMLDivElement>("log");
+
+function print(line: string): void {
+  log.textContent = `${line}\n${log.textContent ?? ""}`.split("\n").slice(0, 20).join("\n");
+}
+
+const socket = new WebSocket(SERVER_URL);
+socket.binaryType = "arraybuffer";
+
+function send(message: ClientMessage): void {
+  socket.send(serialize(ClientMessageSchema, message));
+}
+
+socket.addEventListener("open", () => {
+  status.textContent = `connected to ${SERVER_URL}`;
+});
+socket.addEventListener("close", () => {
+  status.textContent = "disconnected; is the server running (cargo run in server)?";
+});
+
+socket.addEventListener("message", (event: MessageEvent<ArrayBuffer>) => {
+  const message = deserialize(ServerMessageSchema, new Uint8Array(event.data)).value;
+  switch (message.type) {
+    case "Reading":
+      temperature.textContent = `${message.value.celsius.toFixed(1)} °C`;
+      break;
+    case "Echo":
+      print(`echo: ${message.value}`);
+      break;
+    case "IntervalChanged":
+      print(`readings every ${message.value.millis} ms`);
+      break;
+  }
+});
+
+echoForm.addEventListener("submit", (event) => {
+  event.preventDefault();
+  const input = echoForm.elements.namedItem("text") as HTMLInputElement;
+  send({ type: "Echo", value: input.value });
+  input.value = "";
+});
+
+interval.addEventListener("change", () => {
+  send({ type: "SetInterval", value: { millis: Number(interval.value) } });
+});
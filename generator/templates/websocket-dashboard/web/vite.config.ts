@@ -0,0 +1,15 @@
+import { dirname } from "node:path";
+import { defineConfig } from "vite";
+
+// The runtime straight from the serde-postcard-ts checkout postcard-ts was installed from
+const runtime = "{{runtime}}";
+
+export default defineConfig({
+  resolve: {
+    alias: { "@variegated-coffee/serde-postcard-ts": runtime },
+  },
+  server: {
+    // Vite only serves files from outside the project when allowed to
+    fs: { allow: [".", dirname(runtime)] },
+  },
+});
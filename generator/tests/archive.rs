@@ -0,0 +1,87 @@
+// The derived types only exist to be described
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    read_archive, schema_hash, ArchiveError, ArchiveHeader, ArchiveWriter, PostcardTs,
+};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+struct Envelope<T> {
+    payload: T,
+}
+
+// Reading { sensor: 2, celsius: 22.5 } and { sensor: 3, celsius: -1.0 }
+const FIRST: [u8; 5] = [0x02, 0x00, 0x00, 0xb4, 0x41];
+const SECOND: [u8; 5] = [0x03, 0x00, 0x00, 0x80, 0xbf];
+
+fn archive() -> Vec<u8> {
+    let header = ArchiveHeader::of::<Reading>().unwrap();
+    let mut writer = ArchiveWriter::new(Vec::new(), &header).unwrap();
+    writer.write_record(&FIRST).unwrap();
+    writer.write_record(&SECOND).unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn reads_back_the_records_with_the_header() {
+    let archive = read_archive(&archive()).unwrap();
+    assert_eq!(archive.header.type_name, "Reading");
+    assert_eq!(
+        archive.header.schema_hash,
+        schema_hash::<Reading>().unwrap()
+    );
+    assert!(archive.header.ir.starts_with("{\n  \"version\": 1,"));
+    assert_eq!(archive.records, [FIRST.to_vec(), SECOND.to_vec()]);
+}
+
+#[test]
+fn writes_the_header_as_a_postcard_value() {
+    let bytes = archive();
+    // Magic, version, then the type name as a postcard string
+    assert_eq!(&bytes[..13], b"PCTA\x01\x07Reading");
+    // Each record is prefixed with its length
+    assert!(bytes.ends_with(&[&[5][..], &FIRST, &[5], &SECOND].concat()));
+}
+
+#[test]
+fn appends_to_an_archive() {
+    let mut writer = ArchiveWriter::append(archive());
+    writer.write_record(&FIRST).unwrap();
+    let archive = read_archive(&writer.into_inner()).unwrap();
+    assert_eq!(archive.records.len(), 3);
+}
+
+#[test]
+fn rejects_what_is_not_an_archive() {
+    let bytes = archive();
+    assert_eq!(read_archive(b"PCT"), Err(ArchiveError::NotAnArchive));
+    assert_eq!(
+        read_archive(&bytes[..bytes.len() - 1]),
+        Err(ArchiveError::Malformed)
+    );
+    assert_eq!(read_archive(&bytes[..20]), Err(ArchiveError::Malformed));
+    let mut newer = bytes.clone();
+    newer[4] = 2;
+    assert_eq!(
+        read_archive(&newer),
+        Err(ArchiveError::UnsupportedVersion(2))
+    );
+}
+
+#[test]
+fn archives_only_named_types() {
+    assert_eq!(
+        ArchiveHeader::of::<Envelope<u8>>(),
+        Err(ArchiveError::UnnamedType)
+    );
+    assert_eq!(
+        ArchiveHeader::of::<(u8, f32)>(),
+        Err(ArchiveError::UnnamedType)
+    );
+}
@@ -0,0 +1,40 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{build, Generator, Layout, PostcardTs, WriteOptions};
+use std::fs;
+use std::path::Path;
+
+#[derive(PostcardTs)]
+struct BuildStatus {
+    ok: bool,
+}
+
+#[test]
+fn watches_the_files_declaring_the_types() {
+    let files = build::source_files();
+    assert!(files.contains(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/build_script.rs")));
+    assert!(files.iter().all(|file| file.is_file()));
+}
+
+#[test]
+fn rewrites_only_changed_files() {
+    let dir = std::env::temp_dir().join(format!("serde-postcard-ts-build-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let report = build::emit(&dir).unwrap();
+    assert_eq!(report.written, [dir.join("index.ts")]);
+    assert!(fs::read_to_string(dir.join("index.ts"))
+        .unwrap()
+        .contains("export const BuildStatusSchema = struct({"));
+    let report = build::emit(&dir).unwrap();
+    assert!(report.written.is_empty());
+    assert_eq!(report.unchanged, [dir.join("index.ts")]);
+
+    let generator = Generator::new().banner("Regenerated by build.rs");
+    let report =
+        build::emit_with(&generator, Layout::Single, &dir, WriteOptions::default()).unwrap();
+    assert_eq!(report.written, [dir.join("index.ts")]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
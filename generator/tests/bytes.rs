@@ -0,0 +1,67 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{generate_all, schema_hash, Generator, PostcardTs};
+
+#[derive(PostcardTs)]
+struct Firmware {
+    #[serde(with = "serde_bytes")]
+    image: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    signature: Option<Vec<u8>>,
+    nonce: [u8; 4],
+    checksum: Vec<u8>,
+}
+
+#[derive(PostcardTs)]
+struct PlainFirmware {
+    image: Vec<u8>,
+    signature: Option<Vec<u8>>,
+    nonce: [u8; 4],
+    checksum: Vec<u8>,
+}
+
+#[derive(PostcardTs)]
+struct Blob(#[serde(with = "serde_bytes")] Vec<u8>);
+
+const EXPECTED: &str = r#"export const FirmwareSchema = struct({
+  image: bytes(),
+  signature: option(bytes()),
+  nonce: tuple(u8(), u8(), u8(), u8()),
+  checksum: seq(u8()),
+});
+export type Firmware = InferType<typeof FirmwareSchema>;
+"#;
+
+#[test]
+fn writes_serde_bytes_fields_as_bytes() {
+    let source = generate_all().unwrap();
+    assert!(source.contains(EXPECTED), "{}", source);
+}
+
+#[test]
+fn writes_every_u8_vec_as_bytes_when_asked() {
+    let source = Generator::new().u8_vecs_as_bytes().generate().unwrap();
+    assert!(source.contains("  checksum: bytes(),"));
+    // An array has no length on the wire, so it stays a tuple
+    assert!(source.contains("  nonce: tuple(u8(), u8(), u8(), u8()),"));
+}
+
+#[test]
+fn hashes_bytes_like_u8_vecs() {
+    assert_eq!(schema_hash::<Firmware>(), schema_hash::<PlainFirmware>());
+    assert_eq!(schema_hash::<Blob>(), schema_hash::<Vec<u8>>());
+}
+
+#[cfg(feature = "serde_bytes")]
+#[test]
+fn writes_serde_bytes_types_as_bytes() {
+    use serde_postcard_ts::{SerdeBytes, Shape};
+
+    assert_eq!(<serde_bytes::ByteBuf as PostcardTs>::shape(), Shape::Bytes);
+    assert_eq!(<&serde_bytes::Bytes as PostcardTs>::shape(), Shape::Bytes);
+    assert_eq!(
+        <Option<serde_bytes::ByteBuf> as SerdeBytes>::bytes_shape(),
+        Shape::Option(Box::new(Shape::Bytes))
+    );
+}
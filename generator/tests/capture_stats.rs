@@ -0,0 +1,261 @@
+// The derived types only exist to be summarised
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    read_capture, registered, CaptureError, CaptureStats, CapturedFrame, PostcardTs, StatsOptions,
+};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    samples: Vec<f32>,
+}
+
+#[derive(PostcardTs)]
+enum Telemetry {
+    Reading(Reading),
+    Ping,
+    Log { level: i16, text: String },
+}
+
+#[derive(PostcardTs)]
+struct Batch {
+    station: u8,
+    items: Vec<Telemetry>,
+}
+
+#[derive(PostcardTs)]
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+fn frame(time_ms: u64, data: &[u8]) -> CapturedFrame {
+    CapturedFrame {
+        time_ms,
+        data: data.to_vec(),
+    }
+}
+
+/// Sensor 1 sends two readings of one sample, sensor 2 one of two samples
+fn frames() -> Vec<CapturedFrame> {
+    vec![
+        frame(1000, &[0, 1, 1, 0, 0, 192, 63]),
+        frame(1010, &[1]),
+        frame(1020, &[0, 2, 2, 0, 0, 192, 63, 0, 0, 192, 63]),
+        frame(1030, &[0, 1, 1, 0, 0, 192, 63]),
+        frame(1100, &[2, 3, 2, b'h', b'i']),
+        frame(1110, &[1]),
+    ]
+}
+
+fn collect(frames: &[CapturedFrame], options: &StatsOptions) -> CaptureStats {
+    CaptureStats::collect(&registered(), "Telemetry", frames, options).unwrap()
+}
+
+#[test]
+fn reads_captures_as_the_flight_recorder_writes_them() {
+    // Version 1, two frames, the second at 2^14 ms
+    let data = [1, 2, 5, 1, 7, 0x80, 0x80, 0x01, 2, 8, 9];
+    assert_eq!(
+        read_capture(&data),
+        Ok(vec![frame(5, &[7]), frame(1 << 14, &[8, 9])])
+    );
+    assert_eq!(read_capture(&data[..10]), Err(CaptureError::Malformed));
+    assert_eq!(
+        read_capture(&[2, 0]),
+        Err(CaptureError::UnsupportedVersion(2))
+    );
+}
+
+#[test]
+fn counts_frames_and_bytes_per_variant() {
+    let stats = collect(&frames(), &StatsOptions::default());
+    assert_eq!(stats.frames, 6);
+    assert_eq!(stats.bytes, 32);
+    assert_eq!(stats.duration_ms, 110);
+
+    let kinds: Vec<(&str, usize, usize)> = stats
+        .kinds
+        .iter()
+        .map(|kind| (kind.kind.as_str(), kind.frames, kind.bytes))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("Telemetry::Reading", 3, 25),
+            ("Telemetry::Log", 1, 5),
+            ("Telemetry::Ping", 2, 2),
+        ]
+    );
+    let reading = &stats.kinds[0];
+    assert_eq!((reading.min_size, reading.max_size), (7, 11));
+    assert_eq!(reading.sizes.buckets, [(8, 2), (16, 1)]);
+    assert_eq!(
+        stats.sizes.buckets,
+        [(1, 2), (2, 0), (4, 0), (8, 3), (16, 1)]
+    );
+}
+
+#[test]
+fn measures_intervals_overall_and_per_variant() {
+    let stats = collect(&frames(), &StatsOptions::default());
+    let interval = stats.interval.unwrap();
+    assert_eq!((interval.min_ms, interval.max_ms), (10, 70));
+    assert_eq!((interval.p50_ms, interval.p95_ms), (10, 70));
+    assert_eq!(interval.mean_ms, 22.0);
+
+    let ping = stats
+        .kinds
+        .iter()
+        .find(|kind| kind.kind == "Telemetry::Ping")
+        .unwrap();
+    assert_eq!(
+        ping.interval.as_ref().map(|interval| interval.p50_ms),
+        Some(100)
+    );
+    let log = stats
+        .kinds
+        .iter()
+        .find(|kind| kind.kind == "Telemetry::Log")
+        .unwrap();
+    assert_eq!(log.interval, None);
+}
+
+#[test]
+fn ranks_talkers_by_bytes() {
+    let options = StatsOptions {
+        talker: Some("sensor".to_string()),
+        top: 1,
+    };
+    let (field, talkers) = collect(&frames(), &options).talkers.unwrap();
+    assert_eq!(field, "sensor");
+    assert_eq!(talkers.len(), 1);
+    assert_eq!(
+        (
+            talkers[0].value.as_str(),
+            talkers[0].frames,
+            talkers[0].bytes
+        ),
+        ("1", 2, 14)
+    );
+}
+
+#[test]
+fn breaks_down_bytes_by_field_and_nested_variant() {
+    let stats = collect(&frames(), &StatsOptions::default());
+    let fields = |kind: &str| -> Vec<(String, usize, usize)> {
+        stats
+            .kinds
+            .iter()
+            .find(|stats| stats.kind == kind)
+            .unwrap()
+            .fields
+            .iter()
+            .map(|field| (field.path.clone(), field.frames, field.bytes))
+            .collect()
+    };
+    assert_eq!(
+        fields("Telemetry::Reading"),
+        [("sensor".into(), 3, 3), ("samples".into(), 3, 19)]
+    );
+    assert_eq!(fields("Telemetry::Ping"), []);
+
+    // A Ping and a Log, then a Ping alone
+    let frames = [
+        frame(0, &[7, 2, 1, 2, 6, 2, b'h', b'i']),
+        frame(10, &[7, 1, 1]),
+    ];
+    let stats =
+        CaptureStats::collect(&registered(), "Batch", &frames, &StatsOptions::default()).unwrap();
+    let fields: Vec<(&str, usize, usize)> = stats.kinds[0]
+        .fields
+        .iter()
+        .map(|field| (field.path.as_str(), field.frames, field.bytes))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("station", 2, 2),
+            ("items", 2, 9),
+            ("items::Ping", 2, 2),
+            ("items::Log", 1, 5),
+            ("items::Log.level", 1, 1),
+            ("items::Log.text", 1, 3),
+        ]
+    );
+    let table = stats.table();
+    assert!(table.contains(
+        "\nBatch by field\n\
+         \x20 field       frames      bytes   share\n\
+         \x20 station          2          2   18.2%\n\
+         \x20 items            2          9   81.8%\n\
+         \x20   ::Ping         2          2   18.2%\n\
+         \x20   ::Log          1          5   45.5%\n\
+         \x20     level        1          1    9.1%\n\
+         \x20     text         1          3   27.3%\n"
+    ));
+    assert!(stats
+        .to_json()
+        .contains("{\"path\": \"items::Log.text\", \"frames\": 1, \"bytes\": 3}"));
+}
+
+#[test]
+fn counts_frames_that_do_not_decode() {
+    let frames = [
+        frame(0, &[1]),
+        frame(1, &[3]),
+        frame(2, &[1, 0]),
+        frame(3, &[0, 1, 4, 0]),
+    ];
+    let stats = collect(&frames, &StatsOptions::default());
+    assert_eq!(stats.kinds.len(), 1);
+    let errors: Vec<(&str, usize)> = stats
+        .errors
+        .iter()
+        .map(|(code, count)| (code.as_str(), *count))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            ("INVALID_VALUE", 1),
+            ("TRAILING_BYTES", 1),
+            ("UNEXPECTED_END", 1)
+        ]
+    );
+    assert!(stats.table().contains("TRAILING_BYTES"));
+}
+
+#[test]
+fn rejects_unknown_and_generic_types() {
+    let types = registered();
+    assert_eq!(
+        CaptureStats::collect(&types, "Missing", &[], &StatsOptions::default()),
+        Err(CaptureError::UnknownType("Missing".to_string()))
+    );
+    assert_eq!(
+        CaptureStats::collect(&types, "Pair", &[], &StatsOptions::default()),
+        Err(CaptureError::GenericType("Pair".to_string()))
+    );
+}
+
+#[test]
+fn writes_a_table_and_json() {
+    let options = StatsOptions {
+        talker: Some("sensor".to_string()),
+        top: 10,
+    };
+    let stats = collect(&frames(), &options);
+    let table = stats.table();
+    assert!(table.starts_with("Telemetry: 6 frames, 32 bytes over 0.1 s"));
+    assert!(table.contains("Telemetry::Reading        3         25   78.1%"));
+    assert!(table.contains("top talkers by sensor"));
+
+    let json = stats.to_json();
+    assert!(json.contains("\"type\": \"Telemetry\""));
+    assert!(json.contains(
+        "{\"kind\": \"Telemetry::Ping\", \"frames\": 2, \"bytes\": 2, \"minSize\": 1, \"maxSize\": 1"
+    ));
+    assert!(json.contains("\"talkers\": {\"field\": \"sensor\", \"top\": [{\"value\": \"1\""));
+    assert!(json.contains("\"errors\": {}"));
+}
@@ -0,0 +1,7 @@
+//! Attributes the derive cannot represent, and schema hashes pinned on types
+//! whose wire shape changed, fail to compile, with the reason
+
+#[test]
+fn rejected_attributes() {
+    trybuild::TestCases::new().compile_fail("tests/compile_fail/*.rs");
+}
@@ -0,0 +1,36 @@
+use serde_postcard_ts::PostcardTs;
+
+#[derive(PostcardTs)]
+struct Reading {
+    #[serde(with = "ts_millis")]
+    at: u64,
+}
+
+#[derive(PostcardTs)]
+struct Sent(#[serde(serialize_with = "ts_millis::serialize")] u64);
+
+#[derive(PostcardTs)]
+struct Received {
+    id: u8,
+    #[serde(deserialize_with = "ts_millis::deserialize")]
+    at: u64,
+}
+
+#[derive(PostcardTs)]
+#[serde(into = "u8", try_from = "u8")]
+enum Level {
+    Low,
+    High,
+}
+
+#[derive(PostcardTs)]
+#[serde(from = "u64")]
+struct Uptime(u64);
+
+#[derive(PostcardTs)]
+enum Event {
+    #[serde(with = "ts_millis")]
+    Boot(u64),
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use serde_postcard_ts::PostcardTs;
+
+#[derive(PostcardTs)]
+#[serde(untagged)]
+enum Untagged {
+    A(u8),
+    B(u16),
+}
+
+#[derive(PostcardTs)]
+#[serde(tag = "type")]
+enum Internal {
+    A { value: u8 },
+}
+
+#[derive(PostcardTs)]
+#[serde(content = "c", tag = "t")]
+enum Adjacent {
+    A(u8),
+}
+
+#[derive(PostcardTs)]
+enum Mixed {
+    A(u8),
+    #[serde(untagged)]
+    B(u16),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use serde_postcard_ts::PostcardTs;
+
+// Pinned while SetTarget still took a u16
+#[derive(PostcardTs)]
+#[postcard_ts(schema_hash = 0x0585_bd5c_4c77_ecd7)]
+enum Command {
+    Stop,
+    SetTarget(f32),
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+use serde_postcard_ts::PostcardTs;
+
+#[derive(PostcardTs)]
+struct Header {
+    version: u8,
+}
+
+#[derive(PostcardTs)]
+struct Packet {
+    #[serde(flatten)]
+    header: Header,
+    payload: Vec<u8>,
+}
+
+#[derive(PostcardTs)]
+#[serde(transparent)]
+struct Wrapper {
+    inner: u32,
+}
+
+#[derive(PostcardTs)]
+#[serde(field_identifier)]
+enum Selector {
+    Temperature,
+}
+
+fn main() {}
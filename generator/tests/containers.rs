@@ -0,0 +1,121 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{max_size, Generator, ModuleFormat, PostcardTs, Validator};
+
+#[derive(PostcardTs)]
+struct Heightmap {
+    origin: [i32; 2],
+    samples: [f32; 64],
+    tiles: [[u8; 20]; 20],
+}
+
+#[test]
+fn emits_long_arrays_with_the_array_builder() {
+    let source = Generator::new().generate().unwrap();
+    assert!(source.contains("  array,\n"));
+    assert!(source.contains(
+        "export const HeightmapSchema = struct({\n\
+         \x20 origin: tuple(i32(), i32()),\n\
+         \x20 samples: array(f32(), 64),\n\
+         \x20 tiles: array(array(u8(), 20), 20),\n\
+         });\n"
+    ));
+}
+
+#[test]
+fn declares_long_arrays_as_arrays() {
+    let files = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .generate_files(Default::default())
+        .unwrap();
+    let source = &files[0].source;
+    assert!(source.contains("  origin: [number, number];\n"));
+    assert!(source.contains("  samples: number[];\n"));
+    assert!(source.contains("  tiles: number[][];\n"));
+
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap();
+    assert!(source.contains("  samples: z.array(z.number().or(z.nan())).length(64),\n"));
+}
+
+#[test]
+fn sizes_arrays_as_their_items() {
+    assert_eq!(max_size::<Heightmap>(), Ok(2 * 5 + 64 * 4 + 20 * 20));
+}
+
+#[cfg(feature = "heapless")]
+mod heapless_containers {
+    use heapless::{FnvIndexMap, FnvIndexSet, LinearMap, String, Vec};
+    use serde_postcard_ts::{max_size, schema_hash, Generator, PostcardTs, Validator};
+
+    #[derive(PostcardTs)]
+    struct Inventory {
+        slots: Vec<u16, 8>,
+        tags: FnvIndexSet<u8, 4>,
+        names: FnvIndexMap<u8, String<12>, 16>,
+        counts: LinearMap<u8, u32, 3>,
+        history: Option<Vec<[u8; 2], 5>>,
+    }
+
+    #[derive(PostcardTs)]
+    struct UnboundedInventory {
+        slots: std::vec::Vec<u16>,
+        tags: std::collections::BTreeSet<u8>,
+        names: std::collections::BTreeMap<u8, std::string::String>,
+        counts: std::collections::BTreeMap<u8, u32>,
+        history: Option<std::vec::Vec<[u8; 2]>>,
+    }
+
+    #[test]
+    fn emits_the_capacity_as_a_max_length() {
+        let source = Generator::new().generate().unwrap();
+        assert!(source.contains(
+            "export const InventorySchema = struct({\n\
+             \x20 slots: seq(u16(), { maxLength: 8 }),\n\
+             \x20 tags: seq(u8(), { maxLength: 4 }),\n\
+             \x20 names: map(u8(), string({ maxBytes: 12 }), { maxLength: 16 }),\n\
+             \x20 counts: map(u8(), u32(), { maxLength: 3 }),\n\
+             \x20 history: option(seq(tuple(u8(), u8()), { maxLength: 5 })),\n\
+             });\n"
+        ));
+    }
+
+    #[test]
+    fn keeps_the_capacity_in_the_ir_and_validators() {
+        let ir = Generator::new().generate_ir().unwrap();
+        assert!(ir.contains(
+            r#"{"name": "slots", "type": {"kind": "seq", "item": {"kind": "u16"}, "maxLength": 8}}"#
+        ));
+        assert!(ir.contains(r#"{"kind": "u32"}, "maxLength": 3}}"#));
+
+        let source = Generator::new()
+            .with_validators(Validator::Zod)
+            .generate()
+            .unwrap();
+        assert!(source.contains("  slots: z.array(z.number().int().min(0).max(65535)).max(8),\n"));
+        assert!(source.contains(".refine((value) => value.size <= 3, \"more than 3 entries\"),\n"));
+    }
+
+    #[test]
+    fn sizes_and_hashes_containers_by_their_capacity() {
+        // The capacity is not on the wire
+        assert_eq!(
+            schema_hash::<Inventory>(),
+            schema_hash::<UnboundedInventory>()
+        );
+        assert_eq!(Ok(Inventory::SCHEMA_HASH), schema_hash::<Inventory>());
+        assert!(max_size::<UnboundedInventory>().is_err());
+        let slots = 1 + 8 * 3;
+        let tags = 1 + 4;
+        let names = 1 + 16 * (1 + 1 + 12);
+        let counts = 1 + 3 * (1 + 5);
+        let history = 1 + 1 + 5 * 2;
+        assert_eq!(
+            max_size::<Inventory>(),
+            Ok(slots + tags + names + counts + history)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use serde_postcard_ts::crc::{self, CrcAlgorithm};
+use serde_postcard_ts::{Definition, Field, Generator, Shape, TypeName};
+
+fn type_name(name: &'static str, params: &'static [&'static str]) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params,
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    vec![
+        (
+            type_name("Envelope", &["T"]),
+            Definition::Struct(vec![Field {
+                name: "payload",
+                shape: Shape::Param("T"),
+            }]),
+        ),
+        (
+            type_name("Reading", &[]),
+            Definition::Struct(vec![Field {
+                name: "sensor",
+                shape: Shape::U8,
+            }]),
+        ),
+    ]
+}
+
+#[test]
+fn wraps_non_generic_types() {
+    let source = Generator::new()
+        .crc(crc::CRC_32_ISCSI)
+        .generate_types(types())
+        .unwrap();
+
+    assert!(source.contains("  CRC_32_ISCSI,\n  Crc,\n  deserializeCrc,\n  serializeCrc,\n"));
+    assert!(source.contains("  type DeserializeOptions,\n  type InferType,\n"));
+    assert!(source.contains("\nconst crc = new Crc(CRC_32_ISCSI);\n"));
+    assert!(source.ends_with(
+        "export type Reading = InferType<typeof ReadingSchema>;\n\
+         export const serializeReadingCrc = (value: Reading): Uint8Array =>\n  \
+         serializeCrc(ReadingSchema, value, crc);\n\
+         export const deserializeReadingCrc = (\n  \
+         data: Uint8Array,\n  \
+         offset?: number,\n  \
+         options?: DeserializeOptions\n\
+         ) => deserializeCrc(ReadingSchema, data, crc, offset, options);\n"
+    ));
+    // A generic type has no schema to wrap until it is given type arguments
+    assert!(!source.contains("EnvelopeCrc"));
+}
+
+#[test]
+fn spells_out_algorithms_outside_the_catalog() {
+    let crc_16_usb = CrcAlgorithm {
+        width: 16,
+        poly: 0x8005,
+        init: 0xffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffff,
+        check: 0xb4c8,
+    };
+    let source = Generator::new()
+        .crc(crc_16_usb)
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains(
+        "const crc = new Crc({ width: 16, poly: 0x8005, init: 0xffff, refin: true, refout: true, xorout: 0xffff, check: 0xb4c8 });"
+    ));
+
+    // Parameters of 64-bit CRCs are written as bigints
+    let crc_64_go_iso = CrcAlgorithm {
+        width: 64,
+        poly: 0x1b,
+        init: 0xffffffffffffffff,
+        refin: true,
+        refout: true,
+        xorout: 0xffffffffffffffff,
+        check: 0xb90956c775a41001,
+    };
+    let source = Generator::new()
+        .crc(crc_64_go_iso)
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains("poly: 0x1bn, init: 0xffffffffffffffffn, "));
+}
+
+#[test]
+fn looks_up_catalog_names() {
+    assert_eq!(
+        CrcAlgorithm::named("CRC_16_XMODEM"),
+        Some(crc::CRC_16_XMODEM)
+    );
+    assert_eq!(crc::CRC_64_XZ.name(), Some("CRC_64_XZ"));
+    assert_eq!(CrcAlgorithm::named("CRC_32"), None);
+}
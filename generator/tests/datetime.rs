@@ -0,0 +1,149 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use serde_postcard_ts::{
+    max_size, schema_hash, Generator, ModuleFormat, PostcardTs, Shape, TimeRepresentation,
+    TsOverride, Validator,
+};
+
+#[derive(PostcardTs)]
+struct Lease {
+    length: Duration,
+    renewals: Vec<Duration>,
+}
+
+#[derive(PostcardTs)]
+struct Timeout {
+    after: Duration,
+}
+
+#[derive(PostcardTs)]
+struct Interval {
+    secs: u64,
+    nanos: u32,
+}
+
+#[test]
+fn declares_durations_with_the_runtime_builder() {
+    let source = Generator::new().generate().unwrap();
+    assert!(source.contains("  duration,\n"));
+    assert!(source.contains(
+        "export const DurationSchema = duration(\"date\");\n\
+         export type Duration = InferType<typeof DurationSchema>;\n"
+    ));
+    assert!(source.contains("  length: DurationSchema,"));
+    assert!(source.contains("  renewals: seq(DurationSchema),"));
+    // Declared once, before the types using it
+    assert_eq!(source.matches("export const DurationSchema").count(), 1);
+    assert!(source.find("DurationSchema =") < source.find("LeaseSchema ="));
+}
+
+#[test]
+fn decodes_to_bigint_nanoseconds_when_asked() {
+    let source = Generator::new()
+        .time_representation(TimeRepresentation::Nanos)
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const DurationSchema = duration(\"nanos\");"));
+}
+
+#[test]
+fn declares_built_in_types_only_when_used() {
+    let (interval, definition) = serde_postcard_ts::registered()
+        .into_iter()
+        .find(|(type_name, _)| type_name.name == "Interval")
+        .unwrap();
+    let source = Generator::new()
+        .generate_types(vec![(interval, definition)])
+        .unwrap();
+    assert!(!source.contains("Duration"));
+    assert!(!source.contains("duration"));
+}
+
+#[test]
+fn spells_out_the_type_in_declarations() {
+    let files = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .generate_files(Default::default())
+        .unwrap();
+    assert!(files[0].source.contains("export type Duration = number;\n"));
+
+    let files = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .time_representation(TimeRepresentation::Nanos)
+        .generate_files(Default::default())
+        .unwrap();
+    assert!(files[0].source.contains("export type Duration = bigint;\n"));
+}
+
+#[test]
+fn validates_the_decoded_values() {
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const DurationZod = z.number().nonnegative();"));
+    assert!(source.contains("  length: DurationZod,"));
+
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .time_representation(TimeRepresentation::Nanos)
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const DurationZod = z.bigint();"));
+}
+
+#[test]
+fn sizes_and_hashes_durations_by_their_wire_format() {
+    // A varint u64 and a varint u32
+    assert_eq!(max_size::<Timeout>(), Ok(10 + 5));
+    assert_eq!(schema_hash::<Timeout>(), schema_hash::<Interval>());
+    assert_eq!(Ok(Timeout::SCHEMA_HASH), schema_hash::<Timeout>());
+}
+
+#[test]
+fn lets_an_override_replace_a_built_in_type() {
+    let source = Generator::new()
+        .override_type::<Duration>(TsOverride::new(Shape::U64))
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const DurationSchema = u64();"));
+    assert!(!source.contains("duration("));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn declares_chrono_date_times() {
+    #[derive(PostcardTs)]
+    struct Appointment {
+        at: chrono::DateTime<chrono::Utc>,
+    }
+
+    let source = Generator::new().generate().unwrap();
+    assert!(source.contains("export const DateTimeSchema = chronoDateTime(\"date\");"));
+    assert!(source.contains("  at: DateTimeSchema,"));
+    // Written as its RFC 3339 text
+    assert_eq!(max_size::<Appointment>().ok(), None);
+    assert_eq!(Ok(Appointment::SCHEMA_HASH), schema_hash::<Appointment>());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn declares_time_offset_date_times() {
+    #[derive(PostcardTs)]
+    struct Shift {
+        starts: time::OffsetDateTime,
+    }
+
+    let source = Generator::new()
+        .time_representation(TimeRepresentation::Nanos)
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const OffsetDateTimeSchema = offsetDateTime(\"nanos\");"));
+    assert!(source.contains("  starts: OffsetDateTimeSchema,"));
+    // A zigzag i32, a varint u16, three u8, a varint u32 and three i8
+    assert_eq!(max_size::<Shift>(), Ok(5 + 3 + 3 + 5 + 3));
+    assert_eq!(Ok(Shift::SCHEMA_HASH), schema_hash::<Shift>());
+}
@@ -0,0 +1,78 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{Generator, Ident, Naming, PostcardTs};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[derive(PostcardTs)]
+struct Command {
+    led: bool,
+}
+
+#[test]
+fn emits_throwing_result_and_stream_decoders() {
+    let source = Generator::new()
+        .decoders()
+        .encode_only(["Command"])
+        .generate()
+        .unwrap();
+    for import in [
+        "deserializeValue",
+        "tryDeserializeValue",
+        "StreamDecoder",
+        "type DeserializeOptions",
+        "type StreamDecoderOptions",
+    ] {
+        assert!(source.contains(&format!("  {},\n", import)), "{}", import);
+    }
+    assert!(source.contains(
+        "export const decodeReading = (\n  \
+         data: Uint8Array,\n  \
+         offset?: number,\n  \
+         options?: DeserializeOptions\n\
+         ) => deserializeValue(ReadingSchema, data, offset, options);\n\
+         export const tryDecodeReading = (\n  \
+         data: Uint8Array,\n  \
+         offset?: number,\n  \
+         options?: DeserializeOptions\n\
+         ) => tryDeserializeValue(ReadingSchema, data, offset, options);\n\
+         export const createReadingStreamDecoder = (options?: StreamDecoderOptions) =>\n  \
+         new StreamDecoder(ReadingSchema, options);\n"
+    ));
+    // Nor generic types or those only encoded
+    assert!(!source.contains("decodePage"));
+    assert!(!source.contains("decodeCommand"));
+}
+
+#[test]
+fn names_the_decoders_with_the_templates() {
+    let source = Generator::new()
+        .decoders()
+        .naming(
+            Naming::new()
+                .template(Ident::TryDecode, "safeParse{name}")
+                .template(Ident::StreamDecoder, "{name}Stream"),
+        )
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const safeParseReading = (\n"));
+    assert!(source.contains("export const ReadingStream = (options?: StreamDecoderOptions) =>\n"));
+    assert!(!source.contains("tryDecodeReading"));
+}
+
+#[test]
+fn emits_no_decoders_by_default() {
+    let source = Generator::new().generate().unwrap();
+    assert!(!source.contains("DeserializeValue"));
+    assert!(!source.contains("StreamDecoder"));
+}
@@ -0,0 +1,248 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    generate_all, registered, Definition, Field, GenerateError, Generator, PostcardTs, Shape,
+    TypeName, Variant, VariantKind,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    samples: Vec<f32>,
+    location: Option<(f64, f64)>,
+    labels: HashMap<String, u64>,
+    cells: BTreeMap<(u8, u8), Mode>,
+    mode: Mode,
+    r#type: [u16; 2],
+}
+
+#[derive(PostcardTs)]
+enum Mode {
+    Idle,
+    Sample(u32),
+    Range(i32, i32),
+    Burst { count: u8, id: DeviceId },
+}
+
+#[derive(PostcardTs)]
+struct DeviceId(u64);
+
+#[derive(PostcardTs)]
+struct Pair(u8, char);
+
+#[derive(PostcardTs)]
+struct Heartbeat;
+
+#[derive(PostcardTs)]
+enum Never {}
+
+const EXPECTED: &str = r#"// Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.
+
+import {
+  char,
+  enumType,
+  f32,
+  f64,
+  i32,
+  map,
+  never,
+  newtypeStruct,
+  newtypeVariant,
+  option,
+  seq,
+  string,
+  struct,
+  structVariant,
+  tuple,
+  tupleStruct,
+  tupleVariant,
+  u16,
+  u32,
+  u64,
+  u8,
+  unitStruct,
+  unitVariant,
+  type InferType,
+} from "@variegated-coffee/serde-postcard-ts";
+
+export const DeviceIdSchema = newtypeStruct("DeviceId", u64());
+export type DeviceId = InferType<typeof DeviceIdSchema>;
+
+export const HeartbeatSchema = unitStruct("Heartbeat");
+export type Heartbeat = InferType<typeof HeartbeatSchema>;
+
+export const ModeSchema = enumType("Mode", {
+  Idle: unitVariant("Idle"),
+  Sample: newtypeVariant("Sample", u32()),
+  Range: tupleVariant("Range", i32(), i32()),
+  Burst: structVariant("Burst", { count: u8(), id: DeviceIdSchema }),
+});
+export type Mode = InferType<typeof ModeSchema>;
+
+export const NeverSchema = never();
+export type Never = InferType<typeof NeverSchema>;
+
+export const PairSchema = tupleStruct("Pair", u8(), char());
+export type Pair = InferType<typeof PairSchema>;
+
+export const ReadingSchema = struct({
+  sensor: u8(),
+  samples: seq(f32()),
+  location: option(tuple(f64(), f64())),
+  labels: map(string(), u64()),
+  cells: map(tuple(u8(), u8()), ModeSchema),
+  mode: ModeSchema,
+  type: tuple(u16(), u16()),
+});
+export type Reading = InferType<typeof ReadingSchema>;
+"#;
+
+#[test]
+fn generates_every_derived_type() {
+    assert_eq!(generate_all().unwrap(), EXPECTED);
+}
+
+#[test]
+fn registers_derived_types_by_module() {
+    let names: Vec<_> = registered()
+        .iter()
+        .map(|(type_name, _)| (type_name.name, type_name.module))
+        .collect();
+    assert_eq!(
+        names,
+        ["DeviceId", "Heartbeat", "Mode", "Never", "Pair", "Reading"].map(|name| (name, "derive"))
+    );
+    assert_eq!(
+        Reading::shape(),
+        Shape::Named(TypeName {
+            name: "Reading",
+            module: "derive",
+            params: &[],
+        })
+    );
+}
+
+#[test]
+fn imports_from_a_custom_module() {
+    let source = Generator::new()
+        .import_from("../src/index.js")
+        .generate()
+        .unwrap();
+    assert!(source.contains("} from \"../src/index.js\";\n"));
+}
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+#[test]
+fn rejects_duplicate_names() {
+    let types = vec![
+        (type_name("Config", "a"), Definition::UnitStruct),
+        (type_name("Config", "b"), Definition::UnitStruct),
+    ];
+    assert_eq!(
+        Generator::new().generate_types(types),
+        Err(GenerateError::DuplicateName {
+            name: "Config",
+            modules: ["a", "b"]
+        })
+    );
+}
+
+#[test]
+fn rejects_missing_types() {
+    let node = type_name("Node", "m");
+    let missing = vec![(
+        type_name("Holder", "m"),
+        Definition::NewtypeStruct(Shape::Named(node)),
+    )];
+    assert_eq!(
+        Generator::new().generate_types(missing),
+        Err(GenerateError::Missing {
+            name: "Node",
+            referenced_by: "Holder"
+        })
+    );
+}
+
+#[test]
+fn describes_recursive_types_as_ir() {
+    let node = type_name("Node", "m");
+    let types = vec![
+        (
+            node,
+            Definition::Struct(vec![
+                Field {
+                    name: "value",
+                    shape: Shape::U8,
+                },
+                Field {
+                    name: "next",
+                    shape: Shape::Option(Box::new(Shape::Named(node))),
+                },
+            ]),
+        ),
+        (
+            type_name("Command", "m"),
+            Definition::Enum(vec![
+                Variant {
+                    name: "Stop",
+                    kind: VariantKind::Unit,
+                },
+                Variant {
+                    name: "Move",
+                    kind: VariantKind::Tuple(vec![Shape::I32, Shape::String]),
+                },
+            ]),
+        ),
+    ];
+    assert_eq!(
+        Generator::new().generate_types_ir(types).unwrap(),
+        r#"{
+  "version": 1,
+  "types": [
+    {"name": "Node", "kind": "struct", "fields": [{"name": "value", "type": {"kind": "u8"}}, {"name": "next", "type": {"kind": "option", "inner": {"kind": "ref", "name": "Node"}}}]},
+    {"name": "Command", "kind": "enum", "variants": [{"name": "Stop", "kind": "unit"}, {"name": "Move", "kind": "tuple", "items": [{"kind": "i32"}, {"kind": "string"}]}]}
+  ]
+}
+"#
+    );
+}
+
+#[test]
+fn tags_enums_with_enum_tag() {
+    let generator = Generator::new().enum_tag("kind");
+    let source = generator.generate().unwrap();
+    assert!(source.contains(
+        "export const ModeSchema = discriminated(enumType(\"Mode\", {\n  Idle: unitVariant(\"Idle\"),\n"
+    ));
+    assert!(source.contains("}), \"kind\");\nexport type Mode = InferType<typeof ModeSchema>;\n"));
+    // Uninhabited enums have no variants to tag
+    assert!(source.contains("export const NeverSchema = never();\n"));
+
+    let clash = vec![(
+        type_name("Command", "m"),
+        Definition::Enum(vec![Variant {
+            name: "Move",
+            kind: VariantKind::Struct(vec![Field {
+                name: "kind",
+                shape: Shape::U8,
+            }]),
+        }]),
+    )];
+    assert_eq!(
+        generator.generate_types(clash),
+        Err(GenerateError::TagClash {
+            name: "Command",
+            variant: "Move",
+            tag: "kind".to_string()
+        })
+    );
+}
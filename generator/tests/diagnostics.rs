@@ -0,0 +1,103 @@
+// The derived types only exist to be linted
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    diagnostics, lint_with, Definition, Diagnostic, GenerateError, Generator, Layout, LintConfig,
+    PostcardTs, Severity, Shape, TypeName, WriteOptions,
+};
+
+#[derive(PostcardTs)]
+struct Account {
+    owner_id: u64,
+}
+
+fn account_lint(config: &LintConfig) -> Diagnostic {
+    let lint = lint_with(config)
+        .into_iter()
+        .find(|lint| lint.type_name.name == "Account")
+        .unwrap();
+    Diagnostic::lint(&lint)
+}
+
+#[test]
+fn records_lints_with_their_span_and_fix() {
+    let diagnostic = account_lint(&LintConfig::new());
+    assert_eq!(diagnostic.code, "bigint_id");
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(
+        diagnostic.fix.as_deref(),
+        Some("make the id a u32, or allow bigint_id if it needs 64 bits")
+    );
+    let json = diagnostic.to_json();
+    assert!(json.starts_with(
+        r#"{"kind": "diagnostic", "version": 1, "code": "bigint_id", "severity": "warning", "message": "Account.owner_id is a u64"#
+    ));
+    assert!(json.ends_with(
+        r#"tests/diagnostics.rs", "line": 10, "column": 8}, "fix": "make the id a u32, or allow bigint_id if it needs 64 bits"}"#
+    ));
+
+    let denied = account_lint(&LintConfig::new().deny("bigint_id"));
+    assert_eq!(denied.severity, Severity::Error);
+}
+
+#[test]
+fn records_generator_errors_by_code() {
+    let missing = GenerateError::Missing {
+        name: "Reading",
+        referenced_by: "Frame",
+    };
+    assert_eq!(missing.code(), "missing");
+    assert_eq!(
+        Diagnostic::error(&missing).to_json(),
+        r#"{"kind": "diagnostic", "version": 1, "code": "missing", "severity": "error", "message": "Frame refers to Reading, which does not derive PostcardTs", "span": null, "fix": "derive PostcardTs for Reading, or declare it with Generator::override_type"}"#
+    );
+    let example = GenerateError::UnmatchedExample("Calibration".to_string());
+    assert_eq!(Diagnostic::error(&example).fix, None);
+}
+
+#[test]
+fn generates_after_the_lint_records() {
+    let session = TypeName {
+        name: "SessionId",
+        module: "app",
+        params: &[],
+    };
+    let types = vec![(session, Definition::NewtypeStruct(Shape::U64))];
+    let dir = std::env::temp_dir().join(format!("postcard-ts-diagnostics-{}", std::process::id()));
+    let generate = |lints: &LintConfig| {
+        diagnostics::generate(
+            &Generator::new(),
+            types.clone(),
+            Layout::Single,
+            &dir,
+            WriteOptions::default(),
+            lints,
+        )
+    };
+
+    assert!(generate(&LintConfig::new().deny("bigint_id")).is_err());
+    assert!(!dir.exists());
+    let records = generate(&LintConfig::new().allow("bigint_id")).unwrap();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].starts_with(r#"{"kind": "written", "version": 1, "written": ["#));
+    assert!(dir.join("index.ts").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let missing = TypeName {
+        name: "Missing",
+        module: "app",
+        params: &[],
+    };
+    let records = diagnostics::generate(
+        &Generator::new(),
+        vec![(session, Definition::NewtypeStruct(Shape::Named(missing)))],
+        Layout::Single,
+        &dir,
+        WriteOptions::default(),
+        &LintConfig::new(),
+    )
+    .unwrap_err();
+    assert_eq!(records.len(), 1);
+    assert!(records[0].contains(r#""code": "missing", "severity": "error""#));
+    assert!(!dir.exists());
+}
@@ -0,0 +1,140 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    crc, Definition, GenerateError, Generator, PostcardTs, Shape, TsOverride, TypeName,
+};
+
+#[derive(PostcardTs)]
+#[postcard_ts(encode_only)]
+struct SetTarget {
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+#[postcard_ts(decode_only)]
+struct Reading {
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+struct Calibration {
+    offset: f32,
+}
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    vec![
+        (
+            type_name("Command", "app::control"),
+            Definition::NewtypeStruct(Shape::U8),
+        ),
+        (
+            type_name("Status", "app::telemetry"),
+            Definition::NewtypeStruct(Shape::U8),
+        ),
+        (
+            type_name("Label", "app"),
+            Definition::NewtypeStruct(Shape::String),
+        ),
+    ]
+}
+
+#[test]
+fn limits_derived_types_to_their_direction() {
+    let source = Generator::new().generate().unwrap();
+
+    assert!(source.contains("  decodeOnly,\n  encodeOnly,\n"));
+    assert!(source
+        .contains("export const SetTargetSchema = encodeOnly(struct({\n  celsius: f32(),\n}));\n"));
+    assert!(source
+        .contains("export const ReadingSchema = decodeOnly(struct({\n  celsius: f32(),\n}));\n"));
+    assert!(source.contains("export const CalibrationSchema = struct({\n"));
+}
+
+#[test]
+fn limits_types_matched_by_patterns() {
+    let source = Generator::new()
+        .encode_only(["app::control::*"])
+        .decode_only(["Status"])
+        .generate_types(types())
+        .unwrap();
+
+    assert!(source
+        .contains("export const CommandSchema = encodeOnly(newtypeStruct(\"Command\", u8()));\n"));
+    assert!(source
+        .contains("export const StatusSchema = decodeOnly(newtypeStruct(\"Status\", u8()));\n"));
+    assert!(source.contains("export const LabelSchema = newtypeStruct(\"Label\", string());\n"));
+}
+
+#[test]
+fn generates_only_the_crc_wrapper_of_the_direction() {
+    let source = Generator::new()
+        .crc(crc::CRC_32_ISCSI)
+        .decode_only(["Status"])
+        .generate_types(vec![types().remove(1)])
+        .unwrap();
+
+    assert!(source.contains("\nconst crc = new Crc(CRC_32_ISCSI);\n"));
+    assert!(source.contains("export const deserializeStatusCrc = (\n"));
+    assert!(!source.contains("export const serializeStatusCrc"));
+    assert!(!source.contains("  serializeCrc,\n"));
+}
+
+#[test]
+fn limits_overridden_types() {
+    let source = Generator::new()
+        .override_type::<Calibration>(TsOverride::new(Shape::I16).ts_type("number").codec(
+            "(raw: number) => raw / 100",
+            "(value: number) => Math.round(value * 100)",
+        ))
+        .decode_only(["Calibration"])
+        .generate()
+        .unwrap();
+
+    assert!(source.contains("export const CalibrationSchema = decodeOnly(transform(i16(), {\n"));
+    assert!(source.contains("  encode: (value: Calibration) => ((value: number) => Math.round(value * 100))(value),\n}));\n"));
+}
+
+#[test]
+fn rejects_conflicting_directions() {
+    let error = Generator::new()
+        .decode_only(["SetTarget"])
+        .generate()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::DirectionConflict { name: "SetTarget" }
+    );
+    assert_eq!(
+        error.to_string(),
+        "SetTarget is marked both encode-only and decode-only"
+    );
+
+    let error = Generator::new()
+        .encode_only(["Command"])
+        .decode_only(["app::control::*"])
+        .generate_types(types())
+        .unwrap_err();
+    assert_eq!(error, GenerateError::DirectionConflict { name: "Command" });
+}
+
+#[test]
+fn rejects_patterns_matching_no_type() {
+    let error = Generator::new()
+        .encode_only(["app::ctrl::*"])
+        .generate_types(types())
+        .unwrap_err();
+
+    assert_eq!(
+        error,
+        GenerateError::UnmatchedDirectionPattern("app::ctrl::*".to_string())
+    );
+}
@@ -0,0 +1,111 @@
+use serde_postcard_ts::{
+    Definition, Field, GeneratedFile, Generator, Layout, ModuleFormat, Shape, TypeName, Variant,
+    VariantKind,
+};
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn unit(name: &'static str) -> Variant {
+    Variant {
+        name,
+        kind: VariantKind::Unit,
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    let action = type_name("PlayerAction", "game");
+    vec![
+        (action, Definition::Enum(vec![unit("Jump"), unit("Duck")])),
+        (
+            type_name("GameEvent", "game"),
+            Definition::Enum(vec![
+                Variant {
+                    name: "PlayerAction",
+                    kind: VariantKind::Newtype(Shape::Named(action)),
+                },
+                Variant {
+                    name: "EnemySpawn",
+                    kind: VariantKind::Struct(vec![Field {
+                        name: "count",
+                        shape: Shape::U16,
+                    }]),
+                },
+            ]),
+        ),
+        (
+            type_name("Command", "game"),
+            Definition::Enum(vec![unit("Pause")]),
+        ),
+        (
+            TypeName {
+                name: "Reply",
+                module: "game",
+                params: &["T"],
+            },
+            Definition::Enum(vec![Variant {
+                name: "Ok",
+                kind: VariantKind::Newtype(Shape::Param("T")),
+            }]),
+        ),
+    ]
+}
+
+fn dispatchers(generator: Generator, layout: Layout) -> Option<GeneratedFile> {
+    generator
+        .encode_only(["Command"])
+        .generate_types_files(types(), layout)
+        .unwrap()
+        .into_iter()
+        .find(|file| file.path.starts_with("dispatchers."))
+}
+
+#[test]
+fn writes_a_dispatcher_per_top_level_enum() {
+    let file = dispatchers(Generator::new().dispatchers(), Layout::Single).unwrap();
+    assert_eq!(file.path, "dispatchers.ts");
+    // Not the enum inside GameEvent, the encode-only one or the generic one
+    assert!(file.source.ends_with(
+        "import { MessageDispatcher, type MessageDispatcherOptions } from \"@variegated-coffee/serde-postcard-ts\";\n\
+         import {\n  \
+         GameEventSchema,\n\
+         } from \"./index.js\";\n\
+         \n\
+         /**\n \
+         * Routes GameEvent messages from a stream to handlers by variant: PlayerAction, EnemySpawn\n \
+         */\n\
+         export class GameEventDispatcher extends MessageDispatcher<typeof GameEventSchema, \"type\"> {\n  \
+         constructor(options: Omit<MessageDispatcherOptions<\"type\">, \"tag\"> = {}) {\n    \
+         super(GameEventSchema, { ...options, tag: \"type\" });\n  \
+         }\n\
+         }\n"
+    ));
+}
+
+#[test]
+fn dispatches_by_the_enum_tag() {
+    let file = dispatchers(
+        Generator::new().dispatchers().enum_tag("kind"),
+        Layout::Types,
+    )
+    .unwrap();
+    assert!(file.source.contains(
+        "extends MessageDispatcher<typeof GameEventSchema, \"kind\"> {\n  \
+         constructor(options: Omit<MessageDispatcherOptions<\"kind\">, \"tag\"> = {}) {\n    \
+         super(GameEventSchema, { ...options, tag: \"kind\" });"
+    ));
+}
+
+#[test]
+fn writes_no_dispatchers_unless_asked() {
+    assert!(dispatchers(Generator::new(), Layout::Single).is_none());
+    let declarations = Generator::new()
+        .dispatchers()
+        .module_format(ModuleFormat::Declarations);
+    assert!(dispatchers(declarations, Layout::Modules).is_none());
+}
@@ -0,0 +1,58 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{Generator, Ident, Naming, PostcardTs};
+
+#[derive(PostcardTs)]
+enum Phase {
+    Idle,
+    #[postcard_ts(display = "Dark Lord phase")]
+    DarkLord,
+    /// The boss is down
+    #[postcard_ts(display = "Victory \"lap\"")]
+    VictoryLap {
+        laps: u8,
+    },
+    HTTPError(u16),
+}
+
+#[derive(PostcardTs)]
+#[serde(rename_all = "snake_case")]
+enum Difficulty {
+    VeryHard,
+    #[serde(rename = "SHOUTING")]
+    Loud,
+}
+
+#[test]
+fn emits_a_table_per_enum() {
+    let source = Generator::new().display_names().generate().unwrap();
+    assert!(source.contains(
+        "export const PhaseDisplayNames = {\n  \
+           Idle: \"Idle\",\n  \
+           DarkLord: \"Dark Lord phase\",\n  \
+           VictoryLap: \"Victory \\\"lap\\\"\",\n  \
+           HTTPError: \"HTTP error\",\n\
+         } as const;\n"
+    ));
+    assert!(source.contains(
+        "export const DifficultyDisplayNames = {\n  \
+           very_hard: \"Very hard\",\n  \
+           SHOUTING: \"Shouting\",\n\
+         } as const;\n"
+    ));
+    // The display name leaves the doc comment alone
+    assert!(source.contains("  /** The boss is down */\n"));
+}
+
+#[test]
+fn is_left_out_by_default_and_follows_naming() {
+    let source = Generator::new().generate().unwrap();
+    assert!(!source.contains("DisplayNames"));
+    let source = Generator::new()
+        .display_names()
+        .naming(Naming::new().template(Ident::DisplayNames, "{NAME}_LABELS"))
+        .generate()
+        .unwrap();
+    assert!(source.contains("export const PHASE_LABELS = {\n"));
+}
@@ -0,0 +1,115 @@
+// The derived types only exist to be generated
+#![allow(dead_code, deprecated)]
+
+use serde_postcard_ts::{generate_all, Generator, ModuleFormat, PostcardTs};
+
+/// One reading of a sensor.
+///
+/// Sent every `interval` milliseconds, and never written with a `*/` in it.
+#[derive(PostcardTs)]
+struct Sample {
+    /// The sensor's index on the bus
+    sensor: u8,
+    #[serde(rename = "value")]
+    /// Degrees Celsius
+    celsius: f32,
+    #[deprecated(since = "0.3.0", note = "read `sensor` instead")]
+    channel: u8,
+}
+
+/// What the dashboard asks of the device
+#[derive(PostcardTs)]
+enum Request {
+    /// Stop sampling
+    Stop,
+    Start {
+        /// Milliseconds between samples
+        interval: u16,
+        count: u8,
+    },
+    #[deprecated]
+    Reset,
+}
+
+#[deprecated = "send `Request::Start` instead"]
+#[derive(PostcardTs)]
+struct LegacyStart(u16);
+
+#[test]
+fn writes_doc_comments_as_jsdoc() {
+    let source = generate_all().unwrap();
+    let sample = "/**\n \
+         * One reading of a sensor.\n \
+         *\n \
+         * Sent every `interval` milliseconds, and never written with a `*\\/` in it.\n \
+         */\n";
+    assert!(source.contains(&format!(
+        "{sample}export const SampleSchema = struct({{\n  \
+         /** The sensor's index on the bus */\n  \
+         sensor: u8(),\n  \
+         /** Degrees Celsius */\n  \
+         value: f32(),\n  \
+         /** @deprecated read `sensor` instead (since 0.3.0) */\n  \
+         channel: u8(),\n\
+         }});\n\
+         {sample}export type Sample = "
+    )));
+
+    assert!(source.contains(
+        "/** What the dashboard asks of the device */\n\
+         export const RequestSchema = enumType(\"Request\", {\n  \
+         /** Stop sampling */\n  \
+         Stop: unitVariant(\"Stop\"),\n  \
+         Start: structVariant(\"Start\", {\n    \
+         /** Milliseconds between samples */\n    \
+         interval: u16(),\n    \
+         count: u8(),\n  \
+         }),\n  \
+         /** @deprecated */\n  \
+         Reset: unitVariant(\"Reset\"),\n\
+         });\n"
+    ));
+
+    assert!(source.contains(
+        "/** @deprecated send `Request::Start` instead */\n\
+         export const LegacyStartSchema = newtypeStruct(\"LegacyStart\", u16());\n\
+         /** @deprecated send `Request::Start` instead */\n\
+         export type LegacyStart = "
+    ));
+}
+
+#[test]
+fn documents_declared_fields() {
+    let source = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .generate()
+        .unwrap();
+    assert!(source.contains(
+        "export interface Sample {\n  \
+         /** The sensor's index on the bus */\n  \
+         sensor: number;\n  \
+         /** Degrees Celsius */\n  \
+         value: number;\n"
+    ));
+}
+
+#[test]
+fn puts_doc_comments_before_typedoc_summaries() {
+    let source = Generator::new().typedoc().generate().unwrap();
+    assert!(source.contains(
+        "/**\n \
+         * What the dashboard asks of the device\n \
+         *\n \
+         * Rust `docs::Request`, an enum of 3 variants (Stop, Start, Reset),"
+    ));
+    assert!(source.contains(
+        "/**\n \
+         * The schema of {@link LegacyStart}.\n \
+         *\n \
+         * @deprecated send `Request::Start` instead\n \
+         *\n \
+         * @category docs\n \
+         */\n\
+         export const LegacyStartSchema"
+    ));
+}
@@ -0,0 +1,78 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{GenerateError, Generator, PostcardTs, ProtocolFeature};
+
+#[derive(PostcardTs)]
+enum Command {
+    Blink(u8),
+    Update(u32),
+}
+
+#[derive(PostcardTs)]
+struct FirmwareChunk {
+    offset: u32,
+    data: Vec<u8>,
+}
+
+fn firmware_update() -> ProtocolFeature {
+    ProtocolFeature::new("firmware_update", 0)
+        .messages(["Command::Update", "FirmwareChunk"])
+        .doc("Accepts firmware over the air")
+}
+
+#[test]
+fn declares_the_features_in_typescript_and_rust() {
+    let generator = Generator::new()
+        .feature(firmware_update())
+        .feature(ProtocolFeature::new("logs", 5));
+    let source = generator.generate().unwrap();
+    assert!(source.ends_with(
+        "export const PROTOCOL_FEATURES = {\n  \
+           /** Accepts firmware over the air */\n  \
+           firmware_update: { bit: 0, messages: [\"Command::Update\", \"FirmwareChunk\"] },\n  \
+           logs: { bit: 5, messages: [] },\n\
+         } as const;\n"
+    ));
+    assert!(generator.generate_rust_constants().unwrap().ends_with(
+        "\n/// Accepts firmware over the air\n\
+         pub const FEATURE_FIRMWARE_UPDATE: u64 = 1 << 0;\n\
+         \n\
+         pub const FEATURE_LOGS: u64 = 1 << 5;\n"
+    ));
+}
+
+#[test]
+fn rejects_invalid_features() {
+    let cases = [
+        (
+            ProtocolFeature::new("FirmwareUpdate", 0),
+            "invalid protocol feature \"FirmwareUpdate\": \
+             use lowercase letters, digits and _, starting with a letter",
+        ),
+        (
+            ProtocolFeature::new("late", 64),
+            "invalid protocol feature \"late\": bit 64 is outside the 64-bit mask",
+        ),
+        (
+            ProtocolFeature::new("update", 0).messages(["Command::Reboot"]),
+            "invalid protocol feature \"update\": \
+             message Command::Reboot names no type or enum variant",
+        ),
+    ];
+    for (feature, message) in cases {
+        let error = Generator::new().feature(feature).generate().unwrap_err();
+        assert_eq!(error.code(), "invalid_feature");
+        assert_eq!(error.to_string(), message);
+    }
+
+    let error = Generator::new()
+        .feature(firmware_update())
+        .feature(ProtocolFeature::new("logs", 0))
+        .generate_rust_constants()
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        GenerateError::InvalidFeature { name, .. } if name == "logs"
+    ));
+}
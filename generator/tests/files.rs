@@ -0,0 +1,315 @@
+use serde_postcard_ts::{
+    write_files, Definition, Field, GenerateError, GeneratedFile, Generator, ImportExtension,
+    Layout, ModuleFormat, Shape, TypeName, WriteOptions,
+};
+use std::fs;
+use std::path::PathBuf;
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn newtype(name: TypeName, inner: Shape) -> (TypeName, Definition) {
+    (name, Definition::NewtypeStruct(inner))
+}
+
+fn files(layout: Layout) -> Result<Vec<GeneratedFile>, GenerateError> {
+    Generator::new()
+        .import_from("./runtime.js")
+        .generate_types_files(types(), layout)
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    let id = type_name("DeviceId", "app::ids");
+    let reading = type_name("Reading", "app::proto::sensors");
+    vec![
+        newtype(id, Shape::U64),
+        newtype(reading, Shape::Named(id)),
+        newtype(
+            type_name("Frame", "app"),
+            Shape::Seq(Box::new(Shape::Named(reading))),
+        ),
+    ]
+}
+
+fn paths(files: &[GeneratedFile]) -> Vec<&str> {
+    files.iter().map(|file| file.path.as_str()).collect()
+}
+
+#[test]
+fn lays_out_one_file_per_module() {
+    let files = files(Layout::Modules).unwrap();
+    assert_eq!(
+        paths(&files),
+        ["app.ts", "app/ids.ts", "app/proto/sensors.ts", "index.ts"]
+    );
+
+    assert!(files[0].source.contains("} from \"./runtime.js\";\n"));
+    assert!(files[0]
+        .source
+        .contains("import { ReadingSchema } from \"./app/proto/sensors.js\";\n"));
+    assert!(files[2].source.contains("} from \"../../runtime.js\";\n"));
+    assert!(files[2]
+        .source
+        .contains("import { DeviceIdSchema } from \"../ids.js\";\n"));
+    assert!(files[3].source.ends_with(
+        "export * from \"./app.js\";\nexport * from \"./app/ids.js\";\nexport * from \"./app/proto/sensors.js\";\n"
+    ));
+}
+
+#[test]
+fn lays_out_a_single_file() {
+    let files = files(Layout::Single).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "index.ts");
+    assert!(!files[0].source.contains("import { "));
+}
+
+#[test]
+fn rejects_modules_importing_each_other() {
+    let a = type_name("A", "m::a");
+    let b = type_name("B", "m::b");
+    let types = vec![
+        newtype(a, Shape::U8),
+        newtype(b, Shape::Named(a)),
+        newtype(type_name("C", "m::a"), Shape::Named(b)),
+    ];
+    assert_eq!(
+        Generator::new().generate_types_files(types.clone(), Layout::Modules),
+        Err(GenerateError::ModuleCycle(vec!["m::a", "m::b", "m::a"]))
+    );
+    assert!(Generator::new()
+        .generate_types_files(types, Layout::Single)
+        .is_ok());
+}
+
+#[test]
+fn lays_out_one_file_per_type() {
+    let files = files(Layout::Types).unwrap();
+    assert_eq!(
+        paths(&files),
+        [
+            "types/DeviceId.ts",
+            "types/Frame.ts",
+            "types/Reading.ts",
+            "index.ts"
+        ]
+    );
+    assert!(files[1].source.contains("} from \"../runtime.js\";\n"));
+    assert!(files[1]
+        .source
+        .contains("import { ReadingSchema } from \"./Reading.js\";\n"));
+    assert!(files[3]
+        .source
+        .ends_with("export * from \"./types/Reading.js\";\n"));
+}
+
+#[test]
+fn puts_types_referring_to_each_other_in_one_file() {
+    let id = type_name("Id", "app");
+    let statement = type_name("Statement", "app");
+    let block = type_name("Block", "app");
+    let types = vec![
+        newtype(id, Shape::U32),
+        (
+            statement,
+            Definition::Struct(vec![
+                Field {
+                    name: "id",
+                    shape: Shape::Named(id),
+                },
+                Field {
+                    name: "body",
+                    shape: Shape::Option(Box::new(Shape::Named(block))),
+                },
+            ]),
+        ),
+        newtype(block, Shape::Seq(Box::new(Shape::Named(statement)))),
+    ];
+    let files = Generator::new()
+        .generate_types_files(types, Layout::Types)
+        .unwrap();
+    assert_eq!(
+        paths(&files),
+        ["types/Id.ts", "types/Statement.ts", "index.ts"]
+    );
+    // The spelled-out types name the imported type, not just its schema
+    assert!(files[1]
+        .source
+        .contains("import { IdSchema, type Id } from \"./Id.js\";\n"));
+    assert!(files[1]
+        .source
+        .contains("export interface Statement {\n  id: Id;\n"));
+    assert!(files[1]
+        .source
+        .contains("export type Block = Statement[];\n"));
+}
+
+#[test]
+fn generates_commonjs_modules() {
+    let files = Generator::new()
+        .module_format(ModuleFormat::CommonJs)
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    assert_eq!(
+        paths(&files),
+        [
+            "app.cts",
+            "app/ids.cts",
+            "app/proto/sensors.cts",
+            "index.cts"
+        ]
+    );
+    assert!(files[0]
+        .source
+        .contains("import { ReadingSchema } from \"./app/proto/sensors.cjs\";\n"));
+    assert!(files[3]
+        .source
+        .contains("export * from \"./app/ids.cjs\";\n"));
+}
+
+#[test]
+fn names_imported_files_by_import_extension() {
+    let import = |extension, format| {
+        let files = Generator::new()
+            .module_format(format)
+            .import_extension(extension)
+            .generate_types_files(types(), Layout::Modules)
+            .unwrap();
+        let line = files[2].source.lines().find(|line| line.contains("ids"));
+        line.unwrap().to_string()
+    };
+    assert_eq!(
+        import(ImportExtension::Ts, ModuleFormat::Esm),
+        "import { DeviceIdSchema } from \"../ids.ts\";"
+    );
+    assert_eq!(
+        import(ImportExtension::Ts, ModuleFormat::CommonJs),
+        "import { DeviceIdSchema } from \"../ids.cts\";"
+    );
+    assert_eq!(
+        import(ImportExtension::None, ModuleFormat::Esm),
+        "import { DeviceIdSchema } from \"../ids\";"
+    );
+    assert_eq!(
+        import(ImportExtension::Ts, ModuleFormat::Declarations),
+        "import type { DeviceId } from \"../ids.js\";"
+    );
+}
+
+#[test]
+fn generates_declarations_only() {
+    let files = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .max_sizes()
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    assert_eq!(
+        paths(&files),
+        [
+            "app.d.ts",
+            "app/ids.d.ts",
+            "app/proto/sensors.d.ts",
+            "index.d.ts"
+        ]
+    );
+    assert_eq!(
+        files[0].source,
+        "// Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.\n\
+         \n\
+         import type { Reading } from \"./app/proto/sensors.js\";\n\
+         \n\
+         export type Frame = Reading[];\n"
+    );
+    assert!(files[1]
+        .source
+        .ends_with("\nexport type DeviceId = bigint;\n"));
+    assert!(files
+        .iter()
+        .all(|file| !file.source.contains("Schema") && !file.source.contains("MAX_SIZE")));
+}
+
+#[test]
+fn puts_the_banner_after_the_header() {
+    let files = Generator::new()
+        .banner("Copyright Example Corp.\n\nSPDX-License-Identifier: MIT")
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    for file in &files {
+        assert!(file.source.starts_with(
+            "// Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.\n\
+             // Copyright Example Corp.\n\
+             //\n\
+             // SPDX-License-Identifier: MIT\n\
+             \n"
+        ));
+    }
+}
+
+fn scratch(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("serde-postcard-ts-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn writes_cleans_and_protects_files() {
+    let dir = scratch("write");
+    let modules = files(Layout::Modules).unwrap();
+    let report = write_files(&dir, &modules, WriteOptions::default()).unwrap();
+    assert_eq!(report.written.len(), 4);
+    assert!(dir.join("app/proto/sensors.ts").exists());
+
+    // Unchanged files are not rewritten; stale ones stay unless cleaning
+    let single = files(Layout::Single).unwrap();
+    let report = write_files(&dir, &single, WriteOptions::default()).unwrap();
+    assert_eq!(report.written, [dir.join("index.ts")]);
+    assert!(dir.join("app.ts").exists());
+    let report = write_files(
+        &dir,
+        &single,
+        WriteOptions {
+            clean: true,
+            overwrite: false,
+        },
+    )
+    .unwrap();
+    assert_eq!(report.unchanged, [dir.join("index.ts")]);
+    assert_eq!(report.removed.len(), 3);
+    assert!(!dir.join("app.ts").exists());
+
+    // Hand-written files are neither replaced nor cleaned up by default
+    fs::write(dir.join("index.ts"), "export {};\n").unwrap();
+    fs::write(dir.join("extra.ts"), "export {};\n").unwrap();
+    let error = write_files(
+        &dir,
+        &single,
+        WriteOptions {
+            clean: true,
+            overwrite: false,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+    write_files(
+        &dir,
+        &single,
+        WriteOptions {
+            clean: true,
+            overwrite: true,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("index.ts")).unwrap(),
+        single[0].source
+    );
+    assert!(dir.join("extra.ts").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,106 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{generate_all, registered, Generator, PostcardTs, Shape};
+use std::collections::BTreeMap;
+
+#[derive(PostcardTs)]
+struct Envelope<T> {
+    seq: u32,
+    payload: T,
+}
+
+#[derive(PostcardTs)]
+enum Reply<T, E: Clone> {
+    Ok(T),
+    Busy,
+    Failed { error: E, retries: Vec<T> },
+}
+
+#[derive(PostcardTs)]
+struct Table<K, V>(BTreeMap<K, V>)
+where
+    K: Ord;
+
+#[derive(PostcardTs)]
+struct Status {
+    current: Envelope<Reply<u16, String>>,
+    history: Vec<Envelope<Option<u8>>>,
+}
+
+const EXPECTED: &str = r#"import {
+  enumType,
+  map,
+  newtypeStruct,
+  newtypeVariant,
+  option,
+  seq,
+  string,
+  struct,
+  structVariant,
+  u16,
+  u32,
+  u8,
+  unitVariant,
+  type InferType,
+  type Schema,
+  type SchemaOf,
+} from "@variegated-coffee/serde-postcard-ts";
+
+export const EnvelopeSchema = <T extends Schema>(T: T) =>
+  struct({
+    seq: u32(),
+    payload: T,
+  });
+export type Envelope<T> = InferType<ReturnType<typeof EnvelopeSchema<SchemaOf<T>>>>;
+
+export const ReplySchema = <T extends Schema, E extends Schema>(T: T, E: E) =>
+  enumType("Reply", {
+    Ok: newtypeVariant("Ok", T),
+    Busy: unitVariant("Busy"),
+    Failed: structVariant("Failed", { error: E, retries: seq(T) }),
+  });
+export type Reply<T, E> = InferType<ReturnType<typeof ReplySchema<SchemaOf<T>, SchemaOf<E>>>>;
+
+export const StatusSchema = struct({
+  current: EnvelopeSchema(ReplySchema(u16(), string())),
+  history: seq(EnvelopeSchema(option(u8()))),
+});
+export type Status = InferType<typeof StatusSchema>;
+
+export const TableSchema = <K extends Schema, V extends Schema>(K: K, V: V) =>
+  newtypeStruct("Table", map(K, V));
+export type Table<K, V> = InferType<ReturnType<typeof TableSchema<SchemaOf<K>, SchemaOf<V>>>>;
+"#;
+
+#[test]
+fn generates_schema_factories() {
+    let source = generate_all().unwrap();
+    assert!(source.ends_with(EXPECTED), "{}", source);
+}
+
+#[test]
+fn keeps_type_parameters() {
+    let types = registered();
+    let (envelope, _) = types
+        .iter()
+        .find(|(type_name, _)| type_name.name == "Envelope")
+        .unwrap();
+    assert_eq!(envelope.params, ["T"]);
+
+    assert_eq!(
+        <Envelope<u8>>::shape(),
+        Shape::Generic(*envelope, vec![Shape::U8])
+    );
+}
+
+#[test]
+fn writes_parameters_to_the_ir() {
+    let ir = Generator::new().generate_ir().unwrap();
+    assert!(ir.contains(
+        r#"{"name": "Envelope", "params": ["T"], "kind": "struct", "fields": [{"name": "seq", "type": {"kind": "u32"}}, {"name": "payload", "type": {"kind": "param", "name": "T"}}]}"#
+    ));
+    assert!(ir.contains(
+        r#"{"name": "history", "type": {"kind": "seq", "item": {"kind": "ref", "name": "Envelope", "args": [{"kind": "option", "inner": {"kind": "u8"}}]}}}"#
+    ));
+}
@@ -0,0 +1,212 @@
+use serde_postcard_ts::{
+    Definition, Field, GenerateError, GeneratedFile, Generator, Layout, ProtocolGroup, Shape,
+    TypeName,
+};
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn newtype(name: TypeName, inner: Shape) -> (TypeName, Definition) {
+    (name, Definition::NewtypeStruct(inner))
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    let id = type_name("DeviceId", "app::ids");
+    vec![
+        newtype(id, Shape::U64),
+        (
+            type_name("Reading", "app::telemetry"),
+            Definition::Struct(vec![
+                Field {
+                    name: "device",
+                    shape: Shape::Named(id),
+                },
+                Field {
+                    name: "celsius",
+                    shape: Shape::F32,
+                },
+            ]),
+        ),
+        newtype(type_name("Battery", "app::telemetry::power"), Shape::U8),
+        newtype(type_name("Heartbeat", "app::link"), Shape::U32),
+        newtype(type_name("Reboot", "app::commands"), Shape::Unit),
+    ]
+}
+
+fn telemetry() -> ProtocolGroup {
+    ProtocolGroup::new("telemetry", 3).types(["app::telemetry::*", "Heartbeat"])
+}
+
+fn commands() -> ProtocolGroup {
+    ProtocolGroup::new("commands", 1).types(["app::commands::Reboot"])
+}
+
+fn files(generator: Generator) -> Result<Vec<GeneratedFile>, GenerateError> {
+    generator
+        .import_from("./runtime.js")
+        .generate_types_files(types(), Layout::Groups)
+}
+
+fn source<'f>(files: &'f [GeneratedFile], path: &str) -> &'f str {
+    &files.iter().find(|file| file.path == path).unwrap().source
+}
+
+#[test]
+fn lays_out_one_file_per_group() {
+    let files = files(Generator::new().group(telemetry()).group(commands())).unwrap();
+    let paths: Vec<_> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        ["commands.ts", "common.ts", "telemetry.ts", "index.ts"]
+    );
+
+    // Submodules and types named outside the module belong to the group
+    let telemetry = source(&files, "telemetry.ts");
+    for name in ["Reading", "Battery", "Heartbeat"] {
+        assert!(telemetry.contains(&format!("export const {}Schema = ", name)));
+    }
+    assert!(telemetry.contains("import { DeviceIdSchema } from \"./common.js\";\n"));
+    assert!(telemetry.contains("\nexport const TELEMETRY_PROTOCOL_VERSION = 3;\n"));
+    assert!(telemetry.contains("\nexport const TELEMETRY_PROTOCOL_HASH = 0x"));
+    assert!(source(&files, "common.ts").contains("export const DeviceIdSchema = "));
+    assert!(source(&files, "index.ts").ends_with(
+        "export * from \"./commands.js\";\nexport * from \"./common.js\";\nexport * from \"./telemetry.js\";\n"
+    ));
+}
+
+#[test]
+fn generates_constants_for_groups_without_types_of_their_own() {
+    let empty = ProtocolGroup::new("ota", 2);
+    let files = files(Generator::new().group(empty)).unwrap();
+    let ota = source(&files, "ota.ts");
+    assert!(ota.contains("export const OTA_PROTOCOL_VERSION = 2;\n"));
+    assert!(!ota.contains("Schema = "));
+}
+
+#[test]
+fn checks_group_hashes_against_the_pinned_ones() {
+    let generator = Generator::new().group(telemetry()).group(commands());
+    let checks = generator.check_types_groups(&types()).unwrap();
+    assert_eq!(checks.len(), 2);
+    assert_eq!(
+        (checks[0].group.as_str(), checks[0].version),
+        ("telemetry", 3)
+    );
+    assert!(checks
+        .iter()
+        .all(|check| check.pinned.is_none() && !check.is_stale()));
+
+    let hash = checks[0].hash;
+    let pinned = Generator::new().group(telemetry().pin_hash(hash));
+    assert!(!pinned.check_types_groups(&types()).unwrap()[0].is_stale());
+
+    // Widening a field of one of the group's types changes its hash
+    let mut changed = types();
+    changed[2] = newtype(type_name("Battery", "app::telemetry::power"), Shape::U16);
+    let check = &pinned.check_types_groups(&changed).unwrap()[0];
+    assert!(check.is_stale());
+    assert_eq!(check.pinned, Some(hash));
+    assert_ne!(check.hash, hash);
+
+    // Other groups are not affected
+    let commands_hash = checks[1].hash;
+    let after = generator.check_types_groups(&changed).unwrap();
+    assert_eq!(after[1].hash, commands_hash);
+}
+
+#[test]
+fn group_hash_covers_membership() {
+    let hash = |group: ProtocolGroup| {
+        Generator::new()
+            .group(group)
+            .check_types_groups(&types())
+            .unwrap()[0]
+            .hash
+    };
+    let without_heartbeat = ProtocolGroup::new("telemetry", 3).types(["app::telemetry::*"]);
+    assert_ne!(hash(telemetry()), hash(without_heartbeat));
+    // The version is not part of the hash
+    assert_eq!(
+        hash(telemetry()),
+        hash(ProtocolGroup::new("telemetry", 4).types(["app::telemetry::*", "Heartbeat"]))
+    );
+}
+
+#[test]
+fn rejects_types_in_two_groups() {
+    let overlapping = ProtocolGroup::new("link", 1).types(["app::telemetry::Reading"]);
+    assert_eq!(
+        files(Generator::new().group(telemetry()).group(overlapping)),
+        Err(GenerateError::GroupOverlap {
+            name: "Reading",
+            groups: ["telemetry".to_string(), "link".to_string()],
+        })
+    );
+}
+
+#[test]
+fn rejects_patterns_matching_nothing() {
+    let typo = ProtocolGroup::new("commands", 1).types(["app::command::*"]);
+    let error = files(Generator::new().group(typo)).unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::UnmatchedGroupPattern {
+            group: "commands".to_string(),
+            pattern: "app::command::*".to_string(),
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "app::command::* in protocol group commands matches no type"
+    );
+}
+
+#[test]
+fn rejects_invalid_group_names() {
+    for name in ["Telemetry", "2fast", "ota-v2", "common", "index", ""] {
+        assert_eq!(
+            files(Generator::new().group(ProtocolGroup::new(name, 1))),
+            Err(GenerateError::InvalidGroupName(name.to_string())),
+            "{}",
+            name
+        );
+    }
+    let twice = Generator::new()
+        .group(telemetry())
+        .group(ProtocolGroup::new("telemetry", 4));
+    assert_eq!(
+        files(twice),
+        Err(GenerateError::InvalidGroupName("telemetry".to_string()))
+    );
+}
+
+#[test]
+fn rejects_groups_importing_each_other() {
+    // DeviceId, in no group, refers to a grouped type that refers back to it
+    let id = type_name("DeviceId", "app::ids");
+    let serial = type_name("Serial", "app::telemetry");
+    let types = vec![
+        newtype(id, Shape::Named(serial)),
+        newtype(serial, Shape::U32),
+        newtype(type_name("Reading", "app::telemetry"), Shape::Named(id)),
+    ];
+    let generator =
+        Generator::new().group(ProtocolGroup::new("telemetry", 1).types(["app::telemetry::*"]));
+    assert_eq!(
+        generator.generate_types_files(types.clone(), Layout::Groups),
+        Err(GenerateError::GroupCycle(vec![
+            "common".to_string(),
+            "telemetry".to_string(),
+            "common".to_string(),
+        ]))
+    );
+    // Groups only matter to the groups layout
+    assert!(generator
+        .generate_types_files(types, Layout::Single)
+        .is_ok());
+}
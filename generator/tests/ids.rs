@@ -0,0 +1,176 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use std::num::{NonZeroI64, NonZeroU16, NonZeroU8};
+
+use serde_postcard_ts::{max_size, schema_hash, Generator, ModuleFormat, PostcardTs, Validator};
+
+#[derive(PostcardTs)]
+struct PlayerId(u64);
+
+#[derive(PostcardTs)]
+struct ItemId(u64);
+
+#[derive(PostcardTs)]
+struct Pickup {
+    player: PlayerId,
+    item: ItemId,
+    count: NonZeroU8,
+}
+
+#[derive(PostcardTs)]
+struct RawPickup {
+    player: u64,
+    item: u64,
+    count: u8,
+}
+
+#[derive(PostcardTs)]
+struct Listener {
+    port: NonZeroU16,
+    offset: NonZeroI64,
+}
+
+#[derive(PostcardTs)]
+struct Counted {
+    count: u8,
+}
+
+#[derive(PostcardTs)]
+struct Wrapper<T>(T);
+
+#[derive(PostcardTs)]
+enum Tree {
+    Leaf,
+    Node(Box<Tree>),
+}
+
+#[derive(PostcardTs)]
+struct Forest(Vec<Tree>);
+
+#[test]
+fn declares_non_zero_integers_with_the_runtime_builder() {
+    let source = Generator::new().generate().unwrap();
+    assert!(source.contains("  nonZero,\n"));
+    assert!(source.contains(
+        "export const NonZeroU16Schema = nonZero(u16());\n\
+         export type NonZeroU16 = InferType<typeof NonZeroU16Schema>;\n"
+    ));
+    assert!(source.contains("export const NonZeroI64Schema = nonZero(i64());"));
+    assert!(source.contains("  port: NonZeroU16Schema,"));
+    assert!(!source.contains("NonZeroU32"));
+}
+
+#[test]
+fn sizes_and_hashes_non_zero_integers_as_their_values() {
+    assert_eq!(schema_hash::<Pickup>(), schema_hash::<RawPickup>());
+    assert_ne!(schema_hash::<Counted>(), schema_hash::<Pickup>());
+    // A varint u16 and a zigzag varint i64
+    assert_eq!(max_size::<Listener>(), Ok(3 + 10));
+}
+
+#[test]
+fn leaves_newtypes_unbranded_by_default() {
+    let source = Generator::new().generate().unwrap();
+    assert!(source.contains("export const PlayerIdSchema = newtypeStruct(\"PlayerId\", u64());"));
+    assert!(!source.contains("branded"));
+}
+
+#[test]
+fn brands_newtypes_when_asked() {
+    let source = Generator::new().branded_newtypes().generate().unwrap();
+    assert!(source.contains("  branded,\n"));
+    assert!(source.contains(
+        "export const PlayerIdSchema = branded(newtypeStruct(\"PlayerId\", u64()), \"PlayerId\");"
+    ));
+    assert!(source.contains(
+        "export const ItemIdSchema = branded(newtypeStruct(\"ItemId\", u64()), \"ItemId\");"
+    ));
+    assert!(source.contains(
+        "export const ForestSchema = branded(newtypeStruct(\"Forest\", seq(TreeSchema)), \"Forest\");"
+    ));
+    // Generic newtypes stay structural
+    assert!(!source.contains("\"Wrapper\")"));
+}
+
+#[test]
+fn spells_out_brands_in_declarations() {
+    let files = Generator::new()
+        .branded_newtypes()
+        .module_format(ModuleFormat::Declarations)
+        .generate_files(Default::default())
+        .unwrap();
+    let source = &files[0].source;
+    assert!(
+        source.contains("export type PlayerId = bigint & { readonly __brand: \"PlayerId\" };\n")
+    );
+    assert!(source.contains("export type Forest = Tree[] & { readonly __brand: \"Forest\" };\n"));
+    assert!(source.contains("export type NonZeroU8 = number;\n"));
+    assert!(source.contains("export type NonZeroI64 = bigint;\n"));
+}
+
+#[test]
+fn validates_that_non_zero_integers_are_not_zero() {
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap();
+    assert!(source.contains(
+        "export const NonZeroU8Zod = z.number().int().min(0).max(255)\
+         .refine((value) => value !== 0, \"must not be zero\");"
+    ));
+    assert!(source.contains("refine((value) => value !== 0n, \"must not be zero\")"));
+}
+
+#[cfg(feature = "uuid")]
+mod uuid_ids {
+    use serde_postcard_ts::{
+        schema_hash, Generator, ModuleFormat, PostcardTs, UuidRepresentation, Validator,
+    };
+
+    #[derive(PostcardTs)]
+    struct Session {
+        id: uuid::Uuid,
+    }
+
+    #[derive(PostcardTs)]
+    struct RawSession {
+        #[serde(with = "serde_bytes")]
+        id: Vec<u8>,
+    }
+
+    #[test]
+    fn declares_uuids_as_branded_strings() {
+        let source = Generator::new().generate().unwrap();
+        assert!(source.contains(
+            "export const UuidSchema = uuid(\"string\");\n\
+             export type Uuid = InferType<typeof UuidSchema>;\n"
+        ));
+        assert!(source.contains("  id: UuidSchema,"));
+
+        let files = Generator::new()
+            .module_format(ModuleFormat::Declarations)
+            .generate_files(Default::default())
+            .unwrap();
+        assert!(files[0]
+            .source
+            .contains("export type Uuid = string & { readonly __brand: \"Uuid\" };\n"));
+    }
+
+    #[test]
+    fn declares_uuids_as_bytes_when_asked() {
+        let source = Generator::new()
+            .uuid_representation(UuidRepresentation::Bytes)
+            .with_validators(Validator::Zod)
+            .generate()
+            .unwrap();
+        assert!(source.contains("export const UuidSchema = uuid(\"bytes\");"));
+        assert!(source.contains("export const UuidZod = z.instanceof(Uint8Array)"));
+    }
+
+    #[test]
+    fn hashes_uuids_as_byte_strings() {
+        assert_eq!(schema_hash::<Session>(), schema_hash::<RawSession>());
+        assert_eq!(Ok(Session::SCHEMA_HASH), schema_hash::<Session>());
+    }
+}
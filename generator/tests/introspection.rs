@@ -0,0 +1,132 @@
+use serde_postcard_ts::{
+    Definition, Field, Generator, Layout, ModuleFormat, Shape, TypeName, Variant, VariantKind,
+};
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    let reading = type_name("Reading", "app::sensors");
+    vec![
+        (
+            reading,
+            Definition::Struct(vec![
+                Field {
+                    name: "sensor_id",
+                    shape: Shape::U8,
+                },
+                Field {
+                    name: "max-celsius",
+                    shape: Shape::F32,
+                },
+            ]),
+        ),
+        (
+            type_name("Point", "app::sensors"),
+            Definition::TupleStruct(vec![Shape::F32, Shape::F32]),
+        ),
+        (
+            type_name("Telemetry", "app"),
+            Definition::Enum(vec![
+                Variant {
+                    name: "Heartbeat",
+                    kind: VariantKind::Unit,
+                },
+                Variant {
+                    name: "Reading",
+                    kind: VariantKind::Newtype(Shape::Named(reading)),
+                },
+            ]),
+        ),
+        (
+            TypeName {
+                name: "Envelope",
+                module: "app",
+                params: &["T"],
+            },
+            Definition::Struct(vec![Field {
+                name: "body",
+                shape: Shape::Param("T"),
+            }]),
+        ),
+    ]
+}
+
+/// The hash `schema_hashes()` emits for `name`
+fn hash(name: &str) -> String {
+    let source = Generator::new()
+        .schema_hashes()
+        .generate_types(types())
+        .unwrap();
+    let declaration = format!("export const {}SchemaHash = ", name);
+    let start = source.find(&declaration).unwrap() + declaration.len();
+    source[start..start + 19].to_string()
+}
+
+#[test]
+fn describes_each_type_of_the_module() {
+    let source = Generator::new()
+        .introspection()
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains("  type ModuleSchema,\n"));
+    assert!(source.ends_with(&format!(
+        "/** The types this module declares, for tools that look at them at runtime */\n\
+         export const __schema = {{\n  \
+           Reading: {{ kind: \"struct\", schema: ReadingSchema, fields: [\"sensor_id\", \"max-celsius\"], hash: {} }},\n  \
+           Point: {{ kind: \"tuple_struct\", schema: PointSchema, length: 2, hash: {} }},\n  \
+           Telemetry: {{ kind: \"enum\", schema: TelemetrySchema, variants: {{ Heartbeat: 0, Reading: 1 }}, hash: {} }},\n  \
+           Envelope: {{ kind: \"struct\", schema: EnvelopeSchema, fields: [\"body\"], params: [\"T\"] }},\n\
+         }} as const satisfies ModuleSchema;\n",
+        hash("Reading"),
+        hash("Point"),
+        hash("Telemetry"),
+    )));
+    // The hashes are only stated in __schema
+    assert!(!source.contains("ReadingSchemaHash"));
+}
+
+#[test]
+fn is_left_out_by_default_and_of_declarations() {
+    let source = Generator::new().generate_types(types()).unwrap();
+    assert!(!source.contains("__schema"));
+    let source = Generator::new()
+        .introspection()
+        .module_format(ModuleFormat::Declarations)
+        .generate_types(types())
+        .unwrap();
+    assert!(!source.contains("__schema"));
+}
+
+#[test]
+fn merges_the_modules_in_the_index() {
+    let files = Generator::new()
+        .introspection()
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    let file = |path: &str| files.iter().find(|file| file.path == path).unwrap();
+    assert!(file("app/sensors.ts")
+        .source
+        .contains("export const __schema = {\n  Reading: "));
+    assert!(file("app.ts")
+        .source
+        .contains("export const __schema = {\n  Telemetry: "));
+    assert!(file("index.ts").source.ends_with(
+        "export * from \"./app.js\";\n\
+         export * from \"./app/sensors.js\";\n\
+         \n\
+         import { __schema as __schema0 } from \"./app.js\";\n\
+         import { __schema as __schema1 } from \"./app/sensors.js\";\n\
+         \n\
+         /** The types of every module, for tools that look at them at runtime */\n\
+         export const __schema = {\n  \
+           ...__schema0,\n  \
+           ...__schema1,\n\
+         } as const;\n"
+    ));
+}
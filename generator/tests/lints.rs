@@ -0,0 +1,200 @@
+// The derived types only exist to be linted
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use serde_postcard_ts::{
+    lint_types, lint_types_with, lint_with, lsp, Definition, Field, Generator, Layout, LintConfig,
+    LintLevel, PostcardTs, Shape, TypeName, Variant, VariantKind, WriteOptions,
+};
+
+#[derive(PostcardTs)]
+struct Roster {
+    names: HashMap<String, u8>,
+}
+
+fn named(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params: &[],
+    }
+}
+
+fn fields(fields: &[(&'static str, Shape)]) -> Definition {
+    Definition::Struct(
+        fields
+            .iter()
+            .map(|(name, shape)| Field {
+                name,
+                shape: shape.clone(),
+            })
+            .collect(),
+    )
+}
+
+fn messages(types: &[(TypeName, Definition)], config: &LintConfig) -> Vec<String> {
+    lint_types_with(types, config)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// A chain of `depth` structs, each holding the next in a list
+fn chain(depth: usize) -> Vec<(TypeName, Definition)> {
+    const NAMES: [&str; 6] = ["Level0", "Level1", "Level2", "Level3", "Level4", "Level5"];
+    (0..depth)
+        .map(|i| {
+            let shape = match NAMES.get(i + 1).filter(|_| i + 1 < depth) {
+                Some(next) => Shape::Seq(Box::new(Shape::Named(named(next)))),
+                None => Shape::U8,
+            };
+            (named(NAMES[i]), fields(&[("next", shape)]))
+        })
+        .collect()
+}
+
+#[test]
+fn warns_about_ids_decoded_to_bigints() {
+    let types = vec![
+        (
+            named("Player"),
+            fields(&[
+                ("id", Shape::U64),
+                ("team_id", Shape::Option(Box::new(Shape::I64))),
+                ("score", Shape::U64),
+                ("room_id", Shape::U32),
+            ]),
+        ),
+        (named("ItemId"), Definition::NewtypeStruct(Shape::U128)),
+        (named("Seed"), Definition::NewtypeStruct(Shape::U64)),
+    ];
+    assert_eq!(
+        messages(&types, &LintConfig::new()),
+        [
+            "Player.id is a u64, which TypeScript decodes to a bigint that mixes with neither \
+             numbers nor JSON; if the ids fit in 32 bits, a u32 decodes to a plain number \
+             (bigint_id)",
+            "Player.team_id is an i64, which TypeScript decodes to a bigint that mixes with \
+             neither numbers nor JSON; if the ids fit in 32 bits, a u32 decodes to a plain \
+             number (bigint_id)",
+            "ItemId is a u128, which TypeScript decodes to a bigint that mixes with neither \
+             numbers nor JSON; if the ids fit in 32 bits, a u32 decodes to a plain number \
+             (bigint_id)",
+        ]
+    );
+}
+
+#[test]
+fn warns_about_maps_keyed_by_strings() {
+    let map = |key: Shape| Shape::Map(Box::new(key), Box::new(Shape::U8));
+    let types = vec![(
+        named("Settings"),
+        Definition::Enum(vec![
+            Variant {
+                name: "Named",
+                kind: VariantKind::Newtype(Shape::Seq(Box::new(map(Shape::BoundedString(8))))),
+            },
+            Variant {
+                name: "Numbered",
+                kind: VariantKind::Newtype(map(Shape::U16)),
+            },
+        ]),
+    )];
+    let lints = lint_types(&types);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].code, "string_map");
+    assert!(lints[0]
+        .message
+        .starts_with("Settings::Named.0[] is a map keyed by strings, a Map in TypeScript"));
+}
+
+#[test]
+fn warns_about_deeply_nested_types_from_their_roots() {
+    let config = LintConfig::new().max_depth(7);
+    assert!(messages(&chain(4), &config).is_empty());
+    assert_eq!(
+        messages(&chain(5), &config),
+        [
+            "Level0 nests 9 levels deep, down Level0.next[].next[].next[].next[], more than \
+          the 7 allowed; every level is another object or array to walk in TypeScript \
+          (deep_nesting)"
+        ]
+    );
+
+    // Through generic arguments, and once around recursive types
+    let envelope = TypeName {
+        name: "Envelope",
+        module: "app",
+        params: &["T"],
+    };
+    let types = vec![
+        (
+            envelope,
+            fields(&[("body", Shape::Seq(Box::new(Shape::Param("T"))))]),
+        ),
+        (
+            named("Tree"),
+            fields(&[(
+                "children",
+                Shape::Seq(Box::new(Shape::Named(named("Tree")))),
+            )]),
+        ),
+        (
+            named("Message"),
+            fields(&[(
+                "tree",
+                Shape::Generic(envelope, vec![Shape::Named(named("Tree"))]),
+            )]),
+        ),
+    ];
+    assert_eq!(
+        messages(&types, &LintConfig::new().max_depth(5)),
+        [
+            "Message nests 6 levels deep, down Message.tree.body[].children[], more than the 5 \
+          allowed; every level is another object or array to walk in TypeScript (deep_nesting)"
+        ]
+    );
+}
+
+#[test]
+fn allows_and_denies_lints() {
+    let types = vec![(named("Session"), fields(&[("id", Shape::U64)]))];
+    assert!(messages(&types, &LintConfig::new().allow("bigint_id")).is_empty());
+    let lints = lint_types_with(&types, &LintConfig::new().deny("bigint_id"));
+    assert_eq!(lints[0].level, LintLevel::Deny);
+    assert_eq!(lint_types(&types)[0].level, LintLevel::Warn);
+
+    let dir = std::env::temp_dir().join(format!("postcard-ts-lints-{}", std::process::id()));
+    let messages = lsp::generate_with_lints(
+        &Generator::new(),
+        types,
+        Layout::Single,
+        &dir,
+        WriteOptions::default(),
+        &LintConfig::new().deny("bigint_id"),
+    )
+    .unwrap_err();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with(
+        r#"{"kind": "diagnostic", "severity": "error", "code": "bigint_id", "message": "Session.id is a u64"#
+    ));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn points_at_the_declaration_of_registered_types() {
+    let lint = lint_with(&LintConfig::new())
+        .into_iter()
+        .find(|lint| lint.type_name.name == "Roster")
+        .unwrap();
+    let span = lint.span.clone().unwrap();
+    assert!(span.file.ends_with("tests/lints.rs"));
+    assert_eq!((span.line, span.column), (12, 8));
+    assert!(span.to_string().ends_with("tests/lints.rs:12:8"));
+
+    let diagnostic = lsp::lint_diagnostic(&lint);
+    assert!(diagnostic.contains(
+        r#", "range": {"start": {"line": 11, "character": 7}, "end": {"line": 11, "character": 13}}, "types": ["Roster"]}"#
+    ));
+}
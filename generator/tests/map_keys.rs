@@ -0,0 +1,115 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    generate_all, Definition, Field, GenerateError, Generator, PostcardTs, Shape, TypeName,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(PostcardTs)]
+struct Cell {
+    row: u8,
+    column: u8,
+}
+
+#[derive(PostcardTs)]
+enum Layer {
+    Ground,
+    Air,
+}
+
+#[derive(PostcardTs)]
+struct Grid {
+    cells: BTreeMap<Cell, u16>,
+    layers: Vec<HashMap<Layer, String>>,
+    by_id: HashMap<u32, Cell>,
+}
+
+const EXPECTED: &str = r#"import {
+  canonicalKey,
+  enumType,
+  map,
+  seq,
+  string,
+  struct,
+  u16,
+  u32,
+  u8,
+  unitVariant,
+  type InferType,
+} from "@variegated-coffee/serde-postcard-ts";
+
+export const CellSchema = struct({
+  row: u8(),
+  column: u8(),
+});
+export type Cell = InferType<typeof CellSchema>;
+export const CellKey = canonicalKey(CellSchema);
+
+export const LayerSchema = enumType("Layer", {
+  Ground: unitVariant("Ground"),
+  Air: unitVariant("Air"),
+});
+export type Layer = InferType<typeof LayerSchema>;
+export const LayerKey = canonicalKey(LayerSchema);
+
+export const GridSchema = struct({
+  cells: map(CellSchema, u16()),
+  layers: seq(map(LayerSchema, string())),
+  by_id: map(u32(), CellSchema),
+});
+export type Grid = InferType<typeof GridSchema>;
+"#;
+
+#[test]
+fn generates_key_helpers_for_map_key_types() {
+    let source = generate_all().unwrap();
+    assert!(source.ends_with(EXPECTED), "{}", source);
+}
+
+fn type_name(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "m",
+        params: &[],
+    }
+}
+
+fn map_of(key: Shape) -> Vec<(TypeName, Definition)> {
+    vec![
+        (type_name("Celsius"), Definition::NewtypeStruct(Shape::F32)),
+        (
+            type_name("Table"),
+            Definition::Struct(vec![Field {
+                name: "rows",
+                shape: Shape::Seq(Box::new(Shape::Map(Box::new(key), Box::new(Shape::U8)))),
+            }]),
+        ),
+    ]
+}
+
+#[test]
+fn rejects_float_keys() {
+    let rejected = Err(GenerateError::FloatKey { name: "Table" });
+    let generator = Generator::new();
+    assert_eq!(generator.generate_types(map_of(Shape::F64)), rejected);
+    assert_eq!(
+        generator.generate_types(map_of(Shape::Option(Box::new(Shape::F32)))),
+        rejected
+    );
+    assert_eq!(
+        generator.generate_types(map_of(Shape::Named(type_name("Celsius")))),
+        rejected
+    );
+    assert_eq!(
+        generator.generate_types_ir(map_of(Shape::F64)).unwrap_err(),
+        GenerateError::FloatKey { name: "Table" }
+    );
+}
+
+#[test]
+fn accepts_floats_inside_compound_keys() {
+    // Tuple keys are compared by their encoded bytes, so -0.0 stays apart from 0.0
+    let key = Shape::Tuple(vec![Shape::F32, Shape::U8]);
+    assert!(Generator::new().generate_types(map_of(key)).is_ok());
+}
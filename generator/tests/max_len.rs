@@ -0,0 +1,90 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    generate_all, registered, schema_hash, Definition, Generator, PostcardTs, Shape,
+};
+
+#[derive(PostcardTs)]
+struct Label {
+    #[postcard_ts(max_len = 16)]
+    text: String,
+    #[postcard_ts(max_len = 8)]
+    unit: Option<String>,
+    note: String,
+}
+
+#[derive(PostcardTs)]
+struct Name(#[postcard_ts(max_len = 32)] String);
+
+#[derive(PostcardTs)]
+struct Unlimited {
+    text: String,
+    unit: Option<String>,
+    note: String,
+}
+
+const EXPECTED: &str = r#"export const LabelSchema = struct({
+  text: string({ maxBytes: 16 }),
+  unit: option(string({ maxBytes: 8 })),
+  note: string(),
+});
+export type Label = InferType<typeof LabelSchema>;
+
+export const NameSchema = newtypeStruct("Name", string({ maxBytes: 32 }));
+export type Name = InferType<typeof NameSchema>;
+"#;
+
+#[test]
+fn limits_strings_with_max_len() {
+    let source = generate_all().unwrap();
+    assert!(source.contains(EXPECTED), "{}", source);
+}
+
+#[test]
+fn truncates_when_asked() {
+    let source = Generator::new().truncate_strings().generate().unwrap();
+    assert!(source.contains("  text: string({ maxBytes: 16, overflow: \"truncate\" }),"));
+    assert!(source.contains("  note: string(),"));
+}
+
+#[test]
+fn keeps_the_limit_in_the_ir() {
+    let ir = Generator::new().generate_ir().unwrap();
+    assert!(ir.contains(r#"{"name": "text", "type": {"kind": "string", "maxBytes": 16}}"#));
+}
+
+#[test]
+fn hashes_limited_strings_as_strings() {
+    // The limit is not on the wire, so it does not change the hash
+    assert_eq!(schema_hash::<Label>(), schema_hash::<Unlimited>());
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn limits_heapless_strings_to_their_capacity() {
+    assert_eq!(
+        <heapless::String<24> as PostcardTs>::shape(),
+        Shape::BoundedString(24)
+    );
+    assert_eq!(
+        <heapless::Vec<u8, 4> as PostcardTs>::shape(),
+        Shape::BoundedSeq(Box::new(Shape::U8), 4)
+    );
+    assert_eq!(
+        <heapless::String<24> as serde_postcard_ts::MaxLen>::max_len(8),
+        Shape::BoundedString(8)
+    );
+}
+
+#[test]
+fn registers_the_bounded_shape() {
+    let (_, definition) = registered()
+        .into_iter()
+        .find(|(type_name, _)| type_name.name == "Name")
+        .unwrap();
+    assert_eq!(
+        definition,
+        Definition::NewtypeStruct(Shape::BoundedString(32))
+    );
+}
@@ -0,0 +1,199 @@
+// The derived types only exist to be sized
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    max_size, max_sizes, Definition, GenerateError, Generator, PostcardTs, Shape, TypeName,
+    Variant, VariantKind,
+};
+
+#[derive(PostcardTs)]
+struct Player {
+    id: u32,
+    position: [f32; 3],
+    team: Option<u8>,
+    letter: char,
+}
+
+#[derive(PostcardTs)]
+enum Command {
+    Stop,
+    Move { x: i16, y: i16 },
+    Fire(u128),
+}
+
+#[derive(PostcardTs)]
+struct Frame {
+    seq: u16,
+    command: Command,
+    stamp: (u64, bool),
+    nickname: Option<Nickname>,
+}
+
+#[derive(PostcardTs)]
+struct Nickname {
+    #[postcard_ts(max_len = 200)]
+    text: String,
+}
+
+#[derive(PostcardTs)]
+struct Chat {
+    from: u8,
+    lines: Vec<u8>,
+}
+
+#[derive(PostcardTs)]
+enum Expr {
+    Literal(i32),
+    Neg(Box<Expr>),
+}
+
+#[derive(PostcardTs)]
+struct Pair<T> {
+    left: T,
+    right: T,
+}
+
+#[derive(PostcardTs)]
+struct Segment {
+    ends: Pair<u16>,
+}
+
+fn type_name(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params: &[],
+    }
+}
+
+#[test]
+fn sizes_structs_as_postcard_max_size_does() {
+    // Varint u32, three floats, an option tag and a u8, a length byte and up to 4 bytes of UTF-8
+    assert_eq!(max_size::<Player>(), Ok(5 + 12 + 2 + 5));
+    assert_eq!(max_size::<u64>(), Ok(10));
+    assert_eq!(max_size::<[u8; 0]>(), Ok(0));
+}
+
+#[test]
+fn sizes_enums_by_their_largest_variant() {
+    assert_eq!(max_size::<Command>(), Ok(1 + 19));
+    // A 200 byte string takes a two-byte length
+    assert_eq!(max_size::<Nickname>(), Ok(2 + 200));
+    assert_eq!(max_size::<Frame>(), Ok(3 + 20 + 11 + 1 + 202));
+    assert_eq!(max_size::<Segment>(), Ok(6));
+}
+
+#[test]
+fn counts_the_discriminant_from_the_number_of_variants() {
+    let variants = |count: usize| {
+        let variants = (0..count)
+            .map(|_| Variant {
+                name: "V",
+                kind: VariantKind::Unit,
+            })
+            .collect();
+        max_sizes(&[(type_name("Many"), Definition::Enum(variants))]).unwrap()[0].1
+    };
+    assert_eq!(variants(0), 0);
+    assert_eq!(variants(127), 1);
+    // postcard's derive counts 128 variants as two bytes, though index 127 takes one
+    assert_eq!(variants(128), 2);
+}
+
+#[test]
+fn rejects_unbounded_types() {
+    let error = max_size::<Chat>().unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::Unbounded {
+            name: "Chat",
+            reason: "a sequence",
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Chat has no maximum encoded size: it contains a sequence"
+    );
+    assert_eq!(
+        max_size::<Expr>(),
+        Err(GenerateError::Unbounded {
+            name: "Expr",
+            reason: "itself",
+        })
+    );
+    assert!(matches!(
+        max_size::<String>(),
+        Err(GenerateError::Unbounded {
+            reason: "a String",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn leaves_unbounded_and_generic_types_out_of_max_sizes() {
+    let id = type_name("DeviceId");
+    let types = vec![
+        (id, Definition::NewtypeStruct(Shape::U16)),
+        (type_name("Log"), Definition::NewtypeStruct(Shape::String)),
+        (
+            TypeName {
+                name: "Boxed",
+                module: "app",
+                params: &["T"],
+            },
+            Definition::NewtypeStruct(Shape::Param("T")),
+        ),
+    ];
+    assert_eq!(max_sizes(&types), Ok(vec![(id, 3)]));
+
+    let missing = vec![(
+        type_name("Reading"),
+        Definition::NewtypeStruct(Shape::Named(type_name("Sensor"))),
+    )];
+    assert_eq!(
+        max_sizes(&missing),
+        Err(GenerateError::Missing {
+            name: "Sensor",
+            referenced_by: "Reading",
+        })
+    );
+}
+
+#[test]
+fn emits_max_size_constants() {
+    let types = vec![
+        (
+            type_name("GameState"),
+            Definition::NewtypeStruct(Shape::U32),
+        ),
+        (
+            type_name("HTTPStatus"),
+            Definition::NewtypeStruct(Shape::U16),
+        ),
+        (type_name("Log"), Definition::NewtypeStruct(Shape::String)),
+    ];
+    let source = Generator::new()
+        .max_sizes()
+        .generate_types(types.clone())
+        .unwrap();
+    assert!(source.contains("\nexport const GAME_STATE_MAX_SIZE = 5;\n"));
+    assert!(source.contains("\nexport const HTTP_STATUS_MAX_SIZE = 3;\n"));
+    assert!(!source.contains("LOG_MAX_SIZE"));
+
+    let plain = Generator::new().generate_types(types).unwrap();
+    assert!(!plain.contains("_MAX_SIZE"));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn sizes_heapless_containers_by_their_capacity() {
+    assert_eq!(max_size::<heapless::String<16>>(), Ok(1 + 16));
+    assert_eq!(max_size::<heapless::Vec<u16, 4>>(), Ok(1 + 4 * 3));
+    // The length of up to 200 entries takes two bytes
+    assert_eq!(
+        max_size::<heapless::LinearMap<u8, bool, 200>>(),
+        Ok(2 + 200 * 2)
+    );
+    assert!(max_size::<heapless::Vec<String, 4>>().is_err());
+}
@@ -0,0 +1,133 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{GenerateError, Generator, Ident, Layout, Naming, PostcardTs, Validator};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+enum Telemetry {
+    Heartbeat,
+    Reading(Reading),
+}
+
+mod device {
+    use serde_postcard_ts::PostcardTs;
+
+    #[derive(PostcardTs)]
+    pub struct Status {
+        pub last: crate::Telemetry,
+    }
+}
+
+fn naming() -> Naming {
+    Naming::new()
+        .template(Ident::Type, "I{name}")
+        .template(Ident::Schema, "{name}Codec")
+        .template(Ident::VariantPeek, "{name}Is{variant}")
+        .template(Ident::MaxSize, "MAX_{NAME}_BYTES")
+}
+
+#[test]
+fn names_types_schemas_and_helpers_with_the_templates() {
+    let source = Generator::new()
+        .naming(naming())
+        .variant_peeks()
+        .max_sizes()
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap();
+    assert!(source.contains(
+        "export const ReadingCodec = struct({\n  sensor: u8(),\n  celsius: f32(),\n});\n\
+         export type IReading = InferType<typeof ReadingCodec>;\n\
+         export const ReadingZod = z.object({\n"
+    ));
+    assert!(source.contains("  last: TelemetryZod,\n"));
+    assert!(source.contains("  Reading: newtypeVariant(\"Reading\", ReadingCodec),\n"));
+    assert!(source.contains(
+        "export const TelemetryIsHeartbeat = variantFilter(TelemetryCodec, [\"Heartbeat\"]);\n"
+    ));
+    assert!(source.contains("export const MAX_TELEMETRY_BYTES = 6;\n"));
+    assert!(!source.contains("Schema ="));
+}
+
+#[test]
+fn names_the_imports_between_modules() {
+    let files = Generator::new()
+        .naming(naming())
+        .generate_files(Layout::Modules)
+        .unwrap();
+    let device = files
+        .iter()
+        .find(|file| file.path == "naming/device.ts")
+        .unwrap();
+    assert!(device
+        .source
+        .contains("import { TelemetryCodec } from \"../naming.js\";\n"));
+    assert!(device
+        .source
+        .contains("export type IStatus = InferType<typeof StatusCodec>;\n"));
+    assert!(device.source.contains("  last: TelemetryCodec,\n"));
+}
+
+#[test]
+fn leaves_the_output_unchanged_by_default() {
+    assert_eq!(
+        Generator::new().naming(Naming::new()).generate(),
+        Generator::new().generate()
+    );
+    assert_eq!(
+        Naming::new().ident(Ident::MaxSize, "GameState"),
+        "GAME_STATE_MAX_SIZE"
+    );
+}
+
+#[test]
+fn rejects_templates_that_do_not_make_identifiers() {
+    let error = Generator::new()
+        .naming(Naming::new().template(Ident::Schema, "schema"))
+        .generate()
+        .unwrap_err();
+    assert_eq!(error.code(), "invalid_naming");
+    assert_eq!(
+        error.to_string(),
+        "invalid schema name template \"schema\": it must contain {name} or {NAME}"
+    );
+    let error = Generator::new()
+        .naming(Naming::new().template(Ident::Type, "{name}-Type"))
+        .generate()
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        GenerateError::InvalidNaming {
+            ident: Ident::Type,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn rejects_templates_giving_two_exports_one_name() {
+    let error = Generator::new()
+        .naming(Naming::new().template(Ident::Zod, "{name}Schema"))
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap_err();
+    assert_eq!(error.code(), "name_collision");
+    let GenerateError::NameCollision { name, uses } = error else {
+        panic!("expected a name collision");
+    };
+    assert_eq!(name, "ReadingSchema");
+    assert_eq!(uses, [(Ident::Schema, "Reading"), (Ident::Zod, "Reading")]);
+
+    // A type and a schema of one name are fine: they live in different spaces
+    let source = Generator::new()
+        .naming(Naming::new().template(Ident::Schema, "{name}"))
+        .generate()
+        .unwrap();
+    assert!(source.contains("export type Reading = InferType<typeof Reading>;\n"));
+}
@@ -0,0 +1,77 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{generate_all, lint, lsp, PostcardTs};
+
+#[derive(PostcardTs)]
+struct Inventory {
+    restock: Option<Vec<u16>>,
+    slots: Vec<Option<u16>>,
+    items: Vec<u16>,
+}
+
+#[derive(PostcardTs)]
+enum Change {
+    Set(Option<Vec<u8>>, Vec<u8>),
+    Fill {
+        slots: Vec<Option<u16>>,
+        items: Vec<u16>,
+    },
+    Restock(Option<Vec<u16>>),
+}
+
+#[derive(PostcardTs)]
+struct Gaps {
+    slots: Vec<Option<u16>>,
+    items: Vec<u16>,
+}
+
+#[test]
+fn keeps_the_three_shapes_apart() {
+    let source = generate_all().unwrap();
+    assert!(source.contains(
+        "export const InventorySchema = struct({\n  restock: option(seq(u16())),\n  slots: seq(option(u16())),\n  items: seq(u16()),\n});"
+    ));
+    assert!(source.contains("  Set: tupleVariant(\"Set\", option(seq(u8())), seq(u8())),"));
+}
+
+#[test]
+fn warns_about_optional_lists_next_to_plain_ones() {
+    let lints = lint();
+    let mut messages: Vec<(&str, String)> = lints
+        .iter()
+        .map(|lint| (lint.type_name.name, lint.to_string()))
+        .collect();
+    messages.sort();
+    assert_eq!(
+        messages,
+        [
+            (
+                "Change",
+                "Change::Set has optional lists (0) next to plain ones (1); no items is null or [] \
+                 in the former and [] in the latter, so pick one way to say it (optional_seq)"
+                    .to_string()
+            ),
+            (
+                "Inventory",
+                "Inventory has optional lists (restock) next to plain ones (slots, items); no \
+                 items is null or [] in the former and [] in the latter, so pick one way to say \
+                 it (optional_seq)"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reports_lints_as_lsp_warnings() {
+    let lint = lint()
+        .into_iter()
+        .find(|lint| lint.type_name.name == "Inventory")
+        .unwrap();
+    let diagnostic = lsp::lint_diagnostic(&lint);
+    assert!(diagnostic.starts_with(
+        r#"{"kind": "diagnostic", "severity": "warning", "code": "optional_seq", "message": "Inventory has optional lists (restock)"#
+    ));
+    assert!(diagnostic.ends_with(r#""types": ["Inventory"]}"#));
+}
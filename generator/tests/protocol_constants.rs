@@ -0,0 +1,128 @@
+use serde_postcard_ts::{
+    GenerateError, Generator, Layout, ModuleFormat, ProtocolConstant, ProtocolGroup,
+};
+
+fn generator() -> Generator {
+    Generator::new()
+        .constant(
+            ProtocolConstant::new("FRAME_MAGIC", [0xca, 0xfe])
+                .doc("Starts every frame\n\nThen comes the length"),
+        )
+        .constant(ProtocolConstant::new("MAX_FRAME_LENGTH", 512usize))
+        .constant(ProtocolConstant::new("BOOT_EPOCH", 1_700_000_000_000u64))
+        .constant(ProtocolConstant::new("MIN_OFFSET", -40i8))
+        .constant(ProtocolConstant::new("TOPIC", "devices/\"hall\"/telemetry"))
+}
+
+#[test]
+fn emits_the_constants_after_the_types() {
+    let source = generator().generate_types(vec![]).unwrap();
+    assert!(source.ends_with(
+        "\n\
+         /**\n \
+         * Starts every frame\n \
+         *\n \
+         * Then comes the length\n \
+         */\n\
+         export const FRAME_MAGIC = new Uint8Array([0xca, 0xfe]);\n\
+         export const MAX_FRAME_LENGTH = 512;\n\
+         export const BOOT_EPOCH = 1700000000000n;\n\
+         export const MIN_OFFSET = -40;\n\
+         export const TOPIC = \"devices/\\\"hall\\\"/telemetry\";\n"
+    ));
+}
+
+#[test]
+fn writes_constants_ts_with_layouts_writing_several_files() {
+    let files = generator()
+        .generate_types_files(vec![], Layout::Modules)
+        .unwrap();
+    let index = files.iter().find(|file| file.path == "index.ts").unwrap();
+    assert!(index
+        .source
+        .ends_with("export * from \"./constants.js\";\n"));
+    let constants = files
+        .iter()
+        .find(|file| file.path == "constants.ts")
+        .unwrap();
+    assert!(constants
+        .source
+        .starts_with("// Generated by serde-postcard-ts"));
+    assert!(constants
+        .source
+        .contains("\nexport const MAX_FRAME_LENGTH = 512;\n"));
+
+    let single = generator()
+        .generate_types_files(vec![], Layout::Single)
+        .unwrap();
+    assert!(single.iter().all(|file| file.path != "constants.ts"));
+}
+
+#[test]
+fn shares_constants_ts_with_a_group_of_that_name() {
+    let files = generator()
+        .group(ProtocolGroup::new("constants", 2))
+        .generate_types_files(vec![], Layout::Groups)
+        .unwrap();
+    let shared: Vec<_> = files
+        .iter()
+        .filter(|file| file.path == "constants.ts")
+        .collect();
+    assert_eq!(shared.len(), 1);
+    assert!(shared[0]
+        .source
+        .contains("export const CONSTANTS_PROTOCOL_HASH = 0x"));
+    assert!(shared[0].source.ends_with("export const MIN_OFFSET = -40;\nexport const TOPIC = \"devices/\\\"hall\\\"/telemetry\";\n"));
+}
+
+#[test]
+fn leaves_the_constants_out_of_type_declarations() {
+    let declarations = generator().module_format(ModuleFormat::Declarations);
+    let source = declarations.generate_types(vec![]).unwrap();
+    assert!(!source.contains("FRAME_MAGIC"));
+    let files = declarations
+        .generate_types_files(vec![], Layout::Modules)
+        .unwrap();
+    assert!(files.iter().all(|file| !file.source.contains("constants")));
+}
+
+#[test]
+fn writes_a_rust_module_of_the_same_constants() {
+    let module = generator().generate_rust_constants().unwrap();
+    assert_eq!(
+        module,
+        "// Generated by serde-postcard-ts from the protocol constants; do not edit.\n\
+         \n\
+         /// Starts every frame\n\
+         ///\n\
+         /// Then comes the length\n\
+         pub const FRAME_MAGIC: [u8; 2] = [0xca, 0xfe];\n\
+         \n\
+         pub const MAX_FRAME_LENGTH: usize = 512;\n\
+         \n\
+         pub const BOOT_EPOCH: u64 = 1700000000000;\n\
+         \n\
+         pub const MIN_OFFSET: i8 = -40;\n\
+         \n\
+         pub const TOPIC: &str = \"devices/\\\"hall\\\"/telemetry\";\n"
+    );
+}
+
+#[test]
+fn rejects_constants_not_named_as_constants_or_named_twice() {
+    let lowercase = Generator::new().constant(ProtocolConstant::new("maxLength", 1u8));
+    assert_eq!(
+        lowercase.generate_rust_constants(),
+        Err(GenerateError::InvalidConstant {
+            name: "maxLength".to_string(),
+            reason: "use uppercase letters, digits and _, starting with a letter",
+        })
+    );
+    let twice = generator().constant(ProtocolConstant::new("TOPIC", "other"));
+    let error = twice.generate_types(vec![]).unwrap_err();
+    assert_eq!(error.code(), "invalid_constant");
+    assert_eq!(
+        error.to_string(),
+        "invalid protocol constant \"TOPIC\": it is declared twice"
+    );
+}
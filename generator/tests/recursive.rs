@@ -0,0 +1,129 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde_postcard_ts::{
+    registered, Definition, GenerateError, Generator, Layout, PostcardTs, Shape, TypeName,
+};
+
+#[derive(PostcardTs)]
+enum Expr {
+    Lit(i64),
+    Add(Box<Expr>, Box<Expr>),
+    Call { name: String, args: Vec<Expr> },
+}
+
+#[derive(PostcardTs)]
+struct TreeNode {
+    label: String,
+    children: Vec<Rc<TreeNode>>,
+    parent: Option<Arc<Expr>>,
+}
+
+// Refer to each other
+#[derive(PostcardTs)]
+struct Statement {
+    expr: Expr,
+    block: Option<Block>,
+}
+
+#[derive(PostcardTs)]
+struct Block {
+    statements: Vec<Statement>,
+}
+
+#[test]
+fn treats_shared_pointers_as_what_they_point_to() {
+    assert_eq!(<Rc<u8>>::shape(), Shape::U8);
+    assert_eq!(<Arc<str>>::shape(), Shape::String);
+    assert_eq!(<Arc<Box<Expr>>>::shape(), Expr::shape());
+}
+
+#[test]
+fn spells_out_recursive_types_and_refers_to_them_lazily() {
+    let source = Generator::new().generate_types(registered()).unwrap();
+
+    assert!(source.contains(
+        "export type Expr =\n  \
+         | { type: \"Lit\"; value: bigint }\n  \
+         | { type: \"Add\"; value: [Expr, Expr] }\n  \
+         | { type: \"Call\"; value: { name: string; args: Expr[] } };\n\
+         export const ExprSchema = enumType(\"Expr\", {\n  \
+         Lit: newtypeVariant(\"Lit\", i64()),\n  \
+         Add: tupleVariant(\"Add\", lazy<Expr>((): Schema => ExprSchema), lazy<Expr>((): Schema => ExprSchema)),\n"
+    ));
+    // Types of other circles are declared before, so they are used as they are
+    assert!(source.contains(
+        "export interface TreeNode {\n  \
+         label: string;\n  \
+         children: TreeNode[];\n  \
+         parent: Expr | null;\n\
+         }\n\
+         export const TreeNodeSchema = struct({\n  \
+         label: string(),\n  \
+         children: seq(lazy<TreeNode>((): Schema => TreeNodeSchema)),\n  \
+         parent: option(ExprSchema),\n\
+         });\n"
+    ));
+    assert!(source.contains("  block: option(lazy<Block>((): Schema => BlockSchema)),\n"));
+    assert!(source.contains("  statements: seq(lazy<Statement>((): Schema => StatementSchema)),\n"));
+    assert!(source.contains("  lazy,\n"));
+    // Every type is spelled out, none inferred
+    assert!(source.contains("  tupleVariant,\n  type Schema,\n}"));
+}
+
+#[test]
+fn flattens_struct_variants_of_discriminated_recursive_enums() {
+    let source = Generator::new()
+        .enum_tag("kind")
+        .generate_types(registered())
+        .unwrap();
+    assert!(source.contains("  | { kind: \"Call\"; name: string; args: Expr[] };\n"));
+}
+
+#[test]
+fn keeps_recursive_types_in_one_module_file() {
+    let files = Generator::new()
+        .generate_types_files(registered(), Layout::Modules)
+        .unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files[0].source.contains("export interface Block {\n"));
+}
+
+#[test]
+fn rejects_recursive_generic_types() {
+    let tree = TypeName {
+        name: "Tree",
+        module: "m",
+        params: &["T"],
+    };
+    let forest = TypeName {
+        name: "Forest",
+        module: "m",
+        params: &[],
+    };
+    let types = vec![
+        (
+            forest,
+            Definition::NewtypeStruct(Shape::Generic(tree, vec![Shape::U8])),
+        ),
+        (
+            tree,
+            Definition::TupleStruct(vec![
+                Shape::Param("T"),
+                Shape::Seq(Box::new(Shape::Named(forest))),
+            ]),
+        ),
+    ];
+    let error = Generator::new().generate_types(types).unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::Recursive(vec!["Tree", "Forest", "Tree"])
+    );
+    assert_eq!(
+        error.to_string(),
+        "recursive generic types are not supported: Tree -> Forest -> Tree"
+    );
+}
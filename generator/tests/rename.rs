@@ -0,0 +1,102 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{generate_all, registered, Definition, PostcardTs};
+
+#[derive(PostcardTs)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct DeviceSettings {
+    sample_rate_hz: u32,
+    #[serde(rename = "id")]
+    device_id: u16,
+    #[serde(default, with = "module")]
+    #[postcard_ts(with = "u8")]
+    r#type: u8,
+    mode: DeviceMode,
+}
+
+#[derive(PostcardTs)]
+#[serde(rename_all = "snake_case", rename_all_fields = "SCREAMING_SNAKE_CASE")]
+enum DeviceMode {
+    LowPower,
+    Streaming {
+        batch_size: u8,
+    },
+    #[serde(rename_all = "kebab-case")]
+    Triggered {
+        pre_trigger: u16,
+    },
+    #[serde(rename(serialize = "off", deserialize = "off"))]
+    Disabled,
+}
+
+// Names need not start with an ASCII letter
+#[derive(PostcardTs)]
+#[serde(rename_all = "camelCase")]
+enum Category {
+    Ärger,
+    Ölstand,
+}
+
+#[derive(PostcardTs)]
+#[serde(rename_all = "camelCase")]
+struct Anzeige {
+    ölstand: u8,
+    über_druck: bool,
+}
+
+const EXPECTED: &str = r#"export const DeviceModeSchema = enumType("DeviceMode", {
+  low_power: unitVariant("low_power"),
+  streaming: structVariant("streaming", { BATCH_SIZE: u8() }),
+  triggered: structVariant("triggered", { "pre-trigger": u16() }),
+  off: unitVariant("off"),
+});
+export type DeviceMode = InferType<typeof DeviceModeSchema>;
+
+export const DeviceSettingsSchema = struct({
+  sampleRateHz: u32(),
+  id: u16(),
+  type: u8(),
+  mode: DeviceModeSchema,
+});
+export type DeviceSettings = InferType<typeof DeviceSettingsSchema>;
+"#;
+
+#[test]
+fn generates_serde_names() {
+    assert!(generate_all().unwrap().ends_with(EXPECTED));
+}
+
+#[test]
+fn keeps_the_field_order() {
+    let (_, definition) = registered()
+        .into_iter()
+        .find(|(type_name, _)| type_name.name == "DeviceSettings")
+        .unwrap();
+    let Definition::Struct(fields) = definition else {
+        panic!("{:?} is not a struct", definition);
+    };
+    let names: Vec<_> = fields.iter().map(|field| field.name).collect();
+    assert_eq!(names, ["sampleRateHz", "id", "type", "mode"]);
+}
+
+#[test]
+fn renames_non_ascii_names() {
+    let definition = |name: &str| {
+        registered()
+            .into_iter()
+            .find(|(type_name, _)| type_name.name == name)
+            .unwrap()
+            .1
+    };
+    let Definition::Enum(variants) = definition("Category") else {
+        panic!("Category is not an enum");
+    };
+    let names: Vec<_> = variants.iter().map(|variant| variant.name).collect();
+    assert_eq!(names, ["ärger", "ölstand"]);
+    let Definition::Struct(fields) = definition("Anzeige") else {
+        panic!("Anzeige is not a struct");
+    };
+    let names: Vec<_> = fields.iter().map(|field| field.name).collect();
+    assert_eq!(names, ["ölstand", "überDruck"]);
+}
@@ -0,0 +1,99 @@
+use serde_postcard_ts::{RoundTrip, RoundTripDifference, TracedValue};
+
+fn value(path: &str, kind: &str, offset: usize, len: usize) -> TracedValue {
+    TracedValue {
+        path: path.to_string(),
+        kind: kind.to_string(),
+        offset,
+        len,
+    }
+}
+
+/// `GameState { id, health, name }`, inner values first, with `health`
+/// `health_len` bytes long
+fn values(health_len: usize) -> Vec<TracedValue> {
+    vec![
+        value("GameState.id", "u8", 0, 1),
+        value("GameState.health", "u16", 1, health_len),
+        value("GameState.name", "string", 1 + health_len, 3),
+        value("GameState", "struct", 0, 4 + health_len),
+    ]
+}
+
+/// A health of 128 read from an overlong varint, re-encoded to one byte, and
+/// a stray byte after the value
+fn garbled() -> RoundTrip {
+    RoundTrip {
+        type_name: "GameState".to_string(),
+        original: vec![0x01, 0x80, 0x01, 0x02, b'h', b'i', 0x00],
+        bytes_read: 6,
+        values: values(2),
+        reencoded: vec![0x01, 0x7f, 0x02, b'h', b'i'],
+        reencoded_values: Some(values(1)),
+    }
+}
+
+#[test]
+fn names_the_innermost_values_that_differ() {
+    let round_trip = garbled();
+    assert!(!round_trip.is_identical());
+    assert_eq!(
+        round_trip.differences(),
+        [
+            RoundTripDifference {
+                value: Some(value("GameState.health", "u16", 1, 2)),
+                original: 1..3,
+                reencoded: Some(1..2),
+            },
+            RoundTripDifference {
+                value: None,
+                original: 6..7,
+                reencoded: None,
+            },
+        ]
+    );
+    assert_eq!(
+        round_trip.report(),
+        "GameState: 7 bytes, 6 read, re-encoded to 5 bytes\n\
+         \n\
+         original             re-encoded           value\n   \
+            1: 80 01             1: 7f             GameState.health (u16)\n   \
+            6: 00             -                    not read\n\
+         2 differences\n"
+    );
+}
+
+#[test]
+fn falls_back_to_the_first_byte_that_differs() {
+    let round_trip = RoundTrip {
+        reencoded_values: None,
+        ..garbled()
+    };
+    assert_eq!(
+        round_trip.differences()[0],
+        RoundTripDifference {
+            value: Some(value("GameState.health", "u16", 1, 2)),
+            original: 1..6,
+            reencoded: Some(1..5),
+        }
+    );
+}
+
+#[test]
+fn reports_a_payload_that_comes_back_the_same() {
+    let original = vec![0x01, 0x7f, 0x02, b'h', b'i'];
+    let round_trip = RoundTrip {
+        type_name: "GameState".to_string(),
+        original: original.clone(),
+        bytes_read: 5,
+        values: values(1),
+        reencoded: original,
+        reencoded_values: Some(values(1)),
+    };
+    assert!(round_trip.is_identical());
+    assert!(round_trip.differences().is_empty());
+    assert_eq!(
+        round_trip.report(),
+        "GameState: 5 bytes, re-encoded identically\n"
+    );
+}
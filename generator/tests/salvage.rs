@@ -0,0 +1,57 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{Generator, PostcardTs};
+
+#[derive(PostcardTs)]
+struct LogEntry {
+    at: u32,
+    message: String,
+}
+
+#[derive(PostcardTs)]
+struct LogBatch {
+    device: u8,
+    samples: Vec<u16>,
+    entries: Vec<LogEntry>,
+    checksum: u16,
+}
+
+#[derive(PostcardTs)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+#[derive(PostcardTs)]
+struct Upload {
+    entries: Vec<LogEntry>,
+}
+
+#[test]
+fn emits_a_salvage_decoder_per_batch() {
+    let source = Generator::new()
+        .salvage()
+        .encode_only(["Upload"])
+        .generate()
+        .unwrap();
+    assert!(source.contains("  salvage,\n"));
+    assert!(source.contains("  type DeserializeOptions,\n"));
+    // The list of records, not the list of numbers before it
+    assert!(source.contains(
+        "export const salvageLogBatch = (\n  \
+         data: Uint8Array,\n  \
+         offset?: number,\n  \
+         options?: DeserializeOptions\n\
+         ) => salvage(LogBatchSchema, \"entries\", data, offset, options);\n"
+    ));
+    // Nor records with no list, generic batches or those only encoded
+    assert!(!source.contains("salvageLogEntry"));
+    assert!(!source.contains("salvagePage"));
+    assert!(!source.contains("salvageUpload"));
+}
+
+#[test]
+fn emits_no_salvage_decoders_by_default() {
+    let source = Generator::new().generate().unwrap();
+    assert!(!source.contains("salvage"));
+}
@@ -0,0 +1,49 @@
+// The example's messages only exist to be generated here
+#![allow(dead_code)]
+
+#[path = "../templates/websocket-dashboard/server/src/lib.rs"]
+mod websocket_dashboard;
+
+use serde_postcard_ts::{generate_all, Example};
+
+#[test]
+fn names_examples() {
+    assert_eq!(
+        Example::from_name("websocket-dashboard"),
+        Some(Example::WebsocketDashboard)
+    );
+    assert_eq!(Example::from_name("dashboard"), None);
+}
+
+#[test]
+fn ships_up_to_date_bindings() {
+    let files = Example::WebsocketDashboard.files();
+    let bindings = files
+        .iter()
+        .find(|file| file.path == "web/src/generated/index.ts")
+        .unwrap();
+    assert_eq!(bindings.source, generate_all().unwrap());
+}
+
+#[test]
+fn points_at_this_checkout() {
+    let files = Example::WebsocketDashboard.files();
+    let source = |path: &str| &files.iter().find(|file| file.path == path).unwrap().source;
+
+    let generator = format!(
+        "serde-postcard-ts = {{ path = {:?} }}",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    assert!(source("server/Cargo.toml").contains(&generator));
+
+    let runtime = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("src/index.ts");
+    let runtime = format!("{:?}", runtime.display().to_string());
+    assert!(source("web/vite.config.ts").contains(&runtime));
+    assert!(source("web/tsconfig.json").contains(&runtime));
+    assert!(files
+        .iter()
+        .all(|file| !file.source.contains("{{runtime}}")));
+}
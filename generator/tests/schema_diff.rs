@@ -0,0 +1,266 @@
+use serde_postcard_ts::{
+    diff_snapshots, Compatibility, Definition, Field, Generator, Shape, SnapshotError, TypeName,
+    Variant, VariantKind,
+};
+
+fn named(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params: &[],
+    }
+}
+
+fn fields(fields: &[(&'static str, Shape)]) -> Definition {
+    Definition::Struct(
+        fields
+            .iter()
+            .map(|(name, shape)| Field {
+                name,
+                shape: shape.clone(),
+            })
+            .collect(),
+    )
+}
+
+fn units(names: &[&'static str]) -> Definition {
+    Definition::Enum(
+        names
+            .iter()
+            .map(|name| Variant {
+                name,
+                kind: VariantKind::Unit,
+            })
+            .collect(),
+    )
+}
+
+fn snapshot(types: Vec<(&'static str, Definition)>) -> String {
+    let types = types
+        .into_iter()
+        .map(|(name, definition)| (named(name), definition))
+        .collect();
+    Generator::new().generate_types_ir(types).unwrap()
+}
+
+/// The report lines of the changes from `old` to `new`, without the count
+fn changes(
+    old: Vec<(&'static str, Definition)>,
+    new: Vec<(&'static str, Definition)>,
+) -> Vec<String> {
+    let diff = diff_snapshots(&snapshot(old), &snapshot(new)).unwrap();
+    diff.changes.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn finds_no_changes_between_equal_snapshots() {
+    let types = || vec![("Reading", fields(&[("sensor", Shape::U8)]))];
+    let diff = diff_snapshots(&snapshot(types()), &snapshot(types())).unwrap();
+    assert!(diff.changes.is_empty());
+    assert!(!diff.is_breaking());
+    assert_eq!(diff.report(), "no changes\n");
+}
+
+#[test]
+fn classifies_field_changes() {
+    let old = vec![(
+        "Reading",
+        fields(&[
+            ("sensor", Shape::U8),
+            ("value", Shape::F32),
+            ("raw", Shape::U16),
+        ]),
+    )];
+    // Renamed in place, widened and removed
+    let new = vec![(
+        "Reading",
+        fields(&[("sensor_id", Shape::U8), ("value", Shape::F64)]),
+    )];
+    assert_eq!(
+        changes(old, new),
+        [
+            "breaking    Reading.raw: removed",
+            "compatible  Reading.sensor_id: renamed from sensor",
+            "breaking    Reading.value: f32 -> f64",
+        ]
+    );
+
+    let old = vec![("Reading", fields(&[("a", Shape::U8), ("b", Shape::U8)]))];
+    let new = vec![(
+        "Reading",
+        fields(&[("b", Shape::U8), ("a", Shape::U8), ("c", Shape::Bool)]),
+    )];
+    assert_eq!(
+        changes(old, new),
+        [
+            "breaking    Reading: fields reordered: a, b -> b, a",
+            "breaking    Reading.c: added",
+        ]
+    );
+}
+
+#[test]
+fn classifies_variant_changes() {
+    let old = vec![("Mode", units(&["Idle", "Run", "Stop"]))];
+    let new = vec![("Mode", units(&["Idle", "Running", "Stop", "Boost"]))];
+    assert_eq!(
+        changes(old, new),
+        [
+            "compatible  Mode::Running: renamed from Run",
+            "compatible  Mode::Boost: added",
+        ]
+    );
+
+    let old = vec![("Mode", units(&["Idle", "Run", "Stop"]))];
+    let new = vec![("Mode", units(&["Run", "Idle"]))];
+    assert_eq!(
+        changes(old, new),
+        [
+            "breaking    Mode::Idle: moved from index 0 to 1",
+            "breaking    Mode::Run: moved from index 1 to 0",
+            "breaking    Mode::Stop: removed",
+        ]
+    );
+
+    let old = vec![("Mode", units(&["Idle", "Stop"]))];
+    let new = vec![("Mode", units(&["Idle", "Boost", "Stop"]))];
+    assert_eq!(
+        changes(old, new),
+        [
+            "breaking    Mode::Stop: moved from index 1 to 2",
+            "breaking    Mode::Boost: added at index 1, before variants already in use",
+        ]
+    );
+}
+
+#[test]
+fn accepts_changes_encoded_alike() {
+    let old = vec![
+        ("Meters", Definition::NewtypeStruct(Shape::U16)),
+        (
+            "Reading",
+            fields(&[
+                ("range", Shape::U16),
+                ("payload", Shape::Seq(Box::new(Shape::U8))),
+            ]),
+        ),
+    ];
+    let new = vec![
+        ("Meters", Definition::TupleStruct(vec![Shape::U16])),
+        (
+            "Reading",
+            fields(&[
+                ("range", Shape::Named(named("Meters"))),
+                ("payload", Shape::Bytes),
+            ]),
+        ),
+    ];
+    assert_eq!(
+        changes(old, new),
+        [
+            "compatible  Meters: newtype struct -> tuple struct",
+            "compatible  Reading.range: u16 -> Meters, encoded alike",
+            "compatible  Reading.payload: Vec<u8> -> bytes, encoded alike",
+        ]
+    );
+}
+
+#[test]
+fn compares_recursive_types_by_their_encoding() {
+    let expr = |name: &'static str| {
+        Definition::Enum(vec![
+            Variant {
+                name: "Lit",
+                kind: VariantKind::Newtype(Shape::I64),
+            },
+            Variant {
+                name: "Neg",
+                kind: VariantKind::Newtype(Shape::Named(named(name))),
+            },
+        ])
+    };
+    let old = vec![
+        ("Expr", expr("Expr")),
+        ("Query", fields(&[("filter", Shape::Named(named("Expr")))])),
+    ];
+    let new = vec![
+        ("Expr", expr("Expr")),
+        ("Term", expr("Term")),
+        ("Query", fields(&[("filter", Shape::Named(named("Term")))])),
+    ];
+    assert_eq!(
+        changes(old, new),
+        [
+            "compatible  Query.filter: Expr -> Term, encoded alike",
+            "compatible  Term: added",
+        ]
+    );
+}
+
+#[test]
+fn checks_length_limits() {
+    let seq = |max| Shape::BoundedSeq(Box::new(Shape::U16), max);
+    let old = vec![(
+        "Batch",
+        fields(&[
+            ("samples", seq(16)),
+            ("tags", seq(8)),
+            ("name", Shape::String),
+        ]),
+    )];
+    let new = vec![(
+        "Batch",
+        fields(&[
+            ("samples", seq(8)),
+            ("tags", seq(32)),
+            ("name", Shape::BoundedString(24)),
+        ]),
+    )];
+    assert_eq!(
+        changes(old, new),
+        [
+            "breaking    Batch.samples: at most 16 items -> 8",
+            "compatible  Batch.tags: at most 8 items -> 32",
+            "breaking    Batch.name: now at most 24 bytes",
+        ]
+    );
+}
+
+#[test]
+fn reports_removed_and_added_types() {
+    let old = vec![("Ping", Definition::UnitStruct), ("Mode", units(&["Idle"]))];
+    let new = vec![
+        ("Mode", fields(&[("idle", Shape::Bool)])),
+        ("Pong", Definition::UnitStruct),
+    ];
+    let diff = diff_snapshots(&snapshot(old), &snapshot(new)).unwrap();
+    assert!(diff.is_breaking());
+    assert_eq!(
+        diff.report(),
+        "breaking    Ping: removed\n\
+         breaking    Mode: enum -> struct\n\
+         compatible  Pong: added\n\
+         3 changes, 2 breaking\n"
+    );
+    assert_eq!(
+        diff.breaking().next().map(|change| change.compatibility),
+        Some(Compatibility::Breaking)
+    );
+}
+
+#[test]
+fn rejects_files_that_are_not_snapshots() {
+    let types = snapshot(vec![("Ping", Definition::UnitStruct)]);
+    assert!(matches!(
+        diff_snapshots("{\"types\": []}", &types),
+        Err(SnapshotError::Malformed(_))
+    ));
+    assert!(matches!(
+        diff_snapshots(&types, "[1, 2"),
+        Err(SnapshotError::Malformed(_))
+    ));
+    assert_eq!(
+        diff_snapshots("{\"version\": 99, \"types\": []}", &types),
+        Err(SnapshotError::UnsupportedVersion(99))
+    );
+}
@@ -0,0 +1,256 @@
+// The derived types only exist to be hashed
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashSet};
+use std::num::NonZeroU16;
+use std::time::Duration;
+
+use serde_postcard_ts::{
+    check_schema_hashes, check_types_schema_hashes, schema_hash, schema_hashes, Definition, Field,
+    Generator, PostcardTs, Shape, TypeName, Variant, VariantKind,
+};
+
+#[derive(PostcardTs)]
+#[postcard_ts(schema_hash = 0xed07_686b_2b90_34f9)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+enum Command {
+    Stop,
+    SetTarget(f32),
+}
+
+#[derive(PostcardTs)]
+struct Unpinned(u8);
+
+#[derive(PostcardTs)]
+enum Expr {
+    Literal(i32),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+#[derive(PostcardTs)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(PostcardTs)]
+struct List<T> {
+    head: T,
+    tail: Option<Box<List<T>>>,
+}
+
+#[derive(PostcardTs)]
+enum Tree<T> {
+    Leaf(T),
+    Branch(Vec<Tree<T>>, Wrapper<List<T>>),
+}
+
+#[derive(PostcardTs)]
+struct Everything {
+    flags: (bool, char, i128, u64),
+    #[postcard_ts(max_len = 16)]
+    name: String,
+    #[serde(with = "serde_bytes")]
+    blob: Vec<u8>,
+    readings: BTreeMap<String, [f64; 3]>,
+    seen: HashSet<NonZeroU16>,
+    uptime: Option<Duration>,
+    #[postcard_ts(with = "u32")]
+    #[serde(with = "level")]
+    level: u8,
+    trees: List<Tree<Expr>>,
+    words: Tree<List<Box<str>>>,
+    #[serde(skip)]
+    cache: Vec<u8>,
+    nothing: (),
+}
+
+fn type_name(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params: &[],
+    }
+}
+
+fn field(name: &'static str, shape: Shape) -> Field {
+    Field { name, shape }
+}
+
+/// The hash of a struct `T` among `others`
+fn hash(definition: Definition, others: Vec<(TypeName, Definition)>) -> u64 {
+    let mut types = others;
+    types.push((type_name("T"), definition));
+    schema_hashes(&types)
+        .unwrap()
+        .into_iter()
+        .find(|(type_name, _)| type_name.name == "T")
+        .unwrap()
+        .1
+}
+
+fn reading() -> Definition {
+    Definition::Struct(vec![
+        field("sensor", Shape::U8),
+        field("celsius", Shape::F32),
+    ])
+}
+
+#[test]
+fn ignores_names_and_struct_boundaries() {
+    let expected = hash(reading(), vec![]);
+
+    let renamed = Definition::Struct(vec![field("id", Shape::U8), field("value", Shape::F32)]);
+    assert_eq!(hash(renamed, vec![]), expected);
+    assert_eq!(
+        hash(Definition::TupleStruct(vec![Shape::U8, Shape::F32]), vec![]),
+        expected
+    );
+    let nested = Definition::Struct(vec![
+        field("sensor", Shape::Named(type_name("Id"))),
+        field("celsius", Shape::Tuple(vec![Shape::F32])),
+    ]);
+    let id = (type_name("Id"), Definition::NewtypeStruct(Shape::U8));
+    assert_eq!(hash(nested, vec![id]), expected);
+}
+
+#[test]
+fn changes_with_the_wire_shape() {
+    let expected = hash(reading(), vec![]);
+
+    let reordered = Definition::Struct(vec![
+        field("celsius", Shape::F32),
+        field("sensor", Shape::U8),
+    ]);
+    let widened = Definition::Struct(vec![
+        field("sensor", Shape::U16),
+        field("celsius", Shape::F32),
+    ]);
+    let signed = Definition::Struct(vec![
+        field("sensor", Shape::I8),
+        field("celsius", Shape::F32),
+    ]);
+    let optional = Definition::Struct(vec![
+        field("sensor", Shape::U8),
+        field("celsius", Shape::Option(Box::new(Shape::F32))),
+    ]);
+    for changed in [reordered, widened, signed, optional] {
+        assert_ne!(hash(changed, vec![]), expected);
+    }
+
+    let variants = |names: &[&'static str]| {
+        Definition::Enum(
+            names
+                .iter()
+                .map(|name| Variant {
+                    name,
+                    kind: if *name == "Set" {
+                        VariantKind::Newtype(Shape::F32)
+                    } else {
+                        VariantKind::Unit
+                    },
+                })
+                .collect(),
+        )
+    };
+    let enum_hash = hash(variants(&["Stop", "Set"]), vec![]);
+    assert_eq!(hash(variants(&["Halt", "Set"]), vec![]), enum_hash);
+    assert_ne!(hash(variants(&["Set", "Stop"]), vec![]), enum_hash);
+    assert_ne!(hash(variants(&["Stop", "Pause", "Set"]), vec![]), enum_hash);
+}
+
+#[test]
+fn is_stable() {
+    // FNV-1a of "u8;f32;"; changing it breaks every pinned hash
+    assert_eq!(hash(reading(), vec![]), 0xed07_686b_2b90_34f9);
+    assert_eq!(schema_hash::<Reading>().unwrap(), hash(reading(), vec![]));
+}
+
+#[test]
+fn hashes_recursive_and_generic_types() {
+    let expr = schema_hash::<Expr>().unwrap();
+    assert_ne!(expr, schema_hash::<i32>().unwrap());
+
+    let u8_hash = schema_hash::<Wrapper<u8>>().unwrap();
+    assert_eq!(u8_hash, schema_hash::<u8>().unwrap());
+    assert_eq!(schema_hash::<Wrapper<Wrapper<u8>>>().unwrap(), u8_hash);
+    assert_ne!(schema_hash::<Wrapper<u16>>().unwrap(), u8_hash);
+}
+
+#[test]
+fn checks_pinned_hashes() {
+    let checks = check_schema_hashes().unwrap();
+    let check = |name: &str| {
+        checks
+            .iter()
+            .find(|check| check.type_name.name == name)
+            .unwrap()
+    };
+
+    assert_eq!(Reading::SCHEMA_HASH, schema_hash::<Reading>().unwrap());
+    assert!(!check("Reading").is_stale());
+    assert_eq!(check("Reading").pinned, Some(Reading::SCHEMA_HASH));
+    assert_eq!(check("Unpinned").pinned, None);
+    assert!(!check("Unpinned").is_stale());
+    // Generic types are hashed where they are used
+    assert!(checks.iter().all(|check| check.type_name.name != "Wrapper"));
+}
+
+#[test]
+fn declares_the_hash_of_every_non_generic_type() {
+    assert_eq!(Reading::SCHEMA_HASH, 0xed07_686b_2b90_34f9);
+    assert_eq!(Command::SCHEMA_HASH, schema_hash::<Command>().unwrap());
+    assert_eq!(Unpinned::SCHEMA_HASH, schema_hash::<Unpinned>().unwrap());
+    assert_eq!(Expr::SCHEMA_HASH, schema_hash::<Expr>().unwrap());
+    assert_eq!(
+        Everything::SCHEMA_HASH,
+        schema_hash::<Everything>().unwrap()
+    );
+    assert_ne!(Everything::SCHEMA_HASH, schema_hash::<Expr>().unwrap());
+}
+
+#[test]
+fn reports_stale_pins() {
+    let types = vec![(type_name("Reading"), reading())];
+    let hash = hash(reading(), vec![]);
+
+    let checks = check_types_schema_hashes(&types, &[(type_name("Reading"), hash)]).unwrap();
+    assert!(!checks[0].is_stale());
+    let checks = check_types_schema_hashes(&types, &[(type_name("Reading"), hash ^ 1)]).unwrap();
+    assert!(checks[0].is_stale());
+    assert_eq!(checks[0].hash, hash);
+}
+
+#[test]
+fn emits_hashes_of_non_generic_types() {
+    let envelope = TypeName {
+        name: "Envelope",
+        module: "app",
+        params: &["T"],
+    };
+    let types = vec![
+        (
+            envelope,
+            Definition::Struct(vec![field("payload", Shape::Param("T"))]),
+        ),
+        (type_name("Reading"), reading()),
+    ];
+    let source = Generator::new()
+        .schema_hashes()
+        .generate_types(types.clone())
+        .unwrap();
+
+    assert!(source.ends_with(&format!(
+        "export type Reading = InferType<typeof ReadingSchema>;\nexport const ReadingSchemaHash = 0x{:016x}n;\n",
+        hash(reading(), vec![])
+    )));
+    assert!(!source.contains("EnvelopeSchemaHash"));
+    assert!(!Generator::new()
+        .generate_types(types)
+        .unwrap()
+        .contains("SchemaHash"));
+}
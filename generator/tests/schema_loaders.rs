@@ -0,0 +1,100 @@
+use serde_postcard_ts::{
+    Definition, Field, GeneratedFile, Generator, Layout, ModuleFormat, Shape, TypeName,
+};
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    vec![
+        (
+            type_name("Reading", "app::sensors"),
+            Definition::Struct(vec![Field {
+                name: "sensor_id",
+                shape: Shape::U8,
+            }]),
+        ),
+        (
+            type_name("Level", "app"),
+            Definition::NewtypeStruct(Shape::U16),
+        ),
+        (
+            TypeName {
+                name: "Envelope",
+                module: "app",
+                params: &["T"],
+            },
+            Definition::Struct(vec![Field {
+                name: "body",
+                shape: Shape::Param("T"),
+            }]),
+        ),
+    ]
+}
+
+fn loaders(files: &[GeneratedFile]) -> Option<&str> {
+    files
+        .iter()
+        .find(|file| file.path.starts_with("loaders."))
+        .map(|file| file.source.as_str())
+}
+
+#[test]
+fn imports_each_type_from_its_module() {
+    let files = Generator::new()
+        .schema_loaders()
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    assert!(loaders(&files).unwrap().ends_with(
+        "import type { SchemaLoaders } from \"@variegated-coffee/serde-postcard-ts\";\n\
+         \n\
+         /** Each type's schema, imported with the module declaring it on first use */\n\
+         export const SCHEMA_LOADERS = {\n  \
+           Reading: () => import(\"./app/sensors.js\").then((module) => module.ReadingSchema),\n  \
+           Level: () => import(\"./app.js\").then((module) => module.LevelSchema),\n\
+         } satisfies SchemaLoaders;\n"
+    ));
+    // The index does not import the loaders, which would defeat the split
+    let index = files.iter().find(|file| file.path == "index.ts").unwrap();
+    assert!(!index.source.contains("loaders"));
+}
+
+#[test]
+fn follows_the_layout_and_module_format() {
+    let files = Generator::new()
+        .schema_loaders()
+        .generate_types_files(types(), Layout::Single)
+        .unwrap();
+    assert!(loaders(&files)
+        .unwrap()
+        .contains("  Level: () => import(\"./index.js\").then((module) => module.LevelSchema),\n"));
+
+    let files = Generator::new()
+        .schema_loaders()
+        .module_format(ModuleFormat::CommonJs)
+        .generate_types_files(types(), Layout::Types)
+        .unwrap();
+    assert!(files.iter().any(|file| file.path == "loaders.cts"));
+    assert!(loaders(&files).unwrap().contains(
+        "  Reading: () => import(\"./types/Reading.cjs\").then((module) => module.ReadingSchema),\n"
+    ));
+}
+
+#[test]
+fn is_left_out_by_default_and_of_declarations() {
+    let files = Generator::new()
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    assert!(loaders(&files).is_none());
+    let files = Generator::new()
+        .schema_loaders()
+        .module_format(ModuleFormat::Declarations)
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    assert!(loaders(&files).is_none());
+}
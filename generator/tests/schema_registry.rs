@@ -0,0 +1,146 @@
+// The derived types only exist to be registered
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use serde_postcard_ts::{
+    registered, schema_hash, schema_hashes, snapshot_schema_hashes, Generator, PostcardTs,
+    SchemaRegistry,
+};
+
+#[derive(PostcardTs)]
+struct Reading {
+    sensor: u8,
+    celsius: f32,
+}
+
+#[derive(PostcardTs)]
+struct Envelope<T> {
+    id: u32,
+    payload: Option<T>,
+}
+
+#[derive(PostcardTs)]
+enum Expr {
+    Literal(i32),
+    Add(Box<Expr>, Box<Expr>),
+    Tagged { name: String, inner: Vec<Expr> },
+}
+
+#[derive(PostcardTs)]
+struct Telemetry {
+    reading: Envelope<Reading>,
+    expressions: BTreeMap<String, Envelope<Expr>>,
+    raw: (u64, bool, ()),
+}
+
+#[test]
+fn hashes_snapshots_as_the_rust_types() {
+    let snapshot = Generator::new().generate_ir().unwrap();
+    let from_snapshot: BTreeMap<String, u64> = snapshot_schema_hashes(&snapshot)
+        .unwrap()
+        .into_iter()
+        .collect();
+    let from_types: BTreeMap<String, u64> = schema_hashes(&registered())
+        .unwrap()
+        .into_iter()
+        .map(|(type_name, hash)| (type_name.name.to_string(), hash))
+        .collect();
+    assert_eq!(from_snapshot, from_types);
+    assert_eq!(
+        from_snapshot["Telemetry"],
+        schema_hash::<Telemetry>().unwrap()
+    );
+    // Generic types have no hash of their own
+    assert!(!from_snapshot.contains_key("Envelope"));
+}
+
+#[test]
+fn serves_the_snapshot_of_a_reported_hash() {
+    let snapshot = Generator::new().generate_ir().unwrap();
+    let hash = schema_hash::<Reading>().unwrap();
+    let mut registry = SchemaRegistry::new();
+
+    let uploaded = registry.respond("POST", "/ir", snapshot.as_bytes());
+    assert_eq!(uploaded.status, 201);
+    assert!(uploaded
+        .body
+        .contains(&format!("\"Reading\": \"{:016x}\"", hash)));
+    // Uploading it again adds nothing
+    registry.respond("POST", "/ir", snapshot.as_bytes());
+    assert_eq!(registry.entries().len(), 1);
+
+    let found = registry.respond("GET", &format!("/ir/Reading/{:016x}", hash), &[]);
+    assert_eq!((found.status, found.body), (200, snapshot));
+    let missing = registry.respond("GET", &format!("/ir/Expr/{:016x}", hash), &[]);
+    assert_eq!(missing.status, 404);
+    assert_eq!(
+        missing.body,
+        format!(
+            "{{\"error\": \"no snapshot has Expr with schema hash {:016x}\"}}\n",
+            hash
+        )
+    );
+    assert_eq!(registry.respond("GET", "/ir/Reading/xyz", &[]).status, 400);
+    assert_eq!(registry.respond("DELETE", "/ir", &[]).status, 405);
+
+    let rejected = registry.respond("POST", "/ir", b"{\"types\": []}");
+    assert_eq!(rejected.status, 400);
+    assert_eq!(
+        rejected.body,
+        "{\"error\": \"not a schema snapshot: no IR version\"}\n"
+    );
+}
+
+#[test]
+fn keeps_uploads_in_its_directory() {
+    let dir =
+        std::env::temp_dir().join(format!("serde-postcard-ts-registry-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let snapshot = Generator::new().generate_ir().unwrap();
+    let id = SchemaRegistry::open(&dir)
+        .unwrap()
+        .add(&snapshot)
+        .unwrap()
+        .id
+        .clone();
+    assert!(dir.join(format!("{}.json", id)).exists());
+
+    let reopened = SchemaRegistry::open(&dir).unwrap();
+    assert_eq!(reopened.entries().len(), 1);
+    let hash = schema_hash::<Expr>().unwrap();
+    assert_eq!(reopened.find("Expr", hash).unwrap().id, id);
+
+    std::fs::write(dir.join("notes.json"), "not a snapshot").unwrap();
+    let error = SchemaRegistry::open(&dir).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn answers_http_requests() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut registry = SchemaRegistry::new();
+    registry
+        .add(&Generator::new().generate_ir().unwrap())
+        .unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        registry.handle(&mut stream).unwrap();
+    });
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream
+        .write_all(b"GET /ir HTTP/1.1\r\nHost: registry\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    server.join().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Access-Control-Allow-Origin: *\r\n"));
+    assert!(response.contains("\r\n\r\n{\"snapshots\": [{\"id\": \""));
+}
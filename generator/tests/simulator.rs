@@ -0,0 +1,173 @@
+use serde_postcard_ts::{
+    Definition, GenerateError, GeneratedFile, Generator, Layout, Shape, Simulator, TypeName,
+    Variant, VariantKind,
+};
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn types() -> Vec<(TypeName, Definition)> {
+    vec![
+        (
+            type_name("Command", "app::control"),
+            Definition::Enum(vec![
+                Variant {
+                    name: "Ping",
+                    kind: VariantKind::Unit,
+                },
+                Variant {
+                    name: "SetTarget",
+                    kind: VariantKind::Newtype(Shape::F32),
+                },
+            ]),
+        ),
+        (
+            type_name("Reply", "app::control"),
+            Definition::Enum(vec![Variant {
+                name: "Ack",
+                kind: VariantKind::Unit,
+            }]),
+        ),
+        (
+            type_name("Reading", "app::telemetry"),
+            Definition::NewtypeStruct(Shape::F32),
+        ),
+        (
+            TypeName {
+                name: "Envelope",
+                module: "app",
+                params: &["T"],
+            },
+            Definition::NewtypeStruct(Shape::Param("T")),
+        ),
+    ]
+}
+
+fn thermostat() -> Simulator {
+    Simulator::new("HvacUnit", "Command", "app::control::Reply").telemetry("Reading")
+}
+
+fn simulator_file(generator: Generator, layout: Layout) -> Result<GeneratedFile, GenerateError> {
+    let files = generator
+        .simulator(thermostat())
+        .generate_types_files(types(), layout)?;
+    Ok(files
+        .into_iter()
+        .find(|file| file.path.ends_with("-simulator.ts"))
+        .unwrap())
+}
+
+#[test]
+fn generates_a_typed_subclass_per_simulator() {
+    let file = simulator_file(Generator::new(), Layout::Single).unwrap();
+    assert_eq!(file.path, "hvac-unit-simulator.ts");
+    let source = file.source;
+    assert!(source.contains(
+        "import {\n  CommandSchema,\n  ReadingSchema,\n  ReplySchema,\n  type Command,\n  type Reply,\n} from \"./index.js\";\n"
+    ));
+    assert!(source.contains(
+        "  SetTarget?: (\n    request: Extract<Command, { type: \"SetTarget\" }>,\n    simulator: HvacUnitSimulator\n  ) => SimulatorReply<Reply>;\n"
+    ));
+    assert!(source.contains(
+        "export class HvacUnitSimulator extends DeviceSimulator<\n  typeof CommandSchema,\n  typeof ReplySchema,\n  typeof ReadingSchema\n> {\n"
+    ));
+    // Variants are told apart by `type` unless enums are discriminated
+    assert!(!source.contains("kindOf"));
+}
+
+#[test]
+fn looks_handlers_up_by_the_enum_tag() {
+    let file = simulator_file(Generator::new().enum_tag("kind"), Layout::Modules).unwrap();
+    assert!(file
+        .source
+        .contains("request: Extract<Command, { kind: \"Ping\" }>,"));
+    assert!(file
+        .source
+        .contains("{ kindOf: (request) => (request as Command).kind, ...options }"));
+}
+
+#[test]
+fn sends_telemetry_as_responses_by_default() {
+    let files = Generator::new()
+        .simulator(Simulator::new("Probe", "Command", "Reply"))
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    let paths: Vec<_> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "app.ts",
+            "app/control.ts",
+            "app/telemetry.ts",
+            "index.ts",
+            "probe-simulator.ts"
+        ]
+    );
+    assert!(files[4].source.contains("telemetry: ReplySchema }"));
+}
+
+#[test]
+fn rejects_simulators_of_unusable_types() {
+    let invalid = |simulator: Simulator| {
+        Generator::new()
+            .simulator(simulator)
+            .generate_types_files(types(), Layout::Single)
+            .unwrap_err()
+    };
+    let error = invalid(Simulator::new("Probe", "Reading", "Reply"));
+    assert_eq!(
+        error,
+        GenerateError::InvalidSimulator {
+            simulator: "Probe".to_string(),
+            type_name: "Reading".to_string(),
+            reason: "is not an enum",
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Reading in simulator Probe is not an enum"
+    );
+    assert_eq!(
+        invalid(Simulator::new("Probe", "Command", "app::Reply")),
+        GenerateError::InvalidSimulator {
+            simulator: "Probe".to_string(),
+            type_name: "app::Reply".to_string(),
+            reason: "matches no type",
+        }
+    );
+    assert_eq!(
+        invalid(Simulator::new("Probe", "Command", "Reply").telemetry("Envelope")),
+        GenerateError::InvalidSimulator {
+            simulator: "Probe".to_string(),
+            type_name: "Envelope".to_string(),
+            reason: "is generic",
+        }
+    );
+}
+
+#[test]
+fn rejects_invalid_simulator_names() {
+    for name in ["probe", "Hvac-Unit", "Device", ""] {
+        assert_eq!(
+            Generator::new()
+                .simulator(Simulator::new(name, "Command", "Reply"))
+                .generate_types_files(types(), Layout::Single),
+            Err(GenerateError::InvalidSimulatorName(name.to_string())),
+            "{}",
+            name
+        );
+    }
+    // Both would be written to hvac-unit-simulator.ts
+    let twice = Generator::new()
+        .simulator(Simulator::new("HvacUnit", "Command", "Reply"))
+        .simulator(Simulator::new("Hvac_Unit", "Command", "Reply"));
+    assert_eq!(
+        twice.generate_types_files(types(), Layout::Single),
+        Err(GenerateError::InvalidSimulatorName("Hvac_Unit".to_string()))
+    );
+}
@@ -0,0 +1,52 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{generate_all, PostcardTs};
+
+// Nor do attributes that only affect reading other formats
+#[derive(PostcardTs)]
+#[serde(deny_unknown_fields, expecting = "a session")]
+struct Session {
+    id: u32,
+    #[serde(skip)]
+    cache: Vec<u8>,
+    #[serde(default, alias = "tries")]
+    retries: u8,
+    #[serde(skip_serializing, skip_deserializing)]
+    dirty: bool,
+}
+
+#[derive(PostcardTs)]
+struct Sample(u16, #[serde(skip)] u64, i8);
+
+#[derive(PostcardTs)]
+#[serde(default)]
+enum Event {
+    Opened {
+        #[serde(skip)]
+        handle: u64,
+        path: String,
+    },
+    Closed(u32, #[serde(skip)] bool),
+}
+
+const EXPECTED: &str = r#"export const EventSchema = enumType("Event", {
+  Opened: structVariant("Opened", { path: string() }),
+  Closed: tupleVariant("Closed", u32()),
+});
+export type Event = InferType<typeof EventSchema>;
+
+export const SampleSchema = tupleStruct("Sample", u16(), i8());
+export type Sample = InferType<typeof SampleSchema>;
+
+export const SessionSchema = struct({
+  id: u32(),
+  retries: u8(),
+});
+export type Session = InferType<typeof SessionSchema>;
+"#;
+
+#[test]
+fn leaves_out_skipped_fields() {
+    assert!(generate_all().unwrap().ends_with(EXPECTED));
+}
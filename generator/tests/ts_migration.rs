@@ -0,0 +1,163 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{Generator, PostcardTs};
+
+#[derive(PostcardTs)]
+struct Sample {
+    sensor_id: u8,
+    at: u64,
+    label: Option<String>,
+}
+
+#[derive(PostcardTs)]
+struct Batch {
+    samples: Vec<Sample>,
+    #[serde(rename = "next-cursor")]
+    next_cursor: Option<u32>,
+}
+
+#[derive(PostcardTs)]
+struct Calibration {
+    offsets: Vec<f32>,
+    unit: String,
+}
+
+#[derive(PostcardTs)]
+struct Device {
+    calibration: Calibration,
+    name: String,
+}
+
+#[derive(PostcardTs)]
+enum Mode {
+    Idle,
+    Run(u8),
+}
+
+const HAND_WRITTEN: &str = r#"
+/** A sample as the dashboard had it */
+export interface Sample {
+  readonly sensorId: number; // renamed
+  at: number,
+  label?: string
+}
+
+export type Batch = {
+  samples: ReadonlyArray<Sample>;
+  "next-cursor": number | null;
+  /* kept by the dashboard */
+  cached?: boolean;
+};
+
+export interface Calibration {
+  offsets: number[];
+  unit:
+    | "C"
+    | "F";
+}
+
+export interface Device {
+  calibration: Calibration;
+  name: string;
+}
+
+export type Mode = { idle: boolean };
+
+// Not generated, so left alone
+export interface Theme {
+  dark: boolean;
+}
+"#;
+
+#[test]
+fn reports_differences_with_the_generated_types() {
+    let migration = Generator::new().migrate_ts(HAND_WRITTEN).unwrap();
+    assert_eq!(
+        migration.report(),
+        "adapted   Sample.sensorId: named sensor_id in generated\n\
+         adapted   Sample.at: bigint in generated, number here, exact up to 2^53\n\
+         adapted   Sample.label: null in generated, undefined here\n\
+         adapted   Batch.cached: only here, and left out\n\
+         mismatch  Calibration.unit: string in generated, \"C\" | \"F\" here\n\
+         mismatch  Device: holds Calibration, which cannot be adapted\n\
+         mismatch  Mode: generated as a union of its variants, not an object\n\
+         7 differences, 3 not adapted\n"
+    );
+    assert!(!migration.is_adapted());
+    assert_eq!(
+        migration.adapted_types().collect::<Vec<_>>(),
+        ["Sample", "Batch"]
+    );
+}
+
+#[test]
+fn writes_shims_for_the_adapted_types() {
+    let migration = Generator::new().migrate_ts(HAND_WRITTEN).unwrap();
+    assert_eq!(
+        migration.shims("./generated/index.js", "../types.js"),
+        "// Generated by postcard-ts migrate; delete each pair of functions once\n\
+         // nothing uses its hand-written type.\n\
+         \n\
+         import type * as generated from \"./generated/index.js\";\n\
+         import type * as handWritten from \"../types.js\";\n\
+         \n\
+         export function fromSample(value: generated.Sample): handWritten.Sample {\n  \
+           return {\n    \
+             sensorId: value.sensor_id,\n    \
+             at: Number(value.at),\n    \
+             ...(value.label === null ? {} : { label: value.label }),\n  \
+           };\n\
+         }\n\
+         \n\
+         export function toSample(value: handWritten.Sample): generated.Sample {\n  \
+           return {\n    \
+             sensor_id: value.sensorId,\n    \
+             at: BigInt(value.at),\n    \
+             label: value.label ?? null,\n  \
+           };\n\
+         }\n\
+         \n\
+         export function fromBatch(value: generated.Batch): handWritten.Batch {\n  \
+           return {\n    \
+             samples: value.samples.map((item) => fromSample(item)),\n    \
+             \"next-cursor\": value[\"next-cursor\"],\n  \
+           };\n\
+         }\n\
+         \n\
+         export function toBatch(value: handWritten.Batch): generated.Batch {\n  \
+           return {\n    \
+             samples: value.samples.map((item) => toSample(item)),\n    \
+             \"next-cursor\": value[\"next-cursor\"],\n  \
+           };\n\
+         }\n"
+    );
+}
+
+#[test]
+fn needs_no_shims_for_matching_types() {
+    let source = "export interface Calibration { offsets: number[]; unit: string }\n\
+                  export interface Device { calibration: Calibration; name: string }\n";
+    let migration = Generator::new().migrate_ts(source).unwrap();
+    assert_eq!(migration.report(), "no differences\n");
+    assert!(migration.is_adapted());
+    assert_eq!(migration.adapted_types().count(), 0);
+}
+
+#[test]
+fn cannot_adapt_fields_missing_from_either_side() {
+    let source = "export interface Sample {\n  \
+                    sensorId: number;\n  \
+                    at?: bigint;\n  \
+                    location: string;\n\
+                  }\n";
+    let migration = Generator::new().migrate_ts(source).unwrap();
+    assert_eq!(
+        migration.report(),
+        "mismatch  Sample.sensorId: named sensor_id in generated\n\
+         mismatch  Sample.at: optional here, always there in generated as bigint\n\
+         mismatch  Sample.label: only in generated, where it may be null\n\
+         mismatch  Sample.location: only here\n\
+         4 differences, 4 not adapted\n"
+    );
+}
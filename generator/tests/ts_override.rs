@@ -0,0 +1,206 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    registered, schema_hash, GenerateError, Generator, Layout, ModuleFormat, PostcardTs, Shape,
+    TsOverride,
+};
+
+mod clock {
+    use serde_postcard_ts::PostcardTs;
+
+    /// Stands in for fields written by a `ts_millis` module, as a u64
+    #[derive(PostcardTs)]
+    pub struct Timestamp;
+}
+use clock::Timestamp;
+
+/// Has a hand-written `Serialize` impl writing it as a string
+#[derive(PostcardTs)]
+struct DeviceId {
+    vendor: u16,
+    serial: u32,
+}
+
+// Fields written by `with` modules, described by a stand-in type
+#[derive(PostcardTs)]
+struct Reading {
+    id: DeviceId,
+    #[serde(with = "ts_millis")]
+    #[postcard_ts(with = "Timestamp")]
+    at: std::time::SystemTime,
+    #[postcard_ts(with = "Option<Timestamp>")]
+    #[serde(serialize_with = "ts_millis::serialize_option")]
+    #[serde(deserialize_with = "ts_millis::deserialize_option")]
+    calibrated: Option<std::time::SystemTime>,
+}
+
+// Types serde writes as another one, described as that one
+#[derive(PostcardTs)]
+#[serde(into = "u8", try_from = "u8")]
+#[postcard_ts(with = "u8")]
+enum Level {
+    Low,
+    High,
+}
+
+#[derive(PostcardTs)]
+#[serde(from = "std::time::SystemTime", into = "std::time::SystemTime")]
+#[postcard_ts(with = "Timestamp")]
+struct BootedAt {
+    seconds: u64,
+    nanos: u32,
+}
+
+fn generator() -> Generator {
+    Generator::new()
+        .override_type::<Timestamp>(
+            TsOverride::new(Shape::U64)
+                .ts_type("Date")
+                .codec("fromMillis", "(date: Date) => BigInt(date.getTime())")
+                .import("fromMillis", "./codecs.js"),
+        )
+        .override_type::<DeviceId>(TsOverride::new(Shape::String))
+}
+
+#[test]
+fn declares_overridden_types_with_their_codec() {
+    let source = generator().generate_types(registered()).unwrap();
+    assert!(source.contains("  transform,\n"));
+    assert!(source.contains("import { fromMillis } from \"./codecs.js\";\n"));
+    assert!(source.contains(
+        "export type Timestamp = Date;\n\
+         /** Stands in for fields written by a `ts_millis` module, as a u64 */\n\
+         export const TimestampSchema = transform(u64(), {\n  \
+         decode: (value): Timestamp => fromMillis(value),\n  \
+         encode: (value: Timestamp) => ((date: Date) => BigInt(date.getTime()))(value),\n\
+         });\n"
+    ));
+    // Written as the wire shape, without a codec
+    assert!(source.contains(
+        "export const DeviceIdSchema = string();\n\
+         /** Has a hand-written `Serialize` impl writing it as a string */\n\
+         export type DeviceId = InferType<typeof DeviceIdSchema>;\n"
+    ));
+    assert!(source.contains(
+        "  id: DeviceIdSchema,\n  at: TimestampSchema,\n  calibrated: option(TimestampSchema),\n"
+    ));
+}
+
+#[test]
+fn infers_the_type_from_a_codec_without_one() {
+    let source = Generator::new()
+        .override_type::<Timestamp>(TsOverride::new(Shape::I64).codec("fromMillis", "toMillis"))
+        .generate_types(registered())
+        .unwrap();
+    assert!(source.contains(
+        "export const TimestampSchema = transform(i64(), {\n  \
+         decode: fromMillis,\n  \
+         encode: toMillis,\n\
+         });\n\
+         /** Stands in for fields written by a `ts_millis` module, as a u64 */\n\
+         export type Timestamp = InferType<typeof TimestampSchema>;\n"
+    ));
+}
+
+#[test]
+fn declares_overridden_types_alone() {
+    let source = generator()
+        .override_type::<DeviceId>(
+            TsOverride::new(Shape::String)
+                .ts_type("Serial")
+                .codec("parseSerial", "String")
+                .import("parseSerial", "./serials.js")
+                .import("type Serial", "./serials.js"),
+        )
+        .module_format(ModuleFormat::Declarations)
+        .generate_types(registered())
+        .unwrap();
+    // Only types are imported, and no codec applied
+    assert!(source.contains("\nimport type { Serial } from \"./serials.js\";\n\n"));
+    assert!(!source.contains("fromMillis"));
+    assert!(source.contains("export type Timestamp = Date;\n"));
+    assert!(source.contains("export type DeviceId = Serial;\n"));
+    assert!(source.contains("  at: Timestamp;\n  calibrated: Timestamp | null;\n"));
+
+    let inferred = Generator::new()
+        .override_type::<Timestamp>(TsOverride::new(Shape::I64).codec("fromMillis", "toMillis"))
+        .module_format(ModuleFormat::Declarations)
+        .generate_types(registered());
+    assert!(matches!(
+        inferred,
+        Err(GenerateError::InvalidOverride { name, .. }) if name.ends_with("Timestamp")
+    ));
+}
+
+#[test]
+fn imports_codecs_relative_to_each_file() {
+    let files = generator()
+        .import_from("./runtime/index.js")
+        .generate_types_files(registered(), Layout::Modules)
+        .unwrap();
+    assert_eq!(files[1].path, "ts_override/clock.ts");
+    assert!(files[1].source.contains(
+        "} from \"../runtime/index.js\";\nimport { fromMillis } from \"../codecs.js\";\n"
+    ));
+    assert!(!files[0].source.contains("codecs.js"));
+}
+
+#[test]
+fn hashes_and_describes_the_wire_shape() {
+    let hashes = generator()
+        .schema_hashes()
+        .generate_types(registered())
+        .unwrap();
+    let plain = Generator::new()
+        .schema_hashes()
+        .generate_types(registered())
+        .unwrap();
+    let line = |source: &str| {
+        source
+            .lines()
+            .find(|line| line.starts_with("export const ReadingSchemaHash"))
+            .unwrap()
+            .to_string()
+    };
+    assert_ne!(line(&hashes), line(&plain));
+    assert!(generator()
+        .generate_types_ir(registered())
+        .unwrap()
+        .contains("\"name\": \"Timestamp\""));
+    // The derived shape stays what schema_hash() sees
+    assert_eq!(schema_hash::<Timestamp>(), schema_hash::<()>());
+}
+
+#[test]
+fn rejects_overrides_it_cannot_generate() {
+    let error = Generator::new()
+        .override_type::<u64>(TsOverride::new(Shape::U64))
+        .generate_types(registered())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::InvalidOverride {
+            name: "u64".to_string(),
+            reason: "only types describing themselves as a Shape::Named, as derived non-generic \
+                     types do, can be overridden",
+        }
+    );
+    assert_eq!(
+        Generator::new()
+            .override_type::<Timestamp>(TsOverride::new(Shape::U64).ts_type("Date"))
+            .generate_types(registered())
+            .unwrap_err()
+            .to_string(),
+        "cannot override ts_override::clock::Timestamp: a TypeScript type needs a codec converting to it"
+    );
+}
+
+#[test]
+fn describes_converted_types_as_what_serde_writes() {
+    let source = generator().generate_types(registered()).unwrap();
+    assert!(source.contains("export const LevelSchema = newtypeStruct(\"Level\", u8());\n"));
+    // Including as a stand-in with an override
+    assert!(source
+        .contains("export const BootedAtSchema = newtypeStruct(\"BootedAt\", TimestampSchema);\n"));
+}
@@ -0,0 +1,170 @@
+use serde_postcard_ts::{
+    Definition, Field, GenerateError, Generator, Layout, ModuleFormat, ProtocolGroup, Shape,
+    TypeName, Variant, VariantKind,
+};
+
+fn types() -> Vec<(TypeName, Definition)> {
+    let reading = TypeName {
+        name: "Reading",
+        module: "app::sensors",
+        params: &[],
+    };
+    let command = TypeName {
+        name: "Command",
+        module: "app::control",
+        params: &[],
+    };
+    vec![
+        (
+            reading,
+            Definition::Struct(vec![
+                Field {
+                    name: "sensor",
+                    shape: Shape::U8,
+                },
+                Field {
+                    name: "samples",
+                    shape: Shape::Seq(Box::new(Shape::U16)),
+                },
+            ]),
+        ),
+        (
+            command,
+            Definition::Enum(vec![
+                Variant {
+                    name: "Start",
+                    kind: VariantKind::Unit,
+                },
+                Variant {
+                    name: "Stop",
+                    kind: VariantKind::Unit,
+                },
+            ]),
+        ),
+    ]
+}
+
+#[test]
+fn documents_each_type_under_its_module() {
+    let source = Generator::new().typedoc().generate_types(types()).unwrap();
+    assert!(source.contains(
+        "/**\n \
+         * The schema of {@link Reading}.\n \
+         *\n \
+         * @category app::sensors\n \
+         */\n\
+         export const ReadingSchema = struct({\n"
+    ));
+    assert!(source.contains(
+        "/**\n \
+         * Rust `app::sensors::Reading`, a struct of 2 fields.\n \
+         *\n \
+         * Schema hash `0x"
+    ));
+    assert!(source.contains("`.\n *\n * @category app::sensors\n */\nexport type Reading = "));
+    assert!(source.contains(
+        " * Rust `app::control::Command`, an enum of 2 variants (Start, Stop), \
+         at most 1 byte on the wire.\n"
+    ));
+    // Stated in the comments only
+    assert!(!source.contains("SchemaHash ="));
+    assert!(!source.contains("_MAX_SIZE"));
+}
+
+#[test]
+fn puts_every_other_export_in_the_category() {
+    let source = Generator::new()
+        .typedoc()
+        .variant_peeks()
+        .schema_hashes()
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains(
+        "/** @category app::control */\nexport const isCommandStop = variantFilter(CommandSchema, [\"Stop\"]);\n"
+    ));
+    assert!(source.contains("/** @category app::sensors */\nexport const ReadingSchemaHash = 0x"));
+}
+
+#[test]
+fn categorises_grouped_types_by_their_group() {
+    let source = Generator::new()
+        .typedoc()
+        .group(ProtocolGroup::new("control", 1).types(["app::control::*"]))
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains(" * @category control\n */\nexport type Command = "));
+    assert!(source.contains(" * @category app::sensors\n */\nexport type Reading = "));
+}
+
+#[test]
+fn shows_example_frames() {
+    let source = Generator::new()
+        .typedoc()
+        .typedoc_example(
+            "app::sensors::Reading",
+            [3, 14, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+        )
+        .typedoc_example("Command", [1])
+        .encode_only(["Command"])
+        .generate_types(types())
+        .unwrap();
+    assert!(source.contains(
+        " * @example\n \
+         * ```ts\n \
+         * const frame = new Uint8Array([\n \
+         *   0x03, 0x0e, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a,\n \
+         *   0x0b, 0x0c, 0x0d, 0x0e,\n \
+         * ]);\n \
+         * const reading = deserialize(ReadingSchema, frame);\n \
+         * ```\n"
+    ));
+    assert!(source.contains(
+        " * // What serialize(CommandSchema, command) writes for one Command\n \
+         * const frame = new Uint8Array([0x01]);\n \
+         * ```\n"
+    ));
+
+    let error = Generator::new()
+        .typedoc()
+        .typedoc_example("Telemetry", [0])
+        .generate_types(types())
+        .unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::UnmatchedExample("Telemetry".to_string())
+    );
+}
+
+#[test]
+fn writes_typedoc_options_with_the_files() {
+    let files = Generator::new()
+        .typedoc()
+        .generate_types_files(types(), Layout::Modules)
+        .unwrap();
+    let options = files.last().unwrap();
+    assert_eq!(options.path, "typedoc.json");
+    assert!(options
+        .source
+        .starts_with("// Generated by serde-postcard-ts"));
+    assert!(options
+        .source
+        .contains("  \"entryPoints\": [\"index.ts\"],\n"));
+    assert!(options.source.contains("  \"out\": \"docs\",\n"));
+
+    let files = Generator::new()
+        .typedoc()
+        .module_format(ModuleFormat::Declarations)
+        .generate_types_files(types(), Layout::Single)
+        .unwrap();
+    assert!(files[0]
+        .source
+        .contains(" * @category app::sensors\n */\nexport interface Reading {\n"));
+    assert!(files[1]
+        .source
+        .contains("  \"entryPoints\": [\"index.d.ts\"],\n"));
+
+    let files = Generator::new()
+        .generate_types_files(types(), Layout::Single)
+        .unwrap();
+    assert!(files.iter().all(|file| file.path != "typedoc.json"));
+}
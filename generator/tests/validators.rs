@@ -0,0 +1,220 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    Definition, Field, Generator, Layout, ModuleFormat, PostcardTs, Shape, TsOverride, TypeName,
+    Validator, Variant, VariantKind,
+};
+
+#[derive(PostcardTs)]
+struct Stamp {
+    millis: u64,
+}
+
+fn type_name(name: &'static str, module: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module,
+        params: &[],
+    }
+}
+
+fn reading() -> (TypeName, Definition) {
+    (
+        type_name("Reading", "app::telemetry"),
+        Definition::Struct(vec![
+            Field {
+                name: "sensor",
+                shape: Shape::U8,
+            },
+            Field {
+                name: "celsius",
+                shape: Shape::Option(Box::new(Shape::F32)),
+            },
+            Field {
+                name: "label",
+                shape: Shape::BoundedString(8),
+            },
+        ]),
+    )
+}
+
+fn command() -> (TypeName, Definition) {
+    (
+        type_name("Command", "app::control"),
+        Definition::Enum(vec![
+            Variant {
+                name: "Ping",
+                kind: VariantKind::Unit,
+            },
+            Variant {
+                name: "Move",
+                kind: VariantKind::Struct(vec![Field {
+                    name: "x",
+                    shape: Shape::I16,
+                }]),
+            },
+            Variant {
+                name: "Report",
+                kind: VariantKind::Newtype(Shape::Named(type_name("Reading", "app::telemetry"))),
+            },
+        ]),
+    )
+}
+
+#[test]
+fn emits_a_zod_schema_per_type() {
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate_types(vec![reading(), command()])
+        .unwrap();
+
+    assert!(source.contains(
+        "} from \"@variegated-coffee/serde-postcard-ts\";\nimport { z } from \"zod\";\n"
+    ));
+    assert!(source.contains(
+        "export const ReadingZod = z.object({\n  \
+         sensor: z.number().int().min(0).max(255),\n  \
+         celsius: z.number().or(z.nan()).nullable(),\n  \
+         label: z.string().refine((value) => new TextEncoder().encode(value).length <= 8, \"longer than 8 bytes\"),\n\
+         });\n"
+    ));
+    assert!(source.contains(
+        "export const CommandZod = z.discriminatedUnion(\"type\", [\n  \
+         z.object({ type: z.literal(\"Ping\") }),\n  \
+         z.object({ type: z.literal(\"Move\"), value: z.object({ x: z.number().int().min(-32768).max(32767) }) }),\n  \
+         z.object({ type: z.literal(\"Report\"), value: ReadingZod }),\n\
+         ]);\n"
+    ));
+}
+
+#[test]
+fn follows_the_enum_tag() {
+    let source = Generator::new()
+        .enum_tag("kind")
+        .with_validators(Validator::Zod)
+        .generate_types(vec![reading(), command()])
+        .unwrap();
+
+    assert!(source.contains("export const CommandZod = z.discriminatedUnion(\"kind\", [\n"));
+    assert!(source.contains(
+        "  z.object({ kind: z.literal(\"Move\"), x: z.number().int().min(-32768).max(32767) }),\n"
+    ));
+}
+
+#[test]
+fn checks_the_ranges_of_integers() {
+    let types = vec![(
+        type_name("Counters", "app"),
+        Definition::TupleStruct(vec![Shape::I8, Shape::U32, Shape::I64, Shape::U128]),
+    )];
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate_types(types)
+        .unwrap();
+
+    assert!(source.contains(
+        "export const CountersZod = z.tuple([\
+         z.number().int().min(-128).max(127), \
+         z.number().int().min(0).max(4294967295), \
+         z.bigint().min(-9223372036854775808n).max(9223372036854775807n), \
+         z.bigint().min(0n).max(340282366920938463463374607431768211455n)\
+         ]);\n"
+    ));
+}
+
+#[test]
+fn validates_recursive_types_lazily() {
+    let tree = type_name("Tree", "app");
+    let types = vec![(
+        tree,
+        Definition::Struct(vec![Field {
+            name: "children",
+            shape: Shape::Seq(Box::new(Shape::Named(tree))),
+        }]),
+    )];
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate_types(types)
+        .unwrap();
+
+    assert!(source.contains(
+        "export const TreeZod: z.ZodType<Tree> = z.object({\n  \
+         children: z.array(z.lazy(() => TreeZod)),\n\
+         });\n"
+    ));
+}
+
+#[test]
+fn makes_factories_of_generic_types() {
+    let envelope = TypeName {
+        name: "Envelope",
+        module: "app",
+        params: &["T"],
+    };
+    let types = vec![
+        (
+            envelope,
+            Definition::Struct(vec![Field {
+                name: "payload",
+                shape: Shape::Param("T"),
+            }]),
+        ),
+        (
+            type_name("Frame", "app"),
+            Definition::NewtypeStruct(Shape::Generic(envelope, vec![Shape::Bool])),
+        ),
+    ];
+    let source = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate_types(types)
+        .unwrap();
+
+    assert!(source.contains(
+        "export const EnvelopeZod = <T extends z.ZodTypeAny>(T: T) =>\n  \
+         z.object({\n    payload: T,\n  });\n"
+    ));
+    assert!(source.contains("export const FrameZod = EnvelopeZod(z.boolean());\n"));
+}
+
+#[test]
+fn leaves_codec_types_unchecked() {
+    let source = Generator::new()
+        .override_type::<Stamp>(TsOverride::new(Shape::U64).ts_type("Date").codec(
+            "(millis: bigint) => new Date(Number(millis))",
+            "(date: Date) => BigInt(date.getTime())",
+        ))
+        .with_validators(Validator::Zod)
+        .generate()
+        .unwrap();
+
+    assert!(source.contains("export const StampZod = z.custom<Stamp>();\n"));
+}
+
+#[test]
+fn imports_validators_across_files() {
+    let files = Generator::new()
+        .with_validators(Validator::Zod)
+        .generate_types_files(vec![reading(), command()], Layout::Modules)
+        .unwrap();
+    let control = files
+        .iter()
+        .find(|file| file.path == "app/control.ts")
+        .unwrap();
+
+    assert!(control
+        .source
+        .contains("import { ReadingSchema, ReadingZod } from \"./telemetry.js\";\n"));
+}
+
+#[test]
+fn emits_no_validators_in_declarations() {
+    let files = Generator::new()
+        .module_format(ModuleFormat::Declarations)
+        .with_validators(Validator::Zod)
+        .generate_types_files(vec![reading(), command()], Layout::Single)
+        .unwrap();
+
+    assert!(!files[0].source.contains("Zod"));
+    assert!(!files[0].source.contains("zod"));
+}
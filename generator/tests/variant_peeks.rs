@@ -0,0 +1,43 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{Generator, PostcardTs};
+
+#[derive(PostcardTs)]
+#[serde(rename_all = "kebab-case")]
+enum Telemetry {
+    Heartbeat,
+    SensorReading(f32),
+    Alarm { code: u16 },
+}
+
+#[derive(PostcardTs)]
+enum Envelope<T> {
+    Data(T),
+    Ack,
+}
+
+#[derive(PostcardTs)]
+struct Status {
+    last: Telemetry,
+}
+
+#[test]
+fn emits_a_peek_per_variant() {
+    let source = Generator::new().variant_peeks().generate().unwrap();
+    assert!(source.contains("  variantFilter,\n"));
+    assert!(source.contains(
+        "export const isTelemetryHeartbeat = variantFilter(TelemetrySchema, [\"heartbeat\"]);\n\
+         export const isTelemetrySensorReading = variantFilter(TelemetrySchema, [\"sensor-reading\"]);\n\
+         export const isTelemetryAlarm = variantFilter(TelemetrySchema, [\"alarm\"]);\n"
+    ));
+    // Generic enums have a schema factory rather than a schema
+    assert!(!source.contains("isEnvelope"));
+    assert!(!source.contains("isStatus"));
+}
+
+#[test]
+fn emits_no_peeks_by_default() {
+    let source = Generator::new().generate().unwrap();
+    assert!(!source.contains("variantFilter"));
+}
@@ -0,0 +1,166 @@
+// The derived types only exist to be generated
+#![allow(dead_code)]
+
+use serde_postcard_ts::{
+    write_files, GenerateError, Generator, Layout, ModuleFormat, PostcardTs, Shape, TsOverride,
+    WasmBackend, WriteOptions,
+};
+
+/// Has a hand-written `Serialize` impl nobody wants to describe
+#[derive(PostcardTs)]
+struct GainTable {
+    gains: Vec<f32>,
+}
+
+#[derive(PostcardTs)]
+struct Rig {
+    id: u8,
+    gains: GainTable,
+}
+
+#[derive(PostcardTs)]
+struct Heartbeat {
+    uptime: u32,
+}
+
+fn generator() -> Generator {
+    Generator::new()
+        .wasm_backend(
+            WasmBackend::new("protocol-wasm")
+                .dependency(r#"firmware = { path = "../../../firmware" }"#),
+        )
+        .wasm_type::<GainTable>()
+}
+
+#[test]
+fn declares_wasm_types_with_the_crate_functions() {
+    let files = generator().generate_files(Layout::Single).unwrap();
+    let index = &files[0].source;
+    assert!(index.contains("  external,\n"));
+    assert!(index.contains(
+        "import { decode_gain_table, encode_gain_table } from \"./protocol-wasm/pkg/protocol_wasm.js\";\n"
+    ));
+    assert!(index.contains(
+        "export type GainTable = unknown;\n\
+         /** Has a hand-written `Serialize` impl nobody wants to describe */\n\
+         export const GainTableSchema = external<GainTable>(\"GainTable\", {\n  \
+         encode: encode_gain_table,\n  \
+         decode: decode_gain_table,\n\
+         });\n"
+    ));
+    assert!(index.contains("  gains: GainTableSchema,\n"));
+    // The registered definition is not generated
+    assert!(!index.contains("seq(f32())"));
+}
+
+#[test]
+fn writes_the_crate() {
+    let files = generator().generate_files(Layout::Single).unwrap();
+    let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "index.ts",
+            "protocol-wasm/Cargo.toml",
+            "protocol-wasm/src/lib.rs"
+        ]
+    );
+    let manifest = &files[1].source;
+    assert!(manifest.starts_with("# Generated by serde-postcard-ts"));
+    assert!(manifest.contains("name = \"protocol-wasm\"\n"));
+    assert!(manifest.contains("crate-type = [\"cdylib\"]\n"));
+    assert!(
+        manifest.contains("wasm-bindgen = \"0.2\"\nfirmware = { path = \"../../../firmware\" }\n")
+    );
+    let lib = &files[2].source;
+    assert!(lib.starts_with("// Generated by serde-postcard-ts"));
+    assert!(lib.contains(
+        "#[wasm_bindgen]\n\
+         pub fn encode_gain_table(value: JsValue) -> Result<Vec<u8>, JsError> {\n    \
+         encode::<wasm::GainTable>(value)\n\
+         }\n"
+    ));
+    assert!(lib.contains("pub fn decode_gain_table(bytes: &[u8]) -> Result<JsValue, JsError> {\n"));
+}
+
+#[test]
+fn rewrites_its_own_crate_files() {
+    let dir = std::env::temp_dir().join(format!("serde-postcard-ts-wasm-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let files = generator().generate_files(Layout::Single).unwrap();
+    write_files(&dir, &files, WriteOptions::default()).unwrap();
+    std::fs::write(
+        dir.join("protocol-wasm/Cargo.toml"),
+        "# Generated by serde-postcard-ts from the Rust types deriving PostcardTs; do not edit.\n",
+    )
+    .unwrap();
+    let report = write_files(&dir, &files, WriteOptions::default()).unwrap();
+    assert_eq!(report.written, [dir.join("protocol-wasm/Cargo.toml")]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn imports_relative_to_each_file() {
+    let files = generator().generate_files(Layout::Types).unwrap();
+    let file = files
+        .iter()
+        .find(|file| file.path == "types/GainTable.ts")
+        .unwrap();
+    assert!(file
+        .source
+        .contains("from \"../protocol-wasm/pkg/protocol_wasm.js\";\n"));
+
+    let files = generator()
+        .wasm_backend(WasmBackend::new("protocol-wasm").import_from("protocol-wasm"))
+        .generate_files(Layout::Single)
+        .unwrap();
+    assert!(files[0].source.contains("from \"protocol-wasm\";\n"));
+}
+
+#[test]
+fn drops_constants_depending_on_wasm_types() {
+    let source = generator().schema_hashes().generate().unwrap();
+    assert!(source.contains("export const HeartbeatSchemaHash = "));
+    assert!(!source.contains("GainTableSchemaHash"));
+    assert!(!source.contains("RigSchemaHash"));
+}
+
+#[test]
+fn declares_unknown_types_without_the_crate() {
+    let files = generator()
+        .module_format(ModuleFormat::Declarations)
+        .generate_files(Layout::Single)
+        .unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0]
+        .source
+        .contains("export type GainTable = unknown;\n"));
+    assert!(!files[0].source.contains("encode_gain_table"));
+}
+
+#[test]
+fn rejects_output_needing_the_wire_shape() {
+    assert_eq!(
+        generator().generate_ir(),
+        Err(GenerateError::WasmWireShape { name: "GainTable" })
+    );
+    assert_eq!(
+        generator().generate_wire_layout(),
+        Err(GenerateError::WasmWireShape { name: "GainTable" })
+    );
+}
+
+#[test]
+fn rejects_wasm_types_without_a_backend_or_overridden() {
+    let error = Generator::new()
+        .wasm_type::<GainTable>()
+        .generate()
+        .unwrap_err();
+    assert!(matches!(error, GenerateError::InvalidOverride { .. }));
+
+    let error = generator()
+        .override_type::<GainTable>(TsOverride::new(Shape::Bytes))
+        .generate()
+        .unwrap_err();
+    assert!(matches!(error, GenerateError::InvalidOverride { .. }));
+}
@@ -0,0 +1,207 @@
+use serde_postcard_ts::lsp;
+use serde_postcard_ts::{
+    Definition, Field, GenerateError, Generator, Layout, Shape, TypeName, Variant, VariantKind,
+    WriteOptions,
+};
+
+fn type_name(name: &'static str) -> TypeName {
+    TypeName {
+        name,
+        module: "app",
+        params: &[],
+    }
+}
+
+fn field(name: &'static str, shape: Shape) -> Field {
+    Field { name, shape }
+}
+
+fn layout(types: Vec<(TypeName, Definition)>) -> String {
+    Generator::new().generate_types_wire_layout(types).unwrap()
+}
+
+#[test]
+fn places_fields_back_to_back() {
+    let position = type_name("Position");
+    let layout = layout(vec![
+        (
+            position,
+            Definition::Struct(vec![field("x", Shape::F32), field("y", Shape::F32)]),
+        ),
+        (
+            type_name("Reading"),
+            Definition::Struct(vec![
+                field("sensor", Shape::U8),
+                field("at", Shape::Named(position)),
+                field("count", Shape::U32),
+                field("label", Shape::String),
+                field("flag", Shape::Bool),
+            ]),
+        ),
+    ]);
+
+    assert!(layout.starts_with("{\n  \"version\": 1,\n  \"types\": [\n"));
+    assert!(layout.contains(
+        r#"{"name": "Reading", "module": "app", "size": {"min": 12, "max": null}, "segments": [{"path": "sensor", "kind": "u8", "encoding": "byte", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}}, {"path": "at.x", "kind": "f32", "encoding": "f32, little-endian", "offset": {"min": 1, "max": 1}, "size": {"min": 4, "max": 4}}, {"path": "at.y", "kind": "f32", "encoding": "f32, little-endian", "offset": {"min": 5, "max": 5}, "size": {"min": 4, "max": 4}}, {"path": "count", "kind": "u32", "encoding": "varint", "offset": {"min": 9, "max": 9}, "size": {"min": 1, "max": 5}}, {"path": "label", "kind": "string", "encoding": "varint length, then UTF-8", "offset": {"min": 10, "max": 14}, "size": {"min": 1, "max": null}}, {"path": "flag", "kind": "bool", "encoding": "byte, 0 or 1", "offset": {"min": 11, "max": null}, "size": {"min": 1, "max": 1}}]}"#
+    ));
+}
+
+#[test]
+fn lists_the_segments_of_each_variant() {
+    let mode = type_name("Mode");
+    let layout = layout(vec![
+        (
+            mode,
+            Definition::Enum(vec![
+                Variant {
+                    name: "Idle",
+                    kind: VariantKind::Unit,
+                },
+                Variant {
+                    name: "Run",
+                    kind: VariantKind::Newtype(Shape::I16),
+                },
+                Variant {
+                    name: "Move",
+                    kind: VariantKind::Tuple(vec![Shape::U8, Shape::Option(Box::new(Shape::F64))]),
+                },
+            ]),
+        ),
+        (
+            type_name("Command"),
+            Definition::TupleStruct(vec![Shape::Named(mode), Shape::U8]),
+        ),
+    ]);
+
+    assert!(layout.contains(
+        r#"{"name": "Mode", "module": "app", "size": {"min": 1, "max": 11}, "variants": [{"name": "Idle", "index": 0, "segments": [{"path": "", "kind": "variant_index", "encoding": "varint", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}}]}, {"name": "Run", "index": 1, "segments": [{"path": "", "kind": "variant_index", "encoding": "varint", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}}, {"path": "0", "kind": "i16", "encoding": "zigzag varint", "offset": {"min": 1, "max": 1}, "size": {"min": 1, "max": 3}}]}, {"name": "Move", "index": 2, "segments": [{"path": "", "kind": "variant_index", "encoding": "varint", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}}, {"path": "0", "kind": "u8", "encoding": "byte", "offset": {"min": 1, "max": 1}, "size": {"min": 1, "max": 1}}, {"path": "1", "kind": "option", "encoding": "byte, 0 for None or 1 for Some followed by the value", "offset": {"min": 2, "max": 2}, "size": {"min": 1, "max": 9}}]}]}"#
+    ));
+    // Enums are one segment wherever they are used
+    assert!(layout.contains(
+        r#"{"path": "0", "kind": "enum", "encoding": "varint variant index, then the variant's fields", "type": "Mode", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 11}}, {"path": "1", "kind": "u8", "encoding": "byte", "offset": {"min": 1, "max": 11}, "size": {"min": 1, "max": 1}}"#
+    ));
+}
+
+#[test]
+fn fills_in_type_arguments() {
+    let envelope = TypeName {
+        name: "Envelope",
+        module: "app",
+        params: &["T"],
+    };
+    let layout = layout(vec![
+        (
+            envelope,
+            Definition::Struct(vec![
+                field("payload", Shape::Param("T")),
+                field("seq", Shape::U8),
+            ]),
+        ),
+        (
+            type_name("Frame"),
+            Definition::NewtypeStruct(Shape::Generic(envelope, vec![Shape::F64])),
+        ),
+    ]);
+
+    assert!(layout.contains(
+        r#"{"name": "Envelope", "module": "app", "size": {"min": 1, "max": null}, "segments": [{"path": "payload", "kind": "param", "encoding": "as the type argument", "offset": {"min": 0, "max": 0}, "size": {"min": 0, "max": null}}, {"path": "seq", "kind": "u8", "encoding": "byte", "offset": {"min": 0, "max": null}, "size": {"min": 1, "max": 1}}]}"#
+    ));
+    assert!(layout.contains(
+        r#"{"name": "Frame", "module": "app", "size": {"min": 9, "max": 9}, "segments": [{"path": "payload", "kind": "f64", "encoding": "f64, little-endian", "offset": {"min": 0, "max": 0}, "size": {"min": 8, "max": 8}}, {"path": "seq", "kind": "u8", "encoding": "byte", "offset": {"min": 8, "max": 8}, "size": {"min": 1, "max": 1}}]}"#
+    ));
+}
+
+#[test]
+fn stops_at_recursive_types() {
+    let node = type_name("Node");
+    let layout = layout(vec![(
+        node,
+        Definition::Struct(vec![
+            field("value", Shape::U8),
+            field("next", Shape::Option(Box::new(Shape::Named(node)))),
+        ]),
+    )]);
+
+    assert!(layout.contains(
+        r#"{"name": "Node", "module": "app", "size": {"min": 2, "max": null}, "segments": [{"path": "value", "kind": "u8", "encoding": "byte", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 1}}, {"path": "next", "kind": "option", "encoding": "byte, 0 for None or 1 for Some followed by the value", "offset": {"min": 1, "max": 1}, "size": {"min": 1, "max": null}}]}"#
+    ));
+}
+
+#[test]
+fn rejects_missing_types() {
+    let error = Generator::new()
+        .generate_types_wire_layout(vec![(
+            type_name("Frame"),
+            Definition::NewtypeStruct(Shape::Named(type_name("Reading"))),
+        )])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        GenerateError::Missing {
+            name: "Reading",
+            referenced_by: "Frame"
+        }
+    );
+    assert_eq!(
+        lsp::diagnostic(&error),
+        r#"{"kind": "diagnostic", "severity": "error", "code": "missing", "message": "Frame refers to Reading, which does not derive PostcardTs", "types": ["Frame", "Reading"]}"#
+    );
+}
+
+#[test]
+fn streams_lsp_messages() {
+    let dir = std::env::temp_dir().join(format!("serde-postcard-ts-lsp-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let types = vec![(type_name("Id"), Definition::NewtypeStruct(Shape::U64))];
+
+    let messages = lsp::generate(
+        &Generator::new(),
+        types,
+        Layout::Single,
+        &dir,
+        WriteOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(messages.len(), 3);
+    // Lints first
+    assert!(messages[0].starts_with(
+        r#"{"kind": "diagnostic", "severity": "warning", "code": "bigint_id", "message": "Id is a u64"#
+    ));
+    assert_eq!(
+        messages[1],
+        r#"{"kind": "layout", "version": 1, "types": [{"name": "Id", "module": "app", "size": {"min": 1, "max": 10}, "segments": [{"path": "", "kind": "u64", "encoding": "varint", "offset": {"min": 0, "max": 0}, "size": {"min": 1, "max": 10}}]}]}"#
+    );
+    assert_eq!(
+        messages[2],
+        format!(
+            r#"{{"kind": "written", "written": [{:?}], "unchanged": [], "removed": []}}"#,
+            dir.join("index.ts").display().to_string()
+        )
+    );
+
+    let duplicate = vec![
+        (type_name("Id"), Definition::UnitStruct),
+        (
+            TypeName {
+                module: "app::other",
+                ..type_name("Id")
+            },
+            Definition::UnitStruct,
+        ),
+    ];
+    let messages = lsp::generate(
+        &Generator::new(),
+        duplicate,
+        Layout::Single,
+        &dir,
+        WriteOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        messages,
+        [
+            r#"{"kind": "diagnostic", "severity": "error", "code": "duplicate_name", "message": "type name Id is used by both app and app::other", "types": ["Id"]}"#
+        ]
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,27 @@
+/**
+ * Hashing and comparing byte strings, for tables keyed by encoded bytes
+ * (DecodeCache, StringInterner)
+ */
+
+/**
+ * 32-bit FNV-1a
+ */
+export function fnv1a(bytes: Uint8Array): number {
+  let hash = 0x811c9dc5;
+  for (const byte of bytes) {
+    hash = Math.imul(hash ^ byte, 0x01000193);
+  }
+  return hash >>> 0;
+}
+
+export function sameBytes(a: Uint8Array, b: Uint8Array): boolean {
+  if (a.length !== b.length) {
+    return false;
+  }
+  for (let i = 0; i < a.length; i++) {
+    if (a[i] !== b[i]) {
+      return false;
+    }
+  }
+  return true;
+}
@@ -0,0 +1,337 @@
+/**
+ * COBS framing, as postcard's `to_allocvec_cobs` / `from_bytes_cobs`
+ *
+ * Consistent Overhead Byte Stuffing removes every 0x00 from a payload so that
+ * 0x00 can delimit frames on a byte stream (UART, USB CDC, BLE
+ * characteristics). A frame is the stuffed payload followed by one 0x00.
+ *
+ * Zero-length payloads (`()`, unit and empty structs) still get a frame: the
+ * empty payload is stuffed to the single byte 0x01, so it is sent as
+ * `01 00` and is never mistaken for an idle line. Like postcard, decoding also
+ * accepts a bare delimiter as an empty payload.
+ *
+ * `CobsAccumulator` collects frames from a stream delivered in arbitrary
+ * chunks, as postcard's accumulator of the same name does on the device side.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import type { Schema, InferType } from "../types/schema.js";
+import { type SerializeError, type SerializeOptions, trySerialize } from "./serializer.js";
+import {
+  type DeserializeResult,
+  type DeserializeOptions,
+  DeserializeError,
+  tryDeserialize,
+} from "./deserializer.js";
+
+/**
+ * A malformed COBS frame, or one longer than the accumulator takes
+ *
+ * Codes: BAD_ENCODING for a block running past the end of its frame,
+ * BUFFER_FULL for an oversized frame.
+ */
+export class FramingError extends DeserializeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.BadEncoding) {
+    super(message, code);
+    this.name = "FramingError";
+  }
+}
+
+/**
+ * Stuff a payload into a COBS frame, including its trailing 0x00 delimiter
+ */
+export function cobsEncode(payload: Uint8Array): Uint8Array {
+  // One code byte per 254 payload bytes, plus the first code byte and the delimiter
+  const frame = new Uint8Array(payload.length + Math.floor(payload.length / 254) + 2);
+  let codeIndex = 0;
+  let code = 1;
+  let length = 1;
+
+  for (const byte of payload) {
+    if (byte !== 0) {
+      frame[length++] = byte;
+      code++;
+    }
+    if (byte === 0 || code === 0xff) {
+      // Close the block: its code is the distance to the next zero (or block)
+      frame[codeIndex] = code;
+      codeIndex = length++;
+      code = 1;
+    }
+  }
+  frame[codeIndex] = code;
+  frame[length++] = 0x00;
+  return frame.subarray(0, length);
+}
+
+/**
+ * Unstuff the COBS frame starting at `offset` (Result API)
+ *
+ * The frame runs up to the next 0x00, which `bytesRead` includes, or to the
+ * end of `data`, so frames can be decoded back to back from a stream buffer.
+ * Fails with code BAD_ENCODING if a block runs past the end of the frame.
+ */
+export function tryCobsDecode(
+  data: Uint8Array,
+  offset = 0
+): Result<DeserializeResult<Uint8Array>, DeserializeError> {
+  const delimiter = data.indexOf(0x00, offset);
+  const end = delimiter === -1 ? data.length : delimiter;
+  const payload = new Uint8Array(Math.max(end - offset - 1, 0));
+  let length = 0;
+
+  let i = offset;
+  while (i < end) {
+    const code = data[i] as number;
+    if (i + code > end) {
+      return err(
+        new FramingError(`COBS block at offset ${String(i)} runs past the end of the frame`)
+      );
+    }
+    payload.set(data.subarray(i + 1, i + code), length);
+    length += code - 1;
+    i += code;
+    // A block shorter than 254 bytes stood for a zero, unless it ends the frame
+    if (code !== 0xff && i < end) {
+      payload[length++] = 0x00;
+    }
+  }
+
+  const bytesRead = end - offset + (delimiter === -1 ? 0 : 1);
+  return ok({ value: payload.subarray(0, length), bytesRead });
+}
+
+/**
+ * Unstuff the COBS frame starting at `offset` (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function cobsDecode(data: Uint8Array, offset?: number): DeserializeResult<Uint8Array> {
+  return unwrap(tryCobsDecode(data, offset));
+}
+
+/**
+ * Serialize a value into a COBS frame (Result API)
+ */
+export function trySerializeCobs<S extends Schema>(
+  schema: S,
+  value: unknown,
+  options?: SerializeOptions
+): Result<Uint8Array, SerializeError> {
+  const result = trySerialize(schema, value, options);
+  return result.ok ? ok(cobsEncode(result.value)) : result;
+}
+
+/**
+ * Serialize a value into a COBS frame (throwing API)
+ *
+ * Throws SerializeError on failure
+ */
+export function serializeCobs<S extends Schema>(
+  schema: S,
+  value: unknown,
+  options?: SerializeOptions
+): Uint8Array {
+  return unwrap(trySerializeCobs(schema, value, options));
+}
+
+/**
+ * Deserialize the value in the COBS frame starting at `offset` (Result API)
+ *
+ * `bytesRead` counts frame bytes, delimiter included. As with postcard's
+ * `from_bytes_cobs`, payload bytes after the value are ignored.
+ */
+export function tryDeserializeCobs<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset = 0,
+  options: DeserializeOptions = {}
+): Result<DeserializeResult<InferType<S>>, DeserializeError> {
+  const frame = tryCobsDecode(data, offset);
+  if (!frame.ok) {
+    return frame;
+  }
+  const result = tryDeserialize(schema, frame.value.value, 0, options);
+  if (!result.ok) {
+    return result;
+  }
+  return ok({ value: result.value.value, bytesRead: frame.value.bytesRead });
+}
+
+/**
+ * Deserialize the value in the COBS frame starting at `offset` (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function deserializeCobs<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset?: number,
+  options?: DeserializeOptions
+): DeserializeResult<InferType<S>> {
+  return unwrap(tryDeserializeCobs(schema, data, offset, options));
+}
+
+export interface CobsAccumulatorOptions extends DeserializeOptions {
+  /**
+   * Longest frame accepted, delimiter included (default: unbounded), like the
+   * buffer size `N` of postcard's `CobsAccumulator<N>`. Longer frames fail
+   * with code BUFFER_FULL and are skipped up to their delimiter.
+   */
+  readonly maxFrameLength?: number;
+}
+
+/**
+ * Decodes COBS-framed values from a stream delivered in chunks
+ *
+ *   const frames = new CobsAccumulator(TelemetrySchema, { maxFrameLength: 256 });
+ *   port.onChunk = (chunk) => {
+ *     frames.push(chunk);
+ *     for (let r = frames.tryNext(); !r.ok || r.value !== undefined; r = frames.tryNext()) {
+ *       if (r.ok) render(r.value); else console.warn("dropped a frame", r.error);
+ *     }
+ *   };
+ *
+ * Unlike unframed streams, a malformed frame only costs that frame: decoding
+ * continues after its delimiter, as postcard's `CobsAccumulator` does.
+ */
+export class CobsAccumulator<S extends Schema> {
+  readonly schema: S;
+  readonly maxFrameLength: number;
+
+  private readonly options: DeserializeOptions;
+  // Unconsumed bytes are buffer[start..end]; buffer[start..scanned] holds no delimiter
+  private buffer = new Uint8Array(0);
+  private start = 0;
+  private end = 0;
+  private scanned = 0;
+  // Dropping the rest of a frame longer than maxFrameLength
+  private skipping = false;
+
+  constructor(schema: S, options: CobsAccumulatorOptions = {}) {
+    const { maxFrameLength = Infinity, ...deserializeOptions } = options;
+    if (!(maxFrameLength >= 1)) {
+      throw new RangeError(`maxFrameLength must be at least 1, got ${String(maxFrameLength)}`);
+    }
+    this.schema = schema;
+    this.maxFrameLength = maxFrameLength;
+    this.options = deserializeOptions;
+  }
+
+  /** Bytes of the frame received so far */
+  get buffered(): number {
+    return this.end - this.start;
+  }
+
+  /**
+   * Append a chunk of the stream
+   *
+   * The chunk is copied, so the caller may reuse its buffer.
+   */
+  push(chunk: Uint8Array): void {
+    if (chunk.length === 0) {
+      return;
+    }
+    if (this.end + chunk.length > this.buffer.length) {
+      const pending = this.buffer.subarray(this.start, this.end);
+      const grown =
+        pending.length + chunk.length > this.buffer.length
+          ? new Uint8Array(Math.max(2 * this.buffer.length, pending.length + chunk.length))
+          : this.buffer;
+      grown.set(pending, 0);
+      this.buffer = grown;
+      this.scanned -= this.start;
+      this.end = pending.length;
+      this.start = 0;
+    }
+    this.buffer.set(chunk, this.end);
+    this.end += chunk.length;
+  }
+
+  /**
+   * The value of the next complete frame, or undefined until a frame is
+   * complete (Result API)
+   *
+   * An error stands for one malformed or oversized frame, which is dropped:
+   * the next call continues with the frame after it.
+   */
+  tryNext(): Result<InferType<S> | undefined, DeserializeError> {
+    const delimiter = this.buffer.subarray(0, this.end).indexOf(0x00, this.scanned);
+    if (delimiter === -1) {
+      this.scanned = this.end;
+      if (this.skipping || this.end - this.start < this.maxFrameLength) {
+        if (this.skipping) {
+          this.clear();
+        }
+        return ok(undefined);
+      }
+      // Not even the delimiter fits any more
+      const length = this.end - this.start;
+      this.clear();
+      this.skipping = true;
+      return err(this.tooLong(`more than ${String(length)}`));
+    }
+
+    const frame = this.buffer.subarray(this.start, delimiter + 1);
+    this.start = delimiter + 1;
+    this.scanned = this.start;
+    if (this.start === this.end) {
+      this.clear();
+    }
+    if (this.skipping) {
+      this.skipping = false;
+      return this.tryNext();
+    }
+    if (frame.length > this.maxFrameLength) {
+      return err(this.tooLong(String(frame.length)));
+    }
+
+    const result = tryDeserializeCobs(this.schema, frame, 0, this.options);
+    return result.ok ? ok(result.value.value) : result;
+  }
+
+  /**
+   * The value of the next complete frame, or undefined until a frame is
+   * complete (throwing API)
+   *
+   * Throws DeserializeError for a malformed or oversized frame; the frame is
+   * dropped, so calling again continues with the next one.
+   */
+  next(): InferType<S> | undefined {
+    return unwrap(this.tryNext());
+  }
+
+  /**
+   * The values of every frame complete so far
+   *
+   * Throws DeserializeError on a malformed or oversized frame, after yielding
+   * the values before it; iterating again continues after that frame.
+   */
+  *[Symbol.iterator](): IterableIterator<InferType<S>> {
+    for (let value = this.next(); value !== undefined; value = this.next()) {
+      yield value;
+    }
+  }
+
+  /**
+   * Drop the partial frame, e.g. after reconnecting
+   */
+  reset(): void {
+    this.clear();
+    this.skipping = false;
+  }
+
+  private clear(): void {
+    this.start = 0;
+    this.end = 0;
+    this.scanned = 0;
+  }
+
+  private tooLong(length: string): FramingError {
+    return new FramingError(
+      `COBS frame of ${length} bytes exceeds maxFrameLength (${String(this.maxFrameLength)})`,
+      ErrorCode.BufferFull
+    );
+  }
+}
@@ -0,0 +1,310 @@
+/**
+ * Columnar decoding into typed arrays
+ *
+ * Plotting and signal-processing code wants a `Vec<Point>` as one typed array
+ * per field (struct-of-arrays) rather than an array of objects, and a
+ * `Vec<f32>` of samples written straight into an existing buffer. Decoding
+ * directly into typed arrays avoids building intermediate values and a
+ * separate conversion pass.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import type {
+  SeqSchema,
+  StructSchema,
+  U8Schema,
+  U16Schema,
+  U32Schema,
+  I8Schema,
+  I16Schema,
+  I32Schema,
+  F32Schema,
+  F64Schema,
+} from "../types/schema.js";
+import { NumberDecodeError } from "../primitives/numbers.js";
+import type { DeserializeResult } from "./deserializer.js";
+import {
+  tryDecodeVarintU16,
+  tryDecodeVarintU32,
+  tryDecodeVarintU64,
+  tryDecodeVarintI16,
+  tryDecodeVarintI32,
+  type VarintDecodeError,
+} from "./varint.js";
+import { PostcardDecodeError } from "../types/errors.js";
+
+export class ColumnarError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.InvalidValue) {
+    super(message, code);
+    this.name = "ColumnarError";
+  }
+}
+
+/**
+ * Schemas whose values fit a typed array without loss
+ */
+export type NumericSchema =
+  | U8Schema
+  | U16Schema
+  | U32Schema
+  | I8Schema
+  | I16Schema
+  | I32Schema
+  | F32Schema
+  | F64Schema;
+
+export interface TypedArrayByKind {
+  u8: Uint8Array;
+  u16: Uint16Array;
+  u32: Uint32Array;
+  i8: Int8Array;
+  i16: Int16Array;
+  i32: Int32Array;
+  f32: Float32Array;
+  f64: Float64Array;
+}
+
+export type TypedArrayFor<S extends NumericSchema> = TypedArrayByKind[S["kind"]];
+
+/**
+ * Any typed array a numeric value can be stored in
+ */
+export type NumericArray = TypedArrayByKind[NumericSchema["kind"]];
+
+/**
+ * One typed array per struct field, all of the sequence's length
+ */
+export type Columns<F extends Record<string, NumericSchema>> = {
+  [K in keyof F]: TypedArrayFor<F[K]>;
+};
+
+type TypedArrayConstructor = new (length: number) => NumericArray;
+
+const TYPED_ARRAYS: Record<NumericSchema["kind"], TypedArrayConstructor> = {
+  u8: Uint8Array,
+  u16: Uint16Array,
+  u32: Uint32Array,
+  i8: Int8Array,
+  i16: Int16Array,
+  i32: Int32Array,
+  f32: Float32Array,
+  f64: Float64Array,
+};
+
+// Fewest bytes one value can occupy, used to reject implausible lengths
+// before allocating
+const MIN_ENCODED_LEN: Record<NumericSchema["kind"], number> = {
+  u8: 1,
+  u16: 1,
+  u32: 1,
+  i8: 1,
+  i16: 1,
+  i32: 1,
+  f32: 4,
+  f64: 8,
+};
+
+/**
+ * Decode a sequence of numeric structs into one typed array per field
+ * (Result API)
+ *
+ *   const PointSchema = struct({ x: f64(), y: f64(), z: f64() });
+ *   const { value } = decodeColumns(seq(PointSchema), data);
+ *   // value: { x: Float64Array; y: Float64Array; z: Float64Array }
+ */
+export function tryDecodeColumns<F extends Record<string, NumericSchema>>(
+  schema: SeqSchema<StructSchema<F>>,
+  data: Uint8Array,
+  offset = 0
+): Result<DeserializeResult<Columns<F>>, ColumnarError> {
+  const fields = Object.entries(schema.item.fields) as [keyof F & string, NumericSchema][];
+  for (const [name, field] of fields) {
+    if (!(field.kind in TYPED_ARRAYS)) {
+      return err(
+        new ColumnarError(`Field "${name}" has kind ${field.kind}, which is not a numeric column`)
+      );
+    }
+  }
+
+  const lengthResult = tryDecodeVarintU64(data, offset);
+  if (!lengthResult.ok) {
+    return err(
+      new ColumnarError(
+        `Failed to deserialize sequence length: ${lengthResult.error.message}`,
+        lengthResult.error.code
+      )
+    );
+  }
+  const length = Number(lengthResult.value.value);
+  let currentOffset = offset + lengthResult.value.bytesRead;
+
+  // A length the data cannot hold still fails where deserialize() would, on
+  // the item that runs out of bytes, but only the items that fit are allocated
+  const minItemLen = fields.reduce((sum, [, field]) => sum + MIN_ENCODED_LEN[field.kind], 0);
+  const fitting = Math.floor((data.length - currentOffset) / minItemLen);
+  const capacity = minItemLen === 0 ? length : Math.min(length, fitting);
+
+  const columns = {} as Record<keyof F, NumericArray>;
+  for (const [name, field] of fields) {
+    columns[name] = new TYPED_ARRAYS[field.kind](capacity);
+  }
+
+  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  for (let i = 0; i < length; i++) {
+    for (const [name, field] of fields) {
+      const result = readNumeric(field.kind, data, view, currentOffset);
+      if (!result.ok) {
+        return err(
+          new ColumnarError(
+            `Failed to deserialize field "${name}" of item ${String(i)}: ${result.error.message}`,
+            result.error.code
+          )
+        );
+      }
+      columns[name][i] = result.value.value;
+      currentOffset += result.value.bytesRead;
+    }
+  }
+
+  return ok({ value: columns as Columns<F>, bytesRead: currentOffset - offset });
+}
+
+/**
+ * Decode a sequence of numeric structs into one typed array per field
+ * (throwing API)
+ *
+ * Throws ColumnarError on failure
+ */
+export function decodeColumns<F extends Record<string, NumericSchema>>(
+  schema: SeqSchema<StructSchema<F>>,
+  data: Uint8Array,
+  offset?: number
+): DeserializeResult<Columns<F>> {
+  return unwrap(tryDecodeColumns(schema, data, offset));
+}
+
+/**
+ * Decode a sequence of numbers into an existing typed array (Result API)
+ *
+ * Values are written from `targetOffset` on, converted by the typed array as
+ * on any assignment, so i16 ADC samples can go straight into a Float32Array.
+ * The value is the number of items written. A target without room for the
+ * whole sequence fails before anything is written; truncated data fails part
+ * way, leaving the items decoded so far in the target.
+ *
+ *   const { value: count } = decodeSeqInto(seq(f32()), data, samples, writeIndex);
+ */
+export function tryDecodeSeqInto<S extends NumericSchema>(
+  schema: SeqSchema<S>,
+  data: Uint8Array,
+  target: NumericArray,
+  targetOffset = 0,
+  offset = 0
+): Result<DeserializeResult<number>, ColumnarError> {
+  const kind = schema.item.kind;
+  if (!(kind in TYPED_ARRAYS)) {
+    return err(new ColumnarError(`Item kind ${kind} cannot be decoded into a typed array`));
+  }
+
+  const lengthResult = tryDecodeVarintU64(data, offset);
+  if (!lengthResult.ok) {
+    return err(
+      new ColumnarError(
+        `Failed to deserialize sequence length: ${lengthResult.error.message}`,
+        lengthResult.error.code
+      )
+    );
+  }
+  const length = Number(lengthResult.value.value);
+  let currentOffset = offset + lengthResult.value.bytesRead;
+
+  if (!Number.isInteger(targetOffset) || targetOffset < 0 || targetOffset > target.length) {
+    return err(
+      new ColumnarError(
+        `Target offset ${String(targetOffset)} is outside the target (${String(target.length)} items)`
+      )
+    );
+  }
+  if (length > target.length - targetOffset) {
+    return err(
+      new ColumnarError(
+        `Sequence of ${String(length)} items does not fit in the target from offset ${String(targetOffset)}`
+      )
+    );
+  }
+
+  const view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  for (let i = 0; i < length; i++) {
+    const result = readNumeric(kind, data, view, currentOffset);
+    if (!result.ok) {
+      return err(
+        new ColumnarError(
+          `Failed to deserialize item ${String(i)}: ${result.error.message}`,
+          result.error.code
+        )
+      );
+    }
+    target[targetOffset + i] = result.value.value;
+    currentOffset += result.value.bytesRead;
+  }
+
+  return ok({ value: length, bytesRead: currentOffset - offset });
+}
+
+/**
+ * Decode a sequence of numbers into an existing typed array (throwing API)
+ *
+ * Throws ColumnarError on failure
+ */
+export function decodeSeqInto<S extends NumericSchema>(
+  schema: SeqSchema<S>,
+  data: Uint8Array,
+  target: NumericArray,
+  targetOffset?: number,
+  offset?: number
+): DeserializeResult<number> {
+  return unwrap(tryDecodeSeqInto(schema, data, target, targetOffset, offset));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/**
+ * Read one numeric value without allocating intermediate buffers
+ */
+function readNumeric(
+  kind: NumericSchema["kind"],
+  data: Uint8Array,
+  view: DataView,
+  offset: number
+): Result<{ value: number; bytesRead: number }, VarintDecodeError | NumberDecodeError> {
+  switch (kind) {
+    case "u8":
+    case "i8": {
+      if (offset >= data.length) {
+        return err(new NumberDecodeError("Unexpected end of data"));
+      }
+      const value = kind === "u8" ? view.getUint8(offset) : view.getInt8(offset);
+      return ok({ value, bytesRead: 1 });
+    }
+    case "f32":
+    case "f64": {
+      const size = kind === "f32" ? 4 : 8;
+      if (offset + size > data.length) {
+        return err(new NumberDecodeError(`Not enough bytes for ${kind}`));
+      }
+      const value = kind === "f32" ? view.getFloat32(offset, true) : view.getFloat64(offset, true);
+      return ok({ value, bytesRead: size });
+    }
+    case "u16":
+      return tryDecodeVarintU16(data, offset);
+    case "u32":
+      return tryDecodeVarintU32(data, offset);
+    case "i16":
+      return tryDecodeVarintI16(data, offset);
+    case "i32":
+      return tryDecodeVarintI32(data, offset);
+  }
+}
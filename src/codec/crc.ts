@@ -0,0 +1,472 @@
+/**
+ * CRC-checked messages, as postcard's CRC flavor (`use-crc` feature)
+ *
+ * postcard's `ser_flavors::crc::to_allocvec_u32(&value, CRC.digest())` writes
+ * the value followed by a checksum of its bytes, little-endian, and
+ * `de_flavors::crc::from_bytes_u32` checks it on the way back. The checksum is
+ * configured like a `crc::Crc` of the `crc` crate, whose catalog algorithms
+ * are mirrored here under the same names:
+ *
+ *   // Rust: const CRC: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+ *   const crc = new Crc(CRC_32_ISCSI);
+ *   const bytes = serializeCrc(ReadingSchema, reading, crc);
+ *   const { value } = deserializeCrc(ReadingSchema, bytes, crc);
+ *
+ * The checksum takes as many bytes as the smallest unsigned integer holding
+ * `width` bits, the `W` of `Crc<W>`: 1 up to CRC-8, 2 up to CRC-16, 4 up to
+ * CRC-32 and 8 up to CRC-64. Wider CRCs are not supported.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import type { Schema, InferType } from "../types/schema.js";
+import { type SerializeError, type SerializeOptions, trySerialize } from "./serializer.js";
+import {
+  type DeserializeResult,
+  type DeserializeOptions,
+  DeserializeError,
+  tryDeserialize,
+} from "./deserializer.js";
+
+/**
+ * A message whose CRC does not match its bytes (code BAD_CRC)
+ */
+export class CrcMismatchError extends DeserializeError {
+  constructor(
+    message: string,
+    /** The checksum of the message, as it would be sent */
+    readonly expected: Uint8Array,
+    /** The checksum that was sent */
+    readonly received: Uint8Array
+  ) {
+    super(message, ErrorCode.BadCrc);
+    this.name = "CrcMismatchError";
+  }
+}
+
+/**
+ * Parameters of a CRC, named as in the `crc` crate's `Algorithm`
+ */
+export interface CrcAlgorithm {
+  /** Bits of the checksum, 1 to 64 */
+  readonly width: number;
+  /** Generator polynomial, without its top bit */
+  readonly poly: number | bigint;
+  /** Register value before the first byte */
+  readonly init: number | bigint;
+  /** Whether input bytes are processed least significant bit first */
+  readonly refin: boolean;
+  /** Whether the final register is bit-reversed */
+  readonly refout: boolean;
+  /** XORed into the final register */
+  readonly xorout: number | bigint;
+  /** Checksum of the ASCII bytes "123456789"; checked on construction if given */
+  readonly check?: number | bigint;
+}
+
+export const CRC_8_SMBUS: CrcAlgorithm = Object.freeze({
+  width: 8,
+  poly: 0x07,
+  init: 0x00,
+  refin: false,
+  refout: false,
+  xorout: 0x00,
+  check: 0xf4,
+});
+
+export const CRC_8_MAXIM_DOW: CrcAlgorithm = Object.freeze({
+  width: 8,
+  poly: 0x31,
+  init: 0x00,
+  refin: true,
+  refout: true,
+  xorout: 0x00,
+  check: 0xa1,
+});
+
+export const CRC_16_ARC: CrcAlgorithm = Object.freeze({
+  width: 16,
+  poly: 0x8005,
+  init: 0x0000,
+  refin: true,
+  refout: true,
+  xorout: 0x0000,
+  check: 0xbb3d,
+});
+
+export const CRC_16_IBM_SDLC: CrcAlgorithm = Object.freeze({
+  width: 16,
+  poly: 0x1021,
+  init: 0xffff,
+  refin: true,
+  refout: true,
+  xorout: 0xffff,
+  check: 0x906e,
+});
+
+export const CRC_16_KERMIT: CrcAlgorithm = Object.freeze({
+  width: 16,
+  poly: 0x1021,
+  init: 0x0000,
+  refin: true,
+  refout: true,
+  xorout: 0x0000,
+  check: 0x2189,
+});
+
+export const CRC_16_MODBUS: CrcAlgorithm = Object.freeze({
+  width: 16,
+  poly: 0x8005,
+  init: 0xffff,
+  refin: true,
+  refout: true,
+  xorout: 0x0000,
+  check: 0x4b37,
+});
+
+export const CRC_16_XMODEM: CrcAlgorithm = Object.freeze({
+  width: 16,
+  poly: 0x1021,
+  init: 0x0000,
+  refin: false,
+  refout: false,
+  xorout: 0x0000,
+  check: 0x31c3,
+});
+
+export const CRC_32_BZIP2: CrcAlgorithm = Object.freeze({
+  width: 32,
+  poly: 0x04c11db7,
+  init: 0xffffffff,
+  refin: false,
+  refout: false,
+  xorout: 0xffffffff,
+  check: 0xfc891918,
+});
+
+export const CRC_32_ISCSI: CrcAlgorithm = Object.freeze({
+  width: 32,
+  poly: 0x1edc6f41,
+  init: 0xffffffff,
+  refin: true,
+  refout: true,
+  xorout: 0xffffffff,
+  check: 0xe3069283,
+});
+
+/** The CRC-32 of zlib, Ethernet and PNG */
+export const CRC_32_ISO_HDLC: CrcAlgorithm = Object.freeze({
+  width: 32,
+  poly: 0x04c11db7,
+  init: 0xffffffff,
+  refin: true,
+  refout: true,
+  xorout: 0xffffffff,
+  check: 0xcbf43926,
+});
+
+export const CRC_32_MPEG_2: CrcAlgorithm = Object.freeze({
+  width: 32,
+  poly: 0x04c11db7,
+  init: 0xffffffff,
+  refin: false,
+  refout: false,
+  xorout: 0x00000000,
+  check: 0x0376e6e7,
+});
+
+export const CRC_64_ECMA_182: CrcAlgorithm = Object.freeze({
+  width: 64,
+  poly: 0x42f0e1eba9ea3693n,
+  init: 0n,
+  refin: false,
+  refout: false,
+  xorout: 0n,
+  check: 0x6c40df5f0b497347n,
+});
+
+export const CRC_64_XZ: CrcAlgorithm = Object.freeze({
+  width: 64,
+  poly: 0x42f0e1eba9ea3693n,
+  init: 0xffffffffffffffffn,
+  refin: true,
+  refout: true,
+  xorout: 0xffffffffffffffffn,
+  check: 0x995dc9bbdf1939fan,
+});
+
+const CHECK_INPUT = new TextEncoder().encode("123456789");
+
+/** The low `width` bits of `value`, bit-reversed */
+function reflect(value: bigint, width: number): bigint {
+  let reflected = 0n;
+  for (let i = 0; i < width; i++) {
+    reflected = (reflected << 1n) | ((value >> BigInt(i)) & 1n);
+  }
+  return reflected;
+}
+
+interface Parameters {
+  readonly width: number;
+  readonly poly: bigint;
+  readonly init: bigint;
+  readonly refin: boolean;
+  readonly refout: boolean;
+  readonly xorout: bigint;
+}
+
+/**
+ * Table-driven CRC on numbers, for widths up to 32
+ *
+ * Reflected CRCs keep the register reflected and shift right; the others keep
+ * it in the top bits of a register of at least 8 bits and shift left.
+ */
+function numberChecksum(p: Parameters): (data: Uint8Array) => bigint {
+  const width = p.width;
+  const table = new Uint32Array(256);
+  if (p.refin) {
+    const poly = Number(reflect(p.poly, width));
+    for (let i = 0; i < 256; i++) {
+      let c = i;
+      for (let bit = 0; bit < 8; bit++) {
+        c = c & 1 ? (c >>> 1) ^ poly : c >>> 1;
+      }
+      table[i] = c >>> 0;
+    }
+    const init = Number(reflect(p.init, width));
+    const reflectOut = !p.refout;
+    return (data) => {
+      let crc = init;
+      for (const byte of data) {
+        crc = (crc >>> 8) ^ (table[(crc ^ byte) & 0xff] as number);
+      }
+      const out = BigInt(crc >>> 0);
+      return (reflectOut ? reflect(out, width) : out) ^ p.xorout;
+    };
+  }
+
+  const shift = Math.max(8 - width, 0);
+  const bits = width + shift;
+  const mask = 2 ** bits - 1;
+  const top = 2 ** (bits - 1);
+  const poly = Number(p.poly) * 2 ** shift;
+  for (let i = 0; i < 256; i++) {
+    let c = i * 2 ** (bits - 8);
+    for (let bit = 0; bit < 8; bit++) {
+      c = c >= top ? ((c - top) * 2) ^ poly : c * 2;
+      c = c >>> 0;
+    }
+    table[i] = c;
+  }
+  const init = Number(p.init) * 2 ** shift;
+  return (data) => {
+    let crc = init;
+    for (const byte of data) {
+      const index = ((crc >>> (bits - 8)) ^ byte) & 0xff;
+      crc = (((crc << 8) & mask) ^ (table[index] as number)) >>> 0;
+    }
+    const out = BigInt(crc >>> shift);
+    return (p.refout ? reflect(out, width) : out) ^ p.xorout;
+  };
+}
+
+/**
+ * Table-driven CRC on bigints, for widths above 32
+ *
+ * The register is kept unreflected, so reflected CRCs reflect each input byte.
+ */
+function bigintChecksum(p: Parameters): (data: Uint8Array) => bigint {
+  const width = BigInt(p.width);
+  const mask = (1n << width) - 1n;
+  const top = 1n << (width - 1n);
+  const table: bigint[] = [];
+  for (let i = 0; i < 256; i++) {
+    let c = BigInt(i) << (width - 8n);
+    for (let bit = 0; bit < 8; bit++) {
+      c = c & top ? ((c << 1n) ^ p.poly) & mask : (c << 1n) & mask;
+    }
+    table.push(c);
+  }
+  const inputs = new Uint8Array(256);
+  for (let i = 0; i < 256; i++) {
+    inputs[i] = p.refin ? Number(reflect(BigInt(i), 8)) : i;
+  }
+  return (data) => {
+    let crc = p.init;
+    for (const byte of data) {
+      const index = Number(crc >> (width - 8n)) ^ (inputs[byte] as number);
+      crc = ((crc << 8n) & mask) ^ (table[index] as bigint);
+    }
+    return (p.refout ? reflect(crc, p.width) : crc) ^ p.xorout;
+  };
+}
+
+/**
+ * A configured CRC, like the `crc` crate's `Crc<W>`
+ */
+export class Crc {
+  readonly algorithm: CrcAlgorithm;
+  /** Bytes the checksum takes after a message */
+  readonly byteLength: number;
+
+  private readonly compute: (data: Uint8Array) => bigint;
+
+  /**
+   * Throws RangeError if a parameter does not fit `width`, or if the
+   * algorithm's `check` value is not what it computes
+   */
+  constructor(algorithm: CrcAlgorithm) {
+    const { width } = algorithm;
+    if (!Number.isInteger(width) || width < 1 || width > 64) {
+      throw new RangeError(`CRC width must be 1 to 64 bits, got ${String(width)}`);
+    }
+    const limit = 1n << BigInt(width);
+    const parameter = (name: "poly" | "init" | "xorout"): bigint => {
+      const value = BigInt(algorithm[name]);
+      if (value < 0n || value >= limit) {
+        const bits = `${String(width)} bits`;
+        throw new RangeError(`CRC ${name} ${String(algorithm[name])} does not fit in ${bits}`);
+      }
+      return value;
+    };
+    const parameters: Parameters = {
+      width,
+      poly: parameter("poly"),
+      init: parameter("init"),
+      refin: algorithm.refin,
+      refout: algorithm.refout,
+      xorout: parameter("xorout"),
+    };
+
+    this.algorithm = algorithm;
+    this.byteLength = width <= 8 ? 1 : width <= 16 ? 2 : width <= 32 ? 4 : 8;
+    // 8 bits are shifted out at a time, so narrower CRCs fit numbers up to 32 bits
+    this.compute = width <= 32 ? numberChecksum(parameters) : bigintChecksum(parameters);
+
+    if (algorithm.check !== undefined) {
+      const check = this.checksum(CHECK_INPUT);
+      if (check !== BigInt(algorithm.check)) {
+        throw new RangeError(
+          `CRC parameters give check value 0x${check.toString(16)}, ` +
+            `not 0x${BigInt(algorithm.check).toString(16)}`
+        );
+      }
+    }
+  }
+
+  /** The CRC of `data` */
+  checksum(data: Uint8Array): bigint {
+    return this.compute(data);
+  }
+
+  /** The CRC of `data` as postcard appends it: `byteLength` bytes, little-endian */
+  checksumBytes(data: Uint8Array): Uint8Array {
+    let crc = this.compute(data);
+    const bytes = new Uint8Array(this.byteLength);
+    for (let i = 0; i < bytes.length; i++) {
+      bytes[i] = Number(crc & 0xffn);
+      crc >>= 8n;
+    }
+    return bytes;
+  }
+}
+
+/**
+ * Serialize a value followed by its CRC (Result API)
+ */
+export function trySerializeCrc<S extends Schema>(
+  schema: S,
+  value: unknown,
+  crc: Crc,
+  options?: SerializeOptions
+): Result<Uint8Array, SerializeError> {
+  const result = trySerialize(schema, value, options);
+  if (!result.ok) {
+    return result;
+  }
+  const message = new Uint8Array(result.value.length + crc.byteLength);
+  message.set(result.value, 0);
+  message.set(crc.checksumBytes(result.value), result.value.length);
+  return ok(message);
+}
+
+/**
+ * Serialize a value followed by its CRC (throwing API)
+ *
+ * Throws SerializeError on failure
+ */
+export function serializeCrc<S extends Schema>(
+  schema: S,
+  value: unknown,
+  crc: Crc,
+  options?: SerializeOptions
+): Uint8Array {
+  return unwrap(trySerializeCrc(schema, value, crc, options));
+}
+
+/**
+ * Deserialize the value starting at `offset` and check the CRC after it (Result API)
+ *
+ * `bytesRead` includes the CRC. As with postcard, the value is decoded first,
+ * so corrupted bytes may fail to decode before the CRC is reached; a CRC that
+ * does not match fails with code BAD_CRC, and a missing one with
+ * UNEXPECTED_END.
+ */
+export function tryDeserializeCrc<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  crc: Crc,
+  offset = 0,
+  options: DeserializeOptions = {}
+): Result<DeserializeResult<InferType<S>>, DeserializeError> {
+  const result = tryDeserialize(schema, data, offset, options);
+  if (!result.ok) {
+    return result;
+  }
+  const end = offset + result.value.bytesRead;
+  if (data.length < end + crc.byteLength) {
+    return err(
+      new DeserializeError(
+        `Message ends before its ${String(crc.byteLength)}-byte CRC`,
+        ErrorCode.UnexpectedEnd
+      )
+    );
+  }
+  const expected = crc.checksumBytes(data.subarray(offset, end));
+  const actual = data.subarray(end, end + crc.byteLength);
+  if (expected.some((byte, i) => byte !== actual[i])) {
+    return err(
+      new CrcMismatchError(
+        `CRC mismatch at offset ${String(end)}: message checksums to ` +
+          `${hex(expected)}, but ${hex(actual)} was sent`,
+        expected,
+        actual.slice()
+      )
+    );
+  }
+  return ok({ value: result.value.value, bytesRead: result.value.bytesRead + crc.byteLength });
+}
+
+/**
+ * Deserialize the value starting at `offset` and check the CRC after it (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function deserializeCrc<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  crc: Crc,
+  offset?: number,
+  options?: DeserializeOptions
+): DeserializeResult<InferType<S>> {
+  return unwrap(tryDeserializeCrc(schema, data, crc, offset, options));
+}
+
+/** Little-endian CRC bytes as the hex number they stand for */
+function hex(bytes: Uint8Array): string {
+  let digits = "";
+  for (const byte of bytes) {
+    digits = byte.toString(16).padStart(2, "0") + digits;
+  }
+  return `0x${digits}`;
+}
@@ -0,0 +1,191 @@
+/**
+ * Memoized decoding of repeated payloads
+ *
+ * Some devices re-send their whole state many times a second whether or not
+ * it changed. A DecodeCache hashes each payload and, when the same bytes were
+ * decoded before, returns the value decoded then instead of decoding again:
+ *
+ *   const states = new DecodeCache(DeviceStateSchema, { maxEntries: 16 });
+ *   socket.onmessage = (event) => store.set(states.decode(new Uint8Array(event.data)));
+ *
+ * Values are shared between hits, so they are deeply frozen (see
+ * `deepFreeze`), and a hit returns the very same object, which lets UI
+ * frameworks skip re-rendering on identity. Byte strings are always decoded
+ * as copies, since the cached value outlives the buffer it came from.
+ * Payloads that fail to decode are not cached.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import { type Result, ok, unwrap } from "../types/result.js";
+import { type DeserializeError, type DeserializeOptions, tryDeserialize } from "./deserializer.js";
+import { fnv1a, sameBytes } from "./byte-keys.js";
+
+export interface DecodeCacheOptions {
+  /** Most payloads kept, the least recently used dropped first (default: 64) */
+  readonly maxEntries?: number;
+  /**
+   * Largest payload kept, in bytes (default: 4096); larger ones are decoded
+   * every time, as hashing them would cost about as much
+   */
+  readonly maxPayloadBytes?: number;
+  /** Options for decoding; values are always frozen and bytes always copied */
+  readonly deserialize?: Omit<DeserializeOptions, "freeze" | "byteViews">;
+}
+
+/**
+ * How well a DecodeCache is doing, since it was created or `resetStats()`
+ */
+export interface DecodeCacheStats {
+  /** Payloads answered from the cache */
+  readonly hits: number;
+  /** Payloads decoded because they were not in the cache */
+  readonly misses: number;
+  /** Payloads over `maxPayloadBytes`, decoded without the cache */
+  readonly bypassed: number;
+  /** Payloads dropped to stay within `maxEntries` */
+  readonly evictions: number;
+  /** Payloads held now */
+  readonly size: number;
+  /** Hits over all payloads decoded, 0 before the first */
+  readonly hitRate: number;
+}
+
+interface Entry<T> {
+  readonly hash: number;
+  readonly bytes: Uint8Array;
+  readonly value: T;
+}
+
+export class DecodeCache<S extends Schema> {
+  readonly schema: S;
+
+  private readonly maxEntries: number;
+  private readonly maxPayloadBytes: number;
+  private readonly options: DeserializeOptions;
+  // Entries by hash, more than one when hashes collide
+  private readonly buckets = new Map<number, Entry<InferType<S>>[]>();
+  // Entries from least to most recently used
+  private readonly recent = new Set<Entry<InferType<S>>>();
+  private hits = 0;
+  private misses = 0;
+  private bypassed = 0;
+  private evictions = 0;
+
+  /**
+   * Throws RangeError if `maxEntries` is not a positive integer or
+   * `maxPayloadBytes` is not a non-negative integer
+   */
+  constructor(schema: S, options: DecodeCacheOptions = {}) {
+    const { maxEntries = 64, maxPayloadBytes = 4096, deserialize = {} } = options;
+    if (!Number.isInteger(maxEntries) || maxEntries < 1) {
+      throw new RangeError(`maxEntries must be a positive integer, got ${String(maxEntries)}`);
+    }
+    if (!Number.isInteger(maxPayloadBytes) || maxPayloadBytes < 0) {
+      throw new RangeError(
+        `maxPayloadBytes must be a non-negative integer, got ${String(maxPayloadBytes)}`
+      );
+    }
+    this.schema = schema;
+    this.maxEntries = maxEntries;
+    this.maxPayloadBytes = maxPayloadBytes;
+    this.options = { ...deserialize, freeze: true, byteViews: false };
+  }
+
+  get stats(): DecodeCacheStats {
+    const total = this.hits + this.misses + this.bypassed;
+    return {
+      hits: this.hits,
+      misses: this.misses,
+      bypassed: this.bypassed,
+      evictions: this.evictions,
+      size: this.recent.size,
+      hitRate: total === 0 ? 0 : this.hits / total,
+    };
+  }
+
+  /**
+   * Decode `data`, or return the value decoded from the same bytes before
+   * (Result API)
+   */
+  tryDecode(data: Uint8Array): Result<InferType<S>, DeserializeError> {
+    if (data.length > this.maxPayloadBytes) {
+      this.bypassed++;
+      return this.decodeFresh(data);
+    }
+
+    const hash = fnv1a(data);
+    const bucket = this.buckets.get(hash);
+    const cached = bucket?.find((entry) => sameBytes(entry.bytes, data));
+    if (cached !== undefined) {
+      this.hits++;
+      this.recent.delete(cached);
+      this.recent.add(cached);
+      return ok(cached.value);
+    }
+
+    this.misses++;
+    const result = this.decodeFresh(data);
+    if (!result.ok) {
+      return result;
+    }
+    const entry = { hash, bytes: data.slice(), value: result.value };
+    if (bucket === undefined) {
+      this.buckets.set(hash, [entry]);
+    } else {
+      bucket.push(entry);
+    }
+    this.recent.add(entry);
+    if (this.recent.size > this.maxEntries) {
+      this.evictOldest();
+    }
+    return result;
+  }
+
+  /**
+   * Decode `data`, or return the value decoded from the same bytes before
+   *
+   * Throws the DeserializeError of data that does not decode.
+   */
+  decode(data: Uint8Array): InferType<S> {
+    return unwrap(this.tryDecode(data));
+  }
+
+  /**
+   * Start counting hits, misses, bypassed payloads and evictions from zero
+   */
+  resetStats(): void {
+    this.hits = 0;
+    this.misses = 0;
+    this.bypassed = 0;
+    this.evictions = 0;
+  }
+
+  /**
+   * Drop every cached payload, e.g. when the device reconnects
+   */
+  clear(): void {
+    this.buckets.clear();
+    this.recent.clear();
+  }
+
+  private decodeFresh(data: Uint8Array): Result<InferType<S>, DeserializeError> {
+    const result = tryDeserialize(this.schema, data, 0, this.options);
+    return result.ok ? ok(result.value.value) : result;
+  }
+
+  private evictOldest(): void {
+    const [oldest] = this.recent;
+    if (oldest === undefined) {
+      return;
+    }
+    this.recent.delete(oldest);
+    const bucket = this.buckets.get(oldest.hash) ?? [];
+    const remaining = bucket.filter((entry) => entry !== oldest);
+    if (remaining.length === 0) {
+      this.buckets.delete(oldest.hash);
+    } else {
+      this.buckets.set(oldest.hash, remaining);
+    }
+    this.evictions++;
+  }
+}
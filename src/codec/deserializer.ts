@@ -6,6 +6,7 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
 import type {
   Schema,
   InferType,
@@ -28,6 +29,7 @@ import type {
   OptionSchema,
   UnitSchema,
   UnitStructSchema,
+  NeverSchema,
   NewtypeStructSchema,
   SeqSchema,
   TupleSchema,
@@ -40,7 +42,10 @@ import type {
   NewtypeVariantSchema,
   TupleVariantSchema,
   StructVariantSchema,
+  TransformSchema,
+  ExternalSchema,
 } from "../types/schema.js";
+import { directionOf } from "../types/schema.js";
 
 // Import primitive deserializers
 import { tryDecodeBool } from "../primitives/bool.js";
@@ -52,6 +57,7 @@ import {
 } from "../primitives/numbers.js";
 import { tryDecodeString, tryDecodeChar } from "../primitives/string.js";
 import { tryDecodeBytes } from "../primitives/bytes.js";
+import type { StringInterner } from "./intern.js";
 
 // Import varint codec
 import {
@@ -64,95 +70,512 @@ import {
   tryDecodeVarintI64,
   tryDecodeVarintI128,
 } from "./varint.js";
+import { deepFreeze } from "./freeze.js";
+import { encodedKey } from "./map-keys.js";
+import { trySerialize } from "./serializer.js";
+import { PostcardDecodeError } from "../types/errors.js";
 
-export class DeserializeError extends Error {
-  constructor(message: string) {
-    super(message);
+/**
+ * Where in the input and the value a decode failed
+ */
+export interface DecodeErrorLocation {
+  /** Absolute index in the input where the value that failed starts */
+  readonly offset: number;
+  /**
+   * The fields, items and variants from the root to that value, e.g.
+   * `GameState.player.inventory.items[2].Weapon.element`; empty for the root
+   */
+  readonly path: string;
+  /** What the schema called for and what the bytes held, when known */
+  readonly mismatch?: { readonly expected: string; readonly found: string };
+}
+
+/**
+ * Schema-driven decoding failed
+ *
+ * Errors from `tryDeserialize` carry the `offset` and `path` of the value
+ * that failed, also in the message:
+ *
+ *   GameState.player.inventory.items[2].Weapon.element at byte 41: Invalid
+ *   enum discriminant for Element: 7 (max: 3)
+ */
+export class DeserializeError extends PostcardDecodeError {
+  readonly offset: number | undefined;
+  readonly path: string | undefined;
+  readonly mismatch: { readonly expected: string; readonly found: string } | undefined;
+
+  constructor(
+    message: string,
+    code: ErrorCode = ErrorCode.InvalidValue,
+    location?: DecodeErrorLocation
+  ) {
+    super(location === undefined ? message : `${describeLocation(location)}: ${message}`, code);
     this.name = "DeserializeError";
+    this.offset = location?.offset;
+    this.path = location?.path;
+    this.mismatch = location?.mismatch;
   }
 }
 
+/**
+ * A decoded value that does not re-encode to the bytes it was decoded from,
+ * found by the `verify` option: the schema, or a transform in it, encodes
+ * differently than it decodes
+ *
+ * Its offset is the absolute index in the input of the first byte that
+ * differs, and its path that of the decoded value.
+ */
+export class ReencodeMismatchError extends DeserializeError {
+  declare readonly offset: number;
+
+  constructor(
+    message: string,
+    location: DecodeErrorLocation,
+    /** The bytes the value was decoded from */
+    readonly expected: Uint8Array,
+    /** The bytes it re-encoded to, or undefined if it could not be encoded */
+    readonly actual: Uint8Array | undefined
+  ) {
+    super(message, ErrorCode.InvalidValue, location);
+    this.name = "ReencodeMismatchError";
+  }
+}
+
+function describeLocation({ offset, path }: DecodeErrorLocation): string {
+  const at = `byte ${String(offset)}`;
+  return path === "" ? `At ${at}` : `${path} at ${at}`;
+}
+
 export interface DeserializeResult<T> {
   value: T;
   bytesRead: number;
 }
 
+/**
+ * What to do when a map repeats a key, which postcard does not forbid
+ *
+ * - `"last"`: the later value replaces the earlier one, as serde's `HashMap`
+ *   and `BTreeMap` do in Rust
+ * - `"first"`: later values for a key are ignored
+ * - `"error"`: decoding fails with code INVALID_VALUE
+ */
+export type DuplicateKeyPolicy = "last" | "first" | "error";
+
+export interface DeserializeOptions {
+  /**
+   * Recursively freeze the decoded value so accidental mutation throws
+   * (default: false). Meant for development builds, e.g.
+   * `{ freeze: process.env.NODE_ENV !== "production" }`.
+   */
+  readonly freeze?: boolean;
+  /**
+   * Handling of repeated map keys (default: "last", matching Rust)
+   */
+  readonly duplicateKeys?: DuplicateKeyPolicy;
+  /**
+   * Decode `bytes()` as views into `data` instead of copies (default: false)
+   *
+   * Saves a copy per byte string, e.g. for large payloads, but the views
+   * change when `data` is overwritten, such as a reused receive buffer.
+   */
+  readonly byteViews?: boolean;
+  /**
+   * Name of the decoded value, the first segment of error paths, e.g. its
+   * type name (default: none, so paths start at its first field)
+   */
+  readonly root?: string;
+  /**
+   * Table sharing one instance between equal decoded strings, kept across
+   * decodes (default: none, every string decoded anew); see StringInterner
+   */
+  readonly intern?: StringInterner;
+  /**
+   * Bounds on untrusted input, failing with code INVALID_VALUE when crossed
+   * (default: none)
+   */
+  readonly limits?: DecodeLimits;
+  /**
+   * Called after each value is decoded, inner values first; for debugging
+   * and profiling, as it slows decoding down
+   */
+  readonly trace?: (event: DecodeTraceEvent) => void;
+  /** Counters to add this decode to, kept across decodes (default: none) */
+  readonly stats?: DecodeStats;
+  /**
+   * Re-encode every decoded value and fail with ReencodeMismatchError, a
+   * DeserializeError, unless it gives back the bytes it was decoded from
+   * (default: false)
+   *
+   * A canary for development builds, catching a schema or transform that
+   * encodes differently than it decodes at the first message it garbles:
+   * `{ verify: process.env.NODE_ENV !== "production" }`. Input that decodes
+   * but that postcard never writes, such as an overlong varint or a
+   * repeated map key, fails too. Decode-only schemas are not verified.
+   */
+  readonly verify?: boolean;
+}
+
+/**
+ * Bounds on what one decode may read, so input from the network cannot make
+ * it recurse or allocate without end; decoding trusted local replays can
+ * leave them out
+ */
+export interface DecodeLimits {
+  /** Deepest nesting of values, the decoded value itself being 1 */
+  readonly maxDepth?: number;
+  /** Most elements of a sequence or entries of a map */
+  readonly maxLength?: number;
+  /** Most bytes of a string or byte string */
+  readonly maxBytes?: number;
+}
+
+export interface DecodeTraceEvent {
+  readonly kind: Schema["kind"];
+  /** Path of the value, as in error messages */
+  readonly path: string;
+  readonly offset: number;
+  readonly bytesRead: number;
+}
+
+/**
+ * Counters of decodes given them as the `stats` option, e.g. one per
+ * connection
+ */
+export class DecodeStats {
+  /** Decodes, whether or not they succeeded */
+  decodes = 0;
+  /** Decodes that failed */
+  failures = 0;
+  /** Bytes read by the decodes that succeeded */
+  bytes = 0;
+  /** Values decoded, inner values included */
+  values = 0;
+
+  reset(): void {
+    this.decodes = 0;
+    this.failures = 0;
+    this.bytes = 0;
+    this.values = 0;
+  }
+}
+
+/**
+ * State threaded through one decode: the options, and the path to the
+ * value being decoded for error locations
+ *
+ * Segments are pushed on the way down and popped on the way back up. A
+ * failure returns straight to the caller, so the path is never restored
+ * then, and each `tryDeserialize` call starts a fresh context.
+ */
+interface DecodeContext {
+  readonly options: DeserializeOptions;
+  readonly path: string[];
+  /** Values being decoded, from the outermost to the current one */
+  depth: number;
+}
+
+function pathOf(ctx: DecodeContext): string {
+  const path = ctx.path.join("");
+  return path.startsWith(".") ? path.slice(1) : path;
+}
+
+/**
+ * Fail to decode the value starting at `offset`
+ */
+function fail(
+  ctx: DecodeContext,
+  offset: number,
+  message: string,
+  code: ErrorCode = ErrorCode.InvalidValue,
+  mismatch?: { expected: string; found: string }
+): Result<never, DeserializeError> {
+  const path = pathOf(ctx);
+  return err(
+    new DeserializeError(
+      message,
+      code,
+      mismatch === undefined ? { offset, path } : { offset, path, mismatch }
+    )
+  );
+}
+
+function primitiveError(
+  kind: string,
+  error: PostcardDecodeError,
+  offset: number,
+  ctx: DecodeContext
+): Result<never, DeserializeError> {
+  const mismatch =
+    error.code === ErrorCode.UnexpectedEnd ? { expected: kind, found: "end of data" } : undefined;
+  return fail(ctx, offset, `Failed to deserialize ${kind}: ${error.message}`, error.code, mismatch);
+}
+
+/**
+ * Fail if `length` (a count of `unit`) is over `limit`
+ */
+function overLimit(
+  ctx: DecodeContext,
+  offset: number,
+  length: number,
+  limit: number | undefined,
+  unit: string
+): Result<never, DeserializeError> | undefined {
+  if (limit === undefined || length <= limit) {
+    return undefined;
+  }
+  return fail(
+    ctx,
+    offset,
+    `Length of ${String(length)} ${unit} exceeds the limit (${String(limit)})`,
+    ErrorCode.InvalidValue,
+    { expected: `at most ${String(limit)} ${unit}`, found: `${String(length)} ${unit}` }
+  );
+}
+
+/**
+ * Fail if a sequence or map holds more than its schema's `maxLength`, as a
+ * device decoding into a `heapless` container would
+ */
+function overMaxLength(
+  ctx: DecodeContext,
+  offset: number,
+  what: string,
+  length: number,
+  maxLength: number | undefined,
+  unit: string
+): Result<never, DeserializeError> | undefined {
+  if (maxLength === undefined || length <= maxLength) {
+    return undefined;
+  }
+  return fail(
+    ctx,
+    offset,
+    `${what} of ${String(length)} ${unit} exceeds maxLength (${String(maxLength)})`,
+    ErrorCode.InvalidValue,
+    { expected: `at most ${String(maxLength)} ${unit}`, found: `${String(length)} ${unit}` }
+  );
+}
+
+/**
+ * Fail if the length prefix at `offset` is over `limit`, before reading the
+ * bytes it counts; malformed prefixes are left to the decoder to report
+ */
+function prefixOverLimit(
+  ctx: DecodeContext,
+  data: Uint8Array,
+  offset: number,
+  limit: number | undefined
+): Result<never, DeserializeError> | undefined {
+  if (limit === undefined) {
+    return undefined;
+  }
+  const length = tryDecodeVarintU64(data, offset);
+  if (!length.ok) {
+    return undefined;
+  }
+  return overLimit(ctx, offset, Number(length.value.value), limit, "bytes");
+}
+
 /**
  * Deserialize data using a schema (Result API)
  *
  * The schema drives the deserialization process and provides type inference.
+ * Throws TypeError for an `encodeOnly()` schema.
  */
 export function tryDeserialize<S extends Schema>(
-  schema: S,
+  schema: S & { readonly direction?: "decode" },
   data: Uint8Array,
-  offset = 0
+  offset = 0,
+  options: DeserializeOptions = {}
 ): Result<DeserializeResult<InferType<S>>, DeserializeError> {
+  if (directionOf(schema) === "encode") {
+    throw new TypeError("Cannot deserialize with an encode-only schema");
+  }
+  if (options.freeze === true) {
+    const result = tryDeserialize(schema, data, offset, { ...options, freeze: false });
+    if (!result.ok) {
+      return result;
+    }
+    return ok({ value: deepFreeze(result.value.value), bytesRead: result.value.bytesRead });
+  }
+
   // Bounds check
   if (offset < 0 || offset > data.length) {
-    return err(new DeserializeError("Offset out of bounds"));
+    return err(new DeserializeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
+  const path = options.root === undefined ? [] : [options.root];
+  const result = decode(schema, data, offset, { options, path, depth: 0 });
+  const { stats } = options;
+  if (stats !== undefined) {
+    stats.decodes++;
+    if (result.ok) {
+      stats.bytes += result.value.bytesRead;
+    } else {
+      stats.failures++;
+    }
+  }
+  if (options.verify === true && result.ok && directionOf(schema) !== "decode") {
+    const mismatch = verifyReencoding(schema, data, offset, result.value, options.root);
+    if (mismatch !== undefined) {
+      return err(mismatch);
+    }
+  }
+  return result;
+}
+
+/**
+ * The ReencodeMismatchError for `decoded`, read from `data` at `offset`,
+ * unless it encodes to the bytes it was read from
+ */
+function verifyReencoding(
+  schema: Schema,
+  data: Uint8Array,
+  offset: number,
+  decoded: DeserializeResult<unknown>,
+  root: string | undefined
+): ReencodeMismatchError | undefined {
+  const expected = data.subarray(offset, offset + decoded.bytesRead);
+  const path = root ?? "";
+  const encoded = trySerialize(schema as Schema & { readonly direction?: "encode" }, decoded.value);
+  if (!encoded.ok) {
+    return new ReencodeMismatchError(
+      `Value read from byte ${String(offset)} cannot be re-encoded: ${encoded.error.message}`,
+      { offset, path },
+      expected,
+      undefined
+    );
+  }
+  const actual = encoded.value;
+  let index = 0;
+  while (index < expected.length && index < actual.length && expected[index] === actual[index]) {
+    index++;
+  }
+  if (index === expected.length && index === actual.length) {
+    return undefined;
+  }
+  const byte = (bytes: Uint8Array): string => {
+    const value = bytes[index];
+    return value === undefined ? "the end" : `0x${value.toString(16).padStart(2, "0")}`;
+  };
+  return new ReencodeMismatchError(
+    `Value read from byte ${String(offset)} re-encodes to other bytes: ` +
+      `${byte(actual)} instead of ${byte(expected)} ` +
+      `(${String(actual.length)} bytes instead of ${String(expected.length)})`,
+    { offset: offset + index, path, mismatch: { expected: byte(expected), found: byte(actual) } },
+    expected,
+    actual
+  );
+}
+
+function decode<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset: number,
+  ctx: DecodeContext
+): Result<DeserializeResult<InferType<S>>, DeserializeError> {
+  const { limits, trace, stats } = ctx.options;
+  ctx.depth++;
+  if (limits?.maxDepth !== undefined && ctx.depth > limits.maxDepth) {
+    const limit = String(limits.maxDepth);
+    return fail(
+      ctx,
+      offset,
+      `Values nested deeper than the limit (${limit})`,
+      ErrorCode.InvalidValue,
+      { expected: `at most ${limit} levels`, found: `${String(ctx.depth)} levels` }
+    );
+  }
+  const result = decodeValue(schema, data, offset, ctx);
+  if (!result.ok) {
+    return result;
+  }
+  ctx.depth--;
+  if (stats !== undefined) {
+    stats.values++;
+  }
+  if (trace !== undefined) {
+    trace({ kind: schema.kind, path: pathOf(ctx), offset, bytesRead: result.value.bytesRead });
+  }
+  return result;
+}
+
+function decodeValue<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset: number,
+  ctx: DecodeContext
+): Result<DeserializeResult<InferType<S>>, DeserializeError> {
   // Dispatch based on schema kind
   // TypeScript can't narrow InferType<S> based on schema.kind, so we use 'as any' for type safety
   // Each handler returns the correct type for its schema, but TS can't verify this statically
   /* eslint-disable @typescript-eslint/no-unsafe-return, @typescript-eslint/no-explicit-any */
   switch (schema.kind) {
     case "bool":
-      return deserializeBool(schema, data, offset) as any;
+      return deserializeBool(schema, data, offset, ctx) as any;
     case "i8":
-      return deserializeI8(schema, data, offset) as any;
+      return deserializeI8(schema, data, offset, ctx) as any;
     case "i16":
-      return deserializeI16(schema, data, offset) as any;
+      return deserializeI16(schema, data, offset, ctx) as any;
     case "i32":
-      return deserializeI32(schema, data, offset) as any;
+      return deserializeI32(schema, data, offset, ctx) as any;
     case "i64":
-      return deserializeI64(schema, data, offset) as any;
+      return deserializeI64(schema, data, offset, ctx) as any;
     case "i128":
-      return deserializeI128(schema, data, offset) as any;
+      return deserializeI128(schema, data, offset, ctx) as any;
     case "u8":
-      return deserializeU8(schema, data, offset) as any;
+      return deserializeU8(schema, data, offset, ctx) as any;
     case "u16":
-      return deserializeU16(schema, data, offset) as any;
+      return deserializeU16(schema, data, offset, ctx) as any;
     case "u32":
-      return deserializeU32(schema, data, offset) as any;
+      return deserializeU32(schema, data, offset, ctx) as any;
     case "u64":
-      return deserializeU64(schema, data, offset) as any;
+      return deserializeU64(schema, data, offset, ctx) as any;
     case "u128":
-      return deserializeU128(schema, data, offset) as any;
+      return deserializeU128(schema, data, offset, ctx) as any;
     case "f32":
-      return deserializeF32(schema, data, offset) as any;
+      return deserializeF32(schema, data, offset, ctx) as any;
     case "f64":
-      return deserializeF64(schema, data, offset) as any;
+      return deserializeF64(schema, data, offset, ctx) as any;
     case "char":
-      return deserializeChar(schema, data, offset) as any;
+      return deserializeChar(schema, data, offset, ctx) as any;
     case "string":
-      return deserializeString(schema, data, offset) as any;
+      return deserializeString(schema, data, offset, ctx) as any;
     case "bytes":
-      return deserializeBytes(schema, data, offset) as any;
+      return deserializeBytes(schema, data, offset, ctx) as any;
     case "option":
-      return deserializeOption(schema as OptionSchema<Schema>, data, offset) as any;
+      return deserializeOption(schema as OptionSchema<Schema>, data, offset, ctx) as any;
     case "unit":
       return deserializeUnit(schema, data, offset) as any;
     case "unit_struct":
       return deserializeUnitStruct(schema, data, offset) as any;
+    case "never":
+      return deserializeNever(schema, data, offset, ctx) as any;
     case "newtype_struct":
-      return deserializeNewtypeStruct(schema as NewtypeStructSchema<Schema>, data, offset) as any;
+      return deserializeNewtypeStruct(schema as NewtypeStructSchema<Schema>, data, offset, ctx) as any;
     case "seq":
-      return deserializeSeq(schema as SeqSchema<Schema>, data, offset) as any;
+      return deserializeSeq(schema as SeqSchema<Schema>, data, offset, ctx) as any;
     case "tuple":
-      return deserializeTuple(schema as TupleSchema<readonly Schema[]>, data, offset) as any;
+      return deserializeTuple(schema as TupleSchema<readonly Schema[]>, data, offset, ctx) as any;
     case "tuple_struct":
-      return deserializeTupleStruct(schema as TupleStructSchema<readonly Schema[]>, data, offset) as any;
+      return deserializeTupleStruct(schema as TupleStructSchema<readonly Schema[]>, data, offset, ctx) as any;
     case "map":
-      return deserializeMap(schema as MapSchema<Schema, Schema>, data, offset) as any;
+      return deserializeMap(schema as MapSchema<Schema, Schema>, data, offset, ctx) as any;
     case "struct":
-      return deserializeStruct(schema as StructSchema<Record<string, Schema>>, data, offset) as any;
+      return deserializeStruct(schema as StructSchema<Record<string, Schema>>, data, offset, ctx) as any;
     case "enum":
-      return deserializeEnum(schema as EnumSchema<Record<string, EnumVariant>>, data, offset) as any;
+      return deserializeEnum(schema as EnumSchema<Record<string, EnumVariant>>, data, offset, ctx) as any;
+    case "transform":
+      return deserializeTransform(schema as TransformSchema<Schema, unknown>, data, offset, ctx) as any;
+    case "lazy":
+      // Resolve the referenced schema and decode exactly as it would
+      return decodeValue(schema.get(), data, offset, ctx) as any;
+    case "external":
+      return deserializeExternal(schema as ExternalSchema<unknown>, data, offset, ctx) as any;
     default: {
       // Exhaustiveness check
       const _exhaustive: never = schema;
-      return err(new DeserializeError(`Unknown schema kind: ${(_exhaustive as Schema).kind}`));
+      return fail(ctx, offset, `Unknown schema kind: ${(_exhaustive as Schema).kind}`);
     }
   }
   /* eslint-enable @typescript-eslint/no-unsafe-return, @typescript-eslint/no-explicit-any */
@@ -165,11 +588,12 @@ export function tryDeserialize<S extends Schema>(
 function deserializeBool(
   _schema: BoolSchema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<boolean>, DeserializeError> {
   const result = tryDecodeBool(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize bool: ${result.error.message}`));
+    return primitiveError("bool", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -177,11 +601,12 @@ function deserializeBool(
 function deserializeI8(
   _schema: I8Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeI8(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize i8: ${result.error.message}`));
+    return primitiveError("i8", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -189,11 +614,12 @@ function deserializeI8(
 function deserializeI16(
   _schema: I16Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeVarintI16(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize i16: ${result.error.message}`));
+    return primitiveError("i16", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -201,11 +627,12 @@ function deserializeI16(
 function deserializeI32(
   _schema: I32Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeVarintI32(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize i32: ${result.error.message}`));
+    return primitiveError("i32", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -213,11 +640,12 @@ function deserializeI32(
 function deserializeI64(
   _schema: I64Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<bigint>, DeserializeError> {
   const result = tryDecodeVarintI64(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize i64: ${result.error.message}`));
+    return primitiveError("i64", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -225,11 +653,12 @@ function deserializeI64(
 function deserializeI128(
   _schema: I128Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<bigint>, DeserializeError> {
   const result = tryDecodeVarintI128(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize i128: ${result.error.message}`));
+    return primitiveError("i128", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -237,11 +666,12 @@ function deserializeI128(
 function deserializeU8(
   _schema: U8Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeU8(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize u8: ${result.error.message}`));
+    return primitiveError("u8", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -249,11 +679,12 @@ function deserializeU8(
 function deserializeU16(
   _schema: U16Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeVarintU16(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize u16: ${result.error.message}`));
+    return primitiveError("u16", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -261,11 +692,12 @@ function deserializeU16(
 function deserializeU32(
   _schema: U32Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeVarintU32(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize u32: ${result.error.message}`));
+    return primitiveError("u32", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -273,11 +705,12 @@ function deserializeU32(
 function deserializeU64(
   _schema: U64Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<bigint>, DeserializeError> {
   const result = tryDecodeVarintU64(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize u64: ${result.error.message}`));
+    return primitiveError("u64", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -285,11 +718,12 @@ function deserializeU64(
 function deserializeU128(
   _schema: U128Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<bigint>, DeserializeError> {
   const result = tryDecodeVarintU128(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize u128: ${result.error.message}`));
+    return primitiveError("u128", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -297,11 +731,12 @@ function deserializeU128(
 function deserializeF32(
   _schema: F32Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeF32(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize f32: ${result.error.message}`));
+    return primitiveError("f32", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -309,11 +744,12 @@ function deserializeF32(
 function deserializeF64(
   _schema: F64Schema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<number>, DeserializeError> {
   const result = tryDecodeF64(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize f64: ${result.error.message}`));
+    return primitiveError("f64", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -321,23 +757,45 @@ function deserializeF64(
 function deserializeChar(
   _schema: CharSchema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<string>, DeserializeError> {
   const result = tryDecodeChar(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize char: ${result.error.message}`));
+    return primitiveError("char", result.error, offset, ctx);
   }
   return ok(result.value);
 }
 
 function deserializeString(
-  _schema: StringSchema,
+  schema: StringSchema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<string>, DeserializeError> {
-  const result = tryDecodeString(data, offset);
+  const { intern, limits } = ctx.options;
+  const tooLong = prefixOverLimit(ctx, data, offset, limits?.maxBytes);
+  if (tooLong !== undefined) {
+    return tooLong;
+  }
+  const result =
+    intern === undefined ? tryDecodeString(data, offset) : intern.tryDecode(data, offset);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize string: ${result.error.message}`));
+    return primitiveError("string", result.error, offset, ctx);
+  }
+  // The length prefix takes at least one byte, so this bounds the UTF-8
+  if (schema.maxBytes !== undefined && result.value.bytesRead - 1 > schema.maxBytes) {
+    const length = new TextEncoder().encode(result.value.value).length;
+    if (length > schema.maxBytes) {
+      const limit = `at most ${String(schema.maxBytes)} bytes`;
+      return fail(
+        ctx,
+        offset,
+        `String of ${String(length)} bytes exceeds maxBytes (${String(schema.maxBytes)})`,
+        ErrorCode.InvalidValue,
+        { expected: limit, found: `${String(length)} bytes` }
+      );
+    }
   }
   return ok(result.value);
 }
@@ -345,11 +803,16 @@ function deserializeString(
 function deserializeBytes(
   _schema: BytesSchema,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<Uint8Array>, DeserializeError> {
-  const result = tryDecodeBytes(data, offset);
+  const tooLong = prefixOverLimit(ctx, data, offset, ctx.options.limits?.maxBytes);
+  if (tooLong !== undefined) {
+    return tooLong;
+  }
+  const result = tryDecodeBytes(data, offset, ctx.options.byteViews === true);
   if (!result.ok) {
-    return err(new DeserializeError(`Failed to deserialize bytes: ${result.error.message}`));
+    return primitiveError("bytes", result.error, offset, ctx);
   }
   return ok(result.value);
 }
@@ -361,16 +824,19 @@ function deserializeBytes(
 function deserializeOption<T extends Schema>(
   schema: OptionSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<InferType<T> | null>, DeserializeError> {
   // Read discriminant byte: 0x00 = None, 0x01 = Some
-  if (offset >= data.length) {
-    return err(new DeserializeError("Unexpected end of data while reading option discriminant"));
-  }
-
   const discriminant = data[offset];
   if (discriminant === undefined) {
-    return err(new DeserializeError("Unexpected end of data while reading option discriminant"));
+    return fail(
+      ctx,
+      offset,
+      "Unexpected end of data while reading option discriminant",
+      ErrorCode.UnexpectedEnd,
+      { expected: "option", found: "end of data" }
+    );
   }
 
   if (discriminant === 0x00) {
@@ -378,16 +844,20 @@ function deserializeOption<T extends Schema>(
     return ok({ value: null, bytesRead: 1 });
   } else if (discriminant === 0x01) {
     // Some - deserialize inner value
-    const innerResult = tryDeserialize(schema.inner, data, offset + 1);
+    const innerResult = decode(schema.inner, data, offset + 1, ctx);
     if (!innerResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize option value: ${innerResult.error.message}`));
+      return innerResult;
     }
     return ok({
       value: innerResult.value.value,
       bytesRead: 1 + innerResult.value.bytesRead,
     });
   } else {
-    return err(new DeserializeError(`Invalid option discriminant: 0x${discriminant.toString(16)}`));
+    const found = `0x${discriminant.toString(16)}`;
+    return fail(ctx, offset, `Invalid option discriminant: ${found}`, ErrorCode.BadOption, {
+      expected: "option (0x0 or 0x1)",
+      found,
+    });
   }
 }
 
@@ -413,6 +883,16 @@ function deserializeUnitStruct(
   return ok({ value: {} as Record<string, never>, bytesRead: 0 });
 }
 
+function deserializeNever(
+  _schema: NeverSchema,
+  _data: Uint8Array,
+  offset: number,
+  ctx: DecodeContext
+): Result<DeserializeResult<never>, DeserializeError> {
+  // No bytes can encode a value of an uninhabited type
+  return fail(ctx, offset, "Cannot deserialize a value of an uninhabited type");
+}
+
 // ============================================================================
 // NEWTYPE STRUCT HANDLER
 // ============================================================================
@@ -420,14 +900,11 @@ function deserializeUnitStruct(
 function deserializeNewtypeStruct<T extends Schema>(
   schema: NewtypeStructSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<InferType<T>>, DeserializeError> {
   // Newtype struct is just the inner value, no wrapper
-  const innerResult = tryDeserialize(schema.inner, data, offset);
-  if (!innerResult.ok) {
-    return err(new DeserializeError(`Failed to deserialize newtype struct: ${innerResult.error.message}`));
-  }
-  return ok(innerResult.value);
+  return decode(schema.inner, data, offset, ctx);
 }
 
 // ============================================================================
@@ -437,24 +914,33 @@ function deserializeNewtypeStruct<T extends Schema>(
 function deserializeSeq<T extends Schema>(
   schema: SeqSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<InferType<T>[]>, DeserializeError> {
   // Read length as varint(usize)
   const lengthResult = tryDecodeVarintU64(data, offset);
   if (!lengthResult.ok) {
-    return err(new DeserializeError(`Failed to deserialize seq length: ${lengthResult.error.message}`));
+    return primitiveError("seq length", lengthResult.error, offset, ctx);
   }
 
   const length = Number(lengthResult.value.value);
+  const tooLong =
+    overLimit(ctx, offset, length, ctx.options.limits?.maxLength, "items") ??
+    overMaxLength(ctx, offset, "Seq", length, schema.maxLength, "items");
+  if (tooLong !== undefined) {
+    return tooLong;
+  }
   let currentOffset = offset + lengthResult.value.bytesRead;
   const items: InferType<T>[] = [];
 
   // Deserialize each element
   for (let i = 0; i < length; i++) {
-    const itemResult = tryDeserialize(schema.item, data, currentOffset);
+    ctx.path.push(`[${String(i)}]`);
+    const itemResult = decode(schema.item, data, currentOffset, ctx);
     if (!itemResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize seq item ${String(i)}: ${itemResult.error.message}`));
+      return itemResult;
     }
+    ctx.path.pop();
     items.push(itemResult.value.value);
     currentOffset += itemResult.value.bytesRead;
   }
@@ -472,7 +958,8 @@ function deserializeSeq<T extends Schema>(
 function deserializeTuple<T extends readonly Schema[]>(
   schema: TupleSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<{ [K in keyof T]: T[K] extends Schema ? InferType<T[K]> : never }>, DeserializeError> {
   let currentOffset = offset;
   const items: unknown[] = [];
@@ -481,13 +968,15 @@ function deserializeTuple<T extends readonly Schema[]>(
   for (let i = 0; i < schema.items.length; i++) {
     const itemSchema = schema.items[i];
     if (itemSchema === undefined) {
-      return err(new DeserializeError(`Tuple item ${String(i)} schema is undefined`));
+      return fail(ctx, currentOffset, `Tuple item ${String(i)} schema is undefined`);
     }
 
-    const itemResult = tryDeserialize(itemSchema, data, currentOffset);
+    ctx.path.push(`.${String(i)}`);
+    const itemResult = decode(itemSchema, data, currentOffset, ctx);
     if (!itemResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize tuple item ${String(i)}: ${itemResult.error.message}`));
+      return itemResult;
     }
+    ctx.path.pop();
     items.push(itemResult.value.value);
     currentOffset += itemResult.value.bytesRead;
   }
@@ -505,7 +994,8 @@ function deserializeTuple<T extends readonly Schema[]>(
 function deserializeTupleStruct<T extends readonly Schema[]>(
   schema: TupleStructSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<{ [K in keyof T]: T[K] extends Schema ? InferType<T[K]> : never }>, DeserializeError> {
   // Tuple struct is encoded as a tuple
   let currentOffset = offset;
@@ -514,13 +1004,15 @@ function deserializeTupleStruct<T extends readonly Schema[]>(
   for (let i = 0; i < schema.items.length; i++) {
     const itemSchema = schema.items[i];
     if (itemSchema === undefined) {
-      return err(new DeserializeError(`Tuple struct item ${String(i)} schema is undefined`));
+      return fail(ctx, currentOffset, `Tuple struct item ${String(i)} schema is undefined`);
     }
 
-    const itemResult = tryDeserialize(itemSchema, data, currentOffset);
+    ctx.path.push(`.${String(i)}`);
+    const itemResult = decode(itemSchema, data, currentOffset, ctx);
     if (!itemResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize tuple struct item ${String(i)}: ${itemResult.error.message}`));
+      return itemResult;
     }
+    ctx.path.pop();
     items.push(itemResult.value.value);
     currentOffset += itemResult.value.bytesRead;
   }
@@ -538,35 +1030,67 @@ function deserializeTupleStruct<T extends readonly Schema[]>(
 function deserializeMap<K extends Schema, V extends Schema>(
   schema: MapSchema<K, V>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<Map<InferType<K>, InferType<V>>>, DeserializeError> {
   // Read entry count as varint(usize)
   const countResult = tryDecodeVarintU64(data, offset);
   if (!countResult.ok) {
-    return err(new DeserializeError(`Failed to deserialize map count: ${countResult.error.message}`));
+    return primitiveError("map count", countResult.error, offset, ctx);
   }
 
   const count = Number(countResult.value.value);
+  const tooLong =
+    overLimit(ctx, offset, count, ctx.options.limits?.maxLength, "entries") ??
+    overMaxLength(ctx, offset, "Map", count, schema.maxLength, "entries");
+  if (tooLong !== undefined) {
+    return tooLong;
+  }
   let currentOffset = offset + countResult.value.bytesRead;
   const map = new Map<InferType<K>, InferType<V>>();
+  const policy = ctx.options.duplicateKeys ?? "last";
+  // Object keys (structs, tuples, enums) are only equal by reference in a
+  // Map, so repeats are found by their encoded bytes instead, as canonicalKey() does
+  const objectKeys = new Map<string, InferType<K>>();
 
   // Deserialize each key-value pair
   for (let i = 0; i < count; i++) {
     // Deserialize key
-    const keyResult = tryDeserialize(schema.key, data, currentOffset);
+    const keyOffset = currentOffset;
+    ctx.path.push(`[${String(i)}].key`);
+    const keyResult = decode(schema.key, data, currentOffset, ctx);
     if (!keyResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize map key ${String(i)}: ${keyResult.error.message}`));
+      return keyResult;
+    }
+    ctx.path.pop();
+    let key = keyResult.value.value;
+    if (typeof key === "object" && key !== null) {
+      const encoded = encodedKey(data.subarray(currentOffset, currentOffset + keyResult.value.bytesRead));
+      key = objectKeys.get(encoded) ?? key;
+      objectKeys.set(encoded, key);
     }
     currentOffset += keyResult.value.bytesRead;
 
     // Deserialize value
-    const valueResult = tryDeserialize(schema.value, data, currentOffset);
+    ctx.path.push(`[${String(i)}].value`);
+    const valueResult = decode(schema.value, data, currentOffset, ctx);
     if (!valueResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize map value ${String(i)}: ${valueResult.error.message}`));
+      return valueResult;
     }
+    ctx.path.pop();
     currentOffset += valueResult.value.bytesRead;
 
-    map.set(keyResult.value.value, valueResult.value.value);
+    if (map.has(key)) {
+      if (policy === "error") {
+        ctx.path.push(`[${String(i)}].key`);
+        return fail(ctx, keyOffset, `Duplicate map key at entry ${String(i)}`);
+      }
+      if (policy === "first") {
+        continue;
+      }
+    }
+    // Like Rust's insert, a repeated key keeps its first key and position
+    map.set(key, valueResult.value.value);
   }
 
   return ok({
@@ -582,17 +1106,20 @@ function deserializeMap<K extends Schema, V extends Schema>(
 function deserializeStruct<T extends Record<string, Schema>>(
   schema: StructSchema<T>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<{ [K in keyof T]: InferType<T[K]> }>, DeserializeError> {
   let currentOffset = offset;
   const obj: Record<string, unknown> = {};
 
   // Deserialize each field in order
   for (const [fieldName, fieldSchema] of Object.entries(schema.fields)) {
-    const fieldResult = tryDeserialize(fieldSchema, data, currentOffset);
+    ctx.path.push(`.${fieldName}`);
+    const fieldResult = decode(fieldSchema, data, currentOffset, ctx);
     if (!fieldResult.ok) {
-      return err(new DeserializeError(`Failed to deserialize struct field '${fieldName}': ${fieldResult.error.message}`));
+      return fieldResult;
     }
+    ctx.path.pop();
     obj[fieldName] = fieldResult.value.value;
     currentOffset += fieldResult.value.bytesRead;
   }
@@ -610,7 +1137,8 @@ function deserializeStruct<T extends Record<string, Schema>>(
 function deserializeEnum<V extends Record<string, EnumVariant>>(
   schema: EnumSchema<V>,
   data: Uint8Array,
-  offset: number
+  offset: number,
+  ctx: DecodeContext
 ): Result<DeserializeResult<{
   [K in keyof V]: V[K] extends UnitVariantSchema
     ? { type: K }
@@ -625,7 +1153,7 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
   // Read variant discriminant as varint(u32)
   const discriminantResult = tryDecodeVarintU32(data, offset);
   if (!discriminantResult.ok) {
-    return err(new DeserializeError(`Failed to deserialize enum discriminant: ${discriminantResult.error.message}`));
+    return primitiveError(`${schema.name} discriminant`, discriminantResult.error, offset, ctx);
   }
 
   const discriminant = discriminantResult.value.value;
@@ -635,12 +1163,19 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
   const variantNames = Object.keys(schema.variants);
   const variantName = variantNames[discriminant];
   if (variantName === undefined) {
-    return err(new DeserializeError(`Invalid enum discriminant: ${String(discriminant)} (max: ${String(variantNames.length - 1)})`));
+    const max = String(variantNames.length - 1);
+    return fail(
+      ctx,
+      offset,
+      `Invalid enum discriminant for ${schema.name}: ${String(discriminant)} (max: ${max})`,
+      ErrorCode.InvalidValue,
+      { expected: `${schema.name} variant 0..=${max}`, found: String(discriminant) }
+    );
   }
 
   const variant = schema.variants[variantName];
   if (variant === undefined) {
-    return err(new DeserializeError(`Variant '${variantName}' not found in schema`));
+    return fail(ctx, offset, `Variant '${variantName}' not found in schema`);
   }
 
   // Deserialize variant based on its kind
@@ -665,10 +1200,12 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
 
     case "newtype_variant": {
       // Newtype variant contains a single value
-      const valueResult = tryDeserialize(variant.inner, data, currentOffset);
+      ctx.path.push(`.${variantName}`);
+      const valueResult = decode(variant.inner, data, currentOffset, ctx);
       if (!valueResult.ok) {
-        return err(new DeserializeError(`Failed to deserialize newtype variant value: ${valueResult.error.message}`));
+        return valueResult;
       }
+      ctx.path.pop();
       currentOffset += valueResult.value.bytesRead;
 
       return ok({
@@ -696,13 +1233,15 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
       for (let i = 0; i < variant.items.length; i++) {
         const itemSchema = variant.items[i];
         if (itemSchema === undefined) {
-          return err(new DeserializeError(`Tuple variant item ${String(i)} schema is undefined`));
+          return fail(ctx, currentOffset, `Tuple variant item ${String(i)} schema is undefined`);
         }
 
-        const itemResult = tryDeserialize(itemSchema, data, currentOffset);
+        ctx.path.push(`.${variantName}.${String(i)}`);
+        const itemResult = decode(itemSchema, data, currentOffset, ctx);
         if (!itemResult.ok) {
-          return err(new DeserializeError(`Failed to deserialize tuple variant item ${String(i)}: ${itemResult.error.message}`));
+          return itemResult;
         }
+        ctx.path.pop();
         items.push(itemResult.value.value);
         currentOffset += itemResult.value.bytesRead;
       }
@@ -727,10 +1266,12 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
       // Struct variant contains named fields
       const obj: Record<string, unknown> = {};
       for (const [fieldName, fieldSchema] of Object.entries(variant.fields)) {
-        const fieldResult = tryDeserialize(fieldSchema, data, currentOffset);
+        ctx.path.push(`.${variantName}.${fieldName}`);
+        const fieldResult = decode(fieldSchema, data, currentOffset, ctx);
         if (!fieldResult.ok) {
-          return err(new DeserializeError(`Failed to deserialize struct variant field '${fieldName}': ${fieldResult.error.message}`));
+          return fieldResult;
         }
+        ctx.path.pop();
         obj[fieldName] = fieldResult.value.value;
         currentOffset += fieldResult.value.bytesRead;
       }
@@ -753,11 +1294,86 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
 
     default: {
       const _exhaustive: never = variant;
-      return err(new DeserializeError(`Unknown variant kind: ${(_exhaustive as EnumVariant).kind}`));
+      return fail(ctx, offset, `Unknown variant kind: ${(_exhaustive as EnumVariant).kind}`);
     }
   }
 }
 
+// ============================================================================
+// TRANSFORM HANDLER
+// ============================================================================
+
+function deserializeTransform<S extends Schema, T>(
+  schema: TransformSchema<S, T>,
+  data: Uint8Array,
+  offset: number,
+  ctx: DecodeContext
+): Result<DeserializeResult<T>, DeserializeError> {
+  // The wire format is entirely the inner schema's
+  const innerResult = decode(schema.inner, data, offset, ctx);
+  if (!innerResult.ok) {
+    return innerResult;
+  }
+
+  try {
+    return ok({
+      value: schema.decode(innerResult.value.value),
+      bytesRead: innerResult.value.bytesRead,
+    });
+  } catch (e) {
+    const reason = e instanceof Error ? e.message : String(e);
+    return fail(ctx, offset, `Failed to convert decoded value: ${reason}`);
+  }
+}
+
+// ============================================================================
+// EXTERNAL HANDLER
+// ============================================================================
+
+function deserializeExternal<T>(
+  schema: ExternalSchema<T>,
+  data: Uint8Array,
+  offset: number,
+  ctx: DecodeContext
+): Result<DeserializeResult<T>, DeserializeError> {
+  let decoded: { readonly value: T; readonly bytesRead: number };
+  try {
+    decoded = schema.decode(data.subarray(offset));
+  } catch (e) {
+    const reason = e instanceof Error ? e.message : String(e);
+    return fail(ctx, offset, `Failed to decode ${schema.name}: ${reason}`);
+  }
+
+  // The codec's count is trusted no further than the bytes it was given
+  const { value, bytesRead } = decoded;
+  const available = data.length - offset;
+  if (!(Number.isSafeInteger(bytesRead) && bytesRead >= 0 && bytesRead <= available)) {
+    return fail(
+      ctx,
+      offset,
+      `${schema.name} codec read ${String(bytesRead)} bytes of ${String(available)}`
+    );
+  }
+  return ok({ value, bytesRead });
+}
+
+/**
+ * Deserialize a value, without the number of bytes it took (Result API)
+ *
+ * The decoder generated per type by `Generator::decoders`
+ * (`tryDecodeReading`) calls this, for code that handles failures as values
+ * rather than exceptions.
+ */
+export function tryDeserializeValue<S extends Schema>(
+  schema: S & { readonly direction?: "decode" },
+  data: Uint8Array,
+  offset?: number,
+  options?: DeserializeOptions
+): Result<InferType<S>, DeserializeError> {
+  const result = tryDeserialize(schema, data, offset, options);
+  return result.ok ? ok(result.value.value) : result;
+}
+
 // ============================================================================
 // THROWING WRAPPER
 // ============================================================================
@@ -768,9 +1384,24 @@ function deserializeEnum<V extends Record<string, EnumVariant>>(
  * Throws DeserializeError on failure
  */
 export function deserialize<S extends Schema>(
-  schema: S,
+  schema: S & { readonly direction?: "decode" },
   data: Uint8Array,
-  offset?: number
+  offset?: number,
+  options?: DeserializeOptions
 ): DeserializeResult<InferType<S>> {
-  return unwrap(tryDeserialize(schema, data, offset));
+  return unwrap(tryDeserialize(schema, data, offset, options));
+}
+
+/**
+ * Deserialize a value, without the number of bytes it took (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function deserializeValue<S extends Schema>(
+  schema: S & { readonly direction?: "decode" },
+  data: Uint8Array,
+  offset?: number,
+  options?: DeserializeOptions
+): InferType<S> {
+  return unwrap(tryDeserializeValue(schema, data, offset, options));
 }
@@ -0,0 +1,15 @@
+/**
+ * Schemas usable both ways again, for code that encodes and decodes a type
+ * whichever way it goes between the UI and the device: the DeviceSimulator
+ * playing the device, redaction of captures, and `canonicalKey()`, which
+ * encodes keys only to compare them
+ *
+ * Internal: the static type still carries the direction, which the generic
+ * callers do not look at.
+ */
+
+import { type Schema, directionOf } from "../types/schema.js";
+
+export function withoutDirection<S extends Schema>(schema: S): S {
+  return directionOf(schema) === undefined ? schema : { ...schema, direction: undefined };
+}
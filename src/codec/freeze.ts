@@ -0,0 +1,49 @@
+/**
+ * Recursive freezing of decoded values
+ *
+ * Decoded values are often shared between consumers (a dashboard store, several
+ * components). Freezing them makes accidental mutation throw immediately in
+ * strict-mode code instead of silently corrupting shared state.
+ */
+
+const MAP_MUTATORS = ["set", "delete", "clear"] as const;
+
+/**
+ * Freeze a decoded value and everything reachable from it
+ *
+ * Arrays and plain objects are frozen with Object.freeze. Maps are frozen and
+ * their set/delete/clear methods throw a TypeError. Typed arrays (bytes) and
+ * other class instances such as Date are left as they are, since freezing
+ * cannot make them immutable.
+ */
+export function deepFreeze<T>(value: T): T {
+  if (typeof value !== "object" || value === null || Object.isFrozen(value)) {
+    return value;
+  }
+
+  if (value instanceof Map) {
+    for (const [key, entry] of value as Map<unknown, unknown>) {
+      deepFreeze(key);
+      deepFreeze(entry);
+    }
+    for (const method of MAP_MUTATORS) {
+      Object.defineProperty(value, method, {
+        value: () => {
+          throw new TypeError(`Cannot ${method} on a frozen Map`);
+        },
+      });
+    }
+    Object.freeze(value);
+    return value;
+  }
+
+  if (!Array.isArray(value) && Object.getPrototypeOf(value) !== Object.prototype) {
+    return value;
+  }
+
+  for (const entry of Object.values(value)) {
+    deepFreeze(entry);
+  }
+  Object.freeze(value);
+  return value;
+}
@@ -0,0 +1,138 @@
+/**
+ * Interning of repeated strings during decode
+ *
+ * Telemetry repeats the same few strings in every frame: sensor names, units,
+ * status labels. A StringInterner passed as the `intern` option of
+ * `deserialize` remembers the strings it decoded and, when the same bytes come
+ * again, returns the string decoded then without running the UTF-8 decoder,
+ * so equal strings share one instance across frames:
+ *
+ *   const names = new StringInterner();
+ *   const reading = deserialize(ReadingSchema, frame, 0, { intern: names }).value;
+ *
+ * Only short strings are kept (`maxBytes`), and once `maxEntries` are held
+ * new strings are decoded as usual rather than evicting old ones, so a stream
+ * of unique strings cannot churn the table.
+ */
+
+import { type Result, ok } from "../types/result.js";
+import {
+  type StringDecodeError,
+  type StringDecodeResult,
+  tryDecodeString,
+} from "../primitives/string.js";
+import { tryDecodeVarintU64 } from "./varint.js";
+import { fnv1a, sameBytes } from "./byte-keys.js";
+
+export interface StringInternerOptions {
+  /** Most strings kept; later ones are not interned (default: 4096) */
+  readonly maxEntries?: number;
+  /** Longest string kept, in UTF-8 bytes (default: 64) */
+  readonly maxBytes?: number;
+}
+
+/**
+ * How often a StringInterner found a string, since it was created or cleared
+ */
+export interface StringInternerStats {
+  /** Strings answered from the table */
+  readonly hits: number;
+  /** Strings decoded, whether or not they were then kept */
+  readonly misses: number;
+  /** Strings held now */
+  readonly size: number;
+}
+
+interface Entry {
+  readonly bytes: Uint8Array;
+  readonly value: string;
+}
+
+export class StringInterner {
+  private readonly maxEntries: number;
+  private readonly maxBytes: number;
+  // Entries by hash of their UTF-8, more than one when hashes collide
+  private readonly buckets = new Map<number, Entry[]>();
+  private size = 0;
+  private hits = 0;
+  private misses = 0;
+
+  /**
+   * Throws RangeError if `maxEntries` or `maxBytes` is not a non-negative
+   * integer
+   */
+  constructor(options: StringInternerOptions = {}) {
+    const { maxEntries = 4096, maxBytes = 64 } = options;
+    if (!Number.isInteger(maxEntries) || maxEntries < 0) {
+      throw new RangeError(
+        `maxEntries must be a non-negative integer, got ${String(maxEntries)}`
+      );
+    }
+    if (!Number.isInteger(maxBytes) || maxBytes < 0) {
+      throw new RangeError(`maxBytes must be a non-negative integer, got ${String(maxBytes)}`);
+    }
+    this.maxEntries = maxEntries;
+    this.maxBytes = maxBytes;
+  }
+
+  get stats(): StringInternerStats {
+    return { hits: this.hits, misses: this.misses, size: this.size };
+  }
+
+  /**
+   * Decode the string at `offset` as `tryDecodeString` does, returning the
+   * instance decoded before from the same bytes if there is one (Result API)
+   */
+  tryDecode(data: Uint8Array, offset = 0): Result<StringDecodeResult, StringDecodeError> {
+    const length = tryDecodeVarintU64(data, offset);
+    if (!length.ok || length.value.value > BigInt(this.maxBytes)) {
+      return this.decodeFresh(data, offset);
+    }
+    const start = offset + length.value.bytesRead;
+    const end = start + Number(length.value.value);
+    if (end > data.length) {
+      // Reported by tryDecodeString
+      return this.decodeFresh(data, offset);
+    }
+
+    const utf8 = data.subarray(start, end);
+    const hash = fnv1a(utf8);
+    const bucket = this.buckets.get(hash);
+    const interned = bucket?.find((entry) => sameBytes(entry.bytes, utf8));
+    if (interned !== undefined) {
+      this.hits++;
+      return ok({ value: interned.value, bytesRead: end - offset });
+    }
+
+    const result = this.decodeFresh(data, offset);
+    if (!result.ok || this.size >= this.maxEntries) {
+      return result;
+    }
+    const entry = { bytes: utf8.slice(), value: result.value.value };
+    if (bucket === undefined) {
+      this.buckets.set(hash, [entry]);
+    } else {
+      bucket.push(entry);
+    }
+    this.size++;
+    return result;
+  }
+
+  /**
+   * Drop every interned string and start counting from zero
+   */
+  clear(): void {
+    this.buckets.clear();
+    this.size = 0;
+    this.hits = 0;
+    this.misses = 0;
+  }
+
+  private decodeFresh(
+    data: Uint8Array,
+    offset: number
+  ): Result<StringDecodeResult, StringDecodeError> {
+    this.misses++;
+    return tryDecodeString(data, offset);
+  }
+}
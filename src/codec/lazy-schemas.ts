@@ -0,0 +1,143 @@
+/**
+ * Schemas loaded on first use
+ *
+ * A protocol of thousands of types makes a generated index that is slow to
+ * parse and evaluate, though a page usually decodes a handful of them.
+ * Generated with `Generator::schema_loaders()` (`--schema-loaders`),
+ * `loaders.ts` maps each type to a dynamic import of the module declaring
+ * it, so that bundlers split every module (or protocol group) into a chunk
+ * of its own. LazySchemas loads a chunk the first time one of its types is
+ * used:
+ *
+ *   import { SCHEMA_LOADERS } from "./generated/loaders.js";
+ *   const schemas = new LazySchemas(SCHEMA_LOADERS);
+ *   const { value } = await schemas.decode("Reading", bytes);
+ *
+ * A worker can register the types it decodes once they are loaded:
+ *
+ *   exposeDecoders(self, await schemas.load("Reading", "Telemetry"));
+ *
+ * Each type is loaded once and kept; a load that fails (say, a chunk the
+ * network dropped) is forgotten, so that the next use tries again.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import type { Result } from "../types/result.js";
+import {
+  deserialize,
+  tryDeserialize,
+  type DeserializeError,
+  type DeserializeOptions,
+  type DeserializeResult,
+} from "./deserializer.js";
+
+/**
+ * Loads the schema of a type, usually by importing the module declaring it
+ */
+export type SchemaLoader = () => Promise<Schema> | Schema;
+
+/**
+ * The loaders of a protocol's types, by TypeScript type name
+ */
+export type SchemaLoaders = Readonly<Record<string, SchemaLoader>>;
+
+/**
+ * The schema `L[K]` loads
+ */
+export type LoadedSchema<L extends SchemaLoaders, K extends keyof L> = Extract<
+  Awaited<ReturnType<L[K]>>,
+  Schema
+>;
+
+export class LazySchemas<L extends SchemaLoaders> {
+  private readonly loading = new Map<string, Promise<Schema>>();
+  private readonly loaded = new Map<string, Schema>();
+
+  constructor(private readonly loaders: L) {}
+
+  /**
+   * The schema of `type`, loading it on first use
+   *
+   * Throws RangeError for a type without a loader.
+   */
+  get<K extends keyof L & string>(type: K): Promise<LoadedSchema<L, K>> {
+    const loaded = this.loaded.get(type);
+    if (loaded !== undefined) {
+      return Promise.resolve(loaded as LoadedSchema<L, K>);
+    }
+    let loading = this.loading.get(type);
+    if (loading === undefined) {
+      const loader: SchemaLoader | undefined = Object.prototype.hasOwnProperty.call(
+        this.loaders,
+        type
+      )
+        ? this.loaders[type]
+        : undefined;
+      if (loader === undefined) {
+        throw new RangeError(`No schema loader for type "${type}"`);
+      }
+      loading = Promise.resolve()
+        .then(loader)
+        .then(
+          (schema) => {
+            this.loaded.set(type, schema);
+            this.loading.delete(type);
+            return schema;
+          },
+          (error: unknown) => {
+            this.loading.delete(type);
+            throw error;
+          }
+        );
+      this.loading.set(type, loading);
+    }
+    return loading as Promise<LoadedSchema<L, K>>;
+  }
+
+  /**
+   * The schema of `type` if it is loaded already, without loading it
+   */
+  peek<K extends keyof L & string>(type: K): LoadedSchema<L, K> | undefined {
+    return this.loaded.get(type) as LoadedSchema<L, K> | undefined;
+  }
+
+  /**
+   * Load `types` at once, by name, e.g. for `exposeDecoders()`
+   */
+  async load<K extends keyof L & string>(
+    ...types: K[]
+  ): Promise<{ readonly [T in K]: LoadedSchema<L, T> }> {
+    const schemas = await Promise.all(types.map((type) => this.get(type)));
+    return Object.fromEntries(types.map((type, i) => [type, schemas[i]])) as {
+      readonly [T in K]: LoadedSchema<L, T>;
+    };
+  }
+
+  /**
+   * Decode `data` as `type`, loading its schema on first use (Result API)
+   *
+   * Rejects only if the schema fails to load.
+   */
+  async tryDecode<K extends keyof L & string>(
+    type: K,
+    data: Uint8Array,
+    offset = 0,
+    options?: DeserializeOptions
+  ): Promise<Result<DeserializeResult<InferType<LoadedSchema<L, K>>>, DeserializeError>> {
+    return tryDeserialize(await this.get(type), data, offset, options);
+  }
+
+  /**
+   * Decode `data` as `type`, loading its schema on first use (throwing API)
+   *
+   * Rejects with DeserializeError on failure, or if the schema fails to load.
+   */
+  async decode<K extends keyof L & string>(
+    type: K,
+    data: Uint8Array,
+    offset = 0,
+    options?: DeserializeOptions
+  ): Promise<DeserializeResult<InferType<LoadedSchema<L, K>>>> {
+    return deserialize(await this.get(type), data, offset, options);
+  }
+}
@@ -0,0 +1,325 @@
+/**
+ * Length-prefix framing: each message as a varint of its length, then its bytes
+ *
+ * The framing of links that already deliver bytes reliably and in order (TCP,
+ * WebSockets carrying several messages per frame, a log file), where COBS
+ * stuffing is not worth its overhead. The prefix is a postcard `usize`, so a
+ * framed message is exactly how postcard writes a `Vec<u8>` of its bytes:
+ *
+ *   let frame = postcard::to_allocvec(&postcard::to_allocvec(&message)?)?;
+ *
+ * Unlike COBS there is no delimiter to resynchronize on: a frame announcing
+ * more than `maxFrameLength` bytes is skipped by its length, but a malformed
+ * prefix leaves nothing to go by, so `LengthPrefixAccumulator` stops decoding
+ * until reset.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import type { Schema, InferType } from "../types/schema.js";
+import { type SerializeError, type SerializeOptions, trySerialize } from "./serializer.js";
+import {
+  type DeserializeResult,
+  type DeserializeOptions,
+  type DeserializeError,
+  tryDeserialize,
+} from "./deserializer.js";
+import { FramingError } from "./cobs.js";
+import { encodeVarintU32, tryDecodeVarintU32 } from "./varint.js";
+
+/**
+ * Prefix a payload with its length
+ */
+export function lengthPrefixEncode(payload: Uint8Array): Uint8Array {
+  const { bytes } = encodeVarintU32(payload.length);
+  const frame = new Uint8Array(bytes.length + payload.length);
+  frame.set(bytes, 0);
+  frame.set(payload, bytes.length);
+  return frame;
+}
+
+/**
+ * The payload of the length-prefixed frame starting at `offset` (Result API)
+ *
+ * `bytesRead` includes the prefix. Fails with code UNEXPECTED_END if `data`
+ * ends within the frame, or BAD_VARINT for a malformed prefix.
+ */
+export function tryLengthPrefixDecode(
+  data: Uint8Array,
+  offset = 0
+): Result<DeserializeResult<Uint8Array>, DeserializeError> {
+  const prefix = tryDecodeVarintU32(data, offset);
+  if (!prefix.ok) {
+    return err(
+      new FramingError(
+        `Length prefix at offset ${String(offset)}: ${prefix.error.message}`,
+        prefix.error.code
+      )
+    );
+  }
+  const start = offset + prefix.value.bytesRead;
+  const end = start + prefix.value.value;
+  if (end > data.length) {
+    return err(
+      new FramingError(
+        `Frame at offset ${String(offset)} announces ${String(prefix.value.value)} bytes, ` +
+          `but only ${String(data.length - start)} follow`,
+        ErrorCode.UnexpectedEnd
+      )
+    );
+  }
+  return ok({ value: data.subarray(start, end), bytesRead: end - offset });
+}
+
+/**
+ * The payload of the length-prefixed frame starting at `offset` (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function lengthPrefixDecode(
+  data: Uint8Array,
+  offset?: number
+): DeserializeResult<Uint8Array> {
+  return unwrap(tryLengthPrefixDecode(data, offset));
+}
+
+/**
+ * Serialize a value into a length-prefixed frame (Result API)
+ */
+export function trySerializeLengthPrefixed<S extends Schema>(
+  schema: S,
+  value: unknown,
+  options?: SerializeOptions
+): Result<Uint8Array, SerializeError> {
+  const result = trySerialize(schema, value, options);
+  return result.ok ? ok(lengthPrefixEncode(result.value)) : result;
+}
+
+/**
+ * Serialize a value into a length-prefixed frame (throwing API)
+ *
+ * Throws SerializeError on failure
+ */
+export function serializeLengthPrefixed<S extends Schema>(
+  schema: S,
+  value: unknown,
+  options?: SerializeOptions
+): Uint8Array {
+  return unwrap(trySerializeLengthPrefixed(schema, value, options));
+}
+
+/**
+ * Deserialize the value in the length-prefixed frame starting at `offset`
+ * (Result API)
+ *
+ * `bytesRead` counts frame bytes, prefix included, so frames can be decoded
+ * back to back. Payload bytes after the value are ignored.
+ */
+export function tryDeserializeLengthPrefixed<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset = 0,
+  options: DeserializeOptions = {}
+): Result<DeserializeResult<InferType<S>>, DeserializeError> {
+  const frame = tryLengthPrefixDecode(data, offset);
+  if (!frame.ok) {
+    return frame;
+  }
+  const result = tryDeserialize(schema, frame.value.value, 0, options);
+  if (!result.ok) {
+    return result;
+  }
+  return ok({ value: result.value.value, bytesRead: frame.value.bytesRead });
+}
+
+/**
+ * Deserialize the value in the length-prefixed frame starting at `offset`
+ * (throwing API)
+ *
+ * Throws DeserializeError on failure
+ */
+export function deserializeLengthPrefixed<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset?: number,
+  options?: DeserializeOptions
+): DeserializeResult<InferType<S>> {
+  return unwrap(tryDeserializeLengthPrefixed(schema, data, offset, options));
+}
+
+export interface LengthPrefixAccumulatorOptions extends DeserializeOptions {
+  /**
+   * Longest payload accepted, prefix excluded (default: unbounded). Longer
+   * frames fail with code BUFFER_FULL and are skipped without being buffered.
+   */
+  readonly maxFrameLength?: number;
+}
+
+/**
+ * Decodes length-prefixed values from a stream delivered in chunks
+ *
+ *   const frames = new LengthPrefixAccumulator(GameEventSchema, { maxFrameLength: 4096 });
+ *   socket.onmessage = (event) => {
+ *     frames.push(new Uint8Array(event.data));
+ *     for (const message of frames) render(message);
+ *   };
+ *
+ * A payload that does not decode, or is too long, only costs that frame. A
+ * malformed prefix fails every call until `reset()`, as the next frame's start
+ * is unknown.
+ */
+export class LengthPrefixAccumulator<S extends Schema> {
+  readonly schema: S;
+  readonly maxFrameLength: number;
+
+  private readonly options: DeserializeOptions;
+  // Unconsumed bytes are buffer[start..end]
+  private buffer = new Uint8Array(0);
+  private start = 0;
+  private end = 0;
+  // Payload bytes of an oversized frame still to be dropped as they arrive
+  private skipping = 0;
+  private failed: DeserializeError | undefined;
+
+  constructor(schema: S, options: LengthPrefixAccumulatorOptions = {}) {
+    const { maxFrameLength = Infinity, ...deserializeOptions } = options;
+    if (!(maxFrameLength >= 0)) {
+      throw new RangeError(`maxFrameLength must not be negative, got ${String(maxFrameLength)}`);
+    }
+    this.schema = schema;
+    this.maxFrameLength = maxFrameLength;
+    this.options = deserializeOptions;
+  }
+
+  /** Bytes of the frame received so far */
+  get buffered(): number {
+    return this.end - this.start;
+  }
+
+  /**
+   * Append a chunk of the stream
+   *
+   * The chunk is copied, so the caller may reuse its buffer.
+   */
+  push(chunk: Uint8Array): void {
+    if (this.skipping > 0) {
+      const skipped = Math.min(this.skipping, chunk.length);
+      this.skipping -= skipped;
+      chunk = chunk.subarray(skipped);
+    }
+    if (chunk.length === 0 || this.failed !== undefined) {
+      return;
+    }
+    if (this.end + chunk.length > this.buffer.length) {
+      const pending = this.buffer.subarray(this.start, this.end);
+      const grown =
+        pending.length + chunk.length > this.buffer.length
+          ? new Uint8Array(Math.max(2 * this.buffer.length, pending.length + chunk.length))
+          : this.buffer;
+      grown.set(pending, 0);
+      this.buffer = grown;
+      this.end = pending.length;
+      this.start = 0;
+    }
+    this.buffer.set(chunk, this.end);
+    this.end += chunk.length;
+  }
+
+  /**
+   * The value of the next complete frame, or undefined until a frame is
+   * complete (Result API)
+   *
+   * An error for a payload stands for that frame alone, which is dropped: the
+   * next call continues with the frame after it. An error for a malformed
+   * prefix is returned again until `reset()`.
+   */
+  tryNext(): Result<InferType<S> | undefined, DeserializeError> {
+    if (this.failed !== undefined) {
+      return err(this.failed);
+    }
+    if (this.start === this.end) {
+      return ok(undefined);
+    }
+    const prefix = tryDecodeVarintU32(this.buffer.subarray(0, this.end), this.start);
+    if (!prefix.ok) {
+      if (prefix.error.code === ErrorCode.UnexpectedEnd) {
+        return ok(undefined);
+      }
+      this.clear();
+      this.failed = new FramingError(
+        `Malformed length prefix: ${prefix.error.message}`,
+        prefix.error.code
+      );
+      return err(this.failed);
+    }
+
+    const length = prefix.value.value;
+    const payloadStart = this.start + prefix.value.bytesRead;
+    if (length > this.maxFrameLength) {
+      // Drop what arrived of the frame, and the rest as it does
+      const available = this.end - payloadStart;
+      this.start = payloadStart + Math.min(length, available);
+      this.skipping = Math.max(length - available, 0);
+      this.compact();
+      return err(
+        new FramingError(
+          `Frame of ${String(length)} bytes exceeds maxFrameLength (${String(this.maxFrameLength)})`,
+          ErrorCode.BufferFull
+        )
+      );
+    }
+    if (payloadStart + length > this.end) {
+      return ok(undefined);
+    }
+
+    // A copy, as values decoded with `byteViews` must outlive the buffer
+    const payload = this.buffer.slice(payloadStart, payloadStart + length);
+    this.start = payloadStart + length;
+    this.compact();
+    const result = tryDeserialize(this.schema, payload, 0, this.options);
+    return result.ok ? ok(result.value.value) : result;
+  }
+
+  /**
+   * The value of the next complete frame, or undefined until a frame is
+   * complete (throwing API)
+   *
+   * Throws DeserializeError for a frame that does not decode, which is
+   * dropped, or a malformed prefix, which is thrown again until `reset()`.
+   */
+  next(): InferType<S> | undefined {
+    return unwrap(this.tryNext());
+  }
+
+  /**
+   * The values of every frame complete so far
+   *
+   * Throws DeserializeError as `next()` does, after yielding the values
+   * before the failing frame.
+   */
+  *[Symbol.iterator](): IterableIterator<InferType<S>> {
+    for (let value = this.next(); value !== undefined; value = this.next()) {
+      yield value;
+    }
+  }
+
+  /**
+   * Drop the partial frame and any malformed prefix, e.g. after reconnecting
+   */
+  reset(): void {
+    this.clear();
+    this.skipping = 0;
+    this.failed = undefined;
+  }
+
+  private clear(): void {
+    this.start = 0;
+    this.end = 0;
+  }
+
+  private compact(): void {
+    if (this.start === this.end) {
+      this.clear();
+    }
+  }
+}
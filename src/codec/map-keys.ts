@@ -0,0 +1,143 @@
+/**
+ * Lookups in decoded maps by key value
+ *
+ * Maps decode to JS Maps, which compare keys by identity. That suits string,
+ * number, bool and bigint keys, but tuple, struct and enum keys decode to
+ * fresh arrays and objects that no other value is identical to:
+ *
+ *   const cells = deserialize(map(tuple(u8(), u8()), u16()), data).value;
+ *   cells.get([0, 1]);      // undefined: a different array
+ *   mapGet(cells, [0, 1]);  // the value stored for key (0, 1)
+ *
+ * These helpers compare keys by value instead. Primitive keys still take the
+ * Map.get fast path; other keys scan the entries.
+ *
+ * For repeated lookups, or keys that must survive JSON, postMessage or
+ * storage, `canonicalKey()` turns keys into stable strings to index by.
+ */
+
+import type { Schema, InferType } from "../types/schema.js";
+import { serialize } from "./serializer.js";
+import { withoutDirection } from "./direction.js";
+
+/**
+ * Whether two decoded keys hold the same value
+ *
+ * Arrays, plain objects (structs, enums) and Maps are compared recursively,
+ * Uint8Arrays byte by byte and Dates by time. Everything else is compared
+ * with SameValueZero, as Map does.
+ */
+export function keysEqual(a: unknown, b: unknown): boolean {
+  if (a === b || (a !== a && b !== b)) {
+    return true;
+  }
+  if (typeof a !== "object" || typeof b !== "object" || a === null || b === null) {
+    return false;
+  }
+
+  if (Array.isArray(a) || Array.isArray(b)) {
+    return (
+      Array.isArray(a) &&
+      Array.isArray(b) &&
+      a.length === b.length &&
+      a.every((item, i) => keysEqual(item, b[i]))
+    );
+  }
+  if (a instanceof Uint8Array || b instanceof Uint8Array) {
+    return (
+      a instanceof Uint8Array &&
+      b instanceof Uint8Array &&
+      a.length === b.length &&
+      a.every((byte, i) => byte === b[i])
+    );
+  }
+  if (a instanceof Date || b instanceof Date) {
+    return a instanceof Date && b instanceof Date && a.getTime() === b.getTime();
+  }
+  if (a instanceof Map || b instanceof Map) {
+    if (!(a instanceof Map && b instanceof Map) || a.size !== b.size) {
+      return false;
+    }
+    const other = b as Map<unknown, unknown>;
+    for (const [key, entry] of a as Map<unknown, unknown>) {
+      const match = findKey(other, key);
+      if (match === undefined || !keysEqual(entry, other.get(match.key))) {
+        return false;
+      }
+    }
+    return true;
+  }
+
+  const aKeys = Object.keys(a);
+  const bKeys = Object.keys(b);
+  return (
+    aKeys.length === bKeys.length &&
+    aKeys.every(
+      (key) =>
+        Object.prototype.hasOwnProperty.call(b, key) &&
+        keysEqual((a as Record<string, unknown>)[key], (b as Record<string, unknown>)[key])
+    )
+  );
+}
+
+function findKey<K>(map: ReadonlyMap<K, unknown>, key: K): { key: K } | undefined {
+  if (map.has(key)) {
+    return { key };
+  }
+  if (typeof key !== "object" || key === null) {
+    return undefined;
+  }
+  for (const candidate of map.keys()) {
+    if (keysEqual(candidate, key)) {
+      return { key: candidate };
+    }
+  }
+  return undefined;
+}
+
+/**
+ * Get the value stored for a key equal to `key`, or undefined
+ */
+export function mapGet<K, V>(map: ReadonlyMap<K, V>, key: K): V | undefined {
+  const match = findKey(map, key);
+  return match === undefined ? undefined : map.get(match.key);
+}
+
+/**
+ * Whether the map holds a key equal to `key`
+ */
+export function mapHas<K>(map: ReadonlyMap<K, unknown>, key: K): boolean {
+  return findKey(map, key) !== undefined;
+}
+
+/**
+ * The stable string for a key's encoded bytes, as `canonicalKey()` produces
+ */
+export function encodedKey(bytes: Uint8Array): string {
+  let key = "";
+  for (const byte of bytes) {
+    key += byte.toString(16).padStart(2, "0");
+  }
+  return key;
+}
+
+/**
+ * A function turning keys of `keySchema` into stable strings
+ *
+ * Two keys get the same string exactly when they encode to the same bytes,
+ * which for the usual key types (integers, strings, tuples and structs of
+ * them, enums) is when Rust considers them equal. Decoding a map recognises
+ * repeated keys the same way.
+ *
+ *   const cellKey = canonicalKey(tuple(u8(), u8()));
+ *   const byCell = new Map(Array.from(cells, ([cell, color]) => [cellKey(cell), color]));
+ *   byCell.get(cellKey([0, 1]));
+ *
+ * The returned function throws SerializeError for a key that does not match
+ * the schema.
+ */
+export function canonicalKey<S extends Schema>(keySchema: S): (key: InferType<S>) => string {
+  // Keys are encoded only to compare them, so decode-only keys are fine too
+  const schema = withoutDirection(keySchema);
+  return (key) => encodedKey(serialize(schema, key));
+}
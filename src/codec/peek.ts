@@ -0,0 +1,96 @@
+/**
+ * Which enum variant a frame holds, without decoding it
+ *
+ * Postcard writes an enum as its variant index, a varint, and then the
+ * variant's data, so the first bytes of a frame holding an enum tell which
+ * variant it is. On a link sending hundreds of telemetry frames a second
+ * when a view only shows some kinds, checking those bytes drops the other
+ * frames before any of them is decoded:
+ *
+ *   const interesting = variantFilter(TelemetrySchema, ["Reading", "Alarm"]);
+ *   const device = new Channel(link, schemas, { filter: interesting });
+ *
+ * The generator emits one filter per variant with `--variant-peeks`
+ * (`isTelemetryReading`). A frame whose index cannot be read, or is no
+ * variant's, matches no filter; decoding it would fail anyway.
+ */
+
+import type { EnumSchema, Schema } from "../types/schema.js";
+import { tryDecodeVarintU32 } from "./varint.js";
+
+type VariantsOf<S> =
+  S extends EnumSchema<infer V>
+    ? V
+    : S extends { readonly kind: "transform"; readonly inner: infer I }
+      ? VariantsOf<I>
+      : never;
+
+/**
+ * The names of the variants of an enum schema, also through `discriminated()`
+ * or another transform of one
+ */
+export type VariantName<S extends Schema> = keyof VariantsOf<S> & string;
+
+/**
+ * The enum a schema encodes as, looking through transforms and lazy references
+ *
+ * Throws TypeError for schemas of anything else.
+ */
+function enumOf(schema: Schema): Extract<Schema, { kind: "enum" }> {
+  let current = schema;
+  for (;;) {
+    if (current.kind === "enum") {
+      return current;
+    }
+    if (current.kind === "transform") {
+      current = current.inner as Schema;
+    } else if (current.kind === "lazy") {
+      current = current.get();
+    } else {
+      throw new TypeError(`Only enum frames can be peeked, not ${current.kind}`);
+    }
+  }
+}
+
+/**
+ * The variant the frame at `offset` holds, or undefined if its index cannot
+ * be read or is no variant's
+ */
+export function peekVariant<S extends Schema>(
+  schema: S,
+  frame: Uint8Array,
+  offset = 0
+): VariantName<S> | undefined {
+  const index = tryDecodeVarintU32(frame, offset);
+  if (!index.ok) {
+    return undefined;
+  }
+  return Object.keys(enumOf(schema).variants)[index.value.value] as VariantName<S> | undefined;
+}
+
+/**
+ * A check of whether a frame holds one of `variants`, reading only its
+ * variant index
+ *
+ * Throws RangeError if the enum has no variant of one of the names.
+ */
+export function variantFilter<S extends Schema>(
+  schema: S,
+  variants: readonly VariantName<S>[]
+): (frame: Uint8Array) => boolean {
+  const enumSchema = enumOf(schema);
+  const names = Object.keys(enumSchema.variants);
+  const indices = new Set(
+    variants.map((variant) => {
+      const index = names.indexOf(variant);
+      if (index === -1) {
+        throw new RangeError(`${enumSchema.name} has no variant ${variant}`);
+      }
+      return index;
+    })
+  );
+  return (frame) => {
+    const index = tryDecodeVarintU32(frame, 0);
+    return index.ok && indices.has(index.value.value);
+  };
+}
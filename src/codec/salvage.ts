@@ -0,0 +1,139 @@
+/**
+ * Salvaging the leading records of a batch that does not decode
+ *
+ * Devices often send or store records in batches, a struct holding a
+ * `Vec<Record>`, such as a log pulled off flash. One corrupt record makes the
+ * whole batch fail to decode, yet every record before it is intact, and
+ * postcard has no way to skip past the corrupt one. `salvage()` decodes the
+ * batch, and if it fails, returns the records before the failure with the
+ * error and the offset it happened at:
+ *
+ *   const { records, error, offset } = salvage(LogBatchSchema, "entries", flash);
+ *   if (error !== undefined) {
+ *     console.warn(`Lost the entries after byte ${offset}: ${error.message}`);
+ *   }
+ *
+ * The generator emits one per struct holding a list of records with
+ * `--salvage` (`salvageLogBatch`).
+ */
+
+import type { Schema, InferType, SeqSchema, StructSchema } from "../types/schema.js";
+import {
+  type DeserializeError,
+  type DeserializeOptions,
+  tryDeserialize,
+} from "./deserializer.js";
+import { deepFreeze } from "./freeze.js";
+import { tryDecodeVarintU64 } from "./varint.js";
+
+type FieldsOf<S> = S extends StructSchema<infer F> ? F : never;
+
+/**
+ * The fields of a struct schema holding a list
+ */
+export type ListField<S extends Schema> = {
+  [K in keyof FieldsOf<S>]: FieldsOf<S>[K] extends SeqSchema<Schema> ? K : never;
+}[keyof FieldsOf<S>] &
+  string;
+
+/**
+ * The type of the records in list field `K` of a struct schema
+ */
+export type RecordOf<S extends Schema, K extends ListField<S>> =
+  FieldsOf<S>[K] extends SeqSchema<infer I> ? InferType<I> : never;
+
+/**
+ * What could be decoded of a batch
+ */
+export interface Salvaged<T, R> {
+  /** The whole batch, undefined unless all of it decoded */
+  readonly value: T | undefined;
+  /** The records decoded, in order, up to the first that did not */
+  readonly records: R[];
+  /** Why decoding stopped, undefined if the whole batch decoded */
+  readonly error: DeserializeError | undefined;
+  /** Where decoding failed, or the end of the batch if it did not */
+  readonly offset: number;
+}
+
+/**
+ * Decode the batch at `offset`, keeping the records of list field `field`
+ * decoded before any failure
+ *
+ * Fields before the list must decode for any record to be salvaged, and the
+ * batch is only `value` if the fields after it decode too. Throws TypeError
+ * for a schema that is not a struct, or a field that is not a list.
+ */
+export function salvage<S extends Schema, K extends ListField<S>>(
+  schema: S & { readonly direction?: "decode" },
+  field: K,
+  data: Uint8Array,
+  offset = 0,
+  options: DeserializeOptions = {}
+): Salvaged<InferType<S>, RecordOf<S, K>> {
+  const batch: Schema = schema;
+  if (batch.kind !== "struct") {
+    throw new TypeError(`Only the records of a struct can be salvaged, not ${batch.kind}`);
+  }
+  const list = batch.fields[field];
+  if (list?.kind !== "seq") {
+    throw new TypeError(`Field "${field}" is not a list of records`);
+  }
+
+  const whole = tryDeserialize(schema, data, offset, options);
+  if (whole.ok) {
+    const value = whole.value.value;
+    return {
+      value,
+      records: (value as Record<string, unknown>)[field] as RecordOf<S, K>[],
+      error: undefined,
+      offset: offset + whole.value.bytesRead,
+    };
+  }
+
+  // Walk the batch again to find how far it decodes; the decode above
+  // already counted in the stats
+  const { stats, ...walk } = options;
+  const root = options.root ?? "";
+  let records: unknown[] = [];
+  const stop = (error: DeserializeError, at: number): Salvaged<InferType<S>, RecordOf<S, K>> => ({
+    value: undefined,
+    records: (options.freeze === true ? deepFreeze(records) : records) as RecordOf<S, K>[],
+    error,
+    offset: error.offset ?? at,
+  });
+
+  let current = offset;
+  for (const [name, fieldSchema] of Object.entries(batch.fields)) {
+    const fieldOptions = { ...walk, root: `${root}.${name}` };
+    const decoded = tryDeserialize(fieldSchema, data, current, fieldOptions);
+    if (decoded.ok) {
+      if (name === field) {
+        records = decoded.value.value as unknown[];
+      }
+      current += decoded.value.bytesRead;
+      continue;
+    }
+    if (name !== field) {
+      return stop(decoded.error, current);
+    }
+    // The length decoded and is within limits if the list failed past it
+    const length = tryDecodeVarintU64(data, current);
+    if (!length.ok || decoded.error.offset === current) {
+      return stop(decoded.error, current);
+    }
+    let itemOffset = current + length.value.bytesRead;
+    for (let i = 0; i < Number(length.value.value); i++) {
+      const itemOptions = { ...walk, root: `${root}.${name}[${String(i)}]` };
+      const item = tryDeserialize(list.item, data, itemOffset, itemOptions);
+      if (!item.ok) {
+        return stop(item.error, itemOffset);
+      }
+      records.push(item.value.value);
+      itemOffset += item.value.bytesRead;
+    }
+    // Every record decoded on its own, so the list failed as a whole
+    return stop(decoded.error, current);
+  }
+  return stop(whole.error, offset);
+}
@@ -0,0 +1,97 @@
+/**
+ * Schema hash handshakes
+ *
+ * Postcard writes no names or tags, so bytes from a peer built against other
+ * versions of the types decode into garbage rather than failing. With
+ * `Generator::schema_hashes()` (`postcard-ts generate --schema-hashes`), the
+ * Rust generator emits a hash of each type's wire shape, `ReadingSchemaHash`;
+ * exchanging those when a connection opens and comparing them before any
+ * other message turns a mismatch into an error naming the types:
+ *
+ *   const hello = deserialize(SchemaHashesSchema, firstFrame).value;
+ *   checkSchemaHashes({ Reading: ReadingSchemaHash, Command: CommandSchemaHash }, hello);
+ *
+ * A Rust peer sends the same hashes from `serde_postcard_ts::schema_hash` or
+ * the `SCHEMA_HASH` constants `#[derive(PostcardTs)]` declares, as a
+ * `BTreeMap<String, u64>`.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { map, string, u64 } from "../types/schema.js";
+import { PostcardError } from "../types/errors.js";
+
+/** Schema hashes by type name */
+export type SchemaHashes = ReadonlyMap<string, bigint> | Readonly<Record<string, bigint>>;
+
+/** A type whose hash differs between the two sides, or that the peer lacks */
+export interface SchemaHashMismatch {
+  readonly type: string;
+  readonly expected: bigint;
+  /** The peer's hash, or undefined if it sent none for the type */
+  readonly received: bigint | undefined;
+}
+
+export class SchemaHashError extends PostcardError {
+  readonly mismatches: readonly SchemaHashMismatch[];
+
+  constructor(mismatches: readonly SchemaHashMismatch[]) {
+    super(
+      `Schema hashes differ: ${mismatches
+        .map(({ type, expected, received }) =>
+          received === undefined
+            ? `${type} (missing)`
+            : `${type} (expected ${hex(expected)}, received ${hex(received)})`
+        )
+        .join(", ")}`
+    );
+    this.name = "SchemaHashError";
+    this.mismatches = mismatches;
+  }
+}
+
+/**
+ * Schema hashes on the wire, as Rust's `BTreeMap<String, u64>`
+ */
+export const SchemaHashesSchema = map(string(), u64());
+
+/**
+ * Check that the peer has the same hash for every type in `expected`
+ *
+ * Types only the peer knows are ignored, so a peer may support more messages
+ * than this side uses.
+ */
+export function tryCheckSchemaHashes(
+  expected: SchemaHashes,
+  received: SchemaHashes
+): Result<void, SchemaHashError> {
+  const theirs = new Map(entries(received));
+  const mismatches: SchemaHashMismatch[] = [];
+  for (const [type, hash] of entries(expected)) {
+    const other = theirs.get(type);
+    if (other !== hash) {
+      mismatches.push({ type, expected: hash, received: other });
+    }
+  }
+  return mismatches.length === 0 ? ok(undefined) : err(new SchemaHashError(mismatches));
+}
+
+/**
+ * Check that the peer has the same hash for every type in `expected`
+ *
+ * Throws SchemaHashError listing the types that differ.
+ */
+export function checkSchemaHashes(expected: SchemaHashes, received: SchemaHashes): void {
+  unwrap(tryCheckSchemaHashes(expected, received));
+}
+
+function entries(hashes: SchemaHashes): Iterable<readonly [string, bigint]> {
+  return isMap(hashes) ? hashes.entries() : Object.entries(hashes);
+}
+
+function isMap(hashes: SchemaHashes): hashes is ReadonlyMap<string, bigint> {
+  return hashes instanceof Map;
+}
+
+function hex(hash: bigint): string {
+  return `0x${hash.toString(16).padStart(16, "0")}`;
+}
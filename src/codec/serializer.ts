@@ -6,28 +6,14 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
 import type {
   Schema,
   InferType,
   BoolSchema,
-  I8Schema,
-  I16Schema,
-  I32Schema,
-  I64Schema,
-  I128Schema,
-  U8Schema,
-  U16Schema,
-  U32Schema,
-  U64Schema,
-  U128Schema,
   F32Schema,
-  F64Schema,
-  CharSchema,
   StringSchema,
-  BytesSchema,
   OptionSchema,
-  UnitSchema,
-  UnitStructSchema,
   NewtypeStructSchema,
   SeqSchema,
   TupleSchema,
@@ -36,292 +22,283 @@ import type {
   StructSchema,
   EnumSchema,
   EnumVariant,
+  TransformSchema,
+  ExternalSchema,
 } from "../types/schema.js";
+import { directionOf } from "../types/schema.js";
 
-// Import primitive serializers
-import { tryEncodeBool } from "../primitives/bool.js";
-import {
-  tryEncodeU8,
-  tryEncodeI8,
-  tryEncodeF32,
-  tryEncodeF64,
-} from "../primitives/numbers.js";
-import { tryEncodeString, tryEncodeChar } from "../primitives/string.js";
-import { tryEncodeBytes } from "../primitives/bytes.js";
+// Import primitive writers
+import { writeU8, writeI8 } from "../primitives/numbers.js";
+import { writeString, writeChar, truncateUtf8 } from "../primitives/string.js";
+import { writeBytes } from "../primitives/bytes.js";
 
 // Import varint codec
 import {
-  tryEncodeVarintU16,
-  tryEncodeVarintU32,
-  tryEncodeVarintU64,
-  tryEncodeVarintU128,
-  tryEncodeVarintI16,
-  tryEncodeVarintI32,
-  tryEncodeVarintI64,
-  tryEncodeVarintI128,
+  writeVarintU16,
+  writeVarintU32,
+  writeVarintU64,
+  writeVarintU128,
+  writeVarintI16,
+  writeVarintI32,
+  writeVarintI64,
+  writeVarintI128,
 } from "./varint.js";
+import { type Writer, type WriterPool, defaultWriterPool } from "./writer.js";
+import { ValidationError } from "../types/errors.js";
 
-export class SerializeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class SerializeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "SerializeError";
   }
 }
 
+export interface SerializeOptions {
+  /**
+   * Called when an f32 field is rounded to the nearest f32 by more than
+   * `f32Tolerance`, e.g. to log drift a device validator would see:
+   * `{ onF32Rounding: (value, rounded) => console.warn(value, "sent as", rounded) }`
+   */
+  readonly onF32Rounding?: (value: number, rounded: number) => void;
+  /**
+   * Relative change up to which rounding an f32 is not reported (default:
+   * 2^-24, half the f32 precision, so only values out of f32's range or
+   * precision are). 0 reports every value that is not already an f32.
+   */
+  readonly f32Tolerance?: number;
+  /**
+   * Called when a string over its schema's `maxBytes` is truncated
+   * (`overflow: "truncate"`), with the string and what was sent
+   */
+  readonly onStringTruncated?: (value: string, truncated: string) => void;
+  /**
+   * Writers to encode with (default: `defaultWriterPool`). `serialize()`
+   * borrows one per call and copies the bytes out before giving it back.
+   */
+  readonly pool?: WriterPool;
+}
+
 /**
  * Serialize data using a schema (Result API)
  *
  * The schema drives the serialization process and provides type inference.
  * Note: value is unknown for maximum flexibility - the schema validates the structure
+ * Throws TypeError for a `decodeOnly()` schema.
  */
 export function trySerialize<S extends Schema>(
-  schema: S,
-  value: unknown
+  schema: S & { readonly direction?: "encode" },
+  value: unknown,
+  options: SerializeOptions = {}
 ): Result<Uint8Array, SerializeError> {
+  assertEncodable(schema);
+  const pool = options.pool ?? defaultWriterPool;
+  const writer = pool.acquire();
+  try {
+    const error = write(schema, value, writer, options);
+    return error === undefined ? ok(writer.toBytes()) : err(error);
+  } finally {
+    pool.release(writer);
+  }
+}
+
+/**
+ * Serialize data into `writer`, after what it already holds (Result API)
+ *
+ * Returns the number of bytes written. Nothing is copied, so encoding many
+ * messages into one reused writer allocates next to nothing; on failure the
+ * writer is left as it was. Throws TypeError for a `decodeOnly()` schema.
+ */
+export function trySerializeInto<S extends Schema>(
+  schema: S & { readonly direction?: "encode" },
+  value: unknown,
+  writer: Writer,
+  options: SerializeOptions = {}
+): Result<number, SerializeError> {
+  assertEncodable(schema);
+  const start = writer.length;
+  const error = write(schema, value, writer, options);
+  if (error !== undefined) {
+    writer.truncate(start);
+    return err(error);
+  }
+  return ok(writer.length - start);
+}
+
+function assertEncodable(schema: Schema): void {
+  if (directionOf(schema) === "decode") {
+    throw new TypeError("Cannot serialize with a decode-only schema");
+  }
+}
+
+/**
+ * Write `value` to `writer`, returning the error if it does not fit `schema`
+ *
+ * What was written before a failure stays in the writer; the callers above
+ * discard it.
+ */
+function write(
+  schema: Schema,
+  value: unknown,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
   // Dispatch based on schema kind
   // TypeScript can't narrow InferType<S> based on schema.kind, so we use 'as any' for type safety
   // Each handler accepts the correct type for its schema, but TS can't verify this statically
-  /* eslint-disable @typescript-eslint/no-unsafe-return, @typescript-eslint/no-explicit-any, @typescript-eslint/no-unsafe-argument */
+  /* eslint-disable @typescript-eslint/no-explicit-any, @typescript-eslint/no-unsafe-argument */
   switch (schema.kind) {
     case "bool":
-      return serializeBool(schema, value as any);
+      return serializeBool(schema, value as any, writer);
     case "i8":
-      return serializeI8(schema, value as any);
+      return primitive("i8", writeI8(writer, value as any));
     case "i16":
-      return serializeI16(schema, value as any);
+      return primitive("i16", writeVarintI16(writer, value as any));
     case "i32":
-      return serializeI32(schema, value as any);
+      return primitive("i32", writeVarintI32(writer, value as any));
     case "i64":
-      return serializeI64(schema, value as any);
+      return primitive("i64", writeVarintI64(writer, value as any));
     case "i128":
-      return serializeI128(schema, value as any);
+      return primitive("i128", writeVarintI128(writer, value as any));
     case "u8":
-      return serializeU8(schema, value as any);
+      return primitive("u8", writeU8(writer, value as any));
     case "u16":
-      return serializeU16(schema, value as any);
+      return primitive("u16", writeVarintU16(writer, value as any));
     case "u32":
-      return serializeU32(schema, value as any);
+      return primitive("u32", writeVarintU32(writer, value as any));
     case "u64":
-      return serializeU64(schema, value as any);
+      return primitive("u64", writeVarintU64(writer, value as any));
     case "u128":
-      return serializeU128(schema, value as any);
+      return primitive("u128", writeVarintU128(writer, value as any));
     case "f32":
-      return serializeF32(schema, value as any);
+      return serializeF32(schema, value as any, writer, options);
     case "f64":
-      return serializeF64(schema, value as any);
+      writer.writeF64(value as any);
+      return undefined;
     case "char":
-      return serializeChar(schema, value as any);
+      return primitive("char", writeChar(writer, value as any));
     case "string":
-      return serializeString(schema, value as any);
+      return serializeString(schema, value as any, writer, options);
     case "bytes":
-      return serializeBytes(schema, value as any);
+      writeBytes(writer, value as any);
+      return undefined;
     case "option":
-      return serializeOption(schema as OptionSchema<Schema>, value as any);
+      return serializeOption(schema as OptionSchema<Schema>, value as any, writer, options);
     case "unit":
-      return serializeUnit(schema, value as any);
     case "unit_struct":
-      return serializeUnitStruct(schema, value as any);
+      // Zero bytes
+      return undefined;
+    case "never":
+      // Only reachable when a caller bypasses the inferred types
+      return new SerializeError("Cannot serialize a value of an uninhabited type");
     case "newtype_struct":
-      return serializeNewtypeStruct(schema as NewtypeStructSchema<Schema>, value as any);
+      // Newtype struct is just the inner value, no wrapper
+      return within(
+        "Failed to serialize newtype struct",
+        write((schema as NewtypeStructSchema<Schema>).inner, value, writer, options)
+      );
     case "seq":
-      return serializeSeq(schema as SeqSchema<Schema>, value as any);
-    case "tuple":
-      return serializeTuple(schema as TupleSchema<readonly Schema[]>, value as any);
-    case "tuple_struct":
-      return serializeTupleStruct(schema as TupleStructSchema<readonly Schema[]>, value as any);
+      return serializeSeq(schema as SeqSchema<Schema>, value as any, writer, options);
+    case "tuple": {
+      const { items } = schema as TupleSchema<readonly Schema[]>;
+      return serializeItems("tuple", items, value as any, writer, options);
+    }
+    case "tuple_struct": {
+      // Tuple struct is encoded as a tuple
+      const { items } = schema as TupleStructSchema<readonly Schema[]>;
+      return serializeItems("tuple struct", items, value as any, writer, options);
+    }
     case "map":
-      return serializeMap(schema as MapSchema<Schema, Schema>, value as any);
-    case "struct":
-      return serializeStruct(schema as StructSchema<Record<string, Schema>>, value as any);
+      return serializeMap(schema as MapSchema<Schema, Schema>, value as any, writer, options);
+    case "struct": {
+      const { fields } = schema as StructSchema<Record<string, Schema>>;
+      return serializeFields("struct", fields, value as any, writer, options);
+    }
     case "enum":
-      return serializeEnum(schema as EnumSchema<Record<string, EnumVariant>>, value as any);
+      return serializeEnum(schema as EnumSchema<Record<string, EnumVariant>>, value, writer, options);
+    case "transform":
+      return serializeTransform(schema as TransformSchema<Schema, unknown>, value, writer, options);
+    case "lazy":
+      // Resolve the referenced schema and encode exactly as it would
+      return write(schema.get(), value, writer, options);
+    case "external":
+      return serializeExternal(schema as ExternalSchema<unknown>, value, writer);
     default: {
       // Exhaustiveness check
       const _exhaustive: never = schema;
-      return err(new SerializeError(`Unknown schema kind: ${(_exhaustive as Schema).kind}`));
+      return new SerializeError(`Unknown schema kind: ${(_exhaustive as Schema).kind}`);
     }
   }
-  /* eslint-enable @typescript-eslint/no-unsafe-return, @typescript-eslint/no-explicit-any, @typescript-eslint/no-unsafe-argument */
-}
-
-// ============================================================================
-// PRIMITIVE TYPE HANDLERS
-// ============================================================================
-
-function serializeBool(
-  _schema: BoolSchema,
-  value: boolean
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeBool(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize bool: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeI8(
-  _schema: I8Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeI8(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize i8: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeI16(
-  _schema: I16Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintI16(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize i16: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeI32(
-  _schema: I32Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintI32(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize i32: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeI64(
-  _schema: I64Schema,
-  value: bigint
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintI64(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize i64: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
+  /* eslint-enable @typescript-eslint/no-explicit-any, @typescript-eslint/no-unsafe-argument */
 }
 
-function serializeI128(
-  _schema: I128Schema,
-  value: bigint
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintI128(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize i128: ${result.error.message}`));
+/** `error`, if any, as a SerializeError with `context` in front of its message */
+function within(context: string, error: ValidationError | undefined): SerializeError | undefined {
+  if (error === undefined) {
+    return undefined;
   }
-  return ok(result.value.bytes);
+  return new SerializeError(`${context}: ${error.message}`, error.code);
 }
 
-function serializeU8(
-  _schema: U8Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeU8(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize u8: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
+function primitive(kind: string, error: ValidationError | undefined): SerializeError | undefined {
+  return within(`Failed to serialize ${kind}`, error);
 }
 
-function serializeU16(
-  _schema: U16Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintU16(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize u16: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeU32(
-  _schema: U32Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintU32(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize u32: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
+// ============================================================================
+// PRIMITIVE TYPE HANDLERS
+// ============================================================================
 
-function serializeU64(
-  _schema: U64Schema,
-  value: bigint
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintU64(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize u64: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
+/** Largest relative change from rounding a number within f32's normal range */
+const F32_HALF_PRECISION = 2 ** -24;
 
-function serializeU128(
-  _schema: U128Schema,
-  value: bigint
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeVarintU128(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize u128: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
+function serializeBool(
+  _schema: BoolSchema,
+  value: boolean,
+  writer: Writer
+): SerializeError | undefined {
+  writer.writeByte(value ? 0x01 : 0x00);
+  return undefined;
 }
 
 function serializeF32(
   _schema: F32Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeF32(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize f32: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeF64(
-  _schema: F64Schema,
-  value: number
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeF64(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize f64: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeChar(
-  _schema: CharSchema,
-  value: string
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeChar(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize char: ${result.error.message}`));
+  value: number,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
+  // Round explicitly so the reported value is exactly what gets written, and
+  // a decoded f32 re-encodes to the same bytes
+  const rounded = Math.fround(value);
+  if (options.onF32Rounding !== undefined && rounded !== value && !Number.isNaN(value)) {
+    const tolerance = options.f32Tolerance ?? F32_HALF_PRECISION;
+    if (!(Math.abs(rounded - value) <= tolerance * Math.abs(value))) {
+      options.onF32Rounding(value, rounded);
+    }
   }
-  return ok(result.value.bytes);
+  writer.writeF32(rounded);
+  return undefined;
 }
 
 function serializeString(
-  _schema: StringSchema,
-  value: string
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeString(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize string: ${result.error.message}`));
-  }
-  return ok(result.value.bytes);
-}
-
-function serializeBytes(
-  _schema: BytesSchema,
-  value: Uint8Array
-): Result<Uint8Array, SerializeError> {
-  const result = tryEncodeBytes(value);
-  if (!result.ok) {
-    return err(new SerializeError(`Failed to serialize bytes: ${result.error.message}`));
+  schema: StringSchema,
+  value: string,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
+  let text = value;
+  if (schema.maxBytes !== undefined && typeof value === "string") {
+    const fitted = truncateUtf8(value, schema.maxBytes);
+    if (fitted.truncated) {
+      if (schema.overflow !== "truncate") {
+        return new SerializeError(
+          `String of ${String(new TextEncoder().encode(value).length)} bytes exceeds maxBytes (${String(schema.maxBytes)})`
+        );
+      }
+      options.onStringTruncated?.(value, fitted.text);
+      text = fitted.text;
+    }
   }
-  return ok(result.value.bytes);
+  writeString(writer, text);
+  return undefined;
 }
 
 // ============================================================================
@@ -330,61 +307,18 @@ function serializeBytes(
 
 function serializeOption<T extends Schema>(
   schema: OptionSchema<T>,
-  value: InferType<T> | null
-): Result<Uint8Array, SerializeError> {
+  value: InferType<T> | null,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
   if (value === null) {
     // None: 0x00
-    return ok(new Uint8Array([0x00]));
-  } else {
-    // Some: 0x01 + serialized value
-    const discriminant = new Uint8Array([0x01]);
-    const innerResult = trySerialize(schema.inner, value);
-    if (!innerResult.ok) {
-      return err(new SerializeError(`Failed to serialize option value: ${innerResult.error.message}`));
-    }
-
-    // Combine discriminant + value
-    const result = new Uint8Array(1 + innerResult.value.length);
-    result.set(discriminant, 0);
-    result.set(innerResult.value, 1);
-    return ok(result);
-  }
-}
-
-// ============================================================================
-// UNIT TYPE HANDLERS
-// ============================================================================
-
-function serializeUnit(
-  _schema: UnitSchema,
-  _value: undefined
-): Result<Uint8Array, SerializeError> {
-  // Unit type occupies zero bytes
-  return ok(new Uint8Array(0));
-}
-
-function serializeUnitStruct(
-  _schema: UnitStructSchema,
-  _value: Record<string, never>
-): Result<Uint8Array, SerializeError> {
-  // Unit struct occupies zero bytes
-  return ok(new Uint8Array(0));
-}
-
-// ============================================================================
-// NEWTYPE STRUCT HANDLER
-// ============================================================================
-
-function serializeNewtypeStruct(
-  schema: NewtypeStructSchema<Schema>,
-  value: unknown
-): Result<Uint8Array, SerializeError> {
-  // Newtype struct is just the inner value, no wrapper
-  const innerResult = trySerialize(schema.inner, value);
-  if (!innerResult.ok) {
-    return err(new SerializeError(`Failed to serialize newtype struct: ${innerResult.error.message}`));
+    writer.writeByte(0x00);
+    return undefined;
   }
-  return ok(innerResult.value);
+  // Some: 0x01 + serialized value
+  writer.writeByte(0x01);
+  return within("Failed to serialize option value", write(schema.inner, value, writer, options));
 }
 
 // ============================================================================
@@ -393,138 +327,74 @@ function serializeNewtypeStruct(
 
 function serializeSeq<T extends Schema>(
   schema: SeqSchema<T>,
-  value: InferType<T>[]
-): Result<Uint8Array, SerializeError> {
+  value: InferType<T>[],
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
   // Validate that value is an array
   if (!Array.isArray(value)) {
-    return err(new SerializeError(`Expected array, got ${typeof value}`));
+    return new SerializeError(`Expected array, got ${typeof value}`);
   }
-
-  // Encode length as varint
-  const lengthResult = tryEncodeVarintU64(BigInt(value.length));
-  if (!lengthResult.ok) {
-    return err(new SerializeError(`Failed to serialize seq length: ${lengthResult.error.message}`));
+  if (schema.maxLength !== undefined && value.length > schema.maxLength) {
+    return new SerializeError(
+      `Seq of ${String(value.length)} items exceeds maxLength (${String(schema.maxLength)})`
+    );
   }
 
-  const parts: Uint8Array[] = [lengthResult.value.bytes];
-  let totalSize = lengthResult.value.bytesWritten;
-
-  // Serialize each element
+  // Length as varint, then each element
+  writer.writeVarint(value.length);
   for (let i = 0; i < value.length; i++) {
     const item = value[i];
     if (item === undefined) {
-      return err(new SerializeError(`Seq item ${String(i)} is undefined`));
-    }
-
-    const itemResult = trySerialize(schema.item, item);
-    if (!itemResult.ok) {
-      return err(new SerializeError(`Failed to serialize seq item ${String(i)}: ${itemResult.error.message}`));
-    }
-    parts.push(itemResult.value);
-    totalSize += itemResult.value.length;
-  }
-
-  // Combine all parts
-  const result = new Uint8Array(totalSize);
-  let offset = 0;
-  for (const part of parts) {
-    result.set(part, offset);
-    offset += part.length;
-  }
-
-  return ok(result);
-}
-
-// ============================================================================
-// TUPLE HANDLER
-// ============================================================================
-
-function serializeTuple<T extends readonly Schema[]>(
-  schema: TupleSchema<T>,
-  value: { [K in keyof T]: T[K] extends Schema ? InferType<T[K]> : never }
-): Result<Uint8Array, SerializeError> {
-  const parts: Uint8Array[] = [];
-  let totalSize = 0;
-
-  // Serialize each item (no length prefix for tuples)
-  for (let i = 0; i < schema.items.length; i++) {
-    const itemSchema = schema.items[i];
-    // Schema union uses structural typing with `any` for type parameters to handle variance,
-    // so we need to cast itemValue which comes from the structural type back to the typed schema
-    // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
-    const itemValue = value[i];
-
-    if (itemSchema === undefined) {
-      return err(new SerializeError(`Tuple item ${String(i)} schema is undefined`));
+      return new SerializeError(`Seq item ${String(i)} is undefined`);
     }
-    // eslint-disable-next-line @typescript-eslint/no-unnecessary-condition
-    if (itemValue === undefined) {
-      return err(new SerializeError(`Tuple item ${String(i)} value is undefined`));
-    }
-
-    const itemResult = trySerialize(itemSchema, itemValue);
-    if (!itemResult.ok) {
-      return err(new SerializeError(`Failed to serialize tuple item ${String(i)}: ${itemResult.error.message}`));
+    const error = within(
+      `Failed to serialize seq item ${String(i)}`,
+      write(schema.item, item, writer, options)
+    );
+    if (error !== undefined) {
+      return error;
     }
-    parts.push(itemResult.value);
-    totalSize += itemResult.value.length;
   }
-
-  // Combine all parts
-  const result = new Uint8Array(totalSize);
-  let offset = 0;
-  for (const part of parts) {
-    result.set(part, offset);
-    offset += part.length;
-  }
-
-  return ok(result);
+  return undefined;
 }
 
 // ============================================================================
-// TUPLE STRUCT HANDLER
+// TUPLE AND TUPLE STRUCT HANDLER
 // ============================================================================
 
-function serializeTupleStruct<T extends readonly Schema[]>(
-  schema: TupleStructSchema<T>,
-  value: { [K in keyof T]: T[K] extends Schema ? InferType<T[K]> : never }
-): Result<Uint8Array, SerializeError> {
-  // Tuple struct is encoded as a tuple
-  const parts: Uint8Array[] = [];
-  let totalSize = 0;
-
-  for (let i = 0; i < schema.items.length; i++) {
-    const itemSchema = schema.items[i];
-    // Schema union uses structural typing with `any` for type parameters to handle variance,
-    // so we need to cast itemValue which comes from the structural type back to the typed schema
-    // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
+/**
+ * The items of a tuple, tuple struct or tuple variant, one after the other
+ * with no length prefix
+ */
+function serializeItems(
+  what: string,
+  items: readonly Schema[],
+  value: readonly unknown[],
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
+  const label = what.charAt(0).toUpperCase() + what.slice(1);
+  for (let i = 0; i < items.length; i++) {
+    const itemSchema = items[i];
     const itemValue = value[i];
 
     if (itemSchema === undefined) {
-      return err(new SerializeError(`Tuple struct item ${String(i)} schema is undefined`));
+      return new SerializeError(`${label} item ${String(i)} schema is undefined`);
     }
-    // eslint-disable-next-line @typescript-eslint/no-unnecessary-condition
     if (itemValue === undefined) {
-      return err(new SerializeError(`Tuple struct item ${String(i)} value is undefined`));
+      return new SerializeError(`${label} item ${String(i)} value is undefined`);
     }
 
-    const itemResult = trySerialize(itemSchema, itemValue);
-    if (!itemResult.ok) {
-      return err(new SerializeError(`Failed to serialize tuple struct item ${String(i)}: ${itemResult.error.message}`));
+    const error = within(
+      `Failed to serialize ${what} item ${String(i)}`,
+      write(itemSchema, itemValue, writer, options)
+    );
+    if (error !== undefined) {
+      return error;
     }
-    parts.push(itemResult.value);
-    totalSize += itemResult.value.length;
-  }
-
-  // Combine all parts
-  const result = new Uint8Array(totalSize);
-  let offset = 0;
-  for (const part of parts) {
-    result.set(part, offset);
-    offset += part.length;
   }
-
-  return ok(result);
+  return undefined;
 }
 
 // ============================================================================
@@ -533,90 +403,71 @@ function serializeTupleStruct<T extends readonly Schema[]>(
 
 function serializeMap<K extends Schema, V extends Schema>(
   schema: MapSchema<K, V>,
-  value: Map<InferType<K>, InferType<V>>
-): Result<Uint8Array, SerializeError> {
+  value: Map<InferType<K>, InferType<V>>,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
   // Validate that value is a Map
   if (!(value instanceof Map)) {
-    return err(new SerializeError(`Expected Map, got ${typeof value}`));
+    return new SerializeError(`Expected Map, got ${typeof value}`);
   }
-
-  // Encode entry count as varint
-  const countResult = tryEncodeVarintU64(BigInt(value.size));
-  if (!countResult.ok) {
-    return err(new SerializeError(`Failed to serialize map count: ${countResult.error.message}`));
+  if (schema.maxLength !== undefined && value.size > schema.maxLength) {
+    return new SerializeError(
+      `Map of ${String(value.size)} entries exceeds maxLength (${String(schema.maxLength)})`
+    );
   }
 
-  const parts: Uint8Array[] = [countResult.value.bytes];
-  let totalSize = countResult.value.bytesWritten;
-
-  // Serialize each key-value pair
+  // Entry count as varint, then each key-value pair
+  writer.writeVarint(value.size);
   let i = 0;
-  for (const [key, val] of value.entries()) {
-    // Serialize key
-    const keyResult = trySerialize(schema.key, key);
-    if (!keyResult.ok) {
-      return err(new SerializeError(`Failed to serialize map key ${String(i)}: ${keyResult.error.message}`));
-    }
-    parts.push(keyResult.value);
-    totalSize += keyResult.value.length;
-
-    // Serialize value
-    const valueResult = trySerialize(schema.value, val);
-    if (!valueResult.ok) {
-      return err(new SerializeError(`Failed to serialize map value ${String(i)}: ${valueResult.error.message}`));
+  for (const [key, val] of value) {
+    const error =
+      within(
+        `Failed to serialize map key ${String(i)}`,
+        write(schema.key, key, writer, options)
+      ) ??
+      within(
+        `Failed to serialize map value ${String(i)}`,
+        write(schema.value, val, writer, options)
+      );
+    if (error !== undefined) {
+      return error;
     }
-    parts.push(valueResult.value);
-    totalSize += valueResult.value.length;
-
     i++;
   }
-
-  // Combine all parts
-  const result = new Uint8Array(totalSize);
-  let offset = 0;
-  for (const part of parts) {
-    result.set(part, offset);
-    offset += part.length;
-  }
-
-  return ok(result);
+  return undefined;
 }
 
 // ============================================================================
 // STRUCT HANDLER
 // ============================================================================
 
-function serializeStruct<T extends Record<string, Schema>>(
-  schema: StructSchema<T>,
-  value: { [K in keyof T]: InferType<T[K]> }
-): Result<Uint8Array, SerializeError> {
-  const parts: Uint8Array[] = [];
-  let totalSize = 0;
-
-  // Serialize each field in order
-  for (const [fieldName, fieldSchema] of Object.entries(schema.fields)) {
+/**
+ * The fields of a struct or struct variant, in declaration order
+ */
+function serializeFields(
+  what: string,
+  fields: Record<string, Schema>,
+  value: Record<string, unknown>,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
+  const label = what.charAt(0).toUpperCase() + what.slice(1);
+  for (const [fieldName, fieldSchema] of Object.entries(fields)) {
     const fieldValue = value[fieldName];
     if (fieldValue === undefined) {
-      return err(new SerializeError(`Struct field '${fieldName}' is undefined`));
+      return new SerializeError(`${label} field '${fieldName}' is undefined`);
     }
 
-    const fieldResult = trySerialize(fieldSchema, fieldValue);
-    if (!fieldResult.ok) {
-      return err(new SerializeError(`Failed to serialize struct field '${fieldName}': ${fieldResult.error.message}`));
+    const error = within(
+      `Failed to serialize ${what} field '${fieldName}'`,
+      write(fieldSchema, fieldValue, writer, options)
+    );
+    if (error !== undefined) {
+      return error;
     }
-    parts.push(fieldResult.value);
-    totalSize += fieldResult.value.length;
   }
-
-  // Combine all parts
-  const result = new Uint8Array(totalSize);
-  let offset = 0;
-  for (const part of parts) {
-    result.set(part, offset);
-    offset += part.length;
-  }
-
-  return ok(result);
+  return undefined;
 }
 
 // ============================================================================
@@ -625,8 +476,10 @@ function serializeStruct<T extends Record<string, Schema>>(
 
 function serializeEnum(
   schema: EnumSchema<Record<string, EnumVariant>>,
-  value: unknown
-): Result<Uint8Array, SerializeError> {
+  value: unknown,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
   // Get variant type and find discriminant
   // eslint-disable-next-line @typescript-eslint/no-explicit-any, @typescript-eslint/no-unsafe-member-access
   const variantType = (value as any).type as string;
@@ -634,116 +487,93 @@ function serializeEnum(
   const discriminant = variantNames.indexOf(variantType);
 
   if (discriminant === -1) {
-    return err(new SerializeError(`Unknown variant type: ${String(variantType)}`));
-  }
-
-  // Encode discriminant as varint
-  const discriminantResult = tryEncodeVarintU32(discriminant);
-  if (!discriminantResult.ok) {
-    return err(new SerializeError(`Failed to serialize enum discriminant: ${discriminantResult.error.message}`));
+    return new SerializeError(`Unknown variant type: ${String(variantType)}`);
   }
 
   const variant = schema.variants[variantType];
   if (variant === undefined) {
-    return err(new SerializeError(`Variant '${String(variantType)}' not found in schema`));
+    return new SerializeError(`Variant '${String(variantType)}' not found in schema`);
   }
 
-  // Serialize variant based on its kind
+  // Discriminant as varint, then the variant's data
+  writer.writeVarint(discriminant);
   switch (variant.kind) {
-    case "unit_variant": {
+    case "unit_variant":
       // Unit variant has no additional data
-      return ok(discriminantResult.value.bytes);
-    }
+      return undefined;
 
     case "newtype_variant": {
       // Newtype variant contains a single value
       const variantValue = (value as { type: string; value: unknown }).value;
-      const valueResult = trySerialize(variant.inner, variantValue);
-      if (!valueResult.ok) {
-        return err(new SerializeError(`Failed to serialize newtype variant value: ${valueResult.error.message}`));
-      }
-
-      // Combine discriminant + value
-      const result = new Uint8Array(discriminantResult.value.bytesWritten + valueResult.value.length);
-      result.set(discriminantResult.value.bytes, 0);
-      result.set(valueResult.value, discriminantResult.value.bytesWritten);
-      return ok(result);
+      return within(
+        "Failed to serialize newtype variant value",
+        write(variant.inner, variantValue, writer, options)
+      );
     }
 
     case "tuple_variant": {
-      // Tuple variant contains tuple items
       const variantValue = (value as { type: string; value: unknown[] }).value;
-      const parts: Uint8Array[] = [discriminantResult.value.bytes];
-      let totalSize = discriminantResult.value.bytesWritten;
-
-      for (let i = 0; i < variant.items.length; i++) {
-        const itemSchema = variant.items[i];
-        const itemValue = variantValue[i];
-
-        if (itemSchema === undefined) {
-          return err(new SerializeError(`Tuple variant item ${String(i)} schema is undefined`));
-        }
-        if (itemValue === undefined) {
-          return err(new SerializeError(`Tuple variant item ${String(i)} value is undefined`));
-        }
-
-        const itemResult = trySerialize(itemSchema, itemValue);
-        if (!itemResult.ok) {
-          return err(new SerializeError(`Failed to serialize tuple variant item ${String(i)}: ${itemResult.error.message}`));
-        }
-        parts.push(itemResult.value);
-        totalSize += itemResult.value.length;
-      }
-
-      // Combine all parts
-      const result = new Uint8Array(totalSize);
-      let offset = 0;
-      for (const part of parts) {
-        result.set(part, offset);
-        offset += part.length;
-      }
-      return ok(result);
+      return serializeItems("tuple variant", variant.items, variantValue, writer, options);
     }
 
     case "struct_variant": {
-      // Struct variant contains named fields
       const variantValue = (value as { type: string; value: Record<string, unknown> }).value;
-      const parts: Uint8Array[] = [discriminantResult.value.bytes];
-      let totalSize = discriminantResult.value.bytesWritten;
-
-      for (const [fieldName, fieldSchema] of Object.entries(variant.fields)) {
-        const fieldValue = variantValue[fieldName];
-        if (fieldValue === undefined) {
-          return err(new SerializeError(`Struct variant field '${fieldName}' is undefined`));
-        }
-
-        const fieldResult = trySerialize(fieldSchema, fieldValue);
-        if (!fieldResult.ok) {
-          return err(new SerializeError(`Failed to serialize struct variant field '${fieldName}': ${fieldResult.error.message}`));
-        }
-        parts.push(fieldResult.value);
-        totalSize += fieldResult.value.length;
-      }
-
-      // Combine all parts
-      const result = new Uint8Array(totalSize);
-      let offset = 0;
-      for (const part of parts) {
-        result.set(part, offset);
-        offset += part.length;
-      }
-      return ok(result);
+      return serializeFields("struct variant", variant.fields, variantValue, writer, options);
     }
 
     default: {
       const _exhaustive: never = variant;
-      return err(new SerializeError(`Unknown variant kind: ${(_exhaustive as EnumVariant).kind}`));
+      return new SerializeError(`Unknown variant kind: ${(_exhaustive as EnumVariant).kind}`);
     }
   }
 }
 
 // ============================================================================
-// THROWING WRAPPER
+// TRANSFORM HANDLER
+// ============================================================================
+
+function serializeTransform(
+  schema: TransformSchema<Schema, unknown>,
+  value: unknown,
+  writer: Writer,
+  options: SerializeOptions
+): SerializeError | undefined {
+  // Convert to the inner representation, then encode that as usual
+  let innerValue: unknown;
+  try {
+    innerValue = schema.encode(value);
+  } catch (e) {
+    return new SerializeError(`Failed to convert value for serialization: ${e instanceof Error ? e.message : String(e)}`);
+  }
+
+  return within(
+    "Failed to serialize transformed value",
+    write(schema.inner, innerValue, writer, options)
+  );
+}
+
+// ============================================================================
+// EXTERNAL HANDLER
+// ============================================================================
+
+function serializeExternal(
+  schema: ExternalSchema<unknown>,
+  value: unknown,
+  writer: Writer
+): SerializeError | undefined {
+  // The codec writes the whole value, prefixes and all
+  let bytes: Uint8Array;
+  try {
+    bytes = schema.encode(value);
+  } catch (e) {
+    return new SerializeError(`Failed to encode ${schema.name}: ${e instanceof Error ? e.message : String(e)}`);
+  }
+  writer.writeBytes(bytes);
+  return undefined;
+}
+
+// ============================================================================
+// THROWING WRAPPERS
 // ============================================================================
 
 /**
@@ -753,8 +583,23 @@ function serializeEnum(
  * Note: value is unknown for maximum flexibility - the schema validates the structure
  */
 export function serialize<S extends Schema>(
-  schema: S,
-  value: unknown
+  schema: S & { readonly direction?: "encode" },
+  value: unknown,
+  options?: SerializeOptions
 ): Uint8Array {
-  return unwrap(trySerialize(schema, value));
+  return unwrap(trySerialize(schema, value, options));
+}
+
+/**
+ * Serialize data into `writer` (throwing API), returning the bytes written
+ *
+ * Throws SerializeError on failure, leaving the writer as it was
+ */
+export function serializeInto<S extends Schema>(
+  schema: S & { readonly direction?: "encode" },
+  value: unknown,
+  writer: Writer,
+  options?: SerializeOptions
+): number {
+  return unwrap(trySerializeInto(schema, value, writer, options));
 }
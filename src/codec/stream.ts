@@ -0,0 +1,463 @@
+/**
+ * Decoding values from a chunked byte stream
+ *
+ * Web Serial, WebSocket and BLE deliver bytes in chunks that have nothing to
+ * do with value boundaries: one chunk may hold half a value, the next the
+ * rest of it and the start of another. A StreamDecoder buffers chunks and
+ * yields each value once all of its bytes have arrived:
+ *
+ *   const decoder = new StreamDecoder(ReadingSchema);
+ *   port.onChunk = (chunk) => {
+ *     decoder.push(chunk);
+ *     for (const reading of decoder) {
+ *       plot(reading);
+ *     }
+ *   };
+ *
+ * Values are expected back to back, as postcard writes them with no framing;
+ * for COBS-framed streams, use `CobsAccumulator` instead.
+ *
+ * Each chunk resumes scanning the pending value where the previous one left
+ * off, following the schema through length prefixes, option tags and enum
+ * discriminants without building anything, and the value is decoded once
+ * the scan reaches its end. A large value trickling in one byte at a time
+ * thus costs as much as one arriving whole. Values the scan cannot follow,
+ * those of `external()` schemas, are instead decoded from their first byte
+ * at every chunk until decoding no longer runs out of bytes (UNEXPECTED_END).
+ */
+
+import { err, ok, unwrap, type Result } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import {
+  tuple,
+  type EnumSchema,
+  type EnumVariant,
+  type InferType,
+  type MapSchema,
+  type NewtypeStructSchema,
+  type OptionSchema,
+  type Schema,
+  type SeqSchema,
+  type StructSchema,
+  type TransformSchema,
+  type TupleSchema,
+} from "../types/schema.js";
+import {
+  DeserializeError,
+  tryDeserialize,
+  type DecodeLimits,
+  type DeserializeOptions,
+} from "./deserializer.js";
+
+export interface StreamDecoderOptions extends DeserializeOptions {
+  /**
+   * Upper bound on the bytes buffered for a value that is still incomplete
+   * (default: unbounded). Exceeding it fails with code BUFFER_FULL; without
+   * it, a corrupt length prefix would buffer forever.
+   */
+  readonly maxBuffered?: number;
+}
+
+export class StreamDecoder<S extends Schema> {
+  readonly schema: S;
+  readonly maxBuffered: number;
+
+  private readonly options: DeserializeOptions;
+  // Unconsumed bytes are buffer[start..end]
+  private buffer = new Uint8Array(0);
+  private start = 0;
+  private end = 0;
+  private failure: DeserializeError | undefined;
+  private readonly scanner: ValueScanner;
+
+  constructor(schema: S, options: StreamDecoderOptions = {}) {
+    const { maxBuffered = Infinity, ...deserializeOptions } = options;
+    if (!(maxBuffered > 0)) {
+      throw new RangeError(`maxBuffered must be positive, got ${String(maxBuffered)}`);
+    }
+    this.schema = schema;
+    this.maxBuffered = maxBuffered;
+    this.options = deserializeOptions;
+    this.scanner = new ValueScanner(schema, deserializeOptions.limits);
+  }
+
+  /** Bytes received but not yet decoded into a value */
+  get buffered(): number {
+    return this.end - this.start;
+  }
+
+  /**
+   * Append a chunk of the stream
+   *
+   * The chunk is copied, so the caller may reuse its buffer.
+   */
+  push(chunk: Uint8Array): void {
+    if (chunk.length === 0) {
+      return;
+    }
+    if (this.end + chunk.length > this.buffer.length) {
+      const pending = this.buffer.subarray(this.start, this.end);
+      const grown =
+        pending.length + chunk.length > this.buffer.length
+          ? new Uint8Array(Math.max(2 * this.buffer.length, pending.length + chunk.length))
+          : this.buffer;
+      grown.set(pending, 0);
+      this.buffer = grown;
+      this.end = pending.length;
+      this.start = 0;
+    }
+    this.buffer.set(chunk, this.end);
+    this.end += chunk.length;
+  }
+
+  /**
+   * The next complete value, or undefined until more bytes arrive (Result API)
+   *
+   * Any error other than running out of bytes means the stream cannot be
+   * decoded past this point: with no framing there is no telling where the
+   * next value starts. The decoder keeps returning that error until
+   * `reset()`.
+   */
+  tryNext(): Result<InferType<S> | undefined, DeserializeError> {
+    if (this.failure !== undefined) {
+      return err(this.failure);
+    }
+    if (this.start === this.end) {
+      return ok(undefined);
+    }
+    if (!this.scanner.ready(this.buffer.subarray(this.start, this.end))) {
+      return this.incomplete();
+    }
+
+    const data = this.buffer.subarray(0, this.end);
+    const result = tryDeserialize(this.schema, data, this.start, this.options);
+    if (result.ok && result.value.bytesRead === 0) {
+      // Would yield the same value forever without consuming anything
+      this.failure = new DeserializeError(
+        "Values that take no bytes cannot be decoded from a stream",
+        ErrorCode.Unsupported
+      );
+      return err(this.failure);
+    }
+    if (result.ok) {
+      this.scanner.reset();
+      this.start += result.value.bytesRead;
+      if (this.start === this.end) {
+        this.start = 0;
+        this.end = 0;
+      }
+      return ok(result.value.value);
+    }
+    if (result.error.code !== ErrorCode.UnexpectedEnd) {
+      this.failure = result.error;
+      return err(result.error);
+    }
+    this.scanner.lose();
+    return this.incomplete();
+  }
+
+  /** Wait for more bytes, unless the pending value already has too many */
+  private incomplete(): Result<undefined, DeserializeError> {
+    if (this.buffered > this.maxBuffered) {
+      const sizes = `${String(this.buffered)} > ${String(this.maxBuffered)} bytes`;
+      this.failure = new DeserializeError(
+        `Incomplete value exceeds maxBuffered (${sizes})`,
+        ErrorCode.BufferFull
+      );
+      return err(this.failure);
+    }
+    return ok(undefined);
+  }
+
+  /**
+   * The next complete value, or undefined until more bytes arrive (throwing API)
+   *
+   * Throws DeserializeError if the stream cannot be decoded
+   */
+  next(): InferType<S> | undefined {
+    return unwrap(this.tryNext());
+  }
+
+  /**
+   * Every value that is complete so far (Result API)
+   *
+   * On reaching bytes that cannot be decoded, returns the values before them;
+   * the error follows on the next call, as it does from `tryNext()`.
+   */
+  tryDrain(): Result<InferType<S>[], DeserializeError> {
+    const values: InferType<S>[] = [];
+    for (;;) {
+      const next = this.tryNext();
+      if (!next.ok) {
+        return values.length > 0 ? ok(values) : next;
+      }
+      if (next.value === undefined) {
+        return ok(values);
+      }
+      values.push(next.value);
+    }
+  }
+
+  /**
+   * Every value that is complete so far
+   *
+   * Throws DeserializeError on reaching bytes that cannot be decoded, after
+   * yielding the values before them.
+   */
+  *[Symbol.iterator](): IterableIterator<InferType<S>> {
+    for (let value = this.next(); value !== undefined; value = this.next()) {
+      yield value;
+    }
+  }
+
+  /**
+   * Drop buffered bytes and any error, e.g. after reconnecting
+   */
+  reset(): void {
+    this.start = 0;
+    this.end = 0;
+    this.failure = undefined;
+    this.scanner.reset();
+  }
+}
+
+/** Values still to be scanned: `count` of `schema`, or `bytes` to skip */
+type Pending = { readonly schema: Schema; count: number } | { bytes: number };
+
+/** What scanning one value at the start of the unscanned bytes found */
+type Step = { readonly read: number; readonly then?: readonly Pending[] } | "more" | "lost";
+
+// Longest varint of each integer kind, as the decoder reads them
+const VARINT_BYTES = {
+  i16: 3,
+  u16: 3,
+  i32: 5,
+  u32: 5,
+  i64: 10,
+  u64: 10,
+  i128: 19,
+  u128: 19,
+} as const;
+const LENGTH_BYTES = 10;
+const DISCRIMINANT_BYTES = 5;
+
+/**
+ * Finds where the pending value of a stream ends, keeping its place between
+ * chunks
+ *
+ * It only reads what decides the value's length. It loses track, leaving the
+ * value to the decoder, at bytes the decoder rejects outright (a bad option
+ * tag or enum discriminant, a length over a limit) so that those errors
+ * surface as soon as their bytes arrive, and at values it cannot follow.
+ */
+class ValueScanner {
+  private pending: Pending[] = [];
+  // Bytes of the pending value scanned so far
+  private scanned = 0;
+  private lost = false;
+
+  constructor(
+    private readonly schema: Schema,
+    private readonly limits: DecodeLimits | undefined
+  ) {
+    this.reset();
+  }
+
+  /** Start over at the next value */
+  reset(): void {
+    this.pending = [{ schema: this.schema, count: 1 }];
+    this.scanned = 0;
+    this.lost = false;
+  }
+
+  /** Leave the pending value to the decoder, until reset() */
+  lose(): void {
+    this.lost = true;
+  }
+
+  /**
+   * Whether the pending value, starting `data`, is worth decoding: all of it
+   * has arrived, or the scan lost track of it
+   */
+  ready(data: Uint8Array): boolean {
+    while (!this.lost) {
+      const top = this.pending[this.pending.length - 1];
+      if (top === undefined) {
+        return true;
+      }
+      if ("bytes" in top) {
+        const skipped = Math.min(top.bytes, data.length - this.scanned);
+        this.scanned += skipped;
+        top.bytes -= skipped;
+        if (top.bytes > 0) {
+          return false;
+        }
+        this.pending.pop();
+        continue;
+      }
+
+      const step = this.step(top.schema, data, this.scanned);
+      if (step === "more") {
+        return false;
+      }
+      if (step === "lost") {
+        this.lost = true;
+        break;
+      }
+      if (--top.count === 0) {
+        this.pending.pop();
+      }
+      this.scanned += step.read;
+      // What it holds is scanned next, in order
+      this.pending.push(...(step.then ?? []).slice().reverse());
+    }
+    return true;
+  }
+
+  private step(schema: Schema, data: Uint8Array, offset: number): Step {
+    const available = data.length - offset;
+    switch (schema.kind) {
+      case "bool": {
+        const byte = data[offset];
+        return byte === undefined ? "more" : byte > 1 ? "lost" : { read: 1 };
+      }
+      case "i8":
+      case "u8":
+        return available < 1 ? "more" : { read: 1 };
+      case "f32":
+        return available < 4 ? "more" : { read: 4 };
+      case "f64":
+        return available < 8 ? "more" : { read: 8 };
+      case "i16":
+      case "u16":
+      case "i32":
+      case "u32":
+      case "i64":
+      case "u64":
+      case "i128":
+      case "u128": {
+        const varint = scanVarint(data, offset, VARINT_BYTES[schema.kind]);
+        return typeof varint === "string" ? varint : { read: varint.read };
+      }
+      case "char":
+      case "string":
+      case "bytes": {
+        const length = scanVarint(data, offset, LENGTH_BYTES);
+        if (typeof length === "string") {
+          return length;
+        }
+        if (
+          (schema.kind !== "char" && exceeds(length.value, this.limits?.maxBytes)) ||
+          (schema.kind === "string" && exceeds(length.value, schema.maxBytes))
+        ) {
+          return "lost";
+        }
+        return { read: length.read, then: [{ bytes: length.value }] };
+      }
+      case "option": {
+        const tag = data[offset];
+        if (tag === undefined) {
+          return "more";
+        }
+        const { inner } = schema as OptionSchema<Schema>;
+        return tag === 0 ? { read: 1 } : tag === 1 ? { read: 1, then: values([inner]) } : "lost";
+      }
+      case "unit":
+      case "unit_struct":
+        return { read: 0 };
+      case "newtype_struct":
+        return { read: 0, then: values([(schema as NewtypeStructSchema<Schema>).inner]) };
+      case "transform":
+        return { read: 0, then: values([(schema as TransformSchema<Schema, unknown>).inner]) };
+      case "lazy":
+        return { read: 0, then: values([schema.get()]) };
+      case "tuple":
+      case "tuple_struct":
+        return { read: 0, then: values((schema as TupleSchema<readonly Schema[]>).items) };
+      case "struct": {
+        const { fields } = schema as StructSchema<Record<string, Schema>>;
+        return { read: 0, then: values(Object.values(fields)) };
+      }
+      case "seq":
+      case "map": {
+        const length = scanVarint(data, offset, LENGTH_BYTES);
+        if (typeof length === "string") {
+          return length;
+        }
+        const collection = schema as SeqSchema<Schema> | MapSchema<Schema, Schema>;
+        if (
+          exceeds(length.value, this.limits?.maxLength) ||
+          exceeds(length.value, collection.maxLength)
+        ) {
+          return "lost";
+        }
+        if (length.value === 0) {
+          return { read: length.read };
+        }
+        // A map entry is scanned as its key followed by its value
+        const item =
+          collection.kind === "seq" ? collection.item : tuple(collection.key, collection.value);
+        return { read: length.read, then: [{ schema: item, count: length.value }] };
+      }
+      case "enum": {
+        const discriminant = scanVarint(data, offset, DISCRIMINANT_BYTES);
+        if (typeof discriminant === "string") {
+          return discriminant;
+        }
+        const { variants } = schema as EnumSchema<Record<string, EnumVariant>>;
+        const variant = Object.values(variants)[discriminant.value];
+        const read = discriminant.read;
+        switch (variant?.kind) {
+          case "unit_variant":
+            return { read };
+          case "newtype_variant":
+            return { read, then: values([variant.inner]) };
+          case "tuple_variant":
+            return { read, then: values(variant.items) };
+          case "struct_variant":
+            return { read, then: values(Object.values(variant.fields)) };
+          default:
+            return "lost";
+        }
+      }
+      default:
+        // never(), which the decoder rejects, and external(), whose bytes
+        // only its codec knows
+        return "lost";
+    }
+  }
+}
+
+
+/** One of each of `schemas`, to scan in order */
+function values(schemas: readonly Schema[]): Pending[] {
+  return schemas.map((schema) => ({ schema, count: 1 }));
+}
+
+/** Whether a length crosses an optional limit */
+function exceeds(length: number, limit: number | undefined): boolean {
+  return limit !== undefined && length > limit;
+}
+
+/**
+ * The varint at `offset` and its length, "more" if it runs past the data, or
+ * "lost" if it runs past `maxBytes`
+ */
+function scanVarint(
+  data: Uint8Array,
+  offset: number,
+  maxBytes: number
+): { readonly value: number; readonly read: number } | "more" | "lost" {
+  let value = 0;
+  for (let i = 0; i < maxBytes; i++) {
+    const byte = data[offset + i];
+    if (byte === undefined) {
+      return "more";
+    }
+    value += (byte & 0x7f) * 2 ** (7 * i);
+    if (byte < 0x80) {
+      return { value, read: i + 1 };
+    }
+  }
+  return "lost";
+}
@@ -10,13 +10,16 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import { PostcardDecodeError, ValidationError } from "../types/errors.js";
+import { Writer } from "./writer.js";
 
 /**
  * Error thrown when varint decoding fails
  */
-export class VarintDecodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class VarintDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.BadVarint) {
+    super(message, code);
     this.name = "VarintDecodeError";
   }
 }
@@ -93,7 +96,7 @@ export function tryDecodeVarintU16(
   offset = 0
 ): Result<VarintDecodeResult<number>, VarintDecodeError> {
   if (offset < 0 || offset >= data.length) {
-    return err(new VarintDecodeError("Offset out of bounds"));
+    return err(new VarintDecodeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
   let result = 0;
@@ -102,12 +105,12 @@ export function tryDecodeVarintU16(
 
   while (bytesRead < MAX_ENCODED_LEN.u16) {
     if (offset + bytesRead >= data.length) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     const byte = data[offset + bytesRead];
     if (byte === undefined) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     bytesRead++;
@@ -139,7 +142,7 @@ export function tryDecodeVarintU32(
   offset = 0
 ): Result<VarintDecodeResult<number>, VarintDecodeError> {
   if (offset < 0 || offset >= data.length) {
-    return err(new VarintDecodeError("Offset out of bounds"));
+    return err(new VarintDecodeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
   let result = 0;
@@ -148,12 +151,12 @@ export function tryDecodeVarintU32(
 
   while (bytesRead < MAX_ENCODED_LEN.u32) {
     if (offset + bytesRead >= data.length) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     const byte = data[offset + bytesRead];
     if (byte === undefined) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     bytesRead++;
@@ -186,7 +189,7 @@ export function tryDecodeVarintU64(
   offset = 0
 ): Result<VarintDecodeResult<bigint>, VarintDecodeError> {
   if (offset < 0 || offset >= data.length) {
-    return err(new VarintDecodeError("Offset out of bounds"));
+    return err(new VarintDecodeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
   let result = 0n;
@@ -195,12 +198,12 @@ export function tryDecodeVarintU64(
 
   while (bytesRead < MAX_ENCODED_LEN.u64) {
     if (offset + bytesRead >= data.length) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     const byte = data[offset + bytesRead];
     if (byte === undefined) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     bytesRead++;
@@ -232,7 +235,7 @@ export function tryDecodeVarintU128(
   offset = 0
 ): Result<VarintDecodeResult<bigint>, VarintDecodeError> {
   if (offset < 0 || offset >= data.length) {
-    return err(new VarintDecodeError("Offset out of bounds"));
+    return err(new VarintDecodeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
   let result = 0n;
@@ -241,12 +244,12 @@ export function tryDecodeVarintU128(
 
   while (bytesRead < MAX_ENCODED_LEN.u128) {
     if (offset + bytesRead >= data.length) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     const byte = data[offset + bytesRead];
     if (byte === undefined) {
-      return err(new VarintDecodeError("Unexpected end of data"));
+      return err(new VarintDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
     }
 
     bytesRead++;
@@ -369,9 +372,9 @@ export function tryDecodeVarintI128(
 /**
  * Error thrown when varint encoding fails
  */
-export class VarintEncodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class VarintEncodeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "VarintEncodeError";
   }
 }
@@ -389,28 +392,94 @@ export interface VarintEncodeResult {
 // ============================================================================
 
 /**
- * Encode an unsigned 16-bit varint
+ * 64- and 128-bit integers must be bigints: a number above 2^53 has already
+ * lost its low bits, and mixing the two in the shifts below throws a TypeError
  */
-export function tryEncodeVarintU16(
-  value: number
+function notBigInt(value: unknown, kind: string): VarintEncodeError {
+  return new VarintEncodeError(`Expected a bigint for ${kind}, got ${typeof value}`);
+}
+
+function outOfRange(value: number | bigint, kind: string): VarintEncodeError {
+  return new VarintEncodeError(`Value ${String(value)} is out of range for ${kind}`);
+}
+
+/**
+ * Encode `value` on its own with one of the `writeVarint*` functions
+ */
+function encodeWith<T>(
+  write: (writer: Writer, value: T) => VarintEncodeError | undefined,
+  value: T
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
+  // Room for the longest varint, so the writer never grows
+  const writer = new Writer(MAX_ENCODED_LEN.u128);
+  const error = write(writer, value);
+  if (error !== undefined) {
+    return err(error);
+  }
+  return ok({ bytes: writer.toBytes(), bytesWritten: writer.length });
+}
+
+/**
+ * Write an unsigned 16-bit varint, returning the error instead if `value` is
+ * out of range
+ */
+export function writeVarintU16(writer: Writer, value: number): VarintEncodeError | undefined {
   if (value < 0 || value > 0xffff) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for u16`));
+    return outOfRange(value, "u16");
   }
+  writer.writeVarint(value);
+  return undefined;
+}
 
-  const bytes: number[] = [];
-  let n = value;
+/**
+ * Write an unsigned 32-bit varint, returning the error instead if `value` is
+ * out of range
+ */
+export function writeVarintU32(writer: Writer, value: number): VarintEncodeError | undefined {
+  if (value < 0 || value > 0xffffffff) {
+    return outOfRange(value, "u32");
+  }
+  writer.writeVarint(value);
+  return undefined;
+}
+
+/**
+ * Write an unsigned 64-bit varint, returning the error instead if `value` is
+ * not a bigint in range
+ */
+export function writeVarintU64(writer: Writer, value: bigint): VarintEncodeError | undefined {
+  if (typeof value !== "bigint") {
+    return notBigInt(value, "u64");
+  }
+  if (value < 0n || value > 0xffff_ffff_ffff_ffffn) {
+    return outOfRange(value, "u64");
+  }
+  writer.writeVarintBigInt(value);
+  return undefined;
+}
 
-  while (n > 0x7f) {
-    // Write 7 data bits with continuation flag
-    bytes.push((n & 0x7f) | 0x80);
-    n >>>= 7;
+/**
+ * Write an unsigned 128-bit varint, returning the error instead if `value` is
+ * not a bigint in range
+ */
+export function writeVarintU128(writer: Writer, value: bigint): VarintEncodeError | undefined {
+  if (typeof value !== "bigint") {
+    return notBigInt(value, "u128");
+  }
+  if (value < 0n || value > (1n << 128n) - 1n) {
+    return outOfRange(value, "u128");
   }
-  // Write final byte without continuation flag
-  bytes.push(n & 0x7f);
+  writer.writeVarintBigInt(value);
+  return undefined;
+}
 
-  return ok({ bytes: new Uint8Array(bytes), bytesWritten: bytes.length });
+/**
+ * Encode an unsigned 16-bit varint
+ */
+export function tryEncodeVarintU16(
+  value: number
+): Result<VarintEncodeResult, VarintEncodeError> {
+  return encodeWith(writeVarintU16, value);
 }
 
 /**
@@ -419,21 +488,7 @@ export function tryEncodeVarintU16(
 export function tryEncodeVarintU32(
   value: number
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  if (value < 0 || value > 0xffffffff) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for u32`));
-  }
-
-  const bytes: number[] = [];
-  let n = value >>> 0; // Convert to unsigned 32-bit
-
-  while (n > 0x7f) {
-    bytes.push((n & 0x7f) | 0x80);
-    n >>>= 7;
-  }
-  bytes.push(n & 0x7f);
-
-  return ok({ bytes: new Uint8Array(bytes), bytesWritten: bytes.length });
+  return encodeWith(writeVarintU32, value);
 }
 
 /**
@@ -442,21 +497,7 @@ export function tryEncodeVarintU32(
 export function tryEncodeVarintU64(
   value: bigint
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  if (value < 0n || value > 0xffff_ffff_ffff_ffffn) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for u64`));
-  }
-
-  const bytes: number[] = [];
-  let n = value;
-
-  while (n > 0x7fn) {
-    bytes.push(Number(n & 0x7fn) | 0x80);
-    n >>= 7n;
-  }
-  bytes.push(Number(n & 0x7fn));
-
-  return ok({ bytes: new Uint8Array(bytes), bytesWritten: bytes.length });
+  return encodeWith(writeVarintU64, value);
 }
 
 /**
@@ -465,27 +506,66 @@ export function tryEncodeVarintU64(
 export function tryEncodeVarintU128(
   value: bigint
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  const maxU128 = (1n << 128n) - 1n;
-  if (value < 0n || value > maxU128) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for u128`));
-  }
+  return encodeWith(writeVarintU128, value);
+}
 
-  const bytes: number[] = [];
-  let n = value;
+// ============================================================================
+// SIGNED VARINT ENCODERS (with zigzag)
+// ============================================================================
 
-  while (n > 0x7fn) {
-    bytes.push(Number(n & 0x7fn) | 0x80);
-    n >>= 7n;
+/**
+ * Write a signed 16-bit varint (zigzag encoded), returning the error instead
+ * if `value` is out of range
+ */
+export function writeVarintI16(writer: Writer, value: number): VarintEncodeError | undefined {
+  if (value < -32768 || value > 32767) {
+    return outOfRange(value, "i16");
   }
-  bytes.push(Number(n & 0x7fn));
+  writer.writeVarint(zigzagEncodeI32(value));
+  return undefined;
+}
 
-  return ok({ bytes: new Uint8Array(bytes), bytesWritten: bytes.length });
+/**
+ * Write a signed 32-bit varint (zigzag encoded), returning the error instead
+ * if `value` is out of range
+ */
+export function writeVarintI32(writer: Writer, value: number): VarintEncodeError | undefined {
+  if (value < -2147483648 || value > 2147483647) {
+    return outOfRange(value, "i32");
+  }
+  writer.writeVarint(zigzagEncodeI32(value) >>> 0);
+  return undefined;
 }
 
-// ============================================================================
-// SIGNED VARINT ENCODERS (with zigzag)
-// ============================================================================
+/**
+ * Write a signed 64-bit varint (zigzag encoded), returning the error instead
+ * if `value` is not a bigint in range
+ */
+export function writeVarintI64(writer: Writer, value: bigint): VarintEncodeError | undefined {
+  if (typeof value !== "bigint") {
+    return notBigInt(value, "i64");
+  }
+  if (value < -(1n << 63n) || value > (1n << 63n) - 1n) {
+    return outOfRange(value, "i64");
+  }
+  writer.writeVarintBigInt(zigzagEncodeBigInt(value));
+  return undefined;
+}
+
+/**
+ * Write a signed 128-bit varint (zigzag encoded), returning the error instead
+ * if `value` is not a bigint in range
+ */
+export function writeVarintI128(writer: Writer, value: bigint): VarintEncodeError | undefined {
+  if (typeof value !== "bigint") {
+    return notBigInt(value, "i128");
+  }
+  if (value < -(1n << 127n) || value > (1n << 127n) - 1n) {
+    return outOfRange(value, "i128");
+  }
+  writer.writeVarintBigInt(zigzagEncodeBigInt(value));
+  return undefined;
+}
 
 /**
  * Encode a signed 16-bit varint (zigzag encoded)
@@ -493,13 +573,7 @@ export function tryEncodeVarintU128(
 export function tryEncodeVarintI16(
   value: number
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  if (value < -32768 || value > 32767) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for i16`));
-  }
-
-  const encoded = zigzagEncodeI32(value);
-  return tryEncodeVarintU16(encoded);
+  return encodeWith(writeVarintI16, value);
 }
 
 /**
@@ -508,15 +582,7 @@ export function tryEncodeVarintI16(
 export function tryEncodeVarintI32(
   value: number
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  const minI32 = -2147483648;
-  const maxI32 = 2147483647;
-  if (value < minI32 || value > maxI32) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for i32`));
-  }
-
-  const encoded = zigzagEncodeI32(value);
-  return tryEncodeVarintU32(encoded >>> 0); // Convert to unsigned
+  return encodeWith(writeVarintI32, value);
 }
 
 /**
@@ -525,15 +591,7 @@ export function tryEncodeVarintI32(
 export function tryEncodeVarintI64(
   value: bigint
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  const minI64 = -(1n << 63n);
-  const maxI64 = (1n << 63n) - 1n;
-  if (value < minI64 || value > maxI64) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for i64`));
-  }
-
-  const encoded = zigzagEncodeBigInt(value);
-  return tryEncodeVarintU64(encoded);
+  return encodeWith(writeVarintI64, value);
 }
 
 /**
@@ -542,15 +600,7 @@ export function tryEncodeVarintI64(
 export function tryEncodeVarintI128(
   value: bigint
 ): Result<VarintEncodeResult, VarintEncodeError> {
-  // Validate range
-  const minI128 = -(1n << 127n);
-  const maxI128 = (1n << 127n) - 1n;
-  if (value < minI128 || value > maxI128) {
-    return err(new VarintEncodeError(`Value ${String(value)} is out of range for i128`));
-  }
-
-  const encoded = zigzagEncodeBigInt(value);
-  return tryEncodeVarintU128(encoded);
+  return encodeWith(writeVarintI128, value);
 }
 
 // ============================================================================
@@ -0,0 +1,197 @@
+/**
+ * Routing of incoming messages to handlers by enum variant
+ *
+ * A device usually sends one top-level enum, a variant per kind of message,
+ * back to back on one link. A MessageDispatcher splits the stream into
+ * frames, decodes each one and calls the handlers of its variant, with the
+ * message narrowed to that variant:
+ *
+ *   const events = new MessageDispatcher(GameEventSchema);
+ *   events.on("EnemySpawn", (event) => spawn(event.value.enemy_type, event.value.count));
+ *   events.on("LocationDiscovered", (event) => map.reveal(event.value));
+ *   socket.onmessage = (event) => events.push(new Uint8Array(event.data));
+ *
+ * Frames are length-prefixed by default, or COBS frames. `postcard-ts
+ * generate --dispatchers` writes a subclass per top-level enum
+ * (`GameEventDispatcher`), which also knows the enum tag the types were
+ * generated with.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import type { DeserializeError, DeserializeOptions } from "../codec/deserializer.js";
+import { CobsAccumulator } from "../codec/cobs.js";
+import { LengthPrefixAccumulator } from "../codec/length-prefix.js";
+
+/**
+ * How messages are delimited on the stream: `length-prefix` for a varint of
+ * the length before each message, `cobs` for COBS frames
+ */
+export type Framing = "length-prefix" | "cobs";
+
+/**
+ * The variants of `T`, a union tagged by its `D` property
+ */
+export type VariantOf<T, D extends string = "type"> = T extends {
+  readonly [P in D]: infer K extends string;
+}
+  ? K
+  : never;
+
+/**
+ * The messages of `T` of variant `K`
+ */
+export type VariantMessage<T, K extends string, D extends string = "type"> = Extract<
+  T,
+  { readonly [P in D]: K }
+>;
+
+export interface MessageDispatcherOptions<D extends string = "type"> extends DeserializeOptions {
+  /**
+   * Property holding the variant, as given to `discriminated()` (default:
+   * `type`, of plain enums)
+   */
+  readonly tag?: D;
+  /** How messages are delimited (default: `length-prefix`) */
+  readonly framing?: Framing;
+  /**
+   * Longest frame accepted; longer frames fail with code BUFFER_FULL (default:
+   * unbounded)
+   */
+  readonly maxFrameLength?: number;
+  /** Called with each message of a variant without handlers (default: none) */
+  readonly onUnhandled?: (message: unknown) => void;
+  /**
+   * Called with the DeserializeError of each frame that does not decode,
+   * which is dropped (default: the error is thrown from `push()`)
+   */
+  readonly onError?: (error: DeserializeError) => void;
+}
+
+type AnyHandler = (message: never) => void;
+
+export class MessageDispatcher<S extends Schema, D extends string = "type"> {
+  readonly schema: S;
+  readonly tag: D;
+
+  private readonly frames: LengthPrefixAccumulator<S> | CobsAccumulator<S>;
+  private readonly onUnhandled: ((message: unknown) => void) | undefined;
+  private readonly onError: ((error: DeserializeError) => void) | undefined;
+  private readonly handlers = new Map<string, AnyHandler[]>();
+  private failed: DeserializeError | undefined;
+
+  /**
+   * Throws RangeError for an unknown framing or a maxFrameLength it does not
+   * take
+   */
+  constructor(schema: S, options: MessageDispatcherOptions<D> = {}) {
+    const {
+      tag = "type" as D,
+      framing = "length-prefix",
+      maxFrameLength,
+      onUnhandled,
+      onError,
+      ...deserializeOptions
+    } = options;
+    const accumulatorOptions =
+      maxFrameLength === undefined ? deserializeOptions : { ...deserializeOptions, maxFrameLength };
+    switch (framing) {
+      case "length-prefix":
+        this.frames = new LengthPrefixAccumulator(schema, accumulatorOptions);
+        break;
+      case "cobs":
+        this.frames = new CobsAccumulator(schema, accumulatorOptions);
+        break;
+      default:
+        throw new RangeError(`Unknown framing: ${String(framing)}`);
+    }
+    this.schema = schema;
+    this.tag = tag;
+    this.onUnhandled = onUnhandled;
+    this.onError = onError;
+  }
+
+  /**
+   * Call `handler` with every message of `variant`, after the handlers
+   * already added for it
+   *
+   * Returns a function that removes the handler.
+   */
+  on<K extends VariantOf<InferType<S>, D>>(
+    variant: K,
+    handler: (message: VariantMessage<InferType<S>, K, D>) => void
+  ): () => void {
+    const handlers = this.handlers.get(variant) ?? [];
+    this.handlers.set(variant, [...handlers, handler]);
+    return () => {
+      const remaining = (this.handlers.get(variant) ?? []).filter((other) => other !== handler);
+      if (remaining.length === 0) {
+        this.handlers.delete(variant);
+      } else {
+        this.handlers.set(variant, remaining);
+      }
+    };
+  }
+
+  /**
+   * Call the handlers of a decoded message's variant
+   *
+   * Returns whether it had any; messages without go to `onUnhandled`. Throws
+   * whatever a handler throws, skipping the handlers after it.
+   */
+  dispatch(message: InferType<S>): boolean {
+    const handlers = this.handlers.get(this.variantOf(message));
+    if (handlers === undefined) {
+      this.onUnhandled?.(message);
+      return false;
+    }
+    for (const handler of handlers) {
+      (handler as (message: InferType<S>) => void)(message);
+    }
+    return true;
+  }
+
+  /**
+   * Append a chunk of the stream, dispatching every message it completes
+   *
+   * The chunk is copied, so the caller may reuse its buffer. Throws the
+   * DeserializeError of a frame that does not decode, without `onError`, or
+   * whatever a handler throws; the messages after it are dispatched by the
+   * next call.
+   */
+  push(chunk: Uint8Array): void {
+    this.frames.push(chunk);
+    for (let r = this.frames.tryNext(); !r.ok || r.value !== undefined; r = this.frames.tryNext()) {
+      if (r.ok) {
+        this.dispatch(r.value);
+        continue;
+      }
+      // A malformed length prefix fails every call until reset; report it once
+      if (r.error === this.failed) {
+        return;
+      }
+      this.failed = r.error;
+      if (this.onError === undefined) {
+        throw r.error;
+      }
+      this.onError(r.error);
+    }
+  }
+
+  /**
+   * Drop the partial frame, e.g. after reconnecting
+   */
+  reset(): void {
+    this.frames.reset();
+    this.failed = undefined;
+  }
+
+  private variantOf(message: unknown): string {
+    if (typeof message === "object" && message !== null && this.tag in message) {
+      const variant = (message as Record<string, unknown>)[this.tag];
+      if (typeof variant === "string") {
+        return variant;
+      }
+    }
+    return "";
+  }
+}
@@ -0,0 +1,199 @@
+/**
+ * Capability masks exchanged when a device connects
+ *
+ * Devices in a fleet run different firmware, so a dashboard must not send a
+ * message a device cannot parse. Each device sends a `u64` mask of its
+ * features when it connects, and a FeatureSet reads it against the features
+ * the protocol declares, `PROTOCOL_FEATURES` in code generated with
+ * `Generator::feature`:
+ *
+ *   const features = new FeatureSet(PROTOCOL_FEATURES, hello.features);
+ *   if (features.hasFeature("firmware_update")) showUpdateButton();
+ *   port.write(features.serialize("Command", CommandSchema, command));
+ *
+ * A feature lists the messages needing it, types (`FirmwareChunk`) or enum
+ * variants (`Command::Update`), and the FeatureSet refuses to encode them for
+ * a device without it, with a FeatureError instead of bytes the device would
+ * reject or misread.
+ */
+
+import { type Result, err, unwrap } from "../types/result.js";
+import { type Schema, u64 } from "../types/schema.js";
+import { PostcardError } from "../types/errors.js";
+import {
+  type SerializeError,
+  type SerializeOptions,
+  trySerialize,
+} from "../codec/serializer.js";
+
+/**
+ * A feature: its bit in the capability mask and the messages needing it
+ */
+export interface FeatureDefinition {
+  readonly bit: number;
+  /** Types (`FirmwareChunk`) or enum variants (`Command::Update`) */
+  readonly messages: readonly string[];
+}
+
+/**
+ * The features of a protocol by name, as `PROTOCOL_FEATURES`
+ */
+export type FeatureDefinitions = Readonly<Record<string, FeatureDefinition>>;
+
+/**
+ * The schema of a capability mask on the wire
+ */
+export const FeatureMaskSchema = u64();
+
+/**
+ * A message needing a feature the peer does not have
+ */
+export class FeatureError extends PostcardError {
+  constructor(
+    message: string,
+    /** The missing feature */
+    readonly feature: string
+  ) {
+    super(message);
+    this.name = "FeatureError";
+  }
+}
+
+export interface FeatureSetOptions {
+  /**
+   * Property holding the variant of enum messages, as given to
+   * `discriminated()` (default: `type`, of plain enums)
+   */
+  readonly tag?: string;
+}
+
+/**
+ * The features a peer declared, read from its capability mask
+ */
+export class FeatureSet<F extends FeatureDefinitions> {
+  private readonly tag: string;
+
+  /**
+   * Throws RangeError for a feature whose bit is not one of the mask's 64
+   */
+  constructor(
+    readonly definitions: F,
+    /** The peer's mask; bits of features unknown to `definitions` are kept */
+    readonly mask: bigint,
+    options: FeatureSetOptions = {}
+  ) {
+    for (const [name, { bit }] of Object.entries(definitions)) {
+      if (!Number.isInteger(bit) || bit < 0 || bit > 63) {
+        throw new RangeError(`Feature ${name} has bit ${String(bit)}, not 0 to 63`);
+      }
+    }
+    this.tag = options.tag ?? "type";
+  }
+
+  /**
+   * The features `names`, e.g. those a dashboard supports, to announce or
+   * intersect with a peer's
+   */
+  static of<F extends FeatureDefinitions>(
+    definitions: F,
+    names: readonly (keyof F & string)[],
+    options?: FeatureSetOptions
+  ): FeatureSet<F> {
+    const mask = names.reduce((mask, name) => mask | bitOf(definitions[name]), 0n);
+    return new FeatureSet(definitions, mask, options);
+  }
+
+  /**
+   * Whether the peer has the feature `name`
+   */
+  hasFeature(name: keyof F & string): boolean {
+    return (this.mask & bitOf(this.definitions[name])) !== 0n;
+  }
+
+  /**
+   * The names of the features the peer has, in declaration order
+   */
+  features(): (keyof F & string)[] {
+    return this.names().filter((name) => this.hasFeature(name));
+  }
+
+  /**
+   * The features both this set and `other` have, e.g. the dashboard's and
+   * the device's
+   */
+  intersect(other: FeatureSet<F>): FeatureSet<F> {
+    return new FeatureSet(this.definitions, this.mask & other.mask, { tag: this.tag });
+  }
+
+  /**
+   * The feature the peer lacks to parse a `type` message, or of the variant
+   * of `value` for an enum; undefined if it can parse it
+   */
+  missingFeature(type: string, value?: unknown): (keyof F & string) | undefined {
+    const variant = variantOf(value, this.tag);
+    const message = variant === undefined ? undefined : `${type}::${variant}`;
+    return this.names().find((name) => {
+      const messages = this.definitions[name]?.messages ?? [];
+      return !this.hasFeature(name) && messages.some((m) => m === type || m === message);
+    });
+  }
+
+  /**
+   * Whether the peer can parse a `type` message, or `value` of enum `type`
+   */
+  supports(type: string, value?: unknown): boolean {
+    return this.missingFeature(type, value) === undefined;
+  }
+
+  /**
+   * Serialize a `type` message unless the peer lacks a feature it needs
+   * (Result API)
+   */
+  trySerialize<S extends Schema>(
+    type: string,
+    schema: S & { readonly direction?: "encode" },
+    value: unknown,
+    options?: SerializeOptions
+  ): Result<Uint8Array, FeatureError | SerializeError> {
+    const missing = this.missingFeature(type, value);
+    if (missing !== undefined) {
+      const variant = variantOf(value, this.tag);
+      const message = variant === undefined ? type : `${type}::${variant}`;
+      return err(new FeatureError(`The peer lacks feature ${missing} for ${message}`, missing));
+    }
+    return trySerialize(schema, value, options);
+  }
+
+  /**
+   * Serialize a `type` message (throwing API)
+   *
+   * Throws FeatureError if the peer lacks a feature it needs, or
+   * SerializeError on failure
+   */
+  serialize<S extends Schema>(
+    type: string,
+    schema: S & { readonly direction?: "encode" },
+    value: unknown,
+    options?: SerializeOptions
+  ): Uint8Array {
+    return unwrap(this.trySerialize(type, schema, value, options));
+  }
+
+  private names(): (keyof F & string)[] {
+    return Object.keys(this.definitions) as (keyof F & string)[];
+  }
+}
+
+function bitOf(definition: FeatureDefinition | undefined): bigint {
+  return definition === undefined ? 0n : 1n << BigInt(definition.bit);
+}
+
+function variantOf(value: unknown, tag: string): string | undefined {
+  if (typeof value === "object" && value !== null && tag in value) {
+    const variant: unknown = (value as Record<string, unknown>)[tag];
+    if (typeof variant === "string") {
+      return variant;
+    }
+  }
+  return undefined;
+}
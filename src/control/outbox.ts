@@ -0,0 +1,275 @@
+/**
+ * Rate limiting and coalescing of outgoing control messages
+ *
+ * A slider bound to a `SetTarget` command fires dozens of input events a
+ * second, far more than a serial or BLE link to a device can carry, and only
+ * the latest value matters. An Outbox holds such messages back by kind,
+ * replacing a held message with the next one of its kind, so that only the
+ * values still current when the link is free get encoded and sent:
+ *
+ *   const outbox = new Outbox(CommandSchema, {
+ *     send: (bytes) => port.write(bytes),
+ *     policies: { SetTarget: { mode: "throttle", intervalMs: 100 } },
+ *   });
+ *   slider.oninput = () => outbox.send({ type: "SetTarget", value: slider.valueAsNumber });
+ *   slider.onchange = () => outbox.flush("SetTarget");
+ *
+ * Flushing when the slider is released sends the final value right away
+ * rather than at the end of the interval. Messages of kinds without a policy
+ * are sent immediately, after any held messages, so a `Stop` is never
+ * overtaken by a `SetTarget` queued before it.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import { serialize } from "../codec/serializer.js";
+import { TransportError } from "../types/errors.js";
+
+/**
+ * How messages of one kind are held back
+ *
+ * - `immediate`: sent as they come
+ * - `throttle`: the first message is sent at once, then at most one every
+ *   `intervalMs`, the latest one held in between
+ * - `coalesce`: held for `windowMs` after the first one, then only the
+ *   latest is sent
+ */
+export type OutboxPolicy =
+  | { readonly mode: "immediate" }
+  | { readonly mode: "throttle"; readonly intervalMs: number }
+  | { readonly mode: "coalesce"; readonly windowMs: number };
+
+export interface OutboxOptions<S extends Schema> {
+  /** Receives each encoded message */
+  readonly send: (bytes: Uint8Array) => void;
+  /** Policy per message kind */
+  readonly policies?: Readonly<Record<string, OutboxPolicy>>;
+  /** Policy for kinds not in `policies` (default: immediate) */
+  readonly defaultPolicy?: OutboxPolicy;
+  /**
+   * Kind of a message, which policies are looked up by (default: the enum
+   * variant, `value.type`, or one kind for everything else)
+   */
+  readonly kindOf?: (value: InferType<S>) => string;
+  /**
+   * Encoder for the messages (default: `serialize(schema, value)`), e.g. to
+   * frame them with `serializeCobs`
+   */
+  readonly encode?: (value: InferType<S>) => Uint8Array;
+  /**
+   * Called when encoding or sending a held message fails on a timer, with a
+   * TransportError for a failed send
+   * (default: the error is rethrown, and so uncaught)
+   */
+  readonly onError?: (error: unknown) => void;
+}
+
+interface KindState<T> {
+  held: { value: T } | undefined;
+  timer: ReturnType<typeof setTimeout> | undefined;
+  lastSent: number;
+}
+
+const IMMEDIATE: OutboxPolicy = { mode: "immediate" };
+
+export class Outbox<S extends Schema> {
+  readonly schema: S;
+
+  private readonly output: (bytes: Uint8Array) => void;
+  private readonly policies: Readonly<Record<string, OutboxPolicy>>;
+  private readonly defaultPolicy: OutboxPolicy;
+  private readonly kindOf: (value: InferType<S>) => string;
+  private readonly encode: (value: InferType<S>) => Uint8Array;
+  private readonly onError: ((error: unknown) => void) | undefined;
+  // Kinds in the order their held messages were queued
+  private readonly kinds = new Map<string, KindState<InferType<S>>>();
+
+  /**
+   * Throws RangeError if a policy has a non-positive interval or window
+   */
+  constructor(schema: S, options: OutboxOptions<S>) {
+    const {
+      send,
+      policies = {},
+      defaultPolicy = IMMEDIATE,
+      kindOf = defaultKindOf,
+      encode = (value) => serialize(schema, value),
+      onError,
+    } = options;
+    for (const [kind, policy] of Object.entries(policies)) {
+      checkPolicy(kind, policy);
+    }
+    checkPolicy("defaultPolicy", defaultPolicy);
+    this.schema = schema;
+    this.output = send;
+    this.policies = policies;
+    this.defaultPolicy = defaultPolicy;
+    this.kindOf = kindOf;
+    this.encode = encode;
+    this.onError = onError;
+  }
+
+  /**
+   * Number of messages held back
+   */
+  get held(): number {
+    let count = 0;
+    for (const state of this.kinds.values()) {
+      if (state.held !== undefined) {
+        count++;
+      }
+    }
+    return count;
+  }
+
+  /**
+   * Send a message, or hold it back as its kind's policy says
+   *
+   * Throws whatever encoding throws, or a TransportError wrapping what
+   * sending throws, when the message goes out right away.
+   */
+  send(value: InferType<S>): void {
+    const kind = this.kindOf(value);
+    const policy = this.policies[kind] ?? this.defaultPolicy;
+    const now = Date.now();
+
+    switch (policy.mode) {
+      case "immediate":
+        this.flush();
+        this.dispatch(value);
+        return;
+      case "throttle": {
+        const state = this.state(kind);
+        if (state.held === undefined && now - state.lastSent >= policy.intervalMs) {
+          state.lastSent = now;
+          this.dispatch(value);
+          return;
+        }
+        this.hold(kind, state, value, state.lastSent + policy.intervalMs - now);
+        return;
+      }
+      case "coalesce": {
+        const state = this.state(kind);
+        this.hold(kind, state, value, policy.windowMs);
+        return;
+      }
+    }
+  }
+
+  /**
+   * Send the held message of `kind` now, or every held message, in the order
+   * they were queued
+   *
+   * Call it when the control is released, so the final value does not wait
+   * for the rest of its interval.
+   */
+  flush(kind?: string): void {
+    const kinds = kind === undefined ? [...this.kinds.keys()] : [kind];
+    for (const each of kinds) {
+      const state = this.kinds.get(each);
+      if (state?.held === undefined) {
+        continue;
+      }
+      const { value } = state.held;
+      this.release(state);
+      state.lastSent = Date.now();
+      this.dispatch(value);
+    }
+  }
+
+  /**
+   * Drop the held message of `kind`, or every held message, without sending
+   * it, e.g. after the link went down
+   */
+  clear(kind?: string): void {
+    const kinds = kind === undefined ? [...this.kinds.keys()] : [kind];
+    for (const each of kinds) {
+      const state = this.kinds.get(each);
+      if (state !== undefined) {
+        this.release(state);
+      }
+    }
+  }
+
+  private state(kind: string): KindState<InferType<S>> {
+    let state = this.kinds.get(kind);
+    if (state === undefined) {
+      state = { held: undefined, timer: undefined, lastSent: -Infinity };
+      this.kinds.set(kind, state);
+    }
+    return state;
+  }
+
+  private hold(
+    kind: string,
+    state: KindState<InferType<S>>,
+    value: InferType<S>,
+    delayMs: number
+  ): void {
+    if (state.held === undefined) {
+      // Queued behind the kinds already held
+      this.kinds.delete(kind);
+      this.kinds.set(kind, state);
+    }
+    state.held = { value };
+    if (state.timer !== undefined) {
+      return;
+    }
+    state.timer = setTimeout(() => {
+      try {
+        this.flush(kind);
+      } catch (error) {
+        if (this.onError === undefined) {
+          throw error;
+        }
+        this.onError(error);
+      }
+    }, delayMs);
+  }
+
+  private release(state: KindState<InferType<S>>): void {
+    if (state.timer !== undefined) {
+      clearTimeout(state.timer);
+    }
+    state.held = undefined;
+    state.timer = undefined;
+  }
+
+  private dispatch(value: InferType<S>): void {
+    const bytes = this.encode(value);
+    try {
+      this.output(bytes);
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      throw new TransportError(`Sending failed: ${message}`, error);
+    }
+  }
+}
+
+function defaultKindOf(value: unknown): string {
+  if (typeof value === "object" && value !== null && "type" in value) {
+    const { type } = value;
+    if (typeof type === "string") {
+      return type;
+    }
+  }
+  return "";
+}
+
+function checkPolicy(kind: string, policy: OutboxPolicy): void {
+  switch (policy.mode) {
+    case "immediate":
+      return;
+    case "throttle":
+      if (!(policy.intervalMs > 0)) {
+        throw new RangeError(
+          `${kind}: intervalMs must be positive, got ${String(policy.intervalMs)}`
+        );
+      }
+      return;
+    case "coalesce":
+      if (!(policy.windowMs > 0)) {
+        throw new RangeError(`${kind}: windowMs must be positive, got ${String(policy.windowMs)}`);
+      }
+      return;
+  }
+}
@@ -0,0 +1,282 @@
+/**
+ * A deterministic fake device for UI tests
+ *
+ * Playwright or Cypress tests of a device UI need a device that answers the
+ * way the firmware does, byte for byte, and sends its telemetry on cue rather
+ * than on a wall clock. A DeviceSimulator decodes each request the UI sends,
+ * replies with what the handler of its variant returns and emits scripted
+ * telemetry as its virtual clock is advanced:
+ *
+ *   const device = new ThermostatSimulator({
+ *     send: (bytes) => socket.deliver(bytes),
+ *     handlers: { GetTarget: () => ({ type: "Target", value: 21.5 }) },
+ *   });
+ *   device.every(1000, (now) => ({ type: "Reading", value: 20 + now / 1000 }));
+ *   socket.onmessage = (bytes) => device.receive(bytes);
+ *   device.advance(3000); // three readings, at 1000, 2000 and 3000 ms
+ *
+ * `postcard-ts generate` writes a typed subclass per simulator declared in
+ * its `--config` (`ThermostatSimulator` above), with a handler per request
+ * variant; this class does the work and knows variants only by name. As it
+ * plays the device, it decodes requests and encodes replies even when their
+ * schemas are `encodeOnly()` or `decodeOnly()` for the UI.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import { deserialize } from "../codec/deserializer.js";
+import { serialize } from "../codec/serializer.js";
+import { withoutDirection } from "../codec/direction.js";
+import { PostcardError, TransportError } from "../types/errors.js";
+
+/**
+ * What a handler answers: a response to send, `undefined` for none, or a
+ * promise of either
+ */
+export type SimulatorReply<T> = T | undefined | Promise<T | undefined>;
+
+/**
+ * A request handler of any simulator; handlers take the request narrowed to
+ * their variant and the simulator, which this type leaves open
+ */
+export type AnySimulatorHandler<R> = (request: never, simulator: never) => SimulatorReply<R>;
+
+/**
+ * The schemas of what a simulated device receives and sends
+ */
+export interface DeviceSimulatorSchemas<Q extends Schema, R extends Schema, T extends Schema> {
+  /** What the UI sends, usually an enum with a variant per request */
+  readonly request: Q;
+  /** What the device answers requests with */
+  readonly response: R;
+  /** What the device sends unprompted */
+  readonly telemetry: T;
+}
+
+export interface DeviceSimulatorOptions {
+  /** Receives each encoded message the device sends */
+  readonly send: (bytes: Uint8Array) => void;
+  /**
+   * Variant of a request, which handlers are looked up by (default: the enum
+   * variant, `request.type`)
+   */
+  readonly kindOf?: (request: unknown) => string;
+}
+
+/**
+ * A request no handler was given for
+ */
+export class UnhandledRequestError extends PostcardError {
+  constructor(
+    readonly kind: string,
+    readonly request: unknown
+  ) {
+    super(`No handler for request ${kind === "" ? "(no variant)" : kind}`);
+    this.name = "UnhandledRequestError";
+  }
+}
+
+/**
+ * One step of a telemetry script, `atMs` after the script starts
+ */
+export interface ScriptStep<T> {
+  readonly atMs: number;
+  readonly value: T;
+}
+
+interface Timer<T> {
+  dueMs: number;
+  // Order of scheduling, so timers due together fire as they were set
+  readonly seq: number;
+  readonly intervalMs: number | undefined;
+  readonly next: (now: number) => T | undefined;
+}
+
+export class DeviceSimulator<Q extends Schema, R extends Schema, T extends Schema = R> {
+  readonly schemas: DeviceSimulatorSchemas<Q, R, T>;
+  /** Every request received, decoded, oldest first */
+  readonly received: InferType<Q>[] = [];
+
+  private readonly output: (bytes: Uint8Array) => void;
+  private readonly kindOf: (request: unknown) => string;
+  private readonly handlers = new Map<string, AnySimulatorHandler<InferType<R>>>();
+  private readonly timers = new Set<Timer<InferType<T>>>();
+  private clock = 0;
+  private scheduled = 0;
+
+  constructor(schemas: DeviceSimulatorSchemas<Q, R, T>, options: DeviceSimulatorOptions) {
+    this.schemas = schemas;
+    this.output = options.send;
+    this.kindOf = options.kindOf ?? defaultKindOf;
+  }
+
+  /**
+   * Virtual milliseconds since the simulator was created, moved on only by
+   * `advance()`
+   */
+  get now(): number {
+    return this.clock;
+  }
+
+  /**
+   * Decode a request and send the reply of its variant's handler
+   *
+   * Resolves once the reply, if any, was sent. Rejects with the
+   * DeserializeError of bytes that are not a request, an
+   * UnhandledRequestError for a variant without a handler, or whatever the
+   * handler throws.
+   */
+  async receive(bytes: Uint8Array): Promise<void> {
+    const request = deserialize(withoutDirection(this.schemas.request), bytes).value;
+    this.received.push(request);
+    const kind = this.kindOf(request);
+    const handler = this.handlers.get(kind);
+    if (handler === undefined) {
+      throw new UnhandledRequestError(kind, request);
+    }
+    const reply = await handler(request as never, this as never);
+    if (reply !== undefined) {
+      this.respond(reply);
+    }
+  }
+
+  /**
+   * Send a response right away, as if unprompted
+   */
+  respond(value: InferType<R>): void {
+    this.dispatch(serialize(withoutDirection(this.schemas.response), value));
+  }
+
+  /**
+   * Send a telemetry message right away
+   */
+  emit(value: InferType<T>): void {
+    this.dispatch(serialize(withoutDirection(this.schemas.telemetry), value));
+  }
+
+  /**
+   * Emit what `next` returns every `intervalMs`, first `intervalMs` from now;
+   * `next` gets the virtual time and may return `undefined` to skip a beat
+   *
+   * Returns a function that stops it. Throws RangeError if the interval is
+   * not positive.
+   */
+  every(intervalMs: number, next: (now: number) => InferType<T> | undefined): () => void {
+    if (!(intervalMs > 0) || !Number.isFinite(intervalMs)) {
+      throw new RangeError(`intervalMs must be positive, got ${String(intervalMs)}`);
+    }
+    return this.schedule(this.clock + intervalMs, intervalMs, next);
+  }
+
+  /**
+   * Emit each value of `steps` at its time from now
+   *
+   * Returns a function that drops the steps not emitted yet. Throws
+   * RangeError if a step is timed before now.
+   */
+  script(steps: readonly ScriptStep<InferType<T>>[]): () => void {
+    for (const step of steps) {
+      if (!(step.atMs >= 0) || !Number.isFinite(step.atMs)) {
+        throw new RangeError(`atMs must not be negative, got ${String(step.atMs)}`);
+      }
+    }
+    const cancels = steps.map((step) =>
+      this.schedule(this.clock + step.atMs, undefined, () => step.value)
+    );
+    return () => {
+      for (const cancel of cancels) {
+        cancel();
+      }
+    };
+  }
+
+  /**
+   * Move the virtual clock on by `ms`, emitting the telemetry due on the way
+   * in time order
+   *
+   * Throws RangeError if `ms` is negative, and whatever sending throws,
+   * wrapped in a TransportError.
+   */
+  advance(ms: number): void {
+    if (!(ms >= 0) || !Number.isFinite(ms)) {
+      throw new RangeError(`ms must not be negative, got ${String(ms)}`);
+    }
+    const until = this.clock + ms;
+    let due: Timer<InferType<T>> | undefined;
+    while ((due = this.nextDue(until)) !== undefined) {
+      this.clock = due.dueMs;
+      if (due.intervalMs === undefined) {
+        this.timers.delete(due);
+      } else {
+        due.dueMs += due.intervalMs;
+      }
+      const value = due.next(this.clock);
+      if (value !== undefined) {
+        this.emit(value);
+      }
+    }
+    this.clock = until;
+  }
+
+  /**
+   * Handle requests of `kind` with `handler`, or stop handling them
+   */
+  protected setHandler(
+    kind: string,
+    handler: AnySimulatorHandler<InferType<R>> | undefined
+  ): void {
+    if (handler === undefined) {
+      this.handlers.delete(kind);
+    } else {
+      this.handlers.set(kind, handler);
+    }
+  }
+
+  private schedule(
+    dueMs: number,
+    intervalMs: number | undefined,
+    next: (now: number) => InferType<T> | undefined
+  ): () => void {
+    const timer: Timer<InferType<T>> = { dueMs, seq: this.scheduled++, intervalMs, next };
+    this.timers.add(timer);
+    return () => {
+      this.timers.delete(timer);
+    };
+  }
+
+  /** The earliest timer due by `until`, the first scheduled among ties */
+  private nextDue(until: number): Timer<InferType<T>> | undefined {
+    let due: Timer<InferType<T>> | undefined;
+    for (const timer of this.timers) {
+      if (timer.dueMs > until) {
+        continue;
+      }
+      if (
+        due === undefined ||
+        timer.dueMs < due.dueMs ||
+        (timer.dueMs === due.dueMs && timer.seq < due.seq)
+      ) {
+        due = timer;
+      }
+    }
+    return due;
+  }
+
+  private dispatch(bytes: Uint8Array): void {
+    try {
+      this.output(bytes);
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      throw new TransportError(`Sending failed: ${message}`, error);
+    }
+  }
+}
+
+function defaultKindOf(value: unknown): string {
+  if (typeof value === "object" && value !== null && "type" in value) {
+    const { type } = value;
+    if (typeof type === "string") {
+      return type;
+    }
+  }
+  return "";
+}
@@ -0,0 +1,162 @@
+/**
+ * Reading self-describing archives of postcard records
+ *
+ * Postcard writes no names or tags, so stored records only decode with the
+ * types they were written with. An archive, written by the Rust crate's
+ * `ArchiveWriter`, carries the JSON IR of its protocol, the name of the
+ * records' type and its schema hash in a header, so it decodes with nothing
+ * but the file, long after the code that wrote it is gone:
+ *
+ *   const { type, records } = readArchive(await readFile("readings-2024.pcta"));
+ *
+ * The header is a postcard value (`ArchiveHeaderSchema`), and the records
+ * follow it to the end of the file, each prefixed with its length as in
+ * `lengthPrefixEncode`.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { struct, tuple, string, u8, u32, u64 } from "../types/schema.js";
+import { PostcardError } from "../types/errors.js";
+import {
+  type DeserializeOptions,
+  type DeserializeError,
+  tryDeserialize,
+} from "../codec/deserializer.js";
+import { tryLengthPrefixDecode } from "../codec/length-prefix.js";
+import { DynamicDecoder } from "./decoder.js";
+import { IrError } from "./ir.js";
+
+export class ArchiveError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "ArchiveError";
+  }
+}
+
+/**
+ * The first bytes of every archive, "PCTA"
+ */
+export const ARCHIVE_MAGIC: readonly number[] = Object.freeze([0x50, 0x43, 0x54, 0x41]);
+
+/**
+ * Archive format version read by this library
+ */
+export const ARCHIVE_VERSION = 1;
+
+export const ArchiveHeaderSchema = struct({
+  magic: tuple(u8(), u8(), u8(), u8()),
+  version: u32(),
+  type_name: string(),
+  schema_hash: u64(),
+  ir: string(),
+});
+
+export interface ReadArchiveOptions extends DeserializeOptions {
+  /**
+   * The schema hash the records' type must have, e.g. `ReadingSchemaHash`
+   * from generated code; checked before decoding any record
+   */
+  readonly schemaHash?: bigint;
+}
+
+export interface Archive {
+  /** The records' type, as named in the IR */
+  readonly type: string;
+  readonly schemaHash: bigint;
+  /** The IR the archive was written with, parsed */
+  readonly ir: unknown;
+  /** A decoder for the IR, e.g. for the schemas of its other types */
+  readonly decoder: DynamicDecoder;
+  readonly records: unknown[];
+}
+
+/**
+ * Decode an archive and its records (Result API)
+ *
+ * Fails with ArchiveError for a malformed header or record, IrError for an
+ * IR that does not describe the records, or the DeserializeError of a record
+ * that does not decode, its path starting with the type and the record's
+ * index (`Reading[3].celsius`).
+ */
+export function tryReadArchive(
+  data: Uint8Array,
+  options: ReadArchiveOptions = {}
+): Result<Archive, ArchiveError | IrError | DeserializeError> {
+  if (data.length < ARCHIVE_MAGIC.length || ARCHIVE_MAGIC.some((byte, i) => data[i] !== byte)) {
+    return err(new ArchiveError("Not an archive"));
+  }
+  // Check the version first: other versions may not decode with this schema
+  const version = tryDeserialize(u32(), data, ARCHIVE_MAGIC.length);
+  if (version.ok && version.value.value !== ARCHIVE_VERSION) {
+    return err(new ArchiveError(`Unsupported archive version ${String(version.value.value)}`));
+  }
+  const header = tryDeserialize(ArchiveHeaderSchema, data);
+  if (!header.ok) {
+    return err(new ArchiveError(`Failed to decode archive header: ${header.error.message}`));
+  }
+  const { type_name: type, schema_hash: schemaHash } = header.value.value;
+  const { schemaHash: expected, ...decodeOptions } = options;
+  if (expected !== undefined && expected !== schemaHash) {
+    return err(
+      new ArchiveError(
+        `Archive of ${type} has schema hash 0x${schemaHash.toString(16)}, ` +
+          `expected 0x${expected.toString(16)}`
+      )
+    );
+  }
+
+  let ir: unknown;
+  try {
+    ir = JSON.parse(header.value.value.ir);
+  } catch {
+    return err(new IrError("The archive's IR is not JSON"));
+  }
+  let decoder: DynamicDecoder;
+  try {
+    decoder = new DynamicDecoder(ir);
+  } catch (e) {
+    if (e instanceof IrError) {
+      return err(e);
+    }
+    throw e;
+  }
+  if (decoder.schema(type) === undefined) {
+    return err(new IrError(`The archive's IR has no type ${type}`));
+  }
+
+  const records: unknown[] = [];
+  let offset = header.value.bytesRead;
+  while (offset < data.length) {
+    const frame = tryLengthPrefixDecode(data, offset);
+    if (!frame.ok) {
+      return err(new ArchiveError(`Record ${String(records.length)}: ${frame.error.message}`));
+    }
+    const length = frame.value.value.length;
+    const start = offset + frame.value.bytesRead - length;
+    const root = `${type}[${String(records.length)}]`;
+    const record = decoder.tryDecode(type, data, start, { root, ...decodeOptions });
+    if (!record.ok) {
+      return record;
+    }
+    if (record.value.bytesRead !== length) {
+      return err(
+        new ArchiveError(
+          `Record ${String(records.length)} is ${String(length)} bytes, ` +
+            `but a ${type} took ${String(record.value.bytesRead)}`
+        )
+      );
+    }
+    records.push(record.value.value);
+    offset += frame.value.bytesRead;
+  }
+  return ok({ type, schemaHash, ir, decoder, records });
+}
+
+/**
+ * Decode an archive and its records (throwing API)
+ *
+ * Throws ArchiveError, IrError or DeserializeError on failure
+ */
+export function readArchive(data: Uint8Array, options: ReadArchiveOptions = {}): Archive {
+  return unwrap(tryReadArchive(data, options));
+}
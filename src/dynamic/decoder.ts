@@ -0,0 +1,73 @@
+/**
+ * Decoding any type of a protocol from its IR, without generated code
+ *
+ *   const decoder = new DynamicDecoder(await (await fetch("/protocol.json")).json());
+ *   const { value } = decoder.decode("Reading", frame);
+ */
+
+import { type Result, err, unwrap } from "../types/result.js";
+import type { Schema } from "../types/schema.js";
+import {
+  type DeserializeResult,
+  type DeserializeOptions,
+  type DeserializeError,
+  tryDeserialize,
+} from "../codec/deserializer.js";
+import { IrError, schemasFromIr } from "./ir.js";
+
+export class DynamicDecoder {
+  private readonly schemas: Map<string, Schema>;
+
+  /**
+   * Throws IrError if the IR is invalid
+   */
+  constructor(ir: unknown) {
+    this.schemas = schemasFromIr(ir);
+  }
+
+  /**
+   * Names of the types in the IR, except generic ones
+   */
+  get types(): string[] {
+    return Array.from(this.schemas.keys());
+  }
+
+  /**
+   * The schema built for a type, e.g. for serializing values of it
+   */
+  schema(type: string): Schema | undefined {
+    return this.schemas.get(type);
+  }
+
+  /**
+   * Decode a value of the named type (Result API)
+   *
+   * Error paths start with the type name unless `options.root` says otherwise.
+   */
+  tryDecode(
+    type: string,
+    data: Uint8Array,
+    offset = 0,
+    options: DeserializeOptions = {}
+  ): Result<DeserializeResult<unknown>, DeserializeError | IrError> {
+    const schema = this.schemas.get(type);
+    if (schema === undefined) {
+      return err(new IrError(`Unknown type ${type}`));
+    }
+    return tryDeserialize(schema, data, offset, { root: type, ...options });
+  }
+
+  /**
+   * Decode a value of the named type (throwing API)
+   *
+   * Throws DeserializeError, or IrError for an unknown type
+   */
+  decode(
+    type: string,
+    data: Uint8Array,
+    offset = 0,
+    options: DeserializeOptions = {}
+  ): DeserializeResult<unknown> {
+    return unwrap(this.tryDecode(type, data, offset, options));
+  }
+}
@@ -0,0 +1,372 @@
+/**
+ * Encoding any type of a protocol from its IR, without generated code
+ *
+ *   const encoder = new DynamicEncoder(ir);
+ *   const bytes = encoder.encode("Command", { type: "Move", value: { x: -2 } });
+ *
+ * Values are typed `unknown` here, so they are validated against the schema
+ * in full before encoding: every issue is reported with the path to the
+ * offending value, where `serialize()` would stop at the first one or, for a
+ * fractional or out-of-place value, encode something else without complaint.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import type { Schema, EnumVariant } from "../types/schema.js";
+import { type SerializeError, trySerialize } from "../codec/serializer.js";
+import { IrError, schemasFromIr } from "./ir.js";
+import { ValidationError } from "../types/errors.js";
+
+/**
+ * A value that does not match the schema, e.g. `Reading.samples[2]`
+ */
+export interface EncodeIssue {
+  readonly path: string;
+  readonly message: string;
+}
+
+export class DynamicEncodeError extends ValidationError {
+  constructor(
+    message: string,
+    readonly issues: readonly EncodeIssue[]
+  ) {
+    super(message);
+    this.name = "DynamicEncodeError";
+  }
+}
+
+/**
+ * Validation stops after this many issues, e.g. for a long sequence of bad items
+ */
+const MAX_ISSUES = 50;
+
+const INT_RANGES = {
+  i8: [-0x80, 0x7f],
+  i16: [-0x8000, 0x7fff],
+  i32: [-0x8000_0000, 0x7fff_ffff],
+  u8: [0, 0xff],
+  u16: [0, 0xffff],
+  u32: [0, 0xffff_ffff],
+} as const;
+
+const BIGINT_RANGES = {
+  i64: [-(1n << 63n), (1n << 63n) - 1n],
+  i128: [-(1n << 127n), (1n << 127n) - 1n],
+  u64: [0n, (1n << 64n) - 1n],
+  u128: [0n, (1n << 128n) - 1n],
+} as const;
+
+export class DynamicEncoder {
+  private readonly schemas: Map<string, Schema>;
+
+  /**
+   * Throws IrError if the IR is invalid
+   */
+  constructor(ir: unknown) {
+    this.schemas = schemasFromIr(ir);
+  }
+
+  /**
+   * Names of the types in the IR, except generic ones
+   */
+  get types(): string[] {
+    return Array.from(this.schemas.keys());
+  }
+
+  /**
+   * The schema built for a type
+   */
+  schema(type: string): Schema | undefined {
+    return this.schemas.get(type);
+  }
+
+  /**
+   * Every way the value does not match the named type (up to 50), e.g. for
+   * highlighting a message editor; empty if it can be encoded
+   *
+   * Throws IrError for an unknown type
+   */
+  validate(type: string, value: unknown): EncodeIssue[] {
+    const schema = this.schemas.get(type);
+    if (schema === undefined) {
+      throw new IrError(`Unknown type ${type}`);
+    }
+    return issuesOf(schema, value, type);
+  }
+
+  /**
+   * Validate and encode a value of the named type (Result API)
+   */
+  tryEncode(
+    type: string,
+    value: unknown
+  ): Result<Uint8Array, DynamicEncodeError | SerializeError | IrError> {
+    const schema = this.schemas.get(type);
+    if (schema === undefined) {
+      return err(new IrError(`Unknown type ${type}`));
+    }
+    const issues = issuesOf(schema, value, type);
+    if (issues.length > 0) {
+      const listed = issues.map((issue) => `${issue.path}: ${issue.message}`).join("; ");
+      return err(new DynamicEncodeError(`Invalid ${type}: ${listed}`, issues));
+    }
+    const result = trySerialize(schema, value);
+    return result.ok ? ok(result.value) : result;
+  }
+
+  /**
+   * Validate and encode a value of the named type (throwing API)
+   *
+   * Throws DynamicEncodeError for an invalid value, IrError for an unknown
+   * type, or SerializeError
+   */
+  encode(type: string, value: unknown): Uint8Array {
+    return unwrap(this.tryEncode(type, value));
+  }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+function issuesOf(schema: Schema, value: unknown, type: string): EncodeIssue[] {
+  const issues: EncodeIssue[] = [];
+  check(schema, value, type, issues);
+  return issues.slice(0, MAX_ISSUES);
+}
+
+function check(schema: Schema, value: unknown, path: string, issues: EncodeIssue[]): void {
+  if (issues.length >= MAX_ISSUES) {
+    return;
+  }
+  const issue = (message: string): void => {
+    issues.push({ path, message });
+  };
+  const expect = (valid: boolean, expected: string): void => {
+    if (!valid) {
+      issue(`expected ${expected}, got ${describe(value)}`);
+    }
+  };
+
+  switch (schema.kind) {
+    case "bool":
+      expect(typeof value === "boolean", "a boolean");
+      return;
+    case "i8":
+    case "i16":
+    case "i32":
+    case "u8":
+    case "u16":
+    case "u32": {
+      const [min, max] = INT_RANGES[schema.kind];
+      if (typeof value !== "number" || !Number.isInteger(value)) {
+        expect(false, "an integer");
+      } else if (value < min || value > max) {
+        issue(`${String(value)} is out of range for ${schema.kind}`);
+      }
+      return;
+    }
+    case "i64":
+    case "i128":
+    case "u64":
+    case "u128": {
+      const [min, max] = BIGINT_RANGES[schema.kind];
+      if (typeof value !== "bigint") {
+        expect(false, "a bigint");
+      } else if (value < min || value > max) {
+        issue(`${String(value)} is out of range for ${schema.kind}`);
+      }
+      return;
+    }
+    case "f32":
+    case "f64":
+      expect(typeof value === "number", "a number");
+      return;
+    case "char":
+      expect(typeof value === "string" && Array.from(value).length === 1, "a single character");
+      return;
+    case "string":
+      if (typeof value !== "string") {
+        expect(false, "a string");
+      } else if (schema.maxBytes !== undefined && schema.overflow !== "truncate") {
+        const length = new TextEncoder().encode(value).length;
+        if (length > schema.maxBytes) {
+          issue(`${String(length)} bytes exceed maxBytes (${String(schema.maxBytes)})`);
+        }
+      }
+      return;
+    case "bytes":
+      expect(value instanceof Uint8Array, "a Uint8Array");
+      return;
+    case "unit":
+      expect(value === undefined, "undefined");
+      return;
+    case "unit_struct":
+      expect(isRecord(value) && Object.keys(value).length === 0, "{}");
+      return;
+    case "never":
+      issue("no value has an uninhabited type");
+      return;
+    case "option":
+      if (value !== null) {
+        check(schema.inner as Schema, value, path, issues);
+      }
+      return;
+    case "newtype_struct":
+      check(schema.inner as Schema, value, path, issues);
+      return;
+    case "seq":
+      if (!Array.isArray(value)) {
+        expect(false, "an array");
+        return;
+      }
+      if (schema.maxLength !== undefined && value.length > schema.maxLength) {
+        issue(`${String(value.length)} items exceed maxLength (${String(schema.maxLength)})`);
+      }
+      value.forEach((item, i) => {
+        check(schema.item as Schema, item, `${path}[${String(i)}]`, issues);
+      });
+      return;
+    case "tuple":
+    case "tuple_struct":
+      checkItems(schema.items as readonly Schema[], value, path, issues);
+      return;
+    case "map":
+      if (!(value instanceof Map)) {
+        expect(false, "a Map");
+        return;
+      }
+      if (schema.maxLength !== undefined && value.size > schema.maxLength) {
+        issue(`${String(value.size)} entries exceed maxLength (${String(schema.maxLength)})`);
+      }
+      for (const [key, item] of value) {
+        const entry = `${path}[${describeKey(key)}]`;
+        check(schema.key as Schema, key, `${entry} key`, issues);
+        check(schema.value as Schema, item, entry, issues);
+      }
+      return;
+    case "struct":
+      checkFields(schema.fields as Record<string, Schema>, value, path, issues);
+      return;
+    case "enum":
+      checkVariant(schema.variants as Record<string, EnumVariant>, value, path, issues);
+      return;
+    case "lazy":
+      check(schema.get(), value, path, issues);
+      return;
+    default:
+      // Transforms and other kinds the IR does not produce: left to serialize()
+      return;
+  }
+}
+
+function checkItems(
+  items: readonly Schema[],
+  value: unknown,
+  path: string,
+  issues: EncodeIssue[]
+): void {
+  if (!Array.isArray(value) || value.length !== items.length) {
+    issues.push({
+      path,
+      message: `expected an array of ${String(items.length)} items, got ${describe(value)}`,
+    });
+    return;
+  }
+  items.forEach((item, i) => {
+    check(item, value[i], `${path}[${String(i)}]`, issues);
+  });
+}
+
+function checkFields(
+  fields: Record<string, Schema>,
+  value: unknown,
+  path: string,
+  issues: EncodeIssue[]
+): void {
+  if (!isRecord(value)) {
+    issues.push({ path, message: `expected an object, got ${describe(value)}` });
+    return;
+  }
+  for (const [name, field] of Object.entries(fields)) {
+    if (value[name] === undefined && field.kind !== "unit") {
+      issues.push({ path: `${path}.${name}`, message: "missing field" });
+    } else {
+      check(field, value[name], `${path}.${name}`, issues);
+    }
+  }
+  // Typos would otherwise silently drop the field a tool user meant to set
+  for (const name of Object.keys(value)) {
+    if (!Object.prototype.hasOwnProperty.call(fields, name)) {
+      issues.push({ path: `${path}.${name}`, message: "unknown field" });
+    }
+  }
+}
+
+function checkVariant(
+  variants: Record<string, EnumVariant>,
+  value: unknown,
+  path: string,
+  issues: EncodeIssue[]
+): void {
+  const type = isRecord(value) ? value["type"] : undefined;
+  const variant =
+    typeof type === "string" && Object.prototype.hasOwnProperty.call(variants, type)
+      ? variants[type]
+      : undefined;
+  if (!isRecord(value) || variant === undefined) {
+    const names = Object.keys(variants).join(", ");
+    issues.push({
+      path,
+      message: `expected { type } with a variant of ${names}, got ${describe(type ?? value)}`,
+    });
+    return;
+  }
+
+  const inner = `${path}.value`;
+  switch (variant.kind) {
+    case "unit_variant":
+      return;
+    case "newtype_variant":
+      check(variant.inner as Schema, value["value"], inner, issues);
+      return;
+    case "tuple_variant":
+      checkItems(variant.items as readonly Schema[], value["value"], inner, issues);
+      return;
+    case "struct_variant":
+      checkFields(variant.fields as Record<string, Schema>, value["value"], inner, issues);
+      return;
+  }
+}
+
+function isRecord(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+function describe(value: unknown): string {
+  if (value === null || value === undefined) {
+    return String(value);
+  }
+  if (typeof value === "string") {
+    return JSON.stringify(value);
+  }
+  if (typeof value === "number" || typeof value === "boolean") {
+    return String(value);
+  }
+  if (typeof value === "bigint") {
+    return `${String(value)}n`;
+  }
+  if (Array.isArray(value)) {
+    return `an array of ${String(value.length)} items`;
+  }
+  if (value instanceof Map) {
+    return "a Map";
+  }
+  if (value instanceof Uint8Array) {
+    return "a Uint8Array";
+  }
+  return typeof value === "object" ? "an object" : typeof value;
+}
+
+function describeKey(key: unknown): string {
+  return typeof key === "string" ? JSON.stringify(key) : describe(key);
+}
@@ -0,0 +1,451 @@
+/**
+ * Schemas from the generator's JSON IR
+ *
+ * The Rust generator (the `serde-postcard-ts` crate) describes every type
+ * deriving `PostcardTs` as a JSON IR (`Generator::generate_ir()`). Building
+ * schemas from it at runtime lets tools decode any type of a protocol without
+ * generated code: an inspector or a generic log viewer loads the IR next to
+ * the data, and prototypes can decode before committing to codegen.
+ *
+ * Decoding goes through the same schema-driven codec as hand-written or
+ * generated schemas; the price is that values are untyped (`unknown`).
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import {
+  bool,
+  i8,
+  i16,
+  i32,
+  i64,
+  i128,
+  u8,
+  u16,
+  u32,
+  u64,
+  u128,
+  f32,
+  f64,
+  char,
+  string,
+  bytes,
+  unit,
+  never,
+  option,
+  seq,
+  tuple,
+  map,
+  struct,
+  tupleStruct,
+  newtypeStruct,
+  unitStruct,
+  enumType,
+  unitVariant,
+  newtypeVariant,
+  tupleVariant,
+  structVariant,
+  lazy,
+  type Schema,
+  type CollectionOptions,
+  type EnumVariant,
+} from "../types/schema.js";
+import { PostcardError } from "../types/errors.js";
+
+export class IrError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "IrError";
+  }
+}
+
+/**
+ * IR format version understood by this runtime
+ */
+export const IR_VERSION = 1;
+
+const PRIMITIVES = {
+  bool,
+  i8,
+  i16,
+  i32,
+  i64,
+  i128,
+  u8,
+  u16,
+  u32,
+  u64,
+  u128,
+  f32,
+  f64,
+  char,
+  string,
+  bytes,
+  unit,
+} satisfies Record<string, () => Schema>;
+
+export type IrShape =
+  | { readonly kind: keyof typeof PRIMITIVES }
+  | { readonly kind: "string"; readonly maxBytes: number }
+  | { readonly kind: "option"; readonly inner: IrShape }
+  | { readonly kind: "seq"; readonly item: IrShape; readonly maxLength?: number }
+  | { readonly kind: "tuple"; readonly items: readonly IrShape[] }
+  | {
+      readonly kind: "map";
+      readonly key: IrShape;
+      readonly value: IrShape;
+      readonly maxLength?: number;
+    }
+  | { readonly kind: "ref"; readonly name: string; readonly args?: readonly IrShape[] }
+  | { readonly kind: "param"; readonly name: string };
+
+export interface IrField {
+  readonly name: string;
+  readonly type: IrShape;
+}
+
+export type IrVariant =
+  | { readonly name: string; readonly kind: "unit" }
+  | { readonly name: string; readonly kind: "newtype"; readonly inner: IrShape }
+  | { readonly name: string; readonly kind: "tuple"; readonly items: readonly IrShape[] }
+  | { readonly name: string; readonly kind: "struct"; readonly fields: readonly IrField[] };
+
+export type IrType = (
+  | { readonly name: string; readonly kind: "struct"; readonly fields: readonly IrField[] }
+  | { readonly name: string; readonly kind: "tuple_struct"; readonly items: readonly IrShape[] }
+  | { readonly name: string; readonly kind: "newtype_struct"; readonly inner: IrShape }
+  | { readonly name: string; readonly kind: "unit_struct" }
+  | { readonly name: string; readonly kind: "enum"; readonly variants: readonly IrVariant[] }
+) & {
+  /** Type parameters of a generic type, used in it as `param` shapes */
+  readonly params?: readonly string[];
+};
+
+export interface SchemaIr {
+  readonly version: number;
+  readonly types: readonly IrType[];
+}
+
+/**
+ * Build a schema for every type in the IR, by type name (Result API)
+ *
+ * The IR is validated first, so it may come straight from `JSON.parse`.
+ * References between types are resolved lazily, so recursive types work.
+ * Generic types have no schema of their own: they are built for the type
+ * arguments of each reference to them (`Envelope<Reading>`).
+ */
+export function trySchemasFromIr(ir: unknown): Result<Map<string, Schema>, IrError> {
+  try {
+    const { types } = parseIr(ir);
+    const byName = new Map<string, IrType>();
+    for (const type of types) {
+      if (byName.has(type.name)) {
+        return err(new IrError(`Type ${type.name} is defined more than once`));
+      }
+      byName.set(type.name, type);
+    }
+
+    const schemas = new Map<string, Schema>();
+    const resolve: Resolve = (name, args, path) => {
+      const type = byName.get(name);
+      if (type === undefined) {
+        throw new IrError(`${path}: unknown type ${name}`);
+      }
+      const params = type.params ?? [];
+      if (args.length !== params.length) {
+        throw new IrError(
+          `${path}: ${name} takes ${String(params.length)} type arguments, got ${String(args.length)}`
+        );
+      }
+      if (params.length === 0) {
+        return lazy(() => schemas.get(name) ?? never());
+      }
+      // Built on first use, so generic types may be recursive too
+      let instance: Schema | undefined;
+      return lazy(() => (instance ??= buildType(type, resolve, bind(params, args))));
+    };
+    for (const type of types) {
+      const params = type.params ?? [];
+      if (params.length === 0) {
+        schemas.set(type.name, buildType(type, resolve, new Map()));
+      } else {
+        // Check the references in a generic type now rather than on first use
+        buildType(type, resolve, bind(params, params.map(() => unit())));
+      }
+    }
+    return ok(schemas);
+  } catch (e) {
+    if (e instanceof IrError) {
+      return err(e);
+    }
+    throw e;
+  }
+}
+
+/**
+ * Build a schema for every type in the IR, by type name (throwing API)
+ *
+ * Throws IrError on failure
+ */
+export function schemasFromIr(ir: unknown): Map<string, Schema> {
+  return unwrap(trySchemasFromIr(ir));
+}
+
+// ============================================================================
+// Building
+// ============================================================================
+
+type Resolve = (name: string, args: readonly Schema[], path: string) => Schema;
+
+/** Schemas of the type arguments, by type parameter */
+type Bindings = ReadonlyMap<string, Schema>;
+
+function bind(params: readonly string[], args: readonly Schema[]): Bindings {
+  return new Map(params.map((param, i): [string, Schema] => [param, args[i] ?? never()]));
+}
+
+function buildType(type: IrType, resolve: Resolve, bindings: Bindings): Schema {
+  const shape = (s: IrShape, path: string): Schema =>
+    buildShape(s, `${type.name}.${path}`, resolve, bindings);
+  switch (type.kind) {
+    case "struct":
+      return struct(buildFields(type.fields, (f) => shape(f.type, f.name)));
+    case "tuple_struct":
+      return tupleStruct(type.name, ...type.items.map((item, i) => shape(item, String(i))));
+    case "newtype_struct":
+      return newtypeStruct(type.name, shape(type.inner, "0"));
+    case "unit_struct":
+      return unitStruct(type.name);
+    case "enum": {
+      // Uninhabited: no value can be decoded
+      if (type.variants.length === 0) {
+        return never();
+      }
+      const variants: Record<string, EnumVariant> = {};
+      for (const variant of type.variants) {
+        variants[variant.name] = buildVariant(variant, (s, field) =>
+          shape(s, `${variant.name}.${field}`)
+        );
+      }
+      return enumType(type.name, variants);
+    }
+  }
+}
+
+function buildVariant(
+  variant: IrVariant,
+  shape: (shape: IrShape, field: string) => Schema
+): EnumVariant {
+  switch (variant.kind) {
+    case "unit":
+      return unitVariant(variant.name);
+    case "newtype":
+      return newtypeVariant(variant.name, shape(variant.inner, "0"));
+    case "tuple":
+      return tupleVariant(variant.name, ...variant.items.map((item, i) => shape(item, String(i))));
+    case "struct":
+      return structVariant(variant.name, buildFields(variant.fields, (f) => shape(f.type, f.name)));
+  }
+}
+
+function buildFields(
+  fields: readonly IrField[],
+  build: (field: IrField) => Schema
+): Record<string, Schema> {
+  const record: Record<string, Schema> = {};
+  for (const field of fields) {
+    record[field.name] = build(field);
+  }
+  return record;
+}
+
+function buildShape(shape: IrShape, path: string, resolve: Resolve, bindings: Bindings): Schema {
+  const build = (s: IrShape, at = path): Schema => buildShape(s, at, resolve, bindings);
+  switch (shape.kind) {
+    case "option":
+      return option(build(shape.inner));
+    case "seq":
+      return seq(build(shape.item), capacity(shape));
+    case "tuple":
+      return tuple(...shape.items.map((item, i) => build(item, `${path}.${String(i)}`)));
+    case "map": {
+      const key = build(shape.key);
+      const value = build(shape.value);
+      try {
+        return map(key, value, capacity(shape));
+      } catch (error) {
+        throw new IrError(`${path}: ${(error as Error).message}`);
+      }
+    }
+    case "ref": {
+      const args = (shape.args ?? []).map((arg, i) => build(arg, `${path}<${String(i)}>`));
+      return resolve(shape.name, args, path);
+    }
+    case "param": {
+      const bound = bindings.get(shape.name);
+      if (bound === undefined) {
+        throw new IrError(`${path}: unknown type parameter ${shape.name}`);
+      }
+      return bound;
+    }
+    case "string":
+      return "maxBytes" in shape ? string({ maxBytes: shape.maxBytes }) : string();
+    default:
+      return PRIMITIVES[shape.kind]();
+  }
+}
+
+function capacity(shape: { readonly maxLength?: number }): CollectionOptions {
+  return shape.maxLength === undefined ? {} : { maxLength: shape.maxLength };
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+type Json = Record<string, unknown>;
+
+function parseIr(ir: unknown): SchemaIr {
+  const root = object(ir, "IR");
+  if (root["version"] !== IR_VERSION) {
+    throw new IrError(`Unsupported IR version ${String(root["version"])}`);
+  }
+  return { version: IR_VERSION, types: array(root["types"], "types").map(parseType) };
+}
+
+function parseType(value: unknown, index: number): IrType {
+  const type = object(value, `types[${String(index)}]`);
+  const name = text(type["name"], `types[${String(index)}].name`);
+  if (type["params"] !== undefined) {
+    const params = array(type["params"], `${name}.params`).map((param) =>
+      text(param, `${name} type parameter`)
+    );
+    return { ...parseDefinition(type, name), params };
+  }
+  return parseDefinition(type, name);
+}
+
+function parseDefinition(type: Json, name: string): IrType {
+  switch (type["kind"]) {
+    case "struct":
+      return { name, kind: "struct", fields: parseFields(type["fields"], name) };
+    case "tuple_struct":
+      return { name, kind: "tuple_struct", items: parseShapes(type["items"], name) };
+    case "newtype_struct":
+      return { name, kind: "newtype_struct", inner: parseShape(type["inner"], name) };
+    case "unit_struct":
+      return { name, kind: "unit_struct" };
+    case "enum":
+      return {
+        name,
+        kind: "enum",
+        variants: array(type["variants"], `${name}.variants`).map((v) => parseVariant(v, name)),
+      };
+    default:
+      throw new IrError(`${name}: unknown type kind ${String(type["kind"])}`);
+  }
+}
+
+function parseVariant(value: unknown, owner: string): IrVariant {
+  const variant = object(value, `${owner} variant`);
+  const name = text(variant["name"], `${owner} variant name`);
+  const path = `${owner}.${name}`;
+  switch (variant["kind"]) {
+    case "unit":
+      return { name, kind: "unit" };
+    case "newtype":
+      return { name, kind: "newtype", inner: parseShape(variant["inner"], path) };
+    case "tuple":
+      return { name, kind: "tuple", items: parseShapes(variant["items"], path) };
+    case "struct":
+      return { name, kind: "struct", fields: parseFields(variant["fields"], path) };
+    default:
+      throw new IrError(`${path}: unknown variant kind ${String(variant["kind"])}`);
+  }
+}
+
+function parseFields(value: unknown, path: string): IrField[] {
+  return array(value, `${path}.fields`).map((item) => {
+    const field = object(item, `${path} field`);
+    const name = text(field["name"], `${path} field name`);
+    return { name, type: parseShape(field["type"], `${path}.${name}`) };
+  });
+}
+
+function parseShapes(value: unknown, path: string): IrShape[] {
+  return array(value, `${path}.items`).map((item, i) => parseShape(item, `${path}.${String(i)}`));
+}
+
+function parseShape(value: unknown, path: string): IrShape {
+  const shape = object(value, path);
+  const kind = shape["kind"];
+  switch (kind) {
+    case "option":
+      return { kind, inner: parseShape(shape["inner"], path) };
+    case "seq":
+      return { kind, item: parseShape(shape["item"], path), ...parseMaxLength(shape, path) };
+    case "tuple":
+      return { kind, items: parseShapes(shape["items"], path) };
+    case "map":
+      return {
+        kind,
+        key: parseShape(shape["key"], path),
+        value: parseShape(shape["value"], path),
+        ...parseMaxLength(shape, path),
+      };
+    case "ref": {
+      const name = text(shape["name"], `${path} reference`);
+      return shape["args"] === undefined
+        ? { kind, name }
+        : { kind, name, args: parseShapes(shape["args"], `${path}<${name}>`) };
+    }
+    case "param":
+      return { kind, name: text(shape["name"], `${path} type parameter`) };
+    case "string": {
+      const maxBytes = shape["maxBytes"];
+      if (maxBytes === undefined) {
+        return { kind };
+      }
+      if (typeof maxBytes !== "number" || !Number.isSafeInteger(maxBytes) || maxBytes < 0) {
+        throw new IrError(`${path}: expected maxBytes to be a non-negative integer`);
+      }
+      return { kind, maxBytes };
+    }
+    default:
+      if (typeof kind === "string" && Object.prototype.hasOwnProperty.call(PRIMITIVES, kind)) {
+        return { kind: kind as keyof typeof PRIMITIVES };
+      }
+      throw new IrError(`${path}: unknown shape kind ${String(kind)}`);
+  }
+}
+
+function parseMaxLength(shape: Json, path: string): { maxLength?: number } {
+  const maxLength = shape["maxLength"];
+  if (maxLength === undefined) {
+    return {};
+  }
+  if (typeof maxLength !== "number" || !Number.isSafeInteger(maxLength) || maxLength < 0) {
+    throw new IrError(`${path}: expected maxLength to be a non-negative integer`);
+  }
+  return { maxLength };
+}
+
+function object(value: unknown, path: string): Json {
+  if (typeof value !== "object" || value === null || Array.isArray(value)) {
+    throw new IrError(`${path}: expected an object`);
+  }
+  return value as Json;
+}
+
+function array(value: unknown, path: string): unknown[] {
+  if (!Array.isArray(value)) {
+    throw new IrError(`${path}: expected an array`);
+  }
+  return value;
+}
+
+function text(value: unknown, path: string): string {
+  if (typeof value !== "string" || value === "") {
+    throw new IrError(`${path}: expected a name`);
+  }
+  return value;
+}
@@ -0,0 +1,182 @@
+/**
+ * Checking migration code against a corpus of old frames
+ *
+ * A protocol upgrade changes the types, and data stored or sent by the old
+ * version (logs on flash, settings in a database, devices not updated yet)
+ * has to be migrated to the new ones. `simulateMigration()` proves that it
+ * can be: every fixture recorded with the old protocol is decoded with the
+ * old IR, passed through the migration for its type, and the result checked
+ * against the new IR the way `DynamicEncoder` checks values, then encoded
+ * and decoded again with it:
+ *
+ *   const report = simulateMigration(oldIr, newIr, fixtures, {
+ *     Settings: (old) => ({ ...(old as object), brightness: 100 }),
+ *   });
+ *   expect(report.failures).toEqual([]);
+ *
+ * Fixtures are the `{ file, type }` entries of a fixture manifest with their
+ * bytes. A type without a migration must load unchanged.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { DynamicDecoder } from "./decoder.js";
+import { type EncodeIssue, DynamicEncoder } from "./encoder.js";
+import { IrError } from "./ir.js";
+
+/**
+ * A frame recorded with the old version of the protocol
+ */
+export interface MigrationFixture {
+  /** Names the fixture in failures, e.g. its file name */
+  readonly file: string;
+  /** The old type the frame is a value of */
+  readonly type: string;
+  readonly data: Uint8Array;
+}
+
+/**
+ * Turns a value of the old type into one of the new type
+ */
+export type Migration = (value: unknown) => unknown;
+
+export interface MigrationOptions {
+  /** The new name of types renamed by the upgrade, by old name */
+  readonly renamed?: Readonly<Record<string, string>>;
+}
+
+/**
+ * A fixture that does not load with the new version of the protocol
+ *
+ * `stage` says how far it got: it did not `decode` with the old IR, its
+ * migration threw (`migrate`), the migrated value does not match the new
+ * type (`validate`, with the `issues`), or it did not `encode` and decode
+ * back with the new IR.
+ */
+export interface MigrationFailure {
+  readonly file: string;
+  readonly type: string;
+  readonly stage: "decode" | "migrate" | "validate" | "encode";
+  readonly message: string;
+  readonly issues: readonly EncodeIssue[];
+}
+
+export interface MigrationReport {
+  /** How many fixtures were checked */
+  readonly checked: number;
+  /** The fixtures that migrated, by file */
+  readonly migrated: readonly string[];
+  readonly failures: readonly MigrationFailure[];
+}
+
+/**
+ * Migrate every fixture from `oldIr` to `newIr` (Result API)
+ *
+ * Fails with IrError for an invalid IR, or a migration of a type missing
+ * from the old IR or, under its new name, from the new one; fixtures that do
+ * not migrate are failures in the report.
+ */
+export function trySimulateMigration(
+  oldIr: unknown,
+  newIr: unknown,
+  fixtures: Iterable<MigrationFixture>,
+  migrations: Readonly<Record<string, Migration>> = {},
+  options: MigrationOptions = {}
+): Result<MigrationReport, IrError> {
+  let decoder: DynamicDecoder;
+  let encoder: DynamicEncoder;
+  let newDecoder: DynamicDecoder;
+  try {
+    decoder = new DynamicDecoder(oldIr);
+    encoder = new DynamicEncoder(newIr);
+    newDecoder = new DynamicDecoder(newIr);
+  } catch (e) {
+    if (e instanceof IrError) {
+      return err(e);
+    }
+    throw e;
+  }
+  const renamed = options.renamed ?? {};
+  const newName = (type: string): string => renamed[type] ?? type;
+  for (const type of Object.keys(migrations)) {
+    if (decoder.schema(type) === undefined) {
+      return err(new IrError(`Migration of ${type}, which is not in the old IR`));
+    }
+    if (encoder.schema(newName(type)) === undefined) {
+      return err(
+        new IrError(`Migration of ${type} to ${newName(type)}, which is not in the new IR`)
+      );
+    }
+  }
+
+  let checked = 0;
+  const migrated: string[] = [];
+  const failures: MigrationFailure[] = [];
+  for (const { file, type, data } of fixtures) {
+    checked++;
+    const fail = (
+      stage: MigrationFailure["stage"],
+      message: string,
+      issues: readonly EncodeIssue[] = []
+    ): void => {
+      failures.push({ file, type, stage, message, issues });
+    };
+
+    const decoded = decoder.tryDecode(type, data);
+    if (!decoded.ok) {
+      fail("decode", decoded.error.message);
+      continue;
+    }
+    if (decoded.value.bytesRead !== data.length) {
+      const trailing = data.length - decoded.value.bytesRead;
+      fail("decode", `${String(trailing)} bytes left after the ${type}`);
+      continue;
+    }
+
+    const migration = migrations[type];
+    let value = decoded.value.value;
+    if (migration !== undefined) {
+      try {
+        value = migration(value);
+      } catch (e) {
+        fail("migrate", e instanceof Error ? e.message : String(e));
+        continue;
+      }
+    }
+
+    const target = newName(type);
+    if (encoder.schema(target) === undefined) {
+      fail("validate", `${target} is not in the new IR`);
+      continue;
+    }
+    const issues = encoder.validate(target, value);
+    if (issues.length > 0) {
+      const listed = issues.map((issue) => `${issue.path}: ${issue.message}`).join("; ");
+      fail("validate", `Invalid ${target}: ${listed}`, issues);
+      continue;
+    }
+    const encoded = encoder.tryEncode(target, value);
+    const reloaded = encoded.ok ? newDecoder.tryDecode(target, encoded.value) : encoded;
+    if (!reloaded.ok) {
+      fail("encode", reloaded.error.message);
+      continue;
+    }
+    migrated.push(file);
+  }
+  return ok({ checked, migrated, failures });
+}
+
+/**
+ * Migrate every fixture from `oldIr` to `newIr` (throwing API)
+ *
+ * Throws IrError for an invalid IR, or a migration of a type missing from
+ * either IR
+ */
+export function simulateMigration(
+  oldIr: unknown,
+  newIr: unknown,
+  fixtures: Iterable<MigrationFixture>,
+  migrations: Readonly<Record<string, Migration>> = {},
+  options: MigrationOptions = {}
+): MigrationReport {
+  return unwrap(trySimulateMigration(oldIr, newIr, fixtures, migrations, options));
+}
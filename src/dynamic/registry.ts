@@ -0,0 +1,197 @@
+/**
+ * Fetching the schemas of a connected device from a schema registry
+ *
+ * Generated bindings decode what the firmware they were generated from
+ * sends, and misread what other firmware sends. A SchemaResolver compares
+ * the schema hash a device reports in its handshake with the one of the
+ * local bindings, and when they differ downloads the snapshot the device was
+ * built from out of a registry (`postcard-ts registry serve`), decoding with
+ * a DynamicDecoder built from it instead:
+ *
+ *   const resolver = new SchemaResolver({ url: "http://registry.local:7410" });
+ *   const hello = deserialize(SchemaHashesSchema, firstFrame).value;
+ *   const { Reading } = await resolver.resolveAll(
+ *     { Reading: { schema: ReadingSchema, schemaHash: ReadingSchemaHash } },
+ *     hello
+ *   );
+ *   const reading = deserialize(Reading.schema, frame).value;
+ *
+ * Snapshots are fetched once per type and hash.
+ */
+
+import { type Result, ok, err } from "../types/result.js";
+import type { Schema } from "../types/schema.js";
+import { PostcardError } from "../types/errors.js";
+import type { SchemaHashes } from "../codec/schema-hash.js";
+import { DynamicDecoder } from "./decoder.js";
+import { IrError } from "./ir.js";
+
+export class RegistryError extends PostcardError {
+  constructor(
+    message: string,
+    /** The HTTP status the registry answered with, if it answered */
+    readonly status?: number
+  ) {
+    super(message);
+    this.name = "RegistryError";
+  }
+}
+
+/**
+ * Generated bindings of a type: its schema and `SchemaHash` constant
+ */
+export interface LocalBinding {
+  readonly schema: Schema;
+  readonly schemaHash: bigint;
+}
+
+export interface SchemaResolverOptions {
+  /** The registry's address, e.g. `http://localhost:7410` */
+  readonly url: string;
+  /** Fetches the snapshots (default: the global `fetch`) */
+  readonly fetch?: typeof fetch;
+}
+
+export interface ResolvedSchema {
+  readonly schema: Schema;
+  /** `local` for the generated bindings, `registry` for a fetched snapshot */
+  readonly source: "local" | "registry";
+  /** The decoder of the fetched snapshot, for the device's other types */
+  readonly decoder?: DynamicDecoder;
+}
+
+type Loaded = Result<DynamicDecoder, RegistryError | IrError>;
+
+export class SchemaResolver {
+  private readonly url: string;
+  private readonly fetch: typeof fetch;
+  /** Snapshots fetched or being fetched, by type and hash */
+  private readonly decoders = new Map<string, Promise<Loaded>>();
+
+  constructor(options: SchemaResolverOptions) {
+    this.url = options.url.replace(/\/+$/, "");
+    this.fetch = options.fetch ?? ((input, init) => fetch(input, init));
+  }
+
+  /**
+   * The schema to decode `type` with from a device reporting `hash`: the
+   * local bindings if their hash matches, otherwise one built from the
+   * registry's snapshot (Result API)
+   *
+   * Fails with RegistryError when the registry has no snapshot for the hash
+   * or cannot be reached, or IrError for a snapshot that does not describe
+   * the type.
+   */
+  async tryResolve(
+    type: string,
+    hash: bigint,
+    local?: LocalBinding
+  ): Promise<Result<ResolvedSchema, RegistryError | IrError>> {
+    if (local !== undefined && local.schemaHash === hash) {
+      return ok({ schema: local.schema, source: "local" });
+    }
+    const decoder = await this.decoder(type, hash);
+    if (!decoder.ok) {
+      return decoder;
+    }
+    const schema = decoder.value.schema(type);
+    if (schema === undefined) {
+      return err(new IrError(`The snapshot for ${type} has no type ${type}`));
+    }
+    return ok({ schema, source: "registry", decoder: decoder.value });
+  }
+
+  /**
+   * Resolve `type` as `tryResolve` does (throwing API)
+   *
+   * Throws RegistryError or IrError on failure
+   */
+  async resolve(type: string, hash: bigint, local?: LocalBinding): Promise<ResolvedSchema> {
+    const resolved = await this.tryResolve(type, hash, local);
+    if (!resolved.ok) {
+      throw resolved.error;
+    }
+    return resolved.value;
+  }
+
+  /**
+   * Resolve every type of `local` against the hashes a device sent in its
+   * handshake; types it sent no hash for keep their bindings (Result API)
+   */
+  async tryResolveAll(
+    local: Readonly<Record<string, LocalBinding>>,
+    received: SchemaHashes
+  ): Promise<Result<Record<string, ResolvedSchema>, RegistryError | IrError>> {
+    const hashes: ReadonlyMap<string, bigint> =
+      received instanceof Map ? received : new Map(Object.entries(received));
+    const resolved: Record<string, ResolvedSchema> = {};
+    for (const [type, binding] of Object.entries(local)) {
+      const result = await this.tryResolve(type, hashes.get(type) ?? binding.schemaHash, binding);
+      if (!result.ok) {
+        return result;
+      }
+      resolved[type] = result.value;
+    }
+    return ok(resolved);
+  }
+
+  /**
+   * Resolve every type of `local` as `tryResolveAll` does (throwing API)
+   *
+   * Throws RegistryError or IrError on failure
+   */
+  async resolveAll<L extends Readonly<Record<string, LocalBinding>>>(
+    local: L,
+    received: SchemaHashes
+  ): Promise<Record<keyof L, ResolvedSchema>> {
+    const resolved = await this.tryResolveAll(local, received);
+    if (!resolved.ok) {
+      throw resolved.error;
+    }
+    return resolved.value as Record<keyof L, ResolvedSchema>;
+  }
+
+  private decoder(type: string, hash: bigint): Promise<Loaded> {
+    const path = `${encodeURIComponent(type)}/${hash.toString(16).padStart(16, "0")}`;
+    let decoder = this.decoders.get(path);
+    if (decoder === undefined) {
+      decoder = this.download(path);
+      this.decoders.set(path, decoder);
+      // Try again next time after a failure, which may not last
+      void decoder.then((result) => {
+        if (!result.ok) {
+          this.decoders.delete(path);
+        }
+      });
+    }
+    return decoder;
+  }
+
+  private async download(path: string): Promise<Loaded> {
+    const url = `${this.url}/ir/${path}`;
+    let ir: unknown;
+    try {
+      const response = await this.fetch(url);
+      if (!response.ok) {
+        const reason = response.status === 404 ? "has no snapshot for it" : "failed";
+        return err(
+          new RegistryError(
+            `The registry ${reason}: ${String(response.status)} for ${url}`,
+            response.status
+          )
+        );
+      }
+      ir = await response.json();
+    } catch (e) {
+      return err(new RegistryError(`Fetching ${url} failed: ${String(e)}`));
+    }
+    try {
+      return ok(new DynamicDecoder(ir));
+    } catch (e) {
+      if (e instanceof IrError) {
+        return err(e);
+      }
+      throw e;
+    }
+  }
+}
@@ -0,0 +1,161 @@
+/**
+ * Locale-independent float formatting and parsing for display
+ *
+ * Decoded f32 values are widened to JavaScript doubles, so 20.3f32 shows up
+ * as 20.299999237060547. These helpers format floats the way Rust's Display
+ * does (shortest representation that round-trips at the original precision),
+ * optionally to a fixed number of significant digits, and parse user input
+ * back. Output never uses exponent notation, grouping separators or a
+ * locale-specific decimal separator.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { PostcardError } from "../types/errors.js";
+
+export type FloatPrecision = "f32" | "f64";
+
+export class FloatParseError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "FloatParseError";
+  }
+}
+
+export interface FormatFloatOptions {
+  /**
+   * Round to this many significant digits (1-100).
+   * Default: the shortest representation that round-trips at `precision`.
+   */
+  readonly significantDigits?: number;
+  /** Precision the value was decoded from (default: "f64") */
+  readonly precision?: FloatPrecision;
+  /** Keep trailing zeros produced by `significantDigits` (default: false) */
+  readonly keepTrailingZeros?: boolean;
+}
+
+export interface ParseFloatOptions {
+  /** Round the parsed value to this precision (default: "f64") */
+  readonly precision?: FloatPrecision;
+}
+
+// Nine significant digits always suffice to round-trip an f32
+const F32_MAX_SIGNIFICANT_DIGITS = 9;
+
+const DECIMAL_PATTERN = /^[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?$/;
+
+/**
+ * Shortest decimal number that decodes to the same f32 as `value`
+ *
+ * shortestF32(Math.fround(20.3)) === 20.3
+ */
+export function shortestF32(value: number): number {
+  if (!Number.isFinite(value)) {
+    return value;
+  }
+
+  const target = Math.fround(value);
+  for (let digits = 1; digits <= F32_MAX_SIGNIFICANT_DIGITS; digits++) {
+    const candidate = Number(target.toPrecision(digits));
+    if (Math.fround(candidate) === target) {
+      return candidate;
+    }
+  }
+  return target;
+}
+
+/**
+ * Format a decoded float for display
+ */
+export function formatFloat(value: number, options: FormatFloatOptions = {}): string {
+  if (!Number.isFinite(value)) {
+    return String(value);
+  }
+
+  const { significantDigits, precision = "f64", keepTrailingZeros = false } = options;
+  const base = precision === "f32" ? shortestF32(value) : value;
+
+  if (significantDigits === undefined) {
+    return expandExponent(String(base));
+  }
+
+  if (!Number.isInteger(significantDigits) || significantDigits < 1 || significantDigits > 100) {
+    throw new RangeError(
+      `significantDigits must be an integer between 1 and 100, got ${String(significantDigits)}`
+    );
+  }
+
+  const text = expandExponent(base.toPrecision(significantDigits));
+  return keepTrailingZeros ? text : trimTrailingZeros(text);
+}
+
+/**
+ * Parse a decimal number typed by a user (Result API)
+ *
+ * Accepts an optional sign, digits with "." as the decimal separator, and an
+ * optional exponent. Surrounding whitespace is ignored. Rejects anything else,
+ * including "NaN", "Infinity", grouping separators and ",".
+ */
+export function tryParseFloatInput(
+  text: string,
+  options: ParseFloatOptions = {}
+): Result<number, FloatParseError> {
+  const trimmed = text.trim();
+  if (!DECIMAL_PATTERN.test(trimmed)) {
+    return err(new FloatParseError(`Not a decimal number: "${text}"`));
+  }
+
+  const value = Number(trimmed);
+  if (!Number.isFinite(value)) {
+    return err(new FloatParseError(`Value is out of range for f64: "${text}"`));
+  }
+
+  if (options.precision === "f32") {
+    const rounded = Math.fround(value);
+    if (!Number.isFinite(rounded)) {
+      return err(new FloatParseError(`Value is out of range for f32: "${text}"`));
+    }
+    return ok(rounded);
+  }
+
+  return ok(value);
+}
+
+/**
+ * Parse a decimal number typed by a user (throwing API)
+ */
+export function parseFloatInput(text: string, options?: ParseFloatOptions): number {
+  return unwrap(tryParseFloatInput(text, options));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/**
+ * Rewrite exponent notation ("1.5e-7") as a plain decimal ("0.00000015")
+ */
+function expandExponent(text: string): string {
+  const match = /^(-?)(\d+)(?:\.(\d+))?e([+-]\d+)$/.exec(text);
+  if (match === null) {
+    return text;
+  }
+
+  const [, sign = "", intPart = "", fracPart = "", exponent = "0"] = match;
+  const digits = intPart + fracPart;
+  const pointIndex = intPart.length + Number(exponent);
+
+  if (pointIndex <= 0) {
+    return `${sign}0.${"0".repeat(-pointIndex)}${digits}`;
+  }
+  if (pointIndex >= digits.length) {
+    return `${sign}${digits}${"0".repeat(pointIndex - digits.length)}`;
+  }
+  return `${sign}${digits.slice(0, pointIndex)}.${digits.slice(pointIndex)}`;
+}
+
+function trimTrailingZeros(text: string): string {
+  if (!text.includes(".")) {
+    return text;
+  }
+  return text.replace(/\.?0+$/, "");
+}
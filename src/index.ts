@@ -11,6 +11,13 @@ export * from "./types/schema.js";
 export type { Result } from "./types/result.js";
 export { ok, err, unwrap, andThen } from "./types/result.js";
 
+// Export error-code catalogue shared with the Rust helper crate
+export * from "./types/error-codes.js";
+export * from "./types/errors.js";
+
+// Export the description of generated modules for runtime introspection
+export * from "./types/introspection.js";
+
 // Export type definitions (placeholder - not yet implemented)
 // export type * from "./types/serde.js";
 
@@ -23,6 +30,85 @@ export * from "./primitives/numbers.js";
 export * from "./primitives/string.js";
 export * from "./primitives/bytes.js";
 
+// Export schema transforms
+export * from "./transforms/scaled.js";
+export * from "./transforms/timestamp.js";
+export * from "./transforms/flags.js";
+export * from "./transforms/half.js";
+export * from "./transforms/decimal.js";
+export * from "./transforms/datetime.js";
+export * from "./transforms/uuid.js";
+export * from "./transforms/nonzero.js";
+export * from "./transforms/identifier.js";
+export * from "./transforms/discriminated.js";
+
+// Export display helpers
+export * from "./format/float.js";
+
 // Export core codec
 export * from "./codec/serializer.js";
+export * from "./codec/writer.js";
 export * from "./codec/deserializer.js";
+export * from "./codec/freeze.js";
+export * from "./codec/decode-cache.js";
+export * from "./codec/intern.js";
+export * from "./codec/columnar.js";
+export * from "./codec/cobs.js";
+export * from "./codec/length-prefix.js";
+export * from "./codec/crc.js";
+export * from "./codec/schema-hash.js";
+export * from "./codec/map-keys.js";
+export * from "./codec/peek.js";
+export * from "./codec/salvage.js";
+export * from "./codec/stream.js";
+
+// Export schemas loaded on first use
+export * from "./codec/lazy-schemas.js";
+
+// Export worker helpers
+export * from "./worker/decoder.js";
+export * from "./worker/frame-ring.js";
+
+// Export IR-driven schemas and decoding
+export * from "./dynamic/ir.js";
+export * from "./dynamic/decoder.js";
+export * from "./dynamic/encoder.js";
+export * from "./dynamic/migration.js";
+export * from "./dynamic/archive.js";
+export * from "./dynamic/registry.js";
+
+// Export telemetry helpers
+export * from "./telemetry/ring-buffer.js";
+export * from "./telemetry/capture.js";
+export * from "./telemetry/flight-recorder.js";
+export * from "./telemetry/redact.js";
+export * from "./telemetry/downsample.js";
+export * from "./telemetry/clock-skew.js";
+
+// Export helpers for outgoing control messages
+export * from "./control/outbox.js";
+
+// Export routing of incoming messages by variant
+export * from "./control/dispatcher.js";
+
+// Export the fake device for UI tests
+export * from "./control/simulator.js";
+
+// Export capability masks and feature-gated encoding
+export * from "./control/features.js";
+
+// Export transports and typed channels over them
+export * from "./transport/transport.js";
+export * from "./transport/channel.js";
+export * from "./transport/reconnect.js";
+export * from "./transport/devices.js";
+export * from "./transport/broadcast.js";
+export * from "./transport/websocket.js";
+export * from "./transport/serial.js";
+export * from "./transport/bluetooth.js";
+export * from "./transport/webusb.js";
+export * from "./transport/node-socket.js";
+export * from "./transport/mock.js";
+
+// Export the echo side of end-to-end latency measurements
+export * from "./transport/latency.js";
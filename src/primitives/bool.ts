@@ -8,10 +8,12 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import { PostcardDecodeError, ValidationError } from "../types/errors.js";
 
-export class BoolDecodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class BoolDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.BadBool) {
+    super(message, code);
     this.name = "BoolDecodeError";
   }
 }
@@ -29,12 +31,12 @@ export function tryDecodeBool(
   offset = 0
 ): Result<BoolDecodeResult, BoolDecodeError> {
   if (offset < 0 || offset >= data.length) {
-    return err(new BoolDecodeError("Offset out of bounds"));
+    return err(new BoolDecodeError("Offset out of bounds", ErrorCode.UnexpectedEnd));
   }
 
   const byte = data[offset];
   if (byte === undefined) {
-    return err(new BoolDecodeError("Unexpected end of data"));
+    return err(new BoolDecodeError("Unexpected end of data", ErrorCode.UnexpectedEnd));
   }
 
   if (byte === 0x00) {
@@ -57,9 +59,9 @@ export function decodeBool(data: Uint8Array, offset?: number): BoolDecodeResult
 // ENCODING
 // ============================================================================
 
-export class BoolEncodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class BoolEncodeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "BoolEncodeError";
   }
 }
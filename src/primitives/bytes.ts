@@ -6,11 +6,14 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
-import { tryDecodeVarintU64, tryEncodeVarintU64 } from "../codec/varint.js";
-
-export class BytesDecodeError extends Error {
-  constructor(message: string) {
-    super(message);
+import { ErrorCode } from "../types/error-codes.js";
+import { tryDecodeVarintU64 } from "../codec/varint.js";
+import { PostcardDecodeError, ValidationError } from "../types/errors.js";
+import { Writer } from "../codec/writer.js";
+
+export class BytesDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.UnexpectedEnd) {
+    super(message, code);
     this.name = "BytesDecodeError";
   }
 }
@@ -23,10 +26,14 @@ export interface BytesDecodeResult {
 /**
  * Decode a byte array (Result API)
  * Format: varint(usize) length + raw bytes
+ *
+ * The bytes are copied out of `data`, unless `view` is true: then they are a
+ * view into it (`subarray`), which is free but sees later writes to `data`.
  */
 export function tryDecodeBytes(
   data: Uint8Array,
-  offset = 0
+  offset = 0,
+  view = false
 ): Result<BytesDecodeResult, BytesDecodeError> {
   if (offset < 0 || offset >= data.length) {
     return err(new BytesDecodeError("Offset out of bounds"));
@@ -35,7 +42,12 @@ export function tryDecodeBytes(
   // Decode length as varint
   const lengthResult = tryDecodeVarintU64(data, offset);
   if (!lengthResult.ok) {
-    return err(new BytesDecodeError(`Failed to decode bytes length: ${lengthResult.error.message}`));
+    return err(
+      new BytesDecodeError(
+        `Failed to decode bytes length: ${lengthResult.error.message}`,
+        lengthResult.error.code
+      )
+    );
   }
 
   const length = Number(lengthResult.value.value);
@@ -46,8 +58,8 @@ export function tryDecodeBytes(
     return err(new BytesDecodeError("Not enough bytes for byte array data"));
   }
 
-  // Extract bytes
-  const value = data.slice(offset + lengthBytes, offset + lengthBytes + length);
+  const start = offset + lengthBytes;
+  const value = view ? data.subarray(start, start + length) : data.slice(start, start + length);
 
   return ok({ value, bytesRead: lengthBytes + length });
 }
@@ -55,17 +67,17 @@ export function tryDecodeBytes(
 /**
  * Decode a byte array (throwing API)
  */
-export function decodeBytes(data: Uint8Array, offset?: number): BytesDecodeResult {
-  return unwrap(tryDecodeBytes(data, offset));
+export function decodeBytes(data: Uint8Array, offset?: number, view?: boolean): BytesDecodeResult {
+  return unwrap(tryDecodeBytes(data, offset, view));
 }
 
 // ============================================================================
 // ENCODING
 // ============================================================================
 
-export class BytesEncodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class BytesEncodeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "BytesEncodeError";
   }
 }
@@ -75,6 +87,14 @@ export interface BytesEncodeResult {
   bytesWritten: number;
 }
 
+/**
+ * Write a byte array: varint(usize) length + raw bytes
+ */
+export function writeBytes(writer: Writer, value: Uint8Array): void {
+  writer.writeVarint(value.length);
+  writer.writeBytes(value);
+}
+
 /**
  * Encode a byte array (Result API)
  * Format: varint(usize) length + raw bytes
@@ -82,21 +102,9 @@ export interface BytesEncodeResult {
 export function tryEncodeBytes(
   value: Uint8Array
 ): Result<BytesEncodeResult, BytesEncodeError> {
-  // Encode length as varint
-  const lengthResult = tryEncodeVarintU64(BigInt(value.length));
-  if (!lengthResult.ok) {
-    return err(new BytesEncodeError(`Failed to encode bytes length: ${lengthResult.error.message}`));
-  }
-
-  // Combine length + raw bytes
-  const result = new Uint8Array(lengthResult.value.bytesWritten + value.length);
-  result.set(lengthResult.value.bytes, 0);
-  result.set(value, lengthResult.value.bytesWritten);
-
-  return ok({
-    bytes: result,
-    bytesWritten: lengthResult.value.bytesWritten + value.length
-  });
+  const writer = new Writer(value.length + 8);
+  writeBytes(writer, value);
+  return ok({ bytes: writer.toBytes(), bytesWritten: writer.length });
 }
 
 /**
@@ -9,10 +9,13 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
+import { ErrorCode } from "../types/error-codes.js";
+import { PostcardDecodeError, ValidationError } from "../types/errors.js";
+import { Writer } from "../codec/writer.js";
 
-export class NumberDecodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class NumberDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.UnexpectedEnd) {
+    super(message, code);
     this.name = "NumberDecodeError";
   }
 }
@@ -132,9 +135,9 @@ export function decodeF64(data: Uint8Array, offset?: number): NumberDecodeResult
 // ENCODING
 // ============================================================================
 
-export class NumberEncodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class NumberEncodeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "NumberEncodeError";
   }
 }
@@ -144,17 +147,40 @@ export interface NumberEncodeResult {
   bytesWritten: number;
 }
 
+/**
+ * Write an unsigned 8-bit integer, returning the error instead if `value` is
+ * out of range
+ */
+export function writeU8(writer: Writer, value: number): NumberEncodeError | undefined {
+  if (value < 0 || value > 255) {
+    return new NumberEncodeError(`Value ${String(value)} is out of range for u8`);
+  }
+  writer.writeByte(value);
+  return undefined;
+}
+
+/**
+ * Write a signed 8-bit integer, returning the error instead if `value` is
+ * out of range
+ */
+export function writeI8(writer: Writer, value: number): NumberEncodeError | undefined {
+  if (value < -128 || value > 127) {
+    return new NumberEncodeError(`Value ${String(value)} is out of range for i8`);
+  }
+  // Convert to unsigned using two's complement
+  writer.writeByte(value < 0 ? value + 256 : value);
+  return undefined;
+}
+
 /**
  * Encode an unsigned 8-bit integer (Result API)
  */
 export function tryEncodeU8(
   value: number
 ): Result<NumberEncodeResult, NumberEncodeError> {
-  if (value < 0 || value > 255) {
-    return err(new NumberEncodeError(`Value ${String(value)} is out of range for u8`));
-  }
-
-  return ok({ bytes: new Uint8Array([value]), bytesWritten: 1 });
+  const writer = new Writer(1);
+  const error = writeU8(writer, value);
+  return error === undefined ? ok({ bytes: writer.view(), bytesWritten: 1 }) : err(error);
 }
 
 /**
@@ -163,13 +189,9 @@ export function tryEncodeU8(
 export function tryEncodeI8(
   value: number
 ): Result<NumberEncodeResult, NumberEncodeError> {
-  if (value < -128 || value > 127) {
-    return err(new NumberEncodeError(`Value ${String(value)} is out of range for i8`));
-  }
-
-  // Convert to unsigned using two's complement
-  const unsigned = value < 0 ? value + 256 : value;
-  return ok({ bytes: new Uint8Array([unsigned]), bytesWritten: 1 });
+  const writer = new Writer(1);
+  const error = writeI8(writer, value);
+  return error === undefined ? ok({ bytes: writer.view(), bytesWritten: 1 }) : err(error);
 }
 
 /**
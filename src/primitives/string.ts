@@ -7,11 +7,14 @@
  */
 
 import { type Result, ok, err, unwrap } from "../types/result.js";
-import { tryDecodeVarintU64, tryEncodeVarintU64 } from "../codec/varint.js";
-
-export class StringDecodeError extends Error {
-  constructor(message: string) {
-    super(message);
+import { ErrorCode } from "../types/error-codes.js";
+import { tryDecodeVarintU64 } from "../codec/varint.js";
+import { PostcardDecodeError, ValidationError } from "../types/errors.js";
+import { Writer } from "../codec/writer.js";
+
+export class StringDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.UnexpectedEnd) {
+    super(message, code);
     this.name = "StringDecodeError";
   }
 }
@@ -36,7 +39,12 @@ export function tryDecodeString(
   // Decode length as varint
   const lengthResult = tryDecodeVarintU64(data, offset);
   if (!lengthResult.ok) {
-    return err(new StringDecodeError(`Failed to decode string length: ${lengthResult.error.message}`));
+    return err(
+      new StringDecodeError(
+        `Failed to decode string length: ${lengthResult.error.message}`,
+        lengthResult.error.code
+      )
+    );
   }
 
   const length = Number(lengthResult.value.value);
@@ -56,7 +64,12 @@ export function tryDecodeString(
     const value = decoder.decode(stringBytes);
     return ok({ value, bytesRead: lengthBytes + length });
   } catch (e) {
-    return err(new StringDecodeError(`Invalid UTF-8: ${e instanceof Error ? e.message : String(e)}`));
+    return err(
+      new StringDecodeError(
+        `Invalid UTF-8: ${e instanceof Error ? e.message : String(e)}`,
+        ErrorCode.BadUtf8
+      )
+    );
   }
 }
 
@@ -70,7 +83,11 @@ export function tryDecodeChar(
 ): Result<StringDecodeResult, StringDecodeError> {
   const stringResult = tryDecodeString(data, offset);
   if (!stringResult.ok) {
-    return stringResult;
+    // postcard reports any malformed char as BAD_CHAR, including invalid UTF-8
+    const { message, code } = stringResult.error;
+    return code === ErrorCode.BadUtf8
+      ? err(new StringDecodeError(message, ErrorCode.BadChar))
+      : stringResult;
   }
 
   const { value, bytesRead } = stringResult.value;
@@ -79,7 +96,12 @@ export function tryDecodeChar(
   // In JavaScript, we count code points, not UTF-16 code units
   const codePoints = Array.from(value);
   if (codePoints.length !== 1) {
-    return err(new StringDecodeError(`Char must be a single Unicode scalar value, got ${String(codePoints.length)} code points`));
+    return err(
+      new StringDecodeError(
+        `Char must be a single Unicode scalar value, got ${String(codePoints.length)} code points`,
+        ErrorCode.BadChar
+      )
+    );
   }
 
   return ok({ value, bytesRead });
@@ -101,9 +123,9 @@ export function decodeChar(data: Uint8Array, offset?: number): StringDecodeResul
 // ENCODING
 // ============================================================================
 
-export class StringEncodeError extends Error {
-  constructor(message: string) {
-    super(message);
+export class StringEncodeError extends ValidationError {
+  constructor(message: string, code: ErrorCode = ErrorCode.SerializeFailed) {
+    super(message, code);
     this.name = "StringEncodeError";
   }
 }
@@ -113,6 +135,14 @@ export interface StringEncodeResult {
   bytesWritten: number;
 }
 
+/**
+ * Write a UTF-8 string: varint(usize) length + UTF-8 bytes
+ */
+export function writeString(writer: Writer, value: string): void {
+  // Anything else is converted as TextEncoder would
+  writer.writeString(String(value));
+}
+
 /**
  * Encode a UTF-8 string (Result API)
  * Format: varint(usize) length + UTF-8 bytes
@@ -120,25 +150,58 @@ export interface StringEncodeResult {
 export function tryEncodeString(
   value: string
 ): Result<StringEncodeResult, StringEncodeError> {
-  // Encode string as UTF-8
-  const encoder = new TextEncoder();
-  const stringBytes = encoder.encode(value);
+  const writer = new Writer();
+  writeString(writer, value);
+  return ok({ bytes: writer.toBytes(), bytesWritten: writer.length });
+}
 
-  // Encode length as varint
-  const lengthResult = tryEncodeVarintU64(BigInt(stringBytes.length));
-  if (!lengthResult.ok) {
-    return err(new StringEncodeError(`Failed to encode string length: ${lengthResult.error.message}`));
-  }
+export interface TruncatedString {
+  /** The longest prefix of whole characters that fits */
+  text: string;
+  /** Whether anything was cut off */
+  truncated: boolean;
+}
 
-  // Combine length + string bytes
-  const result = new Uint8Array(lengthResult.value.bytesWritten + stringBytes.length);
-  result.set(lengthResult.value.bytes, 0);
-  result.set(stringBytes, lengthResult.value.bytesWritten);
+/**
+ * Cut `text` to at most `maxBytes` bytes of UTF-8 without splitting a
+ * character, as a fixed-capacity string on the device must
+ *
+ *   truncateUtf8("°C", 2); // { text: "°", truncated: true }
+ *
+ * Characters of several code units (emoji) are kept or dropped whole;
+ * grapheme clusters such as flags may still be split between characters.
+ */
+export function truncateUtf8(text: string, maxBytes: number): TruncatedString {
+  const bytes = new TextEncoder().encode(text);
+  if (bytes.length <= maxBytes) {
+    return { text, truncated: false };
+  }
+  // Back up from the cut to the start of the character it falls in
+  let end = Math.max(maxBytes, 0);
+  while (end > 0 && ((bytes[end] ?? 0) & 0xc0) === 0x80) {
+    end--;
+  }
+  return { text: new TextDecoder().decode(bytes.subarray(0, end)), truncated: true };
+}
 
-  return ok({
-    bytes: result,
-    bytesWritten: lengthResult.value.bytesWritten + stringBytes.length
-  });
+/**
+ * Write a char (single Unicode scalar value), returning the error instead if
+ * `value` is not one
+ */
+export function writeChar(writer: Writer, value: string): StringEncodeError | undefined {
+  // One UTF-16 code unit, or a surrogate pair, without splitting the string
+  const single =
+    // eslint-disable-next-line @typescript-eslint/no-unnecessary-condition
+    typeof value === "string" &&
+    (value.length === 1 || (value.length === 2 && (value.codePointAt(0) ?? 0) > 0xffff));
+  if (!single) {
+    const codePoints = Array.from(value);
+    return new StringEncodeError(
+      `Char must be a single Unicode scalar value, got ${String(codePoints.length)} code points`
+    );
+  }
+  writer.writeString(value);
+  return undefined;
 }
 
 /**
@@ -148,13 +211,12 @@ export function tryEncodeString(
 export function tryEncodeChar(
   value: string
 ): Result<StringEncodeResult, StringEncodeError> {
-  // Validate it's a single Unicode scalar value
-  const codePoints = Array.from(value);
-  if (codePoints.length !== 1) {
-    return err(new StringEncodeError(`Char must be a single Unicode scalar value, got ${String(codePoints.length)} code points`));
+  const writer = new Writer();
+  const error = writeChar(writer, value);
+  if (error !== undefined) {
+    return err(error);
   }
-
-  return tryEncodeString(value);
+  return ok({ bytes: writer.toBytes(), bytesWritten: writer.length });
 }
 
 // ============================================================================
@@ -0,0 +1,82 @@
+/**
+ * Capture files of raw frames
+ *
+ * A capture stores raw frames exactly as they came off the wire, each with the
+ * time it was received, so a session can be decoded after the fact (possibly
+ * with a newer schema) or replayed. The file is itself a postcard value, so
+ * Rust tooling can read it with the matching structs:
+ *
+ *   struct Capture { version: u32, frames: Vec<CapturedFrame> }
+ *   struct CapturedFrame { time_ms: u64, data: Vec<u8> }
+ *
+ * where `time_ms` is milliseconds since the Unix epoch.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import { struct, seq, bytes, u32, u64, type InferType } from "../types/schema.js";
+import { timestamp } from "../transforms/timestamp.js";
+import { serialize } from "../codec/serializer.js";
+import { tryDeserialize } from "../codec/deserializer.js";
+import { PostcardError } from "../types/errors.js";
+
+export class CaptureError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "CaptureError";
+  }
+}
+
+/**
+ * Capture format version written by this library
+ */
+export const CAPTURE_VERSION = 1;
+
+export const CapturedFrameSchema = struct({
+  time_ms: timestamp(u64(), "unix_millis"),
+  data: bytes(),
+});
+
+export const CaptureSchema = struct({
+  version: u32(),
+  frames: seq(CapturedFrameSchema),
+});
+
+export type CapturedFrame = InferType<typeof CapturedFrameSchema>;
+
+/**
+ * Encode frames as a capture file
+ */
+export function encodeCapture(frames: readonly CapturedFrame[]): Uint8Array {
+  return serialize(CaptureSchema, { version: CAPTURE_VERSION, frames: [...frames] });
+}
+
+/**
+ * Decode the frames of a capture file (Result API)
+ */
+export function tryDecodeCapture(data: Uint8Array): Result<CapturedFrame[], CaptureError> {
+  // Check the version first: other versions may not decode with this schema
+  const version = tryDeserialize(u32(), data);
+  if (version.ok && version.value.value !== CAPTURE_VERSION) {
+    return err(new CaptureError(`Unsupported capture version ${String(version.value.value)}`));
+  }
+
+  const result = tryDeserialize(CaptureSchema, data);
+  if (!result.ok) {
+    return err(new CaptureError(`Failed to decode capture: ${result.error.message}`));
+  }
+  if (result.value.bytesRead !== data.length) {
+    return err(
+      new CaptureError(`Capture has ${String(data.length - result.value.bytesRead)} trailing bytes`)
+    );
+  }
+  return ok(result.value.value.frames);
+}
+
+/**
+ * Decode the frames of a capture file (throwing API)
+ *
+ * Throws CaptureError on failure
+ */
+export function decodeCapture(data: Uint8Array): CapturedFrame[] {
+  return unwrap(tryDecodeCapture(data));
+}
@@ -0,0 +1,184 @@
+/**
+ * Device clock-skew correction
+ *
+ * Device timestamps (uptime counters, RTC readings) rarely agree with the
+ * browser clock: they start from a different epoch and drift by tens of ppm.
+ * A ping exchange measures the device clock between two browser readings;
+ * `ClockSkewEstimator` fits offset and drift to a window of such samples,
+ * favouring the ones with the shortest round trip, whose timing is the least
+ * ambiguous. `withCorrectedTime()` then attaches the browser-time `Date` of a
+ * designated timestamp field to each decoded message.
+ *
+ * Browser times are `Date.now()`-style milliseconds. Device times may be in
+ * any unit that `deviceUnitMs` converts to milliseconds.
+ */
+
+/**
+ * One ping exchange: the device clock read between two browser clock readings
+ */
+export interface ClockSample {
+  /** Browser time the ping was sent */
+  readonly sentAt: number;
+  /** Device time in the reply */
+  readonly deviceTime: number;
+  /** Browser time the reply arrived */
+  readonly receivedAt: number;
+}
+
+/**
+ * Maps device timestamps to browser time; implement this to plug in another
+ * synchronisation scheme
+ */
+export interface ClockModel {
+  /** Browser time (ms) of a device timestamp, or undefined while unknown */
+  toBrowserTime(deviceTime: number): number | undefined;
+}
+
+export interface ClockSkewOptions {
+  /** Most recent samples kept for the fit (default 32) */
+  readonly maxSamples?: number;
+  /** Milliseconds per device time unit (default 1; e.g. 0.001 for microseconds) */
+  readonly deviceUnitMs?: number;
+}
+
+/**
+ * Least-squares offset and drift estimate from ping samples
+ *
+ *   const clock = new ClockSkewEstimator();
+ *   setInterval(() => void clock.measure(() => device.readClock()), 10_000);
+ */
+export class ClockSkewEstimator implements ClockModel {
+  readonly maxSamples: number;
+  readonly deviceUnitMs: number;
+
+  private readonly samples: ClockSample[] = [];
+  private fit: { offset: number; drift: number; reference: number } | undefined;
+
+  constructor(options: ClockSkewOptions = {}) {
+    const { maxSamples = 32, deviceUnitMs = 1 } = options;
+    if (!Number.isInteger(maxSamples) || maxSamples < 1) {
+      throw new RangeError(`maxSamples must be a positive integer, got ${String(maxSamples)}`);
+    }
+    if (!Number.isFinite(deviceUnitMs) || deviceUnitMs <= 0) {
+      throw new RangeError(`deviceUnitMs must be positive, got ${String(deviceUnitMs)}`);
+    }
+    this.maxSamples = maxSamples;
+    this.deviceUnitMs = deviceUnitMs;
+  }
+
+  /**
+   * Device minus browser time (ms) at the newest sample, once any sample exists
+   */
+  get offset(): number | undefined {
+    return this.fit?.offset;
+  }
+
+  /**
+   * Rate difference of the device clock: 50e-6 means it gains 50 µs per second
+   */
+  get drift(): number {
+    return this.fit?.drift ?? 0;
+  }
+
+  /**
+   * Record a ping exchange and refit
+   */
+  addSample(sample: ClockSample): void {
+    const { sentAt, deviceTime, receivedAt } = sample;
+    if (![sentAt, deviceTime, receivedAt].every(Number.isFinite) || receivedAt < sentAt) {
+      throw new RangeError(
+        `Invalid clock sample (sent ${String(sentAt)}, received ${String(receivedAt)})`
+      );
+    }
+
+    this.samples.push(sample);
+    if (this.samples.length > this.maxSamples) {
+      this.samples.shift();
+    }
+    this.refit();
+  }
+
+  /**
+   * Run one ping exchange and record it
+   *
+   * `ping` asks the device for its clock; `now` reads the browser clock.
+   */
+  async measure(
+    ping: () => Promise<number | bigint>,
+    now: () => number = Date.now
+  ): Promise<ClockSample> {
+    const sentAt = now();
+    const deviceTime = Number(await ping());
+    const sample: ClockSample = { sentAt, deviceTime, receivedAt: now() };
+    this.addSample(sample);
+    return sample;
+  }
+
+  toBrowserTime(deviceTime: number): number | undefined {
+    if (this.fit === undefined) {
+      return undefined;
+    }
+    // Solve device = t + offset + drift × (t - reference) for browser time t
+    const { offset, drift, reference } = this.fit;
+    const device = deviceTime * this.deviceUnitMs;
+    return (device - offset + drift * reference) / (1 + drift);
+  }
+
+  private refit(): void {
+    // Samples with a round trip close to the best one bound the offset tightly;
+    // the rest mostly add queueing noise
+    const bestRtt = Math.min(...this.samples.map((s) => s.receivedAt - s.sentAt));
+    const points = this.samples
+      .filter((s) => s.receivedAt - s.sentAt <= 2 * bestRtt + 1)
+      .map((s) => {
+        const t = (s.sentAt + s.receivedAt) / 2;
+        return { t, offset: s.deviceTime * this.deviceUnitMs - t };
+      });
+
+    const reference = Math.max(...points.map((p) => p.t));
+    const meanT = points.reduce((sum, p) => sum + p.t, 0) / points.length;
+    const meanOffset = points.reduce((sum, p) => sum + p.offset, 0) / points.length;
+
+    let covariance = 0;
+    let variance = 0;
+    for (const p of points) {
+      covariance += (p.t - meanT) * (p.offset - meanOffset);
+      variance += (p.t - meanT) ** 2;
+    }
+    const drift = variance > 0 ? covariance / variance : 0;
+
+    this.fit = { offset: meanOffset + drift * (reference - meanT), drift, reference };
+  }
+}
+
+/**
+ * A decoded message with the browser time of its device timestamp
+ */
+export interface Timed<T> {
+  readonly value: T;
+  /** Undefined while the clock model has no estimate yet */
+  readonly time: Date | undefined;
+}
+
+type TimestampField<T> = {
+  [K in keyof T]: T[K] extends number | bigint ? K : never;
+}[keyof T];
+
+/**
+ * Wrap decoded messages with the corrected time of their `field` timestamp
+ *
+ *   const timed = withCorrectedTime<Reading>(clock, "uptime_ms");
+ *   const { value, time } = timed(deserialize(ReadingSchema, bytes).value);
+ */
+export function withCorrectedTime<T>(
+  clock: ClockModel,
+  field: TimestampField<T>
+): (message: T) => Timed<T> {
+  return (message) => {
+    const browserTime = clock.toBrowserTime(Number(message[field]));
+    return {
+      value: message,
+      time: browserTime === undefined ? undefined : new Date(browserTime),
+    };
+  };
+}
@@ -0,0 +1,324 @@
+/**
+ * Downsampling and windowed aggregation for decoded time series
+ *
+ * Dashboards rarely draw every decoded sample: a chart a thousand pixels wide
+ * needs about a thousand points, and live views summarise samples per time
+ * window. These helpers work on a series of x (usually time) and y columns,
+ * which is exactly what `decodeColumns()` produces; `seriesOf()` builds one
+ * from an array of decoded messages. The downsamplers return the indices of
+ * the points they keep, so the same selection can be applied to the other
+ * columns or to the original messages with `pick()` / `pickColumns()`.
+ */
+
+import type { NumericArray } from "../codec/columnar.js";
+
+/**
+ * Parallel x and y columns of equal length, x non-decreasing
+ */
+export interface Series {
+  readonly x: ArrayLike<number>;
+  readonly y: ArrayLike<number>;
+}
+
+/**
+ * Summary of the samples in one aggregation window
+ */
+export interface WindowSummary {
+  /** Start of the window on the x axis (inclusive) */
+  readonly start: number;
+  readonly count: number;
+  readonly min: number;
+  readonly max: number;
+  readonly mean: number;
+}
+
+/**
+ * Window summaries as columns, one entry per non-empty window
+ */
+export interface Windows {
+  readonly start: Float64Array;
+  readonly count: Uint32Array;
+  readonly min: Float64Array;
+  readonly max: Float64Array;
+  readonly mean: Float64Array;
+}
+
+/**
+ * Build a series from decoded messages
+ *
+ *   const series = seriesOf(readings, (r) => r.timestamp_ms, (r) => r.temperature);
+ */
+export function seriesOf<T>(
+  items: readonly T[],
+  x: (item: T) => number,
+  y: (item: T) => number
+): { x: Float64Array; y: Float64Array } {
+  const xs = new Float64Array(items.length);
+  const ys = new Float64Array(items.length);
+  items.forEach((item, i) => {
+    xs[i] = x(item);
+    ys[i] = y(item);
+  });
+  return { x: xs, y: ys };
+}
+
+/**
+ * Select items by index, e.g. the messages a downsampler kept
+ */
+export function pick<T>(items: ArrayLike<T>, indices: ArrayLike<number>): T[] {
+  return Array.from(indices, (index) => items[index] as T);
+}
+
+/**
+ * Select the same indices from every column, keeping each column's typed array type
+ */
+export function pickColumns<C extends Record<string, NumericArray>>(
+  columns: C,
+  indices: ArrayLike<number>
+): C {
+  const picked: Record<string, NumericArray> = {};
+  for (const [name, column] of Object.entries(columns)) {
+    const Constructor = column.constructor as new (length: number) => NumericArray;
+    const out = new Constructor(indices.length);
+    for (let i = 0; i < indices.length; i++) {
+      out[i] = column[indices[i] as number] as number;
+    }
+    picked[name] = out;
+  }
+  return picked as C;
+}
+
+/**
+ * Largest-Triangle-Three-Buckets downsampling
+ *
+ * Keeps `threshold` points (including the first and last) chosen to preserve
+ * the visual shape of the line. Returns the kept indices in ascending order;
+ * every index when the series has no more than `threshold` points.
+ */
+export function lttb(series: Series, threshold: number): Uint32Array {
+  const n = checkSeries(series);
+  if (!Number.isInteger(threshold) || threshold < 2) {
+    throw new RangeError(`Threshold must be an integer of at least 2, got ${String(threshold)}`);
+  }
+  if (n <= threshold) {
+    return allIndices(n);
+  }
+
+  const { x, y } = series;
+  const at = (column: ArrayLike<number>, i: number): number => column[i] as number;
+  const kept = new Uint32Array(threshold);
+  // Points between the fixed first and last are split into threshold - 2 buckets
+  const every = (n - 2) / (threshold - 2);
+  let previous = 0;
+
+  for (let bucket = 0; bucket < threshold - 2; bucket++) {
+    // Average of the next bucket (or the last point) is the third triangle corner
+    const nextStart = Math.floor((bucket + 1) * every) + 1;
+    const nextEnd = Math.min(Math.floor((bucket + 2) * every) + 1, n);
+    let avgX = 0;
+    let avgY = 0;
+    for (let i = nextStart; i < nextEnd; i++) {
+      avgX += at(x, i);
+      avgY += at(y, i);
+    }
+    avgX /= nextEnd - nextStart;
+    avgY /= nextEnd - nextStart;
+
+    const start = Math.floor(bucket * every) + 1;
+    const end = Math.floor((bucket + 1) * every) + 1;
+    const px = at(x, previous);
+    const py = at(y, previous);
+    let best = start;
+    let bestArea = -1;
+    for (let i = start; i < end; i++) {
+      const area = Math.abs((px - avgX) * (at(y, i) - py) - (px - at(x, i)) * (avgY - py));
+      if (area > bestArea) {
+        bestArea = area;
+        best = i;
+      }
+    }
+
+    kept[bucket + 1] = best;
+    previous = best;
+  }
+
+  kept[threshold - 1] = n - 1;
+  return kept;
+}
+
+/**
+ * Min/max downsampling
+ *
+ * Splits the series into `buckets` runs of consecutive points and keeps the
+ * lowest and highest point of each, so spikes survive however far the series
+ * is reduced. Returns up to 2 × `buckets` indices in ascending order; NaN
+ * values are never selected.
+ */
+export function minMax(series: Series, buckets: number): Uint32Array {
+  const n = checkSeries(series);
+  if (!Number.isInteger(buckets) || buckets < 1) {
+    throw new RangeError(`Bucket count must be a positive integer, got ${String(buckets)}`);
+  }
+  if (n <= 2 * buckets) {
+    return allIndices(n);
+  }
+
+  const { y } = series;
+  const kept: number[] = [];
+  for (let bucket = 0; bucket < buckets; bucket++) {
+    const start = Math.floor((bucket * n) / buckets);
+    const end = Math.floor(((bucket + 1) * n) / buckets);
+    let low = -1;
+    let high = -1;
+    for (let i = start; i < end; i++) {
+      const value = y[i] as number;
+      if (Number.isNaN(value)) {
+        continue;
+      }
+      if (low === -1 || value < (y[low] as number)) {
+        low = i;
+      }
+      if (high === -1 || value > (y[high] as number)) {
+        high = i;
+      }
+    }
+    if (low === -1) {
+      continue;
+    }
+    if (low !== high) {
+      kept.push(Math.min(low, high), Math.max(low, high));
+    } else {
+      kept.push(low);
+    }
+  }
+  return Uint32Array.from(kept);
+}
+
+/**
+ * Incremental fixed-width window aggregation for live streams
+ *
+ * Windows are `[origin + k × width, origin + (k + 1) × width)`; the default
+ * origin of 0 aligns them to multiples of the width, e.g. whole seconds of a
+ * millisecond timestamp. Samples must arrive in non-decreasing x order. NaN
+ * values are ignored.
+ *
+ *   const perSecond = new WindowAggregator(1000);
+ *   for (const r of readings) {
+ *     const closed = perSecond.push(r.timestamp_ms, r.temperature);
+ *     if (closed !== undefined) chart.append(closed);
+ *   }
+ */
+export class WindowAggregator {
+  private index: number | undefined;
+  private count = 0;
+  private sum = 0;
+  private min = Infinity;
+  private max = -Infinity;
+
+  constructor(
+    readonly width: number,
+    readonly origin = 0
+  ) {
+    if (!Number.isFinite(width) || width <= 0) {
+      throw new RangeError(`Window width must be positive and finite, got ${String(width)}`);
+    }
+  }
+
+  /**
+   * Add a sample, returning the previous window if this sample closed it
+   */
+  push(x: number, y: number): WindowSummary | undefined {
+    if (Number.isNaN(x) || Number.isNaN(y)) {
+      return undefined;
+    }
+
+    const index = Math.floor((x - this.origin) / this.width);
+    if (this.index !== undefined && index < this.index) {
+      throw new RangeError(`Sample at ${String(x)} is earlier than the current window`);
+    }
+    let closed: WindowSummary | undefined;
+    if (this.index !== index) {
+      closed = this.flush();
+      this.index = index;
+    }
+
+    this.count++;
+    this.sum += y;
+    this.min = Math.min(this.min, y);
+    this.max = Math.max(this.max, y);
+    return closed;
+  }
+
+  /**
+   * Close the current window, if it holds any samples, and return its summary
+   */
+  flush(): WindowSummary | undefined {
+    if (this.index === undefined) {
+      return undefined;
+    }
+
+    const summary: WindowSummary = {
+      start: this.origin + this.index * this.width,
+      count: this.count,
+      min: this.min,
+      max: this.max,
+      mean: this.sum / this.count,
+    };
+    this.index = undefined;
+    this.count = 0;
+    this.sum = 0;
+    this.min = Infinity;
+    this.max = -Infinity;
+    return summary;
+  }
+}
+
+/**
+ * Aggregate a whole series into fixed-width windows (see WindowAggregator)
+ *
+ *   const { start, mean } = aggregateWindows({ x: columns.t, y: columns.value }, 1000);
+ */
+export function aggregateWindows(series: Series, width: number, origin = 0): Windows {
+  const n = checkSeries(series);
+  const aggregator = new WindowAggregator(width, origin);
+  const summaries: WindowSummary[] = [];
+  for (let i = 0; i < n; i++) {
+    const closed = aggregator.push(series.x[i] as number, series.y[i] as number);
+    if (closed !== undefined) {
+      summaries.push(closed);
+    }
+  }
+  const last = aggregator.flush();
+  if (last !== undefined) {
+    summaries.push(last);
+  }
+
+  return {
+    start: Float64Array.from(summaries, (s) => s.start),
+    count: Uint32Array.from(summaries, (s) => s.count),
+    min: Float64Array.from(summaries, (s) => s.min),
+    max: Float64Array.from(summaries, (s) => s.max),
+    mean: Float64Array.from(summaries, (s) => s.mean),
+  };
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+function checkSeries(series: Series): number {
+  if (series.x.length !== series.y.length) {
+    throw new RangeError(
+      `Series columns differ in length (x: ${String(series.x.length)}, y: ${String(series.y.length)})`
+    );
+  }
+  return series.x.length;
+}
+
+function allIndices(n: number): Uint32Array {
+  const indices = new Uint32Array(n);
+  for (let i = 0; i < n; i++) {
+    indices[i] = i;
+  }
+  return indices;
+}
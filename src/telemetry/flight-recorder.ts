@@ -0,0 +1,131 @@
+/**
+ * Flight recorder for raw frames
+ *
+ * When a user reports a bug, the frames the device sent just before matter
+ * most, and by then they are long gone. A flight recorder keeps the most
+ * recent raw frames (bounded by age and total size) and exports them as a
+ * capture file on demand, so the session can be decoded after the fact.
+ *
+ * Recording is opt-in: hook `record()` into whatever delivers frames (Web
+ * Serial, WebSocket, WebUSB), or wrap the frame handler with `tap()`.
+ */
+
+import { type CapturedFrame, encodeCapture } from "./capture.js";
+
+export interface FlightRecorderOptions {
+  /** Frames older than this many milliseconds are discarded */
+  readonly retentionMs: number;
+  /** Upper bound on the total size of retained frames (default: unbounded) */
+  readonly maxBytes?: number;
+  /** Clock for frame timestamps (default: Date.now) */
+  readonly now?: () => number;
+}
+
+export class FlightRecorder {
+  readonly retentionMs: number;
+  readonly maxBytes: number;
+
+  private readonly now: () => number;
+  // Queue of frames, oldest at `head`; compacted once half of it is stale
+  private frames: { time: number; data: Uint8Array }[] = [];
+  private head = 0;
+  private bytes = 0;
+
+  constructor(options: FlightRecorderOptions) {
+    const { retentionMs, maxBytes = Infinity, now = Date.now } = options;
+    if (!(retentionMs > 0)) {
+      throw new RangeError(`retentionMs must be positive, got ${String(retentionMs)}`);
+    }
+    if (!(maxBytes > 0)) {
+      throw new RangeError(`maxBytes must be positive, got ${String(maxBytes)}`);
+    }
+    this.retentionMs = retentionMs;
+    this.maxBytes = maxBytes;
+    this.now = now;
+  }
+
+  /**
+   * Number of frames currently retained
+   */
+  get size(): number {
+    this.evict(this.now());
+    return this.frames.length - this.head;
+  }
+
+  /**
+   * Total bytes of the retained frames
+   */
+  get byteLength(): number {
+    this.evict(this.now());
+    return this.bytes;
+  }
+
+  /**
+   * Record one raw frame
+   *
+   * The bytes are copied, since transports commonly reuse their buffers.
+   */
+  record(frame: Uint8Array): void {
+    const time = this.now();
+    this.frames.push({ time, data: frame.slice() });
+    this.bytes += frame.length;
+    this.evict(time);
+  }
+
+  /**
+   * Wrap a frame handler so every frame is recorded before it is handled
+   *
+   *   port.onFrame = recorder.tap((frame) => render(deserialize(TelemetrySchema, frame).value));
+   */
+  tap<A extends unknown[], R>(
+    handler: (frame: Uint8Array, ...rest: A) => R
+  ): (frame: Uint8Array, ...rest: A) => R {
+    return (frame, ...rest) => {
+      this.record(frame);
+      return handler(frame, ...rest);
+    };
+  }
+
+  /**
+   * The retained frames, oldest first
+   */
+  snapshot(): CapturedFrame[] {
+    this.evict(this.now());
+    return this.frames
+      .slice(this.head)
+      .map(({ time, data }) => ({ time_ms: new Date(time), data: data.slice() }));
+  }
+
+  /**
+   * The retained frames as a capture file (see decodeCapture)
+   */
+  export(): Uint8Array {
+    return encodeCapture(this.snapshot());
+  }
+
+  /**
+   * Discard every retained frame
+   */
+  clear(): void {
+    this.frames = [];
+    this.head = 0;
+    this.bytes = 0;
+  }
+
+  private evict(now: number): void {
+    const cutoff = now - this.retentionMs;
+    while (this.head < this.frames.length) {
+      const oldest = this.frames[this.head];
+      if (oldest === undefined || (oldest.time >= cutoff && this.bytes <= this.maxBytes)) {
+        break;
+      }
+      this.bytes -= oldest.data.length;
+      this.head++;
+    }
+
+    if (this.head > this.frames.length / 2) {
+      this.frames = this.frames.slice(this.head);
+      this.head = 0;
+    }
+  }
+}
@@ -0,0 +1,319 @@
+/**
+ * Schema-aware redaction of captures
+ *
+ * Field captures often hold customer data (names, locations, serial numbers).
+ * Marking those fields with `sensitive()` in the schema lets a capture be
+ * rewritten with their values zeroed or randomised while every frame stays
+ * decodable with the same schema, so it can be shared or kept long-term.
+ *
+ * Redaction keeps the shape of a sensitive value: sequences keep their length,
+ * options stay Some or None, enums keep their variant and strings and bytes
+ * their length (random mode) so frame sizes stay realistic. Pass an explicit
+ * replacement to `sensitive()` to hide those too, or for transforms whose
+ * decode rejects zeroed or random input (e.g. `decimal()`).
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import {
+  transform,
+  tuple,
+  struct,
+  type Schema,
+  type InferType,
+  type TransformSchema,
+} from "../types/schema.js";
+import { serialize } from "../codec/serializer.js";
+import { tryDeserialize } from "../codec/deserializer.js";
+import { withoutDirection } from "../codec/direction.js";
+import {
+  type CapturedFrame,
+  type CaptureError,
+  encodeCapture,
+  tryDecodeCapture,
+} from "./capture.js";
+import { PostcardError } from "../types/errors.js";
+
+export class RedactionError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "RedactionError";
+  }
+}
+
+/**
+ * - "zero": numbers become 0, booleans false, strings empty and bytes zero-filled
+ * - "random": leaves get random values of the same type and length
+ */
+export type RedactionMode = "zero" | "random";
+
+export interface RedactOptions {
+  readonly mode?: RedactionMode;
+  /** Source of randomness in [0, 1) for "random" mode (default: Math.random) */
+  readonly random?: () => number;
+}
+
+export interface RedactedCapture {
+  /** The redacted capture file */
+  readonly data: Uint8Array;
+  /** Frames rewritten */
+  readonly redacted: number;
+  /** Frames left out because they do not decode with the schema */
+  readonly dropped: number;
+}
+
+// Replacement values by sensitive() schema; an entry without one is redacted structurally
+const SENSITIVE = new WeakMap<Schema, { readonly replacement?: unknown }>();
+
+/**
+ * Mark a field as sensitive; it encodes and decodes exactly like `inner`
+ *
+ *   const ReadingSchema = struct({ owner: sensitive(string()), temperature: f32() });
+ *   const AccountSchema = struct({ balance: sensitive(decimal(), "0") });
+ */
+export function sensitive<S extends Schema>(
+  inner: S,
+  replacement?: InferType<S>
+): TransformSchema<S, InferType<S>> {
+  const schema = transform(inner, {
+    decode: (value: InferType<S>) => value,
+    encode: (value: InferType<S>) => value,
+  });
+  SENSITIVE.set(schema, replacement === undefined ? {} : { replacement });
+  return schema;
+}
+
+/**
+ * Redact the sensitive parts of a decoded value
+ *
+ * Throws RedactionError when a redacted value cannot be converted back by a
+ * transform.
+ */
+export function redact<S extends Schema>(
+  schema: S,
+  value: InferType<S>,
+  options: RedactOptions = {}
+): InferType<S> {
+  const { mode = "zero", random = Math.random } = options;
+  return redactValue(schema, value, false, { mode, random }) as InferType<S>;
+}
+
+/**
+ * Redact every frame of a capture file whose frames are values of `schema`
+ * (Result API)
+ *
+ * Frames that do not decode with the schema are dropped, since they cannot be
+ * shown to be free of sensitive data.
+ */
+export function tryRedactCapture(
+  schema: Schema,
+  capture: Uint8Array,
+  options: RedactOptions = {}
+): Result<RedactedCapture, RedactionError | CaptureError> {
+  const decoded = tryDecodeCapture(capture);
+  if (!decoded.ok) {
+    return decoded;
+  }
+
+  const frames: CapturedFrame[] = [];
+  let dropped = 0;
+  // Captured frames are decoded and encoded again, whichever way they went
+  const codec = withoutDirection(schema);
+  for (const frame of decoded.value) {
+    const result = tryDeserialize(codec, frame.data);
+    if (!result.ok || result.value.bytesRead !== frame.data.length) {
+      dropped++;
+      continue;
+    }
+
+    try {
+      const value = redact(schema, result.value.value, options);
+      frames.push({ time_ms: frame.time_ms, data: serialize(codec, value) });
+    } catch (e) {
+      return err(e instanceof RedactionError ? e : new RedactionError(describe(e)));
+    }
+  }
+
+  return ok({ data: encodeCapture(frames), redacted: frames.length, dropped });
+}
+
+/**
+ * Redact every frame of a capture file (throwing API)
+ *
+ * Throws RedactionError or CaptureError on failure
+ */
+export function redactCapture(
+  schema: Schema,
+  capture: Uint8Array,
+  options?: RedactOptions
+): RedactedCapture {
+  return unwrap(tryRedactCapture(schema, capture, options));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+type Leaf = Extract<
+  Schema["kind"],
+  | "bool"
+  | "i8"
+  | "i16"
+  | "i32"
+  | "i64"
+  | "i128"
+  | "u8"
+  | "u16"
+  | "u32"
+  | "u64"
+  | "u128"
+  | "f32"
+  | "f64"
+  | "char"
+  | "string"
+  | "bytes"
+>;
+
+const INT_RANGES: Partial<Record<Leaf, readonly [number, number]>> = {
+  u8: [0, 0xff],
+  u16: [0, 0xffff],
+  u32: [0, 0xffffffff],
+  i8: [-0x80, 0x7f],
+  i16: [-0x8000, 0x7fff],
+  i32: [-0x80000000, 0x7fffffff],
+};
+
+const BIGINT_WIDTHS: Partial<Record<Leaf, readonly [bits: number, signed: boolean]>> = {
+  u64: [64, false],
+  i64: [64, true],
+  u128: [128, false],
+  i128: [128, true],
+};
+
+const LETTERS = "abcdefghijklmnopqrstuvwxyz";
+
+// Values are walked alongside their schema, so their static type is unknown here
+/* eslint-disable @typescript-eslint/no-unsafe-argument, @typescript-eslint/no-unsafe-member-access, @typescript-eslint/no-explicit-any */
+function redactValue(
+  schema: Schema,
+  value: any,
+  scrubbing: boolean,
+  options: Required<RedactOptions>
+): unknown {
+  const marker = SENSITIVE.get(schema);
+  if (marker !== undefined) {
+    return "replacement" in marker
+      ? marker.replacement
+      : redactValue(schema.inner, value, true, options);
+  }
+
+  const recurse = (inner: Schema, item: unknown): unknown =>
+    redactValue(inner, item, scrubbing, options);
+
+  switch (schema.kind) {
+    case "option":
+      return value === null ? null : recurse(schema.inner, value);
+    case "newtype_struct":
+      return recurse(schema.inner, value);
+    case "seq":
+      return (value as unknown[]).map((item) => recurse(schema.item, item));
+    case "tuple":
+    case "tuple_struct":
+      return (value as unknown[]).map((item, i) => recurse(schema.items[i], item));
+    case "map":
+      return new Map(
+        Array.from(value as Map<unknown, unknown>, ([key, entry]) => [
+          recurse(schema.key, key),
+          recurse(schema.value, entry),
+        ])
+      );
+    case "struct": {
+      const object: Record<string, unknown> = {};
+      for (const [name, field] of Object.entries(schema.fields)) {
+        object[name] = recurse(field, value[name]);
+      }
+      return object;
+    }
+    case "enum": {
+      const variant = schema.variants[value.type];
+      switch (variant.kind) {
+        case "newtype_variant":
+          return { type: value.type, value: recurse(variant.inner, value.value) };
+        case "tuple_variant":
+          return { type: value.type, value: recurse(tuple(...variant.items), value.value) };
+        case "struct_variant":
+          return { type: value.type, value: recurse(struct(variant.fields), value.value) };
+        default:
+          return value;
+      }
+    }
+    case "transform": {
+      const inner = recurse(schema.inner, schema.encode(value));
+      try {
+        return schema.decode(inner);
+      } catch (e) {
+        throw new RedactionError(
+          `Redacted value was rejected by a transform (${describe(e)}); ` +
+            "pass a replacement to sensitive()"
+        );
+      }
+    }
+    case "lazy":
+      return recurse(schema.get(), value);
+    case "unit":
+    case "unit_struct":
+    case "never":
+      return value;
+    case "external":
+      // Opaque to the schemas; a sensitive() replacement is the only way in
+      return value;
+    default:
+      return scrubbing ? scrubLeaf(schema.kind, value, options) : value;
+  }
+}
+/* eslint-enable @typescript-eslint/no-unsafe-argument, @typescript-eslint/no-unsafe-member-access, @typescript-eslint/no-explicit-any */
+
+function scrubLeaf(kind: Leaf, value: unknown, options: Required<RedactOptions>): unknown {
+  const zero = options.mode === "zero";
+  const { random } = options;
+
+  const intRange = INT_RANGES[kind];
+  if (intRange !== undefined) {
+    const [min, max] = intRange;
+    return zero ? 0 : min + Math.floor(random() * (max - min + 1));
+  }
+  const bigintWidth = BIGINT_WIDTHS[kind];
+  if (bigintWidth !== undefined) {
+    const [bits, signed] = bigintWidth;
+    if (zero) {
+      return 0n;
+    }
+    let raw = 0n;
+    for (let i = 0; i < bits / 32; i++) {
+      raw = (raw << 32n) | BigInt(Math.floor(random() * 2 ** 32));
+    }
+    return signed ? BigInt.asIntN(bits, raw) : BigInt.asUintN(bits, raw);
+  }
+
+  const letter = (): string => LETTERS[Math.floor(random() * LETTERS.length)] ?? "a";
+  switch (kind) {
+    case "bool":
+      return zero ? false : random() < 0.5;
+    case "f32":
+    case "f64":
+      return zero ? 0 : random();
+    case "char":
+      return zero ? "\0" : letter();
+    case "string":
+      return zero ? "" : Array.from(value as string, letter).join("");
+    case "bytes": {
+      const bytes = new Uint8Array((value as Uint8Array).length);
+      return zero ? bytes : bytes.map(() => Math.floor(random() * 256));
+    }
+    default:
+      return value;
+  }
+}
+
+function describe(error: unknown): string {
+  return error instanceof Error ? error.message : String(error);
+}
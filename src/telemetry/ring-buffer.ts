@@ -0,0 +1,148 @@
+/**
+ * Ring-buffer log extraction
+ *
+ * Devices often keep logs in a fixed-size ring buffer of concatenated records,
+ * each a varint(u32) byte length followed by the postcard-encoded record (the
+ * same bytes postcard produces for a `Vec<u8>` holding the encoded record).
+ * Once the writer wraps, the oldest record at the write position is usually
+ * partially overwritten. These helpers unroll the buffer, skip the damaged
+ * region and return the surviving records oldest first.
+ */
+
+import { type Result, ok, err, unwrap } from "../types/result.js";
+import type { Schema, InferType } from "../types/schema.js";
+import { tryDeserialize } from "../codec/deserializer.js";
+import { tryDecodeVarintU32 } from "../codec/varint.js";
+import { PostcardError } from "../types/errors.js";
+
+export class RingBufferError extends PostcardError {
+  constructor(message: string) {
+    super(message);
+    this.name = "RingBufferError";
+  }
+}
+
+export interface RingBufferOptions {
+  /** Offset the device will write its next byte to */
+  readonly head: number;
+  /** Whether the writer has wrapped around the end of the buffer at least once */
+  readonly wrapped: boolean;
+}
+
+/**
+ * Decode the records in a ring buffer dump (Result API)
+ *
+ * Without wrap, records fill the buffer from offset 0 up to `head`. With wrap,
+ * the newest record ends at `head` and the oldest complete record is found by
+ * searching from `head` for the earliest offset whose chain of records decodes
+ * cleanly and ends exactly at `head`.
+ */
+export function tryDecodeRingBuffer<S extends Schema>(
+  schema: S,
+  buffer: Uint8Array,
+  options: RingBufferOptions
+): Result<InferType<S>[], RingBufferError> {
+  const { head, wrapped } = options;
+  if (!Number.isInteger(head) || head < 0 || head > buffer.length) {
+    return err(
+      new RingBufferError(
+        `Head ${String(head)} is outside the buffer (${String(buffer.length)} bytes)`
+      )
+    );
+  }
+
+  if (!wrapped) {
+    return decodeChain(schema, buffer.subarray(0, head), 0);
+  }
+
+  // Unroll so the oldest byte comes first and the newest record ends the buffer
+  const size = buffer.length;
+  const linear = new Uint8Array(size);
+  linear.set(buffer.subarray(head), 0);
+  linear.set(buffer.subarray(0, head), size - head);
+
+  // complete[offset]: a chain of records starting at offset ends exactly at size
+  const complete = new Uint8Array(size + 1);
+  complete[size] = 1;
+  let start = -1;
+  for (let offset = size - 1; offset >= 0; offset--) {
+    const end = readRecord(schema, linear, offset)?.end;
+    if (end !== undefined && complete[end] === 1) {
+      complete[offset] = 1;
+      start = offset;
+    }
+  }
+
+  if (start === -1) {
+    return size === 0
+      ? ok([])
+      : err(new RingBufferError("No chain of complete records ends at the write position"));
+  }
+
+  return decodeChain(schema, linear, start);
+}
+
+/**
+ * Decode the records in a ring buffer dump (throwing API)
+ *
+ * Throws RingBufferError on failure
+ */
+export function decodeRingBuffer<S extends Schema>(
+  schema: S,
+  buffer: Uint8Array,
+  options: RingBufferOptions
+): InferType<S>[] {
+  return unwrap(tryDecodeRingBuffer(schema, buffer, options));
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/**
+ * Read the length-prefixed record at `offset`, or undefined if it is truncated
+ * or does not decode to exactly its declared length
+ */
+function readRecord<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  offset: number
+): { value: InferType<S>; end: number } | undefined {
+  const length = tryDecodeVarintU32(data, offset);
+  if (!length.ok) {
+    return undefined;
+  }
+
+  const payloadStart = offset + length.value.bytesRead;
+  const end = payloadStart + length.value.value;
+  if (end > data.length) {
+    return undefined;
+  }
+
+  const decoded = tryDeserialize(schema, data.subarray(payloadStart, end));
+  if (!decoded.ok || decoded.value.bytesRead !== length.value.value) {
+    return undefined;
+  }
+
+  return { value: decoded.value.value, end };
+}
+
+function decodeChain<S extends Schema>(
+  schema: S,
+  data: Uint8Array,
+  start: number
+): Result<InferType<S>[], RingBufferError> {
+  const records: InferType<S>[] = [];
+  let offset = start;
+
+  while (offset < data.length) {
+    const record = readRecord(schema, data, offset);
+    if (record === undefined) {
+      return err(new RingBufferError(`Invalid record at offset ${String(offset)}`));
+    }
+    records.push(record.value);
+    offset = record.end;
+  }
+
+  return ok(records);
+}
@@ -0,0 +1,408 @@
+/**
+ * Rust date and time types: `std::time::Duration`, `chrono::DateTime<Utc>`
+ * and `time::OffsetDateTime`
+ *
+ * Each schema keeps the wire format the type's serde impl writes for postcard:
+ * - Duration: a struct of whole seconds (u64) and nanoseconds (u32)
+ * - chrono::DateTime: its RFC 3339 text, as `to_rfc3339_opts(AutoSi, true)`
+ *   formats it ("2024-02-29T23:59:59.123Z")
+ * - time::OffsetDateTime: a tuple of the year, day of the year, hour, minute,
+ *   second and nanosecond, then the offset's hours, minutes and seconds
+ *
+ * and exposes the value as a Date (a number of milliseconds for durations),
+ * or exactly, as bigint nanoseconds.
+ */
+
+import {
+  i8,
+  i32,
+  string,
+  struct,
+  transform,
+  tuple,
+  u8,
+  u16,
+  u32,
+  u64,
+  type I8Schema,
+  type I32Schema,
+  type StringSchema,
+  type StructSchema,
+  type TransformSchema,
+  type TupleSchema,
+  type U8Schema,
+  type U16Schema,
+  type U32Schema,
+  type U64Schema,
+} from "../types/schema.js";
+
+/**
+ * - "date": instants as Date and durations as a number of milliseconds, both
+ *   to the millisecond
+ * - "nanos": instants as bigint nanoseconds since 1970-01-01T00:00:00Z and
+ *   durations as bigint nanoseconds, exactly
+ */
+export type TimeRepresentation = "date" | "nanos";
+
+/** The fields `std::time::Duration` is serialized as */
+export interface DurationParts {
+  secs: bigint;
+  nanos: number;
+}
+
+/** The tuple `time::OffsetDateTime` is serialized as */
+export type OffsetDateTimeParts = [
+  year: number,
+  ordinal: number,
+  hour: number,
+  minute: number,
+  second: number,
+  nanosecond: number,
+  offsetHours: number,
+  offsetMinutes: number,
+  offsetSeconds: number,
+];
+
+export type StdDurationSchema<T> = TransformSchema<
+  StructSchema<{ secs: U64Schema; nanos: U32Schema }>,
+  T
+>;
+export type ChronoDateTimeSchema<T> = TransformSchema<StringSchema, T>;
+export type TimeOffsetDateTimeSchema<T> = TransformSchema<
+  TupleSchema<
+    [I32Schema, U16Schema, U8Schema, U8Schema, U8Schema, U32Schema, I8Schema, I8Schema, I8Schema]
+  >,
+  T
+>;
+
+const NANOS_PER_SECOND = 1_000_000_000n;
+const NANOS_PER_MILLI = 1_000_000n;
+const SECONDS_PER_DAY = 86_400n;
+const MAX_U64 = (1n << 64n) - 1n;
+
+// Largest magnitude accepted by the Date constructor (±100,000,000 days)
+const MAX_DATE_MILLIS = 8.64e15;
+
+// chrono's years, and time's without its large-dates feature
+const MAX_CHRONO_YEAR = 262_143;
+const MAX_TIME_YEAR = 9999;
+
+const RFC3339 =
+  /^([+-]\d{4,}|\d{4})-(\d{2})-(\d{2})T(\d{2}):(\d{2}):(\d{2})(?:\.(\d{1,9}))?(Z|[+-]\d{2}:\d{2})$/;
+
+/**
+ * Division rounding towards negative infinity, unlike bigint's `/`
+ */
+function floorDiv(a: bigint, b: bigint): bigint {
+  const quotient = a / b;
+  return a % b < 0n ? quotient - 1n : quotient;
+}
+
+function isLeapYear(year: number): boolean {
+  return (year % 4 === 0 && year % 100 !== 0) || year % 400 === 0;
+}
+
+function daysInMonth(year: number, month: number): number {
+  if (month === 2) {
+    return isLeapYear(year) ? 29 : 28;
+  }
+  return month === 4 || month === 6 || month === 9 || month === 11 ? 30 : 31;
+}
+
+/**
+ * Days from 1970-01-01 to a date of the proleptic Gregorian calendar
+ */
+function daysFromCivil(year: number, month: number, day: number): number {
+  // Years start in March, so the leap day ends them
+  const y = month <= 2 ? year - 1 : year;
+  const era = Math.floor(y / 400);
+  const yearOfEra = y - era * 400;
+  const dayOfYear = Math.floor((153 * ((month + 9) % 12) + 2) / 5) + day - 1;
+  const dayOfEra =
+    yearOfEra * 365 + Math.floor(yearOfEra / 4) - Math.floor(yearOfEra / 100) + dayOfYear;
+  return era * 146097 + dayOfEra - 719468;
+}
+
+/**
+ * The date of the proleptic Gregorian calendar `days` after 1970-01-01
+ */
+function civilFromDays(days: number): { year: number; month: number; day: number } {
+  const z = days + 719468;
+  const era = Math.floor(z / 146097);
+  const dayOfEra = z - era * 146097;
+  const yearOfEra = Math.floor(
+    (dayOfEra -
+      Math.floor(dayOfEra / 1460) +
+      Math.floor(dayOfEra / 36524) -
+      Math.floor(dayOfEra / 146096)) /
+      365
+  );
+  const dayOfYear =
+    dayOfEra - (365 * yearOfEra + Math.floor(yearOfEra / 4) - Math.floor(yearOfEra / 100));
+  const shiftedMonth = Math.floor((5 * dayOfYear + 2) / 153);
+  const day = dayOfYear - Math.floor((153 * shiftedMonth + 2) / 5) + 1;
+  const month = shiftedMonth < 10 ? shiftedMonth + 3 : shiftedMonth - 9;
+  return { year: yearOfEra + era * 400 + (month <= 2 ? 1 : 0), month, day };
+}
+
+/**
+ * Nanoseconds since the epoch of a day since the epoch, a second of that day
+ * (which may run over, or under for offsets) and a nanosecond
+ */
+function epochNanos(days: number, second: number, nanosecond: number): bigint {
+  return (
+    (BigInt(days) * SECONDS_PER_DAY + BigInt(second)) * NANOS_PER_SECOND + BigInt(nanosecond)
+  );
+}
+
+/**
+ * The day since the epoch, second of the day and nanosecond of an instant
+ */
+function splitEpochNanos(nanos: bigint): { days: number; second: number; nanosecond: number } {
+  const seconds = floorDiv(nanos, NANOS_PER_SECOND);
+  const days = floorDiv(seconds, SECONDS_PER_DAY);
+  return {
+    days: Number(days),
+    second: Number(seconds - days * SECONDS_PER_DAY),
+    nanosecond: Number(nanos - seconds * NANOS_PER_SECOND),
+  };
+}
+
+function pad(value: number, digits: number): string {
+  return String(value).padStart(digits, "0");
+}
+
+function dateToNanos(date: Date): bigint {
+  const millis = date.getTime();
+  if (Number.isNaN(millis)) {
+    throw new RangeError("Cannot encode an invalid Date");
+  }
+  return BigInt(millis) * NANOS_PER_MILLI;
+}
+
+function nanosToDate(nanos: bigint): Date {
+  const millis = Number(floorDiv(nanos, NANOS_PER_MILLI));
+  if (Math.abs(millis) > MAX_DATE_MILLIS) {
+    throw new RangeError(`${String(nanos)}ns since the epoch is outside the range of Date`);
+  }
+  return new Date(millis);
+}
+
+/**
+ * The length of a `std::time::Duration` in nanoseconds
+ */
+export function durationToNanos(duration: Readonly<DurationParts>): bigint {
+  // Rust carries nanoseconds over a second into the seconds too
+  return duration.secs * NANOS_PER_SECOND + BigInt(duration.nanos);
+}
+
+/**
+ * The `std::time::Duration` of `nanos` nanoseconds
+ */
+export function nanosToDuration(nanos: bigint): DurationParts {
+  const secs = nanos / NANOS_PER_SECOND;
+  if (nanos < 0n || secs > MAX_U64) {
+    throw new RangeError(`${String(nanos)}ns is outside the range of std::time::Duration`);
+  }
+  return { secs, nanos: Number(nanos % NANOS_PER_SECOND) };
+}
+
+/**
+ * The length of a `std::time::Duration` in milliseconds, which is only exact
+ * up to 2^53 nanoseconds (about 104 days)
+ */
+export function durationToMillis(duration: Readonly<DurationParts>): number {
+  return Number(duration.secs) * 1000 + duration.nanos / 1e6;
+}
+
+/**
+ * The `std::time::Duration` of `millis` milliseconds, to the nanosecond
+ */
+export function millisToDuration(millis: number): DurationParts {
+  if (!Number.isFinite(millis)) {
+    throw new RangeError(`${String(millis)}ms is outside the range of std::time::Duration`);
+  }
+  const whole = Math.floor(millis);
+  return nanosToDuration(
+    BigInt(whole) * NANOS_PER_MILLI + BigInt(Math.round((millis - whole) * 1e6))
+  );
+}
+
+/**
+ * Nanoseconds since the epoch of an RFC 3339 date and time
+ */
+export function rfc3339ToNanos(text: string): bigint {
+  const match = RFC3339.exec(text);
+  if (match === null) {
+    throw new RangeError(`"${text}" is not an RFC 3339 date and time`);
+  }
+  const field = (i: number): number => Number(match[i]);
+  const [year, month, day] = [field(1), field(2), field(3)];
+  const [hour, minute, second] = [field(4), field(5), field(6)];
+  // chrono writes a leap second as second 60, which runs into the next minute
+  if (
+    month < 1 ||
+    month > 12 ||
+    day < 1 ||
+    day > daysInMonth(year, month) ||
+    hour > 23 ||
+    minute > 59 ||
+    second > 60
+  ) {
+    throw new RangeError(`"${text}" is not a valid date and time`);
+  }
+
+  const zone = match[8] ?? "Z";
+  const offset =
+    zone === "Z"
+      ? 0
+      : (zone.startsWith("-") ? -1 : 1) *
+        (Number(zone.slice(1, 3)) * 3600 + Number(zone.slice(4, 6)) * 60);
+  const nanosecond = Number((match[7] ?? "").padEnd(9, "0"));
+  return epochNanos(
+    daysFromCivil(year, month, day),
+    hour * 3600 + minute * 60 + second - offset,
+    nanosecond
+  );
+}
+
+/**
+ * The RFC 3339 text of an instant in UTC, as chrono writes a
+ * `DateTime<Utc>`: fractional seconds in 3, 6 or 9 digits, as few as exact
+ */
+export function nanosToRfc3339(nanos: bigint): string {
+  const { days, second, nanosecond } = splitEpochNanos(nanos);
+  const { year, month, day } = civilFromDays(days);
+  if (Math.abs(year) > MAX_CHRONO_YEAR) {
+    throw new RangeError(`${String(nanos)}ns since the epoch is outside the range of chrono`);
+  }
+
+  // ISO 8601 requires the sign for years outside 0000 to 9999
+  const yearText =
+    year >= 0 && year <= 9999 ? pad(year, 4) : `${year < 0 ? "-" : "+"}${pad(Math.abs(year), 4)}`;
+  const time = [Math.floor(second / 3600), Math.floor(second / 60) % 60, second % 60]
+    .map((part) => pad(part, 2))
+    .join(":");
+  return `${yearText}-${pad(month, 2)}-${pad(day, 2)}T${time}${secondsFraction(nanosecond)}Z`;
+}
+
+/**
+ * The fraction of a second, as chrono's `SecondsFormat::AutoSi` writes it
+ */
+function secondsFraction(nanosecond: number): string {
+  if (nanosecond === 0) {
+    return "";
+  }
+  if (nanosecond % 1_000_000 === 0) {
+    return `.${pad(nanosecond / 1_000_000, 3)}`;
+  }
+  if (nanosecond % 1000 === 0) {
+    return `.${pad(nanosecond / 1000, 6)}`;
+  }
+  return `.${pad(nanosecond, 9)}`;
+}
+
+/**
+ * Nanoseconds since the epoch of a `time::OffsetDateTime`
+ */
+export function offsetDateTimeToNanos(parts: Readonly<OffsetDateTimeParts>): bigint {
+  const [
+    year,
+    ordinal,
+    hour,
+    minute,
+    second,
+    nanosecond,
+    offsetHours,
+    offsetMinutes,
+    offsetSeconds,
+  ] = parts;
+  if (
+    ordinal < 1 ||
+    ordinal > (isLeapYear(year) ? 366 : 365) ||
+    hour > 23 ||
+    minute > 59 ||
+    second > 59 ||
+    nanosecond > 999_999_999
+  ) {
+    throw new RangeError(`[${parts.join(", ")}] is not a valid time::OffsetDateTime`);
+  }
+
+  const offset = offsetHours * 3600 + offsetMinutes * 60 + offsetSeconds;
+  return epochNanos(
+    daysFromCivil(year, 1, 1) + ordinal - 1,
+    hour * 3600 + minute * 60 + second - offset,
+    nanosecond
+  );
+}
+
+/**
+ * The `time::OffsetDateTime` of an instant, in UTC
+ */
+export function nanosToOffsetDateTime(nanos: bigint): OffsetDateTimeParts {
+  const { days, second, nanosecond } = splitEpochNanos(nanos);
+  const { year } = civilFromDays(days);
+  if (Math.abs(year) > MAX_TIME_YEAR) {
+    throw new RangeError(
+      `${String(nanos)}ns since the epoch is outside the range of time::OffsetDateTime`
+    );
+  }
+  const ordinal = days - daysFromCivil(year, 1, 1) + 1;
+  const [hour, minute] = [Math.floor(second / 3600), Math.floor(second / 60) % 60];
+  return [year, ordinal, hour, minute, second % 60, nanosecond, 0, 0, 0];
+}
+
+/**
+ * A `std::time::Duration` field
+ *
+ *   duration()         // 1500 for 1.5s
+ *   duration("nanos")  // 1_500_000_000n
+ */
+export function duration(representation?: "date"): StdDurationSchema<number>;
+export function duration(representation: "nanos"): StdDurationSchema<bigint>;
+export function duration(
+  representation: TimeRepresentation = "date"
+): StdDurationSchema<number> | StdDurationSchema<bigint> {
+  const wire = struct({ secs: u64(), nanos: u32() });
+  return representation === "nanos"
+    ? transform(wire, { decode: durationToNanos, encode: nanosToDuration })
+    : transform(wire, { decode: durationToMillis, encode: millisToDuration });
+}
+
+/**
+ * A `chrono::DateTime<Utc>` field
+ *
+ * Encoding writes UTC ("Z"), as chrono does; decoding also takes other
+ * offsets, as chrono's `Deserialize` does.
+ */
+export function chronoDateTime(representation?: "date"): ChronoDateTimeSchema<Date>;
+export function chronoDateTime(representation: "nanos"): ChronoDateTimeSchema<bigint>;
+export function chronoDateTime(
+  representation: TimeRepresentation = "date"
+): ChronoDateTimeSchema<Date> | ChronoDateTimeSchema<bigint> {
+  return representation === "nanos"
+    ? transform(string(), { decode: rfc3339ToNanos, encode: nanosToRfc3339 })
+    : transform(string(), {
+        decode: (text: string): Date => nanosToDate(rfc3339ToNanos(text)),
+        encode: (value: Date): string => nanosToRfc3339(dateToNanos(value)),
+      });
+}
+
+/**
+ * A `time::OffsetDateTime` field
+ *
+ * The offset is applied when decoding and not kept: encoding writes UTC.
+ */
+export function offsetDateTime(representation?: "date"): TimeOffsetDateTimeSchema<Date>;
+export function offsetDateTime(representation: "nanos"): TimeOffsetDateTimeSchema<bigint>;
+export function offsetDateTime(
+  representation: TimeRepresentation = "date"
+): TimeOffsetDateTimeSchema<Date> | TimeOffsetDateTimeSchema<bigint> {
+  const wire = tuple(i32(), u16(), u8(), u8(), u8(), u32(), i8(), i8(), i8());
+  return representation === "nanos"
+    ? transform(wire, { decode: offsetDateTimeToNanos, encode: nanosToOffsetDateTime })
+    : transform(wire, {
+        decode: (parts): Date => nanosToDate(offsetDateTimeToNanos(parts)),
+        encode: (value: Date) => nanosToOffsetDateTime(dateToNanos(value)),
+      });
+}
@@ -0,0 +1,78 @@
+/**
+ * `rust_decimal::Decimal` fields
+ *
+ * With its default `serde` feature, rust_decimal serializes a Decimal as its
+ * display string ("-12.50"), which postcard writes as a length-prefixed string.
+ * A decimal schema keeps that wire format, checks the text is a Decimal Rust
+ * can parse back, and exposes it as a string, or as any decimal type (such as
+ * decimal.js) through `parse`/`format` conversions.
+ *
+ * Fields using `rust_decimal::serde::float` are plain f64 on the wire; use
+ * `f64()` for those.
+ */
+
+import { transform, string, type TransformSchema, type StringSchema } from "../types/schema.js";
+
+export type DecimalSchema<T = string> = TransformSchema<StringSchema, T>;
+
+/**
+ * Conversions between the decimal text and an application decimal type
+ */
+export interface DecimalConversions<T> {
+  readonly parse: (text: string) => T;
+  /**
+   * Must produce plain decimal notation, e.g. decimal.js `toFixed()`, not
+   * `toString()`, which switches to exponent notation for large exponents
+   */
+  readonly format: (value: T) => string;
+}
+
+// Decimal stores a 96-bit mantissa and a scale of at most 28
+const MAX_MANTISSA = (1n << 96n) - 1n;
+const MAX_SCALE = 28;
+
+const DECIMAL_TEXT = /^-?(\d+)(?:\.(\d+))?$/;
+
+/**
+ * Check that `text` is plain decimal notation within Decimal's range
+ */
+function validateDecimal(text: string): string {
+  const match = DECIMAL_TEXT.exec(text);
+  if (match === null) {
+    throw new RangeError(`"${text}" is not a decimal number`);
+  }
+
+  const integer = match[1] ?? "";
+  const fraction = match[2] ?? "";
+  if (fraction.length > MAX_SCALE) {
+    throw new RangeError(
+      `"${text}" has ${String(fraction.length)} fractional digits, Decimal supports at most ${String(MAX_SCALE)}`
+    );
+  }
+  if (BigInt(integer + fraction) > MAX_MANTISSA) {
+    throw new RangeError(`"${text}" is outside the range of Decimal`);
+  }
+  return text;
+}
+
+/**
+ * Expose a `rust_decimal::Decimal` field
+ *
+ * Without conversions the value is the decimal string as Rust wrote it,
+ * trailing zeros included ("1.10" stays "1.10"), so no precision is lost:
+ *
+ *   decimal()
+ *   decimal({ parse: (text) => new Decimal(text), format: (value) => value.toFixed() })
+ */
+export function decimal(): DecimalSchema;
+export function decimal<T>(conversions: DecimalConversions<T>): DecimalSchema<T>;
+export function decimal<T>(conversions?: DecimalConversions<T>): DecimalSchema<T> | DecimalSchema {
+  if (conversions === undefined) {
+    return transform(string(), { decode: validateDecimal, encode: validateDecimal });
+  }
+
+  return transform(string(), {
+    decode: (text: string): T => conversions.parse(validateDecimal(text)),
+    encode: (value: T): string => validateDecimal(conversions.format(value)),
+  });
+}
@@ -0,0 +1,125 @@
+/**
+ * Enums as flat discriminated unions
+ *
+ * Enum values decode as `{ type, value }`, which keeps every variant shape
+ * uniform. Application code often prefers the variant's fields next to the
+ * tag instead, as with serde's internally tagged representation:
+ *
+ *   { type: "Goblin", value: { id: 3, aggro: true } }  // enumType()
+ *   { kind: "Goblin", id: 3, aggro: true }             // discriminated(..., "kind")
+ *
+ * Struct variants are flattened; newtype and tuple variants keep their data
+ * under `value`, and unit variants are the tag alone. The wire format is
+ * unchanged: the tag maps back to the variant's index, in declaration order.
+ */
+
+import {
+  transform,
+  type EnumSchema,
+  type EnumVariant,
+  type EnumVariantRecord,
+  type InferType,
+  type NewtypeVariantSchema,
+  type Schema,
+  type StructVariantSchema,
+  type TransformSchema,
+  type TupleVariantSchema,
+  type UnitVariantSchema,
+} from "../types/schema.js";
+
+type Tagged<D extends string, K> = { [P in D]: K };
+
+type Flatten<T> = { [P in keyof T]: T[P] };
+
+type TupleValue<T extends readonly Schema[]> = {
+  [I in keyof T]: T[I] extends Schema ? InferType<T[I]> : never;
+};
+
+/**
+ * The union of variants of `V`, tagged by the `D` property
+ */
+export type DiscriminatedValue<V extends EnumVariantRecord, D extends string> = {
+  [K in keyof V]: V[K] extends UnitVariantSchema
+    ? Tagged<D, K>
+    : V[K] extends NewtypeVariantSchema<infer T>
+      ? [InferType<T>] extends [never]
+        ? never
+        : Flatten<Tagged<D, K> & { value: InferType<T> }>
+      : V[K] extends TupleVariantSchema<infer T>
+        ? Flatten<Tagged<D, K> & { value: TupleValue<T> }>
+        : V[K] extends StructVariantSchema<infer T>
+          ? Flatten<Tagged<D, K> & { [F in keyof T]: InferType<T[F]> }>
+          : never;
+}[keyof V];
+
+export type DiscriminatedSchema<
+  V extends EnumVariantRecord,
+  D extends string,
+> = TransformSchema<EnumSchema<V>, DiscriminatedValue<V, D>>;
+
+type EnumValue = { type: string; value?: unknown };
+
+/**
+ * Expose an enum as a union of objects tagged by `tag` (default `"kind"`)
+ *
+ *   const EnemySchema = discriminated(enumType("Enemy", { ... }), "kind");
+ *   switch (enemy.kind) { case "Goblin": enemy.aggro; ... }
+ *
+ * Throws RangeError if a struct variant has a field named like the tag, or
+ * the tag is `"value"` while a newtype or tuple variant needs that property.
+ */
+export function discriminated<V extends EnumVariantRecord, D extends string = "kind">(
+  schema: EnumSchema<V>,
+  tag: D = "kind" as D
+): DiscriminatedSchema<V, D> {
+  const variants: Readonly<Record<string, EnumVariant>> = schema.variants;
+  for (const [name, variant] of Object.entries(variants)) {
+    const clash =
+      variant.kind === "struct_variant"
+        ? Object.prototype.hasOwnProperty.call(variant.fields, tag)
+        : variant.kind !== "unit_variant" && tag === "value";
+    if (clash) {
+      throw new RangeError(`Variant ${name} of ${schema.name} already has a "${tag}" property`);
+    }
+  }
+
+  // InferType cannot resolve the variant union while V is still generic, hence the casts
+  return transform(schema, {
+    decode: (value: InferType<EnumSchema<V>>): DiscriminatedValue<V, D> => {
+      const { type, value: data } = value as unknown as EnumValue;
+      const kind = variants[type]?.kind;
+      const tagged =
+        kind === "struct_variant"
+          ? { [tag]: type, ...(data as object) }
+          : kind === "unit_variant"
+            ? { [tag]: type }
+            : { [tag]: type, value: data };
+      return tagged as unknown as DiscriminatedValue<V, D>;
+    },
+    encode: (value: DiscriminatedValue<V, D>): InferType<EnumSchema<V>> => {
+      const record = value as unknown as Record<string, unknown>;
+      const type = record[tag];
+      const variant =
+        typeof type === "string" && Object.prototype.hasOwnProperty.call(variants, type)
+          ? variants[type]
+          : undefined;
+      if (variant === undefined) {
+        throw new RangeError(`${JSON.stringify(type)} is not a variant of ${schema.name}`);
+      }
+
+      let encoded: EnumValue;
+      if (variant.kind === "struct_variant") {
+        const fields: Record<string, unknown> = {};
+        for (const field of Object.keys(variant.fields)) {
+          fields[field] = record[field];
+        }
+        encoded = { type: String(type), value: fields };
+      } else if (variant.kind === "unit_variant") {
+        encoded = { type: String(type) };
+      } else {
+        encoded = { type: String(type), value: record["value"] };
+      }
+      return encoded as unknown as InferType<EnumSchema<V>>;
+    },
+  });
+}
@@ -0,0 +1,89 @@
+/**
+ * Bit-packed boolean flags
+ *
+ * Firmware often packs a struct of booleans into a single u8/u16/u32 with a
+ * hand-written Serialize impl. A flags schema keeps that integer wire format
+ * and exposes each bit as a named boolean.
+ */
+
+import {
+  transform,
+  type TransformSchema,
+  type U8Schema,
+  type U16Schema,
+  type U32Schema,
+  type InferType,
+} from "../types/schema.js";
+
+export type FlagsSchema = U8Schema | U16Schema | U32Schema;
+
+export interface FlagsOptions {
+  /**
+   * Decode integers with bits set that have no name, dropping those bits
+   * (default: false, which fails to decode them)
+   */
+  readonly ignoreUnknownBits?: boolean;
+}
+
+const BIT_WIDTH: Record<FlagsSchema["kind"], number> = {
+  u8: 8,
+  u16: 16,
+  u32: 32,
+};
+
+/**
+ * Expose an unsigned integer as named boolean flags
+ *
+ * `bits` maps each flag name to its bit position, with 0 the least
+ * significant bit:
+ *
+ *   flags(u8(), { heater: 0, pump: 1, alarm: 7 })
+ */
+export function flags<S extends FlagsSchema, N extends string>(
+  inner: S,
+  bits: Readonly<Record<N, number>>,
+  options: FlagsOptions = {}
+): TransformSchema<S, Record<N, boolean>> {
+  const width = BIT_WIDTH[inner.kind];
+  const entries = Object.entries(bits) as [N, number][];
+
+  let knownMask = 0;
+  for (const [name, bit] of entries) {
+    if (!Number.isInteger(bit) || bit < 0 || bit >= width) {
+      throw new RangeError(
+        `Bit for flag "${name}" must be an integer between 0 and ${String(width - 1)}, got ${String(bit)}`
+      );
+    }
+    if (((knownMask >>> bit) & 1) !== 0) {
+      throw new RangeError(`Bit ${String(bit)} is assigned to more than one flag`);
+    }
+    knownMask = (knownMask | (1 << bit)) >>> 0;
+  }
+
+  const ignoreUnknownBits = options.ignoreUnknownBits ?? false;
+
+  return transform(inner, {
+    decode: (raw: InferType<S>): Record<N, boolean> => {
+      const packed = Number(raw);
+      const unknown = (packed & ~knownMask) >>> 0;
+      if (unknown !== 0 && !ignoreUnknownBits) {
+        throw new RangeError(`Unknown flag bits set: 0x${unknown.toString(16)}`);
+      }
+
+      const value = {} as Record<N, boolean>;
+      for (const [name, bit] of entries) {
+        value[name] = ((packed >>> bit) & 1) === 1;
+      }
+      return value;
+    },
+    encode: (value: Record<N, boolean>): InferType<S> => {
+      let raw = 0;
+      for (const [name, bit] of entries) {
+        if (value[name]) {
+          raw |= 1 << bit;
+        }
+      }
+      return (raw >>> 0) as InferType<S>;
+    },
+  });
+}
@@ -0,0 +1,118 @@
+/**
+ * Half-precision floats (`half::f16` and `half::bf16`)
+ *
+ * The half crate serializes both types as their raw 16-bit pattern, so on the
+ * postcard wire they are a varint u16 (1-3 bytes), not a fixed two bytes.
+ * These schemas keep that wire format and expose the value as a number.
+ */
+
+import { transform, u16, type TransformSchema, type U16Schema } from "../types/schema.js";
+
+export type HalfFloatSchema = TransformSchema<U16Schema, number>;
+
+interface HalfFormat {
+  readonly exponentBits: number;
+  readonly mantissaBits: number;
+}
+
+// IEEE 754 binary16
+const F16_FORMAT: HalfFormat = { exponentBits: 5, mantissaBits: 10 };
+
+// bfloat16: the top half of an f32
+const BF16_FORMAT: HalfFormat = { exponentBits: 8, mantissaBits: 7 };
+
+const float64View = new DataView(new ArrayBuffer(8));
+
+/**
+ * Decode a 16-bit pattern to a number (always exact)
+ */
+function fromBits(format: HalfFormat, bits: number): number {
+  const { exponentBits, mantissaBits } = format;
+  const bias = (1 << (exponentBits - 1)) - 1;
+  const maxExponent = (1 << exponentBits) - 1;
+
+  const sign = ((bits >>> (exponentBits + mantissaBits)) & 1) === 1 ? -1 : 1;
+  const exponent = (bits >>> mantissaBits) & maxExponent;
+  const mantissa = bits & ((1 << mantissaBits) - 1);
+
+  if (exponent === maxExponent) {
+    return mantissa === 0 ? sign * Infinity : NaN;
+  }
+  if (exponent === 0) {
+    // Subnormal
+    return sign * mantissa * 2 ** (1 - bias - mantissaBits);
+  }
+  return sign * (1 + mantissa / 2 ** mantissaBits) * 2 ** (exponent - bias);
+}
+
+/**
+ * Encode a number as a 16-bit pattern, rounding to nearest, ties to even
+ *
+ * Rounds directly from the f64 bits rather than going through f32, which would
+ * round twice. Values beyond the largest finite half overflow to infinity, as
+ * in the half crate, and NaN becomes the canonical quiet NaN.
+ */
+function toBits(format: HalfFormat, value: number): number {
+  const { exponentBits, mantissaBits } = format;
+  const bias = (1 << (exponentBits - 1)) - 1;
+  const maxExponent = (1 << exponentBits) - 1;
+
+  if (Number.isNaN(value)) {
+    return (maxExponent << mantissaBits) | (1 << (mantissaBits - 1));
+  }
+
+  float64View.setFloat64(0, value);
+  const high = float64View.getUint32(0);
+  const low = float64View.getUint32(4);
+
+  const sign = high >>> 31 === 1 ? 1 << (exponentBits + mantissaBits) : 0;
+  const exponent = ((high >>> 20) & 0x7ff) - 1023 + bias;
+
+  if (exponent >= maxExponent) {
+    return sign | (maxExponent << mantissaBits);
+  }
+
+  if (exponent <= 0) {
+    // Subnormal (or zero): count units of the smallest subnormal. Scaling by a
+    // power of two is exact, and rounding up into the smallest normal carries
+    // into the exponent field on its own.
+    const scaled = Math.abs(value) * 2 ** (bias - 1 + mantissaBits);
+    const whole = Math.floor(scaled);
+    const fraction = scaled - whole;
+    const roundUp = fraction > 0.5 || (fraction === 0.5 && whole % 2 === 1);
+    return sign | (roundUp ? whole + 1 : whole);
+  }
+
+  // Normal: keep the top mantissa bits of the f64 (20 of which are in `high`)
+  const shift = 20 - mantissaBits;
+  let bits = (exponent << mantissaBits) | ((high >>> shift) & ((1 << mantissaBits) - 1));
+  const roundBit = (high >>> (shift - 1)) & 1;
+  const sticky = (high & ((1 << (shift - 1)) - 1)) | low;
+  if (roundBit === 1 && (sticky !== 0 || (bits & 1) === 1)) {
+    // A carry out of the mantissa bumps the exponent, up to infinity
+    bits++;
+  }
+  return sign | bits;
+}
+
+function halfFloat(format: HalfFormat): HalfFloatSchema {
+  return transform(u16(), {
+    decode: (raw: number): number => fromBits(format, raw),
+    encode: (value: number): number => toBits(format, value),
+  });
+}
+
+/**
+ * `half::f16` field, exposed as a number
+ *
+ * Decoding is exact. Encoding rounds to the nearest f16 (max 65504).
+ */
+export const f16 = (): HalfFloatSchema => halfFloat(F16_FORMAT);
+
+/**
+ * `half::bf16` field, exposed as a number
+ *
+ * Decoding is exact. Encoding rounds to the nearest bf16, which keeps the f32
+ * range with 8 bits of precision.
+ */
+export const bf16 = (): HalfFloatSchema => halfFloat(BF16_FORMAT);
@@ -0,0 +1,64 @@
+/**
+ * Identifier enums as string-literal unions
+ *
+ * Enums with only unit variants are often used as field selectors or map keys
+ * (`HashMap<Field, f32>`), and are frequently marked
+ * `#[serde(variant_identifier)]` or `#[serde(field_identifier)]` for custom
+ * deserializers. On the postcard wire such a value is its variant index as a
+ * varint, exactly like a unit variant. An identifier schema keeps that format
+ * but exposes the variant name itself, so values compare by `===` and work as
+ * Map keys, instead of `{ type: "Name" }` objects.
+ *
+ * Note that postcard cannot deserialize `variant_identifier`/`field_identifier`
+ * types on the Rust side (they require a self-describing format), so data
+ * read back by Rust must use a plain enum with the same variants.
+ */
+
+import {
+  enumType,
+  transform,
+  unitVariant,
+  type EnumSchema,
+  type InferType,
+  type TransformSchema,
+  type UnitVariantSchema,
+} from "../types/schema.js";
+
+type IdentifierEnumSchema<N extends string> = EnumSchema<Record<N, UnitVariantSchema>>;
+
+export type IdentifierSchema<N extends string> = TransformSchema<IdentifierEnumSchema<N>, N>;
+
+type IdentifierValue<N extends string> = InferType<IdentifierEnumSchema<N>>;
+
+/**
+ * Expose a unit-only enum as the union of its variant names
+ *
+ * Variants are listed in declaration order, which determines their index:
+ *
+ *   identifier("Field", ["Temperature", "Humidity", "Pressure"])
+ *   // "Temperature" | "Humidity" | "Pressure"
+ */
+export function identifier<N extends string>(
+  name: string,
+  variants: readonly N[]
+): IdentifierSchema<N> {
+  if (new Set(variants).size !== variants.length) {
+    throw new RangeError(`Identifier enum ${name} lists a variant more than once`);
+  }
+
+  const record = {} as Record<N, UnitVariantSchema>;
+  for (const variant of variants) {
+    record[variant] = unitVariant(variant);
+  }
+
+  // InferType cannot resolve the variant union while N is still generic, hence the casts
+  return transform(enumType(name, record), {
+    decode: (value: IdentifierValue<N>): N => (value as unknown as { type: N }).type,
+    encode: (value: N): IdentifierValue<N> => {
+      if (!variants.includes(value)) {
+        throw new RangeError(`"${String(value)}" is not a variant of ${name}`);
+      }
+      return { type: value } as unknown as IdentifierValue<N>;
+    },
+  });
+}
@@ -0,0 +1,56 @@
+/**
+ * The `NonZero` integers (`NonZeroU32`, `NonZeroI64`, ...)
+ *
+ * serde writes a NonZero integer as its value, so on the postcard wire it is
+ * the plain integer. These schemas keep that wire format and fail to encode
+ * or decode zero, which Rust rejects when deserializing.
+ */
+
+import {
+  transform,
+  type I8Schema,
+  type I16Schema,
+  type I32Schema,
+  type I64Schema,
+  type I128Schema,
+  type InferType,
+  type TransformSchema,
+  type U8Schema,
+  type U16Schema,
+  type U32Schema,
+  type U64Schema,
+  type U128Schema,
+} from "../types/schema.js";
+
+/**
+ * The integer schemas a NonZero type exists for
+ */
+export type IntegerSchema =
+  | U8Schema
+  | U16Schema
+  | U32Schema
+  | U64Schema
+  | U128Schema
+  | I8Schema
+  | I16Schema
+  | I32Schema
+  | I64Schema
+  | I128Schema;
+
+export type NonZeroSchema<S extends IntegerSchema> = TransformSchema<S, InferType<S>>;
+
+/**
+ * A NonZero integer field over `inner`, the schema of its values
+ *
+ *   nonZero(u16())  // NonZeroU16, a number
+ *   nonZero(i64())  // NonZeroI64, a bigint
+ */
+export function nonZero<S extends IntegerSchema>(inner: S): NonZeroSchema<S> {
+  const check = (value: InferType<S>): InferType<S> => {
+    if (Number(value as number | bigint) === 0) {
+      throw new RangeError(`Expected a non-zero ${inner.kind}, got 0`);
+    }
+    return value;
+  };
+  return transform(inner, { decode: check, encode: check });
+}
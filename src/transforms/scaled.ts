@@ -0,0 +1,126 @@
+/**
+ * Scaled fixed-point integers
+ *
+ * Devices commonly send fractional quantities as scaled integers
+ * (temperature in centi-degrees, voltage in millivolts, ...). A scaled schema
+ * keeps the integer wire format and exposes the real-world value as a number:
+ *
+ *   value = raw * scale + offset
+ */
+
+import {
+  transform,
+  type TransformSchema,
+  type I8Schema,
+  type I16Schema,
+  type I32Schema,
+  type I64Schema,
+  type I128Schema,
+  type U8Schema,
+  type U16Schema,
+  type U32Schema,
+  type U64Schema,
+  type U128Schema,
+  type InferType,
+} from "../types/schema.js";
+
+export type IntegerSchema =
+  | I8Schema
+  | I16Schema
+  | I32Schema
+  | I64Schema
+  | I128Schema
+  | U8Schema
+  | U16Schema
+  | U32Schema
+  | U64Schema
+  | U128Schema;
+
+/**
+ * How a value that does not land exactly on a raw integer is encoded
+ *
+ * - "round": nearest integer, halves away from zero
+ * - "floor" / "ceil" / "trunc": as the Math functions of the same name
+ * - "error": refuse to encode values that are not exactly representable
+ */
+export type RoundingMode = "round" | "floor" | "ceil" | "trunc" | "error";
+
+export interface ScaledOptions {
+  /** Multiplier applied to the raw integer, e.g. 0.01 for centi-units */
+  readonly scale: number;
+  /** Added after scaling (default: 0) */
+  readonly offset?: number;
+  /** Rounding policy when encoding (default: "round") */
+  readonly rounding?: RoundingMode;
+}
+
+// Values this close to an integer are treated as that integer, so that
+// 21.53 / 0.01 = 2153.0000000000005 doesn't ceil to 2154
+const INTEGER_EPSILON = 1e-9;
+
+/**
+ * Expose an integer schema as a scaled floating-point value
+ *
+ * Encoding a value outside the inner type's range fails with SerializeError,
+ * exactly as encoding the raw integer would.
+ */
+export function scaled<S extends IntegerSchema>(
+  inner: S,
+  options: ScaledOptions
+): TransformSchema<S, number> {
+  const { scale, offset = 0, rounding = "round" } = options;
+
+  if (!Number.isFinite(scale) || scale === 0) {
+    throw new RangeError(`Scale must be a finite, non-zero number, got ${String(scale)}`);
+  }
+  if (!Number.isFinite(offset)) {
+    throw new RangeError(`Offset must be a finite number, got ${String(offset)}`);
+  }
+
+  // Scales like 0.01 aren't exact in binary; dividing by the integer
+  // reciprocal gives the correctly rounded result (2153 / 100 = 21.53)
+  const reciprocal = 1 / scale;
+  const divisor =
+    Math.abs(reciprocal - Math.round(reciprocal)) < INTEGER_EPSILON ? Math.round(reciprocal) : null;
+
+  const isBigInt =
+    inner.kind === "i64" || inner.kind === "u64" || inner.kind === "i128" || inner.kind === "u128";
+
+  return transform(inner, {
+    decode: (raw: InferType<S>): number => {
+      const n = Number(raw);
+      return (divisor !== null ? n / divisor : n * scale) + offset;
+    },
+    encode: (value: number): InferType<S> => {
+      if (!Number.isFinite(value)) {
+        throw new RangeError(`Cannot encode non-finite value ${String(value)} as a scaled integer`);
+      }
+
+      const exact = divisor !== null ? (value - offset) * divisor : (value - offset) / scale;
+      const raw = roundScaled(exact, rounding);
+
+      return (isBigInt ? BigInt(raw) : raw) as InferType<S>;
+    },
+  });
+}
+
+function roundScaled(exact: number, rounding: RoundingMode): number {
+  const nearest = Math.round(exact);
+  if (Math.abs(exact - nearest) <= INTEGER_EPSILON * Math.max(1, Math.abs(exact))) {
+    return nearest;
+  }
+
+  switch (rounding) {
+    case "round":
+      // Math.round rounds halves towards +Infinity; use away-from-zero instead
+      return Math.sign(exact) * Math.round(Math.abs(exact));
+    case "floor":
+      return Math.floor(exact);
+    case "ceil":
+      return Math.ceil(exact);
+    case "trunc":
+      return Math.trunc(exact);
+    case "error":
+      throw new RangeError(`Value is not exactly representable (raw ${String(exact)})`);
+  }
+}
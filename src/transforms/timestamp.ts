@@ -0,0 +1,68 @@
+/**
+ * Unix timestamps exposed as Date
+ *
+ * Firmware usually stores timestamps as plain integers with an implied epoch
+ * and resolution. A timestamp schema records that convention once, keeps the
+ * integer wire format, and exposes the value as a JavaScript Date.
+ */
+
+import {
+  transform,
+  type TransformSchema,
+  type I32Schema,
+  type I64Schema,
+  type U32Schema,
+  type U64Schema,
+  type InferType,
+} from "../types/schema.js";
+
+export type TimestampSchema = I32Schema | I64Schema | U32Schema | U64Schema;
+
+/**
+ * - "unix_seconds": whole seconds since 1970-01-01T00:00:00Z
+ * - "unix_millis": milliseconds since 1970-01-01T00:00:00Z
+ */
+export type TimestampConvention = "unix_seconds" | "unix_millis";
+
+const MILLIS_PER_UNIT: Record<TimestampConvention, number> = {
+  unix_seconds: 1000,
+  unix_millis: 1,
+};
+
+// Largest magnitude accepted by the Date constructor (±100,000,000 days)
+const MAX_DATE_MILLIS = 8.64e15;
+
+/**
+ * Expose an integer timestamp field as a Date
+ *
+ * Encoding truncates towards the past to the convention's resolution, so
+ * sub-second precision is dropped for "unix_seconds".
+ */
+export function timestamp<S extends TimestampSchema>(
+  inner: S,
+  convention: TimestampConvention
+): TransformSchema<S, Date> {
+  const millisPerUnit = MILLIS_PER_UNIT[convention];
+  const isBigInt = inner.kind === "i64" || inner.kind === "u64";
+
+  return transform(inner, {
+    decode: (raw: InferType<S>): Date => {
+      const millis = Number(raw) * millisPerUnit;
+      if (Math.abs(millis) > MAX_DATE_MILLIS) {
+        throw new RangeError(
+          `Timestamp ${String(raw)} (${convention}) is outside the range of Date`
+        );
+      }
+      return new Date(millis);
+    },
+    encode: (value: Date): InferType<S> => {
+      const millis = value.getTime();
+      if (Number.isNaN(millis)) {
+        throw new RangeError("Cannot encode an invalid Date");
+      }
+
+      const raw = Math.floor(millis / millisPerUnit);
+      return (isBigInt ? BigInt(raw) : raw) as InferType<S>;
+    },
+  });
+}
@@ -0,0 +1,100 @@
+/**
+ * `uuid::Uuid` as a branded string or its bytes
+ *
+ * The uuid crate serializes a Uuid for postcard, which is not
+ * human-readable, as a byte string of its 16 bytes: a varint length of 16,
+ * then the bytes. These schemas keep that wire format and expose the value
+ * as its lowercase hyphenated text ("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+ * branded so that it is not mixed up with other strings, or as the bytes.
+ */
+
+import {
+  bytes,
+  transform,
+  type Branded,
+  type BytesSchema,
+  type TransformSchema,
+} from "../types/schema.js";
+
+/**
+ * - "string": the lowercase hyphenated text, as a branded Uuid string
+ * - "bytes": the 16 bytes, as a Uint8Array
+ */
+export type UuidRepresentation = "string" | "bytes";
+
+export type Uuid = Branded<string, "Uuid">;
+
+export type UuidSchema<T> = TransformSchema<BytesSchema, T>;
+
+const UUID_LENGTH = 16;
+
+// Hyphenated, or the 32 digits alone, as uuid's `parse_str` takes them
+const UUID_TEXT = /^(?:[0-9a-f]{8}(?:-[0-9a-f]{4}){3}-[0-9a-f]{12}|[0-9a-f]{32})$/i;
+
+/**
+ * The Uuid written as `text`, in either case, hyphenated or not; throws
+ * RangeError if it is not one
+ */
+export function parseUuid(text: string): Uuid {
+  if (!UUID_TEXT.test(text)) {
+    throw new RangeError(`"${text}" is not a UUID`);
+  }
+  const digits = text.replace(/-/g, "").toLowerCase();
+  return [
+    digits.slice(0, 8),
+    digits.slice(8, 12),
+    digits.slice(12, 16),
+    digits.slice(16, 20),
+    digits.slice(20),
+  ].join("-") as Uuid;
+}
+
+/**
+ * The 16 bytes of the Uuid written as `text`
+ */
+export function uuidToBytes(text: string): Uint8Array {
+  const digits = parseUuid(text).replace(/-/g, "");
+  const result = new Uint8Array(UUID_LENGTH);
+  for (let i = 0; i < UUID_LENGTH; i++) {
+    result[i] = parseInt(digits.slice(i * 2, i * 2 + 2), 16);
+  }
+  return result;
+}
+
+/**
+ * The lowercase hyphenated text of a Uuid's 16 bytes
+ */
+export function bytesToUuid(data: Uint8Array): Uuid {
+  checkLength(data);
+  const digits = Array.from(data, (byte) => byte.toString(16).padStart(2, "0")).join("");
+  return parseUuid(digits);
+}
+
+function checkLength(data: Uint8Array): void {
+  if (data.length !== UUID_LENGTH) {
+    throw new RangeError(`A UUID is ${String(UUID_LENGTH)} bytes, got ${String(data.length)}`);
+  }
+}
+
+function checkedBytes(data: Uint8Array): Uint8Array {
+  checkLength(data);
+  return data;
+}
+
+/**
+ * A `uuid::Uuid` field
+ *
+ *   uuid()         // "67e55044-10b1-426f-9247-bb680e5fe0c8" as Uuid
+ *   uuid("bytes")  // Uint8Array(16) [103, 229, 80, 68, ...]
+ *
+ * Encoding takes the text in either case, hyphenated or not.
+ */
+export function uuid(representation?: "string"): UuidSchema<Uuid>;
+export function uuid(representation: "bytes"): UuidSchema<Uint8Array>;
+export function uuid(
+  representation: UuidRepresentation = "string"
+): UuidSchema<Uuid> | UuidSchema<Uint8Array> {
+  return representation === "bytes"
+    ? transform(bytes(), { decode: checkedBytes, encode: checkedBytes })
+    : transform(bytes(), { decode: bytesToUuid, encode: uuidToBytes });
+}
@@ -0,0 +1,119 @@
+/**
+ * Transport over Web Bluetooth GATT characteristics
+ *
+ * The device notifies frames on one characteristic and takes frames written
+ * to another, as a UART-style service (such as Nordic's UART service) does.
+ * Notifications and writes are small, so frames are delimited with COBS and
+ * may span several of them:
+ *
+ *   const service = await server.getPrimaryService(UART_SERVICE);
+ *   const link = new BluetoothTransport({
+ *     notify: await service.getCharacteristic(UART_TX),
+ *     write: await service.getCharacteristic(UART_RX),
+ *   });
+ *
+ * Notifications are started on the first iteration of `messages()`. An
+ * iteration does not see the device disconnect; abort its signal on the
+ * device's `gattserverdisconnected` event.
+ */
+
+import { cobsEncode } from "../codec/cobs.js";
+import { TransportError } from "../types/errors.js";
+import { CobsFrames, SendQueue, checkedMaxFrameLength, pushedFrames } from "./frames.js";
+import type { ByteStreamTransportOptions, Transport } from "./transport.js";
+
+/**
+ * The parts of the `BluetoothRemoteGATTCharacteristic` frames are notified
+ * on that the transport uses
+ */
+export interface BluetoothNotifyCharacteristic {
+  readonly value?: DataView | undefined;
+  startNotifications(): Promise<unknown>;
+  addEventListener(type: string, listener: (event: Event) => void): void;
+  removeEventListener(type: string, listener: (event: Event) => void): void;
+}
+
+/**
+ * The parts of the `BluetoothRemoteGATTCharacteristic` frames are written to
+ * that the transport uses
+ */
+export interface BluetoothWriteCharacteristic {
+  writeValueWithoutResponse(value: Uint8Array): Promise<void>;
+}
+
+export interface BluetoothTransportOptions extends ByteStreamTransportOptions {
+  /** Characteristic the device notifies frames on */
+  readonly notify: BluetoothNotifyCharacteristic;
+  /** Characteristic frames are written to */
+  readonly write: BluetoothWriteCharacteristic;
+  /**
+   * Most bytes per write (default: 20, what the smallest MTU leaves); frames
+   * are written in pieces of at most this size
+   */
+  readonly chunkSize?: number;
+}
+
+export class BluetoothTransport implements Transport {
+  readonly notify: BluetoothNotifyCharacteristic;
+  readonly write: BluetoothWriteCharacteristic;
+  readonly chunkSize: number;
+
+  private readonly options: ByteStreamTransportOptions;
+  private readonly sends = new SendQueue();
+  private started: Promise<unknown> | undefined;
+
+  /**
+   * Throws RangeError if `chunkSize` is not a positive integer or
+   * `maxFrameLength` is less than 1
+   */
+  constructor(options: BluetoothTransportOptions) {
+    const { notify, write, chunkSize = 20, ...byteStream } = options;
+    if (!Number.isInteger(chunkSize) || chunkSize < 1) {
+      throw new RangeError(`chunkSize must be a positive integer, got ${String(chunkSize)}`);
+    }
+    checkedMaxFrameLength(byteStream);
+    this.notify = notify;
+    this.write = write;
+    this.chunkSize = chunkSize;
+    this.options = byteStream;
+  }
+
+  async *messages(signal?: AbortSignal): AsyncGenerator<Uint8Array, void, undefined> {
+    try {
+      this.started ??= this.notify.startNotifications();
+      await this.started;
+    } catch (error) {
+      this.started = undefined;
+      throw new TransportError("Starting Bluetooth notifications failed", error);
+    }
+    const frames = new CobsFrames(this.options);
+    const notify = this.notify;
+    yield* pushedFrames((sink) => {
+      const onChange = (): void => {
+        if (notify.value !== undefined) {
+          const { buffer, byteOffset, byteLength } = notify.value;
+          for (const frame of frames.push(new Uint8Array(buffer, byteOffset, byteLength))) {
+            sink.frame(frame);
+          }
+        }
+      };
+      notify.addEventListener("characteristicvaluechanged", onChange);
+      return () => notify.removeEventListener("characteristicvaluechanged", onChange);
+    }, signal);
+  }
+
+  send(frame: Uint8Array): Promise<void> {
+    return this.sends.run(async () => {
+      const encoded = cobsEncode(frame);
+      try {
+        for (let start = 0; start < encoded.length; start += this.chunkSize) {
+          await this.write.writeValueWithoutResponse(
+            encoded.subarray(start, start + this.chunkSize)
+          );
+        }
+      } catch (error) {
+        throw new TransportError("Writing the Bluetooth characteristic failed", error);
+      }
+    });
+  }
+}
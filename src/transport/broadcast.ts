@@ -0,0 +1,305 @@
+/**
+ * One stream of decoded messages, read once and handed to several consumers
+ *
+ * A dashboard reads a device's messages in several places at once: the UI
+ * shows the latest, a recorder keeps every one and metrics count them. An
+ * async iterable can only be read by one of them, and teeing it naively
+ * either lets the slowest consumer hold everyone up or buffers without end.
+ * A Broadcast reads its source once and gives each subscriber a buffer of
+ * its own, with its own policy for when that buffer is full:
+ *
+ *   const messages = new Broadcast(device.messages());
+ *   const ui = messages.subscribe({ bufferSize: 1, overflow: "drop-oldest", late: "replay-last" });
+ *   const recorder = messages.subscribe({ overflow: "block" });
+ *   const metrics = messages.subscribe({ bufferSize: 256, overflow: "drop-newest" });
+ *
+ * Reading starts with the first subscription. A subscriber whose full buffer
+ * blocks holds back reading, and with it every other subscriber, until it
+ * takes a message; one that drops messages never holds anyone back. Each
+ * subscriber receives the messages read after it subscribed, and ends or
+ * throws the source's error once it has taken the messages it still holds.
+ */
+
+/**
+ * What a subscriber's full buffer does with the next message
+ *
+ * - `block`: reading the source waits until the subscriber takes one
+ * - `drop-oldest`: the oldest message held is dropped to make room
+ * - `drop-newest`: the new message is dropped
+ */
+export type BufferOverflow = "block" | "drop-oldest" | "drop-newest";
+
+/**
+ * What a subscriber receives of the messages read before it subscribed
+ *
+ * - `drop`: none of them
+ * - `replay-last`: the last one, if any, e.g. for a view showing the
+ *   current state
+ */
+export type LateSubscriberPolicy = "drop" | "replay-last";
+
+export interface SubscribeOptions<T> {
+  /** Most messages held for the subscriber (default: 64) */
+  readonly bufferSize?: number;
+  /** What a full buffer does with the next message (default: "block") */
+  readonly overflow?: BufferOverflow;
+  /** What the subscriber receives of earlier messages (default: "drop") */
+  readonly late?: LateSubscriberPolicy;
+  /** Called with each message dropped for the subscriber */
+  readonly onDrop?: (message: T) => void;
+  /** Ends the subscription, dropping the messages it still holds */
+  readonly signal?: AbortSignal;
+}
+
+const DEFAULT_BUFFER_SIZE = 64;
+
+/**
+ * What a Broadcast's subscriptions share: the source's outcome and the
+ * reader waiting for room
+ */
+class Hub<T> {
+  readonly subscriptions = new Set<Subscription<T>>();
+  ended = false;
+  failure: { readonly error: unknown } | undefined;
+  private room: (() => void) | undefined;
+
+  /** Whether a subscriber holds back reading until it takes a message */
+  blocked(): boolean {
+    for (const subscription of this.subscriptions) {
+      if (subscription.blocking) {
+        return true;
+      }
+    }
+    return false;
+  }
+
+  waitForRoom(): Promise<void> {
+    return new Promise<void>((resolve) => {
+      this.room = resolve;
+    });
+  }
+
+  /** A subscriber took a message or left, so there may be room again */
+  freed(): void {
+    this.room?.();
+    this.room = undefined;
+  }
+
+  end(): void {
+    this.ended = true;
+    this.freed();
+    for (const subscription of this.subscriptions) {
+      subscription.notify();
+    }
+  }
+}
+
+/**
+ * A subscriber's iteration of the messages read for it
+ */
+class Subscription<T> implements AsyncIterableIterator<T> {
+  readonly buffer: T[] = [];
+  private readonly size: number;
+  private readonly overflow: BufferOverflow;
+  private readonly onDrop: ((message: T) => void) | undefined;
+  private readonly hub: Hub<T>;
+  private readonly signal: AbortSignal | undefined;
+  private wake: (() => void) | undefined;
+  private left = false;
+
+  constructor(hub: Hub<T>, options: SubscribeOptions<T>) {
+    const { bufferSize = DEFAULT_BUFFER_SIZE, overflow = "block" } = options;
+    if (!(bufferSize === Infinity || (Number.isSafeInteger(bufferSize) && bufferSize >= 1))) {
+      throw new RangeError(`bufferSize must be a positive integer, got ${String(bufferSize)}`);
+    }
+    this.size = bufferSize;
+    this.overflow = overflow;
+    this.onDrop = options.onDrop;
+    this.hub = hub;
+    this.signal = options.signal;
+  }
+
+  [Symbol.asyncIterator](): AsyncIterableIterator<T> {
+    return this;
+  }
+
+  get blocking(): boolean {
+    return this.overflow === "block" && this.buffer.length >= this.size;
+  }
+
+  join(): void {
+    if (this.signal?.aborted === true) {
+      this.leave();
+      return;
+    }
+    this.signal?.addEventListener("abort", this.abort);
+    this.hub.subscriptions.add(this);
+  }
+
+  deliver(message: T): void {
+    if (this.buffer.length < this.size || this.overflow === "block") {
+      this.buffer.push(message);
+    } else if (this.overflow === "drop-oldest") {
+      this.buffer.push(message);
+      const dropped = this.buffer.shift() as T;
+      this.onDrop?.(dropped);
+    } else {
+      this.onDrop?.(message);
+    }
+    this.notify();
+  }
+
+  notify(): void {
+    this.wake?.();
+    this.wake = undefined;
+  }
+
+  async next(): Promise<IteratorResult<T, undefined>> {
+    for (;;) {
+      if (this.left) {
+        return { done: true, value: undefined };
+      }
+      if (this.buffer.length > 0) {
+        const message = this.buffer.shift() as T;
+        this.hub.freed();
+        return { done: false, value: message };
+      }
+      const { ended, failure } = this.hub;
+      if (failure !== undefined) {
+        this.leave();
+        throw failure.error;
+      }
+      if (ended) {
+        this.leave();
+        return { done: true, value: undefined };
+      }
+      await new Promise<void>((resolve) => {
+        this.wake = resolve;
+      });
+    }
+  }
+
+  return(): Promise<IteratorResult<T, undefined>> {
+    this.leave();
+    return Promise.resolve({ done: true, value: undefined });
+  }
+
+  private readonly abort = (): void => {
+    this.leave();
+  };
+
+  private leave(): void {
+    if (this.left) {
+      return;
+    }
+    this.left = true;
+    this.buffer.length = 0;
+    this.signal?.removeEventListener("abort", this.abort);
+    this.hub.subscriptions.delete(this);
+    this.hub.freed();
+    this.notify();
+  }
+}
+
+export class Broadcast<T> {
+  private readonly source: AsyncIterable<T>;
+  private readonly hub = new Hub<T>();
+  private iterator: AsyncIterator<T> | undefined;
+  private last: { readonly message: T } | undefined;
+
+  /**
+   * Reads `source` from the first subscription on
+   */
+  constructor(source: AsyncIterable<T>) {
+    this.source = source;
+  }
+
+  /**
+   * The number of subscriptions that have not ended
+   */
+  get subscribers(): number {
+    return this.hub.subscriptions.size;
+  }
+
+  /**
+   * A new iteration of the messages read from now on
+   *
+   * Once the source has ended, it only receives what `late` replays. Throws
+   * RangeError if `bufferSize` is not a positive integer or Infinity.
+   */
+  subscribe(options: SubscribeOptions<T> = {}): AsyncIterableIterator<T> {
+    const subscription = new Subscription(this.hub, options);
+    if (options.late === "replay-last" && this.last !== undefined) {
+      subscription.buffer.push(this.last.message);
+    }
+    subscription.join();
+    if (this.iterator === undefined && !this.hub.ended) {
+      this.iterator = this.source[Symbol.asyncIterator]();
+      void this.read(this.iterator);
+    }
+    return subscription;
+  }
+
+  /**
+   * Stop reading the source; subscribers end once they have taken the
+   * messages they hold
+   *
+   * The source is asked to end with `return()`, which an async generator
+   * only handles after the message it is waiting for; abort the signal it
+   * was created with, if any, to stop it at once.
+   */
+  close(): void {
+    if (this.hub.ended) {
+      return;
+    }
+    this.iterator?.return?.().catch(() => undefined);
+    this.hub.end();
+  }
+
+  private async read(iterator: AsyncIterator<T>): Promise<void> {
+    const hub = this.hub;
+    try {
+      for (;;) {
+        while (!hub.ended && hub.blocked()) {
+          await hub.waitForRoom();
+        }
+        if (hub.ended) {
+          return;
+        }
+        const result = await iterator.next();
+        if (hub.ended) {
+          return;
+        }
+        if (result.done === true) {
+          break;
+        }
+        this.last = { message: result.value };
+        for (const subscription of hub.subscriptions) {
+          subscription.deliver(result.value);
+        }
+      }
+    } catch (error) {
+      if (hub.ended) {
+        return;
+      }
+      hub.failure = { error };
+    }
+    hub.end();
+  }
+}
+
+/**
+ * `count` iterations of `source`, each receiving every message, as
+ * `subscribe(options)` on a Broadcast of it gives
+ */
+export function tee<T>(
+  source: AsyncIterable<T>,
+  count: number,
+  options: SubscribeOptions<T> = {}
+): AsyncIterableIterator<T>[] {
+  if (!(Number.isSafeInteger(count) && count >= 0)) {
+    throw new RangeError(`count must be a non-negative integer, got ${String(count)}`);
+  }
+  const broadcast = new Broadcast(source);
+  return Array.from({ length: count }, () => broadcast.subscribe(options));
+}
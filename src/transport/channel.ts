@@ -0,0 +1,99 @@
+/**
+ * Typed messages over any transport
+ *
+ * A Channel decodes the frames a Transport receives with one schema and
+ * encodes what it sends with another, so the code using it knows only its
+ * message types, whatever the link:
+ *
+ *   const device = new Channel(new SerialTransport(port), {
+ *     receive: TelemetrySchema,
+ *     send: CommandSchema,
+ *   });
+ *   await device.send({ type: "SetTarget", value: 21.5 });
+ *   for await (const telemetry of device.messages(signal)) {
+ *     render(telemetry);
+ *   }
+ *
+ * Each frame holds one message; bytes after it are ignored, as postcard's
+ * `from_bytes` does.
+ */
+
+import type { InferType, Schema } from "../types/schema.js";
+import {
+  type DeserializeError,
+  type DeserializeOptions,
+  tryDeserialize,
+} from "../codec/deserializer.js";
+import { serialize } from "../codec/serializer.js";
+import type { Transport } from "./transport.js";
+
+/**
+ * The schemas of what a channel receives and sends
+ */
+export interface ChannelSchemas<In extends Schema, Out extends Schema> {
+  readonly receive: In & { readonly direction?: "decode" };
+  readonly send: Out & { readonly direction?: "encode" };
+}
+
+export interface ChannelOptions extends DeserializeOptions {
+  /**
+   * Called with each frame that does not decode, which is skipped
+   * (default: the iteration throws the DeserializeError)
+   */
+  readonly onDecodeError?: (error: DeserializeError, frame: Uint8Array) => void;
+  /**
+   * Called with each frame before it is decoded; frames it returns false for
+   * are skipped, e.g. a `variantFilter()` of the variants a view shows
+   */
+  readonly filter?: (frame: Uint8Array) => boolean;
+}
+
+export class Channel<In extends Schema, Out extends Schema> {
+  readonly transport: Transport;
+  readonly schemas: ChannelSchemas<In, Out>;
+
+  private readonly options: ChannelOptions;
+
+  constructor(
+    transport: Transport,
+    schemas: ChannelSchemas<In, Out>,
+    options: ChannelOptions = {}
+  ) {
+    this.transport = transport;
+    this.schemas = schemas;
+    this.options = options;
+  }
+
+  /**
+   * The messages received, decoded, until the transport's iteration ends
+   *
+   * Frames the `filter` option rejects are skipped without being decoded.
+   * Throws the transport's TransportError, or the DeserializeError of a frame
+   * that does not decode unless `onDecodeError` is given.
+   */
+  async *messages(signal?: AbortSignal): AsyncGenerator<InferType<In>, void, undefined> {
+    for await (const frame of this.transport.messages(signal)) {
+      if (this.options.filter?.(frame) === false) {
+        continue;
+      }
+      const result = tryDeserialize<In>(this.schemas.receive, frame, 0, this.options);
+      if (result.ok) {
+        yield result.value.value;
+      } else if (this.options.onDecodeError !== undefined) {
+        this.options.onDecodeError(result.error, frame);
+      } else {
+        throw result.error;
+      }
+    }
+  }
+
+  /**
+   * Encode and send a message
+   *
+   * Throws SerializeError if the schema cannot encode it; rejects with the
+   * transport's TransportError if sending fails.
+   */
+  send(value: InferType<Out>): Promise<void> {
+    return this.transport.send(serialize(this.schemas.send, value));
+  }
+}
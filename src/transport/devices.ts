@@ -0,0 +1,291 @@
+/**
+ * Several devices at once, each with its own typed channel
+ *
+ * A production-test bench or a fleet dashboard talks to many devices of the
+ * same kind at the same time: a serial port per board, a socket per gateway.
+ * A DeviceManager keeps track of them by id, reads each link, hands every
+ * device a Channel with the same schemas, and reports devices coming and
+ * going:
+ *
+ *   const bench = new DeviceManager(
+ *     { receive: TelemetrySchema, send: CommandSchema },
+ *     {
+ *       onAttach: ({ device }) => void runTest(device),
+ *       onDetach: ({ device, cause }) => report(device.id, cause),
+ *       disconnect: (link) => link.port.close(),
+ *     }
+ *   );
+ *   navigator.serial.addEventListener("connect", async ({ target: port }) => {
+ *     await port.open({ baudRate: 115200 });
+ *     bench.attach(serialNumber(port), new SerialTransport(port));
+ *   });
+ *
+ * The manager is the only reader of each link, since a serial port can only
+ * be read by one iteration at a time, and every iteration of a device's
+ * `channel.messages()` receives the frames read while it runs. A device is
+ * detached when `detach()` is called, or on its own when its link closes or
+ * fails, as when the board is unplugged.
+ */
+
+import { TransportError } from "../types/errors.js";
+import type { InferType, Schema } from "../types/schema.js";
+import { serialize } from "../codec/serializer.js";
+import { Channel, type ChannelOptions, type ChannelSchemas } from "./channel.js";
+import { pushedFrames, type FrameSink } from "./frames.js";
+import type { Transport } from "./transport.js";
+
+/**
+ * A device attached to a DeviceManager
+ */
+export interface Device<In extends Schema, Out extends Schema, Id, T extends Transport> {
+  readonly id: Id;
+  /** The link it was attached with */
+  readonly transport: T;
+  /** Its messages, decoded and encoded with the manager's schemas */
+  readonly channel: Channel<In, Out>;
+}
+
+export interface AttachEvent<In extends Schema, Out extends Schema, Id, T extends Transport> {
+  readonly device: Device<In, Out, Id, T>;
+}
+
+export interface DetachEvent<In extends Schema, Out extends Schema, Id, T extends Transport> {
+  readonly device: Device<In, Out, Id, T>;
+  /** The error its link failed with; undefined if detached or the link closed */
+  readonly cause: unknown;
+}
+
+export interface DeviceManagerOptions<
+  In extends Schema,
+  Out extends Schema,
+  Id,
+  T extends Transport,
+> {
+  /** Options of every device's channel */
+  readonly channel?: ChannelOptions;
+  /** Called with each device attached, before its frames are read */
+  readonly onAttach?: (event: AttachEvent<In, Out, Id, T>) => void;
+  /** Called with each device detached, once its `disconnect` has run */
+  readonly onDetach?: (event: DetachEvent<In, Out, Id, T>) => void;
+  /**
+   * Called with each device's link once it is detached, e.g. to close the
+   * port or socket under it (default: nothing)
+   */
+  readonly disconnect?: (transport: T) => void | Promise<void>;
+}
+
+/**
+ * The transport a device's channel uses: the frames the manager reads from
+ * its link, and sends on the link until the device is detached
+ */
+class AttachedLink implements Transport {
+  private readonly sinks = new Set<FrameSink>();
+  private readonly link: Transport;
+  private detached = false;
+
+  constructor(link: Transport) {
+    this.link = link;
+  }
+
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array> {
+    return pushedFrames((sink) => {
+      if (this.detached) {
+        sink.end();
+      }
+      this.sinks.add(sink);
+      return () => this.sinks.delete(sink);
+    }, signal);
+  }
+
+  send(frame: Uint8Array): Promise<void> {
+    if (this.detached) {
+      return Promise.reject(new TransportError("Cannot send to a detached device"));
+    }
+    return this.link.send(frame);
+  }
+
+  deliver(frame: Uint8Array): void {
+    for (const sink of this.sinks) {
+      sink.frame(frame);
+    }
+  }
+
+  /** End the iterations, or fail them with what the link failed with */
+  detach(failure: TransportError | undefined): void {
+    this.detached = true;
+    for (const sink of this.sinks) {
+      if (failure !== undefined) {
+        sink.fail(failure);
+      } else {
+        sink.end();
+      }
+    }
+  }
+}
+
+interface Attachment<In extends Schema, Out extends Schema, Id, T extends Transport> {
+  readonly device: Device<In, Out, Id, T>;
+  readonly stop: AbortController;
+  readonly reading: Promise<void>;
+}
+
+export class DeviceManager<
+  In extends Schema,
+  Out extends Schema,
+  Id = string,
+  T extends Transport = Transport,
+> {
+  readonly schemas: ChannelSchemas<In, Out>;
+
+  private readonly options: DeviceManagerOptions<In, Out, Id, T>;
+  private readonly attached = new Map<Id, Attachment<In, Out, Id, T>>();
+  private closed = false;
+
+  constructor(
+    schemas: ChannelSchemas<In, Out>,
+    options: DeviceManagerOptions<In, Out, Id, T> = {}
+  ) {
+    this.schemas = schemas;
+    this.options = options;
+  }
+
+  /**
+   * The number of devices attached
+   */
+  get size(): number {
+    return this.attached.size;
+  }
+
+  /**
+   * The devices attached, in the order they were attached
+   */
+  get devices(): Device<In, Out, Id, T>[] {
+    return Array.from(this.attached.values(), (attachment) => attachment.device);
+  }
+
+  get(id: Id): Device<In, Out, Id, T> | undefined {
+    return this.attached.get(id)?.device;
+  }
+
+  has(id: Id): boolean {
+    return this.attached.has(id);
+  }
+
+  /**
+   * Start reading `transport` as the device `id`
+   *
+   * Throws RangeError if a device with that id is attached, and a
+   * TransportError once the manager is closed.
+   */
+  attach(id: Id, transport: T): Device<In, Out, Id, T> {
+    if (this.closed) {
+      throw new TransportError("Device manager is closed");
+    }
+    if (this.attached.has(id)) {
+      throw new RangeError(`Device ${String(id)} is already attached`);
+    }
+    const link = new AttachedLink(transport);
+    const device: Device<In, Out, Id, T> = {
+      id,
+      transport,
+      channel: new Channel(link, this.schemas, this.options.channel),
+    };
+    const stop = new AbortController();
+    // Subscribed by the first next(), before onAttach could send a request
+    const frames = transport.messages(stop.signal)[Symbol.asyncIterator]();
+    const reading = this.read(device, link, frames);
+    this.attached.set(id, { device, stop, reading });
+    this.options.onAttach?.({ device });
+    return device;
+  }
+
+  /**
+   * Stop reading the device `id` and end its channel's iterations; resolves
+   * once `disconnect` and `onDetach` have run, to whether it was attached
+   */
+  async detach(id: Id): Promise<boolean> {
+    const attachment = this.attached.get(id);
+    if (attachment === undefined) {
+      return false;
+    }
+    attachment.stop.abort();
+    await attachment.reading;
+    return true;
+  }
+
+  /**
+   * Send a message to the device `id`
+   *
+   * Throws SerializeError if the schema cannot encode it; rejects with a
+   * TransportError if no such device is attached or sending fails.
+   */
+  send(id: Id, value: InferType<Out>): Promise<void> {
+    const device = this.get(id);
+    if (device === undefined) {
+      return Promise.reject(new TransportError(`Device ${String(id)} is not attached`));
+    }
+    return device.channel.send(value);
+  }
+
+  /**
+   * Send a message to every device attached, encoding it once
+   *
+   * Rejects with a SerializeError, before sending anything, if the schema
+   * cannot encode it. Otherwise resolves once every send has settled, or
+   * rejects with a TransportError, whose cause is the first failure, if any
+   * of them failed.
+   */
+  async broadcast(value: InferType<Out>): Promise<void> {
+    const frame = serialize(this.schemas.send, value);
+    const devices = this.devices;
+    const results = await Promise.allSettled(
+      devices.map((device) => device.channel.transport.send(frame))
+    );
+    const failures = results.filter(
+      (result): result is PromiseRejectedResult => result.status === "rejected"
+    );
+    if (failures.length > 0) {
+      throw new TransportError(
+        `Sending to ${String(failures.length)} of ${String(devices.length)} devices failed`,
+        failures[0]?.reason
+      );
+    }
+  }
+
+  /**
+   * Detach every device and refuse new ones; resolves once every device's
+   * `disconnect` and `onDetach` have run
+   */
+  async close(): Promise<void> {
+    this.closed = true;
+    await Promise.all(Array.from(this.attached.keys(), (id) => this.detach(id)));
+  }
+
+  private async read(
+    device: Device<In, Out, Id, T>,
+    link: AttachedLink,
+    frames: AsyncIterator<Uint8Array>
+  ): Promise<void> {
+    let cause: unknown;
+    try {
+      let result = await frames.next();
+      while (result.done !== true) {
+        link.deliver(result.value);
+        result = await frames.next();
+      }
+    } catch (error) {
+      cause = error;
+    } finally {
+      await frames.return?.();
+    }
+
+    link.detach(
+      cause === undefined || cause instanceof TransportError
+        ? cause
+        : new TransportError("Reading the device failed", cause)
+    );
+    this.attached.delete(device.id);
+    await this.options.disconnect?.(device.transport);
+    this.options.onDetach?.({ device, cause });
+  }
+}
@@ -0,0 +1,226 @@
+/**
+ * Frame plumbing shared by the transport adapters
+ *
+ * Push-based links (WebSocket, Web Bluetooth notifications, Node sockets,
+ * the mock) hand their frames to a FrameSink that `pushedFrames` turns into
+ * an async iteration; byte streams are cut into frames by CobsFrames.
+ */
+
+import { ErrorCode } from "../types/error-codes.js";
+import type { TransportError } from "../types/errors.js";
+import { FramingError, tryCobsDecode } from "../codec/cobs.js";
+import type { ByteStreamTransportOptions } from "./transport.js";
+
+/**
+ * Where a push-based link delivers what it receives
+ */
+export interface FrameSink {
+  frame(frame: Uint8Array): void;
+  /** The link closed: the iteration ends after the frames before */
+  end(): void;
+  /** The link failed: the iteration throws after the frames before */
+  fail(error: TransportError): void;
+}
+
+/**
+ * The frames a link delivers to the sink `subscribe` registers, from the
+ * first `next()` until the link ends or fails or `signal` aborts;
+ * `subscribe` returns the function unregistering the sink
+ */
+export async function* pushedFrames(
+  subscribe: (sink: FrameSink) => () => void,
+  signal?: AbortSignal
+): AsyncGenerator<Uint8Array, void, undefined> {
+  if (signal?.aborted === true) {
+    return;
+  }
+  const frames: Uint8Array[] = [];
+  // Changed by the callbacks, so read through an object rather than narrowed
+  const state: { ended: boolean; aborted: boolean; failure: TransportError | undefined } = {
+    ended: false,
+    aborted: false,
+    failure: undefined,
+  };
+  let wake: (() => void) | undefined;
+  const notify = (): void => {
+    wake?.();
+    wake = undefined;
+  };
+  const onAbort = (): void => {
+    state.aborted = true;
+    notify();
+  };
+  const unsubscribe = subscribe({
+    frame: (frame) => {
+      frames.push(frame);
+      notify();
+    },
+    end: () => {
+      state.ended = true;
+      notify();
+    },
+    fail: (error) => {
+      state.failure ??= error;
+      notify();
+    },
+  });
+  signal?.addEventListener("abort", onAbort);
+  try {
+    while (!state.aborted) {
+      const frame = frames.shift();
+      if (frame !== undefined) {
+        yield frame;
+      } else if (state.failure !== undefined) {
+        throw state.failure;
+      } else if (state.ended) {
+        return;
+      } else {
+        await new Promise<void>((resolve) => {
+          wake = resolve;
+        });
+      }
+    }
+  } finally {
+    signal?.removeEventListener("abort", onAbort);
+    unsubscribe();
+  }
+}
+
+/**
+ * `promise`, or undefined once `signal` aborts, whichever comes first; for
+ * links read by awaiting calls that cannot be cancelled
+ */
+export function unlessAborted<T>(
+  promise: Promise<T>,
+  signal: AbortSignal | undefined
+): Promise<T | undefined> {
+  if (signal === undefined) {
+    return promise;
+  }
+  if (signal.aborted) {
+    return Promise.resolve(undefined);
+  }
+  return new Promise<T | undefined>((resolve, reject) => {
+    const onAbort = (): void => resolve(undefined);
+    signal.addEventListener("abort", onAbort, { once: true });
+    promise.then(
+      (value) => {
+        signal.removeEventListener("abort", onAbort);
+        resolve(value);
+      },
+      (error: unknown) => {
+        signal.removeEventListener("abort", onAbort);
+        reject(error);
+      }
+    );
+  });
+}
+
+/**
+ * The `maxFrameLength` of byte stream options (default: unbounded); throws
+ * RangeError if it is less than 1
+ */
+export function checkedMaxFrameLength(options: ByteStreamTransportOptions): number {
+  const { maxFrameLength = Infinity } = options;
+  if (!(maxFrameLength >= 1)) {
+    throw new RangeError(`maxFrameLength must be at least 1, got ${String(maxFrameLength)}`);
+  }
+  return maxFrameLength;
+}
+
+const EMPTY = new Uint8Array(0);
+
+function concat(a: Uint8Array, b: Uint8Array): Uint8Array {
+  if (a.length === 0) {
+    return b.slice();
+  }
+  const joined = new Uint8Array(a.length + b.length);
+  joined.set(a, 0);
+  joined.set(b, a.length);
+  return joined;
+}
+
+/**
+ * Cuts a COBS-framed byte stream, delivered in chunks, into the payloads of
+ * its frames
+ */
+export class CobsFrames {
+  private readonly maxFrameLength: number;
+  private readonly onFramingError: (error: FramingError) => void;
+  // Bytes of the frame received so far, none of them a delimiter
+  private pending = EMPTY;
+  // Dropping the rest of a frame longer than maxFrameLength
+  private skipping = false;
+
+  /**
+   * Throws RangeError if `maxFrameLength` is less than 1
+   */
+  constructor(options: ByteStreamTransportOptions) {
+    const { onFramingError = () => {} } = options;
+    this.maxFrameLength = checkedMaxFrameLength(options);
+    this.onFramingError = onFramingError;
+  }
+
+  /**
+   * The payloads of the frames `chunk` completes, in order
+   */
+  push(chunk: Uint8Array): Uint8Array[] {
+    const payloads: Uint8Array[] = [];
+    let start = 0;
+    for (let end = chunk.indexOf(0x00); end !== -1; end = chunk.indexOf(0x00, start)) {
+      const frame = concat(this.pending, chunk.subarray(start, end + 1));
+      this.pending = EMPTY;
+      start = end + 1;
+      if (this.skipping) {
+        this.skipping = false;
+      } else if (frame.length > this.maxFrameLength) {
+        this.onFramingError(this.tooLong(String(frame.length)));
+      } else {
+        const result = tryCobsDecode(frame);
+        if (result.ok) {
+          payloads.push(result.value.value);
+        } else {
+          this.onFramingError(
+            result.error instanceof FramingError
+              ? result.error
+              : new FramingError(result.error.message)
+          );
+        }
+      }
+    }
+    if (!this.skipping && start < chunk.length) {
+      this.pending = concat(this.pending, chunk.subarray(start));
+      if (this.pending.length >= this.maxFrameLength) {
+        // Not even the delimiter fits any more
+        this.onFramingError(this.tooLong(`more than ${String(this.pending.length)}`));
+        this.pending = EMPTY;
+        this.skipping = true;
+      }
+    }
+    return payloads;
+  }
+
+  private tooLong(length: string): FramingError {
+    return new FramingError(
+      `COBS frame of ${length} bytes exceeds maxFrameLength (${String(this.maxFrameLength)})`,
+      ErrorCode.BufferFull
+    );
+  }
+}
+
+/**
+ * Runs sends one after another, for links that fail a write started while
+ * another is in progress (a stream's writer lock, a GATT operation)
+ */
+export class SendQueue {
+  private last: Promise<void> = Promise.resolve();
+
+  /**
+   * Run `send` once the sends before it have settled
+   */
+  run(send: () => Promise<void>): Promise<void> {
+    const sent = this.last.then(send);
+    this.last = sent.catch(() => undefined);
+    return sent;
+  }
+}
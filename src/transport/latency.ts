@@ -0,0 +1,142 @@
+/**
+ * The echo side of end-to-end latency measurements
+ *
+ * `postcard-ts-bench latency` (the bench crate) plays the device: it sends
+ * each message of a corpus wrapped in a timestamped LatencyProbe and waits for
+ * it to come back. echoLatencyProbes answers the probes arriving on a
+ * transport: it decodes each message with the schema of its type, encodes the
+ * value again and sends it back in a LatencyEcho saying how long each step
+ * took, so the bench can split the round trip into decoding, encoding and the
+ * transport:
+ *
+ *   const link = new WebSocketTransport(new WebSocket("ws://127.0.0.1:7420"));
+ *   const echoed = await echoLatencyProbes(link, { Reading: ReadingSchema });
+ *
+ * Running it in the application's own page measures the browser and link
+ * users actually get. Times come from `performance.now()`, which browsers
+ * coarsen unless the page is cross-origin isolated.
+ */
+
+import {
+  type InferType,
+  type Schema,
+  struct,
+  u32,
+  u64,
+  f64,
+  string,
+  bytes,
+  option,
+} from "../types/schema.js";
+import { tryDeserialize } from "../codec/deserializer.js";
+import { serialize, trySerialize } from "../codec/serializer.js";
+import { withoutDirection } from "../codec/direction.js";
+import type { Transport } from "./transport.js";
+
+/**
+ * A message sent to be echoed: `payload` is a `type` encoded, and `sentAt`
+ * the sender's clock when it sent it, returned as is
+ */
+export const LatencyProbeSchema = struct({
+  seq: u32(),
+  sentAt: u64(),
+  type: string(),
+  payload: bytes(),
+});
+
+/**
+ * The answer to a probe: its payload decoded and encoded again, or the
+ * `error` that stopped it, and the microseconds each step took;
+ * `handledMicros` runs from the probe's arrival to the echo being encoded
+ */
+export const LatencyEchoSchema = struct({
+  seq: u32(),
+  sentAt: u64(),
+  decodeMicros: f64(),
+  encodeMicros: f64(),
+  handledMicros: f64(),
+  payload: bytes(),
+  error: option(string()),
+});
+
+export type LatencyProbe = InferType<typeof LatencyProbeSchema>;
+export type LatencyEcho = InferType<typeof LatencyEchoSchema>;
+
+export interface LatencyEchoOptions {
+  /** Stops echoing once aborted */
+  readonly signal?: AbortSignal;
+  /** The clock, in milliseconds (default: `performance.now()`) */
+  readonly now?: () => number;
+}
+
+/**
+ * Echo every LatencyProbe arriving on `transport`, decoding and encoding its
+ * payload with the schema of its type in `schemas`, until the link closes or
+ * `signal` aborts
+ *
+ * Resolves with the number of probes echoed. Frames that are not probes are
+ * ignored; a probe of a type without a schema, or whose payload does not
+ * decode or encode, is echoed with the error and no payload. Rejects with a
+ * TransportError if the link fails.
+ */
+export async function echoLatencyProbes(
+  transport: Transport,
+  schemas: Readonly<Record<string, Schema>>,
+  options: LatencyEchoOptions = {}
+): Promise<number> {
+  const now = options.now ?? (() => performance.now());
+  let echoed = 0;
+  for await (const frame of transport.messages(options.signal)) {
+    const received = now();
+    const probe = tryDeserialize(LatencyProbeSchema, frame);
+    if (!probe.ok) {
+      continue;
+    }
+    const echo = answer(probe.value.value, schemas, now);
+    const handledMicros = (now() - received) * 1000;
+    await transport.send(serialize(LatencyEchoSchema, { ...echo, handledMicros }));
+    echoed++;
+  }
+  return echoed;
+}
+
+/**
+ * The echo of `probe`, but for how long it took to handle
+ */
+function answer(
+  probe: LatencyProbe,
+  schemas: Readonly<Record<string, Schema>>,
+  now: () => number
+): Omit<LatencyEcho, "handledMicros"> {
+  const { seq, sentAt } = probe;
+  let decodeMicros = 0;
+  let encodeMicros = 0;
+  const failed = (error: string): Omit<LatencyEcho, "handledMicros"> => ({
+    seq,
+    sentAt,
+    decodeMicros,
+    encodeMicros,
+    payload: new Uint8Array(0),
+    error,
+  });
+
+  const found = schemas[probe.type];
+  if (found === undefined) {
+    return failed(`No schema for ${probe.type}`);
+  }
+  // The bench plays both ends of every type, whatever its direction in the app
+  const schema = withoutDirection(found);
+  const decodeStart = now();
+  const decoded = tryDeserialize(schema, probe.payload);
+  decodeMicros = (now() - decodeStart) * 1000;
+  if (!decoded.ok) {
+    return failed(decoded.error.message);
+  }
+  const encodeStart = now();
+  const encoded = trySerialize(schema, decoded.value.value);
+  encodeMicros = (now() - encodeStart) * 1000;
+  if (!encoded.ok) {
+    return failed(encoded.error.message);
+  }
+  return { seq, sentAt, decodeMicros, encodeMicros, payload: encoded.value, error: null };
+}
@@ -0,0 +1,97 @@
+/**
+ * In-memory transport for tests
+ *
+ * `deliver()` hands a frame to the iterations of `messages()` as if it had
+ * been received, and `sent` records every frame sent. `MockTransport.pair()`
+ * connects two mocks back to back, each delivering what the other sends, so
+ * code under test can talk to a `DeviceSimulator` through a Transport:
+ *
+ *   const [link, device] = MockTransport.pair();
+ *   const thermostat = new ThermostatSimulator({
+ *     send: (bytes) => void device.send(bytes),
+ *     handlers,
+ *   });
+ *   void (async () => {
+ *     for await (const frame of device.messages()) await thermostat.receive(frame);
+ *   })();
+ */
+
+import { TransportError } from "../types/errors.js";
+import { pushedFrames, type FrameSink } from "./frames.js";
+import type { Transport } from "./transport.js";
+
+export class MockTransport implements Transport {
+  /** Every frame sent, oldest first */
+  readonly sent: Uint8Array[] = [];
+
+  private readonly sinks = new Set<FrameSink>();
+  private peer: MockTransport | undefined;
+  private closed = false;
+
+  /**
+   * Two mocks, each delivering the frames sent on the other
+   */
+  static pair(): [MockTransport, MockTransport] {
+    const a = new MockTransport();
+    const b = new MockTransport();
+    a.peer = b;
+    b.peer = a;
+    return [a, b];
+  }
+
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array> {
+    return pushedFrames((sink) => {
+      if (this.closed) {
+        sink.end();
+      }
+      this.sinks.add(sink);
+      return () => this.sinks.delete(sink);
+    }, signal);
+  }
+
+  /**
+   * Record a frame, delivering it to the peer if paired; rejects with a
+   * TransportError once closed
+   */
+  send(frame: Uint8Array): Promise<void> {
+    if (this.closed) {
+      return Promise.reject(new TransportError("Mock transport is closed"));
+    }
+    this.sent.push(frame.slice());
+    this.peer?.deliver(frame);
+    return Promise.resolve();
+  }
+
+  /**
+   * Hand a frame to every iteration of `messages()`, as if received
+   */
+  deliver(frame: Uint8Array): void {
+    for (const sink of this.sinks) {
+      sink.frame(frame.slice());
+    }
+  }
+
+  /**
+   * End every iteration, and the peer's, as a closed link would
+   */
+  close(): void {
+    if (this.closed) {
+      return;
+    }
+    this.closed = true;
+    for (const sink of this.sinks) {
+      sink.end();
+    }
+    this.peer?.close();
+  }
+
+  /**
+   * Fail every iteration with a TransportError whose cause is `cause`, as a
+   * broken link would
+   */
+  fail(cause?: unknown): void {
+    for (const sink of this.sinks) {
+      sink.fail(new TransportError("Mock transport failed", cause));
+    }
+  }
+}
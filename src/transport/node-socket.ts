@@ -0,0 +1,80 @@
+/**
+ * Transport over a Node.js stream socket
+ *
+ * Takes a `net.Socket` (TCP, or a Unix socket a serial bridge listens on),
+ * a `tls.TLSSocket` or the `serialport` package's port, all of them byte
+ * streams, so frames are delimited with COBS as postcard firmware writes
+ * them with `to_allocvec_cobs`:
+ *
+ *   const link = new NodeSocketTransport(net.connect(9000, "device.local"));
+ *
+ * Only the stream's events and `write()` are used, so the module does not
+ * import Node's and can be bundled for the browser with the rest.
+ */
+
+import { cobsEncode } from "../codec/cobs.js";
+import { TransportError } from "../types/errors.js";
+import { CobsFrames, checkedMaxFrameLength, pushedFrames } from "./frames.js";
+import type { ByteStreamTransportOptions, Transport } from "./transport.js";
+
+/**
+ * The parts of a Node.js duplex stream the transport uses
+ */
+export interface NodeSocketLike {
+  on(event: string, listener: (...args: never[]) => void): unknown;
+  off(event: string, listener: (...args: never[]) => void): unknown;
+  write(data: Uint8Array, callback: (error?: Error | null) => void): unknown;
+}
+
+export class NodeSocketTransport implements Transport {
+  readonly socket: NodeSocketLike;
+
+  private readonly options: ByteStreamTransportOptions;
+
+  /**
+   * Throws RangeError if `maxFrameLength` is less than 1
+   */
+  constructor(socket: NodeSocketLike, options: ByteStreamTransportOptions = {}) {
+    checkedMaxFrameLength(options);
+    this.socket = socket;
+    this.options = options;
+  }
+
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array> {
+    const socket = this.socket;
+    const frames = new CobsFrames(this.options);
+    return pushedFrames((sink) => {
+      const onData = (chunk: Uint8Array): void => {
+        for (const frame of frames.push(chunk)) {
+          sink.frame(frame);
+        }
+      };
+      const onEnd = (): void => sink.end();
+      const onError = (error: Error): void =>
+        sink.fail(new TransportError("Socket failed", error));
+      socket.on("data", onData);
+      socket.on("end", onEnd);
+      socket.on("close", onEnd);
+      socket.on("error", onError);
+      return () => {
+        socket.off("data", onData);
+        socket.off("end", onEnd);
+        socket.off("close", onEnd);
+        socket.off("error", onError);
+      };
+    }, signal);
+  }
+
+  send(frame: Uint8Array): Promise<void> {
+    return new Promise((resolve, reject) => {
+      // Writes are queued by the stream itself, in order
+      this.socket.write(cobsEncode(frame), (error) => {
+        if (error === undefined || error === null) {
+          resolve();
+        } else {
+          reject(new TransportError("Writing to the socket failed", error));
+        }
+      });
+    });
+  }
+}
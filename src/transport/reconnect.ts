@@ -0,0 +1,346 @@
+/**
+ * Reconnecting links, resynchronized after each connect
+ *
+ * A device that reboots, a laptop that sleeps or a gateway that restarts
+ * drops the link, and every dashboard then needs the same loop: wait a while,
+ * connect again, ask the device for its current state (whatever it sent in
+ * between is gone) and tell the UI whether this is a new session or the old
+ * one resumed. A ReconnectingTransport is that loop around a function opening
+ * the link, and is a Transport itself, so a Channel over it keeps receiving
+ * across reconnects:
+ *
+ *   const link = new ReconnectingTransport(() => new WebSocketTransport(new WebSocket(url)), {
+ *     policy: { mode: "exponential", initialDelayMs: 500, maxDelayMs: 30_000 },
+ *     resync: () => serialize(CommandSchema, { type: "GetState" }),
+ *     onConnect: ({ resumed }) => status.set(resumed ? "resumed" : "connected"),
+ *     onDisconnect: ({ cause, retryInMs }) => console.warn("Link lost", cause, retryInMs),
+ *   });
+ *   const device = new Channel(link, { receive: TelemetrySchema, send: CommandSchema });
+ *
+ * Connecting starts right away. A link counts as connected once `connect`
+ * returns it, and as lost when its `messages()` iteration ends or fails;
+ * a link that is lost before receiving a frame counts as a failed attempt,
+ * so the delays keep growing while a server accepts and drops connections.
+ */
+
+import { TransportError } from "../types/errors.js";
+import { pushedFrames, type FrameSink } from "./frames.js";
+import type { Transport } from "./transport.js";
+
+/**
+ * How long to wait before each reconnection attempt
+ *
+ * - `never`: give up when the link is lost
+ * - `fixed`: `delayMs` before every attempt
+ * - `exponential`: `initialDelayMs` before the first attempt, multiplied by
+ *   `factor` (default: 2) for each further one, up to `maxDelayMs`; `jitter`
+ *   (default: 0.2) takes up to that fraction off each delay at random, so
+ *   that many clients of a restarted server do not all reconnect at once
+ *
+ * `maxAttempts` bounds the attempts after consecutive failures (default:
+ * unbounded).
+ */
+export type ReconnectPolicy =
+  | { readonly mode: "never" }
+  | { readonly mode: "fixed"; readonly delayMs: number; readonly maxAttempts?: number }
+  | {
+      readonly mode: "exponential";
+      readonly initialDelayMs: number;
+      readonly maxDelayMs: number;
+      readonly factor?: number;
+      readonly jitter?: number;
+      readonly maxAttempts?: number;
+    };
+
+/**
+ * A link connected, the first one or a later one replacing a lost link
+ */
+export interface ConnectEvent<T extends Transport = Transport> {
+  readonly transport: T;
+  /** Whether an earlier link was connected, so the session is resumed */
+  readonly resumed: boolean;
+}
+
+/**
+ * A link was lost, or an attempt to connect one failed
+ */
+export interface DisconnectEvent {
+  /** The error the link failed or connecting threw with; undefined if the link closed */
+  readonly cause: unknown;
+  /** Failed attempts in a row, this one included */
+  readonly failures: number;
+  /** Delay before the next attempt; undefined when the policy gives up */
+  readonly retryInMs: number | undefined;
+}
+
+/**
+ * Where a ReconnectingTransport is in its loop
+ */
+export type ReconnectState = "connecting" | "connected" | "waiting" | "closed" | "failed";
+
+export interface ReconnectOptions<T extends Transport> {
+  /** Delays between attempts (default: exponential from 500 ms up to 30 s) */
+  readonly policy?: ReconnectPolicy;
+  /**
+   * The frame to send on each newly connected link, typically a request for
+   * the device's current state (default: none)
+   *
+   * The link's frames are already being received when it is sent, so the
+   * reply is not missed. A resync that fails loses the link.
+   */
+  readonly resync?: (
+    event: ConnectEvent<T>
+  ) => Uint8Array | undefined | Promise<Uint8Array | undefined>;
+  /** Called with each link connected, before its resync is sent */
+  readonly onConnect?: (event: ConnectEvent<T>) => void;
+  /** Called with each link lost and each failed attempt */
+  readonly onDisconnect?: (event: DisconnectEvent) => void;
+  /**
+   * Called with each link once it is no longer used, lost or closed, e.g. to
+   * close the port or socket under it (default: nothing)
+   */
+  readonly disconnect?: (transport: T) => void | Promise<void>;
+  /** Source of the policy's jitter, in [0, 1) (default: Math.random) */
+  readonly random?: () => number;
+}
+
+const DEFAULT_POLICY: ReconnectPolicy = {
+  mode: "exponential",
+  initialDelayMs: 500,
+  maxDelayMs: 30_000,
+};
+
+export class ReconnectingTransport<T extends Transport = Transport> implements Transport {
+  private readonly connect: (signal: AbortSignal) => T | Promise<T>;
+  private readonly options: ReconnectOptions<T>;
+  private readonly policy: ReconnectPolicy;
+  private readonly sinks = new Set<FrameSink>();
+  private readonly closing = new AbortController();
+  private readonly running: Promise<void>;
+  private link: T | undefined;
+  private failures = 0;
+  private failure: TransportError | undefined;
+  private phase: ReconnectState = "connecting";
+
+  /**
+   * Starts connecting with `connect`, which is given a signal aborted by
+   * `close()`; throws RangeError if the policy has a negative delay, a
+   * factor below 1, a jitter outside [0, 1] or a negative `maxAttempts`
+   */
+  constructor(connect: (signal: AbortSignal) => T | Promise<T>, options: ReconnectOptions<T> = {}) {
+    this.policy = options.policy ?? DEFAULT_POLICY;
+    checkReconnectPolicy(this.policy);
+    this.connect = connect;
+    this.options = options;
+    this.running = this.run();
+  }
+
+  get state(): ReconnectState {
+    return this.phase;
+  }
+
+  /**
+   * The connected link, if any
+   */
+  get transport(): T | undefined {
+    return this.link;
+  }
+
+  /**
+   * The frames received over every link connected from the first `next()`
+   * on, until `close()`; throws a TransportError once the policy gives up
+   */
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array> {
+    return pushedFrames((sink) => {
+      if (this.failure !== undefined) {
+        sink.fail(this.failure);
+      } else if (this.phase === "closed") {
+        sink.end();
+      }
+      this.sinks.add(sink);
+      return () => this.sinks.delete(sink);
+    }, signal);
+  }
+
+  /**
+   * Send one frame on the connected link; rejects with a TransportError if
+   * none is connected, rather than waiting for one
+   */
+  send(frame: Uint8Array): Promise<void> {
+    if (this.link === undefined) {
+      return Promise.reject(new TransportError(`Cannot send while ${this.phase}`));
+    }
+    return this.link.send(frame);
+  }
+
+  /**
+   * Stop reconnecting, let go of the connected link and end the iterations;
+   * resolves once `disconnect` has run
+   */
+  close(): Promise<void> {
+    this.closing.abort();
+    return this.running;
+  }
+
+  private async run(): Promise<void> {
+    const closed = this.closing.signal;
+    let connected = false;
+    while (!closed.aborted) {
+      this.phase = "connecting";
+      let cause: unknown;
+      let link: T | undefined;
+      try {
+        link = await this.connect(closed);
+      } catch (error) {
+        cause = error;
+      }
+      if (link !== undefined) {
+        if (!closed.aborted) {
+          cause = await this.session(link, connected);
+          connected = true;
+        }
+        await this.options.disconnect?.(link);
+      }
+      if (closed.aborted) {
+        break;
+      }
+
+      this.failures++;
+      const retryInMs = reconnectDelay(this.policy, this.failures, this.options.random);
+      this.options.onDisconnect?.({ cause, failures: this.failures, retryInMs });
+      if (retryInMs === undefined) {
+        this.phase = "failed";
+        this.failure = new TransportError("Gave up reconnecting", cause);
+        for (const sink of this.sinks) {
+          sink.fail(this.failure);
+        }
+        return;
+      }
+      this.phase = "waiting";
+      await delay(retryInMs, closed);
+    }
+    this.phase = "closed";
+    for (const sink of this.sinks) {
+      sink.end();
+    }
+  }
+
+  /**
+   * Receive over `link` until it is lost or the transport closes; returns
+   * what it failed with
+   */
+  private async session(link: T, resumed: boolean): Promise<unknown> {
+    const stop = new AbortController();
+    const onClose = (): void => stop.abort();
+    this.closing.signal.addEventListener("abort", onClose);
+    let cause: unknown;
+    // Subscribed by the first next(), before the resync goes out
+    const frames = link.messages(stop.signal)[Symbol.asyncIterator]();
+    const first = frames.next();
+
+    const event: ConnectEvent<T> = { transport: link, resumed };
+    this.link = link;
+    this.phase = "connected";
+    this.options.onConnect?.(event);
+    void this.resync(link, event).catch((error: unknown) => {
+      cause = new TransportError("Sending the resync failed", error);
+      stop.abort();
+    });
+
+    try {
+      let result = await first;
+      while (result.done !== true) {
+        this.failures = 0;
+        for (const sink of this.sinks) {
+          sink.frame(result.value);
+        }
+        result = await frames.next();
+      }
+    } catch (error) {
+      cause ??= error;
+    } finally {
+      this.link = undefined;
+      this.closing.signal.removeEventListener("abort", onClose);
+      await frames.return?.();
+    }
+    return cause;
+  }
+
+  private async resync(link: T, event: ConnectEvent<T>): Promise<void> {
+    const frame = await this.options.resync?.(event);
+    if (frame !== undefined) {
+      await link.send(frame);
+    }
+  }
+}
+
+/**
+ * The delay `policy` waits before the attempt following `failures`
+ * consecutive failures (1 for the first), or undefined if it gives up
+ */
+export function reconnectDelay(
+  policy: ReconnectPolicy,
+  failures: number,
+  random: () => number = Math.random
+): number | undefined {
+  if (policy.mode === "never" || failures > (policy.maxAttempts ?? Infinity)) {
+    return undefined;
+  }
+  if (policy.mode === "fixed") {
+    return policy.delayMs;
+  }
+  const { initialDelayMs, maxDelayMs, factor = 2, jitter = 0.2 } = policy;
+  const base = Math.min(maxDelayMs, initialDelayMs * factor ** (failures - 1));
+  return base * (1 - jitter * random());
+}
+
+/**
+ * Throws RangeError if `policy` cannot be followed
+ */
+export function checkReconnectPolicy(policy: ReconnectPolicy): void {
+  if (policy.mode === "never") {
+    return;
+  }
+  const { maxAttempts = 0 } = policy;
+  if (!(maxAttempts >= 0)) {
+    throw new RangeError(`maxAttempts must not be negative, got ${String(maxAttempts)}`);
+  }
+  if (policy.mode === "fixed") {
+    if (!(policy.delayMs >= 0)) {
+      throw new RangeError(`delayMs must not be negative, got ${String(policy.delayMs)}`);
+    }
+    return;
+  }
+  const { initialDelayMs, maxDelayMs, factor = 2, jitter = 0.2 } = policy;
+  if (!(initialDelayMs >= 0) || !(maxDelayMs >= initialDelayMs)) {
+    throw new RangeError(
+      `Delays must satisfy 0 <= initialDelayMs <= maxDelayMs, got ${String(initialDelayMs)} ` +
+        `and ${String(maxDelayMs)}`
+    );
+  }
+  if (!(factor >= 1)) {
+    throw new RangeError(`factor must be at least 1, got ${String(factor)}`);
+  }
+  if (!(jitter >= 0 && jitter <= 1)) {
+    throw new RangeError(`jitter must be within [0, 1], got ${String(jitter)}`);
+  }
+}
+
+/**
+ * Resolves after `ms`, or as soon as `signal` aborts
+ */
+function delay(ms: number, signal: AbortSignal): Promise<void> {
+  if (signal.aborted) {
+    return Promise.resolve();
+  }
+  return new Promise((resolve) => {
+    let timer: ReturnType<typeof setTimeout> | undefined;
+    const done = (): void => {
+      clearTimeout(timer);
+      signal.removeEventListener("abort", done);
+      resolve();
+    };
+    timer = setTimeout(done, ms);
+    signal.addEventListener("abort", done);
+  });
+}
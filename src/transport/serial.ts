@@ -0,0 +1,96 @@
+/**
+ * Transport over a Web Serial port
+ *
+ * A serial line is a plain byte stream, so frames are delimited with COBS,
+ * as postcard firmware writes them with `to_allocvec_cobs`:
+ *
+ *   const port = await navigator.serial.requestPort();
+ *   await port.open({ baudRate: 115200 });
+ *   const link = new SerialTransport(port, { maxFrameLength: 256 });
+ *
+ * The port must be open. Only one iteration of `messages()` can read it at a
+ * time, as the port has a single readable stream; another fails with a
+ * TransportError until the first ends.
+ */
+
+import { cobsEncode } from "../codec/cobs.js";
+import { TransportError } from "../types/errors.js";
+import { CobsFrames, SendQueue, checkedMaxFrameLength, unlessAborted } from "./frames.js";
+import type { ByteStreamTransportOptions, Transport } from "./transport.js";
+
+/**
+ * The parts of an opened Web Serial `SerialPort` the transport uses
+ */
+export interface SerialPortLike {
+  readonly readable: ReadableStream<Uint8Array> | null;
+  readonly writable: WritableStream<Uint8Array> | null;
+}
+
+export class SerialTransport implements Transport {
+  readonly port: SerialPortLike;
+
+  private readonly options: ByteStreamTransportOptions;
+  private readonly sends = new SendQueue();
+
+  /**
+   * Throws RangeError if `maxFrameLength` is less than 1
+   */
+  constructor(port: SerialPortLike, options: ByteStreamTransportOptions = {}) {
+    checkedMaxFrameLength(options);
+    this.port = port;
+    this.options = options;
+  }
+
+  async *messages(signal?: AbortSignal): AsyncGenerator<Uint8Array, void, undefined> {
+    const readable = this.port.readable;
+    if (readable === null) {
+      throw new TransportError("Serial port is not open");
+    }
+    let reader: ReadableStreamDefaultReader<Uint8Array>;
+    try {
+      reader = readable.getReader();
+    } catch (error) {
+      throw new TransportError("Serial port is already being read", error);
+    }
+    const frames = new CobsFrames(this.options);
+    try {
+      for (;;) {
+        let chunk: ReadableStreamReadResult<Uint8Array> | undefined;
+        try {
+          chunk = await unlessAborted(reader.read(), signal);
+        } catch (error) {
+          throw new TransportError("Reading the serial port failed", error);
+        }
+        if (chunk === undefined || chunk.done) {
+          return;
+        }
+        yield* frames.push(chunk.value);
+      }
+    } finally {
+      // Also rejects a read left pending by an abort, its bytes left unread
+      reader.releaseLock();
+    }
+  }
+
+  send(frame: Uint8Array): Promise<void> {
+    return this.sends.run(async () => {
+      const writable = this.port.writable;
+      if (writable === null) {
+        throw new TransportError("Serial port is not open");
+      }
+      let writer: WritableStreamDefaultWriter<Uint8Array>;
+      try {
+        writer = writable.getWriter();
+      } catch (error) {
+        throw new TransportError("Serial port is being written by someone else", error);
+      }
+      try {
+        await writer.write(cobsEncode(frame));
+      } catch (error) {
+        throw new TransportError("Writing to the serial port failed", error);
+      } finally {
+        writer.releaseLock();
+      }
+    });
+  }
+}
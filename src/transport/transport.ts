@@ -0,0 +1,53 @@
+/**
+ * The transport interface every adapter implements
+ *
+ * Whatever carries the bytes (a WebSocket, Web Serial, Web Bluetooth, WebUSB
+ * or a Node socket), the application deals in frames: one postcard message
+ * each, framing removed. A Transport yields the frames it receives and sends
+ * frames, and a typed `Channel` on top decodes and encodes them, so code
+ * written against a Transport runs over any link, or over a `MockTransport`
+ * in tests:
+ *
+ *   const link: Transport = new WebSocketTransport(socket);
+ *   for await (const frame of link.messages(signal)) {
+ *     handle(frame);
+ *   }
+ *
+ * Each iteration of `messages()` receives the frames arriving while it runs;
+ * frames arriving while nothing iterates are lost. Aborting the signal, or
+ * leaving the loop, ends the iteration without closing the link.
+ */
+
+import type { FramingError } from "../codec/cobs.js";
+
+export interface Transport {
+  /**
+   * The frames received from the first `next()` on, until the link closes
+   * or `signal` aborts
+   *
+   * The iteration throws a TransportError if the link fails.
+   */
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array>;
+  /**
+   * Send one frame; rejects with a TransportError if the link cannot take it
+   */
+  send(frame: Uint8Array): Promise<void>;
+}
+
+/**
+ * Options of the adapters for byte streams (Web Serial, Web Bluetooth,
+ * WebUSB, Node sockets), which delimit frames with COBS as postcard's
+ * `to_allocvec_cobs` does
+ */
+export interface ByteStreamTransportOptions {
+  /**
+   * Longest frame accepted, delimiter included (default: unbounded); longer
+   * frames are dropped
+   */
+  readonly maxFrameLength?: number;
+  /**
+   * Called with each malformed or oversized frame, which is dropped
+   * (default: dropped silently)
+   */
+  readonly onFramingError?: (error: FramingError) => void;
+}
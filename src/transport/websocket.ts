@@ -0,0 +1,92 @@
+/**
+ * Transport over a WebSocket
+ *
+ * Each binary WebSocket message carries one frame, so no further framing is
+ * needed. Takes the browser's WebSocket, or one shaped like it such as the
+ * `ws` package's in Node:
+ *
+ *   const link = new WebSocketTransport(new WebSocket("wss://device.local/ws"));
+ *
+ * Frames sent while the socket is still connecting wait for it to open.
+ * Text messages are ignored.
+ */
+
+import { TransportError } from "../types/errors.js";
+import { pushedFrames } from "./frames.js";
+import type { Transport } from "./transport.js";
+
+// WebSocket.readyState values; the constants are missing from some runtimes
+const CONNECTING = 0;
+const OPEN = 1;
+
+export class WebSocketTransport implements Transport {
+  readonly socket: WebSocket;
+
+  /**
+   * Sets the socket's `binaryType` to `"arraybuffer"`
+   */
+  constructor(socket: WebSocket) {
+    this.socket = socket;
+    socket.binaryType = "arraybuffer";
+  }
+
+  messages(signal?: AbortSignal): AsyncIterable<Uint8Array> {
+    const socket = this.socket;
+    return pushedFrames((sink) => {
+      const onMessage = (event: MessageEvent): void => {
+        if (event.data instanceof ArrayBuffer) {
+          sink.frame(new Uint8Array(event.data));
+        }
+      };
+      const onClose = (): void => sink.end();
+      const onError = (event: Event): void =>
+        sink.fail(new TransportError("WebSocket failed", event));
+      socket.addEventListener("message", onMessage);
+      socket.addEventListener("close", onClose);
+      socket.addEventListener("error", onError);
+      if (socket.readyState > OPEN) {
+        sink.end();
+      }
+      return () => {
+        socket.removeEventListener("message", onMessage);
+        socket.removeEventListener("close", onClose);
+        socket.removeEventListener("error", onError);
+      };
+    }, signal);
+  }
+
+  async send(frame: Uint8Array): Promise<void> {
+    if (this.socket.readyState === CONNECTING) {
+      await opened(this.socket);
+    }
+    if (this.socket.readyState !== OPEN) {
+      throw new TransportError("WebSocket is closed");
+    }
+    try {
+      this.socket.send(frame);
+    } catch (error) {
+      throw new TransportError("Sending on the WebSocket failed", error);
+    }
+  }
+}
+
+/**
+ * Resolves once `socket` opens; rejects if it closes first
+ */
+function opened(socket: WebSocket): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const settle = (open: boolean): void => {
+      socket.removeEventListener("open", onOpen);
+      socket.removeEventListener("close", onClose);
+      if (open) {
+        resolve();
+      } else {
+        reject(new TransportError("WebSocket closed before opening"));
+      }
+    };
+    const onOpen = (): void => settle(true);
+    const onClose = (): void => settle(false);
+    socket.addEventListener("open", onOpen);
+    socket.addEventListener("close", onClose);
+  });
+}
@@ -0,0 +1,114 @@
+/**
+ * Transport over a WebUSB device's bulk or interrupt endpoints
+ *
+ * The endpoints carry a byte stream, so frames are delimited with COBS, as
+ * postcard firmware writes them with `to_allocvec_cobs`:
+ *
+ *   const device = await navigator.usb.requestDevice({ filters: [{ vendorId: 0x16c0 }] });
+ *   await device.open();
+ *   await device.claimInterface(0);
+ *   const link = new WebUsbTransport(device, { endpointIn: 1, endpointOut: 2 });
+ *
+ * The device must be opened and its interface claimed. A transfer cannot be
+ * cancelled, so aborting an iteration leaves the pending one to complete and
+ * drops what it brings.
+ */
+
+import { cobsEncode } from "../codec/cobs.js";
+import { TransportError } from "../types/errors.js";
+import { CobsFrames, SendQueue, checkedMaxFrameLength, unlessAborted } from "./frames.js";
+import type { ByteStreamTransportOptions, Transport } from "./transport.js";
+
+/**
+ * The parts of a WebUSB `USBDevice` the transport uses
+ */
+export interface UsbDeviceLike {
+  transferIn(
+    endpointNumber: number,
+    length: number
+  ): Promise<{ readonly data?: DataView | undefined; readonly status?: string | undefined }>;
+  transferOut(
+    endpointNumber: number,
+    data: Uint8Array
+  ): Promise<{ readonly status?: string | undefined }>;
+}
+
+export interface WebUsbTransportOptions extends ByteStreamTransportOptions {
+  /** Number of the IN endpoint the device sends on */
+  readonly endpointIn: number;
+  /** Number of the OUT endpoint the device receives on */
+  readonly endpointOut: number;
+  /** Bytes asked for per IN transfer (default: 64, a full-speed packet) */
+  readonly transferSize?: number;
+}
+
+export class WebUsbTransport implements Transport {
+  readonly device: UsbDeviceLike;
+  readonly endpointIn: number;
+  readonly endpointOut: number;
+  readonly transferSize: number;
+
+  private readonly options: ByteStreamTransportOptions;
+  private readonly sends = new SendQueue();
+
+  /**
+   * Throws RangeError if `transferSize` is not a positive integer or
+   * `maxFrameLength` is less than 1
+   */
+  constructor(device: UsbDeviceLike, options: WebUsbTransportOptions) {
+    const { endpointIn, endpointOut, transferSize = 64, ...byteStream } = options;
+    if (!Number.isInteger(transferSize) || transferSize < 1) {
+      throw new RangeError(`transferSize must be a positive integer, got ${String(transferSize)}`);
+    }
+    checkedMaxFrameLength(byteStream);
+    this.device = device;
+    this.endpointIn = endpointIn;
+    this.endpointOut = endpointOut;
+    this.transferSize = transferSize;
+    this.options = byteStream;
+  }
+
+  async *messages(signal?: AbortSignal): AsyncGenerator<Uint8Array, void, undefined> {
+    const frames = new CobsFrames(this.options);
+    for (;;) {
+      let transfer: Awaited<ReturnType<UsbDeviceLike["transferIn"]>> | undefined;
+      try {
+        transfer = await unlessAborted(
+          this.device.transferIn(this.endpointIn, this.transferSize),
+          signal
+        );
+      } catch (error) {
+        throw new TransportError("USB transfer from the device failed", error);
+      }
+      if (transfer === undefined) {
+        return;
+      }
+      checkStatus(transfer.status);
+      if (transfer.data !== undefined) {
+        const { buffer, byteOffset, byteLength } = transfer.data;
+        yield* frames.push(new Uint8Array(buffer, byteOffset, byteLength));
+      }
+    }
+  }
+
+  send(frame: Uint8Array): Promise<void> {
+    return this.sends.run(async () => {
+      let transfer: Awaited<ReturnType<UsbDeviceLike["transferOut"]>>;
+      try {
+        transfer = await this.device.transferOut(this.endpointOut, cobsEncode(frame));
+      } catch (error) {
+        throw new TransportError("USB transfer to the device failed", error);
+      }
+      checkStatus(transfer.status);
+    });
+  }
+}
+
+/**
+ * Throws a TransportError for a transfer that stalled or overran
+ */
+function checkStatus(status: string | undefined): void {
+  if (status !== undefined && status !== "ok") {
+    throw new TransportError(`USB transfer ended with status ${status}`);
+  }
+}
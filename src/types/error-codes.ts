@@ -0,0 +1,62 @@
+/**
+ * Stable error codes shared with Rust
+ *
+ * Every codec error carries one of these codes. They mirror the variants of
+ * `postcard::Error`, and the `postcard-ts-error-codes` crate in this repository
+ * maps Rust errors onto the same strings, so a Rust gateway and a TypeScript
+ * dashboard report the same identifier for the same malformed bytes. Codes are
+ * part of the public API: existing ones never change meaning.
+ */
+
+export const ErrorCode = Object.freeze({
+  /** Input ended before the value did (`DeserializeUnexpectedEnd`) */
+  UnexpectedEnd: "UNEXPECTED_END",
+  /** Varint too long or too large for its type (`DeserializeBadVarint`) */
+  BadVarint: "BAD_VARINT",
+  /** Bool byte other than 0 or 1 (`DeserializeBadBool`) */
+  BadBool: "BAD_BOOL",
+  /** Char that is not a single Unicode scalar value (`DeserializeBadChar`) */
+  BadChar: "BAD_CHAR",
+  /** String bytes that are not valid UTF-8 (`DeserializeBadUtf8`) */
+  BadUtf8: "BAD_UTF8",
+  /** Option tag other than 0 or 1 (`DeserializeBadOption`) */
+  BadOption: "BAD_OPTION",
+  /** Enum discriminant above `u32::MAX` (`DeserializeBadEnum`) */
+  BadEnum: "BAD_ENUM",
+  /** Malformed framing around the data (`DeserializeBadEncoding`) */
+  BadEncoding: "BAD_ENCODING",
+  /** CRC mismatch (`DeserializeBadCrc`) */
+  BadCrc: "BAD_CRC",
+  /**
+   * Well-formed data the target type rejects, such as an unknown enum variant
+   * index or a failed transform (`SerdeDeCustom`)
+   */
+  InvalidValue: "INVALID_VALUE",
+  /**
+   * A value that cannot be encoded, such as an out-of-range integer or a
+   * value that does not match its schema (`SerdeSerCustom`, `CollectStrError`)
+   */
+  SerializeFailed: "SERIALIZE_FAILED",
+  /** Fixed-size output buffer too small (`SerializeBufferFull`) */
+  BufferFull: "BUFFER_FULL",
+  /** Sequence serialized without a known length (`SerializeSeqLengthUnknown`) */
+  SeqLengthUnknown: "SEQ_LENGTH_UNKNOWN",
+  /** Feature postcard does not support (`WontImplement`, `NotYetImplemented`) */
+  Unsupported: "UNSUPPORTED",
+} as const);
+
+export type ErrorCode = (typeof ErrorCode)[keyof typeof ErrorCode];
+
+const CODES: ReadonlySet<string> = new Set(Object.values(ErrorCode));
+
+/**
+ * The catalogue code of an error thrown or returned by this library, or
+ * undefined for any other value
+ */
+export function errorCode(error: unknown): ErrorCode | undefined {
+  if (!(error instanceof Error) || !("code" in error)) {
+    return undefined;
+  }
+  const code: unknown = error.code;
+  return typeof code === "string" && CODES.has(code) ? (code as ErrorCode) : undefined;
+}
@@ -0,0 +1,69 @@
+/**
+ * The tree of errors the runtime throws or returns
+ *
+ * Application code can branch on the kind of failure with `instanceof`
+ * rather than matching messages:
+ *
+ *   PostcardError                 anything this library reports
+ *   ├── PostcardDecodeError       bytes that are not a value of the schema
+ *   │   └── DeserializeError      ... from schema-driven decoding
+ *   │       ├── FramingError      ... because a COBS frame is malformed or oversized
+ *   │       ├── CrcMismatchError  ... because the checksum after it does not match
+ *   │       └── ReencodeMismatchError ... because it re-encodes to other bytes (`verify`)
+ *   ├── ValidationError           a value the schema cannot encode
+ *   │   └── SerializeError        ... from schema-driven encoding
+ *   └── TransportError            sending or receiving failed
+ *
+ * Decode and validation errors carry an ErrorCode (see `errorCode()`); test
+ * for the most specific class first. Errors about the library's own inputs,
+ * such as an invalid IR or capture file, extend PostcardError directly, and
+ * misuse such as an invalid option is a plain RangeError.
+ */
+
+import { ErrorCode } from "./error-codes.js";
+
+export class PostcardError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "PostcardError";
+  }
+}
+
+/**
+ * Bytes that are not a valid value: cut off, malformed or rejected by the type
+ */
+export class PostcardDecodeError extends PostcardError {
+  constructor(
+    message: string,
+    readonly code: ErrorCode = ErrorCode.InvalidValue
+  ) {
+    super(message);
+    this.name = "PostcardDecodeError";
+  }
+}
+
+/**
+ * A value that does not fit its schema, found before anything is encoded
+ */
+export class ValidationError extends PostcardError {
+  constructor(
+    message: string,
+    readonly code: ErrorCode = ErrorCode.SerializeFailed
+  ) {
+    super(message);
+    this.name = "ValidationError";
+  }
+}
+
+/**
+ * A transport failed to carry a message; `cause` is what it threw
+ */
+export class TransportError extends PostcardError {
+  constructor(
+    message: string,
+    readonly cause?: unknown
+  ) {
+    super(message);
+    this.name = "TransportError";
+  }
+}
@@ -0,0 +1,51 @@
+/**
+ * Runtime descriptions of generated types
+ *
+ * Generated with `Generator::introspection()` (`--introspection`), every
+ * module exports `__schema`, a ModuleSchema describing the types it declares,
+ * and the index merges those of all modules. Tools shipped in the same app,
+ * such as a protocol explorer panel or a log formatter, can walk it without
+ * the JSON IR being bundled separately:
+ *
+ *   import { __schema } from "./generated/index.js";
+ *   for (const [name, type] of Object.entries(__schema)) {
+ *     console.log(name, type.kind, type.fields ?? Object.keys(type.variants ?? {}));
+ *   }
+ */
+
+import type { Schema } from "./schema.js";
+
+/**
+ * One generated type
+ */
+export interface TypeDescription {
+  readonly kind: "struct" | "tuple_struct" | "newtype_struct" | "unit_struct" | "enum";
+  /** The schema; for a generic type, the function applying it to the schemas of `params` */
+  readonly schema: Schema | ((...params: never[]) => Schema);
+  /** A struct's field names, in wire order */
+  readonly fields?: readonly string[];
+  /** A tuple struct's number of fields */
+  readonly length?: number;
+  /** An enum's variant indices, by variant name */
+  readonly variants?: Readonly<Record<string, number>>;
+  /** A generic type's type parameters */
+  readonly params?: readonly string[];
+  /** The schema hash of a non-generic type, as `checkSchemaHashes` compares */
+  readonly hash?: bigint;
+}
+
+/**
+ * The types of a generated module, by TypeScript type name
+ */
+export type ModuleSchema = Readonly<Record<string, TypeDescription>>;
+
+/**
+ * The name and description of the type in `module` with schema hash `hash`,
+ * e.g. the one a device announced in a handshake; undefined if none has it
+ */
+export function typeWithHash(
+  module: ModuleSchema,
+  hash: bigint
+): [name: string, type: TypeDescription] | undefined {
+  return Object.entries(module).find(([, type]) => type.hash === hash);
+}
@@ -42,7 +42,27 @@ export type F64Schema = BaseSchema<"f64", number>
 // ============================================================================
 
 export type CharSchema = BaseSchema<"char", string>
-export type StringSchema = BaseSchema<"string", string>
+export interface StringSchema extends BaseSchema<"string", string> {
+  /** Most bytes of UTF-8 the string may take, e.g. N for `heapless::String<N>` */
+  readonly maxBytes?: number;
+  /** What encoding does with a longer string (default: "error") */
+  readonly overflow?: StringOverflow;
+}
+
+/**
+ * Handling of strings over `maxBytes` when encoding
+ *
+ * - `error`: encoding fails with code SERIALIZE_FAILED
+ * - `truncate`: the string is cut at the last character boundary that fits
+ *
+ * Decoding always rejects them, as the device would.
+ */
+export type StringOverflow = "error" | "truncate";
+
+export interface StringOptions {
+  readonly maxBytes?: number;
+  readonly overflow?: StringOverflow;
+}
 export type BytesSchema = BaseSchema<"bytes", Uint8Array>
 
 // ============================================================================
@@ -62,6 +82,13 @@ export interface UnitStructSchema extends BaseSchema<"unit_struct", Record<strin
   readonly name: string;
 }
 
+/**
+ * Uninhabited type (`std::convert::Infallible`, `!`). No value exists, so
+ * encoding and decoding always fail, and enum variants wrapping it drop out of
+ * the inferred union: `Result<T, Infallible>` infers as the Ok variant only.
+ */
+export type NeverSchema = BaseSchema<"never", never>
+
 // ============================================================================
 // NEWTYPE TYPES (21-22)
 // ============================================================================
@@ -85,6 +112,16 @@ export interface NewtypeVariantSchema<T extends Schema>
 export interface SeqSchema<T extends Schema>
   extends BaseSchema<"seq", InferType<T>[]> {
   readonly item: T;
+  /** Most items it may hold, e.g. N for `heapless::Vec<T, N>` */
+  readonly maxLength?: number;
+}
+
+/**
+ * Capacity of a sequence or map, which encoding checks values against and
+ * decoding checks the length prefix against, as the device would
+ */
+export interface CollectionOptions {
+  readonly maxLength?: number;
 }
 
 // ============================================================================
@@ -116,6 +153,8 @@ export interface MapSchema<K extends Schema, V extends Schema>
   extends BaseSchema<"map", Map<InferType<K>, InferType<V>>> {
   readonly key: K;
   readonly value: V;
+  /** Most entries it may hold, e.g. N for `heapless::IndexMap<K, V, S, N>` */
+  readonly maxLength?: number;
 }
 
 // ============================================================================
@@ -133,6 +172,18 @@ export interface StructVariantSchema<T extends Record<string, Schema>>
   readonly fields: T;
 }
 
+/**
+ * `std::ops::Range<T>` serializes as a struct of `start` and `end`.
+ * Half-open: start <= x < end
+ */
+export type RangeSchema<S extends Schema> = StructSchema<{ start: S; end: S }>;
+
+/**
+ * `std::ops::RangeInclusive<T>` has the same layout as Range.
+ * Closed: start <= x <= end
+ */
+export type RangeInclusiveSchema<S extends Schema> = StructSchema<{ start: S; end: S }>;
+
 // ============================================================================
 // ENUM TYPE (Tagged Union - 20, 22, 26, 29)
 // ============================================================================
@@ -155,6 +206,112 @@ export interface EnumSchema<V extends EnumVariantRecord>
   readonly variants: V;
 }
 
+// ============================================================================
+// TRANSFORM TYPE
+// ============================================================================
+
+/**
+ * A schema that is encoded exactly like `inner` on the wire, but exposes a
+ * different value type in TypeScript.
+ *
+ * `decode` runs on the value read for `inner`, and `encode` produces the
+ * value that `inner` writes. Neither changes the bytes on the wire, so a
+ * transform is invisible to the Rust side.
+ */
+export interface TransformSchema<S extends Schema, T> extends BaseSchema<"transform", T> {
+  readonly inner: S;
+  readonly decode: (value: InferType<S>) => T;
+  readonly encode: (value: T) => InferType<S>;
+}
+
+// ============================================================================
+// LAZY TYPE
+// ============================================================================
+
+/**
+ * A schema resolved when it is used, for recursive types such as
+ * `Option<Box<Node>>`. Box is transparent in serde, so only the reference
+ * needs deferring; `T` is the TypeScript type of the referenced schema and
+ * must be given explicitly since it cannot be inferred from itself.
+ */
+export interface LazySchema<T> extends BaseSchema<"lazy", T> {
+  readonly get: () => Schema;
+}
+
+/**
+ * A schema of values of type `T`, for naming the value type of a generic
+ * schema factory without a schema for `T` at hand:
+ *
+ *   const EnvelopeSchema = <T extends Schema>(T: T) => struct({ seq: u32(), payload: T });
+ *   type Envelope<T> = InferType<ReturnType<typeof EnvelopeSchema<SchemaOf<T>>>>;
+ */
+export type SchemaOf<T> = LazySchema<T>;
+
+// ============================================================================
+// EXTERNAL TYPE
+// ============================================================================
+
+/**
+ * Functions encoding and decoding values of a type outside the schemas, such
+ * as the ones a wasm-bindgen crate exports for a type with a hand-written
+ * `Serialize` impl
+ */
+export interface ExternalCodec<T> {
+  /** The postcard bytes of `value` */
+  readonly encode: (value: T) => Uint8Array;
+  /** The value at the start of `data`, and how many bytes it took */
+  readonly decode: (data: Uint8Array) => { readonly value: T; readonly bytesRead: number };
+}
+
+/**
+ * A schema whose bytes are written and read by an external codec, for types
+ * the schema builders cannot express. It nests like any other schema, since
+ * the codec reports how many bytes it read.
+ */
+export interface ExternalSchema<T> extends BaseSchema<"external", T>, ExternalCodec<T> {
+  readonly name: string;
+}
+
+// ============================================================================
+// DIRECTION
+// ============================================================================
+
+/**
+ * The one way a schema may be used, for types only ever sent (`encode`) or
+ * only ever received (`decode`). Passing an `encodeOnly()` schema to
+ * `deserialize()`, or a `decodeOnly()` one to `serialize()`, is a type error,
+ * and a TypeError at runtime for callers that lost the type.
+ */
+export type Direction = "encode" | "decode";
+
+export type EncodeOnlySchema<S extends Schema> = S & { readonly direction: "encode" };
+export type DecodeOnlySchema<S extends Schema> = S & { readonly direction: "decode" };
+
+// ============================================================================
+// BRAND
+// ============================================================================
+
+/**
+ * A nominal tag for a value type, so that values that are the same on the
+ * wire (a `PlayerId(u64)` and an `ItemId(u64)`) cannot be mixed up:
+ *
+ *   const PlayerIdSchema = branded(newtypeStruct("PlayerId", u64()), "PlayerId");
+ *   type PlayerId = InferType<typeof PlayerIdSchema>; // bigint & Brand<"PlayerId">
+ *
+ * The brand only exists in TypeScript; a decoded value is the plain value,
+ * and a plain value becomes a branded one with a cast (`42n as PlayerId`).
+ */
+export interface Brand<B extends string> {
+  readonly __brand: B;
+}
+
+export type Branded<T, B extends string> = T & Brand<B>;
+
+export type BrandedSchema<S extends Schema, B extends string> = S & {
+  readonly brand: B;
+  readonly _type?: Branded<InferType<S>, B>;
+};
+
 // ============================================================================
 // UNION TYPE
 // ============================================================================
@@ -178,6 +335,7 @@ export type Schema =
   | BytesSchema
   | UnitSchema
   | UnitStructSchema
+  | NeverSchema
   // Use structural typing for generic schema types to handle variance.
   // Note: _type is omitted here as it's only needed on concrete schema types for inference.
   // The optional _type property from concrete types (e.g., OptionSchema<T>) is preserved
@@ -197,7 +355,25 @@ export type Schema =
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   | { readonly kind: "struct"; readonly fields: Record<string, any> }
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  | { readonly kind: "enum"; readonly name: string; readonly variants: Record<string, any> };
+  | { readonly kind: "enum"; readonly name: string; readonly variants: Record<string, any> }
+  | {
+      readonly kind: "transform";
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      readonly inner: any;
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      readonly decode: (value: any) => unknown;
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      readonly encode: (value: any) => unknown;
+    }
+  | { readonly kind: "lazy"; readonly get: () => Schema }
+  | {
+      readonly kind: "external";
+      readonly name: string;
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      readonly encode: (value: any) => Uint8Array;
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      readonly decode: (data: Uint8Array) => { readonly value: any; readonly bytesRead: number };
+    };
 
 // ============================================================================
 // TYPE INFERENCE
@@ -232,7 +408,9 @@ type InferEnumType<V extends Record<string, EnumVariant>> = {
   [K in keyof V]: V[K] extends UnitVariantSchema
     ? { type: K }
     : V[K] extends NewtypeVariantSchema<infer T>
-      ? { type: K; value: InferType<T> }
+      ? [InferType<T>] extends [never]
+        ? never
+        : { type: K; value: InferType<T> }
       : V[K] extends TupleVariantSchema<infer T>
         ? { type: K; value: InferTupleType<T> }
         : V[K] extends StructVariantSchema<infer T>
@@ -264,7 +442,13 @@ export const f64 = (): F64Schema => ({ kind: "f64" });
 
 // Strings
 export const char = (): CharSchema => ({ kind: "char" });
-export const string = (): StringSchema => ({ kind: "string" });
+export const string = (options: StringOptions = {}): StringSchema => {
+  const { maxBytes } = options;
+  if (maxBytes !== undefined && !(Number.isSafeInteger(maxBytes) && maxBytes >= 0)) {
+    throw new RangeError(`maxBytes must be a non-negative integer, got ${String(maxBytes)}`);
+  }
+  return { kind: "string", ...options };
+};
 export const bytes = (): BytesSchema => ({ kind: "bytes" });
 
 // Option
@@ -275,6 +459,7 @@ export const option = <T extends Schema>(inner: T): OptionSchema<T> => ({
 
 // Unit
 export const unit = (): UnitSchema => ({ kind: "unit" });
+export const never = (): NeverSchema => ({ kind: "never" });
 export const unitStruct = (name: string): UnitStructSchema => ({
   kind: "unit_struct",
   name,
@@ -291,16 +476,29 @@ export const newtypeStruct = <T extends Schema>(
 });
 
 // Collections
-export const seq = <T extends Schema>(item: T): SeqSchema<T> => ({
-  kind: "seq",
-  item,
-});
+export const seq = <T extends Schema>(item: T, options: CollectionOptions = {}): SeqSchema<T> => {
+  checkMaxLength(options);
+  return { kind: "seq", item, ...options };
+};
 
 export const tuple = <T extends readonly Schema[]>(...items: T): TupleSchema<T> => ({
   kind: "tuple",
   items,
 });
 
+/**
+ * A fixed-length array `[T; N]`, which postcard writes as a tuple of `length`
+ * items; its values are plain arrays, as a tuple type that long is no help
+ *
+ * Throws RangeError if `length` is not a non-negative integer.
+ */
+export const array = <T extends Schema>(item: T, length: number): TupleSchema<T[]> => {
+  if (!(Number.isSafeInteger(length) && length >= 0)) {
+    throw new RangeError(`Array length must be a non-negative integer, got ${String(length)}`);
+  }
+  return { kind: "tuple", items: new Array<T>(length).fill(item) };
+};
+
 export const tupleStruct = <T extends readonly Schema[]>(
   name: string,
   ...items: T
@@ -310,14 +508,45 @@ export const tupleStruct = <T extends readonly Schema[]>(
   items,
 });
 
+/**
+ * Keys may be of any type postcard can encode, except floats: a JS `Map`
+ * holds 0 and -0, or NaNs with different payloads, as one key, so decoding
+ * would drop entries. Floats inside tuples, structs or enums are fine, as
+ * those keys are compared by their encoded bytes.
+ *
+ * Throws RangeError for an `f32` or `f64` key, also inside an option or a
+ * newtype struct, or for a `maxLength` that is not a non-negative integer.
+ */
 export const map = <K extends Schema, V extends Schema>(
   key: K,
-  value: V
-): MapSchema<K, V> => ({
-  kind: "map",
-  key,
-  value,
-});
+  value: V,
+  options: CollectionOptions = {}
+): MapSchema<K, V> => {
+  if (isFloatKey(key)) {
+    throw new RangeError("Map keys cannot be f32 or f64, which a JS Map cannot tell apart");
+  }
+  checkMaxLength(options);
+  return { kind: "map", key, value, ...options };
+};
+
+function checkMaxLength({ maxLength }: CollectionOptions): void {
+  if (maxLength !== undefined && !(Number.isSafeInteger(maxLength) && maxLength >= 0)) {
+    throw new RangeError(`maxLength must be a non-negative integer, got ${String(maxLength)}`);
+  }
+}
+
+function isFloatKey(schema: Schema): boolean {
+  switch (schema.kind) {
+    case "f32":
+    case "f64":
+      return true;
+    case "option":
+    case "newtype_struct":
+      return isFloatKey(schema.inner as Schema);
+    default:
+      return false;
+  }
+}
 
 // Struct
 export const struct = <T extends Record<string, Schema>>(
@@ -327,6 +556,13 @@ export const struct = <T extends Record<string, Schema>>(
   fields,
 });
 
+// Ranges (std::ops)
+export const range = <S extends Schema>(bound: S): RangeSchema<S> =>
+  struct({ start: bound, end: bound });
+
+export const rangeInclusive = <S extends Schema>(bound: S): RangeInclusiveSchema<S> =>
+  struct({ start: bound, end: bound });
+
 // Enum
 export const unitVariant = (name: string): UnitVariantSchema => ({
   kind: "unit_variant",
@@ -368,3 +604,57 @@ export const enumType = <V extends EnumVariantRecord>(
   name,
   variants,
 });
+
+// Transform
+export const transform = <S extends Schema, T>(
+  inner: S,
+  conversions: {
+    decode: (value: InferType<S>) => T;
+    encode: (value: T) => InferType<S>;
+  }
+): TransformSchema<S, T> => ({
+  kind: "transform",
+  inner,
+  decode: conversions.decode,
+  encode: conversions.encode,
+});
+
+// Lazy
+export const lazy = <T>(get: () => Schema): LazySchema<T> => ({
+  kind: "lazy",
+  get,
+});
+
+// External
+export const external = <T>(name: string, codec: ExternalCodec<T>): ExternalSchema<T> => ({
+  kind: "external",
+  name,
+  encode: codec.encode,
+  decode: codec.decode,
+});
+
+// Direction
+export const encodeOnly = <S extends Schema>(schema: S): EncodeOnlySchema<S> => ({
+  ...schema,
+  direction: "encode" as const,
+});
+
+export const decodeOnly = <S extends Schema>(schema: S): DecodeOnlySchema<S> => ({
+  ...schema,
+  direction: "decode" as const,
+});
+
+// Brand
+export const branded = <S extends Schema, B extends string>(
+  schema: S,
+  brand: B
+): BrandedSchema<S, B> => ({
+  ...schema,
+  brand,
+});
+
+/**
+ * The direction a schema is limited to, if any
+ */
+export const directionOf = (schema: Schema): Direction | undefined =>
+  (schema as { readonly direction?: Direction }).direction;
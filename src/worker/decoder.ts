@@ -0,0 +1,249 @@
+/**
+ * Decoding in a Web Worker
+ *
+ * Decoding large telemetry payloads on the UI thread costs frames. These
+ * helpers move the work into a worker: the worker registers its schemas by
+ * name with `exposeDecoders()`, and the UI thread gets a typed async facade
+ * from `createWorkerDecoder()`. Input bytes are transferred to the worker
+ * rather than copied; decoded values come back by structured clone, and
+ * columnar results transfer their typed arrays back.
+ *
+ * Schemas hold functions (transforms, lazy references) and cannot be posted
+ * to a worker, which is why both sides refer to them by name. Structured clone
+ * keeps Maps, bigints, Dates and typed arrays, but values produced by custom
+ * transforms lose their class (e.g. a decimal library type arrives as a plain
+ * object).
+ *
+ * Any number of workers can expose the same schemas, and several facades can
+ * share one worker: request ids are unique per endpoint, so each facade only
+ * settles its own requests. Bytes in a SharedArrayBuffer are copied before
+ * being posted, so the worker never decodes memory another thread is writing.
+ */
+
+import type { Schema, InferType, SeqSchema, StructSchema } from "../types/schema.js";
+import { tryDeserialize, type DeserializeResult } from "../codec/deserializer.js";
+import { tryDecodeColumns, type Columns, type NumericSchema } from "../codec/columnar.js";
+import { ErrorCode } from "../types/error-codes.js";
+import { PostcardDecodeError, TransportError } from "../types/errors.js";
+
+export class WorkerDecodeError extends PostcardDecodeError {
+  constructor(message: string, code: ErrorCode = ErrorCode.InvalidValue) {
+    super(message, code);
+    this.name = "WorkerDecodeError";
+  }
+}
+
+/**
+ * Schemas a worker can decode, by name
+ */
+export type DecoderRegistry = Readonly<Record<string, Schema>>;
+
+/**
+ * Either end of a message channel: a Worker, the worker's global scope, or a
+ * MessagePort
+ */
+export interface MessageEndpoint {
+  postMessage(message: unknown, transfer: Transferable[]): void;
+  addEventListener(type: "message", listener: (event: MessageEvent) => void): void;
+  /** Present on MessagePort, which only delivers messages once started */
+  start?(): void;
+}
+
+interface DecodeRequest {
+  readonly id: number;
+  readonly name: string;
+  readonly mode: "value" | "columns";
+  readonly data: Uint8Array;
+}
+
+// Guarded by endpoint: the next request id of each endpoint facades post on,
+// and the schemas exposed on each worker scope
+const NEXT_IDS = new WeakMap<MessageEndpoint, { next: number }>();
+const EXPOSED = new WeakMap<MessageEndpoint, Map<string, Schema>>();
+
+type DecodeResponse =
+  | { readonly id: number; readonly ok: true; readonly value: unknown; readonly bytesRead: number }
+  | { readonly id: number; readonly ok: false; readonly message: string; readonly code: ErrorCode };
+
+/**
+ * Registry names whose schema is a sequence of numeric structs
+ */
+export type ColumnarName<R extends DecoderRegistry> = {
+  [K in keyof R & string]: R[K] extends SeqSchema<StructSchema<infer F>>
+    ? F extends Record<string, NumericSchema>
+      ? K
+      : never
+    : never;
+}[keyof R & string];
+
+type ColumnsOf<S> =
+  S extends SeqSchema<StructSchema<infer F>>
+    ? F extends Record<string, NumericSchema>
+      ? Columns<F>
+      : never
+    : never;
+
+export interface WorkerDecoder<R extends DecoderRegistry> {
+  /**
+   * Decode `data` with the schema registered as `name`
+   *
+   * The bytes are transferred to the worker: if `data` spans its whole
+   * ArrayBuffer, that buffer is detached and unusable afterwards; otherwise,
+   * or if it is a SharedArrayBuffer, the viewed bytes are copied first.
+   */
+  decode<K extends keyof R & string>(
+    name: K,
+    data: Uint8Array
+  ): Promise<DeserializeResult<InferType<R[K]>>>;
+
+  /**
+   * Decode a sequence of numeric structs into typed arrays (see decodeColumns),
+   * transferring the arrays back without copying
+   */
+  decodeColumns<K extends ColumnarName<R>>(
+    name: K,
+    data: Uint8Array
+  ): Promise<DeserializeResult<ColumnsOf<R[K]>>>;
+}
+
+/**
+ * Answer decode requests from `createWorkerDecoder()` (call inside the worker)
+ *
+ *   // decoder.worker.ts
+ *   export const schemas = { telemetry: TelemetrySchema, trace: seq(PointSchema) };
+ *   exposeDecoders(self, schemas);
+ *
+ * Exposing more schemas on the same scope adds them to those it answers for.
+ * Throws TypeError if a name is already exposed with a different schema.
+ */
+export function exposeDecoders(scope: MessageEndpoint, schemas: DecoderRegistry): void {
+  const exposed = EXPOSED.get(scope);
+  if (exposed !== undefined) {
+    for (const [name, schema] of Object.entries(schemas)) {
+      if ((exposed.get(name) ?? schema) !== schema) {
+        throw new TypeError(`A different schema is already exposed as "${name}"`);
+      }
+    }
+    for (const [name, schema] of Object.entries(schemas)) {
+      exposed.set(name, schema);
+    }
+    return;
+  }
+  const registry = new Map(Object.entries(schemas));
+  EXPOSED.set(scope, registry);
+  scope.addEventListener("message", (event: MessageEvent) => {
+    const request = event.data as DecodeRequest;
+    const response = handleRequest(registry, request);
+    scope.postMessage(response, response.ok ? transferablesOf(request.mode, response.value) : []);
+  });
+  scope.start?.();
+}
+
+/**
+ * Create a typed facade for decoding in a worker set up with `exposeDecoders()`
+ *
+ *   import type { schemas } from "./decoder.worker.js";
+ *   const worker = new Worker(new URL("./decoder.worker.js", import.meta.url), { type: "module" });
+ *   const decoder = createWorkerDecoder<typeof schemas>(worker);
+ *   const { value } = await decoder.decode("telemetry", bytes);
+ */
+export function createWorkerDecoder<R extends DecoderRegistry>(
+  worker: MessageEndpoint
+): WorkerDecoder<R> {
+  const pending = new Map<
+    number,
+    { resolve: (result: DeserializeResult<unknown>) => void; reject: (error: Error) => void }
+  >();
+  const ids = NEXT_IDS.get(worker) ?? { next: 0 };
+  NEXT_IDS.set(worker, ids);
+
+  worker.addEventListener("message", (event: MessageEvent) => {
+    const response = event.data as DecodeResponse;
+    const request = pending.get(response.id);
+    if (request === undefined) {
+      return;
+    }
+    pending.delete(response.id);
+
+    if (response.ok) {
+      request.resolve({ value: response.value, bytesRead: response.bytesRead });
+    } else {
+      request.reject(new WorkerDecodeError(response.message, response.code));
+    }
+  });
+  worker.start?.();
+
+  const send = (
+    name: string,
+    mode: DecodeRequest["mode"],
+    data: Uint8Array
+  ): Promise<DeserializeResult<unknown>> =>
+    new Promise((resolve, reject) => {
+      const id = ids.next++;
+      pending.set(id, { resolve, reject });
+
+      // Transfer only a buffer the view covers entirely; never detach bytes
+      // the caller did not pass in, nor share memory other threads may write
+      const owned =
+        !isShared(data) && data.byteOffset === 0 && data.byteLength === data.buffer.byteLength
+          ? data
+          : data.slice();
+      const request: DecodeRequest = { id, name, mode, data: owned };
+      try {
+        worker.postMessage(request, [owned.buffer as ArrayBuffer]);
+      } catch (error) {
+        pending.delete(id);
+        const message = error instanceof Error ? error.message : String(error);
+        reject(new TransportError(`Posting to the worker failed: ${message}`, error));
+      }
+    });
+
+  return {
+    decode: (name, data) => send(name, "value", data) as Promise<DeserializeResult<never>>,
+    decodeColumns: (name, data) => send(name, "columns", data) as Promise<DeserializeResult<never>>,
+  };
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+function isShared(data: Uint8Array): boolean {
+  return typeof SharedArrayBuffer !== "undefined" && data.buffer instanceof SharedArrayBuffer;
+}
+
+function handleRequest(
+  schemas: ReadonlyMap<string, Schema>,
+  request: DecodeRequest
+): DecodeResponse {
+  const schema = schemas.get(request.name);
+  if (schema === undefined) {
+    return {
+      id: request.id,
+      ok: false,
+      message: `No schema registered as "${request.name}"`,
+      code: ErrorCode.InvalidValue,
+    };
+  }
+
+  const result =
+    request.mode === "columns"
+      ? tryDecodeColumns(
+          schema as SeqSchema<StructSchema<Record<string, NumericSchema>>>,
+          request.data
+        )
+      : tryDeserialize(schema, request.data);
+
+  return result.ok
+    ? { id: request.id, ok: true, value: result.value.value, bytesRead: result.value.bytesRead }
+    : { id: request.id, ok: false, message: result.error.message, code: result.error.code };
+}
+
+function transferablesOf(mode: DecodeRequest["mode"], value: unknown): Transferable[] {
+  if (mode !== "columns") {
+    return [];
+  }
+  return Object.values(value as Record<string, ArrayBufferView>).map(
+    (column) => column.buffer as ArrayBuffer
+  );
+}
@@ -0,0 +1,159 @@
+/**
+ * Shared-memory frame ring between a decoding worker and the UI thread
+ *
+ * For the highest message rates, even posting decoded values costs too much.
+ * A frame ring is a lock-free single-producer/single-consumer queue over a
+ * SharedArrayBuffer: the worker pushes decoded numeric frames, the UI thread
+ * drains them each animation frame, and nothing is copied between threads.
+ *
+ * The frame layout comes from a struct schema of numeric fields: one f64 slot
+ * per field, in declaration order. Every numeric kind up to 32 bits fits an
+ * f64 exactly.
+ *
+ * SharedArrayBuffer requires a cross-origin isolated page in browsers (COOP
+ * and COEP headers).
+ */
+
+import type { StructSchema, InferType } from "../types/schema.js";
+import type { NumericSchema } from "../codec/columnar.js";
+
+// Header: read and write slot indices, each owned by one side
+const READ = 0;
+const WRITE = 1;
+const HEADER_BYTES = 8;
+
+const FRAME_KINDS: ReadonlySet<string> = new Set([
+  "u8",
+  "u16",
+  "u32",
+  "i8",
+  "i16",
+  "i32",
+  "f32",
+  "f64",
+]);
+
+export class FrameRing<F extends Record<string, NumericSchema>> {
+  /** Shared memory to post to the other thread, which attaches with `FrameRing.attach()` */
+  readonly buffer: SharedArrayBuffer;
+  /** Field names in slot order */
+  readonly fields: readonly (keyof F & string)[];
+  /** Most frames the ring holds at once */
+  readonly capacity: number;
+
+  private readonly header: Int32Array;
+  private readonly slots: Float64Array;
+  private readonly slotCount: number;
+
+  private constructor(schema: StructSchema<F>, buffer: SharedArrayBuffer) {
+    this.fields = Object.keys(schema.fields) as (keyof F & string)[];
+    for (const name of this.fields) {
+      const kind: string = schema.fields[name].kind;
+      if (!FRAME_KINDS.has(kind)) {
+        throw new RangeError(`Field "${name}" has kind ${kind}, which does not fit a frame slot`);
+      }
+    }
+
+    const frameBytes = this.fields.length * Float64Array.BYTES_PER_ELEMENT;
+    const slotBytes = buffer.byteLength - HEADER_BYTES;
+    if (frameBytes === 0 || slotBytes <= 0 || slotBytes % frameBytes !== 0) {
+      throw new RangeError(
+        `Buffer of ${String(buffer.byteLength)} bytes does not match the frame layout`
+      );
+    }
+
+    this.buffer = buffer;
+    this.header = new Int32Array(buffer, 0, 2);
+    this.slots = new Float64Array(buffer, HEADER_BYTES);
+    // One slot always stays empty so a full ring can be told apart from an empty one
+    this.slotCount = slotBytes / frameBytes;
+    this.capacity = this.slotCount - 1;
+  }
+
+  /**
+   * Allocate a ring holding up to `capacity` frames of `schema`
+   */
+  static create<F extends Record<string, NumericSchema>>(
+    schema: StructSchema<F>,
+    capacity: number
+  ): FrameRing<F> {
+    if (!Number.isInteger(capacity) || capacity < 1) {
+      throw new RangeError(`Capacity must be a positive integer, got ${String(capacity)}`);
+    }
+    const frameBytes = Object.keys(schema.fields).length * Float64Array.BYTES_PER_ELEMENT;
+    return new FrameRing(schema, new SharedArrayBuffer(HEADER_BYTES + (capacity + 1) * frameBytes));
+  }
+
+  /**
+   * Use a ring created on another thread, received via postMessage
+   */
+  static attach<F extends Record<string, NumericSchema>>(
+    schema: StructSchema<F>,
+    buffer: SharedArrayBuffer
+  ): FrameRing<F> {
+    return new FrameRing(schema, buffer);
+  }
+
+  /**
+   * Frames waiting to be read
+   */
+  get size(): number {
+    const read = Atomics.load(this.header, READ);
+    const write = Atomics.load(this.header, WRITE);
+    return (write - read + this.slotCount) % this.slotCount;
+  }
+
+  /**
+   * Append a frame (producer side only)
+   *
+   * Returns false, dropping the frame, when the ring is full.
+   */
+  push(frame: InferType<StructSchema<F>>): boolean {
+    const write = Atomics.load(this.header, WRITE);
+    const next = (write + 1) % this.slotCount;
+    if (next === Atomics.load(this.header, READ)) {
+      return false;
+    }
+
+    const values = frame as Record<string, number>;
+    const base = write * this.fields.length;
+    this.fields.forEach((name, i) => {
+      this.slots[base + i] = values[name] ?? NaN;
+    });
+
+    // Publish only after the frame is written
+    Atomics.store(this.header, WRITE, next);
+    return true;
+  }
+
+  /**
+   * Remove and return the oldest frame (consumer side only)
+   */
+  shift(): InferType<StructSchema<F>> | undefined {
+    const read = Atomics.load(this.header, READ);
+    if (read === Atomics.load(this.header, WRITE)) {
+      return undefined;
+    }
+
+    const frame: Record<string, number> = {};
+    const base = read * this.fields.length;
+    this.fields.forEach((name, i) => {
+      frame[name] = this.slots[base + i] ?? NaN;
+    });
+
+    // Release the slot only after the frame is read
+    Atomics.store(this.header, READ, (read + 1) % this.slotCount);
+    return frame as InferType<StructSchema<F>>;
+  }
+
+  /**
+   * Remove and return every waiting frame, oldest first (consumer side only)
+   */
+  drain(): InferType<StructSchema<F>>[] {
+    const frames: InferType<StructSchema<F>>[] = [];
+    for (let frame = this.shift(); frame !== undefined; frame = this.shift()) {
+      frames.push(frame);
+    }
+    return frames;
+  }
+}
@@ -0,0 +1,22 @@
+//! Expose the resolved postcard version as `POSTCARD_VERSION`, so the manifest
+//! records which postcard wrote the fixtures (see `interop-matrix.sh`).
+
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let version = lock
+        .split("[[package]]")
+        .find(|package| package.contains("\nname = \"postcard\"\n"))
+        .and_then(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("version = \""))
+                .and_then(|rest| rest.strip_suffix('"'))
+        })
+        .unwrap_or("unknown");
+
+    println!("cargo:rustc-env=POSTCARD_VERSION={}", version);
+}
@@ -0,0 +1,30 @@
+#!/usr/bin/env sh
+# Generate the fixtures once per postcard version into fixtures/postcard-<version>/
+#
+#   ./interop-matrix.sh               # pinned versions plus the latest 1.x
+#   ./interop-matrix.sh 1.0.8 1.1.1   # specific versions ("latest" for the newest)
+#
+# tests/integration/postcard-versions.test.ts then checks that every version's
+# fixtures decode identically. Cargo.lock is restored afterwards; set
+# CARGO_NET_OFFLINE=true to pick from the versions already downloaded.
+set -eu
+
+cd "$(dirname "$0")"
+
+if [ "$#" -eq 0 ]; then
+    set -- 1.0.8 1.1.1 latest
+fi
+
+[ -f Cargo.lock ] || cargo generate-lockfile
+cp Cargo.lock Cargo.lock.matrix-backup
+trap 'mv Cargo.lock.matrix-backup Cargo.lock' EXIT
+
+for version in "$@"; do
+    if [ "$version" = latest ]; then
+        cargo update -p postcard
+    else
+        cargo update -p postcard --precise "$version"
+    fi
+    resolved=$(cargo pkgid postcard | sed 's/.*[@#]//')
+    cargo run --release -- "fixtures/postcard-$resolved"
+done
@@ -1,15 +1,32 @@
+mod manifest;
+mod random;
+mod ring;
+mod stream;
 mod types;
 
-use std::collections::HashMap;
-use std::fs;
+use chrono::DateTime;
+use crc::Crc;
+use manifest::{CrcFlavor, FixtureWriter, NamedCrc};
+use ring::LogRing;
+use stream::Framing;
+use std::collections::BTreeMap;
+use std::num::{NonZeroU16, NonZeroU64, Wrapping};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32};
 use std::path::Path;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
 use types::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let fixtures_dir = Path::new("fixtures");
-    fs::create_dir_all(fixtures_dir)?;
+    // The interop matrix writes each postcard version's fixtures to its own directory
+    let dir = std::env::args().nth(1).unwrap_or_else(|| "fixtures".to_string());
+    let mut fixtures = FixtureWriter::new(Path::new(&dir))?;
 
-    println!("Generating postcard binary fixtures...\n");
+    println!(
+        "Generating postcard binary fixtures with postcard {}...\n",
+        env!("POSTCARD_VERSION")
+    );
 
     // Primitives
     let primitives = Primitives {
@@ -24,12 +41,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         u32_field: 4294967295,
         u64_field: 18446744073709551615,
         u128_field: 340282366920938463463374607431768211455,
-        f32_field: -32.005859375,
+        f32_field: -32.00586,
         f64_field: -32.005859375,
         char_field: '🦀',
         string_field: "Hello, postcard!".to_string(),
     };
-    write_fixture("primitives.bin", &primitives)?;
+    fixtures.write("primitives.bin", &primitives)?;
 
     // Collections
     let collections = Collections {
@@ -40,30 +57,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         option_some: Some(12345),
         option_none: None,
     };
-    write_fixture("collections.bin", &collections)?;
+    fixtures.write("collections.bin", &collections)?;
 
     // Enums - Unit variant
     let enum_unit = ComplexEnum::UnitVariant;
-    write_fixture("enum_unit.bin", &enum_unit)?;
+    fixtures.write("enum_unit.bin", &enum_unit)?;
 
     // Enums - Newtype variant
     let enum_newtype = ComplexEnum::NewtypeVariant(999);
-    write_fixture("enum_newtype.bin", &enum_newtype)?;
+    fixtures.write("enum_newtype.bin", &enum_newtype)?;
 
     // Enums - Tuple variant
     let enum_tuple = ComplexEnum::TupleVariant("tuple".to_string(), -500, false);
-    write_fixture("enum_tuple.bin", &enum_tuple)?;
+    fixtures.write("enum_tuple.bin", &enum_tuple)?;
 
     // Enums - Struct variant
+    #[allow(clippy::approx_constant)]
     let enum_struct = ComplexEnum::StructVariant {
         x: 3.14159,
         y: 2.71828,
         label: "point".to_string(),
     };
-    write_fixture("enum_struct.bin", &enum_struct)?;
+    fixtures.write("enum_struct.bin", &enum_struct)?;
 
     // Nested structures
-    let mut map = HashMap::new();
+    let mut map = BTreeMap::new();
     map.insert("alice".to_string(), 100);
     map.insert("bob".to_string(), 200);
     map.insert("charlie".to_string(), 300);
@@ -85,7 +103,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             },
         ],
     };
-    write_fixture("nested.bin", &nested)?;
+    fixtures.write("nested.bin", &nested)?;
 
     // Edge cases
     let edge_cases = EdgeCases {
@@ -99,28 +117,562 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         max_u32: u32::MAX,
         negative: -999999,
     };
-    write_fixture("edge_cases.bin", &edge_cases)?;
+    fixtures.write("edge_cases.bin", &edge_cases)?;
 
     // Newtype struct
     let newtype = NewtypeStruct(987654321);
-    write_fixture("newtype_struct.bin", &newtype)?;
+    fixtures.write("newtype_struct.bin", &newtype)?;
 
     // Unit struct
     let unit = UnitStruct;
-    write_fixture("unit_struct.bin", &unit)?;
+    fixtures.write("unit_struct.bin", &unit)?;
 
     // Tuple struct
     let tuple_struct = TupleStruct("tuple_data".to_string(), 777, true);
-    write_fixture("tuple_struct.bin", &tuple_struct)?;
+    fixtures.write("tuple_struct.bin", &tuple_struct)?;
 
     // Complex integration test - Game State
     let game_state = create_game_state();
-    write_fixture("game_state.bin", &game_state)?;
+    fixtures.write("game_state.bin", &game_state)?;
+
+    // Enum variants wrapping maps of sequences of enums
+    let channel_layouts = create_channel_layouts();
+    fixtures.write("channel_layouts.bin", &channel_layouts)?;
+
+    // std::ops ranges
+    let calibration = Calibration {
+        raw_window: 100..4000,
+        temperature_c: -20.5..=85.0,
+        sample_ids: -5..10_000_000_000,
+    };
+    fixtures.write("calibration.bin", &calibration)?;
+
+    // Wrapping<T> and atomics
+    let counters = SharedCounters {
+        sequence: Wrapping(u32::MAX) + Wrapping(3),
+        rx_packets: AtomicU32::new(70000),
+        drift_us: AtomicI64::new(-1500),
+        link_up: AtomicBool::new(true),
+        checksum: Wrapping(250u8) + Wrapping(10),
+    };
+    fixtures.write("shared_counters.bin", &counters)?;
+
+    // Bit-packed flags via a hand-written Serialize impl
+    let device_status = DeviceStatus {
+        flags: StatusFlags {
+            heater: true,
+            pump: false,
+            valve_open: true,
+            alarm: true,
+        },
+        uptime_s: 86400,
+    };
+    fixtures.write("device_status.bin", &device_status)?;
+
+    // Half-precision floats - including subnormal and overflow-to-infinity values
+    let fusion_sample = FusionSample {
+        orientation: [
+            half::f16::from_f32(std::f32::consts::FRAC_1_SQRT_2),
+            half::f16::from_f32(-std::f32::consts::FRAC_1_SQRT_2),
+            half::f16::from_f32(0.0),
+            half::f16::from_f32(1.0e-7),
+        ],
+        accel: [
+            half::bf16::from_f32(9.81),
+            half::bf16::from_f32(-0.015),
+            half::bf16::from_f32(3.0e38),
+        ],
+        temperature: half::f16::from_f32(21.37),
+        peak: half::f16::from_f32(70000.0),
+    };
+    fixtures.write("fusion_sample.bin", &fusion_sample)?;
+
+    // Decimals - scale is preserved, so trailing zeros survive the round trip
+    let ledger_entry = LedgerEntry {
+        amount: "1234.50".parse()?,
+        refund: "-0.01".parse()?,
+        fx_rate: "0.0000000000000000000000000001".parse()?,
+        balance: "0".parse()?,
+        limit: rust_decimal::Decimal::MAX,
+    };
+    fixtures.write("ledger_entry.bin", &ledger_entry)?;
+
+    // Date and time types - chrono writes only as many fraction digits as it needs
+    let schedule = Schedule {
+        created: DateTime::from_timestamp(1_700_000_000, 123_000_000).ok_or("created")?,
+        starts: OffsetDateTime::from_unix_timestamp_nanos(1_700_003_600_000_250_000)?,
+        interval: Duration::new(90, 500_000),
+        deadline: Some(DateTime::from_timestamp(1_700_086_400, 1).ok_or("deadline")?),
+    };
+    fixtures.write("schedule.bin", &schedule)?;
+    // Negative and five-digit years, and the longest duration
+    let schedule_extremes = Schedule {
+        created: DateTime::from_timestamp(-62_198_755_200, 999_999_999).ok_or("created")?,
+        starts: OffsetDateTime::from_unix_timestamp(253_402_300_799)?,
+        interval: Duration::MAX,
+        deadline: Some(DateTime::from_timestamp(253_402_300_800, 0).ok_or("deadline")?),
+    };
+    fixtures.write("schedule_extremes.bin", &schedule_extremes)?;
+
+    // Ids - uuid writes its bytes for postcard and its text for serde_json
+    let account = Account {
+        id: Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8")?,
+        parent: Some(Uuid::max()),
+        port: NonZeroU16::new(8080).ok_or("port")?,
+        sequence: NonZeroU64::MAX,
+    };
+    fixtures.write("account.bin", &account)?;
+
+    // Identifier enum - unit-only enum as map key and field selector
+    let mut enabled = BTreeMap::new();
+    enabled.insert(SensorField::Temperature, true);
+    enabled.insert(SensorField::Pressure, false);
+    let sensor_selection = SensorSelection {
+        primary: SensorField::Humidity,
+        enabled,
+        order: vec![SensorField::Pressure, SensorField::Temperature],
+    };
+    fixtures.write("sensor_selection.bin", &sensor_selection)?;
+
+    // Plotting data - a trajectory of points, decoded column-wise in TypeScript
+    let trajectory: Vec<Coordinates> = (0..5)
+        .map(|i| {
+            let t = f64::from(i) * 0.5;
+            Coordinates {
+                x: t,
+                y: 2.0 - t * t,
+                z: 100.0 + t,
+            }
+        })
+        .collect();
+    fixtures.write("trajectory.bin", &trajectory)?;
+
+    // ADC samples - a homogeneous numeric sequence decoded into a typed array
+    let adc_samples: Vec<i16> = vec![-2048, -1024, 0, 1023, 2047, 12, -7];
+    fixtures.write("adc_samples.bin", &adc_samples)?;
+
+    // Recursive type - three-level tree through Option<Box<T>>
+    let tree = TreeNode {
+        left: Some(Box::new(TreeNode {
+            left: Some(Box::new(TreeNode::leaf("left-left", 3))),
+            ..TreeNode::leaf("left", 2)
+        })),
+        right: Some(Box::new(TreeNode {
+            right: Some(Box::new(TreeNode::leaf("right-right", 5))),
+            ..TreeNode::leaf("right", 4)
+        })),
+        ..TreeNode::leaf("root", 1)
+    };
+    fixtures.write("tree.bin", &tree)?;
+
+    // Uninhabited type - Result<T, Infallible> only ever holds Ok
+    let infallible = InfallibleReplies {
+        replies: vec![Ok(7), Ok(300)],
+    };
+    fixtures.write("infallible_replies.bin", &infallible)?;
+
+    // Telemetry - wrapped ring buffer of log records
+    let log_ring = create_log_ring()?;
+    fixtures.write_ring("log_ring", &log_ring)?;
+
+    // Telemetry - capture of raw frames holding encoded log records
+    let capture = Capture {
+        version: 1,
+        frames: log_ring
+            .records
+            .iter()
+            .enumerate()
+            .map(|(i, record)| {
+                Ok(CapturedFrame {
+                    time_ms: 1_700_000_000_000 + 250 * i as u64,
+                    data: postcard::to_allocvec(record)?,
+                })
+            })
+            .collect::<Result<_, postcard::Error>>()?,
+    };
+    fixtures.write("capture.bin", &capture)?;
+
+    // Zero-length messages and single-variant enums
+    fixtures.write("unit.bin", &())?;
+    fixtures.write("empty_struct.bin", &EmptyStruct {})?;
+    fixtures.write("single_variant.bin", &SingleVariant::Only)?;
+    fixtures.write("single_data_variant.bin", &SingleDataVariant::Reading(300))?;
+
+    // Renamed fields and variants
+    let settings = DeviceSettings {
+        sample_rate_hz: 48_000,
+        device_id: 7,
+        mode: DeviceMode::Streaming { batch_size: 32 },
+    };
+    fixtures.write("device_settings.bin", &settings)?;
+    fixtures.write("device_mode_off.bin", &DeviceMode::Disabled)?;
+
+    // Skipped fields are not on the wire
+    let session = Session {
+        id: 42,
+        cache: vec![1, 2, 3],
+        retries: 3,
+    };
+    fixtures.write("session.bin", &session)?;
+
+    // Strings at their length limit, one of them ending in a 4-byte character
+    let label = DeviceLabel {
+        name: "Kühlraum 2 🦀".to_string(),
+        unit: Some("°C".to_string()),
+    };
+    fixtures.write("device_label.bin", &label)?;
+
+    // Maps keyed by integers, enums and tuples
+    let mut by_id = BTreeMap::new();
+    by_id.insert(7, "seven".to_string());
+    by_id.insert(300, "three hundred".to_string());
+    by_id.insert(u32::MAX, "max".to_string());
+    let mut by_color = BTreeMap::new();
+    by_color.insert(DragonColor::Red, 12);
+    by_color.insert(DragonColor::Green, 900);
+    fixtures.write("keyed_maps.bin", &KeyedMaps { by_id, by_color })?;
+
+    let mut cells = CellMap::new();
+    cells.insert((0, 0), DragonColor::Blue);
+    cells.insert((0, 1), DragonColor::Red);
+    cells.insert((2, 255), DragonColor::Green);
+    fixtures.write_map("cell_map.bin", &cells)?;
+
+    // Maps keyed by the remaining key types, at their extremes
+    let wide_keys = WideKeys {
+        by_serial: BTreeMap::from([(0, "first".to_string()), (u64::MAX, "last".to_string())]),
+        by_offset: BTreeMap::from([(i16::MIN, 1), (-1, 2), (0, 3), (i16::MAX, 4)]),
+        by_flag: BTreeMap::from([(false, 0), (true, 1)]),
+        by_letter: BTreeMap::from([('a', 1), ('ß', 2), ('🦀', 3)]),
+        by_sensor: BTreeMap::from([(SensorId(3), 21.5), (SensorId(65535), -4.25)]),
+    };
+    fixtures.write("wide_keys.bin", &wide_keys)?;
+
+    let slots = SlotMap::from([
+        (None, "spare".to_string()),
+        (Some(0), "left".to_string()),
+        (Some(1), "right".to_string()),
+    ]);
+    fixtures.write_map("slot_map.bin", &slots)?;
+
+    // Byte strings, one of them long enough for a two-byte length
+    let chunk = FirmwareChunk {
+        offset: 4096,
+        data: (0..200).map(|i| (i * 7) as u8).collect(),
+        signature: Some(serde_bytes::ByteBuf::from(vec![0x00, 0xff])),
+    };
+    fixtures.write("firmware_chunk.bin", &chunk)?;
+
+    // Absent, empty and filled lists in each shape
+    fixtures.write(
+        "list_shapes_absent.bin",
+        &ListShapes {
+            maybe_items: None,
+            sparse_items: vec![],
+            items: vec![],
+        },
+    )?;
+    fixtures.write(
+        "list_shapes_empty.bin",
+        &ListShapes {
+            maybe_items: Some(vec![]),
+            sparse_items: vec![None],
+            items: vec![],
+        },
+    )?;
+    fixtures.write(
+        "list_shapes_full.bin",
+        &ListShapes {
+            maybe_items: Some(vec![1, 300]),
+            sparse_items: vec![None, Some(300), None],
+            items: vec![7],
+        },
+    )?;
+
+    // Generic types, plain and applied by another type
+    let samples = Envelope {
+        seq: 7,
+        payload: vec![1u16, 300, 65535],
+    };
+    fixtures.write("envelope_samples.bin", &samples)?;
+    let exchange = Exchange {
+        request: Envelope {
+            seq: 1,
+            payload: "locate".to_string(),
+        },
+        replies: vec![
+            Envelope {
+                seq: 2,
+                payload: Reply::Busy,
+            },
+            Envelope {
+                seq: 3,
+                payload: Reply::Failed {
+                    code: 504,
+                    partial: Some(Coordinates {
+                        x: 1.5,
+                        y: -2.0,
+                        z: 0.0,
+                    }),
+                },
+            },
+            Envelope {
+                seq: 4,
+                payload: Reply::Done(Coordinates {
+                    x: 10.0,
+                    y: 20.5,
+                    z: -3.25,
+                }),
+            },
+        ],
+    };
+    fixtures.write("exchange.bin", &exchange)?;
+
+    // COBS framing, including frames of zero-length payloads
+    fixtures.write_cobs("unit.cobs", &())?;
+    fixtures.write_cobs("empty_struct.cobs", &EmptyStruct {})?;
+    fixtures.write_cobs("unit_struct.cobs", &UnitStruct)?;
+    fixtures.write_cobs("single_variant.cobs", &SingleVariant::Only)?;
+    fixtures.write_cobs("edge_cases.cobs", &edge_cases)?;
+    // 254 non-zero payload bytes (length varint 0xfc 0x01, then 252 bytes): a full COBS block
+    fixtures.write_cobs("full_block.cobs", &vec![0xaau8; 252])?;
+    fixtures.write_cobs("primitives.cobs", &primitives)?;
+    fixtures.cobs_error_case::<u32>("cobs_truncated_block", &[0x05, 0x01, 0x02, 0x00])?;
+    fixtures.cobs_error_case::<u8>("cobs_empty_frame", &[0x00])?;
+    // Zero-heavy payload: every byte but the length prefix is stuffed
+    fixtures.write_cobs("zeros.cobs", &vec![0u8; 300])?;
+    // Frames back to back, across full blocks, runs of zeros, a malformed
+    // frame and a bare delimiter, as a device's serial output would be
+    let mixed: Vec<u8> = (0..600u32).map(|i| if i % 97 < 3 { 0 } else { i as u8 | 1 }).collect();
+    fixtures.write_cobs_stream::<Vec<u8>>(
+        "cobs_stream.bin",
+        &[
+            postcard::to_allocvec_cobs(&Vec::<u8>::new())?,
+            postcard::to_allocvec_cobs(&vec![0u8; 300])?,
+            postcard::to_allocvec_cobs(&vec![0xaau8; 252])?,
+            postcard::to_allocvec_cobs(&vec![0xaau8; 253])?,
+            vec![0x05, 0x01, 0x02, 0x00],
+            postcard::to_allocvec_cobs(&mixed)?,
+            vec![0x00],
+            postcard::to_allocvec_cobs(&vec![7u8])?,
+        ],
+    )?;
+
+    // Events back to back on one link, every variant, one of them long
+    // enough for a two-byte length prefix
+    let mut events = game_state.events.clone();
+    events.push(GameEvent::EnemySpawn {
+        enemy_type: "Dragon".to_string(),
+        count: 1,
+    });
+    events.push(GameEvent::LocationDiscovered("the long way round ".repeat(8)));
+    events.push(GameEvent::ItemDropped(Item::Weapon(Weapon {
+        name: "Frostbrand".to_string(),
+        damage: 40,
+        element: Some(Element::Ice),
+    })));
+    fixtures.write_stream("game_events_length_prefix.bin", Framing::LengthPrefix, &events)?;
+    fixtures.write_stream("game_events_cobs.bin", Framing::Cobs, &events)?;
+
+    // Values followed by a CRC, as postcard's CRC flavor writes them
+    let crc32 = NamedCrc {
+        name: "CRC_32_ISCSI",
+        crc: Crc::<u32>::new(&crc::CRC_32_ISCSI),
+    };
+    fixtures.write_crc("unit_crc32.bin", &(), &crc32)?;
+    fixtures.write_crc("primitives_crc32.bin", &primitives, &crc32)?;
+    fixtures.write_crc("edge_cases_crc32.bin", &edge_cases, &crc32)?;
+    fixtures.write_crc("exchange_crc32.bin", &exchange, &crc32)?;
+    // Other widths, including one that is not reflected
+    let crc16 = NamedCrc {
+        name: "CRC_16_XMODEM",
+        crc: Crc::<u16>::new(&crc::CRC_16_XMODEM),
+    };
+    fixtures.write_crc("primitives_crc16.bin", &primitives, &crc16)?;
+    let crc64 = NamedCrc {
+        name: "CRC_64_XZ",
+        crc: Crc::<u64>::new(&crc::CRC_64_XZ),
+    };
+    fixtures.write_crc("primitives_crc64.bin", &primitives, &crc64)?;
+    let mut corrupted = crc32.serialize(&300u32)?;
+    corrupted[0] ^= 0x01;
+    fixtures.crc_error_case::<u32>("crc_bit_flip", &corrupted, &crc32)?;
+    let message = crc32.serialize(&300u32)?;
+    fixtures.crc_error_case::<u32>("crc_truncated", &message[..message.len() - 1], &crc32)?;
+
+    // Maps repeating a key, which postcard does not forbid; serde keeps the last value
+    fixtures.duplicate_keys_case::<String, u32>(
+        "duplicate_string_keys",
+        &[0x03, 0x01, b'a', 0x01, 0x01, b'b', 0x02, 0x01, b'a', 0x03],
+    )?;
+    fixtures.duplicate_keys_case::<u64, u8>(
+        "duplicate_u64_keys",
+        &[0x02, 0x80, 0x01, 0x01, 0x80, 0x01, 0x02],
+    )?;
+    fixtures.duplicate_keys_case::<SensorField, bool>(
+        "duplicate_enum_keys",
+        &[0x03, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00],
+    )?;
+    // Tuple keys decode to arrays in TypeScript, so repeats are not the same object
+    fixtures.duplicate_keys_case::<(u8, u8), String>(
+        "duplicate_tuple_keys",
+        &[0x02, 0x01, 0x02, 0x01, b'x', 0x01, 0x02, 0x01, b'y'],
+    )?;
+
+    // Error codes - malformed bytes and the code postcard reports for each
+    fixtures.error_case::<u32>("empty_input", &[])?;
+    fixtures.error_case::<bool>("bad_bool", &[0x02])?;
+    fixtures.error_case::<u16>("varint_above_u16", &[0xff, 0xff, 0x04])?;
+    fixtures.error_case::<u32>("varint_too_long", &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01])?;
+    fixtures.error_case::<f32>("truncated_f32", &[0x00, 0x00])?;
+    fixtures.error_case::<String>("truncated_string", &[0x05, b'a', b'b'])?;
+    fixtures.error_case::<String>("bad_utf8", &[0x02, 0xc3, 0x28])?;
+    fixtures.error_case::<char>("bad_char", &[0x01, 0xff])?;
+    fixtures.error_case::<Option<u8>>("bad_option", &[0x02])?;
+    fixtures.error_case::<ComplexEnum>("unknown_variant", &[0x09])?;
+    fixtures.error_case::<Primitives>("truncated_struct", &[0x01, 0x56])?;
+
+    // Random instances of every registered type, reproducible from the seed
+    let (seed, count) = random_settings()?;
+    macro_rules! write_random {
+        ($($ty:ty),* $(,)?) => {
+            $(fixtures.write_random::<$ty>(seed, count)?;)*
+        };
+    }
+    write_random!(
+        Primitives,
+        Collections,
+        ComplexEnum,
+        Nested,
+        InnerStruct,
+        EdgeCases,
+        NewtypeStruct,
+        UnitStruct,
+        TupleStruct,
+        GameState,
+        Player,
+        Inventory,
+        Coordinates,
+        Enemy,
+        DragonData,
+        DragonColor,
+        Item,
+        Weapon,
+        Element,
+        World,
+        Location,
+        BossInfo,
+        GameEvent,
+        PlayerAction,
+        GameMetadata,
+        Difficulty,
+        Channel,
+        ChannelLayout,
+        LogRecord,
+        LogLevel,
+        Capture,
+        CapturedFrame,
+        SensorField,
+        SensorSelection,
+        TreeNode,
+        EmptyStruct,
+        SingleVariant,
+        SingleDataVariant,
+        DeviceSettings,
+        DeviceMode,
+        Session,
+        DeviceLabel,
+        KeyedMaps,
+        SensorId,
+        WideKeys,
+        ListShapes,
+        FirmwareChunk,
+        Exchange,
+    );
+
+    fixtures.write_ir()?;
+    fixtures.write_generated_ts()?;
+    fixtures.finish()?;
 
     println!("\n✓ All fixtures generated successfully!");
     Ok(())
 }
 
+/// The seed and number of random fixtures per type, from
+/// `POSTCARD_FIXTURE_SEED` (decimal or `0x` hex) and `POSTCARD_RANDOM_FIXTURES`
+fn random_settings() -> Result<(u64, u64), Box<dyn std::error::Error>> {
+    let seed = match std::env::var("POSTCARD_FIXTURE_SEED") {
+        Ok(seed) => match seed.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16)?,
+            None => seed.parse()?,
+        },
+        Err(_) => 0x5eed,
+    };
+    let count = match std::env::var("POSTCARD_RANDOM_FIXTURES") {
+        Ok(count) => count.parse()?,
+        Err(_) => 4,
+    };
+    Ok((seed, count))
+}
+
+fn create_channel_layouts() -> Vec<ChannelLayout> {
+    let mut by_name = BTreeMap::new();
+    by_name.insert(
+        "inputs".to_string(),
+        vec![Channel::Analog(512), Channel::Digital(true), Channel::Off],
+    );
+    by_name.insert("unused".to_string(), vec![]);
+    by_name.insert("outputs".to_string(), vec![Channel::Digital(false)]);
+
+    let mut groups = BTreeMap::new();
+    groups.insert("a".to_string(), vec![Channel::Off, Channel::Analog(1023)]);
+
+    let mut layer = BTreeMap::new();
+    layer.insert("x".to_string(), Channel::Analog(300));
+    layer.insert("y".to_string(), Channel::Off);
+
+    vec![
+        ChannelLayout::Empty,
+        ChannelLayout::ByName(by_name),
+        ChannelLayout::Grouped {
+            groups,
+            fallback: Some(Channel::Digital(true)),
+        },
+        ChannelLayout::Grouped {
+            groups: BTreeMap::new(),
+            fallback: None,
+        },
+        ChannelLayout::Layered(vec![layer, BTreeMap::new()]),
+        ChannelLayout::ByName(BTreeMap::new()),
+    ]
+}
+
+fn create_log_ring() -> Result<LogRing<LogRecord>, Box<dyn std::error::Error>> {
+    let messages = [
+        (LogLevel::Info, "boot"),
+        (LogLevel::Debug, "sensor init"),
+        (LogLevel::Info, "wifi connected"),
+        (LogLevel::Warn, "temperature high"),
+        (LogLevel::Debug, "sample"),
+        (LogLevel::Error, "pump stalled"),
+        (LogLevel::Info, "pump restarted"),
+        (LogLevel::Debug, "sample"),
+        (LogLevel::Warn, "low water"),
+        (LogLevel::Info, "shutdown requested"),
+    ];
+
+    // Small enough that the ring wraps and overwrites the oldest records
+    let mut ring = LogRing::new(96);
+    for (i, (level, message)) in messages.into_iter().enumerate() {
+        ring.push(LogRecord {
+            timestamp_ms: 1000 + 250 * i as u32,
+            level,
+            message: message.to_string(),
+        })?;
+    }
+    Ok(ring)
+}
+
 fn create_game_state() -> GameState {
     // Create player with inventory
     let player = Player {
@@ -161,10 +713,7 @@ fn create_game_state() -> GameState {
 
     // Create diverse enemy types
     let enemies = vec![
-        Enemy::Goblin {
-            id: 1,
-            aggro: true,
-        },
+        Enemy::Goblin { id: 1, aggro: true },
         Enemy::Dragon(DragonData {
             color: DragonColor::Red,
             age: 500,
@@ -178,7 +727,7 @@ fn create_game_state() -> GameState {
     ];
 
     // Create world with locations
-    let mut locations = HashMap::new();
+    let mut locations = BTreeMap::new();
     locations.insert(
         "forest".to_string(),
         Location {
@@ -266,14 +815,3 @@ fn create_game_state() -> GameState {
         metadata,
     }
 }
-
-fn write_fixture<T: serde::Serialize>(
-    filename: &str,
-    value: &T,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let bytes = postcard::to_allocvec(value)?;
-    let path = Path::new("fixtures").join(filename);
-    fs::write(&path, &bytes)?;
-    println!("  {} ({} bytes)", filename, bytes.len());
-    Ok(())
-}
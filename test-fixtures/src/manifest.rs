@@ -0,0 +1,698 @@
+use crate::random::{random_bytes, Random};
+use crate::ring::LogRing;
+use crate::stream::Framing;
+use crc::Crc;
+use postcard::accumulator::{CobsAccumulator, FeedResult};
+use postcard_ts_error_codes::ErrorCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use serde_postcard_ts::crc::CRC_32_ISCSI;
+use serde_postcard_ts::{registered, GenerateError, Generator, PostcardTs, Shape, Validator};
+use std::collections::HashMap;
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+/// Largest integer a JavaScript number represents exactly (2^53 - 1)
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Writes `.bin` fixtures and records each one in `manifest.json`
+///
+/// Every entry holds the fixture file name, the Rust type name, the byte
+/// length, the bytes as hex and the value as serde_json sees it (or, for maps
+/// with tuple or struct keys, its entries). The TypeScript suite walks the
+/// manifest, so a fixture written here is tested without further edits (as
+/// long as a schema for its type is registered in `tests/fixtures/schemas.ts`).
+///
+/// Fixtures are byte-stable across runs: their maps are `BTreeMap`s, which
+/// postcard writes in key order, where a `HashMap` would write its entries in
+/// a different order each run.
+///
+/// Malformed inputs go to `error_cases.json` instead, each with the error code
+/// postcard reported for it. COBS-framed fixtures, malformed frames and
+/// streams of frames go to `cobs.json`, and values followed by a CRC to
+/// `crc.json`, since they are not plain postcard values. Streams of several
+/// messages back to back, length-prefixed or COBS-framed, go to
+/// `streams.json` with the messages in order. Hand-crafted maps
+/// with repeated keys go to `duplicate_keys.json` with the entries Rust kept.
+///
+/// Random instances of the registered types are ordinary manifest entries,
+/// with the seed that reproduces each one.
+pub struct FixtureWriter {
+    dir: PathBuf,
+    entries: Vec<Value>,
+    random_types: Vec<&'static str>,
+    error_cases: Vec<Value>,
+    cobs_entries: Vec<Value>,
+    cobs_error_cases: Vec<Value>,
+    cobs_streams: Vec<Value>,
+    message_streams: Vec<Value>,
+    crc_entries: Vec<Value>,
+    crc_error_cases: Vec<Value>,
+    duplicate_key_cases: Vec<Value>,
+}
+
+/// A `crc::Crc` for postcard's CRC flavor, with its name in the `crc` catalog
+pub struct NamedCrc<W: crc::Width> {
+    pub name: &'static str,
+    pub crc: Crc<W>,
+}
+
+/// postcard's CRC flavor, which has functions per CRC width
+pub trait CrcFlavor {
+    fn name(&self) -> &'static str;
+    fn serialize<T: Serialize>(&self, value: &T) -> postcard::Result<Vec<u8>>;
+    fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> postcard::Result<T>;
+}
+
+macro_rules! crc_flavor {
+    ($($width:ty => $to_allocvec:ident, $from_bytes:ident;)*) => {
+        $(
+            impl CrcFlavor for NamedCrc<$width> {
+                fn name(&self) -> &'static str {
+                    self.name
+                }
+
+                fn serialize<T: Serialize>(&self, value: &T) -> postcard::Result<Vec<u8>> {
+                    postcard::ser_flavors::crc::$to_allocvec(value, self.crc.digest())
+                }
+
+                fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> postcard::Result<T> {
+                    postcard::de_flavors::crc::$from_bytes(bytes, self.crc.digest())
+                }
+            }
+        )*
+    };
+}
+
+crc_flavor! {
+    u16 => to_allocvec_u16, from_bytes_u16;
+    u32 => to_allocvec_u32, from_bytes_u32;
+    u64 => to_allocvec_u64, from_bytes_u64;
+}
+
+impl FixtureWriter {
+    pub fn new(dir: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            random_types: Vec::new(),
+            error_cases: Vec::new(),
+            cobs_entries: Vec::new(),
+            cobs_error_cases: Vec::new(),
+            cobs_streams: Vec::new(),
+            message_streams: Vec::new(),
+            crc_entries: Vec::new(),
+            crc_error_cases: Vec::new(),
+            duplicate_key_cases: Vec::new(),
+        })
+    }
+
+    pub fn write<T: Serialize>(
+        &mut self,
+        filename: &str,
+        value: &T,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.entries.iter().any(|entry| entry["file"] == filename) {
+            return Err(format!("fixture {} written twice", filename).into());
+        }
+
+        let bytes = postcard::to_allocvec(value)?;
+        fs::write(self.dir.join(filename), &bytes)?;
+
+        self.entries.push(json!({
+            "file": filename,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "length": bytes.len(),
+            "hex": hex(&bytes),
+            "value": js_safe(serde_json::to_value(value)?),
+        }));
+
+        println!("  {} ({} bytes)", filename, bytes.len());
+        Ok(())
+    }
+
+    /// Write `count` random instances of `T` as `random_<type>_<n>.bin`
+    ///
+    /// Each is random bytes of `T`'s wire shape (see `random.rs`) decoded
+    /// with postcard, from a seed derived from `seed`, the type and `n` and
+    /// recorded in its manifest entry. The value is written back with
+    /// postcard, which differs from the random bytes only in map entries,
+    /// written back in key order and without repeated keys.
+    pub fn write_random<T>(
+        &mut self,
+        seed: u64,
+        count: u64,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        T: Serialize + DeserializeOwned + PostcardTs,
+    {
+        let Shape::Named(type_name) = T::shape() else {
+            return Err(format!("{} is not a registered type", std::any::type_name::<T>()).into());
+        };
+        let definitions = registered().into_iter().collect();
+        for n in 0..count {
+            let seed = fnv1a(type_name.name.as_bytes(), seed ^ n);
+            let bytes = random_bytes(&definitions, type_name, &mut Random::new(seed));
+            let value: T = postcard::from_bytes(&bytes).map_err(|error| {
+                format!(
+                    "random {} (seed {:#018x}) does not decode: {}; bytes {:02x?}",
+                    type_name.name, seed, error, bytes
+                )
+            })?;
+            let filename = format!("random_{}_{}.bin", snake_case(type_name.name), n);
+            self.write(&filename, &value)?;
+            if let Some(entry) = self.entries.last_mut() {
+                entry["seed"] = json!(format!("{:#018x}", seed));
+            }
+        }
+        self.random_types.push(type_name.name);
+        Ok(())
+    }
+
+    /// Write a map whose keys serde_json cannot represent (tuples, structs)
+    ///
+    /// The manifest entry holds `entries`, the `[key, value]` pairs in wire
+    /// order, instead of `value`.
+    pub fn write_map<M, K, V>(
+        &mut self,
+        filename: &str,
+        map: &M,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        M: Serialize,
+        for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+        K: Serialize + 'static,
+        V: Serialize + 'static,
+    {
+        if self.entries.iter().any(|entry| entry["file"] == filename) {
+            return Err(format!("fixture {} written twice", filename).into());
+        }
+
+        let bytes = postcard::to_allocvec(map)?;
+        fs::write(self.dir.join(filename), &bytes)?;
+
+        let entries: Vec<(&K, &V)> = map.into_iter().collect();
+        self.entries.push(json!({
+            "file": filename,
+            "type": short_type_name(std::any::type_name::<M>()),
+            "length": bytes.len(),
+            "hex": hex(&bytes),
+            "entries": js_safe(serde_json::to_value(entries)?),
+        }));
+
+        println!("  {} ({} bytes)", filename, bytes.len());
+        Ok(())
+    }
+
+    /// Write a value as a COBS frame (`postcard::to_allocvec_cobs`, with its
+    /// trailing 0x00 delimiter) and record it in `cobs.json`
+    pub fn write_cobs<T: Serialize>(
+        &mut self,
+        filename: &str,
+        value: &T,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let frame = postcard::to_allocvec_cobs(value)?;
+        fs::write(self.dir.join(filename), &frame)?;
+
+        self.cobs_entries.push(json!({
+            "file": filename,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "value": js_safe(serde_json::to_value(value)?),
+        }));
+
+        println!("  {} ({} bytes)", filename, frame.len());
+        Ok(())
+    }
+
+    /// Record a frame that must fail to decode as `T` with `postcard::from_bytes_cobs`
+    pub fn cobs_error_case<T: DeserializeOwned>(
+        &mut self,
+        name: &str,
+        frame: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let code = match postcard::from_bytes_cobs::<T>(&mut frame.to_vec()) {
+            Ok(_) => return Err(format!("COBS error case {} decoded successfully", name).into()),
+            Err(error) => ErrorCode::from(&error),
+        };
+
+        self.cobs_error_cases.push(json!({
+            "name": name,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "bytes": frame,
+            "code": code.as_str(),
+        }));
+
+        println!("  {} ({})", name, code);
+        Ok(())
+    }
+
+    /// Write COBS frames back to back as one stream, and record what postcard's
+    /// `CobsAccumulator` makes of it: each frame's value, or the code
+    /// `from_bytes_cobs` fails with for a frame it drops
+    ///
+    /// The stream is fed to the accumulator in 7-byte chunks, so frames
+    /// straddle chunk boundaries. Every frame must end with its only 0x00.
+    pub fn write_cobs_stream<T: Serialize + DeserializeOwned>(
+        &mut self,
+        filename: &str,
+        frames: &[Vec<u8>],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(frame) = frames
+            .iter()
+            .find(|frame| frame.iter().position(|&byte| byte == 0) != Some(frame.len() - 1))
+        {
+            return Err(format!("{:02x?} is not a single COBS frame", frame).into());
+        }
+        let stream = frames.concat();
+        fs::write(self.dir.join(filename), &stream)?;
+
+        let mut accumulator = CobsAccumulator::<4096>::new();
+        let mut results = Vec::new();
+        for chunk in stream.chunks(7) {
+            let mut window = chunk;
+            while !window.is_empty() {
+                window = match accumulator.feed::<T>(window) {
+                    FeedResult::Consumed => break,
+                    FeedResult::OverFull(_) => {
+                        return Err(format!("{}: frame too large for the accumulator", filename)
+                            .into())
+                    }
+                    FeedResult::DeserError(remaining) => {
+                        let frame = &frames[results.len()];
+                        let code = match postcard::from_bytes_cobs::<T>(&mut frame.clone()) {
+                            Ok(_) => {
+                                return Err(format!("{}: dropped {:02x?}", filename, frame).into())
+                            }
+                            Err(error) => ErrorCode::from(&error),
+                        };
+                        results.push(json!({ "code": code.as_str() }));
+                        remaining
+                    }
+                    FeedResult::Success { data, remaining } => {
+                        results.push(json!({ "value": js_safe(serde_json::to_value(&data)?) }));
+                        remaining
+                    }
+                };
+            }
+        }
+
+        self.cobs_streams.push(json!({
+            "file": filename,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "frames": results,
+        }));
+
+        println!("  {} ({} frames, {} bytes)", filename, frames.len(), stream.len());
+        Ok(())
+    }
+
+    /// Write `messages` back to back as `framing` delimits them, as a device
+    /// sends them on one link, and record them in `streams.json`
+    pub fn write_stream<T: Serialize + DeserializeOwned>(
+        &mut self,
+        filename: &str,
+        framing: Framing,
+        messages: &[T],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let stream = framing.stream(messages)?;
+        let expected = serde_json::to_value(messages)?;
+        if serde_json::to_value(framing.read::<T>(&stream)?)? != expected {
+            return Err(format!("{}: the stream does not read back", filename).into());
+        }
+        fs::write(self.dir.join(filename), &stream)?;
+
+        self.message_streams.push(json!({
+            "file": filename,
+            "framing": framing.as_str(),
+            "type": short_type_name(std::any::type_name::<T>()),
+            "length": stream.len(),
+            "messages": js_safe(expected),
+        }));
+
+        println!("  {} ({} messages, {} bytes)", filename, messages.len(), stream.len());
+        Ok(())
+    }
+
+    /// Write a value followed by its CRC (postcard's CRC flavor, e.g.
+    /// `ser_flavors::crc::to_allocvec_u32`) and record it in `crc.json`
+    pub fn write_crc<T: Serialize>(
+        &mut self,
+        filename: &str,
+        value: &T,
+        crc: &impl CrcFlavor,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bytes = crc.serialize(value)?;
+        fs::write(self.dir.join(filename), &bytes)?;
+
+        self.crc_entries.push(json!({
+            "file": filename,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "algorithm": crc.name(),
+            "value": js_safe(serde_json::to_value(value)?),
+        }));
+
+        println!("  {} ({}, {} bytes)", filename, crc.name(), bytes.len());
+        Ok(())
+    }
+
+    /// Record a message that must fail to decode as `T` with postcard's CRC
+    /// flavor (e.g. `de_flavors::crc::from_bytes_u32`)
+    pub fn crc_error_case<T: DeserializeOwned>(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        crc: &impl CrcFlavor,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let code = match crc.deserialize::<T>(bytes) {
+            Ok(_) => return Err(format!("CRC error case {} decoded successfully", name).into()),
+            Err(error) => ErrorCode::from(&error),
+        };
+
+        self.crc_error_cases.push(json!({
+            "name": name,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "algorithm": crc.name(),
+            "bytes": bytes,
+            "code": code.as_str(),
+        }));
+
+        println!("  {} ({})", name, code);
+        Ok(())
+    }
+
+    /// Write a raw ring buffer dump as `<name>.bin` and its expected contents as `<name>.json`
+    ///
+    /// Ring dumps are not a single postcard value, so they stay out of the manifest.
+    pub fn write_ring<T: Serialize>(
+        &mut self,
+        name: &str,
+        ring: &LogRing<T>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let bin = format!("{}.bin", name);
+        fs::write(self.dir.join(&bin), &ring.buffer)?;
+
+        let expected = json!({
+            "head": ring.head,
+            "wrapped": ring.wrapped,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "records": js_safe(serde_json::to_value(&ring.records)?),
+        });
+        let json_name = format!("{}.json", name);
+        fs::write(
+            self.dir.join(&json_name),
+            serde_json::to_string_pretty(&expected)? + "\n",
+        )?;
+
+        println!(
+            "  {} ({} bytes, {} records) + {}",
+            bin,
+            ring.buffer.len(),
+            ring.records.len(),
+            json_name
+        );
+        Ok(())
+    }
+
+    /// Record bytes that must fail to decode as `T`, with the code postcard reports
+    pub fn error_case<T: DeserializeOwned>(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let code = match postcard::from_bytes::<T>(bytes) {
+            Ok(_) => return Err(format!("error case {} decoded successfully", name).into()),
+            Err(error) => ErrorCode::from(&error),
+        };
+
+        self.error_cases.push(json!({
+            "name": name,
+            "type": short_type_name(std::any::type_name::<T>()),
+            "bytes": bytes,
+            "code": code.as_str(),
+        }));
+
+        println!("  {} ({})", name, code);
+        Ok(())
+    }
+
+    /// Record a hand-crafted map that repeats keys, with the entries left after
+    /// decoding it as a `HashMap<K, V>` (serde keeps the last value for a key),
+    /// sorted by key so the file is the same every run
+    pub fn duplicate_keys_case<K, V>(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        K: DeserializeOwned + Serialize + Eq + Hash,
+        V: DeserializeOwned + Serialize,
+    {
+        let map: HashMap<K, V> = postcard::from_bytes(bytes)?;
+        // As pairs, since serde_json only takes string-like map keys
+        let mut entries: Vec<Value> = map
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?;
+        entries.sort_by_key(|entry| entry[0].to_string());
+
+        self.duplicate_key_cases.push(json!({
+            "name": name,
+            "type": short_type_name(std::any::type_name::<HashMap<K, V>>()),
+            "bytes": bytes,
+            "entries": js_safe(serde_json::to_value(entries)?),
+        }));
+
+        println!("  {} ({} entries)", name, map.len());
+        Ok(())
+    }
+
+    /// Write the schema IR of every type deriving `PostcardTs` as `ir.json`
+    pub fn write_ir(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let ir = Generator::new().generate_ir()?;
+        fs::write(self.dir.join("ir.json"), ir)?;
+        println!("  ir.json ({} types)", registered().len());
+        Ok(())
+    }
+
+    /// Write the TypeScript schemas generated from every type deriving
+    /// `PostcardTs` as `generated.ts`, importing this checkout's runtime
+    ///
+    /// `tests/generated_ts.rs` decodes the fixtures with them and checks each
+    /// decoded value with the Zod validator generated alongside. The
+    /// generated module cannot declare recursive types yet, so those are left
+    /// out.
+    ///
+    /// `generated-options.ts` holds the same types generated with the
+    /// optional exports (tagged enums, CRC wrappers, variant checks, hashes,
+    /// sizes, salvage decoders, branded newtypes, `__schema` and display
+    /// names) for
+    /// `tests/tsc.rs` to type-check along with `generated.ts`.
+    pub fn write_generated_ts(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let runtime = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../src")
+            .canonicalize()?;
+        let dir = self.dir.canonicalize()?;
+        let generator = Generator::new()
+            .import_from(relative_import(&dir, &runtime))
+            .with_validators(Validator::Zod);
+
+        let (source, types) = generate_without_recursive(&generator)?;
+        fs::write(self.dir.join("generated.ts"), source)?;
+        println!("  generated.ts ({} types)", types);
+
+        let generator = generator
+            .enum_tag("kind")
+            .crc(CRC_32_ISCSI)
+            .variant_peeks()
+            .schema_hashes()
+            .max_sizes()
+            .salvage()
+            .decoders()
+            .branded_newtypes()
+            .introspection()
+            .display_names();
+        let (source, types) = generate_without_recursive(&generator)?;
+        fs::write(self.dir.join("generated-options.ts"), source)?;
+        println!("  generated-options.ts ({} types)", types);
+        Ok(())
+    }
+
+    /// Write `manifest.json`, `error_cases.json`, `cobs.json`, `crc.json` and
+    /// `duplicate_keys.json` next to the fixtures
+    ///
+    /// Fails if a registered type other than a generic one (which only has
+    /// random instances through the types applying it) got none.
+    pub fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        let missing: Vec<&str> = registered()
+            .into_iter()
+            .filter(|(type_name, _)| type_name.params.is_empty())
+            .map(|(type_name, _)| type_name.name)
+            .filter(|name| !self.random_types.contains(name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "no random fixtures of {}; add them to the write_random calls in main.rs",
+                missing.join(", ")
+            )
+            .into());
+        }
+
+        let manifest = json!({
+            "postcard": env!("POSTCARD_VERSION"),
+            "fixtures": self.entries,
+        });
+        let path = self.dir.join("manifest.json");
+        fs::write(&path, serde_json::to_string_pretty(&manifest)? + "\n")?;
+        println!("  manifest.json ({} fixtures)", self.entries.len());
+
+        let codes: Vec<&str> = ErrorCode::ALL.iter().map(|code| code.as_str()).collect();
+        let error_cases = json!({ "codes": codes, "cases": self.error_cases });
+        let path = self.dir.join("error_cases.json");
+        fs::write(&path, serde_json::to_string_pretty(&error_cases)? + "\n")?;
+        println!("  error_cases.json ({} cases)", self.error_cases.len());
+
+        let cobs = json!({
+            "fixtures": self.cobs_entries,
+            "cases": self.cobs_error_cases,
+            "streams": self.cobs_streams,
+        });
+        let path = self.dir.join("cobs.json");
+        fs::write(&path, serde_json::to_string_pretty(&cobs)? + "\n")?;
+        println!(
+            "  cobs.json ({} frames, {} cases, {} streams)",
+            self.cobs_entries.len(),
+            self.cobs_error_cases.len(),
+            self.cobs_streams.len()
+        );
+
+        let streams = json!({ "streams": self.message_streams });
+        let path = self.dir.join("streams.json");
+        fs::write(&path, serde_json::to_string_pretty(&streams)? + "\n")?;
+        println!("  streams.json ({} streams)", self.message_streams.len());
+
+        let crc = json!({
+            "fixtures": self.crc_entries,
+            "cases": self.crc_error_cases,
+        });
+        let path = self.dir.join("crc.json");
+        fs::write(&path, serde_json::to_string_pretty(&crc)? + "\n")?;
+        println!(
+            "  crc.json ({} messages, {} cases)",
+            self.crc_entries.len(),
+            self.crc_error_cases.len()
+        );
+
+        let duplicate_keys = json!({ "cases": self.duplicate_key_cases });
+        let path = self.dir.join("duplicate_keys.json");
+        fs::write(&path, serde_json::to_string_pretty(&duplicate_keys)? + "\n")?;
+        println!(
+            "  duplicate_keys.json ({} cases)",
+            self.duplicate_key_cases.len()
+        );
+        Ok(())
+    }
+}
+
+/// FNV-1a of `bytes`, starting from `basis` mixed into the offset basis
+fn fnv1a(bytes: &[u8], basis: u64) -> u64 {
+    bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325 ^ basis, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// `GameState` becomes `game_state`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// `[0x0a, 0xff]` becomes `0aff`
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Strip module paths: `alloc::vec::Vec<my_crate::types::Item>` becomes `Vec<Item>`
+fn short_type_name(full: &str) -> String {
+    let mut short = String::with_capacity(full.len());
+    let mut segment = String::new();
+
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            short.push_str(segment.rsplit("::").next().unwrap_or_default());
+            segment.clear();
+            short.push(c);
+        }
+    }
+    short.push_str(segment.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+/// An import specifier for `index.js` in `runtime_dir` from a module in `dir`
+fn relative_import(dir: &Path, runtime_dir: &Path) -> String {
+    let common = dir
+        .components()
+        .zip(runtime_dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut specifier = String::new();
+    for _ in dir.components().skip(common) {
+        specifier.push_str("../");
+    }
+    if specifier.is_empty() {
+        specifier.push_str("./");
+    }
+    for component in runtime_dir.components().skip(common) {
+        specifier.push_str(&component.as_os_str().to_string_lossy());
+        specifier.push('/');
+    }
+    specifier + "index.js"
+}
+
+/// The TypeScript `generator` writes for every type deriving `PostcardTs`
+/// but the recursive ones, and how many types it declares
+fn generate_without_recursive(generator: &Generator) -> Result<(String, usize), GenerateError> {
+    let mut types = registered();
+    loop {
+        match generator.generate_types(types.clone()) {
+            Err(GenerateError::Recursive(path)) => {
+                types.retain(|(type_name, _)| !path.contains(&type_name.name))
+            }
+            result => return Ok((result?, types.len())),
+        }
+    }
+}
+
+/// Replace integers JavaScript cannot represent exactly with decimal strings
+fn js_safe(value: Value) -> Value {
+    match value {
+        Value::Number(number) => {
+            let text = number.to_string();
+            let is_integer = !text.contains(['.', 'e', 'E']);
+            match text.parse::<f64>() {
+                Ok(parsed) if is_integer && parsed.abs() > MAX_SAFE_INTEGER => Value::String(text),
+                _ => Value::Number(number),
+            }
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(js_safe).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, js_safe(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
@@ -0,0 +1,430 @@
+//! Random postcard bytes for any type deriving `PostcardTs`
+//!
+//! Values are built from the registered wire shape rather than the Rust type,
+//! so every registered type gets random instances without implementing
+//! anything for it; `FixtureWriter::write_random` decodes the bytes with
+//! postcard to get the Rust value. Choices lean towards the boundaries curated
+//! fixtures tend to miss: the largest value of each varint length, integer
+//! minimums and maximums, subnormal and extreme floats, chars next to the
+//! surrogate range, and empty strings and collections.
+//!
+//! Floats are always finite, since serde_json writes NaN and infinities as
+//! `null`.
+
+use serde_postcard_ts::{Definition, Field, Shape, TypeName, Variant, VariantKind};
+use std::collections::HashMap;
+
+/// Options and collections below this depth are always empty, which bounds
+/// recursive types
+const MAX_DEPTH: usize = 4;
+
+/// Longest random string, collection or byte array
+const MAX_LEN: u64 = 6;
+
+const BOUNDARY_CHARS: [char; 11] = [
+    '\0',
+    '\u{7f}',
+    '\u{80}',
+    '\u{7ff}',
+    '\u{800}',
+    '\u{d7ff}',
+    '\u{e000}',
+    '\u{fffd}',
+    '\u{ffff}',
+    '\u{10000}',
+    '\u{10ffff}',
+];
+
+/// splitmix64: small and plenty for picking test inputs
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// True one time in `n`
+    fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    fn u128(&mut self) -> u128 {
+        (u128::from(self.next()) << 64) | u128::from(self.next())
+    }
+
+    /// A `bits`-wide unsigned integer; a third of them boundaries
+    fn unsigned(&mut self, bits: u32) -> u128 {
+        let max = u128::MAX >> (128 - bits);
+        if self.one_in(3) {
+            // 0, the maximum, and both sides of each varint length
+            let mut boundaries = vec![0, max];
+            for length in 1..bits.div_ceil(7) {
+                let longest = (1u128 << (7 * length)) - 1;
+                boundaries.extend([longest, longest + 1]);
+            }
+            return boundaries[self.below(boundaries.len() as u64) as usize];
+        }
+        // Spread over every varint length, not mostly the longest
+        let width = 1 + self.below(u64::from(bits)) as u32;
+        self.u128() & (u128::MAX >> (128 - width))
+    }
+
+    /// A `bits`-wide signed integer; a third of them boundaries
+    fn signed(&mut self, bits: u32) -> i128 {
+        let max = (u128::MAX >> (129 - bits)) as i128;
+        if self.one_in(3) {
+            return match self.below(5) {
+                0 => 0,
+                1 => -1,
+                2 => max,
+                3 => -max - 1,
+                _ => 1,
+            };
+        }
+        let magnitude = self.unsigned(bits - 1) as i128;
+        if self.one_in(2) {
+            -magnitude - 1
+        } else {
+            magnitude
+        }
+    }
+
+    fn f32(&mut self) -> f32 {
+        const BOUNDARIES: [f32; 10] = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            f32::EPSILON,
+            f32::MIN_POSITIVE,
+            f32::MAX,
+            f32::MIN,
+            // Smallest and largest subnormals
+            1e-45,
+            1.1754942e-38,
+        ];
+        if self.one_in(3) {
+            return BOUNDARIES[self.below(BOUNDARIES.len() as u64) as usize];
+        }
+        loop {
+            let value = f32::from_bits(self.next() as u32);
+            if value.is_finite() {
+                return value;
+            }
+        }
+    }
+
+    fn f64(&mut self) -> f64 {
+        const BOUNDARIES: [f64; 10] = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            f64::EPSILON,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+            // Smallest subnormal, and the largest integer JavaScript
+            // represents exactly
+            5e-324,
+            9007199254740991.0,
+        ];
+        if self.one_in(3) {
+            return BOUNDARIES[self.below(BOUNDARIES.len() as u64) as usize];
+        }
+        loop {
+            let value = f64::from_bits(self.next());
+            if value.is_finite() {
+                return value;
+            }
+        }
+    }
+
+    /// Mostly ASCII, sometimes a boundary of the UTF-8 lengths or the
+    /// surrogate range, sometimes any scalar value
+    fn char(&mut self) -> char {
+        match self.below(4) {
+            0 => BOUNDARY_CHARS[self.below(BOUNDARY_CHARS.len() as u64) as usize],
+            1 => loop {
+                if let Some(c) = char::from_u32(self.below(0x11_0000) as u32) {
+                    break c;
+                }
+            },
+            _ => char::from(b' ' + self.below(95) as u8),
+        }
+    }
+}
+
+/// Postcard bytes of a random value of the registered type `type_name`
+pub fn random_bytes(
+    definitions: &HashMap<TypeName, Definition>,
+    type_name: TypeName,
+    random: &mut Random,
+) -> Vec<u8> {
+    let mut writer = Writer {
+        definitions,
+        random,
+        out: Vec::new(),
+    };
+    writer.value(&Shape::Named(type_name), 0);
+    writer.out
+}
+
+struct Writer<'a> {
+    definitions: &'a HashMap<TypeName, Definition>,
+    random: &'a mut Random,
+    out: Vec<u8>,
+}
+
+impl Writer<'_> {
+    fn value(&mut self, shape: &Shape, depth: usize) {
+        match shape {
+            Shape::Bool => self.out.push(self.random.below(2) as u8),
+            Shape::U8 => self.out.push(self.random.unsigned(8) as u8),
+            Shape::I8 => self.out.push(self.random.signed(8) as u8),
+            Shape::U16 => varint(&mut self.out, self.random.unsigned(16)),
+            Shape::U32 => varint(&mut self.out, self.random.unsigned(32)),
+            Shape::U64 => varint(&mut self.out, self.random.unsigned(64)),
+            Shape::U128 => varint(&mut self.out, self.random.unsigned(128)),
+            Shape::I16 => zigzag(&mut self.out, self.random.signed(16)),
+            Shape::I32 => zigzag(&mut self.out, self.random.signed(32)),
+            Shape::I64 => zigzag(&mut self.out, self.random.signed(64)),
+            Shape::I128 => zigzag(&mut self.out, self.random.signed(128)),
+            Shape::F32 => {
+                let value = self.random.f32();
+                self.out.extend_from_slice(&value.to_le_bytes());
+            }
+            Shape::F64 => {
+                let value = self.random.f64();
+                self.out.extend_from_slice(&value.to_le_bytes());
+            }
+            Shape::Char => {
+                let mut buffer = [0; 4];
+                let encoded = self.random.char().encode_utf8(&mut buffer);
+                varint(&mut self.out, encoded.len() as u128);
+                self.out.extend_from_slice(encoded.as_bytes());
+            }
+            Shape::String => {
+                let len = self.len(depth);
+                let text: String = (0..len).map(|_| self.random.char()).collect();
+                varint(&mut self.out, text.len() as u128);
+                self.out.extend_from_slice(text.as_bytes());
+            }
+            Shape::BoundedString(max) => {
+                // Characters of several bytes may stop it short of the limit
+                let len = self.len(depth);
+                let mut text = String::new();
+                for _ in 0..len {
+                    let c = self.random.char();
+                    if text.len() + c.len_utf8() > *max {
+                        break;
+                    }
+                    text.push(c);
+                }
+                varint(&mut self.out, text.len() as u128);
+                self.out.extend_from_slice(text.as_bytes());
+            }
+            Shape::Bytes => {
+                let len = self.len(depth);
+                varint(&mut self.out, u128::from(len));
+                for _ in 0..len {
+                    self.out.push(self.random.next() as u8);
+                }
+            }
+            Shape::Unit => {}
+            Shape::Option(inner) => {
+                if depth >= MAX_DEPTH || self.random.one_in(2) {
+                    self.out.push(0);
+                } else {
+                    self.out.push(1);
+                    self.value(inner, depth + 1);
+                }
+            }
+            Shape::Seq(item) => {
+                let len = self.len(depth);
+                varint(&mut self.out, u128::from(len));
+                for _ in 0..len {
+                    self.value(item, depth + 1);
+                }
+            }
+            Shape::BoundedSeq(item, max) => {
+                let len = self.len(depth).min(*max as u64);
+                varint(&mut self.out, u128::from(len));
+                for _ in 0..len {
+                    self.value(item, depth + 1);
+                }
+            }
+            Shape::Map(key, value) => {
+                let len = self.len(depth);
+                varint(&mut self.out, u128::from(len));
+                for _ in 0..len {
+                    self.value(key, depth + 1);
+                    self.value(value, depth + 1);
+                }
+            }
+            Shape::BoundedMap(key, value, max) => {
+                let len = self.len(depth).min(*max as u64);
+                varint(&mut self.out, u128::from(len));
+                for _ in 0..len {
+                    self.value(key, depth + 1);
+                    self.value(value, depth + 1);
+                }
+            }
+            Shape::Tuple(items) => {
+                for item in items {
+                    self.value(item, depth);
+                }
+            }
+            Shape::Named(type_name) => {
+                let definition = self.definition(type_name).clone();
+                self.definition_value(&definition, depth);
+            }
+            Shape::Generic(type_name, args) => {
+                let bindings: HashMap<&str, &Shape> =
+                    type_name.params.iter().copied().zip(args).collect();
+                let definition = substitute_definition(self.definition(type_name), &bindings);
+                self.definition_value(&definition, depth);
+            }
+            Shape::Param(name) => panic!("unbound type parameter {}", name),
+        }
+    }
+
+    fn definition(&self, type_name: &TypeName) -> &Definition {
+        self.definitions
+            .get(type_name)
+            .unwrap_or_else(|| panic!("{} is not registered", type_name.name))
+    }
+
+    fn definition_value(&mut self, definition: &Definition, depth: usize) {
+        match definition {
+            Definition::Struct(fields) => {
+                for field in fields {
+                    self.value(&field.shape, depth);
+                }
+            }
+            Definition::TupleStruct(items) => {
+                for item in items {
+                    self.value(item, depth);
+                }
+            }
+            Definition::NewtypeStruct(inner) => self.value(inner, depth),
+            Definition::UnitStruct => {}
+            Definition::Enum(variants) => {
+                let index = self.random.below(variants.len() as u64);
+                varint(&mut self.out, u128::from(index));
+                match &variants[index as usize].kind {
+                    VariantKind::Unit => {}
+                    VariantKind::Newtype(inner) => self.value(inner, depth),
+                    VariantKind::Tuple(items) => {
+                        for item in items {
+                            self.value(item, depth);
+                        }
+                    }
+                    VariantKind::Struct(fields) => {
+                        for field in fields {
+                            self.value(&field.shape, depth);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Length of a string or collection: empty often, never below `MAX_DEPTH`
+    fn len(&mut self, depth: usize) -> u64 {
+        if depth >= MAX_DEPTH || self.random.one_in(4) {
+            0
+        } else {
+            1 + self.random.below(MAX_LEN)
+        }
+    }
+}
+
+fn varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(out: &mut Vec<u8>, value: i128) {
+    varint(out, ((value << 1) ^ (value >> 127)) as u128);
+}
+
+fn substitute_definition(definition: &Definition, bindings: &HashMap<&str, &Shape>) -> Definition {
+    let fields = |fields: &[Field]| {
+        fields
+            .iter()
+            .map(|field| Field {
+                name: field.name,
+                shape: substitute(&field.shape, bindings),
+            })
+            .collect()
+    };
+    let all = |items: &[Shape]| {
+        items
+            .iter()
+            .map(|item| substitute(item, bindings))
+            .collect()
+    };
+    match definition {
+        Definition::Struct(items) => Definition::Struct(fields(items)),
+        Definition::TupleStruct(items) => Definition::TupleStruct(all(items)),
+        Definition::NewtypeStruct(inner) => Definition::NewtypeStruct(substitute(inner, bindings)),
+        Definition::UnitStruct => Definition::UnitStruct,
+        Definition::Enum(variants) => Definition::Enum(
+            variants
+                .iter()
+                .map(|variant| Variant {
+                    name: variant.name,
+                    kind: match &variant.kind {
+                        VariantKind::Unit => VariantKind::Unit,
+                        VariantKind::Newtype(inner) => {
+                            VariantKind::Newtype(substitute(inner, bindings))
+                        }
+                        VariantKind::Tuple(items) => VariantKind::Tuple(all(items)),
+                        VariantKind::Struct(items) => VariantKind::Struct(fields(items)),
+                    },
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// `shape` with the type parameters in `bindings` replaced by their arguments
+fn substitute(shape: &Shape, bindings: &HashMap<&str, &Shape>) -> Shape {
+    let boxed = |inner: &Shape| Box::new(substitute(inner, bindings));
+    match shape {
+        Shape::Param(name) => bindings
+            .get(name)
+            .map_or_else(|| shape.clone(), |bound| (*bound).clone()),
+        Shape::Option(inner) => Shape::Option(boxed(inner)),
+        Shape::Seq(item) => Shape::Seq(boxed(item)),
+        Shape::Map(key, value) => Shape::Map(boxed(key), boxed(value)),
+        Shape::Tuple(items) => Shape::Tuple(
+            items
+                .iter()
+                .map(|item| substitute(item, bindings))
+                .collect(),
+        ),
+        Shape::Generic(type_name, args) => Shape::Generic(
+            *type_name,
+            args.iter().map(|arg| substitute(arg, bindings)).collect(),
+        ),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Fixed-size byte ring of length-prefixed postcard records, as firmware keeps logs
+///
+/// Each record is written as a varint length followed by its postcard bytes,
+/// wrapping byte by byte at the end of the buffer and overwriting the oldest
+/// data. `records` tracks which records are still fully intact.
+pub struct LogRing<T> {
+    pub buffer: Vec<u8>,
+    pub head: usize,
+    pub wrapped: bool,
+    pub records: VecDeque<T>,
+    lengths: VecDeque<usize>,
+}
+
+impl<T: Serialize> LogRing<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: vec![0; capacity],
+            head: 0,
+            wrapped: false,
+            records: VecDeque::new(),
+            lengths: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, record: T) -> Result<(), Box<dyn std::error::Error>> {
+        // A Vec<u8> encodes as varint(len) followed by the bytes
+        let framed = postcard::to_allocvec(&postcard::to_allocvec(&record)?)?;
+        if framed.len() > self.buffer.len() {
+            return Err("record does not fit in the ring".into());
+        }
+
+        for byte in &framed {
+            self.buffer[self.head] = *byte;
+            self.head += 1;
+            if self.head == self.buffer.len() {
+                self.head = 0;
+                self.wrapped = true;
+            }
+        }
+
+        self.records.push_back(record);
+        self.lengths.push_back(framed.len());
+        while self.lengths.iter().sum::<usize>() > self.buffer.len() {
+            self.records.pop_front();
+            self.lengths.pop_front();
+        }
+        Ok(())
+    }
+}
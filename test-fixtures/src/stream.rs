@@ -0,0 +1,55 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// How messages are delimited when sent back to back on one link
+#[derive(Clone, Copy)]
+pub enum Framing {
+    /// Each message preceded by its length as a varint, as postcard writes a
+    /// `Vec<u8>` of the message's bytes
+    LengthPrefix,
+    /// Each message a COBS frame ending in 0x00 (`to_allocvec_cobs`)
+    Cobs,
+}
+
+impl Framing {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Framing::LengthPrefix => "length-prefix",
+            Framing::Cobs => "cobs",
+        }
+    }
+
+    /// `messages` framed one after the other, as a device would send them
+    pub fn stream<T: Serialize>(self, messages: &[T]) -> postcard::Result<Vec<u8>> {
+        let mut stream = Vec::new();
+        for message in messages {
+            let frame = match self {
+                Framing::LengthPrefix => postcard::to_allocvec(&postcard::to_allocvec(message)?)?,
+                Framing::Cobs => postcard::to_allocvec_cobs(message)?,
+            };
+            stream.extend_from_slice(&frame);
+        }
+        Ok(stream)
+    }
+
+    /// The messages of `stream`, read back frame by frame as postcard does
+    pub fn read<T: DeserializeOwned>(self, stream: &[u8]) -> postcard::Result<Vec<T>> {
+        let mut messages = Vec::new();
+        let mut rest = stream.to_vec();
+        while !rest.is_empty() {
+            let (message, used) = match self {
+                Framing::LengthPrefix => {
+                    let (payload, tail) = postcard::take_from_bytes::<&[u8]>(&rest)?;
+                    (postcard::from_bytes(payload)?, rest.len() - tail.len())
+                }
+                Framing::Cobs => {
+                    let end = rest.iter().position(|&byte| byte == 0).map_or(rest.len(), |i| i + 1);
+                    (postcard::from_bytes_cobs(&mut rest[..end])?, end)
+                }
+            };
+            messages.push(message);
+            rest.drain(..used);
+        }
+        Ok(messages)
+    }
+}
@@ -1,8 +1,18 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use half::{bf16, f16};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_postcard_ts::PostcardTs;
+use std::collections::BTreeMap;
+use std::num::{NonZeroU16, NonZeroU64, Wrapping};
+use std::ops::{Range, RangeInclusive};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32};
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 /// Test all primitive integer types, floats, bool, char, and string
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Primitives {
     pub bool_field: bool,
     pub i8_field: i8,
@@ -22,7 +32,7 @@ pub struct Primitives {
 }
 
 /// Test collection types: Vec, arrays, tuples, Option
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Collections {
     pub vec_u8: Vec<u8>,
     pub vec_string: Vec<String>,
@@ -33,7 +43,8 @@ pub struct Collections {
 }
 
 /// Test all enum variant types from Serde data model
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum ComplexEnum {
     /// Unit variant - no data
     UnitVariant,
@@ -46,21 +57,21 @@ pub enum ComplexEnum {
 }
 
 /// Test nested structures and maps
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Nested {
     pub inner: InnerStruct,
-    pub map: HashMap<String, i32>,
+    pub map: BTreeMap<String, i32>,
     pub vec_of_structs: Vec<InnerStruct>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct InnerStruct {
     pub id: u64,
     pub name: String,
 }
 
 /// Test edge cases: empty collections, boundary values
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct EdgeCases {
     pub empty_vec: Vec<u8>,
     pub empty_string: String,
@@ -74,15 +85,15 @@ pub struct EdgeCases {
 }
 
 /// Newtype pattern test
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct NewtypeStruct(pub u64);
 
 /// Unit struct test
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct UnitStruct;
 
 /// Tuple struct test
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct TupleStruct(pub String, pub i32, pub bool);
 
 // ============================================================================
@@ -90,7 +101,7 @@ pub struct TupleStruct(pub String, pub i32, pub bool);
 // ============================================================================
 
 /// Complete game state - exercises deep nesting, enums in collections, optional complex types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct GameState {
     pub player: Player,
     pub enemies: Vec<Enemy>,
@@ -100,7 +111,7 @@ pub struct GameState {
 }
 
 /// Player with nested inventory and equipment
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Player {
     pub id: u64,
     pub name: String,
@@ -112,7 +123,7 @@ pub struct Player {
 }
 
 /// Player inventory containing items
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Inventory {
     pub items: Vec<Item>,
     pub capacity: u8,
@@ -120,7 +131,7 @@ pub struct Inventory {
 }
 
 /// 3D coordinates
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Coordinates {
     pub x: f64,
     pub y: f64,
@@ -128,7 +139,7 @@ pub struct Coordinates {
 }
 
 /// Enemy types - demonstrates all enum variant types in a Vec
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum Enemy {
     /// Struct variant
     Goblin { id: u32, aggro: bool },
@@ -145,14 +156,14 @@ pub enum Enemy {
 }
 
 /// Dragon-specific data
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct DragonData {
     pub color: DragonColor,
     pub age: u16,
 }
 
 /// Dragon color enum
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, PostcardTs)]
 pub enum DragonColor {
     Red,
     Blue,
@@ -160,7 +171,7 @@ pub enum DragonColor {
 }
 
 /// Item types in inventory
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum Item {
     Consumable { name: String, quantity: u16 },
     Weapon(Weapon),
@@ -168,7 +179,7 @@ pub enum Item {
 }
 
 /// Weapon data
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Weapon {
     pub name: String,
     pub damage: u16,
@@ -176,7 +187,7 @@ pub struct Weapon {
 }
 
 /// Elemental damage types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum Element {
     Fire,
     Ice,
@@ -184,15 +195,15 @@ pub enum Element {
 }
 
 /// Game world with locations map
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct World {
     pub name: String,
-    pub locations: HashMap<String, Location>,
+    pub locations: BTreeMap<String, Location>,
     pub boss: Option<BossInfo>,
 }
 
 /// Location information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct Location {
     pub description: String,
     pub coordinates: Coordinates,
@@ -200,14 +211,14 @@ pub struct Location {
 }
 
 /// Boss encounter information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct BossInfo {
     pub name: String,
     pub difficulty: u8,
 }
 
 /// Game events - nested enums
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum GameEvent {
     PlayerAction(PlayerAction),
     EnemySpawn { enemy_type: String, count: u16 },
@@ -216,7 +227,7 @@ pub enum GameEvent {
 }
 
 /// Player action types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum PlayerAction {
     Move { from: Coordinates, to: Coordinates },
     Attack { target_id: u32 },
@@ -224,7 +235,7 @@ pub enum PlayerAction {
 }
 
 /// Game metadata
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub struct GameMetadata {
     pub version: String,
     pub timestamp: u64,
@@ -232,9 +243,433 @@ pub struct GameMetadata {
 }
 
 /// Difficulty level
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
 pub enum Difficulty {
     Easy,
     Normal,
     Hard,
 }
+
+// ============================================================================
+// Enums Wrapping Maps of Enums
+// ============================================================================
+
+/// I/O channel mode - mixes unit and newtype variants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum Channel {
+    Analog(u16),
+    Digital(bool),
+    Off,
+}
+
+/// Channel layout - variants wrapping maps of sequences of enums
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum ChannelLayout {
+    Empty,
+    ByName(BTreeMap<String, Vec<Channel>>),
+    Grouped {
+        groups: BTreeMap<String, Vec<Channel>>,
+        fallback: Option<Channel>,
+    },
+    Layered(Vec<BTreeMap<String, Channel>>),
+}
+
+// ============================================================================
+// Ranges
+// ============================================================================
+
+/// Sensor calibration bounds using std::ops ranges
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub raw_window: Range<u16>,
+    pub temperature_c: RangeInclusive<f32>,
+    pub sample_ids: Range<i64>,
+}
+
+// ============================================================================
+// Wrapping and Atomic Integers
+// ============================================================================
+
+/// Shared-state snapshot - Wrapping<T> and atomics serialize as their value
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedCounters {
+    pub sequence: Wrapping<u32>,
+    pub rx_packets: AtomicU32,
+    pub drift_us: AtomicI64,
+    pub link_up: AtomicBool,
+    pub checksum: Wrapping<u8>,
+}
+
+// ============================================================================
+// Telemetry - Ring Buffer Log Records
+// ============================================================================
+
+/// Log record as stored in an on-device ring buffer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct LogRecord {
+    pub timestamp_ms: u32,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Log severity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Capture file of raw frames, as exported by the TypeScript flight recorder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct Capture {
+    pub version: u32,
+    pub frames: Vec<CapturedFrame>,
+}
+
+/// One raw frame and the time it was received (ms since the Unix epoch)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct CapturedFrame {
+    pub time_ms: u64,
+    pub data: Vec<u8>,
+}
+
+// ============================================================================
+// Bit-Packed Flags
+// ============================================================================
+
+/// Status booleans packed into a single u8 by a hand-written Serialize impl
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusFlags {
+    pub heater: bool,
+    pub pump: bool,
+    pub valve_open: bool,
+    pub alarm: bool,
+}
+
+impl StatusFlags {
+    const HEATER: u8 = 1 << 0;
+    const PUMP: u8 = 1 << 1;
+    const VALVE_OPEN: u8 = 1 << 2;
+    const ALARM: u8 = 1 << 7;
+}
+
+impl Serialize for StatusFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bits = 0u8;
+        if self.heater {
+            bits |= Self::HEATER;
+        }
+        if self.pump {
+            bits |= Self::PUMP;
+        }
+        if self.valve_open {
+            bits |= Self::VALVE_OPEN;
+        }
+        if self.alarm {
+            bits |= Self::ALARM;
+        }
+        serializer.serialize_u8(bits)
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u8::deserialize(deserializer)?;
+        Ok(Self {
+            heater: bits & Self::HEATER != 0,
+            pump: bits & Self::PUMP != 0,
+            valve_open: bits & Self::VALVE_OPEN != 0,
+            alarm: bits & Self::ALARM != 0,
+        })
+    }
+}
+
+/// Device status report carrying packed flags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceStatus {
+    pub flags: StatusFlags,
+    pub uptime_s: u32,
+}
+
+// ============================================================================
+// Half-Precision Floats
+// ============================================================================
+
+/// Sensor fusion sample using f16/bf16 to halve bandwidth
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FusionSample {
+    pub orientation: [f16; 4],
+    pub accel: [bf16; 3],
+    pub temperature: f16,
+    pub peak: f16,
+}
+
+// ============================================================================
+// Decimal Types
+// ============================================================================
+
+/// Money amounts as `rust_decimal::Decimal`, which serializes as a string
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub amount: Decimal,
+    pub refund: Decimal,
+    pub fx_rate: Decimal,
+    pub balance: Decimal,
+    pub limit: Decimal,
+}
+
+// ============================================================================
+// Date and Time Types
+// ============================================================================
+
+/// A chrono instant (RFC 3339 text), a time instant (a tuple of its fields)
+/// and a std duration (`secs` and `nanos`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub created: DateTime<Utc>,
+    pub starts: OffsetDateTime,
+    pub interval: Duration,
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// Ids
+// ============================================================================
+
+/// A uuid (its 16 bytes as a byte string) and NonZero integers (their values)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub id: Uuid,
+    pub parent: Option<Uuid>,
+    pub port: NonZeroU16,
+    pub sequence: NonZeroU64,
+}
+
+// ============================================================================
+// Identifier Enums
+// ============================================================================
+
+/// Unit-only enum used as a map key and field selector
+///
+/// Receivers often mark their copy `#[serde(field_identifier)]`, but postcard
+/// cannot deserialize identifier enums, so the wire type is a plain enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, PostcardTs)]
+pub enum SensorField {
+    Temperature,
+    Humidity,
+    Pressure,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct SensorSelection {
+    pub primary: SensorField,
+    pub enabled: BTreeMap<SensorField, bool>,
+    pub order: Vec<SensorField>,
+}
+
+// ============================================================================
+// Recursive Types
+// ============================================================================
+
+/// Binary tree node with boxed, optional children
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct TreeNode {
+    pub label: String,
+    pub value: u32,
+    pub left: Option<Box<TreeNode>>,
+    pub right: Option<Box<TreeNode>>,
+}
+
+impl TreeNode {
+    pub fn leaf(label: &str, value: u32) -> Self {
+        Self {
+            label: label.to_string(),
+            value,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+// ============================================================================
+// Uninhabited Types
+// ============================================================================
+
+/// Empty enum standing in for `std::convert::Infallible`, which serde does
+/// not implement its traits for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Never {}
+
+/// Replies from an operation that cannot fail; the Err variant never occurs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InfallibleReplies {
+    pub replies: Vec<Result<u32, Never>>,
+}
+
+// ============================================================================
+// Zero-Length and Single-Variant Messages
+// ============================================================================
+
+/// Struct with no fields; encodes to zero bytes like `()` and unit structs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct EmptyStruct {}
+
+/// Enum with a single unit variant; still encodes its discriminant (0x00)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum SingleVariant {
+    Only,
+}
+
+/// Enum whose only variant carries data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum SingleDataVariant {
+    Reading(u16),
+}
+
+// ============================================================================
+// Renamed Fields and Variants
+// ============================================================================
+
+/// Settings using serde's renames; the wire format is positional, so only the
+/// names in serde_json (and the TypeScript objects) change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSettings {
+    pub sample_rate_hz: u32,
+    #[serde(rename = "id")]
+    pub device_id: u16,
+    pub mode: DeviceMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceMode {
+    LowPower,
+    #[serde(rename_all = "kebab-case")]
+    Streaming { batch_size: u8 },
+    #[serde(rename = "off")]
+    Disabled,
+}
+
+// ============================================================================
+// Skipped and Defaulted Fields
+// ============================================================================
+
+/// `cache` is never written or read; `retries` is always written, since
+/// postcard reads every field and `default` never applies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct Session {
+    pub id: u32,
+    #[serde(skip)]
+    pub cache: Vec<u8>,
+    #[serde(default)]
+    pub retries: u8,
+}
+
+// ============================================================================
+// Length-Limited Strings
+// ============================================================================
+
+/// Strings the device keeps in `heapless::String<N>`; the limits are not on
+/// the wire, but the generated schemas check them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct DeviceLabel {
+    #[postcard_ts(max_len = 16)]
+    pub name: String,
+    #[postcard_ts(max_len = 4)]
+    pub unit: Option<String>,
+}
+
+// ============================================================================
+// Non-String Map Keys
+// ============================================================================
+
+/// Maps keyed by integers and enums, which decode to JS `Map`s keyed by
+/// numbers and `{ type }` objects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct KeyedMaps {
+    pub by_id: BTreeMap<u32, String>,
+    pub by_color: BTreeMap<DragonColor, u16>,
+}
+
+/// Grid cells keyed by `(row, column)`; serde_json cannot write tuple keys, so
+/// the manifest records its entries as pairs
+pub type CellMap = BTreeMap<(u8, u8), DragonColor>;
+
+/// A sensor's bus address; newtype structs are transparent, so as a key it
+/// decodes to a plain number
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, PostcardTs,
+)]
+pub struct SensorId(pub u16);
+
+/// Maps keyed by the other primitive key types: 64-bit integers (bigint keys
+/// in TypeScript), signed integers, bools, chars and newtype structs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct WideKeys {
+    pub by_serial: BTreeMap<u64, String>,
+    pub by_offset: BTreeMap<i16, u8>,
+    pub by_flag: BTreeMap<bool, u8>,
+    pub by_letter: BTreeMap<char, u16>,
+    pub by_sensor: BTreeMap<SensorId, f32>,
+}
+
+/// Slots keyed by `Option<u8>`, `None` first; serde_json cannot write a
+/// `None` key, so the manifest records its entries as pairs
+pub type SlotMap = BTreeMap<Option<u8>, String>;
+
+// ============================================================================
+// Optional and Empty Lists
+// ============================================================================
+
+/// The three list shapes that are easily confused: `None` (`00`) and
+/// `Some(vec![])` (`01 00`) are different values, a list with gaps holds an
+/// option per item, and an empty plain list is `00` like `None`. Mixing the
+/// first and last is what the generator's `optional_seq` lint warns about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct ListShapes {
+    pub maybe_items: Option<Vec<u16>>,
+    pub sparse_items: Vec<Option<u16>>,
+    pub items: Vec<u16>,
+}
+
+// ============================================================================
+// Byte Strings
+// ============================================================================
+
+/// Byte buffers written with serde_bytes, which decode to `Uint8Array`s; on
+/// the wire they are the same as a plain `Vec<u8>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct FirmwareChunk {
+    pub offset: u32,
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>,
+    pub signature: Option<serde_bytes::ByteBuf>,
+}
+
+// ============================================================================
+// Generic Types
+// ============================================================================
+
+/// A sequence-numbered wrapper around any payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct Envelope<T> {
+    pub seq: u32,
+    pub payload: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub enum Reply<T> {
+    Done(T),
+    Busy,
+    Failed { code: u16, partial: Option<T> },
+}
+
+/// Applies the generic types, so they are also covered through the IR
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, PostcardTs)]
+pub struct Exchange {
+    pub request: Envelope<String>,
+    pub replies: Vec<Envelope<Reply<Coordinates>>>,
+}
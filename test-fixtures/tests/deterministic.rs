@@ -0,0 +1,51 @@
+//! The fixture generator writes the same bytes every run, so regenerated
+//! fixtures can be diffed, and records each fixture's bytes in
+//! `manifest.json`
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+fn generate(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let status = Command::new(env!("CARGO_BIN_EXE_postcard-test-fixtures"))
+        .arg(&dir)
+        .stdout(Stdio::null())
+        .status()
+        .expect("running the fixture generator");
+    assert!(status.success(), "the fixture generator failed");
+    dir
+}
+
+#[test]
+fn writes_the_same_fixtures_every_run() {
+    let first = generate("deterministic-1");
+    let second = generate("deterministic-2");
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&first)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    assert!(files.len() > 1);
+    for file in files {
+        let name = file.file_name().unwrap();
+        assert!(
+            fs::read(&file).unwrap() == fs::read(second.join(name)).unwrap(),
+            "{} differs between runs",
+            name.to_string_lossy()
+        );
+    }
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(first.join("manifest.json")).unwrap()).unwrap();
+    for entry in manifest["fixtures"].as_array().unwrap() {
+        let file = entry["file"].as_str().unwrap();
+        let bytes = fs::read(first.join(file)).unwrap();
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(entry["length"], bytes.len(), "{}", file);
+        assert_eq!(entry["hex"], hex, "{}", file);
+    }
+}
@@ -0,0 +1,135 @@
+//! Cross-language conformance of the generated TypeScript
+//!
+//! Writes the fixtures and the schemas generated from their types
+//! (`generated.ts`) to a scratch directory, decodes every fixture with those
+//! schemas in TypeScript (`tests/conformance/generated-runner.ts`), checks
+//! each decoded value with the Zod validator generated for its type and
+//! compares it, as JSON, with the one recorded in `manifest.json`.
+//! Failures name the fixture and its type.
+//!
+//! ```text
+//! npm install
+//! cd test-fixtures && cargo test --features ts-conformance
+//! ```
+//!
+//! The runner runs under Node through vite-node (from the npm dev
+//! dependencies, as is `zod`), or under deno with `POSTCARD_TS_RUNNER=deno`.
+#![cfg(feature = "ts-conformance")]
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const RUNNER: &str = "tests/conformance/generated-runner.ts";
+
+fn runner(root: &Path, dir: &Path) -> Command {
+    let mut command = match env::var("POSTCARD_TS_RUNNER").as_deref() {
+        Ok("deno") => {
+            let mut command = Command::new("deno");
+            command.args([
+                "run",
+                "--allow-read",
+                "--allow-env",
+                "--unstable-sloppy-imports",
+            ]);
+            command
+        }
+        Ok("node") | Err(_) => Command::new(root.join("node_modules/.bin/vite-node")),
+        Ok(other) => panic!("unknown POSTCARD_TS_RUNNER {}; use node or deno", other),
+    };
+    command.arg(RUNNER).arg(dir).current_dir(root);
+    command
+}
+
+/// JSON values as equal as the two languages write them: numbers by value,
+/// since JavaScript and serde_json spell some floats differently
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+#[test]
+fn generated_typescript_decodes_every_fixture() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated-ts");
+    let status = Command::new(env!("CARGO_BIN_EXE_postcard-test-fixtures"))
+        .arg(&dir)
+        .stdout(Stdio::null())
+        .status()
+        .expect("running the fixture generator");
+    assert!(status.success(), "the fixture generator failed");
+
+    let output = runner(&root, &dir)
+        .output()
+        .expect("running the TypeScript runner; install the npm dev dependencies first");
+    assert!(
+        output.status.success(),
+        "the TypeScript runner failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let decoded: HashMap<String, Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|result| Some((result["file"].as_str()?.to_string(), result)))
+        .collect();
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("manifest.json")).unwrap()).unwrap();
+    let mut checked = 0;
+    let mut skipped = Vec::new();
+    let mut failures = Vec::new();
+    for entry in manifest["fixtures"].as_array().unwrap() {
+        let file = entry["file"].as_str().unwrap();
+        let name = format!("{} ({})", file, entry["type"].as_str().unwrap());
+        let Some(result) = decoded.get(file) else {
+            failures.push(format!("{}: the runner did not report it", name));
+            continue;
+        };
+        if result.get("skipped").is_some() {
+            skipped.push(name);
+            continue;
+        }
+        if let Some(error) = result.get("error") {
+            failures.push(format!("{}: {}", name, error));
+            continue;
+        }
+        checked += 1;
+        for key in ["value", "entries"] {
+            if let Some(expected) = entry.get(key) {
+                if !same(expected, &result[key]) {
+                    failures.push(format!(
+                        "{}: expected {}\n    decoded  {}",
+                        name, expected, result[key]
+                    ));
+                }
+            }
+        }
+    }
+
+    println!(
+        "{} fixtures decoded with the generated schemas, {} skipped without one: {}",
+        checked,
+        skipped.len(),
+        skipped.join(", ")
+    );
+    assert!(
+        failures.is_empty(),
+        "{} fixtures failed:\n  {}",
+        failures.len(),
+        failures.join("\n  ")
+    );
+    assert!(checked > 0, "no fixture has a generated schema");
+}
@@ -0,0 +1,65 @@
+//! Type-checking the generated TypeScript
+//!
+//! Writes the schemas generated from the fixture types (`generated.ts`, and
+//! `generated-options.ts` with every optional export) to a scratch directory
+//! and runs `tsc --strict --noEmit` on them with the runtime's own compiler
+//! options, so that the generator emitting TypeScript that does not compile
+//! fails `cargo test` rather than a user's build.
+//!
+//! ```text
+//! npm install
+//! cd test-fixtures && cargo test --features tsc
+//! ```
+//!
+//! tsc comes from the npm dev dependencies, as does `zod`; without them (or
+//! without Node) the test is skipped.
+#![cfg(feature = "tsc")]
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[test]
+fn generated_typescript_compiles() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .canonicalize()
+        .unwrap();
+    let tsc = root.join("node_modules/.bin/tsc");
+    if !tsc.exists() {
+        eprintln!("skipped: {} not found; run npm install", tsc.display());
+        return;
+    }
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("tsc");
+    let status = Command::new(env!("CARGO_BIN_EXE_postcard-test-fixtures"))
+        .arg(&dir)
+        .stdout(Stdio::null())
+        .status()
+        .expect("running the fixture generator");
+    assert!(status.success(), "the fixture generator failed");
+
+    // The runtime's compiler options, for the generated files alone
+    let tsconfig = format!(
+        "{{\n  \"extends\": {:?},\n  \"include\": [\"generated.ts\", \"generated-options.ts\"]\n}}\n",
+        root.join("tsconfig.json").display().to_string()
+    );
+    fs::write(dir.join("tsconfig.json"), tsconfig).unwrap();
+
+    let output = match Command::new(&tsc)
+        .args(["--project", ".", "--strict", "--noEmit"])
+        .current_dir(&dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("skipped: running {}: {}", tsc.display(), e);
+            return;
+        }
+    };
+    assert!(
+        output.status.success(),
+        "the generated TypeScript does not compile:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
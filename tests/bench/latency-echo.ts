@@ -0,0 +1,49 @@
+/**
+ * Echo latency probes for `postcard-ts-bench latency`
+ *
+ * Run by the `bench` crate, which plays the device on the other end:
+ *
+ *   npx vite-node tests/bench/latency-echo.ts websocket ws://127.0.0.1:7420
+ *   npx vite-node tests/bench/latency-echo.ts serial /dev/pts/5
+ *
+ * Connects to the bench's WebSocket server, or opens the given end of a
+ * serial line (set up raw beforehand, as by socat), and echoes every probe
+ * with the schemas of tests/fixtures/schemas.ts until the bench closes the
+ * link or the process is killed. POSTCARD_RUNTIME names a built runtime as for
+ * throughput-runner.ts; the sources are measured by default.
+ */
+
+import { createReadStream, createWriteStream } from "node:fs";
+import { resolve } from "node:path";
+import process from "node:process";
+import { Readable, Writable } from "node:stream";
+import { pathToFileURL } from "node:url";
+import { FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+
+type Runtime = typeof import("../../src/index.js");
+
+const [mode, target] = process.argv.slice(2);
+if (target === undefined || (mode !== "websocket" && mode !== "serial")) {
+  throw new Error("Usage: latency-echo.ts websocket <url> | serial <device>");
+}
+
+const RUNTIME_DIR = process.env["POSTCARD_RUNTIME"];
+const runtime = (
+  RUNTIME_DIR === undefined
+    ? await import("../../src/index.js")
+    : await import(pathToFileURL(resolve(RUNTIME_DIR, "index.js")).href)
+) as Runtime;
+
+let link: InstanceType<Runtime["WebSocketTransport"] | Runtime["SerialTransport"]>;
+if (mode === "websocket") {
+  link = new runtime.WebSocketTransport(new WebSocket(target));
+} else {
+  link = new runtime.SerialTransport({
+    readable: Readable.toWeb(createReadStream(target)) as ReadableStream<Uint8Array>,
+    writable: Writable.toWeb(createWriteStream(target, { flags: "r+" })),
+  });
+}
+
+const echoed = await runtime.echoLatencyProbes(link, FIXTURE_SCHEMAS);
+process.stderr.write(`echoed ${echoed} probes\n`);
+process.exit(0);
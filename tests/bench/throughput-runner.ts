@@ -0,0 +1,88 @@
+/**
+ * Measure encode and decode throughput over the fixture corpus
+ *
+ * Run by the `bench` crate (`cargo run --release` in bench/), once per
+ * runtime to compare:
+ *
+ *   npx vite-node tests/bench/throughput-runner.ts <fixtures dir> <millis>
+ *
+ * POSTCARD_RUNTIME names a built runtime (a directory holding its index.js,
+ * e.g. a build of an earlier release) as for the conformance suite; the
+ * sources are measured by default. Each fixture with a schema in
+ * tests/fixtures/schemas.ts is decoded, then encoded again, as often as fits
+ * in <millis> milliseconds each. Prints one JSON line per fixture:
+ * - { file, type, bytes, decode, encode, encodeInto } in operations per
+ *   second; encodeInto (a reused `Writer`) is null for runtimes without it
+ * - { file, type, skipped } for fixtures without a schema
+ */
+
+import { readFileSync } from "node:fs";
+import { join, resolve } from "node:path";
+import process from "node:process";
+import { pathToFileURL } from "node:url";
+import { FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+
+type Runtime = typeof import("../../src/index.js");
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+}
+
+const [dir, millisArg = "200"] = process.argv.slice(2);
+if (dir === undefined) {
+  throw new Error("Usage: throughput-runner.ts <fixtures dir> [millis]");
+}
+const millis = Number(millisArg);
+
+const RUNTIME_DIR = process.env["POSTCARD_RUNTIME"];
+const runtime = (
+  RUNTIME_DIR === undefined
+    ? await import("../../src/index.js")
+    : await import(pathToFileURL(resolve(RUNTIME_DIR, "index.js")).href)
+) as Partial<Runtime> & Pick<Runtime, "serialize" | "deserialize">;
+
+/** Operations per second of `run`, measured for about `millis` milliseconds */
+function rate(run: () => void): number {
+  // Warm up, then time batches until the budget is spent
+  for (let i = 0; i < 10; i++) run();
+  let operations = 0;
+  let batch = 1;
+  const start = performance.now();
+  let elapsed = 0;
+  while (elapsed < millis) {
+    for (let i = 0; i < batch; i++) run();
+    operations += batch;
+    batch *= 2;
+    elapsed = performance.now() - start;
+  }
+  return (operations * 1000) / elapsed;
+}
+
+const { fixtures } = JSON.parse(readFileSync(join(dir, "manifest.json"), "utf8")) as {
+  fixtures: ManifestEntry[];
+};
+
+for (const entry of fixtures) {
+  const schema = FIXTURE_SCHEMAS[entry.type];
+  if (schema === undefined) {
+    process.stdout.write(`${JSON.stringify({ ...entry, skipped: "no schema" })}\n`);
+    continue;
+  }
+  const data = new Uint8Array(readFileSync(join(dir, entry.file)));
+  const { value } = runtime.deserialize(schema, data);
+
+  const decode = rate(() => runtime.deserialize(schema, data));
+  const encode = rate(() => runtime.serialize(schema, value));
+  let encodeInto: number | null = null;
+  const { Writer, serializeInto } = runtime;
+  if (Writer !== undefined && serializeInto !== undefined) {
+    const writer = new Writer();
+    encodeInto = rate(() => {
+      writer.reset();
+      serializeInto(schema, value, writer);
+    });
+  }
+  const result = { ...entry, bytes: data.length, decode, encode, encodeInto };
+  process.stdout.write(`${JSON.stringify(result)}\n`);
+}
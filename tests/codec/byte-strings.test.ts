@@ -0,0 +1,68 @@
+/**
+ * Tests for bytes() (serde_bytes, or Vec<u8> with u8_vecs_as_bytes) and
+ * zero-copy byte views
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import { bytes, deserialize, serialize, seq, struct, u8, u32 } from "../../src/index.js";
+import { FirmwareChunkSchema } from "../fixtures/schemas.js";
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+const ChunkSchema = struct({ offset: u32(), data: bytes() });
+
+describe("bytes()", () => {
+  it("should encode like a sequence of u8", () => {
+    const data = Array.from({ length: 200 }, (_, i) => i);
+    expect(serialize(ChunkSchema, { offset: 1, data: new Uint8Array(data) })).toEqual(
+      serialize(struct({ offset: u32(), data: seq(u8()) }), { offset: 1, data })
+    );
+  });
+
+  it("should decode to a copy by default", () => {
+    const data = serialize(ChunkSchema, { offset: 1, data: new Uint8Array([1, 2, 3]) });
+    const { value } = deserialize(ChunkSchema, data);
+
+    expect(value.data).toBeInstanceOf(Uint8Array);
+    expect(value.data.buffer).not.toBe(data.buffer);
+    data[2] = 9;
+    expect(value.data).toEqual(new Uint8Array([1, 2, 3]));
+  });
+
+  it("should decode to views into the input with byteViews", () => {
+    const data = serialize(ChunkSchema, { offset: 1, data: new Uint8Array([1, 2, 3]) });
+    const { value } = deserialize(ChunkSchema, data, 0, { byteViews: true });
+
+    expect(value.data.buffer).toBe(data.buffer);
+    expect(value.data.byteOffset).toBe(data.byteOffset + 2);
+    data[2] = 9;
+    expect(value.data).toEqual(new Uint8Array([9, 2, 3]));
+  });
+
+  it("should keep views within a frame at an offset", () => {
+    const frame = serialize(ChunkSchema, { offset: 300, data: new Uint8Array([4, 5]) });
+    const buffer = new Uint8Array(frame.length + 3);
+    buffer.set(frame, 3);
+    const { value } = deserialize(ChunkSchema, buffer.subarray(3), 0, { byteViews: true });
+    expect(value).toEqual({ offset: 300, data: new Uint8Array([4, 5]) });
+    expect(value.data.byteOffset).toBe(6);
+  });
+});
+
+describe.runIf(existsSync(join(FIXTURES_DIR, "firmware_chunk.bin")))(
+  "Byte strings written by Rust",
+  () => {
+    it("should decode serde_bytes fields to Uint8Array", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "firmware_chunk.bin")));
+      const { value } = deserialize(FirmwareChunkSchema, data);
+
+      expect(value.offset).toBe(4096);
+      expect(value.data).toHaveLength(200);
+      expect(value.data[199]).toBe((199 * 7) % 256);
+      expect(value.signature).toEqual(new Uint8Array([0x00, 0xff]));
+      expect(serialize(FirmwareChunkSchema, value)).toEqual(data);
+    });
+  }
+);
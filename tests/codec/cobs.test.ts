@@ -0,0 +1,253 @@
+/**
+ * Tests for COBS framing
+ *
+ * The Rust generator writes COBS frames with postcard's `to_allocvec_cobs`,
+ * records malformed frames with the code `from_bytes_cobs` reported, and
+ * writes streams of frames with what postcard's `CobsAccumulator` made of
+ * them, all in cobs.json; each frame must decode to the recorded value and
+ * re-encode to the same bytes, and each stream must split into the same
+ * frames however it is chunked.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  cobsEncode,
+  cobsDecode,
+  tryCobsDecode,
+  serializeCobs,
+  deserializeCobs,
+  tryDeserializeCobs,
+  CobsAccumulator,
+  DeserializeError,
+  ErrorCode,
+  struct,
+  unit,
+  u8,
+  u32,
+  seq,
+  string,
+  type Schema,
+} from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface CobsFixtures {
+  readonly fixtures: { readonly file: string; readonly type: string; readonly value: unknown }[];
+  readonly cases: {
+    readonly name: string;
+    readonly type: string;
+    readonly bytes: number[];
+    readonly code: string;
+  }[];
+  readonly streams: {
+    readonly file: string;
+    readonly type: string;
+    readonly frames: ({ readonly value: unknown } | { readonly code: string })[];
+  }[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const COBS_PATH = join(FIXTURES_DIR, "cobs.json");
+
+const recorded: CobsFixtures = existsSync(COBS_PATH)
+  ? (JSON.parse(readFileSync(COBS_PATH, "utf8")) as CobsFixtures)
+  : { fixtures: [], cases: [], streams: [] };
+
+const bytes = (...values: number[]): Uint8Array => new Uint8Array(values);
+
+describe("cobsEncode / cobsDecode", () => {
+  it("should frame a zero-length payload as 01 00", () => {
+    expect(cobsEncode(bytes())).toEqual(bytes(0x01, 0x00));
+    expect(cobsDecode(bytes(0x01, 0x00))).toEqual({ value: bytes(), bytesRead: 2 });
+  });
+
+  it("should decode a bare delimiter as an empty payload, like postcard", () => {
+    expect(cobsDecode(bytes(0x00))).toEqual({ value: bytes(), bytesRead: 1 });
+  });
+
+  it("should stuff zeros", () => {
+    expect(cobsEncode(bytes(0x00))).toEqual(bytes(0x01, 0x01, 0x00));
+    expect(cobsEncode(bytes(0x11, 0x00, 0x00, 0x22))).toEqual(
+      bytes(0x02, 0x11, 0x01, 0x02, 0x22, 0x00)
+    );
+    expect(cobsDecode(bytes(0x02, 0x11, 0x01, 0x02, 0x22, 0x00)).value).toEqual(
+      bytes(0x11, 0x00, 0x00, 0x22)
+    );
+  });
+
+  it("should split runs of 254 non-zero bytes into full blocks", () => {
+    for (const length of [253, 254, 255, 508, 1000]) {
+      const payload = new Uint8Array(length).map((_, i) => (i % 255) + 1);
+      const frame = cobsEncode(payload);
+      expect(frame.indexOf(0x00)).toBe(frame.length - 1);
+      expect(cobsDecode(frame)).toEqual({ value: payload, bytesRead: frame.length });
+    }
+  });
+
+  it("should round-trip arbitrary payloads", () => {
+    for (let seed = 0; seed < 50; seed++) {
+      const payload = new Uint8Array(seed * 13).map((_, i) => (i * 31 + seed) % 4 === 0 ? 0 : i);
+      expect(cobsDecode(cobsEncode(payload)).value).toEqual(payload);
+    }
+  });
+
+  it("should decode frames back to back", () => {
+    const stream = bytes(0x01, 0x00, 0x02, 0x07, 0x00, 0x01, 0x01, 0x00);
+    const first = cobsDecode(stream);
+    const second = cobsDecode(stream, first.bytesRead);
+    const third = cobsDecode(stream, first.bytesRead + second.bytesRead);
+    expect([first.value, second.value, third.value]).toEqual([bytes(), bytes(0x07), bytes(0x00)]);
+  });
+
+  it("should reject blocks running past the frame with BAD_ENCODING", () => {
+    const result = tryCobsDecode(bytes(0x05, 0x01, 0x02, 0x00));
+    expect(!result.ok && result.error).toBeInstanceOf(DeserializeError);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.BadEncoding);
+  });
+});
+
+describe("serializeCobs / deserializeCobs", () => {
+  it("should frame zero-length messages", () => {
+    expect(serializeCobs(unit(), undefined)).toEqual(bytes(0x01, 0x00));
+    expect(serializeCobs(struct({}), {})).toEqual(bytes(0x01, 0x00));
+    expect(deserializeCobs(struct({}), bytes(0x01, 0x00))).toEqual({ value: {}, bytesRead: 2 });
+  });
+
+  it("should report frame bytes read, delimiter included", () => {
+    const Reading = struct({ id: u8(), samples: seq(u32()) });
+    const frame = serializeCobs(Reading, { id: 0, samples: [0, 300] });
+    const stream = new Uint8Array([...frame, ...frame]);
+
+    const first = deserializeCobs(Reading, stream);
+    expect(first).toEqual({ value: { id: 0, samples: [0, 300] }, bytesRead: frame.length });
+    expect(deserializeCobs(Reading, stream, first.bytesRead).value).toEqual(first.value);
+  });
+
+  it("should fail like postcard on a payload too short for the value", () => {
+    const result = tryDeserializeCobs(u8(), bytes(0x01, 0x00));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+});
+
+/** Every frame complete so far, as values or error codes */
+function drain<S extends Schema>(accumulator: CobsAccumulator<S>): unknown[] {
+  const out: unknown[] = [];
+  for (let r = accumulator.tryNext(); !r.ok || r.value !== undefined; r = accumulator.tryNext()) {
+    out.push(r.ok ? r.value : r.error.code);
+  }
+  return out;
+}
+
+describe("CobsAccumulator", () => {
+  const Reading = struct({ id: u8(), samples: seq(u32()) });
+  const readings = [
+    { id: 0, samples: [] },
+    { id: 1, samples: [0, 0, 0, 256] },
+    { id: 2, samples: Array.from({ length: 100 }, (_, i) => i * 1000) },
+  ];
+  const stream = new Uint8Array(
+    readings.flatMap((reading) => Array.from(serializeCobs(Reading, reading)))
+  );
+
+  it.each([1, 2, 5, 64, stream.length])("should collect frames from %i-byte chunks", (size) => {
+    const accumulator = new CobsAccumulator(Reading);
+    const decoded: unknown[] = [];
+    for (let i = 0; i < stream.length; i += size) {
+      accumulator.push(stream.subarray(i, i + size));
+      decoded.push(...accumulator);
+    }
+    expect(decoded).toEqual(readings);
+    expect(accumulator.buffered).toBe(0);
+  });
+
+  it("should drop a malformed frame and continue after it", () => {
+    const accumulator = new CobsAccumulator(Reading);
+    accumulator.push(bytes(0x05, 0x01, 0x02, 0x00));
+    accumulator.push(serializeCobs(Reading, { id: 7, samples: [] }));
+
+    expect(() => accumulator.next()).toThrow(DeserializeError);
+    expect(accumulator.next()).toEqual({ id: 7, samples: [] });
+    expect(accumulator.next()).toBeUndefined();
+  });
+
+  it("should report bare delimiters like postcard", () => {
+    const accumulator = new CobsAccumulator(u8());
+    accumulator.push(bytes(0x00, 0x02, 0x09, 0x00));
+    expect(drain(accumulator)).toEqual([ErrorCode.UnexpectedEnd, 9]);
+  });
+
+  it("should skip frames longer than maxFrameLength", () => {
+    const accumulator = new CobsAccumulator(string(), { maxFrameLength: 8 });
+    const long = serializeCobs(string(), "far too long for the buffer");
+    const short = serializeCobs(string(), "ok");
+
+    accumulator.push(long.subarray(0, 10));
+    expect(drain(accumulator)).toEqual([ErrorCode.BufferFull]);
+    expect(accumulator.buffered).toBe(0);
+    accumulator.push(long.subarray(10));
+    accumulator.push(short);
+    expect(drain(accumulator)).toEqual(["ok"]);
+
+    // A whole oversized frame in one chunk
+    accumulator.push(new Uint8Array([...long, ...short]));
+    expect(drain(accumulator)).toEqual([ErrorCode.BufferFull, "ok"]);
+    expect(() => new CobsAccumulator(u8(), { maxFrameLength: 0 })).toThrow(RangeError);
+  });
+
+  it("should drop the partial frame on reset", () => {
+    const accumulator = new CobsAccumulator(u8());
+    accumulator.push(bytes(0x02));
+    accumulator.reset();
+    accumulator.push(bytes(0x02, 0x05, 0x00));
+    expect(drain(accumulator)).toEqual([5]);
+  });
+});
+
+describe.runIf(existsSync(COBS_PATH))("COBS frames written by Rust", () => {
+  it.each(recorded.fixtures)("$file ($type) should decode and re-encode", (entry) => {
+    const schema = ERROR_CASE_SCHEMAS[entry.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${entry.type}"`);
+    }
+    const frame = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const { value, bytesRead } = deserializeCobs(schema, frame);
+
+    expect(bytesRead).toBe(frame.length);
+    expect(toSerdeJson(schema, value)).toEqual(entry.value);
+    expect(serializeCobs(schema, value)).toEqual(frame);
+  });
+
+  it.each(recorded.cases)("$name ($type) should fail with the code Rust reported", (errorCase) => {
+    const schema = ERROR_CASE_SCHEMAS[errorCase.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${errorCase.type}"`);
+    }
+    const result = tryDeserializeCobs(schema, new Uint8Array(errorCase.bytes));
+    expect(!result.ok && result.error.code).toBe(errorCase.code);
+  });
+
+  it.each(recorded.streams)("$file ($type) should split into frames like postcard", (entry) => {
+    const schema = ERROR_CASE_SCHEMAS[entry.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${entry.type}"`);
+    }
+    const stream = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const expected = entry.frames.map((frame) => ("code" in frame ? frame.code : frame.value));
+
+    for (const size of [1, 7, 255, stream.length]) {
+      const accumulator = new CobsAccumulator(schema);
+      const frames: unknown[] = [];
+      for (let i = 0; i < stream.length; i += size) {
+        accumulator.push(stream.subarray(i, i + size));
+        frames.push(
+          ...drain(accumulator).map((frame) =>
+            typeof frame === "string" ? frame : toSerdeJson(schema, frame)
+          )
+        );
+      }
+      expect(frames).toEqual(expected);
+    }
+  });
+});
@@ -0,0 +1,140 @@
+/**
+ * Tests for sequences and maps with a capacity (heapless::Vec<T, N>,
+ * heapless::IndexMap) and for fixed-length arrays
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DynamicDecoder,
+  DynamicEncoder,
+  ErrorCode,
+  IrError,
+  SerializeError,
+  DeserializeError,
+  array,
+  deserialize,
+  errorCode,
+  f32,
+  map,
+  serialize,
+  seq,
+  struct,
+  tryDeserialize,
+  trySerialize,
+  u8,
+  u16,
+  type InferType,
+} from "../../src/index.js";
+
+const QueueSchema = struct({
+  slots: seq(u16(), { maxLength: 3 }),
+  names: map(u8(), u8(), { maxLength: 1 }),
+});
+
+describe("maxLength", () => {
+  it("should encode collections within the limit like plain ones", () => {
+    const value = { slots: [1, 2, 3], names: new Map([[1, 2]]) };
+    expect(serialize(QueueSchema, value)).toEqual(
+      serialize(struct({ slots: seq(u16()), names: map(u8(), u8()) }), value)
+    );
+  });
+
+  it("should refuse longer collections", () => {
+    const result = trySerialize(QueueSchema, { slots: [1, 2, 3, 4], names: new Map() });
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(SerializeError);
+    expect(errorCode(error)).toBe(ErrorCode.SerializeFailed);
+    expect((error as SerializeError).message).toContain("Seq of 4 items exceeds maxLength (3)");
+
+    const names = new Map([
+      [1, 1],
+      [2, 2],
+    ]);
+    expect(() => serialize(QueueSchema, { slots: [], names })).toThrow(
+      "Map of 2 entries exceeds maxLength (1)"
+    );
+  });
+
+  it("should reject longer collections when decoding", () => {
+    const bytes = serialize(struct({ slots: seq(u16()), names: map(u8(), u8()) }), {
+      slots: [1, 2, 3, 4],
+      names: new Map(),
+    });
+    const result = tryDeserialize(QueueSchema, bytes);
+    expect(!result.ok && result.error).toBeInstanceOf(DeserializeError);
+    expect(!result.ok && result.error.message).toContain("Seq of 4 items exceeds maxLength (3)");
+    expect(() => deserialize(QueueSchema, new Uint8Array([0, 2, 1, 1, 2, 2]))).toThrow(
+      "Map of 2 entries exceeds maxLength (1)"
+    );
+  });
+
+  it("should refuse invalid limits", () => {
+    expect(() => seq(u8(), { maxLength: -1 })).toThrow(RangeError);
+    expect(() => map(u8(), u8(), { maxLength: 0.5 })).toThrow(RangeError);
+  });
+});
+
+describe("maxLength in the IR", () => {
+  const ir = {
+    version: 1,
+    types: [
+      {
+        name: "Queue",
+        kind: "struct",
+        fields: [{ name: "slots", type: { kind: "seq", item: { kind: "u8" }, maxLength: 2 } }],
+      },
+    ],
+  };
+
+  it("should check the limit", () => {
+    const encoder = new DynamicEncoder(ir);
+    expect(encoder.validate("Queue", { slots: [1, 2] })).toEqual([]);
+    expect(encoder.validate("Queue", { slots: [1, 2, 3] })).toEqual([
+      { path: "Queue.slots", message: "3 items exceed maxLength (2)" },
+    ]);
+    const frame = new Uint8Array([3, 1, 2, 3]);
+    expect(() => new DynamicDecoder(ir).decode("Queue", frame)).toThrow(DeserializeError);
+  });
+
+  it("should reject invalid limits", () => {
+    const invalid = {
+      version: 1,
+      types: [
+        {
+          name: "Queue",
+          kind: "newtype_struct",
+          inner: { kind: "seq", item: { kind: "u8" }, maxLength: "2" },
+        },
+      ],
+    };
+    expect(() => new DynamicEncoder(invalid)).toThrow(IrError);
+  });
+});
+
+describe("array", () => {
+  const HeightmapSchema = struct({ tiles: array(array(u8(), 3), 2), gain: array(f32(), 2) });
+  type Heightmap = InferType<typeof HeightmapSchema>;
+
+  it("should encode its items back to back, as a tuple", () => {
+    const value: Heightmap = {
+      tiles: [
+        [1, 2, 3],
+        [4, 5, 6],
+      ],
+      gain: [0.5, 2],
+    };
+    const bytes = serialize(HeightmapSchema, value);
+    // No length prefixes; 0.5 and 2 as little-endian f32
+    expect(bytes).toEqual(new Uint8Array([1, 2, 3, 4, 5, 6, 0, 0, 0, 0x3f, 0, 0, 0, 0x40]));
+    expect(deserialize(HeightmapSchema, bytes).value).toEqual(value);
+  });
+
+  it("should refuse values that are too short", () => {
+    expect(() => serialize(array(u8(), 3), [1, 2])).toThrow(SerializeError);
+  });
+
+  it("should refuse invalid lengths", () => {
+    expect(() => array(u8(), -1)).toThrow(RangeError);
+    expect(() => array(u8(), 2.5)).toThrow(RangeError);
+  });
+});
@@ -0,0 +1,165 @@
+/**
+ * Tests for columnar decoding into typed arrays
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  decodeColumns,
+  tryDecodeColumns,
+  ColumnarError,
+  ErrorCode,
+  decodeSeqInto,
+  tryDecodeSeqInto,
+  seq,
+  struct,
+  string,
+  u8,
+  u16,
+  u32,
+  i8,
+  i16,
+  i32,
+  f32,
+  f64,
+  type InferType,
+} from "../../src/index.js";
+
+const SampleSchema = struct({ t: f64(), value: f32(), channel: u8(), count: u32() });
+type Sample = InferType<typeof SampleSchema>;
+
+describe("decodeColumns()", () => {
+  const samples: Sample[] = [
+    { t: 0.5, value: 1.25, channel: 0, count: 300 },
+    { t: 1.5, value: -2, channel: 3, count: 0 },
+    { t: 2.5, value: 0.5, channel: 255, count: 4294967295 },
+  ];
+
+  it("should decode each field into its own typed array", () => {
+    const data = serialize(seq(SampleSchema), samples);
+    const { value, bytesRead } = decodeColumns(seq(SampleSchema), data);
+
+    expect(bytesRead).toBe(data.length);
+    expect(value.t).toEqual(new Float64Array([0.5, 1.5, 2.5]));
+    expect(value.value).toEqual(new Float32Array([1.25, -2, 0.5]));
+    expect(value.channel).toEqual(new Uint8Array([0, 3, 255]));
+    expect(value.count).toEqual(new Uint32Array([300, 0, 4294967295]));
+  });
+
+  it("should infer the typed array type per field", () => {
+    const { value } = decodeColumns(seq(SampleSchema), serialize(seq(SampleSchema), samples));
+    const _t: Float64Array = value.t;
+    const _value: Float32Array = value.value;
+    const _channel: Uint8Array = value.channel;
+    void _t;
+    void _value;
+    void _channel;
+  });
+
+  it("should decode signed varint and small integer fields", () => {
+    const SignedSchema = struct({ a: i8(), b: i16(), c: i32(), d: u16() });
+    const items = [
+      { a: -128, b: -300, c: -70000, d: 65535 },
+      { a: 127, b: 32767, c: 2147483647, d: 0 },
+    ];
+    const { value } = decodeColumns(seq(SignedSchema), serialize(seq(SignedSchema), items));
+
+    expect(value.a).toEqual(new Int8Array([-128, 127]));
+    expect(value.b).toEqual(new Int16Array([-300, 32767]));
+    expect(value.c).toEqual(new Int32Array([-70000, 2147483647]));
+    expect(value.d).toEqual(new Uint16Array([65535, 0]));
+  });
+
+  it("should start at the given offset", () => {
+    const prefix = serialize(string(), "hdr");
+    const body = serialize(seq(SampleSchema), samples);
+    const data = new Uint8Array([...prefix, ...body]);
+
+    const { value, bytesRead } = decodeColumns(seq(SampleSchema), data, prefix.length);
+    expect(bytesRead).toBe(body.length);
+    expect(value.count[0]).toBe(300);
+  });
+
+  it("should decode an empty sequence", () => {
+    const { value } = decodeColumns(seq(SampleSchema), serialize(seq(SampleSchema), []));
+    expect(value.t).toHaveLength(0);
+  });
+
+  describe("errors", () => {
+    it("should fail on truncated data", () => {
+      const data = serialize(seq(SampleSchema), samples);
+      const result = tryDecodeColumns(seq(SampleSchema), data.subarray(0, data.length - 1));
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(ColumnarError);
+      }
+    });
+
+    it("should reject lengths the data cannot hold without allocating for them", () => {
+      // Claims 2^28 items of at least 8 bytes each
+      const data = new Uint8Array([0x80, 0x80, 0x80, 0x80, 0x01]);
+      expect(() => decodeColumns(seq(struct({ x: f64() })), data)).toThrow(ColumnarError);
+      const result = tryDecodeColumns(seq(struct({ x: f64() })), data);
+      expect(!result.ok && result.error.code).toBe(ErrorCode.UnexpectedEnd);
+    });
+
+    it("should reject non-numeric fields", () => {
+      const schema = seq(struct({ name: string() })) as never;
+      expect(() => decodeColumns(schema, new Uint8Array([0]))).toThrow(ColumnarError);
+    });
+  });
+});
+
+describe("decodeSeqInto()", () => {
+  it("should write the sequence into the target at the given index", () => {
+    const target = new Float32Array(6).fill(-1);
+    const { value, bytesRead } = decodeSeqInto(
+      seq(f32()),
+      serialize(seq(f32()), [0.5, 1.5, 2.5]),
+      target,
+      2
+    );
+
+    expect(value).toBe(3);
+    expect(bytesRead).toBe(1 + 3 * 4);
+    expect(target).toEqual(new Float32Array([-1, -1, 0.5, 1.5, 2.5, -1]));
+  });
+
+  it("should convert integer samples to the target's element type", () => {
+    const target = new Float64Array(3);
+    decodeSeqInto(seq(i16()), serialize(seq(i16()), [-2048, 0, 2047]), target);
+    expect(target).toEqual(new Float64Array([-2048, 0, 2047]));
+  });
+
+  it("should read from the given data offset", () => {
+    const data = new Uint8Array([0xaa, ...serialize(seq(u16()), [1, 300])]);
+    const target = new Uint16Array(2);
+    const { bytesRead } = decodeSeqInto(seq(u16()), data, target, 0, 1);
+    expect(bytesRead).toBe(data.length - 1);
+    expect(target).toEqual(new Uint16Array([1, 300]));
+  });
+
+  describe("errors", () => {
+    it("should leave the target untouched when the sequence does not fit", () => {
+      const target = new Float32Array(4);
+      const result = tryDecodeSeqInto(seq(f32()), serialize(seq(f32()), [1, 2, 3]), target, 2);
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(ColumnarError);
+      }
+      expect(target).toEqual(new Float32Array(4));
+    });
+
+    it("should reject a target offset outside the target", () => {
+      const data = serialize(seq(f32()), []);
+      expect(tryDecodeSeqInto(seq(f32()), data, new Float32Array(2), 3).ok).toBe(false);
+      expect(tryDecodeSeqInto(seq(f32()), data, new Float32Array(2), -1).ok).toBe(false);
+    });
+
+    it("should fail on truncated data", () => {
+      const data = serialize(seq(f64()), [1, 2]);
+      const result = tryDecodeSeqInto(seq(f64()), data.subarray(0, 12), new Float64Array(2));
+      expect(result.ok).toBe(false);
+    });
+  });
+});
@@ -0,0 +1,188 @@
+/**
+ * Tests for CRC-checked messages
+ *
+ * The Rust generator writes values with postcard's CRC flavor and records
+ * malformed messages with the code postcard's CRC deserializer reported, all
+ * in crc.json; each message must decode to the recorded value and re-encode
+ * to the same bytes.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  Crc,
+  CRC_8_SMBUS,
+  CRC_8_MAXIM_DOW,
+  CRC_16_ARC,
+  CRC_16_IBM_SDLC,
+  CRC_16_KERMIT,
+  CRC_16_MODBUS,
+  CRC_16_XMODEM,
+  CRC_32_BZIP2,
+  CRC_32_ISCSI,
+  CRC_32_ISO_HDLC,
+  CRC_32_MPEG_2,
+  CRC_64_ECMA_182,
+  CRC_64_XZ,
+  serializeCrc,
+  deserializeCrc,
+  tryDeserializeCrc,
+  DeserializeError,
+  ErrorCode,
+  string,
+  u32,
+  type CrcAlgorithm,
+} from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface CrcFixtures {
+  readonly fixtures: {
+    readonly file: string;
+    readonly type: string;
+    readonly algorithm: string;
+    readonly value: unknown;
+  }[];
+  readonly cases: {
+    readonly name: string;
+    readonly type: string;
+    readonly algorithm: string;
+    readonly bytes: number[];
+    readonly code: string;
+  }[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const CRC_PATH = join(FIXTURES_DIR, "crc.json");
+
+const recorded: CrcFixtures = existsSync(CRC_PATH)
+  ? (JSON.parse(readFileSync(CRC_PATH, "utf8")) as CrcFixtures)
+  : { fixtures: [], cases: [] };
+
+const CATALOG: Readonly<Record<string, CrcAlgorithm>> = {
+  CRC_8_SMBUS,
+  CRC_8_MAXIM_DOW,
+  CRC_16_ARC,
+  CRC_16_IBM_SDLC,
+  CRC_16_KERMIT,
+  CRC_16_MODBUS,
+  CRC_16_XMODEM,
+  CRC_32_BZIP2,
+  CRC_32_ISCSI,
+  CRC_32_ISO_HDLC,
+  CRC_32_MPEG_2,
+  CRC_64_ECMA_182,
+  CRC_64_XZ,
+};
+
+const CHECK_INPUT = new TextEncoder().encode("123456789");
+
+function crcFor(name: string): Crc {
+  const algorithm = CATALOG[name];
+  if (algorithm === undefined) {
+    throw new Error(`No CRC algorithm named ${name}`);
+  }
+  return new Crc(algorithm);
+}
+
+describe("Crc", () => {
+  it.each(Object.entries(CATALOG))("%s should compute its check value", (_, algorithm) => {
+    expect(new Crc(algorithm).checksum(CHECK_INPUT)).toBe(BigInt(algorithm.check ?? -1));
+  });
+
+  // Widths that are not a whole number of bytes, from the crc crate's catalog
+  const CRC_3_GSM = { width: 3, poly: 0x3, init: 0, refin: false, refout: false, xorout: 0x7 };
+  const CRC_5_USB = { width: 5, poly: 0x5, init: 0x1f, refin: true, refout: true, xorout: 0x1f };
+  const CRC_12_UMTS = { width: 12, poly: 0x80f, init: 0, refin: false, refout: true, xorout: 0 };
+  const CRC_15_CAN = { width: 15, poly: 0x4599, init: 0, refin: false, refout: false, xorout: 0 };
+
+  it.each([
+    { name: "CRC_3_GSM", algorithm: CRC_3_GSM, check: 0x4 },
+    { name: "CRC_5_USB", algorithm: CRC_5_USB, check: 0x19 },
+    { name: "CRC_12_UMTS", algorithm: CRC_12_UMTS, check: 0xdaf },
+    { name: "CRC_15_CAN", algorithm: CRC_15_CAN, check: 0x59e },
+  ])("$name should compute its check value", ({ algorithm, check }) => {
+    expect(new Crc(algorithm).checksum(CHECK_INPUT)).toBe(BigInt(check));
+  });
+
+  it("should take the bytes of the smallest integer holding the width", () => {
+    expect(new Crc(CRC_8_SMBUS).byteLength).toBe(1);
+    expect(new Crc(CRC_12_UMTS).byteLength).toBe(2);
+    expect(new Crc(CRC_32_ISCSI).byteLength).toBe(4);
+    expect(new Crc(CRC_64_XZ).byteLength).toBe(8);
+  });
+
+  it("should append checksums little-endian", () => {
+    const crc = new Crc(CRC_32_ISO_HDLC);
+    expect(crc.checksumBytes(CHECK_INPUT)).toEqual(new Uint8Array([0x26, 0x39, 0xf4, 0xcb]));
+  });
+
+  it("should reject parameters that do not match their check value", () => {
+    expect(() => new Crc({ ...CRC_32_ISCSI, refout: false })).toThrow(RangeError);
+    expect(() => new Crc({ ...CRC_16_XMODEM, poly: 0x11021 })).toThrow(RangeError);
+    expect(() => new Crc({ ...CRC_64_XZ, width: 65 })).toThrow(RangeError);
+  });
+});
+
+describe("serializeCrc / deserializeCrc", () => {
+  const crc = new Crc(CRC_32_ISCSI);
+
+  it("should append the CRC of the serialized value", () => {
+    const message = serializeCrc(string(), "hi", crc);
+    const payload = new Uint8Array([0x02, 0x68, 0x69]);
+    expect(message.subarray(0, 3)).toEqual(payload);
+    expect(message.subarray(3)).toEqual(crc.checksumBytes(payload));
+    expect(deserializeCrc(string(), message, crc)).toEqual({ value: "hi", bytesRead: 7 });
+  });
+
+  it("should decode messages back to back", () => {
+    const first = serializeCrc(u32(), 300, crc);
+    const second = serializeCrc(u32(), 7, crc);
+    const data = new Uint8Array([...first, ...second]);
+
+    const { value, bytesRead } = deserializeCrc(u32(), data, crc);
+    expect(value).toBe(300);
+    expect(deserializeCrc(u32(), data, crc, bytesRead).value).toBe(7);
+  });
+
+  it("should fail with BAD_CRC on a corrupted message", () => {
+    const message = serializeCrc(string(), "hi", crc);
+    message[1] = 0x67;
+    const result = tryDeserializeCrc(string(), message, crc);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.BadCrc);
+    expect(() => deserializeCrc(string(), message, crc)).toThrow(DeserializeError);
+  });
+
+  it("should fail with UNEXPECTED_END when the CRC is cut off", () => {
+    const message = serializeCrc(u32(), 300, crc);
+    const result = tryDeserializeCrc(u32(), message.subarray(0, message.length - 1), crc);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+});
+
+describe.runIf(existsSync(CRC_PATH))("CRC-checked messages written by Rust", () => {
+  it.each(recorded.fixtures)("$file ($algorithm) should decode and re-encode", (entry) => {
+    const schema = ERROR_CASE_SCHEMAS[entry.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${entry.type}"`);
+    }
+    const crc = crcFor(entry.algorithm);
+    const message = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const { value, bytesRead } = deserializeCrc(schema, message, crc);
+
+    expect(bytesRead).toBe(message.length);
+    expect(toSerdeJson(schema, value)).toEqual(entry.value);
+    expect(serializeCrc(schema, value, crc)).toEqual(message);
+  });
+
+  it.each(recorded.cases)("$name ($type) should fail with the code Rust reported", (errorCase) => {
+    const schema = ERROR_CASE_SCHEMAS[errorCase.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${errorCase.type}"`);
+    }
+    const crc = crcFor(errorCase.algorithm);
+    const result = tryDeserializeCrc(schema, new Uint8Array(errorCase.bytes), crc);
+    expect(!result.ok && result.error.code).toBe(errorCase.code);
+  });
+});
@@ -0,0 +1,109 @@
+/**
+ * Tests for memoized decoding of repeated payloads
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DecodeCache,
+  DeserializeError,
+  serialize,
+  struct,
+  seq,
+  bytes,
+  string,
+  u8,
+  u32,
+  type InferType,
+} from "../../src/index.js";
+
+const StateSchema = struct({
+  name: string(),
+  readings: seq(u32()),
+  raw: bytes(),
+});
+type State = InferType<typeof StateSchema>;
+
+const state = (name: string): State => ({
+  name,
+  readings: [1, 2, 3],
+  raw: new Uint8Array([4, 5]),
+});
+const encode = (value: State): Uint8Array => serialize(StateSchema, value);
+
+describe("DecodeCache", () => {
+  it("should return the same frozen value for repeated payloads", () => {
+    const cache = new DecodeCache(StateSchema);
+    const first = cache.decode(encode(state("boiler")));
+    const again = cache.decode(encode(state("boiler")));
+
+    expect(again).toBe(first);
+    expect(first).toEqual(state("boiler"));
+    expect(Object.isFrozen(first)).toBe(true);
+    expect(Object.isFrozen(first.readings)).toBe(true);
+    expect(cache.stats).toEqual({
+      hits: 1,
+      misses: 1,
+      bypassed: 0,
+      evictions: 0,
+      size: 1,
+      hitRate: 0.5,
+    });
+  });
+
+  it("should not be affected by later writes to the payload buffer", () => {
+    const cache = new DecodeCache(StateSchema);
+    const buffer = encode(state("boiler"));
+    const first = cache.decode(buffer);
+    buffer.fill(0);
+
+    expect(first.raw).toEqual(new Uint8Array([4, 5]));
+    expect(cache.decode(encode(state("boiler")))).toBe(first);
+  });
+
+  it("should drop the least recently used payload", () => {
+    const cache = new DecodeCache(StateSchema, { maxEntries: 2 });
+    const a = cache.decode(encode(state("a")));
+    cache.decode(encode(state("b")));
+    cache.decode(encode(state("a")));
+    cache.decode(encode(state("c")));
+
+    expect(cache.decode(encode(state("a")))).toBe(a);
+    expect(cache.stats).toMatchObject({ hits: 2, misses: 3, evictions: 1, size: 2 });
+    cache.decode(encode(state("b")));
+    expect(cache.stats.misses).toBe(4);
+  });
+
+  it("should decode large payloads without caching them", () => {
+    const cache = new DecodeCache(StateSchema, { maxPayloadBytes: 4 });
+    const first = cache.decode(encode(state("boiler")));
+
+    expect(cache.decode(encode(state("boiler")))).not.toBe(first);
+    expect(cache.stats).toMatchObject({ hits: 0, misses: 0, bypassed: 2, size: 0, hitRate: 0 });
+  });
+
+  it("should not cache payloads that fail to decode", () => {
+    const cache = new DecodeCache(u8());
+    const result = cache.tryDecode(new Uint8Array());
+
+    expect(result.ok).toBe(false);
+    expect(() => cache.decode(new Uint8Array())).toThrow(DeserializeError);
+    expect(cache.stats).toMatchObject({ misses: 2, size: 0 });
+  });
+
+  it("should reset stats and clear entries", () => {
+    const cache = new DecodeCache(StateSchema);
+    const first = cache.decode(encode(state("boiler")));
+    cache.resetStats();
+    expect(cache.stats).toMatchObject({ hits: 0, misses: 0, size: 1 });
+
+    cache.clear();
+    expect(cache.decode(encode(state("boiler")))).not.toBe(first);
+    expect(cache.stats).toMatchObject({ misses: 1, size: 1 });
+  });
+
+  it("should reject invalid limits", () => {
+    expect(() => new DecodeCache(u8(), { maxEntries: 0 })).toThrow(RangeError);
+    expect(() => new DecodeCache(u8(), { maxEntries: 1.5 })).toThrow(RangeError);
+    expect(() => new DecodeCache(u8(), { maxPayloadBytes: -1 })).toThrow(RangeError);
+  });
+});
@@ -0,0 +1,121 @@
+/**
+ * Tests for per-decode limits, tracing and stats
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DecodeStats,
+  DeserializeError,
+  ErrorCode,
+  deserialize,
+  lazy,
+  newtypeVariant,
+  enumType,
+  serialize,
+  seq,
+  string,
+  struct,
+  tryDeserialize,
+  u8,
+  unit,
+  unitVariant,
+  type DecodeTraceEvent,
+  type Schema,
+} from "../../src/index.js";
+
+const DeviceSchema = struct({
+  id: u8(),
+  tags: seq(string()),
+});
+
+type Node = { type: "Leaf" } | { type: "Branch"; value: Node };
+const NodeSchema = enumType("Node", {
+  Leaf: unitVariant("Leaf"),
+  Branch: newtypeVariant("Branch", lazy<Node>((): Schema => NodeSchema)),
+});
+
+const nested = (depth: number): Node =>
+  depth === 0 ? { type: "Leaf" } : { type: "Branch", value: nested(depth - 1) };
+
+describe("decode limits", () => {
+  const data = serialize(DeviceSchema, { id: 1, tags: ["boiler", "hot", "loud"] });
+
+  it("should reject sequences longer than maxLength", () => {
+    const result = tryDeserialize(DeviceSchema, data, 0, { limits: { maxLength: 2 } });
+
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error.code).toBe(ErrorCode.InvalidValue);
+      expect(result.error.path).toBe("tags");
+      expect(result.error.mismatch).toEqual({ expected: "at most 2 items", found: "3 items" });
+    }
+  });
+
+  it("should fail fast on huge lengths of values taking no bytes", () => {
+    // A billion units, which would otherwise be decoded one by one
+    const units = new Uint8Array([0x80, 0x94, 0xeb, 0xdc, 0x03]);
+    const result = tryDeserialize(seq(unit()), units, 0, { limits: { maxLength: 1000 } });
+
+    expect(result.ok).toBe(false);
+  });
+
+  it("should reject strings longer than maxBytes before decoding them", () => {
+    const result = tryDeserialize(DeviceSchema, data, 0, { limits: { maxBytes: 4 } });
+
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error.path).toBe("tags[0]");
+      expect(result.error.mismatch).toEqual({ expected: "at most 4 bytes", found: "6 bytes" });
+    }
+  });
+
+  it("should reject values nested deeper than maxDepth", () => {
+    const tree = serialize(NodeSchema, nested(3));
+
+    expect(deserialize(NodeSchema, tree, 0, { limits: { maxDepth: 4 } }).value).toEqual(
+      nested(3)
+    );
+    expect(() => deserialize(NodeSchema, tree, 0, { limits: { maxDepth: 3 } })).toThrow(
+      DeserializeError
+    );
+  });
+
+  it("should apply only to the decodes given them", () => {
+    const strict = { limits: { maxLength: 2 } };
+    const lenient = {};
+
+    expect(tryDeserialize(DeviceSchema, data, 0, strict).ok).toBe(false);
+    expect(tryDeserialize(DeviceSchema, data, 0, lenient).ok).toBe(true);
+  });
+});
+
+describe("decode tracing", () => {
+  it("should report each value after decoding it, inner values first", () => {
+    const events: DecodeTraceEvent[] = [];
+    const data = serialize(DeviceSchema, { id: 1, tags: ["a"] });
+    deserialize(DeviceSchema, data, 0, { trace: (event) => events.push(event) });
+
+    expect(events).toEqual([
+      { kind: "u8", path: "id", offset: 0, bytesRead: 1 },
+      { kind: "string", path: "tags[0]", offset: 2, bytesRead: 2 },
+      { kind: "seq", path: "tags", offset: 1, bytesRead: 3 },
+      { kind: "struct", path: "", offset: 0, bytesRead: 4 },
+    ]);
+  });
+});
+
+describe("DecodeStats", () => {
+  it("should count decodes, failures, bytes and values across decodes", () => {
+    const stats = new DecodeStats();
+    const data = serialize(DeviceSchema, { id: 1, tags: ["a"] });
+    deserialize(DeviceSchema, data, 0, { stats });
+    tryDeserialize(DeviceSchema, data.subarray(0, 2), 0, { stats });
+
+    expect(stats).toMatchObject({ decodes: 2, failures: 1, bytes: 4 });
+    // 4 values from the first decode, the id from the second
+    expect(stats.values).toBe(5);
+
+    stats.reset();
+    expect(stats).toMatchObject({ decodes: 0, failures: 0, bytes: 0, values: 0 });
+  });
+});
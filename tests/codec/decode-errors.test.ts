@@ -0,0 +1,145 @@
+/**
+ * Tests for the location of decode errors: byte offset, path and mismatch
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DeserializeError,
+  DynamicDecoder,
+  ErrorCode,
+  bool,
+  deserialize,
+  enumType,
+  map,
+  newtypeVariant,
+  seq,
+  serialize,
+  string,
+  struct,
+  structVariant,
+  tryDeserialize,
+  tuple,
+  u16,
+  u32,
+  u8,
+  unitVariant,
+  type Result,
+} from "../../src/index.js";
+
+const ElementSchema = enumType("Element", {
+  Fire: unitVariant("Fire"),
+  Ice: unitVariant("Ice"),
+});
+
+const ItemSchema = enumType("Item", {
+  Potion: newtypeVariant("Potion", u8()),
+  Weapon: structVariant("Weapon", { damage: u16(), element: ElementSchema }),
+});
+
+const GameStateSchema = struct({
+  player: struct({
+    name: string(),
+    inventory: struct({ gold: u32(), items: seq(ItemSchema) }),
+  }),
+});
+
+// name 0..=3, gold 4, item count 5, items 6..=7, 8..=9 and 10..=12
+const GAME_STATE = serialize(GameStateSchema, {
+  player: {
+    name: "Ada",
+    inventory: {
+      gold: 10,
+      items: [
+        { type: "Potion", value: 1 },
+        { type: "Potion", value: 2 },
+        { type: "Weapon", value: { damage: 5, element: { type: "Ice" } } },
+      ],
+    },
+  },
+});
+
+function withByte(data: Uint8Array, index: number, byte: number): Uint8Array {
+  const copy = data.slice();
+  copy[index] = byte;
+  return copy;
+}
+
+function decodeError(result: Result<unknown, DeserializeError>): DeserializeError {
+  if (result.ok) {
+    throw new Error("expected decoding to fail");
+  }
+  return result.error;
+}
+
+describe("Decode error locations", () => {
+  it("should report the path, offset and mismatch of a bad discriminant", () => {
+    const data = withByte(GAME_STATE, 12, 7);
+    const error = decodeError(tryDeserialize(GameStateSchema, data, 0, { root: "GameState" }));
+
+    expect(error).toBeInstanceOf(DeserializeError);
+    expect(error.code).toBe(ErrorCode.InvalidValue);
+    expect(error.path).toBe("GameState.player.inventory.items[2].Weapon.element");
+    expect(error.offset).toBe(12);
+    expect(error.mismatch).toEqual({ expected: "Element variant 0..=1", found: "7" });
+    expect(error.message).toBe(
+      "GameState.player.inventory.items[2].Weapon.element at byte 12: " +
+        "Invalid enum discriminant for Element: 7 (max: 1)"
+    );
+  });
+
+  it("should start paths at the first field without a root", () => {
+    const error = decodeError(tryDeserialize(GameStateSchema, withByte(GAME_STATE, 12, 7)));
+    expect(error.path).toBe("player.inventory.items[2].Weapon.element");
+  });
+
+  it("should report where the data ran out", () => {
+    const error = decodeError(tryDeserialize(GameStateSchema, GAME_STATE.subarray(0, 12)));
+    expect(error.code).toBe(ErrorCode.UnexpectedEnd);
+    expect(error.path).toBe("player.inventory.items[2].Weapon.element");
+    expect(error.offset).toBe(12);
+    expect(error.mismatch).toEqual({ expected: "Element discriminant", found: "end of data" });
+  });
+
+  it("should count offsets from the start of the input", () => {
+    const data = new Uint8Array([0xaa, 0xbb, 0xcc, ...withByte(GAME_STATE, 12, 7)]);
+    const error = decodeError(tryDeserialize(GameStateSchema, data, 3));
+    expect(error.offset).toBe(15);
+  });
+
+  it("should name newtype variants, tuple items and map entries", () => {
+    expect(decodeError(tryDeserialize(GameStateSchema, GAME_STATE.subarray(0, 7))).path).toBe(
+      "player.inventory.items[0].Potion"
+    );
+
+    const Flags = map(string(), tuple(u8(), bool()));
+    // { "a": (5, <2>) }
+    const error = decodeError(tryDeserialize(Flags, new Uint8Array([1, 1, 0x61, 5, 2])));
+    expect(error.path).toBe("[0].value.1");
+    expect(error.offset).toBe(4);
+  });
+
+  it("should carry the location through the throwing API", () => {
+    expect(() => deserialize(GameStateSchema, withByte(GAME_STATE, 12, 7))).toThrow(
+      "player.inventory.items[2].Weapon.element at byte 12"
+    );
+  });
+
+  it("should start dynamic decoder paths at the type name", () => {
+    const decoder = new DynamicDecoder({
+      version: 1,
+      types: [
+        {
+          name: "Reading",
+          kind: "struct",
+          fields: [
+            { name: "id", type: { kind: "u8" } },
+            { name: "valid", type: { kind: "bool" } },
+          ],
+        },
+      ],
+    });
+    expect(() => decoder.decode("Reading", new Uint8Array([1, 9]))).toThrow(
+      "Reading.valid at byte 1"
+    );
+  });
+});
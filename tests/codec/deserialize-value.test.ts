@@ -0,0 +1,59 @@
+/**
+ * Tests for deserializing a value without the number of bytes it took
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DeserializeError,
+  ErrorCode,
+  deserializeValue,
+  encodeOnly,
+  f32,
+  string,
+  struct,
+  tryDeserializeValue,
+  u8,
+} from "../../src/index.js";
+
+const ReadingSchema = struct({ sensor: u8(), celsius: f32() });
+
+// sensor 2, celsius 22.5
+const READING = new Uint8Array([0x02, 0x00, 0x00, 0xb4, 0x41]);
+
+describe("tryDeserializeValue", () => {
+  it("should return the value alone", () => {
+    expect(tryDeserializeValue(ReadingSchema, READING)).toEqual({
+      ok: true,
+      value: { sensor: 2, celsius: 22.5 },
+    });
+  });
+
+  it("should return failures instead of throwing", () => {
+    const result = tryDeserializeValue(ReadingSchema, READING.subarray(0, 3));
+    expect(result.ok).toBe(false);
+    expect(!result.ok && result.error).toBeInstanceOf(DeserializeError);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+
+  it("should pass the offset and options on", () => {
+    const data = new Uint8Array([0xff, 0x03, 0x61, 0x62, 0x63]);
+    expect(tryDeserializeValue(string(), data, 1)).toEqual({ ok: true, value: "abc" });
+
+    const limited = tryDeserializeValue(string(), data, 1, { limits: { maxBytes: 2 } });
+    expect(!limited.ok && limited.error.code).toBe(ErrorCode.InvalidValue);
+  });
+});
+
+describe("deserializeValue", () => {
+  it("should return the value or throw", () => {
+    expect(deserializeValue(ReadingSchema, READING)).toEqual({ sensor: 2, celsius: 22.5 });
+    expect(() => deserializeValue(ReadingSchema, READING.subarray(0, 3))).toThrow(
+      DeserializeError
+    );
+  });
+
+  it("should refuse encode-only schemas", () => {
+    // @ts-expect-error - the schema is only encoded
+    expect(() => deserializeValue(encodeOnly(ReadingSchema), READING)).toThrow(TypeError);
+  });
+});
@@ -0,0 +1,243 @@
+/**
+ * Differential tests between decoders
+ *
+ * Every decoder that reads postcard bytes on its own (the chunked
+ * StreamDecoder, the columnar decoders) must agree with deserialize() on any
+ * input: the same value and length for valid bytes, the same error code for
+ * invalid ones. Inputs are random values, those values with bytes flipped,
+ * cut off or appended, and plain random bytes, from a fixed seed so failures
+ * reproduce; the failing input is part of the assertion message.
+ *
+ * A new decoder joins by adding a profile to the cases it can decode.
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  StreamDecoder,
+  tryDecodeColumns,
+  tryDeserialize,
+  serialize,
+  enumType,
+  newtypeVariant,
+  structVariant,
+  unitVariant,
+  option,
+  seq,
+  string,
+  struct,
+  bool,
+  f32,
+  f64,
+  i8,
+  i16,
+  i32,
+  u8,
+  u16,
+  u32,
+  u64,
+  type Schema,
+} from "../../src/index.js";
+
+type Outcome =
+  | { readonly ok: true; readonly value: unknown; readonly bytesRead: number }
+  | { readonly ok: false; readonly code: string };
+
+type Random = () => number;
+
+interface Profile {
+  readonly name: string;
+  decode(data: Uint8Array, random: Random): Outcome;
+}
+
+interface Case {
+  readonly name: string;
+  readonly schema: Schema;
+  arbitrary(random: Random): unknown;
+  readonly profiles: readonly Profile[];
+}
+
+const SEED = 0x5eed;
+const ITERATIONS = 500;
+
+/** mulberry32: small, fast and good enough to pick test inputs */
+function generator(seed: number): Random {
+  let state = seed >>> 0;
+  return () => {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+}
+
+function integer(random: Random, min: number, max: number): number {
+  return min + Math.floor(random() * (max - min + 1));
+}
+
+function randomBytes(random: Random, length: number): Uint8Array {
+  return new Uint8Array(length).map(() => integer(random, 0, 255));
+}
+
+function reference(schema: Schema): Profile {
+  return {
+    name: "deserialize",
+    decode(data) {
+      const result = tryDeserialize(schema, data);
+      return result.ok
+        ? { ok: true, value: result.value.value, bytesRead: result.value.bytesRead }
+        : { ok: false, code: result.error.code };
+    },
+  };
+}
+
+/** The first value of the stream, pushed in random chunks */
+function stream(schema: Schema): Profile {
+  return {
+    name: "StreamDecoder",
+    decode(data, random) {
+      const decoder = new StreamDecoder(schema);
+      let pushed = 0;
+      while (pushed < data.length) {
+        const end = Math.min(data.length, pushed + integer(random, 1, 8));
+        decoder.push(data.subarray(pushed, end));
+        pushed = end;
+        const result = decoder.tryNext();
+        if (!result.ok) {
+          return { ok: false, code: result.error.code };
+        }
+        if (result.value !== undefined) {
+          return { ok: true, value: result.value, bytesRead: pushed - decoder.buffered };
+        }
+      }
+      // Still waiting for the rest of the value
+      return { ok: false, code: "UNEXPECTED_END" };
+    },
+  };
+}
+
+const NUMERIC_FIELDS = {
+  level: u8(),
+  offset: i8(),
+  raw: u16(),
+  delta: i16(),
+  count: u32(),
+  position: i32(),
+  x: f32(),
+  y: f64(),
+};
+
+const SampleSchema = struct(NUMERIC_FIELDS);
+const SamplesSchema = seq(SampleSchema);
+
+/** decodeColumns(), turned back into the array of structs deserialize() returns */
+const columns: Profile = {
+  name: "decodeColumns",
+  decode(data) {
+    const result = tryDecodeColumns(SamplesSchema, data);
+    if (!result.ok) {
+      return { ok: false, code: result.error.code };
+    }
+    const { value, bytesRead } = result.value;
+    const items = Array.from(value.level, (_, i) =>
+      Object.fromEntries(Object.entries(value).map(([name, column]) => [name, column[i]]))
+    );
+    return { ok: true, value: items, bytesRead };
+  },
+};
+
+const MessageSchema = enumType("Message", {
+  Ping: unitVariant("Ping"),
+  Text: newtypeVariant("Text", string()),
+  Reading: structVariant("Reading", {
+    sensor: u8(),
+    value: option(f32()),
+    at: u64(),
+    valid: bool(),
+  }),
+});
+
+// Split by code point, so that cutting it never leaves half a surrogate pair
+const TEXT = Array.from("héllo wörld 📡");
+
+const CASES: readonly Case[] = [
+  {
+    name: "Vec<Sample>",
+    schema: SamplesSchema,
+    arbitrary: (random) =>
+      Array.from({ length: integer(random, 0, 6) }, () => ({
+        level: integer(random, 0, 0xff),
+        offset: integer(random, -0x80, 0x7f),
+        raw: integer(random, 0, 0xffff),
+        delta: integer(random, -0x8000, 0x7fff),
+        count: integer(random, 0, 0xffffffff),
+        position: integer(random, -0x80000000, 0x7fffffff),
+        x: Math.fround((random() - 0.5) * 1e6),
+        y: (random() - 0.5) * 1e12,
+      })),
+    profiles: [stream(SamplesSchema), columns],
+  },
+  {
+    name: "Message",
+    schema: MessageSchema,
+    arbitrary: (random) => {
+      switch (integer(random, 0, 2)) {
+        case 0:
+          return { type: "Ping" };
+        case 1:
+          return { type: "Text", value: TEXT.slice(0, integer(random, 0, TEXT.length)).join("") };
+        default:
+          return {
+            type: "Reading",
+            value: {
+              sensor: integer(random, 0, 0xff),
+              value: random() < 0.5 ? null : Math.fround(random() * 100),
+              at: BigInt(integer(random, 0, 0xffffffff)) << BigInt(integer(random, 0, 32)),
+              valid: random() < 0.5,
+            },
+          };
+      }
+    },
+    profiles: [stream(MessageSchema)],
+  },
+];
+
+/** A valid encoding, possibly damaged, or random bytes */
+function input(testCase: Case, random: Random): Uint8Array {
+  const roll = random();
+  if (roll < 0.1) {
+    return randomBytes(random, integer(random, 0, 32));
+  }
+  const bytes = serialize(testCase.schema, testCase.arbitrary(random));
+  if (roll < 0.4 || bytes.length === 0) {
+    return bytes;
+  }
+  if (roll < 0.6) {
+    const flipped = bytes.slice();
+    flipped[integer(random, 0, bytes.length - 1)] = integer(random, 0, 255);
+    return flipped;
+  }
+  if (roll < 0.8) {
+    return bytes.subarray(0, integer(random, 0, bytes.length - 1));
+  }
+  return new Uint8Array([...bytes, ...randomBytes(random, integer(random, 1, 8))]);
+}
+
+function hex(data: Uint8Array): string {
+  return Array.from(data, (byte) => byte.toString(16).padStart(2, "0")).join(" ");
+}
+
+describe("decoders", () => {
+  it.each(CASES)("should agree with deserialize() on $name", (testCase) => {
+    const random = generator(SEED);
+    const expectedDecoder = reference(testCase.schema);
+    for (let i = 0; i < ITERATIONS; i++) {
+      const data = input(testCase, random);
+      const expected = expectedDecoder.decode(data, random);
+      for (const profile of testCase.profiles) {
+        const actual = profile.decode(data, random);
+        expect(actual, `${profile.name} on [${hex(data)}]`).toEqual(expected);
+      }
+    }
+  });
+});
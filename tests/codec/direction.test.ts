@@ -0,0 +1,68 @@
+/**
+ * Tests for schemas limited to encoding or decoding
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  canonicalKey,
+  decodeOnly,
+  deserialize,
+  directionOf,
+  encodeOnly,
+  f32,
+  serialize,
+  serializeInto,
+  struct,
+  tryDeserialize,
+  trySerialize,
+  u8,
+  Writer,
+  type InferType,
+} from "../../src/index.js";
+
+const SetTargetSchema = encodeOnly(struct({ celsius: f32() }));
+const ReadingSchema = decodeOnly(struct({ sensor: u8(), celsius: f32() }));
+type Reading = InferType<typeof ReadingSchema>;
+
+const reading: Reading = { sensor: 1, celsius: 21.5 };
+const readingBytes = serialize(struct({ sensor: u8(), celsius: f32() }), reading);
+
+describe("encodeOnly()", () => {
+  it("should encode as the schema it wraps", () => {
+    expect(serialize(SetTargetSchema, { celsius: 21.5 })).toEqual(
+      serialize(struct({ celsius: f32() }), { celsius: 21.5 })
+    );
+    expect(directionOf(SetTargetSchema)).toBe("encode");
+  });
+
+  it("should not decode", () => {
+    const data = serialize(SetTargetSchema, { celsius: 21.5 });
+
+    // @ts-expect-error - SetTarget is only encoded
+    expect(() => deserialize(SetTargetSchema, data)).toThrow(TypeError);
+    // @ts-expect-error - SetTarget is only encoded
+    expect(() => tryDeserialize(SetTargetSchema, data)).toThrow(TypeError);
+  });
+});
+
+describe("decodeOnly()", () => {
+  it("should decode as the schema it wraps", () => {
+    expect(deserialize(ReadingSchema, readingBytes).value).toEqual(reading);
+    expect(directionOf(ReadingSchema)).toBe("decode");
+  });
+
+  it("should not encode", () => {
+    // @ts-expect-error - Reading is only decoded
+    expect(() => serialize(ReadingSchema, reading)).toThrow(TypeError);
+    // @ts-expect-error - Reading is only decoded
+    expect(() => trySerialize(ReadingSchema, reading)).toThrow(TypeError);
+    // @ts-expect-error - Reading is only decoded
+    expect(() => serializeInto(ReadingSchema, reading, new Writer())).toThrow(TypeError);
+  });
+
+  it("should still give decode-only keys a canonical string", () => {
+    const readingKey = canonicalKey(ReadingSchema);
+
+    expect(readingKey(reading)).toBe(readingKey({ ...reading }));
+  });
+});
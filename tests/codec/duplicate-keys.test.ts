@@ -0,0 +1,128 @@
+/**
+ * Tests for repeated map keys
+ *
+ * Postcard does not forbid a map from repeating a key. The Rust generator
+ * writes hand-crafted maps that do and records the entries serde kept when
+ * decoding them as a `HashMap` in duplicate_keys.json; the default policy must
+ * keep the same ones.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  deserialize,
+  tryDeserialize,
+  DeserializeError,
+  ErrorCode,
+  map,
+  seq,
+  string,
+  struct,
+  tuple,
+  u8,
+  type DuplicateKeyPolicy,
+} from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface DuplicateKeyCase {
+  readonly name: string;
+  readonly type: string;
+  readonly bytes: number[];
+  readonly entries: [unknown, unknown][];
+}
+
+const DUPLICATE_KEYS_PATH = join(process.cwd(), "test-fixtures", "fixtures", "duplicate_keys.json");
+
+function loadCases(): DuplicateKeyCase[] {
+  if (!existsSync(DUPLICATE_KEYS_PATH)) {
+    return [];
+  }
+  return (JSON.parse(readFileSync(DUPLICATE_KEYS_PATH, "utf8")) as { cases: DuplicateKeyCase[] })
+    .cases;
+}
+
+// { a: 1, b: 2, a: 3 }
+const STRING_KEYS = new Uint8Array([3, 1, 0x61, 1, 1, 0x62, 2, 1, 0x61, 3]);
+// { (1, 2): "x", (1, 2): "y" }
+const TUPLE_KEYS = new Uint8Array([2, 1, 2, 1, 0x78, 1, 2, 1, 0x79]);
+
+describe("Duplicate map keys", () => {
+  const StringMap = map(string(), u8());
+  const TupleMap = map(tuple(u8(), u8()), string());
+
+  it("should keep the last value by default, as Rust does", () => {
+    const { value, bytesRead } = deserialize(StringMap, STRING_KEYS);
+    expect(value).toEqual(new Map([["a", 3], ["b", 2]]));
+    expect(Array.from(value.keys())).toEqual(["a", "b"]);
+    expect(bytesRead).toBe(STRING_KEYS.length);
+  });
+
+  it("should keep the first value with the first policy", () => {
+    const { value, bytesRead } = deserialize(StringMap, STRING_KEYS, 0, { duplicateKeys: "first" });
+    expect(value).toEqual(new Map([["a", 1], ["b", 2]]));
+    expect(bytesRead).toBe(STRING_KEYS.length);
+  });
+
+  it("should fail with INVALID_VALUE with the error policy", () => {
+    const result = tryDeserialize(StringMap, STRING_KEYS, 0, { duplicateKeys: "error" });
+    expect(!result.ok && result.error).toBeInstanceOf(DeserializeError);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.InvalidValue);
+    expect(!result.ok && result.error.message).toBe(
+      "[2].key at byte 7: Duplicate map key at entry 2"
+    );
+  });
+
+  it("should match object keys by value", () => {
+    expect(deserialize(TupleMap, TUPLE_KEYS).value).toEqual(new Map([[[1, 2], "y"]]));
+    expect(deserialize(TupleMap, TUPLE_KEYS, 0, { duplicateKeys: "first" }).value).toEqual(
+      new Map([[[1, 2], "x"]])
+    );
+    const result = tryDeserialize(TupleMap, TUPLE_KEYS, 0, { duplicateKeys: "error" });
+    expect(!result.ok && result.error.code).toBe(ErrorCode.InvalidValue);
+  });
+
+  it("should apply the policy to nested maps", () => {
+    const Nested = struct({ maps: seq(StringMap) });
+    const bytes = new Uint8Array([1, ...STRING_KEYS]);
+    const { value } = deserialize(Nested, bytes, 0, { duplicateKeys: "first" });
+    expect(value.maps[0]?.get("a")).toBe(1);
+    expect(tryDeserialize(Nested, bytes, 0, { duplicateKeys: "error" }).ok).toBe(false);
+  });
+
+  it("should accept maps without repeats under every policy", () => {
+    const bytes = new Uint8Array([2, 1, 0x61, 1, 1, 0x62, 2]);
+    for (const policy of ["last", "first", "error"] as DuplicateKeyPolicy[]) {
+      expect(deserialize(StringMap, bytes, 0, { duplicateKeys: policy }).value).toEqual(
+        new Map([["a", 1], ["b", 2]])
+      );
+    }
+  });
+});
+
+describe.runIf(existsSync(DUPLICATE_KEYS_PATH))("Duplicate keys written by Rust", () => {
+  describe.each(loadCases())("$name ($type)", (duplicateCase) => {
+    const schema = ERROR_CASE_SCHEMAS[duplicateCase.type];
+    const bytes = new Uint8Array(duplicateCase.bytes);
+
+    it("should keep the entries Rust kept", () => {
+      if (schema?.kind !== "map") {
+        throw new Error(`No map schema for Rust type "${duplicateCase.type}"`);
+      }
+      const { value } = deserialize(schema, bytes);
+      // HashMap iteration order is arbitrary, so compare by key
+      const entries = Array.from(value as Map<unknown, unknown>, ([key, item]) => [
+        JSON.stringify(toSerdeJson(schema.key, key)),
+        toSerdeJson(schema.value, item),
+      ]);
+      const expected = duplicateCase.entries.map(([key, item]) => [JSON.stringify(key), item]);
+      expect(new Map(entries)).toEqual(new Map(expected));
+    });
+
+    it("should fail with INVALID_VALUE with the error policy", () => {
+      const result = tryDeserialize(schema ?? u8(), bytes, 0, { duplicateKeys: "error" });
+      expect(!result.ok && result.error.code).toBe(ErrorCode.InvalidValue);
+    });
+  });
+});
@@ -0,0 +1,82 @@
+/**
+ * Tests for schemas encoded by an external codec
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  deserialize,
+  external,
+  serialize,
+  seq,
+  struct,
+  tryDeserialize,
+  trySerialize,
+  u8,
+  DeserializeError,
+  SerializeError,
+  type ExternalCodec,
+} from "../../src/index.js";
+
+/** A big-endian u16, which no schema builder writes */
+const bigEndian: ExternalCodec<number> = {
+  encode: (value) => new Uint8Array([value >> 8, value & 0xff]),
+  decode: (data) => {
+    const [high, low] = data;
+    if (high === undefined || low === undefined) {
+      throw new Error("needs two bytes");
+    }
+    return { value: (high << 8) | low, bytesRead: 2 };
+  },
+};
+
+const PortSchema = external("Port", bigEndian);
+
+describe("external()", () => {
+  it("should write and read the codec's bytes as they are", () => {
+    const bytes = serialize(PortSchema, 0x1f90);
+    expect(Array.from(bytes)).toEqual([0x1f, 0x90]);
+    expect(deserialize(PortSchema, bytes)).toBe(0x1f90);
+  });
+
+  it("should nest in other schemas by the bytes the codec read", () => {
+    const Listener = struct({ id: u8(), ports: seq(PortSchema), flags: u8() });
+    const value = { id: 7, ports: [80, 8080], flags: 3 };
+    const bytes = serialize(Listener, value);
+    expect(Array.from(bytes)).toEqual([7, 2, 0x00, 0x50, 0x1f, 0x90, 3]);
+    expect(deserialize(Listener, bytes)).toEqual(value);
+  });
+
+  it("should report codec failures with the type's name", () => {
+    const failing = external<number>("Port", {
+      encode: () => {
+        throw new Error("out of range");
+      },
+      decode: bigEndian.decode,
+    });
+    const encoded = trySerialize(failing, 1);
+    expect(encoded.ok).toBe(false);
+    if (!encoded.ok) {
+      expect(encoded.error).toBeInstanceOf(SerializeError);
+      expect(encoded.error.message).toContain("Failed to encode Port: out of range");
+    }
+
+    const decoded = tryDeserialize(PortSchema, new Uint8Array([0x1f]));
+    expect(decoded.ok).toBe(false);
+    if (!decoded.ok) {
+      expect(decoded.error).toBeInstanceOf(DeserializeError);
+      expect(decoded.error.message).toContain("Failed to decode Port: needs two bytes");
+    }
+  });
+
+  it("should reject byte counts beyond the data", () => {
+    const greedy = external<number>("Port", {
+      encode: bigEndian.encode,
+      decode: (data) => ({ value: 0, bytesRead: data.length + 1 }),
+    });
+    const result = tryDeserialize(greedy, new Uint8Array([0, 1]));
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error.message).toContain("Port codec read 3 bytes of 2");
+    }
+  });
+});
@@ -0,0 +1,101 @@
+/**
+ * Tests for freezing decoded values
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  deepFreeze,
+  struct,
+  seq,
+  map,
+  option,
+  bytes,
+  string,
+  u8,
+  u32,
+  enumType,
+  unitVariant,
+  structVariant,
+  type InferType,
+} from "../../src/index.js";
+
+const StateSchema = struct({
+  name: string(),
+  readings: seq(u32()),
+  labels: map(string(), struct({ color: string() })),
+  mode: enumType("Mode", {
+    Idle: unitVariant("Idle"),
+    Running: structVariant("Running", { speed: u8() }),
+  }),
+  config: option(struct({ retries: u8() })),
+  raw: bytes(),
+});
+
+type State = InferType<typeof StateSchema>;
+
+const state: State = {
+  name: "pump",
+  readings: [1, 2, 3],
+  labels: new Map([["a", { color: "red" }]]),
+  mode: { type: "Running", value: { speed: 4 } },
+  config: { retries: 3 },
+  raw: new Uint8Array([1, 2]),
+};
+
+describe("deserialize() with freeze", () => {
+  const data = serialize(StateSchema, state);
+
+  it("should not freeze by default", () => {
+    const { value } = deserialize(StateSchema, data);
+    expect(Object.isFrozen(value)).toBe(false);
+    value.readings.push(4);
+    expect(value.readings).toEqual([1, 2, 3, 4]);
+  });
+
+  it("should freeze the decoded value recursively", () => {
+    const { value } = deserialize(StateSchema, data, 0, { freeze: true });
+    expect(value).toEqual(state);
+
+    expect(Object.isFrozen(value)).toBe(true);
+    expect(Object.isFrozen(value.readings)).toBe(true);
+    expect(Object.isFrozen(value.mode)).toBe(true);
+    expect(Object.isFrozen(value.config)).toBe(true);
+    expect(Object.isFrozen(value.labels.get("a"))).toBe(true);
+  });
+
+  it("should make mutation throw", () => {
+    const { value } = deserialize(StateSchema, data, 0, { freeze: true });
+    expect(() => value.readings.push(4)).toThrow(TypeError);
+    expect(() => {
+      value.name = "valve";
+    }).toThrow(TypeError);
+    expect(() => value.labels.set("b", { color: "blue" })).toThrow(TypeError);
+    expect(() => value.labels.delete("a")).toThrow(TypeError);
+    expect(value.labels.size).toBe(1);
+  });
+
+  it("should be available on the Result API", () => {
+    const result = tryDeserialize(StateSchema, data, 0, { freeze: true });
+    expect(result.ok).toBe(true);
+    if (result.ok) {
+      expect(Object.isFrozen(result.value.value)).toBe(true);
+    }
+  });
+});
+
+describe("deepFreeze()", () => {
+  it("should pass primitives through", () => {
+    expect(deepFreeze(5)).toBe(5);
+    expect(deepFreeze(null)).toBe(null);
+  });
+
+  it("should leave typed arrays and class instances alone", () => {
+    const value = deepFreeze({ raw: new Uint8Array([1]), when: new Date(0) });
+    expect(Object.isFrozen(value)).toBe(true);
+    expect(Object.isFrozen(value.raw)).toBe(false);
+    expect(Object.isFrozen(value.when)).toBe(false);
+  });
+});
@@ -0,0 +1,93 @@
+/**
+ * Tests for interning repeated strings during decode
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DeserializeError,
+  StringInterner,
+  deserialize,
+  serialize,
+  struct,
+  seq,
+  string,
+  type InferType,
+} from "../../src/index.js";
+
+const ReadingSchema = struct({
+  sensor: string(),
+  unit: string(),
+  tags: seq(string()),
+});
+type Reading = InferType<typeof ReadingSchema>;
+
+const reading: Reading = { sensor: "boiler", unit: "°C", tags: ["boiler", "hot"] };
+const frame = (value: Reading = reading): Uint8Array => serialize(ReadingSchema, value);
+
+describe("StringInterner", () => {
+  it("should decode the same strings as without interning", () => {
+    const intern = new StringInterner();
+
+    expect(deserialize(ReadingSchema, frame(), 0, { intern }).value).toEqual(reading);
+    expect(deserialize(ReadingSchema, frame(), 0, { intern }).value).toEqual(reading);
+  });
+
+  it("should answer repeated strings from the table, within and across decodes", () => {
+    const intern = new StringInterner();
+    deserialize(ReadingSchema, frame(), 0, { intern });
+    expect(intern.stats).toEqual({ hits: 1, misses: 3, size: 3 });
+
+    deserialize(ReadingSchema, frame(), 0, { intern });
+    expect(intern.stats).toEqual({ hits: 5, misses: 3, size: 3 });
+  });
+
+  it("should not intern strings over maxBytes", () => {
+    const intern = new StringInterner({ maxBytes: 3 });
+    deserialize(ReadingSchema, frame(), 0, { intern });
+    deserialize(ReadingSchema, frame(), 0, { intern });
+
+    // "°C" and "hot" fit, "boiler" does not
+    expect(intern.stats).toEqual({ hits: 2, misses: 6, size: 2 });
+  });
+
+  it("should decode new strings as usual once full", () => {
+    const intern = new StringInterner({ maxEntries: 1 });
+    const decoded = deserialize(ReadingSchema, frame(), 0, { intern }).value;
+
+    expect(decoded).toEqual(reading);
+    expect(intern.stats).toEqual({ hits: 1, misses: 3, size: 1 });
+  });
+
+  it("should still enforce string limits on interned strings", () => {
+    const Limited = struct({ name: string({ maxBytes: 2 }) });
+    const intern = new StringInterner();
+    const data = serialize(struct({ name: string() }), { name: "boiler" });
+
+    expect(() => deserialize(Limited, data, 0, { intern })).toThrow(DeserializeError);
+    expect(() => deserialize(Limited, data, 0, { intern })).toThrow(DeserializeError);
+    expect(intern.stats.hits).toBe(1);
+  });
+
+  it("should report malformed strings as tryDecodeString does", () => {
+    const intern = new StringInterner();
+
+    const truncated = intern.tryDecode(new Uint8Array([5, 0x61]));
+    expect(truncated.ok).toBe(false);
+    const badUtf8 = intern.tryDecode(new Uint8Array([1, 0xff]));
+    expect(badUtf8.ok).toBe(false);
+    expect(intern.stats.size).toBe(0);
+  });
+
+  it("should clear strings and stats", () => {
+    const intern = new StringInterner();
+    deserialize(ReadingSchema, frame(), 0, { intern });
+    intern.clear();
+
+    expect(intern.stats).toEqual({ hits: 0, misses: 0, size: 0 });
+  });
+
+  it("should reject invalid limits", () => {
+    expect(() => new StringInterner({ maxEntries: -1 })).toThrow(RangeError);
+    expect(() => new StringInterner({ maxBytes: 1.5 })).toThrow(RangeError);
+  });
+});
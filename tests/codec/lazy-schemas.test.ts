@@ -0,0 +1,84 @@
+/**
+ * Tests for schemas loaded on first use
+ */
+
+import { describe, it, expect, vi } from "vitest";
+import { LazySchemas, PostcardDecodeError, f32, struct, u8 } from "../../src/index.js";
+
+// As `Generator::schema_loaders()` writes `loaders.ts`, with the modules it
+// imports already at hand
+const ReadingSchema = struct({ sensor_id: u8(), celsius: f32() });
+const LevelSchema = u8();
+
+function loaders() {
+  const sensors = vi.fn(() => Promise.resolve({ ReadingSchema }));
+  const levels = vi.fn(() => ({ LevelSchema }));
+  return {
+    sensors,
+    levels,
+    SCHEMA_LOADERS: {
+      Reading: () => sensors().then((module) => module.ReadingSchema),
+      Level: () => levels().LevelSchema,
+    },
+  };
+}
+
+describe("LazySchemas", () => {
+  it("loads a type's module on first use only", async () => {
+    const { sensors, levels, SCHEMA_LOADERS } = loaders();
+    const schemas = new LazySchemas(SCHEMA_LOADERS);
+    expect(schemas.peek("Reading")).toBeUndefined();
+
+    const [first, second] = await Promise.all([schemas.get("Reading"), schemas.get("Reading")]);
+    expect(first).toBe(ReadingSchema);
+    expect(second).toBe(ReadingSchema);
+    expect(await schemas.get("Reading")).toBe(ReadingSchema);
+    expect(schemas.peek("Reading")).toBe(ReadingSchema);
+    expect(sensors).toHaveBeenCalledTimes(1);
+    expect(levels).not.toHaveBeenCalled();
+  });
+
+  it("decodes with the loaded schema", async () => {
+    const schemas = new LazySchemas(loaders().SCHEMA_LOADERS);
+    const { value, bytesRead } = await schemas.decode(
+      "Reading",
+      new Uint8Array([2, 0, 0, 0xb4, 0x41])
+    );
+    expect(value).toEqual({ sensor_id: 2, celsius: 22.5 });
+    expect(bytesRead).toBe(5);
+
+    const result = await schemas.tryDecode("Level", new Uint8Array([]));
+    expect(result.ok).toBe(false);
+    await expect(schemas.decode("Reading", new Uint8Array([2]))).rejects.toBeInstanceOf(
+      PostcardDecodeError
+    );
+  });
+
+  it("loads several types for a worker registry", async () => {
+    const schemas = new LazySchemas(loaders().SCHEMA_LOADERS);
+    expect(await schemas.load("Reading", "Level")).toEqual({
+      Reading: ReadingSchema,
+      Level: LevelSchema,
+    });
+  });
+
+  it("tries a failed load again on next use", async () => {
+    let attempts = 0;
+    const chunk = vi.fn(() =>
+      ++attempts === 1
+        ? Promise.reject(new Error("Failed to fetch dynamically imported module"))
+        : Promise.resolve(ReadingSchema)
+    );
+    const schemas = new LazySchemas({ Reading: chunk });
+
+    await expect(schemas.get("Reading")).rejects.toThrow("Failed to fetch");
+    expect(schemas.peek("Reading")).toBeUndefined();
+    expect(await schemas.get("Reading")).toBe(ReadingSchema);
+    expect(chunk).toHaveBeenCalledTimes(2);
+  });
+
+  it("throws RangeError for a type without a loader", () => {
+    const schemas = new LazySchemas<Readonly<Record<string, () => typeof LevelSchema>>>({});
+    expect(() => schemas.get("toString")).toThrow(RangeError);
+  });
+});
@@ -0,0 +1,193 @@
+/**
+ * Tests for length-prefix framing
+ *
+ * The Rust generator writes streams of messages back to back in
+ * streams.json; the length-prefixed ones must split into the same messages
+ * however they are chunked.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  lengthPrefixEncode,
+  lengthPrefixDecode,
+  tryLengthPrefixDecode,
+  serializeLengthPrefixed,
+  deserializeLengthPrefixed,
+  LengthPrefixAccumulator,
+  FramingError,
+  ErrorCode,
+  bytes as bytesSchema,
+  struct,
+  u8,
+  u32,
+  seq,
+  type Schema,
+} from "../../src/index.js";
+import { FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface StreamFixtures {
+  readonly streams: {
+    readonly file: string;
+    readonly framing: string;
+    readonly type: string;
+    readonly messages: unknown[];
+  }[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const STREAMS_PATH = join(FIXTURES_DIR, "streams.json");
+
+const recorded: StreamFixtures = existsSync(STREAMS_PATH)
+  ? (JSON.parse(readFileSync(STREAMS_PATH, "utf8")) as StreamFixtures)
+  : { streams: [] };
+
+const bytes = (...values: number[]): Uint8Array => new Uint8Array(values);
+
+function drain<S extends Schema>(accumulator: LengthPrefixAccumulator<S>): unknown[] {
+  const out: unknown[] = [];
+  for (let r = accumulator.tryNext(); !r.ok || r.value !== undefined; r = accumulator.tryNext()) {
+    out.push(r.ok ? r.value : r.error.code);
+    if (!r.ok && r.error.code === ErrorCode.BadVarint) {
+      break;
+    }
+  }
+  return out;
+}
+
+describe("lengthPrefixEncode / lengthPrefixDecode", () => {
+  it("should prefix payloads with their length as a varint", () => {
+    expect(lengthPrefixEncode(bytes())).toEqual(bytes(0x00));
+    expect(lengthPrefixEncode(bytes(7, 8))).toEqual(bytes(0x02, 7, 8));
+    const long = new Uint8Array(300).fill(0xaa);
+    const frame = lengthPrefixEncode(long);
+    expect(frame.subarray(0, 2)).toEqual(bytes(0xac, 0x02));
+    expect(lengthPrefixDecode(frame)).toEqual({ value: long, bytesRead: 302 });
+  });
+
+  it("should decode frames back to back", () => {
+    const data = bytes(0x01, 9, 0x00, 0x02, 1, 2);
+    expect(lengthPrefixDecode(data, 0)).toEqual({ value: bytes(9), bytesRead: 2 });
+    expect(lengthPrefixDecode(data, 2)).toEqual({ value: bytes(), bytesRead: 1 });
+    expect(lengthPrefixDecode(data, 3)).toEqual({ value: bytes(1, 2), bytesRead: 3 });
+  });
+
+  it("should fail on a frame cut off or a malformed prefix", () => {
+    const short = tryLengthPrefixDecode(bytes(0x03, 1, 2));
+    expect(!short.ok && short.error).toBeInstanceOf(FramingError);
+    expect(!short.ok && short.error.code).toBe(ErrorCode.UnexpectedEnd);
+    expect(!short.ok && short.error.message).toBe(
+      "Frame at offset 0 announces 3 bytes, but only 2 follow"
+    );
+    const malformed = tryLengthPrefixDecode(bytes(0xff, 0xff, 0xff, 0xff, 0xff, 0x01));
+    expect(!malformed.ok && malformed.error.code).toBe(ErrorCode.BadVarint);
+  });
+
+  it("should round-trip values as postcard frames a Vec<u8> of them", () => {
+    const Reading = struct({ id: u8(), samples: seq(u32()) });
+    const reading = { id: 3, samples: [1, 300] };
+    const frame = serializeLengthPrefixed(Reading, reading);
+    expect(frame).toEqual(bytes(0x05, 3, 2, 1, 0xac, 0x02));
+    expect(deserializeLengthPrefixed(Reading, frame)).toEqual({ value: reading, bytesRead: 6 });
+  });
+});
+
+describe("LengthPrefixAccumulator", () => {
+  const Reading = struct({ id: u8(), samples: seq(u32()) });
+  const readings = [
+    { id: 0, samples: [] },
+    { id: 1, samples: [0, 0, 0, 256] },
+    { id: 2, samples: Array.from({ length: 100 }, (_, i) => i * 1000) },
+  ];
+  const stream = new Uint8Array(
+    readings.flatMap((reading) => [...serializeLengthPrefixed(Reading, reading)])
+  );
+
+  it("should decode values however the stream is chunked", () => {
+    for (const size of [1, 2, 5, stream.length]) {
+      const accumulator = new LengthPrefixAccumulator(Reading);
+      const values: unknown[] = [];
+      for (let i = 0; i < stream.length; i += size) {
+        accumulator.push(stream.subarray(i, i + size));
+        values.push(...accumulator);
+      }
+      expect(values).toEqual(readings);
+      expect(accumulator.buffered).toBe(0);
+    }
+  });
+
+  it("should keep byte views intact as more of the stream arrives", () => {
+    const Blob = bytesSchema();
+    const accumulator = new LengthPrefixAccumulator(Blob, { byteViews: true });
+    accumulator.push(serializeLengthPrefixed(Blob, bytes(1, 2, 3)));
+    const first = accumulator.next();
+    accumulator.push(serializeLengthPrefixed(Blob, bytes(9, 9, 9)));
+    expect(accumulator.next()).toEqual(bytes(9, 9, 9));
+    expect(first).toEqual(bytes(1, 2, 3));
+  });
+
+  it("should skip oversized frames by their length", () => {
+    const accumulator = new LengthPrefixAccumulator(Reading, { maxFrameLength: 10 });
+    // The long frame arrives in pieces, and is dropped as they do
+    for (let i = 0; i < stream.length; i += 7) {
+      accumulator.push(stream.subarray(i, i + 7));
+    }
+    expect(drain(accumulator)).toEqual([readings[0], readings[1], ErrorCode.BufferFull]);
+
+    const chunked = new LengthPrefixAccumulator(Reading, { maxFrameLength: 10 });
+    const out: unknown[] = [];
+    for (let i = 0; i < stream.length; i += 7) {
+      chunked.push(stream.subarray(i, i + 7));
+      out.push(...drain(chunked));
+    }
+    chunked.push(serializeLengthPrefixed(Reading, readings[0]));
+    out.push(...drain(chunked));
+    expect(out).toEqual([readings[0], readings[1], ErrorCode.BufferFull, readings[0]]);
+  });
+
+  it("should drop a frame that does not decode, and go on", () => {
+    const accumulator = new LengthPrefixAccumulator(Reading);
+    accumulator.push(bytes(0x02, 1, 0x05));
+    accumulator.push(serializeLengthPrefixed(Reading, readings[1]));
+    expect(drain(accumulator)).toEqual([ErrorCode.UnexpectedEnd, readings[1]]);
+  });
+
+  it("should fail on a malformed prefix until reset", () => {
+    const accumulator = new LengthPrefixAccumulator(Reading);
+    accumulator.push(bytes(0xff, 0xff, 0xff, 0xff, 0xff, 0x01));
+    expect(() => accumulator.next()).toThrow(FramingError);
+    accumulator.push(serializeLengthPrefixed(Reading, readings[0]));
+    expect(() => accumulator.next()).toThrow("Malformed length prefix");
+    accumulator.reset();
+    accumulator.push(serializeLengthPrefixed(Reading, readings[0]));
+    expect(accumulator.next()).toEqual(readings[0]);
+  });
+
+  it("should reject a negative maxFrameLength", () => {
+    expect(() => new LengthPrefixAccumulator(Reading, { maxFrameLength: -1 })).toThrow(RangeError);
+  });
+});
+
+describe("Rust length-prefixed streams", () => {
+  const streams = recorded.streams.filter((entry) => entry.framing === "length-prefix");
+
+  it.each(streams)("$file ($type) should split into the messages Rust wrote", (entry) => {
+    const schema = FIXTURE_SCHEMAS[entry.type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${entry.type}"`);
+    }
+    const stream = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+
+    for (const size of [1, 7, 255, stream.length]) {
+      const accumulator = new LengthPrefixAccumulator(schema);
+      const messages: unknown[] = [];
+      for (let i = 0; i < stream.length; i += size) {
+        accumulator.push(stream.subarray(i, i + size));
+        messages.push(...[...accumulator].map((message) => toSerdeJson(schema, message)));
+      }
+      expect(messages).toEqual(entry.messages);
+    }
+  });
+});
@@ -0,0 +1,62 @@
+/**
+ * Tests for Option<Vec<T>>, Vec<Option<T>> and empty Vec<T>, which are easily
+ * confused but differ on the wire
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import { deserialize, serialize, type InferType } from "../../src/index.js";
+import { ListShapesSchema } from "../fixtures/schemas.js";
+
+type ListShapes = InferType<typeof ListShapesSchema>;
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+describe("List shapes", () => {
+  it("should tell None from Some of an empty list", () => {
+    const absent: ListShapes = { maybe_items: null, sparse_items: [], items: [] };
+    const empty: ListShapes = { maybe_items: [], sparse_items: [null], items: [] };
+
+    expect(serialize(ListShapesSchema, absent)).toEqual(new Uint8Array([0x00, 0x00, 0x00]));
+    expect(serialize(ListShapesSchema, empty)).toEqual(
+      new Uint8Array([0x01, 0x00, 0x01, 0x00, 0x00])
+    );
+    const decoded = deserialize(ListShapesSchema, new Uint8Array([0x01, 0x00, 0x00, 0x00]));
+    expect(decoded.value).toEqual({ maybe_items: [], sparse_items: [], items: [] });
+  });
+
+  it("should write an option per item of a list with gaps", () => {
+    const value: ListShapes = { maybe_items: null, sparse_items: [null, 300, null], items: [] };
+    expect(serialize(ListShapesSchema, value)).toEqual(
+      new Uint8Array([0x00, 0x03, 0x00, 0x01, 0xac, 0x02, 0x00, 0x00])
+    );
+  });
+
+  it("should not accept one shape for another", () => {
+    const wrong = { maybe_items: [null], sparse_items: [], items: [] };
+    // @ts-expect-error maybe_items holds numbers, not options
+    expect(() => serialize(ListShapesSchema, wrong)).toThrow();
+  });
+});
+
+describe.runIf(existsSync(join(FIXTURES_DIR, "list_shapes_full.bin")))(
+  "List shapes written by Rust",
+  () => {
+    const read = (file: string): Uint8Array =>
+      new Uint8Array(readFileSync(join(FIXTURES_DIR, file)));
+
+    it.each([
+      ["list_shapes_absent.bin", { maybe_items: null, sparse_items: [], items: [] }],
+      ["list_shapes_empty.bin", { maybe_items: [], sparse_items: [null], items: [] }],
+      [
+        "list_shapes_full.bin",
+        { maybe_items: [1, 300], sparse_items: [null, 300, null], items: [7] },
+      ],
+    ])("should decode %s and write it back", (file, expected) => {
+      const data = read(file);
+      expect(deserialize(ListShapesSchema, data).value).toEqual(expected);
+      expect(serialize(ListShapesSchema, expected as ListShapes)).toEqual(data);
+    });
+  }
+);
@@ -0,0 +1,227 @@
+/**
+ * Tests for maps with non-string keys and value-based key lookups
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  deserialize,
+  serialize,
+  canonicalKey,
+  encodedKey,
+  keysEqual,
+  mapGet,
+  mapHas,
+  map,
+  enumType,
+  f32,
+  f64,
+  newtypeStruct,
+  newtypeVariant,
+  option,
+  string,
+  struct,
+  tuple,
+  u8,
+  u16,
+  unitVariant,
+  type InferType,
+} from "../../src/index.js";
+import {
+  CellMapSchema,
+  KeyedMapsSchema,
+  SlotMapSchema,
+  WideKeysSchema,
+} from "../fixtures/schemas.js";
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+describe("keysEqual()", () => {
+  it("should compare primitives like Map does", () => {
+    expect(keysEqual(1, 1)).toBe(true);
+    expect(keysEqual(NaN, NaN)).toBe(true);
+    expect(keysEqual(0, -0)).toBe(true);
+    expect(keysEqual(1n, 1n)).toBe(true);
+    expect(keysEqual(1, 1n)).toBe(false);
+    expect(keysEqual("1", 1)).toBe(false);
+  });
+
+  it("should compare tuples, structs and enums by value", () => {
+    expect(keysEqual([0, 1], [0, 1])).toBe(true);
+    expect(keysEqual([0, 1], [1, 0])).toBe(false);
+    expect(keysEqual([0, 1], [0, 1, 2])).toBe(false);
+    expect(keysEqual({ x: 1, y: [2] }, { y: [2], x: 1 })).toBe(true);
+    expect(keysEqual({ x: 1 }, { x: 1, y: 2 })).toBe(false);
+    expect(keysEqual({ type: "Red" }, { type: "Red" })).toBe(true);
+    expect(keysEqual({ type: "Red" }, { type: "Blue" })).toBe(false);
+    expect(keysEqual([1], { 0: 1 })).toBe(false);
+  });
+
+  it("should compare bytes, dates and maps by contents", () => {
+    expect(keysEqual(new Uint8Array([1, 2]), new Uint8Array([1, 2]))).toBe(true);
+    expect(keysEqual(new Uint8Array([1, 2]), [1, 2])).toBe(false);
+    expect(keysEqual(new Date(5), new Date(5))).toBe(true);
+    expect(keysEqual(new Map([[[1], "a"]]), new Map([[[1], "a"]]))).toBe(true);
+    expect(keysEqual(new Map([[[1], "a"]]), new Map([[[1], "b"]]))).toBe(false);
+  });
+});
+
+describe("mapGet() and mapHas()", () => {
+  const GridSchema = map(tuple(u8(), u8()), string());
+
+  it("should find tuple keys that Map.get misses", () => {
+    const data = serialize(GridSchema, new Map([[[0, 1], "a"] as [[number, number], string]]));
+    const grid = deserialize(GridSchema, data).value;
+
+    expect(grid.get([0, 1])).toBeUndefined();
+    expect(mapGet(grid, [0, 1])).toBe("a");
+    expect(mapHas(grid, [0, 1])).toBe(true);
+    expect(mapHas(grid, [1, 0])).toBe(false);
+  });
+
+  it("should find struct and bytes keys", () => {
+    const PointSchema = struct({ x: u16(), y: u16() });
+    const points = deserialize(
+      map(PointSchema, u8()),
+      serialize(map(PointSchema, u8()), new Map([[{ x: 1, y: 2 }, 9]]))
+    ).value;
+    expect(mapGet(points, { x: 1, y: 2 })).toBe(9);
+
+    const blobs = new Map([[new Uint8Array([0xde, 0xad]), "dead"]]);
+    expect(mapGet(blobs, new Uint8Array([0xde, 0xad]))).toBe("dead");
+    expect(mapGet(blobs, new Uint8Array([0xde]))).toBeUndefined();
+  });
+
+  it("should look up primitive keys directly", () => {
+    const ids = new Map([
+      [7, "seven"],
+      [NaN, "nan"],
+    ]);
+    expect(mapGet(ids, 7)).toBe("seven");
+    expect(mapGet(ids, NaN)).toBe("nan");
+    expect(mapHas(ids, 8)).toBe(false);
+  });
+});
+
+describe("map()", () => {
+  it("should refuse float keys, which a Map cannot tell apart", () => {
+    expect(() => map(f64(), u8())).toThrow(RangeError);
+    expect(() => map(option(f32()), u8())).toThrow(RangeError);
+    expect(() => map(newtypeStruct("Celsius", f32()), u8())).toThrow(RangeError);
+  });
+
+  it("should accept floats inside compound keys", () => {
+    // Compared by their encoded bytes, so 0 and -0 stay apart
+    const PointSchema = map(tuple(f32(), f32()), u8());
+    const points = new Map([
+      [[0, 0], 1],
+      [[-0, 0], 2],
+    ] as [[number, number], number][]);
+    expect(deserialize(PointSchema, serialize(PointSchema, points)).value.size).toBe(2);
+  });
+});
+
+describe("canonicalKey()", () => {
+  it("should give equal keys the same string", () => {
+    const cellKey = canonicalKey(tuple(u8(), u8()));
+    expect(cellKey([0, 1])).toBe("0001");
+    expect(cellKey([0, 1])).toBe(cellKey([0, 1]));
+    expect(cellKey([1, 0])).not.toBe(cellKey([0, 1]));
+
+    const pointKey = canonicalKey(struct({ x: u16(), y: u16() }));
+    expect(pointKey({ y: 2, x: 1 })).toBe(pointKey({ x: 1, y: 2 }));
+  });
+
+  it("should tell enum variants apart", () => {
+    const ShapeSchema = enumType("Shape", {
+      Dot: unitVariant("Dot"),
+      Circle: newtypeVariant("Circle", u8()),
+    });
+    const shapeKey = canonicalKey(ShapeSchema);
+    expect(shapeKey({ type: "Dot" })).toBe("00");
+    expect(shapeKey({ type: "Circle", value: 0 })).toBe("0100");
+    expect(shapeKey({ type: "Circle", value: 1 })).not.toBe(shapeKey({ type: "Circle", value: 0 }));
+  });
+
+  it("should throw for keys that do not match the schema", () => {
+    expect(() => canonicalKey(u8())(256)).toThrow();
+  });
+
+  it("should match the encoded bytes", () => {
+    const KeySchema = tuple(string(), u8());
+    expect(canonicalKey(KeySchema)(["a", 2])).toBe(encodedKey(serialize(KeySchema, ["a", 2])));
+    expect(encodedKey(new Uint8Array([0x00, 0x0f, 0xff]))).toBe("000fff");
+  });
+});
+
+describe.runIf(existsSync(join(FIXTURES_DIR, "cell_map.bin")))(
+  "Non-string keys written by Rust",
+  () => {
+    it("should decode integer and enum keys", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "keyed_maps.bin")));
+      const { by_id, by_color }: InferType<typeof KeyedMapsSchema> = deserialize(
+        KeyedMapsSchema,
+        data
+      ).value;
+
+      expect(by_id.get(4294967295)).toBe("max");
+      expect(by_id.get(300)).toBe("three hundred");
+      expect(mapGet(by_color, { type: "Green" })).toBe(900);
+      expect(mapHas(by_color, { type: "Blue" })).toBe(false);
+    });
+
+    it("should decode bigint, signed, bool, char and newtype keys", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "wide_keys.bin")));
+      const keys = deserialize(WideKeysSchema, data).value;
+
+      expect(keys.by_serial.get(18446744073709551615n)).toBe("last");
+      expect(Array.from(keys.by_offset.keys())).toEqual([-32768, -1, 0, 32767]);
+      expect(keys.by_flag.get(true)).toBe(1);
+      expect(keys.by_letter.get("🦀")).toBe(3);
+      expect(keys.by_sensor.get(65535)).toBe(-4.25);
+      expect(serialize(WideKeysSchema, keys)).toEqual(data);
+    });
+
+    it("should decode None keys as null", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "slot_map.bin")));
+      const slots = deserialize(SlotMapSchema, data).value;
+
+      expect(Array.from(slots.keys())).toEqual([null, 0, 1]);
+      expect(slots.get(null)).toBe("spare");
+    });
+
+    it("should decode tuple keys in BTreeMap order", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "cell_map.bin")));
+      const cells = deserialize(CellMapSchema, data).value;
+
+      expect(Array.from(cells.keys())).toEqual([
+        [0, 0],
+        [0, 1],
+        [2, 255],
+      ]);
+      expect(mapGet(cells, [2, 255])).toEqual({ type: "Green" });
+      expect(serialize(CellMapSchema, cells)).toEqual(data);
+    });
+
+    it("should index decoded tuple keys by canonical string", () => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "cell_map.bin")));
+      const cells = deserialize(CellMapSchema, data).value;
+      const cellKey = canonicalKey(CellMapSchema.key);
+      const byCell = new Map(Array.from(cells, ([cell, color]) => [cellKey(cell), color]));
+
+      expect(byCell.size).toBe(3);
+      expect(byCell.get(cellKey([2, 255]))).toEqual({ type: "Green" });
+    });
+
+    it("should accept keys built by hand when encoding", () => {
+      const cells: InferType<typeof CellMapSchema> = new Map([
+        [[0, 0], { type: "Blue" }],
+        [[0, 1], { type: "Red" }],
+        [[2, 255], { type: "Green" }],
+      ]);
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "cell_map.bin")));
+      expect(serialize(CellMapSchema, cells)).toEqual(data);
+    });
+  }
+);
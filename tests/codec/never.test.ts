@@ -0,0 +1,76 @@
+/**
+ * Tests for uninhabited types (Infallible)
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  trySerialize,
+  enumType,
+  newtypeVariant,
+  never,
+  string,
+  u32,
+  DeserializeError,
+  SerializeError,
+  type InferType,
+} from "../../src/index.js";
+
+const ReplySchema = enumType("Result", {
+  Ok: newtypeVariant("Ok", u32()),
+  Err: newtypeVariant("Err", never()),
+});
+
+type Reply = InferType<typeof ReplySchema>;
+
+describe("never()", () => {
+  it("should fail to decode any bytes", () => {
+    const result = tryDeserialize(never(), new Uint8Array([0x00]));
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error).toBeInstanceOf(DeserializeError);
+    }
+  });
+
+  it("should fail to encode", () => {
+    const result = trySerialize(never(), undefined as never);
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error).toBeInstanceOf(SerializeError);
+    }
+  });
+
+  describe("in enum variants", () => {
+    it("should drop the uninhabited variant from the inferred union", () => {
+      const reply: Reply = { type: "Ok", value: 5 };
+      const _verify: "Ok" = reply.type;
+      void _verify;
+
+      // @ts-expect-error - Err cannot hold a value
+      const _invalid: Reply = { type: "Err", value: undefined };
+      void _invalid;
+    });
+
+    it("should round-trip the inhabited variant", () => {
+      const data = serialize(ReplySchema, { type: "Ok", value: 300 });
+      expect(data).toEqual(new Uint8Array([0x00, 0xac, 0x02]));
+      expect(deserialize(ReplySchema, data).value).toEqual({ type: "Ok", value: 300 });
+    });
+
+    it("should fail to decode the uninhabited variant", () => {
+      const result = tryDeserialize(ReplySchema, new Uint8Array([0x01, 0x00]));
+      expect(result.ok).toBe(false);
+    });
+
+    it("should leave other variants with payloads unaffected", () => {
+      const MixedSchema = enumType("Mixed", {
+        Text: newtypeVariant("Text", string()),
+        Impossible: newtypeVariant("Impossible", never()),
+      });
+      const value: InferType<typeof MixedSchema> = { type: "Text", value: "ok" };
+      expect(deserialize(MixedSchema, serialize(MixedSchema, value)).value).toEqual(value);
+    });
+  });
+});
@@ -0,0 +1,91 @@
+/**
+ * Tests for telling the variant of an enum frame without decoding it
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  decodeOnly,
+  discriminated,
+  enumType,
+  f32,
+  lazy,
+  newtypeVariant,
+  peekVariant,
+  serialize,
+  struct,
+  structVariant,
+  u16,
+  u8,
+  unitVariant,
+  variantFilter,
+  type EnumVariantRecord,
+} from "../../src/index.js";
+
+const TelemetrySchema = enumType("Telemetry", {
+  Heartbeat: unitVariant("Heartbeat"),
+  Reading: newtypeVariant("Reading", f32()),
+  Alarm: structVariant("Alarm", { code: u16() }),
+});
+
+const frames = {
+  heartbeat: serialize(TelemetrySchema, { type: "Heartbeat" }),
+  reading: serialize(TelemetrySchema, { type: "Reading", value: 21.5 }),
+  alarm: serialize(TelemetrySchema, { type: "Alarm", value: { code: 3 } }),
+};
+
+describe("peekVariant", () => {
+  it("should read the variant from the index alone", () => {
+    expect(peekVariant(TelemetrySchema, frames.heartbeat)).toBe("Heartbeat");
+    expect(peekVariant(TelemetrySchema, frames.reading)).toBe("Reading");
+    // The data after the index is not looked at
+    expect(peekVariant(TelemetrySchema, new Uint8Array([2]))).toBe("Alarm");
+    expect(peekVariant(TelemetrySchema, new Uint8Array([0, 0, 2]), 2)).toBe("Alarm");
+  });
+
+  it("should give undefined for frames no variant starts", () => {
+    expect(peekVariant(TelemetrySchema, new Uint8Array([]))).toBeUndefined();
+    expect(peekVariant(TelemetrySchema, new Uint8Array([3]))).toBeUndefined();
+    expect(peekVariant(TelemetrySchema, new Uint8Array([0x80]))).toBeUndefined();
+  });
+
+  it("should look through transforms, directions and lazy references", () => {
+    const flat = discriminated(TelemetrySchema, "kind");
+    expect(peekVariant(flat, frames.alarm)).toBe("Alarm");
+    expect(peekVariant(decodeOnly(TelemetrySchema), frames.alarm)).toBe("Alarm");
+    const deferred = lazy<unknown>(() => TelemetrySchema);
+    expect(peekVariant(deferred, frames.reading)).toBe("Reading");
+  });
+
+  it("should refuse schemas other than enums", () => {
+    expect(() => peekVariant(struct({ id: u8() }), new Uint8Array([0]))).toThrow(TypeError);
+  });
+});
+
+describe("variantFilter", () => {
+  it("should keep the frames of the given variants only", () => {
+    const interesting = variantFilter(TelemetrySchema, ["Reading", "Alarm"]);
+    expect(interesting(frames.heartbeat)).toBe(false);
+    expect(interesting(frames.reading)).toBe(true);
+    expect(interesting(frames.alarm)).toBe(true);
+    expect(interesting(new Uint8Array([]))).toBe(false);
+    expect(interesting(new Uint8Array([9]))).toBe(false);
+  });
+
+  it("should read indices of more than one byte", () => {
+    const variants: EnumVariantRecord = {};
+    for (let i = 0; i < 200; i++) {
+      variants[`V${String(i)}`] = unitVariant(`V${String(i)}`);
+    }
+    const Wide = enumType("Wide", variants);
+    // 150 as a varint
+    const frame = new Uint8Array([0x96, 0x01]);
+    expect(peekVariant(Wide, frame)).toBe("V150");
+    expect(variantFilter(Wide, ["V150"])(frame)).toBe(true);
+    expect(variantFilter(Wide, ["V22"])(frame)).toBe(false);
+  });
+
+  it("should refuse variants the enum does not have", () => {
+    // @ts-expect-error - Telemetry has no Status variant
+    expect(() => variantFilter(TelemetrySchema, ["Status"])).toThrow(RangeError);
+  });
+});
@@ -0,0 +1,124 @@
+/**
+ * Tests for recursive schemas built with lazy()
+ */
+
+import { describe, it, expect } from "vitest";
+import { readFileSync } from "fs";
+import { join } from "path";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  lazy,
+  enumType,
+  newtypeVariant,
+  tupleVariant,
+  seq,
+  struct,
+  string,
+  i32,
+  u32,
+  type InferType,
+  type Schema,
+} from "../../src/index.js";
+import { TreeNodeSchema, type TreeNode } from "../fixtures/schemas.js";
+
+const leaf = (label: string, value: number): TreeNode => ({
+  label,
+  value,
+  left: null,
+  right: null,
+});
+
+describe("lazy()", () => {
+  it("should encode Option<Box<T>> exactly like the inner type", () => {
+    const tree: TreeNode = { ...leaf("root", 1), left: leaf("child", 2) };
+    // Some(child) is a 0x01 tag followed by the child, with no extra framing for the Box
+    expect(serialize(TreeNodeSchema, tree)).toEqual(
+      new Uint8Array([
+        ...serialize(string(), "root"),
+        ...serialize(u32(), 1),
+        0x01,
+        ...serialize(TreeNodeSchema, leaf("child", 2)),
+        0x00,
+      ])
+    );
+  });
+
+  it("should round trip a tree", () => {
+    const tree: TreeNode = {
+      ...leaf("root", 1),
+      left: { ...leaf("left", 2), left: leaf("left-left", 3) },
+      right: { ...leaf("right", 4), right: leaf("right-right", 5) },
+    };
+    expect(deserialize(TreeNodeSchema, serialize(TreeNodeSchema, tree)).value).toEqual(tree);
+  });
+
+  it("should infer the declared type", () => {
+    const decoded: InferType<typeof TreeNodeSchema> = leaf("root", 1);
+    const _verify: TreeNode | null = decoded.left;
+    void _verify;
+  });
+
+  it("should support recursion through enums and sequences", () => {
+    type Expr =
+      | { type: "Literal"; value: number }
+      | { type: "Add"; value: [Expr, Expr] }
+      | { type: "Sum"; value: Expr[] };
+
+    const ExprSchema = enumType("Expr", {
+      Literal: newtypeVariant("Literal", i32()),
+      Add: tupleVariant(
+        "Add",
+        lazy<Expr>((): Schema => ExprSchema),
+        lazy<Expr>((): Schema => ExprSchema)
+      ),
+      Sum: newtypeVariant("Sum", seq(lazy<Expr>((): Schema => ExprSchema))),
+    });
+
+    const expr: Expr = {
+      type: "Add",
+      value: [
+        { type: "Literal", value: 1 },
+        {
+          type: "Sum",
+          value: [
+            { type: "Literal", value: 2 },
+            { type: "Literal", value: -3 },
+          ],
+        },
+      ],
+    };
+    expect(deserialize(ExprSchema, serialize(ExprSchema, expr)).value).toEqual(expr);
+  });
+
+  it("should resolve the schema only when used", () => {
+    let resolved = 0;
+    const schema = struct({
+      name: string(),
+      next: lazy<string>(() => {
+        resolved++;
+        return string();
+      }),
+    });
+    expect(resolved).toBe(0);
+    serialize(schema, { name: "a", next: "b" });
+    expect(resolved).toBe(1);
+  });
+
+  it("should fail cleanly on truncated recursive data", () => {
+    const data = serialize(TreeNodeSchema, { ...leaf("root", 1), left: leaf("child", 2) });
+    expect(tryDeserialize(TreeNodeSchema, data.subarray(0, data.length - 3)).ok).toBe(false);
+  });
+
+  it("should decode the three-level tree from Rust", () => {
+    const data = new Uint8Array(
+      readFileSync(join(process.cwd(), "test-fixtures", "fixtures", "tree.bin"))
+    );
+    const tree = deserialize(TreeNodeSchema, data).value;
+    expect(tree.label).toBe("root");
+    expect(tree.left?.left?.label).toBe("left-left");
+    expect(tree.left?.right).toBeNull();
+    expect(tree.right?.right?.value).toBe(5);
+  });
+});
@@ -0,0 +1,114 @@
+/**
+ * Tests for verifying decodes by re-encoding
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  decodeOnly,
+  transform,
+  struct,
+  map,
+  seq,
+  string,
+  u8,
+  u16,
+  DeserializeError,
+  ErrorCode,
+  ReencodeMismatchError,
+} from "../../src/index.js";
+
+const ReadingSchema = struct({
+  sensor: u8(),
+  label: string(),
+  samples: seq(u16()),
+});
+
+// Decodes tenths of a degree to degrees, but forgets to scale them back
+const Lossy = struct({
+  sensor: u8(),
+  celsius: transform(u8(), {
+    decode: (tenths: number) => tenths / 10,
+    encode: (celsius: number) => Math.round(celsius),
+  }),
+});
+
+describe("deserialize() with verify", () => {
+  it("should decode values that re-encode to their bytes", () => {
+    const reading = { sensor: 3, label: "boiler", samples: [1, 300, 65535] };
+    const data = serialize(ReadingSchema, reading);
+    expect(deserialize(ReadingSchema, data, 0, { verify: true }).value).toEqual(reading);
+
+    // Only the value's own bytes are compared
+    const framed = new Uint8Array([0xff, ...data, 0xff]);
+    const { value, bytesRead } = deserialize(ReadingSchema, framed, 1, {
+      verify: true,
+      freeze: true,
+    });
+    expect(value).toEqual(reading);
+    expect(bytesRead).toBe(data.length);
+    expect(Object.isFrozen(value)).toBe(true);
+  });
+
+  it("should throw at the first byte that re-encodes differently", () => {
+    const data = new Uint8Array([0xff, 7, 215]);
+    expect(deserialize(Lossy, data, 1).value).toEqual({ sensor: 7, celsius: 21.5 });
+
+    let error: unknown;
+    try {
+      deserialize(Lossy, data, 1, { verify: true, root: "Lossy" });
+    } catch (e) {
+      error = e;
+    }
+    expect(error).toBeInstanceOf(ReencodeMismatchError);
+    expect(error).toBeInstanceOf(DeserializeError);
+    const mismatch = error as ReencodeMismatchError;
+    expect(mismatch.message).toBe(
+      "Lossy at byte 2: Value read from byte 1 re-encodes to other bytes: 0x16 instead of 0xd7 " +
+        "(2 bytes instead of 2)"
+    );
+    expect(mismatch.code).toBe(ErrorCode.InvalidValue);
+    expect(mismatch.offset).toBe(2);
+    expect(mismatch.path).toBe("Lossy");
+    expect(mismatch.mismatch).toEqual({ expected: "0xd7", found: "0x16" });
+    expect(mismatch.expected).toEqual(new Uint8Array([7, 215]));
+    expect(mismatch.actual).toEqual(new Uint8Array([7, 22]));
+  });
+
+  it("should return the mismatch from tryDeserialize without throwing", () => {
+    const result = tryDeserialize(Lossy, new Uint8Array([7, 215]), 0, { verify: true });
+    expect(result.ok).toBe(false);
+    const error = !result.ok ? result.error : undefined;
+    expect(error).toBeInstanceOf(ReencodeMismatchError);
+    expect(error?.offset).toBe(1);
+    expect(error?.path).toBe("");
+
+    // Frozen values are verified the same way
+    const frozen = tryDeserialize(Lossy, new Uint8Array([7, 215]), 0, {
+      verify: true,
+      freeze: true,
+    });
+    expect(!frozen.ok && frozen.error).toBeInstanceOf(ReencodeMismatchError);
+  });
+
+  it("should throw for input postcard never writes", () => {
+    // The repeated key decodes to one entry, which re-encodes shorter
+    const data = new Uint8Array([2, 1, 10, 1, 20]);
+    const Schema = map(u8(), u8());
+    expect(deserialize(Schema, data).value).toEqual(new Map([[1, 20]]));
+    expect(() => deserialize(Schema, data, 0, { verify: true })).toThrow(
+      "At byte 0: Value read from byte 0 re-encodes to other bytes: 0x01 instead of 0x02 " +
+        "(3 bytes instead of 5)"
+    );
+  });
+
+  it("should leave decode-only schemas unverified", () => {
+    const data = new Uint8Array([7, 215]);
+    expect(deserialize(decodeOnly(Lossy), data, 0, { verify: true }).value).toEqual({
+      sensor: 7,
+      celsius: 21.5,
+    });
+  });
+});
@@ -0,0 +1,130 @@
+/**
+ * Tests for salvaging the records of a batch that does not decode
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  salvage,
+  serialize,
+  DecodeStats,
+  ErrorCode,
+  encodeOnly,
+  enumType,
+  unitVariant,
+  struct,
+  seq,
+  string,
+  u8,
+  u16,
+  u32,
+  type InferType,
+} from "../../src/index.js";
+
+const LevelSchema = enumType("Level", {
+  Info: unitVariant("Info"),
+  Warn: unitVariant("Warn"),
+  Error: unitVariant("Error"),
+});
+
+const EntrySchema = struct({ at: u32(), level: LevelSchema });
+
+const LogBatchSchema = struct({
+  device: u8(),
+  entries: seq(EntrySchema),
+  checksum: u16(),
+});
+type LogBatch = InferType<typeof LogBatchSchema>;
+
+const batch: LogBatch = {
+  device: 7,
+  entries: [
+    { at: 1, level: { type: "Info" } },
+    { at: 2, level: { type: "Warn" } },
+    { at: 300, level: { type: "Error" } },
+  ],
+  checksum: 0x1234,
+};
+
+// 07 | 03 | 01 00 | 02 01 | ac 02 02 | b4 24
+const encoded = serialize(LogBatchSchema, batch);
+
+function corrupt(index: number, byte: number): Uint8Array {
+  const data = encoded.slice();
+  data[index] = byte;
+  return data;
+}
+
+describe("salvage", () => {
+  it("should return the whole batch when it decodes", () => {
+    const salvaged = salvage(LogBatchSchema, "entries", encoded);
+    expect(salvaged).toEqual({
+      value: batch,
+      records: batch.entries,
+      error: undefined,
+      offset: encoded.length,
+    });
+  });
+
+  it("should keep the records before a corrupt one", () => {
+    const salvaged = salvage(LogBatchSchema, "entries", corrupt(5, 0x09));
+    expect(salvaged.value).toBeUndefined();
+    expect(salvaged.records).toEqual(batch.entries.slice(0, 1));
+    expect(salvaged.offset).toBe(5);
+    expect(salvaged.error?.code).toBe(ErrorCode.InvalidValue);
+    expect(salvaged.error?.path).toBe("entries[1].level");
+  });
+
+  it("should keep the records before the end of a cut off batch", () => {
+    const salvaged = salvage(LogBatchSchema, "entries", encoded.subarray(0, 8));
+    expect(salvaged.records).toEqual(batch.entries.slice(0, 2));
+    expect(salvaged.offset).toBe(8);
+    expect(salvaged.error?.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+
+  it("should keep every record when only a field after the list fails", () => {
+    const salvaged = salvage(LogBatchSchema, "entries", encoded.subarray(0, 10));
+    expect(salvaged.value).toBeUndefined();
+    expect(salvaged.records).toEqual(batch.entries);
+    expect(salvaged.offset).toBe(9);
+    expect(salvaged.error?.path).toBe("checksum");
+  });
+
+  it("should salvage nothing when the list length does not decode", () => {
+    const malformed = new Uint8Array([0x07, ...new Array<number>(10).fill(0xff)]);
+    const salvaged = salvage(LogBatchSchema, "entries", malformed);
+    expect(salvaged.records).toEqual([]);
+    expect(salvaged.offset).toBe(1);
+    expect(salvaged.error?.code).toBe(ErrorCode.BadVarint);
+
+    const tooMany = salvage(LogBatchSchema, "entries", encoded, 0, { limits: { maxLength: 2 } });
+    expect(tooMany.records).toEqual([]);
+    expect(tooMany.offset).toBe(1);
+  });
+
+  it("should decode a batch at an offset, counting it once in the stats", () => {
+    const data = new Uint8Array([0xee, 0xee, ...corrupt(8, 0x05)]);
+    const stats = new DecodeStats();
+    const salvaged = salvage(LogBatchSchema, "entries", data, 2, { stats, root: "LogBatch" });
+    expect(salvaged.records).toEqual(batch.entries.slice(0, 2));
+    expect(salvaged.offset).toBe(10);
+    expect(salvaged.error?.path).toBe("LogBatch.entries[2].level");
+    expect(stats.decodes).toBe(1);
+    expect(stats.failures).toBe(1);
+  });
+
+  it("should freeze the records it salvages when asked to", () => {
+    const salvaged = salvage(LogBatchSchema, "entries", corrupt(5, 0x09), 0, { freeze: true });
+    expect(Object.isFrozen(salvaged.records)).toBe(true);
+    expect(Object.isFrozen(salvaged.records[0])).toBe(true);
+  });
+
+  it("should only salvage the lists of structs it can decode", () => {
+    const Names = struct({ names: seq(string()), count: u8() });
+    expect(() => salvage(Names, "count" as "names", encoded)).toThrow(TypeError);
+    expect(() => salvage(seq(EntrySchema) as never, "entries" as never, encoded)).toThrow(
+      TypeError
+    );
+    const Outgoing = encodeOnly(LogBatchSchema);
+    expect(() => salvage(Outgoing as never, "entries" as never, encoded)).toThrow(TypeError);
+  });
+});
@@ -0,0 +1,51 @@
+/**
+ * Tests for schema hash handshakes
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  checkSchemaHashes,
+  tryCheckSchemaHashes,
+  SchemaHashError,
+  SchemaHashesSchema,
+  serialize,
+  deserialize,
+} from "../../src/index.js";
+
+// As the generator emits them for the types in generator/tests/schema_hash.rs
+const ReadingSchemaHash = 0xed07686b2b9034f9n;
+const CommandSchemaHash = 0xe85a523bb379a530n;
+
+const LOCAL = { Reading: ReadingSchemaHash, Command: CommandSchemaHash };
+
+describe("checkSchemaHashes", () => {
+  it("should accept a peer with the same hashes", () => {
+    const received = new Map([
+      ["Reading", ReadingSchemaHash],
+      ["Command", CommandSchemaHash],
+    ]);
+    expect(tryCheckSchemaHashes(LOCAL, received)).toEqual({ ok: true, value: undefined });
+  });
+
+  it("should ignore types only the peer knows", () => {
+    expect(() => checkSchemaHashes(LOCAL, { ...LOCAL, Status: 1n })).not.toThrow();
+  });
+
+  it("should list types whose hashes differ or are missing", () => {
+    const result = tryCheckSchemaHashes(LOCAL, { Reading: 1n });
+    expect(!result.ok && result.error.mismatches).toEqual([
+      { type: "Reading", expected: ReadingSchemaHash, received: 1n },
+      { type: "Command", expected: CommandSchemaHash, received: undefined },
+    ]);
+    expect(() => checkSchemaHashes(LOCAL, { Reading: 1n })).toThrow(SchemaHashError);
+    expect(() => checkSchemaHashes(LOCAL, { Reading: 1n })).toThrow(
+      "Reading (expected 0xed07686b2b9034f9, received 0x0000000000000001), Command (missing)"
+    );
+  });
+
+  it("should read hashes sent as a BTreeMap<String, u64>", () => {
+    const bytes = serialize(SchemaHashesSchema, new Map(Object.entries(LOCAL)));
+    const { value } = deserialize(SchemaHashesSchema, bytes);
+    expect(() => checkSchemaHashes(LOCAL, value)).not.toThrow();
+  });
+});
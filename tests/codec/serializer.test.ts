@@ -613,6 +613,45 @@ describe("Serialization", () => {
     });
   });
 
+  describe("f32 rounding", () => {
+    it("should write the nearest f32 and re-encode decoded values byte for byte", () => {
+      const schema = struct({ x: f32(), y: option(f32()) });
+      const original = serialize(schema, { x: 0.1, y: 21.7 });
+      const decoded = deserialize(schema, original).value;
+      expect(decoded.x).toBe(Math.fround(0.1));
+      expect(serialize(schema, decoded)).toEqual(original);
+    });
+
+    it("should report only rounding beyond the tolerance", () => {
+      const schema = seq(f32());
+      const rounded: [number, number][] = [];
+      const onF32Rounding = (value: number, to: number) => rounded.push([value, to]);
+
+      serialize(schema, [0.1, 1e39, 1e-46, NaN, Math.fround(0.1)], { onF32Rounding });
+      expect(rounded).toEqual([
+        [1e39, Infinity],
+        [1e-46, 0],
+      ]);
+
+      rounded.length = 0;
+      serialize(schema, [0.1, 0.5], { onF32Rounding, f32Tolerance: 0 });
+      expect(rounded).toEqual([[0.1, Math.fround(0.1)]]);
+    });
+
+    it("should pass the options through nested schemas", () => {
+      const schema = enumType("Command", {
+        SetTarget: newtypeVariant("SetTarget", struct({ value: f32() })),
+      });
+      let reported = 0;
+      serialize(
+        schema,
+        { type: "SetTarget", value: { value: 3.4e39 } },
+        { onF32Rounding: () => reported++ }
+      );
+      expect(reported).toBe(1);
+    });
+  });
+
   describe("Error condition tests", () => {
     it("should error on i8 underflow", () => {
       const schema = i8();
@@ -671,6 +710,25 @@ describe("Serialization", () => {
       }
     });
 
+    it("should error on a number for u64", () => {
+      const schema = u64();
+      const result = trySerialize(schema, 42 as any);
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(SerializeError);
+        expect(result.error.message).toContain("Expected a bigint for u64");
+      }
+    });
+
+    it("should error on a number for i128", () => {
+      const schema = i128();
+      const result = trySerialize(schema, -1 as any);
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(SerializeError);
+      }
+    });
+
     it("should error on char with multiple codepoints", () => {
       const schema = char();
       const result = trySerialize(schema, "ab");
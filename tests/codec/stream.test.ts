@@ -0,0 +1,190 @@
+/**
+ * Tests for decoding values from a chunked byte stream
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  StreamDecoder,
+  DeserializeError,
+  ErrorCode,
+  deserialize,
+  serialize,
+  enumType,
+  newtypeVariant,
+  string,
+  struct,
+  seq,
+  u8,
+  u32,
+  u64,
+  unit,
+  unitVariant,
+  type InferType,
+} from "../../src/index.js";
+import { EdgeCasesSchema } from "../fixtures/schemas.js";
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+const MessageSchema = enumType("Message", {
+  Ping: unitVariant("Ping"),
+  Text: newtypeVariant("Text", string()),
+  Samples: newtypeVariant("Samples", struct({ at: u64(), values: seq(u32()) })),
+});
+type Message = InferType<typeof MessageSchema>;
+
+const MESSAGES: Message[] = [
+  { type: "Text", value: "héllo".repeat(40) },
+  { type: "Ping" },
+  { type: "Samples", value: { at: 2n ** 40n, values: [0, 127, 128, 300000, 4294967295] } },
+  { type: "Text", value: "" },
+];
+
+function concat(parts: Uint8Array[]): Uint8Array {
+  const out = new Uint8Array(parts.reduce((sum, part) => sum + part.length, 0));
+  let offset = 0;
+  for (const part of parts) {
+    out.set(part, offset);
+    offset += part.length;
+  }
+  return out;
+}
+
+function chunks(data: Uint8Array, size: number): Uint8Array[] {
+  const out: Uint8Array[] = [];
+  for (let i = 0; i < data.length; i += size) {
+    out.push(data.subarray(i, i + size));
+  }
+  return out;
+}
+
+describe("StreamDecoder", () => {
+  const stream = concat(MESSAGES.map((message) => serialize(MessageSchema, message)));
+
+  it.each([1, 2, 3, 7, 64, stream.length])("should decode values in %i-byte chunks", (size) => {
+    const decoder = new StreamDecoder(MessageSchema);
+    const decoded: Message[] = [];
+    for (const chunk of chunks(stream, size)) {
+      decoder.push(chunk);
+      decoded.push(...decoder);
+    }
+
+    expect(decoded).toEqual(MESSAGES);
+    expect(decoder.buffered).toBe(0);
+  });
+
+  it("should wait for the rest of a value", () => {
+    const decoder = new StreamDecoder(MessageSchema);
+    const text = serialize(MessageSchema, { type: "Text", value: "abc" });
+
+    decoder.push(text.subarray(0, 3));
+    expect(decoder.next()).toBeUndefined();
+    expect(decoder.buffered).toBe(3);
+
+    decoder.push(text.subarray(3));
+    expect(decoder.next()).toEqual({ type: "Text", value: "abc" });
+    expect(decoder.next()).toBeUndefined();
+  });
+
+  it("should copy pushed chunks", () => {
+    const decoder = new StreamDecoder(u32());
+    const chunk = new Uint8Array([0x80]);
+    decoder.push(chunk);
+    chunk[0] = 0xff;
+    decoder.push(new Uint8Array([0x01]));
+    expect(decoder.next()).toBe(128);
+  });
+
+  it("should stop at bytes that cannot be decoded until reset", () => {
+    const decoder = new StreamDecoder(MessageSchema);
+    decoder.push(concat([serialize(MessageSchema, { type: "Ping" }), new Uint8Array([9, 0])]));
+
+    const decoded: Message[] = [];
+    expect(() => {
+      for (const message of decoder) {
+        decoded.push(message);
+      }
+    }).toThrow(DeserializeError);
+    expect(decoded).toEqual([{ type: "Ping" }]);
+
+    const failure = decoder.tryNext();
+    expect(!failure.ok && failure.error.code).toBe(ErrorCode.InvalidValue);
+
+    decoder.reset();
+    expect(decoder.buffered).toBe(0);
+    decoder.push(serialize(MessageSchema, { type: "Ping" }));
+    expect(decoder.next()).toEqual({ type: "Ping" });
+  });
+
+  it("should drain complete values, then the error, without throwing", () => {
+    const decoder = new StreamDecoder(MessageSchema);
+    const ping = serialize(MessageSchema, { type: "Ping" });
+    decoder.push(concat([ping, ping, new Uint8Array([9, 0])]));
+
+    expect(decoder.tryDrain()).toEqual({ ok: true, value: [{ type: "Ping" }, { type: "Ping" }] });
+    const failure = decoder.tryDrain();
+    expect(!failure.ok && failure.error.code).toBe(ErrorCode.InvalidValue);
+
+    decoder.reset();
+    expect(decoder.tryDrain()).toEqual({ ok: true, value: [] });
+  });
+
+  it("should give up on incomplete values larger than maxBuffered", () => {
+    const decoder = new StreamDecoder(string(), { maxBuffered: 8 });
+    // Length prefix of 1000 bytes
+    decoder.push(new Uint8Array([0xe8, 0x07, 0x61, 0x62]));
+    expect(decoder.next()).toBeUndefined();
+
+    decoder.push(new Uint8Array(8));
+    const result = decoder.tryNext();
+    expect(!result.ok && result.error.code).toBe(ErrorCode.BufferFull);
+    expect(() => new StreamDecoder(u8(), { maxBuffered: 0 })).toThrow(RangeError);
+  });
+
+  it("should reject values that take no bytes", () => {
+    const decoder = new StreamDecoder(unit());
+    expect(decoder.next()).toBeUndefined();
+
+    decoder.push(new Uint8Array([0]));
+    const result = decoder.tryNext();
+    expect(!result.ok && result.error.code).toBe(ErrorCode.Unsupported);
+  });
+
+  it("should decode a large value pushed byte by byte only once", () => {
+    let decoded = 0;
+    const decoder = new StreamDecoder(MessageSchema, { trace: () => decoded++ });
+    const values = Array.from({ length: 5000 }, (_, i) => i * 1000);
+    const samples = serialize(MessageSchema, { type: "Samples", value: { at: 1n, values } });
+
+    let message: Message | undefined;
+    for (const chunk of chunks(samples, 1)) {
+      decoder.push(chunk);
+      message = decoder.next() ?? message;
+    }
+    expect(message).toEqual({ type: "Samples", value: { at: 1n, values } });
+    // Each item, the seq, `at`, the struct and the enum, decoded once each
+    expect(decoded).toBe(values.length + 4);
+  });
+
+  it("should pass deserialize options on", () => {
+    const decoder = new StreamDecoder(seq(u8()), { freeze: true });
+    decoder.push(new Uint8Array([2, 1, 2]));
+    expect(Object.isFrozen(decoder.next())).toBe(true);
+  });
+});
+
+describe.runIf(existsSync(join(FIXTURES_DIR, "edge_cases.bin")))("Streams of Rust values", () => {
+  it("should decode back-to-back values byte by byte", () => {
+    const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, "edge_cases.bin")));
+    const expected = deserialize(EdgeCasesSchema, data).value;
+
+    const decoder = new StreamDecoder(EdgeCasesSchema);
+    const decoded: unknown[] = [];
+    for (const chunk of chunks(concat([data, data, data]), 1)) {
+      decoder.push(chunk);
+      decoded.push(...decoder);
+    }
+    expect(decoded).toEqual([expected, expected, expected]);
+  });
+});
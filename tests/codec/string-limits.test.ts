@@ -0,0 +1,126 @@
+/**
+ * Tests for length-limited strings (`#[postcard_ts(max_len = N)]`, heapless::String<N>)
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DynamicDecoder,
+  DynamicEncoder,
+  ErrorCode,
+  IrError,
+  SerializeError,
+  DeserializeError,
+  errorCode,
+  option,
+  serialize,
+  string,
+  struct,
+  truncateUtf8,
+  tryDeserialize,
+  trySerialize,
+} from "../../src/index.js";
+
+const LabelSchema = struct({
+  name: string({ maxBytes: 16 }),
+  unit: option(string({ maxBytes: 4 })),
+});
+
+const TruncatingSchema = struct({
+  name: string({ maxBytes: 6, overflow: "truncate" }),
+});
+
+describe("truncateUtf8", () => {
+  it("should leave strings that fit alone", () => {
+    expect(truncateUtf8("abc", 3)).toEqual({ text: "abc", truncated: false });
+    expect(truncateUtf8("", 0)).toEqual({ text: "", truncated: false });
+  });
+
+  it("should cut ASCII at the limit", () => {
+    expect(truncateUtf8("abcdef", 4)).toEqual({ text: "abcd", truncated: true });
+    expect(truncateUtf8("abc", 0)).toEqual({ text: "", truncated: true });
+  });
+
+  it("should never split a character", () => {
+    // ° is 2 bytes, 🦀 is 4 bytes (two UTF-16 code units)
+    expect(truncateUtf8("°C", 2)).toEqual({ text: "°", truncated: true });
+    expect(truncateUtf8("°C", 1)).toEqual({ text: "", truncated: true });
+    expect(truncateUtf8("a🦀", 4)).toEqual({ text: "a", truncated: true });
+    expect(truncateUtf8("a🦀b", 5)).toEqual({ text: "a🦀", truncated: true });
+  });
+});
+
+describe("maxBytes", () => {
+  it("should encode strings within the limit like plain strings", () => {
+    const value = { name: "Kühlraum 2 🦀", unit: "°C" };
+    expect(serialize(LabelSchema, value)).toEqual(
+      serialize(struct({ name: string(), unit: option(string()) }), value)
+    );
+  });
+
+  it("should refuse longer strings by default", () => {
+    const result = trySerialize(LabelSchema, { name: "Kühlraum 2 🦀!", unit: null });
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(SerializeError);
+    expect(errorCode(error)).toBe(ErrorCode.SerializeFailed);
+    expect((error as SerializeError).message).toContain("String of 17 bytes exceeds maxBytes (16)");
+  });
+
+  it("should truncate and report when asked", () => {
+    const reports: [string, string][] = [];
+    const bytes = serialize(
+      TruncatingSchema,
+      { name: "Grüße!" },
+      { onStringTruncated: (value, truncated) => reports.push([value, truncated]) }
+    );
+
+    // "Grüße!" is 8 bytes; "Grüß" is the 6 that fit
+    expect(bytes).toEqual(new Uint8Array([6, 0x47, 0x72, 0xc3, 0xbc, 0xc3, 0x9f]));
+    expect(reports).toEqual([["Grüße!", "Grüß"]]);
+  });
+
+  it("should reject longer strings when decoding", () => {
+    const bytes = serialize(struct({ name: string(), unit: option(string()) }), {
+      name: "ok",
+      unit: "Kelvin",
+    });
+    const result = tryDeserialize(LabelSchema, bytes);
+    expect(!result.ok && result.error).toBeInstanceOf(DeserializeError);
+    expect(!result.ok && result.error.message).toContain("String of 6 bytes exceeds maxBytes (4)");
+  });
+
+  it("should refuse invalid limits", () => {
+    expect(() => string({ maxBytes: -1 })).toThrow(RangeError);
+    expect(() => string({ maxBytes: 1.5 })).toThrow(RangeError);
+  });
+});
+
+describe("maxBytes in the IR", () => {
+  const ir = {
+    version: 1,
+    types: [
+      {
+        name: "Label",
+        kind: "struct",
+        fields: [{ name: "name", type: { kind: "string", maxBytes: 4 } }],
+      },
+    ],
+  };
+
+  it("should check the limit", () => {
+    const encoder = new DynamicEncoder(ir);
+    expect(encoder.validate("Label", { name: "°C" })).toEqual([]);
+    expect(encoder.validate("Label", { name: "Kelvin" })).toEqual([
+      { path: "Label.name", message: "6 bytes exceed maxBytes (4)" },
+    ]);
+    const frame = new Uint8Array([5, 0x61, 0x62, 0x63, 0x64, 0x65]);
+    expect(() => new DynamicDecoder(ir).decode("Label", frame)).toThrow(DeserializeError);
+  });
+
+  it("should reject invalid limits", () => {
+    const invalid = {
+      version: 1,
+      types: [{ name: "Label", kind: "newtype_struct", inner: { kind: "string", maxBytes: -1 } }],
+    };
+    expect(() => new DynamicEncoder(invalid)).toThrow(IrError);
+  });
+});
@@ -202,6 +202,14 @@ describe("Varint encoding", () => {
       const result = encodeVarintU128(340282366920938463463374607431768211455n);
       expect(result.bytesWritten).toBe(19);
     });
+
+    it("should encode above 2^53 without losing bits", () => {
+      const result = encodeVarintU128((1n << 100n) + 1n);
+      expect(result.bytes).toEqual(
+        new Uint8Array([0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x04])
+      );
+      expect(decodeVarintU128(result.bytes).value).toBe((1n << 100n) + 1n);
+    });
   });
 
   describe("Signed integers with zigzag encoding (i16)", () => {
@@ -471,6 +479,23 @@ describe("Varint encoding", () => {
         expect(result.error).toBeInstanceOf(VarintEncodeError);
       }
     });
+
+    it("should return error for numbers where a bigint is expected", () => {
+      const number = 9007199254740993 as unknown as bigint;
+      for (const encode of [
+        tryEncodeVarintU64,
+        tryEncodeVarintU128,
+        tryEncodeVarintI64,
+        tryEncodeVarintI128,
+      ]) {
+        const result = encode(number);
+        expect(result.ok).toBe(false);
+        if (!result.ok) {
+          expect(result.error).toBeInstanceOf(VarintEncodeError);
+          expect(result.error.message).toMatch(/Expected a bigint for [ui](64|128), got number/);
+        }
+      }
+    });
   });
 
   describe("Error handling - Throwing API", () => {
@@ -552,7 +577,15 @@ describe("Varint encoding", () => {
     });
 
     it("should round-trip i128 values", () => {
-      const values = [-123456789012345678901234567890n, -1n, 0n, 1n, 123456789012345678901234567890n];
+      const values = [
+        -(1n << 127n),
+        -123456789012345678901234567890n,
+        -1n,
+        0n,
+        1n,
+        123456789012345678901234567890n,
+        (1n << 127n) - 1n,
+      ];
       for (const value of values) {
         const encoded = encodeVarintI128(value);
         const decoded = decodeVarintI128(encoded.bytes);
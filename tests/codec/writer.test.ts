@@ -0,0 +1,201 @@
+/**
+ * Tests for Writer, WriterPool and serializing into a reused writer
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  SerializeError,
+  Writer,
+  WriterPool,
+  bytes,
+  deserialize,
+  serialize,
+  serializeInto,
+  string,
+  struct,
+  transform,
+  trySerializeInto,
+  u8,
+  u32,
+  u64,
+} from "../../src/index.js";
+
+const ReadingSchema = struct({ sensor: u8(), millis: u64(), label: string() });
+
+describe("Writer", () => {
+  it("should grow geometrically and keep what was written", () => {
+    const writer = new Writer(2);
+    writer.writeBytes(new Uint8Array([1, 2]));
+    expect(writer.capacity).toBe(2);
+
+    writer.writeByte(3);
+    expect(writer.capacity).toBe(64);
+    writer.writeBytes(new Uint8Array(100));
+    expect(writer.capacity).toBe(128);
+    expect(writer.length).toBe(103);
+    expect(writer.view().subarray(0, 4)).toEqual(new Uint8Array([1, 2, 3, 0]));
+  });
+
+  it("should keep its buffer across resets", () => {
+    const writer = new Writer(8);
+    writer.writeBytes(new Uint8Array(20));
+    const capacity = writer.capacity;
+    writer.reset();
+
+    expect(writer.length).toBe(0);
+    expect(writer.capacity).toBe(capacity);
+    writer.writeVarint(300);
+    expect(writer.toBytes()).toEqual(new Uint8Array([0xac, 0x02]));
+  });
+
+  it("should return views of the buffer and copies from toBytes", () => {
+    const writer = new Writer();
+    writer.writeByte(1);
+    const view = writer.view();
+    const copy = writer.toBytes();
+    writer.reset();
+    writer.writeByte(2);
+
+    expect(view).toEqual(new Uint8Array([2]));
+    expect(copy).toEqual(new Uint8Array([1]));
+  });
+
+  it("should keep a string written just below its capacity", () => {
+    const writer = new Writer(64);
+    writer.writeBytes(new Uint8Array(57));
+    writer.writeString("hi");
+
+    expect(writer.capacity).toBe(128);
+    expect(writer.view().subarray(57)).toEqual(new Uint8Array([2, 0x68, 0x69]));
+  });
+
+  it("should write varints past 32 bits", () => {
+    const writer = new Writer();
+    writer.writeVarint(Number.MAX_SAFE_INTEGER);
+    writer.writeVarintBigInt(2n ** 128n - 1n);
+
+    expect(writer.view()).toEqual(
+      new Uint8Array([
+        ...serialize(u64(), BigInt(Number.MAX_SAFE_INTEGER)),
+        ...[...Array<number>(18).fill(0xff), 0x03],
+      ])
+    );
+  });
+
+  it.each([
+    ["empty", ""],
+    ["ASCII", "hello"],
+    ["multi-byte", "grüße, 世界 🌍"],
+    ["long enough for a two-byte length", "x".repeat(200)],
+    ["shorter in UTF-8 than its worst case", "é".repeat(50)],
+  ])("should write %s strings as string() does", (_, text) => {
+    const writer = new Writer(0);
+    writer.writeString(text);
+    expect(writer.toBytes()).toEqual(serialize(string(), text));
+  });
+
+  it("should reject invalid capacities", () => {
+    expect(() => new Writer(-1)).toThrow(RangeError);
+    expect(() => new Writer(1.5)).toThrow(RangeError);
+  });
+});
+
+describe("serializeInto", () => {
+  it("should append to what the writer holds", () => {
+    const writer = new Writer();
+    const first = { sensor: 1, millis: 1000n, label: "a" };
+    const second = { sensor: 2, millis: 2000n, label: "bb" };
+
+    const written = serializeInto(ReadingSchema, first, writer);
+    serializeInto(ReadingSchema, second, writer);
+
+    expect(written).toBe(serialize(ReadingSchema, first).length);
+    expect(writer.toBytes()).toEqual(
+      new Uint8Array([...serialize(ReadingSchema, first), ...serialize(ReadingSchema, second)])
+    );
+  });
+
+  it("should leave the writer as it was on failure", () => {
+    const writer = new Writer();
+    writer.writeByte(7);
+
+    const result = trySerializeInto(ReadingSchema, { sensor: 1, millis: -1n, label: "" }, writer);
+
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error).toBeInstanceOf(SerializeError);
+      expect(result.error.message).toContain("struct field 'millis'");
+    }
+    expect(writer.toBytes()).toEqual(new Uint8Array([7]));
+  });
+
+  it("should round-trip through a reused writer", () => {
+    const writer = new Writer(4);
+    for (let sensor = 0; sensor < 20; sensor++) {
+      writer.reset();
+      const reading = { sensor, millis: BigInt(sensor) * 2n ** 40n, label: "x".repeat(sensor) };
+      serializeInto(ReadingSchema, reading, writer);
+      expect(deserialize(ReadingSchema, writer.view()).value).toEqual(reading);
+    }
+  });
+});
+
+describe("WriterPool", () => {
+  it("should reuse released writers", () => {
+    const pool = new WriterPool({ initialCapacity: 16 });
+    const writer = pool.acquire();
+    expect(writer.capacity).toBe(16);
+    writer.writeByte(1);
+    pool.release(writer);
+
+    expect(pool.size).toBe(1);
+    const again = pool.acquire();
+    expect(again).toBe(writer);
+    expect(again.length).toBe(0);
+  });
+
+  it("should drop writers over its limits", () => {
+    const pool = new WriterPool({ maxPooled: 1, maxRetainedCapacity: 128 });
+    const large = pool.acquire();
+    large.writeBytes(new Uint8Array(200));
+    pool.release(large);
+    expect(pool.size).toBe(0);
+
+    pool.release(pool.acquire());
+    pool.release(new Writer());
+    expect(pool.size).toBe(1);
+  });
+
+  it("should give serialize() its writers back", () => {
+    const pool = new WriterPool();
+    const first = serialize(ReadingSchema, { sensor: 1, millis: 1n, label: "a" }, { pool });
+    const second = serialize(ReadingSchema, { sensor: 2, millis: 2n, label: "b" }, { pool });
+
+    expect(pool.size).toBe(1);
+    // Each result is a copy, not a view of the pooled writer
+    expect(first).toEqual(serialize(ReadingSchema, { sensor: 1, millis: 1n, label: "a" }));
+    expect(first.buffer).not.toBe(second.buffer);
+  });
+
+  it("should give nested serialize() calls writers of their own", () => {
+    const pool = new WriterPool();
+    // A frame carrying another message encoded separately, as a tunnel would
+    const Tunnelled = transform(bytes(), {
+      decode: (data: Uint8Array) => deserialize(ReadingSchema, data).value,
+      encode: (reading: { sensor: number; millis: bigint; label: string }) =>
+        serialize(ReadingSchema, reading, { pool }),
+    });
+    const FrameSchema = struct({ seq: u32(), payload: Tunnelled });
+    const frame = { seq: 9, payload: { sensor: 3, millis: 5n, label: "inner" } };
+
+    const data = serialize(FrameSchema, frame, { pool });
+
+    expect(deserialize(FrameSchema, data).value).toEqual(frame);
+    expect(pool.size).toBe(2);
+  });
+
+  it("should reject invalid options", () => {
+    expect(() => new WriterPool({ maxPooled: -1 })).toThrow(RangeError);
+    expect(() => new WriterPool({ initialCapacity: Number.NaN })).toThrow(RangeError);
+  });
+});
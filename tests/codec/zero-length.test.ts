@@ -0,0 +1,95 @@
+/**
+ * Tests for zero-length messages and single-variant enums
+ *
+ * `()`, unit structs and structs without fields encode to zero bytes, so
+ * decoding them must succeed on an empty buffer and at the very end of one.
+ * An enum with a single variant still encodes its discriminant.
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  ErrorCode,
+  unit,
+  unitStruct,
+  struct,
+  seq,
+  tuple,
+  u8,
+  u16,
+  enumType,
+  unitVariant,
+  newtypeVariant,
+  structVariant,
+} from "../../src/index.js";
+
+const EMPTY = new Uint8Array(0);
+
+describe("Zero-length messages", () => {
+  const cases = [
+    { name: "unit", schema: unit(), value: undefined },
+    { name: "unit struct", schema: unitStruct("Heartbeat"), value: {} },
+    { name: "empty struct", schema: struct({}), value: {} },
+    { name: "empty tuple", schema: tuple(), value: [] },
+  ];
+
+  it.each(cases)("should encode $name to zero bytes", ({ schema, value }) => {
+    expect(serialize(schema, value)).toEqual(EMPTY);
+  });
+
+  it.each(cases)("should decode $name from an empty buffer", ({ schema, value }) => {
+    expect(deserialize(schema, EMPTY)).toEqual({ value, bytesRead: 0 });
+  });
+
+  it.each(cases)("should decode $name at the end of a buffer", ({ schema, value }) => {
+    const data = new Uint8Array([0x2a]);
+    expect(deserialize(schema, data, data.length)).toEqual({ value, bytesRead: 0 });
+  });
+
+  it("should encode a sequence of zero-length values as its length alone", () => {
+    const Heartbeats = seq(struct({}));
+    const data = serialize(Heartbeats, [{}, {}, {}]);
+    expect(data).toEqual(new Uint8Array([0x03]));
+    expect(deserialize(Heartbeats, data).value).toEqual([{}, {}, {}]);
+  });
+
+  it("should encode empty struct variants as their discriminant alone", () => {
+    const Command = enumType("Command", {
+      Ping: structVariant("Ping", {}),
+      Set: newtypeVariant("Set", u8()),
+    });
+    expect(serialize(Command, { type: "Ping", value: {} })).toEqual(new Uint8Array([0x00]));
+    expect(deserialize(Command, new Uint8Array([0x00])).value).toEqual({ type: "Ping", value: {} });
+  });
+});
+
+describe("Single-variant enums", () => {
+  const SingleVariant = enumType("SingleVariant", { Only: unitVariant("Only") });
+  const SingleDataVariant = enumType("SingleDataVariant", {
+    Reading: newtypeVariant("Reading", u16()),
+  });
+
+  it("should still encode the discriminant", () => {
+    expect(serialize(SingleVariant, { type: "Only" })).toEqual(new Uint8Array([0x00]));
+    expect(serialize(SingleDataVariant, { type: "Reading", value: 300 })).toEqual(
+      new Uint8Array([0x00, 0xac, 0x02])
+    );
+  });
+
+  it("should decode the only variant", () => {
+    expect(deserialize(SingleVariant, new Uint8Array([0x00]))).toEqual({
+      value: { type: "Only" },
+      bytesRead: 1,
+    });
+  });
+
+  it("should reject other discriminants and missing ones", () => {
+    const unknown = tryDeserialize(SingleVariant, new Uint8Array([0x01]));
+    expect(!unknown.ok && unknown.error.code).toBe(ErrorCode.InvalidValue);
+
+    const missing = tryDeserialize(SingleVariant, EMPTY);
+    expect(!missing.ok && missing.error.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+});
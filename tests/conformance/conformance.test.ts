@@ -0,0 +1,156 @@
+/**
+ * Conformance suite for a built runtime
+ *
+ * Runs the whole fixture corpus against the runtime in POSTCARD_RUNTIME (a
+ * directory holding its index.js, e.g. ./dist) or, by default, the sources:
+ * - positive: every manifest fixture decodes to the value Rust serialized and
+ *   re-encodes to the same bytes
+ * - negative: every error case fails with the code Rust reported
+ * - streaming: the fixtures concatenated decode back to back, each consuming
+ *   exactly its own bytes
+ * - large: multi-megabyte values round-trip
+ *
+ *   npm run build && POSTCARD_RUNTIME=./dist npm run conformance
+ *
+ * `npm run conformance` also writes a JUnit report to conformance-report.xml.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join, resolve } from "path";
+import { pathToFileURL } from "url";
+import { bytes, map, seq, string, u32, type Schema } from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS, FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+type Runtime = typeof import("../../src/index.js");
+
+const RUNTIME_DIR = process.env["POSTCARD_RUNTIME"];
+const runtime = (
+  RUNTIME_DIR === undefined
+    ? await import("../../src/index.js")
+    : await import(pathToFileURL(resolve(RUNTIME_DIR, "index.js")).href)
+) as Runtime;
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+  readonly value: unknown;
+}
+
+interface ErrorCase {
+  readonly name: string;
+  readonly type: string;
+  readonly bytes: number[];
+  readonly code: string;
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+function loadJson<T>(file: string, empty: T): T {
+  const path = join(FIXTURES_DIR, file);
+  return existsSync(path) ? (JSON.parse(readFileSync(path, "utf8")) as T) : empty;
+}
+
+const { fixtures } = loadJson<{ fixtures: ManifestEntry[] }>("manifest.json", { fixtures: [] });
+const { cases } = loadJson<{ cases: ErrorCase[] }>("error_cases.json", { cases: [] });
+
+const positive = fixtures.map((entry) => ({
+  ...entry,
+  schema: FIXTURE_SCHEMAS[entry.type],
+  data: new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file))),
+}));
+
+describe(`Conformance (${RUNTIME_DIR ?? "src"})`, () => {
+  it("should have a fixture corpus (run `npm run generate-fixtures`)", () => {
+    expect(fixtures.length).toBeGreaterThan(0);
+    expect(cases.length).toBeGreaterThan(0);
+  });
+
+  describe("positive", () => {
+    describe.each(positive)("$file ($type)", ({ type, schema, data, value }) => {
+      it("should decode to the value Rust serialized", () => {
+        if (schema === undefined) {
+          throw new Error(`No schema registered for Rust type "${type}"`);
+        }
+        const result = runtime.deserialize(schema, data);
+        expect(result.bytesRead).toBe(data.length);
+        expect(toSerdeJson(schema, result.value)).toEqual(value);
+      });
+
+      it.runIf(schema !== undefined)("should re-encode to the same bytes", () => {
+        if (schema === undefined) return;
+        const { value: decoded } = runtime.deserialize(schema, data);
+        expect(runtime.serialize(schema, decoded)).toEqual(data);
+      });
+    });
+  });
+
+  describe("negative", () => {
+    describe.each(cases)("$name ($type)", (errorCase) => {
+      it(`should fail with ${errorCase.code}`, () => {
+        const schema = ERROR_CASE_SCHEMAS[errorCase.type];
+        if (schema === undefined) {
+          throw new Error(`No schema for Rust type "${errorCase.type}"`);
+        }
+
+        const result = runtime.tryDeserialize(schema, new Uint8Array(errorCase.bytes));
+        expect(result.ok).toBe(false);
+        if (!result.ok) {
+          expect(runtime.errorCode(result.error)).toBe(errorCase.code);
+        }
+      });
+    });
+  });
+
+  describe("streaming", () => {
+    it("should decode concatenated fixtures back to back", () => {
+      const frames = positive.filter((f) => f.schema !== undefined);
+      const stream = new Uint8Array(frames.reduce((total, f) => total + f.data.length, 0));
+      let offset = 0;
+      for (const { data } of frames) {
+        stream.set(data, offset);
+        offset += data.length;
+      }
+
+      offset = 0;
+      for (const { file, schema, value } of frames) {
+        const result = runtime.tryDeserialize(schema as Schema, stream.subarray(offset));
+        if (!result.ok) {
+          throw new Error(`${file} at offset ${String(offset)}: ${result.error.message}`);
+        }
+        expect(toSerdeJson(schema as Schema, result.value.value)).toEqual(value);
+        offset += result.value.bytesRead;
+      }
+      expect(offset).toBe(stream.length);
+    });
+  });
+
+  describe("large", () => {
+    const roundTrip = (schema: Schema, value: unknown): void => {
+      const data = runtime.serialize(schema, value as never);
+      const result = runtime.deserialize(schema, data);
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual(value);
+    };
+
+    it("should round-trip a 4 MB string", () => {
+      roundTrip(string(), "x".repeat(4 << 20));
+    });
+
+    it("should round-trip 8 MB of bytes", () => {
+      roundTrip(bytes(), Uint8Array.from({ length: 8 << 20 }, (_, i) => i & 0xff));
+    });
+
+    it("should round-trip a sequence of one million u32", () => {
+      roundTrip(seq(u32()), Array.from({ length: 1_000_000 }, (_, i) => i * 4099));
+    });
+
+    it("should round-trip a map of 100k entries", () => {
+      roundTrip(
+        map(string(), u32()),
+        new Map(Array.from({ length: 100_000 }, (_, i) => [`key-${String(i)}`, i]))
+      );
+    });
+  });
+});
@@ -0,0 +1,89 @@
+/**
+ * Decode the fixtures with the schemas the Rust generator wrote
+ *
+ * Run by test-fixtures/tests/generated_ts.rs with a fixtures directory holding
+ * manifest.json and generated.ts (see FixtureWriter::write_generated_ts):
+ *
+ *   npx vite-node tests/conformance/generated-runner.ts <fixtures dir>
+ *
+ * Prints one JSON line per manifest entry, decoded as serde_json would write
+ * it for the Rust side to compare with the recorded value:
+ * - { file, type, value } (or entries, for maps recorded as pairs)
+ * - { file, type, error } when decoding fails, leaves bytes unread or gives a
+ *   value the type's generated Zod validator rejects
+ * - { file, type, skipped } when the generated module has no schema for the
+ *   type (std types, generic types applied to arguments, recursive types)
+ */
+
+import { readFileSync } from "node:fs";
+import { join, resolve } from "node:path";
+import process from "node:process";
+import { pathToFileURL } from "node:url";
+import { tryDeserialize, type Schema } from "../../src/index.js";
+import { toSerdeJson, toSerdeJsonEntries } from "../fixtures/serde-json.js";
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+  readonly entries?: unknown[];
+}
+
+const dir = process.argv[2];
+if (dir === undefined) {
+  throw new Error("Usage: generated-runner.ts <fixtures dir>");
+}
+
+const { fixtures } = JSON.parse(readFileSync(join(dir, "manifest.json"), "utf8")) as {
+  fixtures: ManifestEntry[];
+};
+const url = pathToFileURL(resolve(dir, "generated.ts")).href;
+const generated = (await import(url)) as Record<string, unknown>;
+
+function isSchema(value: unknown): value is Schema {
+  return typeof value === "object" && value !== null && "kind" in value;
+}
+
+interface Validator {
+  safeParse(value: unknown): { success: boolean; error?: { message: string } };
+}
+
+function isValidator(value: unknown): value is Validator {
+  return typeof value === "object" && value !== null && "safeParse" in value;
+}
+
+function decode(entry: ManifestEntry): Record<string, unknown> {
+  const schema = generated[`${entry.type}Schema`];
+  if (!isSchema(schema)) {
+    return { skipped: `generated.ts has no ${entry.type}Schema` };
+  }
+  const data = new Uint8Array(readFileSync(join(dir, entry.file)));
+  const result = tryDeserialize(schema, data);
+  if (!result.ok) {
+    return { error: `${result.error.code}: ${result.error.message}` };
+  }
+  const { value, bytesRead } = result.value;
+  if (bytesRead !== data.length) {
+    return { error: `read ${String(bytesRead)} of ${String(data.length)} bytes` };
+  }
+  const validator = generated[`${entry.type}Zod`];
+  if (!isValidator(validator)) {
+    return { error: `generated.ts has no ${entry.type}Zod` };
+  }
+  const checked = validator.safeParse(value);
+  if (!checked.success) {
+    return { error: `rejected by ${entry.type}Zod: ${checked.error?.message ?? "invalid"}` };
+  }
+  return entry.entries !== undefined
+    ? { entries: toSerdeJsonEntries(schema, value) }
+    : { value: toSerdeJson(schema, value) };
+}
+
+for (const entry of fixtures) {
+  let outcome: Record<string, unknown>;
+  try {
+    outcome = decode(entry);
+  } catch (error) {
+    outcome = { error: error instanceof Error ? error.message : String(error) };
+  }
+  process.stdout.write(`${JSON.stringify({ file: entry.file, type: entry.type, ...outcome })}\n`);
+}
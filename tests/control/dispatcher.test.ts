@@ -0,0 +1,179 @@
+/**
+ * Tests for routing incoming messages to handlers by variant
+ *
+ * Besides hand-made streams, the game events Rust writes back to back in
+ * streams.json must reach the handlers of their variants, in order, with
+ * either framing.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  MessageDispatcher,
+  DeserializeError,
+  ErrorCode,
+  serializeLengthPrefixed,
+  serializeCobs,
+  discriminated,
+  enumType,
+  newtypeVariant,
+  structVariant,
+  unitVariant,
+  string,
+  u8,
+  u16,
+  type Framing,
+  type InferType,
+} from "../../src/index.js";
+import { GameEventSchema } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface StreamFixtures {
+  readonly streams: {
+    readonly file: string;
+    readonly framing: Framing;
+    readonly type: string;
+    readonly messages: unknown[];
+  }[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const STREAMS_PATH = join(FIXTURES_DIR, "streams.json");
+
+const recorded: StreamFixtures = existsSync(STREAMS_PATH)
+  ? (JSON.parse(readFileSync(STREAMS_PATH, "utf8")) as StreamFixtures)
+  : { streams: [] };
+
+const EventSchema = enumType("Event", {
+  Started: unitVariant("Started"),
+  Progress: newtypeVariant("Progress", u8()),
+  Spawn: structVariant("Spawn", { name: string(), count: u16() }),
+});
+type Event = InferType<typeof EventSchema>;
+
+const events: Event[] = [
+  { type: "Started" },
+  { type: "Progress", value: 40 },
+  { type: "Spawn", value: { name: "Goblin", count: 3 } },
+  { type: "Progress", value: 80 },
+];
+
+function stream(values: readonly Event[]): Uint8Array {
+  return new Uint8Array(
+    values.flatMap((event) => [...serializeLengthPrefixed(EventSchema, event)])
+  );
+}
+
+describe("MessageDispatcher", () => {
+  it("should call the handlers of each message's variant, in order", () => {
+    const dispatcher = new MessageDispatcher(EventSchema);
+    const seen: string[] = [];
+    dispatcher.on("Progress", (event) => seen.push(`progress ${String(event.value)}`));
+    dispatcher.on("Spawn", (event) =>
+      seen.push(`${String(event.value.count)} ${event.value.name}`)
+    );
+    dispatcher.on("Progress", (event) => seen.push(`again ${String(event.value)}`));
+
+    const bytes = stream(events);
+    for (let i = 0; i < bytes.length; i += 3) {
+      dispatcher.push(bytes.subarray(i, i + 3));
+    }
+    expect(seen).toEqual(["progress 40", "again 40", "3 Goblin", "progress 80", "again 80"]);
+  });
+
+  it("should remove handlers and report messages without any", () => {
+    const unhandled: unknown[] = [];
+    const dispatcher = new MessageDispatcher(EventSchema, {
+      onUnhandled: (message) => unhandled.push(message),
+    });
+    const progress: number[] = [];
+    const off = dispatcher.on("Progress", (event) => progress.push(event.value));
+    expect(dispatcher.dispatch({ type: "Progress", value: 1 })).toBe(true);
+    off();
+    expect(dispatcher.dispatch({ type: "Progress", value: 2 })).toBe(false);
+    expect(dispatcher.dispatch({ type: "Started" })).toBe(false);
+    expect(progress).toEqual([1]);
+    expect(unhandled).toEqual([{ type: "Progress", value: 2 }, { type: "Started" }]);
+  });
+
+  it("should route COBS frames", () => {
+    const dispatcher = new MessageDispatcher(EventSchema, { framing: "cobs" });
+    const spawned: string[] = [];
+    dispatcher.on("Spawn", (event) => spawned.push(event.value.name));
+    for (const event of events) {
+      dispatcher.push(serializeCobs(EventSchema, event));
+    }
+    expect(spawned).toEqual(["Goblin"]);
+  });
+
+  it("should route discriminated enums by their tag", () => {
+    const Flat = discriminated(EventSchema, "kind");
+    const dispatcher = new MessageDispatcher(Flat, { tag: "kind" });
+    const spawned: string[] = [];
+    dispatcher.on("Spawn", (event) => spawned.push(`${String(event.count)} ${event.name}`));
+    dispatcher.push(stream(events));
+    expect(spawned).toEqual(["3 Goblin"]);
+  });
+
+  it("should throw a frame that does not decode, then go on with the next", () => {
+    const dispatcher = new MessageDispatcher(EventSchema);
+    const progress: number[] = [];
+    dispatcher.on("Progress", (event) => progress.push(event.value));
+    const bytes = new Uint8Array([0x01, 0x07, ...stream(events.slice(1, 2))]);
+    expect(() => dispatcher.push(bytes)).toThrow(DeserializeError);
+    dispatcher.push(new Uint8Array(0));
+    expect(progress).toEqual([40]);
+  });
+
+  it("should report errors to onError once, a malformed prefix until reset", () => {
+    const errors: string[] = [];
+    const dispatcher = new MessageDispatcher(EventSchema, {
+      maxFrameLength: 4,
+      onError: (error) => errors.push(error.code),
+    });
+    const progress: number[] = [];
+    dispatcher.on("Progress", (event) => progress.push(event.value));
+
+    dispatcher.push(stream(events));
+    expect(errors).toEqual([ErrorCode.BufferFull]);
+    expect(progress).toEqual([40, 80]);
+
+    dispatcher.push(new Uint8Array([0xff, 0xff, 0xff, 0xff, 0xff, 0x01]));
+    dispatcher.push(stream(events));
+    expect(errors).toEqual([ErrorCode.BufferFull, ErrorCode.BadVarint]);
+    dispatcher.reset();
+    dispatcher.push(stream(events.slice(1, 2)));
+    expect(progress).toEqual([40, 80, 40]);
+  });
+
+  it("should reject an unknown framing", () => {
+    expect(
+      () => new MessageDispatcher(EventSchema, { framing: "slip" as unknown as Framing })
+    ).toThrow(RangeError);
+  });
+});
+
+describe("Rust message streams", () => {
+  it.each(recorded.streams)("$file ($framing) should reach the handlers in order", (entry) => {
+    expect(entry.type).toBe("GameEvent");
+    const dispatcher = new MessageDispatcher(GameEventSchema, { framing: entry.framing });
+    const received: unknown[] = [];
+    const record = (message: InferType<typeof GameEventSchema>): void => {
+      received.push(toSerdeJson(GameEventSchema, message));
+    };
+    dispatcher.on("PlayerAction", record);
+    dispatcher.on("EnemySpawn", record);
+    dispatcher.on("ItemDropped", record);
+    dispatcher.on("LocationDiscovered", record);
+    const spawns: string[] = [];
+    dispatcher.on("EnemySpawn", (event) => spawns.push(event.value.enemy_type));
+
+    const bytes = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    for (let i = 0; i < bytes.length; i += 5) {
+      dispatcher.push(bytes.subarray(i, i + 5));
+    }
+    expect(received).toEqual(entry.messages);
+    expect(spawns).toEqual(["Goblin", "Dragon"]);
+  });
+});
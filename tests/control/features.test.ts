@@ -0,0 +1,97 @@
+/**
+ * Tests for capability masks and feature-gated encoding
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  FeatureSet,
+  FeatureError,
+  FeatureMaskSchema,
+  SerializeError,
+  deserialize,
+  serialize,
+  enumType,
+  newtypeVariant,
+  unitVariant,
+  struct,
+  bytes,
+  u8,
+  u32,
+} from "../../src/index.js";
+
+// As generated by `Generator::feature`
+const PROTOCOL_FEATURES = {
+  firmware_update: { bit: 0, messages: ["Command::Update", "FirmwareChunk"] },
+  logs: { bit: 5, messages: [] },
+} as const;
+
+const CommandSchema = enumType("Command", {
+  Update: newtypeVariant("Update", u32()),
+  Blink: newtypeVariant("Blink", u8()),
+  Stop: unitVariant("Stop"),
+});
+
+const FirmwareChunkSchema = struct({ offset: u32(), data: bytes() });
+
+describe("FeatureSet", () => {
+  it("reads the features of a mask", () => {
+    const features = new FeatureSet(PROTOCOL_FEATURES, 0b100000n);
+    expect(features.hasFeature("logs")).toBe(true);
+    expect(features.hasFeature("firmware_update")).toBe(false);
+    expect(features.features()).toEqual(["logs"]);
+    // @ts-expect-error - not a declared feature
+    features.hasFeature("telemetry");
+  });
+
+  it("round-trips a mask on the wire", () => {
+    const ours = FeatureSet.of(PROTOCOL_FEATURES, ["firmware_update", "logs"]);
+    expect(ours.mask).toBe(0b100001n);
+    const mask = deserialize(FeatureMaskSchema, serialize(FeatureMaskSchema, ours.mask)).value;
+    const device = new FeatureSet(PROTOCOL_FEATURES, mask | (1n << 40n));
+    expect(ours.intersect(device).features()).toEqual(["firmware_update", "logs"]);
+    expect(ours.intersect(new FeatureSet(PROTOCOL_FEATURES, 1n)).mask).toBe(1n);
+  });
+
+  it("refuses messages needing a missing feature", () => {
+    const device = new FeatureSet(PROTOCOL_FEATURES, 0n);
+    expect(device.supports("Command", { type: "Blink", value: 3 })).toBe(true);
+    expect(device.missingFeature("Command", { type: "Update", value: 2 })).toBe("firmware_update");
+    expect(device.supports("FirmwareChunk")).toBe(false);
+
+    const blink = device.trySerialize("Command", CommandSchema, { type: "Blink", value: 3 });
+    expect(blink).toEqual({ ok: true, value: serialize(CommandSchema, { type: "Blink", value: 3 }) });
+
+    const update = device.trySerialize("Command", CommandSchema, { type: "Update", value: 2 });
+    expect(update.ok).toBe(false);
+    if (!update.ok) {
+      expect(update.error).toBeInstanceOf(FeatureError);
+      expect(update.error.message).toBe("The peer lacks feature firmware_update for Command::Update");
+    }
+    expect(() =>
+      device.serialize("FirmwareChunk", FirmwareChunkSchema, {
+        offset: 0,
+        data: new Uint8Array(4),
+      })
+    ).toThrow(FeatureError);
+  });
+
+  it("encodes everything for a peer with the features", () => {
+    const device = new FeatureSet(PROTOCOL_FEATURES, 1n);
+    const bytes = device.serialize("Command", CommandSchema, { type: "Update", value: 2 });
+    expect(deserialize(CommandSchema, bytes).value).toEqual({ type: "Update", value: 2 });
+    // Values that do not fit still fail as SerializeError
+    expect(() => device.serialize("Command", CommandSchema, { type: "Blink", value: 300 })).toThrow(
+      SerializeError
+    );
+  });
+
+  it("reads the variant from a custom tag", () => {
+    const device = new FeatureSet(PROTOCOL_FEATURES, 0n, { tag: "kind" });
+    expect(device.supports("Command", { kind: "Update" })).toBe(false);
+    expect(device.supports("Command", { type: "Update" })).toBe(true);
+  });
+
+  it("rejects bits outside the mask", () => {
+    expect(() => new FeatureSet({ late: { bit: 64, messages: [] } }, 0n)).toThrow(RangeError);
+  });
+});
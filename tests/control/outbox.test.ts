@@ -0,0 +1,195 @@
+/**
+ * Tests for rate limiting and coalescing outgoing control messages
+ */
+
+import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
+import {
+  Outbox,
+  TransportError,
+  deserialize,
+  serializeCobs,
+  enumType,
+  newtypeVariant,
+  unitVariant,
+  f32,
+  u8,
+  type InferType,
+  type OutboxOptions,
+} from "../../src/index.js";
+
+const CommandSchema = enumType("Command", {
+  SetTarget: newtypeVariant("SetTarget", f32()),
+  SetMode: newtypeVariant("SetMode", u8()),
+  Stop: unitVariant("Stop"),
+});
+type Command = InferType<typeof CommandSchema>;
+
+function outbox(options: Omit<OutboxOptions<typeof CommandSchema>, "send"> = {}): {
+  outbox: Outbox<typeof CommandSchema>;
+  sent: Command[];
+} {
+  const sent: Command[] = [];
+  return {
+    outbox: new Outbox(CommandSchema, {
+      ...options,
+      send: (bytes) => sent.push(deserialize(CommandSchema, bytes).value),
+    }),
+    sent,
+  };
+}
+
+const target = (value: number): Command => ({ type: "SetTarget", value });
+
+describe("Outbox", () => {
+  beforeEach(() => {
+    vi.useFakeTimers();
+  });
+
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  it("should send kinds without a policy immediately", () => {
+    const { outbox: box, sent } = outbox();
+    box.send(target(1));
+    box.send({ type: "Stop" });
+    expect(sent).toEqual([target(1), { type: "Stop" }]);
+  });
+
+  it("should throttle to one message per interval, keeping the latest", () => {
+    const { outbox: box, sent } = outbox({
+      policies: { SetTarget: { mode: "throttle", intervalMs: 100 } },
+    });
+
+    box.send(target(1));
+    box.send(target(2));
+    box.send(target(3));
+    expect(sent).toEqual([target(1)]);
+    expect(box.held).toBe(1);
+
+    vi.advanceTimersByTime(99);
+    expect(sent).toEqual([target(1)]);
+    vi.advanceTimersByTime(1);
+    expect(sent).toEqual([target(1), target(3)]);
+
+    // Still within the interval of the last message sent
+    box.send(target(4));
+    expect(sent).toHaveLength(2);
+    vi.advanceTimersByTime(100);
+    expect(sent).toEqual([target(1), target(3), target(4)]);
+
+    vi.advanceTimersByTime(500);
+    box.send(target(5));
+    expect(sent).toHaveLength(4);
+  });
+
+  it("should coalesce messages within a window", () => {
+    const { outbox: box, sent } = outbox({
+      policies: { SetTarget: { mode: "coalesce", windowMs: 50 } },
+    });
+
+    box.send(target(1));
+    vi.advanceTimersByTime(30);
+    box.send(target(2));
+    expect(sent).toEqual([]);
+    vi.advanceTimersByTime(20);
+    expect(sent).toEqual([target(2)]);
+  });
+
+  it("should send the held message on flush", () => {
+    const { outbox: box, sent } = outbox({
+      policies: { SetTarget: { mode: "throttle", intervalMs: 100 } },
+    });
+
+    box.send(target(1));
+    box.send(target(2));
+    box.flush("SetTarget");
+    expect(sent).toEqual([target(1), target(2)]);
+    expect(box.held).toBe(0);
+
+    // The timer was cancelled, and the flush counts as a send
+    vi.advanceTimersByTime(100);
+    expect(sent).toHaveLength(2);
+  });
+
+  it("should send held messages before an immediate one, in the order they were queued", () => {
+    const { outbox: box, sent } = outbox({
+      defaultPolicy: { mode: "coalesce", windowMs: 50 },
+      policies: { Stop: { mode: "immediate" } },
+    });
+
+    box.send({ type: "SetMode", value: 2 });
+    box.send(target(1));
+    box.send({ type: "SetMode", value: 3 });
+    box.send({ type: "Stop" });
+    expect(sent).toEqual([{ type: "SetMode", value: 3 }, target(1), { type: "Stop" }]);
+
+    vi.advanceTimersByTime(50);
+    expect(sent).toHaveLength(3);
+  });
+
+  it("should drop held messages on clear", () => {
+    const { outbox: box, sent } = outbox({
+      defaultPolicy: { mode: "coalesce", windowMs: 50 },
+    });
+
+    box.send(target(1));
+    box.send({ type: "SetMode", value: 2 });
+    box.clear("SetTarget");
+    vi.advanceTimersByTime(50);
+    expect(sent).toEqual([{ type: "SetMode", value: 2 }]);
+
+    box.send(target(1));
+    box.clear();
+    vi.advanceTimersByTime(50);
+    expect(sent).toHaveLength(1);
+  });
+
+  it("should group messages by a custom kind", () => {
+    const { outbox: box, sent } = outbox({
+      defaultPolicy: { mode: "coalesce", windowMs: 50 },
+      kindOf: (command) => (command.type === "Stop" ? "stop" : "setting"),
+    });
+
+    box.send(target(1));
+    box.send({ type: "SetMode", value: 2 });
+    vi.advanceTimersByTime(50);
+    expect(sent).toEqual([{ type: "SetMode", value: 2 }]);
+  });
+
+  it("should encode with a custom encoder", () => {
+    const frames: Uint8Array[] = [];
+    const box = new Outbox(CommandSchema, {
+      send: (bytes) => frames.push(bytes),
+      encode: (command) => serializeCobs(CommandSchema, command),
+    });
+    box.send({ type: "Stop" });
+    expect(frames).toEqual([serializeCobs(CommandSchema, { type: "Stop" })]);
+  });
+
+  it("should report failures of held messages to onError", () => {
+    const onError = vi.fn();
+    const box = new Outbox(CommandSchema, {
+      send: () => {
+        throw new Error("port closed");
+      },
+      defaultPolicy: { mode: "coalesce", windowMs: 50 },
+      onError,
+    });
+
+    box.send({ type: "Stop" });
+    vi.advanceTimersByTime(50);
+    const [error] = onError.mock.calls[0] as [unknown];
+    expect(error).toBeInstanceOf(TransportError);
+    expect((error as TransportError).cause).toEqual(new Error("port closed"));
+  });
+
+  it("should reject non-positive durations", () => {
+    expect(() => outbox({ policies: { SetTarget: { mode: "throttle", intervalMs: 0 } } })).toThrow(
+      RangeError
+    );
+    expect(() => outbox({ defaultPolicy: { mode: "coalesce", windowMs: NaN } })).toThrow(
+      RangeError
+    );
+  });
+});
@@ -0,0 +1,183 @@
+/**
+ * Tests for the fake device of UI tests
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DeserializeError,
+  DeviceSimulator,
+  TransportError,
+  UnhandledRequestError,
+  decodeOnly,
+  deserialize,
+  discriminated,
+  encodeOnly,
+  enumType,
+  f32,
+  newtypeVariant,
+  serialize,
+  u32,
+  unitVariant,
+  type AnySimulatorHandler,
+  type DecodeOnlySchema,
+  type DeviceSimulatorOptions,
+  type EncodeOnlySchema,
+  type InferType,
+} from "../../src/index.js";
+
+const CommandSchema = enumType("Command", {
+  Ping: unitVariant("Ping"),
+  SetTarget: newtypeVariant("SetTarget", f32()),
+});
+type Command = InferType<typeof CommandSchema>;
+
+const ReplySchema = enumType("Reply", {
+  Pong: unitVariant("Pong"),
+  Target: newtypeVariant("Target", f32()),
+});
+type Reply = InferType<typeof ReplySchema>;
+
+const ReadingSchema = u32();
+
+/** A simulator as the generator writes them, with handlers from the constructor */
+class Thermostat extends DeviceSimulator<
+  typeof CommandSchema,
+  typeof ReplySchema,
+  typeof ReadingSchema
+> {
+  constructor(
+    options: DeviceSimulatorOptions,
+    handlers: Record<string, AnySimulatorHandler<Reply>> = {}
+  ) {
+    super({ request: CommandSchema, response: ReplySchema, telemetry: ReadingSchema }, options);
+    for (const [variant, handler] of Object.entries(handlers)) {
+      this.setHandler(variant, handler);
+    }
+  }
+}
+
+function thermostat(handlers: Record<string, AnySimulatorHandler<Reply>> = {}): {
+  device: Thermostat;
+  sent: Uint8Array[];
+} {
+  const sent: Uint8Array[] = [];
+  return { device: new Thermostat({ send: (bytes) => sent.push(bytes) }, handlers), sent };
+}
+
+/** A simulator of types the UI only encodes or only decodes */
+class OneWayThermostat extends DeviceSimulator<
+  EncodeOnlySchema<typeof CommandSchema>,
+  DecodeOnlySchema<typeof ReplySchema>
+> {
+  constructor(options: DeviceSimulatorOptions) {
+    const response = decodeOnly(ReplySchema);
+    super({ request: encodeOnly(CommandSchema), response, telemetry: response }, options);
+    this.setHandler("Ping", () => ({ type: "Pong" }));
+  }
+}
+
+const request = (command: Command): Uint8Array => serialize(CommandSchema, command);
+const reply = (bytes: Uint8Array): Reply => deserialize(ReplySchema, bytes).value;
+const reading = (bytes: Uint8Array): number => deserialize(ReadingSchema, bytes).value;
+
+describe("DeviceSimulator", () => {
+  it("should answer each request with its variant's handler", async () => {
+    const { device, sent } = thermostat({
+      Ping: () => ({ type: "Pong" }),
+      SetTarget: (command: Extract<Command, { type: "SetTarget" }>) =>
+        Promise.resolve({ type: "Target", value: command.value }),
+    });
+
+    await device.receive(request({ type: "Ping" }));
+    await device.receive(request({ type: "SetTarget", value: 21.5 }));
+
+    expect(sent.map(reply)).toEqual([{ type: "Pong" }, { type: "Target", value: 21.5 }]);
+    expect(device.received).toEqual([{ type: "Ping" }, { type: "SetTarget", value: 21.5 }]);
+  });
+
+  it("should send nothing when a handler returns undefined", async () => {
+    const { device, sent } = thermostat({ Ping: () => undefined });
+    await device.receive(request({ type: "Ping" }));
+    expect(sent).toEqual([]);
+  });
+
+  it("should reject requests without a handler and bytes that are no request", async () => {
+    const { device, sent } = thermostat();
+
+    const unhandled = device.receive(request({ type: "Ping" }));
+    await expect(unhandled).rejects.toBeInstanceOf(UnhandledRequestError);
+    await expect(unhandled).rejects.toMatchObject({ kind: "Ping", request: { type: "Ping" } });
+    await expect(device.receive(new Uint8Array([9]))).rejects.toBeInstanceOf(DeserializeError);
+    expect(sent).toEqual([]);
+  });
+
+  it("should emit telemetry only as the virtual clock advances", () => {
+    const { device, sent } = thermostat();
+    device.every(1000, (now) => now / 10);
+    device.script([
+      { atMs: 1500, value: 7 },
+      { atMs: 1000, value: 5 },
+    ]);
+    expect(sent).toEqual([]);
+
+    device.advance(2000);
+
+    // Timers due together fire in the order they were set
+    expect(sent.map(reading)).toEqual([100, 5, 7, 200]);
+    expect(device.now).toBe(2000);
+  });
+
+  it("should stop emitters and skip beats", () => {
+    const { device, sent } = thermostat();
+    const stop = device.every(100, (now) => (now === 200 ? undefined : now));
+    device.advance(300);
+    stop();
+    device.advance(1000);
+
+    expect(sent.map(reading)).toEqual([100, 300]);
+  });
+
+  it("should look handlers up with kindOf", async () => {
+    const Discriminated = discriminated(CommandSchema, "kind");
+    class Tagged extends DeviceSimulator<typeof Discriminated, typeof ReplySchema> {
+      constructor(options: DeviceSimulatorOptions) {
+        super({ request: Discriminated, response: ReplySchema, telemetry: ReplySchema }, options);
+        this.setHandler("Ping", () => ({ type: "Pong" }));
+      }
+    }
+    const sent: Uint8Array[] = [];
+    const device = new Tagged({
+      send: (bytes) => sent.push(bytes),
+      kindOf: (value) => (value as InferType<typeof Discriminated>).kind,
+    });
+
+    await device.receive(serialize(Discriminated, { kind: "Ping" }));
+
+    expect(sent.map(reply)).toEqual([{ type: "Pong" }]);
+  });
+
+  it("should wrap failed sends in a TransportError", () => {
+    const device = new Thermostat({
+      send: () => {
+        throw new Error("socket closed");
+      },
+    });
+    expect(() => device.emit(1)).toThrow(TransportError);
+  });
+
+  it("should reject invalid times", () => {
+    const { device } = thermostat();
+    expect(() => device.every(0, () => 1)).toThrow(RangeError);
+    expect(() => device.script([{ atMs: -1, value: 1 }])).toThrow(RangeError);
+    expect(() => device.advance(Number.NaN)).toThrow(RangeError);
+  });
+
+  it("should decode requests and encode replies the UI uses only one way", async () => {
+    const sent: Uint8Array[] = [];
+    const device = new OneWayThermostat({ send: (bytes) => sent.push(bytes) });
+
+    await device.receive(request({ type: "Ping" }));
+
+    expect(sent.map(reply)).toEqual([{ type: "Pong" }]);
+  });
+});
@@ -0,0 +1,117 @@
+/**
+ * Tests for reading self-describing archives
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  readArchive,
+  tryReadArchive,
+  serialize,
+  lengthPrefixEncode,
+  ArchiveError,
+  ArchiveHeaderSchema,
+  ARCHIVE_MAGIC,
+  ARCHIVE_VERSION,
+  DeserializeError,
+  IrError,
+} from "../../src/index.js";
+
+const ReadingIr = {
+  version: 1,
+  types: [
+    {
+      name: "Reading",
+      kind: "struct",
+      fields: [
+        { name: "sensor", type: { kind: "u8" } },
+        { name: "celsius", type: { kind: "f32" } },
+      ],
+    },
+  ],
+};
+
+// Reading { sensor: 2, celsius: 22.5 } and { sensor: 3, celsius: -1.0 }
+const FIRST = new Uint8Array([0x02, 0x00, 0x00, 0xb4, 0x41]);
+const SECOND = new Uint8Array([0x03, 0x00, 0x00, 0x80, 0xbf]);
+
+function archive(
+  records: readonly Uint8Array[],
+  header: Partial<{ version: number; type_name: string; schema_hash: bigint; ir: string }> = {}
+): Uint8Array {
+  const parts = [
+    serialize(ArchiveHeaderSchema, {
+      magic: [0x50, 0x43, 0x54, 0x41],
+      version: ARCHIVE_VERSION,
+      type_name: "Reading",
+      schema_hash: 0x1234n,
+      ir: JSON.stringify(ReadingIr),
+      ...header,
+    }),
+    ...records.map(lengthPrefixEncode),
+  ];
+  const bytes = new Uint8Array(parts.reduce((length, part) => length + part.length, 0));
+  let offset = 0;
+  for (const part of parts) {
+    bytes.set(part, offset);
+    offset += part.length;
+  }
+  return bytes;
+}
+
+describe("readArchive", () => {
+  it("should decode the records with the IR in the header", () => {
+    const read = readArchive(archive([FIRST, SECOND]));
+    expect(read.type).toBe("Reading");
+    expect(read.schemaHash).toBe(0x1234n);
+    expect(read.ir).toEqual(ReadingIr);
+    expect(read.decoder.types).toEqual(["Reading"]);
+    expect(read.records).toEqual([
+      { sensor: 2, celsius: 22.5 },
+      { sensor: 3, celsius: -1 },
+    ]);
+  });
+
+  it("should start with the magic bytes", () => {
+    expect(Array.from(archive([]).subarray(0, 4))).toEqual(ARCHIVE_MAGIC);
+    expect(readArchive(archive([])).records).toEqual([]);
+  });
+
+  it("should check the schema hash when given one", () => {
+    expect(readArchive(archive([FIRST]), { schemaHash: 0x1234n }).records).toHaveLength(1);
+    const other = tryReadArchive(archive([FIRST]), { schemaHash: 0xffn });
+    expect(other.ok).toBe(false);
+    expect(!other.ok && other.error.message).toBe(
+      "Archive of Reading has schema hash 0x1234, expected 0xff"
+    );
+  });
+
+  it("should reject files that are not archives of a supported version", () => {
+    expect(() => readArchive(new Uint8Array([0x50, 0x43]))).toThrow(ArchiveError);
+    expect(() => readArchive(archive([], { version: 2 }))).toThrow(
+      "Unsupported archive version 2"
+    );
+    expect(() => readArchive(archive([]).subarray(0, 12))).toThrow(ArchiveError);
+  });
+
+  it("should reject an IR that does not describe the records", () => {
+    expect(() => readArchive(archive([], { ir: "{" }))).toThrow(IrError);
+    expect(() => readArchive(archive([], { type_name: "Command" }))).toThrow(
+      "The archive's IR has no type Command"
+    );
+  });
+
+  it("should say which record does not decode", () => {
+    const cut = archive([FIRST, SECOND]);
+    expect(() => readArchive(cut.subarray(0, cut.length - 1))).toThrow(
+      /^Record 1: Frame at offset/
+    );
+    const short = archive([FIRST, SECOND.subarray(0, 3)]);
+    const error = tryReadArchive(short);
+    expect(!error.ok && error.error).toBeInstanceOf(DeserializeError);
+    expect(!error.ok && error.error instanceof DeserializeError && error.error.path).toBe(
+      "Reading[1].celsius"
+    );
+    const long = archive([new Uint8Array([...FIRST, 0x00])]);
+    expect(() => readArchive(long)).toThrow("Record 0 is 6 bytes, but a Reading took 5");
+  });
+});
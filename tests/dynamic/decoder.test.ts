@@ -0,0 +1,223 @@
+/**
+ * Tests for IR-driven schemas and the dynamic decoder
+ *
+ * The fixture generator writes the IR of every fixture type deriving
+ * PostcardTs to ir.json; each manifest fixture of such a type must decode
+ * through it to the value Rust serialized.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  DynamicDecoder,
+  IrError,
+  schemasFromIr,
+  trySchemasFromIr,
+  DeserializeError,
+  serialize,
+  u8,
+  seq,
+  string,
+  option,
+  struct,
+} from "../../src/index.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+  readonly value: unknown;
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const IR_PATH = join(FIXTURES_DIR, "ir.json");
+
+function readJson(file: string): unknown {
+  return JSON.parse(readFileSync(join(FIXTURES_DIR, file), "utf8"));
+}
+
+const fixtureIr = existsSync(IR_PATH) ? readJson("ir.json") : undefined;
+const fixtures = existsSync(IR_PATH)
+  ? (readJson("manifest.json") as { fixtures: ManifestEntry[] }).fixtures
+  : [];
+
+const ReadingIr = {
+  version: 1,
+  types: [
+    {
+      name: "Reading",
+      kind: "struct",
+      fields: [
+        { name: "sensor", type: { kind: "u8" } },
+        { name: "label", type: { kind: "option", inner: { kind: "string" } } },
+        { name: "samples", type: { kind: "seq", item: { kind: "u8" } } },
+      ],
+    },
+    {
+      name: "Node",
+      kind: "struct",
+      fields: [
+        { name: "value", type: { kind: "u8" } },
+        { name: "next", type: { kind: "option", inner: { kind: "ref", name: "Node" } } },
+      ],
+    },
+    {
+      name: "Command",
+      kind: "enum",
+      variants: [
+        { name: "Stop", kind: "unit" },
+        { name: "Move", kind: "struct", fields: [{ name: "x", type: { kind: "i32" } }] },
+      ],
+    },
+  ],
+};
+
+// Envelope<T> and Batch(Vec<Envelope<Option<Vec<u8>>>>)
+const GenericIr = {
+  version: 1,
+  types: [
+    {
+      name: "Envelope",
+      params: ["T"],
+      kind: "struct",
+      fields: [
+        { name: "seq", type: { kind: "u8" } },
+        { name: "payload", type: { kind: "param", name: "T" } },
+      ],
+    },
+    {
+      name: "Batch",
+      kind: "newtype_struct",
+      inner: {
+        kind: "seq",
+        item: {
+          kind: "ref",
+          name: "Envelope",
+          args: [{ kind: "option", inner: { kind: "seq", item: { kind: "u8" } } }],
+        },
+      },
+    },
+  ],
+};
+
+describe("DynamicDecoder", () => {
+  it("should decode values of any type in the IR", () => {
+    const decoder = new DynamicDecoder(ReadingIr);
+    const Reading = struct({ sensor: u8(), label: option(string()), samples: seq(u8()) });
+    const data = serialize(Reading, { sensor: 3, label: "lab", samples: [1, 2] });
+
+    expect(decoder.types).toEqual(["Reading", "Node", "Command"]);
+    expect(decoder.decode("Reading", data)).toEqual({
+      value: { sensor: 3, label: "lab", samples: [1, 2] },
+      bytesRead: data.length,
+    });
+    expect(decoder.decode("Command", new Uint8Array([1, 0x03])).value).toEqual({
+      type: "Move",
+      value: { x: -2 },
+    });
+  });
+
+  it("should decode recursive types", () => {
+    const decoder = new DynamicDecoder(ReadingIr);
+    expect(decoder.decode("Node", new Uint8Array([1, 1, 2, 0])).value).toEqual({
+      value: 1,
+      next: { value: 2, next: null },
+    });
+  });
+
+  it("should decode generic types applied to type arguments", () => {
+    const decoder = new DynamicDecoder(GenericIr);
+    const Envelope = struct({ seq: u8(), payload: option(seq(u8())) });
+    const data = serialize(seq(Envelope), [
+      { seq: 1, payload: [5] },
+      { seq: 2, payload: null },
+    ]);
+
+    expect(decoder.types).toEqual(["Batch"]);
+    expect(decoder.schema("Envelope")).toBeUndefined();
+    expect(decoder.decode("Batch", data).value).toEqual([
+      { seq: 1, payload: [5] },
+      { seq: 2, payload: null },
+    ]);
+  });
+
+  it("should report unknown types and malformed data", () => {
+    const decoder = new DynamicDecoder(ReadingIr);
+    const unknown = decoder.tryDecode("Missing", new Uint8Array([0]));
+    expect(!unknown.ok && unknown.error).toBeInstanceOf(IrError);
+    expect(() => decoder.decode("Command", new Uint8Array([7]))).toThrow(DeserializeError);
+  });
+});
+
+describe("schemasFromIr", () => {
+  it("should reject invalid IR", () => {
+    const newtype = (inner: unknown): unknown => ({
+      version: 1,
+      types: [{ name: "A", kind: "newtype_struct", inner }],
+    });
+    const invalid = [
+      null,
+      { version: 2, types: [] },
+      { version: 1, types: [{ name: "A", kind: "union" }] },
+      newtype({ kind: "u256" }),
+      newtype({ kind: "toString" }),
+      newtype({ kind: "ref", name: "B" }),
+      newtype({ kind: "param", name: "T" }),
+      newtype({ kind: "ref", name: "A", args: [{ kind: "u8" }] }),
+      newtype({ kind: "map", key: { kind: "f64" }, value: { kind: "u8" } }),
+      {
+        version: 1,
+        types: [
+          { name: "G", params: ["T"], kind: "newtype_struct", inner: { kind: "param", name: "U" } },
+        ],
+      },
+      {
+        version: 1,
+        types: [
+          { name: "G", params: ["T"], kind: "newtype_struct", inner: { kind: "param", name: "T" } },
+          { name: "A", kind: "newtype_struct", inner: { kind: "ref", name: "G" } },
+        ],
+      },
+      {
+        version: 1,
+        types: [
+          { name: "A", kind: "unit_struct" },
+          { name: "A", kind: "unit_struct" },
+        ],
+      },
+    ];
+    for (const ir of invalid) {
+      const result = trySchemasFromIr(ir);
+      expect(!result.ok && result.error).toBeInstanceOf(IrError);
+    }
+  });
+
+  it("should build never() for empty enums", () => {
+    const schemas = schemasFromIr({
+      version: 1,
+      types: [{ name: "Never", kind: "enum", variants: [] }],
+    });
+    expect(schemas.get("Never")).toEqual({ kind: "never" });
+  });
+});
+
+describe.runIf(fixtureIr !== undefined)("Fixture IR written by Rust", () => {
+  const decoder = fixtureIr === undefined ? undefined : new DynamicDecoder(fixtureIr);
+  const covered = fixtures.filter((entry) => decoder?.schema(entry.type) !== undefined);
+
+  it("should cover the derived fixture types", () => {
+    expect(covered.map((entry) => entry.type)).toContain("GameState");
+    expect(covered.map((entry) => entry.type)).toContain("TreeNode");
+    expect(covered.map((entry) => entry.type)).toContain("Exchange");
+  });
+
+  it.each(covered)("should decode $file ($type) to the value Rust serialized", (entry) => {
+    const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const schema = decoder?.schema(entry.type);
+    const result = decoder?.decode(entry.type, data);
+
+    expect(result?.bytesRead).toBe(data.length);
+    expect(schema && toSerdeJson(schema, result?.value)).toEqual(entry.value);
+  });
+});
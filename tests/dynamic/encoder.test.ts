@@ -0,0 +1,140 @@
+/**
+ * Tests for the IR-driven encoder
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  DynamicDecoder,
+  DynamicEncoder,
+  DynamicEncodeError,
+  IrError,
+} from "../../src/index.js";
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const IR_PATH = join(FIXTURES_DIR, "ir.json");
+
+const CommandIr = {
+  version: 1,
+  types: [
+    {
+      name: "Command",
+      kind: "enum",
+      variants: [
+        { name: "Stop", kind: "unit" },
+        { name: "Move", kind: "struct", fields: [{ name: "x", type: { kind: "i32" } }] },
+        { name: "Tag", kind: "tuple", items: [{ kind: "u8" }, { kind: "char" }] },
+      ],
+    },
+    {
+      name: "Batch",
+      kind: "struct",
+      fields: [
+        { name: "id", type: { kind: "u64" } },
+        { name: "commands", type: { kind: "seq", item: { kind: "ref", name: "Command" } } },
+        { name: "labels", type: { kind: "map", key: { kind: "string" }, value: { kind: "u8" } } },
+        { name: "note", type: { kind: "option", inner: { kind: "string" } } },
+      ],
+    },
+  ],
+};
+
+describe("DynamicEncoder", () => {
+  const encoder = new DynamicEncoder(CommandIr);
+  const decoder = new DynamicDecoder(CommandIr);
+
+  it("should encode valid values of any type in the IR", () => {
+    const batch = {
+      id: 7n,
+      commands: [{ type: "Stop" }, { type: "Move", value: { x: -2 } }],
+      labels: new Map([["a", 1]]),
+      note: null,
+    };
+    const bytes = encoder.encode("Batch", batch);
+
+    expect(encoder.validate("Batch", batch)).toEqual([]);
+    expect(decoder.decode("Batch", bytes)).toEqual({ value: batch, bytesRead: bytes.length });
+    expect(encoder.encode("Command", { type: "Move", value: { x: -2 } })).toEqual(
+      new Uint8Array([1, 0x03])
+    );
+  });
+
+  it("should report every issue with its path", () => {
+    const issues = encoder.validate("Batch", {
+      id: 7,
+      commands: [
+        { type: "Jump" },
+        { type: "Move", value: { x: 1.5 } },
+        { type: "Tag", value: [256] },
+      ],
+      labels: { a: 1 },
+      extra: true,
+    });
+
+    expect(issues.map((issue) => issue.path)).toEqual([
+      "Batch.id",
+      "Batch.commands[0]",
+      "Batch.commands[1].value.x",
+      "Batch.commands[2].value",
+      "Batch.labels",
+      "Batch.note",
+      "Batch.extra",
+    ]);
+    expect(issues[0]?.message).toBe("expected a bigint, got 7");
+    expect(issues[5]?.message).toBe("missing field");
+    expect(issues[6]?.message).toBe("unknown field");
+  });
+
+  it("should check ranges and characters", () => {
+    expect(encoder.validate("Command", { type: "Tag", value: [256, "ab"] })).toEqual([
+      { path: "Command.value[0]", message: "256 is out of range for u8" },
+      { path: "Command.value[1]", message: 'expected a single character, got "ab"' },
+    ]);
+    expect(encoder.validate("Command", { type: "Tag", value: [255, "🦀"] })).toEqual([]);
+  });
+
+  it("should stop after 50 issues", () => {
+    const commands = Array.from({ length: 80 }, () => ({ type: "Jump" }));
+    const issues = encoder.validate("Batch", { id: 1n, commands, labels: new Map(), note: null });
+    expect(issues).toHaveLength(50);
+  });
+
+  it("should fail with DynamicEncodeError or IrError", () => {
+    const invalid = encoder.tryEncode("Command", { type: "Move", value: {} });
+    expect(!invalid.ok && invalid.error).toBeInstanceOf(DynamicEncodeError);
+    expect(!invalid.ok && invalid.error.message).toBe(
+      "Invalid Command: Command.value.x: missing field"
+    );
+
+    const unknown = encoder.tryEncode("Missing", {});
+    expect(!unknown.ok && unknown.error).toBeInstanceOf(IrError);
+    expect(() => encoder.validate("Missing", {})).toThrow(IrError);
+    expect(() => encoder.encode("Command", null)).toThrow(DynamicEncodeError);
+  });
+});
+
+describe.runIf(existsSync(IR_PATH))("Fixture IR written by Rust", () => {
+  const ir: unknown = existsSync(IR_PATH) ? JSON.parse(readFileSync(IR_PATH, "utf8")) : undefined;
+  const manifest = existsSync(IR_PATH)
+    ? (JSON.parse(readFileSync(join(FIXTURES_DIR, "manifest.json"), "utf8")) as {
+        fixtures: ManifestEntry[];
+      })
+    : { fixtures: [] };
+  const decoder = ir === undefined ? undefined : new DynamicDecoder(ir);
+  const encoder = ir === undefined ? undefined : new DynamicEncoder(ir);
+  const covered = manifest.fixtures.filter((entry) => decoder?.schema(entry.type) !== undefined);
+
+  it.each(covered)("should re-encode $file ($type) to the bytes Rust wrote", (entry) => {
+    const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const value = decoder?.decode(entry.type, data).value;
+
+    expect(encoder?.validate(entry.type, value)).toEqual([]);
+    expect(encoder?.encode(entry.type, value)).toEqual(data);
+  });
+});
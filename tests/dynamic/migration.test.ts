@@ -0,0 +1,143 @@
+/**
+ * Tests for simulating a protocol upgrade over old fixtures
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  simulateMigration,
+  trySimulateMigration,
+  DynamicEncoder,
+  IrError,
+  type MigrationFixture,
+} from "../../src/index.js";
+
+const OldIr = {
+  version: 1,
+  types: [
+    {
+      name: "Settings",
+      kind: "struct",
+      fields: [
+        { name: "name", type: { kind: "string" } },
+        { name: "brightness", type: { kind: "u8" } },
+      ],
+    },
+    { name: "Heartbeat", kind: "struct", fields: [{ name: "uptime", type: { kind: "u32" } }] },
+    { name: "Ping", kind: "unit_struct" },
+  ],
+};
+
+// Brightness became a percentage next to a new field, and Ping was renamed
+const NewIr = {
+  version: 1,
+  types: [
+    {
+      name: "Settings",
+      kind: "struct",
+      fields: [
+        { name: "name", type: { kind: "string" } },
+        { name: "brightness", type: { kind: "u8" } },
+        { name: "contrast", type: { kind: "u8" } },
+      ],
+    },
+    { name: "Heartbeat", kind: "struct", fields: [{ name: "uptime", type: { kind: "u32" } }] },
+    { name: "Probe", kind: "unit_struct" },
+  ],
+};
+
+const old = new DynamicEncoder(OldIr);
+
+function fixture(file: string, type: string, value: unknown): MigrationFixture {
+  return { file, type, data: old.encode(type, value) };
+}
+
+const fixtures = [
+  fixture("settings_dim.bin", "Settings", { name: "hall", brightness: 51 }),
+  fixture("settings_bright.bin", "Settings", { name: "lab", brightness: 255 }),
+  fixture("heartbeat.bin", "Heartbeat", { uptime: 600 }),
+];
+
+type OldSettings = { name: string; brightness: number };
+
+const migrations = {
+  Settings: (value: unknown) => {
+    const settings = value as OldSettings;
+    return { ...settings, brightness: Math.round(settings.brightness / 2.55), contrast: 50 };
+  },
+};
+
+describe("simulateMigration", () => {
+  it("should migrate every fixture that loads with the new IR", () => {
+    const report = simulateMigration(OldIr, NewIr, fixtures, migrations);
+    expect(report).toEqual({
+      checked: 3,
+      migrated: ["settings_dim.bin", "settings_bright.bin", "heartbeat.bin"],
+      failures: [],
+    });
+  });
+
+  it("should report values that do not match the new type", () => {
+    const report = simulateMigration(OldIr, NewIr, fixtures, {
+      Settings: (value) => ({ ...(value as OldSettings), contrast: 300 }),
+    });
+    expect(report.migrated).toEqual(["heartbeat.bin"]);
+    expect(report.failures).toHaveLength(2);
+    expect(report.failures[0]).toEqual({
+      file: "settings_dim.bin",
+      type: "Settings",
+      stage: "validate",
+      message: "Invalid Settings: Settings.contrast: 300 is out of range for u8",
+      issues: [{ path: "Settings.contrast", message: "300 is out of range for u8" }],
+    });
+  });
+
+  it("should require types without a migration to load unchanged", () => {
+    const report = simulateMigration(OldIr, NewIr, fixtures);
+    expect(report.failures.map((failure) => failure.file)).toEqual([
+      "settings_dim.bin",
+      "settings_bright.bin",
+    ]);
+    expect(report.failures[0]?.issues).toEqual([
+      { path: "Settings.contrast", message: "missing field" },
+    ]);
+  });
+
+  it("should report migrations that throw and fixtures that do not decode", () => {
+    const broken: MigrationFixture[] = [
+      { file: "cut.bin", type: "Heartbeat", data: new Uint8Array([0x80]) },
+      { file: "long.bin", type: "Heartbeat", data: new Uint8Array([0x01, 0x02]) },
+      fixture("settings_dim.bin", "Settings", { name: "hall", brightness: 51 }),
+    ];
+    const report = simulateMigration(OldIr, NewIr, broken, {
+      Settings: () => {
+        throw new Error("no contrast for hall");
+      },
+    });
+    expect(report.migrated).toEqual([]);
+    expect(report.failures.map(({ file, stage }) => [file, stage])).toEqual([
+      ["cut.bin", "decode"],
+      ["long.bin", "decode"],
+      ["settings_dim.bin", "migrate"],
+    ]);
+    expect(report.failures[1]?.message).toBe("1 bytes left after the Heartbeat");
+    expect(report.failures[2]?.message).toBe("no contrast for hall");
+  });
+
+  it("should migrate renamed types to their new name", () => {
+    const pings = [fixture("ping.bin", "Ping", {})];
+    expect(simulateMigration(OldIr, NewIr, pings).failures[0]?.message).toBe(
+      "Ping is not in the new IR"
+    );
+    const renamed = { renamed: { Ping: "Probe" } };
+    expect(simulateMigration(OldIr, NewIr, pings, {}, renamed).migrated).toEqual(["ping.bin"]);
+  });
+
+  it("should fail on migrations of types in neither IR", () => {
+    const unknown = trySimulateMigration(OldIr, NewIr, fixtures, { Reading: (value) => value });
+    expect(unknown.ok).toBe(false);
+    expect(() => simulateMigration(OldIr, NewIr, fixtures, { Ping: (value) => value })).toThrow(
+      "Migration of Ping to Ping, which is not in the new IR"
+    );
+    expect(() => simulateMigration({ version: 99, types: [] }, NewIr, fixtures)).toThrow(IrError);
+  });
+});
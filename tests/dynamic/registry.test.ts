@@ -0,0 +1,119 @@
+/**
+ * Tests for resolving a device's schemas through a schema registry
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  SchemaResolver,
+  RegistryError,
+  IrError,
+  deserialize,
+  struct,
+  u8,
+  f32,
+} from "../../src/index.js";
+
+// The firmware's Reading gained a field since the bindings were generated
+const DeviceIr = {
+  version: 1,
+  types: [
+    {
+      name: "Reading",
+      kind: "struct",
+      fields: [
+        { name: "sensor", type: { kind: "u8" } },
+        { name: "celsius", type: { kind: "f32" } },
+        { name: "battery", type: { kind: "u8" } },
+      ],
+    },
+  ],
+};
+
+const ReadingSchema = struct({ sensor: u8(), celsius: f32() });
+const Reading = { schema: ReadingSchema, schemaHash: 0xed07686b2b9034f9n };
+const DEVICE_HASH = 0x0123456789abcdefn;
+
+function registry(): { resolver: SchemaResolver; requests: string[] } {
+  const requests: string[] = [];
+  const fetch = (input: RequestInfo | URL): Promise<Response> => {
+    const url = String(input);
+    requests.push(url);
+    const found = url.endsWith("/ir/Reading/0123456789abcdef");
+    return Promise.resolve(
+      found
+        ? new Response(JSON.stringify(DeviceIr), { status: 200 })
+        : new Response('{"error": "no snapshot"}', { status: 404 })
+    );
+  };
+  return { resolver: new SchemaResolver({ url: "http://registry/", fetch }), requests };
+}
+
+describe("SchemaResolver", () => {
+  it("keeps the local bindings when the hashes match", async () => {
+    const { resolver, requests } = registry();
+    const resolved = await resolver.resolve("Reading", Reading.schemaHash, Reading);
+    expect(resolved).toEqual({ schema: ReadingSchema, source: "local" });
+    expect(requests).toEqual([]);
+  });
+
+  it("decodes with the registry's snapshot when they differ", async () => {
+    const { resolver, requests } = registry();
+    const resolved = await resolver.resolve("Reading", DEVICE_HASH, Reading);
+    expect(resolved.source).toBe("registry");
+    const frame = new Uint8Array([0x02, 0x00, 0x00, 0xb4, 0x41, 0x5a]);
+    expect(deserialize(resolved.schema, frame).value).toEqual({
+      sensor: 2,
+      celsius: 22.5,
+      battery: 90,
+    });
+    expect(resolved.decoder?.types).toEqual(["Reading"]);
+
+    // Fetched once
+    await resolver.resolve("Reading", DEVICE_HASH);
+    expect(requests).toEqual(["http://registry/ir/Reading/0123456789abcdef"]);
+  });
+
+  it("resolves every type against a handshake", async () => {
+    const { resolver } = registry();
+    const resolved = await resolver.resolveAll(
+      { Reading },
+      new Map([["Reading", DEVICE_HASH]])
+    );
+    expect(resolved.Reading.source).toBe("registry");
+    // A type the device sent no hash for keeps its bindings
+    const local = await resolver.tryResolveAll({ Reading }, {});
+    expect(local.ok && local.value["Reading"]?.source).toBe("local");
+  });
+
+  it("fails for a hash the registry does not know", async () => {
+    const { resolver, requests } = registry();
+    const result = await resolver.tryResolve("Reading", 1n, Reading);
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error).toBeInstanceOf(RegistryError);
+      expect((result.error as RegistryError).status).toBe(404);
+    }
+    // Failures are not kept
+    await expect(resolver.resolve("Reading", 1n)).rejects.toThrow(RegistryError);
+    expect(requests).toHaveLength(2);
+  });
+
+  it("fails for a snapshot without the type", async () => {
+    const fetch = (): Promise<Response> =>
+      Promise.resolve(new Response(JSON.stringify({ version: 1, types: [] })));
+    const resolver = new SchemaResolver({ url: "http://registry", fetch });
+    await expect(resolver.resolve("Reading", DEVICE_HASH)).rejects.toThrow(IrError);
+  });
+
+  it("fails when the registry cannot be reached", async () => {
+    const fetch = (): Promise<Response> => Promise.reject(new TypeError("fetch failed"));
+    const resolver = new SchemaResolver({ url: "http://registry", fetch });
+    const result = await resolver.tryResolve("Reading", DEVICE_HASH);
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error.message).toBe(
+        "Fetching http://registry/ir/Reading/0123456789abcdef failed: TypeError: fetch failed"
+      );
+    }
+  });
+});
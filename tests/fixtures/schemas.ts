@@ -21,6 +21,7 @@ import {
   f64,
   char,
   string,
+  bytes,
   seq,
   tuple,
   option,
@@ -34,8 +35,23 @@ import {
   newtypeStruct,
   unitStruct,
   tupleStruct,
+  unit,
+  lazy,
+  never,
+  range,
+  rangeInclusive,
   type InferType,
+  type Schema,
+  type SchemaOf,
 } from "../../src/types/schema.js";
+import { flags } from "../../src/transforms/flags.js";
+import { f16, bf16 } from "../../src/transforms/half.js";
+import { decimal } from "../../src/transforms/decimal.js";
+import { chronoDateTime, duration, offsetDateTime } from "../../src/transforms/datetime.js";
+import { uuid } from "../../src/transforms/uuid.js";
+import { nonZero } from "../../src/transforms/nonzero.js";
+import { identifier } from "../../src/transforms/identifier.js";
+import { CaptureSchema } from "../../src/telemetry/capture.js";
 
 /**
  * Test all primitive types
@@ -392,3 +408,582 @@ export const GameStateSchema = struct({
 });
 
 export type GameState = InferType<typeof GameStateSchema>;
+
+// ============================================================================
+// Enums Wrapping Maps of Enums
+// ============================================================================
+
+/**
+ * I/O channel mode
+ * Rust: enum Channel
+ */
+export const ChannelSchema = enumType("Channel", {
+  Analog: newtypeVariant("Analog", u16()),
+  Digital: newtypeVariant("Digital", bool()),
+  Off: unitVariant("Off"),
+});
+
+export type Channel = InferType<typeof ChannelSchema>;
+
+/**
+ * Channel layout - variants wrapping maps of sequences of enums
+ * Rust: enum ChannelLayout
+ */
+export const ChannelLayoutSchema = enumType("ChannelLayout", {
+  Empty: unitVariant("Empty"),
+  ByName: newtypeVariant("ByName", map(string(), seq(ChannelSchema))),
+  Grouped: structVariant("Grouped", {
+    groups: map(string(), seq(ChannelSchema)),
+    fallback: option(ChannelSchema),
+  }),
+  Layered: newtypeVariant("Layered", seq(map(string(), ChannelSchema))),
+});
+
+export type ChannelLayout = InferType<typeof ChannelLayoutSchema>;
+
+// ============================================================================
+// Ranges
+// ============================================================================
+
+/**
+ * Sensor calibration bounds
+ * Rust: struct Calibration (Range<u16>, RangeInclusive<f32>, Range<i64>)
+ */
+export const CalibrationSchema = struct({
+  raw_window: range(u16()),
+  temperature_c: rangeInclusive(f32()),
+  sample_ids: range(i64()),
+});
+
+export type Calibration = InferType<typeof CalibrationSchema>;
+
+// ============================================================================
+// Wrapping and Atomic Integers
+// ============================================================================
+
+/**
+ * Shared-state snapshot
+ * Rust: struct SharedCounters (Wrapping<u32>, AtomicU32, AtomicI64, AtomicBool, Wrapping<u8>)
+ * Wrapping<T> and atomics serialize exactly like the value they hold.
+ */
+export const SharedCountersSchema = struct({
+  sequence: u32(),
+  rx_packets: u32(),
+  drift_us: i64(),
+  link_up: bool(),
+  checksum: u8(),
+});
+
+export type SharedCounters = InferType<typeof SharedCountersSchema>;
+
+// ============================================================================
+// Telemetry - Ring Buffer Log Records
+// ============================================================================
+
+/**
+ * Log severity
+ * Rust: enum LogLevel
+ */
+export const LogLevelSchema = enumType("LogLevel", {
+  Debug: unitVariant("Debug"),
+  Info: unitVariant("Info"),
+  Warn: unitVariant("Warn"),
+  Error: unitVariant("Error"),
+});
+
+export type LogLevel = InferType<typeof LogLevelSchema>;
+
+/**
+ * Log record stored in an on-device ring buffer
+ * Rust: struct LogRecord
+ */
+export const LogRecordSchema = struct({
+  timestamp_ms: u32(),
+  level: LogLevelSchema,
+  message: string(),
+});
+
+export type LogRecord = InferType<typeof LogRecordSchema>;
+
+// ============================================================================
+// Bit-Packed Flags
+// ============================================================================
+
+/**
+ * Status booleans packed into one byte by a hand-written Serialize impl
+ * Rust: struct StatusFlags
+ */
+export const StatusFlagsSchema = flags(u8(), {
+  heater: 0,
+  pump: 1,
+  valve_open: 2,
+  alarm: 7,
+});
+
+export type StatusFlags = InferType<typeof StatusFlagsSchema>;
+
+/**
+ * Device status report
+ * Rust: struct DeviceStatus
+ */
+export const DeviceStatusSchema = struct({
+  flags: StatusFlagsSchema,
+  uptime_s: u32(),
+});
+
+export type DeviceStatus = InferType<typeof DeviceStatusSchema>;
+
+// ============================================================================
+// Half-Precision Floats
+// ============================================================================
+
+/**
+ * FusionSample { orientation: [f16; 4], accel: [bf16; 3], temperature: f16, peak: f16 }
+ */
+export const FusionSampleSchema = struct({
+  orientation: tuple(f16(), f16(), f16(), f16()), // Fixed-size arrays have no length prefix
+  accel: tuple(bf16(), bf16(), bf16()),
+  temperature: f16(),
+  peak: f16(),
+});
+
+export type FusionSample = InferType<typeof FusionSampleSchema>;
+
+// ============================================================================
+// Decimal Types
+// ============================================================================
+
+/**
+ * LedgerEntry { amount, refund, fx_rate, balance, limit: rust_decimal::Decimal }
+ */
+export const LedgerEntrySchema = struct({
+  amount: decimal(),
+  refund: decimal(),
+  fx_rate: decimal(),
+  balance: decimal(),
+  limit: decimal(),
+});
+
+export type LedgerEntry = InferType<typeof LedgerEntrySchema>;
+
+// ============================================================================
+// Date and Time Types
+// ============================================================================
+
+/**
+ * Schedule { created: chrono::DateTime<Utc>, starts: time::OffsetDateTime,
+ *   interval: std::time::Duration, deadline: Option<DateTime<Utc>> }
+ *
+ * In nanoseconds, which keep every digit chrono writes
+ */
+export const ScheduleSchema = struct({
+  created: chronoDateTime("nanos"),
+  starts: offsetDateTime("nanos"),
+  interval: duration("nanos"),
+  deadline: option(chronoDateTime("nanos")),
+});
+
+export type Schedule = InferType<typeof ScheduleSchema>;
+
+// ============================================================================
+// Ids
+// ============================================================================
+
+/**
+ * Account { id: uuid::Uuid, parent: Option<Uuid>, port: NonZeroU16,
+ *   sequence: NonZeroU64 }
+ */
+export const AccountSchema = struct({
+  id: uuid(),
+  parent: option(uuid()),
+  port: nonZero(u16()),
+  sequence: nonZero(u64()),
+});
+
+export type Account = InferType<typeof AccountSchema>;
+
+// ============================================================================
+// Identifier Enums
+// ============================================================================
+
+/**
+ * enum SensorField { Temperature, Humidity, Pressure }
+ */
+export const SensorFieldSchema = identifier("SensorField", ["Temperature", "Humidity", "Pressure"]);
+
+/**
+ * SensorSelection { primary: SensorField, enabled: HashMap<SensorField, bool>, order: Vec<SensorField> }
+ */
+export const SensorSelectionSchema = struct({
+  primary: SensorFieldSchema,
+  enabled: map(SensorFieldSchema, bool()),
+  order: seq(SensorFieldSchema),
+});
+
+export type SensorSelection = InferType<typeof SensorSelectionSchema>;
+
+// ============================================================================
+// Recursive Types
+// ============================================================================
+
+/**
+ * Binary tree node with boxed, optional children
+ * Rust: struct TreeNode (left/right: Option<Box<TreeNode>>)
+ */
+export interface TreeNode {
+  label: string;
+  value: number;
+  left: TreeNode | null;
+  right: TreeNode | null;
+}
+
+export const TreeNodeSchema = struct({
+  label: string(),
+  value: u32(),
+  left: option(lazy<TreeNode>((): Schema => TreeNodeSchema)),
+  right: option(lazy<TreeNode>((): Schema => TreeNodeSchema)),
+});
+
+// ============================================================================
+// Uninhabited Types
+// ============================================================================
+
+/**
+ * InfallibleReplies { replies: Vec<Result<u32, Never>> }
+ *
+ * Never is an empty enum, so the Err variant is excluded from the inferred type
+ */
+export const InfallibleRepliesSchema = struct({
+  replies: seq(
+    enumType("Result", {
+      Ok: newtypeVariant("Ok", u32()),
+      Err: newtypeVariant("Err", never()),
+    })
+  ),
+});
+
+export type InfallibleReplies = InferType<typeof InfallibleRepliesSchema>;
+
+// ============================================================================
+// Zero-Length and Single-Variant Messages
+// ============================================================================
+
+/**
+ * Struct with no fields; encodes to zero bytes
+ * Rust: struct EmptyStruct {}
+ */
+export const EmptyStructSchema = struct({});
+
+export type EmptyStruct = InferType<typeof EmptyStructSchema>;
+
+/**
+ * Rust: enum SingleVariant { Only }
+ */
+export const SingleVariantSchema = enumType("SingleVariant", {
+  Only: unitVariant("Only"),
+});
+
+export type SingleVariant = InferType<typeof SingleVariantSchema>;
+
+/**
+ * Rust: enum SingleDataVariant { Reading(u16) }
+ */
+export const SingleDataVariantSchema = enumType("SingleDataVariant", {
+  Reading: newtypeVariant("Reading", u16()),
+});
+
+export type SingleDataVariant = InferType<typeof SingleDataVariantSchema>;
+
+// ============================================================================
+// Renamed Fields and Variants
+// ============================================================================
+
+/**
+ * Rust: #[serde(rename_all = "snake_case")]
+ * enum DeviceMode {
+ *   LowPower,
+ *   #[serde(rename_all = "kebab-case")] Streaming { batch_size: u8 },
+ *   #[serde(rename = "off")] Disabled,
+ * }
+ */
+export const DeviceModeSchema = enumType("DeviceMode", {
+  low_power: unitVariant("low_power"),
+  streaming: structVariant("streaming", { "batch-size": u8() }),
+  off: unitVariant("off"),
+});
+
+export type DeviceMode = InferType<typeof DeviceModeSchema>;
+
+/**
+ * Rust: #[serde(rename_all = "camelCase")]
+ * struct DeviceSettings {
+ *   sample_rate_hz: u32,
+ *   #[serde(rename = "id")] device_id: u16,
+ *   mode: DeviceMode,
+ * }
+ */
+export const DeviceSettingsSchema = struct({
+  sampleRateHz: u32(),
+  id: u16(),
+  mode: DeviceModeSchema,
+});
+
+export type DeviceSettings = InferType<typeof DeviceSettingsSchema>;
+
+// ============================================================================
+// Skipped and Defaulted Fields
+// ============================================================================
+
+/**
+ * The skipped field is not on the wire, so it has no schema
+ * Rust: struct Session { id: u32, #[serde(skip)] cache: Vec<u8>, #[serde(default)] retries: u8 }
+ */
+export const SessionSchema = struct({
+  id: u32(),
+  retries: u8(),
+});
+
+export type Session = InferType<typeof SessionSchema>;
+
+// ============================================================================
+// Length-Limited Strings
+// ============================================================================
+
+/**
+ * Rust: struct DeviceLabel {
+ *   #[postcard_ts(max_len = 16)] name: String,
+ *   #[postcard_ts(max_len = 4)] unit: Option<String>,
+ * }
+ */
+export const DeviceLabelSchema = struct({
+  name: string({ maxBytes: 16 }),
+  unit: option(string({ maxBytes: 4 })),
+});
+
+export type DeviceLabel = InferType<typeof DeviceLabelSchema>;
+
+// ============================================================================
+// Non-String Map Keys
+// ============================================================================
+
+/**
+ * Rust: struct KeyedMaps { by_id: HashMap<u32, String>, by_color: HashMap<DragonColor, u16> }
+ */
+export const KeyedMapsSchema = struct({
+  by_id: map(u32(), string()),
+  by_color: map(DragonColorSchema, u16()),
+});
+
+export type KeyedMaps = InferType<typeof KeyedMapsSchema>;
+
+/**
+ * Rust: type CellMap = BTreeMap<(u8, u8), DragonColor>
+ */
+export const CellMapSchema = map(tuple(u8(), u8()), DragonColorSchema);
+
+export type CellMap = InferType<typeof CellMapSchema>;
+
+/**
+ * Rust: struct SensorId(u16)
+ */
+export const SensorIdSchema = newtypeStruct("SensorId", u16());
+
+export type SensorId = InferType<typeof SensorIdSchema>;
+
+/**
+ * Rust: struct WideKeys {
+ *   by_serial: BTreeMap<u64, String>,
+ *   by_offset: BTreeMap<i16, u8>,
+ *   by_flag: BTreeMap<bool, u8>,
+ *   by_letter: BTreeMap<char, u16>,
+ *   by_sensor: BTreeMap<SensorId, f32>,
+ * }
+ */
+export const WideKeysSchema = struct({
+  by_serial: map(u64(), string()),
+  by_offset: map(i16(), u8()),
+  by_flag: map(bool(), u8()),
+  by_letter: map(char(), u16()),
+  by_sensor: map(SensorIdSchema, f32()),
+});
+
+export type WideKeys = InferType<typeof WideKeysSchema>;
+
+/**
+ * Rust: type SlotMap = BTreeMap<Option<u8>, String>
+ */
+export const SlotMapSchema = map(option(u8()), string());
+
+export type SlotMap = InferType<typeof SlotMapSchema>;
+
+// ============================================================================
+// Optional and Empty Lists
+// ============================================================================
+
+/**
+ * Rust: struct ListShapes {
+ *   maybe_items: Option<Vec<u16>>,
+ *   sparse_items: Vec<Option<u16>>,
+ *   items: Vec<u16>,
+ * }
+ */
+export const ListShapesSchema = struct({
+  maybe_items: option(seq(u16())),
+  sparse_items: seq(option(u16())),
+  items: seq(u16()),
+});
+
+export type ListShapes = InferType<typeof ListShapesSchema>;
+
+// ============================================================================
+// Byte Strings
+// ============================================================================
+
+/**
+ * Rust: struct FirmwareChunk {
+ *   offset: u32,
+ *   #[serde(with = "serde_bytes")] data: Vec<u8>,
+ *   signature: Option<serde_bytes::ByteBuf>,
+ * }
+ */
+export const FirmwareChunkSchema = struct({
+  offset: u32(),
+  data: bytes(),
+  signature: option(bytes()),
+});
+
+export type FirmwareChunk = InferType<typeof FirmwareChunkSchema>;
+
+// ============================================================================
+// Generic Types
+// ============================================================================
+
+/**
+ * Rust: struct Envelope<T> { seq: u32, payload: T }
+ */
+export const EnvelopeSchema = <T extends Schema>(T: T) =>
+  struct({
+    seq: u32(),
+    payload: T,
+  });
+
+export type Envelope<T> = InferType<ReturnType<typeof EnvelopeSchema<SchemaOf<T>>>>;
+
+/**
+ * Rust: enum Reply<T> { Done(T), Busy, Failed { code: u16, partial: Option<T> } }
+ */
+export const ReplySchema = <T extends Schema>(T: T) =>
+  enumType("Reply", {
+    Done: newtypeVariant("Done", T),
+    Busy: unitVariant("Busy"),
+    Failed: structVariant("Failed", { code: u16(), partial: option(T) }),
+  });
+
+export type Reply<T> = InferType<ReturnType<typeof ReplySchema<SchemaOf<T>>>>;
+
+/**
+ * Rust: struct Exchange { request: Envelope<String>, replies: Vec<Envelope<Reply<Coordinates>>> }
+ */
+export const ExchangeSchema = struct({
+  request: EnvelopeSchema(string()),
+  replies: seq(EnvelopeSchema(ReplySchema(CoordinatesSchema))),
+});
+
+export type Exchange = InferType<typeof ExchangeSchema>;
+
+// ============================================================================
+// Fixture Registry
+// ============================================================================
+
+/**
+ * Schemas by Rust type name, as recorded in test-fixtures/fixtures/manifest.json
+ *
+ * Every fixture written by the generator is decoded with the schema registered
+ * here for its type. A fixture whose type is missing fails the suite.
+ */
+export const FIXTURE_SCHEMAS: Readonly<Record<string, Schema>> = {
+  Primitives: PrimitivesSchema,
+  Collections: CollectionsSchema,
+  InnerStruct: InnerStructSchema,
+  Nested: NestedSchema,
+  ComplexEnum: ComplexEnumSchema,
+  EdgeCases: EdgeCasesSchema,
+  NewtypeStruct: NewtypeStructSchema,
+  UnitStruct: UnitStructSchema,
+  TupleStruct: TupleStructSchema,
+  Coordinates: CoordinatesSchema,
+  Element: ElementSchema,
+  Weapon: WeaponSchema,
+  Item: ItemSchema,
+  Inventory: InventorySchema,
+  Player: PlayerSchema,
+  DragonColor: DragonColorSchema,
+  DragonData: DragonDataSchema,
+  Enemy: EnemySchema,
+  Location: LocationSchema,
+  BossInfo: BossInfoSchema,
+  World: WorldSchema,
+  PlayerAction: PlayerActionSchema,
+  GameEvent: GameEventSchema,
+  Difficulty: DifficultySchema,
+  GameMetadata: GameMetadataSchema,
+  GameState: GameStateSchema,
+  Channel: ChannelSchema,
+  ChannelLayout: ChannelLayoutSchema,
+  "Vec<ChannelLayout>": seq(ChannelLayoutSchema),
+  "Vec<Coordinates>": seq(CoordinatesSchema),
+  "Vec<i16>": seq(i16()),
+  "Vec<u8>": seq(u8()),
+  Calibration: CalibrationSchema,
+  SharedCounters: SharedCountersSchema,
+  LogLevel: LogLevelSchema,
+  LogRecord: LogRecordSchema,
+  Capture: CaptureSchema,
+  StatusFlags: StatusFlagsSchema,
+  DeviceStatus: DeviceStatusSchema,
+  FusionSample: FusionSampleSchema,
+  LedgerEntry: LedgerEntrySchema,
+  Schedule: ScheduleSchema,
+  Account: AccountSchema,
+  SensorSelection: SensorSelectionSchema,
+  TreeNode: TreeNodeSchema,
+  InfallibleReplies: InfallibleRepliesSchema,
+  "()": unit(),
+  EmptyStruct: EmptyStructSchema,
+  SingleVariant: SingleVariantSchema,
+  SingleDataVariant: SingleDataVariantSchema,
+  DeviceMode: DeviceModeSchema,
+  DeviceSettings: DeviceSettingsSchema,
+  Session: SessionSchema,
+  DeviceLabel: DeviceLabelSchema,
+  KeyedMaps: KeyedMapsSchema,
+  "BTreeMap<(u8, u8), DragonColor>": CellMapSchema,
+  "BTreeMap<Option<u8>, String>": SlotMapSchema,
+  SensorId: SensorIdSchema,
+  WideKeys: WideKeysSchema,
+  ListShapes: ListShapesSchema,
+  FirmwareChunk: FirmwareChunkSchema,
+  "Envelope<Vec<u16>>": EnvelopeSchema(seq(u16())),
+  Exchange: ExchangeSchema,
+};
+
+/**
+ * Schemas for the types named in error_cases.json and duplicate_keys.json:
+ * the fixture structs plus the Rust primitives and std types used by the
+ * malformed and hand-crafted inputs
+ */
+export const ERROR_CASE_SCHEMAS: Readonly<Record<string, Schema>> = {
+  ...FIXTURE_SCHEMAS,
+  bool: bool(),
+  u8: u8(),
+  u16: u16(),
+  u32: u32(),
+  f32: f32(),
+  char: char(),
+  String: string(),
+  "Option<u8>": option(u8()),
+  "HashMap<String, u32>": map(string(), u32()),
+  "HashMap<u64, u8>": map(u64(), u8()),
+  "HashMap<SensorField, bool>": map(SensorFieldSchema, bool()),
+  "HashMap<(u8, u8), String>": map(tuple(u8(), u8()), string()),
+};
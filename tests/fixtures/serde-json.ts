@@ -0,0 +1,119 @@
+/**
+ * Convert decoded values to the JSON shape serde_json produces
+ *
+ * The fixture generator records every value with serde_json next to its
+ * binary fixture (see test-fixtures/src/manifest.rs). Converting the decoded
+ * TypeScript value to the same shape lets one generic test compare them:
+ * - Enums are externally tagged: "Unit" or { "Variant": value }
+ * - Maps become objects with string keys; maps with tuple or struct keys,
+ *   which serde_json cannot write, are recorded as [key, value] pairs instead
+ * - 64/128-bit integers become numbers when safe, decimal strings otherwise
+ * - f32 values use their shortest representation, as serde_json prints them
+ * - Unit, unit structs and None become null
+ * - Uuids stay text, which uuid writes for serde_json instead of the bytes
+ */
+
+import { shortestF32, type Schema } from "../../src/index.js";
+
+/* eslint-disable @typescript-eslint/no-unsafe-argument, @typescript-eslint/no-unsafe-member-access, @typescript-eslint/no-explicit-any */
+export function toSerdeJson(schema: Schema, value: any): unknown {
+  switch (schema.kind) {
+    case "bool":
+    case "i8":
+    case "i16":
+    case "i32":
+    case "u8":
+    case "u16":
+    case "u32":
+    case "f64":
+    case "char":
+    case "string":
+      return value;
+    case "i64":
+    case "i128":
+    case "u64":
+    case "u128":
+      return bigintToJson(value);
+    case "f32":
+      return shortestF32(value);
+    case "bytes":
+      return Array.from(value as Uint8Array);
+    case "unit":
+    case "unit_struct":
+      return null;
+    case "option":
+      return value === null ? null : toSerdeJson(schema.inner, value);
+    case "newtype_struct":
+      return toSerdeJson(schema.inner, value);
+    case "seq":
+      return (value as unknown[]).map((item) => toSerdeJson(schema.item, item));
+    case "tuple":
+    case "tuple_struct":
+      return (value as unknown[]).map((item, i) => toSerdeJson(schema.items[i], item));
+    case "map": {
+      const object: Record<string, unknown> = {};
+      for (const [key, entry] of value as Map<unknown, unknown>) {
+        object[String(toSerdeJson(schema.key, key))] = toSerdeJson(schema.value, entry);
+      }
+      return object;
+    }
+    case "struct": {
+      const object: Record<string, unknown> = {};
+      for (const [name, field] of Object.entries(schema.fields)) {
+        object[name] = toSerdeJson(field, value[name]);
+      }
+      return object;
+    }
+    case "enum":
+      return enumToJson(schema.variants, value);
+    case "transform":
+      if (schema.inner.kind === "bytes" && typeof value === "string") {
+        return value;
+      }
+      return toSerdeJson(schema.inner, schema.encode(value));
+    case "lazy":
+      return toSerdeJson(schema.get(), value);
+    case "never":
+      throw new Error("Uninhabited types have no values");
+  }
+}
+
+/**
+ * Convert a decoded map to the `[key, value]` pairs recorded for maps whose
+ * keys serde_json cannot write, in the map's (wire) order
+ */
+export function toSerdeJsonEntries(schema: Schema, value: unknown): unknown[] {
+  if (schema.kind !== "map") {
+    throw new Error(`Expected a map schema, got ${schema.kind}`);
+  }
+  return Array.from(value as Map<unknown, unknown>, ([key, entry]) => [
+    toSerdeJson(schema.key, key),
+    toSerdeJson(schema.value, entry),
+  ]);
+}
+
+function enumToJson(variants: Record<string, any>, value: any): unknown {
+  const variant = variants[value.type];
+  switch (variant.kind) {
+    case "unit_variant":
+      return variant.name;
+    case "newtype_variant":
+      return { [variant.name]: toSerdeJson(variant.inner, value.value) };
+    case "tuple_variant":
+      return {
+        [variant.name]: (value.value as unknown[]).map((item, i) =>
+          toSerdeJson(variant.items[i], item)
+        ),
+      };
+    case "struct_variant":
+      return { [variant.name]: toSerdeJson({ kind: "struct", fields: variant.fields }, value.value) };
+    default:
+      throw new Error(`Unknown variant kind: ${String(variant.kind)}`);
+  }
+}
+/* eslint-enable @typescript-eslint/no-unsafe-argument, @typescript-eslint/no-unsafe-member-access, @typescript-eslint/no-explicit-any */
+
+function bigintToJson(value: bigint): number | string {
+  const asNumber = Number(value);
+  return Number.isSafeInteger(asNumber) ? asNumber : value.toString();
+}
@@ -0,0 +1,110 @@
+/**
+ * Tests for float display helpers
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  deserialize,
+  serialize,
+  f32,
+  shortestF32,
+  formatFloat,
+  parseFloatInput,
+  tryParseFloatInput,
+  FloatParseError,
+} from "../../src/index.js";
+
+describe("shortestF32()", () => {
+  it("should remove f32 widening noise", () => {
+    const decoded = deserialize(f32(), serialize(f32(), 20.3)).value;
+    expect(decoded).not.toBe(20.3);
+    expect(shortestF32(decoded)).toBe(20.3);
+  });
+
+  it("should keep values that are exact in f32", () => {
+    expect(shortestF32(85.5)).toBe(85.5);
+    expect(shortestF32(-32.005859375)).toBe(-32.00586);
+    expect(shortestF32(0)).toBe(0);
+  });
+
+  it("should keep distinct f32 values distinct", () => {
+    const next = Math.fround(85.5000076);
+    expect(shortestF32(next)).toBe(85.50001);
+    expect(Math.fround(shortestF32(next))).toBe(next);
+  });
+
+  it("should pass through non-finite values", () => {
+    expect(shortestF32(NaN)).toBeNaN();
+    expect(shortestF32(Infinity)).toBe(Infinity);
+  });
+});
+
+describe("formatFloat()", () => {
+  it("should use the shortest round-trip representation by default", () => {
+    expect(formatFloat(0.1 + 0.2)).toBe("0.30000000000000004");
+    expect(formatFloat(Math.fround(20.3), { precision: "f32" })).toBe("20.3");
+  });
+
+  it("should round to significant digits and trim trailing zeros", () => {
+    expect(formatFloat(85.5000076, { significantDigits: 4 })).toBe("85.5");
+    expect(formatFloat(3.14159, { significantDigits: 3 })).toBe("3.14");
+    expect(formatFloat(1200, { significantDigits: 2 })).toBe("1200");
+  });
+
+  it("should keep trailing zeros when asked", () => {
+    expect(formatFloat(85.5, { significantDigits: 4, keepTrailingZeros: true })).toBe("85.50");
+  });
+
+  it("should never use exponent notation", () => {
+    expect(formatFloat(1.5e-7)).toBe("0.00000015");
+    expect(formatFloat(1e21)).toBe("1000000000000000000000");
+    expect(formatFloat(1234567, { significantDigits: 3 })).toBe("1230000");
+    expect(formatFloat(-0.000123456, { significantDigits: 2 })).toBe("-0.00012");
+  });
+
+  it("should format non-finite values", () => {
+    expect(formatFloat(NaN)).toBe("NaN");
+    expect(formatFloat(-Infinity)).toBe("-Infinity");
+  });
+
+  it("should reject invalid significant digit counts", () => {
+    expect(() => formatFloat(1, { significantDigits: 0 })).toThrow(RangeError);
+    expect(() => formatFloat(1, { significantDigits: 1.5 })).toThrow(RangeError);
+  });
+});
+
+describe("parseFloatInput()", () => {
+  it("should parse plain decimals", () => {
+    expect(parseFloatInput("85.5")).toBe(85.5);
+    expect(parseFloatInput("  -0.25 ")).toBe(-0.25);
+    expect(parseFloatInput("+3")).toBe(3);
+    expect(parseFloatInput(".5")).toBe(0.5);
+    expect(parseFloatInput("5.")).toBe(5);
+    expect(parseFloatInput("1.5e-3")).toBe(0.0015);
+  });
+
+  it("should round to f32 when asked", () => {
+    expect(parseFloatInput("20.3", { precision: "f32" })).toBe(Math.fround(20.3));
+  });
+
+  it("should reject locale-specific and non-numeric input", () => {
+    for (const text of ["", "abc", "1,5", "1 000", "NaN", "Infinity", "0x10", "1.2.3"]) {
+      const result = tryParseFloatInput(text);
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(FloatParseError);
+      }
+    }
+  });
+
+  it("should reject values out of range", () => {
+    expect(tryParseFloatInput("1e400").ok).toBe(false);
+    expect(tryParseFloatInput("1e39", { precision: "f32" }).ok).toBe(false);
+  });
+
+  it("should round trip with formatFloat", () => {
+    for (const value of [0.1, -1234.5678, 1e-9, 6.02214076e23]) {
+      expect(parseFloatInput(formatFloat(value))).toBe(value);
+    }
+  });
+});
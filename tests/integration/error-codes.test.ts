@@ -0,0 +1,61 @@
+/**
+ * Error-code conformance with Rust
+ *
+ * The Rust generator decodes a set of malformed inputs with postcard and
+ * records the catalogue code each one produced (via the postcard-ts-error-codes
+ * crate) in error_cases.json. Decoding the same bytes here must report the
+ * same code, and both sides must list the same catalogue.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import { ErrorCode, tryDeserialize } from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS } from "../fixtures/schemas.js";
+
+interface ErrorCase {
+  readonly name: string;
+  readonly type: string;
+  readonly bytes: number[];
+  readonly code: string;
+}
+
+const ERROR_CASES_PATH = join(process.cwd(), "test-fixtures", "fixtures", "error_cases.json");
+
+function loadErrorCases(): { codes: string[]; cases: ErrorCase[] } {
+  if (!existsSync(ERROR_CASES_PATH)) {
+    return { codes: [], cases: [] };
+  }
+  return JSON.parse(readFileSync(ERROR_CASES_PATH, "utf8")) as {
+    codes: string[];
+    cases: ErrorCase[];
+  };
+}
+
+const { codes, cases } = loadErrorCases();
+
+describe("Error codes shared with Rust", () => {
+  it("should exist (run `npm run generate-fixtures`)", () => {
+    expect(existsSync(ERROR_CASES_PATH)).toBe(true);
+    expect(cases.length).toBeGreaterThan(0);
+  });
+
+  it("should list the same catalogue as the Rust crate", () => {
+    expect(Object.values(ErrorCode)).toEqual(codes);
+  });
+
+  describe.each(cases)("$name ($type)", (errorCase) => {
+    it(`should fail with ${errorCase.code}`, () => {
+      const schema = ERROR_CASE_SCHEMAS[errorCase.type];
+      if (schema === undefined) {
+        throw new Error(`No schema for Rust type "${errorCase.type}"`);
+      }
+
+      const result = tryDeserialize(schema, new Uint8Array(errorCase.bytes));
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error.code).toBe(errorCase.code);
+      }
+    });
+  });
+});
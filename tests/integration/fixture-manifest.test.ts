@@ -0,0 +1,83 @@
+/**
+ * Manifest-driven fixture tests
+ *
+ * The Rust generator records every fixture it writes in manifest.json along
+ * with its type name, length, bytes as hex and serde_json value (or, for maps
+ * with tuple or struct keys, its entries). Each entry is checked against its
+ * file, decoded with the schema registered for its type, compared against
+ * that value, and re-encoded to the original bytes.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import { deserialize, serialize } from "../../src/index.js";
+import { FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson, toSerdeJsonEntries } from "../fixtures/serde-json.js";
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+  readonly length: number;
+  readonly hex: string;
+  readonly value?: unknown;
+  readonly entries?: unknown[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const MANIFEST_PATH = join(FIXTURES_DIR, "manifest.json");
+
+function loadManifest(): ManifestEntry[] {
+  if (!existsSync(MANIFEST_PATH)) {
+    return [];
+  }
+  const manifest = JSON.parse(readFileSync(MANIFEST_PATH, "utf8")) as {
+    fixtures: ManifestEntry[];
+  };
+  return manifest.fixtures;
+}
+
+const entries = loadManifest();
+
+describe("Fixture manifest", () => {
+  it("should exist (run `npm run generate-fixtures`)", () => {
+    expect(existsSync(MANIFEST_PATH)).toBe(true);
+    expect(entries.length).toBeGreaterThan(0);
+  });
+
+  describe.each(entries)("$file ($type)", (entry) => {
+    const schema = FIXTURE_SCHEMAS[entry.type];
+    const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+
+    it("should have a registered schema", () => {
+      if (schema === undefined) {
+        throw new Error(
+          `No schema registered for Rust type "${entry.type}"; add it to FIXTURE_SCHEMAS in tests/fixtures/schemas.ts`
+        );
+      }
+    });
+
+    it("should hold the bytes recorded in the manifest", () => {
+      expect(data.length).toBe(entry.length);
+      const hex = Array.from(data, (byte) => byte.toString(16).padStart(2, "0")).join("");
+      expect(hex).toBe(entry.hex);
+    });
+
+    it.runIf(schema !== undefined)("should decode to the value Rust serialized", () => {
+      if (schema === undefined) return;
+      const result = deserialize(schema, data);
+      expect(result.bytesRead).toBe(data.length);
+      if (entry.entries !== undefined) {
+        expect(toSerdeJsonEntries(schema, result.value)).toEqual(entry.entries);
+      } else {
+        expect(toSerdeJson(schema, result.value)).toEqual(entry.value);
+      }
+    });
+
+    it.runIf(schema !== undefined)("should re-encode to the same bytes", () => {
+      if (schema === undefined) return;
+      const { value } = deserialize(schema, data);
+      expect(serialize(schema, value)).toEqual(data);
+    });
+  });
+});
@@ -0,0 +1,92 @@
+/**
+ * Interop across postcard versions
+ *
+ * `test-fixtures/interop-matrix.sh` regenerates the fixtures with several
+ * postcard versions into fixtures/postcard-<version>/. Every version's
+ * fixtures must hold the same bytes as the main fixtures (which the
+ * generator writes identically every run) and decode to the same values, and
+ * its malformed inputs must fail with the same codes.
+ *
+ * The matrix is opt-in; without generated version directories the suite is
+ * skipped.
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readdirSync, readFileSync } from "fs";
+import { join } from "path";
+import { deserialize, tryDeserialize } from "../../src/index.js";
+import { ERROR_CASE_SCHEMAS, FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+interface Manifest {
+  readonly postcard: string;
+  readonly fixtures: { readonly file: string; readonly type: string; readonly hex: string }[];
+}
+
+interface ErrorCases {
+  readonly cases: { readonly name: string; readonly type: string; readonly bytes: number[] }[];
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+
+function readJson<T>(dir: string, file: string): T {
+  return JSON.parse(readFileSync(join(dir, file), "utf8")) as T;
+}
+
+function decodeFixture(dir: string, file: string, type: string): unknown {
+  const schema = FIXTURE_SCHEMAS[type];
+  if (schema === undefined) {
+    throw new Error(`No schema registered for Rust type "${type}"`);
+  }
+  const data = new Uint8Array(readFileSync(join(dir, file)));
+  const result = deserialize(schema, data);
+  expect(result.bytesRead).toBe(data.length);
+  return toSerdeJson(schema, result.value);
+}
+
+function errorCodes(dir: string): Record<string, string | undefined> {
+  const codes: Record<string, string | undefined> = {};
+  for (const { name, type, bytes } of readJson<ErrorCases>(dir, "error_cases.json").cases) {
+    const schema = ERROR_CASE_SCHEMAS[type];
+    if (schema === undefined) {
+      throw new Error(`No schema for Rust type "${type}"`);
+    }
+    const result = tryDeserialize(schema, new Uint8Array(bytes));
+    codes[name] = result.ok ? undefined : result.error.code;
+  }
+  return codes;
+}
+
+const versions = existsSync(FIXTURES_DIR)
+  ? readdirSync(FIXTURES_DIR)
+      .filter((name) => name.startsWith("postcard-"))
+      .map((name) => ({ name, dir: join(FIXTURES_DIR, name) }))
+  : [];
+
+describe.skipIf(versions.length === 0)("Fixtures across postcard versions", () => {
+  describe.each(versions)("$name", ({ name, dir }) => {
+    const manifest = readJson<Manifest>(dir, "manifest.json");
+
+    it("should have been written by that postcard version", () => {
+      expect(`postcard-${manifest.postcard}`).toBe(name);
+    });
+
+    it("should hold the same fixtures as the main corpus", () => {
+      const main = readJson<Manifest>(FIXTURES_DIR, "manifest.json");
+      expect(manifest.fixtures.map((f) => f.file)).toEqual(main.fixtures.map((f) => f.file));
+    });
+
+    it("should write the same bytes as the main corpus", () => {
+      const main = readJson<Manifest>(FIXTURES_DIR, "manifest.json");
+      expect(manifest.fixtures.map((f) => f.hex)).toEqual(main.fixtures.map((f) => f.hex));
+    });
+
+    it.each(manifest.fixtures)("should decode $file identically", ({ file, type }) => {
+      expect(decodeFixture(dir, file, type)).toEqual(decodeFixture(FIXTURES_DIR, file, type));
+    });
+
+    it("should fail on malformed inputs with the same codes", () => {
+      expect(errorCodes(dir)).toEqual(errorCodes(FIXTURES_DIR));
+    });
+  });
+});
@@ -7,6 +7,9 @@ import { describe, it, expect, beforeAll } from "vitest";
 import { readFileSync } from "fs";
 import { join } from "path";
 import { deserialize } from "../../src/codec/deserializer.js";
+import { serialize } from "../../src/codec/serializer.js";
+import { decodeColumns, decodeSeqInto } from "../../src/codec/columnar.js";
+import { seq, i16 } from "../../src/types/schema.js";
 import {
   PrimitivesSchema,
   CollectionsSchema,
@@ -17,6 +20,16 @@ import {
   UnitStructSchema,
   TupleStructSchema,
   GameStateSchema,
+  ChannelLayoutSchema,
+  CalibrationSchema,
+  SharedCountersSchema,
+  InfallibleRepliesSchema,
+  FusionSampleSchema,
+  LedgerEntrySchema,
+  ScheduleSchema,
+  AccountSchema,
+  SensorSelectionSchema,
+  CoordinatesSchema,
 } from "../fixtures/schemas.js";
 import type {
   Primitives,
@@ -63,6 +76,12 @@ describe("Rust postcard compatibility", () => {
       expect(result.value.char_field).toBe("🦀");
       expect(result.value.string_field).toBe("Hello, postcard!");
     });
+
+    it("should re-encode the 64- and 128-bit fields byte for byte", () => {
+      const data = loadFixture("primitives.bin");
+      const { value } = deserialize(PrimitivesSchema, data);
+      expect(serialize(PrimitivesSchema, value)).toEqual(data);
+    });
   });
 
   describe("Collections", () => {
@@ -340,4 +359,222 @@ describe("Rust postcard compatibility", () => {
       expect(game.metadata.difficulty.type).toBe("Normal");
     });
   });
+
+  describe("Enums wrapping maps of enums", () => {
+    it("should deserialize variants holding maps of sequences of enums", () => {
+      const data = loadFixture("channel_layouts.bin");
+      const result = deserialize(seq(ChannelLayoutSchema), data);
+      const layouts = result.value;
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(layouts).toHaveLength(6);
+      expect(layouts[0]).toEqual({ type: "Empty" });
+
+      const byName = layouts[1];
+      expect(byName?.type).toBe("ByName");
+      if (byName?.type === "ByName") {
+        expect(byName.value.size).toBe(3);
+        expect(byName.value.get("inputs")).toEqual([
+          { type: "Analog", value: 512 },
+          { type: "Digital", value: true },
+          { type: "Off" },
+        ]);
+        expect(byName.value.get("unused")).toEqual([]);
+        expect(byName.value.get("outputs")).toEqual([{ type: "Digital", value: false }]);
+      }
+
+      const grouped = layouts[2];
+      expect(grouped?.type).toBe("Grouped");
+      if (grouped?.type === "Grouped") {
+        expect(grouped.value.groups.get("a")).toEqual([
+          { type: "Off" },
+          { type: "Analog", value: 1023 },
+        ]);
+        expect(grouped.value.fallback).toEqual({ type: "Digital", value: true });
+      }
+
+      const emptyGrouped = layouts[3];
+      if (emptyGrouped?.type === "Grouped") {
+        expect(emptyGrouped.value.groups.size).toBe(0);
+        expect(emptyGrouped.value.fallback).toBeNull();
+      }
+
+      const layered = layouts[4];
+      expect(layered?.type).toBe("Layered");
+      if (layered?.type === "Layered") {
+        expect(layered.value).toHaveLength(2);
+        expect(layered.value[0]?.get("x")).toEqual({ type: "Analog", value: 300 });
+        expect(layered.value[0]?.get("y")).toEqual({ type: "Off" });
+        expect(layered.value[1]?.size).toBe(0);
+      }
+
+      const emptyByName = layouts[5];
+      if (emptyByName?.type === "ByName") {
+        expect(emptyByName.value.size).toBe(0);
+      }
+    });
+
+    it("should re-encode to the exact bytes Rust produced", () => {
+      const data = loadFixture("channel_layouts.bin");
+      const schema = seq(ChannelLayoutSchema);
+      expect(serialize(schema, deserialize(schema, data).value)).toEqual(data);
+    });
+  });
+
+  describe("Ranges", () => {
+    it("should deserialize Range and RangeInclusive as start/end structs", () => {
+      const data = loadFixture("calibration.bin");
+      const result = deserialize(CalibrationSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value.raw_window).toEqual({ start: 100, end: 4000 });
+      expect(result.value.temperature_c).toEqual({ start: -20.5, end: 85 });
+      expect(result.value.sample_ids).toEqual({ start: -5n, end: 10000000000n });
+    });
+  });
+
+  describe("Wrapping and atomic integers", () => {
+    it("should deserialize Wrapping<T> and atomics as plain values", () => {
+      const data = loadFixture("shared_counters.bin");
+      const result = deserialize(SharedCountersSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        sequence: 2, // u32::MAX + 3, wrapped
+        rx_packets: 70000,
+        drift_us: -1500n,
+        link_up: true,
+        checksum: 4, // 250u8 + 10, wrapped
+      });
+    });
+  });
+
+  describe("Uninhabited types", () => {
+    it("should deserialize Result<T, Never> with only Ok values", () => {
+      const data = loadFixture("infallible_replies.bin");
+      const result = deserialize(InfallibleRepliesSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        replies: [
+          { type: "Ok", value: 7 },
+          { type: "Ok", value: 300 },
+        ],
+      });
+    });
+  });
+
+  describe("Half-precision floats", () => {
+    it("should deserialize half::f16 and half::bf16 fields as numbers", () => {
+      const data = loadFixture("fusion_sample.bin");
+      const result = deserialize(FusionSampleSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        orientation: [0.70703125, -0.70703125, 0, 2 ** -23], // 1e-7 rounds to a subnormal
+        accel: [9.8125, -0.0150146484375, 3.00405527047391e38],
+        temperature: 21.375,
+        peak: Infinity, // 70000 overflows f16
+      });
+    });
+  });
+
+  describe("Decimals", () => {
+    it("should deserialize rust_decimal::Decimal as its exact string", () => {
+      const data = loadFixture("ledger_entry.bin");
+      const result = deserialize(LedgerEntrySchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        amount: "1234.50",
+        refund: "-0.01",
+        fx_rate: "0.0000000000000000000000000001",
+        balance: "0",
+        limit: "79228162514264337593543950335", // Decimal::MAX
+      });
+      expect(serialize(LedgerEntrySchema, result.value)).toEqual(data);
+    });
+  });
+
+  describe("Date and time types", () => {
+    it("should deserialize chrono, time and std durations to exact nanoseconds", () => {
+      const data = loadFixture("schedule.bin");
+      const result = deserialize(ScheduleSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        created: 1_700_000_000_123_000_000n,
+        starts: 1_700_003_600_000_250_000n,
+        interval: 90_000_500_000n,
+        deadline: 1_700_086_400_000_000_001n,
+      });
+      expect(serialize(ScheduleSchema, result.value)).toEqual(data);
+    });
+
+    it("should deserialize years outside 0000-9999 and Duration::MAX", () => {
+      const data = loadFixture("schedule_extremes.bin");
+      const result = deserialize(ScheduleSchema, data);
+
+      expect(result.value).toEqual({
+        created: -62_198_755_199_000_000_001n, // -0001-01-01T00:00:00.999999999Z
+        starts: 253_402_300_799_000_000_000n, // 9999-12-31T23:59:59Z
+        interval: 18_446_744_073_709_551_615_999_999_999n,
+        deadline: 253_402_300_800_000_000_000n, // +10000-01-01T00:00:00Z
+      });
+      expect(serialize(ScheduleSchema, result.value)).toEqual(data);
+    });
+  });
+
+  describe("Ids", () => {
+    it("should deserialize uuids from their bytes and NonZero integers", () => {
+      const data = loadFixture("account.bin");
+      const result = deserialize(AccountSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value).toEqual({
+        id: "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        parent: "ffffffff-ffff-ffff-ffff-ffffffffffff",
+        port: 8080,
+        sequence: 18_446_744_073_709_551_615n,
+      });
+      expect(serialize(AccountSchema, result.value)).toEqual(data);
+    });
+  });
+
+  describe("Identifier enums", () => {
+    it("should deserialize unit-only enum map keys as variant names", () => {
+      const data = loadFixture("sensor_selection.bin");
+      const result = deserialize(SensorSelectionSchema, data);
+
+      expect(result.bytesRead).toBe(data.length);
+      expect(result.value.primary).toBe("Humidity");
+      expect(result.value.order).toEqual(["Pressure", "Temperature"]);
+      expect(result.value.enabled.get("Temperature")).toBe(true);
+      expect(result.value.enabled.get("Pressure")).toBe(false);
+      expect(result.value.enabled.has("Humidity")).toBe(false);
+      expect(serialize(SensorSelectionSchema, result.value)).toEqual(data);
+    });
+  });
+
+  describe("Columnar decoding", () => {
+    it("should decode Vec<Coordinates> into one Float64Array per axis", () => {
+      const data = loadFixture("trajectory.bin");
+      const { value, bytesRead } = decodeColumns(seq(CoordinatesSchema), data);
+
+      expect(bytesRead).toBe(data.length);
+      expect(value.x).toEqual(new Float64Array([0, 0.5, 1, 1.5, 2]));
+      expect(value.y).toEqual(new Float64Array([2, 1.75, 1, -0.25, -2]));
+      expect(value.z).toEqual(new Float64Array([100, 100.5, 101, 101.5, 102]));
+    });
+
+    it("should decode Vec<i16> samples into an existing Float32Array", () => {
+      const data = loadFixture("adc_samples.bin");
+      const samples = new Float32Array(10);
+      const { value, bytesRead } = decodeSeqInto(seq(i16()), data, samples, 3);
+
+      expect(value).toBe(7);
+      expect(bytesRead).toBe(data.length);
+      expect(samples).toEqual(new Float32Array([0, 0, 0, -2048, -1024, 0, 1023, 2047, 12, -7]));
+    });
+  });
 });
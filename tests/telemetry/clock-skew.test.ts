@@ -0,0 +1,127 @@
+/**
+ * Tests for device clock-skew correction
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  ClockSkewEstimator,
+  withCorrectedTime,
+  deserialize,
+  serialize,
+  struct,
+  u64,
+  f32,
+  type ClockModel,
+  type ClockSample,
+  type InferType,
+} from "../../src/index.js";
+
+const BOOT = 1_700_000_000_000;
+
+// Device uptime clock: booted at BOOT, 5 s ahead, gaining 50 ppm
+const deviceClock = (browserTime: number): number => (browserTime - BOOT) * (1 + 50e-6) + 5000;
+
+function pingAt(sentAt: number, oneWay = 10): ClockSample {
+  return { sentAt, deviceTime: deviceClock(sentAt + oneWay), receivedAt: sentAt + 2 * oneWay };
+}
+
+describe("ClockSkewEstimator", () => {
+  it("should have no estimate before the first sample", () => {
+    const clock = new ClockSkewEstimator();
+    expect(clock.offset).toBeUndefined();
+    expect(clock.drift).toBe(0);
+    expect(clock.toBrowserTime(1234)).toBeUndefined();
+  });
+
+  it("should estimate the offset from a single sample", () => {
+    const clock = new ClockSkewEstimator();
+    clock.addSample(pingAt(BOOT));
+    expect(clock.toBrowserTime(deviceClock(BOOT + 10))).toBeCloseTo(BOOT + 10, 2);
+  });
+
+  it("should estimate drift from samples over time", () => {
+    const clock = new ClockSkewEstimator();
+    for (let i = 0; i < 10; i++) {
+      clock.addSample(pingAt(BOOT + i * 10_000));
+    }
+    expect(clock.drift).toBeCloseTo(50e-6, 9);
+    expect(clock.toBrowserTime(deviceClock(BOOT + 600_000))).toBeCloseTo(BOOT + 600_000, 2);
+  });
+
+  it("should discount samples with slow round trips", () => {
+    const clock = new ClockSkewEstimator();
+    for (let i = 0; i < 5; i++) {
+      clock.addSample(pingAt(BOOT + i * 10_000));
+    }
+    // Reply queued for 400 ms on the way back: its midpoint is off by ~190 ms
+    const sentAt = BOOT + 60_000;
+    clock.addSample({ sentAt, deviceTime: deviceClock(sentAt + 10), receivedAt: sentAt + 420 });
+    expect(clock.toBrowserTime(deviceClock(BOOT + 60_000))).toBeCloseTo(BOOT + 60_000, 2);
+  });
+
+  it("should keep only the newest samples", () => {
+    const clock = new ClockSkewEstimator({ maxSamples: 2 });
+    clock.addSample({ sentAt: 0, deviceTime: 1000, receivedAt: 0 });
+    clock.addSample({ sentAt: 100, deviceTime: 2100, receivedAt: 100 });
+    clock.addSample({ sentAt: 200, deviceTime: 2200, receivedAt: 200 });
+    expect(clock.offset).toBe(2000);
+    expect(clock.drift).toBe(0);
+  });
+
+  it("should convert device time units", () => {
+    const clock = new ClockSkewEstimator({ deviceUnitMs: 0.001 });
+    clock.addSample({ sentAt: BOOT, deviceTime: 3_000_000, receivedAt: BOOT });
+    expect(clock.toBrowserTime(4_000_000)).toBe(BOOT + 1000);
+  });
+
+  it("should measure with a ping exchange", async () => {
+    const clock = new ClockSkewEstimator();
+    let browserTime = BOOT;
+    const now = (): number => (browserTime += 10);
+    const sample = await clock.measure(() => Promise.resolve(5000n), now);
+
+    expect(sample).toEqual({ sentAt: BOOT + 10, deviceTime: 5000, receivedAt: BOOT + 20 });
+    expect(clock.offset).toBe(5000 - (BOOT + 15));
+  });
+
+  it("should reject invalid samples and options", () => {
+    const clock = new ClockSkewEstimator();
+    expect(() => clock.addSample({ sentAt: 10, deviceTime: 0, receivedAt: 5 })).toThrow(RangeError);
+    expect(() => clock.addSample({ sentAt: 0, deviceTime: NaN, receivedAt: 5 })).toThrow(RangeError);
+    expect(() => new ClockSkewEstimator({ maxSamples: 0 })).toThrow(RangeError);
+    expect(() => new ClockSkewEstimator({ deviceUnitMs: -1 })).toThrow(RangeError);
+  });
+});
+
+describe("withCorrectedTime()", () => {
+  const ReadingSchema = struct({ uptime_ms: u64(), temperature: f32() });
+  type Reading = InferType<typeof ReadingSchema>;
+
+  it("should attach the browser time of the designated field", () => {
+    const clock = new ClockSkewEstimator();
+    clock.addSample({ sentAt: BOOT, deviceTime: 5000, receivedAt: BOOT });
+    const timed = withCorrectedTime<Reading>(clock, "uptime_ms");
+
+    const data = serialize(ReadingSchema, { uptime_ms: 7500n, temperature: 21.5 });
+    const { value, time } = timed(deserialize(ReadingSchema, data).value);
+
+    expect(value.temperature).toBe(21.5);
+    expect(time).toEqual(new Date(BOOT + 2500));
+  });
+
+  it("should accept any clock model", () => {
+    const fixed: ClockModel = { toBrowserTime: (deviceTime) => deviceTime * 1000 };
+    const timed = withCorrectedTime<{ at: number }>(fixed, "at");
+    expect(timed({ at: 60 }).time).toEqual(new Date(60_000));
+  });
+
+  it("should leave the time undefined until the clock has an estimate", () => {
+    const timed = withCorrectedTime<Reading>(new ClockSkewEstimator(), "uptime_ms");
+    expect(timed({ uptime_ms: 1n, temperature: 0 }).time).toBeUndefined();
+  });
+
+  it("should only accept numeric fields", () => {
+    // @ts-expect-error - label is not a numeric field
+    withCorrectedTime<{ label: string; at: number }>(new ClockSkewEstimator(), "label");
+  });
+});
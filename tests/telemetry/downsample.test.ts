@@ -0,0 +1,129 @@
+/**
+ * Tests for downsampling and windowed aggregation
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  lttb,
+  minMax,
+  aggregateWindows,
+  WindowAggregator,
+  seriesOf,
+  pick,
+  pickColumns,
+  decodeColumns,
+  serialize,
+  seq,
+  struct,
+  f64,
+  u32,
+} from "../../src/index.js";
+
+const x = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+const spikes = [0, 0, 0, 10, 0, 0, -5, 0, 0, 0];
+
+describe("lttb()", () => {
+  it("should keep the first and last points and the spikes", () => {
+    expect(Array.from(lttb({ x, y: spikes }, 4))).toEqual([0, 3, 6, 9]);
+  });
+
+  it("should return every index for short series", () => {
+    expect(Array.from(lttb({ x: [0, 1, 2], y: [5, 6, 7] }, 10))).toEqual([0, 1, 2]);
+  });
+
+  it("should keep only the endpoints with a threshold of 2", () => {
+    expect(Array.from(lttb({ x, y: spikes }, 2))).toEqual([0, 9]);
+  });
+
+  it("should reject invalid thresholds and mismatched columns", () => {
+    expect(() => lttb({ x, y: spikes }, 1)).toThrow(RangeError);
+    expect(() => lttb({ x, y: spikes }, 2.5)).toThrow(RangeError);
+    expect(() => lttb({ x, y: [1, 2] }, 4)).toThrow(RangeError);
+  });
+});
+
+describe("minMax()", () => {
+  it("should keep the lowest and highest point of each bucket in index order", () => {
+    expect(Array.from(minMax({ x, y: spikes }, 2))).toEqual([0, 3, 5, 6]);
+  });
+
+  it("should keep a single point for flat buckets and skip NaN", () => {
+    const y = [NaN, 4, 4, 4, NaN, NaN, NaN, NaN];
+    expect(Array.from(minMax({ x: y.map((_, i) => i), y }, 2))).toEqual([1]);
+  });
+
+  it("should return every index when buckets can hold the whole series", () => {
+    expect(Array.from(minMax({ x, y: spikes }, 5))).toEqual(x);
+  });
+});
+
+describe("aggregateWindows()", () => {
+  it("should summarise samples per window aligned to the width", () => {
+    const windows = aggregateWindows(
+      { x: [0, 400, 999, 1000, 2500, 2600], y: [1, 3, 5, 10, 2, NaN] },
+      1000
+    );
+    expect(Array.from(windows.start)).toEqual([0, 1000, 2000]);
+    expect(Array.from(windows.count)).toEqual([3, 1, 1]);
+    expect(Array.from(windows.min)).toEqual([1, 10, 2]);
+    expect(Array.from(windows.max)).toEqual([5, 10, 2]);
+    expect(Array.from(windows.mean)).toEqual([3, 10, 2]);
+  });
+
+  it("should honour the origin", () => {
+    const windows = aggregateWindows({ x: [5, 14, 15], y: [1, 2, 3] }, 10, 5);
+    expect(Array.from(windows.start)).toEqual([5, 15]);
+    expect(Array.from(windows.count)).toEqual([2, 1]);
+  });
+
+  it("should return empty columns for an empty series", () => {
+    expect(aggregateWindows({ x: [], y: [] }, 1000).start.length).toBe(0);
+  });
+});
+
+describe("WindowAggregator", () => {
+  it("should return each window once the next one starts", () => {
+    const aggregator = new WindowAggregator(10);
+    expect(aggregator.push(1, 2)).toBeUndefined();
+    expect(aggregator.push(9, 4)).toBeUndefined();
+    expect(aggregator.push(25, 1)).toEqual({ start: 0, count: 2, min: 2, max: 4, mean: 3 });
+    expect(aggregator.flush()).toEqual({ start: 20, count: 1, min: 1, max: 1, mean: 1 });
+    expect(aggregator.flush()).toBeUndefined();
+  });
+
+  it("should reject samples from an earlier window", () => {
+    const aggregator = new WindowAggregator(10);
+    aggregator.push(25, 1);
+    expect(() => aggregator.push(3, 1)).toThrow(RangeError);
+  });
+
+  it("should reject widths that are not positive", () => {
+    expect(() => new WindowAggregator(0)).toThrow(RangeError);
+    expect(() => new WindowAggregator(Infinity)).toThrow(RangeError);
+  });
+});
+
+describe("Selecting downsampled points", () => {
+  const ReadingSchema = struct({ timestamp_ms: u32(), value: f64() });
+  const readings = x.map((t, i) => ({ timestamp_ms: t * 100, value: spikes[i] ?? 0 }));
+
+  it("should downsample decoded messages", () => {
+    const series = seriesOf(readings, (r) => r.timestamp_ms, (r) => r.value);
+    expect(pick(readings, lttb(series, 4))).toEqual([
+      readings[0],
+      readings[3],
+      readings[6],
+      readings[9],
+    ]);
+  });
+
+  it("should downsample decoded columns, keeping their array types", () => {
+    const data = serialize(seq(ReadingSchema), readings);
+    const { value: columns } = decodeColumns(seq(ReadingSchema), data);
+    const kept = pickColumns(columns, minMax({ x: columns.timestamp_ms, y: columns.value }, 2));
+
+    expect(kept.timestamp_ms).toBeInstanceOf(Uint32Array);
+    expect(Array.from(kept.timestamp_ms)).toEqual([0, 300, 500, 600]);
+    expect(Array.from(kept.value)).toEqual([0, 10, 0, -5]);
+  });
+});
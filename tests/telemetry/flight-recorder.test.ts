@@ -0,0 +1,136 @@
+/**
+ * Tests for capture files and the flight recorder
+ */
+
+import { describe, it, expect } from "vitest";
+import { readFileSync } from "fs";
+import { join } from "path";
+import {
+  FlightRecorder,
+  encodeCapture,
+  decodeCapture,
+  tryDecodeCapture,
+  CaptureError,
+  serialize,
+  deserialize,
+  u32,
+} from "../../src/index.js";
+import { LogRecordSchema } from "../fixtures/schemas.js";
+
+function fakeClock(start = 1_700_000_000_000): {
+  now: () => number;
+  advance: (ms: number) => void;
+} {
+  let time = start;
+  return {
+    now: () => time,
+    advance: (ms) => {
+      time += ms;
+    },
+  };
+}
+
+describe("Capture files", () => {
+  it("should round-trip frames", () => {
+    const frames = [
+      { time_ms: new Date(1_700_000_000_000), data: new Uint8Array([1, 2, 3]) },
+      { time_ms: new Date(1_700_000_000_250), data: new Uint8Array([]) },
+    ];
+    expect(decodeCapture(encodeCapture(frames))).toEqual(frames);
+  });
+
+  it("should reject other versions", () => {
+    const result = tryDecodeCapture(serialize(u32(), 2));
+    expect(result.ok).toBe(false);
+    if (!result.ok) {
+      expect(result.error).toBeInstanceOf(CaptureError);
+      expect(result.error.message).toContain("version 2");
+    }
+  });
+
+  it("should reject trailing bytes", () => {
+    const data = encodeCapture([]);
+    expect(() => decodeCapture(new Uint8Array([...data, 0]))).toThrow(CaptureError);
+  });
+
+  it("should decode a capture written by Rust", () => {
+    const data = new Uint8Array(
+      readFileSync(join(process.cwd(), "test-fixtures", "fixtures", "capture.bin"))
+    );
+    const frames = decodeCapture(data);
+
+    expect(frames.map((f) => f.time_ms.getTime())).toEqual(
+      frames.map((_, i) => 1_700_000_000_000 + 250 * i)
+    );
+    const first = deserialize(LogRecordSchema, frames[0]?.data ?? new Uint8Array()).value;
+    expect(first.message).toBe("sample");
+  });
+});
+
+describe("FlightRecorder", () => {
+  it("should keep frames within the retention window", () => {
+    const clock = fakeClock();
+    const recorder = new FlightRecorder({ retentionMs: 60_000, now: clock.now });
+
+    recorder.record(new Uint8Array([1]));
+    clock.advance(30_000);
+    recorder.record(new Uint8Array([2]));
+    clock.advance(40_000);
+
+    expect(recorder.snapshot().map((f) => Array.from(f.data))).toEqual([[2]]);
+    clock.advance(30_000);
+    expect(recorder.size).toBe(0);
+  });
+
+  it("should drop the oldest frames beyond maxBytes", () => {
+    const recorder = new FlightRecorder({ retentionMs: 60_000, maxBytes: 5 });
+    recorder.record(new Uint8Array([1, 1]));
+    recorder.record(new Uint8Array([2, 2]));
+    recorder.record(new Uint8Array([3, 3]));
+
+    expect(recorder.byteLength).toBe(4);
+    expect(recorder.snapshot().map((f) => f.data[0])).toEqual([2, 3]);
+  });
+
+  it("should copy frames so reused transport buffers do not alter them", () => {
+    const recorder = new FlightRecorder({ retentionMs: 60_000 });
+    const buffer = new Uint8Array([7, 7]);
+    recorder.record(buffer);
+    buffer.fill(0);
+    expect(Array.from(recorder.snapshot()[0]?.data ?? [])).toEqual([7, 7]);
+  });
+
+  it("should record frames passing through a tapped handler", () => {
+    const recorder = new FlightRecorder({ retentionMs: 60_000 });
+    const handled: number[] = [];
+    const onFrame = recorder.tap((frame: Uint8Array, port: number) => {
+      handled.push(port);
+      return frame.length;
+    });
+
+    expect(onFrame(new Uint8Array([1, 2, 3]), 4)).toBe(3);
+    expect(handled).toEqual([4]);
+    expect(recorder.size).toBe(1);
+  });
+
+  it("should export the retained frames as a capture", () => {
+    const clock = fakeClock();
+    const recorder = new FlightRecorder({ retentionMs: 60_000, now: clock.now });
+    recorder.record(serialize(u32(), 300));
+    clock.advance(100);
+    recorder.record(serialize(u32(), 7));
+
+    const frames = decodeCapture(recorder.export());
+    expect(frames.map((f) => f.time_ms.getTime())).toEqual([1_700_000_000_000, 1_700_000_000_100]);
+    expect(frames.map((f) => deserialize(u32(), f.data).value)).toEqual([300, 7]);
+  });
+
+  it("should clear and validate its options", () => {
+    const recorder = new FlightRecorder({ retentionMs: 60_000 });
+    recorder.record(new Uint8Array([1]));
+    recorder.clear();
+    expect(recorder.size).toBe(0);
+    expect(() => new FlightRecorder({ retentionMs: 0 })).toThrow(RangeError);
+    expect(() => new FlightRecorder({ retentionMs: 1000, maxBytes: NaN })).toThrow(RangeError);
+  });
+});
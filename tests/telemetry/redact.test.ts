@@ -0,0 +1,158 @@
+/**
+ * Tests for schema-aware redaction
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  sensitive,
+  redact,
+  redactCapture,
+  tryRedactCapture,
+  RedactionError,
+  CaptureError,
+  encodeCapture,
+  decodeCapture,
+  serialize,
+  deserialize,
+  struct,
+  seq,
+  option,
+  map,
+  string,
+  bytes,
+  char,
+  u8,
+  i16,
+  u64,
+  i128,
+  f32,
+  bool,
+  decimal,
+  enumType,
+  unitVariant,
+  structVariant,
+} from "../../src/index.js";
+
+const LocationSchema = enumType("Location", {
+  Unknown: unitVariant("Unknown"),
+  Fix: structVariant("Fix", { lat: f32(), lon: f32() }),
+});
+
+const ReadingSchema = struct({
+  sensor: u8(),
+  owner: sensitive(string()),
+  location: sensitive(LocationSchema),
+  temperature: f32(),
+  serial: sensitive(option(bytes())),
+  labels: sensitive(map(string(), u64())),
+});
+
+const reading = {
+  sensor: 3,
+  owner: "Ada Lovelace",
+  location: { type: "Fix" as const, value: { lat: 51.5, lon: -0.125 } },
+  temperature: 21.5,
+  serial: new Uint8Array([0xde, 0xad, 0xbe, 0xef]),
+  labels: new Map([["site", 7n]]),
+};
+
+// Deterministic stand-in for Math.random
+function sequence(): () => number {
+  let state = 1;
+  return () => {
+    state = (state * 48271) % 0x7fffffff;
+    return state / 0x7fffffff;
+  };
+}
+
+describe("redact", () => {
+  it("should zero sensitive fields and keep the rest", () => {
+    expect(redact(ReadingSchema, reading)).toEqual({
+      sensor: 3,
+      owner: "",
+      location: { type: "Fix", value: { lat: 0, lon: 0 } },
+      temperature: 21.5,
+      serial: new Uint8Array(4),
+      labels: new Map([["", 0n]]),
+    });
+  });
+
+  it("should randomise leaves while keeping their type and length", () => {
+    const redacted = redact(ReadingSchema, reading, { mode: "random", random: sequence() });
+
+    expect(redacted.sensor).toBe(3);
+    expect(redacted.owner).toMatch(/^[a-z]{12}$/);
+    expect(redacted.location.type).toBe("Fix");
+    expect(redacted.serial).toHaveLength(4);
+    expect(redacted.serial).not.toEqual(reading.serial);
+    expect(deserialize(ReadingSchema, serialize(ReadingSchema, redacted)).value).toEqual(redacted);
+  });
+
+  it("should keep random integers within range", () => {
+    const Schema = struct({ a: sensitive(i16()), b: sensitive(i128()), c: sensitive(char()) });
+    const random = sequence();
+    for (let i = 0; i < 100; i++) {
+      const { a, b, c } = redact(Schema, { a: 1, b: 1n, c: "x" }, { mode: "random", random });
+      expect(a).toBeGreaterThanOrEqual(-0x8000);
+      expect(a).toBeLessThanOrEqual(0x7fff);
+      expect(b >= -(2n ** 127n) && b < 2n ** 127n).toBe(true);
+      expect(c).toMatch(/^[a-z]$/);
+    }
+  });
+
+  it("should use an explicit replacement", () => {
+    const Schema = struct({ balance: sensitive(decimal(), "0"), flag: sensitive(bool(), true) });
+    expect(redact(Schema, { balance: "12.50", flag: false })).toEqual({ balance: "0", flag: true });
+  });
+
+  it("should report transforms that reject redacted values", () => {
+    const Schema = struct({ balance: sensitive(decimal()) });
+    expect(() => redact(Schema, { balance: "12.50" })).toThrow(RedactionError);
+  });
+
+  it("should encode sensitive fields exactly like their inner schema", () => {
+    expect(serialize(sensitive(seq(u8())), [1, 2])).toEqual(serialize(seq(u8()), [1, 2]));
+  });
+});
+
+describe("redactCapture", () => {
+  it("should rewrite every frame and keep timestamps", () => {
+    const frames = [
+      { time_ms: new Date(1_700_000_000_000), data: serialize(ReadingSchema, reading) },
+      { time_ms: new Date(1_700_000_000_250), data: serialize(ReadingSchema, reading) },
+    ];
+    const result = redactCapture(ReadingSchema, encodeCapture(frames));
+
+    expect(result.redacted).toBe(2);
+    expect(result.dropped).toBe(0);
+    const redacted = decodeCapture(result.data);
+    expect(redacted.map((f) => f.time_ms)).toEqual(frames.map((f) => f.time_ms));
+    for (const frame of redacted) {
+      expect(deserialize(ReadingSchema, frame.data).value.owner).toBe("");
+    }
+  });
+
+  it("should drop frames that do not decode with the schema", () => {
+    const good = serialize(ReadingSchema, reading);
+    const frames = [
+      { time_ms: new Date(0), data: good },
+      { time_ms: new Date(1), data: good.slice(0, 3) },
+      { time_ms: new Date(2), data: new Uint8Array([...good, 0]) },
+    ];
+    const result = redactCapture(ReadingSchema, encodeCapture(frames));
+    expect(result.redacted).toBe(1);
+    expect(result.dropped).toBe(2);
+  });
+
+  it("should report invalid captures and failed redactions", () => {
+    const invalid = tryRedactCapture(ReadingSchema, new Uint8Array([1]));
+    expect(!invalid.ok && invalid.error).toBeInstanceOf(CaptureError);
+
+    const Schema = struct({ balance: sensitive(decimal()) });
+    const capture = encodeCapture([
+      { time_ms: new Date(0), data: serialize(Schema, { balance: "1.5" }) },
+    ]);
+    const failed = tryRedactCapture(Schema, capture);
+    expect(!failed.ok && failed.error).toBeInstanceOf(RedactionError);
+  });
+});
@@ -0,0 +1,128 @@
+/**
+ * Tests for ring-buffer log extraction
+ */
+
+import { describe, it, expect } from "vitest";
+import { readFileSync } from "fs";
+import { join } from "path";
+import {
+  serialize,
+  bytes,
+  decodeRingBuffer,
+  tryDecodeRingBuffer,
+  RingBufferError,
+} from "../../src/index.js";
+import { LogRecordSchema, type LogRecord } from "../fixtures/schemas.js";
+import { toSerdeJson } from "../fixtures/serde-json.js";
+
+const record = (timestamp_ms: number, message: string): LogRecord => ({
+  timestamp_ms,
+  level: { type: "Info" },
+  message,
+});
+
+/**
+ * Write records into a byte ring the way firmware does, wrapping at the end
+ */
+function writeRing(
+  size: number,
+  records: LogRecord[]
+): { buffer: Uint8Array; head: number; wrapped: boolean } {
+  const buffer = new Uint8Array(size);
+  let head = 0;
+  let wrapped = false;
+  for (const r of records) {
+    for (const byte of serialize(bytes(), serialize(LogRecordSchema, r))) {
+      buffer[head] = byte;
+      head++;
+      if (head === size) {
+        head = 0;
+        wrapped = true;
+      }
+    }
+  }
+  return { buffer, head, wrapped };
+}
+
+describe("decodeRingBuffer()", () => {
+  it("should decode records up to head when the ring has not wrapped", () => {
+    const records = [record(1000, "boot"), record(1250, "ready")];
+    const { buffer, head, wrapped } = writeRing(64, records);
+    expect(wrapped).toBe(false);
+    expect(decodeRingBuffer(LogRecordSchema, buffer, { head, wrapped })).toEqual(records);
+  });
+
+  it("should return no records for an empty ring", () => {
+    const buffer = new Uint8Array(32);
+    expect(decodeRingBuffer(LogRecordSchema, buffer, { head: 0, wrapped: false })).toEqual([]);
+  });
+
+  it("should skip the partially overwritten record after wrapping", () => {
+    const records = Array.from({ length: 8 }, (_, i) => record(1000 + i, `event ${String(i)}`));
+    const { buffer, head, wrapped } = writeRing(40, records);
+    expect(wrapped).toBe(true);
+
+    // Each framed record is 12 bytes, so three fit entirely
+    expect(decodeRingBuffer(LogRecordSchema, buffer, { head, wrapped })).toEqual(records.slice(5));
+  });
+
+  it("should return records in chronological order across the wrap point", () => {
+    const records = Array.from({ length: 5 }, (_, i) => record(i, "x".repeat(i + 1)));
+    const { buffer, head, wrapped } = writeRing(24, records);
+    expect(head).toBe(11);
+    expect(decodeRingBuffer(LogRecordSchema, buffer, { head, wrapped })).toEqual(records.slice(2));
+  });
+
+  it("should decode a full ring whose head sits on a record boundary", () => {
+    const records = [record(1, "aaaa"), record(2, "bbbb"), record(3, "cccc")];
+    const { buffer, head, wrapped } = writeRing(24, records);
+    expect(head).toBe(0);
+    expect(wrapped).toBe(true);
+    expect(decodeRingBuffer(LogRecordSchema, buffer, { head, wrapped })).toEqual(records);
+  });
+
+  describe("errors", () => {
+    it("should reject a head outside the buffer", () => {
+      const result = tryDecodeRingBuffer(LogRecordSchema, new Uint8Array(8), {
+        head: 9,
+        wrapped: false,
+      });
+      expect(result.ok).toBe(false);
+      if (!result.ok) {
+        expect(result.error).toBeInstanceOf(RingBufferError);
+      }
+    });
+
+    it("should fail when records before head do not decode", () => {
+      const buffer = new Uint8Array([0x05, 0xff, 0xff]);
+      expect(() => decodeRingBuffer(LogRecordSchema, buffer, { head: 3, wrapped: false })).toThrow(
+        RingBufferError
+      );
+    });
+
+    it("should fail when no chain of records ends at head", () => {
+      const buffer = new Uint8Array(16).fill(0xff);
+      const result = tryDecodeRingBuffer(LogRecordSchema, buffer, { head: 4, wrapped: true });
+      expect(result.ok).toBe(false);
+    });
+  });
+
+  describe("Rust fixture", () => {
+    const dir = join(process.cwd(), "test-fixtures", "fixtures");
+
+    it("should recover the records the Rust ring writer left intact", () => {
+      const buffer = new Uint8Array(readFileSync(join(dir, "log_ring.bin")));
+      const expected = JSON.parse(readFileSync(join(dir, "log_ring.json"), "utf8")) as {
+        head: number;
+        wrapped: boolean;
+        records: unknown[];
+      };
+
+      const records = decodeRingBuffer(LogRecordSchema, buffer, {
+        head: expected.head,
+        wrapped: expected.wrapped,
+      });
+      expect(records.map((r) => toSerdeJson(LogRecordSchema, r))).toEqual(expected.records);
+    });
+  });
+});
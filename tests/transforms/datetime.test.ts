@@ -0,0 +1,129 @@
+/**
+ * Tests for the chrono, time and std::time date and time schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  i8,
+  i32,
+  string,
+  struct,
+  tuple,
+  u8,
+  u16,
+  u32,
+  u64,
+  duration,
+  chronoDateTime,
+  offsetDateTime,
+  nanosToRfc3339,
+  rfc3339ToNanos,
+  type InferType,
+} from "../../src/index.js";
+
+const DurationWire = struct({ secs: u64(), nanos: u32() });
+const OffsetDateTimeWire = tuple(i32(), u16(), u8(), u8(), u8(), u32(), i8(), i8(), i8());
+
+describe("duration()", () => {
+  it("should decode to milliseconds by default", () => {
+    const data = serialize(DurationWire, { secs: 90n, nanos: 500_000 });
+    expect(deserialize(duration(), data).value).toBe(90_000.5);
+    expect(serialize(duration(), 90_000.5)).toEqual(data);
+  });
+
+  it("should decode to exact nanoseconds", () => {
+    const data = serialize(DurationWire, { secs: 0xffff_ffff_ffff_ffffn, nanos: 999_999_999 });
+    const nanos = 0xffff_ffff_ffff_ffffn * 1_000_000_000n + 999_999_999n;
+    expect(deserialize(duration("nanos"), data).value).toBe(nanos);
+    expect(serialize(duration("nanos"), nanos)).toEqual(data);
+  });
+
+  it("should carry nanoseconds over a second into the seconds, as Rust does", () => {
+    const data = serialize(DurationWire, { secs: 1n, nanos: 1_500_000_000 });
+    expect(deserialize(duration("nanos"), data).value).toBe(2_500_000_000n);
+  });
+
+  it("should fail to encode negative and overlong durations", () => {
+    expect(trySerialize(duration(), -1).ok).toBe(false);
+    expect(trySerialize(duration(), Infinity).ok).toBe(false);
+    expect(trySerialize(duration("nanos"), 1n << 100n).ok).toBe(false);
+  });
+});
+
+describe("chronoDateTime()", () => {
+  it("should decode RFC 3339 text to a Date", () => {
+    const data = serialize(string(), "2024-02-29T23:59:59.123Z");
+    expect(deserialize(chronoDateTime(), data).value).toEqual(
+      new Date("2024-02-29T23:59:59.123Z")
+    );
+  });
+
+  it("should round-trip nanoseconds, written as chrono writes them", () => {
+    for (const text of [
+      "1969-07-20T20:17:40Z",
+      "2024-02-29T23:59:59.500Z",
+      "2024-02-29T23:59:59.000250Z",
+      "2024-02-29T23:59:59.000000001Z",
+      "-0001-12-31T23:59:59Z",
+      "+10000-01-01T00:00:00Z",
+    ]) {
+      const data = serialize(string(), text);
+      const nanos = deserialize(chronoDateTime("nanos"), data).value;
+      expect(serialize(chronoDateTime("nanos"), nanos)).toEqual(data);
+    }
+    expect(nanosToRfc3339(-1n)).toBe("1969-12-31T23:59:59.999999999Z");
+  });
+
+  it("should apply offsets when decoding", () => {
+    expect(rfc3339ToNanos("2024-03-01T05:29:59.123+05:30")).toBe(
+      rfc3339ToNanos("2024-02-29T23:59:59.123Z")
+    );
+  });
+
+  it("should fail to decode text that is not a valid date and time", () => {
+    for (const text of ["2024-02-30T00:00:00Z", "2024-02-29 23:59:59Z", "yesterday"]) {
+      expect(tryDeserialize(chronoDateTime(), serialize(string(), text)).ok).toBe(false);
+    }
+  });
+
+  it("should infer Date and bigint", () => {
+    const EventSchema = struct({ at: chronoDateTime(), exact: chronoDateTime("nanos") });
+    type Event = InferType<typeof EventSchema>;
+
+    const event: Event = { at: new Date(0), exact: 1n };
+    const _at: Date = event.at;
+    const _exact: bigint = event.exact;
+    void _at;
+    void _exact;
+
+    expect(deserialize(EventSchema, serialize(EventSchema, event)).value).toEqual(event);
+  });
+});
+
+describe("offsetDateTime()", () => {
+  it("should decode the tuple time writes, applying its offset", () => {
+    // 2024-03-01T05:29:59.123+05:30, day 61 of 2024
+    const data = serialize(OffsetDateTimeWire, [2024, 61, 5, 29, 59, 123_000_000, 5, 30, 0]);
+    expect(deserialize(offsetDateTime(), data).value).toEqual(
+      new Date("2024-02-29T23:59:59.123Z")
+    );
+  });
+
+  it("should encode in UTC", () => {
+    const data = serialize(offsetDateTime("nanos"), -1n);
+    expect(deserialize(OffsetDateTimeWire, data).value).toEqual([
+      1969, 365, 23, 59, 59, 999_999_999, 0, 0, 0,
+    ]);
+    expect(deserialize(offsetDateTime("nanos"), data).value).toBe(-1n);
+  });
+
+  it("should fail on values outside time's range", () => {
+    const invalid = serialize(OffsetDateTimeWire, [2023, 366, 0, 0, 0, 0, 0, 0, 0]);
+    expect(tryDeserialize(offsetDateTime(), invalid).ok).toBe(false);
+    expect(trySerialize(offsetDateTime(), new Date("+010000-01-01T00:00:00Z")).ok).toBe(false);
+  });
+});
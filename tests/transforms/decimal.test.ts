@@ -0,0 +1,96 @@
+/**
+ * Tests for rust_decimal::Decimal schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  string,
+  struct,
+  decimal,
+  type InferType,
+} from "../../src/index.js";
+
+describe("decimal()", () => {
+  it("should keep the string wire format", () => {
+    const data = serialize(decimal(), "-12.50");
+    expect(data).toEqual(serialize(string(), "-12.50"));
+    expect(deserialize(decimal(), data).value).toBe("-12.50");
+  });
+
+  it("should accept zero, negative and high-precision values", () => {
+    for (const text of [
+      "0",
+      "-0.01",
+      "0.0000000000000000000000000001",
+      "79228162514264337593543950335",
+    ]) {
+      expect(deserialize(decimal(), serialize(decimal(), text)).value).toBe(text);
+    }
+  });
+
+  it("should reject text that is not plain decimal notation", () => {
+    for (const text of ["", "1e5", "+1", "1.", ".5", "NaN", "1,5"]) {
+      expect(trySerialize(decimal(), text).ok).toBe(false);
+      expect(tryDeserialize(decimal(), serialize(string(), text)).ok).toBe(false);
+    }
+  });
+
+  it("should reject values outside Decimal's range", () => {
+    // One more than Decimal::MAX
+    expect(trySerialize(decimal(), "79228162514264337593543950336").ok).toBe(false);
+    // 29 fractional digits
+    expect(trySerialize(decimal(), "0.00000000000000000000000000001").ok).toBe(false);
+  });
+
+  describe("with conversions", () => {
+    /**
+     * Minimal stand-in for a decimal library: an integer count of cents
+     */
+    class Cents {
+      constructor(readonly cents: bigint) {}
+      static parse(text: string): Cents {
+        const [whole = "0", fraction = ""] = text.split(".");
+        const sign = whole.startsWith("-") ? -1n : 1n;
+        const digits = BigInt(whole.replace("-", "") + fraction.padEnd(2, "0").slice(0, 2));
+        return new Cents(sign * digits);
+      }
+      toFixed(): string {
+        const sign = this.cents < 0n ? "-" : "";
+        const abs = this.cents < 0n ? -this.cents : this.cents;
+        return `${sign}${String(abs / 100n)}.${String(abs % 100n).padStart(2, "0")}`;
+      }
+    }
+
+    const MoneySchema = decimal({
+      parse: (text) => Cents.parse(text),
+      format: (value: Cents) => value.toFixed(),
+    });
+
+    it("should decode into the application type", () => {
+      const { value } = deserialize(MoneySchema, serialize(string(), "-12.50"));
+      expect(value).toBeInstanceOf(Cents);
+      expect(value.cents).toBe(-1250n);
+    });
+
+    it("should encode through format", () => {
+      expect(serialize(MoneySchema, new Cents(105n))).toEqual(serialize(string(), "1.05"));
+    });
+
+    it("should infer the application type", () => {
+      const InvoiceSchema = struct({ total: MoneySchema, rate: decimal() });
+      type Invoice = InferType<typeof InvoiceSchema>;
+
+      const invoice: Invoice = { total: new Cents(999n), rate: "0.25" };
+      const _verify: Cents = invoice.total;
+      const _verifyRate: string = invoice.rate;
+      void _verify;
+      void _verifyRate;
+
+      expect(deserialize(InvoiceSchema, serialize(InvoiceSchema, invoice)).value).toEqual(invoice);
+    });
+  });
+});
@@ -0,0 +1,152 @@
+/**
+ * Tests for discriminated union schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join } from "path";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  discriminated,
+  enumType,
+  newtypeVariant,
+  structVariant,
+  unitVariant,
+  bool,
+  u32,
+  DeserializeError,
+  ErrorCode,
+  type InferType,
+} from "../../src/index.js";
+import { ComplexEnumSchema, EnemySchema } from "../fixtures/schemas.js";
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+}
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const MANIFEST_PATH = join(FIXTURES_DIR, "manifest.json");
+
+const TaggedEnemy = discriminated(EnemySchema, "kind");
+type TaggedEnemy = InferType<typeof TaggedEnemy>;
+
+describe("discriminated()", () => {
+  it("should infer a union tagged by the given property", () => {
+    const goblin: TaggedEnemy = { kind: "Goblin", id: 3, aggro: true };
+    const skeleton: TaggedEnemy = { kind: "Skeleton" };
+
+    // @ts-expect-error - struct variant fields sit next to the tag, not under value
+    const _nested: TaggedEnemy = { kind: "Goblin", value: { id: 3, aggro: true } };
+    // @ts-expect-error - not a variant
+    const _unknown: TaggedEnemy = { kind: "Orc" };
+    void [goblin, skeleton, _nested, _unknown];
+  });
+
+  it("should narrow in an exhaustive switch", () => {
+    const label = (enemy: TaggedEnemy): string => {
+      switch (enemy.kind) {
+        case "Goblin":
+          return enemy.aggro ? "angry goblin" : "goblin";
+        case "Dragon":
+          return `${enemy.value.color.type} dragon`;
+        case "Skeleton":
+          return "skeleton";
+        case "Boss":
+          return `${enemy.name} (phase ${String(enemy.phase)})`;
+        default: {
+          const _exhaustive: never = enemy;
+          return _exhaustive;
+        }
+      }
+    };
+    const bytes = serialize(EnemySchema, { type: "Goblin", value: { id: 3, aggro: true } });
+    expect(label(deserialize(TaggedEnemy, bytes).value)).toBe("angry goblin");
+  });
+
+  it("should encode exactly like the enum", () => {
+    const cases: [TaggedEnemy, InferType<typeof EnemySchema>][] = [
+      [
+        { kind: "Goblin", id: 3, aggro: true },
+        { type: "Goblin", value: { id: 3, aggro: true } },
+      ],
+      [{ kind: "Skeleton" }, { type: "Skeleton" }],
+      [
+        { kind: "Boss", name: "Lich", phase: 2, health_percent: 0.5 },
+        { type: "Boss", value: { name: "Lich", phase: 2, health_percent: 0.5 } },
+      ],
+    ];
+    for (const [tagged, plain] of cases) {
+      const bytes = serialize(TaggedEnemy, tagged);
+      expect(bytes).toEqual(serialize(EnemySchema, plain));
+      expect(deserialize(TaggedEnemy, bytes).value).toEqual(tagged);
+    }
+  });
+
+  it("should keep newtype and tuple data under value", () => {
+    const Tagged = discriminated(ComplexEnumSchema);
+    const bytes = serialize(ComplexEnumSchema, { type: "TupleVariant", value: ["a", -1, true] });
+    expect(deserialize(Tagged, bytes).value).toEqual({
+      kind: "TupleVariant",
+      value: ["a", -1, true],
+    });
+    expect(serialize(Tagged, { kind: "NewtypeVariant", value: 7 })).toEqual(
+      new Uint8Array([1, 7])
+    );
+  });
+
+  it("should map the tag back to the variant index", () => {
+    const Signal = discriminated(
+      enumType("Signal", {
+        Off: unitVariant("Off"),
+        Level: newtypeVariant("Level", u32()),
+        Pulse: structVariant("Pulse", { width: u32(), inverted: bool() }),
+      }),
+      "signal"
+    );
+    expect(serialize(Signal, { signal: "Off" })).toEqual(new Uint8Array([0]));
+    expect(serialize(Signal, { signal: "Level", value: 5 })).toEqual(new Uint8Array([1, 5]));
+    expect(serialize(Signal, { signal: "Pulse", width: 4, inverted: false })).toEqual(
+      new Uint8Array([2, 4, 0])
+    );
+  });
+
+  it("should reject unknown tags and discriminants", () => {
+    const encoded = trySerialize(TaggedEnemy, { kind: "Orc" } as unknown as TaggedEnemy);
+    expect(!encoded.ok && encoded.error.message).toContain('"Orc" is not a variant of Enemy');
+
+    const decoded = tryDeserialize(TaggedEnemy, new Uint8Array([9]));
+    expect(!decoded.ok && decoded.error).toBeInstanceOf(DeserializeError);
+    expect(!decoded.ok && decoded.error.code).toBe(ErrorCode.InvalidValue);
+    expect(!decoded.ok && decoded.error.message).toContain(
+      "Invalid enum discriminant for Enemy: 9 (max: 3)"
+    );
+  });
+
+  it("should reject a tag that clashes with a variant property", () => {
+    expect(() => discriminated(EnemySchema, "id")).toThrow(RangeError);
+    expect(() => discriminated(ComplexEnumSchema, "value")).toThrow(
+      'Variant NewtypeVariant of ComplexEnum already has a "value" property'
+    );
+  });
+});
+
+describe.runIf(existsSync(MANIFEST_PATH))("Tagged enum fixtures written by Rust", () => {
+  const entries = existsSync(MANIFEST_PATH)
+    ? (JSON.parse(readFileSync(MANIFEST_PATH, "utf8")) as { fixtures: ManifestEntry[] }).fixtures
+    : [];
+  const Tagged = discriminated(ComplexEnumSchema, "kind");
+
+  it.each(entries.filter((entry) => entry.type === "ComplexEnum"))(
+    "should round-trip $file",
+    (entry) => {
+      const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+      const { value } = deserialize(Tagged, data);
+      expect(value.kind).toBe(deserialize(ComplexEnumSchema, data).value.type);
+      expect(serialize(Tagged, value)).toEqual(data);
+    }
+  );
+});
@@ -0,0 +1,81 @@
+/**
+ * Tests for bit-packed flag schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  tryDeserialize,
+  u8,
+  u16,
+  u32,
+  struct,
+  flags,
+  type InferType,
+} from "../../src/index.js";
+
+describe("flags()", () => {
+  const StatusSchema = flags(u8(), { heater: 0, pump: 1, valve_open: 2, alarm: 7 });
+
+  it("should decode each bit as a named boolean", () => {
+    const data = serialize(u8(), 0b1000_0101);
+    expect(deserialize(StatusSchema, data).value).toEqual({
+      heater: true,
+      pump: false,
+      valve_open: true,
+      alarm: true,
+    });
+  });
+
+  it("should keep the integer wire format", () => {
+    const data = serialize(StatusSchema, {
+      heater: false,
+      pump: true,
+      valve_open: false,
+      alarm: true,
+    });
+    expect(data).toEqual(serialize(u8(), 0b1000_0010));
+  });
+
+  it("should support the full width of u16 and u32", () => {
+    const wide = flags(u32(), { low: 0, high: 31 });
+    const data = serialize(wide, { low: true, high: true });
+    expect(deserialize(u32(), data).value).toBe(0x8000_0001);
+    expect(deserialize(wide, data).value).toEqual({ low: true, high: true });
+
+    const medium = flags(u16(), { top: 15 });
+    expect(deserialize(u16(), serialize(medium, { top: true })).value).toBe(0x8000);
+  });
+
+  it("should fail to decode unnamed bits by default", () => {
+    const result = tryDeserialize(StatusSchema, serialize(u8(), 0b0000_1000));
+    expect(result.ok).toBe(false);
+  });
+
+  it("should drop unnamed bits when asked", () => {
+    const lenient = flags(u8(), { heater: 0 }, { ignoreUnknownBits: true });
+    expect(deserialize(lenient, serialize(u8(), 0xff)).value).toEqual({ heater: true });
+  });
+
+  it("should reject invalid bit layouts", () => {
+    expect(() => flags(u8(), { a: 8 })).toThrow(RangeError);
+    expect(() => flags(u8(), { a: -1 })).toThrow(RangeError);
+    expect(() => flags(u16(), { a: 1.5 })).toThrow(RangeError);
+    expect(() => flags(u8(), { a: 3, b: 3 })).toThrow(RangeError);
+  });
+
+  it("should infer named booleans inside structs", () => {
+    const DeviceSchema = struct({ flags: StatusSchema, uptime_s: u32() });
+    type Device = InferType<typeof DeviceSchema>;
+
+    const device: Device = {
+      flags: { heater: true, pump: true, valve_open: false, alarm: false },
+      uptime_s: 60,
+    };
+    const _verify: boolean = device.flags.valve_open;
+    void _verify;
+
+    expect(deserialize(DeviceSchema, serialize(DeviceSchema, device)).value).toEqual(device);
+  });
+});
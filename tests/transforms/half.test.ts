@@ -0,0 +1,110 @@
+/**
+ * Tests for half-precision float schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  u16,
+  f16,
+  bf16,
+  struct,
+  type HalfFloatSchema,
+  type InferType,
+} from "../../src/index.js";
+
+const decodeBits = (schema: HalfFloatSchema, bits: number): number =>
+  deserialize(schema, serialize(u16(), bits)).value;
+
+const encodeBits = (schema: HalfFloatSchema, value: number): number =>
+  deserialize(u16(), serialize(schema, value)).value;
+
+/**
+ * Bit patterns that do not survive decode followed by encode (NaNs excluded)
+ */
+function roundTripFailures(schema: HalfFloatSchema): number[] {
+  const failures: number[] = [];
+  for (let bits = 0; bits < 0x10000; bits++) {
+    const value = decodeBits(schema, bits);
+    if (!Number.isNaN(value) && encodeBits(schema, value) !== bits) {
+      failures.push(bits);
+    }
+  }
+  return failures;
+}
+
+describe("f16()", () => {
+  it("should use the varint u16 wire format of the half crate", () => {
+    // 1.5 is 0x3e00, which takes two varint bytes
+    expect(serialize(f16(), 1.5)).toEqual(new Uint8Array([0x80, 0x7c]));
+    expect(deserialize(f16(), new Uint8Array([0x80, 0x7c])).value).toBe(1.5);
+  });
+
+  it("should decode normal, subnormal and special values exactly", () => {
+    expect(decodeBits(f16(), 0x3c00)).toBe(1);
+    expect(decodeBits(f16(), 0xc000)).toBe(-2);
+    expect(decodeBits(f16(), 0x7bff)).toBe(65504);
+    expect(decodeBits(f16(), 0x0400)).toBe(2 ** -14);
+    expect(decodeBits(f16(), 0x0001)).toBe(2 ** -24);
+    expect(decodeBits(f16(), 0x8000)).toBe(-0);
+    expect(decodeBits(f16(), 0x7c00)).toBe(Infinity);
+    expect(decodeBits(f16(), 0xfc00)).toBe(-Infinity);
+    expect(decodeBits(f16(), 0x7e00)).toBeNaN();
+  });
+
+  it("should round to nearest, ties to even", () => {
+    expect(encodeBits(f16(), 0.1)).toBe(0x2e66);
+    // Halfway between 1 and the next f16 (1 + 2^-10) rounds down to even
+    expect(encodeBits(f16(), 1 + 2 ** -11)).toBe(0x3c00);
+    expect(encodeBits(f16(), 1 + 3 * 2 ** -11)).toBe(0x3c02);
+    // Halfway between the two smallest subnormals rounds up to even
+    expect(encodeBits(f16(), 1.5 * 2 ** -24)).toBe(0x0002);
+    expect(encodeBits(f16(), 2 ** -26)).toBe(0x0000);
+  });
+
+  it("should overflow to infinity and keep NaN", () => {
+    expect(encodeBits(f16(), 65519)).toBe(0x7bff);
+    expect(encodeBits(f16(), 65520)).toBe(0x7c00);
+    expect(encodeBits(f16(), -1e9)).toBe(0xfc00);
+    expect(encodeBits(f16(), NaN)).toBe(0x7e00);
+  });
+
+  it("should round-trip every finite bit pattern", () => {
+    expect(roundTripFailures(f16())).toEqual([]);
+  });
+});
+
+describe("bf16()", () => {
+  it("should decode the top half of an f32", () => {
+    expect(decodeBits(bf16(), 0x3f80)).toBe(1);
+    expect(decodeBits(bf16(), 0xc000)).toBe(-2);
+    expect(decodeBits(bf16(), 0x4049)).toBe(3.140625);
+    expect(decodeBits(bf16(), 0x7f80)).toBe(Infinity);
+    expect(decodeBits(bf16(), 0x0001)).toBe(2 ** -133);
+  });
+
+  it("should keep the f32 range with reduced precision", () => {
+    expect(encodeBits(bf16(), Math.PI)).toBe(0x4049);
+    expect(encodeBits(bf16(), 3e38)).toBe(0x7f62);
+    expect(encodeBits(bf16(), 1e39)).toBe(0x7f80);
+    expect(encodeBits(bf16(), NaN)).toBe(0x7fc0);
+  });
+
+  it("should round-trip every finite bit pattern", () => {
+    expect(roundTripFailures(bf16())).toEqual([]);
+  });
+});
+
+describe("half-precision fields in structs", () => {
+  it("should infer numbers", () => {
+    const SampleSchema = struct({ pitch: f16(), gain: bf16() });
+    type Sample = InferType<typeof SampleSchema>;
+
+    const sample: Sample = { pitch: 0.5, gain: -4 };
+    const _verify: number = sample.pitch;
+    void _verify;
+
+    expect(deserialize(SampleSchema, serialize(SampleSchema, sample)).value).toEqual(sample);
+  });
+});
@@ -0,0 +1,66 @@
+/**
+ * Tests for identifier enum schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  enumType,
+  unitVariant,
+  identifier,
+  map,
+  u32,
+  f32,
+  type InferType,
+} from "../../src/index.js";
+
+const FieldSchema = identifier("Field", ["Temperature", "Humidity", "Pressure"]);
+
+describe("identifier()", () => {
+  it("should infer a string-literal union", () => {
+    type Field = InferType<typeof FieldSchema>;
+    const field: Field = "Humidity";
+    const _verify: "Temperature" | "Humidity" | "Pressure" = field;
+    void _verify;
+
+    // @ts-expect-error - not a variant
+    const _invalid: Field = "Voltage";
+    void _invalid;
+  });
+
+  it("should encode exactly like a unit-only enum", () => {
+    const EnumSchema = enumType("Field", {
+      Temperature: unitVariant("Temperature"),
+      Humidity: unitVariant("Humidity"),
+      Pressure: unitVariant("Pressure"),
+    });
+
+    expect(serialize(FieldSchema, "Pressure")).toEqual(serialize(EnumSchema, { type: "Pressure" }));
+    expect(serialize(FieldSchema, "Pressure")).toEqual(serialize(u32(), 2));
+    expect(deserialize(FieldSchema, serialize(u32(), 1)).value).toBe("Humidity");
+  });
+
+  it("should work as a Map key", () => {
+    const ReadingsSchema = map(FieldSchema, f32());
+    const readings = new Map<InferType<typeof FieldSchema>, number>([
+      ["Temperature", 21.5],
+      ["Pressure", 1013],
+    ]);
+
+    const { value } = deserialize(ReadingsSchema, serialize(ReadingsSchema, readings));
+    expect(value.get("Temperature")).toBe(21.5);
+    expect(value.get("Pressure")).toBe(1013);
+  });
+
+  it("should reject unknown indices and names", () => {
+    expect(tryDeserialize(FieldSchema, serialize(u32(), 3)).ok).toBe(false);
+    expect(trySerialize(FieldSchema, "Voltage" as "Pressure").ok).toBe(false);
+  });
+
+  it("should reject duplicate variants", () => {
+    expect(() => identifier("Field", ["A", "B", "A"])).toThrow(RangeError);
+  });
+});
@@ -0,0 +1,42 @@
+/**
+ * Tests for the NonZero integer schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  i64,
+  u16,
+  struct,
+  nonZero,
+  type InferType,
+} from "../../src/index.js";
+
+describe("nonZero()", () => {
+  it("should encode as the integer it wraps", () => {
+    expect(serialize(nonZero(u16()), 300)).toEqual(serialize(u16(), 300));
+    expect(deserialize(nonZero(u16()), serialize(u16(), 300)).value).toBe(300);
+    expect(deserialize(nonZero(i64()), serialize(i64(), -1n)).value).toBe(-1n);
+  });
+
+  it("should fail to encode or decode zero", () => {
+    expect(trySerialize(nonZero(u16()), 0).ok).toBe(false);
+    expect(trySerialize(nonZero(i64()), 0n).ok).toBe(false);
+    expect(tryDeserialize(nonZero(u16()), serialize(u16(), 0)).ok).toBe(false);
+    expect(tryDeserialize(nonZero(i64()), serialize(i64(), 0n)).ok).toBe(false);
+  });
+
+  it("should infer the integer's type", () => {
+    const ListenerSchema = struct({ port: nonZero(u16()), offset: nonZero(i64()) });
+    type Listener = InferType<typeof ListenerSchema>;
+
+    const listener: Listener = { port: 8080, offset: -1n };
+    const _port: number = listener.port;
+    const _offset: bigint = listener.offset;
+    void _port;
+    void _offset;
+  });
+});
@@ -0,0 +1,121 @@
+/**
+ * Tests for scaled fixed-point schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  i16,
+  u16,
+  i32,
+  u64,
+  struct,
+  string,
+  scaled,
+  type InferType,
+} from "../../src/index.js";
+
+describe("scaled()", () => {
+  it("should keep the integer wire format", () => {
+    const schema = scaled(i16(), { scale: 0.01 });
+    expect(serialize(schema, 21.5)).toEqual(serialize(i16(), 2150));
+  });
+
+  it("should decode to the scaled value", () => {
+    const schema = scaled(i16(), { scale: 0.01 });
+    const data = serialize(i16(), -2153);
+    expect(deserialize(schema, data).value).toBe(-21.53);
+  });
+
+  it("should apply offset on decode and remove it on encode", () => {
+    // Kelvin in tenths, exposed as Celsius
+    const schema = scaled(u16(), { scale: 0.1, offset: -273.15 });
+    const data = serialize(schema, 21.85);
+    expect(deserialize(u16(), data).value).toBe(2950);
+    expect(deserialize(schema, data).value).toBeCloseTo(21.85, 10);
+  });
+
+  it("should support scales greater than one", () => {
+    const schema = scaled(u16(), { scale: 250 });
+    const data = serialize(schema, 1000);
+    expect(deserialize(u16(), data).value).toBe(4);
+    expect(deserialize(schema, data).value).toBe(1000);
+  });
+
+  it("should support bigint-backed integer schemas", () => {
+    const schema = scaled(u64(), { scale: 0.001 });
+    const data = serialize(schema, 1699000000.5);
+    expect(deserialize(u64(), data).value).toBe(1699000000500n);
+    expect(deserialize(schema, data).value).toBe(1699000000.5);
+  });
+
+  it("should round trip values that are not exact in binary", () => {
+    const schema = scaled(i32(), { scale: 0.01, rounding: "ceil" });
+    for (const value of [21.53, -0.07, 0.29, 1234.56]) {
+      expect(deserialize(schema, serialize(schema, value)).value).toBe(value);
+    }
+  });
+
+  describe("rounding", () => {
+    const raw = (rounding: "round" | "floor" | "ceil" | "trunc", value: number): number => {
+      const schema = scaled(i16(), { scale: 0.1, rounding });
+      return deserialize(i16(), serialize(schema, value)).value;
+    };
+
+    it("should round halves away from zero by default", () => {
+      expect(raw("round", 0.25)).toBe(3);
+      expect(raw("round", -0.25)).toBe(-3);
+      expect(raw("round", 0.24)).toBe(2);
+    });
+
+    it("should floor, ceil and truncate", () => {
+      expect(raw("floor", -0.21)).toBe(-3);
+      expect(raw("ceil", -0.29)).toBe(-2);
+      expect(raw("trunc", -0.29)).toBe(-2);
+      expect(raw("ceil", 0.21)).toBe(3);
+    });
+
+    it("should reject inexact values in error mode", () => {
+      const schema = scaled(i16(), { scale: 0.1, rounding: "error" });
+      expect(serialize(schema, 0.3)).toEqual(serialize(i16(), 3));
+      const result = trySerialize(schema, 0.35);
+      expect(result.ok).toBe(false);
+    });
+  });
+
+  describe("errors", () => {
+    it("should fail when the raw value is out of range", () => {
+      const schema = scaled(i16(), { scale: 0.01 });
+      const result = trySerialize(schema, 400);
+      expect(result.ok).toBe(false);
+    });
+
+    it("should fail on non-finite values", () => {
+      const schema = scaled(i16(), { scale: 0.01 });
+      expect(trySerialize(schema, NaN).ok).toBe(false);
+      expect(trySerialize(schema, Infinity).ok).toBe(false);
+    });
+
+    it("should reject a zero or non-finite scale", () => {
+      expect(() => scaled(i16(), { scale: 0 })).toThrow(RangeError);
+      expect(() => scaled(i16(), { scale: NaN })).toThrow(RangeError);
+    });
+  });
+
+  it("should infer number inside structs", () => {
+    const ReadingSchema = struct({
+      sensor: string(),
+      temperature: scaled(i16(), { scale: 0.01 }),
+    });
+    type Reading = InferType<typeof ReadingSchema>;
+
+    const reading: Reading = { sensor: "probe-1", temperature: 23.45 };
+    const _verify: number = reading.temperature;
+    void _verify;
+
+    const decoded = deserialize(ReadingSchema, serialize(ReadingSchema, reading));
+    expect(decoded.value).toEqual(reading);
+  });
+});
@@ -0,0 +1,88 @@
+/**
+ * Tests for timestamp schemas
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  i32,
+  i64,
+  u32,
+  u64,
+  struct,
+  string,
+  timestamp,
+  type InferType,
+} from "../../src/index.js";
+
+describe("timestamp()", () => {
+  it("should decode unix seconds to a Date", () => {
+    const schema = timestamp(u64(), "unix_seconds");
+    const data = serialize(u64(), 1699000000n);
+    expect(deserialize(schema, data).value).toEqual(new Date("2023-11-03T08:26:40.000Z"));
+  });
+
+  it("should decode unix millis to a Date", () => {
+    const schema = timestamp(u64(), "unix_millis");
+    const data = serialize(u64(), 1699000000123n);
+    expect(deserialize(schema, data).value.toISOString()).toBe("2023-11-03T08:26:40.123Z");
+  });
+
+  it("should keep the integer wire format", () => {
+    const date = new Date("2023-11-03T08:26:40.000Z");
+    expect(serialize(timestamp(u32(), "unix_seconds"), date)).toEqual(serialize(u32(), 1699000000));
+    expect(serialize(timestamp(u64(), "unix_seconds"), date)).toEqual(serialize(u64(), 1699000000n));
+    expect(serialize(timestamp(i64(), "unix_millis"), date)).toEqual(
+      serialize(i64(), 1699000000000n)
+    );
+  });
+
+  it("should truncate sub-second precision towards the past", () => {
+    const schema = timestamp(i64(), "unix_seconds");
+    const after = serialize(schema, new Date("2023-11-03T08:26:40.999Z"));
+    expect(deserialize(i64(), after).value).toBe(1699000000n);
+
+    const before = serialize(schema, new Date("1969-12-31T23:59:59.500Z"));
+    expect(deserialize(i64(), before).value).toBe(-1n);
+  });
+
+  it("should support dates before the epoch with signed schemas", () => {
+    const schema = timestamp(i32(), "unix_seconds");
+    const date = new Date("1950-06-01T00:00:00.000Z");
+    expect(deserialize(schema, serialize(schema, date)).value).toEqual(date);
+  });
+
+  it("should fail to encode dates before the epoch with unsigned schemas", () => {
+    const schema = timestamp(u32(), "unix_seconds");
+    expect(trySerialize(schema, new Date("1950-06-01T00:00:00.000Z")).ok).toBe(false);
+  });
+
+  it("should fail to encode an invalid Date", () => {
+    const schema = timestamp(u64(), "unix_millis");
+    expect(trySerialize(schema, new Date("not a date")).ok).toBe(false);
+  });
+
+  it("should fail to decode timestamps outside the range of Date", () => {
+    const schema = timestamp(u64(), "unix_seconds");
+    const data = serialize(u64(), 0xffff_ffff_ffffn);
+    expect(tryDeserialize(schema, data).ok).toBe(false);
+  });
+
+  it("should infer Date inside structs", () => {
+    const MetadataSchema = struct({
+      version: string(),
+      timestamp: timestamp(u64(), "unix_seconds"),
+    });
+    type Metadata = InferType<typeof MetadataSchema>;
+
+    const metadata: Metadata = { version: "1.0.0", timestamp: new Date(1699000000 * 1000) };
+    const _verify: Date = metadata.timestamp;
+    void _verify;
+
+    const decoded = deserialize(MetadataSchema, serialize(MetadataSchema, metadata));
+    expect(decoded.value).toEqual(metadata);
+  });
+});
@@ -0,0 +1,73 @@
+/**
+ * Tests for the uuid::Uuid schema
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  serialize,
+  deserialize,
+  trySerialize,
+  tryDeserialize,
+  bytes,
+  struct,
+  uuid,
+  parseUuid,
+  uuidToBytes,
+  bytesToUuid,
+  type InferType,
+  type Uuid,
+} from "../../src/index.js";
+
+const TEXT = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+const BYTES = new Uint8Array([
+  0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f, 0xe0, 0xc8,
+]);
+
+describe("uuid()", () => {
+  it("should decode the 16 bytes to hyphenated text", () => {
+    const data = serialize(bytes(), BYTES);
+    expect(data[0]).toBe(16);
+    expect(deserialize(uuid(), data).value).toBe(TEXT);
+    expect(serialize(uuid(), TEXT as Uuid)).toEqual(data);
+  });
+
+  it("should decode to the bytes when asked", () => {
+    const data = serialize(bytes(), BYTES);
+    expect(deserialize(uuid("bytes"), data).value).toEqual(BYTES);
+    expect(serialize(uuid("bytes"), BYTES)).toEqual(data);
+  });
+
+  it("should fail on anything but 16 bytes", () => {
+    const short = serialize(bytes(), BYTES.subarray(0, 15));
+    expect(tryDeserialize(uuid(), short).ok).toBe(false);
+    expect(tryDeserialize(uuid("bytes"), short).ok).toBe(false);
+    expect(trySerialize(uuid("bytes"), new Uint8Array(17)).ok).toBe(false);
+    expect(trySerialize(uuid(), "not a uuid" as Uuid).ok).toBe(false);
+  });
+
+  it("should infer a branded string", () => {
+    const SessionSchema = struct({ id: uuid() });
+    type Session = InferType<typeof SessionSchema>;
+
+    const session: Session = { id: parseUuid(TEXT) };
+    const _text: string = session.id;
+    // @ts-expect-error - a plain string is not a Uuid
+    const _id: Uuid = TEXT;
+    void _text;
+    void _id;
+  });
+});
+
+describe("parseUuid()", () => {
+  it("should normalize to lowercase hyphenated text", () => {
+    expect(parseUuid(TEXT.toUpperCase())).toBe(TEXT);
+    expect(parseUuid(TEXT.replace(/-/g, ""))).toBe(TEXT);
+    expect(bytesToUuid(uuidToBytes(TEXT))).toBe(TEXT);
+  });
+
+  it("should reject text that is not a UUID", () => {
+    for (const text of ["", "67e55044-10b1-426f-9247", "g7e55044-10b1-426f-9247-bb680e5fe0c8"]) {
+      expect(() => parseUuid(text)).toThrow(RangeError);
+    }
+  });
+});
@@ -0,0 +1,301 @@
+/**
+ * Tests for the transport adapters, over fakes of the links they wrap
+ */
+
+import { EventEmitter } from "node:events";
+import { describe, it, expect } from "vitest";
+import {
+  BluetoothTransport,
+  ErrorCode,
+  FramingError,
+  NodeSocketTransport,
+  SerialTransport,
+  TransportError,
+  WebSocketTransport,
+  WebUsbTransport,
+  cobsEncode,
+  type BluetoothNotifyCharacteristic,
+  type UsbDeviceLike,
+} from "../../src/index.js";
+
+async function collect<T>(iterable: AsyncIterable<T>, count: number): Promise<T[]> {
+  const items: T[] = [];
+  for await (const item of iterable) {
+    items.push(item);
+    if (items.length === count) {
+      break;
+    }
+  }
+  return items;
+}
+
+const concat = (...parts: Uint8Array[]): Uint8Array => {
+  const joined = new Uint8Array(parts.reduce((length, part) => length + part.length, 0));
+  let offset = 0;
+  for (const part of parts) {
+    joined.set(part, offset);
+    offset += part.length;
+  }
+  return joined;
+};
+
+const frameA = new Uint8Array([1, 0, 2]);
+const frameB = new Uint8Array([3]);
+const stream = concat(cobsEncode(frameA), cobsEncode(frameB));
+
+class FakeWebSocket extends EventTarget {
+  binaryType = "blob";
+  readyState = 0;
+  readonly sent: Uint8Array[] = [];
+
+  send(data: Uint8Array): void {
+    this.sent.push(data);
+  }
+
+  open(): void {
+    this.readyState = 1;
+    this.dispatchEvent(new Event("open"));
+  }
+
+  receive(data: unknown): void {
+    this.dispatchEvent(new MessageEvent("message", { data }));
+  }
+
+  close(): void {
+    this.readyState = 3;
+    this.dispatchEvent(new Event("close"));
+  }
+}
+
+describe("WebSocketTransport", () => {
+  it("should yield each binary message as a frame until the socket closes", async () => {
+    const socket = new FakeWebSocket();
+    const link = new WebSocketTransport(socket as unknown as WebSocket);
+    const frames = collect(link.messages(), 10);
+
+    socket.receive(new Uint8Array([1, 2]).buffer);
+    socket.receive("hello");
+    socket.close();
+
+    expect(socket.binaryType).toBe("arraybuffer");
+    expect(await frames).toEqual([new Uint8Array([1, 2])]);
+  });
+
+  it("should send once the socket opens and fail once it closed", async () => {
+    const socket = new FakeWebSocket();
+    const link = new WebSocketTransport(socket as unknown as WebSocket);
+
+    const sent = link.send(new Uint8Array([9]));
+    socket.open();
+    await sent;
+    expect(socket.sent).toEqual([new Uint8Array([9])]);
+
+    socket.close();
+    await expect(link.send(new Uint8Array([9]))).rejects.toThrow(TransportError);
+  });
+});
+
+describe("SerialTransport", () => {
+  it("should cut the byte stream into frames, whatever the chunks", async () => {
+    const readable = new ReadableStream<Uint8Array>({
+      start(controller) {
+        controller.enqueue(stream.subarray(0, 2));
+        controller.enqueue(stream.subarray(2));
+        controller.close();
+      },
+    });
+    const link = new SerialTransport({ readable, writable: null });
+
+    expect(await collect(link.messages(), 10)).toEqual([frameA, frameB]);
+    // The reader was released
+    expect(readable.locked).toBe(false);
+  });
+
+  it("should write COBS frames, one after another", async () => {
+    const written: Uint8Array[] = [];
+    const writable = new WritableStream<Uint8Array>({
+      write: (chunk) => {
+        written.push(chunk);
+      },
+    });
+    const link = new SerialTransport({ readable: null, writable });
+
+    await Promise.all([link.send(frameA), link.send(frameB)]);
+
+    expect(written).toEqual([cobsEncode(frameA), cobsEncode(frameB)]);
+  });
+
+  it("should refuse a port that is not open", async () => {
+    const link = new SerialTransport({ readable: null, writable: null });
+
+    await expect(collect(link.messages(), 1)).rejects.toThrow(TransportError);
+    await expect(link.send(frameA)).rejects.toThrow(TransportError);
+  });
+
+  it("should report malformed and oversized frames and go on", async () => {
+    const errors: FramingError[] = [];
+    const readable = new ReadableStream<Uint8Array>({
+      start(controller) {
+        // A block running past its frame, a frame too long, then a good one
+        controller.enqueue(concat(new Uint8Array([5, 1, 0]), new Uint8Array(8).fill(7)));
+        controller.enqueue(concat(new Uint8Array([0]), cobsEncode(frameB)));
+        controller.close();
+      },
+    });
+    const link = new SerialTransport(
+      { readable, writable: null },
+      { maxFrameLength: 4, onFramingError: (error) => errors.push(error) }
+    );
+
+    expect(await collect(link.messages(), 10)).toEqual([frameB]);
+    expect(errors.map((error) => error.code)).toEqual([
+      ErrorCode.BadEncoding,
+      ErrorCode.BufferFull,
+    ]);
+  });
+
+  it("should stop reading when aborted", async () => {
+    const readable = new ReadableStream<Uint8Array>();
+    const link = new SerialTransport({ readable, writable: null });
+    const controller = new AbortController();
+    const frames = collect(link.messages(controller.signal), 10);
+
+    controller.abort();
+
+    expect(await frames).toEqual([]);
+    expect(readable.locked).toBe(false);
+  });
+});
+
+describe("BluetoothTransport", () => {
+  class FakeCharacteristic extends EventTarget implements BluetoothNotifyCharacteristic {
+    value: DataView | undefined;
+    notifying = false;
+    readonly written: Uint8Array[] = [];
+
+    startNotifications(): Promise<unknown> {
+      this.notifying = true;
+      return Promise.resolve(this);
+    }
+
+    notify(bytes: Uint8Array): void {
+      this.value = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+      this.dispatchEvent(new Event("characteristicvaluechanged"));
+    }
+
+    writeValueWithoutResponse(value: Uint8Array): Promise<void> {
+      this.written.push(value.slice());
+      return Promise.resolve();
+    }
+  }
+
+  it("should start notifications and reassemble frames spanning them", async () => {
+    const characteristic = new FakeCharacteristic();
+    const link = new BluetoothTransport({ notify: characteristic, write: characteristic });
+    const frames = collect(link.messages(), 2);
+    await new Promise((resolve) => setTimeout(resolve, 0));
+
+    characteristic.notify(stream.subarray(0, 3));
+    characteristic.notify(stream.subarray(3));
+
+    expect(characteristic.notifying).toBe(true);
+    expect(await frames).toEqual([frameA, frameB]);
+  });
+
+  it("should write frames in pieces of at most chunkSize bytes", async () => {
+    const characteristic = new FakeCharacteristic();
+    const link = new BluetoothTransport({
+      notify: characteristic,
+      write: characteristic,
+      chunkSize: 2,
+    });
+
+    await link.send(frameA);
+
+    expect(characteristic.written).toEqual([
+      new Uint8Array([2, 1]),
+      new Uint8Array([2, 2]),
+      new Uint8Array([0]),
+    ]);
+    expect(
+      () => new BluetoothTransport({ notify: characteristic, write: characteristic, chunkSize: 0 })
+    ).toThrow(RangeError);
+  });
+});
+
+describe("WebUsbTransport", () => {
+  it("should read IN transfers into frames and write OUT transfers", async () => {
+    const chunks = [stream.subarray(0, 4), stream.subarray(4)];
+    const out: Uint8Array[] = [];
+    const device: UsbDeviceLike = {
+      transferIn: (endpoint, length) => {
+        expect({ endpoint, length }).toEqual({ endpoint: 1, length: 64 });
+        const chunk = chunks.shift();
+        return chunk === undefined
+          ? new Promise(() => {})
+          : Promise.resolve({ data: new DataView(chunk.slice().buffer), status: "ok" });
+      },
+      transferOut: (endpoint, data) => {
+        expect(endpoint).toBe(2);
+        out.push(data);
+        return Promise.resolve({ status: "ok" });
+      },
+    };
+    const link = new WebUsbTransport(device, { endpointIn: 1, endpointOut: 2 });
+
+    expect(await collect(link.messages(), 2)).toEqual([frameA, frameB]);
+    await link.send(frameB);
+    expect(out).toEqual([cobsEncode(frameB)]);
+  });
+
+  it("should fail on a stalled transfer", async () => {
+    const device: UsbDeviceLike = {
+      transferIn: () => Promise.resolve({ status: "stall" }),
+      transferOut: () => Promise.resolve({ status: "stall" }),
+    };
+    const link = new WebUsbTransport(device, { endpointIn: 1, endpointOut: 2 });
+
+    await expect(collect(link.messages(), 1)).rejects.toThrow(TransportError);
+    await expect(link.send(frameA)).rejects.toThrow(TransportError);
+  });
+});
+
+describe("NodeSocketTransport", () => {
+  class FakeSocket extends EventEmitter {
+    readonly written: Uint8Array[] = [];
+    failWrites = false;
+
+    write(data: Uint8Array, callback: (error?: Error | null) => void): boolean {
+      this.written.push(data);
+      callback(this.failWrites ? new Error("EPIPE") : null);
+      return true;
+    }
+  }
+
+  it("should cut data events into frames until the socket ends", async () => {
+    const socket = new FakeSocket();
+    const link = new NodeSocketTransport(socket);
+    const frames = collect(link.messages(), 10);
+
+    socket.emit("data", stream.subarray(0, 5));
+    socket.emit("data", stream.subarray(5));
+    socket.emit("end");
+
+    expect(await frames).toEqual([frameA, frameB]);
+    expect(socket.listenerCount("data")).toBe(0);
+  });
+
+  it("should fail on socket errors and failed writes", async () => {
+    const socket = new FakeSocket();
+    const link = new NodeSocketTransport(socket);
+    const frames = collect(link.messages(), 10);
+
+    socket.emit("error", new Error("ECONNRESET"));
+    await expect(frames).rejects.toThrow(TransportError);
+
+    await link.send(frameA);
+    expect(socket.written).toEqual([cobsEncode(frameA)]);
+    socket.failWrites = true;
+    await expect(link.send(frameA)).rejects.toThrow(TransportError);
+  });
+});
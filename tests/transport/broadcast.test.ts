@@ -0,0 +1,196 @@
+/**
+ * Tests for handing one stream of messages to several consumers
+ */
+
+import { describe, it, expect } from "vitest";
+import { Broadcast, tee } from "../../src/index.js";
+
+/**
+ * A source of the given messages, counting how many were pulled from it
+ */
+function counted<T>(messages: T[]): { source: AsyncIterable<T>; pulled: () => number } {
+  let pulled = 0;
+  async function* source(): AsyncGenerator<T> {
+    for (const message of messages) {
+      pulled++;
+      yield message;
+    }
+  }
+  return { source: source(), pulled: () => pulled };
+}
+
+/**
+ * A source yielding what `push` is given, until `end` or `fail`
+ */
+function pushed<T>(): {
+  source: AsyncIterable<T>;
+  push: (message: T) => void;
+  end: () => void;
+  fail: (error: Error) => void;
+} {
+  const queue: ({ message: T } | { end: true } | { error: Error })[] = [];
+  let wake: (() => void) | undefined;
+  const add = (item: (typeof queue)[number]): void => {
+    queue.push(item);
+    wake?.();
+  };
+  async function* source(): AsyncGenerator<T> {
+    for (;;) {
+      const item = queue.shift();
+      if (item === undefined) {
+        await new Promise<void>((resolve) => {
+          wake = resolve;
+        });
+      } else if ("message" in item) {
+        yield item.message;
+      } else if ("error" in item) {
+        throw item.error;
+      } else {
+        return;
+      }
+    }
+  }
+  return {
+    source: source(),
+    push: (message) => add({ message }),
+    end: () => add({ end: true }),
+    fail: (error) => add({ error }),
+  };
+}
+
+async function collect<T>(iterable: AsyncIterable<T>): Promise<T[]> {
+  const items: T[] = [];
+  for await (const item of iterable) {
+    items.push(item);
+  }
+  return items;
+}
+
+async function settle(): Promise<void> {
+  for (let i = 0; i < 5; i++) {
+    await new Promise((resolve) => setTimeout(resolve, 0));
+  }
+}
+
+describe("Broadcast", () => {
+  it("should hand every message to every subscriber", async () => {
+    const messages = new Broadcast(counted([1, 2, 3]).source);
+    const [a, b] = [messages.subscribe(), messages.subscribe()];
+
+    expect(await Promise.all([collect(a), collect(b)])).toEqual([
+      [1, 2, 3],
+      [1, 2, 3],
+    ]);
+    expect(messages.subscribers).toBe(0);
+  });
+
+  it("should drop for full subscribers without holding the others back", async () => {
+    const { source, pulled } = counted([1, 2, 3, 4, 5]);
+    const messages = new Broadcast(source);
+    const dropped: number[] = [];
+    const latest = messages.subscribe({ bufferSize: 2, overflow: "drop-oldest" });
+    const first = messages.subscribe({
+      bufferSize: 2,
+      overflow: "drop-newest",
+      onDrop: (message) => dropped.push(message),
+    });
+    const all = messages.subscribe({ bufferSize: Infinity });
+    await settle();
+
+    expect(pulled()).toBe(5);
+    expect(await collect(all)).toEqual([1, 2, 3, 4, 5]);
+    expect(await collect(latest)).toEqual([4, 5]);
+    expect(await collect(first)).toEqual([1, 2]);
+    expect(dropped).toEqual([3, 4, 5]);
+  });
+
+  it("should stop reading while a blocking subscriber is full", async () => {
+    const { source, pulled } = counted([1, 2, 3, 4, 5]);
+    const messages = new Broadcast(source);
+    const slow = messages.subscribe({ bufferSize: 2 });
+    const fast = messages.subscribe({ bufferSize: 1, overflow: "drop-oldest" });
+    await settle();
+
+    expect(pulled()).toBe(2);
+    expect((await slow.next()).value).toBe(1);
+    await settle();
+    expect(pulled()).toBe(3);
+    expect((await fast.next()).value).toBe(3);
+
+    // Leaving lets the rest through
+    await slow.return?.();
+    await settle();
+    expect(await collect(fast)).toEqual([5]);
+    expect(pulled()).toBe(5);
+  });
+
+  it("should replay the last message to late subscribers that ask for it", async () => {
+    const { source, push, end } = pushed<string>();
+    const messages = new Broadcast(source);
+    const early = messages.subscribe();
+    push("idle");
+    push("heating");
+    await settle();
+
+    const replayed = messages.subscribe({ late: "replay-last" });
+    const dropped = messages.subscribe({ late: "drop" });
+    push("ready");
+    end();
+
+    expect(await collect(early)).toEqual(["idle", "heating", "ready"]);
+    expect(await collect(replayed)).toEqual(["heating", "ready"]);
+    expect(await collect(dropped)).toEqual(["ready"]);
+    expect(await collect(messages.subscribe({ late: "replay-last" }))).toEqual(["ready"]);
+  });
+
+  it("should fail every subscriber after the messages it holds", async () => {
+    const { source, push, fail } = pushed<number>();
+    const messages = new Broadcast(source);
+    const a = messages.subscribe();
+    const b = messages.subscribe();
+    push(1);
+    fail(new Error("Link lost"));
+
+    const received: number[] = [];
+    await expect(
+      (async () => {
+        for await (const message of a) {
+          received.push(message);
+        }
+      })()
+    ).rejects.toThrow("Link lost");
+    expect(received).toEqual([1]);
+    await expect(collect(b)).rejects.toThrow("Link lost");
+  });
+
+  it("should end subscribers on close or abort", async () => {
+    const { source, push } = pushed<number>();
+    const messages = new Broadcast(source);
+    const stop = new AbortController();
+    const aborted = messages.subscribe({ signal: stop.signal });
+    const closed = messages.subscribe();
+    push(1);
+    await settle();
+
+    expect((await aborted.next()).value).toBe(1);
+    stop.abort();
+    expect(await aborted.next()).toEqual({ done: true, value: undefined });
+    expect(messages.subscribers).toBe(1);
+
+    messages.close();
+    expect(await collect(closed)).toEqual([1]);
+    expect(() => messages.subscribe({ bufferSize: 0 })).toThrow(RangeError);
+  });
+});
+
+describe("tee", () => {
+  it("should give each iteration every message", async () => {
+    const iterations = tee(counted(["x", "y"]).source, 3);
+    expect(await Promise.all(iterations.map((iteration) => collect(iteration)))).toEqual([
+      ["x", "y"],
+      ["x", "y"],
+      ["x", "y"],
+    ]);
+    expect(() => tee(counted([]).source, -1)).toThrow(RangeError);
+  });
+});
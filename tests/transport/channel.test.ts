@@ -0,0 +1,187 @@
+/**
+ * Tests for typed channels over the in-memory transport
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  Channel,
+  DeserializeError,
+  MockTransport,
+  TransportError,
+  decodeOnly,
+  deserialize,
+  enumType,
+  f32,
+  newtypeVariant,
+  serialize,
+  struct,
+  u8,
+  unitVariant,
+  variantFilter,
+  type InferType,
+} from "../../src/index.js";
+
+const CommandSchema = enumType("Command", {
+  SetTarget: newtypeVariant("SetTarget", f32()),
+  Stop: unitVariant("Stop"),
+});
+type Command = InferType<typeof CommandSchema>;
+
+const ReadingSchema = struct({ sensor: u8(), celsius: f32() });
+type Reading = InferType<typeof ReadingSchema>;
+
+async function collect<T>(iterable: AsyncIterable<T>, count: number): Promise<T[]> {
+  const items: T[] = [];
+  for await (const item of iterable) {
+    items.push(item);
+    if (items.length === count) {
+      break;
+    }
+  }
+  return items;
+}
+
+describe("MockTransport", () => {
+  it("should hand delivered frames to every iteration", async () => {
+    const link = new MockTransport();
+    const first = collect(link.messages(), 2);
+    const second = collect(link.messages(), 2);
+    // Let both iterations subscribe
+    await Promise.resolve();
+
+    link.deliver(new Uint8Array([1]));
+    link.deliver(new Uint8Array([2]));
+
+    const expected = [new Uint8Array([1]), new Uint8Array([2])];
+    expect(await first).toEqual(expected);
+    expect(await second).toEqual(expected);
+  });
+
+  it("should deliver what one of a pair sends to the other", async () => {
+    const [a, b] = MockTransport.pair();
+    const received = collect(b.messages(), 1);
+    await Promise.resolve();
+
+    await a.send(new Uint8Array([7, 8]));
+
+    expect(await received).toEqual([new Uint8Array([7, 8])]);
+    expect(a.sent).toEqual([new Uint8Array([7, 8])]);
+  });
+
+  it("should end iterations on close and refuse to send after", async () => {
+    const [a, b] = MockTransport.pair();
+    const received = collect(b.messages(), 10);
+    await Promise.resolve();
+
+    a.close();
+
+    expect(await received).toEqual([]);
+    await expect(a.send(new Uint8Array([1]))).rejects.toThrow(TransportError);
+    await expect(b.send(new Uint8Array([1]))).rejects.toThrow(TransportError);
+  });
+
+  it("should fail iterations with a TransportError", async () => {
+    const link = new MockTransport();
+    const received = collect(link.messages(), 10);
+    await Promise.resolve();
+
+    link.fail(new Error("unplugged"));
+
+    await expect(received).rejects.toThrow(TransportError);
+  });
+
+  it("should end an iteration when its signal aborts", async () => {
+    const link = new MockTransport();
+    const controller = new AbortController();
+    const received = collect(link.messages(controller.signal), 10);
+    await Promise.resolve();
+
+    link.deliver(new Uint8Array([1]));
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    controller.abort();
+    link.deliver(new Uint8Array([2]));
+
+    expect(await received).toEqual([new Uint8Array([1])]);
+  });
+});
+
+describe("Channel", () => {
+  it("should decode received frames and encode sent messages", async () => {
+    const [link, device] = MockTransport.pair();
+    const channel = new Channel(link, { receive: ReadingSchema, send: CommandSchema });
+    const readings = collect(channel.messages(), 1);
+    await Promise.resolve();
+
+    const reading: Reading = { sensor: 1, celsius: 21.5 };
+    await device.send(serialize(ReadingSchema, reading));
+    const command: Command = { type: "SetTarget", value: 19 };
+    await channel.send(command);
+
+    expect(await readings).toEqual([reading]);
+    expect(link.sent.map((frame) => deserialize(CommandSchema, frame).value)).toEqual([command]);
+  });
+
+  it("should throw on a frame that does not decode", async () => {
+    const link = new MockTransport();
+    const channel = new Channel(link, { receive: ReadingSchema, send: CommandSchema });
+    const readings = collect(channel.messages(), 1);
+    await Promise.resolve();
+
+    link.deliver(new Uint8Array([1]));
+
+    await expect(readings).rejects.toThrow(DeserializeError);
+  });
+
+  it("should skip frames that do not decode with onDecodeError", async () => {
+    const link = new MockTransport();
+    const dropped: Uint8Array[] = [];
+    const channel = new Channel(
+      link,
+      { receive: ReadingSchema, send: CommandSchema },
+      { onDecodeError: (_error, frame) => dropped.push(frame) }
+    );
+    const readings = collect(channel.messages(), 1);
+    await Promise.resolve();
+
+    link.deliver(new Uint8Array([1]));
+    link.deliver(serialize(ReadingSchema, { sensor: 2, celsius: 0 }));
+
+    expect(await readings).toEqual([{ sensor: 2, celsius: 0 }]);
+    expect(dropped).toEqual([new Uint8Array([1])]);
+  });
+
+  it("should skip frames the filter rejects before decoding them", async () => {
+    const link = new MockTransport();
+    const dropped: Uint8Array[] = [];
+    const channel = new Channel(
+      link,
+      { receive: CommandSchema, send: CommandSchema },
+      {
+        filter: variantFilter(CommandSchema, ["Stop"]),
+        onDecodeError: (_error, frame) => dropped.push(frame),
+      }
+    );
+    const commands = collect(channel.messages(), 1);
+    await Promise.resolve();
+
+    link.deliver(serialize(CommandSchema, { type: "SetTarget", value: 21.5 }));
+    // Not a command at all, but not a Stop either
+    link.deliver(new Uint8Array([7]));
+    link.deliver(serialize(CommandSchema, { type: "Stop" }));
+
+    expect(await commands).toEqual([{ type: "Stop" }]);
+    expect(dropped).toEqual([]);
+  });
+
+  it("should take decode-only schemas for receiving only", () => {
+    const link = new MockTransport();
+    const TelemetrySchema = decodeOnly(ReadingSchema);
+
+    const receiving = { receive: TelemetrySchema, send: CommandSchema };
+    const sending = { receive: ReadingSchema, send: TelemetrySchema };
+
+    expect(() => new Channel(link, receiving)).not.toThrow();
+    // @ts-expect-error - Telemetry is only decoded
+    expect(() => new Channel(link, sending)).not.toThrow();
+  });
+});
@@ -0,0 +1,164 @@
+/**
+ * Tests for managing several devices over in-memory links
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  DeviceManager,
+  MockTransport,
+  SerializeError,
+  TransportError,
+  deserialize,
+  enumType,
+  f32,
+  newtypeVariant,
+  serialize,
+  struct,
+  u8,
+  unitVariant,
+  type InferType,
+} from "../../src/index.js";
+
+const CommandSchema = enumType("Command", {
+  SetTarget: newtypeVariant("SetTarget", f32()),
+  Stop: unitVariant("Stop"),
+});
+type Command = InferType<typeof CommandSchema>;
+
+const ReadingSchema = struct({ sensor: u8(), celsius: f32() });
+type Reading = InferType<typeof ReadingSchema>;
+
+const schemas = { receive: ReadingSchema, send: CommandSchema };
+
+async function collect<T>(iterable: AsyncIterable<T>, count: number): Promise<T[]> {
+  const items: T[] = [];
+  for await (const item of iterable) {
+    items.push(item);
+    if (items.length === count) {
+      break;
+    }
+  }
+  return items;
+}
+
+// Let the iterations subscribe, or the reading loops see their links end
+async function settle(): Promise<void> {
+  for (let i = 0; i < 5; i++) {
+    await new Promise((resolve) => setTimeout(resolve, 0));
+  }
+}
+
+describe("DeviceManager", () => {
+  it("should give each device its own typed channel", async () => {
+    const bench = new DeviceManager<typeof ReadingSchema, typeof CommandSchema>(schemas);
+    const [a, boardA] = MockTransport.pair();
+    const [b, boardB] = MockTransport.pair();
+    const first = bench.attach("A", a);
+    const second = bench.attach("B", b);
+
+    const readingsA = collect(first.channel.messages(), 1);
+    const readingsB = collect(second.channel.messages(), 1);
+    await settle();
+    const fromA: Reading = { sensor: 1, celsius: 20.5 };
+    const fromB: Reading = { sensor: 2, celsius: -4 };
+    await boardA.send(serialize(ReadingSchema, fromA));
+    await boardB.send(serialize(ReadingSchema, fromB));
+
+    expect(await readingsA).toEqual([fromA]);
+    expect(await readingsB).toEqual([fromB]);
+    expect(bench.size).toBe(2);
+    expect(bench.devices.map((device) => device.id)).toEqual(["A", "B"]);
+    expect(bench.get("B")?.transport).toBe(b);
+    await bench.close();
+  });
+
+  it("should send to one device or all of them", async () => {
+    const bench = new DeviceManager(schemas);
+    const links = [new MockTransport(), new MockTransport()];
+    links.forEach((link, i) => bench.attach(`board-${String(i)}`, link));
+
+    const stop: Command = { type: "Stop" };
+    await bench.send("board-1", { type: "SetTarget", value: 21.5 });
+    await bench.broadcast(stop);
+
+    expect(links[0]?.sent.map((frame) => deserialize(CommandSchema, frame).value)).toEqual([stop]);
+    expect(links[1]?.sent.map((frame) => deserialize(CommandSchema, frame).value)).toEqual([
+      { type: "SetTarget", value: 21.5 },
+      stop,
+    ]);
+    await expect(bench.send("board-2", stop)).rejects.toThrow(TransportError);
+    await bench.close();
+  });
+
+  it("should report the sends a broadcast could not make", async () => {
+    const bench = new DeviceManager(schemas);
+    const working = new MockTransport();
+    const broken = new MockTransport();
+    bench.attach("working", working);
+    bench.attach("broken", broken);
+    broken.close();
+    // Not detached yet: its reading loop has not seen the link end
+    expect(bench.has("broken")).toBe(true);
+
+    await expect(bench.broadcast({ type: "Stop" })).rejects.toThrow(
+      "Sending to 1 of 2 devices failed"
+    );
+    expect(working.sent).toHaveLength(1);
+    // @ts-expect-error - not a Command
+    await expect(bench.broadcast({ type: "Reboot" })).rejects.toThrow(SerializeError);
+    await bench.close();
+  });
+
+  it("should report devices attaching and detaching", async () => {
+    const attached: string[] = [];
+    const detached: [string, unknown][] = [];
+    const released: MockTransport[] = [];
+    const bench = new DeviceManager<
+      typeof ReadingSchema,
+      typeof CommandSchema,
+      string,
+      MockTransport
+    >(schemas, {
+      onAttach: ({ device }) => attached.push(device.id),
+      onDetach: ({ device, cause }) => detached.push([device.id, cause]),
+      disconnect: (transport) => {
+        released.push(transport);
+      },
+    });
+    const unplugged = new MockTransport();
+    const kept = new MockTransport();
+    const device = bench.attach("unplugged", unplugged);
+    bench.attach("kept", kept);
+    const readings = collect(device.channel.messages(), 1);
+    await settle();
+
+    unplugged.close();
+    await settle();
+
+    expect(await readings).toEqual([]);
+    expect(attached).toEqual(["unplugged", "kept"]);
+    expect(detached).toEqual([["unplugged", undefined]]);
+    expect(released).toEqual([unplugged]);
+    expect(bench.has("unplugged")).toBe(false);
+    await expect(device.channel.send({ type: "Stop" })).rejects.toThrow(TransportError);
+
+    expect(await bench.detach("kept")).toBe(true);
+    expect(await bench.detach("kept")).toBe(false);
+    expect(released).toEqual([unplugged, kept]);
+    expect(bench.size).toBe(0);
+  });
+
+  it("should take a device back once it detached, but nothing once closed", async () => {
+    const bench = new DeviceManager(schemas);
+    const link = new MockTransport();
+    bench.attach("A", link);
+    expect(() => bench.attach("A", new MockTransport())).toThrow(RangeError);
+
+    await bench.detach("A");
+    bench.attach("A", new MockTransport());
+    await bench.close();
+
+    expect(bench.size).toBe(0);
+    expect(() => bench.attach("B", new MockTransport())).toThrow(TransportError);
+  });
+});
@@ -0,0 +1,78 @@
+/**
+ * Tests for echoing latency probes
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  LatencyEchoSchema,
+  LatencyProbeSchema,
+  MockTransport,
+  decodeOnly,
+  deserialize,
+  echoLatencyProbes,
+  f32,
+  serialize,
+  struct,
+  u8,
+} from "../../src/index.js";
+
+const ReadingSchema = decodeOnly(struct({ sensor: u8(), celsius: f32() }));
+
+/** A clock advancing half a millisecond each time it is read */
+function ticking(): () => number {
+  let now = 0;
+  return () => (now += 0.5);
+}
+
+function probe(seq: number, type: string, payload: number[]): Uint8Array {
+  return serialize(LatencyProbeSchema, {
+    seq,
+    sentAt: 1_000_000n + BigInt(seq),
+    type,
+    payload: new Uint8Array(payload),
+  });
+}
+
+async function echoes(frames: Uint8Array[]): Promise<{ echoed: number; sent: Uint8Array[] }> {
+  const [bench, app] = MockTransport.pair();
+  const echoing = echoLatencyProbes(app, { Reading: ReadingSchema }, { now: ticking() });
+  // Let the echo subscribe
+  await Promise.resolve();
+  for (const frame of frames) {
+    await bench.send(frame);
+  }
+  bench.close();
+  return { echoed: await echoing, sent: app.sent };
+}
+
+describe("echoLatencyProbes", () => {
+  it("echoes each probe re-encoded, with the time each step took", async () => {
+    const reading = [0x02, 0x00, 0x00, 0xb4, 0x41];
+    const { echoed, sent } = await echoes([probe(7, "Reading", reading)]);
+    expect(echoed).toBe(1);
+    expect(deserialize(LatencyEchoSchema, sent[0]!).value).toEqual({
+      seq: 7,
+      sentAt: 1_000_007n,
+      decodeMicros: 500,
+      encodeMicros: 500,
+      handledMicros: 2500,
+      payload: new Uint8Array(reading),
+      error: null,
+    });
+  });
+
+  it("echoes the error of a probe it cannot decode", async () => {
+    const { sent } = await echoes([probe(1, "Reading", [0x02]), probe(2, "Command", [0x00])]);
+    const [truncated, unknown] = sent.map((frame) => deserialize(LatencyEchoSchema, frame).value);
+    expect(truncated?.error).toMatch(/Not enough bytes for f32/);
+    expect(truncated?.payload).toEqual(new Uint8Array(0));
+    expect(unknown?.error).toBe("No schema for Command");
+  });
+
+  it("ignores frames that are not probes", async () => {
+    const notProbe = new Uint8Array([0xff]);
+    const { echoed, sent } = await echoes([notProbe, probe(3, "Reading", [1, 0, 0, 0, 0])]);
+    expect(echoed).toBe(1);
+    expect(sent).toHaveLength(1);
+  });
+});
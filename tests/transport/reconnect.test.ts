@@ -0,0 +1,163 @@
+/**
+ * Tests for reconnecting transports, over in-memory links
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  MockTransport,
+  ReconnectingTransport,
+  TransportError,
+  reconnectDelay,
+  type DisconnectEvent,
+} from "../../src/index.js";
+
+async function collect<T>(iterable: AsyncIterable<T>, count: number): Promise<T[]> {
+  const items: T[] = [];
+  for await (const item of iterable) {
+    items.push(item);
+    if (items.length === count) {
+      break;
+    }
+  }
+  return items;
+}
+
+// Let the reconnect loop run through its zero-length delays
+async function settle(): Promise<void> {
+  for (let i = 0; i < 5; i++) {
+    await new Promise((resolve) => setTimeout(resolve, 0));
+  }
+}
+
+describe("reconnectDelay()", () => {
+  it("should grow exponentially up to the maximum, less the jitter", () => {
+    const policy = { mode: "exponential", initialDelayMs: 100, maxDelayMs: 1000 } as const;
+    const delays = [1, 2, 3, 4, 5].map((failures) => reconnectDelay(policy, failures, () => 0));
+    expect(delays).toEqual([100, 200, 400, 800, 1000]);
+    // The default jitter takes up to a fifth off
+    expect(reconnectDelay(policy, 1, () => 0.5)).toBe(90);
+    expect(reconnectDelay({ ...policy, factor: 3, jitter: 0 }, 3, () => 0.5)).toBe(900);
+  });
+
+  it("should give up after maxAttempts, or right away with never", () => {
+    const policy = { mode: "fixed", delayMs: 250, maxAttempts: 2 } as const;
+    const delays = [1, 2, 3].map((failures) => reconnectDelay(policy, failures));
+    expect(delays).toEqual([250, 250, undefined]);
+    expect(reconnectDelay({ mode: "never" }, 1)).toBeUndefined();
+  });
+
+  it("should reject policies that cannot be followed", () => {
+    const connect = (): MockTransport => new MockTransport();
+    for (const policy of [
+      { mode: "fixed", delayMs: -1 },
+      { mode: "fixed", delayMs: 10, maxAttempts: -1 },
+      { mode: "exponential", initialDelayMs: 100, maxDelayMs: 50 },
+      { mode: "exponential", initialDelayMs: 100, maxDelayMs: 500, factor: 0.5 },
+      { mode: "exponential", initialDelayMs: 100, maxDelayMs: 500, jitter: 2 },
+    ] as const) {
+      expect(() => new ReconnectingTransport(connect, { policy })).toThrow(RangeError);
+    }
+  });
+});
+
+describe("ReconnectingTransport", () => {
+  it("should keep receiving across links and resync each one", async () => {
+    const links: MockTransport[] = [];
+    const devices: MockTransport[] = [];
+    const resumed: boolean[] = [];
+    const link = new ReconnectingTransport(
+      () => {
+        const [ours, device] = MockTransport.pair();
+        links.push(ours);
+        devices.push(device);
+        return ours;
+      },
+      {
+        policy: { mode: "fixed", delayMs: 0 },
+        resync: (event) => new Uint8Array([event.resumed ? 2 : 1]),
+        onConnect: (event) => resumed.push(event.resumed),
+      }
+    );
+    const frames = collect(link.messages(), 2);
+    await settle();
+
+    expect(link.state).toBe("connected");
+    expect(link.transport).toBe(links[0]);
+    await devices[0]?.send(new Uint8Array([10]));
+    links[0]?.close();
+    await settle();
+    await devices[1]?.send(new Uint8Array([11]));
+
+    expect(await frames).toEqual([new Uint8Array([10]), new Uint8Array([11])]);
+    expect(resumed).toEqual([false, true]);
+    expect(links.map((each) => each.sent)).toEqual([[new Uint8Array([1])], [new Uint8Array([2])]]);
+    await link.close();
+  });
+
+  it("should back off while links drop before receiving, then give up", async () => {
+    const disconnects: DisconnectEvent[] = [];
+    const released: MockTransport[] = [];
+    const link = new ReconnectingTransport(
+      () => {
+        const dropped = new MockTransport();
+        dropped.close();
+        return dropped;
+      },
+      {
+        policy: { mode: "exponential", initialDelayMs: 1, maxDelayMs: 4, maxAttempts: 3 },
+        random: () => 0,
+        onDisconnect: (event) => disconnects.push(event),
+        disconnect: (transport) => {
+          released.push(transport);
+        },
+      }
+    );
+
+    await expect(collect(link.messages(), 1)).rejects.toThrow(TransportError);
+    expect(disconnects.map((event) => [event.failures, event.retryInMs])).toEqual([
+      [1, 1],
+      [2, 2],
+      [3, 4],
+      [4, undefined],
+    ]);
+    expect(released).toHaveLength(4);
+    expect(link.state).toBe("failed");
+    // Later iterations fail too
+    await expect(collect(link.messages(), 1)).rejects.toThrow(TransportError);
+  });
+
+  it("should report connections that fail with their cause", async () => {
+    const refused = new Error("ECONNREFUSED");
+    const disconnects: DisconnectEvent[] = [];
+    const link = new ReconnectingTransport<MockTransport>(() => Promise.reject(refused), {
+      policy: { mode: "never" },
+      onDisconnect: (event) => disconnects.push(event),
+    });
+
+    await expect(collect(link.messages(), 1)).rejects.toThrow(TransportError);
+    expect(disconnects).toEqual([{ cause: refused, failures: 1, retryInMs: undefined }]);
+  });
+
+  it("should refuse to send while not connected and stop on close", async () => {
+    const mock = new MockTransport();
+    const released: MockTransport[] = [];
+    const link = new ReconnectingTransport(() => mock, {
+      disconnect: (transport) => {
+        released.push(transport);
+      },
+    });
+
+    await expect(link.send(new Uint8Array([1]))).rejects.toThrow(TransportError);
+    await settle();
+    await link.send(new Uint8Array([2]));
+    expect(mock.sent).toEqual([new Uint8Array([2])]);
+
+    const frames = collect(link.messages(), 10);
+    await link.close();
+
+    expect(await frames).toEqual([]);
+    expect(released).toEqual([mock]);
+    expect(link.state).toBe("closed");
+    await expect(link.send(new Uint8Array([3]))).rejects.toThrow(TransportError);
+  });
+});
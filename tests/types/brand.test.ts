@@ -0,0 +1,54 @@
+/**
+ * Tests for branded schemas, such as newtype ids
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  branded,
+  deserialize,
+  newtypeStruct,
+  serialize,
+  struct,
+  u64,
+  type InferType,
+} from "../../src/index.js";
+
+const PlayerIdSchema = branded(newtypeStruct("PlayerId", u64()), "PlayerId");
+const ItemIdSchema = branded(newtypeStruct("ItemId", u64()), "ItemId");
+type PlayerId = InferType<typeof PlayerIdSchema>;
+type ItemId = InferType<typeof ItemIdSchema>;
+
+const PickupSchema = struct({ player: PlayerIdSchema, item: ItemIdSchema });
+
+describe("branded()", () => {
+  it("should encode as the schema it wraps", () => {
+    expect(PlayerIdSchema.kind).toBe("newtype_struct");
+    expect(PlayerIdSchema.brand).toBe("PlayerId");
+    expect(serialize(PlayerIdSchema, 300n as PlayerId)).toEqual(
+      serialize(newtypeStruct("PlayerId", u64()), 300n)
+    );
+  });
+
+  it("should decode to the plain value", () => {
+    const data = serialize(u64(), 7n);
+    const id: PlayerId = deserialize(PlayerIdSchema, data).value;
+    expect(id).toBe(7n);
+  });
+
+  it("should keep ids of different types apart", () => {
+    const player = 1n as PlayerId;
+    const item = 2n as ItemId;
+    const pickup = { player, item };
+    expect(deserialize(PickupSchema, serialize(PickupSchema, pickup)).value).toEqual(pickup);
+
+    // @ts-expect-error - an ItemId is not a PlayerId
+    const _swapped: InferType<typeof PickupSchema> = { player: item, item: player };
+    // @ts-expect-error - nor is a plain bigint
+    const _plain: PlayerId = 1n;
+    void _swapped;
+    void _plain;
+    // Still a bigint
+    const _value: bigint = player;
+    void _value;
+  });
+});
@@ -0,0 +1,107 @@
+/**
+ * Tests for the error-code catalogue
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  ErrorCode,
+  errorCode,
+  deserialize,
+  tryDeserialize,
+  trySerialize,
+  tryDecodeVarintU32,
+  tryDecodeChar,
+  tryDecodeColumns,
+  transform,
+  struct,
+  seq,
+  string,
+  u8,
+  u32,
+  f64,
+  enumType,
+  unitVariant,
+  DeserializeError,
+} from "../../src/index.js";
+
+describe("ErrorCode", () => {
+  it("should use unique upper-case codes", () => {
+    const codes = Object.values(ErrorCode);
+    expect(new Set(codes).size).toBe(codes.length);
+    for (const code of codes) {
+      expect(code).toMatch(/^[A-Z][A-Z0-9_]*$/);
+    }
+  });
+});
+
+describe("errorCode()", () => {
+  it("should read the code of a codec error", () => {
+    expect(errorCode(new DeserializeError("bad", ErrorCode.BadBool))).toBe("BAD_BOOL");
+  });
+
+  it("should read the code of a thrown error", () => {
+    let thrown: unknown;
+    try {
+      deserialize(u32(), new Uint8Array([0x80]));
+    } catch (e) {
+      thrown = e;
+    }
+    expect(errorCode(thrown)).toBe(ErrorCode.UnexpectedEnd);
+  });
+
+  it("should ignore values that are not library errors", () => {
+    expect(errorCode(new Error("plain"))).toBeUndefined();
+    expect(errorCode({ code: "BAD_BOOL" })).toBeUndefined();
+    expect(errorCode(Object.assign(new Error("fs"), { code: "ENOENT" }))).toBeUndefined();
+    expect(errorCode("BAD_BOOL")).toBeUndefined();
+  });
+});
+
+describe("Error codes", () => {
+  it("should tell truncated varints from overlong ones", () => {
+    const truncated = tryDecodeVarintU32(new Uint8Array([0x80, 0x80]));
+    const overlong = tryDecodeVarintU32(new Uint8Array([0x80, 0x80, 0x80, 0x80, 0x80, 0x01]));
+    expect(!truncated.ok && truncated.error.code).toBe(ErrorCode.UnexpectedEnd);
+    expect(!overlong.ok && overlong.error.code).toBe(ErrorCode.BadVarint);
+  });
+
+  it("should report invalid UTF-8 in a char as BAD_CHAR, as postcard does", () => {
+    const result = tryDecodeChar(new Uint8Array([0x01, 0xff]));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.BadChar);
+  });
+
+  it("should keep the innermost code through nested schemas", () => {
+    const Schema = struct({ names: seq(string()) });
+    const result = tryDeserialize(Schema, new Uint8Array([0x01, 0x02, 0xc3, 0x28]));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.BadUtf8);
+  });
+
+  it("should report out-of-range variant indices as INVALID_VALUE", () => {
+    const Schema = enumType("Mode", { Off: unitVariant("Off"), On: unitVariant("On") });
+    const result = tryDeserialize(Schema, new Uint8Array([0x02]));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.InvalidValue);
+  });
+
+  it("should report failed transforms as INVALID_VALUE", () => {
+    const Even = transform(u8(), {
+      decode: (value: number) => {
+        if (value % 2 !== 0) throw new RangeError("odd");
+        return value;
+      },
+      encode: (value: number) => value,
+    });
+    const result = tryDeserialize(Even, new Uint8Array([0x03]));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.InvalidValue);
+  });
+
+  it("should report values that cannot be encoded as SERIALIZE_FAILED", () => {
+    const result = trySerialize(u8(), 256);
+    expect(!result.ok && result.error.code).toBe(ErrorCode.SerializeFailed);
+  });
+
+  it("should carry codes on columnar errors", () => {
+    const Points = seq(struct({ x: f64() }));
+    const result = tryDecodeColumns(Points, new Uint8Array([0x02, 0x00, 0x00]));
+    expect(!result.ok && result.error.code).toBe(ErrorCode.UnexpectedEnd);
+  });
+});
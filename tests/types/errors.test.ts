@@ -0,0 +1,98 @@
+/**
+ * Tests for the tree of runtime error classes
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  PostcardError,
+  PostcardDecodeError,
+  ValidationError,
+  TransportError,
+  DeserializeError,
+  SerializeError,
+  FramingError,
+  CrcMismatchError,
+  ErrorCode,
+  errorCode,
+  tryCobsDecode,
+  tryDeserialize,
+  trySerialize,
+  serializeCrc,
+  tryDeserializeCrc,
+  CRC_16_IBM_SDLC,
+  Outbox,
+  struct,
+  u8,
+} from "../../src/index.js";
+
+const ReadingSchema = struct({ sensor: u8() });
+
+describe("error classes", () => {
+  it("should make framing errors deserialize errors", () => {
+    const result = tryCobsDecode(new Uint8Array([0x05, 0x01, 0x02, 0x00]));
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(FramingError);
+    expect(error).toBeInstanceOf(DeserializeError);
+    expect(error).toBeInstanceOf(PostcardDecodeError);
+    expect(error).toBeInstanceOf(PostcardError);
+    expect(errorCode(error)).toBe(ErrorCode.BadEncoding);
+  });
+
+  it("should report CRC mismatches with both checksums", () => {
+    const message = serializeCrc(ReadingSchema, { sensor: 7 }, CRC_16_IBM_SDLC);
+    message[0] = 8;
+    const result = tryDeserializeCrc(ReadingSchema, message, CRC_16_IBM_SDLC);
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(CrcMismatchError);
+    expect(error).toBeInstanceOf(DeserializeError);
+    expect(error).not.toBeInstanceOf(FramingError);
+    expect((error as CrcMismatchError).received).toEqual(message.subarray(1));
+    expect((error as CrcMismatchError).expected).not.toEqual(message.subarray(1));
+    expect(errorCode(error)).toBe(ErrorCode.BadCrc);
+  });
+
+  it("should keep plain decode errors out of the framing branches", () => {
+    const result = tryDeserialize(ReadingSchema, new Uint8Array());
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(PostcardDecodeError);
+    expect(error).not.toBeInstanceOf(FramingError);
+    expect(error).not.toBeInstanceOf(CrcMismatchError);
+    expect(error).not.toBeInstanceOf(ValidationError);
+  });
+
+  it("should make serialize errors validation errors", () => {
+    const result = trySerialize(ReadingSchema, { sensor: 256 });
+    const error = !result.ok && result.error;
+    expect(error).toBeInstanceOf(SerializeError);
+    expect(error).toBeInstanceOf(ValidationError);
+    expect(error).not.toBeInstanceOf(PostcardDecodeError);
+    expect(errorCode(error)).toBe(ErrorCode.SerializeFailed);
+  });
+
+  it("should wrap failed sends in a TransportError", () => {
+    const cause = new Error("port closed");
+    const outbox = new Outbox(ReadingSchema, {
+      send: () => {
+        throw cause;
+      },
+    });
+
+    let thrown: unknown;
+    try {
+      outbox.send({ sensor: 1 });
+    } catch (error) {
+      thrown = error;
+    }
+    expect(thrown).toBeInstanceOf(TransportError);
+    expect(thrown).toBeInstanceOf(PostcardError);
+    expect((thrown as TransportError).cause).toBe(cause);
+    expect((thrown as TransportError).message).toBe("Sending failed: port closed");
+  });
+
+  it("should name each class", () => {
+    expect(new FramingError("x").name).toBe("FramingError");
+    expect(new TransportError("x").name).toBe("TransportError");
+    expect(new ValidationError("x").code).toBe(ErrorCode.SerializeFailed);
+    expect(new PostcardDecodeError("x").code).toBe(ErrorCode.InvalidValue);
+  });
+});
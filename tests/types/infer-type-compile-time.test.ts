@@ -29,6 +29,8 @@ import {
   newtypeVariant,
   structVariant,
   type InferType,
+  type Schema,
+  type SchemaOf,
 } from '../../src/types/schema.js';
 
 describe('InferType compile-time type inference', () => {
@@ -230,6 +232,31 @@ describe('InferType compile-time type inference', () => {
     void test.settings.get("setting1");
   });
 
+  it('should infer correct type for generic schema factories', () => {
+    // As generated for `struct Envelope<T> { seq: u32, payload: T }`
+    const coordinates = struct({ x: u32(), y: u32() });
+    const EnvelopeSchema = <T extends Schema>(T: T) =>
+      struct({
+        seq: u32(),
+        payload: T,
+      });
+    type Envelope<T> = InferType<ReturnType<typeof EnvelopeSchema<SchemaOf<T>>>>;
+
+    const fromSchema: InferType<ReturnType<typeof EnvelopeSchema<typeof coordinates>>> = {
+      seq: 1,
+      payload: { x: 1, y: 2 },
+    };
+    const named: Envelope<{ x: number; y: number }> = fromSchema;
+    const nested: Envelope<Envelope<string[]>> = { seq: 1, payload: { seq: 2, payload: ['a'] } };
+
+    // @ts-expect-error - payload must be a string
+    const wrongPayload: Envelope<string> = { seq: 1, payload: 2 };
+
+    void named;
+    void nested;
+    void wrongPayload;
+  });
+
   it('should NOT infer never for any schema type', () => {
     // This is a critical test - InferType should NEVER return 'never' for valid schemas
     const schemas = {
@@ -0,0 +1,65 @@
+/**
+ * Tests for runtime descriptions of generated types
+ */
+
+import { describe, it, expect } from "vitest";
+import {
+  deserialize,
+  enumType,
+  f32,
+  struct,
+  typeWithHash,
+  u8,
+  unitVariant,
+  type ModuleSchema,
+  type Schema,
+} from "../../src/index.js";
+
+// As `Generator::introspection()` writes it
+const ReadingSchema = struct({ sensor_id: u8(), celsius: f32() });
+const ModeSchema = enumType("Mode", { Idle: unitVariant("Idle"), Run: unitVariant("Run") });
+const EnvelopeSchema = <T extends Schema>(T: T) => struct({ seq: u8(), body: T });
+
+const __schema = {
+  Reading: {
+    kind: "struct",
+    schema: ReadingSchema,
+    fields: ["sensor_id", "celsius"],
+    hash: 0x1f2e3d4c5b6a7988n,
+  },
+  Mode: { kind: "enum", schema: ModeSchema, variants: { Idle: 0, Run: 1 }, hash: 0x42n },
+  Envelope: { kind: "struct", schema: EnvelopeSchema, fields: ["seq", "body"], params: ["T"] },
+} as const satisfies ModuleSchema;
+
+describe("ModuleSchema", () => {
+  it("describes the generated types for tools", () => {
+    const module: ModuleSchema = __schema;
+    const described = Object.entries(module).map(([name, type]) => [
+      name,
+      type.kind,
+      type.fields ?? Object.keys(type.variants ?? {}),
+    ]);
+    expect(described).toEqual([
+      ["Reading", "struct", ["sensor_id", "celsius"]],
+      ["Mode", "enum", ["Idle", "Run"]],
+      ["Envelope", "struct", ["seq", "body"]],
+    ]);
+  });
+
+  it("keeps each schema usable for decoding", () => {
+    const reading = deserialize(__schema.Reading.schema, new Uint8Array([2, 0, 0, 0xb4, 0x41]));
+    expect(reading.value).toEqual({ sensor_id: 2, celsius: 22.5 });
+  });
+});
+
+describe("typeWithHash()", () => {
+  it("finds the type a hash stands for", () => {
+    const found = typeWithHash(__schema, 0x42n);
+    expect(found?.[0]).toBe("Mode");
+    expect(found?.[1].variants).toEqual({ Idle: 0, Run: 1 });
+  });
+
+  it("returns undefined for an unknown hash, never matching generic types", () => {
+    expect(typeWithHash(__schema, 0x7n)).toBeUndefined();
+  });
+});
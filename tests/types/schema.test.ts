@@ -30,6 +30,8 @@ import {
   newtypeVariant,
   tupleVariant,
   structVariant,
+  range,
+  rangeInclusive,
   type InferType,
   type Schema,
 } from "../../src/types/schema.js";
@@ -115,6 +117,27 @@ describe("Schema builders", () => {
     });
   });
 
+  describe("Ranges", () => {
+    it("should create range schemas as start/end structs", () => {
+      const schema = range(u16());
+      expect(schema.kind).toBe("struct");
+      expect(Object.keys(schema.fields)).toEqual(["start", "end"]);
+      expect(schema.fields.start.kind).toBe("u16");
+      expect(schema.fields.end.kind).toBe("u16");
+
+      expect(rangeInclusive(f32())).toEqual(struct({ start: f32(), end: f32() }));
+    });
+
+    it("should infer start/end objects", () => {
+      const windowSchema = range(i64());
+      type Window = InferType<typeof windowSchema>;
+
+      // Compile-time type check (would fail if inference is wrong)
+      const _typeCheck: Window = { start: -5n, end: 10n };
+      expect(windowSchema.fields.start.kind).toBe("i64");
+    });
+  });
+
   describe("Options", () => {
     it("should create option schema", () => {
       const schema = option(u32());
@@ -0,0 +1,226 @@
+/**
+ * Tests for decoding in several workers at once
+ *
+ * Each worker is stood in for by a MessageChannel port and a fresh import of
+ * the runtime and fixture schemas, so it holds module instances of its own as
+ * a real worker does. All of them decode the fixture corpus concurrently from
+ * one SharedArrayBuffer.
+ *
+ * Real worker_threads workers run the built runtime (POSTCARD_RUNTIME, a
+ * directory holding its index.js, or ./dist) on the schemas of the fixture
+ * IR; their results must match decoding on this thread:
+ *
+ *   npm run build && npm test
+ */
+
+import { describe, it, expect, afterEach, vi } from "vitest";
+import { existsSync, readFileSync } from "fs";
+import { join, resolve } from "path";
+import { pathToFileURL } from "url";
+import { MessageChannel as NodeMessageChannel, Worker } from "worker_threads";
+import {
+  createWorkerDecoder,
+  exposeDecoders,
+  schemasFromIr,
+  serialize,
+  string,
+  struct,
+  tryDeserialize,
+  u32,
+  type DecoderRegistry,
+  type MessageEndpoint,
+  type WorkerDecoder,
+} from "../../src/index.js";
+import { FIXTURE_SCHEMAS } from "../fixtures/schemas.js";
+
+type Runtime = typeof import("../../src/index.js");
+
+const FIXTURES_DIR = join(process.cwd(), "test-fixtures", "fixtures");
+const IR_PATH = join(FIXTURES_DIR, "ir.json");
+const RUNTIME_ENTRY = resolve(process.env["POSTCARD_RUNTIME"] ?? "dist", "index.js");
+const WORKERS = 4;
+
+interface ManifestEntry {
+  readonly file: string;
+  readonly type: string;
+}
+
+const manifestPath = join(FIXTURES_DIR, "manifest.json");
+const { fixtures } = existsSync(manifestPath)
+  ? (JSON.parse(readFileSync(manifestPath, "utf8")) as { fixtures: ManifestEntry[] })
+  : { fixtures: [] };
+
+const channels: { port1: { close(): void }; port2: { close(): void } }[] = [];
+const threads: Worker[] = [];
+
+afterEach(async () => {
+  for (const channel of channels.splice(0)) {
+    channel.port1.close();
+    channel.port2.close();
+  }
+  await Promise.all(threads.splice(0).map((thread) => thread.terminate()));
+});
+
+/** A worker with its own runtime and schemas, exposing the fixture schemas */
+async function spawnWorker(): Promise<MessagePort> {
+  vi.resetModules();
+  const runtime = (await import("../../src/index.js")) as Runtime;
+  const { FIXTURE_SCHEMAS: schemas } = await import("../fixtures/schemas.js");
+  const channel = new MessageChannel();
+  channels.push(channel);
+  runtime.exposeDecoders(channel.port1, schemas);
+  return channel.port2;
+}
+
+/**
+ * A worker_threads worker exposing the fixture IR's schemas, and a promise
+ * rejected if it fails
+ */
+function startThread(): { port: MessageEndpoint; failed: Promise<never> } {
+  const channel = new NodeMessageChannel();
+  channels.push(channel);
+  const thread = new Worker(new URL("./ir-worker.mjs", import.meta.url), {
+    workerData: {
+      runtime: pathToFileURL(RUNTIME_ENTRY).href,
+      ir: IR_PATH,
+      port: channel.port1,
+    },
+    transferList: [channel.port1],
+  });
+  threads.push(thread);
+  return {
+    // Node's ports are EventTargets too
+    port: channel.port2 as unknown as MessageEndpoint,
+    failed: new Promise((_, reject) => thread.once("error", reject)),
+  };
+}
+
+/** `frames` copied back to back into one SharedArrayBuffer */
+function shared(frames: readonly Uint8Array[]): Uint8Array[] {
+  const memory = new SharedArrayBuffer(frames.reduce((total, frame) => total + frame.length, 0));
+  let offset = 0;
+  return frames.map((frame) => {
+    const view = new Uint8Array(memory, offset, frame.length);
+    view.set(frame);
+    offset += frame.length;
+    return view;
+  });
+}
+
+const LabelSchema = struct({ id: u32(), label: string() });
+
+function pair(schemas: DecoderRegistry): [MessagePort, MessagePort] {
+  const channel = new MessageChannel();
+  channels.push(channel);
+  exposeDecoders(channel.port1, schemas);
+  return [channel.port1, channel.port2];
+}
+
+describe("decoding in several workers", () => {
+  const corpus = fixtures.flatMap((entry) => {
+    const schema = FIXTURE_SCHEMAS[entry.type];
+    const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+    const expected = schema === undefined ? undefined : tryDeserialize(schema, data);
+    return expected?.ok === true ? [{ ...entry, data, expected: expected.value }] : [];
+  });
+
+  it.skipIf(corpus.length === 0)(
+    "decodes the corpus concurrently from shared memory",
+    async () => {
+      const frames = shared(corpus.map((entry) => entry.data));
+      const decoders: WorkerDecoder<DecoderRegistry>[] = [];
+      for (let i = 0; i < WORKERS; i++) {
+        decoders.push(createWorkerDecoder<DecoderRegistry>(await spawnWorker()));
+      }
+
+      // Every worker decodes every fixture, all requests in flight at once
+      const results = await Promise.all(
+        decoders.flatMap((decoder) =>
+          corpus.map((entry, i) => decoder.decode(entry.type, frames[i]!))
+        )
+      );
+      results.forEach((result, i) => {
+        const entry = corpus[i % corpus.length]!;
+        expect(result.bytesRead, entry.file).toBe(entry.expected.bytesRead);
+        expect(result.value, entry.file).toEqual(entry.expected.value);
+      });
+      // The shared frames were copied, not detached
+      frames.forEach((frame, i) => expect(frame).toEqual(corpus[i]!.data));
+    }
+  );
+
+  it.skipIf(!existsSync(RUNTIME_ENTRY) || !existsSync(IR_PATH))(
+    "decodes the corpus in worker threads as on this thread",
+    async () => {
+      const schemas = schemasFromIr(JSON.parse(readFileSync(IR_PATH, "utf8")));
+      const expected = fixtures.flatMap((entry) => {
+        const schema = schemas.get(entry.type);
+        const data = new Uint8Array(readFileSync(join(FIXTURES_DIR, entry.file)));
+        const result = schema === undefined ? undefined : tryDeserialize(schema, data);
+        return result?.ok === true ? [{ ...entry, data, result: result.value }] : [];
+      });
+      expect(expected.map((entry) => entry.type)).toContain("GameState");
+
+      const started = Array.from({ length: WORKERS }, () => startThread());
+      const decoders = started.map(({ port }) => createWorkerDecoder<DecoderRegistry>(port));
+      // Every thread decodes every fixture, all requests in flight at once;
+      // each gets its own copy of the bytes, which are transferred
+      const results = await Promise.race([
+        Promise.all(
+          decoders.flatMap((decoder) =>
+            expected.map((entry) => decoder.decode(entry.type, entry.data.slice()))
+          )
+        ),
+        ...started.map(({ failed }) => failed),
+      ]);
+      results.forEach((result, i) => {
+        const entry = expected[i % expected.length]!;
+        expect(result.bytesRead, entry.file).toBe(entry.result.bytesRead);
+        expect(result.value, entry.file).toEqual(entry.result.value);
+      });
+    },
+    20_000
+  );
+
+  it("settles each facade's own requests when several share a worker", async () => {
+    const [, port] = pair({ label: LabelSchema });
+    const first = createWorkerDecoder<{ label: typeof LabelSchema }>(port);
+    const second = createWorkerDecoder<{ label: typeof LabelSchema }>(port);
+
+    const results = await Promise.all(
+      [1, 2, 3, 4].map((id) =>
+        (id % 2 === 0 ? second : first).decode(
+          "label",
+          serialize(LabelSchema, { id, label: `sensor ${String(id)}` })
+        )
+      )
+    );
+    expect(results.map((result) => result.value.id)).toEqual([1, 2, 3, 4]);
+  });
+
+  it("answers once for schemas exposed in several calls", async () => {
+    const CountSchema = struct({ count: u32() });
+    const [scope, port] = pair({ label: LabelSchema });
+    exposeDecoders(scope, { count: CountSchema, label: LabelSchema });
+    const decoder = createWorkerDecoder<{
+      label: typeof LabelSchema;
+      count: typeof CountSchema;
+    }>(port);
+
+    const [label, count] = await Promise.all([
+      decoder.decode("label", serialize(LabelSchema, { id: 7, label: "pump" })),
+      decoder.decode("count", serialize(CountSchema, { count: 3 })),
+    ]);
+    expect(label.value).toEqual({ id: 7, label: "pump" });
+    expect(count.value).toEqual({ count: 3 });
+    expect(() => exposeDecoders(scope, { label: CountSchema })).toThrow(TypeError);
+  });
+
+  it("only answers for schemas it was given", async () => {
+    const [, port] = pair({ label: LabelSchema });
+    const decoder = createWorkerDecoder<{ label: typeof LabelSchema }>(port);
+    await expect(decoder.decode("toString" as "label", new Uint8Array(0))).rejects.toThrow(
+      /No schema registered/
+    );
+  });
+});
@@ -0,0 +1,136 @@
+/**
+ * Tests for worker-offloaded decoding
+ *
+ * A MessageChannel stands in for the Worker: one port plays the worker's
+ * global scope, the other the Worker object on the UI thread.
+ */
+
+import { describe, it, expect, afterEach } from "vitest";
+import {
+  serialize,
+  exposeDecoders,
+  createWorkerDecoder,
+  WorkerDecodeError,
+  seq,
+  struct,
+  map,
+  string,
+  u64,
+  f32,
+  f64,
+  type InferType,
+  type WorkerDecoder,
+} from "../../src/index.js";
+
+const PointSchema = struct({ x: f64(), y: f32() });
+const StatusSchema = struct({ name: string(), uptime: u64(), counters: map(string(), f64()) });
+
+const schemas = {
+  status: StatusSchema,
+  trace: seq(PointSchema),
+};
+
+type Status = InferType<typeof StatusSchema>;
+
+const channels: MessageChannel[] = [];
+
+function setup(): WorkerDecoder<typeof schemas> {
+  const channel = new MessageChannel();
+  channels.push(channel);
+  exposeDecoders(channel.port1, schemas);
+  return createWorkerDecoder<typeof schemas>(channel.port2);
+}
+
+afterEach(() => {
+  for (const channel of channels.splice(0)) {
+    channel.port1.close();
+    channel.port2.close();
+  }
+});
+
+describe("createWorkerDecoder()", () => {
+  const status: Status = {
+    name: "pump",
+    uptime: 2n ** 40n,
+    counters: new Map([["starts", 3]]),
+  };
+
+  it("should decode values in the worker", async () => {
+    const decoder = setup();
+    const data = serialize(StatusSchema, status);
+    const length = data.length;
+
+    const result = await decoder.decode("status", data);
+    expect(result.value).toEqual(status);
+    expect(result.bytesRead).toBe(length);
+
+    const _verify: bigint = result.value.uptime;
+    void _verify;
+  });
+
+  it("should transfer the input buffer", async () => {
+    const decoder = setup();
+    const data = serialize(StatusSchema, status);
+    const pending = decoder.decode("status", data);
+    expect(data.byteLength).toBe(0);
+    await pending;
+  });
+
+  it("should copy views into a larger buffer instead of detaching it", async () => {
+    const decoder = setup();
+    const encoded = serialize(StatusSchema, status);
+    const backing = new Uint8Array(encoded.length + 4);
+    backing.set(encoded, 2);
+
+    const result = await decoder.decode("status", backing.subarray(2, 2 + encoded.length));
+    expect(result.value).toEqual(status);
+    expect(backing.byteLength).toBe(encoded.length + 4);
+  });
+
+  it("should return columns as typed arrays", async () => {
+    const decoder = setup();
+    const points = [
+      { x: 1.5, y: 2 },
+      { x: -3, y: 0.5 },
+    ];
+
+    const { value } = await decoder.decodeColumns("trace", serialize(seq(PointSchema), points));
+    expect(value.x).toEqual(new Float64Array([1.5, -3]));
+    expect(value.y).toEqual(new Float32Array([2, 0.5]));
+
+    const _verify: Float32Array = value.y;
+    void _verify;
+  });
+
+  it("should match concurrent requests to their responses", async () => {
+    const decoder = setup();
+    const results = await Promise.all(
+      [1, 2, 3].map((n) =>
+        decoder.decode("status", serialize(StatusSchema, { ...status, uptime: BigInt(n) }))
+      )
+    );
+    expect(results.map((r) => r.value.uptime)).toEqual([1n, 2n, 3n]);
+  });
+
+  describe("errors", () => {
+    it("should reject with WorkerDecodeError on invalid data", async () => {
+      const decoder = setup();
+      await expect(decoder.decode("status", new Uint8Array([0x05]))).rejects.toThrow(
+        WorkerDecodeError
+      );
+    });
+
+    it("should reject unknown schema names", async () => {
+      const decoder = setup();
+      await expect(decoder.decode("missing" as "status", new Uint8Array(0))).rejects.toThrow(
+        /No schema registered/
+      );
+    });
+
+    it("should only offer columnar decoding for numeric struct sequences", () => {
+      const decoder = setup();
+      // @ts-expect-error - status is not a sequence of numeric structs
+      void decoder.decodeColumns("status", new Uint8Array(0)).catch(() => undefined);
+    });
+  });
+});
@@ -0,0 +1,71 @@
+/**
+ * Tests for the shared-memory frame ring
+ */
+
+import { describe, it, expect } from "vitest";
+import { FrameRing, struct, u8, u32, i16, f32, f64, i64, string } from "../../src/index.js";
+
+const SampleSchema = struct({ t: f64(), level: f32(), raw: i16(), channel: u8() });
+
+describe("FrameRing", () => {
+  it("should pass frames from producer to consumer in order", () => {
+    const producer = FrameRing.create(SampleSchema, 4);
+    const consumer = FrameRing.attach(SampleSchema, producer.buffer);
+
+    expect(producer.push({ t: 0.5, level: 1.25, raw: -300, channel: 2 })).toBe(true);
+    expect(producer.push({ t: 1.5, level: -2, raw: 300, channel: 255 })).toBe(true);
+    expect(consumer.size).toBe(2);
+
+    expect(consumer.shift()).toEqual({ t: 0.5, level: 1.25, raw: -300, channel: 2 });
+    expect(consumer.drain()).toEqual([{ t: 1.5, level: -2, raw: 300, channel: 255 }]);
+    expect(consumer.shift()).toBeUndefined();
+  });
+
+  it("should drop frames when full", () => {
+    const ring = FrameRing.create(struct({ n: u32() }), 2);
+    expect(ring.capacity).toBe(2);
+    expect(ring.push({ n: 1 })).toBe(true);
+    expect(ring.push({ n: 2 })).toBe(true);
+    expect(ring.push({ n: 3 })).toBe(false);
+    expect(ring.drain()).toEqual([{ n: 1 }, { n: 2 }]);
+  });
+
+  it("should wrap around the end of the buffer", () => {
+    const ring = FrameRing.create(struct({ n: u32() }), 3);
+    const received: number[] = [];
+    for (let n = 0; n < 10; n++) {
+      ring.push({ n });
+      if (n % 2 === 1) {
+        received.push(...ring.drain().map((frame) => frame.n));
+      }
+    }
+    expect(received).toEqual([0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    expect(ring.size).toBe(0);
+  });
+
+  it("should lay frames out as one f64 per field", () => {
+    const ring = FrameRing.create(SampleSchema, 1);
+    expect(ring.fields).toEqual(["t", "level", "raw", "channel"]);
+    // 8-byte header, then capacity + 1 frames of 4 f64 slots
+    expect(ring.buffer.byteLength).toBe(8 + 2 * 4 * 8);
+  });
+
+  describe("errors", () => {
+    it("should reject fields that do not fit an f64 slot", () => {
+      expect(() => FrameRing.create(struct({ big: i64() }) as never, 4)).toThrow(RangeError);
+      expect(() => FrameRing.create(struct({ s: string() }) as never, 4)).toThrow(RangeError);
+    });
+
+    it("should reject invalid capacities", () => {
+      expect(() => FrameRing.create(SampleSchema, 0)).toThrow(RangeError);
+      expect(() => FrameRing.create(SampleSchema, 1.5)).toThrow(RangeError);
+    });
+
+    it("should reject a buffer laid out for another schema", () => {
+      const ring = FrameRing.create(struct({ a: u8(), b: u8(), c: u8() }), 2);
+      expect(() => FrameRing.attach(struct({ a: u8(), b: u8() }), ring.buffer)).toThrow(
+        RangeError
+      );
+    });
+  });
+});
@@ -0,0 +1,16 @@
+/**
+ * A worker_threads worker for the concurrency tests
+ *
+ * Imports the built runtime at `workerData.runtime`, builds the schemas of
+ * the IR at `workerData.ir` and exposes them on `workerData.port`. Plain
+ * JavaScript, as Node runs worker threads without the test transforms.
+ */
+
+import { readFileSync } from "node:fs";
+import { workerData } from "node:worker_threads";
+
+const { runtime, ir, port } = workerData;
+const { exposeDecoders, schemasFromIr } = await import(runtime);
+
+// Requests posted before this are queued on the port until it starts
+exposeDecoders(port, Object.fromEntries(schemasFromIr(JSON.parse(readFileSync(ir, "utf8")))));
@@ -0,0 +1,12 @@
+import { defineConfig } from "vitest/config";
+
+// Conformance run of a built runtime (POSTCARD_RUNTIME=./dist), see tests/conformance
+export default defineConfig({
+  test: {
+    globals: true,
+    environment: "node",
+    include: ["tests/conformance/**/*.test.ts"],
+    reporters: ["default", "junit"],
+    outputFile: { junit: "conformance-report.xml" },
+  },
+});